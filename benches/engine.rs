@@ -0,0 +1,25 @@
+//! Per-phase engine benchmarks on representative programs.
+//!
+//! Each workload from `solilang::self_bench` (template rendering, JSON
+//! handling, ORM-style loops) is timed through the lexer, parser, type
+//! checker, compiler, tree-walker and VM, one criterion group per workload.
+//! `soli self-bench` runs the same phases without criterion.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use solilang::self_bench::{Bench, Phase, WORKLOADS};
+
+fn engine_benchmarks(c: &mut Criterion) {
+    for workload in WORKLOADS {
+        let mut bench = Bench::new(*workload).expect("workload failed to load");
+        let mut group = c.benchmark_group(format!("engine/{}", workload.name));
+        for phase in Phase::ALL {
+            group.bench_function(phase.name(), |b| {
+                b.iter(|| bench.run(phase).expect("phase failed"))
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, engine_benchmarks);
+criterion_main!(benches);
@@ -1,11 +1,15 @@
 //! Interpreter benchmarks for Solilang.
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use solilang::interpreter::builtins::server::wire_header_pairs;
 use solilang::interpreter::Interpreter;
 use solilang::lexer::Scanner;
 use solilang::parser::Parser;
 use solilang::types::TypeChecker;
 use std::fs;
+use std::path::PathBuf;
+use std::sync::{mpsc, OnceLock};
+use std::time::{Duration, Instant};
 
 /// Run a Solilang program from source code.
 fn run_program(source: &str) {
@@ -133,6 +137,16 @@ fn parsing_benchmarks(c: &mut Criterion) {
         })
     });
 
+    // A controller-sized file: the same program forty times over.
+    let large = source.repeat(40);
+    group.bench_function("parse_large_program", |b| {
+        b.iter(|| {
+            let tokens = Scanner::new(black_box(&large)).scan_tokens().unwrap();
+            let mut parser = Parser::new(tokens);
+            parser.parse().unwrap()
+        })
+    });
+
     group.finish();
 }
 
@@ -155,6 +169,107 @@ fn typecheck_benchmarks(c: &mut Criterion) {
     group.finish();
 }
 
+/// Benchmark turning a browser's request headers into `req["headers"]`, as
+/// every serve worker does per request.
+fn request_header_benchmarks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("request");
+
+    let mut headers = hyper::HeaderMap::new();
+    for (name, value) in [
+        ("host", "localhost:3000"),
+        (
+            "user-agent",
+            "Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0",
+        ),
+        (
+            "accept",
+            "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+        ),
+        ("accept-language", "en-US,en;q=0.5"),
+        ("accept-encoding", "gzip, deflate, br, zstd"),
+        ("connection", "keep-alive"),
+        ("cookie", "soli_session=3f9c2a7e5b1d4c8f9a6e2b7d1c5f8a3e"),
+        ("upgrade-insecure-requests", "1"),
+        ("sec-fetch-dest", "document"),
+        ("sec-fetch-mode", "navigate"),
+        ("sec-fetch-site", "same-origin"),
+        ("sec-fetch-user", "?1"),
+        ("priority", "u=0, i"),
+    ] {
+        headers.insert(
+            hyper::header::HeaderName::from_static(name),
+            hyper::header::HeaderValue::from_static(value),
+        );
+    }
+
+    group.bench_function("browser_headers", |b| {
+        b.iter(|| wire_header_pairs(black_box(&headers)))
+    });
+
+    group.finish();
+}
+
+/// Port of the in-process server that `serve_request_benchmarks` drives,
+/// started once on first use.
+fn bench_server_port() -> u16 {
+    static PORT: OnceLock<u16> = OnceLock::new();
+    *PORT.get_or_init(|| {
+        let app = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/_e2e_app");
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let on_bound: solilang::serve::BoundPortHook = Box::new(move |port| {
+                let _ = tx.send(port);
+            });
+            solilang::serve::serve_folder_with_options_and_hooks(&app, 0, false, 2, Some(on_bound))
+                .expect("bench server failed");
+        });
+        let port = rx
+            .recv_timeout(Duration::from_secs(30))
+            .expect("bench server never bound a port");
+
+        let deadline = Instant::now() + Duration::from_secs(15);
+        while ureq::get(&format!("http://127.0.0.1:{}/ping", port))
+            .call()
+            .is_err()
+        {
+            assert!(Instant::now() < deadline, "bench server never became ready");
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        port
+    })
+}
+
+/// Benchmark whole requests through the serve worker pool: HTTP parsing,
+/// routing, the handler, and the response — what the header conversion above
+/// is one slice of. Runs the production (VM) path with two workers over a
+/// keep-alive connection.
+fn serve_request_benchmarks(c: &mut Criterion) {
+    let port = bench_server_port();
+    let agent = ureq::AgentBuilder::new().build();
+    let mut group = c.benchmark_group("serve");
+
+    for path in [
+        "/ping",
+        "/header?name=accept-language",
+        "/hash",
+        "/typed/42",
+    ] {
+        let url = format!("http://127.0.0.1:{}{}", port, path);
+        group.bench_with_input(BenchmarkId::new("request", path), &url, |b, url| {
+            b.iter(|| {
+                let response = agent
+                    .get(url)
+                    .set("Accept-Language", "en-US,en;q=0.5")
+                    .call()
+                    .expect("request failed");
+                black_box(response.into_string().expect("unreadable body"))
+            })
+        });
+    }
+
+    group.finish();
+}
+
 /// Parameterized fibonacci benchmark for different N values.
 fn fibonacci_scaling(c: &mut Criterion) {
     let mut group = c.benchmark_group("fib_scaling");
@@ -192,6 +307,8 @@ criterion_group!(
     string_benchmarks,
     parsing_benchmarks,
     typecheck_benchmarks,
+    request_header_benchmarks,
+    serve_request_benchmarks,
     fibonacci_scaling,
 );
 
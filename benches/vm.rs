@@ -26,24 +26,24 @@ fn run_vm(source: &str) {
     let module = Compiler::compile(&program).expect("compile error");
     let mut vm = Vm::new();
     // Register print as a native function so programs that call it don't fail
-    vm.globals.insert(
-        "print".to_string(),
+    vm.globals.define(
+        "print",
         solilang::interpreter::value::Value::NativeFunction(
             solilang::interpreter::value::NativeFunction::new("print", None, |_args| {
                 Ok(solilang::interpreter::value::Value::Null)
             }),
         ),
     );
-    vm.globals.insert(
-        "puts".to_string(),
+    vm.globals.define(
+        "puts",
         solilang::interpreter::value::Value::NativeFunction(
             solilang::interpreter::value::NativeFunction::new("puts", None, |_args| {
                 Ok(solilang::interpreter::value::Value::Null)
             }),
         ),
     );
-    vm.globals.insert(
-        "clock".to_string(),
+    vm.globals.define(
+        "clock",
         solilang::interpreter::value::Value::NativeFunction(
             solilang::interpreter::value::NativeFunction::new("clock", Some(0), |_args| {
                 use std::time::{SystemTime, UNIX_EPOCH};
@@ -54,8 +54,8 @@ fn run_vm(source: &str) {
             }),
         ),
     );
-    vm.globals.insert(
-        "str".to_string(),
+    vm.globals.define(
+        "str",
         solilang::interpreter::value::Value::NativeFunction(
             solilang::interpreter::value::NativeFunction::new("str", Some(1), |args| {
                 let resolved = args.into_iter().next().unwrap();
@@ -65,8 +65,8 @@ fn run_vm(source: &str) {
             }),
         ),
     );
-    vm.globals.insert(
-        "len".to_string(),
+    vm.globals.define(
+        "len",
         solilang::interpreter::value::Value::NativeFunction(
             solilang::interpreter::value::NativeFunction::new("len", Some(1), |args| {
                 let resolved = args.into_iter().next().unwrap();
@@ -50,6 +50,17 @@ function activate(context) {
     })
   );
 
+  // `soli debug` speaks the Debug Adapter Protocol on stdio
+  context.subscriptions.push(
+    vscode.debug.registerDebugAdapterDescriptorFactory("soli", {
+      createDebugAdapterDescriptor() {
+        const config = vscode.workspace.getConfiguration("soli.lint");
+        const executable = config.get("executablePath") || "soli";
+        return new vscode.DebugAdapterExecutable(executable, ["debug"]);
+      },
+    })
+  );
+
   // Lint already-open .sl files
   vscode.workspace.textDocuments.forEach((document) => {
     if (document.languageId === "soli") {
@@ -77,7 +88,8 @@ function lintDocument(document) {
 
     // soli lint outputs to stdout; parse each line
     // Format: file:line:column - [rule] message
-    const pattern = /^.+?:(\d+):(\d+) - \[(.+?)\] (.+)$/;
+    // (error-level findings: file:line:column - error [rule] message)
+    const pattern = /^.+?:(\d+):(\d+) - (error )?\[(.+?)\] (.+)$/;
     const output = (stdout || "") + (stderr || "");
 
     for (const line of output.split("\n")) {
@@ -86,14 +98,15 @@ function lintDocument(document) {
 
       const lineNum = parseInt(match[1], 10) - 1; // VS Code is 0-indexed
       const colNum = parseInt(match[2], 10) - 1;
-      const rule = match[3];
-      const message = match[4];
+      const isError = Boolean(match[3]);
+      const rule = match[4];
+      const message = match[5];
 
       const range = new vscode.Range(lineNum, colNum, lineNum, colNum + 1);
       const diagnostic = new vscode.Diagnostic(
         range,
         `${message} [${rule}]`,
-        vscode.DiagnosticSeverity.Warning
+        isError ? vscode.DiagnosticSeverity.Error : vscode.DiagnosticSeverity.Warning
       );
       diagnostic.source = "soli lint";
       diagnostic.code = rule;
@@ -81,6 +81,7 @@ fn read_metadata(doc: &lopdf::Document) -> Option<Vec<u8>> {
 }
 
 /// Sizes of every embedded `/FontFile2` (TrueType) program in the document.
+#[cfg(feature = "font-embedding")]
 fn embedded_truetype_sizes(doc: &lopdf::Document) -> Vec<usize> {
     let mut sizes = Vec::new();
     for (_, obj) in doc.objects.iter() {
@@ -97,6 +98,8 @@ fn embedded_truetype_sizes(doc: &lopdf::Document) -> Vec<usize> {
     sizes
 }
 
+// Subsetting needs the font parsing `font-embedding` turns on.
+#[cfg(feature = "font-embedding")]
 #[test]
 fn embedded_fonts_are_subset() {
     // On-disk source faces (Titillium Regular ~62 KB, Bold ~58 KB).
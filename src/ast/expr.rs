@@ -1,19 +1,84 @@
 //! Expression AST nodes.
 
-use crate::ast::stmt::{Parameter, Stmt};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::ast::node_id::NodeId;
+use crate::ast::stmt::{Body, Parameter, Stmt};
 use crate::ast::types::TypeAnnotation;
 use crate::span::Span;
 
 /// An expression in the AST.
-#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Expr {
     pub kind: ExprKind,
     pub span: Span,
+    /// Set once the whole program is parsed; see [`crate::ast::node_id`].
+    #[serde(skip)]
+    pub id: NodeId,
 }
 
 impl Expr {
     pub fn new(kind: ExprKind, span: Span) -> Self {
-        Self { kind, span }
+        Self {
+            kind,
+            span,
+            id: NodeId::NONE,
+        }
+    }
+}
+
+/// Two expressions are equal when they read the same, wherever they were
+/// parsed: the id only says where.
+impl PartialEq for Expr {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.span == other.span
+    }
+}
+
+/// The call-frame slot a variable read, an assignment target or a `let` was
+/// tied to when the interpreter resolved the body holding it (see
+/// `interpreter::resolver`): the id of that body's frame layout and the slot
+/// index. Unset until then, and on every copy, since a copy belongs to
+/// another body. Atomic because serve workers share one parsed program.
+#[derive(Default)]
+pub struct FrameSlot(AtomicU64);
+
+impl FrameSlot {
+    /// The layout id and slot index, if the node has been resolved.
+    #[inline]
+    pub fn get(&self) -> Option<(u32, usize)> {
+        let packed = self.0.load(Ordering::Relaxed);
+        (packed != 0).then_some(((packed >> 32) as u32, packed as u32 as usize))
+    }
+
+    /// Tie the node to `slot` of the frame layout `layout` (never 0).
+    pub fn set(&self, layout: u32, slot: usize) {
+        debug_assert!(layout != 0);
+        self.0
+            .store((u64::from(layout) << 32) | slot as u64, Ordering::Relaxed);
+    }
+}
+
+impl Clone for FrameSlot {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+/// Resolution is a cache, not part of what the node means.
+impl PartialEq for FrameSlot {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl std::fmt::Debug for FrameSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.get() {
+            Some((layout, slot)) => write!(f, "FrameSlot({}:{})", layout, slot),
+            None => f.write_str("FrameSlot(-)"),
+        }
     }
 }
 
@@ -25,12 +90,17 @@ pub struct NamedArgument {
     pub span: Span,
 }
 
-/// An argument in a function call (positional, named, or block)
+/// An argument in a function call (positional, named, block, or spread)
+///
+/// `Named` is boxed: inline, its name and span made every argument 120 bytes
+/// where a positional one needs only its `Expr`.
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum Argument {
     Positional(Expr),
-    Named(NamedArgument),
+    Named(Box<NamedArgument>),
     Block(Expr),
+    /// `*array`: the array's elements, passed as positional arguments.
+    Spread(Expr),
 }
 
 /// All expression variants.
@@ -63,8 +133,12 @@ pub enum ExprKind {
     /// Null literal
     Null,
 
-    /// Variable reference: foo
-    Variable(String),
+    /// Variable reference: foo. The slot is filled in by the interpreter's
+    /// resolver; build one with [`ExprKind::variable`]. Serialized as the
+    /// name alone.
+    #[serde(serialize_with = "serialize_variable")]
+    #[serde(deserialize_with = "deserialize_variable")]
+    Variable(String, FrameSlot),
 
     /// Binary operation: a + b
     Binary {
@@ -119,6 +193,9 @@ pub enum ExprKind {
     /// Array literal: [1, 2, 3]
     Array(Vec<Expr>),
 
+    /// Tuple literal: (1, "a")
+    Tuple(Vec<Expr>),
+
     /// Hash literal: { "key" => "value", ... }
     Hash(Vec<(Expr, Expr)>),
 
@@ -154,10 +231,16 @@ pub enum ExprKind {
     /// `return_type` is boxed: an inline `Option<TypeAnnotation>` (~64B) made
     /// `Lambda` the largest `ExprKind` variant and thus set `size_of::<Expr>()`
     /// for every expression node. Boxing it (rare, usually `None`) shrinks Expr.
+    ///
+    /// A lambda captures the variables it uses by reference: it shares them
+    /// with the scope that created it. `is_move` (`move fn() { ... }`) makes it
+    /// capture by value instead — see `control::lambdas` for the rules.
+    /// Every function value the lambda evaluates to shares `body`.
     Lambda {
         params: Vec<Parameter>,
         return_type: Option<Box<TypeAnnotation>>,
-        body: Vec<Stmt>,
+        body: Arc<Body>,
+        is_move: bool,
     },
 
     /// Ternary/conditional expression: cond ? then_expr : else_expr
@@ -182,13 +265,7 @@ pub enum ExprKind {
     },
 
     /// Hash comprehension: {key: expr for x in iter if cond}
-    HashComprehension {
-        key: Box<Expr>,
-        value: Box<Expr>,
-        variable: String,
-        iterable: Box<Expr>,
-        condition: Option<Box<Expr>>,
-    },
+    HashComprehension(Box<HashComprehension>),
 
     /// Spread expression: ...expr (for arrays/hashes)
     Spread(Box<Expr>),
@@ -196,6 +273,10 @@ pub enum ExprKind {
     /// Throw expression: throw expr
     Throw(Box<Expr>),
 
+    /// Await expression: `await expr`. Waits for a future (or each future in
+    /// an array of them), running queued async tasks in the meantime.
+    Await(Box<Expr>),
+
     /// Postfix rescue: expr rescue fallback
     Rescue {
         expr: Box<Expr>,
@@ -203,12 +284,49 @@ pub enum ExprKind {
     },
 }
 
+impl ExprKind {
+    /// A read of the variable `name`, not yet resolved to a frame slot.
+    pub fn variable(name: impl Into<String>) -> Self {
+        ExprKind::Variable(name.into(), FrameSlot::default())
+    }
+}
+
+fn serialize_variable<S: serde::Serializer>(
+    name: &String,
+    _: &FrameSlot,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(name, serializer)
+}
+
+fn deserialize_variable<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<(String, FrameSlot), D::Error> {
+    let name = <String as serde::Deserialize>::deserialize(deserializer)?;
+    Ok((name, FrameSlot::default()))
+}
+
 /// Size guard — an `Expr` is allocated per parsed expression (and embedded
 /// inline in `Argument`/`InterpolatedPart` and every `Expr`-holding `StmtKind`),
 /// so its size multiplies across every worker's AST. Keep fat payloads boxed.
 /// See the `size_guards` test in `interpreter::value` for the live numbers.
 const _: () = assert!(std::mem::size_of::<Expr>() <= 80);
 
+/// Size guard — every call's argument list holds one `Argument` per argument.
+const _: () = assert!(std::mem::size_of::<Argument>() <= 88);
+
+/// The parts of a hash comprehension, `{key: value for variable in iterable
+/// if condition}`. Boxed whole in [`ExprKind::HashComprehension`]: inline, its
+/// five parts made it the largest variant and so set the size of every `Expr`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HashComprehension {
+    pub key: Expr,
+    pub value: Expr,
+    pub variable: String,
+    pub iterable: Expr,
+    pub condition: Option<Expr>,
+}
+
 /// Part of an interpolated string.
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum InterpolatedPart {
@@ -251,6 +369,8 @@ pub enum MatchPattern {
         elements: Vec<MatchPattern>,
         rest: Option<String>,
     },
+    /// Tuple pattern: (a, b) — matches a tuple of exactly that many elements
+    Tuple(Vec<MatchPattern>),
     /// Hash/object pattern: {field: pattern, ...rest}
     Hash {
         fields: Vec<(String, MatchPattern)>,
@@ -289,6 +409,8 @@ pub enum BinaryOp {
     Greater,
     GreaterEqual,
     Range,
+    /// `..=` — a range that includes its end.
+    RangeInclusive,
     /// `<<` — array push / HABTM mutator. Always returns the LHS.
     Shovel,
 }
@@ -308,11 +430,54 @@ impl std::fmt::Display for BinaryOp {
             BinaryOp::Greater => write!(f, ">"),
             BinaryOp::GreaterEqual => write!(f, ">="),
             BinaryOp::Range => write!(f, ".."),
+            BinaryOp::RangeInclusive => write!(f, "..="),
             BinaryOp::Shovel => write!(f, "<<"),
         }
     }
 }
 
+impl BinaryOp {
+    /// The method a class defines to overload this operator (`op_add` for
+    /// `+`). `!=` has none of its own: it negates `op_eq`. `..` and `<<`
+    /// can't be overloaded.
+    pub fn method_name(&self) -> Option<&'static str> {
+        match self {
+            BinaryOp::Add => Some("op_add"),
+            BinaryOp::Subtract => Some("op_sub"),
+            BinaryOp::Multiply => Some("op_mul"),
+            BinaryOp::Divide => Some("op_div"),
+            BinaryOp::Modulo => Some("op_mod"),
+            BinaryOp::Equal | BinaryOp::NotEqual => Some("op_eq"),
+            BinaryOp::Less => Some("op_lt"),
+            BinaryOp::LessEqual => Some("op_le"),
+            BinaryOp::Greater => Some("op_gt"),
+            BinaryOp::GreaterEqual => Some("op_ge"),
+            BinaryOp::Range | BinaryOp::RangeInclusive | BinaryOp::Shovel => None,
+        }
+    }
+
+    /// Whether the operator yields a Bool, so an overload's result is taken
+    /// by truthiness.
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            BinaryOp::Equal
+                | BinaryOp::NotEqual
+                | BinaryOp::Less
+                | BinaryOp::LessEqual
+                | BinaryOp::Greater
+                | BinaryOp::GreaterEqual
+        )
+    }
+}
+
+/// The method behind `-value` on an instance.
+pub const NEGATE_METHOD: &str = "op_neg";
+/// The method behind `value[index]` on an instance.
+pub const INDEX_METHOD: &str = "op_index";
+/// The method behind `value[index] = x` on an instance.
+pub const INDEX_SET_METHOD: &str = "op_index_set";
+
 /// Compound assignment operators.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum CompoundOp {
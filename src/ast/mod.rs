@@ -1,13 +1,18 @@
 //! Abstract Syntax Tree for Solilang.
 
 pub mod expr;
+pub mod node_id;
 pub mod stmt;
 pub mod types;
 
-pub use expr::{BinaryOp, CompoundOp, Expr, ExprKind, MatchArm, MatchPattern, UnaryOp};
+pub use expr::{
+    BinaryOp, CompoundOp, Expr, ExprKind, HashComprehension, MatchArm, MatchPattern, UnaryOp,
+};
+pub use node_id::{NodeId, NodeMap};
 pub use stmt::{
-    ClassDecl, ConstructorDecl, EnumDecl, EnumPayloadField, EnumVariantDecl, FieldDecl,
-    FunctionDecl, ImportDecl, ImportItem, ImportSpecifier, InterfaceDecl, InterfaceMethod,
-    MethodDecl, Parameter, Program, Stmt, StmtKind, Visibility,
+    Body, ClassDecl, ConstructorDecl, Decorator, EnumDecl, EnumPayloadField, EnumVariantDecl,
+    FieldDecl, FunctionDecl, ImportDecl, ImportItem, ImportSpecifier, InterfaceDecl,
+    InterfaceMethod, MethodDecl, Parameter, Program, Stmt, StmtKind, StructDecl, StructFieldDecl,
+    TypeAliasDecl, Visibility,
 };
-pub use types::{TypeAnnotation, TypeKind};
+pub use types::{RecordField, TypeAnnotation, TypeKind};
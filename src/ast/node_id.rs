@@ -0,0 +1,464 @@
+//! Stable ids for expression nodes, and tables keyed by them.
+//!
+//! Once a whole program is parsed, every expression in it gets a [`NodeId`]:
+//! its position, from 1, in a pre-order walk of the file. The same source
+//! always numbers the same way, so a re-parse of an unchanged file (a hot
+//! reload, the next LSP request) gives each node the id it had before, and a
+//! table of facts about the nodes can be a [`NodeMap`], a vector indexed by
+//! id, instead of a hash map keyed by span or by address.
+//!
+//! Ids are unique within one parse. Statements an import merges into another
+//! program keep the ids of the file they came from, and expressions built
+//! after parsing (an enum lowered to a class, a template expression) have
+//! none. Ids aren't serialized: a deserialized program is numbered again,
+//! which gives it the same ids.
+
+use std::sync::Arc;
+
+use crate::ast::expr::{Argument, Expr, ExprKind, HashComprehension, InterpolatedPart};
+use crate::ast::stmt::{Body, ClassDecl, Decorator, Parameter, Program, Stmt, StmtKind};
+
+/// The id of an expression within its parse. See the module docs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    /// The id of an expression that wasn't numbered.
+    pub const NONE: NodeId = NodeId(0);
+
+    pub fn is_none(self) -> bool {
+        self == Self::NONE
+    }
+
+    /// The id's position in a [`NodeMap`].
+    #[inline]
+    fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Values for some of a program's expressions, indexed by their [`NodeId`].
+#[derive(Debug, Clone)]
+pub struct NodeMap<T> {
+    slots: Vec<Option<T>>,
+    len: usize,
+}
+
+impl<T> Default for NodeMap<T> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<T> NodeMap<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The value recorded for `id`.
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.slots.get(id.index())?.as_ref()
+    }
+
+    /// Record `value` for `id`, returning the value it replaces. An
+    /// unnumbered expression has no entry, so nothing is recorded for it.
+    pub fn insert(&mut self, id: NodeId, value: T) -> Option<T> {
+        if id.is_none() {
+            return None;
+        }
+        if self.slots.len() <= id.index() {
+            self.slots.resize_with(id.index() + 1, || None);
+        }
+        let previous = self.slots[id.index()].replace(value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    /// The number of ids with a value.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The recorded values, in id order.
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter().flatten()
+    }
+}
+
+impl Program {
+    /// Give every expression of the program its [`NodeId`].
+    pub(crate) fn number_nodes(&mut self) {
+        let mut numbering = Numbering { next: 0 };
+        numbering.stmts(&mut self.statements);
+    }
+}
+
+struct Numbering {
+    next: u32,
+}
+
+impl Numbering {
+    fn stmts(&mut self, stmts: &mut [Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &mut Stmt) {
+        match &mut stmt.kind {
+            StmtKind::Expression(expr)
+            | StmtKind::Throw(expr)
+            | StmtKind::Yield(expr)
+            | StmtKind::Const {
+                initializer: expr, ..
+            }
+            | StmtKind::LetTuple {
+                initializer: expr, ..
+            } => self.expr(expr),
+            StmtKind::Let { initializer, .. } | StmtKind::Return(initializer) => {
+                if let Some(initializer) = initializer {
+                    self.expr(initializer);
+                }
+            }
+            StmtKind::Block(stmts) => self.stmts(stmts),
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expr(condition);
+                self.stmt(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.stmt(else_branch);
+                }
+            }
+            StmtKind::While { condition, body } => {
+                self.expr(condition);
+                self.stmt(body);
+            }
+            StmtKind::For { iterable, body, .. } => {
+                self.expr(iterable);
+                self.stmt(body);
+            }
+            StmtKind::Try {
+                try_block,
+                catch_clauses,
+                finally_block,
+            } => {
+                self.stmt(try_block);
+                for clause in catch_clauses {
+                    self.stmt(&mut clause.body);
+                }
+                if let Some(finally_block) = finally_block {
+                    self.stmt(finally_block);
+                }
+            }
+            StmtKind::Function(decl) => {
+                self.decorators(&mut decl.decorators);
+                self.function(&mut decl.params, &mut decl.body);
+            }
+            StmtKind::Class(decl) => self.class(decl),
+            StmtKind::Enum(decl) => {
+                for method in &mut decl.methods {
+                    self.decorators(&mut method.decorators);
+                    self.function(&mut method.params, &mut method.body);
+                }
+            }
+            StmtKind::Struct(decl) => {
+                for field in &mut decl.fields {
+                    if let Some(default) = &mut field.default_value {
+                        self.expr(default);
+                    }
+                }
+            }
+            StmtKind::Export(inner) => self.stmt(inner),
+            StmtKind::Break
+            | StmtKind::Import(_)
+            | StmtKind::Interface(_)
+            | StmtKind::TypeAlias(_) => {}
+        }
+    }
+
+    fn class(&mut self, decl: &mut ClassDecl) {
+        self.decorators(&mut decl.decorators);
+        for field in &mut decl.fields {
+            if let Some(initializer) = &mut field.initializer {
+                self.expr(initializer);
+            }
+        }
+        for method in &mut decl.methods {
+            self.decorators(&mut method.decorators);
+            self.function(&mut method.params, &mut method.body);
+        }
+        if let Some(ctor) = &mut decl.constructor {
+            self.function(&mut ctor.params, &mut ctor.body);
+        }
+        if let Some(block) = &mut decl.static_block {
+            self.stmts(block);
+        }
+        self.stmts(&mut decl.class_statements);
+        for nested in &mut decl.nested_classes {
+            self.class(nested);
+        }
+    }
+
+    /// A freshly parsed body has no other owner, so this doesn't copy it.
+    fn function(&mut self, params: &mut [Parameter], body: &mut Arc<Body>) {
+        for param in params {
+            if let Some(default) = &mut param.default_value {
+                self.expr(default);
+            }
+        }
+        self.stmts(Arc::make_mut(body).as_mut_slice());
+    }
+
+    fn decorators(&mut self, decorators: &mut [Decorator]) {
+        for decorator in decorators {
+            self.args(&mut decorator.arguments);
+        }
+    }
+
+    fn args(&mut self, args: &mut [Argument]) {
+        for arg in args {
+            match arg {
+                Argument::Positional(expr) | Argument::Block(expr) | Argument::Spread(expr) => {
+                    self.expr(expr)
+                }
+                Argument::Named(named) => self.expr(&mut named.value),
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &mut Expr) {
+        self.next += 1;
+        expr.id = NodeId(self.next);
+        match &mut expr.kind {
+            ExprKind::InterpolatedString(parts) => {
+                for part in parts {
+                    if let InterpolatedPart::Expression(expr) = part {
+                        self.expr(expr);
+                    }
+                }
+            }
+            ExprKind::Binary { left, right, .. }
+            | ExprKind::Pipeline { left, right }
+            | ExprKind::LogicalAnd { left, right }
+            | ExprKind::LogicalOr { left, right }
+            | ExprKind::NullishCoalescing { left, right }
+            | ExprKind::Index {
+                object: left,
+                index: right,
+            }
+            | ExprKind::Assign {
+                target: left,
+                value: right,
+            }
+            | ExprKind::CompoundAssign {
+                target: left,
+                value: right,
+                ..
+            }
+            | ExprKind::Rescue {
+                expr: left,
+                fallback: right,
+            } => {
+                self.expr(left);
+                self.expr(right);
+            }
+            ExprKind::Unary { operand: inner, .. }
+            | ExprKind::Grouping(inner)
+            | ExprKind::Member { object: inner, .. }
+            | ExprKind::SafeMember { object: inner, .. }
+            | ExprKind::QualifiedName {
+                qualifier: inner, ..
+            }
+            | ExprKind::PostfixIncrement(inner)
+            | ExprKind::PostfixDecrement(inner)
+            | ExprKind::Spread(inner)
+            | ExprKind::Throw(inner)
+            | ExprKind::Await(inner) => self.expr(inner),
+            ExprKind::Call { callee, arguments }
+            | ExprKind::New {
+                class_expr: callee,
+                arguments,
+            } => {
+                self.expr(callee);
+                self.args(arguments);
+            }
+            ExprKind::Array(items) | ExprKind::Tuple(items) => {
+                for item in items {
+                    self.expr(item);
+                }
+            }
+            ExprKind::Hash(pairs) => {
+                for (key, value) in pairs {
+                    self.expr(key);
+                    self.expr(value);
+                }
+            }
+            ExprKind::Block(stmts) => self.stmts(stmts),
+            ExprKind::Lambda { params, body, .. } => self.function(params, body),
+            ExprKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expr(condition);
+                self.expr(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.expr(else_branch);
+                }
+            }
+            ExprKind::Match { expression, arms } => {
+                self.expr(expression);
+                for arm in arms {
+                    if let Some(guard) = &mut arm.guard {
+                        self.expr(guard);
+                    }
+                    self.expr(&mut arm.body);
+                }
+            }
+            ExprKind::ListComprehension {
+                element,
+                iterable,
+                condition,
+                ..
+            } => {
+                self.expr(element);
+                self.expr(iterable);
+                if let Some(condition) = condition {
+                    self.expr(condition);
+                }
+            }
+            ExprKind::HashComprehension(comp) => {
+                let HashComprehension {
+                    key,
+                    value,
+                    iterable,
+                    condition,
+                    ..
+                } = &mut **comp;
+                self.expr(key);
+                self.expr(value);
+                self.expr(iterable);
+                if let Some(condition) = condition {
+                    self.expr(condition);
+                }
+            }
+            ExprKind::IntLiteral(_)
+            | ExprKind::FloatLiteral(_)
+            | ExprKind::DecimalLiteral(_)
+            | ExprKind::StringLiteral(_)
+            | ExprKind::CommandSubstitution(_)
+            | ExprKind::SdqlBlock { .. }
+            | ExprKind::BoolLiteral(_)
+            | ExprKind::Symbol(_)
+            | ExprKind::Null
+            | ExprKind::Variable(_, _)
+            | ExprKind::This
+            | ExprKind::Super => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Program {
+        let tokens = crate::lexer::Scanner::new(source).scan_tokens().unwrap();
+        crate::parser::Parser::new(tokens).parse().unwrap()
+    }
+
+    /// Every expression's id, in walk order.
+    fn ids(program: &Program) -> Vec<NodeId> {
+        fn expr(e: &Expr, out: &mut Vec<NodeId>) {
+            out.push(e.id);
+            match &e.kind {
+                ExprKind::Binary { left, right, .. } => {
+                    expr(left, out);
+                    expr(right, out);
+                }
+                ExprKind::Call { callee, arguments } => {
+                    expr(callee, out);
+                    for arg in arguments {
+                        if let Argument::Positional(arg) = arg {
+                            expr(arg, out);
+                        }
+                    }
+                }
+                ExprKind::Lambda { body, .. } => stmts(body, out),
+                ExprKind::HashComprehension(comp) => {
+                    expr(&comp.key, out);
+                    expr(&comp.value, out);
+                    expr(&comp.iterable, out);
+                }
+                _ => {}
+            }
+        }
+        fn stmts(list: &[Stmt], out: &mut Vec<NodeId>) {
+            for stmt in list {
+                match &stmt.kind {
+                    StmtKind::Let {
+                        initializer: Some(e),
+                        ..
+                    }
+                    | StmtKind::Expression(e)
+                    | StmtKind::Return(Some(e)) => expr(e, out),
+                    StmtKind::Function(decl) => stmts(&decl.body, out),
+                    _ => {}
+                }
+            }
+        }
+        let mut out = Vec::new();
+        stmts(&program.statements, &mut out);
+        out
+    }
+
+    const SOURCE: &str = "fn total(items) { return items.length + 1 }\n\
+                          let scale = |x| { return x * 2 }\n\
+                          let index = {k: k for k in [1, 2]}\n\
+                          print(total([3]), scale(4))";
+
+    #[test]
+    fn every_expression_is_numbered_once() {
+        let ids = ids(&parse(SOURCE));
+        assert!(ids.iter().all(|id| !id.is_none()));
+        let mut sorted = ids.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), ids.len());
+    }
+
+    #[test]
+    fn the_same_source_numbers_the_same_way() {
+        assert_eq!(ids(&parse(SOURCE)), ids(&parse(SOURCE)));
+        let blob = crate::bundle::serialize_program(&parse(SOURCE)).unwrap();
+        let loaded = crate::bundle::deserialize_program(&blob).unwrap();
+        assert_eq!(ids(&loaded), ids(&parse(SOURCE)));
+    }
+
+    #[test]
+    fn node_maps_hold_a_value_per_id() {
+        let program = parse(SOURCE);
+        let ids = ids(&program);
+        let mut map = NodeMap::new();
+        for (n, id) in ids.iter().enumerate() {
+            assert_eq!(map.insert(*id, n), None);
+        }
+        assert_eq!(map.insert(ids[0], 99), Some(0));
+        assert_eq!(map.insert(NodeId::NONE, 1), None);
+        assert_eq!(map.len(), ids.len());
+        assert_eq!(map.get(ids[1]), Some(&1));
+        assert_eq!(map.get(NodeId::NONE), None);
+    }
+}
@@ -1,9 +1,12 @@
 //! Statement AST nodes.
 
-use std::path::PathBuf;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
 
-use crate::ast::expr::Expr;
+use crate::ast::expr::{Argument, Expr, FrameSlot};
 use crate::ast::types::TypeAnnotation;
+use crate::interpreter::resolver::FrameLayout;
 use crate::span::Span;
 
 /// A statement in the AST.
@@ -11,17 +14,50 @@ use crate::span::Span;
 pub struct Stmt {
     pub kind: StmtKind,
     pub span: Span,
-    pub source_path: Option<PathBuf>,
+    /// The module this statement was imported from. Shared: every statement
+    /// of a module points at the same allocation.
+    pub source_path: Option<Arc<Path>>,
 }
 
 impl Stmt {
-    pub fn new(kind: StmtKind, span: Span, source_path: Option<PathBuf>) -> Self {
+    pub fn new(kind: StmtKind, span: Span, source_path: Option<Arc<Path>>) -> Self {
         Self {
             kind,
             span,
             source_path,
         }
     }
+
+    /// Whether running this statement can reach a `yield`. Nested function,
+    /// lambda and class bodies don't count: a `yield` there belongs to them.
+    pub fn yields(&self) -> bool {
+        match &self.kind {
+            StmtKind::Yield(_) => true,
+            StmtKind::Block(stmts) => body_yields(stmts),
+            StmtKind::If {
+                then_branch,
+                else_branch,
+                ..
+            } => then_branch.yields() || else_branch.as_ref().is_some_and(|e| e.yields()),
+            StmtKind::While { body, .. } | StmtKind::For { body, .. } => body.yields(),
+            StmtKind::Try {
+                try_block,
+                catch_clauses,
+                finally_block,
+            } => {
+                try_block.yields()
+                    || catch_clauses.iter().any(|c| c.body.yields())
+                    || finally_block.as_ref().is_some_and(|f| f.yields())
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Whether a function body contains a `yield`, which makes the function a
+/// generator: calling it returns a lazy iterator instead of running the body.
+pub fn body_yields(body: &[Stmt]) -> bool {
+    body.iter().any(Stmt::yields)
 }
 
 /// A single catch clause in a try/catch statement.
@@ -44,14 +80,24 @@ pub enum StmtKind {
     /// Variable declaration: let x: Type = expr;
     Let {
         name: String,
-        type_annotation: Option<TypeAnnotation>,
+        type_annotation: Option<Box<TypeAnnotation>>,
         initializer: Option<Expr>,
+        /// Filled in by the interpreter's resolver.
+        #[serde(skip)]
+        slot: FrameSlot,
+    },
+
+    /// Tuple destructuring: let (a, b): (A, B) = expr;
+    LetTuple {
+        names: Vec<String>,
+        type_annotation: Option<Box<TypeAnnotation>>,
+        initializer: Expr,
     },
 
     /// Constant declaration: const x: Type = expr;
     Const {
         name: String,
-        type_annotation: Option<TypeAnnotation>,
+        type_annotation: Option<Box<TypeAnnotation>>,
         initializer: Expr,
     },
 
@@ -69,10 +115,15 @@ pub enum StmtKind {
     While { condition: Expr, body: Box<Stmt> },
 
     /// For loop: for (x in iter) { ... } or for (x, i in iter) { ... }
+    ///
+    /// `for (k, v) in iter` sets `destructure` to the names each item (a
+    /// tuple, or a hash's `(key, value)` entry) is unpacked into; `variable`
+    /// then holds the whole item under a name no program can spell.
     For {
         variable: String,
         index_variable: Option<String>,
-        iterable: Expr,
+        destructure: Option<Vec<String>>,
+        iterable: Box<Expr>,
         body: Box<Stmt>,
     },
 
@@ -85,6 +136,10 @@ pub enum StmtKind {
     /// Throw statement: throw expr;
     Throw(Expr),
 
+    /// Yield statement: yield expr — suspends the enclosing generator
+    /// function and hands `expr` to whoever is pulling from it.
+    Yield(Expr),
+
     /// Try/Catch/Finally: try { ... } catch TypeName e { ... } catch e { ... } finally { ... }
     Try {
         try_block: Box<Stmt>,
@@ -103,9 +158,15 @@ pub enum StmtKind {
     /// Enum declaration
     Enum(Box<EnumDecl>),
 
+    /// Struct declaration
+    Struct(Box<StructDecl>),
+
     /// Interface declaration
     Interface(Box<InterfaceDecl>),
 
+    /// Type alias: `type User = { id: Int, name: String }`
+    TypeAlias(Box<TypeAliasDecl>),
+
     /// Import declaration: import "path" or import { items } from "path"
     Import(ImportDecl),
 
@@ -125,24 +186,26 @@ impl StmtKind {
     pub fn enum_decl(decl: EnumDecl) -> StmtKind {
         StmtKind::Enum(Box::new(decl))
     }
+    pub fn struct_decl(decl: StructDecl) -> StmtKind {
+        StmtKind::Struct(Box::new(decl))
+    }
     pub fn interface(decl: InterfaceDecl) -> StmtKind {
         StmtKind::Interface(Box::new(decl))
     }
+    pub fn type_alias(decl: TypeAliasDecl) -> StmtKind {
+        StmtKind::TypeAlias(Box::new(decl))
+    }
 }
 
 /// Size guard — a `Stmt` is allocated per parsed statement in every
 /// `Rc<[Stmt]>` function/method body, once per worker. Keep the large decl
-/// variants boxed. See the `size_guards` test in `interpreter::value`.
-#[cfg(not(windows))]
-const _: () = assert!(std::mem::size_of::<Stmt>() <= 200);
-// Windows gets 8 more bytes, and it is not this type growing: `Option<PathBuf>`
-// is 24 bytes on Unix, where the niche in `Vec`'s pointer absorbs the
-// discriminant, and 32 on Windows, whose `OsString` representation does not
-// expose that niche. Measured, not assumed. Each platform keeps its own tight
-// bound rather than everyone inheriting the loosest one, so the guard still
-// catches an unboxed variant being added.
-#[cfg(windows)]
-const _: () = assert!(std::mem::size_of::<Stmt>() <= 208);
+/// variants boxed, and so are the parts of common variants that most
+/// statements don't have: inline, a `let` type annotation and a `for` loop's
+/// iterable took every `Stmt` from 152 to 192 bytes.
+/// `source_path` is an `Arc<Path>`, which is the same size on every platform
+/// where `PathBuf` was not. See the `size_guards` test in
+/// `interpreter::value`.
+const _: () = assert!(std::mem::size_of::<Stmt>() <= 152);
 
 /// An import specifier (what to import from a module).
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -171,13 +234,105 @@ pub struct ImportDecl {
     pub span: Span,
 }
 
+/// The statements of a function, method, constructor or lambda body.
+///
+/// Every function value made from one declaration shares its `Body`, and
+/// with it the call-frame layout the interpreter resolves on the first call
+/// (see `interpreter::resolver`). Changing the statements drops the layout.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct Body {
+    stmts: Vec<Stmt>,
+    #[serde(skip)]
+    pub(crate) layout: OnceLock<Option<Arc<FrameLayout>>>,
+}
+
+impl Body {
+    pub fn new(stmts: Vec<Stmt>) -> Self {
+        Self {
+            stmts,
+            layout: OnceLock::new(),
+        }
+    }
+
+    pub fn into_stmts(self) -> Vec<Stmt> {
+        self.stmts
+    }
+}
+
+impl From<Vec<Stmt>> for Body {
+    fn from(stmts: Vec<Stmt>) -> Self {
+        Self::new(stmts)
+    }
+}
+
+impl Deref for Body {
+    type Target = Vec<Stmt>;
+
+    fn deref(&self) -> &Vec<Stmt> {
+        &self.stmts
+    }
+}
+
+impl DerefMut for Body {
+    fn deref_mut(&mut self) -> &mut Vec<Stmt> {
+        self.layout = OnceLock::new();
+        &mut self.stmts
+    }
+}
+
+impl Clone for Body {
+    fn clone(&self) -> Self {
+        Self::new(self.stmts.clone())
+    }
+}
+
+impl PartialEq for Body {
+    fn eq(&self, other: &Self) -> bool {
+        self.stmts == other.stmts
+    }
+}
+
+impl std::fmt::Debug for Body {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.stmts.fmt(f)
+    }
+}
+
+impl<'a> IntoIterator for &'a Body {
+    type Item = &'a Stmt;
+    type IntoIter = std::slice::Iter<'a, Stmt>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.stmts.iter()
+    }
+}
+
 /// Function declaration.
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct FunctionDecl {
     pub name: String,
     pub params: Vec<Parameter>,
     pub return_type: Option<TypeAnnotation>,
-    pub body: Vec<Stmt>,
+    pub body: Arc<Body>,
+    /// `@name(...)` annotations written above the declaration, in order.
+    pub decorators: Vec<Decorator>,
+    /// `async fn`: calling it queues the body as a task and returns a future.
+    pub is_async: bool,
+    pub span: Span,
+}
+
+/// A decorator on a function, class or method: `@cached`,
+/// `@deprecated("use fetch")`, `@route("/users", method: "POST")`.
+///
+/// Decorators carry no behaviour of their own. Their arguments are evaluated
+/// when the declaration runs and exposed through `.decorators`, so framework
+/// code (and serve's controller registry, for `@route`) can act on them.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Decorator {
+    /// The name without its `@`.
+    pub name: String,
+    pub arguments: Vec<Argument>,
     pub span: Span,
 }
 
@@ -189,6 +344,12 @@ pub struct Parameter {
     pub default_value: Option<Expr>,
     pub span: Span,
     pub is_block_param: bool,
+    /// Declared after a bare `*` in the parameter list: the argument can
+    /// only be passed by name (`connect(host, port: 5432)`).
+    pub is_keyword_only: bool,
+    /// `...args`: collects the remaining positional arguments into an array.
+    /// Parameters after it are keyword-only.
+    pub is_variadic: bool,
 }
 
 /// Class declaration.
@@ -208,6 +369,7 @@ pub struct ClassDecl {
     pub class_statements: Vec<Stmt>,
     /// Nested classes defined within this class
     pub nested_classes: Vec<ClassDecl>,
+    pub decorators: Vec<Decorator>,
     pub span: Span,
 }
 
@@ -255,7 +417,7 @@ impl EnumDecl {
     ///   variant → ordered payload field names (drives pattern binding & equality).
     /// - the user's `def`s, copied verbatim.
     pub fn lower_to_class(&self) -> ClassDecl {
-        use crate::ast::expr::{Argument, Expr, ExprKind};
+        use crate::ast::expr::ExprKind;
         use crate::ast::types::{TypeAnnotation, TypeKind};
 
         let span = self.span;
@@ -263,7 +425,7 @@ impl EnumDecl {
         // The enum's own type — so variant construction is typed as the enum
         // (enables `.method()` resolution and match-scrutinee typing).
         let enum_type = || TypeAnnotation::new(TypeKind::Named(self.name.clone()), span);
-        let var = |name: &str| Expr::new(ExprKind::Variable(name.to_string()), span);
+        let var = |name: &str| Expr::new(ExprKind::variable(name.to_string()), span);
         let str_lit = |s: &str| Expr::new(ExprKind::StringLiteral(s.to_string()), span);
 
         // __enum_construct(EnumName, "<variant>", <fields-hash>)
@@ -321,6 +483,8 @@ impl EnumDecl {
                         default_value: None,
                         span: field.span,
                         is_block_param: false,
+                        is_keyword_only: false,
+                        is_variadic: false,
                     })
                     .collect();
                 let hash_fields: Vec<(Expr, Expr)> = variant
@@ -334,11 +498,16 @@ impl EnumDecl {
                     name: variant.name.clone(),
                     params,
                     return_type: Some(enum_type()),
-                    body: vec![Stmt::new(
-                        StmtKind::Return(Some(construct(&variant.name, hash_fields))),
-                        variant.span,
-                        None,
-                    )],
+                    body: Arc::new(
+                        vec![Stmt::new(
+                            StmtKind::Return(Some(construct(&variant.name, hash_fields))),
+                            variant.span,
+                            None,
+                        )]
+                        .into(),
+                    ),
+                    decorators: Vec::new(),
+                    is_async: false,
                     span: variant.span,
                 });
             }
@@ -379,22 +548,29 @@ impl EnumDecl {
                 default_value: None,
                 span,
                 is_block_param: false,
+                is_keyword_only: false,
+                is_variadic: false,
             }],
             return_type: Some(enum_type()),
-            body: vec![Stmt::new(
-                StmtKind::Return(Some(Expr::new(
-                    ExprKind::Call {
-                        callee: Box::new(var("__enum_from")),
-                        arguments: vec![
-                            Argument::Positional(var(&self.name)),
-                            Argument::Positional(var("value")),
-                        ],
-                    },
+            body: Arc::new(
+                vec![Stmt::new(
+                    StmtKind::Return(Some(Expr::new(
+                        ExprKind::Call {
+                            callee: Box::new(var("__enum_from")),
+                            arguments: vec![
+                                Argument::Positional(var(&self.name)),
+                                Argument::Positional(var("value")),
+                            ],
+                        },
+                        span,
+                    ))),
                     span,
-                ))),
-                span,
-                None,
-            )],
+                    None,
+                )]
+                .into(),
+            ),
+            decorators: Vec::new(),
+            is_async: false,
             span,
         });
 
@@ -408,17 +584,22 @@ impl EnumDecl {
                 TypeKind::Named("String".to_string()),
                 span,
             )),
-            body: vec![Stmt::new(
-                StmtKind::Return(Some(Expr::new(
-                    ExprKind::Member {
-                        object: Box::new(Expr::new(ExprKind::This, span)),
-                        name: "__variant".to_string(),
-                    },
+            body: Arc::new(
+                vec![Stmt::new(
+                    StmtKind::Return(Some(Expr::new(
+                        ExprKind::Member {
+                            object: Box::new(Expr::new(ExprKind::This, span)),
+                            name: "__variant".to_string(),
+                        },
+                        span,
+                    ))),
                     span,
-                ))),
-                span,
-                None,
-            )],
+                    None,
+                )]
+                .into(),
+            ),
+            decorators: Vec::new(),
+            is_async: false,
             span,
         });
 
@@ -435,11 +616,44 @@ impl EnumDecl {
             static_block: None,
             class_statements: Vec::new(),
             nested_classes: Vec::new(),
+            decorators: Vec::new(),
             span,
         }
     }
 }
 
+/// Struct declaration: `struct Money { amount: Int, currency: String = "EUR" }`.
+///
+/// A struct is an immutable value type: its values carry their fields inline
+/// (no shared, mutable instance behind them), compare by value, and are
+/// changed only by copying (`money.with(amount: 10)`).
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StructDecl {
+    pub name: String,
+    /// Fields in declaration order — the positional construction order.
+    pub fields: Vec<StructFieldDecl>,
+    pub span: Span,
+}
+
+/// A struct field: `amount: Int`, optionally with a default (`= "EUR"`).
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct StructFieldDecl {
+    pub name: String,
+    pub type_annotation: Option<TypeAnnotation>,
+    pub default_value: Option<Expr>,
+    pub span: Span,
+}
+
+/// Type alias: `type User = { id: Int, name: String, email: String? }`.
+///
+/// Purely a checker-side name for a type — nothing exists at runtime.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TypeAliasDecl {
+    pub name: String,
+    pub type_annotation: TypeAnnotation,
+    pub span: Span,
+}
+
 /// Visibility modifier.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
 pub enum Visibility {
@@ -469,7 +683,9 @@ pub struct MethodDecl {
     pub name: String,
     pub params: Vec<Parameter>,
     pub return_type: Option<TypeAnnotation>,
-    pub body: Vec<Stmt>,
+    pub body: Arc<Body>,
+    pub decorators: Vec<Decorator>,
+    pub is_async: bool,
     pub span: Span,
 }
 
@@ -477,7 +693,7 @@ pub struct MethodDecl {
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct ConstructorDecl {
     pub params: Vec<Parameter>,
-    pub body: Vec<Stmt>,
+    pub body: Arc<Body>,
     pub span: Span,
 }
 
@@ -500,10 +716,25 @@ pub struct InterfaceMethod {
 
 /// A complete program.
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(from = "SerializedProgram")]
 pub struct Program {
     pub statements: Vec<Stmt>,
 }
 
+/// A `Program` as serialized, which is deserialized without its node ids.
+#[derive(serde::Deserialize)]
+struct SerializedProgram {
+    statements: Vec<Stmt>,
+}
+
+impl From<SerializedProgram> for Program {
+    fn from(program: SerializedProgram) -> Self {
+        let mut program = Program::new(program.statements);
+        program.number_nodes();
+        program
+    }
+}
+
 impl Program {
     pub fn new(statements: Vec<Stmt>) -> Self {
         Self { statements }
@@ -13,6 +13,57 @@ impl TypeAnnotation {
     pub fn new(kind: TypeKind, span: Span) -> Self {
         Self { kind, span }
     }
+
+    /// The type names this annotation mentions, nested ones included.
+    pub fn named_types(&self) -> Vec<&str> {
+        match &self.kind {
+            TypeKind::Named(name) => vec![name.as_str()],
+            TypeKind::Void => Vec::new(),
+            TypeKind::Array(inner) | TypeKind::Nullable(inner) => inner.named_types(),
+            TypeKind::Hash {
+                key_type,
+                value_type,
+            } => [key_type.named_types(), value_type.named_types()].concat(),
+            TypeKind::Function {
+                params,
+                return_type,
+            } => params
+                .iter()
+                .chain(std::iter::once(&**return_type))
+                .flat_map(TypeAnnotation::named_types)
+                .collect(),
+            TypeKind::Tuple(elements) => elements
+                .iter()
+                .flat_map(TypeAnnotation::named_types)
+                .collect(),
+            TypeKind::Record(fields) => fields
+                .iter()
+                .flat_map(|f| f.type_annotation.named_types())
+                .collect(),
+        }
+    }
+
+    /// Whether `type name = <self>` would refer back to `name`, following
+    /// the aliases `lookup` knows.
+    pub fn refers_to<'a>(
+        &'a self,
+        name: &str,
+        lookup: &dyn Fn(&str) -> Option<&'a TypeAnnotation>,
+    ) -> bool {
+        let mut seen = std::collections::HashSet::new();
+        let mut pending = self.named_types();
+        while let Some(next) = pending.pop() {
+            if next == name {
+                return true;
+            }
+            if seen.insert(next) {
+                if let Some(aliased) = lookup(next) {
+                    pending.extend(aliased.named_types());
+                }
+            }
+        }
+        false
+    }
 }
 
 /// The kinds of types that can be expressed in source.
@@ -36,6 +87,19 @@ pub enum TypeKind {
     },
     /// Nullable type: Type?
     Nullable(Box<TypeAnnotation>),
+    /// Tuple type: (A, B)
+    Tuple(Vec<TypeAnnotation>),
+    /// Record type — a hash with known keys: { id: Int, email: String? }
+    Record(Vec<RecordField>),
+}
+
+/// A field of a record type: `email: String?`. A nullable field may be
+/// left out of the hash entirely.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RecordField {
+    pub name: String,
+    pub type_annotation: TypeAnnotation,
+    pub span: Span,
 }
 
 impl std::fmt::Display for TypeAnnotation {
@@ -62,6 +126,26 @@ impl std::fmt::Display for TypeAnnotation {
                 write!(f, ") -> {}", return_type)
             }
             TypeKind::Nullable(inner) => write!(f, "{}?", inner),
+            TypeKind::Tuple(elements) => {
+                write!(f, "(")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, ")")
+            }
+            TypeKind::Record(fields) => {
+                write!(f, "{{")?;
+                for (i, field) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, " {}: {}", field.name, field.type_annotation)?;
+                }
+                write!(f, " }}")
+            }
         }
     }
 }
@@ -96,6 +180,19 @@ mod tests {
         assert_eq!(named("MyClass").to_string(), "MyClass");
     }
 
+    #[test]
+    fn refers_to_follows_aliases() {
+        let record = ann(TypeKind::Record(vec![RecordField {
+            name: "next".to_string(),
+            type_annotation: ann(TypeKind::Nullable(Box::new(named("Node")))),
+            span: span(),
+        }]));
+        assert!(record.refers_to("Node", &|_| None));
+        let alias = named("Other");
+        assert!(!alias.refers_to("Node", &|_| None));
+        assert!(alias.refers_to("Node", &|n| (n == "Other").then_some(&record)));
+    }
+
     #[test]
     fn display_void() {
         assert_eq!(ann(TypeKind::Void).to_string(), "Void");
@@ -147,6 +244,12 @@ mod tests {
         assert_eq!(n.to_string(), "String?");
     }
 
+    #[test]
+    fn display_tuple_lists_element_types() {
+        let t = ann(TypeKind::Tuple(vec![named("Int"), named("String")]));
+        assert_eq!(t.to_string(), "(Int, String)");
+    }
+
     #[test]
     fn display_nullable_array_nests_correctly() {
         // (Int[])? — array first, then nullable wrap.
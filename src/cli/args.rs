@@ -1,4 +1,5 @@
 use std::env;
+use std::path::{Path, PathBuf};
 use std::process;
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION", "0.2.0");
@@ -49,6 +50,8 @@ pub enum Command {
         dev_mode: bool,
         workers: usize,
         daemonize: bool,
+        /// `--max-call-depth N`: the workers' call-depth limit.
+        max_call_depth: Option<usize>,
     },
     Test {
         paths: Vec<String>,
@@ -58,7 +61,8 @@ pub enum Command {
         /// makes high parallelism a regression — see test_runner.rs).
         jobs: Option<usize>,
         /// Additional output format(s) beyond the console summary. Accepted
-        /// via `--coverage=html`, `--coverage=json`, `--coverage=xml`.
+        /// via `--coverage=html`, `--coverage=json`, `--coverage=xml`,
+        /// `--coverage=lcov`, or `--coverage-format FORMAT`.
         /// Empty = console only.
         coverage_formats: Vec<String>,
         coverage_min: Option<f64>,
@@ -72,6 +76,11 @@ pub enum Command {
         /// `--fail-on-n1`; a project-wide guard over the same detection the
         /// dev-bar N+1 badge uses.
         fail_on_n1: bool,
+        /// Fail any request spec whose response drifts from the app's
+        /// OpenAPI document (status code, content type, JSON body shape).
+        /// Opt-in via `--contract`; the test servers serve the spec with
+        /// `SOLI_OPENAPI=1` and every response is checked against it.
+        contract: bool,
         /// Run browser specs, which drive a real headless Chrome. Opt-in via
         /// `--browser` because they need a browser installed and cost seconds
         /// rather than milliseconds — the default suite stays fast and has no
@@ -80,6 +89,26 @@ pub enum Command {
         /// Show the browser window instead of running headless. Debugging aid;
         /// implies `--browser`.
         headed: bool,
+        /// `--seed N`: seed the randomness builtins and shuffle file and test
+        /// order from N, so an order-dependent failure can be replayed
+        /// exactly. `None` = declaration order, unseeded randomness.
+        seed: Option<u64>,
+        /// `--vm`: run spec files on the bytecode VM instead of the
+        /// tree-walking interpreter. Coverage is interpreter-only, so it is
+        /// off for such a run.
+        vm: bool,
+        /// `--filter PATTERN`: run only the tests whose full name (enclosing
+        /// suite names, then the test's own, space-separated) matches this
+        /// case-insensitive regex.
+        filter: Option<String>,
+        /// `--tag T`: run only tests tagged with one of these
+        /// (`it("x", tags: ["slow"])`, inherited from `describe`).
+        tags: Vec<String>,
+        /// `--exclude-tag T`: skip tests tagged with any of these.
+        exclude_tags: Vec<String>,
+        /// `--fail-on-todo`: fail the run if any test is left pending
+        /// (`todo("...")` or `pending()`), as a CI gate against stubs.
+        fail_on_todo: bool,
     },
     DbMigrate {
         action: DbMigrateAction,
@@ -106,6 +135,30 @@ pub enum Command {
         /// Emit a machine-readable JSON array instead of the table.
         json: bool,
     },
+    /// `soli check-routes [folder]` — cross-reference the route table with
+    /// the controllers: dangling routes, unreachable actions, duplicate paths.
+    CheckRoutes {
+        folder: String,
+        /// Emit the report as JSON instead of text.
+        json: bool,
+    },
+    /// `soli introspect [folder] [--json]` — dump the project's top-level
+    /// symbols, builtin signatures and route table for editor completion.
+    Introspect {
+        folder: String,
+        /// Emit the full JSON dump instead of a summary.
+        json: bool,
+    },
+    /// `soli export-static [folder] [-o dir]` — boot the app, render its
+    /// pages through the normal controller/template pipeline and write them,
+    /// with `public/`, as a static site.
+    ExportStatic {
+        folder: String,
+        /// Output directory (default: `dist`).
+        output: Option<String>,
+        /// Extra pages to start from (`--path`, repeatable).
+        paths: Vec<String>,
+    },
     /// `soli graph build [folder]` — extract a code graph (files, classes,
     /// methods, routes, views and their relationships) and store it in SolidB
     /// so agents can retrieve code by semantic search and graph traversal.
@@ -149,10 +202,97 @@ pub enum Command {
     },
     Lint {
         paths: Vec<String>,
+        /// `--dead-code`: whole-project unused-definition report instead of
+        /// the per-file rules.
+        dead_code: bool,
+        /// `--fix`: rewrite files with the fixes the rules suggest, then
+        /// report what is left.
+        fix: bool,
+    },
+    /// `soli profile <script.sl> [-o out.folded] [--top N]` — run a script on
+    /// the interpreter with the profiler on, then print where the time went
+    /// and write a collapsed-stack (flamegraph) file.
+    Profile {
+        file: String,
+        /// Collapsed-stack output path (default: `<script>.folded`).
+        output: Option<String>,
+        /// Rows in the terminal summary (default 20).
+        top: usize,
+    },
+    /// `soli bench [paths...]` — time the `bench` blocks in benchmark files
+    /// (default: everything under `bench/`).
+    Bench {
+        paths: Vec<String>,
+        /// `--vm`: run the benchmarks on the bytecode VM.
+        vm: bool,
+        /// `--iterations N`: measure exactly N iterations per benchmark
+        /// instead of as many as fit in the measuring time.
+        iterations: Option<u64>,
+        /// `--time MS`: measuring time per benchmark (default 1000).
+        time_ms: Option<u64>,
+        /// `--warmup MS`: untimed warmup per benchmark (default 200).
+        warmup_ms: Option<u64>,
+        /// Print the results as JSON, for `--baseline` or other tooling.
+        json: bool,
+        /// An earlier `--json` output to show the change against.
+        baseline: Option<String>,
+    },
+    /// `soli self-bench` — time the engine's phases on the bundled
+    /// workloads, to compare releases.
+    SelfBench {
+        /// `--workload NAME` (repeatable): only these workloads.
+        workloads: Vec<String>,
+        /// `--phase NAME` (repeatable): only these phases.
+        phases: Vec<String>,
+        /// `--time MS`: measuring time per phase (default 500).
+        time_ms: Option<u64>,
+        json: bool,
+        /// An earlier `--json` output to show the change against.
+        baseline: Option<String>,
+        /// `--threshold PCT`: fail when a phase is PCT% slower than the baseline.
+        threshold: Option<f64>,
+    },
+    /// `soli task [names...]` — run tasks from the project's `tasks.sl`,
+    /// dependencies first.
+    Task {
+        names: Vec<String>,
+        /// `--list`: print the tasks instead of running any.
+        list: bool,
+        /// `--force`: run tasks even when their outputs are up to date.
+        force: bool,
+        /// `--jobs N`: at most N tasks at once (default: one per CPU).
+        jobs: Option<usize>,
     },
     /// `soli check [paths...]` — static type-check without executing.
     Check {
         paths: Vec<String>,
+        /// `--max-errors N`: print at most N errors (all are counted).
+        max_errors: Option<usize>,
+        /// `--templates`: also check the views under `app/views/` against
+        /// the locals their renders pass.
+        templates: bool,
+        /// `--format json|sarif` (or `--json`): machine-readable findings
+        /// for CI annotations instead of source excerpts.
+        format: CheckFormat,
+    },
+    /// `soli types --coverage [paths...]` — the share of expressions the
+    /// type checker infers a type other than `Any` for, per file.
+    TypesCoverage {
+        paths: Vec<String>,
+        /// Emit the report as JSON instead of a table.
+        json: bool,
+        /// `--min N`: exit non-zero when the total is below N percent.
+        min: Option<f64>,
+        /// `--show-untyped`: list the lines holding `Any`-typed expressions.
+        show_untyped: bool,
+    },
+    /// `soli codemod <script.sl> [paths...]` — rewrite .sl files with a
+    /// script's AST visitors.
+    Codemod {
+        script: String,
+        paths: Vec<String>,
+        /// Don't rewrite — show the changes and exit non-zero if there are any.
+        check: bool,
     },
     Fmt {
         paths: Vec<String>,
@@ -174,6 +314,21 @@ pub enum Command {
     Remove {
         name: String,
     },
+    /// `soli js add|remove|list`: vendored JavaScript and its import map.
+    Js {
+        action: JsAction,
+    },
+    /// `soli deps check [--fix]`: flag unused dependencies and uncovered imports.
+    DepsCheck {
+        fix: bool,
+    },
+    /// `soli stubs gen [path] [-o out.sli] [--stdout]`: write a `.sli` stub
+    /// of a module's (or a package's main module's) exported signatures.
+    StubsGen {
+        path: Option<String>,
+        output: Option<String>,
+        stdout: bool,
+    },
     Install,
     SelfUpdate,
     Update {
@@ -195,6 +350,8 @@ pub enum Command {
     /// Start the Soli LSP server on stdio. Used by editor plugins
     /// (Nova, VS Code, etc.) — not typically run interactively.
     Lsp,
+    /// Start the Soli debug adapter (DAP) on stdio, for an editor's debugger.
+    Debug,
     Build {
         folder: String,
         output: Option<String>,
@@ -209,6 +366,8 @@ pub enum Command {
         /// linux-amd64, linux-arm64, darwin-amd64, darwin-arm64,
         /// windows-amd64). None = host platform.
         target: Option<String>,
+        /// Write a `.slc` next to each app file instead of a bundle.
+        precompile: bool,
     },
     /// Package an app as a self-contained desktop application.
     DesktopBuild {
@@ -222,6 +381,31 @@ pub enum Command {
         protect: bool,
         target: Option<String>,
     },
+    /// `soli <name> [args...]` — a project command declared under
+    /// `[commands]` in soli.toml. Built-in commands take precedence.
+    Project {
+        name: String,
+        script: PathBuf,
+        args: Vec<String>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum CheckFormat {
+    Text,
+    Json,
+    Sarif,
+}
+
+pub enum JsAction {
+    /// Vendor packages (`name` or `name@version`) and their imports.
+    Add {
+        packages: Vec<String>,
+    },
+    Remove {
+        name: String,
+    },
+    List,
 }
 
 pub enum EngineAction {
@@ -234,7 +418,12 @@ pub enum DbMigrateAction {
     Up,
     Down,
     Status,
-    Generate { name: String },
+    Generate {
+        name: String,
+    },
+    /// Diff the models' declared attributes against `db/schema.json` and
+    /// write a migration for the changes.
+    Autogenerate,
 }
 
 pub enum DbSeedAction {
@@ -250,6 +439,12 @@ pub struct Options {
     pub command: Command,
     pub no_type_check: bool,
     pub use_vm: bool,
+    /// `-e` limits: wall-clock milliseconds, steps and allocations.
+    pub timeout_ms: Option<u64>,
+    pub max_steps: Option<u64>,
+    pub max_allocations: Option<u64>,
+    /// `--max-errors N`: print at most N type errors before giving up.
+    pub max_errors: Option<usize>,
 }
 
 pub fn print_usage() {
@@ -262,6 +457,9 @@ pub fn print_usage() {
     eprintln!("       soli add <name> --path <path>");
     eprintln!("       soli add <name> --version <version>");
     eprintln!("       soli remove <name>");
+    eprintln!("       soli js <add|remove|list> [package[@version]...]");
+    eprintln!("       soli deps check [--fix]");
+    eprintln!("       soli stubs gen [path] [-o <file.sli>] [--stdout]");
     eprintln!("       soli install");
     eprintln!("       soli update [name]");
     eprintln!("       soli login [--registry URL] [--token TOKEN]");
@@ -270,19 +468,32 @@ pub fn print_usage() {
     eprintln!("       soli generate auth [folder]");
     eprintln!("       soli generate oidc_provider [folder]");
     eprintln!("       soli generate component <name> [folder]");
-    eprintln!("       soli serve <folder> [-d] [--dev] [--port PORT] [--workers N]");
-    eprintln!("       soli test [paths...] [--jobs N] [--coverage] [--coverage=FORMAT] [--coverage-min N] [--show-uncovered] [--no-coverage] [--fail-on-n1] [--browser] [--headed]");
-    eprintln!("       soli lint [paths...]");
-    eprintln!("       soli check [paths...]");
+    eprintln!(
+        "       soli serve <folder> [-d] [--dev] [--port PORT] [--workers N] [--max-call-depth N]"
+    );
+    eprintln!("       soli test [paths...] [--jobs N] [--coverage] [--coverage-format FORMAT] [--coverage-min N] [--show-uncovered] [--no-coverage] [--fail-on-n1] [--contract] [--browser] [--headed] [--seed N] [--vm] [--filter PATTERN] [--tag T] [--exclude-tag T] [--fail-on-todo]");
+    eprintln!("       soli lint [paths...] [--dead-code] [--fix]");
+    eprintln!("       soli check [paths...] [--templates] [--max-errors N] [--format json|sarif]");
+    eprintln!("       soli types --coverage [paths...] [--json] [--min N] [--show-untyped]");
+    eprintln!("       soli profile <script.sl> [-o <file.folded>] [--top N]");
+    eprintln!("       soli bench [paths...] [--vm] [--iterations N] [--time MS] [--warmup MS] [--json] [--baseline FILE]");
+    eprintln!("       soli task [names...] [--list] [--force] [--jobs N]");
+    eprintln!("       soli self-bench [--workload NAME] [--phase NAME] [--time MS] [--json] [--baseline FILE] [--threshold PCT]");
     eprintln!("       soli lsp");
     eprintln!("  soli build <folder> [-o <file>] [--encrypt] [--protect] [--standalone] [--target PLATFORM]");
+    eprintln!("  soli build <script.sl> [-o <file.slc>]");
+    eprintln!("  soli build <folder> --precompile");
     eprintln!("  soli deploy [--folder <path>]");
     eprintln!("  soli db:migrate <up|down|status> [folder]");
     eprintln!("  soli db:migrate generate <name> [folder]");
+    eprintln!("  soli db:migrate autogenerate [folder]");
     eprintln!("  soli db:seed [folder] [file.sl]");
     eprintln!("  soli db:seed generate <name> [folder]");
     eprintln!("  soli db:indexes [folder]");
     eprintln!("  soli routes [folder] [-g PATTERN] [--json]");
+    eprintln!("  soli check-routes [folder] [--json]");
+    eprintln!("  soli introspect [folder] [--json]");
+    eprintln!("  soli export-static [folder] [-o DIR] [--path PATH]...");
     eprintln!("  soli graph build [folder] [--no-embed] [--database NAME] [--dry-run] [--fresh]");
     eprintln!("  soli graph query \"<question>\" [folder] [--json] [--limit N] [--hops N] [--path PREFIX] [--kind KINDS]");
     eprintln!();
@@ -294,6 +505,13 @@ pub fn print_usage() {
     eprintln!("  add <name> --path <path>  Add a local path dependency");
     eprintln!("  add <name> --version <ver>  Add a registry dependency");
     eprintln!("  remove <name>        Remove a dependency");
+    eprintln!("  js add <pkg[@ver]>   Vendor an npm package's ESM build into vendor/javascript");
+    eprintln!("  js remove <pkg>      Unpin a vendored JavaScript package");
+    eprintln!("  js list              List the JavaScript packages in the import map");
+    eprintln!(
+        "  deps check [--fix]   Find unused dependencies and imports soli.toml doesn't cover"
+    );
+    eprintln!("  stubs gen [path]     Write a .sli type stub of a module's exports");
     eprintln!("  login                Login to the package registry");
     eprintln!("  publish              Publish the current package to the registry");
     eprintln!("  install              Install all dependencies from soli.toml");
@@ -308,6 +526,9 @@ pub fn print_usage() {
         "  generate component   Scaffold a view component (app/views/components/<name>.html.slv)"
     );
     eprintln!("  build <folder>       Bundle app into a single .soli file");
+    eprintln!("  build <script.sl>    Compile a script to VM bytecode (.slc)");
+    eprintln!("  build <folder> --precompile  Save each app file's parsed form as a .slc");
+    eprintln!("                       next to it; soli serve loads it instead of parsing");
     eprintln!("                       --output, -o <file>  Custom output path");
     eprintln!("                       --encrypt      Encrypt the bundle (AES-256-GCM; key from");
     eprintln!("                                      SOLI_BUNDLE_KEY or SOLI_BUNDLE_AUTH_URL)");
@@ -323,35 +544,78 @@ pub fn print_usage() {
     eprintln!("  serve <folder>       Start MVC server from a project folder");
     eprintln!("                       Supports .soli bundle files");
     eprintln!("  test [paths...]      Run tests (default: tests/ directory)");
-    eprintln!("  lint [paths...]      Lint .sl files for style issues and code smells");
-    eprintln!("  check [paths...]     Static type-check .sl files without running them");
+    eprintln!("  lint [paths...]      Lint .sl files for style issues and code smells (--dead-code for unused code,
+                       --fix to apply the suggested fixes)");
+    eprintln!("  check [paths...]     Static type-check .sl files (and views with --templates) without running them");
+    eprintln!(
+        "  types --coverage     Report the share of expressions typed other than Any, per file"
+    );
+    eprintln!("  profile <script.sl>  Run a script with the profiler: time per function + flamegraph file");
+    eprintln!("  bench [paths...]     Run bench(\"name\") {{ ... }} blocks (default: bench/); ns/iter + allocations");
+    eprintln!(
+        "  task [names...]      Run tasks from tasks.sl, dependencies first (--list to show them)"
+    );
+    eprintln!("  self-bench           Time the lexer, parser, checker, compiler, interpreter and VM on bundled programs");
     eprintln!("  lsp                  Start the Soli LSP server on stdio (for editor plugins)");
+    eprintln!(
+        "  debug                Start the Soli debug adapter (DAP) on stdio (for editor debuggers)"
+    );
     eprintln!(
         "  fmt [paths...]       Format .sl files in place (--check to dry-run, --stdin to filter)"
     );
+    eprintln!(
+        "  codemod <script.sl> [paths...]  Rewrite .sl files with the script's AST visitors (--check to dry-run)"
+    );
     eprintln!("  deploy [--folder <path>]  Deploy application to servers via deploy.toml");
     eprintln!("  db:migrate           Database migration commands");
     eprintln!("  db:seed              Run database seed scripts (db/seeds.sl, db/seeds/*.sl, or a given file)");
     eprintln!("  routes [folder]      Print the app's route table (-g PATTERN to filter, --json for tooling)");
+    eprintln!(
+        "  check-routes [folder] Find dangling routes, unreachable actions and duplicate paths"
+    );
+    eprintln!("  introspect [folder]  Dump symbols, builtin signatures and routes for completion (--json)");
+    eprintln!("  export-static [folder] Render the app's pages into a static site (-o DIR, default dist/)");
     eprintln!("  graph build [folder] Build a code graph in SolidB for agents (graph RAG); --dry-run for JSON");
     eprintln!("  graph query <q>      Retrieve the code most relevant to a task (semantic + graph); --json for agents, --path PREFIX / --kind KINDS to filter");
     eprintln!("  engine               Engine commands (create, db:migrate, db:rollback)");
+    eprintln!("  <command> [args...]  Run a project command from soli.toml [commands]");
     eprintln!("  -e <code>            Evaluate code and print result");
+    eprintln!(
+        "                       (limit it with --timeout MS, --max-steps N, --max-allocations N)"
+    );
+    let project_commands = solilang::module::project_commands(&current_dir());
+    if !project_commands.is_empty() {
+        eprintln!();
+        eprintln!("Project commands (soli.toml):");
+        for (name, script) in &project_commands {
+            eprintln!("  {:<20} {}", name, script);
+        }
+    }
     eprintln!();
     eprintln!("Options:");
     eprintln!("  --no-type-check Skip type checking");
+    eprintln!("  --max-errors N  Print at most N type errors (scripts and soli check)");
+    eprintln!("  --vm            Run scripts, or test specs, on the bytecode VM");
+    eprintln!("  --interp        Run scripts on the tree-walking interpreter (the default)");
     eprintln!("  -d              Daemonize server (creates soli.pid and soli.log)");
     eprintln!("  --dev           Enable development mode (hot reload, no caching)");
     eprintln!("  --port PORT     Port for serve command (default: 5011)");
     eprintln!("  --workers N     Number of worker threads (default: CPU cores)");
+    eprintln!("  --max-call-depth N  Call-depth limit of serve workers (default: 2000)");
     eprintln!("  --jobs N        Number of parallel test workers (default: 3 for apps with app/controllers/, 1 otherwise)");
     eprintln!("  --coverage           Generate coverage report (console)");
-    eprintln!("  --coverage=FORMAT    Also generate FORMAT reports: html, json, xml (comma-sep)");
+    eprintln!("  --coverage-format F  Also generate F reports: html, json, xml (cobertura), lcov (comma-sep;");
+    eprintln!("                       --coverage=F works too)");
     eprintln!("  --coverage-min N     Fail if coverage is below N% (default: 80)");
     eprintln!("  --show-uncovered     List every uncovered line in the console report");
     eprintln!("  --no-coverage        Skip coverage collection");
     eprintln!("  --browser            Run browser specs in a real headless Chrome");
     eprintln!("  --headed             Show the browser window (implies --browser)");
+    eprintln!("  --seed N             Seed randomness and shuffle test order reproducibly");
+    eprintln!("  --filter PATTERN     Run only tests whose full name matches PATTERN (regex)");
+    eprintln!("  --tag T              Run only tests tagged T (repeatable, comma-sep)");
+    eprintln!("  --exclude-tag T      Skip tests tagged T (repeatable, comma-sep)");
+    eprintln!("  --fail-on-todo       Fail the run if any test is left pending (todo/pending)");
     eprintln!("  --help, -h      Show this help message");
     eprintln!();
     eprintln!("Examples:");
@@ -363,6 +627,9 @@ pub fn print_usage() {
     eprintln!("  soli add math --git https://github.com/user/soli-math --tag v1.0.0");
     eprintln!("  soli add utils --path ../shared/utils");
     eprintln!("  soli remove math              Remove dependency");
+    eprintln!("  soli js add @hotwired/stimulus Vendor Stimulus and pin it in the import map");
+    eprintln!("  soli deps check --fix         Prune dependencies nothing imports");
+    eprintln!("  soli stubs gen lib/money.sl   Write lib/money.sli for the type checker");
     eprintln!("  soli install                  Install all dependencies");
     eprintln!("  soli update                    Update soli CLI to latest release");
     eprintln!("  soli update math               Update a specific dependency");
@@ -373,6 +640,10 @@ pub fn print_usage() {
     eprintln!("  soli build my_app             Bundle app into my_app.soli");
     eprintln!("  soli build my_app -o release.soli  Custom bundle output path");
     eprintln!("  soli build my_app --standalone     Self-contained executable ./my_app");
+    eprintln!(
+        "  soli build script.sl               Precompile to script.slc (run with soli script.slc)"
+    );
+    eprintln!("  soli build my_app --precompile     Write app/**/*.slc for faster worker startup");
     eprintln!("  soli build my_app --standalone --protect --target linux-arm64");
     eprintln!(
         "                                Cross-build ./my_app-linux-arm64 (encrypted, no source)"
@@ -386,23 +657,112 @@ pub fn print_usage() {
     eprintln!("  soli test                     Run all tests in tests/");
     eprintln!("  soli test spec.sl             Run specific test file");
     eprintln!("  soli test --coverage          Run tests with coverage");
+    eprintln!(
+        "  soli test --coverage-format lcov  Also write coverage/lcov.info for Codecov/Coveralls"
+    );
     eprintln!("  soli test --jobs=4            Run tests with 4 workers");
     eprintln!("  soli test --fail-on-n1        Fail any request spec that triggers an N+1");
+    eprintln!(
+        "  soli test --contract          Fail any response that drifts from the OpenAPI spec"
+    );
     eprintln!("  soli test --browser           Also run browser specs (needs Chrome)");
+    eprintln!("  soli test --seed 1234         Replay the random order of a failed run");
+    eprintln!("  soli test --filter \"user login\" --exclude-tag slow  Run a fast subset");
+    eprintln!("  soli lint --fix               Apply the fixes the lint rules suggest");
+    eprintln!("  soli check --format sarif > check.sarif  Type errors as SARIF for CI annotations");
+    eprintln!("  soli types --coverage --min 60  Fail if under 60% of expressions are typed");
     eprintln!("  soli db:migrate up            Run pending migrations");
     eprintln!("  soli db:migrate down          Rollback last migration");
     eprintln!("  soli db:migrate status        Show migration status");
     eprintln!("  soli db:migrate generate create_users  Generate new migration");
+    eprintln!("  soli db:migrate autogenerate  Generate a migration from model attribute changes");
     eprintln!("  soli db:seed                  Run db/seeds.sl and db/seeds/*.sl");
     eprintln!("  soli db:seed db/seeds/demo.sl  Run a single seed file");
     eprintln!("  soli db:seed generate demo_users  Generate new seed file");
     eprintln!("  soli routes                   Print the route table of the app in .");
     eprintln!("  soli routes -g posts          Only routes matching 'posts'");
+    eprintln!(
+        "  soli check-routes             Check routes against controllers (exit 1 on problems)"
+    );
+    eprintln!(
+        "  soli introspect --json        Symbols, builtins and routes of the app in . as JSON"
+    );
+    eprintln!("  soli export-static www -o dist  Render www's pages and public/ into dist/");
     eprintln!("  soli engine create shop       Create a new engine named 'shop'");
     eprintln!("  soli engine db:migrate        Run all engine migrations");
     eprintln!("  soli engine db:migrate shop   Run migrations for 'shop' engine only");
     eprintln!("  soli engine db:rollback shop  Rollback last migration of 'shop'");
+    eprintln!(
+        "  soli deploy staging           Run the project's `deploy` command (soli.toml [commands])"
+    );
+    eprintln!("  soli profile bench.sl         Show the slowest functions, write bench.folded");
+    eprintln!("  soli bench --json > base.json Record benchmark results");
+    eprintln!("  soli bench --vm --baseline base.json  Compare the VM against that run");
+    eprintln!("  soli task build               Run the build task after its deps, in parallel");
+    eprintln!(
+        "  soli self-bench --baseline prev.json --threshold 10  Fail on a >10% engine slowdown"
+    );
     eprintln!("  soli -e 'print(1 + 1)'        Evaluate code directly");
+    eprintln!("  soli --timeout 500 -e 'while (true) {{}}'  Stop evaluating after 500ms");
+}
+
+/// The directory `soli` was started in, where project commands are looked
+/// up from (walking up to the nearest soli.toml).
+fn current_dir() -> PathBuf {
+    env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
+}
+
+/// The positive integer after a limit flag such as `--max-steps`.
+fn limit_arg(args: &[String], i: usize, flag: &str) -> u64 {
+    let Some(arg) = args.get(i) else {
+        eprintln!("{} requires a number", flag);
+        print_usage();
+        process::exit(64);
+    };
+    match arg.parse::<u64>() {
+        Ok(n) if n > 0 => n,
+        _ => {
+            eprintln!("Invalid {} value: {}", flag, arg);
+            process::exit(64);
+        }
+    }
+}
+
+/// Add the comma-separated coverage report formats in `list` (from
+/// `--coverage=` or `--coverage-format`) to `formats`, once each.
+/// Add the tags of a `--tag`/`--exclude-tag` value (`slow` or `slow,db`).
+fn add_tags(tags: &mut Vec<String>, list: &str) {
+    for tag in list.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+}
+
+fn add_coverage_formats(formats: &mut Vec<String>, list: &str) {
+    for fmt in list.split(',') {
+        let fmt = fmt.trim();
+        if fmt.is_empty() {
+            continue;
+        }
+        let normalized = match fmt.to_ascii_lowercase().as_str() {
+            "cobertura" => "xml".to_string(),
+            other => other.to_string(),
+        };
+        if !matches!(
+            normalized.as_str(),
+            "console" | "html" | "json" | "xml" | "lcov"
+        ) {
+            eprintln!(
+                "Unknown --coverage format '{}'. Valid: console, html, json, xml (cobertura), lcov",
+                fmt
+            );
+            process::exit(64);
+        }
+        if !formats.iter().any(|f| f == &normalized) {
+            formats.push(normalized);
+        }
+    }
 }
 
 pub fn parse_args() -> Options {
@@ -411,6 +771,10 @@ pub fn parse_args() -> Options {
         command: Command::Repl,
         no_type_check: false,
         use_vm: false,
+        timeout_ms: None,
+        max_steps: None,
+        max_allocations: None,
+        max_errors: None,
     };
 
     let mut i = 0;
@@ -576,7 +940,9 @@ pub fn parse_args() -> Options {
             "db:migrate" => {
                 i += 1;
                 if i >= args.len() {
-                    eprintln!("db:migrate command requires an action (up, down, status, generate)");
+                    eprintln!(
+                        "db:migrate command requires an action (up, down, status, generate, autogenerate)"
+                    );
                     print_usage();
                     process::exit(64);
                 }
@@ -596,9 +962,10 @@ pub fn parse_args() -> Options {
                             name: args[i].clone(),
                         }
                     }
+                    "autogenerate" => DbMigrateAction::Autogenerate,
                     _ => {
                         eprintln!(
-                            "Unknown db:migrate action: {} (valid: up, down, status, generate)",
+                            "Unknown db:migrate action: {} (valid: up, down, status, generate, autogenerate)",
                             action_str
                         );
                         print_usage();
@@ -659,6 +1026,91 @@ pub fn parse_args() -> Options {
                 options.command = Command::Routes { folder, grep, json };
                 return options;
             }
+            "introspect" => {
+                i += 1;
+                let mut folder = ".".to_string();
+                let mut folder_set = false;
+                let mut json = false;
+                while i < args.len() {
+                    match args[i].as_str() {
+                        "--json" => json = true,
+                        arg if !arg.starts_with('-') && !folder_set => {
+                            folder = arg.to_string();
+                            folder_set = true;
+                        }
+                        other => {
+                            eprintln!("Unknown option for introspect: {}", other);
+                            print_usage();
+                            process::exit(64);
+                        }
+                    }
+                    i += 1;
+                }
+                options.command = Command::Introspect { folder, json };
+                return options;
+            }
+            "check-routes" => {
+                i += 1;
+                let mut folder = ".".to_string();
+                let mut folder_set = false;
+                let mut json = false;
+                while i < args.len() {
+                    match args[i].as_str() {
+                        "--json" => json = true,
+                        arg if !arg.starts_with('-') && !folder_set => {
+                            folder = arg.to_string();
+                            folder_set = true;
+                        }
+                        other => {
+                            eprintln!("Unknown option for check-routes: {}", other);
+                            print_usage();
+                            process::exit(64);
+                        }
+                    }
+                    i += 1;
+                }
+                options.command = Command::CheckRoutes { folder, json };
+                return options;
+            }
+            "export-static" => {
+                i += 1;
+                let mut folder = ".".to_string();
+                let mut folder_set = false;
+                let mut output = None;
+                let mut paths = Vec::new();
+                while i < args.len() {
+                    match args[i].as_str() {
+                        "-o" | "--output" | "--path" => {
+                            i += 1;
+                            if i >= args.len() {
+                                eprintln!("export-static: {} requires a value", args[i - 1]);
+                                process::exit(64);
+                            }
+                            if args[i - 1] == "--path" {
+                                paths.push(args[i].clone());
+                            } else {
+                                output = Some(args[i].clone());
+                            }
+                        }
+                        arg if !arg.starts_with('-') && !folder_set => {
+                            folder = arg.to_string();
+                            folder_set = true;
+                        }
+                        other => {
+                            eprintln!("Unknown option for export-static: {}", other);
+                            print_usage();
+                            process::exit(64);
+                        }
+                    }
+                    i += 1;
+                }
+                options.command = Command::ExportStatic {
+                    folder,
+                    output,
+                    paths,
+                };
+                return options;
+            }
             "graph" => {
                 i += 1;
                 let action = args.get(i).cloned().unwrap_or_default();
@@ -905,6 +1357,7 @@ pub fn parse_args() -> Options {
                 let mut port = 5011u16;
                 let mut dev_mode = false;
                 let mut daemonize = false;
+                let mut max_call_depth = None;
                 // Worker count: `SOLI_WORKERS` env (the documented baseline-RSS
                 // lever) if set, else the CPU core count. An explicit
                 // `--workers N` below overrides either.
@@ -941,6 +1394,20 @@ pub fn parse_args() -> Options {
                             eprintln!("Invalid workers number: {}", args[i]);
                             process::exit(64);
                         });
+                    } else if args[i] == "--max-call-depth" {
+                        i += 1;
+                        if i >= args.len() {
+                            eprintln!("--max-call-depth requires a number");
+                            print_usage();
+                            process::exit(64);
+                        }
+                        max_call_depth = match args[i].parse::<usize>() {
+                            Ok(depth) if depth > 0 => Some(depth),
+                            _ => {
+                                eprintln!("Invalid call depth: {}", args[i]);
+                                process::exit(64);
+                            }
+                        };
                     } else if args[i] == "-d" {
                         daemonize = true;
                     } else if args[i] == "--dev" {
@@ -958,6 +1425,7 @@ pub fn parse_args() -> Options {
                     dev_mode,
                     workers,
                     daemonize,
+                    max_call_depth,
                 };
                 return options;
             }
@@ -1061,6 +1529,102 @@ pub fn parse_args() -> Options {
                 };
                 return options;
             }
+            "js" => {
+                i += 1;
+                let action = match args.get(i).map(String::as_str) {
+                    Some("add") => {
+                        let packages: Vec<String> = args[i + 1..].to_vec();
+                        if packages.is_empty() {
+                            eprintln!("js add requires a package name");
+                            print_usage();
+                            process::exit(64);
+                        }
+                        JsAction::Add { packages }
+                    }
+                    Some("remove") => match args.get(i + 1) {
+                        Some(name) => JsAction::Remove { name: name.clone() },
+                        None => {
+                            eprintln!("js remove requires a package name");
+                            print_usage();
+                            process::exit(64);
+                        }
+                    },
+                    Some("list") => JsAction::List,
+                    other => {
+                        eprintln!(
+                            "Unknown js action: {} (valid: add, remove, list)",
+                            other.unwrap_or("(none)")
+                        );
+                        print_usage();
+                        process::exit(64);
+                    }
+                };
+                options.command = Command::Js { action };
+                return options;
+            }
+            "deps" => {
+                i += 1;
+                if args.get(i).map(String::as_str) != Some("check") {
+                    eprintln!("deps command requires an action (check)");
+                    print_usage();
+                    process::exit(64);
+                }
+                i += 1;
+                let mut fix = false;
+                while i < args.len() {
+                    match args[i].as_str() {
+                        "--fix" => fix = true,
+                        _ => {
+                            eprintln!("Unknown option for deps check: {}", args[i]);
+                            print_usage();
+                            process::exit(64);
+                        }
+                    }
+                    i += 1;
+                }
+                options.command = Command::DepsCheck { fix };
+                return options;
+            }
+            "stubs" => {
+                i += 1;
+                if args.get(i).map(String::as_str) != Some("gen") {
+                    eprintln!("stubs command requires an action (gen)");
+                    print_usage();
+                    process::exit(64);
+                }
+                i += 1;
+                let mut path = None;
+                let mut output = None;
+                let mut stdout = false;
+                while i < args.len() {
+                    match args[i].as_str() {
+                        "-o" | "--output" => {
+                            i += 1;
+                            if i >= args.len() {
+                                eprintln!("-o requires a file path");
+                                process::exit(64);
+                            }
+                            output = Some(args[i].clone());
+                        }
+                        "--stdout" => stdout = true,
+                        arg if !arg.starts_with('-') && path.is_none() => {
+                            path = Some(arg.to_string());
+                        }
+                        _ => {
+                            eprintln!("Unknown option for stubs gen: {}", args[i]);
+                            print_usage();
+                            process::exit(64);
+                        }
+                    }
+                    i += 1;
+                }
+                options.command = Command::StubsGen {
+                    path,
+                    output,
+                    stdout,
+                };
+                return options;
+            }
             "install" => {
                 options.command = Command::Install;
                 return options;
@@ -1138,15 +1702,42 @@ pub fn parse_args() -> Options {
             }
             "--no-type-check" => options.no_type_check = true,
             "--vm" => options.use_vm = true,
+            "--interp" => options.use_vm = false,
+            "--timeout" => {
+                i += 1;
+                options.timeout_ms = Some(limit_arg(&args, i, "--timeout"));
+            }
+            "--max-steps" => {
+                i += 1;
+                options.max_steps = Some(limit_arg(&args, i, "--max-steps"));
+            }
+            "--max-allocations" => {
+                i += 1;
+                options.max_allocations = Some(limit_arg(&args, i, "--max-allocations"));
+            }
+            "--max-errors" => {
+                i += 1;
+                options.max_errors = Some(limit_arg(&args, i, "--max-errors") as usize);
+            }
             "lsp" => {
                 options.command = Command::Lsp;
                 return options;
             }
+            "debug" => {
+                options.command = Command::Debug;
+                return options;
+            }
             "lint" => {
                 i += 1;
                 let mut paths: Vec<String> = Vec::new();
+                let mut dead_code = false;
+                let mut fix = false;
                 while i < args.len() {
-                    if !args[i].starts_with('-') {
+                    if args[i] == "--dead-code" {
+                        dead_code = true;
+                    } else if args[i] == "--fix" {
+                        fix = true;
+                    } else if !args[i].starts_with('-') {
                         paths.push(args[i].clone());
                     } else {
                         eprintln!("Unknown option for lint: {}", args[i]);
@@ -1155,14 +1746,227 @@ pub fn parse_args() -> Options {
                     }
                     i += 1;
                 }
-                options.command = Command::Lint { paths };
+                if dead_code && fix {
+                    eprintln!("--fix can't be combined with --dead-code");
+                    process::exit(64);
+                }
+                options.command = Command::Lint {
+                    paths,
+                    dead_code,
+                    fix,
+                };
+                return options;
+            }
+            "bench" => {
+                i += 1;
+                let mut paths: Vec<String> = Vec::new();
+                let mut vm = options.use_vm;
+                let mut iterations = None;
+                let mut time_ms = None;
+                let mut warmup_ms = None;
+                let mut json = false;
+                let mut baseline = None;
+                while i < args.len() {
+                    match args[i].as_str() {
+                        "--vm" => vm = true,
+                        "--json" => json = true,
+                        "--iterations" => {
+                            i += 1;
+                            iterations = Some(limit_arg(&args, i, "--iterations"));
+                        }
+                        "--time" => {
+                            i += 1;
+                            time_ms = Some(limit_arg(&args, i, "--time"));
+                        }
+                        "--warmup" => {
+                            i += 1;
+                            warmup_ms = Some(limit_arg(&args, i, "--warmup"));
+                        }
+                        "--baseline" => {
+                            i += 1;
+                            if i >= args.len() {
+                                eprintln!("bench: --baseline requires a file");
+                                process::exit(64);
+                            }
+                            baseline = Some(args[i].clone());
+                        }
+                        arg if !arg.starts_with('-') => paths.push(arg.to_string()),
+                        other => {
+                            eprintln!("Unknown option for bench: {}", other);
+                            print_usage();
+                            process::exit(64);
+                        }
+                    }
+                    i += 1;
+                }
+                options.command = Command::Bench {
+                    paths,
+                    vm,
+                    iterations,
+                    time_ms,
+                    warmup_ms,
+                    json,
+                    baseline,
+                };
+                return options;
+            }
+            "self-bench" => {
+                i += 1;
+                let mut workloads = Vec::new();
+                let mut phases = Vec::new();
+                let mut time_ms = None;
+                let mut json = false;
+                let mut baseline = None;
+                let mut threshold = None;
+                while i < args.len() {
+                    let flag = args[i].clone();
+                    match flag.as_str() {
+                        "--json" => json = true,
+                        "--time" => {
+                            i += 1;
+                            time_ms = Some(limit_arg(&args, i, "--time"));
+                        }
+                        "--workload" | "--phase" | "--baseline" | "--threshold" => {
+                            i += 1;
+                            let Some(value) = args.get(i).cloned() else {
+                                eprintln!("self-bench: {} requires a value", flag);
+                                process::exit(64);
+                            };
+                            match flag.as_str() {
+                                "--workload" => workloads.push(value),
+                                "--phase" => phases.push(value),
+                                "--baseline" => baseline = Some(value),
+                                _ => match value.parse::<f64>() {
+                                    Ok(pct) if pct >= 0.0 => threshold = Some(pct),
+                                    _ => {
+                                        eprintln!("Invalid --threshold value: {}", value);
+                                        process::exit(64);
+                                    }
+                                },
+                            }
+                        }
+                        other => {
+                            eprintln!("Unknown option for self-bench: {}", other);
+                            print_usage();
+                            process::exit(64);
+                        }
+                    }
+                    i += 1;
+                }
+                options.command = Command::SelfBench {
+                    workloads,
+                    phases,
+                    time_ms,
+                    json,
+                    baseline,
+                    threshold,
+                };
+                return options;
+            }
+            "task" => {
+                i += 1;
+                let mut names: Vec<String> = Vec::new();
+                let mut list = false;
+                let mut force = false;
+                let mut jobs = None;
+                while i < args.len() {
+                    match args[i].as_str() {
+                        "--list" | "-l" => list = true,
+                        "--force" | "-f" => force = true,
+                        "--jobs" | "-j" => {
+                            i += 1;
+                            jobs = Some(limit_arg(&args, i, "--jobs") as usize);
+                        }
+                        arg if !arg.starts_with('-') => names.push(arg.to_string()),
+                        other => {
+                            eprintln!("Unknown option for task: {}", other);
+                            print_usage();
+                            process::exit(64);
+                        }
+                    }
+                    i += 1;
+                }
+                options.command = Command::Task {
+                    names,
+                    list,
+                    force,
+                    jobs,
+                };
+                return options;
+            }
+            "profile" => {
+                i += 1;
+                let mut file: Option<String> = None;
+                let mut output: Option<String> = None;
+                let mut top = 20;
+                while i < args.len() {
+                    match args[i].as_str() {
+                        "-o" | "--output" => {
+                            i += 1;
+                            if i >= args.len() {
+                                eprintln!("profile: {} requires a path", args[i - 1]);
+                                process::exit(64);
+                            }
+                            output = Some(args[i].clone());
+                        }
+                        "--top" => {
+                            i += 1;
+                            top = limit_arg(&args, i, "--top") as usize;
+                        }
+                        arg if !arg.starts_with('-') && file.is_none() => {
+                            file = Some(arg.to_string());
+                        }
+                        other => {
+                            eprintln!("Unknown option for profile: {}", other);
+                            print_usage();
+                            process::exit(64);
+                        }
+                    }
+                    i += 1;
+                }
+                let Some(file) = file else {
+                    eprintln!("profile requires a script argument");
+                    print_usage();
+                    process::exit(64);
+                };
+                options.command = Command::Profile { file, output, top };
                 return options;
             }
             "check" => {
                 i += 1;
                 let mut paths: Vec<String> = Vec::new();
+                let mut max_errors = options.max_errors;
+                let mut templates = false;
+                let mut format = CheckFormat::Text;
                 while i < args.len() {
-                    if !args[i].starts_with('-') {
+                    if args[i] == "--templates" {
+                        templates = true;
+                    } else if args[i] == "--json" {
+                        format = CheckFormat::Json;
+                    } else if args[i] == "--format" || args[i].starts_with("--format=") {
+                        let value = match args[i].strip_prefix("--format=") {
+                            Some(value) => value.to_string(),
+                            None => {
+                                i += 1;
+                                args.get(i).cloned().unwrap_or_default()
+                            }
+                        };
+                        format = match value.as_str() {
+                            "text" => CheckFormat::Text,
+                            "json" => CheckFormat::Json,
+                            "sarif" => CheckFormat::Sarif,
+                            other => {
+                                eprintln!(
+                                    "Invalid check format '{}' (expected text, json or sarif)",
+                                    other
+                                );
+                                process::exit(64);
+                            }
+                        };
+                    } else if args[i] == "--max-errors" {
+                        i += 1;
+                        max_errors = Some(limit_arg(&args, i, "--max-errors") as usize);
+                    } else if !args[i].starts_with('-') {
                         paths.push(args[i].clone());
                     } else {
                         eprintln!("Unknown option for check: {}", args[i]);
@@ -1171,7 +1975,90 @@ pub fn parse_args() -> Options {
                     }
                     i += 1;
                 }
-                options.command = Command::Check { paths };
+                options.command = Command::Check {
+                    paths,
+                    max_errors,
+                    templates,
+                    format,
+                };
+                return options;
+            }
+            "types" => {
+                i += 1;
+                let mut paths: Vec<String> = Vec::new();
+                let mut coverage = false;
+                let mut json = false;
+                let mut min = None;
+                let mut show_untyped = false;
+                while i < args.len() {
+                    match args[i].as_str() {
+                        "--coverage" => coverage = true,
+                        "--json" => json = true,
+                        "--show-untyped" => show_untyped = true,
+                        "--min" => {
+                            i += 1;
+                            if i >= args.len() {
+                                eprintln!("--min requires a percentage");
+                                print_usage();
+                                process::exit(64);
+                            }
+                            min = Some(args[i].parse().unwrap_or_else(|_| {
+                                eprintln!("Invalid coverage percentage: {}", args[i]);
+                                process::exit(64);
+                            }));
+                        }
+                        s if !s.starts_with('-') => paths.push(s.to_string()),
+                        other => {
+                            eprintln!("Unknown option for types: {}", other);
+                            print_usage();
+                            process::exit(64);
+                        }
+                    }
+                    i += 1;
+                }
+                if !coverage {
+                    eprintln!("types: expected --coverage");
+                    print_usage();
+                    process::exit(64);
+                }
+                options.command = Command::TypesCoverage {
+                    paths,
+                    json,
+                    min,
+                    show_untyped,
+                };
+                return options;
+            }
+            "codemod" => {
+                i += 1;
+                let mut script: Option<String> = None;
+                let mut paths: Vec<String> = Vec::new();
+                let mut check = false;
+                while i < args.len() {
+                    match args[i].as_str() {
+                        "--check" => check = true,
+                        s if !s.starts_with('-') && script.is_none() => {
+                            script = Some(s.to_string())
+                        }
+                        s if !s.starts_with('-') => paths.push(s.to_string()),
+                        other => {
+                            eprintln!("Unknown option for codemod: {}", other);
+                            print_usage();
+                            process::exit(64);
+                        }
+                    }
+                    i += 1;
+                }
+                let Some(script) = script else {
+                    eprintln!("codemod requires a script argument");
+                    print_usage();
+                    process::exit(64);
+                };
+                options.command = Command::Codemod {
+                    script,
+                    paths,
+                    check,
+                };
                 return options;
             }
             "fmt" => {
@@ -1281,35 +2168,41 @@ pub fn parse_args() -> Options {
                 let mut no_coverage = false;
                 let mut show_uncovered = false;
                 let mut fail_on_n1 = false;
+                let mut contract = false;
                 let mut browser = false;
                 let mut headed = false;
+                let mut seed: Option<u64> = None;
+                let mut vm = false;
+                let mut filter: Option<String> = None;
+                let mut tags: Vec<String> = Vec::new();
+                let mut exclude_tags: Vec<String> = Vec::new();
+                let mut fail_on_todo = false;
                 while i < args.len() {
                     if args[i].starts_with('-') {
+                        if let Some(rest) = args[i].strip_prefix("--filter=") {
+                            filter = Some(rest.to_string());
+                            i += 1;
+                            continue;
+                        }
+                        if let Some(rest) = args[i].strip_prefix("--tag=") {
+                            add_tags(&mut tags, rest);
+                            i += 1;
+                            continue;
+                        }
+                        if let Some(rest) = args[i].strip_prefix("--exclude-tag=") {
+                            add_tags(&mut exclude_tags, rest);
+                            i += 1;
+                            continue;
+                        }
                         // Support `--coverage=html`, `--coverage=json,xml`,
                         // `--coverage=html --coverage=json`, etc. Any non-empty
                         // value implies --coverage.
-                        if let Some(rest) = args[i].strip_prefix("--coverage=") {
+                        if let Some(rest) = args[i]
+                            .strip_prefix("--coverage=")
+                            .or_else(|| args[i].strip_prefix("--coverage-format="))
+                        {
                             no_coverage = false;
-                            for fmt in rest.split(',') {
-                                let fmt = fmt.trim();
-                                if fmt.is_empty() {
-                                    continue;
-                                }
-                                let normalized = fmt.to_ascii_lowercase();
-                                if !matches!(
-                                    normalized.as_str(),
-                                    "console" | "html" | "json" | "xml"
-                                ) {
-                                    eprintln!(
-                                        "Unknown --coverage format '{}'. Valid: console, html, json, xml",
-                                        fmt
-                                    );
-                                    process::exit(64);
-                                }
-                                if !coverage_formats.iter().any(|f| f == &normalized) {
-                                    coverage_formats.push(normalized);
-                                }
-                            }
+                            add_coverage_formats(&mut coverage_formats, rest);
                             i += 1;
                             continue;
                         }
@@ -1321,6 +2214,14 @@ pub fn parse_args() -> Options {
                             i += 1;
                             continue;
                         }
+                        if let Some(rest) = args[i].strip_prefix("--seed=") {
+                            seed = Some(rest.parse().unwrap_or_else(|_| {
+                                eprintln!("Invalid seed: {}", rest);
+                                process::exit(64);
+                            }));
+                            i += 1;
+                            continue;
+                        }
                         if let Some(rest) = args[i].strip_prefix("--coverage-min=") {
                             coverage_min = Some(rest.parse().unwrap_or_else(|_| {
                                 eprintln!("Invalid coverage percentage: {}", rest);
@@ -1356,6 +2257,15 @@ pub fn parse_args() -> Options {
                             "--fail-on-n1" => {
                                 fail_on_n1 = true;
                             }
+                            "--fail-on-todo" => {
+                                fail_on_todo = true;
+                            }
+                            "--contract" => {
+                                contract = true;
+                            }
+                            "--vm" => {
+                                vm = true;
+                            }
                             "--browser" => {
                                 browser = true;
                             }
@@ -1366,6 +2276,51 @@ pub fn parse_args() -> Options {
                                 headed = true;
                                 browser = true;
                             }
+                            "--seed" => {
+                                i += 1;
+                                if i >= args.len() {
+                                    eprintln!("--seed requires a number");
+                                    print_usage();
+                                    process::exit(64);
+                                }
+                                seed = Some(args[i].parse().unwrap_or_else(|_| {
+                                    eprintln!("Invalid seed: {}", args[i]);
+                                    process::exit(64);
+                                }));
+                            }
+                            "--filter" => {
+                                i += 1;
+                                if i >= args.len() {
+                                    eprintln!("--filter requires a pattern");
+                                    print_usage();
+                                    process::exit(64);
+                                }
+                                filter = Some(args[i].clone());
+                            }
+                            "--tag" | "--exclude-tag" => {
+                                let flag = args[i].clone();
+                                i += 1;
+                                if i >= args.len() {
+                                    eprintln!("{} requires a tag", flag);
+                                    print_usage();
+                                    process::exit(64);
+                                }
+                                if flag == "--tag" {
+                                    add_tags(&mut tags, &args[i]);
+                                } else {
+                                    add_tags(&mut exclude_tags, &args[i]);
+                                }
+                            }
+                            "--coverage-format" => {
+                                i += 1;
+                                if i >= args.len() {
+                                    eprintln!("--coverage-format requires a format");
+                                    print_usage();
+                                    process::exit(64);
+                                }
+                                no_coverage = false;
+                                add_coverage_formats(&mut coverage_formats, &args[i]);
+                            }
                             "--coverage-min" => {
                                 i += 1;
                                 if i >= args.len() {
@@ -1401,8 +2356,15 @@ pub fn parse_args() -> Options {
                     no_coverage,
                     show_uncovered,
                     fail_on_n1,
+                    contract,
                     browser,
                     headed,
+                    seed,
+                    vm,
+                    filter,
+                    tags,
+                    exclude_tags,
+                    fail_on_todo,
                 };
                 return options;
             }
@@ -1498,6 +2460,7 @@ pub fn parse_args() -> Options {
                 let mut standalone = false;
                 let mut encrypt = false;
                 let mut protect = false;
+                let mut precompile = false;
                 let mut target: Option<String> = None;
                 while i < args.len() {
                     match args[i].as_str() {
@@ -1531,6 +2494,9 @@ pub fn parse_args() -> Options {
                             protect = true;
                             encrypt = true;
                         }
+                        "--precompile" => {
+                            precompile = true;
+                        }
                         other if other.starts_with('-') => {
                             eprintln!("Unknown option for build: {}", other);
                             print_usage();
@@ -1551,7 +2517,7 @@ pub fn parse_args() -> Options {
                     i += 1;
                 }
                 let folder = folder.unwrap_or_else(|| {
-                    eprintln!("build command requires a folder argument");
+                    eprintln!("build command requires a folder or .sl file argument");
                     print_usage();
                     process::exit(64);
                 });
@@ -1567,6 +2533,7 @@ pub fn parse_args() -> Options {
                     encrypt,
                     protect,
                     target,
+                    precompile,
                 };
                 return options;
             }
@@ -1594,6 +2561,23 @@ pub fn parse_args() -> Options {
                 print_usage();
                 process::exit(64);
             }
+            _ if matches!(options.command, Command::Repl) && !Path::new(arg).exists() => {
+                match solilang::module::find_command_script(&current_dir(), arg) {
+                    Ok(Some(script)) => {
+                        options.command = Command::Project {
+                            name: arg.clone(),
+                            script,
+                            args: args[i + 1..].to_vec(),
+                        };
+                        return options;
+                    }
+                    Ok(None) => options.command = Command::Run { file: arg.clone() },
+                    Err(msg) => {
+                        eprintln!("Error: {}", msg);
+                        process::exit(66);
+                    }
+                }
+            }
             _ => {
                 if let Command::Run { .. } = options.command {
                     eprintln!("Only one script file can be specified");
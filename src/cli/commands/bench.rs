@@ -0,0 +1,241 @@
+//! `soli bench` — run `bench("name") { ... }` blocks and report their speed.
+//!
+//! Each benchmark file runs once on the chosen engine, recording its
+//! `bench` blocks; every block is then warmed up and timed on that engine,
+//! and reported as nanoseconds and heap allocations per iteration. `--json`
+//! prints the same figures for tooling, and `--baseline FILE` compares a run
+//! against such an earlier output, for catching regressions between commits
+//! or between the interpreter and `--vm`. Run from an app, benchmarks can
+//! render its `app/views` templates.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+
+use serde_json::{json, Value as Json};
+use solilang::interpreter::builtins::bench::{BenchConfig, BenchResult};
+
+/// Where `soli bench` looks when no path is given.
+const DEFAULT_DIR: &str = "bench";
+
+/// Everything `soli bench` needs.
+pub struct BenchArgs<'a> {
+    pub paths: &'a [String],
+    pub vm: bool,
+    pub iterations: Option<u64>,
+    /// Measuring time per benchmark, in milliseconds.
+    pub time_ms: Option<u64>,
+    /// Warmup time per benchmark, in milliseconds.
+    pub warmup_ms: Option<u64>,
+    pub json: bool,
+    pub baseline: Option<&'a str>,
+}
+
+pub fn run(args: BenchArgs<'_>) {
+    let files = bench_files(args.paths);
+    if files.is_empty() {
+        eprintln!(
+            "No benchmark files found (looked in {})",
+            if args.paths.is_empty() {
+                format!("{}/", DEFAULT_DIR)
+            } else {
+                args.paths.join(", ")
+            }
+        );
+        process::exit(1);
+    }
+
+    let mut config = BenchConfig {
+        iterations: args.iterations,
+        ..BenchConfig::default()
+    };
+    if let Some(ms) = args.time_ms {
+        config.measure = Duration::from_millis(ms);
+    }
+    if let Some(ms) = args.warmup_ms {
+        config.warmup = Duration::from_millis(ms);
+    }
+    let baseline = args.baseline.map(load_baseline).unwrap_or_default();
+    // In an app, benchmarks can render its views.
+    let views_dir = Path::new("app/views");
+    if views_dir.is_dir() {
+        solilang::interpreter::builtins::template::init_templates(views_dir.to_path_buf());
+    }
+    let engine = if args.vm { "vm" } else { "interpreter" };
+
+    let mut failed = false;
+    let mut records = Vec::new();
+    for file in &files {
+        let display = file.display().to_string();
+        let source = match fs::read_to_string(file) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("Error: cannot read {}: {}", display, e);
+                failed = true;
+                continue;
+            }
+        };
+        if !args.json {
+            println!("{} ({})", display, engine);
+        }
+        let results = match solilang::run_benches(&source, Some(file), args.vm, &config) {
+            Ok(results) => results,
+            Err(e) => {
+                eprintln!("  Error: {}", e);
+                failed = true;
+                continue;
+            }
+        };
+        if results.is_empty() && !args.json {
+            println!("  no benchmarks");
+        }
+        for (name, result) in results {
+            match result {
+                Ok(result) => {
+                    let previous = baseline.get(&(display.clone(), name.clone())).copied();
+                    if !args.json {
+                        println!("{}", format_row(&result, previous));
+                    }
+                    records.push(json!({
+                        "file": display,
+                        "name": result.name,
+                        "engine": engine,
+                        "iterations": result.iterations,
+                        "ns_per_iter": result.ns_per_iter,
+                        "allocations_per_iter": result.allocations_per_iter,
+                        "bytes_per_iter": result.bytes_per_iter,
+                    }));
+                }
+                Err(e) => {
+                    eprintln!("  {}: Error: {}", name, e);
+                    failed = true;
+                }
+            }
+        }
+    }
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({ "benches": records })).unwrap_or_default()
+        );
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+/// The files to run: the given files, the `.sl` files under the given
+/// directories, or everything under `bench/`.
+fn bench_files(paths: &[String]) -> Vec<PathBuf> {
+    let roots: Vec<PathBuf> = if paths.is_empty() {
+        vec![PathBuf::from(DEFAULT_DIR)]
+    } else {
+        paths.iter().map(PathBuf::from).collect()
+    };
+    let mut files = Vec::new();
+    for root in roots {
+        if root.is_dir() {
+            let mut found = super::test_runner::collect_test_files(&root);
+            found.sort();
+            files.extend(found);
+        } else if root.is_file() {
+            files.push(root);
+        } else {
+            eprintln!("Error: {} does not exist", root.display());
+            process::exit(1);
+        }
+    }
+    files
+}
+
+/// `ns_per_iter` by `(file, name)` from an earlier `soli bench --json`.
+fn load_baseline(path: &str) -> HashMap<(String, String), f64> {
+    let parsed = fs::read_to_string(Path::new(path))
+        .map_err(|e| e.to_string())
+        .and_then(|text| serde_json::from_str::<Json>(&text).map_err(|e| e.to_string()));
+    let doc = match parsed {
+        Ok(doc) => doc,
+        Err(e) => {
+            eprintln!("Error: cannot read baseline {}: {}", path, e);
+            process::exit(1);
+        }
+    };
+    doc["benches"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|bench| {
+            Some((
+                (
+                    bench["file"].as_str()?.to_string(),
+                    bench["name"].as_str()?.to_string(),
+                ),
+                bench["ns_per_iter"].as_f64()?,
+            ))
+        })
+        .collect()
+}
+
+fn format_row(result: &BenchResult, baseline_ns: Option<f64>) -> String {
+    let mut row = format!(
+        "  {:<32} {:>14} ns/iter  {:>10} iters  {:>8.1} allocs/iter  {:>10}/iter",
+        result.name,
+        group_thousands(result.ns_per_iter.round() as u64),
+        group_thousands(result.iterations),
+        result.allocations_per_iter,
+        format_bytes(result.bytes_per_iter)
+    );
+    if let Some(before) = baseline_ns.filter(|ns| *ns > 0.0) {
+        let change = (result.ns_per_iter - before) / before * 100.0;
+        row.push_str(&format!("  {:+.1}%", change));
+    }
+    row
+}
+
+pub(super) fn group_thousands(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn format_bytes(bytes: f64) -> String {
+    if bytes >= 1024.0 * 1024.0 {
+        format!("{:.1} MB", bytes / (1024.0 * 1024.0))
+    } else if bytes >= 1024.0 {
+        format!("{:.1} KB", bytes / 1024.0)
+    } else {
+        format!("{:.0} B", bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_group_digits_and_show_the_change_from_the_baseline() {
+        let result = BenchResult {
+            name: "render card".to_string(),
+            iterations: 81_234,
+            ns_per_iter: 12_345.4,
+            allocations_per_iter: 42.0,
+            bytes_per_iter: 3_174.4,
+        };
+        let row = format_row(&result, Some(10_000.0));
+        assert!(row.contains("12,345 ns/iter"), "{}", row);
+        assert!(row.contains("81,234 iters"), "{}", row);
+        assert!(row.contains("3.1 KB/iter"), "{}", row);
+        assert!(row.ends_with("+23.5%"), "{}", row);
+        assert_eq!(group_thousands(999), "999");
+        assert_eq!(group_thousands(1_000_000), "1,000,000");
+    }
+}
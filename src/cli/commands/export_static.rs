@@ -0,0 +1,384 @@
+//! `soli export-static` — render an MVC app into a folder of static files.
+//!
+//! The app is booted as a normal production server on a loopback port and
+//! every page is fetched over HTTP, so controllers, layouts, helpers and
+//! middleware run exactly as they do when serving. The starting pages are
+//! the `[export] paths` listed in soli.toml plus any `--path` flags, or, when
+//! neither is given, every GET route without a `:param`/`*` segment. Each
+//! exported HTML page is scanned for same-site `href`/`src` links, so pages
+//! behind parameterised routes (`/posts/:slug`) are reached by following the
+//! index that links to them. `public/` is copied over first.
+
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::process::{self, Child};
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use reqwest::Url;
+use solilang::module::Package;
+
+/// How long the server gets to boot before the export gives up.
+const BOOT_TIMEOUT: Duration = Duration::from_secs(60);
+
+static LINK_ATTR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)\b(?:href|src)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+
+/// Everything `soli export-static` needs.
+pub struct ExportStaticArgs<'a> {
+    pub folder: &'a str,
+    /// Output directory. Defaults to `dist`.
+    pub output: Option<&'a str>,
+    /// Pages to start from on top of soli.toml's `[export] paths`.
+    pub paths: &'a [String],
+}
+
+/// Kills the server when the export finishes or bails out.
+struct Server(Child);
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+pub fn run(args: ExportStaticArgs<'_>) {
+    let app_dir = Path::new(args.folder);
+    if !app_dir.is_dir() {
+        eprintln!("Error: Folder '{}' does not exist", args.folder);
+        process::exit(1);
+    }
+    let out_dir = PathBuf::from(args.output.unwrap_or("dist"));
+
+    let seeds = seed_paths(app_dir, args.paths).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    });
+    if seeds.is_empty() {
+        eprintln!("Error: nothing to export — no GET route without parameters and no [export] paths in soli.toml");
+        process::exit(1);
+    }
+
+    if let Err(e) = std::fs::create_dir_all(&out_dir) {
+        eprintln!("Error: cannot create '{}': {}", out_dir.display(), e);
+        process::exit(1);
+    }
+    let public_dir = app_dir.join("public");
+    let copied = match copy_dir(&public_dir, &out_dir) {
+        Ok(n) => n,
+        Err(e) => {
+            eprintln!("Error: cannot copy public/: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let (server, base) = start_server(app_dir).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    });
+
+    let client = reqwest::blocking::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(Duration::from_secs(60))
+        .build()
+        .expect("Failed to build HTTP client");
+
+    let mut queue: VecDeque<String> = seeds.iter().cloned().collect();
+    let mut seen: HashSet<String> = queue.iter().cloned().collect();
+    let mut pages = 0usize;
+    let mut failed_seeds = 0usize;
+
+    while let Some(path) = queue.pop_front() {
+        let is_seed = seeds.contains(&path);
+        let url = base.join(&path).expect("page paths are absolute");
+        let response = match client.get(url.clone()).send() {
+            Ok(r) => r,
+            Err(e) => {
+                eprintln!("  \x1b[31m✗\x1b[0m {} ({})", path, e);
+                failed_seeds += usize::from(is_seed);
+                continue;
+            }
+        };
+        let status = response.status();
+        if !status.is_success() {
+            // Links to pages that need a session or redirect elsewhere are
+            // expected on most sites; only a page asked for by name fails
+            // the export.
+            if is_seed {
+                eprintln!("  \x1b[31m✗\x1b[0m {} ({})", path, status);
+                failed_seeds += 1;
+            } else {
+                eprintln!("  \x1b[33m-\x1b[0m {} skipped ({})", path, status);
+            }
+            continue;
+        }
+        let is_html = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/html"));
+        let body = match response.bytes() {
+            Ok(b) => b,
+            Err(e) => {
+                eprintln!("  \x1b[31m✗\x1b[0m {} ({})", path, e);
+                failed_seeds += usize::from(is_seed);
+                continue;
+            }
+        };
+
+        let target = out_dir.join(output_file(&path, is_html));
+        if let Some(parent) = target.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Err(e) = std::fs::write(&target, &body) {
+            eprintln!("Error: cannot write '{}': {}", target.display(), e);
+            process::exit(1);
+        }
+        pages += 1;
+        println!("  \x1b[32m✓\x1b[0m {}", path);
+
+        if is_html {
+            for link in page_links(&String::from_utf8_lossy(&body), &url) {
+                // Files already copied from public/ don't need fetching.
+                let in_public = public_dir.join(link.trim_start_matches('/')).is_file();
+                if !in_public && seen.insert(link.clone()) {
+                    queue.push_back(link);
+                }
+            }
+        }
+    }
+    drop(server);
+
+    println!();
+    println!(
+        "Exported {} page(s) and {} public file(s) to {}",
+        pages,
+        copied,
+        out_dir.display()
+    );
+    if failed_seeds > 0 {
+        eprintln!("{} requested page(s) failed", failed_seeds);
+        process::exit(1);
+    }
+}
+
+/// The pages the crawl starts from: soli.toml's `[export] paths` plus
+/// `extra`, or the app's parameterless GET routes when there are none.
+fn seed_paths(app_dir: &Path, extra: &[String]) -> Result<Vec<String>, String> {
+    let mut paths = Vec::new();
+    let manifest = app_dir.join("soli.toml");
+    if manifest.is_file() {
+        let package = Package::load(&manifest).map_err(|e| format!("soli.toml: {}", e))?;
+        paths.extend(package.export_paths);
+    }
+    paths.extend(extra.iter().cloned());
+
+    if paths.is_empty() {
+        let listing = solilang::serve::route_listing::collect_routes(app_dir)?;
+        paths.extend(
+            listing
+                .routes
+                .iter()
+                .filter(|r| r.method == "GET")
+                .map(|r| r.path_pattern.clone())
+                .filter(|p| is_static_route(p)),
+        );
+    }
+
+    let mut seen = HashSet::new();
+    Ok(paths
+        .into_iter()
+        .map(|p| {
+            if p.starts_with('/') {
+                p
+            } else {
+                format!("/{}", p)
+            }
+        })
+        .filter(|p| seen.insert(p.clone()))
+        .collect())
+}
+
+/// A route that names exactly one page: no parameters or wildcards, and not
+/// one of the framework's own `/_…` endpoints.
+fn is_static_route(pattern: &str) -> bool {
+    !pattern.starts_with("/_") && !pattern.contains(':') && !pattern.contains('*')
+}
+
+/// Where the page at `path` goes inside the output directory. HTML pages
+/// become `<path>/index.html` so they're served at the same URL by any
+/// static host; other responses (`/feed.xml`, `/robots.txt`) keep their
+/// name.
+fn output_file(path: &str, is_html: bool) -> PathBuf {
+    let trimmed = path.trim_matches('/');
+    let last = trimmed.rsplit('/').next().unwrap_or("");
+    if trimmed.is_empty() {
+        PathBuf::from("index.html")
+    } else if is_html && !last.ends_with(".html") && !last.ends_with(".htm") {
+        Path::new(trimmed).join("index.html")
+    } else {
+        PathBuf::from(trimmed)
+    }
+}
+
+/// Same-site pages and assets `html` links to, as absolute paths. Links are
+/// resolved against `page`. A static host ignores query strings, so a page
+/// link with one (`/search?q=x`) is skipped, while an asset link with one
+/// (`/__soli/nav.js?v=…`, a cache-buster) is fetched by its bare path.
+fn page_links(html: &str, page: &Url) -> Vec<String> {
+    LINK_ATTR
+        .captures_iter(html)
+        .filter_map(|c| c.get(1).or_else(|| c.get(2)))
+        .filter_map(|m| page.join(m.as_str().trim()).ok())
+        .filter(|url| {
+            url.scheme() == page.scheme()
+                && url.host_str() == page.host_str()
+                && url.port_or_known_default() == page.port_or_known_default()
+                && (url.query().is_none() || has_extension(url.path()))
+        })
+        .map(|url| url.path().to_string())
+        .collect()
+}
+
+fn has_extension(path: &str) -> bool {
+    path.rsplit('/')
+        .next()
+        .is_some_and(|last| last.contains('.'))
+}
+
+/// Copy `from` into `to`, returning how many files were copied. A missing
+/// `from` copies nothing.
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<usize> {
+    if !from.is_dir() {
+        return Ok(0);
+    }
+    let mut copied = 0;
+    for entry in walkdir::WalkDir::new(from) {
+        let entry = entry?;
+        let rel = entry.path().strip_prefix(from).unwrap();
+        let dest = to.join(rel);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&dest)?;
+        } else {
+            std::fs::copy(entry.path(), &dest)?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+/// Boot `soli serve` for the app on a free loopback port and wait until it
+/// answers.
+fn start_server(app_dir: &Path) -> Result<(Server, Url), String> {
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|l| l.local_addr())
+        .map_err(|e| format!("cannot pick a port: {}", e))?
+        .port();
+    let exe = std::env::current_exe().unwrap_or_else(|_| PathBuf::from("soli"));
+    let log_path = std::env::temp_dir().join(format!("soli_export_static_{}.log", port));
+    let log = std::fs::File::create(&log_path)
+        .map_err(|e| format!("cannot create '{}': {}", log_path.display(), e))?;
+    let log_err = log.try_clone().map_err(|e| e.to_string())?;
+
+    println!("Starting {} on port {}...", app_dir.display(), port);
+    // Not `--dev`: the dev bar and live-reload script would end up in every
+    // exported page.
+    let child = process::Command::new(exe)
+        .arg("serve")
+        .arg(app_dir)
+        .arg("--port")
+        .arg(port.to_string())
+        .arg("--workers")
+        .arg("1")
+        .env("SOLI_HOST", "127.0.0.1")
+        .stdout(log)
+        .stderr(log_err)
+        .spawn()
+        .map_err(|e| format!("cannot start the server: {}", e))?;
+    let mut server = Server(child);
+
+    let base = Url::parse(&format!("http://127.0.0.1:{}/", port)).unwrap();
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let started = Instant::now();
+    loop {
+        if client.get(base.clone()).send().is_ok() {
+            return Ok((server, base));
+        }
+        if let Ok(Some(status)) = server.0.try_wait() {
+            return Err(format!(
+                "the server exited during boot ({}); see {}",
+                status,
+                log_path.display()
+            ));
+        }
+        if started.elapsed() > BOOT_TIMEOUT {
+            return Err(format!(
+                "the server didn't start within {}s; see {}",
+                BOOT_TIMEOUT.as_secs(),
+                log_path.display()
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_map_to_index_files_and_assets_keep_their_name() {
+        assert_eq!(output_file("/", true), PathBuf::from("index.html"));
+        assert_eq!(
+            output_file("/about", true),
+            PathBuf::from("about/index.html")
+        );
+        assert_eq!(
+            output_file("/docs/intro/", true),
+            PathBuf::from("docs/intro/index.html")
+        );
+        assert_eq!(output_file("/404.html", true), PathBuf::from("404.html"));
+        assert_eq!(output_file("/feed.xml", false), PathBuf::from("feed.xml"));
+    }
+
+    #[test]
+    fn only_same_site_links_without_queries_are_followed() {
+        let page = Url::parse("http://127.0.0.1:4000/docs/intro").unwrap();
+        let html = r#"
+            <a href="/">Home</a>
+            <a href='routing'>Routing</a>
+            <a href="../about#team">About</a>
+            <link href="/css/app.css" rel="stylesheet">
+            <img src="/img/logo.png">
+            <a href="https://example.com/">Elsewhere</a>
+            <a href="/search?q=soli">Search</a>
+            <script src="/__soli/nav.js?v=3168c859" defer></script>
+            <a href="mailto:hi@example.com">Mail</a>
+        "#;
+        assert_eq!(
+            page_links(html, &page),
+            vec![
+                "/",
+                "/docs/routing",
+                "/about",
+                "/css/app.css",
+                "/img/logo.png",
+                "/__soli/nav.js"
+            ]
+        );
+    }
+
+    #[test]
+    fn parameterised_and_internal_routes_are_not_seeds() {
+        assert!(is_static_route("/"));
+        assert!(is_static_route("/docs/routing"));
+        assert!(!is_static_route("/posts/:slug"));
+        assert!(!is_static_route("/files/*path"));
+        assert!(!is_static_route("/__dev/repl"));
+    }
+}
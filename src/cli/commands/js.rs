@@ -0,0 +1,90 @@
+//! `soli js` — vendor npm packages' ESM builds and pin them in the import map.
+//!
+//! Packages are downloaded into `vendor/javascript` and versioned under
+//! `[javascript]` in soli.toml; `javascript_importmap_tags()` in a layout
+//! maps their names to the vendored files. See `solilang::module::importmap`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::cli::args::JsAction;
+use solilang::module::{importmap, Package};
+
+pub fn run(action: &JsAction) {
+    let toml_path = match Package::find(Path::new(".")) {
+        Some(p) => p,
+        None => {
+            eprintln!("No soli.toml found. Run 'soli init' first.");
+            process::exit(1);
+        }
+    };
+    let mut pkg = Package::load(&toml_path).unwrap_or_else(|e| {
+        eprintln!("Error: Failed to load soli.toml: {}", e);
+        process::exit(1);
+    });
+    let vendor_dir = toml_path
+        .parent()
+        .map(|dir| dir.join(importmap::VENDOR_DIR))
+        .unwrap_or_else(|| PathBuf::from(importmap::VENDOR_DIR));
+
+    match action {
+        JsAction::Add { packages } => {
+            for spec in packages {
+                let (name, version) = importmap::parse_spec(spec);
+                let vendored = importmap::vendor(
+                    &name,
+                    version.as_deref(),
+                    &vendor_dir,
+                    &pkg.javascript,
+                    &mut importmap::http_fetch,
+                )
+                .unwrap_or_else(|e| {
+                    eprintln!("Error: {}", e);
+                    process::exit(1);
+                });
+                for (pin, version) in vendored {
+                    println!(
+                        "  \x1b[32m\x1b[1m✓\x1b[0m Pinned {}@{} ({}/{})",
+                        pin,
+                        version,
+                        importmap::VENDOR_DIR,
+                        importmap::file_name(&pin)
+                    );
+                    pkg.javascript.insert(pin, version);
+                }
+            }
+        }
+        JsAction::Remove { name } => {
+            if pkg.javascript.remove(name).is_none() {
+                eprintln!("Error: '{}' is not pinned in soli.toml", name);
+                process::exit(1);
+            }
+            let _ = fs::remove_file(vendor_dir.join(importmap::file_name(name)));
+            println!("  \x1b[32m\x1b[1m✓\x1b[0m Unpinned {}", name);
+            return write_manifest(&toml_path, &pkg);
+        }
+        JsAction::List => {
+            if pkg.javascript.is_empty() {
+                println!("No JavaScript packages pinned. Add one with 'soli js add <package>'.");
+            }
+            for (name, version) in &pkg.javascript {
+                let missing = if vendor_dir.join(importmap::file_name(name)).is_file() {
+                    ""
+                } else {
+                    "  (missing from vendor/javascript)"
+                };
+                println!("{}@{}{}", name, version, missing);
+            }
+            return;
+        }
+    }
+    write_manifest(&toml_path, &pkg);
+}
+
+fn write_manifest(toml_path: &Path, pkg: &Package) {
+    fs::write(toml_path, pkg.to_toml()).unwrap_or_else(|e| {
+        eprintln!("Error: Failed to write soli.toml: {}", e);
+        process::exit(1);
+    });
+}
@@ -1,5 +1,10 @@
+pub mod bench;
 pub mod desktop;
+pub mod export_static;
+pub mod js;
 mod progress;
+pub mod self_bench;
+pub mod task;
 mod test_runner;
 
 use std::env;
@@ -7,11 +12,11 @@ use std::env;
 use std::fs::OpenOptions;
 use std::fs::{self, File};
 use std::io::Read;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process;
 
 use crate::cli::args::{
-    print_usage, DbMigrateAction, DbSeedAction, EngineAction, Options, VERSION,
+    print_usage, CheckFormat, DbMigrateAction, DbSeedAction, EngineAction, Options, VERSION,
 };
 
 #[cfg(unix)]
@@ -28,6 +33,7 @@ pub fn run_build(
     encrypt: bool,
     protect: bool,
     target: Option<&str>,
+    precompile: bool,
 ) {
     // Resolve "." to current directory so file_name() works properly
     let source_dir = if folder == "." {
@@ -41,11 +47,29 @@ pub fn run_build(
         process::exit(1);
     }
 
+    if source_dir.is_file() && source_dir.extension().is_some_and(|ext| ext == "sl") {
+        if standalone || encrypt || target.is_some() || precompile {
+            eprintln!("Error: --standalone, --encrypt, --protect, --target and --precompile apply to app folders, not single scripts");
+            process::exit(1);
+        }
+        run_build_bytecode(&source_dir, output);
+        return;
+    }
+
     if !source_dir.is_dir() {
         eprintln!("Error: '{}' is not a directory", folder);
         process::exit(1);
     }
 
+    if precompile {
+        if standalone || encrypt || target.is_some() || output.is_some() {
+            eprintln!("Error: --precompile writes .slc files into the app folder and takes no other build option");
+            process::exit(1);
+        }
+        run_build_precompile(&source_dir);
+        return;
+    }
+
     // Catch a --target typo before doing any build work.
     if let Some(t) = target {
         if let Err(e) = crate::cli::standalone::validate_target(t) {
@@ -185,6 +209,60 @@ pub fn run_build(
     }
 }
 
+/// `soli build script.sl`: compile a script to a `.slc` module the VM loads
+/// without lexing, parsing or type-checking it again.
+fn run_build_bytecode(script: &Path, output: Option<&str>) {
+    let source = fs::read_to_string(script).unwrap_or_else(|e| {
+        eprintln!("Error: Failed to read '{}': {}", script.display(), e);
+        process::exit(1);
+    });
+    let (module, sources) =
+        solilang::compiled_cache::compile_with_sources(&source, Some(script), true).unwrap_or_else(
+            |e| {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            },
+        );
+    let out = output
+        .map(PathBuf::from)
+        .unwrap_or_else(|| solilang::vm::bytecode::cache_path(script));
+    match solilang::vm::bytecode::write_module(&module, &sources, &out) {
+        Ok(size) => println!(
+            "Compiled {} to {} ({} bytes)",
+            script.display(),
+            out.display(),
+            size
+        ),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+/// `soli build my_app --precompile`: save the parsed form of every app file
+/// `soli serve` loads as a `.slc` next to it.
+fn run_build_precompile(app_dir: &Path) {
+    println!("Precompiling {}...", app_dir.display());
+    match solilang::serve::precompile_app(app_dir) {
+        Ok(written) => {
+            let total: usize = written.iter().map(|(_, size)| size).sum();
+            for (path, size) in &written {
+                println!("  {} ({} bytes)", path.display(), size);
+            }
+            println!(
+                "  \x1b[32m\x1b[1m✓\x1b[0m {} files precompiled ({:.1} KB)",
+                written.len(),
+                total as f64 / 1024.0
+            );
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
 /// Resolve the bundle encryption/decryption key. Order: `SOLI_BUNDLE_KEY`
 /// (the key itself), then `SOLI_BUNDLE_AUTH_URL` (a key server queried with
 /// an optional `SOLI_BUNDLE_API_KEY` sent as `x-api-key`). Returns the key
@@ -331,7 +409,7 @@ pub fn run_serve(folder: &str, port: u16, dev_mode: bool, workers: usize, daemon
     }
 
     if let Err(e) = solilang::serve::serve_folder_with_options(path, port, dev_mode, workers) {
-        eprintln!("Error: {}", e);
+        eprintln!("Error: {}", e.report());
         process::exit(70);
     }
 }
@@ -704,6 +782,38 @@ fn kill_previous_process(pid_file: &Path) {
 }
 
 pub fn run_file(path: &str, options: &Options) {
+    run_on_interpreter_thread("soli-main", || run_file_on_current_thread(path, options));
+}
+
+/// Run `body` on a thread named `name` and sized for the call-depth limit, so
+/// deep recursion hits the interpreter's limit rather than the main thread's
+/// stack.
+fn run_on_interpreter_thread(name: &str, body: impl FnOnce() + Send) {
+    let stack_size = solilang::interpreter::executor::stack_size_for_depth(
+        solilang::interpreter::executor::max_call_depth_from_env(),
+    );
+    std::thread::scope(|scope| {
+        let spawned = std::thread::Builder::new()
+            .name(name.to_string())
+            .stack_size(stack_size)
+            .spawn_scoped(scope, body);
+        match spawned {
+            Ok(handle) => handle.join().unwrap_or_else(|_| process::exit(101)),
+            Err(e) => {
+                eprintln!(
+                    "Error: cannot start the script thread with a {} MiB stack: {}. \
+                     Lower {} to need less.",
+                    stack_size / (1024 * 1024),
+                    e,
+                    solilang::interpreter::executor::MAX_CALL_DEPTH_ENV
+                );
+                process::exit(1);
+            }
+        }
+    });
+}
+
+fn run_file_on_current_thread(path: &str, options: &Options) {
     let path = Path::new(path);
 
     // Enforce a `soli_version` floor if this script lives inside a project.
@@ -717,29 +827,119 @@ pub fn run_file(path: &str, options: &Options) {
         process::exit(1);
     }
 
-    let result = if options.use_vm {
+    let compiled = path
+        .extension()
+        .is_some_and(|ext| ext == solilang::vm::bytecode::BYTECODE_EXTENSION);
+    let result = if options.use_vm || compiled {
         solilang::run_file_vm(path, !options.no_type_check)
     } else {
         solilang::run_file(path, !options.no_type_check)
     };
 
     if let Err(e) = result {
-        eprintln!("Error: {}", e);
+        let type_errors = e.type_errors();
+        if !type_errors.is_empty() {
+            let source = fs::read_to_string(path).unwrap_or_default();
+            let path = path.display().to_string();
+            let shown = options.max_errors.unwrap_or(usize::MAX);
+            for err in type_errors.iter().take(shown) {
+                eprintln!("{}", err.render(&source, &path));
+            }
+            if type_errors.len() > shown {
+                eprintln!("... {} more error(s) not shown", type_errors.len() - shown);
+            }
+            eprintln!("{} type error(s); not running {}", type_errors.len(), path);
+            process::exit(70);
+        }
+        eprintln!("Error: {}", e.report());
+        process::exit(70);
+    }
+}
+
+/// Run a `[commands]` script from soli.toml as `soli <name> [args...]`.
+///
+/// Like rake, the script runs from the project root, whichever subdirectory
+/// it was invoked from, and reads its arguments with `System.args()`.
+pub fn run_project_command(name: &str, script: &Path, args: &[String], options: &Options) {
+    let cwd = env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+    let root = solilang::module::Package::find(&cwd)
+        .and_then(|manifest| manifest.parent().map(Path::to_path_buf))
+        .unwrap_or(cwd);
+    let script = match script.canonicalize() {
+        Ok(script) => script,
+        Err(e) => {
+            eprintln!("Error: cannot run command '{}': {}", name, e);
+            process::exit(66);
+        }
+    };
+    if let Err(e) = std::env::set_current_dir(&root) {
+        eprintln!("Error: cannot enter {}: {}", root.display(), e);
+        process::exit(66);
+    }
+    solilang::interpreter::builtins::system::set_script_args(args.to_vec());
+    run_file(&script.to_string_lossy(), options);
+}
+
+/// Run a script with the profiler on. Prints the slowest functions and
+/// writes the collapsed stacks (for `flamegraph.pl`, inferno or speedscope)
+/// to `output`, or `<script>.folded`. The profile is reported even when the
+/// script fails, since a crash is often what is being chased.
+pub fn run_profile(path: &str, output: Option<&str>, top: usize, options: &Options) {
+    let path = Path::new(path);
+    let output = output.map(PathBuf::from).unwrap_or_else(|| {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        PathBuf::from(format!("{}.folded", stem))
+    });
+
+    let (profile, result) = solilang::run_file_profiled(path, !options.no_type_check);
+
+    eprintln!();
+    eprint!("{}", profile.summary(top));
+    match File::create(&output).and_then(|mut file| profile.write_folded(&mut file)) {
+        Ok(()) => eprintln!("flamegraph stacks written to {}", output.display()),
+        Err(e) => {
+            eprintln!("Error: cannot write {}: {}", output.display(), e);
+            process::exit(73);
+        }
+    }
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e.report());
         process::exit(70);
     }
 }
 
 pub fn run_eval(code: &str, options: &Options) {
-    let result = solilang::run_with_type_check(code, !options.no_type_check);
+    run_on_interpreter_thread("soli-eval", || run_eval_on_current_thread(code, options));
+}
+
+fn run_eval_on_current_thread(code: &str, options: &Options) {
+    let limited = options.timeout_ms.is_some()
+        || options.max_steps.is_some()
+        || options.max_allocations.is_some();
+    let budget = limited.then(|| {
+        let mut budget = solilang::interpreter::EvalBudget::default();
+        if let Some(ms) = options.timeout_ms {
+            budget = budget.with_timeout(std::time::Duration::from_millis(ms));
+        }
+        if let Some(steps) = options.max_steps {
+            budget = budget.with_max_steps(steps);
+        }
+        if let Some(allocations) = options.max_allocations {
+            budget = budget.with_max_allocations(allocations);
+        }
+        budget
+    });
+    let result = solilang::run_with_budget(code, None, !options.no_type_check, budget);
 
     if let Err(e) = result {
-        eprintln!("Error: {}", e);
+        eprintln!("Error: {}", e.report());
         process::exit(70);
     }
 }
 
 pub fn run_repl() {
-    solilang::repl_tui::run_tui_repl().unwrap();
+    run_on_interpreter_thread("soli-repl", || solilang::repl_tui::run_tui_repl().unwrap());
 }
 
 pub fn run_lsp() {
@@ -750,13 +950,62 @@ pub fn run_lsp() {
     solilang::lsp::start_lsp();
 }
 
-pub fn run_lint(paths: &[String]) {
+pub fn run_debug() {
+    let stdin = std::io::stdin();
+    // The debuggee's prints would corrupt the DAP framing on stdout: keep a
+    // copy of stdout for the protocol, point fd 1 at a pipe, and let the
+    // adapter forward what arrives there as output events.
+    #[cfg(unix)]
+    let result = match redirect_stdout() {
+        Some((protocol, program_output)) => {
+            solilang::dap::serve(stdin.lock(), protocol, Some(Box::new(program_output)))
+        }
+        None => {
+            eprintln!("Error: cannot redirect stdout for the debuggee");
+            process::exit(1);
+        }
+    };
+    #[cfg(not(unix))]
+    let result = solilang::dap::serve(stdin.lock(), std::io::stdout(), None);
+    if let Err(e) = result {
+        eprintln!("Error: debug adapter: {}", e);
+        process::exit(1);
+    }
+    process::exit(0);
+}
+
+/// Duplicate stdout for the caller, then replace fd 1 with the write end of
+/// a pipe. Returns the duplicate and the pipe's read end.
+#[cfg(unix)]
+fn redirect_stdout() -> Option<(File, File)> {
+    use std::os::fd::FromRawFd;
+    // SAFETY: plain fd syscalls; each fd wrapped in a `File` is owned by it
+    // alone, and the pipe's write end lives on as fd 1.
+    unsafe {
+        let protocol = libc::dup(1);
+        if protocol < 0 {
+            return None;
+        }
+        let mut fds = [0; 2];
+        if libc::pipe(fds.as_mut_ptr()) != 0 || libc::dup2(fds[1], 1) < 0 {
+            libc::close(protocol);
+            return None;
+        }
+        libc::close(fds[1]);
+        Some((File::from_raw_fd(protocol), File::from_raw_fd(fds[0])))
+    }
+}
+
+pub fn run_lint(paths: &[String], dead_code: bool, fix: bool) {
     let targets: Vec<std::path::PathBuf> = if paths.is_empty() {
         let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
         vec![cwd]
     } else {
         paths.iter().map(std::path::PathBuf::from).collect()
     };
+    if dead_code {
+        return run_dead_code(&targets);
+    }
 
     let mut files: Vec<std::path::PathBuf> = Vec::new();
     for t in &targets {
@@ -772,12 +1021,15 @@ pub fn run_lint(paths: &[String]) {
     }
 
     if files.is_empty() {
-        println!("No .sl or .slv files found.");
+        println!("No .sl, .slv or .erb files found.");
         return;
     }
 
+    let mut configs = LintConfigs::default();
     let mut total_issues = 0;
+    let mut total_errors = 0;
     let mut files_with_issues = 0;
+    let mut total_fixed = 0;
 
     for file in &files {
         let source = match fs::read_to_string(file) {
@@ -788,7 +1040,9 @@ pub fn run_lint(paths: &[String]) {
             }
         };
 
-        let diagnostics = match solilang::lint_file(&source, &file.display().to_string()) {
+        let config = configs.for_file(file);
+        let path = file.display().to_string();
+        let mut diagnostics = match solilang::lint_file_with_config(&source, &path, config) {
             Ok(d) => d,
             Err(e) => {
                 eprintln!("{}: parse error: {}", file.display(), e);
@@ -796,35 +1050,183 @@ pub fn run_lint(paths: &[String]) {
             }
         };
 
+        if fix {
+            let (fixed_source, remaining, fixed) =
+                fix_lint_issues(&source, &path, config, diagnostics);
+            diagnostics = remaining;
+            if fixed > 0 {
+                if let Err(e) = fs::write(file, &fixed_source) {
+                    eprintln!("{}: error writing file: {}", file.display(), e);
+                    process::exit(1);
+                }
+                println!("{}: fixed {} issue(s)", file.display(), fixed);
+                total_fixed += fixed;
+            }
+        }
+
         if !diagnostics.is_empty() {
             files_with_issues += 1;
             for d in &diagnostics {
-                println!(
-                    "{}:{}:{} - [{}] {}",
-                    file.display(),
-                    d.span.line,
-                    d.span.column,
-                    d.rule,
-                    d.message
-                );
+                print_lint_diagnostic(file, d);
             }
             total_issues += diagnostics.len();
+            total_errors += diagnostics
+                .iter()
+                .filter(|d| d.severity == solilang::lint::Severity::Error)
+                .count();
         }
     }
 
+    if total_fixed > 0 {
+        println!();
+        println!("{} issue(s) fixed", total_fixed);
+    }
+
     if total_issues > 0 {
         println!();
         println!(
-            "{} issue(s) found in {} file(s)",
-            total_issues, files_with_issues
+            "{} issue(s) found in {} file(s), {} error(s)",
+            total_issues, files_with_issues, total_errors
         );
-        process::exit(1);
+        if total_errors > 0 {
+            process::exit(1);
+        }
+        return;
     }
 
     println!("No issues found.");
 }
 
-pub fn run_check(paths: &[String]) {
+/// The `[lint]` config of each file's soli.toml, loaded once per directory.
+/// A manifest that can't be read stops the run: a typo in a level must not
+/// silently turn a rule back into a warning.
+#[derive(Default)]
+struct LintConfigs {
+    by_dir: std::collections::HashMap<PathBuf, solilang::lint::LintConfig>,
+}
+
+impl LintConfigs {
+    fn for_file(&mut self, file: &Path) -> &solilang::lint::LintConfig {
+        let dir = file
+            .parent()
+            .filter(|d| !d.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        self.by_dir.entry(dir).or_insert_with_key(|dir| {
+            solilang::lint::LintConfig::find(dir).unwrap_or_else(|e| {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            })
+        })
+    }
+}
+
+/// One finding in the `file:line:column - [rule] message` format; error-level
+/// findings are marked `error`.
+fn print_lint_diagnostic(file: &Path, d: &solilang::lint::LintDiagnostic) {
+    let level = match d.severity {
+        solilang::lint::Severity::Error => "error ",
+        solilang::lint::Severity::Warning => "",
+    };
+    println!(
+        "{}:{}:{} - {}[{}] {}",
+        file.display(),
+        d.span.line,
+        d.span.column,
+        level,
+        d.rule,
+        d.message
+    );
+}
+
+/// `soli lint --fix` on one file: apply the diagnostics' fixes and lint the
+/// result again, until nothing more is fixable — a fix can uncover another,
+/// or wait on one it overlapped. Returns the new source, what is still
+/// reported, and how many fixes were applied. A pass whose result no longer
+/// parses is dropped.
+fn fix_lint_issues(
+    source: &str,
+    path: &str,
+    config: &solilang::lint::LintConfig,
+    mut diagnostics: Vec<solilang::lint::LintDiagnostic>,
+) -> (String, Vec<solilang::lint::LintDiagnostic>, usize) {
+    const MAX_PASSES: usize = 10;
+    let mut source = source.to_string();
+    let mut fixed = 0;
+    for _ in 0..MAX_PASSES {
+        let (next, applied) = solilang::lint::apply_fixes(&source, &diagnostics);
+        if applied == 0 {
+            break;
+        }
+        let Ok(next_diagnostics) = solilang::lint_file_with_config(&next, path, config) else {
+            break;
+        };
+        source = next;
+        diagnostics = next_diagnostics;
+        fixed += applied;
+    }
+    (source, diagnostics, fixed)
+}
+
+/// `soli lint --dead-code`: each target is analysed as one project (see
+/// `solilang::lint::dead_code`), reported in the per-file lint format.
+fn run_dead_code(targets: &[std::path::PathBuf]) {
+    let mut findings = Vec::new();
+    for t in targets {
+        if !t.is_dir() {
+            eprintln!(
+                "Error: --dead-code needs a project folder, got '{}'",
+                t.display()
+            );
+            process::exit(1);
+        }
+        findings.extend(solilang::lint::dead_code::find_dead_code(t));
+    }
+
+    let mut configs = LintConfigs::default();
+    findings.retain_mut(|f| {
+        let config = configs.for_file(&f.file);
+        match config.apply(vec![f.diagnostic.clone()]).pop() {
+            Some(d) => {
+                f.diagnostic = d;
+                true
+            }
+            None => false,
+        }
+    });
+
+    for f in &findings {
+        print_lint_diagnostic(&f.file, &f.diagnostic);
+    }
+
+    if !findings.is_empty() {
+        let files: std::collections::HashSet<_> = findings.iter().map(|f| &f.file).collect();
+        let errors = findings
+            .iter()
+            .filter(|f| f.diagnostic.severity == solilang::lint::Severity::Error)
+            .count();
+        println!();
+        println!(
+            "{} issue(s) found in {} file(s), {} error(s)",
+            findings.len(),
+            files.len(),
+            errors
+        );
+        if errors > 0 {
+            process::exit(1);
+        }
+        return;
+    }
+
+    println!("No unused code found.");
+}
+
+pub fn run_check(
+    paths: &[String],
+    max_errors: Option<usize>,
+    templates: bool,
+    format: CheckFormat,
+) {
     let targets: Vec<std::path::PathBuf> = if paths.is_empty() {
         let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
         vec![cwd]
@@ -841,21 +1243,27 @@ pub fn run_check(paths: &[String]) {
         if t.is_file() {
             files.push(t.clone());
         } else {
-            // Type-check Soli programs only (skip .slv templates).
-            files.extend(
-                test_runner::collect_lint_files(t)
-                    .into_iter()
-                    .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("sl")),
-            );
+            // Type-check Soli programs, and templates with --templates.
+            files.extend(test_runner::collect_lint_files(t).into_iter().filter(|p| {
+                p.extension().and_then(|e| e.to_str()) == Some("sl")
+                    || (templates && is_template(p))
+            }));
         }
     }
 
+    if format != CheckFormat::Text {
+        run_check_machine(&files, format);
+        return;
+    }
+
     if files.is_empty() {
         println!("No .sl files found.");
         return;
     }
 
     let checked = files.len();
+    let max_errors = max_errors.unwrap_or(usize::MAX);
+    let mut shown_errors = 0;
     let mut total_errors = 0;
     let mut total_warnings = 0;
     let mut files_with_errors = 0;
@@ -868,7 +1276,12 @@ pub fn run_check(paths: &[String]) {
                 continue;
             }
         };
-        match solilang::type_check_source(&source, Some(file.as_path())) {
+        let result = if is_template(file) {
+            solilang::type_check_template(&source, file)
+        } else {
+            solilang::type_check_source(&source, Some(file.as_path()))
+        };
+        match result {
             Ok(warnings) => {
                 total_warnings += warnings.len();
                 for warning in &warnings {
@@ -879,14 +1292,32 @@ pub fn run_check(paths: &[String]) {
                 files_with_errors += 1;
                 total_errors += errors.len();
                 for err in &errors {
-                    println!("{}: {}", file.display(), err);
+                    if shown_errors == max_errors {
+                        break;
+                    }
+                    shown_errors += 1;
+                    match err.type_errors() {
+                        [type_error] => {
+                            println!(
+                                "{}",
+                                type_error.render(&source, &file.display().to_string())
+                            )
+                        }
+                        _ => println!("{}: {}\n", file.display(), err),
+                    }
                 }
             }
         }
     }
 
     if total_errors > 0 {
-        println!();
+        if total_errors > shown_errors {
+            println!(
+                "... {} more error(s) not shown",
+                total_errors - shown_errors
+            );
+            println!();
+        }
         println!(
             "{} error(s){} in {} of {} file(s)",
             total_errors,
@@ -913,6 +1344,170 @@ pub fn run_check(paths: &[String]) {
     println!("No type errors. Checked {} file(s).", checked);
 }
 
+/// `soli types --coverage [paths...]` — per file, the share of expressions
+/// the type checker infers a type other than `Any` for. Files with type
+/// errors still report: coverage measures annotations, not correctness.
+pub fn run_types_coverage(paths: &[String], json: bool, min: Option<f64>, show_untyped: bool) {
+    let targets: Vec<PathBuf> = if paths.is_empty() {
+        vec![std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))]
+    } else {
+        paths.iter().map(PathBuf::from).collect()
+    };
+
+    let mut files: Vec<PathBuf> = Vec::new();
+    for t in &targets {
+        if !t.exists() {
+            eprintln!("Error: Path '{}' does not exist", t.display());
+            process::exit(1);
+        }
+        if t.is_file() {
+            files.push(t.clone());
+        } else {
+            files.extend(
+                test_runner::collect_lint_files(t)
+                    .into_iter()
+                    .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("sl")),
+            );
+        }
+    }
+
+    let mut reports: Vec<(String, solilang::types::TypeCoverage)> = Vec::new();
+    for file in &files {
+        let source = match fs::read_to_string(file) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("{}: error reading file: {}", file.display(), e);
+                continue;
+            }
+        };
+        match solilang::type_coverage_source(&source, Some(file.as_path())) {
+            Ok(coverage) => reports.push((file.display().to_string(), coverage)),
+            Err(errors) => {
+                for err in &errors {
+                    eprintln!("{}: {}", file.display(), err);
+                }
+            }
+        }
+    }
+
+    let total: usize = reports.iter().map(|(_, c)| c.total()).sum();
+    let typed: usize = reports.iter().map(|(_, c)| c.typed()).sum();
+    let percent = if total == 0 {
+        100.0
+    } else {
+        typed as f64 * 100.0 / total as f64
+    };
+
+    if json {
+        let files: Vec<serde_json::Value> = reports
+            .iter()
+            .map(|(path, c)| {
+                serde_json::json!({
+                    "path": path,
+                    "typed": c.typed(),
+                    "total": c.total(),
+                    "percent": c.percent(),
+                    "untyped_lines": c.untyped_lines(),
+                })
+            })
+            .collect();
+        let report = serde_json::json!({
+            "files": files,
+            "typed": typed,
+            "total": total,
+            "percent": percent,
+        });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).unwrap_or_default()
+        );
+    } else if reports.is_empty() {
+        println!("No .sl files found.");
+    } else {
+        let width = reports
+            .iter()
+            .map(|(path, _)| path.len())
+            .max()
+            .unwrap_or(0);
+        for (path, c) in &reports {
+            println!(
+                "{:<width$}  {:>6}/{:<6} {:>5.1}%",
+                path,
+                c.typed(),
+                c.total(),
+                c.percent(),
+            );
+            if show_untyped {
+                let lines = c.untyped_lines();
+                if !lines.is_empty() {
+                    let lines: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+                    println!("    untyped lines: {}", lines.join(", "));
+                }
+            }
+        }
+        println!();
+        println!(
+            "Type coverage: {:.1}% ({} of {} expressions typed) in {} file(s)",
+            percent,
+            typed,
+            total,
+            reports.len()
+        );
+    }
+
+    if let Some(min) = min {
+        if percent < min {
+            eprintln!(
+                "Type coverage {:.1}% is below the minimum of {:.1}%",
+                percent, min
+            );
+            process::exit(1);
+        }
+    }
+}
+
+/// `soli check --format json|sarif`: every finding of every file as one
+/// document on stdout, exiting 1 when any is an error.
+fn run_check_machine(files: &[PathBuf], format: CheckFormat) {
+    use solilang::types::diagnostics::{self, Diagnostic, Severity};
+
+    let mut found: Vec<Diagnostic> = Vec::new();
+    for file in files {
+        let path = file.display().to_string();
+        let source = match fs::read_to_string(file) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("{}: error reading file: {}", path, e);
+                continue;
+            }
+        };
+        if is_template(file) {
+            if let Err(errors) = solilang::type_check_template(&source, file) {
+                found.extend(errors.iter().flat_map(|e| Diagnostic::from_error(&path, e)));
+            }
+        } else {
+            found.extend(solilang::type_check_diagnostics(
+                &source,
+                Some(file.as_path()),
+                &path,
+            ));
+        }
+    }
+
+    match format {
+        CheckFormat::Sarif => println!("{}", diagnostics::format_sarif(&found)),
+        _ => println!("{}", diagnostics::format_json(&found, files.len())),
+    }
+    if found.iter().any(|d| d.severity == Severity::Error) {
+        process::exit(1);
+    }
+}
+
+fn is_template(path: &Path) -> bool {
+    let name = path.to_string_lossy();
+    name.ends_with(".slv") || name.ends_with(".erb")
+}
+
 pub fn run_fmt(paths: &[String], check: bool, stdin: bool) {
     use std::io::Read;
     if stdin {
@@ -1001,6 +1596,89 @@ pub fn run_fmt(paths: &[String], check: bool, stdin: bool) {
     }
 }
 
+pub fn run_codemod(script: &str, paths: &[String], check: bool) {
+    let script_path = Path::new(script);
+    let script_source = match fs::read_to_string(script_path) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Error: cannot read {}: {}", script, e);
+            process::exit(1);
+        }
+    };
+    let mut codemod = match solilang::codemod::Codemod::load(&script_source, Some(script_path)) {
+        Ok(codemod) => codemod,
+        Err(e) => {
+            eprintln!("Error in {}: {}", script, e);
+            process::exit(1);
+        }
+    };
+
+    let targets: Vec<PathBuf> = if paths.is_empty() {
+        vec![env::current_dir().unwrap_or_else(|_| PathBuf::from("."))]
+    } else {
+        paths.iter().map(PathBuf::from).collect()
+    };
+    let script_path = script_path.canonicalize().ok();
+    let mut files: Vec<PathBuf> = Vec::new();
+    for t in &targets {
+        if !t.exists() {
+            eprintln!("Error: Path '{}' does not exist", t.display());
+            process::exit(1);
+        }
+        if t.is_file() {
+            files.push(t.clone());
+        } else {
+            let mut found = test_runner::collect_test_files(t);
+            found.sort();
+            files.extend(found);
+        }
+    }
+    // Never rewrite the codemod itself.
+    files.retain(|file| file.canonicalize().ok() != script_path);
+
+    let mut changed = 0usize;
+    let mut errors = 0usize;
+    for file in &files {
+        let source = match fs::read_to_string(file) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("{}: error reading file: {}", file.display(), e);
+                errors += 1;
+                continue;
+            }
+        };
+        let rewritten = match codemod.apply(&source) {
+            Ok(Some(rewritten)) if rewritten != source => rewritten,
+            Ok(_) => continue,
+            Err(e) => {
+                eprintln!("{}: {}", file.display(), e);
+                errors += 1;
+                continue;
+            }
+        };
+        changed += 1;
+        if check {
+            println!("would rewrite: {}", file.display());
+            print_unified_diff(&source, &rewritten);
+        } else if let Err(e) = fs::write(file, &rewritten) {
+            eprintln!("{}: error writing: {}", file.display(), e);
+            errors += 1;
+        } else {
+            println!("rewrote: {}", file.display());
+        }
+    }
+
+    println!(
+        "{} of {} file(s) {}.",
+        changed,
+        files.len(),
+        if check { "would change" } else { "changed" }
+    );
+    if errors > 0 || (check && changed > 0) {
+        process::exit(1);
+    }
+}
+
 /// Print a minimal unified-style diff so `soli fmt --check` shows what
 /// would change (and on which lines), not just which files would change.
 /// Groups runs of consecutive differences into hunks with line numbers.
@@ -1121,8 +1799,15 @@ pub fn run_test(
     no_coverage: bool,
     show_uncovered: bool,
     fail_on_n1: bool,
+    contract: bool,
     browser: bool,
     headed: bool,
+    seed: Option<u64>,
+    vm: bool,
+    filter: Option<&str>,
+    tags: &[String],
+    exclude_tags: &[String],
+    fail_on_todo: bool,
 ) {
     test_runner::run_test(
         paths,
@@ -1132,8 +1817,15 @@ pub fn run_test(
         no_coverage,
         show_uncovered,
         fail_on_n1,
+        contract,
         browser,
         headed,
+        seed,
+        vm,
+        filter,
+        tags,
+        exclude_tags,
+        fail_on_todo,
     );
 }
 
@@ -1270,6 +1962,115 @@ pub fn run_remove(name: &str) {
     println!();
 }
 
+pub fn run_deps_check(fix: bool) {
+    use solilang::module::{deps_check, Package};
+
+    let toml_path = match Package::find(Path::new(".")) {
+        Some(p) => p,
+        None => {
+            eprintln!("No soli.toml found.");
+            process::exit(1);
+        }
+    };
+
+    let mut pkg = Package::load(&toml_path).unwrap_or_else(|e| {
+        eprintln!("Error: Failed to load soli.toml: {}", e);
+        process::exit(1);
+    });
+
+    let root = toml_path.parent().unwrap_or(Path::new("."));
+    let report = deps_check::check_dependencies(&pkg, root);
+
+    println!();
+    for name in &report.unused {
+        let action = if fix { "removed" } else { "unused" };
+        println!(
+            "  \x1b[33m{}\x1b[0m  dependency '{}' is never imported",
+            action, name
+        );
+    }
+    for import in &report.uncovered {
+        let file = import.file.strip_prefix(root).unwrap_or(&import.file);
+        println!(
+            "  \x1b[31mmissing\x1b[0m  {}:{}: import \"{}\" is not covered by any dependency",
+            file.display(),
+            import.line,
+            import.path
+        );
+    }
+
+    if fix && !report.unused.is_empty() {
+        if let Err(e) = deps_check::prune_dependencies(&mut pkg, &toml_path, &report.unused) {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    }
+
+    if report.is_clean() {
+        println!("  \x1b[32m\x1b[1m✓\x1b[0m All dependencies are used and every import is covered");
+        println!();
+        return;
+    }
+    println!();
+    if !report.uncovered.is_empty() || !fix {
+        process::exit(1);
+    }
+}
+
+/// `soli stubs gen`: write the `.sli` stub of a module's exports, next to the
+/// module unless `-o` says otherwise. A directory stands for its package's
+/// main module.
+pub fn run_stubs_gen(path: Option<&str>, output: Option<&str>, stdout: bool) {
+    use solilang::module::Package;
+    use solilang::types::stubs::StubFile;
+
+    let path = Path::new(path.unwrap_or("."));
+    let source_path = if path.is_dir() {
+        let toml_path = Package::find(path).unwrap_or_else(|| {
+            eprintln!("No soli.toml found in {}.", path.display());
+            process::exit(1);
+        });
+        let pkg = Package::load(&toml_path).unwrap_or_else(|e| {
+            eprintln!("Error: Failed to load soli.toml: {}", e);
+            process::exit(1);
+        });
+        toml_path.parent().unwrap_or(Path::new(".")).join(&pkg.main)
+    } else {
+        path.to_path_buf()
+    };
+
+    let source = fs::read_to_string(&source_path).unwrap_or_else(|e| {
+        eprintln!("Error: Failed to read {}: {}", source_path.display(), e);
+        process::exit(1);
+    });
+    let program = solilang::lexer::Scanner::new(&source)
+        .scan_tokens()
+        .map_err(solilang::error::SolilangError::from)
+        .and_then(|tokens| {
+            solilang::parser::Parser::new(tokens)
+                .parse()
+                .map_err(solilang::error::SolilangError::from)
+        })
+        .unwrap_or_else(|e| {
+            eprintln!("{}: {}", source_path.display(), e);
+            process::exit(1);
+        });
+    let stubs = StubFile::from_module(&program, &source).to_string();
+
+    if stdout {
+        print!("{}", stubs);
+        return;
+    }
+    let out = output
+        .map(PathBuf::from)
+        .unwrap_or_else(|| source_path.with_extension("sli"));
+    if let Err(e) = fs::write(&out, stubs) {
+        eprintln!("Error: Failed to write {}: {}", out.display(), e);
+        process::exit(1);
+    }
+    println!("Wrote {}", out.display());
+}
+
 pub fn run_install() {
     use solilang::module::{installer, lockfile::LockFile, Package};
 
@@ -1914,6 +2715,39 @@ pub fn run_db_migrate(action: &DbMigrateAction, folder: &str) {
                 }
             }
         }
+        DbMigrateAction::Autogenerate => {
+            use solilang::migration::autogenerate::{autogenerate_migration, Autogenerated};
+
+            match autogenerate_migration(app_path) {
+                Ok(Autogenerated::Baseline(path)) => {
+                    println!();
+                    println!(
+                        "  \x1b[32mRecorded schema snapshot:\x1b[0m {}",
+                        path.display()
+                    );
+                    println!("  Later runs generate migrations from changes to it.");
+                    println!();
+                }
+                Ok(Autogenerated::NoChanges) => {
+                    println!();
+                    println!("  No model changes since the last schema snapshot.");
+                    println!();
+                }
+                Ok(Autogenerated::Migration { path, changes }) => {
+                    println!();
+                    println!("  \x1b[32mCreated migration:\x1b[0m {}", path.display());
+                    println!(
+                        "  {} change(s) — review it, then run \x1b[36msoli db:migrate up\x1b[0m",
+                        changes.len()
+                    );
+                    println!();
+                }
+                Err(e) => {
+                    eprintln!("  \x1b[31mError:\x1b[0m {}", e);
+                    process::exit(1);
+                }
+            }
+        }
     }
 }
 
@@ -1999,6 +2833,53 @@ pub fn run_routes(folder: &str, grep: Option<&str>, json: bool) {
     }
 }
 
+/// `soli check-routes [folder]` — statically cross-reference the route table
+/// with the controllers it dispatches to and report dangling routes,
+/// unreachable actions and duplicate paths. Exits 1 when anything is found,
+/// so it can gate a deploy or CI run.
+pub fn run_check_routes(folder: &str, json: bool) {
+    use solilang::serve::route_check;
+
+    let check = match route_check::check_routes(Path::new(folder)) {
+        Ok(check) => check,
+        Err(e) => {
+            eprintln!("\x1b[31mError:\x1b[0m {}", e);
+            process::exit(1);
+        }
+    };
+
+    if json {
+        println!("{}", route_check::format_json(&check));
+    } else {
+        print!("{}", route_check::format_report(&check));
+    }
+    if !check.is_clean() {
+        process::exit(1);
+    }
+}
+
+/// `soli introspect [folder]` — the project's top-level symbols, the builtin
+/// signatures and the route table, as JSON with `--json` (for editor
+/// plugins) or as a count summary.
+pub fn run_introspect(folder: &str, json: bool) {
+    let introspection = match solilang::introspect::introspect(Path::new(folder)) {
+        Ok(introspection) => introspection,
+        Err(e) => {
+            eprintln!("\x1b[31mError:\x1b[0m {}", e);
+            process::exit(1);
+        }
+    };
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&introspection.to_json()).unwrap_or_default()
+        );
+    } else {
+        print!("{}", introspection.summary());
+    }
+}
+
 /// `soli graph build [folder]` — extract the project's code graph and store it
 /// in SolidB (nodes = files/classes/methods/routes/views, edges = imports/
 /// inherits/calls/renders/routes_to/relates), embedding node text so agents can
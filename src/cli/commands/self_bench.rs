@@ -0,0 +1,186 @@
+//! `soli self-bench` — time the engine itself on the bundled workloads.
+//!
+//! Runs every phase from `solilang::self_bench` (lex, parse, typecheck,
+//! compile, interpreter, vm) on every workload and prints nanoseconds per
+//! iteration. `--json` records a run — tagged with the version and build
+//! profile — and `--baseline FILE` compares against such a record;
+//! `--threshold PCT` then fails the run when any phase got slower by more
+//! than that, so a release can be checked against the previous one.
+
+use std::collections::HashMap;
+use std::fs;
+use std::process;
+use std::time::Duration;
+
+use serde_json::{json, Value as Json};
+use solilang::self_bench::{measure, Bench, Phase, PhaseResult, WORKLOADS};
+
+use super::bench::group_thousands;
+
+/// Measuring time per phase when `--time` isn't given.
+const DEFAULT_TIME_MS: u64 = 500;
+
+/// Everything `soli self-bench` needs.
+pub struct SelfBenchArgs<'a> {
+    /// Only these workloads (default: all).
+    pub workloads: &'a [String],
+    /// Only these phases (default: all).
+    pub phases: &'a [String],
+    /// Measuring time per phase, in milliseconds.
+    pub time_ms: Option<u64>,
+    pub json: bool,
+    pub baseline: Option<&'a str>,
+    /// Fail when a phase is this many percent slower than the baseline.
+    pub threshold: Option<f64>,
+}
+
+pub fn run(args: SelfBenchArgs<'_>) {
+    let workloads: Vec<_> = WORKLOADS
+        .iter()
+        .filter(|w| args.workloads.is_empty() || args.workloads.iter().any(|n| n == w.name))
+        .copied()
+        .collect();
+    let phases: Vec<Phase> = Phase::ALL
+        .into_iter()
+        .filter(|p| args.phases.is_empty() || args.phases.iter().any(|n| n == p.name()))
+        .collect();
+    if workloads.is_empty() || phases.is_empty() {
+        eprintln!(
+            "Nothing to run. Workloads: {}. Phases: {}.",
+            WORKLOADS
+                .iter()
+                .map(|w| w.name)
+                .collect::<Vec<_>>()
+                .join(", "),
+            Phase::ALL.map(Phase::name).join(", ")
+        );
+        process::exit(64);
+    }
+    let baseline = args.baseline.map(load_baseline).unwrap_or_default();
+    if args.threshold.is_some() && args.baseline.is_none() {
+        eprintln!("self-bench: --threshold needs --baseline");
+        process::exit(64);
+    }
+    let time = Duration::from_millis(args.time_ms.unwrap_or(DEFAULT_TIME_MS));
+    let profile = if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    };
+
+    if !args.json {
+        println!(
+            "soli {} self-bench ({} build)",
+            env!("CARGO_PKG_VERSION"),
+            profile
+        );
+        if profile == "debug" {
+            println!("  note: debug builds are much slower; compare release builds");
+        }
+    }
+    let mut records = Vec::new();
+    let mut regressions = Vec::new();
+    for workload in workloads {
+        let mut bench = match Bench::new(workload) {
+            Ok(bench) => bench,
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                process::exit(1);
+            }
+        };
+        for &phase in &phases {
+            let result = match measure(&mut bench, phase, time) {
+                Ok(result) => result,
+                Err(e) => {
+                    eprintln!("Error: {} {}: {}", workload.name, phase.name(), e);
+                    process::exit(1);
+                }
+            };
+            let change = baseline
+                .get(&(workload.name.to_string(), phase.name().to_string()))
+                .filter(|before| **before > 0.0)
+                .map(|before| (result.ns_per_iter - before) / before * 100.0);
+            if let (Some(change), Some(threshold)) = (change, args.threshold) {
+                if change > threshold {
+                    regressions.push(format!(
+                        "{} {} {:+.1}%",
+                        workload.name,
+                        phase.name(),
+                        change
+                    ));
+                }
+            }
+            if !args.json {
+                println!("{}", format_row(&result, change));
+            }
+            records.push(json!({
+                "workload": result.workload,
+                "phase": result.phase.name(),
+                "iterations": result.iterations,
+                "ns_per_iter": result.ns_per_iter,
+            }));
+        }
+    }
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "profile": profile,
+                "results": records,
+            }))
+            .unwrap_or_default()
+        );
+    }
+    if !regressions.is_empty() {
+        eprintln!(
+            "Regressions over {:.1}%: {}",
+            args.threshold.unwrap_or_default(),
+            regressions.join(", ")
+        );
+        process::exit(1);
+    }
+}
+
+/// `ns_per_iter` by `(workload, phase)` from an earlier `--json` run.
+fn load_baseline(path: &str) -> HashMap<(String, String), f64> {
+    let parsed = fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| serde_json::from_str::<Json>(&text).map_err(|e| e.to_string()));
+    let doc = match parsed {
+        Ok(doc) => doc,
+        Err(e) => {
+            eprintln!("Error: cannot read baseline {}: {}", path, e);
+            process::exit(1);
+        }
+    };
+    doc["results"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|row| {
+            Some((
+                (
+                    row["workload"].as_str()?.to_string(),
+                    row["phase"].as_str()?.to_string(),
+                ),
+                row["ns_per_iter"].as_f64()?,
+            ))
+        })
+        .collect()
+}
+
+fn format_row(result: &PhaseResult, change: Option<f64>) -> String {
+    let mut row = format!(
+        "  {:<10} {:<12} {:>14} ns/iter  {:>9} iters",
+        result.workload,
+        result.phase.name(),
+        group_thousands(result.ns_per_iter.round() as u64),
+        group_thousands(result.iterations)
+    );
+    if let Some(change) = change {
+        row.push_str(&format!("  {:+.1}%", change));
+    }
+    row
+}
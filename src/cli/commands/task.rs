@@ -0,0 +1,371 @@
+//! `soli task` — run the tasks declared in the project's `tasks.sl`.
+//!
+//! `tasks.sl` is found in the current directory or the nearest parent, and
+//! tasks run from that directory. A task runs after its `deps`, and tasks
+//! that don't depend on each other run in parallel, each on its own thread
+//! with its own interpreter. A task that declares both `inputs` and
+//! `outputs` is skipped while every output is newer than every input;
+//! `--force` runs it anyway. The first failure stops new tasks from
+//! starting, and `soli task` exits non-zero once the running ones finish.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Instant, SystemTime};
+
+use solilang::interpreter::builtins::task::TaskSpec;
+
+/// The file `soli task` looks for.
+const TASKS_FILE: &str = "tasks.sl";
+
+/// The task run when none is named, if `tasks.sl` declares it.
+const DEFAULT_TASK: &str = "default";
+
+/// Everything `soli task` needs.
+pub struct TaskArgs<'a> {
+    pub names: &'a [String],
+    pub list: bool,
+    pub force: bool,
+    /// At most this many tasks at once (default: one per CPU).
+    pub jobs: Option<usize>,
+}
+
+pub fn run(args: TaskArgs<'_>) {
+    let Some(path) = find_tasks_file() else {
+        eprintln!("Error: no {} in this directory or its parents", TASKS_FILE);
+        process::exit(1);
+    };
+    let root = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+    if let Err(e) = std::env::set_current_dir(&root) {
+        eprintln!("Error: cannot enter {}: {}", root.display(), e);
+        process::exit(1);
+    }
+    let path = PathBuf::from(TASKS_FILE);
+    let source = match fs::read_to_string(&path) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("Error: cannot read {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    };
+    let specs = match solilang::load_tasks(&source, Some(&path)) {
+        Ok(specs) => specs,
+        Err(e) => {
+            eprintln!("Error in {}: {}", TASKS_FILE, e);
+            process::exit(1);
+        }
+    };
+
+    if args.list {
+        print!("{}", format_list(&specs));
+        return;
+    }
+    let targets: Vec<String> = if !args.names.is_empty() {
+        args.names.to_vec()
+    } else if specs.iter().any(|spec| spec.name == DEFAULT_TASK) {
+        vec![DEFAULT_TASK.to_string()]
+    } else {
+        eprintln!("No task given and no '{}' task. Tasks:", DEFAULT_TASK);
+        eprint!("{}", format_list(&specs));
+        process::exit(64);
+    };
+    let order = match plan(&specs, &targets) {
+        Ok(order) => order,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            process::exit(1);
+        }
+    };
+
+    let jobs = args.jobs.unwrap_or_else(|| {
+        thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    });
+    if !execute(&specs, order, &source, jobs, args.force) {
+        process::exit(1);
+    }
+}
+
+/// `tasks.sl` in the current directory or the nearest parent that has one.
+fn find_tasks_file() -> Option<PathBuf> {
+    let mut dir = std::env::current_dir().ok()?;
+    loop {
+        let candidate = dir.join(TASKS_FILE);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if !dir.pop() {
+            return None;
+        }
+    }
+}
+
+/// The tasks needed for `targets`, each after its dependencies.
+fn plan(specs: &[TaskSpec], targets: &[String]) -> Result<Vec<String>, String> {
+    let mut by_name = HashMap::new();
+    for spec in specs {
+        if by_name.insert(spec.name.as_str(), spec).is_some() {
+            return Err(format!("task '{}' is declared twice", spec.name));
+        }
+    }
+
+    fn visit<'a>(
+        name: &'a str,
+        by_name: &HashMap<&'a str, &'a TaskSpec>,
+        path: &mut Vec<&'a str>,
+        order: &mut Vec<String>,
+    ) -> Result<(), String> {
+        if order.iter().any(|done| done == name) {
+            return Ok(());
+        }
+        if let Some(start) = path.iter().position(|open| *open == name) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(name);
+            return Err(format!("dependency cycle: {}", cycle.join(" -> ")));
+        }
+        let spec = by_name[name];
+        path.push(name);
+        for dep in &spec.deps {
+            if !by_name.contains_key(dep.as_str()) {
+                return Err(format!("task '{}' depends on unknown task '{}'", name, dep));
+            }
+            visit(dep, by_name, path, order)?;
+        }
+        path.pop();
+        order.push(name.to_string());
+        Ok(())
+    }
+
+    let mut order = Vec::new();
+    for target in targets {
+        let Some((name, _)) = by_name.get_key_value(target.as_str()) else {
+            return Err(format!("no task named '{}' in {}", target, TASKS_FILE));
+        };
+        visit(name, &by_name, &mut Vec::new(), &mut order)?;
+    }
+    Ok(order)
+}
+
+/// Run the planned tasks, up to `jobs` at a time. Returns whether they all
+/// succeeded.
+fn execute(specs: &[TaskSpec], order: Vec<String>, source: &str, jobs: usize, force: bool) -> bool {
+    let by_name: HashMap<&str, &TaskSpec> = specs
+        .iter()
+        .map(|spec| (spec.name.as_str(), spec))
+        .collect();
+    let stack_size = solilang::interpreter::executor::stack_size_for_depth(
+        solilang::interpreter::executor::max_call_depth_from_env(),
+    );
+    let (sender, receiver) = mpsc::channel();
+    let mut pending = order;
+    let mut done: HashSet<String> = HashSet::new();
+    let mut running = 0usize;
+    let mut failed = false;
+
+    loop {
+        // Start every task whose dependencies are done, skipping the fresh
+        // ones — which may in turn make others ready.
+        let mut progressed = true;
+        while progressed && !failed {
+            progressed = false;
+            let mut i = 0;
+            while i < pending.len() && running < jobs.max(1) {
+                let spec = by_name[pending[i].as_str()];
+                if !spec.deps.iter().all(|dep| done.contains(dep)) {
+                    i += 1;
+                    continue;
+                }
+                let name = pending.remove(i);
+                progressed = true;
+                if !force && up_to_date(spec) {
+                    println!("==> {} (up to date)", name);
+                    done.insert(name);
+                    continue;
+                }
+                println!("==> {}", name);
+                let sender = sender.clone();
+                let source = source.to_string();
+                let spawned = thread::Builder::new()
+                    .name(format!("task-{}", name))
+                    .stack_size(stack_size)
+                    .spawn(move || {
+                        let start = Instant::now();
+                        let outcome =
+                            solilang::run_task(&source, Some(Path::new(TASKS_FILE)), &name)
+                                .map(|()| start.elapsed())
+                                .map_err(|e| e.to_string());
+                        let _ = sender.send((name, outcome));
+                    });
+                match spawned {
+                    Ok(_) => running += 1,
+                    Err(e) => {
+                        eprintln!("Error: cannot start a task thread: {}", e);
+                        failed = true;
+                        break;
+                    }
+                }
+            }
+        }
+
+        if running == 0 {
+            break;
+        }
+        let Ok((name, outcome)) = receiver.recv() else {
+            break;
+        };
+        running -= 1;
+        match outcome {
+            Ok(elapsed) => {
+                println!("    {} done in {:.2?}", name, elapsed);
+                done.insert(name);
+            }
+            Err(e) => {
+                eprintln!("Error: task '{}' failed: {}", name, e);
+                failed = true;
+            }
+        }
+    }
+
+    if !failed && !pending.is_empty() {
+        eprintln!("Error: tasks never became ready: {}", pending.join(", "));
+        failed = true;
+    }
+    !failed
+}
+
+/// Whether every output exists and is newer than every input. Tasks that
+/// don't declare both always run.
+fn up_to_date(spec: &TaskSpec) -> bool {
+    if spec.inputs.is_empty() || spec.outputs.is_empty() {
+        return false;
+    }
+    let mut oldest_output: Option<SystemTime> = None;
+    for pattern in &spec.outputs {
+        let times = modified_times(pattern);
+        // An output pattern that matches nothing hasn't been built yet.
+        let Some(oldest) = times.iter().min() else {
+            return false;
+        };
+        oldest_output = Some(oldest_output.map_or(*oldest, |t| t.min(*oldest)));
+    }
+    let newest_input = spec
+        .inputs
+        .iter()
+        .flat_map(|pattern| modified_times(pattern))
+        .max();
+    match (newest_input, oldest_output) {
+        (Some(input), Some(output)) => input <= output,
+        (None, Some(_)) => true,
+        _ => false,
+    }
+}
+
+/// Modification times of the files matching a glob pattern.
+fn modified_times(pattern: &str) -> Vec<SystemTime> {
+    let Ok(paths) = glob::glob(pattern) else {
+        return Vec::new();
+    };
+    paths
+        .flatten()
+        .filter_map(|path| fs::metadata(path).ok())
+        .filter(|meta| meta.is_file())
+        .filter_map(|meta| meta.modified().ok())
+        .collect()
+}
+
+/// The `--list` table: every task with its description and dependencies.
+fn format_list(specs: &[TaskSpec]) -> String {
+    let width = specs.iter().map(|spec| spec.name.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for spec in specs {
+        let mut line = format!("  {:<width$}", spec.name, width = width);
+        if let Some(desc) = &spec.desc {
+            line.push_str("  ");
+            line.push_str(desc);
+        }
+        if !spec.deps.is_empty() {
+            line.push_str(&format!("  [deps: {}]", spec.deps.join(", ")));
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    if specs.is_empty() {
+        out.push_str("  (no tasks)\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn spec(name: &str, deps: &[&str]) -> TaskSpec {
+        TaskSpec {
+            name: name.to_string(),
+            deps: deps.iter().map(|dep| dep.to_string()).collect(),
+            ..TaskSpec::default()
+        }
+    }
+
+    #[test]
+    fn plan_orders_dependencies_first_and_rejects_cycles() {
+        let specs = vec![
+            spec("build", &["assets", "migrate"]),
+            spec("assets", &["deps"]),
+            spec("migrate", &["deps"]),
+            spec("deps", &[]),
+            spec("lint", &[]),
+        ];
+        let order = plan(&specs, &["build".to_string()]).unwrap();
+        assert_eq!(order, ["deps", "assets", "migrate", "build"]);
+
+        let cyclic = vec![spec("a", &["b"]), spec("b", &["c"]), spec("c", &["a"])];
+        assert_eq!(
+            plan(&cyclic, &["a".to_string()]).unwrap_err(),
+            "dependency cycle: a -> b -> c -> a"
+        );
+        let unknown = vec![spec("a", &["nope"])];
+        assert!(plan(&unknown, &["a".to_string()])
+            .unwrap_err()
+            .contains("'nope'"));
+        assert!(plan(&specs, &["deploy".to_string()]).is_err());
+    }
+
+    #[test]
+    fn tasks_are_up_to_date_only_when_outputs_are_newer_than_inputs() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("style.scss");
+        let output = dir.path().join("style.css");
+        fs::write(&input, "a {}").unwrap();
+        let task = TaskSpec {
+            name: "css".to_string(),
+            inputs: vec![dir.path().join("*.scss").display().to_string()],
+            outputs: vec![output.display().to_string()],
+            ..TaskSpec::default()
+        };
+        assert!(!up_to_date(&task), "output not built yet");
+
+        fs::write(&output, "a {}").unwrap();
+        let now = SystemTime::now();
+        let set = |path: &Path, time: SystemTime| {
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(time)
+                .unwrap()
+        };
+        set(&input, now - Duration::from_secs(60));
+        set(&output, now);
+        assert!(up_to_date(&task));
+
+        set(&input, now + Duration::from_secs(60));
+        assert!(!up_to_date(&task), "input changed since the build");
+        assert!(!up_to_date(&spec("no-files", &[])));
+    }
+}
@@ -7,13 +7,22 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use solilang::coverage::data::AggregatedCoverage;
-use solilang::coverage::tracker::{clear_global_coverage_tracker, set_global_coverage_tracker};
-use solilang::coverage::{CoverageConfig, CoverageReporter, CoverageTracker, OutputFormat};
+use solilang::coverage::profile::{worker_profile_path, PROFILES_DIR};
+use solilang::coverage::tracker::{
+    clear_global_coverage_tracker, set_global_coverage_tracker, take_test_coverage,
+};
+use solilang::coverage::{
+    CoverageConfig, CoverageProfile, CoverageReporter, CoverageTracker, OutputFormat,
+};
+use solilang::interpreter::builtins::test_dsl::{self, TestFilter};
 
 struct ProgressState {
     passed: usize,
     failed: usize,
     total_assertions: i64,
+    /// Tests, not files: `xit`/`skip()` and `todo`/`pending()`.
+    skipped: usize,
+    pending: usize,
 }
 
 #[derive(Clone)]
@@ -286,8 +295,15 @@ pub fn run_test(
     no_coverage: bool,
     show_uncovered: bool,
     fail_on_n1: bool,
+    contract: bool,
     browser: bool,
     headed: bool,
+    seed: Option<u64>,
+    vm: bool,
+    filter: Option<&str>,
+    tags: &[String],
+    exclude_tags: &[String],
+    fail_on_todo: bool,
 ) {
     let test_paths: Vec<PathBuf> = if paths.is_empty() {
         vec![std::env::current_dir()
@@ -329,6 +345,13 @@ pub fn run_test(
         solilang::interpreter::builtins::request_helpers::enable_fail_on_n1();
     }
 
+    // `--contract`: same process-global shape. The servers spawned below get
+    // `SOLI_OPENAPI=1` so each serves the document the responses are checked
+    // against.
+    if contract {
+        solilang::interpreter::builtins::api_contract::enable_contract_mode();
+    }
+
     // `--browser`: fail now rather than at the first `visit()`. Discovering
     // there is no browser thirty seconds into a suite, from inside a worker
     // thread, is a much worse way to learn it.
@@ -342,6 +365,24 @@ pub fn run_test(
             solilang::interpreter::builtins::browser::enable_headed();
         }
     }
+
+    // `--seed N`: seed the randomness builtins and shuffle the run order.
+    // Process-global for the same reason as `--fail-on-n1`; each worker
+    // thread reseeds its own RNG per file and per test from it.
+    if let Some(seed) = seed {
+        solilang::interpreter::builtins::rng::set_test_seed(seed);
+    }
+    // `--filter`/`--tag`/`--exclude-tag`: process-global too, read by the
+    // suite runners in every worker.
+    if filter.is_some() || !tags.is_empty() || !exclude_tags.is_empty() {
+        match TestFilter::new(filter, tags.to_vec(), exclude_tags.to_vec()) {
+            Ok(test_filter) => test_dsl::set_test_filter(test_filter),
+            Err(message) => {
+                eprintln!("{}", message);
+                process::exit(64);
+            }
+        }
+    }
     let app_dir = resolve_app_dir(&test_path, test_path.is_file());
 
     if let Err(msg) = solilang::module::enforce_min_soli_version(&app_dir) {
@@ -358,6 +399,10 @@ pub fn run_test(
         process::exit(1);
     }
     solilang::serve::env_loader::load_env_files(&app_dir);
+    if let Err(report) = solilang::serve::config_schema::validate(&app_dir) {
+        eprintln!("{}", report);
+        process::exit(1);
+    }
 
     solilang::interpreter::builtins::model::init_db_config();
 
@@ -461,7 +506,8 @@ pub fn run_test(
         test_path.clone()
     };
 
-    let enable_coverage = !no_coverage;
+    // Coverage hooks live in the interpreter; a `--vm` run has none to record.
+    let enable_coverage = !no_coverage && !vm;
     // Console is always present; every other accepted name adds its format.
     let output_formats = {
         let mut formats = vec![OutputFormat::Console];
@@ -470,7 +516,8 @@ pub fn run_test(
                 "console" => continue, // already added
                 "html" => OutputFormat::Html,
                 "json" => OutputFormat::Json,
-                "xml" => OutputFormat::Xml,
+                "xml" | "cobertura" => OutputFormat::Xml,
+                "lcov" => OutputFormat::Lcov,
                 other => {
                     eprintln!("Unknown --coverage format '{}'.", other);
                     process::exit(64);
@@ -482,10 +529,13 @@ pub fn run_test(
         }
         formats
     };
+    let coverage_dir = PathBuf::from("coverage");
     let tracker = if enable_coverage {
+        // Profiles left behind by an interrupted run would be merged in.
+        let _ = fs::remove_dir_all(coverage_dir.join(PROFILES_DIR));
         let config = CoverageConfig {
             enabled: true,
-            output_dir: PathBuf::from("coverage"),
+            output_dir: coverage_dir.clone(),
             formats: output_formats.clone(),
             threshold: coverage_min.or(Some(80.0)),
             exclude_patterns: Vec::new(),
@@ -533,6 +583,9 @@ pub fn run_test(
         test_files.len(),
         num_workers
     );
+    if let Some(seed) = seed {
+        println!("Randomized with seed {}", seed);
+    }
     println!();
 
     let worker_databases = worker_database_names(num_workers, &base_test_database());
@@ -668,6 +721,9 @@ pub fn run_test(
                         .map(std::process::Stdio::from)
                         .unwrap_or(std::process::Stdio::null()),
                 );
+            if contract {
+                cmd.env("SOLI_OPENAPI", "1");
+            }
             if enable_coverage {
                 cmd.env("SOLI_COVERAGE_ENABLED", "1");
                 if let Some(ref token) = coverage_token {
@@ -734,8 +790,10 @@ pub fn run_test(
         passed: 0,
         failed: 0,
         total_assertions: 0,
+        skipped: 0,
+        pending: 0,
     }));
-    type TestResult = (PathBuf, bool, String, Duration, i64);
+    type TestResult = (PathBuf, bool, String, Duration, i64, test_dsl::TestOutcomes);
     let all_results_shared: Arc<Mutex<Vec<TestResult>>> = Arc::new(Mutex::new(Vec::new()));
 
     // Per-worker live state for the grid display. Each worker writes only
@@ -769,6 +827,8 @@ pub fn run_test(
                         passed: p.passed,
                         failed: p.failed,
                         total_assertions: p.total_assertions,
+                        skipped: p.skipped,
+                        pending: p.pending,
                     }
                 };
                 let slot_snapshot: Vec<WorkerSlot> =
@@ -813,14 +873,31 @@ pub fn run_test(
     // Shared work queue, ordered largest-first (LPT scheduling). Workers pop
     // one file at a time so a heavy file can't trap a chunk while peers
     // idle — replaces the old static `test_files.chunks(N)` partition.
+    //
+    // Under `--seed` the queue is a seeded shuffle instead. That gives up LPT
+    // balancing, but the point of a seeded run is that it replays: with one
+    // worker the file order is exactly reproduced, and with several each
+    // file's tests still run in their seeded order.
     let work_queue: Arc<Mutex<Vec<PathBuf>>> = {
         let mut files = test_files.clone();
-        files.sort_by_key(|p| std::cmp::Reverse(fs::metadata(p).map(|m| m.len()).unwrap_or(0)));
-        // Workers pop from the end, so reverse so the largest is popped first.
+        if let Some(mut rng) = solilang::interpreter::builtins::rng::derived_rng(&["files"]) {
+            use rand::seq::SliceRandom;
+            files.sort();
+            files.shuffle(&mut rng);
+        } else {
+            files.sort_by_key(|p| std::cmp::Reverse(fs::metadata(p).map(|m| m.len()).unwrap_or(0)));
+        }
+        // Workers pop from the end, so reverse so the first entry is popped
+        // first (the largest file, in the LPT order).
         files.reverse();
         Arc::new(Mutex::new(files))
     };
 
+    // Workers run specs on threads sized for the interpreter's call-depth
+    // limit, so deep recursion fails with a Soli error, not a stack overflow.
+    let stack_size = solilang::interpreter::executor::stack_size_for_depth(
+        solilang::interpreter::executor::max_call_depth_from_env(),
+    );
     let suite_start = std::time::Instant::now();
     std::thread::scope(|s| {
         let mut handles = Vec::new();
@@ -833,127 +910,181 @@ pub fn run_test(
             let rt_handle = shared_rt_handle.clone();
             let env = env.clone();
             let slots = worker_slots.clone();
-
-            handles.push(s.spawn(move || {
-                if let Some(handle) = rt_handle {
-                    solilang::serve::set_tokio_handle(handle);
-                }
-                solilang::interpreter::builtins::model::db_config::set_database_override(
-                    env.database.clone(),
-                );
-                if let Some(port) = env.port {
-                    solilang::interpreter::builtins::test_server::set_thread_test_server_port(port);
-                }
-                loop {
-                    let file = {
-                        let mut q = queue.lock().unwrap();
-                        match q.pop() {
-                            Some(f) => f,
-                            None => break,
-                        }
-                    };
-                    // Mark this worker as running `file`. Strip the `_test.sl`
-                    // suffix when present so the cell shows what's under
-                    // test, not the suffix.
-                    let display_name = file
-                        .file_stem()
-                        .map(|s| s.to_string_lossy().to_string())
-                        .unwrap_or_else(|| file.to_string_lossy().to_string());
-                    let display_name = display_name
-                        .strip_suffix("_test")
-                        .map(|s| s.to_string())
-                        .unwrap_or(display_name);
-                    {
-                        let mut slot = slots[worker_idx].lock().unwrap();
-                        slot.current_file = Some(display_name);
-                        slot.started_at = Some(std::time::Instant::now());
+            let app_dir = app_dir.clone();
+            let coverage_dir = &coverage_dir;
+
+            let spawned = std::thread::Builder::new()
+                .stack_size(stack_size)
+                .spawn_scoped(s, move || {
+                    if let Some(handle) = rt_handle {
+                        solilang::serve::set_tokio_handle(handle);
                     }
-                    let start = std::time::Instant::now();
-                    let result = fs::read_to_string(&file).map_err(|e| e.to_string());
-
-                    let (passed, error, assertions) = match result {
-                        Ok(source) => {
-                            let is_integration = file
-                                .file_name()
-                                .map(|n| n.to_string_lossy().contains("integration"))
-                                .unwrap_or(false);
-                            let preamble_slice: &[(PathBuf, String)] =
-                                if is_integration { &[] } else { &preamble_files };
-                            if let Some(ref tracker) = tracker_clone {
-                                let tracker_guard = tracker.lock().unwrap();
-                                tracker_guard.start_test(file.to_string_lossy().as_ref());
+                    solilang::interpreter::builtins::model::db_config::set_database_override(
+                        env.database.clone(),
+                    );
+                    if let Some(port) = env.port {
+                        solilang::interpreter::builtins::test_server::set_thread_test_server_port(
+                            port,
+                        );
+                    }
+                    // Everything this worker's test files record, kept to itself
+                    // until the suite is over (see `merge_worker_profiles`).
+                    let mut profile = CoverageProfile::new();
+                    loop {
+                        let file = {
+                            let mut q = queue.lock().unwrap();
+                            match q.pop() {
+                                Some(f) => f,
+                                None => break,
                             }
-                            let tracker_for_run = tracker_clone.clone();
-                            // Per-thread stdout capture — fd-level redirection
-                            // (e.g. `gag::BufferRedirect`) deadlocks under
-                            // `--jobs > 1` because every worker fights over
-                            // the same process stdout fd and the OS pipe
-                            // backing the redirect fills.
-                            let print_guard =
-                                solilang::interpreter::builtins::StdoutCaptureGuard::start();
-                            let panic_result =
-                                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                                    solilang::run_with_path_and_coverage(
-                                        &source,
-                                        Some(&file),
+                        };
+                        // Mark this worker as running `file`. Strip the `_test.sl`
+                        // suffix when present so the cell shows what's under
+                        // test, not the suffix.
+                        let display_name = file
+                            .file_stem()
+                            .map(|s| s.to_string_lossy().to_string())
+                            .unwrap_or_else(|| file.to_string_lossy().to_string());
+                        let display_name = display_name
+                            .strip_suffix("_test")
+                            .map(|s| s.to_string())
+                            .unwrap_or(display_name);
+                        {
+                            let mut slot = slots[worker_idx].lock().unwrap();
+                            slot.current_file = Some(display_name);
+                            slot.started_at = Some(std::time::Instant::now());
+                        }
+                        // Keyed on the app-relative path so the same seed gives
+                        // the same values on any checkout location.
+                        solilang::interpreter::builtins::rng::reseed(&[&file
+                            .strip_prefix(&app_dir)
+                            .unwrap_or(&file)
+                            .to_string_lossy()]);
+                        let start = std::time::Instant::now();
+                        let result = fs::read_to_string(&file).map_err(|e| e.to_string());
+
+                        let (passed, error, assertions) = match result {
+                            Ok(source) => {
+                                let is_integration = file
+                                    .file_name()
+                                    .map(|n| n.to_string_lossy().contains("integration"))
+                                    .unwrap_or(false);
+                                let preamble_slice: &[(PathBuf, String)] =
+                                    if is_integration { &[] } else { &preamble_files };
+                                if let Some(ref tracker) = tracker_clone {
+                                    let tracker_guard = tracker.lock().unwrap();
+                                    tracker_guard.start_test(file.to_string_lossy().as_ref());
+                                }
+                                let tracker_for_run = tracker_clone.clone();
+                                // Per-thread stdout capture — fd-level redirection
+                                // (e.g. `gag::BufferRedirect`) deadlocks under
+                                // `--jobs > 1` because every worker fights over
+                                // the same process stdout fd and the OS pipe
+                                // backing the redirect fills.
+                                let print_guard =
+                                    solilang::interpreter::builtins::StdoutCaptureGuard::start();
+                                let panic_result =
+                                    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                                        if vm {
+                                            solilang::run_tests_vm(
+                                                &source,
+                                                Some(&file),
+                                                preamble_slice,
+                                            )
+                                        } else {
+                                            solilang::run_with_path_and_coverage(
+                                                &source,
+                                                Some(&file),
+                                                false,
+                                                tracker_for_run.as_ref(),
+                                                Some(&file),
+                                                preamble_slice,
+                                            )
+                                        }
+                                    }));
+                                let _ = print_guard.finish();
+                                if let Some(test_cov) = take_test_coverage() {
+                                    profile.add_test_coverage(&test_cov);
+                                }
+                                match panic_result {
+                                    Ok((count, Ok(()))) => (true, String::new(), count),
+                                    Ok((count, Err(e))) => (false, e.to_string(), count),
+                                    Err(_) => (
                                         false,
-                                        tracker_for_run.as_ref(),
-                                        Some(&file),
-                                        preamble_slice,
-                                    )
-                                }));
-                            let _ = print_guard.finish();
-                            if let Some(ref tracker) = tracker_clone {
-                                let mut tracker_guard = tracker.lock().unwrap();
-                                tracker_guard.end_test();
+                                        "Test panicked (may require async runtime)".to_string(),
+                                        0,
+                                    ),
+                                }
                             }
-                            match panic_result {
-                                Ok((count, Ok(()))) => (true, String::new(), count),
-                                Ok((count, Err(e))) => (false, e.to_string(), count),
-                                Err(_) => (
-                                    false,
-                                    "Test panicked (may require async runtime)".to_string(),
-                                    0,
-                                ),
+                            Err(e) => (false, e, 0),
+                        };
+
+                        let duration = start.elapsed();
+                        let outcomes = test_dsl::take_test_outcomes();
+
+                        {
+                            let mut p = progress.lock().unwrap();
+                            if passed {
+                                p.passed += 1;
+                            } else {
+                                p.failed += 1;
                             }
+                            p.total_assertions += assertions;
+                            p.skipped += outcomes.skipped.len();
+                            p.pending += outcomes.pending.len();
                         }
-                        Err(e) => (false, e, 0),
-                    };
 
-                    let duration = start.elapsed();
-
-                    {
-                        let mut p = progress.lock().unwrap();
-                        if passed {
-                            p.passed += 1;
-                        } else {
-                            p.failed += 1;
+                        {
+                            let mut slot = slots[worker_idx].lock().unwrap();
+                            slot.current_file = None;
+                            slot.started_at = None;
+                            slot.files_done += 1;
+                            if !passed {
+                                slot.files_failed += 1;
+                            }
+                            slot.last_status = if passed { '✓' } else { '✗' };
                         }
-                        p.total_assertions += assertions;
+
+                        all_results_shared
+                            .lock()
+                            .unwrap()
+                            .push((file, passed, error, duration, assertions, outcomes));
                     }
 
-                    {
-                        let mut slot = slots[worker_idx].lock().unwrap();
-                        slot.current_file = None;
-                        slot.started_at = None;
-                        slot.files_done += 1;
-                        if !passed {
-                            slot.files_failed += 1;
+                    if tracker_clone.is_some() {
+                        let path = worker_profile_path(coverage_dir, worker_idx);
+                        if let Err(e) = profile.write(&path) {
+                            eprintln!(
+                            "\x1b[33mWarning:\x1b[0m could not write the coverage profile {}: {}",
+                            path.display(),
+                            e
+                        );
                         }
-                        slot.last_status = if passed { '✓' } else { '✗' };
                     }
 
-                    all_results_shared
-                        .lock()
-                        .unwrap()
-                        .push((file, passed, error, duration, assertions));
-                }
-
-                // This worker is done: shut its browser down here rather than
-                // leaving it to thread teardown, so the browsers are gone
-                // before the suite prints its summary instead of during it.
-                solilang::interpreter::builtins::browser::close_browser();
-            }));
+                    // This worker is done: shut its browser down here rather than
+                    // leaving it to thread teardown, so the browsers are gone
+                    // before the suite prints its summary instead of during it.
+                    solilang::interpreter::builtins::browser::close_browser();
+                });
+            match spawned {
+                Ok(handle) => handles.push(handle),
+                // The workers share one queue, so the ones that started
+                // still run every file.
+                Err(e) => eprintln!(
+                    "Warning: cannot start test worker {} with a {} MiB stack: {}",
+                    worker_idx,
+                    stack_size / (1024 * 1024),
+                    e
+                ),
+            }
+        }
+        if handles.is_empty() {
+            eprintln!(
+                "Error: no test worker could start. Lower {} to need a smaller stack.",
+                solilang::interpreter::executor::MAX_CALL_DEPTH_ENV
+            );
+            process::exit(1);
         }
 
         for handle in handles {
@@ -961,21 +1092,28 @@ pub fn run_test(
         }
     });
     let suite_duration = suite_start.elapsed();
+    if let Some(ref tracker) = tracker {
+        merge_worker_profiles(tracker, &coverage_dir, num_workers);
+    }
 
     stop_animation.store(true, Ordering::Relaxed);
     if let Some(handle) = anim_handle {
         handle.join().unwrap();
     }
 
-    let (passed, failed, total_assertions_val) = {
+    let (passed, failed, total_assertions_val, skipped, pending) = {
         let p = progress.lock().unwrap();
-        (p.passed, p.failed, p.total_assertions)
+        (p.passed, p.failed, p.total_assertions, p.skipped, p.pending)
     };
     let final_state = ProgressState {
         passed,
         failed,
         total_assertions: total_assertions_val,
+        skipped,
+        pending,
     };
+    // `--fail-on-todo`: a test left pending fails the run like a failure.
+    let todo_fails = fail_on_todo && pending > 0;
     let final_icon = if failed > 0 { '✗' } else { '✓' };
     if animate {
         // Final repaint over the animator's last frame. We rewind by the
@@ -1010,7 +1148,7 @@ pub fn run_test(
     // some filenames are longer than the default 40-char padding.
     let display_rows: Vec<String> = all_results
         .iter()
-        .map(|(path, _, _, _, _)| {
+        .map(|(path, _, _, _, _, _)| {
             let relative_to_test_dir = path.strip_prefix(&test_dir).unwrap_or(path);
             let parent_str = relative_to_test_dir
                 .parent()
@@ -1032,7 +1170,7 @@ pub fn run_test(
         .max(40);
 
     let mut current_dir: Option<PathBuf> = None;
-    for ((path, passed_test, error, duration, assertions), display_path) in
+    for ((path, passed_test, error, duration, assertions, outcomes), display_path) in
         all_results.iter().zip(display_rows.iter())
     {
         let parent = path.parent().unwrap_or(path).to_path_buf();
@@ -1062,6 +1200,7 @@ pub fn run_test(
                 duration_str,
                 assertions
             );
+            print_not_run(outcomes);
         } else {
             println!(
                 "  {}{} {:>8} {:>6} ✗",
@@ -1118,21 +1257,46 @@ pub fn run_test(
                 }
                 println!("  └─");
             }
+            print_not_run(outcomes);
             println!();
         }
         std::io::stdout().flush().unwrap();
     }
 
     println!();
-    println!("{}", if failed > 0 { "❌ " } else { "✓ " });
+    println!(
+        "{}",
+        if failed > 0 || todo_fails {
+            "❌ "
+        } else {
+            "✓ "
+        }
+    );
     println!(
         "  {} passed, {} failed ({} total)",
         passed,
         failed,
         passed + failed
     );
+    if skipped > 0 || pending > 0 {
+        println!("  {} skipped, {} pending", skipped, pending);
+    }
+    if todo_fails {
+        println!(
+            "  --fail-on-todo: {} pending test{}",
+            pending,
+            if pending == 1 { "" } else { "s" }
+        );
+    }
     println!("  {} assertions", total_assertions_val);
     println!("  Time: {}", format_duration(suite_duration));
+    if let Some(seed) = seed {
+        println!("  Seed: {}", seed);
+        if failed > 0 {
+            println!();
+            println!("  Rerun this exact order with: soli test --seed {}", seed);
+        }
+    }
 
     if enable_coverage {
         // Fetch coverage from the subprocess test server (controllers,
@@ -1192,9 +1356,8 @@ pub fn run_test(
                             continue;
                         }
                     };
-                    let files = &files;
-                    let mut guard = tracker_rc.lock().unwrap();
-                    for f in files {
+                    let mut profile = CoverageProfile::new();
+                    for f in &files {
                         let path = match f.get("path").and_then(|v| v.as_str()) {
                             Some(p) => PathBuf::from(p),
                             None => continue,
@@ -1205,13 +1368,12 @@ pub fn run_test(
                                     let line =
                                         arr.first().and_then(|v| v.as_u64()).unwrap_or(0) as usize;
                                     let count = arr.get(1).and_then(|v| v.as_u64()).unwrap_or(0);
-                                    for _ in 0..count {
-                                        guard.record_line_hit_to_global(&path, line);
-                                    }
+                                    profile.record_line(&path, line, count as u32);
                                 }
                             }
                         }
                     }
+                    tracker_rc.lock().unwrap().merge_profile(&profile);
                 }
             }
         }
@@ -1242,7 +1404,7 @@ pub fn run_test(
             } else {
                 let config = CoverageConfig {
                     enabled: true,
-                    output_dir: PathBuf::from("coverage"),
+                    output_dir: coverage_dir.clone(),
                     formats: output_formats.clone(),
                     threshold: coverage_min.or(Some(80.0)),
                     exclude_patterns: Vec::new(),
@@ -1264,6 +1426,9 @@ pub fn run_test(
                         OutputFormat::Xml => {
                             println!("  Cobertura XML report: coverage/cobertura.xml");
                         }
+                        OutputFormat::Lcov => {
+                            println!("  LCOV coverage report: coverage/lcov.info");
+                        }
                         OutputFormat::Console => {}
                     }
                 }
@@ -1298,11 +1463,21 @@ pub fn run_test(
 
     drop(test_server_children);
 
-    if failed > 0 {
+    if failed > 0 || todo_fails {
         process::exit(1);
     }
 }
 
+/// List the tests of a file that were skipped or left pending, under its row.
+fn print_not_run(outcomes: &test_dsl::TestOutcomes) {
+    for name in &outcomes.skipped {
+        println!("     \x1b[33m○ skipped\x1b[0m {}", name);
+    }
+    for name in &outcomes.pending {
+        println!("     \x1b[36m… pending\x1b[0m {}", name);
+    }
+}
+
 fn base_test_database() -> String {
     std::env::var("SOLIDB_DATABASE").unwrap_or_else(|_| "default".to_string())
 }
@@ -1787,7 +1962,10 @@ pub fn collect_test_files(dir: &Path) -> Vec<PathBuf> {
     if let Ok(entries) = fs::read_dir(dir) {
         for entry in entries.flatten() {
             let path = entry.path();
-            if path.is_file() && path.extension().map(|e| e == "sl").unwrap_or(false) {
+            if path.is_file()
+                && path.extension().map(|e| e == "sl").unwrap_or(false)
+                && !solilang::types::stubs::is_declaration_file(&path)
+            {
                 files.push(path);
             } else if path.is_dir() {
                 let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
@@ -1835,8 +2013,9 @@ fn check_browser_available() -> Result<(), String> {
     ))
 }
 
-/// Like `collect_test_files`, but also picks up `.slv` view templates. Used by
+/// Like `collect_test_files`, but also picks up `.slv`/`.erb` view templates. Used by
 /// `soli lint` so that directory linting covers views, not just `.sl` sources.
+/// Declaration files (`.d.sl`) hold no code and are skipped.
 pub fn collect_lint_files(dir: &Path) -> Vec<PathBuf> {
     let mut files = Vec::new();
 
@@ -1847,8 +2026,9 @@ pub fn collect_lint_files(dir: &Path) -> Vec<PathBuf> {
                 let lintable = path
                     .extension()
                     .and_then(|e| e.to_str())
-                    .map(|ext| ext == "sl" || ext == "slv")
-                    .unwrap_or(false);
+                    .map(|ext| matches!(ext, "sl" | "slv" | "erb"))
+                    .unwrap_or(false)
+                    && !solilang::types::stubs::is_declaration_file(&path);
                 if lintable {
                     files.push(path);
                 }
@@ -1865,6 +2045,27 @@ pub fn collect_lint_files(dir: &Path) -> Vec<PathBuf> {
     files
 }
 
+/// Merge the profile each worker wrote into `tracker`, in worker order, then
+/// remove them. A worker whose profile is missing or unreadable is reported:
+/// the lines only its test files reached would otherwise just read as
+/// uncovered.
+fn merge_worker_profiles(tracker: &Mutex<CoverageTracker>, coverage_dir: &Path, workers: usize) {
+    let mut tracker = tracker.lock().unwrap();
+    for worker in 0..workers {
+        let path = worker_profile_path(coverage_dir, worker);
+        match CoverageProfile::read(&path) {
+            Ok(profile) => tracker.merge_profile(&profile),
+            Err(e) => eprintln!(
+                "\x1b[33mWarning:\x1b[0m could not read the coverage profile of worker {} ({}): {}",
+                worker,
+                path.display(),
+                e
+            ),
+        }
+    }
+    let _ = fs::remove_dir_all(coverage_dir.join(PROFILES_DIR));
+}
+
 fn register_app_source_lines(tracker: &mut CoverageTracker, app_dir: &Path) {
     let source_dirs = [
         app_dir.join("app"),
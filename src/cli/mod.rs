@@ -39,6 +39,17 @@ pub fn run() {
         Command::Routes { folder, grep, json } => {
             commands::run_routes(folder, grep.as_deref(), *json)
         }
+        Command::CheckRoutes { folder, json } => commands::run_check_routes(folder, *json),
+        Command::Introspect { folder, json } => commands::run_introspect(folder, *json),
+        Command::ExportStatic {
+            folder,
+            output,
+            paths,
+        } => commands::export_static::run(commands::export_static::ExportStaticArgs {
+            folder,
+            output: output.as_deref(),
+            paths,
+        }),
         Command::Graph {
             folder,
             no_embed,
@@ -83,14 +94,86 @@ pub fn run() {
             dev_mode,
             workers,
             daemonize,
-        } => commands::run_serve(folder, *port, *dev_mode, *workers, *daemonize),
-        Command::Lint { paths } => commands::run_lint(paths),
-        Command::Check { paths } => commands::run_check(paths),
+            max_call_depth,
+        } => {
+            if let Some(depth) = max_call_depth {
+                solilang::interpreter::executor::set_worker_call_depth(*depth);
+            }
+            commands::run_serve(folder, *port, *dev_mode, *workers, *daemonize)
+        }
+        Command::Lint {
+            paths,
+            dead_code,
+            fix,
+        } => commands::run_lint(paths, *dead_code, *fix),
+        Command::Check {
+            paths,
+            max_errors,
+            templates,
+            format,
+        } => commands::run_check(paths, *max_errors, *templates, *format),
+        Command::TypesCoverage {
+            paths,
+            json,
+            min,
+            show_untyped,
+        } => commands::run_types_coverage(paths, *json, *min, *show_untyped),
+        Command::Bench {
+            paths,
+            vm,
+            iterations,
+            time_ms,
+            warmup_ms,
+            json,
+            baseline,
+        } => commands::bench::run(commands::bench::BenchArgs {
+            paths,
+            vm: *vm,
+            iterations: *iterations,
+            time_ms: *time_ms,
+            warmup_ms: *warmup_ms,
+            json: *json,
+            baseline: baseline.as_deref(),
+        }),
+        Command::SelfBench {
+            workloads,
+            phases,
+            time_ms,
+            json,
+            baseline,
+            threshold,
+        } => commands::self_bench::run(commands::self_bench::SelfBenchArgs {
+            workloads,
+            phases,
+            time_ms: *time_ms,
+            json: *json,
+            baseline: baseline.as_deref(),
+            threshold: *threshold,
+        }),
+        Command::Task {
+            names,
+            list,
+            force,
+            jobs,
+        } => commands::task::run(commands::task::TaskArgs {
+            names,
+            list: *list,
+            force: *force,
+            jobs: *jobs,
+        }),
+        Command::Profile { file, output, top } => {
+            commands::run_profile(file, output.as_deref(), *top, &options)
+        }
         Command::Fmt {
             paths,
             check,
             stdin,
         } => commands::run_fmt(paths, *check, *stdin),
+        Command::Codemod {
+            script,
+            paths,
+            check,
+        } => commands::run_codemod(script, paths, *check),
         Command::Deploy { folder } => commands::run_deploy(folder.as_deref()),
         Command::Init => commands::run_init(),
         Command::Add {
@@ -103,6 +186,13 @@ pub fn run() {
             version,
         } => commands::run_add(name, git, path, tag, branch, rev, version),
         Command::Remove { name } => commands::run_remove(name),
+        Command::Js { action } => commands::js::run(action),
+        Command::DepsCheck { fix } => commands::run_deps_check(*fix),
+        Command::StubsGen {
+            path,
+            output,
+            stdout,
+        } => commands::run_stubs_gen(path.as_deref(), output.as_deref(), *stdout),
         Command::Install => commands::run_install(),
         Command::Update { name } => commands::run_update(name.as_deref()),
         Command::SelfUpdate => commands::run_self_update()
@@ -120,8 +210,15 @@ pub fn run() {
             no_coverage,
             show_uncovered,
             fail_on_n1,
+            contract,
             browser,
             headed,
+            seed,
+            vm,
+            filter,
+            tags,
+            exclude_tags,
+            fail_on_todo,
         } => commands::run_test(
             paths,
             *jobs,
@@ -130,11 +227,19 @@ pub fn run() {
             *no_coverage,
             *show_uncovered,
             *fail_on_n1,
+            *contract,
             *browser,
             *headed,
+            *seed,
+            *vm,
+            filter.as_deref(),
+            tags,
+            exclude_tags,
+            *fail_on_todo,
         ),
         Command::Engine { action } => commands::run_engine(action),
         Command::Lsp => commands::run_lsp(),
+        Command::Debug => commands::run_debug(),
         Command::Build {
             folder,
             output,
@@ -142,6 +247,7 @@ pub fn run() {
             encrypt,
             protect,
             target,
+            precompile,
         } => commands::run_build(
             folder,
             output.as_deref(),
@@ -149,6 +255,7 @@ pub fn run() {
             *encrypt,
             *protect,
             target.as_deref(),
+            *precompile,
         ),
         Command::DesktopBuild {
             folder,
@@ -171,5 +278,8 @@ pub fn run() {
             protect: *protect,
             target: target.as_deref(),
         }),
+        Command::Project { name, script, args } => {
+            commands::run_project_command(name, script, args, &options)
+        }
     }
 }
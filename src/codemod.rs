@@ -0,0 +1,581 @@
+//! AST codemods, run by `soli codemod <script.sl> <path>`.
+//!
+//! A codemod script is a Soli file that defines visitor functions. Every node
+//! of each target file's AST is handed to the script as a hash — its `kind`
+//! (`"Call"`, `"Variable"`, `"Let"`, …), its `span`, and the variant's fields
+//! by name, or `value` for variants that wrap a single value. Nodes are
+//! visited children first, each by `visit_<kind in snake_case>(node)` and
+//! then by `visit(node)`, whichever the script defines. A visitor edits the
+//! node in place or returns a replacement hash; any other return value keeps
+//! the node as it is.
+//!
+//! Only the nodes that changed are re-printed, in `soli fmt` style, and
+//! spliced back into the original text, so the rest of the file — comments,
+//! blank lines and layout — is left exactly as it was. An expression that
+//! holds comments is re-printed with its whole statement, which keeps them.
+//! A codemod that adds or removes top-level statements re-prints the file.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+
+use ahash::RandomState as AHasher;
+use serde_json::{Map, Value as Json};
+
+use crate::ast::{Expr, Stmt, TypeAnnotation};
+use crate::fmt::comments::{extract_comments, Comment};
+use crate::interpreter::value::{HashKey, HashPairs, Value};
+use crate::interpreter::value_json::value_to_json;
+use crate::interpreter::Interpreter;
+use crate::lexer::Scanner;
+use crate::parser::Parser;
+use crate::span::Span;
+
+/// Keys every node hash carries besides the variant's own fields.
+const NODE_KEYS: [&str; 3] = ["kind", "span", "source_path"];
+
+/// A loaded codemod script, ready to apply to any number of files.
+pub struct Codemod {
+    interpreter: Interpreter,
+    /// The script's `visit*` functions, by name.
+    visitors: HashMap<String, Value>,
+}
+
+impl Codemod {
+    /// Run the script once, collecting the visitor functions it defines.
+    pub fn load(source: &str, source_path: Option<&Path>) -> Result<Self, String> {
+        let program =
+            crate::prepare_program(source, source_path, false).map_err(|e| e.to_string())?;
+        let mut interpreter = Interpreter::new();
+        if let Some(path) = source_path {
+            interpreter.set_source_path(path.to_path_buf());
+        }
+        interpreter.interpret(&program).map_err(|e| e.to_string())?;
+        let visitors: HashMap<String, Value> = interpreter
+            .environment
+            .borrow()
+            .local_bindings()
+            .filter(|(name, value)| {
+                (*name == "visit" || name.starts_with("visit_"))
+                    && matches!(value, Value::Function(_))
+            })
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        if visitors.is_empty() {
+            return Err(
+                "the codemod defines no visitors (fn visit(node) or fn visit_<kind>(node))"
+                    .to_string(),
+            );
+        }
+        Ok(Self {
+            interpreter,
+            visitors,
+        })
+    }
+
+    /// Rewrite one file's source. Returns `None` when the codemod changed
+    /// nothing.
+    pub fn apply(&mut self, source: &str) -> Result<Option<String>, String> {
+        let statements = parse(source)?;
+        let original = serde_json::to_value(&statements).map_err(|e| e.to_string())?;
+        let original = flatten(original);
+
+        let tree = to_soli(&original);
+        let tree = self.visit(tree)?;
+        let rewritten = value_to_json(&tree)?;
+        if same_ignoring_spans(&original, &rewritten) {
+            return Ok(None);
+        }
+
+        let comments = extract_comments(source);
+        let output = match (&original, &rewritten) {
+            (Json::Array(before), Json::Array(after)) if before.len() == after.len() => {
+                let mut edits = Vec::new();
+                for (before, after) in before.iter().zip(after) {
+                    diff(before, after, None, &mut edits);
+                }
+                splice(source, &comments, edits)?
+            }
+            _ => {
+                let statements: Vec<Stmt> = serde_json::from_value(unflatten(rewritten))
+                    .map_err(|e| format!("the codemod built an invalid program: {}", e))?;
+                let mut printer = crate::fmt::Printer::new(source, comments);
+                printer.print_program(&crate::ast::Program { statements });
+                printer.finish()
+            }
+        };
+        parse(&output).map_err(|e| format!("the rewritten source does not parse ({})", e))?;
+        Ok(Some(output))
+    }
+
+    /// Visit `value` and everything under it, children first.
+    fn visit(&mut self, value: Value) -> Result<Value, String> {
+        match &value {
+            Value::Array(items) => {
+                let visited = items
+                    .borrow()
+                    .iter()
+                    .cloned()
+                    .map(|item| self.visit(item))
+                    .collect::<Result<Vec<_>, _>>()?;
+                *items.borrow_mut() = visited;
+                Ok(value)
+            }
+            Value::Hash(hash) => {
+                let entries: Vec<(HashKey, Value)> = hash
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect();
+                for (key, child) in entries {
+                    if matches!(&key, HashKey::String(name) if &**name == "span") {
+                        continue;
+                    }
+                    let child = self.visit(child)?;
+                    hash.borrow_mut().insert(key, child);
+                }
+                let kind = match hash.borrow().get(&HashKey::String("kind".into())) {
+                    Some(Value::String(kind)) => Some(kind.to_string()),
+                    _ => None,
+                };
+                let Some(kind) = kind else {
+                    return Ok(value.clone());
+                };
+                let mut node = value.clone();
+                for name in [format!("visit_{}", snake_case(&kind)), "visit".to_string()] {
+                    if let Some(visitor) = self.visitors.get(&name).cloned() {
+                        let returned = self
+                            .interpreter
+                            .call_value(visitor, vec![node.clone()], Span::default())
+                            .map_err(|e| format!("{}: {}", name, e))?;
+                        if matches!(returned, Value::Hash(_)) {
+                            node = returned;
+                        }
+                    }
+                }
+                Ok(node)
+            }
+            _ => Ok(value),
+        }
+    }
+}
+
+fn parse(source: &str) -> Result<Vec<Stmt>, String> {
+    let tokens = Scanner::new(source)
+        .scan_tokens()
+        .map_err(|e| e.to_string())?;
+    Parser::new(tokens)
+        .keeping_platform_code()
+        .parse()
+        .map(|program| program.statements)
+        .map_err(|e| e.to_string())
+}
+
+/// The AST as a codemod sees it: `{"kind": {"Call": {...}}, "span": ...}`
+/// becomes `{"kind": "Call", "span": ..., "callee": ..., "arguments": ...}`.
+/// A variant that wraps one value keeps it under `value` — and so does one
+/// whose fields would clash with the node's own keys.
+fn flatten(json: Json) -> Json {
+    match json {
+        Json::Array(items) => Json::Array(items.into_iter().map(flatten).collect()),
+        Json::Object(map) if is_node(&map) => {
+            let mut node = Map::new();
+            let mut payload = None;
+            for (key, value) in map {
+                match (key.as_str(), value) {
+                    ("kind", Json::Object(variant)) if variant.len() == 1 => {
+                        let (name, value) = variant.into_iter().next().unwrap_or_default();
+                        node.insert("kind".to_string(), Json::String(name));
+                        payload = Some(flatten(value));
+                    }
+                    (_, value) => {
+                        node.insert(key, value);
+                    }
+                }
+            }
+            match payload {
+                Some(Json::Object(fields))
+                    if !fields.keys().any(|key| NODE_KEYS.contains(&key.as_str())) =>
+                {
+                    node.extend(fields)
+                }
+                Some(value) => {
+                    node.insert("value".to_string(), value);
+                }
+                None => {}
+            }
+            Json::Object(node)
+        }
+        Json::Object(map) => Json::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, flatten(value)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Undo [`flatten`], giving nodes a zero span where a codemod left it out.
+fn unflatten(json: Json) -> Json {
+    match json {
+        Json::Array(items) => Json::Array(items.into_iter().map(unflatten).collect()),
+        Json::Object(mut map) if matches!(map.get("kind"), Some(Json::String(_))) => {
+            let Some(Json::String(kind)) = map.remove("kind") else {
+                unreachable!()
+            };
+            let span = map
+                .remove("span")
+                .unwrap_or_else(|| serde_json::to_value(Span::default()).unwrap_or_default());
+            let source_path = map.remove("source_path");
+            let kind = if map.is_empty() {
+                Json::String(kind)
+            } else {
+                let payload = match map.remove("value") {
+                    Some(value) if map.is_empty() => value,
+                    value => {
+                        map.extend(value.map(|value| ("value".to_string(), value)));
+                        Json::Object(map)
+                    }
+                };
+                let mut variant = Map::new();
+                variant.insert(kind, unflatten(payload));
+                Json::Object(variant)
+            };
+            let mut node = Map::new();
+            node.insert("kind".to_string(), kind);
+            node.insert("span".to_string(), span);
+            if let Some(source_path) = source_path {
+                node.insert("source_path".to_string(), source_path);
+            }
+            Json::Object(node)
+        }
+        Json::Object(map) => Json::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, unflatten(value)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+fn is_node(map: &Map<String, Json>) -> bool {
+    map.contains_key("kind") && matches!(map.get("span"), Some(Json::Object(_)))
+}
+
+fn to_soli(json: &Json) -> Value {
+    match json {
+        Json::Null => Value::Null,
+        Json::Bool(b) => Value::Bool(*b),
+        Json::Number(n) => n
+            .as_i64()
+            .map(Value::Int)
+            .unwrap_or_else(|| Value::Float(n.as_f64().unwrap_or_default())),
+        Json::String(s) => Value::String(s.as_str().into()),
+        Json::Array(items) => {
+            Value::Array(Rc::new(RefCell::new(items.iter().map(to_soli).collect())))
+        }
+        Json::Object(map) => {
+            let mut pairs = HashPairs::with_capacity_and_hasher(map.len(), AHasher::default());
+            for (key, value) in map {
+                pairs.insert(HashKey::String(key.as_str().into()), to_soli(value));
+            }
+            Value::Hash(Rc::new(RefCell::new(pairs)))
+        }
+    }
+}
+
+/// `MethodCall` → `method_call`.
+fn snake_case(kind: &str) -> String {
+    let mut out = String::with_capacity(kind.len() + 4);
+    for (i, c) in kind.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn same_ignoring_spans(a: &Json, b: &Json) -> bool {
+    match (a, b) {
+        (Json::Array(a), Json::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_ignoring_spans(a, b))
+        }
+        (Json::Object(a), Json::Object(b)) => {
+            let keys = |map: &Map<String, Json>| {
+                map.keys()
+                    .filter(|key| *key != "span")
+                    .cloned()
+                    .collect::<Vec<_>>()
+            };
+            keys(a) == keys(b)
+                && a.iter()
+                    .filter(|(key, _)| *key != "span")
+                    .all(|(key, value)| same_ignoring_spans(value, &b[key]))
+        }
+        _ => a == b,
+    }
+}
+
+/// A node to re-print: the original node, the nearest statement around it,
+/// and what the codemod turned each into.
+struct Edit<'a> {
+    before: &'a Json,
+    after: &'a Json,
+    statement: Option<(&'a Json, &'a Json)>,
+}
+
+/// Collect the smallest nodes that contain every change. Returns false when
+/// `before` and `after` differ outside any node, so the caller's node has to
+/// be re-printed whole.
+fn diff<'a>(
+    before: &'a Json,
+    after: &'a Json,
+    statement: Option<(&'a Json, &'a Json)>,
+    edits: &mut Vec<Edit<'a>>,
+) -> bool {
+    if same_ignoring_spans(before, after) {
+        return true;
+    }
+    match (before, after) {
+        (Json::Object(map), Json::Object(new_map)) if is_node(map) => {
+            let statement = if map.contains_key("source_path") {
+                Some((before, after))
+            } else {
+                statement
+            };
+            let mut inner = Vec::new();
+            let children_only = map.get("kind") == new_map.get("kind")
+                && map.len() == new_map.len()
+                && map.iter().all(|(key, value)| {
+                    key == "span"
+                        || new_map
+                            .get(key)
+                            .is_some_and(|new| diff(value, new, statement, &mut inner))
+                });
+            if children_only {
+                edits.extend(inner);
+            } else {
+                edits.push(Edit {
+                    before,
+                    after,
+                    statement,
+                });
+            }
+            true
+        }
+        (Json::Array(items), Json::Array(new_items)) if items.len() == new_items.len() => items
+            .iter()
+            .zip(new_items)
+            .all(|(item, new)| diff(item, new, statement, edits)),
+        (Json::Object(map), Json::Object(new_map)) if map.len() == new_map.len() => {
+            map.iter().all(|(key, value)| {
+                new_map
+                    .get(key)
+                    .is_some_and(|new| diff(value, new, statement, edits))
+            })
+        }
+        _ => false,
+    }
+}
+
+/// Re-print each edited node and splice it over the original text.
+fn splice(source: &str, comments: &[Comment], edits: Vec<Edit<'_>>) -> Result<String, String> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let offset = |comment: &Comment| {
+        line_starts
+            .get(comment.line - 1)
+            .copied()
+            .unwrap_or(source.len())
+            + comment.column
+            - 1
+    };
+
+    let mut replacements: Vec<(usize, usize, String)> = Vec::new();
+    for edit in edits {
+        let (mut before, mut after) = (edit.before, edit.after);
+        let (mut start, mut end) = node_range(source, before);
+        let inner = |start: usize, end: usize| {
+            comments
+                .iter()
+                .filter(|comment| (start..end).contains(&offset(comment)))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let is_statement = before.get("source_path").is_some();
+        if !is_statement && !inner(start, end).is_empty() {
+            if let Some((statement, rewritten)) = edit.statement {
+                (before, after) = (statement, rewritten);
+                (start, end) = node_range(source, before);
+            }
+        }
+        let text = print_node(before, after, source, inner(start, end))?;
+        let indent: String = source[line_starts[line_of(&line_starts, start)]..start]
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .collect();
+        let text = text
+            .trim_end_matches('\n')
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                if i == 0 || line.is_empty() {
+                    line.to_string()
+                } else {
+                    format!("{}{}", indent, line)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        replacements.push((start, end, text));
+    }
+
+    // An expression escalated to its statement may now overlap other edits
+    // in that statement; the outermost one covers them.
+    replacements.sort_by_key(|(start, end, _)| (*start, std::cmp::Reverse(*end)));
+    let mut kept: Vec<(usize, usize, String)> = Vec::new();
+    for replacement in replacements {
+        match kept.last() {
+            Some((_, end, _)) if replacement.0 < *end => {}
+            _ => kept.push(replacement),
+        }
+    }
+    let mut output = source.to_string();
+    for (start, end, text) in kept.into_iter().rev() {
+        output.replace_range(start..end, &text);
+    }
+    Ok(output)
+}
+
+fn line_of(line_starts: &[usize], offset: usize) -> usize {
+    line_starts
+        .partition_point(|start| *start <= offset)
+        .saturating_sub(1)
+}
+
+/// The bytes a node covers. A string literal's span starts after its opening
+/// quote, as does that of any node that begins with one.
+fn node_range(source: &str, node: &Json) -> (usize, usize) {
+    let span = |node: &Json, key: &str| node["span"][key].as_u64().unwrap_or(0) as usize;
+    let (mut start, end) = (span(node, "start"), span(node, "end"));
+    fn starts_with_string(node: &Json, start: usize) -> bool {
+        match node {
+            Json::Array(items) => items.iter().any(|item| starts_with_string(item, start)),
+            Json::Object(map) => {
+                (map.get("kind") == Some(&Json::String("StringLiteral".to_string()))
+                    && node["span"]["start"].as_u64() == Some(start as u64))
+                    || map.values().any(|value| starts_with_string(value, start))
+            }
+            _ => false,
+        }
+    }
+    if start > 0
+        && matches!(source.as_bytes().get(start - 1), Some(b'"' | b'\''))
+        && starts_with_string(node, start)
+    {
+        start -= 1;
+    }
+    (start.min(source.len()), end.clamp(start, source.len()))
+}
+
+/// Print what the codemod made of `before` as the same sort of node.
+fn print_node(
+    before: &Json,
+    after: &Json,
+    source: &str,
+    comments: Vec<Comment>,
+) -> Result<String, String> {
+    let invalid = |e: serde_json::Error| {
+        format!(
+            "the codemod built an invalid {} node: {}",
+            before["kind"].as_str().unwrap_or("?"),
+            e
+        )
+    };
+    let after = unflatten(after.clone());
+    if before.get("source_path").is_some() {
+        let stmt: Stmt = serde_json::from_value(after).map_err(invalid)?;
+        return Ok(crate::fmt::format_stmt(&stmt, source, comments));
+    }
+    if serde_json::from_value::<Expr>(unflatten(before.clone())).is_ok() {
+        let expr: Expr = serde_json::from_value(after).map_err(invalid)?;
+        return Ok(crate::fmt::format_expr(&expr, source));
+    }
+    let ty: TypeAnnotation = serde_json::from_value(after).map_err(invalid)?;
+    Ok(ty.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codemod(script: &str, source: &str) -> Option<String> {
+        Codemod::load(script, None).unwrap().apply(source).unwrap()
+    }
+
+    #[test]
+    fn renames_a_call_and_keeps_everything_else() {
+        let script = r#"
+fn visit_call(node)
+  if node["callee"]["kind"] == "Variable" && node["callee"]["value"] == "old_name"
+    node["callee"]["value"] = "new_name"
+  end
+end
+"#;
+        let source = "# helpers\nfn greet(x)\n  # say it\n  let  s = old_name( x,\"hi\" )   # trailing\n  return s\nend\n\n\nprint(old_name(1, 'a'))\n";
+        assert_eq!(
+            codemod(script, source).unwrap(),
+            "# helpers\nfn greet(x)\n  # say it\n  let  s = new_name( x,\"hi\" )   # trailing\n  return s\nend\n\n\nprint(new_name(1, 'a'))\n"
+        );
+        assert_eq!(codemod(script, "print(other(1))\n"), None);
+    }
+
+    #[test]
+    fn replacements_and_node_shape() {
+        let script = r#"
+fn visit_string_literal(node)
+  return { "kind": "IntLiteral", "value": node["value"].length }
+end
+"#;
+        assert_eq!(
+            codemod(script, "let a = [\"abc\",  2]\n").unwrap(),
+            "let a = [3,  2]\n"
+        );
+
+        let flat = flatten(serde_json::to_value(parse("x = f(1)").unwrap()).unwrap());
+        let call = &flat[0]["value"]["value"];
+        assert_eq!(call["kind"], "Call");
+        assert_eq!(call["callee"]["value"], "f");
+        assert_eq!(unflatten(flat.clone()), unflatten(flatten(unflatten(flat))));
+    }
+
+    #[test]
+    fn expressions_with_comments_reprint_their_statement() {
+        let script = r#"
+fn visit_array(node)
+  node["value"].push({ "kind": "IntLiteral", "value": 3 })
+end
+"#;
+        let source = "fn f()\n  let x = [\n    1, # one\n    2\n  ]\n  return x\nend\n";
+        let out = codemod(script, source).unwrap();
+        assert!(out.contains("# one"), "{}", out);
+        assert!(
+            out.contains('3') && out.ends_with("  return x\nend\n"),
+            "{}",
+            out
+        );
+    }
+
+    #[test]
+    fn a_script_without_visitors_is_an_error() {
+        assert!(Codemod::load("let x = 1", None).is_err());
+        assert_eq!(snake_case("StringLiteral"), "string_literal");
+    }
+}
@@ -1,6 +1,7 @@
 //! Compiled module cache for avoiding repeated compilation.
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, OnceLock};
 
 use crate::error::SolilangError;
@@ -50,10 +51,22 @@ fn compile(
     source_path: Option<&std::path::Path>,
     type_check: bool,
 ) -> Result<CompiledModule, SolilangError> {
+    compile_with_sources(source, source_path, type_check).map(|(module, _)| module)
+}
+
+/// Compile `source` and also return the files it was compiled from: the
+/// script itself and every module its imports pulled in. `soli build`
+/// records them so a stale `.slc` can be told apart from a fresh one.
+pub fn compile_with_sources(
+    source: &str,
+    source_path: Option<&std::path::Path>,
+    type_check: bool,
+) -> Result<(CompiledModule, Vec<PathBuf>), SolilangError> {
     let tokens = Scanner::new(source).scan_tokens()?;
 
     let mut program = Parser::new(tokens).parse()?;
 
+    let mut sources: Vec<PathBuf> = source_path.map(Path::to_path_buf).into_iter().collect();
     if let Some(path) = source_path.filter(|_| has_imports(&program)) {
         let base_dir = path.parent().unwrap_or(std::path::Path::new("."));
         let mut resolver = ModuleResolver::new(base_dir);
@@ -64,21 +77,20 @@ fn compile(
                     message: format!("Module resolution error: {}", e),
                     span: crate::span::Span::new(0, 0, 1, 1),
                 })?;
+        sources.extend(resolver.loaded_modules().map(Path::to_path_buf));
     }
 
     if type_check {
         let mut checker = TypeChecker::new();
         if let Err(errors) = checker.check(&program) {
-            return Err(errors.into_iter().next().unwrap().into());
+            return Err(errors.into());
         }
     }
 
-    let module = Compiler::compile(&program).map_err(|e| crate::error::RuntimeError::General {
-        message: format!("Compile error: {}", e),
-        span: crate::span::Span::new(0, 0, 1, 1),
-    })?;
+    crate::optimize::fold_constants(&mut program);
+    let module = Compiler::compile(&program)?;
 
-    Ok(module)
+    Ok((module, sources))
 }
 
 pub fn clear_cache() {
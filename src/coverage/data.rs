@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
@@ -26,7 +27,7 @@ pub struct BranchCoverage {
     pub hits_false: u32,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BranchType {
     If,
     Match,
@@ -47,6 +48,36 @@ pub struct FileCoverage {
 }
 
 impl FileCoverage {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lines: HashMap::new(),
+            branches: HashMap::new(),
+            total_lines: 0,
+            covered_lines: 0,
+            total_branches: 0,
+            covered_branches: 0,
+        }
+    }
+
+    /// Recompute the covered/total counters from `lines` and `branches`, so
+    /// they don't depend on the order hits were merged in. `total_lines` is
+    /// the number of lines registered as executable for the file.
+    pub fn recount(&mut self, total_lines: u32) {
+        self.total_lines = total_lines;
+        self.covered_lines = self
+            .lines
+            .values()
+            .filter(|l| l.is_executable && l.hits > 0)
+            .count() as u32;
+        self.total_branches = self.branches.len() as u32;
+        self.covered_branches = self
+            .branches
+            .values()
+            .filter(|b| b.hits_true > 0 || b.hits_false > 0)
+            .count() as u32;
+    }
+
     pub fn line_coverage_percent(&self) -> f64 {
         if self.total_lines == 0 {
             return 100.0;
@@ -205,6 +236,7 @@ pub enum OutputFormat {
     Html,
     Json,
     Xml,
+    Lcov,
 }
 
 impl CoverageConfig {
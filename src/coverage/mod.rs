@@ -1,10 +1,12 @@
 pub mod data;
+pub mod profile;
 pub mod reporter;
 pub mod tracker;
 
 pub use data::*;
+pub use profile::CoverageProfile;
 pub use reporter::CoverageReporter;
 pub use tracker::{
-    clear_global_coverage_tracker, get_global_coverage_tracker, set_global_coverage_tracker,
-    CoverageTracker,
+    clear_global_coverage_tracker, flush_current_coverage, get_global_coverage_tracker,
+    set_global_coverage_tracker, take_test_coverage, CoverageTracker,
 };
@@ -0,0 +1,166 @@
+//! Coverage profiles: the raw hit counts one test worker recorded, kept
+//! apart from every other worker's until the run is over.
+//!
+//! Each `soli test --jobs N` worker folds the coverage of the files it ran
+//! into its own profile and writes it to `coverage/.profiles/worker-<i>.json`.
+//! The main process then reads them back in worker order and merges them
+//! into the tracker. Hit counts only ever add up and the maps are ordered, so
+//! the merged report is the same however the files were spread across
+//! workers.
+
+use crate::coverage::data::{BranchType, TestCoverage};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Directory, under the coverage output directory, holding worker profiles.
+pub const PROFILES_DIR: &str = ".profiles";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CoverageProfile {
+    pub files: BTreeMap<PathBuf, FileProfile>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileProfile {
+    /// Hits per line number.
+    pub lines: BTreeMap<usize, u32>,
+    pub branches: BTreeMap<usize, BranchProfile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BranchProfile {
+    pub branch_type: BranchType,
+    pub hits_true: u32,
+    pub hits_false: u32,
+}
+
+impl CoverageProfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn record_line(&mut self, path: &Path, line: usize, hits: u32) {
+        let file = self.files.entry(path.to_path_buf()).or_default();
+        let count = file.lines.entry(line).or_insert(0);
+        *count = count.saturating_add(hits);
+    }
+
+    pub fn record_branch(
+        &mut self,
+        path: &Path,
+        line: usize,
+        branch_type: BranchType,
+        hits_true: u32,
+        hits_false: u32,
+    ) {
+        let file = self.files.entry(path.to_path_buf()).or_default();
+        let branch = file.branches.entry(line).or_insert(BranchProfile {
+            branch_type,
+            hits_true: 0,
+            hits_false: 0,
+        });
+        branch.hits_true = branch.hits_true.saturating_add(hits_true);
+        branch.hits_false = branch.hits_false.saturating_add(hits_false);
+    }
+
+    /// Add what one test file recorded.
+    pub fn add_test_coverage(&mut self, test_cov: &TestCoverage) {
+        for (path, file_cov) in &test_cov.file_coverages {
+            for (line, line_cov) in &file_cov.lines {
+                self.record_line(path, *line, line_cov.hits);
+            }
+            for (line, branch) in &file_cov.branches {
+                self.record_branch(
+                    path,
+                    *line,
+                    branch.branch_type.clone(),
+                    branch.hits_true,
+                    branch.hits_false,
+                );
+            }
+        }
+    }
+
+    /// Add every hit of `other` to this profile.
+    pub fn merge(&mut self, other: &CoverageProfile) {
+        for (path, file) in &other.files {
+            for (line, hits) in &file.lines {
+                self.record_line(path, *line, *hits);
+            }
+            for (line, branch) in &file.branches {
+                self.record_branch(
+                    path,
+                    *line,
+                    branch.branch_type.clone(),
+                    branch.hits_true,
+                    branch.hits_false,
+                );
+            }
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string(self).map_err(io::Error::other)?;
+        std::fs::write(path, json)
+    }
+
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Where worker `worker` writes its profile under `output_dir`.
+pub fn worker_profile_path(output_dir: &Path, worker: usize) -> PathBuf {
+    output_dir
+        .join(PROFILES_DIR)
+        .join(format!("worker-{}.json", worker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(hits: &[(&str, usize, u32)]) -> CoverageProfile {
+        let mut profile = CoverageProfile::new();
+        for (path, line, count) in hits {
+            profile.record_line(Path::new(path), *line, *count);
+        }
+        profile
+    }
+
+    #[test]
+    fn merging_is_independent_of_order() {
+        let a = profile(&[("/app/a.sl", 1, 2), ("/app/b.sl", 4, 1)]);
+        let mut b = profile(&[("/app/a.sl", 1, 3), ("/app/a.sl", 2, 1)]);
+        b.record_branch(Path::new("/app/a.sl"), 2, BranchType::If, 1, 0);
+
+        let mut ab = a.clone();
+        ab.merge(&b);
+        let mut ba = b.clone();
+        ba.merge(&a);
+        assert_eq!(ab, ba);
+        assert_eq!(ab.files[Path::new("/app/a.sl")].lines[&1], 5);
+        assert_eq!(ab.files[Path::new("/app/a.sl")].branches[&2].hits_true, 1);
+    }
+
+    #[test]
+    fn profiles_round_trip_through_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut original = profile(&[("/app/a.sl", 3, 7)]);
+        original.record_branch(Path::new("/app/a.sl"), 3, BranchType::Match, 2, 5);
+        let path = worker_profile_path(dir.path(), 2);
+        original.write(&path).unwrap();
+        assert!(path.ends_with(".profiles/worker-2.json"));
+        assert_eq!(CoverageProfile::read(&path).unwrap(), original);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::coverage::data::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -38,6 +38,10 @@ impl CoverageReporter {
                     self.generate_xml_report(coverage);
                     reports.push("XML report: coverage/cobertura.xml".to_string());
                 }
+                OutputFormat::Lcov => {
+                    self.generate_lcov_report(coverage);
+                    reports.push("LCOV report: coverage/lcov.info".to_string());
+                }
             }
         }
 
@@ -510,76 +514,162 @@ th { background: #f1f5f9; font-weight: 600; font-size: 14px; }
         let _ = fs::write(self.config.output_dir.join("cobertura.xml"), xml);
     }
 
+    /// Cobertura XML as GitLab and Codecov read it: one package per
+    /// directory, filenames relative to the `<source>` root, and only
+    /// executable lines, in order.
     fn generate_xml_output(&self, coverage: &AggregatedCoverage) -> String {
-        let mut packages = String::new();
-
-        let mut file_groups: HashMap<String, Vec<&FileCoverage>> = HashMap::new();
-        for (path, file_cov) in &coverage.file_coverages {
-            if let Some(parent) = path.parent() {
-                let key = parent.to_string_lossy();
-                file_groups
-                    .entry(key.to_string())
-                    .or_default()
-                    .push(file_cov);
-            }
+        let mut file_groups: BTreeMap<String, Vec<&FileCoverage>> = BTreeMap::new();
+        for file_cov in coverage.file_coverages.values() {
+            let display = self.display_path(&file_cov.path);
+            let dir = Path::new(&display)
+                .parent()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default();
+            file_groups.entry(dir).or_default().push(file_cov);
         }
 
-        for (dir_path, files) in file_groups {
+        let mut packages = String::new();
+        for (dir_path, mut files) in file_groups {
+            files.sort_by(|a, b| a.path.cmp(&b.path));
+            let (mut lines_valid, mut lines_covered) = (0, 0);
+            let (mut branches_valid, mut branches_covered) = (0, 0);
             let mut classes = String::new();
             for file_cov in files {
+                lines_valid += file_cov.total_lines;
+                lines_covered += file_cov.covered_lines;
+                branches_valid += file_cov.total_branches;
+                branches_covered += file_cov.covered_branches;
                 let file_name = file_cov
                     .path
                     .file_name()
                     .unwrap_or_default()
                     .to_string_lossy();
                 classes.push_str(&format!(
-                    r#"    <class name="{}" filename="{}" line-rate="{:.3}" branch-rate="{:.3}">
-        <lines>
-"#,
-                    file_name,
-                    file_cov.path.to_string_lossy(),
+                    "      <class name=\"{}\" filename=\"{}\" line-rate=\"{:.3}\" branch-rate=\"{:.3}\" complexity=\"0\">\n        <methods/>\n        <lines>\n",
+                    xml_escape(&file_name),
+                    xml_escape(&self.display_path(&file_cov.path)),
                     file_cov.line_coverage_percent() / 100.0,
                     file_cov.branch_coverage_percent() / 100.0
                 ));
-
-                for (line_num, line_cov) in &file_cov.lines {
-                    classes.push_str(&format!(
-                        r#"            <line number="{}" hits="{}"/>
-"#,
-                        line_num, line_cov.hits
-                    ));
+                for line_cov in executable_lines(file_cov) {
+                    match file_cov.branches.get(&line_cov.line_number) {
+                        Some(branch) => {
+                            let taken = branch_outcomes_taken(branch);
+                            classes.push_str(&format!(
+                                "          <line number=\"{}\" hits=\"{}\" branch=\"true\" condition-coverage=\"{}% ({}/2)\"/>\n",
+                                line_cov.line_number,
+                                line_cov.hits,
+                                taken * 50,
+                                taken
+                            ));
+                        }
+                        None => classes.push_str(&format!(
+                            "          <line number=\"{}\" hits=\"{}\" branch=\"false\"/>\n",
+                            line_cov.line_number, line_cov.hits
+                        )),
+                    }
                 }
-
-                classes.push_str("        </lines>\n    </class>\n");
+                classes.push_str("        </lines>\n      </class>\n");
             }
 
             packages.push_str(&format!(
-                r#"  <package name="{}" line-rate="{:.3}" branch-rate="{:.3}">
-{}
-  </package>
-"#,
-                dir_path,
-                coverage.total_line_coverage_percent() / 100.0,
-                coverage.total_branch_coverage_percent() / 100.0,
+                "    <package name=\"{}\" line-rate=\"{:.3}\" branch-rate=\"{:.3}\" complexity=\"0\">\n      <classes>\n{}      </classes>\n    </package>\n",
+                xml_escape(&dir_path.replace('/', ".")),
+                rate(lines_covered, lines_valid),
+                rate(branches_covered, branches_valid),
                 classes
             ));
         }
 
+        let source = self
+            .config
+            .root_dir
+            .as_ref()
+            .map(|root| root.to_string_lossy().to_string())
+            .unwrap_or_else(|| ".".to_string());
+        let (branches_valid, branches_covered) =
+            coverage
+                .file_coverages
+                .values()
+                .fold((0, 0), |(valid, covered), file_cov| {
+                    (
+                        valid + file_cov.total_branches,
+                        covered + file_cov.covered_branches,
+                    )
+                });
         format!(
-            r#"<?xml version="1.0" ?>
-<coverage version="5.5" timestamp="{}" lines-valid="{}" lines-covered="{}" line-rate="{:.3}" branches-covered="0" branches-valid="0" branch-rate="0" complexity="0">
-  <packages>
-{}
-  </packages>
-</coverage>"#,
-            chrono::Utc::now().timestamp(),
+            "<?xml version=\"1.0\" ?>\n<!DOCTYPE coverage SYSTEM \"http://cobertura.sourceforge.net/xml/coverage-04.dtd\">\n<coverage version=\"{}\" timestamp=\"{}\" lines-valid=\"{}\" lines-covered=\"{}\" line-rate=\"{:.3}\" branches-valid=\"{}\" branches-covered=\"{}\" branch-rate=\"{:.3}\" complexity=\"0\">\n  <sources>\n    <source>{}</source>\n  </sources>\n  <packages>\n{}  </packages>\n</coverage>\n",
+            VERSION,
+            chrono::Utc::now().timestamp_millis(),
             coverage.total_lines(),
             coverage.covered_lines(),
             coverage.total_line_coverage_percent() / 100.0,
+            branches_valid,
+            branches_covered,
+            rate(branches_covered, branches_valid),
+            xml_escape(&source),
             packages
         )
     }
 
+    pub fn generate_lcov_report(&self, coverage: &AggregatedCoverage) {
+        let lcov = self.generate_lcov_output(coverage);
+        let _ = fs::create_dir_all(&self.config.output_dir);
+        let _ = fs::write(self.config.output_dir.join("lcov.info"), lcov);
+    }
+
+    /// The LCOV tracefile Codecov, Coveralls and `genhtml` read: per file,
+    /// a `DA` record per executable line and a pair of `BRDA` records (the
+    /// true and false outcomes) per branch.
+    fn generate_lcov_output(&self, coverage: &AggregatedCoverage) -> String {
+        let mut files: Vec<&FileCoverage> = coverage.file_coverages.values().collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut out = String::new();
+        for file_cov in files {
+            out.push_str("TN:\n");
+            out.push_str(&format!("SF:{}\n", self.display_path(&file_cov.path)));
+
+            let mut branches: Vec<&BranchCoverage> = file_cov.branches.values().collect();
+            branches.sort_by_key(|b| b.line_number);
+            let mut branches_hit = 0;
+            for (block, branch) in branches.iter().enumerate() {
+                for (outcome, hits) in [branch.hits_true, branch.hits_false].iter().enumerate() {
+                    if *hits > 0 {
+                        branches_hit += 1;
+                    }
+                    out.push_str(&format!(
+                        "BRDA:{},{},{},{}\n",
+                        branch.line_number, block, outcome, hits
+                    ));
+                }
+            }
+            out.push_str(&format!("BRF:{}\n", branches.len() * 2));
+            out.push_str(&format!("BRH:{}\n", branches_hit));
+
+            let lines = executable_lines(file_cov);
+            for line_cov in &lines {
+                out.push_str(&format!("DA:{},{}\n", line_cov.line_number, line_cov.hits));
+            }
+            out.push_str(&format!("LF:{}\n", lines.len()));
+            out.push_str(&format!(
+                "LH:{}\n",
+                lines.iter().filter(|l| l.hits > 0).count()
+            ));
+            out.push_str("end_of_record\n");
+        }
+        out
+    }
+
+    /// `path` relative to the project root when it lies under it.
+    fn display_path(&self, path: &Path) -> String {
+        let relative = match self.config.root_dir {
+            Some(ref root) => path.strip_prefix(root).unwrap_or(path),
+            None => path,
+        };
+        relative.to_string_lossy().replace('\\', "/")
+    }
+
     pub fn check_threshold(&self, coverage: &AggregatedCoverage) -> bool {
         if let Some(threshold) = self.config.threshold {
             return coverage.total_line_coverage_percent() >= threshold;
@@ -588,6 +678,37 @@ th { background: #f1f5f9; font-weight: 600; font-size: 14px; }
     }
 }
 
+/// A file's executable lines in line order.
+fn executable_lines(file_cov: &FileCoverage) -> Vec<&LineCoverage> {
+    let mut lines: Vec<&LineCoverage> = file_cov
+        .lines
+        .values()
+        .filter(|l| l.is_executable)
+        .collect();
+    lines.sort_by_key(|l| l.line_number);
+    lines
+}
+
+/// How many of a branch's two outcomes ran.
+fn branch_outcomes_taken(branch: &BranchCoverage) -> u32 {
+    (branch.hits_true > 0) as u32 + (branch.hits_false > 0) as u32
+}
+
+fn rate(covered: u32, valid: u32) -> f64 {
+    if valid == 0 {
+        1.0
+    } else {
+        covered as f64 / valid as f64
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 /// Map a coverage percentage to a green / orange / red ANSI color escape.
 /// Mirrors the HTML pill thresholds: 80% green, 50% orange, below that red.
 fn ansi_coverage_color(percent: f64) -> &'static str {
@@ -756,3 +877,81 @@ fn token_class(kind: &crate::lexer::token::TokenKind) -> &'static str {
         _ => "",
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(num: usize, hits: u32, executable: bool) -> LineCoverage {
+        LineCoverage {
+            line_number: num,
+            hits,
+            source_code: String::new(),
+            is_executable: executable,
+        }
+    }
+
+    /// `/app/models/user.sl`: lines 1 and 3 executable (3 missed), a
+    /// comment on line 2, and an `if` on line 1 whose false side never ran.
+    fn coverage() -> AggregatedCoverage {
+        let path = PathBuf::from("/app/models/user.sl");
+        let mut file = FileCoverage {
+            path: path.clone(),
+            lines: HashMap::new(),
+            branches: HashMap::new(),
+            total_lines: 2,
+            covered_lines: 1,
+            total_branches: 1,
+            covered_branches: 0,
+        };
+        for l in [line(3, 0, true), line(1, 4, true), line(2, 0, false)] {
+            file.lines.insert(l.line_number, l);
+        }
+        file.branches.insert(
+            1,
+            BranchCoverage {
+                line_number: 1,
+                branch_type: BranchType::If,
+                hits_true: 4,
+                hits_false: 0,
+            },
+        );
+        let mut coverage = AggregatedCoverage::new();
+        coverage.file_coverages.insert(path, file);
+        coverage
+    }
+
+    fn reporter() -> CoverageReporter {
+        CoverageReporter::new(CoverageConfig {
+            root_dir: Some(PathBuf::from("/app")),
+            ..CoverageConfig::new()
+        })
+    }
+
+    #[test]
+    fn lcov_lists_executable_lines_and_branch_outcomes() {
+        let lcov = reporter().generate_lcov_output(&coverage());
+        assert_eq!(
+            lcov,
+            "TN:\nSF:models/user.sl\nBRDA:1,0,0,4\nBRDA:1,0,1,0\nBRF:2\nBRH:1\n\
+             DA:1,4\nDA:3,0\nLF:2\nLH:1\nend_of_record\n"
+        );
+    }
+
+    #[test]
+    fn cobertura_uses_relative_filenames_and_ordered_lines() {
+        let xml = reporter().generate_xml_output(&coverage());
+        assert!(xml.contains("<source>/app</source>"), "{}", xml);
+        assert!(xml.contains(r#"<package name="models" line-rate="0.500""#));
+        assert!(xml.contains(r#"filename="models/user.sl""#));
+        let first =
+            xml.find(r#"<line number="1" hits="4" branch="true" condition-coverage="50% (1/2)"/>"#);
+        let third = xml.find(r#"<line number="3" hits="0" branch="false"/>"#);
+        assert!(
+            first.is_some() && third.is_some() && first < third,
+            "{}",
+            xml
+        );
+        assert!(!xml.contains(r#"<line number="2""#));
+    }
+}
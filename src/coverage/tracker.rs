@@ -1,5 +1,6 @@
 use crate::ast::expr::Argument;
 use crate::coverage::data::*;
+use crate::coverage::profile::CoverageProfile;
 use crate::lexer::Scanner;
 use crate::parser::Parser;
 use std::cell::RefCell;
@@ -58,135 +59,64 @@ impl CoverageTracker {
         });
     }
 
+    /// Fold this thread's in-progress coverage into the aggregate.
     pub fn end_test(&mut self) -> Option<TestCoverage> {
-        let mut result: Option<TestCoverage> = None;
-
-        CURRENT_COVERAGE.with(|cov| {
-            if let Some(test_cov) = cov.borrow_mut().take() {
-                let mut aggregated = self.global_coverage.lock().unwrap();
-
-                for (path, file_cov) in &test_cov.file_coverages {
-                    let aggregated_file = aggregated
-                        .file_coverages
-                        .entry(path.clone())
-                        .or_insert_with(|| FileCoverage {
-                            path: path.clone(),
-                            lines: HashMap::new(),
-                            branches: HashMap::new(),
-                            total_lines: 0,
-                            covered_lines: 0,
-                            total_branches: 0,
-                            covered_branches: 0,
-                        });
-
-                    let executable = match self.executable_lines.get(path) {
-                        Some(e) => e,
-                        None => {
-                            for (line_num, line_cov) in &file_cov.lines {
-                                let aggregated_line = aggregated_file
-                                    .lines
-                                    .entry(*line_num)
-                                    .or_insert_with(|| LineCoverage {
-                                        line_number: *line_num,
-                                        hits: line_cov.hits,
-                                        source_code: line_cov.source_code.clone(),
-                                        is_executable: false,
-                                    });
-                                aggregated_line.hits += line_cov.hits;
-                            }
-                            continue;
-                        }
-                    };
-
-                    if aggregated_file.total_lines == 0 {
-                        aggregated_file.total_lines = executable.len() as u32;
-                    }
-
-                    for (line_num, line_cov) in &file_cov.lines {
-                        let is_executable = executable.get(line_num).is_some();
-
-                        let aggregated_line = aggregated_file
-                            .lines
-                            .entry(*line_num)
-                            .or_insert_with(|| LineCoverage {
-                                line_number: *line_num,
-                                hits: 0,
-                                source_code: line_cov.source_code.clone(),
-                                is_executable,
-                            });
-
-                        let was_already_covered = aggregated_line.hits > 0;
-                        aggregated_line.hits += line_cov.hits;
-
-                        if line_cov.hits > 0 && !was_already_covered && is_executable {
-                            aggregated_file.covered_lines += 1;
-                        }
-                    }
-
-                    for (line_num, branch_cov) in &file_cov.branches {
-                        let aggregated_branch = aggregated_file
-                            .branches
-                            .entry(*line_num)
-                            .or_insert_with(|| BranchCoverage {
-                                line_number: *line_num,
-                                branch_type: branch_cov.branch_type.clone(),
-                                hits_true: 0,
-                                hits_false: 0,
-                            });
-
-                        let was_covered =
-                            aggregated_branch.hits_true > 0 || aggregated_branch.hits_false > 0;
-
-                        aggregated_branch.hits_true += branch_cov.hits_true;
-                        aggregated_branch.hits_false += branch_cov.hits_false;
-
-                        aggregated_file.total_branches += 1;
-
-                        let is_now_covered =
-                            aggregated_branch.hits_true > 0 || aggregated_branch.hits_false > 0;
-                        if is_now_covered && !was_covered {
-                            aggregated_file.covered_branches += 1;
-                        }
-                    }
-                }
+        let test_cov = take_test_coverage()?;
+        let mut profile = CoverageProfile::new();
+        profile.add_test_coverage(&test_cov);
+        self.merge_profile(&profile);
+        Some(test_cov)
+    }
 
-                for (path, executable) in &self.executable_lines {
-                    if test_cov.file_coverages.contains_key(path) {
-                        continue;
-                    }
-                    let aggregated_file = aggregated
-                        .file_coverages
-                        .entry(path.clone())
-                        .or_insert_with(|| FileCoverage {
-                            path: path.clone(),
-                            lines: HashMap::new(),
-                            branches: HashMap::new(),
-                            total_lines: 0,
-                            covered_lines: 0,
-                            total_branches: 0,
-                            covered_branches: 0,
+    /// Add a worker's profile to the aggregate. Counters are recomputed from
+    /// the merged hits, so merging profiles in any order gives one report.
+    pub fn merge_profile(&mut self, profile: &CoverageProfile) {
+        let mut aggregated = self.global_coverage.lock().unwrap();
+        for (path, file) in &profile.files {
+            let executable = self.executable_lines.get(path);
+            let file_cov = aggregated
+                .file_coverages
+                .entry(path.clone())
+                .or_insert_with(|| FileCoverage::new(path.clone()));
+            for (line_num, hits) in &file.lines {
+                let source = executable.and_then(|lines| lines.get(line_num));
+                let line_cov = file_cov
+                    .lines
+                    .entry(*line_num)
+                    .or_insert_with(|| LineCoverage {
+                        line_number: *line_num,
+                        hits: 0,
+                        source_code: source.cloned().unwrap_or_default(),
+                        is_executable: source.is_some(),
+                    });
+                line_cov.hits = line_cov.hits.saturating_add(*hits);
+            }
+            for (line_num, branch) in &file.branches {
+                let branch_cov =
+                    file_cov
+                        .branches
+                        .entry(*line_num)
+                        .or_insert_with(|| BranchCoverage {
+                            line_number: *line_num,
+                            branch_type: branch.branch_type.clone(),
+                            hits_true: 0,
+                            hits_false: 0,
                         });
-
-                    if aggregated_file.total_lines == 0 {
-                        aggregated_file.total_lines = executable.len() as u32;
-                    }
-                }
-
-                result = Some(test_cov);
+                branch_cov.hits_true = branch_cov.hits_true.saturating_add(branch.hits_true);
+                branch_cov.hits_false = branch_cov.hits_false.saturating_add(branch.hits_false);
             }
-        });
-
-        result
+            file_cov.recount(executable.map(|lines| lines.len()).unwrap_or(0) as u32);
+        }
     }
 
-    pub fn record_line_hit(&self, path: &PathBuf, line: usize) {
-        CURRENT_COVERAGE.with(|cov| {
+    pub fn record_line_hit(&self, path: &Path, line: usize) {
+        let in_test = CURRENT_COVERAGE.with(|cov| {
             if let Some(ref mut test_cov) = *cov.borrow_mut() {
                 let file_cov = test_cov
                     .file_coverages
-                    .entry(path.clone())
+                    .entry(path.to_path_buf())
                     .or_insert_with(|| FileCoverage {
-                        path: path.clone(),
+                        path: path.to_path_buf(),
                         lines: HashMap::new(),
                         branches: HashMap::new(),
                         total_lines: 0,
@@ -212,8 +142,17 @@ impl CoverageTracker {
                 });
 
                 line_cov.hits += 1;
+                true
+            } else {
+                false
             }
         });
+        if in_test {
+            // Stays on this thread until `end_test` / the worker's profile
+            // picks it up; counting it in the aggregate too would count it
+            // twice.
+            return;
+        }
 
         // Important: callers in the interpreter hold the tracker's Mutex via
         // `.lock()` before invoking this method, so a re-entrant `try_lock()`
@@ -224,15 +163,15 @@ impl CoverageTracker {
         self.update_global_coverage(path, line);
     }
 
-    fn update_global_coverage(&self, path: &PathBuf, line: usize) {
+    fn update_global_coverage(&self, path: &Path, line: usize) {
         let Ok(mut global) = self.global_coverage.lock() else {
             return;
         };
         let file_cov = global
             .file_coverages
-            .entry(path.clone())
+            .entry(path.to_path_buf())
             .or_insert_with(|| FileCoverage {
-                path: path.clone(),
+                path: path.to_path_buf(),
                 lines: HashMap::new(),
                 branches: HashMap::new(),
                 total_lines: 0,
@@ -483,6 +422,27 @@ where
     f()
 }
 
+/// Take this thread's in-progress coverage, ending it. A `soli test`
+/// worker adds it to its own profile rather than to the shared tracker.
+pub fn take_test_coverage() -> Option<TestCoverage> {
+    CURRENT_COVERAGE.with(|cov| cov.borrow_mut().take())
+}
+
+/// Fold this thread's in-progress coverage into the global tracker (or drop
+/// it when there is none), so nothing recorded while serving one request is
+/// left buffered on a worker thread that serves the next.
+pub fn flush_current_coverage() {
+    if CURRENT_COVERAGE.with(|cov| cov.borrow().is_none()) {
+        return;
+    }
+    if let Some(global) = get_global_coverage_tracker() {
+        if let Ok(mut tracker) = global.lock() {
+            tracker.end_test();
+        }
+    }
+    CURRENT_COVERAGE.with(|cov| *cov.borrow_mut() = None);
+}
+
 pub fn current_coverage<F, R>(f: F) -> R
 where
     F: FnOnce(Option<&TestCoverage>) -> R,
@@ -539,7 +499,7 @@ impl CoverageTracker {
                     self.collect_lines_from_expr(path, lines, expr);
                 }
             }
-            Const { initializer, .. } => {
+            Const { initializer, .. } | LetTuple { initializer, .. } => {
                 self.collect_lines_from_expr(path, lines, initializer);
             }
             Break => {}
@@ -563,12 +523,7 @@ impl CoverageTracker {
                 self.collect_lines_from_expr(path, lines, condition);
                 self.collect_lines_from_stmt(path, lines, body);
             }
-            For {
-                variable: _,
-                index_variable: _,
-                iterable,
-                body,
-            } => {
+            For { iterable, body, .. } => {
                 self.collect_lines_from_expr(path, lines, iterable);
                 self.collect_lines_from_stmt(path, lines, body);
             }
@@ -577,7 +532,7 @@ impl CoverageTracker {
                     self.collect_lines_from_expr(path, lines, e);
                 }
             }
-            Throw(expr) => {
+            Throw(expr) | Yield(expr) => {
                 self.collect_lines_from_expr(path, lines, expr);
             }
             Try {
@@ -594,7 +549,7 @@ impl CoverageTracker {
                 }
             }
             Function(decl) => {
-                for stmt in &decl.body {
+                for stmt in decl.body.iter() {
                     self.collect_lines_from_stmt(path, lines, stmt);
                 }
             }
@@ -603,12 +558,12 @@ impl CoverageTracker {
                     self.collect_lines_from_stmt(path, lines, stmt);
                 }
                 if let Some(ctor) = &decl.constructor {
-                    for stmt in &ctor.body {
+                    for stmt in ctor.body.iter() {
                         self.collect_lines_from_stmt(path, lines, stmt);
                     }
                 }
                 for method in &decl.methods {
-                    for stmt in &method.body {
+                    for stmt in method.body.iter() {
                         self.collect_lines_from_stmt(path, lines, stmt);
                     }
                 }
@@ -622,12 +577,19 @@ impl CoverageTracker {
                 // Only the user-defined methods carry real source lines; the
                 // synthesized variant machinery has no source span.
                 for method in &decl.methods {
-                    for stmt in &method.body {
+                    for stmt in method.body.iter() {
                         self.collect_lines_from_stmt(path, lines, stmt);
                     }
                 }
             }
-            Interface(_) | Import(_) | Export(_) => {}
+            Struct(decl) => {
+                for field in &decl.fields {
+                    if let Some(default) = &field.default_value {
+                        self.collect_lines_from_expr(path, lines, default);
+                    }
+                }
+            }
+            Interface(_) | TypeAlias(_) | Import(_) | Export(_) => {}
         }
     }
 
@@ -655,7 +617,7 @@ impl CoverageTracker {
             | Symbol(_)
             | Null
             | CommandSubstitution(_) => {}
-            Variable(_) => {}
+            Variable(..) => {}
             Assign { value, .. } => {
                 self.collect_lines_from_expr(path, lines, value);
             }
@@ -668,7 +630,7 @@ impl CoverageTracker {
                         Argument::Named(named) => {
                             self.collect_lines_from_expr(path, lines, &named.value);
                         }
-                        Argument::Block(expr) => {
+                        Argument::Block(expr) | Argument::Spread(expr) => {
                             self.collect_lines_from_expr(path, lines, expr);
                         }
                     }
@@ -694,13 +656,13 @@ impl CoverageTracker {
                         Argument::Named(named) => {
                             self.collect_lines_from_expr(path, lines, &named.value);
                         }
-                        Argument::Block(expr) => {
+                        Argument::Block(expr) | Argument::Spread(expr) => {
                             self.collect_lines_from_expr(path, lines, expr);
                         }
                     }
                 }
             }
-            Array(elements) => {
+            Array(elements) | Tuple(elements) => {
                 for elem in elements {
                     self.collect_lines_from_expr(path, lines, elem);
                 }
@@ -724,7 +686,7 @@ impl CoverageTracker {
                 self.collect_lines_from_expr(path, lines, right);
             }
             Lambda { body, .. } => {
-                for stmt in body {
+                for stmt in body.iter() {
                     self.collect_lines_from_stmt(path, lines, stmt);
                 }
             }
@@ -766,13 +728,14 @@ impl CoverageTracker {
                     self.collect_lines_from_expr(path, lines, cond);
                 }
             }
-            HashComprehension {
-                key,
-                value,
-                iterable,
-                condition,
-                ..
-            } => {
+            HashComprehension(comp) => {
+                let crate::ast::HashComprehension {
+                    key,
+                    value,
+                    iterable,
+                    condition,
+                    ..
+                } = &**comp;
                 self.collect_lines_from_expr(path, lines, key);
                 self.collect_lines_from_expr(path, lines, value);
                 self.collect_lines_from_expr(path, lines, iterable);
@@ -783,7 +746,7 @@ impl CoverageTracker {
             Spread(expr) => {
                 self.collect_lines_from_expr(path, lines, expr);
             }
-            Throw(expr) => {
+            Throw(expr) | Await(expr) => {
                 self.collect_lines_from_expr(path, lines, expr);
             }
             Rescue { expr, fallback } => {
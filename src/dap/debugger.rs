@@ -0,0 +1,511 @@
+//! The interpreter observer behind `soli debug`: decides where to stop, and
+//! answers the client's requests while the program is stopped.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+
+use serde_json::{json, Value as Json};
+
+use super::{canonical, Shared, THREAD_ID};
+use crate::ast::{Stmt, StmtKind};
+use crate::interpreter::environment::Environment;
+use crate::interpreter::observer::InterpreterObserver;
+use crate::interpreter::value::{HashKey, Value};
+use crate::interpreter::Interpreter;
+use crate::lexer::Scanner;
+use crate::parser::Parser;
+use crate::span::Span;
+
+/// Longest value preview shown in the variables view.
+const MAX_PREVIEW: usize = 200;
+
+/// Where a step request stops next, by call depth (0 is the top level).
+#[derive(Debug, Clone, Copy)]
+enum Step {
+    /// Any statement, including one in a called function.
+    In,
+    /// A statement at this depth or shallower.
+    Over(usize),
+    /// A statement shallower than this depth.
+    Out(usize),
+}
+
+/// What the debugger knows about one frame: the scope its latest
+/// statement ran in, and where that statement is.
+struct Frame {
+    env: Rc<RefCell<Environment>>,
+    file: PathBuf,
+    line: usize,
+}
+
+/// Something the variables view can expand.
+enum Handle {
+    /// The variables of a frame's scopes, innermost first, up to (not
+    /// including) the globals.
+    Locals(Rc<RefCell<Environment>>),
+    Globals(Rc<RefCell<Environment>>),
+    Value(Value),
+}
+
+pub(super) struct Debugger {
+    shared: Arc<Shared>,
+    requests: Receiver<Json>,
+    /// The global scope before the program ran. Globals still bound to
+    /// these values are hidden from the globals view.
+    builtins: HashMap<String, Value>,
+    step: Option<Step>,
+    stop_on_entry: bool,
+    /// One per call depth, outermost first.
+    frames: Vec<Frame>,
+    /// The statement before this one: its file, span and depth.
+    last: Option<(PathBuf, Span, usize)>,
+    /// Canonical paths, by the path a statement or frame gave.
+    paths: HashMap<String, PathBuf>,
+    /// Variables-view handles, valid until the program resumes.
+    handles: Vec<Handle>,
+    /// Set when the client has gone: the program runs on, unobserved.
+    detached: bool,
+}
+
+impl Debugger {
+    pub(super) fn new(
+        shared: Arc<Shared>,
+        requests: Receiver<Json>,
+        builtins: HashMap<String, Value>,
+        stop_on_entry: bool,
+    ) -> Self {
+        Self {
+            shared,
+            requests,
+            builtins,
+            step: None,
+            stop_on_entry,
+            frames: Vec::new(),
+            last: None,
+            paths: HashMap::new(),
+            handles: Vec::new(),
+            detached: false,
+        }
+    }
+
+    /// The file `stmt` belongs to: its module, else the function running
+    /// it, else the program.
+    fn file_of(&mut self, stmt: &Stmt, interpreter: &Interpreter) -> PathBuf {
+        let given = stmt
+            .source_path
+            .as_deref()
+            .map(|path| path.to_string_lossy().to_string())
+            .or_else(|| {
+                interpreter
+                    .call_stack
+                    .last()
+                    .and_then(|frame| frame.file_path.clone())
+            })
+            .or_else(|| {
+                interpreter
+                    .current_source_path
+                    .as_ref()
+                    .map(|path| path.to_string_lossy().to_string())
+            })
+            .unwrap_or_default();
+        self.paths
+            .entry(given)
+            .or_insert_with_key(|given| canonical(Path::new(given)))
+            .clone()
+    }
+
+    /// Whether a breakpoint is set on this line and its condition, if any,
+    /// holds.
+    fn breakpoint_hit(&self, file: &Path, line: usize, interpreter: &mut Interpreter) -> bool {
+        let condition = {
+            let breakpoints = self
+                .shared
+                .breakpoints
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            let Some(bp) = breakpoints
+                .get(file)
+                .and_then(|bps| bps.iter().find(|bp| bp.line == line))
+            else {
+                return false;
+            };
+            bp.condition.clone()
+        };
+        match condition {
+            None => true,
+            Some(condition) => {
+                let env = interpreter.environment.clone();
+                evaluate(interpreter, &condition, env).is_ok_and(|value| value.is_truthy())
+            }
+        }
+    }
+
+    /// Stop: tell the client, then answer its requests until it resumes.
+    fn pause(&mut self, reason: &str, interpreter: &mut Interpreter) {
+        let connection = &self.shared.connection;
+        self.step = None;
+        self.shared.stopped.store(true, Ordering::SeqCst);
+        connection.event(
+            "stopped",
+            json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+        );
+        loop {
+            let Ok(request) = self.requests.recv() else {
+                self.detached = true;
+                break;
+            };
+            let arguments = &request["arguments"];
+            let resume = match request["command"].as_str().unwrap_or_default() {
+                "continue" => Some(None),
+                "next" => Some(Some(Step::Over(self.frames.len() - 1))),
+                "stepIn" => Some(Some(Step::In)),
+                "stepOut" => Some(Some(Step::Out(self.frames.len() - 1))),
+                _ => None,
+            };
+            if let Some(step) = resume {
+                self.step = step;
+                self.handles.clear();
+                self.shared.stopped.store(false, Ordering::SeqCst);
+                let body = if request["command"] == "continue" {
+                    json!({ "allThreadsContinued": true })
+                } else {
+                    json!({})
+                };
+                self.shared.connection.respond(&request, body);
+                break;
+            }
+            let answer = match request["command"].as_str().unwrap_or_default() {
+                "stackTrace" => Ok(self.stack_trace(interpreter)),
+                "scopes" => self.scopes(arguments["frameId"].as_i64().unwrap_or(0)),
+                "variables" => {
+                    self.variables(arguments["variablesReference"].as_i64().unwrap_or(0))
+                }
+                "evaluate" => self.evaluate_request(arguments, interpreter),
+                other => Err(format!("unsupported request '{}'", other)),
+            };
+            match answer {
+                Ok(body) => self.shared.connection.respond(&request, body),
+                Err(e) => self.shared.connection.fail(&request, &e),
+            }
+        }
+    }
+
+    fn stack_trace(&self, interpreter: &Interpreter) -> Json {
+        let frames: Vec<Json> = self
+            .frames
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(depth, frame)| {
+                let name = match depth {
+                    0 => "main".to_string(),
+                    _ => interpreter
+                        .call_stack
+                        .get(depth - 1)
+                        .map(|f| f.function_name.clone())
+                        .filter(|name| !name.is_empty())
+                        .unwrap_or_else(|| "<lambda>".to_string()),
+                };
+                json!({
+                    "id": depth + 1,
+                    "name": name,
+                    "line": frame.line,
+                    "column": 1,
+                    "source": {
+                        "name": frame.file.file_name().map(|n| n.to_string_lossy()),
+                        "path": frame.file.display().to_string(),
+                    },
+                })
+            })
+            .collect();
+        json!({ "stackFrames": frames, "totalFrames": frames.len() })
+    }
+
+    fn frame(&self, frame_id: i64) -> Result<&Frame, String> {
+        usize::try_from(frame_id - 1)
+            .ok()
+            .and_then(|depth| self.frames.get(depth))
+            .ok_or_else(|| format!("no frame {}", frame_id))
+    }
+
+    fn scopes(&mut self, frame_id: i64) -> Result<Json, String> {
+        let env = self.frame(frame_id)?.env.clone();
+        let globals = root(&env);
+        let mut scopes = Vec::new();
+        if !Rc::ptr_eq(&env, &globals) {
+            let locals = self.handle(Handle::Locals(env));
+            scopes.push(
+                json!({ "name": "Locals", "variablesReference": locals, "expensive": false }),
+            );
+        }
+        let globals = self.handle(Handle::Globals(globals));
+        scopes
+            .push(json!({ "name": "Globals", "variablesReference": globals, "expensive": false }));
+        Ok(json!({ "scopes": scopes }))
+    }
+
+    fn variables(&mut self, reference: i64) -> Result<Json, String> {
+        let entries: Vec<(String, Value)> = match usize::try_from(reference - 1)
+            .ok()
+            .and_then(|i| self.handles.get(i))
+        {
+            Some(Handle::Locals(env)) => {
+                let mut seen = HashSet::new();
+                let mut entries = Vec::new();
+                let mut scope = Some(env.clone());
+                while let Some(env) = scope {
+                    let parent = env.borrow().enclosing();
+                    if parent.is_none() {
+                        break;
+                    }
+                    let mut local: Vec<(String, Value)> = env
+                        .borrow()
+                        .local_bindings()
+                        .filter(|(name, _)| seen.insert((*name).clone()))
+                        .map(|(name, value)| (name.clone(), value.clone()))
+                        .collect();
+                    local.sort_by(|a, b| a.0.cmp(&b.0));
+                    entries.extend(local);
+                    scope = parent;
+                }
+                entries
+            }
+            Some(Handle::Globals(env)) => {
+                let mut entries: Vec<(String, Value)> = env
+                    .borrow()
+                    .local_bindings()
+                    .filter(|(name, value)| !self.is_builtin(name, value))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                entries
+            }
+            Some(Handle::Value(value)) => children(value),
+            None => return Err(format!("no variables {}", reference)),
+        };
+        let variables: Vec<Json> = entries
+            .into_iter()
+            .map(|(name, value)| {
+                let mut variable = self.describe(value);
+                variable["name"] = json!(name);
+                variable
+            })
+            .collect();
+        Ok(json!({ "variables": variables }))
+    }
+
+    fn evaluate_request(
+        &mut self,
+        arguments: &Json,
+        interpreter: &mut Interpreter,
+    ) -> Result<Json, String> {
+        let expression = arguments["expression"].as_str().unwrap_or_default();
+        let env = match arguments["frameId"].as_i64() {
+            Some(id) => self.frame(id)?.env.clone(),
+            None => interpreter.environment.clone(),
+        };
+        let value = evaluate(interpreter, expression, env)?;
+        let mut described = self.describe(value);
+        described["result"] = described["value"].take();
+        Ok(described)
+    }
+
+    /// A value for the variables view, with a handle if it has children.
+    fn describe(&mut self, value: Value) -> Json {
+        let preview = preview(&value);
+        let type_name = value.type_name();
+        let reference = if children(&value).is_empty() {
+            0
+        } else {
+            self.handle(Handle::Value(value))
+        };
+        json!({ "value": preview, "type": type_name, "variablesReference": reference })
+    }
+
+    /// Whether a global is still what the runtime defined it as.
+    fn is_builtin(&self, name: &str, value: &Value) -> bool {
+        let callable = |value: &Value| {
+            matches!(
+                value,
+                Value::Function(_) | Value::NativeFunction(_) | Value::Class(_)
+            )
+        };
+        match self.builtins.get(name) {
+            Some(builtin) if callable(builtin) => callable(value),
+            Some(builtin) => builtin == value,
+            None => matches!(value, Value::NativeFunction(_)),
+        }
+    }
+
+    fn handle(&mut self, handle: Handle) -> usize {
+        self.handles.push(handle);
+        self.handles.len()
+    }
+}
+
+impl InterpreterObserver for Debugger {
+    fn before_statement(&mut self, stmt: &Stmt, interpreter: &mut Interpreter) {
+        if self.detached {
+            return;
+        }
+        let depth = interpreter.call_stack.len();
+        let file = self.file_of(stmt, interpreter);
+        let line = stmt.span.line_usize();
+        self.frames.truncate(depth + 1);
+        let frame = Frame {
+            env: interpreter.environment.clone(),
+            file: file.clone(),
+            line,
+        };
+        if depth < self.frames.len() {
+            self.frames[depth] = frame;
+        } else {
+            // Frames entered by native code (a callback's caller) get the
+            // new statement's place until one of their own runs.
+            while self.frames.len() <= depth {
+                self.frames.push(Frame {
+                    env: frame.env.clone(),
+                    file: frame.file.clone(),
+                    line: frame.line,
+                });
+            }
+        }
+
+        // A statement nested in the one before, on the same line — an `if`
+        // body written on one line — doesn't hit that line's breakpoint again.
+        let nested = self
+            .last
+            .as_ref()
+            .is_some_and(|(last_file, last, last_depth)| {
+                *last_file == file
+                    && *last_depth == depth
+                    && stmt.span != *last
+                    && stmt.span.start >= last.start
+                    && stmt.span.end <= last.end
+            });
+        self.last = Some((file.clone(), stmt.span, depth));
+
+        let reason = if std::mem::take(&mut self.stop_on_entry) {
+            Some("entry")
+        } else if self.shared.pause_requested.swap(false, Ordering::SeqCst) {
+            Some("pause")
+        } else if match self.step {
+            Some(Step::In) => true,
+            Some(Step::Over(from)) => depth <= from,
+            Some(Step::Out(from)) => depth < from,
+            None => false,
+        } {
+            Some("step")
+        } else if !nested && self.breakpoint_hit(&file, line, interpreter) {
+            Some("breakpoint")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            self.pause(reason, interpreter);
+        }
+    }
+
+    fn on_debug_call(&mut self, _stmt: &Stmt, interpreter: &mut Interpreter) -> bool {
+        if self.detached {
+            return false;
+        }
+        self.pause("breakpoint", interpreter);
+        true
+    }
+}
+
+/// The outermost scope `env` sees.
+fn root(env: &Rc<RefCell<Environment>>) -> Rc<RefCell<Environment>> {
+    let mut env = env.clone();
+    loop {
+        let parent = env.borrow().enclosing();
+        match parent {
+            Some(parent) => env = parent,
+            None => return env,
+        }
+    }
+}
+
+/// Evaluate `source` in `env`: an expression's value, or null for a
+/// statement.
+fn evaluate(
+    interpreter: &mut Interpreter,
+    source: &str,
+    env: Rc<RefCell<Environment>>,
+) -> Result<Value, String> {
+    let tokens = Scanner::new(source)
+        .scan_tokens()
+        .map_err(|e| e.to_string())?;
+    let program = Parser::new(tokens).parse().map_err(|e| e.to_string())?;
+    let previous = std::mem::replace(&mut interpreter.environment, env);
+    let mut result = Ok(Value::Null);
+    for stmt in &program.statements {
+        result = match &stmt.kind {
+            StmtKind::Expression(expr) => interpreter.evaluate(expr),
+            _ => interpreter.execute(stmt).map(|_| Value::Null),
+        };
+        if result.is_err() {
+            break;
+        }
+    }
+    interpreter.environment = previous;
+    result.map_err(|e| e.to_string())
+}
+
+/// The entries the variables view can expand a value into.
+fn children(value: &Value) -> Vec<(String, Value)> {
+    match value {
+        Value::Array(items) => items
+            .borrow()
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (format!("[{}]", i), item.clone()))
+            .collect(),
+        Value::Tuple(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| (format!("[{}]", i), item.clone()))
+            .collect(),
+        Value::Hash(hash) => hash
+            .borrow()
+            .iter()
+            .map(|(key, value)| {
+                let name = match key {
+                    HashKey::String(s) => format!("{:?}", s.to_string()),
+                    other => other.to_value().to_string(),
+                };
+                (name, value.clone())
+            })
+            .collect(),
+        Value::Instance(instance) => {
+            let mut fields: Vec<(String, Value)> = instance
+                .borrow()
+                .fields
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+            fields.sort_by(|a, b| a.0.cmp(&b.0));
+            fields
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn preview(value: &Value) -> String {
+    let text = match value {
+        Value::String(s) => format!("{:?}", s.to_string()),
+        other => other.to_string(),
+    };
+    if text.chars().count() > MAX_PREVIEW {
+        let cut: String = text.chars().take(MAX_PREVIEW).collect();
+        format!("{}…", cut)
+    } else {
+        text
+    }
+}
@@ -0,0 +1,505 @@
+//! Debug Adapter Protocol server for `soli debug`.
+//!
+//! An editor such as VS Code starts `soli debug` and speaks DAP to it over
+//! stdin/stdout: it launches a program, sets breakpoints (optionally with a
+//! condition), and once the program stops, asks for the call stack, the
+//! variables in each frame, and the value of watch and hover expressions,
+//! then continues or steps over, into or out of calls.
+//!
+//! The program runs on its own thread, on the tree-walking interpreter, with
+//! a [`debugger::Debugger`] observer. While the program runs, this side
+//! answers the requests that don't need it (breakpoints, threads, pause);
+//! while it is stopped, everything else is passed to the observer, which
+//! answers from inside the paused interpreter. A `debug()` call in the
+//! program stops it like a breakpoint.
+
+mod debugger;
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde_json::{json, Value as Json};
+
+use crate::interpreter::value::Value;
+use crate::interpreter::Interpreter;
+
+/// The only thread a Soli program has, as DAP sees it.
+const THREAD_ID: i64 = 1;
+
+/// A source breakpoint.
+#[derive(Debug, Clone)]
+struct Breakpoint {
+    line: usize,
+    condition: Option<String>,
+}
+
+/// Writes framed DAP messages. Shared by the request loop, the program
+/// thread and the output forwarder.
+struct Connection {
+    writer: Mutex<Box<dyn Write + Send>>,
+    seq: AtomicI64,
+}
+
+impl Connection {
+    fn send(&self, mut message: Json) {
+        message["seq"] = json!(self.seq.fetch_add(1, Ordering::SeqCst) + 1);
+        let body = message.to_string();
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body);
+        let _ = writer.flush();
+    }
+
+    fn respond(&self, request: &Json, body: Json) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": true,
+            "body": body,
+        }));
+    }
+
+    fn fail(&self, request: &Json, message: &str) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": false,
+            "message": message,
+        }));
+    }
+
+    fn event(&self, event: &str, body: Json) {
+        self.send(json!({ "type": "event", "event": event, "body": body }));
+    }
+}
+
+/// State the request loop shares with the program thread.
+struct Shared {
+    connection: Connection,
+    /// Breakpoints by canonical file path.
+    breakpoints: Mutex<HashMap<PathBuf, Vec<Breakpoint>>>,
+    /// Set by a `pause` request; the program stops at its next statement.
+    pause_requested: AtomicBool,
+    /// Whether the program is stopped and taking requests.
+    stopped: AtomicBool,
+}
+
+/// What a `launch` request asked for.
+struct Launch {
+    program: PathBuf,
+    source: String,
+    stop_on_entry: bool,
+}
+
+/// Serve one debug session: read requests from `input` until the client
+/// disconnects or closes the stream. `program_output`, if given, is read on
+/// a thread of its own and forwarded to the client as output events — for
+/// when the program's stdout has been redirected away from `output`.
+pub fn serve(
+    mut input: impl BufRead,
+    output: impl Write + Send + 'static,
+    program_output: Option<Box<dyn Read + Send>>,
+) -> io::Result<()> {
+    let shared = Arc::new(Shared {
+        connection: Connection {
+            writer: Mutex::new(Box::new(output)),
+            seq: AtomicI64::new(0),
+        },
+        breakpoints: Mutex::new(HashMap::new()),
+        pause_requested: AtomicBool::new(false),
+        stopped: AtomicBool::new(false),
+    });
+    if let Some(reader) = program_output {
+        let shared = shared.clone();
+        thread::spawn(move || forward_output(reader, &shared.connection));
+    }
+
+    let mut launch: Option<Launch> = None;
+    let mut paused_requests: Option<Sender<Json>> = None;
+    while let Some(request) = read_message(&mut input)? {
+        if request["type"] != "request" {
+            continue;
+        }
+        let connection = &shared.connection;
+        let arguments = &request["arguments"];
+        match request["command"].as_str().unwrap_or_default() {
+            "initialize" => {
+                connection.respond(
+                    &request,
+                    json!({
+                        "supportsConfigurationDoneRequest": true,
+                        "supportsConditionalBreakpoints": true,
+                        "supportsEvaluateForHovers": true,
+                        "supportsTerminateRequest": true,
+                    }),
+                );
+                connection.event("initialized", json!({}));
+            }
+            "launch" => match read_launch(arguments) {
+                Ok(config) => {
+                    launch = Some(config);
+                    connection.respond(&request, json!({}));
+                }
+                Err(e) => connection.fail(&request, &e),
+            },
+            "setBreakpoints" => {
+                let path = arguments["source"]["path"].as_str().unwrap_or_default();
+                let breakpoints: Vec<Breakpoint> = arguments["breakpoints"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|bp| {
+                        Some(Breakpoint {
+                            line: bp["line"].as_u64()? as usize,
+                            condition: bp["condition"]
+                                .as_str()
+                                .filter(|c| !c.trim().is_empty())
+                                .map(str::to_string),
+                        })
+                    })
+                    .collect();
+                let verified: Vec<Json> = breakpoints
+                    .iter()
+                    .map(|bp| json!({ "verified": true, "line": bp.line }))
+                    .collect();
+                shared
+                    .breakpoints
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(canonical(Path::new(path)), breakpoints);
+                connection.respond(&request, json!({ "breakpoints": verified }));
+            }
+            "setExceptionBreakpoints" => connection.respond(&request, json!({})),
+            "configurationDone" => {
+                connection.respond(&request, json!({}));
+                match launch.take() {
+                    Some(config) => {
+                        let (sender, receiver) = mpsc::channel();
+                        paused_requests = Some(sender);
+                        let shared = shared.clone();
+                        let stack_size = crate::interpreter::executor::stack_size_for_depth(
+                            crate::interpreter::executor::max_call_depth_from_env(),
+                        );
+                        thread::Builder::new()
+                            .name("soli-debuggee".to_string())
+                            .stack_size(stack_size)
+                            .spawn(move || run_program(config, shared, receiver))?;
+                    }
+                    None => connection.event(
+                        "output",
+                        json!({ "category": "stderr", "output": "Nothing to debug: no launch request\n" }),
+                    ),
+                }
+            }
+            "threads" => connection.respond(
+                &request,
+                json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] }),
+            ),
+            "pause" => {
+                shared.pause_requested.store(true, Ordering::SeqCst);
+                connection.respond(&request, json!({}));
+            }
+            "disconnect" | "terminate" => {
+                connection.respond(&request, json!({}));
+                return Ok(());
+            }
+            "stackTrace" | "scopes" | "variables" | "evaluate" | "continue" | "next" | "stepIn"
+            | "stepOut" => {
+                let forwarded = shared.stopped.load(Ordering::SeqCst)
+                    && paused_requests
+                        .as_ref()
+                        .is_some_and(|sender| sender.send(request.clone()).is_ok());
+                if !forwarded {
+                    connection.fail(&request, "the program is not stopped");
+                }
+            }
+            other => connection.fail(&request, &format!("unsupported request '{}'", other)),
+        }
+    }
+    Ok(())
+}
+
+fn read_launch(arguments: &Json) -> Result<Launch, String> {
+    let Some(program) = arguments["program"].as_str() else {
+        return Err("launch needs a \"program\" to debug".to_string());
+    };
+    if let Some(cwd) = arguments["cwd"].as_str() {
+        std::env::set_current_dir(cwd).map_err(|e| format!("cannot enter {}: {}", cwd, e))?;
+    }
+    let program = canonical(Path::new(program));
+    let source = std::fs::read_to_string(&program)
+        .map_err(|e| format!("cannot read {}: {}", program.display(), e))?;
+    Ok(Launch {
+        program,
+        source,
+        stop_on_entry: arguments["stopOnEntry"].as_bool().unwrap_or(false),
+    })
+}
+
+/// Run the launched program under the debugger, then report how it ended.
+fn run_program(launch: Launch, shared: Arc<Shared>, requests: mpsc::Receiver<Json>) {
+    let connection = &shared.connection;
+    let outcome = crate::prepare_program(&launch.source, Some(&launch.program), false)
+        .map_err(|e| e.to_string())
+        .and_then(|program| {
+            let mut interpreter = Interpreter::new();
+            crate::interpreter::builtins::mailer::ensure_prelude(&mut interpreter);
+            crate::interpreter::builtins::events::ensure_prelude(&mut interpreter);
+            crate::interpreter::builtins::export::ensure_prelude(&mut interpreter);
+            interpreter.set_source_path(launch.program.clone());
+            let builtins: HashMap<String, Value> = interpreter
+                .environment
+                .borrow()
+                .local_bindings()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+            interpreter.set_observer(Box::new(debugger::Debugger::new(
+                shared.clone(),
+                requests,
+                builtins,
+                launch.stop_on_entry,
+            )));
+            let result = interpreter.interpret(&program);
+            interpreter.take_observer();
+            result.map_err(|e| e.to_string())
+        });
+    let _ = io::stdout().flush();
+    let exit_code = match outcome {
+        Ok(()) => 0,
+        Err(e) => {
+            connection.event(
+                "output",
+                json!({ "category": "stderr", "output": format!("Error: {}\n", e) }),
+            );
+            1
+        }
+    };
+    connection.event("exited", json!({ "exitCode": exit_code }));
+    connection.event("terminated", json!({}));
+}
+
+fn forward_output(mut reader: Box<dyn Read + Send>, connection: &Connection) {
+    let mut buffer = [0u8; 4096];
+    while let Ok(n) = reader.read(&mut buffer) {
+        if n == 0 {
+            break;
+        }
+        connection.event(
+            "output",
+            json!({ "category": "stdout", "output": String::from_utf8_lossy(&buffer[..n]) }),
+        );
+    }
+}
+
+/// Read one `Content-Length`-framed message. `None` at end of input.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Json>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let mut body = vec![0u8; length.unwrap_or(0)];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// `path` made absolute and resolved, so breakpoints and statements agree
+/// on it however each was spelled.
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| {
+        std::env::current_dir()
+            .map(|cwd| cwd.join(path))
+            .unwrap_or_else(|_| path.to_path_buf())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::Receiver;
+    use std::time::{Duration, Instant};
+
+    /// Input fed a message at a time, like an editor's pipe.
+    struct ChannelReader {
+        receiver: Receiver<Vec<u8>>,
+        pending: io::Cursor<Vec<u8>>,
+    }
+
+    impl Read for ChannelReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            loop {
+                let n = self.pending.read(buf)?;
+                if n > 0 {
+                    return Ok(n);
+                }
+                match self.receiver.recv() {
+                    Ok(bytes) => self.pending = io::Cursor::new(bytes),
+                    Err(_) => return Ok(0),
+                }
+            }
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct Client {
+        sender: Sender<Vec<u8>>,
+        output: SharedBuffer,
+        read: usize,
+        seq: i64,
+    }
+
+    impl Client {
+        fn request(&mut self, command: &str, arguments: Json) -> Json {
+            self.seq += 1;
+            let body =
+                json!({ "seq": self.seq, "type": "request", "command": command, "arguments": arguments })
+                    .to_string();
+            self.sender
+                .send(format!("Content-Length: {}\r\n\r\n{}", body.len(), body).into_bytes())
+                .unwrap();
+            let seq = self.seq;
+            self.wait_for(|m| m["type"] == "response" && m["request_seq"] == seq)
+        }
+
+        /// The next message (skipping others) that `matches`.
+        fn wait_for(&mut self, matches: impl Fn(&Json) -> bool) -> Json {
+            let deadline = Instant::now() + Duration::from_secs(10);
+            loop {
+                let bytes = self.output.0.lock().unwrap()[self.read..].to_vec();
+                let mut cursor = io::Cursor::new(bytes);
+                while let Ok(Some(message)) = read_message(&mut cursor) {
+                    self.read += cursor.position() as usize;
+                    cursor =
+                        io::Cursor::new(cursor.get_ref()[cursor.position() as usize..].to_vec());
+                    if matches(&message) {
+                        return message;
+                    }
+                }
+                assert!(Instant::now() < deadline, "timed out waiting for a message");
+                thread::sleep(Duration::from_millis(5));
+            }
+        }
+    }
+
+    #[test]
+    fn stops_at_breakpoints_and_inspects_and_steps() {
+        let dir = tempfile::tempdir().unwrap();
+        let program = dir.path().join("main.sl");
+        std::fs::write(
+            &program,
+            "fn double(n)\n  let twice = n * 2\n  return twice\nend\nlet total = 0\nfor i in [1, 2, 3]\n  total = total + double(i)\nend\nlet done = true\n",
+        )
+        .unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        let output = SharedBuffer::default();
+        let reader = ChannelReader {
+            receiver,
+            pending: io::Cursor::new(Vec::new()),
+        };
+        let server_output = output.clone();
+        let server = thread::spawn(move || serve(io::BufReader::new(reader), server_output, None));
+        let mut client = Client {
+            sender,
+            output,
+            read: 0,
+            seq: 0,
+        };
+
+        let init = client.request("initialize", json!({ "adapterID": "soli" }));
+        assert_eq!(init["body"]["supportsConfigurationDoneRequest"], true);
+        client.request(
+            "launch",
+            json!({ "program": program.display().to_string() }),
+        );
+        let set = client.request(
+            "setBreakpoints",
+            json!({
+                "source": { "path": program.display().to_string() },
+                "breakpoints": [{ "line": 2, "condition": "n == 2" }, { "line": 9 }],
+            }),
+        );
+        assert_eq!(set["body"]["breakpoints"][0]["verified"], true);
+        client.request("configurationDone", json!({}));
+
+        // The conditional breakpoint skips the first call.
+        let stopped = client.wait_for(|m| m["event"] == "stopped");
+        assert_eq!(stopped["body"]["reason"], "breakpoint");
+        let trace = client.request("stackTrace", json!({ "threadId": 1 }));
+        let frames = &trace["body"]["stackFrames"];
+        assert_eq!(frames[0]["name"], "double");
+        assert_eq!(frames[0]["line"], 2);
+        assert_eq!(frames[1]["name"], "main");
+        assert_eq!(frames[1]["line"], 7);
+
+        let scopes = client.request("scopes", json!({ "frameId": frames[0]["id"] }));
+        let locals = scopes["body"]["scopes"][0]["variablesReference"].clone();
+        let variables = client.request("variables", json!({ "variablesReference": locals }));
+        assert!(variables["body"]["variables"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|v| v["name"] == "n" && v["value"] == "2"));
+        let watch = client.request(
+            "evaluate",
+            json!({ "expression": "total + i * 10", "frameId": frames[1]["id"], "context": "watch" }),
+        );
+        assert_eq!(watch["body"]["result"], "22", "{}", watch);
+
+        client.request("next", json!({ "threadId": 1 }));
+        let stopped = client.wait_for(|m| m["event"] == "stopped");
+        assert_eq!(stopped["body"]["reason"], "step");
+        let trace = client.request("stackTrace", json!({ "threadId": 1 }));
+        assert_eq!(trace["body"]["stackFrames"][0]["line"], 3);
+
+        client.request("stepOut", json!({ "threadId": 1 }));
+        client.wait_for(|m| m["event"] == "stopped");
+        let trace = client.request("stackTrace", json!({ "threadId": 1 }));
+        assert_eq!(trace["body"]["stackFrames"][0]["name"], "main");
+
+        client.request("continue", json!({ "threadId": 1 }));
+        client.wait_for(|m| m["event"] == "stopped");
+        let trace = client.request("stackTrace", json!({ "threadId": 1 }));
+        assert_eq!(trace["body"]["stackFrames"][0]["line"], 9);
+
+        client.request("continue", json!({ "threadId": 1 }));
+        let exited = client.wait_for(|m| m["event"] == "exited");
+        assert_eq!(exited["body"]["exitCode"], 0);
+        client.request("disconnect", json!({}));
+        server.join().unwrap().unwrap();
+    }
+}
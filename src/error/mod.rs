@@ -1,5 +1,9 @@
 //! Error types for all compilation phases.
 
+mod snippet;
+
+pub use snippet::render_snippet;
+
 use crate::span::Span;
 use thiserror::Error;
 
@@ -17,6 +21,9 @@ pub enum LexerError {
 
     #[error("Invalid number '{0}' at {1}")]
     InvalidNumber(String, Span),
+
+    #[error("Empty interpolation '#{{}}' at {0}")]
+    EmptyInterpolation(Span),
 }
 
 impl LexerError {
@@ -36,12 +43,29 @@ impl LexerError {
         Self::InvalidNumber(s, span)
     }
 
+    pub fn empty_interpolation(span: Span) -> Self {
+        Self::EmptyInterpolation(span)
+    }
+
     pub fn span(&self) -> Span {
         match self {
             Self::UnexpectedChar(_, span) => *span,
             Self::UnterminatedString(span) => *span,
             Self::InvalidEscape(_, span) => *span,
             Self::InvalidNumber(_, span) => *span,
+            Self::EmptyInterpolation(span) => *span,
+        }
+    }
+
+    /// The same error with its span rewritten — used when a fragment (an
+    /// interpolation's expression) is scanned on its own.
+    pub fn map_span(self, f: impl FnOnce(Span) -> Span) -> Self {
+        match self {
+            Self::UnexpectedChar(c, span) => Self::UnexpectedChar(c, f(span)),
+            Self::UnterminatedString(span) => Self::UnterminatedString(f(span)),
+            Self::InvalidEscape(c, span) => Self::InvalidEscape(c, f(span)),
+            Self::InvalidNumber(s, span) => Self::InvalidNumber(s, f(span)),
+            Self::EmptyInterpolation(span) => Self::EmptyInterpolation(f(span)),
         }
     }
 }
@@ -129,6 +153,9 @@ pub enum TypeError {
     #[error("Undefined type '{0}' at {1}")]
     UndefinedType(String, Span),
 
+    #[error("Cannot reassign constant '{0}' at {1}")]
+    ConstReassignment(String, Span),
+
     #[error("Undefined function '{0}' at {1}")]
     UndefinedFunction(String, Span),
 
@@ -161,17 +188,25 @@ pub enum TypeError {
     #[error("{message} at {span}")]
     General { message: String, span: Span },
 
-    /// Non-blocking warning: a `match` on a known enum doesn't cover every
-    /// variant and has no `_` catch-all. Surfaced by `soli check`; never fails
-    /// the check or blocks `soli run`.
+    /// Non-blocking warning: a `match` on a known enum, a `Bool` or an array
+    /// doesn't cover every case and has no `_` catch-all. Surfaced by
+    /// `soli check`; never fails the check or blocks `soli run`.
     #[error(
-        "warning: match on enum '{enum_name}' is not exhaustive at {span} — missing: {missing} (add them, or a `_ =>` arm)"
+        "warning: match on '{type_name}' is not exhaustive at {span} — missing: {missing} (add them, or a `_ =>` arm)"
     )]
     ExhaustivenessWarning {
-        enum_name: String,
+        type_name: String,
         missing: String,
         span: Span,
     },
+
+    /// An error with notes pointing at related code, such as the annotation
+    /// a value failed to match. Displays as the error it wraps.
+    #[error("{error}")]
+    WithNotes {
+        error: Box<TypeError>,
+        notes: Vec<(Span, String)>,
+    },
 }
 
 impl TypeError {
@@ -188,6 +223,7 @@ impl TypeError {
             Self::Mismatch { span, .. } => *span,
             Self::UndefinedVariable(_, span) => *span,
             Self::UndefinedType(_, span) => *span,
+            Self::ConstReassignment(_, span) => *span,
             Self::UndefinedFunction(_, span) => *span,
             Self::NotCallable(_, span) => *span,
             Self::WrongArity { span, .. } => *span,
@@ -197,8 +233,126 @@ impl TypeError {
             Self::SuperOutsideClass(span) => *span,
             Self::General { span, .. } => *span,
             Self::ExhaustivenessWarning { span, .. } => *span,
+            Self::WithNotes { error, .. } => error.span(),
+        }
+    }
+
+    /// Attach a note pointing at related code.
+    pub fn with_note(self, span: Span, note: impl Into<String>) -> Self {
+        match self {
+            Self::WithNotes { error, mut notes } => {
+                notes.push((span, note.into()));
+                Self::WithNotes { error, notes }
+            }
+            error => Self::WithNotes {
+                error: Box::new(error),
+                notes: vec![(span, note.into())],
+            },
+        }
+    }
+
+    /// The same error with every span, the notes' included, passed through
+    /// `f` — for code checked somewhere other than where it's written, like
+    /// a template's embedded code.
+    pub fn map_spans(self, f: &mut dyn FnMut(Span) -> Span) -> Self {
+        match self {
+            Self::Mismatch {
+                expected,
+                found,
+                span,
+            } => Self::Mismatch {
+                expected,
+                found,
+                span: f(span),
+            },
+            Self::UndefinedVariable(name, span) => Self::UndefinedVariable(name, f(span)),
+            Self::UndefinedType(name, span) => Self::UndefinedType(name, f(span)),
+            Self::ConstReassignment(name, span) => Self::ConstReassignment(name, f(span)),
+            Self::UndefinedFunction(name, span) => Self::UndefinedFunction(name, f(span)),
+            Self::NotCallable(name, span) => Self::NotCallable(name, f(span)),
+            Self::WrongArity {
+                expected,
+                got,
+                span,
+            } => Self::WrongArity {
+                expected,
+                got,
+                span: f(span),
+            },
+            Self::NoSuchMember {
+                type_name,
+                member,
+                span,
+            } => Self::NoSuchMember {
+                type_name,
+                member,
+                span: f(span),
+            },
+            Self::NoSuperclass(name, span) => Self::NoSuperclass(name, f(span)),
+            Self::ThisOutsideClass(span) => Self::ThisOutsideClass(f(span)),
+            Self::SuperOutsideClass(span) => Self::SuperOutsideClass(f(span)),
+            Self::General { message, span } => Self::General {
+                message,
+                span: f(span),
+            },
+            Self::ExhaustivenessWarning {
+                type_name,
+                missing,
+                span,
+            } => Self::ExhaustivenessWarning {
+                type_name,
+                missing,
+                span: f(span),
+            },
+            Self::WithNotes { error, notes } => Self::WithNotes {
+                error: Box::new(error.map_spans(f)),
+                notes: notes
+                    .into_iter()
+                    .map(|(span, note)| (f(span), note))
+                    .collect(),
+            },
+        }
+    }
+
+    /// The notes attached with [`TypeError::with_note`], in order.
+    pub fn notes(&self) -> &[(Span, String)] {
+        match self {
+            Self::WithNotes { notes, .. } => notes,
+            _ => &[],
+        }
+    }
+
+    /// The error itself, without the notes attached to it: match on this
+    /// to tell the kind of error.
+    pub fn without_notes(&self) -> &TypeError {
+        match self {
+            Self::WithNotes { error, .. } => error,
+            error => error,
+        }
+    }
+
+    /// The message without the trailing `at line:column`.
+    pub fn headline(&self) -> String {
+        let message = self.to_string();
+        let suffix = format!(" at {}", self.span());
+        match message.strip_suffix(&suffix) {
+            Some(head) => head.to_string(),
+            None => message,
         }
     }
+
+    /// The error as a source excerpt: the headline, `path:line:column`, the
+    /// offending code underlined and each note under the code it points at.
+    pub fn render(&self, source: &str, path: &str) -> String {
+        render_snippet(
+            "error",
+            &self.headline(),
+            source,
+            path,
+            self.span(),
+            self.notes(),
+        )
+    }
 }
 
 /// Bytecode compilation errors.
@@ -270,6 +424,13 @@ pub enum RuntimeError {
     #[error("{0} requires the interpreter at {1}")]
     EngineFallback(String, Span),
 
+    /// The evaluation ran past the time or memory budget it was given (see
+    /// `Interpreter::set_budget`). Like `EngineFallback`, NOT routed through
+    /// `try`/`catch`: the runaway code must not be able to swallow it and
+    /// keep going.
+    #[error("{0} at {1}")]
+    BudgetExceeded(String, Span),
+
     #[error("{message} at {span}")]
     General { message: String, span: Span },
 
@@ -296,6 +457,26 @@ pub enum RuntimeError {
         /// Stack trace captured at the moment of the error
         stack_trace: Vec<String>,
     },
+
+    /// A failure that wraps the lower-level error which triggered it (e.g.
+    /// "Failed to parse app/models/user.sl" caused by the parser error).
+    /// The cause stays typed so `report()` can render the whole chain.
+    #[error("{message}")]
+    Caused {
+        message: String,
+        span: Span,
+        #[source]
+        caused_by: Box<SolilangError>,
+    },
+
+    /// An error annotated with the context frames callers attached through
+    /// `with_context` ("while loading config/routes.sl"). Displays as the
+    /// wrapped error; the frames only show up in `report()`.
+    #[error("{error}")]
+    Context {
+        error: Box<RuntimeError>,
+        context: Vec<String>,
+    },
 }
 
 impl RuntimeError {
@@ -318,19 +499,13 @@ impl RuntimeError {
     /// Uses `contains` rather than `starts_with` so the marker survives
     /// error-wrapping layers (e.g. "Error calling method: __RecordNotFound__:...").
     pub fn is_record_not_found(&self) -> bool {
-        let msg = match self {
-            Self::General { message, .. } | Self::WithEnv { message, .. } => message.as_str(),
-            _ => return false,
-        };
-        msg.contains(Self::RECORD_NOT_FOUND_MARKER)
+        self.marker_message()
+            .is_some_and(|msg| msg.contains(Self::RECORD_NOT_FOUND_MARKER))
     }
 
     /// The user-facing message from a record-not-found error (marker stripped).
     pub fn record_not_found_message(&self) -> Option<String> {
-        let msg = match self {
-            Self::General { message, .. } | Self::WithEnv { message, .. } => message.as_str(),
-            _ => return None,
-        };
+        let msg = self.marker_message()?;
         msg.find(Self::RECORD_NOT_FOUND_MARKER)
             .map(|idx| msg[idx + Self::RECORD_NOT_FOUND_MARKER.len()..].to_string())
     }
@@ -344,14 +519,62 @@ impl RuntimeError {
     /// The user-facing message from a forbidden error (marker stripped).
     /// `Some` only when the error carries the forbidden marker.
     pub fn forbidden_message(&self) -> Option<String> {
-        let msg = match self {
-            Self::General { message, .. } | Self::WithEnv { message, .. } => message.as_str(),
-            _ => return None,
-        };
+        let msg = self.marker_message()?;
         msg.find(Self::FORBIDDEN_MARKER)
             .map(|idx| msg[idx + Self::FORBIDDEN_MARKER.len()..].to_string())
     }
 
+    /// Sentinels the test DSL's `skip(reason)` / `pending(reason)` raise to
+    /// end the running test early. The test runners look for them and
+    /// report the test as skipped or pending rather than failed.
+    pub const SKIP_TEST_MARKER: &'static str = "__SkipTest__:";
+    pub const PENDING_TEST_MARKER: &'static str = "__PendingTest__:";
+
+    /// The reason given to `skip(...)`, when this error is one (empty
+    /// without a reason).
+    pub fn skipped_test_reason(&self) -> Option<String> {
+        self.marker_payload(Self::SKIP_TEST_MARKER)
+    }
+
+    /// The reason given to `pending(...)`, when this error is one.
+    pub fn pending_test_reason(&self) -> Option<String> {
+        self.marker_payload(Self::PENDING_TEST_MARKER)
+    }
+
+    fn marker_payload(&self, marker: &str) -> Option<String> {
+        let msg = self.marker_message()?;
+        let mut payload = &msg[msg.find(marker)? + marker.len()..];
+        // Re-raised through a function call, the message picks up the
+        // " at line:col" of each frame's `Display`.
+        while let Some((rest, at)) = payload.rsplit_once(" at ") {
+            let is_position = at
+                .split_once(':')
+                .is_some_and(|(l, c)| l.parse::<usize>().is_ok() && c.parse::<usize>().is_ok());
+            if !is_position {
+                break;
+            }
+            payload = rest;
+        }
+        Some(payload.to_string())
+    }
+
+    /// The message the record-not-found / forbidden markers are embedded
+    /// in, looking through context frames.
+    fn marker_message(&self) -> Option<&str> {
+        match self.root() {
+            Self::General { message, .. } | Self::WithEnv { message, .. } => Some(message),
+            _ => None,
+        }
+    }
+
+    /// The error with any context frames peeled off.
+    fn root(&self) -> &RuntimeError {
+        match self {
+            Self::Context { error, .. } => error.root(),
+            _ => self,
+        }
+    }
+
     pub fn new(message: impl Into<String>, span: Span) -> Self {
         Self::General {
             message: message.into(),
@@ -398,25 +621,34 @@ impl RuntimeError {
             Self::NotAClass(_, span) => *span,
             Self::EngineFallback(_, span) => *span,
             Self::General { span, .. } => *span,
+            Self::BudgetExceeded(_, span) => *span,
             Self::Breakpoint { span, .. } => *span,
             Self::WithEnv { span, .. } => *span,
+            Self::Caused { span, .. } => *span,
+            Self::Context { error, .. } => error.span(),
         }
     }
 
     /// Check if this is a breakpoint error.
     pub fn is_breakpoint(&self) -> bool {
-        matches!(self, Self::Breakpoint { .. })
+        matches!(self.root(), Self::Breakpoint { .. })
     }
 
     /// True when the VM deliberately punted this operation to the
     /// tree-walking interpreter. Must bypass `try`/`rescue` routing.
     pub fn is_engine_fallback(&self) -> bool {
-        matches!(self, Self::EngineFallback(..))
+        matches!(self.root(), Self::EngineFallback(..))
+    }
+
+    /// True when the evaluation ran out of its time or memory budget. Must
+    /// bypass `try`/`catch` routing.
+    pub fn is_budget_exceeded(&self) -> bool {
+        matches!(self.root(), Self::BudgetExceeded(..))
     }
 
     /// Get the environment JSON from a breakpoint or WithEnv error.
     pub fn breakpoint_env_json(&self) -> Option<&str> {
-        match self {
+        match self.root() {
             Self::Breakpoint { env_json, .. } => Some(env_json),
             Self::WithEnv { env_json, .. } => Some(env_json),
             _ => None,
@@ -440,17 +672,75 @@ impl RuntimeError {
 
     /// Check if this error has captured environment
     pub fn has_captured_env(&self) -> bool {
-        matches!(self, Self::WithEnv { .. })
+        matches!(self.root(), Self::WithEnv { .. })
     }
 
     /// Get the stack trace from a breakpoint or WithEnv error.
     pub fn breakpoint_stack_trace(&self) -> Option<&[String]> {
-        match self {
+        match self.root() {
             Self::Breakpoint { stack_trace, .. } => Some(stack_trace),
             Self::WithEnv { stack_trace, .. } => Some(stack_trace),
             _ => None,
         }
     }
+
+    /// Wrap `cause` in a new error whose message describes what was being
+    /// attempted when it failed.
+    pub fn caused_by(
+        message: impl Into<String>,
+        span: Span,
+        cause: impl Into<SolilangError>,
+    ) -> Self {
+        Self::Caused {
+            message: message.into(),
+            span,
+            caused_by: Box::new(cause.into()),
+        }
+    }
+
+    /// Attach a context frame ("while parsing config/routes.sl"). Frames
+    /// accumulate innermost-first; the error itself (its `Display`, span
+    /// and markers) is unchanged.
+    pub fn with_context(self, frame: impl Into<String>) -> Self {
+        match self {
+            Self::Context { error, mut context } => {
+                context.push(frame.into());
+                Self::Context { error, context }
+            }
+            error => Self::Context {
+                error: Box::new(error),
+                context: vec![frame.into()],
+            },
+        }
+    }
+
+    /// The context frames attached to this error, innermost first.
+    pub fn context(&self) -> &[String] {
+        match self {
+            Self::Context { context, .. } => context,
+            _ => &[],
+        }
+    }
+
+    /// The lower-level error this one was caused by, if any.
+    pub fn cause(&self) -> Option<&SolilangError> {
+        match self.root() {
+            Self::Caused { caused_by, .. } => Some(caused_by),
+            _ => None,
+        }
+    }
+
+    /// The context frames and cause chain, one line per entry, for display
+    /// under the error message.
+    pub fn chain(&self) -> Vec<String> {
+        chain_lines(self.context(), self.cause())
+    }
+
+    /// The error message followed by its context frames and the chain of
+    /// causes — what the CLI prints instead of the bare `Display`.
+    pub fn report(&self) -> String {
+        render_report(&self.to_string(), &self.chain())
+    }
 }
 
 /// A unified error type for all phases.
@@ -465,6 +755,11 @@ pub enum SolilangError {
     #[error("Type error: {0}")]
     Type(#[from] TypeError),
 
+    /// Every error the type checker found, in the order it found them.
+    /// Displays as the first, with a count of the rest.
+    #[error("{}", type_errors_message(.0))]
+    TypeErrors(Vec<TypeError>),
+
     #[error("Compile error: {0}")]
     Compile(#[from] CompileError),
 
@@ -476,6 +771,113 @@ pub enum SolilangError {
 
     #[error("Template error: {0}")]
     Template(String),
+
+    /// An error annotated with context frames; see `RuntimeError::Context`.
+    #[error("{error}")]
+    Context {
+        error: Box<SolilangError>,
+        context: Vec<String>,
+    },
+}
+
+impl From<Vec<TypeError>> for SolilangError {
+    /// One error stays a plain [`SolilangError::Type`].
+    fn from(mut errors: Vec<TypeError>) -> Self {
+        if errors.len() == 1 {
+            Self::Type(errors.remove(0))
+        } else {
+            Self::TypeErrors(errors)
+        }
+    }
+}
+
+fn type_errors_message(errors: &[TypeError]) -> String {
+    let Some(first) = errors.first() else {
+        return "Type error".to_string();
+    };
+    match errors.len() {
+        1 => format!("Type error: {}", first),
+        2 => format!("Type error: {} (and 1 more type error)", first),
+        n => format!("Type error: {} (and {} more type errors)", first, n - 1),
+    }
+}
+
+impl SolilangError {
+    /// The type errors this error carries, if it is one.
+    pub fn type_errors(&self) -> &[TypeError] {
+        match self {
+            Self::Type(e) => std::slice::from_ref(e),
+            Self::TypeErrors(errors) => errors,
+            Self::Context { error, .. } => error.type_errors(),
+            _ => &[],
+        }
+    }
+
+    /// Attach a context frame. Runtime errors carry the frame themselves so
+    /// it survives a later conversion back to `RuntimeError`.
+    pub fn with_context(self, frame: impl Into<String>) -> Self {
+        match self {
+            Self::Runtime(e) => Self::Runtime(e.with_context(frame)),
+            Self::Context { error, mut context } => {
+                context.push(frame.into());
+                Self::Context { error, context }
+            }
+            error => Self::Context {
+                error: Box::new(error),
+                context: vec![frame.into()],
+            },
+        }
+    }
+
+    /// The context frames attached to this error, innermost first.
+    pub fn context(&self) -> &[String] {
+        match self {
+            Self::Runtime(e) => e.context(),
+            Self::Context { context, .. } => context,
+            _ => &[],
+        }
+    }
+
+    /// The lower-level error this one was caused by, if any.
+    pub fn cause(&self) -> Option<&SolilangError> {
+        match self {
+            Self::Runtime(e) => e.cause(),
+            Self::Context { error, .. } => error.cause(),
+            _ => None,
+        }
+    }
+
+    /// The context frames and cause chain, one line per entry.
+    pub fn chain(&self) -> Vec<String> {
+        chain_lines(self.context(), self.cause())
+    }
+
+    /// The error message followed by its context frames and cause chain.
+    pub fn report(&self) -> String {
+        render_report(&self.to_string(), &self.chain())
+    }
+}
+
+fn chain_lines(context: &[String], mut cause: Option<&SolilangError>) -> Vec<String> {
+    let mut lines: Vec<String> = context.to_vec();
+    while let Some(err) = cause {
+        lines.push(format!("Caused by: {}", err));
+        lines.extend(err.context().iter().cloned());
+        cause = err.cause();
+    }
+    lines
+}
+
+fn render_report(head: &str, chain: &[String]) -> String {
+    let mut out = head.to_string();
+    for line in chain {
+        out.push('\n');
+        if !line.starts_with("Caused by: ") {
+            out.push_str("  ");
+        }
+        out.push_str(line);
+    }
+    out
 }
 
 #[cfg(test)]
@@ -589,6 +991,7 @@ mod tests {
             },
             TypeError::UndefinedVariable("x".into(), s),
             TypeError::UndefinedType("Foo".into(), s),
+            TypeError::ConstReassignment("MAX".into(), s),
             TypeError::UndefinedFunction("f".into(), s),
             TypeError::NotCallable("Int".into(), s),
             TypeError::WrongArity {
@@ -683,6 +1086,8 @@ mod tests {
                 env_json: "{}".into(),
                 stack_trace: vec![],
             },
+            RuntimeError::caused_by("m", s, CompileError::new("c", span(9, 9))),
+            RuntimeError::division_by_zero(s).with_context("while testing"),
         ];
         for e in &cases {
             assert_eq!(e.span(), s, "span() mismatch for {e:?}");
@@ -812,6 +1217,80 @@ mod tests {
         assert!(e.breakpoint_stack_trace().is_none());
     }
 
+    // ---------- context frames + cause chain ----------
+
+    #[test]
+    fn with_context_keeps_display_and_stacks_frames() {
+        let e = RuntimeError::division_by_zero(span(1, 1))
+            .with_context("while calling total")
+            .with_context("while loading config/routes.sl");
+        assert_eq!(e.to_string(), "Division by zero at 1:1");
+        assert_eq!(
+            e.context(),
+            ["while calling total", "while loading config/routes.sl"]
+        );
+        assert!(matches!(&e, RuntimeError::Context { error, .. }
+            if matches!(**error, RuntimeError::DivisionByZero(_))));
+    }
+
+    #[test]
+    fn report_renders_context_and_cause_chain() {
+        let parse = ParserError::general("unexpected 'end'", span(3, 1));
+        let e = RuntimeError::caused_by("Failed to parse app/models/user.sl", span(1, 1), parse)
+            .with_context("while loading config/routes.sl");
+        assert_eq!(e.to_string(), "Failed to parse app/models/user.sl");
+        assert!(e.cause().is_some());
+        assert_eq!(
+            e.report(),
+            "Failed to parse app/models/user.sl\n  while loading config/routes.sl\n\
+             Caused by: Parser error: unexpected 'end' at 3:1"
+        );
+    }
+
+    #[test]
+    fn report_without_chain_is_display() {
+        let e = RuntimeError::new("plain", span(1, 1));
+        assert!(e.chain().is_empty());
+        assert_eq!(e.report(), e.to_string());
+    }
+
+    #[test]
+    fn cause_is_exposed_as_std_error_source() {
+        use std::error::Error;
+        let e =
+            RuntimeError::caused_by("outer", span(1, 1), CompileError::new("inner", span(1, 1)));
+        let source = e.source().expect("source");
+        assert!(source.to_string().contains("inner"));
+    }
+
+    #[test]
+    fn markers_and_flags_survive_context() {
+        let e = RuntimeError::record_not_found("user 42", span(1, 1)).with_context("while loading");
+        assert!(e.is_record_not_found());
+        assert_eq!(e.record_not_found_message().as_deref(), Some("user 42"));
+
+        let e = RuntimeError::EngineFallback("x".into(), span(1, 1)).with_context("ctx");
+        assert!(e.is_engine_fallback());
+
+        let e =
+            RuntimeError::with_env("boom", span(1, 1), "{}", vec!["f".into()]).with_context("ctx");
+        assert!(e.has_captured_env());
+        assert_eq!(e.breakpoint_env_json(), Some("{}"));
+        assert_eq!(e.breakpoint_stack_trace().map(|t| t.len()), Some(1));
+    }
+
+    #[test]
+    fn solilang_error_with_context_threads_runtime_frames() {
+        let e: SolilangError = RuntimeError::new("boom", span(1, 1)).into();
+        let e = e.with_context("while running main.sl");
+        assert!(matches!(e, SolilangError::Runtime(_)));
+        assert_eq!(e.context(), ["while running main.sl"]);
+
+        let e = SolilangError::Template("bad".into()).with_context("while rendering");
+        assert_eq!(e.to_string(), "Template error: bad");
+        assert_eq!(e.report(), "Template error: bad\n  while rendering");
+    }
+
     // =====================================================================
     // SolilangError From conversions + Display prefix
     // =====================================================================
@@ -0,0 +1,178 @@
+//! Plain-text source excerpts for diagnostics, in the familiar
+//! rustc/codespan layout:
+//!
+//! ```text
+//! error: Type mismatch: expected Int, found String
+//!  --> app/models/user.sl:3:11
+//!   |
+//! 2 |     let age: Int = 0
+//!   |              --- expected `Int` because of this annotation
+//! 3 |     age = "thirty"
+//!   |           ^^^^^^^^
+//! ```
+//!
+//! The primary span is underlined with `^`, each note with `-` and its text.
+//! Lines and columns are derived from the span's byte offsets into `source`,
+//! so the excerpt stays right even when a span's recorded line is stale.
+
+use crate::span::Span;
+
+/// Render `headline` and the lines `primary` and `notes` point at.
+pub fn render_snippet(
+    severity: &str,
+    headline: &str,
+    source: &str,
+    path: &str,
+    primary: Span,
+    notes: &[(Span, String)],
+) -> String {
+    let mut labels: Vec<Label> = Vec::with_capacity(notes.len() + 1);
+    labels.push(Label::new(source, primary, None));
+    for (span, text) in notes {
+        labels.push(Label::new(source, *span, Some(text)));
+    }
+
+    let (line, column) = (labels[0].line, labels[0].column);
+    let mut out = format!("{}: {}\n", severity, headline);
+
+    let mut lines: Vec<usize> = labels.iter().map(|label| label.line).collect();
+    lines.sort_unstable();
+    lines.dedup();
+    let gutter = lines.last().map_or(1, |n| n.to_string().len());
+    let pad = " ".repeat(gutter);
+
+    out.push_str(&format!("{}--> {}:{}:{}\n", pad, path, line, column));
+    out.push_str(&format!("{} |\n", pad));
+
+    let source_lines: Vec<&str> = source.lines().collect();
+    let mut previous: Option<usize> = None;
+    for &number in &lines {
+        let Some(text) = source_lines.get(number - 1) else {
+            continue;
+        };
+        if previous.is_some_and(|p| number > p + 1) {
+            out.push_str(&format!("{}...\n", pad));
+        }
+        previous = Some(number);
+        out.push_str(&format!("{:>gutter$} | {}\n", number, text));
+
+        // Primary first, then notes in the order they were attached.
+        for label in labels.iter().filter(|label| label.line == number) {
+            let indent: String = text
+                .chars()
+                .take(label.column - 1)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let marker = if label.text.is_some() { "-" } else { "^" };
+            let underline = marker.repeat(label.width);
+            match label.text {
+                Some(note) => {
+                    out.push_str(&format!("{} | {}{} {}\n", pad, indent, underline, note))
+                }
+                None => out.push_str(&format!("{} | {}{}\n", pad, indent, underline)),
+            }
+        }
+    }
+    out
+}
+
+struct Label<'a> {
+    line: usize,
+    column: usize,
+    width: usize,
+    text: Option<&'a str>,
+}
+
+impl<'a> Label<'a> {
+    fn new(source: &str, span: Span, text: Option<&'a String>) -> Self {
+        let start = floor_char_boundary(source, span.start_usize().min(source.len()));
+        let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |i| start + i);
+        let end = floor_char_boundary(source, span.end_usize().clamp(start, line_end));
+        Self {
+            line: source[..start].matches('\n').count() + 1,
+            column: source[line_start..start].chars().count() + 1,
+            width: source[start..end].chars().count().max(1),
+            text: text.map(String::as_str),
+        }
+    }
+}
+
+fn floor_char_boundary(source: &str, mut index: usize) -> usize {
+    while !source.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span_of(source: &str, needle: &str) -> Span {
+        let start = source.find(needle).unwrap();
+        Span::new(start, start + needle.len(), 0, 0)
+    }
+
+    #[test]
+    fn underlines_primary_span_and_labels_notes() {
+        let source = "fn main() {\n    let age: Int = 0\n    age = \"thirty\"\n}\n";
+        let rendered = render_snippet(
+            "error",
+            "Type mismatch: expected Int, found String",
+            source,
+            "main.sl",
+            span_of(source, "\"thirty\""),
+            &[(span_of(source, "Int"), "declared here".to_string())],
+        );
+        assert_eq!(
+            rendered,
+            "error: Type mismatch: expected Int, found String\n \
+             --> main.sl:3:11\n  \
+             |\n\
+             2 |     let age: Int = 0\n  \
+             |              --- declared here\n\
+             3 |     age = \"thirty\"\n  \
+             |           ^^^^^^^^\n"
+        );
+    }
+
+    #[test]
+    fn marks_skipped_lines_between_labels() {
+        let source = "const LIMIT = 10\n\n\nLIMIT = 11\n";
+        let rendered = render_snippet(
+            "error",
+            "Cannot reassign constant 'LIMIT'",
+            source,
+            "limits.sl",
+            span_of(source, "LIMIT = 11"),
+            &[(
+                span_of(source, "LIMIT"),
+                "declared as a constant here".to_string(),
+            )],
+        );
+        assert!(rendered.contains(" --> limits.sl:4:1\n"), "{}", rendered);
+        assert!(rendered.contains("1 | const LIMIT = 10\n"), "{}", rendered);
+        assert!(
+            rendered.contains("\n ...\n4 | LIMIT = 11\n"),
+            "{}",
+            rendered
+        );
+        assert!(rendered.ends_with("  | ^^^^^^^^^^\n"), "{}", rendered);
+    }
+
+    #[test]
+    fn tolerates_spans_outside_the_source() {
+        let rendered = render_snippet(
+            "error",
+            "Undefined variable 'x'",
+            "x\n",
+            "main.sl",
+            Span::new(40, 50, 9, 9),
+            &[],
+        );
+        assert!(rendered.starts_with("error: Undefined variable 'x'\n"));
+    }
+}
@@ -46,34 +46,7 @@ pub fn extract_comments(source: &str) -> Vec<Comment> {
 
         // String literal — skip its body so `#`/`//` inside don't register.
         if b == b'"' || b == b'\'' {
-            let quote = b;
-            i += 1;
-            col += 1;
-            while i < bytes.len() && bytes[i] != quote {
-                if bytes[i] == b'\\' && i + 1 < bytes.len() {
-                    // Skip an escape sequence.
-                    if bytes[i + 1] == b'\n' {
-                        line += 1;
-                        col = 1;
-                        i += 2;
-                        continue;
-                    }
-                    i += 2;
-                    col += 2;
-                    continue;
-                }
-                if bytes[i] == b'\n' {
-                    line += 1;
-                    col = 1;
-                } else {
-                    col += 1;
-                }
-                i += 1;
-            }
-            if i < bytes.len() {
-                i += 1;
-                col += 1;
-            }
+            i = skip_string(bytes, i, &mut line, &mut col);
             continue;
         }
 
@@ -242,6 +215,67 @@ pub fn extract_comments(source: &str) -> Vec<Comment> {
     out
 }
 
+/// Skip the string literal whose opening quote is at `i`, returning the
+/// index just past its closing quote. `#{...}` interpolations are skipped as
+/// code, so a string nested inside one (`"#{ok ? "a" : "b"}"`) doesn't end
+/// the outer literal early.
+fn skip_string(bytes: &[u8], mut i: usize, line: &mut usize, col: &mut usize) -> usize {
+    let quote = bytes[i];
+    i += 1;
+    *col += 1;
+    while i < bytes.len() && bytes[i] != quote {
+        match bytes[i] {
+            b'\\' if i + 1 < bytes.len() => {
+                // Skip an escape sequence.
+                if bytes[i + 1] == b'\n' {
+                    *line += 1;
+                    *col = 1;
+                } else {
+                    *col += 2;
+                }
+                i += 2;
+            }
+            b'#' if bytes.get(i + 1) == Some(&b'{') => {
+                i += 2;
+                *col += 2;
+                let mut depth = 1u32;
+                while i < bytes.len() && depth > 0 {
+                    match bytes[i] {
+                        b'"' | b'\'' => {
+                            i = skip_string(bytes, i, line, col);
+                            continue;
+                        }
+                        b'{' => depth += 1,
+                        b'}' => depth -= 1,
+                        _ => {}
+                    }
+                    if bytes[i] == b'\n' {
+                        *line += 1;
+                        *col = 1;
+                    } else {
+                        *col += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'\n' => {
+                *line += 1;
+                *col = 1;
+                i += 1;
+            }
+            _ => {
+                *col += 1;
+                i += 1;
+            }
+        }
+    }
+    if i < bytes.len() {
+        i += 1;
+        *col += 1;
+    }
+    i
+}
+
 fn starts_with(bytes: &[u8], at: usize, needle: &[u8]) -> bool {
     bytes.get(at..at + needle.len()) == Some(needle)
 }
@@ -287,6 +321,14 @@ mod tests {
         assert_eq!(cs[0].line, 2);
     }
 
+    #[test]
+    fn ignores_hash_inside_string_nested_in_interpolation() {
+        let src = "let s = \"a #{\"in #{n}\"} # b\" # real\n";
+        let cs = extract_comments(src);
+        assert_eq!(cs.len(), 1);
+        assert_eq!(cs[0].text, "# real");
+    }
+
     #[test]
     fn ignores_slashes_inside_string() {
         let src = "let url = \"http://example.com\"\n// real\n";
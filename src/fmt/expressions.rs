@@ -60,7 +60,8 @@ pub(super) fn ast_inline_width(source: &str, e: &Expr) -> usize {
                 w += match a {
                     Argument::Positional(x) => ast_inline_width(source, x),
                     Argument::Named(na) => na.name.len() + 2 + ast_inline_width(source, &na.value),
-                    Argument::Block(x) => 1 + ast_inline_width(source, x), // `&` prefix
+                    // `&` / `*` prefix
+                    Argument::Block(x) | Argument::Spread(x) => 1 + ast_inline_width(source, x),
                 };
             }
             w
@@ -183,40 +184,14 @@ impl Printer<'_> {
             }
             ExprKind::StringLiteral(s) => {
                 self.write("\"");
-                for c in s.chars() {
-                    match c {
-                        '\\' => self.write("\\\\"),
-                        '"' => self.write("\\\""),
-                        '\n' => self.write("\\n"),
-                        '\r' => self.write("\\r"),
-                        '\t' => self.write("\\t"),
-                        c => {
-                            let mut buf = [0u8; 4];
-                            self.write(c.encode_utf8(&mut buf));
-                        }
-                    }
-                }
+                self.write_string_body(s);
                 self.write("\"");
             }
             ExprKind::InterpolatedString(parts) => {
                 self.write("\"");
                 for part in parts {
                     match part {
-                        InterpolatedPart::Literal(s) => {
-                            for c in s.chars() {
-                                match c {
-                                    '\\' => self.write("\\\\"),
-                                    '"' => self.write("\\\""),
-                                    '\n' => self.write("\\n"),
-                                    '\r' => self.write("\\r"),
-                                    '\t' => self.write("\\t"),
-                                    c => {
-                                        let mut buf = [0u8; 4];
-                                        self.write(c.encode_utf8(&mut buf));
-                                    }
-                                }
-                            }
-                        }
+                        InterpolatedPart::Literal(s) => self.write_string_body(s),
                         InterpolatedPart::Expression(e) => {
                             self.write("#{");
                             self.print_expr(e);
@@ -252,7 +227,7 @@ impl Printer<'_> {
                     self.write("null");
                 }
             }
-            ExprKind::Variable(name) => self.write(name),
+            ExprKind::Variable(name, _) => self.write(name),
             ExprKind::This => self.write("this"),
             ExprKind::Super => self.write("super"),
             ExprKind::Binary {
@@ -275,18 +250,50 @@ impl Printer<'_> {
                 self.print_expr(inner);
                 self.write(")");
             }
+            ExprKind::Tuple(elements) => {
+                self.write("(");
+                for (i, elem) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.write(", ");
+                    }
+                    self.print_expr(elem);
+                }
+                if elements.len() == 1 {
+                    self.write(",");
+                }
+                self.write(")");
+            }
             ExprKind::Call { callee, arguments } => {
-                self.print_expr(callee);
-                // Preserve () for zero-arg calls so the linter can distinguish
-                // function calls (.all(), .keys()) from variable reads (.all).
-                // But don't ADD parens to bare DSL forms like `soft_delete`
-                // that were written without them in the source.
-                if arguments.is_empty()
-                    && !source_has_parens_after(self.source, callee.span.end_usize())
-                {
-                    // bare call, no source parens — skip "()"
+                // `x |> f(_, 2)` desugars to `f(x, 2)`; the piped argument is
+                // the only one whose source precedes the callee.
+                let piped = arguments.iter().position(
+                    |a| matches!(a, Argument::Positional(e) if e.span.start < callee.span.start),
+                );
+                if let Some(slot) = piped {
+                    if let Argument::Positional(value) = &arguments[slot] {
+                        self.print_expr(value);
+                        self.write(" |> ");
+                    }
+                    let mut rest = arguments.clone();
+                    rest[slot] = Argument::Positional(Expr::new(
+                        ExprKind::variable("_".to_string()),
+                        callee.span,
+                    ));
+                    self.print_expr(callee);
+                    self.print_arg_list(&rest);
                 } else {
-                    self.print_arg_list(arguments);
+                    self.print_expr(callee);
+                    // Preserve () for zero-arg calls so the linter can distinguish
+                    // function calls (.all(), .keys()) from variable reads (.all).
+                    // But don't ADD parens to bare DSL forms like `soft_delete`
+                    // that were written without them in the source.
+                    if arguments.is_empty()
+                        && !source_has_parens_after(self.source, callee.span.end_usize())
+                    {
+                        // bare call, no source parens — skip "()"
+                    } else {
+                        self.print_arg_list(arguments);
+                    }
                 }
             }
             ExprKind::Pipeline { left, right } => {
@@ -304,6 +311,11 @@ impl Printer<'_> {
                 {
                     self.write("@");
                     self.write(name);
+                } else if source_pipes_after(self.source, object.span.end_usize()) {
+                    // `x |> .trim()` desugars to `x.trim()`.
+                    self.print_expr(object);
+                    self.write(" |> .");
+                    self.write(name);
                 } else {
                     self.print_expr(object);
                     self.write(".");
@@ -488,7 +500,11 @@ impl Printer<'_> {
                 params,
                 return_type,
                 body,
+                is_move,
             } => {
+                if *is_move {
+                    self.write("move ");
+                }
                 // Prefer `fn(params) { body }` for non-block-param lambdas.
                 self.write("fn");
                 self.print_param_list(params);
@@ -586,7 +602,7 @@ impl Printer<'_> {
                 });
                 self.write("}");
             }
-            ExprKind::ListComprehension { .. } | ExprKind::HashComprehension { .. } => {
+            ExprKind::ListComprehension { .. } | ExprKind::HashComprehension(_) => {
                 self.write_source_span(expr.span.start_usize(), expr.span.end_usize());
             }
             ExprKind::Spread(inner) => {
@@ -597,6 +613,10 @@ impl Printer<'_> {
                 self.write("throw ");
                 self.print_expr(inner);
             }
+            ExprKind::Await(inner) => {
+                self.write("await ");
+                self.print_expr(inner);
+            }
             ExprKind::Rescue { expr, fallback } => {
                 // Estimate full inline width and break before `rescue` when
                 // it would push the line past MAX_LINE_LENGTH. Use
@@ -622,7 +642,27 @@ impl Printer<'_> {
         }
     }
 
-    fn print_arg_list(&mut self, args: &[Argument]) {
+    /// Write string contents re-escaped for a double-quoted literal. A
+    /// literal `#{` is written `\#{` so it doesn't turn into interpolation.
+    fn write_string_body(&mut self, s: &str) {
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => self.write("\\\\"),
+                '"' => self.write("\\\""),
+                '\n' => self.write("\\n"),
+                '\r' => self.write("\\r"),
+                '\t' => self.write("\\t"),
+                '#' if chars.peek() == Some(&'{') => self.write("\\#"),
+                c => {
+                    let mut buf = [0u8; 4];
+                    self.write(c.encode_utf8(&mut buf));
+                }
+            }
+        }
+    }
+
+    pub(super) fn print_arg_list(&mut self, args: &[Argument]) {
         let arg_count = args.len();
         // If the estimated inline width exceeds MAX_LINE_LENGTH, break
         // arguments across multiple lines so the formatter doesn't produce
@@ -662,7 +702,7 @@ impl Printer<'_> {
                 let (prefix, expr) = match a {
                     Argument::Positional(e) => (0, e),
                     Argument::Named(na) => (na.name.len() + 2, &na.value),
-                    Argument::Block(e) => (1, e),
+                    Argument::Block(e) | Argument::Spread(e) => (1, e),
                 };
                 prefix
                     + if super::statements::expr_likely_breaks(expr) {
@@ -717,6 +757,10 @@ impl Printer<'_> {
                 self.write("&");
                 self.print_block_arg_expr(e);
             }
+            Argument::Spread(e) => {
+                self.write("*");
+                self.print_expr(e);
+            }
         }
     }
 
@@ -733,6 +777,9 @@ impl Printer<'_> {
                         if p.is_block_param {
                             self.write("&");
                         }
+                        if p.is_variadic {
+                            self.write("...");
+                        }
                         self.write(&p.name);
                     }
                     self.write("|");
@@ -825,14 +872,15 @@ impl Printer<'_> {
             }
             MatchPattern::Literal(kind) => {
                 // Re-wrap into a temporary Expr to reuse the literal printer.
-                let tmp_expr = Expr {
-                    kind: kind.clone(),
-                    span: crate::span::Span::new(0, 0, 0, 0),
-                };
+                let tmp_expr = Expr::new(kind.clone(), crate::span::Span::new(0, 0, 0, 0));
                 self.print_expr(&tmp_expr);
             }
             MatchPattern::Array { elements, rest } => {
                 self.write("[");
+                // Same `[[` multiline-string hazard as array literals.
+                if matches!(elements.first(), Some(MatchPattern::Array { .. })) {
+                    self.write(" ");
+                }
                 for (i, el) in elements.iter().enumerate() {
                     if i > 0 {
                         self.write(", ");
@@ -848,6 +896,19 @@ impl Printer<'_> {
                 }
                 self.write("]");
             }
+            MatchPattern::Tuple(elements) => {
+                self.write("(");
+                for (i, el) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.write(", ");
+                    }
+                    self.print_match_pattern(el);
+                }
+                if elements.len() == 1 {
+                    self.write(",");
+                }
+                self.write(")");
+            }
             MatchPattern::Hash { fields, rest } => {
                 self.write("{");
                 for (i, (name, pat)) in fields.iter().enumerate() {
@@ -941,6 +1002,15 @@ fn source_has_parens_after(source: &str, at: usize) -> bool {
     bytes.get(i) == Some(&b'(')
 }
 
+/// True when the next non-whitespace source bytes after `at` are `|>` —
+/// marks a `value |> .method()` step, which shares an AST node with
+/// `value.method()`.
+fn source_pipes_after(source: &str, at: usize) -> bool {
+    source
+        .get(at.min(source.len())..)
+        .is_some_and(|rest| rest.trim_start().starts_with("|>"))
+}
+
 /// True when the source byte at `at` is `@` — used to distinguish the
 /// `@name` instance-var sigil from `this.name` (they share an AST node).
 fn source_starts_with_at(source: &str, at: usize) -> bool {
@@ -17,7 +17,7 @@
 use crate::lexer::Scanner;
 use crate::parser::Parser;
 
-mod comments;
+pub(crate) mod comments;
 mod expressions;
 mod printer;
 mod statements;
@@ -50,6 +50,7 @@ pub fn format_source(source: &str) -> Result<String, FmtError> {
         .scan_tokens()
         .map_err(|e| FmtError::Lex(format!("{:?}", e)))?;
     let program = Parser::new(tokens)
+        .keeping_platform_code()
         .parse()
         .map_err(|e| FmtError::Parse(format!("{:?}", e)))?;
     let comments = comments::extract_comments(source);
@@ -57,3 +58,23 @@ pub fn format_source(source: &str) -> Result<String, FmtError> {
     printer.print_program(&program);
     Ok(printer.finish())
 }
+
+/// Print one statement the way `soli fmt` would, with the given comments
+/// interleaved. `source` is the text the statement's spans point into. Used by
+/// `soli codemod` to re-print the statements a codemod rewrote.
+pub(crate) fn format_stmt(
+    stmt: &crate::ast::Stmt,
+    source: &str,
+    comments: Vec<comments::Comment>,
+) -> String {
+    let mut printer = Printer::new(source, comments);
+    printer.print_stmt(stmt);
+    printer.finish()
+}
+
+/// Print one expression the way `soli fmt` would. Comments are not kept.
+pub(crate) fn format_expr(expr: &crate::ast::Expr, source: &str) -> String {
+    let mut printer = Printer::new(source, Vec::new());
+    printer.print_expr(expr);
+    printer.finish()
+}
@@ -416,7 +416,7 @@ fn ends_in_expression(stmt: &Stmt) -> bool {
         StmtKind::Let { initializer, .. } => initializer.is_some(),
         StmtKind::Const { .. } => true,
         StmtKind::Return(opt) => opt.is_some(),
-        StmtKind::Throw(_) => true,
+        StmtKind::Throw(_) | StmtKind::Yield(_) => true,
         // Postfix `if`/`unless` lower to StmtKind::If but the printed form
         // ends with an expression too. Block-form `if/while/for/try/fn/class`
         // end with `end`, safe.
@@ -426,7 +426,7 @@ fn ends_in_expression(stmt: &Stmt) -> bool {
             ..
         } => matches!(
             &then_branch.kind,
-            StmtKind::Expression(_) | StmtKind::Return(_) | StmtKind::Throw(_)
+            StmtKind::Expression(_) | StmtKind::Return(_) | StmtKind::Throw(_) | StmtKind::Yield(_)
         ),
         _ => false,
     }
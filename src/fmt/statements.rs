@@ -86,8 +86,9 @@ fn starts_with_keyword(bytes: &[u8], at: usize, kw: &[u8]) -> bool {
 }
 
 use crate::ast::stmt::{
-    CatchClause, ClassDecl, ConstructorDecl, EnumDecl, FieldDecl, FunctionDecl, ImportDecl,
-    ImportSpecifier, InterfaceDecl, MethodDecl, Parameter, Stmt, StmtKind, Visibility,
+    CatchClause, ClassDecl, ConstructorDecl, Decorator, EnumDecl, FieldDecl, FunctionDecl,
+    ImportDecl, ImportSpecifier, InterfaceDecl, MethodDecl, Parameter, Stmt, StmtKind, StructDecl,
+    Visibility,
 };
 
 use super::printer::Printer;
@@ -133,6 +134,7 @@ impl Printer<'_> {
                 name,
                 type_annotation,
                 initializer,
+                ..
             } => {
                 self.write("let ");
                 self.write(name);
@@ -146,6 +148,22 @@ impl Printer<'_> {
                 }
                 self.newline();
             }
+            StmtKind::LetTuple {
+                names,
+                type_annotation,
+                initializer,
+            } => {
+                self.write("let (");
+                self.write(&names.join(", "));
+                self.write(")");
+                if let Some(ty) = type_annotation {
+                    self.write(": ");
+                    self.write(&format_type(ty));
+                }
+                self.write(" = ");
+                self.print_expr(initializer);
+                self.newline();
+            }
             StmtKind::Const {
                 name,
                 type_annotation,
@@ -207,11 +225,19 @@ impl Printer<'_> {
             StmtKind::For {
                 variable,
                 index_variable,
+                destructure,
                 iterable,
                 body,
             } => {
                 self.write("for ");
-                self.write(variable);
+                match destructure {
+                    Some(names) => {
+                        self.write("(");
+                        self.write(&names.join(", "));
+                        self.write(")");
+                    }
+                    None => self.write(variable),
+                }
                 if let Some(idx) = index_variable {
                     self.write(", ");
                     self.write(idx);
@@ -240,6 +266,11 @@ impl Printer<'_> {
                 self.print_expr(expr);
                 self.newline();
             }
+            StmtKind::Yield(expr) => {
+                self.write("yield ");
+                self.print_expr(expr);
+                self.newline();
+            }
             StmtKind::Try {
                 try_block,
                 catch_clauses,
@@ -259,15 +290,43 @@ impl Printer<'_> {
                 self.write("end");
                 self.newline();
             }
-            StmtKind::Function(decl) => self.print_function_decl(decl, false),
-            StmtKind::Class(decl) => self.print_class_decl(decl),
+            StmtKind::Function(decl) => {
+                self.print_decorators(&decl.decorators);
+                self.print_function_decl(decl, false);
+            }
+            StmtKind::Class(decl) => {
+                self.print_decorators(&decl.decorators);
+                self.print_class_decl(decl);
+            }
             StmtKind::Enum(decl) => self.print_enum_decl(decl),
+            StmtKind::Struct(decl) => self.print_struct_decl(decl),
             StmtKind::Interface(decl) => self.print_interface_decl(decl),
-            StmtKind::Import(decl) => self.print_import_decl(decl),
-            StmtKind::Export(inner) => {
-                self.write("export ");
-                self.print_stmt(inner);
+            StmtKind::TypeAlias(decl) => {
+                self.write(&format!(
+                    "type {} = {}",
+                    decl.name,
+                    format_type(&decl.type_annotation)
+                ));
+                self.newline();
             }
+            StmtKind::Import(decl) => self.print_import_decl(decl),
+            // Decorators of an exported declaration go above the `export`.
+            StmtKind::Export(inner) => match &inner.kind {
+                StmtKind::Function(decl) => {
+                    self.print_decorators(&decl.decorators);
+                    self.write("export ");
+                    self.print_function_decl(decl, false);
+                }
+                StmtKind::Class(decl) => {
+                    self.print_decorators(&decl.decorators);
+                    self.write("export ");
+                    self.print_class_decl(decl);
+                }
+                _ => {
+                    self.write("export ");
+                    self.print_stmt(inner);
+                }
+            },
         }
         self.flush_trailing_comments_on(stmt.span.line_usize());
         let end_line = super::printer::source_end_line(self.source, stmt.span);
@@ -280,7 +339,7 @@ impl Printer<'_> {
     ///
     /// Conditions for rewrite:
     /// - No `else` / `elsif` branch (postfix has no else form)
-    /// - Body is a single Return / Throw / Expression statement
+    /// - Body is a single Return / Throw / Yield / Expression statement
     /// - No comments anywhere inside the block (they'd be detached)
     /// - The resulting postfix line fits within `MAX_LINE_LENGTH`
     fn guard_clause_to_rewrite<'s>(
@@ -299,7 +358,7 @@ impl Printer<'_> {
         };
         if !matches!(
             inner.kind,
-            StmtKind::Return(_) | StmtKind::Throw(_) | StmtKind::Expression(_)
+            StmtKind::Return(_) | StmtKind::Throw(_) | StmtKind::Yield(_) | StmtKind::Expression(_)
         ) {
             return None;
         }
@@ -356,6 +415,10 @@ impl Printer<'_> {
                 self.write("throw ");
                 self.print_expr(e);
             }
+            StmtKind::Yield(e) => {
+                self.write("yield ");
+                self.print_expr(e);
+            }
             StmtKind::Break => self.write("break"),
             // Block-bodied postfix should not exist (parser wraps a single
             // statement in Expression/Return/Throw). Fall back to recursing
@@ -516,6 +579,9 @@ impl Printer<'_> {
 
     pub(super) fn print_function_decl(&mut self, decl: &FunctionDecl, is_method: bool) {
         let keyword = if is_method { "def" } else { "fn" };
+        if decl.is_async {
+            self.write("async ");
+        }
         self.write(keyword);
         self.write(" ");
         self.write(&decl.name);
@@ -539,11 +605,14 @@ impl Printer<'_> {
     }
 
     fn print_method_decl(&mut self, decl: &MethodDecl) {
+        self.print_decorators(&decl.decorators);
         if decl.is_static {
-            self.write("static def ");
-        } else {
-            self.write("def ");
+            self.write("static ");
         }
+        if decl.is_async {
+            self.write("async ");
+        }
+        self.write("def ");
         self.write(&decl.name);
         // Drop empty parens (`def run()` -> `def run`), matching Soli's
         // optional-parens convention for no-arg definitions. Keep them when
@@ -562,6 +631,18 @@ impl Printer<'_> {
         self.newline();
     }
 
+    /// `@name` / `@name(args)`, one per line above the declaration.
+    fn print_decorators(&mut self, decorators: &[Decorator]) {
+        for decorator in decorators {
+            self.write("@");
+            self.write(&decorator.name);
+            if !decorator.arguments.is_empty() {
+                self.print_arg_list(&decorator.arguments);
+            }
+            self.newline();
+        }
+    }
+
     fn print_constructor_decl(&mut self, decl: &ConstructorDecl) {
         self.write("new");
         self.print_param_list(&decl.params);
@@ -579,7 +660,9 @@ impl Printer<'_> {
         let est: usize = params
             .iter()
             .map(|p| {
-                let mut w = p.name.len() + if p.is_block_param { 1 } else { 0 };
+                let mut w = p.name.len()
+                    + if p.is_block_param { 1 } else { 0 }
+                    + if p.is_variadic { 3 } else { 0 };
                 let ty = format_type(&p.type_annotation);
                 if !ty.is_empty() && ty != "Any" {
                     w += 2 + ty.len();
@@ -591,6 +674,13 @@ impl Printer<'_> {
             })
             .sum::<usize>()
             + 2;
+        // The bare `*` that starts the keyword-only parameters, unless a
+        // `...rest` before them already does.
+        let star_at = params
+            .iter()
+            .position(|p| p.is_keyword_only)
+            .filter(|_| !params.iter().any(|p| p.is_variadic));
+        let est = est + if star_at.is_some() { 3 } else { 0 };
         if params.len() > 1 && self.current_column() + est > MAX_LINE_LENGTH {
             self.write("(");
             self.newline();
@@ -600,6 +690,10 @@ impl Printer<'_> {
                         p.write(",");
                         p.newline();
                     }
+                    if star_at == Some(i) {
+                        p.write("*,");
+                        p.newline();
+                    }
                     p.write_param(param);
                 }
             });
@@ -611,6 +705,9 @@ impl Printer<'_> {
                 if i > 0 {
                     self.write(", ");
                 }
+                if star_at == Some(i) {
+                    self.write("*, ");
+                }
                 self.write_param(p);
             }
             self.write(")");
@@ -621,6 +718,9 @@ impl Printer<'_> {
         if p.is_block_param {
             self.write("&");
         }
+        if p.is_variadic {
+            self.write("...");
+        }
         self.write(&p.name);
         let ty_str = format_type(&p.type_annotation);
         if !ty_str.is_empty() && ty_str != "Any" {
@@ -710,6 +810,7 @@ impl Printer<'_> {
                         p.blank_line();
                     }
                     p.flush_comments_before(n.span.line_usize());
+                    p.print_decorators(&n.decorators);
                     p.print_class_decl(n);
                 }
             }
@@ -762,6 +863,29 @@ impl Printer<'_> {
         self.newline();
     }
 
+    fn print_struct_decl(&mut self, decl: &StructDecl) {
+        self.write("struct ");
+        self.write(&decl.name);
+        self.newline();
+        self.with_indent(|p| {
+            for field in &decl.fields {
+                p.flush_comments_before(field.span.line_usize());
+                p.write(&field.name);
+                if let Some(ty) = &field.type_annotation {
+                    p.write(": ");
+                    p.write(&format_type(ty));
+                }
+                if let Some(default) = &field.default_value {
+                    p.write(" = ");
+                    p.print_expr(default);
+                }
+                p.newline();
+            }
+        });
+        self.write("end");
+        self.newline();
+    }
+
     fn print_field_decl(&mut self, field: &FieldDecl) {
         match field.visibility {
             Visibility::Public => {}
@@ -910,6 +1034,7 @@ pub(super) fn expr_likely_breaks(e: &Expr) -> bool {
         | ExprKind::Grouping(operand)
         | ExprKind::Spread(operand)
         | ExprKind::Throw(operand)
+        | ExprKind::Await(operand)
         | ExprKind::Member {
             object: operand, ..
         }
@@ -931,7 +1056,7 @@ pub(super) fn expr_likely_breaks(e: &Expr) -> bool {
                 || arguments.iter().any(|a| match a {
                     Argument::Positional(x) => expr_likely_breaks(x),
                     Argument::Named(na) => expr_likely_breaks(&na.value),
-                    Argument::Block(x) => expr_likely_breaks(x),
+                    Argument::Block(x) | Argument::Spread(x) => expr_likely_breaks(x),
                 })
         }
         ExprKind::Rescue { expr, fallback } => {
@@ -945,9 +1070,10 @@ pub(super) fn expr_likely_breaks(e: &Expr) -> bool {
 /// inner statement (`return`, `throw`, or a bare expression).
 fn expr_in_stmt_likely_breaks(s: &Stmt) -> bool {
     match &s.kind {
-        StmtKind::Return(Some(e)) | StmtKind::Throw(e) | StmtKind::Expression(e) => {
-            expr_likely_breaks(e)
-        }
+        StmtKind::Return(Some(e))
+        | StmtKind::Throw(e)
+        | StmtKind::Yield(e)
+        | StmtKind::Expression(e) => expr_likely_breaks(e),
         StmtKind::Return(None) => false,
         _ => true,
     }
@@ -127,11 +127,36 @@ fn call_with_named_args() {
     );
 }
 
+#[test]
+fn keyword_only_marker_is_kept() {
+    assert_fmt(
+        "fn connect(host,*,port=5432,tls)\nhost\nend\n",
+        "fn connect(host, *, port = 5432, tls)\n  host\nend\n",
+    );
+}
+
+#[test]
+fn variadic_params_and_spread_args_are_kept() {
+    assert_fmt(
+        "fn log(level,...parts,sep=\" \")\nwrite(level,*parts)\nend\n",
+        "fn log(level, ...parts, sep = \" \")\n  write(level, *parts)\nend\n",
+    );
+}
+
 #[test]
 fn lambda_inline_form() {
     assert_fmt("let f=fn(x){return x*2}\n", "let f = fn(x) { x * 2 }\n");
 }
 
+#[test]
+fn move_lambda_keeps_move() {
+    assert_fmt(
+        "let f=move fn(x){return x*k}\n",
+        "let f = move fn(x) { x * k }\n",
+    );
+    assert_idempotent("let r = [1, 2].map(move |x| x * k)\n");
+}
+
 #[test]
 fn idempotent_controller_sample() {
     let src = "# A controller\nclass PostsController < Controller\n  def index(req)\n    let posts = Post.all()\n    return render(\"posts/index\", {\"posts\": posts})\n  end\nend\n";
@@ -156,6 +181,17 @@ fn idempotent_match_expression() {
     assert_idempotent(src);
 }
 
+#[test]
+fn idempotent_match_binding_patterns() {
+    // Typed bindings print as `n: Int`, which must parse back as a typed
+    // binding, and a nested array pattern must not open with `[[`.
+    let src = "fn label(v)\n  match v {\n    n: Int => n,\n    [ [a, b], ...rest] => a,\n    _ => 0,\n  }\nend\n";
+    let once = format_source(src).expect("format_source failed");
+    assert!(once.contains("n: Int =>"), "got: {once}");
+    assert!(once.contains("[ [a, b], ...rest]"), "got: {once}");
+    assert_idempotent(src);
+}
+
 #[test]
 fn idempotent_test_with_inline_lambda_assertion() {
     // Regression: `test("...", fn() { assert_eq(a, b) })` used to oscillate
@@ -221,6 +257,14 @@ fn string_interpolation_idempotent() {
     assert_idempotent("let x = 1\nlet s = \"x=#{x}\"\n");
 }
 
+#[test]
+fn string_interpolation_keeps_escaped_hash_brace_literal() {
+    assert_fmt(
+        "let a = \"\\#{x}\"\nlet b = \"#{x} and \\#{y}\"\n",
+        "let a = \"\\#{x}\"\nlet b = \"#{x} and \\#{y}\"\n",
+    );
+}
+
 // ---- Bug 2: ternary `?:` must round-trip as `?:`, not `if-then-else` ----
 
 #[test]
@@ -470,3 +514,26 @@ xs.each(|x| print(x))
     assert_round_trip(src);
     assert_idempotent(src);
 }
+
+#[test]
+fn pipeline_placeholder_and_method_steps_are_preserved() {
+    assert_fmt(
+        "let a = x |> add(1, _)\nlet b = s |> .trim() |> .upcase()\n",
+        "let a = x |> add(1, _)\nlet b = s |> .trim() |> .upcase()\n",
+    );
+    assert_idempotent("let c = \"a,b\"\n  |> .split(\",\")\n  |> .join(\"-\")\n");
+}
+
+#[test]
+fn async_declarations_and_await_round_trip() {
+    let src = "async fn load(id)\n  await fetch(id)\nend\n\nclass Api\n  static async def all()\n    await [load(1), load(2)]\n  end\nend\n";
+    assert_round_trip(src);
+    assert_idempotent(src);
+}
+
+#[test]
+fn decorators_print_above_their_declarations() {
+    let src = "@cached\n@route(\"/users\", method: \"GET\")\nexport fn index(req)\n  req\nend\n\n@route(\"/admin\")\nclass Admin\n  @deprecated(\"old\")\n  def stats()\n    1\n  end\nend\n";
+    assert_round_trip(src);
+    assert_idempotent(src);
+}
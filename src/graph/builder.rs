@@ -23,7 +23,7 @@
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
-use crate::ast::expr::{Argument, Expr, ExprKind};
+use crate::ast::expr::{Argument, Expr, ExprKind, HashComprehension};
 use crate::ast::stmt::{
     ClassDecl, EnumDecl, FunctionDecl, InterfaceDecl, MethodDecl, Program, Stmt, StmtKind,
 };
@@ -777,7 +777,7 @@ impl GraphBuilder {
         let ExprKind::Call { callee, arguments } = &expr.kind else {
             return;
         };
-        let ExprKind::Variable(dsl) = &callee.kind else {
+        let ExprKind::Variable(dsl, _) = &callee.kind else {
             return;
         };
         let line = expr.span.line;
@@ -979,6 +979,7 @@ impl GraphBuilder {
                 name,
                 type_annotation,
                 initializer,
+                ..
             } => {
                 if let Some(e) = initializer {
                     self.walk_expr(e, ctx, locals);
@@ -988,7 +989,7 @@ impl GraphBuilder {
                 // A re-declaration with no inferable class clears any prior
                 // binding, so a shadowed name never keeps a stale type.
                 let inferred = type_annotation
-                    .as_ref()
+                    .as_deref()
                     .and_then(named_class_type)
                     .filter(|c| self.class_by_name.contains_key(c))
                     .or_else(|| initializer.as_ref().and_then(|e| self.class_from_expr(e)));
@@ -1001,7 +1002,7 @@ impl GraphBuilder {
             } => {
                 self.walk_expr(initializer, ctx, locals);
                 let inferred = type_annotation
-                    .as_ref()
+                    .as_deref()
                     .and_then(named_class_type)
                     .filter(|c| self.class_by_name.contains_key(c))
                     .or_else(|| self.class_from_expr(initializer));
@@ -1036,7 +1037,7 @@ impl GraphBuilder {
                 self.walk_stmt(body, ctx, &mut nested);
             }
             StmtKind::Return(Some(e)) => self.walk_expr(e, ctx, locals),
-            StmtKind::Throw(e) => self.walk_expr(e, ctx, locals),
+            StmtKind::Throw(e) | StmtKind::Yield(e) => self.walk_expr(e, ctx, locals),
             StmtKind::Try {
                 try_block,
                 catch_clauses,
@@ -1100,6 +1101,7 @@ impl GraphBuilder {
             ExprKind::Grouping(e)
             | ExprKind::Spread(e)
             | ExprKind::Throw(e)
+            | ExprKind::Await(e)
             | ExprKind::PostfixIncrement(e)
             | ExprKind::PostfixDecrement(e) => self.walk_expr(e, ctx, locals),
             ExprKind::Member { object, .. } | ExprKind::SafeMember { object, .. } => {
@@ -1116,7 +1118,7 @@ impl GraphBuilder {
                 // Reassigning a bare local updates (or clears) its tracked type
                 // so a later `local.method()` never resolves against a class the
                 // variable no longer holds.
-                if let ExprKind::Variable(name) = &target.kind {
+                if let ExprKind::Variable(name, _) = &target.kind {
                     let inferred = self.class_from_expr(value);
                     bind_local(locals, name, inferred);
                 }
@@ -1183,13 +1185,14 @@ impl GraphBuilder {
                     self.walk_expr(c, ctx, locals);
                 }
             }
-            ExprKind::HashComprehension {
-                key,
-                value,
-                iterable,
-                condition,
-                ..
-            } => {
+            ExprKind::HashComprehension(comp) => {
+                let HashComprehension {
+                    key,
+                    value,
+                    iterable,
+                    condition,
+                    ..
+                } = &**comp;
                 self.walk_expr(key, ctx, locals);
                 self.walk_expr(value, ctx, locals);
                 self.walk_expr(iterable, ctx, locals);
@@ -1212,7 +1215,9 @@ impl GraphBuilder {
         locals: &mut HashMap<String, String>,
     ) {
         match arg {
-            Argument::Positional(e) | Argument::Block(e) => self.walk_expr(e, ctx, locals),
+            Argument::Positional(e) | Argument::Block(e) | Argument::Spread(e) => {
+                self.walk_expr(e, ctx, locals)
+            }
             Argument::Named(named) => self.walk_expr(&named.value, ctx, locals),
         }
     }
@@ -1233,7 +1238,7 @@ impl GraphBuilder {
                 ExprKind::Member { object, name }
                     if CLASS_INSTANCE_FACTORIES.contains(&name.as_str()) =>
                 {
-                    if let ExprKind::Variable(recv) = &object.kind {
+                    if let ExprKind::Variable(recv, _) = &object.kind {
                         if self.class_by_name.contains_key(recv) {
                             return Some(recv.clone());
                         }
@@ -1260,7 +1265,9 @@ impl GraphBuilder {
     ) {
         match &callee.kind {
             // `render("view", ...)` / `partial("view", ...)` inside a controller.
-            ExprKind::Variable(name) if name == "render" || name == "partial" => {
+            ExprKind::Variable(name, _)
+                if name == "render" || name == "render_stream" || name == "partial" =>
+            {
                 if let Some(view) = render_target(arguments, ctx.render_prefix.as_deref()) {
                     if let Some(to) = self.resolve_view_id(&view) {
                         self.push_edge(&ctx.caller_id, &to, "renders", "", &ctx.relpath, line);
@@ -1268,7 +1275,7 @@ impl GraphBuilder {
                 }
             }
             // `redirect("/path")` — deferred until routes are indexed.
-            ExprKind::Variable(name) if name == "redirect" => {
+            ExprKind::Variable(name, _) if name == "redirect" => {
                 if let Some(path) = first_string_or_symbol(arguments) {
                     self.deferred_redirects.push((
                         ctx.caller_id.clone(),
@@ -1290,7 +1297,7 @@ impl GraphBuilder {
                 }
             }
             // Bare `foo(...)` — a project function if there's exactly one.
-            ExprKind::Variable(name) => {
+            ExprKind::Variable(name, _) => {
                 if let Some(ids) = self.functions_by_name.get(name) {
                     match ids.len() {
                         1 => {
@@ -1305,7 +1312,7 @@ impl GraphBuilder {
             // `Klass.method(...)` (static), `this.method(...)`,
             // `local.method(...)` (typed local), or `super.method(...)`.
             ExprKind::Member { object, name } => match &object.kind {
-                ExprKind::Variable(recv) if self.class_by_name.contains_key(recv) => {
+                ExprKind::Variable(recv, _) if self.class_by_name.contains_key(recv) => {
                     let target = format!("method:{}#{}", recv, name);
                     if self.has_node(&target) {
                         self.push_edge(&ctx.caller_id, &target, "calls", "", &ctx.relpath, line);
@@ -1313,7 +1320,7 @@ impl GraphBuilder {
                         self.push_edge(&ctx.caller_id, &class_id, "calls", "", &ctx.relpath, line);
                     }
                 }
-                ExprKind::Variable(recv) => {
+                ExprKind::Variable(recv, _) => {
                     // Instance call on a locally-typed variable — method only
                     // (no class fallback; avoids inventing edges for Hashes).
                     if let Some(class) = locals.get(recv) {
@@ -1691,7 +1698,7 @@ fn named_string_arg(arguments: &[Argument], key: &str) -> Option<String> {
 /// The class name for `new X(...)` / `new Outer::Inner(...)`.
 fn simple_name(expr: &Expr) -> Option<String> {
     match &expr.kind {
-        ExprKind::Variable(name) => Some(name.clone()),
+        ExprKind::Variable(name, _) => Some(name.clone()),
         ExprKind::QualifiedName { name, .. } => Some(name.clone()),
         _ => None,
     }
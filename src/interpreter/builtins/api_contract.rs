@@ -0,0 +1,688 @@
+//! `soli test --contract`: check every request-spec response against the
+//! app's OpenAPI document.
+//!
+//! The test servers run with `SOLI_OPENAPI=1`, so each one serves the same
+//! `/openapi.json` the app would publish (generated routes + the
+//! `config/openapi.*` overlay). The runner fetches it once per server and
+//! `request_helpers::http_request` hands every response to [`check_response`],
+//! which fails the spec with one `at: expected …, got …` line per drift:
+//! undocumented operations, status codes, content types and JSON body shapes.
+//!
+//! Operations whose responses are still the generator's placeholder `200` are
+//! only checked for existence — there is nothing documented to drift from yet.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde_json::Value as Json;
+
+use crate::serve::openapi::{CATCH_ALL, GENERATED_RESPONSES, OVERLAY_ERROR};
+
+static CONTRACT_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Arm `--contract` checking for this (test-runner) process.
+pub fn enable_contract_mode() {
+    CONTRACT_MODE.store(true, Ordering::SeqCst);
+}
+
+pub(crate) fn contract_mode_enabled() -> bool {
+    CONTRACT_MODE.load(Ordering::Relaxed)
+}
+
+/// Drift lines past this are summarized, so one wrong array element repeated
+/// across a 500-row index doesn't bury the report.
+const MAX_DRIFTS: usize = 20;
+/// Guard against self-referencing `$ref` cycles.
+const MAX_DEPTH: usize = 64;
+
+/// One place where the response disagrees with the document.
+#[derive(Debug, Clone, PartialEq)]
+struct Drift {
+    at: String,
+    expected: String,
+    actual: String,
+}
+
+impl Drift {
+    fn new(at: &str, expected: impl Into<String>, actual: impl Into<String>) -> Self {
+        Drift {
+            at: at.to_string(),
+            expected: expected.into(),
+            actual: actual.into(),
+        }
+    }
+}
+
+/// Check one response from the test server on `port`. `Err` carries the
+/// report that fails the spec.
+pub(crate) fn check_response(
+    port: u16,
+    method: &str,
+    path: &str,
+    status: u16,
+    content_type: Option<&str>,
+    body: &str,
+) -> Result<(), String> {
+    let spec = spec_for_port(port)?;
+    validate(&spec, method, path, status, content_type, body)
+}
+
+/// The document served by the test server on `port`, fetched on first use.
+fn spec_for_port(port: u16) -> Result<Arc<Json>, String> {
+    static SPECS: OnceLock<Mutex<HashMap<u16, Arc<Json>>>> = OnceLock::new();
+    let specs = SPECS.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(spec) = specs.lock().unwrap().get(&port) {
+        return Ok(spec.clone());
+    }
+    let (status, _, _, body) = super::request_helpers::raw_http_request(
+        port,
+        "GET",
+        "/openapi.json",
+        &HashMap::new(),
+        "",
+        None,
+    )
+    .map_err(|e| format!("--contract: fetching /openapi.json failed: {}", e))?;
+    if status != 200 {
+        return Err(format!(
+            "--contract: GET /openapi.json returned {} (is the test server running with SOLI_OPENAPI=1?)",
+            status
+        ));
+    }
+    let spec: Json = serde_json::from_str(&body)
+        .map_err(|e| format!("--contract: /openapi.json is not valid JSON: {}", e))?;
+    let spec = Arc::new(spec);
+    specs.lock().unwrap().insert(port, spec.clone());
+    Ok(spec)
+}
+
+fn validate(
+    spec: &Json,
+    method: &str,
+    path: &str,
+    status: u16,
+    content_type: Option<&str>,
+    body: &str,
+) -> Result<(), String> {
+    if let Some(error) = spec.get(OVERLAY_ERROR).and_then(Json::as_str) {
+        return Err(format!("--contract: invalid OpenAPI overlay: {}", error));
+    }
+    let path_only = path.split(['?', '#']).next().unwrap_or(path);
+    // Framework endpoints are never part of the app's document.
+    if path_only.starts_with("/_") || path_only == "/openapi.json" || path_only == "/openapi" {
+        return Ok(());
+    }
+    let request = format!("{} {}", method.to_uppercase(), path_only);
+
+    let Some((template, item)) = find_path_item(spec, path_only) else {
+        // A 404 for an unknown path is the server agreeing with the document.
+        if status == 404 {
+            return Ok(());
+        }
+        let drift = Drift::new("operation", "a documented path", path_only);
+        return Err(report(&request, None, status, &[drift]));
+    };
+
+    let lower = method.to_lowercase();
+    let op = item
+        .get(&lower)
+        .or_else(|| (lower == "head").then(|| item.get("get")).flatten());
+    let Some(op) = op else {
+        if status == 404 || status == 405 {
+            return Ok(());
+        }
+        let documented: Vec<String> = item
+            .as_object()
+            .map(|m| {
+                m.keys()
+                    .filter(|k| !k.starts_with("x-") && *k != "parameters")
+                    .map(|k| k.to_uppercase())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let drift = Drift::new(
+            "operation",
+            format!("one of {}", documented.join(" | ")),
+            method.to_uppercase(),
+        );
+        return Err(report(&request, Some(template), status, &[drift]));
+    };
+    if op.get(GENERATED_RESPONSES).is_some() {
+        return Ok(());
+    }
+
+    let drifts = check_operation(spec, op, &lower, status, content_type, body);
+    if drifts.is_empty() {
+        Ok(())
+    } else {
+        Err(report(&request, Some(template), status, &drifts))
+    }
+}
+
+fn check_operation(
+    spec: &Json,
+    op: &Json,
+    method: &str,
+    status: u16,
+    content_type: Option<&str>,
+    body: &str,
+) -> Vec<Drift> {
+    let empty = serde_json::Map::new();
+    let responses = op
+        .get("responses")
+        .and_then(Json::as_object)
+        .unwrap_or(&empty);
+    let Some(response) = find_response(responses, status) else {
+        let codes: Vec<&str> = responses.keys().map(String::as_str).collect();
+        return vec![Drift::new(
+            "status",
+            format!("one of {}", codes.join(" | ")),
+            status.to_string(),
+        )];
+    };
+    let response = resolve(spec, response);
+
+    // No `content` documents "no body shape"; HEAD and 204/304 carry none.
+    let Some(content) = response.get("content").and_then(Json::as_object) else {
+        return Vec::new();
+    };
+    if method == "head" || status == 204 || status == 304 {
+        return Vec::new();
+    }
+    let media = content_type
+        .and_then(|ct| ct.split(';').next())
+        .map(|m| m.trim().to_ascii_lowercase());
+    let Some(media_type) = find_media_type(content, media.as_deref()) else {
+        let documented: Vec<&str> = content.keys().map(String::as_str).collect();
+        return vec![Drift::new(
+            "content-type",
+            format!("one of {}", documented.join(" | ")),
+            media.unwrap_or_else(|| "none".to_string()),
+        )];
+    };
+
+    let Some(schema) = media_type.get("schema") else {
+        return Vec::new();
+    };
+    if !media.as_deref().is_some_and(is_json_media) {
+        return Vec::new();
+    }
+    let value: Json = match serde_json::from_str(body) {
+        Ok(value) => value,
+        Err(_) => {
+            return vec![Drift::new("body", "JSON", truncate(body, 40))];
+        }
+    };
+    let mut drifts = Vec::new();
+    check_schema(spec, schema, &value, "body", &mut drifts, 0);
+    drifts
+}
+
+/// Match a concrete path against the document's templates. Literal segments
+/// beat `{params}`, which beat a trailing catch-all, so `/posts/new` resolves to
+/// its own entry rather than `/posts/{id}`.
+fn find_path_item<'a>(spec: &'a Json, path: &str) -> Option<(&'a str, &'a Json)> {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let paths = spec.get("paths")?.as_object()?;
+    let mut best: Option<((usize, bool), &str, &Json)> = None;
+    for (template, item) in paths {
+        let parts: Vec<&str> = template.split('/').filter(|s| !s.is_empty()).collect();
+        let Some(score) = match_template(&parts, &segments, item) else {
+            continue;
+        };
+        if best.as_ref().is_none_or(|(b, _, _)| score > *b) {
+            best = Some((score, template, item));
+        }
+    }
+    best.map(|(_, template, item)| (template, item))
+}
+
+/// `(literal segments matched, not a catch-all)` when `parts` matches.
+fn match_template(parts: &[&str], segments: &[&str], item: &Json) -> Option<(usize, bool)> {
+    let is_param = |p: &str| p.starts_with('{') && p.ends_with('}');
+    let catch_all = parts
+        .last()
+        .filter(|p| is_param(p))
+        .is_some_and(|p| is_catch_all(item, &p[1..p.len() - 1]));
+    let exact = parts.len() == segments.len();
+    let spills = catch_all && segments.len() > parts.len();
+    if !exact && !spills {
+        return None;
+    }
+    let mut literals = 0;
+    for (part, segment) in parts.iter().zip(segments) {
+        if is_param(part) {
+            continue;
+        }
+        if part != segment {
+            return None;
+        }
+        literals += 1;
+    }
+    Some((literals, !spills))
+}
+
+/// Whether any operation on the path item marks `name` as a `*name` segment.
+fn is_catch_all(item: &Json, name: &str) -> bool {
+    item.as_object().is_some_and(|ops| {
+        ops.values().any(|op| {
+            op.get("parameters")
+                .and_then(Json::as_array)
+                .is_some_and(|params| {
+                    params.iter().any(|p| {
+                        p.get("name").and_then(Json::as_str) == Some(name)
+                            && p.get(CATCH_ALL).and_then(Json::as_bool) == Some(true)
+                    })
+                })
+        })
+    })
+}
+
+/// The documented response for `status`: exact code, then `4XX`-style range,
+/// then `default`.
+fn find_response(responses: &serde_json::Map<String, Json>, status: u16) -> Option<&Json> {
+    let code = status.to_string();
+    let range = format!("{}XX", status / 100);
+    responses.get(&code).or_else(|| {
+        responses
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(&range))
+            .map(|(_, v)| v)
+            .or_else(|| responses.get("default"))
+    })
+}
+
+/// The documented media type for the response's: exact, then `type/*`, then
+/// `*/*`.
+fn find_media_type<'a>(
+    content: &'a serde_json::Map<String, Json>,
+    media: Option<&str>,
+) -> Option<&'a Json> {
+    let lookup = |key: &str| {
+        content
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v)
+    };
+    if let Some(media) = media {
+        if let Some(found) = lookup(media) {
+            return Some(found);
+        }
+        if let Some((major, _)) = media.split_once('/') {
+            if let Some(found) = lookup(&format!("{}/*", major)) {
+                return Some(found);
+            }
+        }
+    }
+    lookup("*/*")
+}
+
+fn is_json_media(media: &str) -> bool {
+    media == "application/json" || media.ends_with("+json")
+}
+
+/// Follow local `$ref`s (`#/components/...`). An unresolvable reference is
+/// returned as-is, which checks as the empty (accept-anything) schema.
+fn resolve<'a>(spec: &'a Json, mut node: &'a Json) -> &'a Json {
+    for _ in 0..MAX_DEPTH {
+        let Some(pointer) = node
+            .get("$ref")
+            .and_then(Json::as_str)
+            .and_then(|r| r.strip_prefix('#'))
+        else {
+            break;
+        };
+        match spec.pointer(pointer) {
+            Some(target) => node = target,
+            None => break,
+        }
+    }
+    node
+}
+
+fn check_schema(
+    spec: &Json,
+    schema: &Json,
+    value: &Json,
+    at: &str,
+    drifts: &mut Vec<Drift>,
+    depth: usize,
+) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    let schema = resolve(spec, schema);
+
+    if let Some(all) = schema.get("allOf").and_then(Json::as_array) {
+        for branch in all {
+            check_schema(spec, branch, value, at, drifts, depth + 1);
+        }
+    }
+    for key in ["oneOf", "anyOf"] {
+        if let Some(branches) = schema.get(key).and_then(Json::as_array) {
+            let fits = branches.iter().any(|branch| {
+                let mut scratch = Vec::new();
+                check_schema(spec, branch, value, at, &mut scratch, depth + 1);
+                scratch.is_empty()
+            });
+            if !fits {
+                drifts.push(Drift::new(
+                    at,
+                    format!("a value matching {}", key),
+                    describe(value),
+                ));
+            }
+        }
+    }
+
+    let types: Vec<&str> = match schema.get("type") {
+        Some(Json::String(t)) => vec![t.as_str()],
+        Some(Json::Array(ts)) => ts.iter().filter_map(Json::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if value.is_null() {
+        let nullable =
+            schema.get("nullable").and_then(Json::as_bool) == Some(true) || types.contains(&"null");
+        if !types.is_empty() && !nullable {
+            drifts.push(Drift::new(at, types.join(" | "), "null"));
+        }
+        return;
+    }
+    if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+        drifts.push(Drift::new(at, types.join(" | "), describe(value)));
+        return;
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Json::as_array) {
+        if !allowed.contains(value) {
+            let listed: Vec<String> = allowed.iter().map(Json::to_string).collect();
+            drifts.push(Drift::new(
+                at,
+                format!("one of {}", listed.join(" | ")),
+                describe(value),
+            ));
+        }
+    }
+
+    match value {
+        Json::Object(fields) => {
+            if let Some(required) = schema.get("required").and_then(Json::as_array) {
+                for name in required.iter().filter_map(Json::as_str) {
+                    if !fields.contains_key(name) {
+                        drifts.push(Drift::new(
+                            &format!("{}.{}", at, name),
+                            "required property",
+                            "missing",
+                        ));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Json::as_object);
+            let additional = schema.get("additionalProperties");
+            for (name, field) in fields {
+                let field_at = format!("{}.{}", at, name);
+                match properties.and_then(|p| p.get(name)) {
+                    Some(field_schema) => {
+                        check_schema(spec, field_schema, field, &field_at, drifts, depth + 1)
+                    }
+                    None => match additional {
+                        Some(Json::Bool(false)) => {
+                            drifts.push(Drift::new(&field_at, "no such property", describe(field)))
+                        }
+                        Some(extra @ Json::Object(_)) => {
+                            check_schema(spec, extra, field, &field_at, drifts, depth + 1)
+                        }
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Json::Array(elements) => {
+            if let Some(items) = schema.get("items") {
+                for (i, element) in elements.iter().enumerate() {
+                    let element_at = format!("{}[{}]", at, i);
+                    check_schema(spec, items, element, &element_at, drifts, depth + 1);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Check `value` against `schema` (its `$ref`s resolved in `spec`): one
+/// `at: expected …, got …` line per drift. Route `returns:` shapes use this
+/// (see `serve::response_shape`).
+pub(crate) fn schema_drifts(spec: &Json, schema: &Json, value: &Json, at: &str) -> Vec<String> {
+    let mut drifts = Vec::new();
+    check_schema(spec, schema, value, at, &mut drifts, 0);
+    drifts
+        .into_iter()
+        .map(|d| format!("{}: expected {}, got {}", d.at, d.expected, d.actual))
+        .collect()
+}
+
+fn has_type(value: &Json, ty: &str) -> bool {
+    match ty {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "null" => value.is_null(),
+        // Unknown type keyword: don't invent a drift the spec can't justify.
+        _ => true,
+    }
+}
+
+/// `integer 42`, `string "draft"`, `array (3 items)` — enough to recognize
+/// the value without dumping a whole payload into the report.
+fn describe(value: &Json) -> String {
+    match value {
+        Json::Null => "null".to_string(),
+        Json::Bool(b) => format!("boolean {}", b),
+        Json::Number(n) if n.is_i64() || n.is_u64() => format!("integer {}", n),
+        Json::Number(n) => format!("number {}", n),
+        Json::String(_) => format!("string {}", truncate(&value.to_string(), 40)),
+        Json::Array(items) => format!("array ({} items)", items.len()),
+        Json::Object(fields) => format!("object ({} properties)", fields.len()),
+    }
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        format!("{}…", text.chars().take(max).collect::<String>())
+    }
+}
+
+fn report(request: &str, template: Option<&str>, status: u16, drifts: &[Drift]) -> String {
+    let mut out = format!("OpenAPI contract drift: {} -> {}", request, status);
+    if let Some(template) = template {
+        out.push_str(&format!(" (documented as {})", template));
+    }
+    for drift in drifts.iter().take(MAX_DRIFTS) {
+        out.push_str(&format!(
+            "\n  {}: expected {}, got {}",
+            drift.at, drift.expected, drift.actual
+        ));
+    }
+    if drifts.len() > MAX_DRIFTS {
+        out.push_str(&format!("\n  … {} more", drifts.len() - MAX_DRIFTS));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn spec() -> Json {
+        json!({
+            "paths": {
+                "/posts": {
+                    "get": {
+                        "responses": {
+                            "200": {
+                                "description": "OK",
+                                "content": { "application/json": { "schema": {
+                                    "type": "array",
+                                    "items": { "$ref": "#/components/schemas/Post" }
+                                } } }
+                            }
+                        }
+                    },
+                    "post": { "responses": { "200": { "description": "OK" } }, GENERATED_RESPONSES: true }
+                },
+                "/posts/{id}": {
+                    "get": {
+                        "responses": {
+                            "200": {
+                                "description": "OK",
+                                "content": { "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/Post" }
+                                } }
+                            },
+                            "4XX": { "description": "Client error" }
+                        }
+                    }
+                },
+                "/posts/new": { "get": { "responses": { "200": {
+                    "description": "Form",
+                    "content": { "text/html": {} }
+                } } } },
+                "/files/{path}": { "get": {
+                    "parameters": [{ "name": "path", "in": "path", CATCH_ALL: true }],
+                    "responses": { "200": { "description": "OK" } }
+                } }
+            },
+            "components": { "schemas": { "Post": {
+                "type": "object",
+                "required": ["id", "title"],
+                "additionalProperties": false,
+                "properties": {
+                    "id": { "type": "integer" },
+                    "title": { "type": "string" },
+                    "status": { "type": "string", "enum": ["draft", "published"] },
+                    "summary": { "type": "string", "nullable": true }
+                }
+            } } }
+        })
+    }
+
+    fn check(
+        method: &str,
+        path: &str,
+        status: u16,
+        ct: Option<&str>,
+        body: &str,
+    ) -> Result<(), String> {
+        validate(&spec(), method, path, status, ct, body)
+    }
+
+    const JSON: Option<&str> = Some("application/json; charset=utf-8");
+
+    #[test]
+    fn conforming_responses_pass() {
+        let post = r#"{"id": 1, "title": "Hi", "status": "draft", "summary": null}"#;
+        assert_eq!(check("GET", "/posts/1?preview=1", 200, JSON, post), Ok(()));
+        assert_eq!(
+            check("GET", "/posts", 200, JSON, &format!("[{}]", post)),
+            Ok(())
+        );
+        assert_eq!(
+            check("GET", "/posts/1", 404, Some("text/html"), "nope"),
+            Ok(())
+        );
+        assert_eq!(
+            check("GET", "/posts/new", 200, Some("text/html"), "<form>"),
+            Ok(())
+        );
+        assert_eq!(check("GET", "/files/a/b.txt", 200, None, ""), Ok(()));
+        assert_eq!(check("HEAD", "/posts/1", 200, JSON, ""), Ok(()));
+        // Placeholder responses, framework paths and unknown-path 404s are
+        // not drift.
+        assert_eq!(check("POST", "/posts", 302, None, ""), Ok(()));
+        assert_eq!(check("GET", "/_metrics", 200, None, ""), Ok(()));
+        assert_eq!(check("GET", "/nowhere", 404, None, ""), Ok(()));
+    }
+
+    #[test]
+    fn body_drift_lists_every_mismatch() {
+        let err = check(
+            "GET",
+            "/posts/1",
+            200,
+            JSON,
+            r#"{"id": "1", "status": "archived", "extra": true}"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            "OpenAPI contract drift: GET /posts/1 -> 200 (documented as /posts/{id})\n  \
+             body.title: expected required property, got missing\n  \
+             body.extra: expected no such property, got boolean true\n  \
+             body.id: expected integer, got string \"1\"\n  \
+             body.status: expected one of \"draft\" | \"published\", got string \"archived\""
+        );
+
+        let err = check("GET", "/posts", 200, JSON, r#"[{"id": 1, "title": null}]"#).unwrap_err();
+        assert!(
+            err.ends_with("\n  body[0].title: expected string, got null"),
+            "got: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn status_content_type_and_operation_drift() {
+        let err = check("GET", "/posts/1", 500, JSON, "{}").unwrap_err();
+        assert!(
+            err.ends_with("status: expected one of 200 | 4XX, got 500"),
+            "got: {}",
+            err
+        );
+
+        let err = check("GET", "/posts/1", 200, Some("text/html"), "<p>").unwrap_err();
+        assert!(
+            err.ends_with("content-type: expected one of application/json, got text/html"),
+            "got: {}",
+            err
+        );
+
+        let err = check("GET", "/posts/1", 200, JSON, "not json").unwrap_err();
+        assert!(
+            err.ends_with("body: expected JSON, got not json"),
+            "got: {}",
+            err
+        );
+
+        let err = check("DELETE", "/posts/1", 200, None, "").unwrap_err();
+        assert!(
+            err.ends_with("operation: expected one of GET, got DELETE"),
+            "got: {}",
+            err
+        );
+
+        let err = check("GET", "/comments", 200, None, "").unwrap_err();
+        assert_eq!(
+            err,
+            "OpenAPI contract drift: GET /comments -> 200\n  \
+             operation: expected a documented path, got /comments"
+        );
+    }
+
+    #[test]
+    fn overlay_errors_fail_every_request() {
+        let spec = json!({ "paths": {}, OVERLAY_ERROR: "config/openapi.yml: bad indent" });
+        let err = validate(&spec, "GET", "/", 200, None, "").unwrap_err();
+        assert!(
+            err.contains("config/openapi.yml: bad indent"),
+            "got: {}",
+            err
+        );
+    }
+}
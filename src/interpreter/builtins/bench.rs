@@ -0,0 +1,149 @@
+//! Benchmark DSL: `bench("name") { ... }`, run by `soli bench`.
+//!
+//! Like `test` in the VM test runner, `bench` only records its body while
+//! the file runs; the runner then takes the recorded benchmarks and times
+//! each body with [`measure`], on whichever engine ran the file. The body is
+//! the same closure either way, so an interpreter run and a `--vm` run of
+//! one file compare like for like.
+
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+use crate::interpreter::environment::Environment;
+use crate::interpreter::executor::heap_counters;
+use crate::interpreter::value::{NativeFunction, Value};
+
+/// A benchmark declared with `bench`.
+#[derive(Clone)]
+pub struct BenchDefinition {
+    pub name: String,
+    pub body: Value,
+}
+
+thread_local! {
+    static BENCHES: RefCell<Vec<BenchDefinition>> = const { RefCell::new(Vec::new()) };
+}
+
+/// How long to warm up and measure each benchmark.
+#[derive(Debug, Clone)]
+pub struct BenchConfig {
+    /// Run the body for this long first, untimed, so caches and the
+    /// allocator settle.
+    pub warmup: Duration,
+    /// Measure for this long, as many iterations as fit.
+    pub measure: Duration,
+    /// Measure exactly this many iterations instead.
+    pub iterations: Option<u64>,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            warmup: Duration::from_millis(200),
+            measure: Duration::from_secs(1),
+            iterations: None,
+        }
+    }
+}
+
+/// What [`measure`] found for one benchmark.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub name: String,
+    pub iterations: u64,
+    pub ns_per_iter: f64,
+    pub allocations_per_iter: f64,
+    pub bytes_per_iter: f64,
+}
+
+/// A benchmark's name and its result, or why its body failed.
+pub type BenchOutcome = (String, Result<BenchResult, String>);
+
+pub fn register_bench_builtins(env: &mut Environment) {
+    env.define(
+        "bench".to_string(),
+        Value::NativeFunction(NativeFunction::new("bench", Some(2), |args| {
+            let name = match &args[0] {
+                Value::String(s) => s.to_string(),
+                _ => return Err("bench requires string name".to_string()),
+            };
+            let body = args[1].clone();
+            BENCHES.with(|benches| benches.borrow_mut().push(BenchDefinition { name, body }));
+            Ok(Value::Null)
+        })),
+    );
+}
+
+/// Hand back the benchmarks declared since the last call, in order.
+pub fn take_benches() -> Vec<BenchDefinition> {
+    BENCHES.with(|benches| std::mem::take(&mut *benches.borrow_mut()))
+}
+
+/// Time `run`: warm up, then call it repeatedly and report the average
+/// time and heap allocations per call. Stops at the first error.
+pub fn measure(
+    name: &str,
+    config: &BenchConfig,
+    mut run: impl FnMut() -> Result<(), String>,
+) -> Result<BenchResult, String> {
+    let warmup_start = Instant::now();
+    loop {
+        run()?;
+        if warmup_start.elapsed() >= config.warmup {
+            break;
+        }
+    }
+
+    let heap_before = heap_counters();
+    let start = Instant::now();
+    let mut iterations = 0u64;
+    loop {
+        run()?;
+        iterations += 1;
+        let done = match config.iterations {
+            Some(target) => iterations >= target,
+            None => start.elapsed() >= config.measure,
+        };
+        if done {
+            break;
+        }
+    }
+    let elapsed = start.elapsed();
+    let heap_after = heap_counters();
+
+    let per_iter = |total: u64| total as f64 / iterations as f64;
+    Ok(BenchResult {
+        name: name.to_string(),
+        iterations,
+        ns_per_iter: elapsed.as_nanos() as f64 / iterations as f64,
+        allocations_per_iter: per_iter(heap_after.allocations - heap_before.allocations),
+        bytes_per_iter: per_iter(heap_after.allocated_bytes - heap_before.allocated_bytes),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measure_runs_the_requested_iterations_after_warming_up() {
+        let mut calls = 0u64;
+        let config = BenchConfig {
+            warmup: Duration::ZERO,
+            measure: Duration::ZERO,
+            iterations: Some(50),
+        };
+        let result = measure("count", &config, || {
+            calls += 1;
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(result.iterations, 50);
+        assert_eq!(calls, 51, "one warmup call, then the measured ones");
+        assert!(result.ns_per_iter >= 0.0);
+
+        let failed = measure("boom", &config, || Err("boom".to_string()));
+        assert_eq!(failed.unwrap_err(), "boom");
+    }
+}
@@ -686,9 +686,9 @@ pub fn register_array_class(env: &mut Environment) {
                         return Ok(Value::Null);
                     }
                     use rand::seq::SliceRandom;
-                    use rand::thread_rng;
-                    let mut rng = thread_rng();
-                    Ok(items.choose(&mut rng).cloned().unwrap_or(Value::Null))
+                    Ok(crate::interpreter::builtins::rng::with_rng(|rng| {
+                        items.choose(rng).cloned().unwrap_or(Value::Null)
+                    }))
                 }
                 _ => Err("Array missing internal value".to_string()),
             }
@@ -705,10 +705,8 @@ pub fn register_array_class(env: &mut Environment) {
             match this.borrow().fields.get("__value").cloned() {
                 Some(Value::Array(arr)) => {
                     use rand::seq::SliceRandom;
-                    use rand::thread_rng;
                     let mut result = arr.borrow().clone();
-                    let mut rng = thread_rng();
-                    result.shuffle(&mut rng);
+                    crate::interpreter::builtins::rng::with_rng(|rng| result.shuffle(rng));
                     Ok(Value::Array(Rc::new(RefCell::new(result))))
                 }
                 _ => Err("Array missing internal value".to_string()),
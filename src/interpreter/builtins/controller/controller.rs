@@ -206,6 +206,17 @@ pub struct ControllerInfo {
     /// `this.layout("name", only: [...])` / `except: [...]`. Checked in
     /// order; the first matching rule wins, falling back to `layout`.
     pub action_layouts: Vec<LayoutRule>,
+    /// Routes declared on actions with `@route("/path", method: "GET")`.
+    pub routes: Vec<AnnotatedRoute>,
+}
+
+/// A route declared by a `@route` decorator on a controller action. `path`
+/// already includes the prefix from a `@route` on the controller class.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AnnotatedRoute {
+    pub method: String,
+    pub path: String,
+    pub action: String,
 }
 
 impl ControllerInfo {
@@ -219,6 +230,7 @@ impl ControllerInfo {
             after_actions: Vec::new(),
             layout: None,
             action_layouts: Vec::new(),
+            routes: Vec::new(),
         }
     }
 
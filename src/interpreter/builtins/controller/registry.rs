@@ -10,9 +10,14 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::path::Path;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::RwLock;
 
-use super::controller::{AfterAction, BeforeAction, ControllerAction, ControllerInfo, LayoutRule};
+use super::controller::{
+    AfterAction, AnnotatedRoute, BeforeAction, ControllerAction, ControllerInfo, LayoutRule,
+};
+use crate::ast::expr::{Argument, Expr, ExprKind};
+use crate::ast::{Decorator, StmtKind, Visibility};
 use crate::interpreter::builtins::template as template_module;
 use crate::interpreter::value::{Instance, Value};
 use crate::interpreter::Interpreter;
@@ -23,6 +28,16 @@ lazy_static::lazy_static! {
     pub static ref CONTROLLER_REGISTRY: RwLock<ControllerRegistry> = RwLock::new(ControllerRegistry::new());
 }
 
+// Bumped each time `scan_controllers` rewrites the registry, so per-thread
+// caches derived from it can tell they are stale.
+static REGISTRY_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// How many times the controller registry has been (re)scanned. Compare
+/// against a saved value to tell whether a hot reload changed it since.
+pub fn registry_generation() -> u64 {
+    REGISTRY_GENERATION.load(Ordering::Acquire)
+}
+
 // Thread-local controller instances for current request.
 thread_local! {
     static CURRENT_CONTROLLER: RefCell<Option<Value>> = const { RefCell::new(None) };
@@ -98,6 +113,7 @@ pub fn scan_controllers(controllers_dir: &Path) -> Result<(), String> {
     if let Some(meta) = crate::bundle::bundle_meta() {
         if meta.protected {
             let mut registry = CONTROLLER_REGISTRY.write().unwrap();
+            REGISTRY_GENERATION.fetch_add(1, Ordering::AcqRel);
             for info in &meta.controllers {
                 registry.register(info.clone());
             }
@@ -107,6 +123,9 @@ pub fn scan_controllers(controllers_dir: &Path) -> Result<(), String> {
     }
 
     let mut registry = CONTROLLER_REGISTRY.write().unwrap();
+    // Bumped while the write lock is held: a reader that sees the new
+    // generation can only read the registry once this scan is done.
+    REGISTRY_GENERATION.fetch_add(1, Ordering::AcqRel);
 
     if !controllers_dir.exists() {
         return Ok(());
@@ -306,9 +325,118 @@ pub(crate) fn parse_controller_source(
     // Extract public methods (actions)
     extract_actions(source, &actual_class_name, &mut info);
 
+    // Routes declared with `@route` on the actions
+    extract_annotated_routes(source, &mut info)?;
+
     Ok(info)
 }
 
+/// Register every controller's `@route` routes with the router. Called after
+/// `config/routes.sl` has run, so an explicit route for the same path wins.
+/// The caller rebuilds the route index.
+pub fn register_annotated_routes() {
+    let registry = CONTROLLER_REGISTRY.read().unwrap();
+    for info in registry.all() {
+        for route in &info.routes {
+            crate::interpreter::builtins::server::register_route_with_handler(
+                &route.method,
+                &route.path,
+                format!("{}#{}", info.class_name, route.action),
+            );
+        }
+    }
+}
+
+/// Collect `@route(path, method: "GET")` decorators from the controller
+/// class's public instance methods. A `@route(prefix)` on the class itself
+/// prefixes every action path. Unlike the rest of this scan the decorators
+/// are read from the parsed AST; a file that does not parse declares no
+/// routes here and reports its syntax error when it is loaded.
+fn extract_annotated_routes(source: &str, info: &mut ControllerInfo) -> Result<(), String> {
+    if !source.contains("@route") {
+        return Ok(());
+    }
+    let Ok(program) = crate::parse(source) else {
+        return Ok(());
+    };
+    let class = program.statements.iter().find_map(|stmt| match &stmt.kind {
+        StmtKind::Class(decl) if decl.name == info.name => Some(decl.as_ref()),
+        StmtKind::Export(inner) => match &inner.kind {
+            StmtKind::Class(decl) if decl.name == info.name => Some(decl.as_ref()),
+            _ => None,
+        },
+        _ => None,
+    });
+    let Some(class) = class else {
+        return Ok(());
+    };
+
+    let prefix = match class.decorators.iter().find(|d| d.name == "route") {
+        Some(decorator) => route_decorator(decorator)?.1,
+        None => String::new(),
+    };
+    for method in &class.methods {
+        if method.is_static || method.visibility != Visibility::Public {
+            continue;
+        }
+        for decorator in method.decorators.iter().filter(|d| d.name == "route") {
+            let (http_method, path) = route_decorator(decorator)?;
+            info.routes.push(AnnotatedRoute {
+                method: http_method,
+                path: join_route_path(&prefix, &path),
+                action: method.name.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// `(HTTP method, path)` from `@route("/path")` / `@route("/path", method: "POST")`.
+/// Only literals are accepted: the registry reads the source, it does not run it.
+fn route_decorator(decorator: &Decorator) -> Result<(String, String), String> {
+    fn literal(expr: &Expr) -> Option<&str> {
+        match &expr.kind {
+            ExprKind::StringLiteral(s) | ExprKind::Symbol(s) => Some(s),
+            _ => None,
+        }
+    }
+    let error = |what: &str| format!("@route {} at line {}", what, decorator.span.line);
+
+    let mut path = None;
+    let mut method = "GET".to_string();
+    for argument in &decorator.arguments {
+        match argument {
+            Argument::Positional(expr) if path.is_none() => {
+                path = Some(
+                    literal(expr)
+                        .ok_or_else(|| error("path must be a string literal"))?
+                        .to_string(),
+                );
+            }
+            Argument::Named(named) if named.name == "method" => {
+                method = literal(&named.value)
+                    .ok_or_else(|| error("method must be a string literal"))?
+                    .to_ascii_uppercase();
+            }
+            _ => return Err(error("takes a path and an optional method:")),
+        }
+    }
+    let path = path.ok_or_else(|| error("needs a path"))?;
+    Ok((method, path))
+}
+
+/// `"/users"` + `"/:id"` → `"/users/:id"`; an empty or `/` action path is the
+/// prefix itself.
+fn join_route_path(prefix: &str, path: &str) -> String {
+    let prefix = prefix.trim_end_matches('/');
+    let path = path.trim_start_matches('/');
+    match (prefix.is_empty(), path.is_empty()) {
+        (true, true) => "/".to_string(),
+        (_, true) => prefix.to_string(),
+        _ => format!("{}/{}", prefix, path),
+    }
+}
+
 /// Compute the registry/route key for a controller file relative to the
 /// controllers directory. Strips `_controller` from the file stem and joins
 /// any subdirectory segments with `/`.
@@ -1166,6 +1294,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn route_decorators_declare_routes_under_the_class_prefix() {
+        let source = r#"
+@route("/users")
+class UsersController extends Controller
+    @route("/", method: "GET")
+    fn index(req) { "all" }
+
+    @route("/:id")
+    @route("/:id/show")
+    fn show(req) { "one" }
+
+    @route("/", method: :post)
+    fn create(req) { "new" }
+
+    @route("/stats")
+    static fn stats() { 0 }
+
+    fn helper(req) { "no route" }
+end
+"#;
+        let info = parse_controller_source(source, "users_controller", "users").unwrap();
+        let routes: Vec<(&str, &str, &str)> = info
+            .routes
+            .iter()
+            .map(|r| (r.method.as_str(), r.path.as_str(), r.action.as_str()))
+            .collect();
+        assert_eq!(
+            routes,
+            vec![
+                ("GET", "/users", "index"),
+                ("GET", "/users/:id", "show"),
+                ("GET", "/users/:id/show", "show"),
+                ("POST", "/users", "create"),
+            ]
+        );
+    }
+
+    #[test]
+    fn route_decorator_arguments_must_be_literals() {
+        let source = r#"
+class PostsController extends Controller
+    @route(base + "/x")
+    fn index(req) { "all" }
+end
+"#;
+        let err = parse_controller_source(source, "posts_controller", "posts").unwrap_err();
+        assert!(err.contains("path must be a string literal"), "{}", err);
+    }
+
+    #[test]
+    fn join_route_path_normalizes_slashes() {
+        assert_eq!(join_route_path("", "/"), "/");
+        assert_eq!(join_route_path("", "/posts"), "/posts");
+        assert_eq!(join_route_path("/posts/", "/"), "/posts");
+        assert_eq!(join_route_path("/posts", ":id"), "/posts/:id");
+    }
+
     #[test]
     fn relative_route_key_uses_slash_for_subdirs() {
         use std::path::PathBuf;
@@ -27,7 +27,6 @@
 
 use std::cell::RefCell;
 use std::sync::RwLock;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::Engine as _;
@@ -79,10 +78,7 @@ pub(crate) struct CookieJar {
 }
 
 fn now_unix_secs() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_secs())
-        .unwrap_or(0)
+    super::datetime::helpers::datetime_now().max(0) as u64
 }
 
 /// The purpose-binding bytes: `info ‖ NUL ‖ cookie name`. NUL is unambiguous —
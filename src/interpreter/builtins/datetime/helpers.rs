@@ -30,11 +30,57 @@ pub fn is_datetime_frozen() -> bool {
     FROZEN_NOW.with(|frozen| frozen.borrow().is_some())
 }
 
+/// The frozen timestamp on this thread, if any. Paired with
+/// `restore_frozen_datetime()` so a block-scoped freeze can put back whatever
+/// was active before it (an outer freeze, or none).
+pub fn frozen_datetime() -> Option<i64> {
+    FROZEN_NOW.with(|frozen| *frozen.borrow())
+}
+
+/// Reinstate a state captured by `frozen_datetime()`.
+pub fn restore_frozen_datetime(previous: Option<i64>) {
+    FROZEN_NOW.with(|frozen| {
+        *frozen.borrow_mut() = previous;
+    });
+}
+
+/// Move the clock `seconds` away from the current (possibly frozen) time and
+/// pin it there. Returns the new timestamp.
+pub fn travel_datetime(seconds: i64) -> i64 {
+    let timestamp = datetime_now().saturating_add(seconds);
+    freeze_datetime(timestamp);
+    timestamp
+}
+
 /// Get current Unix timestamp (UTC), or the frozen value when set.
 pub fn datetime_now() -> i64 {
     FROZEN_NOW.with(|frozen| frozen.borrow().unwrap_or_else(|| Utc::now().timestamp()))
 }
 
+/// Milliseconds since the epoch, honoring a frozen clock. Session stores and
+/// anything else that tracks expiry at sub-second resolution read this so a
+/// spec can age a record with `travel()` instead of sleeping.
+pub fn clock_now_millis() -> i64 {
+    FROZEN_NOW.with(|frozen| match *frozen.borrow() {
+        Some(timestamp) => timestamp.saturating_mul(1_000),
+        None => Utc::now().timestamp_millis(),
+    })
+}
+
+/// Nanoseconds since the epoch, honoring a frozen clock (`DateTime.now()`'s
+/// internal representation).
+pub fn clock_now_nanos() -> i64 {
+    FROZEN_NOW.with(|frozen| match *frozen.borrow() {
+        Some(timestamp) => timestamp.saturating_mul(1_000_000_000),
+        None => Utc::now().timestamp_nanos_opt().unwrap_or(0),
+    })
+}
+
+/// `Utc::now()` honoring a frozen clock, for code that stamps RFC 3339 times.
+pub fn clock_now_utc() -> DateTime<Utc> {
+    DateTime::from_timestamp_nanos(clock_now_nanos())
+}
+
 /// Format a Unix timestamp using strftime format string.
 ///
 /// # Arguments
@@ -147,7 +193,7 @@ pub fn time_ago(timestamp: i64) -> String {
 /// # Returns
 /// Localized human-readable string like "il y a 5 minutes", "vor 2 Stunden"
 pub fn time_ago_localized(timestamp: i64, locale: &str) -> String {
-    let now = Utc::now().timestamp();
+    let now = datetime_now();
     let diff = now - timestamp;
 
     if diff < 0 {
@@ -743,6 +789,21 @@ mod tests {
         assert!(!is_datetime_frozen());
     }
 
+    #[test]
+    fn test_travel_datetime_shifts_frozen_clock() {
+        freeze_datetime(1_700_000_000);
+        assert_eq!(travel_datetime(3600), 1_700_003_600);
+        assert_eq!(datetime_now(), 1_700_003_600);
+        assert_eq!(clock_now_millis(), 1_700_003_600_000);
+        assert_eq!(clock_now_nanos(), 1_700_003_600_000_000_000);
+
+        let saved = frozen_datetime();
+        freeze_datetime(0);
+        restore_frozen_datetime(saved);
+        assert_eq!(datetime_now(), 1_700_003_600);
+        unfreeze_datetime();
+    }
+
     #[test]
     fn test_datetime_now() {
         let now = datetime_now();
@@ -3,6 +3,41 @@ pub mod helpers;
 use crate::interpreter::environment::Environment;
 use crate::interpreter::value::{NativeFunction, Value};
 
+/// Offset accepted by `travel()`: whole seconds (int or float, truncated) or a
+/// `Duration` instance.
+pub fn travel_seconds(value: &Value) -> Result<i64, String> {
+    match value {
+        Value::Int(n) => Ok(*n),
+        Value::Float(f) => Ok(*f as i64),
+        Value::Instance(inst) if inst.borrow().class.name == "Duration" => {
+            match inst.borrow().fields.get("seconds") {
+                Some(Value::Int(n)) => Ok(*n),
+                Some(Value::Float(f)) => Ok(*f as i64),
+                _ => Err("travel(): Duration missing seconds".to_string()),
+            }
+        }
+        other => Err(format!(
+            "travel() expects seconds (int/float) or Duration, got {}",
+            other.type_name()
+        )),
+    }
+}
+
+/// Resolve a `freeze_time()` / `travel_to()` target: a Unix timestamp or a
+/// parseable date string.
+pub fn freeze_target(func: &str, value: &Value) -> Result<i64, String> {
+    match value {
+        Value::Int(n) => Ok(*n),
+        Value::String(s) => helpers::datetime_parse(s)
+            .ok_or_else(|| format!("{}(): invalid date string {:?}", func, s)),
+        other => Err(format!(
+            "{}() expects timestamp (int) or date string, got {}",
+            func,
+            other.type_name()
+        )),
+    }
+}
+
 pub fn register_datetime_builtins(env: &mut Environment) {
     env.define(
         "datetime_now".to_string(),
@@ -14,17 +49,7 @@ pub fn register_datetime_builtins(env: &mut Environment) {
     env.define(
         "freeze_time".to_string(),
         Value::NativeFunction(NativeFunction::new("freeze_time", Some(1), |args| {
-            let timestamp = match &args[0] {
-                Value::Int(n) => *n,
-                Value::String(s) => helpers::datetime_parse(s)
-                    .ok_or_else(|| format!("freeze_time(): invalid date string {:?}", s))?,
-                other => {
-                    return Err(format!(
-                        "freeze_time() expects timestamp (int) or date string, got {}",
-                        other.type_name()
-                    ));
-                }
-            };
+            let timestamp = freeze_target("freeze_time", &args[0])?;
             helpers::freeze_datetime(timestamp);
             Ok(Value::Int(timestamp))
         })),
@@ -33,22 +58,20 @@ pub fn register_datetime_builtins(env: &mut Environment) {
     env.define(
         "travel_to".to_string(),
         Value::NativeFunction(NativeFunction::new("travel_to", Some(1), |args| {
-            let timestamp = match &args[0] {
-                Value::Int(n) => *n,
-                Value::String(s) => helpers::datetime_parse(s)
-                    .ok_or_else(|| format!("travel_to(): invalid date string {:?}", s))?,
-                other => {
-                    return Err(format!(
-                        "travel_to() expects timestamp (int) or date string, got {}",
-                        other.type_name()
-                    ));
-                }
-            };
+            let timestamp = freeze_target("travel_to", &args[0])?;
             helpers::freeze_datetime(timestamp);
             Ok(Value::Int(timestamp))
         })),
     );
 
+    env.define(
+        "travel".to_string(),
+        Value::NativeFunction(NativeFunction::new("travel", Some(1), |args| {
+            let seconds = travel_seconds(&args[0])?;
+            Ok(Value::Int(helpers::travel_datetime(seconds)))
+        })),
+    );
+
     env.define(
         "unfreeze_time".to_string(),
         Value::NativeFunction(NativeFunction::new("unfreeze_time", Some(0), |_args| {
@@ -51,7 +51,7 @@ fn weekday_name(wday: chrono::Weekday) -> String {
     .to_string()
 }
 
-fn parse_datetime_string(s: &str) -> Result<i64, String> {
+pub(crate) fn parse_datetime_string(s: &str) -> Result<i64, String> {
     let s = s.trim();
     let datetime = if s.ends_with('Z') || s.contains("+") {
         chrono::DateTime::parse_from_rfc3339(s).or_else(|_| chrono::DateTime::parse_from_rfc2822(s))
@@ -1126,12 +1126,9 @@ pub fn register_datetime_and_duration_classes(env: &mut Environment) {
     dt_static_methods.insert(
         "now".to_string(),
         Rc::new(NativeFunction::new("DateTime.now", Some(0), move |_args| {
-            let now = Local::now();
+            let now = super::datetime::helpers::datetime_now();
             let mut inst = Instance::new(datetime_instance_class()?);
-            inst.set(
-                "_ts".to_string(),
-                Value::Int(now.timestamp() * 1_000_000_000),
-            );
+            inst.set("_ts".to_string(), Value::Int(now * 1_000_000_000));
             Ok(Value::Instance(Rc::new(RefCell::new(inst))))
         })),
     );
@@ -1139,12 +1136,9 @@ pub fn register_datetime_and_duration_classes(env: &mut Environment) {
     dt_static_methods.insert(
         "utc".to_string(),
         Rc::new(NativeFunction::new("DateTime.utc", Some(0), move |_args| {
-            let now = chrono::Utc::now();
+            let now = super::datetime::helpers::clock_now_nanos();
             let mut inst = Instance::new(datetime_instance_class()?);
-            inst.set(
-                "_ts".to_string(),
-                Value::Int(now.timestamp_nanos_opt().unwrap_or(0)),
-            );
+            inst.set("_ts".to_string(), Value::Int(now));
             Ok(Value::Instance(Rc::new(RefCell::new(inst))))
         })),
     );
@@ -1168,19 +1162,14 @@ pub fn register_datetime_and_duration_classes(env: &mut Environment) {
     );
 
     // microtime() - Returns current time in microseconds as float (static method)
-    use std::time::{SystemTime, UNIX_EPOCH};
     dt_static_methods.insert(
         "microtime".to_string(),
         Rc::new(NativeFunction::new(
             "DateTime.microtime",
             Some(0),
             |_args| {
-                let duration = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .map_err(|e| e.to_string())?;
-                let micros =
-                    duration.as_secs() as f64 * 1_000_000.0 + duration.subsec_micros() as f64;
-                Ok(Value::Float(micros))
+                let micros = super::datetime::helpers::clock_now_nanos() / 1_000;
+                Ok(Value::Float(micros as f64))
             },
         )),
     );
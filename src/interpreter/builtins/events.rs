@@ -0,0 +1,433 @@
+//! `Events` — an in-process publish/subscribe bus.
+//!
+//! ```soli
+//! Events.subscribe("user.created", fn(user) { WelcomeMailer.welcome(user).deliver_later() })
+//! Events.subscribe("user.*", fn(payload, topic) { print(topic) })
+//! Events.publish("user.created", user)          # handlers run now
+//! Events.publish_later("user.created", user)    # handlers run in a job worker
+//! ```
+//!
+//! Topics are dot-separated names. A subscription pattern may use `*` for
+//! exactly one segment and `**` for any number of them (including none), so
+//! `"user.*"` sees `user.created` but not `user.profile.updated`, and `"**"`
+//! sees everything.
+//!
+//! The `Events` class is a Soli prelude (see [`EVENTS_PRELUDE`]) over the
+//! native `__events_*` builtins below: natives can't call back into Soli, so
+//! `publish` asks for the matching handlers and calls them itself, one at a
+//! time. A failing handler is reported on stderr and the rest still run.
+//!
+//! Subscriptions hold interpreter values, so they live per thread, like the
+//! lifecycle hooks: every web and job worker loads `app/models` and registers
+//! its own. `publish_later` enqueues an `__EventDelivery` job whose `perform`
+//! publishes on the job worker, so only load-time subscriptions see it.
+//!
+//! Cleanup is automatic in three ways:
+//! - a subscription made while a request is being handled lasts only until
+//!   that request finishes ([`begin_request`] / [`end_request`]);
+//! - subscribing a handler defined at the same place in the source to the
+//!   same pattern replaces the earlier subscription, so a hot reload that
+//!   re-runs a model file doesn't deliver every event twice;
+//! - `Events.once` subscriptions drop themselves after their first event.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::interpreter::environment::Environment;
+use crate::interpreter::value::{HashKey, HashPairs, NativeFunction, Value};
+
+struct Subscription {
+    id: i64,
+    pattern: String,
+    handler: Value,
+    once: bool,
+    request_scoped: bool,
+    /// Where the handler was defined, for replacing a re-run subscription.
+    origin: Option<(String, u32)>,
+}
+
+#[derive(Default)]
+struct Bus {
+    next_id: i64,
+    subscriptions: Vec<Subscription>,
+    in_request: bool,
+}
+
+thread_local! {
+    static BUS: RefCell<Bus> = RefCell::new(Bus::default());
+}
+
+type EventsBuiltin = fn(Vec<Value>) -> Result<Value, String>;
+
+/// Register the native `__events_*` builtins. The `Events` class itself is
+/// defined by [`EVENTS_PRELUDE`], loaded via [`ensure_prelude`].
+pub fn register_events_builtins(env: &mut Environment) {
+    let defs: [(&str, Option<usize>, EventsBuiltin); 7] = [
+        ("__events_subscribe", Some(3), events_subscribe),
+        ("__events_unsubscribe", Some(1), events_unsubscribe),
+        ("__events_handlers", Some(1), events_handlers),
+        ("__events_count", Some(1), events_count),
+        ("__events_clear", Some(0), events_clear),
+        ("__events_enqueue", Some(3), events_enqueue),
+        ("__events_report", Some(2), events_report),
+    ];
+    for (name, arity, func) in defs {
+        env.define(
+            name.to_string(),
+            Value::NativeFunction(NativeFunction::new(name, arity, func)),
+        );
+    }
+}
+
+/// Start handling a request on this thread: subscriptions made from now on
+/// are dropped by [`end_request`]. Also drops any a previous request left
+/// behind, in case its response never went through `end_request`.
+pub fn begin_request() {
+    BUS.with(|bus| {
+        let mut bus = bus.borrow_mut();
+        bus.subscriptions.retain(|s| !s.request_scoped);
+        bus.in_request = true;
+    });
+}
+
+/// Finish the current request, dropping the subscriptions it made.
+pub fn end_request() {
+    BUS.with(|bus| {
+        let mut bus = bus.borrow_mut();
+        bus.subscriptions.retain(|s| !s.request_scoped);
+        bus.in_request = false;
+    });
+}
+
+/// Forget every subscription on this thread.
+pub fn clear_subscriptions() {
+    BUS.with(|bus| bus.borrow_mut().subscriptions.clear());
+}
+
+/// Whether `topic` is matched by `pattern` (`*` = one segment, `**` = any
+/// number of segments).
+pub fn topic_matches(pattern: &str, topic: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('.').collect();
+    let topic: Vec<&str> = topic.split('.').collect();
+    segments_match(&pattern, &topic)
+}
+
+fn segments_match(pattern: &[&str], topic: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => topic.is_empty(),
+        Some((&"**", rest)) => (0..=topic.len()).any(|skip| segments_match(rest, &topic[skip..])),
+        Some((&segment, rest)) => match topic.split_first() {
+            Some((&name, topic_rest)) => {
+                (segment == "*" || segment == name) && segments_match(rest, topic_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+fn validate_name(name: &str, what: &str, wildcards: bool) -> Result<(), String> {
+    if name.split('.').any(str::is_empty) {
+        return Err(format!(
+            "Events: invalid {} '{}' (expected dot-separated names like \"user.created\")",
+            what, name
+        ));
+    }
+    if !wildcards && name.contains('*') {
+        return Err(format!(
+            "Events: can't publish to '{}': wildcards only go in subscriptions",
+            name
+        ));
+    }
+    Ok(())
+}
+
+fn string_arg(args: &[Value], index: usize, func: &str) -> Result<String, String> {
+    match args.get(index) {
+        Some(Value::String(s)) => Ok(s.to_string()),
+        Some(other) => Err(format!(
+            "{}() expects a string topic, got {}",
+            func,
+            other.type_name()
+        )),
+        None => Err(format!("{}() expects a topic", func)),
+    }
+}
+
+fn handler_origin(handler: &Value) -> Option<(String, u32)> {
+    match handler {
+        Value::Function(func) => Some((func.source_path.clone()?, func.span?.start)),
+        // Compiled closures carry no source path; every closure made from
+        // the same `fn` literal shares its prototype, so that identifies it.
+        Value::VmClosure(closure) => Some((
+            format!("<vm {:p}>", std::sync::Arc::as_ptr(&closure.proto)),
+            0,
+        )),
+        _ => None,
+    }
+}
+
+/// Handlers declared with a second positional parameter also get the topic,
+/// which is what a wildcard subscriber usually needs.
+fn handler_takes_topic(handler: &Value) -> bool {
+    match handler {
+        Value::Function(func) => {
+            func.params.iter().any(|p| p.is_variadic)
+                || func
+                    .params
+                    .iter()
+                    .filter(|p| !p.is_block_param && !p.is_keyword_only)
+                    .count()
+                    >= 2
+        }
+        Value::VmClosure(closure) => {
+            let proto = &closure.proto;
+            proto.variadic_index.is_some()
+                || (proto.arity as u32).saturating_sub(proto.keyword_only_mask.count_ones()) >= 2
+        }
+        _ => false,
+    }
+}
+
+/// `__events_subscribe(pattern, handler, once)` — returns the subscription id.
+fn events_subscribe(args: Vec<Value>) -> Result<Value, String> {
+    let pattern = string_arg(&args, 0, "Events.subscribe")?;
+    validate_name(&pattern, "pattern", true)?;
+    let handler = args.get(1).cloned().unwrap_or(Value::Null);
+    if !handler.is_callable() {
+        return Err(format!(
+            "Events.subscribe() expects a handler function, got {}",
+            handler.type_name()
+        ));
+    }
+    let once = args.get(2).is_some_and(Value::is_truthy);
+    let origin = handler_origin(&handler);
+
+    BUS.with(|bus| {
+        let mut bus = bus.borrow_mut();
+        let request_scoped = bus.in_request;
+        if origin.is_some() {
+            bus.subscriptions.retain(|s| {
+                s.origin != origin || s.pattern != pattern || s.request_scoped != request_scoped
+            });
+        }
+        bus.next_id += 1;
+        let id = bus.next_id;
+        bus.subscriptions.push(Subscription {
+            id,
+            pattern,
+            handler,
+            once,
+            request_scoped,
+            origin,
+        });
+        Ok(Value::Int(id))
+    })
+}
+
+/// `__events_unsubscribe(id_or_pattern)` — drop one subscription by id, or
+/// every subscription to exactly that pattern. Returns how many went.
+fn events_unsubscribe(args: Vec<Value>) -> Result<Value, String> {
+    BUS.with(|bus| {
+        let mut bus = bus.borrow_mut();
+        let before = bus.subscriptions.len();
+        match args.first() {
+            Some(Value::Int(id)) => bus.subscriptions.retain(|s| s.id != *id),
+            Some(Value::String(pattern)) => bus
+                .subscriptions
+                .retain(|s| s.pattern.as_str() != pattern.as_str()),
+            other => {
+                return Err(format!(
+                    "Events.unsubscribe() expects a subscription id or a pattern, got {}",
+                    other.map_or("nothing".to_string(), Value::type_name)
+                ))
+            }
+        }
+        Ok(Value::Int((before - bus.subscriptions.len()) as i64))
+    })
+}
+
+/// `__events_handlers(topic)` — the handlers subscribed to `topic`, in
+/// subscription order, each as `[handler, takes_topic]`. `once`
+/// subscriptions are removed as they are handed out.
+fn events_handlers(args: Vec<Value>) -> Result<Value, String> {
+    let topic = string_arg(&args, 0, "Events.publish")?;
+    validate_name(&topic, "topic", false)?;
+    let handlers = BUS.with(|bus| {
+        let mut bus = bus.borrow_mut();
+        let mut handlers = Vec::new();
+        bus.subscriptions.retain(|s| {
+            if !topic_matches(&s.pattern, &topic) {
+                return true;
+            }
+            let takes_topic = Value::Bool(handler_takes_topic(&s.handler));
+            handlers.push(Value::Array(Rc::new(RefCell::new(vec![
+                s.handler.clone(),
+                takes_topic,
+            ]))));
+            !s.once
+        });
+        handlers
+    });
+    Ok(Value::Array(Rc::new(RefCell::new(handlers))))
+}
+
+/// `__events_count(topic)` — how many subscriptions `topic` would reach, or
+/// the total when `topic` is null.
+fn events_count(args: Vec<Value>) -> Result<Value, String> {
+    let topic = match args.first() {
+        Some(Value::Null) | None => None,
+        _ => Some(string_arg(&args, 0, "Events.count")?),
+    };
+    let count = BUS.with(|bus| {
+        let bus = bus.borrow();
+        match &topic {
+            Some(topic) => bus
+                .subscriptions
+                .iter()
+                .filter(|s| topic_matches(&s.pattern, topic))
+                .count(),
+            None => bus.subscriptions.len(),
+        }
+    });
+    Ok(Value::Int(count as i64))
+}
+
+fn events_clear(_args: Vec<Value>) -> Result<Value, String> {
+    clear_subscriptions();
+    Ok(Value::Null)
+}
+
+/// `__events_enqueue(topic, payload, queue)` — enqueue an `__EventDelivery`
+/// job and return its id, or null when the caller should publish inline:
+/// always under `APP_ENV=test` (so specs see the handlers run), and, with a
+/// warning, when the queue is unavailable — an event is never silently lost.
+fn events_enqueue(args: Vec<Value>) -> Result<Value, String> {
+    let topic = string_arg(&args, 0, "Events.publish_later")?;
+    validate_name(&topic, "topic", false)?;
+    if std::env::var("APP_ENV").is_ok_and(|env| env == "test") {
+        return Ok(Value::Null);
+    }
+
+    let mut job_args = HashPairs::default();
+    job_args.insert(
+        HashKey::String("topic".into()),
+        Value::String(topic.clone().into()),
+    );
+    job_args.insert(
+        HashKey::String("payload".into()),
+        args.get(1).cloned().unwrap_or(Value::Null),
+    );
+    let mut enqueue_args = vec![
+        Value::String("__EventDelivery".into()),
+        Value::Hash(Rc::new(RefCell::new(job_args))),
+    ];
+    if let Some(queue) = args.get(2) {
+        if !matches!(queue, Value::Null) {
+            enqueue_args.push(queue.clone());
+        }
+    }
+    match crate::interpreter::builtins::jobs::enqueue(enqueue_args) {
+        Ok(id) => Ok(id),
+        Err(e) => {
+            eprintln!("[events] publish_later({topic}): queue unavailable ({e}); publishing now");
+            Ok(Value::Null)
+        }
+    }
+}
+
+/// `__events_report(topic, error)` — log a handler failure.
+fn events_report(args: Vec<Value>) -> Result<Value, String> {
+    let topic = args.first().map(Value::to_string).unwrap_or_default();
+    let error = args.get(1).map(Value::to_string).unwrap_or_default();
+    eprintln!("[events] handler for '{topic}' failed: {error}");
+    Ok(Value::Null)
+}
+
+/// The Soli-level `Events` class and its job target.
+pub const EVENTS_PRELUDE: &str = r#"
+class Events {
+  static def subscribe(pattern, handler) { return __events_subscribe(pattern, handler, false); }
+  static def once(pattern, handler) { return __events_subscribe(pattern, handler, true); }
+  static def unsubscribe(id_or_pattern) { return __events_unsubscribe(id_or_pattern); }
+  static def count(topic = null) { return __events_count(topic); }
+  static def clear() { return __events_clear(); }
+
+  # Run every matching handler now; returns how many ran.
+  static def publish(topic, payload = null) {
+    let handlers = __events_handlers(topic);
+    for entry in handlers {
+      try {
+        if entry[1] { entry[0](payload, topic); } else { entry[0](payload); }
+      } catch e {
+        __events_report(topic, e);
+      }
+    }
+    return len(handlers);
+  }
+
+  # Run the handlers in a background job worker. The payload travels as
+  # JSON, so pass ids and plain hashes rather than model instances.
+  static def publish_later(topic, payload = null, queue = null) {
+    let id = __events_enqueue(topic, payload, queue);
+    if id.nil? { Events.publish(topic, payload); }
+    return id;
+  }
+}
+
+# Background-delivery job target: the Job queue worker POSTs back to
+# /_jobs/run/__EventDelivery, which publishes on the job worker.
+class __EventDelivery {
+  static def perform(args) { return Events.publish(args["topic"], args["payload"]); }
+}
+"#;
+
+/// Define the `Events` prelude class in `interpreter` if not already present.
+/// Idempotent and cheap to re-call (one env lookup).
+pub fn ensure_prelude(interpreter: &mut crate::interpreter::Interpreter) {
+    if interpreter.global_env().borrow().get("Events").is_some() {
+        return;
+    }
+    if let Err(e) = interpreter.interpret_synthetic("events_prelude", EVENTS_PRELUDE) {
+        eprintln!("events prelude error: {e}");
+        return;
+    }
+
+    if let Some(class) = interpreter.global_env().borrow().get("__EventDelivery") {
+        crate::interpreter::builtins::jobs::register_job_class_in_registry(
+            "__EventDelivery",
+            class,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_star_matches_one_segment() {
+        assert!(topic_matches("user.*", "user.created"));
+        assert!(!topic_matches("user.*", "user.profile.updated"));
+        assert!(!topic_matches("user.*", "user"));
+        assert!(topic_matches("*.created", "order.created"));
+        assert!(topic_matches("user.created", "user.created"));
+        assert!(!topic_matches("user.created", "user.deleted"));
+    }
+
+    #[test]
+    fn double_star_matches_any_number_of_segments() {
+        assert!(topic_matches("**", "user.created"));
+        assert!(topic_matches("user.**", "user"));
+        assert!(topic_matches("user.**", "user.profile.updated"));
+        assert!(topic_matches("**.updated", "user.profile.updated"));
+        assert!(!topic_matches("**.updated", "user.created"));
+    }
+
+    #[test]
+    fn publishing_rejects_wildcards_and_empty_segments() {
+        assert!(validate_name("user.*", "topic", false).is_err());
+        assert!(validate_name("user..created", "pattern", true).is_err());
+        assert!(validate_name("", "pattern", true).is_err());
+        assert!(validate_name("user.**", "pattern", true).is_ok());
+    }
+}
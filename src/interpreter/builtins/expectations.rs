@@ -334,6 +334,7 @@ pub fn register_expectation_class(env: &mut Environment) {
     let expectation_class = Class {
         name: "Expectation".to_string(),
         superclass: None,
+        interfaces: Vec::new(),
         methods: Rc::new(RefCell::new(HashMap::new())),
         static_methods: HashMap::new(),
         native_static_methods: HashMap::new(),
@@ -350,6 +351,8 @@ pub fn register_expectation_class(env: &mut Environment) {
         vm_methods: Rc::new(RefCell::new(HashMap::default())),
         vm_static_methods: Rc::new(RefCell::new(HashMap::default())),
         model_subclass_memo: std::cell::Cell::new(None),
+        struct_fields: None,
+        decorators: None,
     };
 
     env.define(
@@ -0,0 +1,681 @@
+//! `Export` — per-user data exports as compressed archives.
+//!
+//! ```soli
+//! # app/models/order.sl
+//! Export.register("orders", fn(user_id) { Order.where("doc.user_id == @id", {"id": user_id}) })
+//!
+//! # in a controller
+//! let id = Export.generate(current_user.id, formats: ["csv", "json"])
+//! ```
+//!
+//! Each registered source maps a user id to the rows to export: a model
+//! query, which is read a page at a time, or an array. `generate` enqueues an
+//! `__ExportJob`; its `perform` walks the sources and appends every page to
+//! one file per source and format, so a large table never has to fit in
+//! memory. The files, plus a `manifest.json`, are then packed into
+//! `export-<id>.tar.gz` and stored on disk (`dir:`, else `SOLI_EXPORT_DIR`,
+//! else `storage/exports`) or as a SoliDB blob in the `exports` collection.
+//!
+//! Progress goes out on the [`events`](super::events) bus: `export.started`,
+//! `export.progress` after each page, then `export.completed` or
+//! `export.failed`. As with `Events.publish_later`, the job runs inline under
+//! `APP_ENV=test` or when the queue is unavailable.
+//!
+//! Like the bus, sources and in-progress archives are per thread: every web
+//! and job worker loads `app/models` and registers the sources itself.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::interpreter::environment::Environment;
+use crate::interpreter::value::{value_to_json, HashKey, HashPairs, NativeFunction, Value};
+
+/// Rows per query page when reading a model source.
+pub const PAGE_SIZE: i64 = 500;
+
+const DEFAULT_EXPORT_DIR: &str = "storage/exports";
+const BLOB_COLLECTION: &str = "exports";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    fn parse(name: &str) -> Result<Format, String> {
+        match name {
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            other => Err(format!(
+                "Export: unknown format '{}' (expected \"csv\" or \"json\")",
+                other
+            )),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Json => "json",
+        }
+    }
+}
+
+/// One source's output file in one format.
+enum EntryWriter {
+    /// The columns are the first row's keys, sorted; later rows fill them by
+    /// name.
+    Csv {
+        writer: Box<csv::Writer<BufWriter<File>>>,
+        columns: Option<Vec<String>>,
+    },
+    /// A JSON array, written one element at a time.
+    Json { writer: BufWriter<File>, rows: u64 },
+}
+
+impl EntryWriter {
+    fn create(path: &Path, format: Format) -> Result<EntryWriter, String> {
+        let file = File::create(path).map_err(|e| format!("Export: {}", e))?;
+        let writer = BufWriter::new(file);
+        Ok(match format {
+            Format::Csv => EntryWriter::Csv {
+                writer: Box::new(csv::Writer::from_writer(writer)),
+                columns: None,
+            },
+            Format::Json => EntryWriter::Json { writer, rows: 0 },
+        })
+    }
+
+    fn write_row(
+        &mut self,
+        row: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<(), String> {
+        match self {
+            EntryWriter::Csv { writer, columns } => {
+                let columns = match columns {
+                    Some(columns) => columns,
+                    None => {
+                        let mut names: Vec<String> = row.keys().cloned().collect();
+                        names.sort();
+                        writer.write_record(&names).map_err(csv_error)?;
+                        columns.insert(names)
+                    }
+                };
+                let record = columns.iter().map(|c| csv_cell(row.get(c)));
+                writer.write_record(record).map_err(csv_error)
+            }
+            EntryWriter::Json { writer, rows } => {
+                let separator = if *rows == 0 { "[\n" } else { ",\n" };
+                writer
+                    .write_all(separator.as_bytes())
+                    .and_then(|_| serde_json::to_writer(&mut *writer, row).map_err(Into::into))
+                    .map_err(|e| format!("Export: {}", e))?;
+                *rows += 1;
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(self) -> Result<(), String> {
+        match self {
+            EntryWriter::Csv { mut writer, .. } => {
+                writer.flush().map_err(|e| format!("Export: {}", e))
+            }
+            EntryWriter::Json { mut writer, rows } => {
+                let tail: &[u8] = if rows == 0 { b"[]\n" } else { b"\n]\n" };
+                writer
+                    .write_all(tail)
+                    .and_then(|_| writer.flush())
+                    .map_err(|e| format!("Export: {}", e))
+            }
+        }
+    }
+}
+
+fn csv_error(e: csv::Error) -> String {
+    format!("Export: {}", e)
+}
+
+/// A CSV cell: strings as-is, null as empty, anything nested as JSON text.
+fn csv_cell(value: Option<&serde_json::Value>) -> String {
+    match value {
+        None | Some(serde_json::Value::Null) => String::new(),
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// An export being written: one entry per source and format, in a scratch
+/// directory that is removed when the export is dropped.
+struct ExportArchive {
+    dir: tempfile::TempDir,
+    formats: Vec<Format>,
+    entries: Vec<(String, EntryWriter)>,
+    counts: Vec<(String, u64)>,
+}
+
+impl ExportArchive {
+    fn new(formats: Vec<Format>) -> Result<ExportArchive, String> {
+        let dir = tempfile::Builder::new()
+            .prefix("soli-export-")
+            .tempdir()
+            .map_err(|e| format!("Export: {}", e))?;
+        Ok(ExportArchive {
+            dir,
+            formats,
+            entries: Vec::new(),
+            counts: Vec::new(),
+        })
+    }
+
+    fn write(&mut self, source: &str, rows: &[Value]) -> Result<u64, String> {
+        if !self.counts.iter().any(|(name, _)| name == source) {
+            for &format in &self.formats {
+                let file_name = format!("{}.{}", source, format.extension());
+                let writer = EntryWriter::create(&self.dir.path().join(&file_name), format)?;
+                self.entries.push((file_name, writer));
+            }
+            self.counts.push((source.to_string(), 0));
+        }
+
+        let prefix = format!("{}.", source);
+        for row in rows {
+            let row = match value_to_json(row)? {
+                serde_json::Value::Object(map) => map,
+                _ => {
+                    return Err(format!(
+                        "Export: source '{}' must return records or hashes, got {}",
+                        source,
+                        row.type_name()
+                    ))
+                }
+            };
+            for (file_name, writer) in self.entries.iter_mut() {
+                if file_name.starts_with(&prefix) {
+                    writer.write_row(&row)?;
+                }
+            }
+        }
+
+        let count = self
+            .counts
+            .iter_mut()
+            .find(|(name, _)| name == source)
+            .map(|(_, count)| count)
+            .expect("source registered above");
+        *count += rows.len() as u64;
+        Ok(*count)
+    }
+
+    /// Close every entry, add `manifest.json` and pack the lot as a gzipped
+    /// tarball at `dest`.
+    fn pack(self, manifest: serde_json::Value, dest: &Path) -> Result<Vec<String>, String> {
+        let io = |e: std::io::Error| format!("Export: {}", e);
+        let mut files = Vec::new();
+        for (file_name, writer) in self.entries {
+            writer.finish()?;
+            files.push(file_name);
+        }
+        let manifest = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+        std::fs::write(self.dir.path().join("manifest.json"), manifest).map_err(io)?;
+        files.push("manifest.json".to_string());
+
+        let encoder = flate2::write::GzEncoder::new(
+            BufWriter::new(File::create(dest).map_err(io)?),
+            flate2::Compression::default(),
+        );
+        let mut tar = tar::Builder::new(encoder);
+        for file_name in &files {
+            tar.append_path_with_name(self.dir.path().join(file_name), file_name)
+                .map_err(io)?;
+        }
+        tar.into_inner()
+            .and_then(|gz| gz.finish())
+            .and_then(|mut w| w.flush())
+            .map_err(io)?;
+        Ok(files)
+    }
+}
+
+thread_local! {
+    static SOURCES: RefCell<Vec<(String, Value)>> = const { RefCell::new(Vec::new()) };
+    static ARCHIVES: RefCell<HashMap<String, ExportArchive>> = RefCell::new(HashMap::new());
+}
+
+type ExportBuiltin = fn(Vec<Value>) -> Result<Value, String>;
+
+/// Register the native `__export_*` builtins. The `Export` class itself is
+/// defined by [`EXPORT_PRELUDE`], loaded via [`ensure_prelude`].
+pub fn register_export_builtins(env: &mut Environment) {
+    let defs: [(&str, Option<usize>, ExportBuiltin); 7] = [
+        ("__export_register", Some(2), export_register),
+        ("__export_sources", Some(0), export_sources),
+        ("__export_begin", Some(2), export_begin),
+        ("__export_write", Some(3), export_write),
+        ("__export_finish", Some(4), export_finish),
+        ("__export_abort", Some(1), export_abort),
+        ("__export_enqueue", Some(2), export_enqueue),
+    ];
+    for (name, arity, func) in defs {
+        env.define(
+            name.to_string(),
+            Value::NativeFunction(NativeFunction::new(name, arity, func)),
+        );
+    }
+}
+
+/// Forget every source registered on this thread.
+pub fn clear_sources() {
+    SOURCES.with(|sources| sources.borrow_mut().clear());
+}
+
+fn string_arg(args: &[Value], index: usize, what: &str) -> Result<String, String> {
+    match args.get(index) {
+        Some(Value::String(s)) => Ok(s.to_string()),
+        Some(Value::Int(n)) => Ok(n.to_string()),
+        other => Err(format!(
+            "Export: expected {} as a string, got {}",
+            what,
+            other.map_or("nothing".to_string(), Value::type_name)
+        )),
+    }
+}
+
+/// Source names become archive file names, so keep them to a safe alphabet.
+fn validate_source_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Export: invalid source name '{}' (use letters, digits, '_' and '-')",
+            name
+        ))
+    }
+}
+
+/// `__export_register(name, source)` — `source(user_id)` returns the rows.
+/// Registering a name again replaces it.
+fn export_register(args: Vec<Value>) -> Result<Value, String> {
+    let name = string_arg(&args, 0, "a source name")?;
+    validate_source_name(&name)?;
+    let source = args.get(1).cloned().unwrap_or(Value::Null);
+    if !source.is_callable() {
+        return Err(format!(
+            "Export.register() expects a function of the user id, got {}",
+            source.type_name()
+        ));
+    }
+    SOURCES.with(|sources| {
+        let mut sources = sources.borrow_mut();
+        match sources.iter_mut().find(|(n, _)| *n == name) {
+            Some(entry) => entry.1 = source,
+            None => sources.push((name, source)),
+        }
+    });
+    Ok(Value::Null)
+}
+
+/// `__export_sources()` — `[name, source]` pairs in registration order.
+fn export_sources(_args: Vec<Value>) -> Result<Value, String> {
+    let pairs = SOURCES.with(|sources| {
+        sources
+            .borrow()
+            .iter()
+            .map(|(name, source)| {
+                Value::Array(Rc::new(RefCell::new(vec![
+                    Value::String(name.clone().into()),
+                    source.clone(),
+                ])))
+            })
+            .collect()
+    });
+    Ok(Value::Array(Rc::new(RefCell::new(pairs))))
+}
+
+fn parse_formats(value: Option<&Value>) -> Result<Vec<Format>, String> {
+    let mut formats = Vec::new();
+    match value {
+        Some(Value::Array(items)) => {
+            for item in items.borrow().iter() {
+                let format = match item {
+                    Value::String(s) => Format::parse(s)?,
+                    other => Format::parse(&other.to_string())?,
+                };
+                if !formats.contains(&format) {
+                    formats.push(format);
+                }
+            }
+        }
+        Some(Value::String(s)) => formats.push(Format::parse(s)?),
+        _ => return Err("Export: formats must be an array like [\"csv\", \"json\"]".to_string()),
+    }
+    if formats.is_empty() {
+        return Err("Export: at least one format is required".to_string());
+    }
+    Ok(formats)
+}
+
+/// `__export_begin(id, formats)` — open the scratch archive for `id`.
+fn export_begin(args: Vec<Value>) -> Result<Value, String> {
+    let id = string_arg(&args, 0, "an export id")?;
+    let formats = parse_formats(args.get(1))?;
+    let archive = ExportArchive::new(formats)?;
+    ARCHIVES.with(|archives| archives.borrow_mut().insert(id, archive));
+    Ok(Value::Null)
+}
+
+/// `__export_write(id, source, rows)` — append a page of rows; returns the
+/// source's running row count.
+fn export_write(args: Vec<Value>) -> Result<Value, String> {
+    let id = string_arg(&args, 0, "an export id")?;
+    let source = string_arg(&args, 1, "a source name")?;
+    let rows = match args.get(2) {
+        Some(Value::Array(rows)) => rows.borrow().clone(),
+        other => {
+            return Err(format!(
+                "Export: source '{}' must return a query or an array, got {}",
+                source,
+                other.map_or("nothing".to_string(), Value::type_name)
+            ))
+        }
+    };
+    ARCHIVES.with(|archives| {
+        let mut archives = archives.borrow_mut();
+        let archive = archives
+            .get_mut(&id)
+            .ok_or_else(|| format!("Export: no export '{}' in progress", id))?;
+        archive.write(&source, &rows).map(|n| Value::Int(n as i64))
+    })
+}
+
+/// `dir`, else `SOLI_EXPORT_DIR`, else `storage/exports`.
+fn export_dir(dir: Option<&Value>) -> PathBuf {
+    if let Some(Value::String(dir)) = dir {
+        return PathBuf::from(dir.to_string());
+    }
+    std::env::var("SOLI_EXPORT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_EXPORT_DIR))
+}
+
+/// `__export_finish(id, user_id, storage, dir)` — pack the archive and store
+/// it, `storage` being `"disk"` (under `dir`) or `"blob"`. Returns `{ id,
+/// file, bytes, files, rows, path | blob_id }`.
+fn export_finish(args: Vec<Value>) -> Result<Value, String> {
+    let id = string_arg(&args, 0, "an export id")?;
+    let user_id = args
+        .get(1)
+        .map(value_to_json)
+        .transpose()?
+        .unwrap_or(serde_json::Value::Null);
+    let storage = string_arg(&args, 2, "the storage")?;
+    if storage != "disk" && storage != "blob" {
+        return Err(format!(
+            "Export: unknown storage '{}' (expected \"disk\" or \"blob\")",
+            storage
+        ));
+    }
+    let archive = ARCHIVES
+        .with(|archives| archives.borrow_mut().remove(&id))
+        .ok_or_else(|| format!("Export: no export '{}' in progress", id))?;
+
+    let rows: serde_json::Map<String, serde_json::Value> = archive
+        .counts
+        .iter()
+        .map(|(name, count)| (name.clone(), serde_json::Value::from(*count)))
+        .collect();
+    let manifest = serde_json::json!({
+        "id": id,
+        "user_id": user_id,
+        "generated_at": chrono::Utc::now().to_rfc3339(),
+        "formats": archive.formats.iter().map(|f| f.extension()).collect::<Vec<_>>(),
+        "rows": rows,
+    });
+
+    let file_name = format!("export-{}.tar.gz", id);
+    let mut result = HashPairs::default();
+    let dest = match storage.as_str() {
+        "disk" => {
+            let dir = export_dir(args.get(3));
+            std::fs::create_dir_all(&dir).map_err(|e| format!("Export: {}", e))?;
+            dir.join(&file_name)
+        }
+        _ => archive.dir.path().join(&file_name),
+    };
+    let files = archive.pack(manifest.clone(), &dest)?;
+    let bytes = std::fs::metadata(&dest)
+        .map_err(|e| format!("Export: {}", e))?
+        .len();
+
+    if storage == "disk" {
+        result.insert(
+            HashKey::String("path".into()),
+            Value::String(dest.to_string_lossy().to_string().into()),
+        );
+    } else {
+        let blob_id = store_blob(&dest, &file_name)?;
+        result.insert(
+            HashKey::String("blob_id".into()),
+            Value::String(blob_id.into()),
+        );
+    }
+    result.insert(HashKey::String("id".into()), Value::String(id.into()));
+    result.insert(
+        HashKey::String("file".into()),
+        Value::String(file_name.into()),
+    );
+    result.insert(HashKey::String("bytes".into()), Value::Int(bytes as i64));
+    result.insert(
+        HashKey::String("files".into()),
+        Value::Array(Rc::new(RefCell::new(
+            files.into_iter().map(|f| Value::String(f.into())).collect(),
+        ))),
+    );
+    result.insert(
+        HashKey::String("rows".into()),
+        crate::interpreter::value::json_to_value(manifest["rows"].clone())?,
+    );
+    Ok(Value::Hash(Rc::new(RefCell::new(result))))
+}
+
+fn store_blob(path: &Path, file_name: &str) -> Result<String, String> {
+    let addr = crate::interpreter::builtins::uploads::get_solidb_address()
+        .ok_or_else(|| "Export: blob storage needs SOLIDB_HOST".to_string())?;
+    let client = crate::interpreter::builtins::uploads::blob_client(&addr)?;
+    let data = std::fs::read(path).map_err(|e| format!("Export: {}", e))?;
+    client
+        .store_blob(BLOB_COLLECTION, &data, file_name, "application/gzip")
+        .map_err(|e| format!("Export: failed to store blob: {}", e))
+}
+
+/// `__export_abort(id)` — drop a failed export's scratch files.
+fn export_abort(args: Vec<Value>) -> Result<Value, String> {
+    let id = string_arg(&args, 0, "an export id")?;
+    ARCHIVES.with(|archives| archives.borrow_mut().remove(&id));
+    Ok(Value::Null)
+}
+
+/// `__export_enqueue(args, queue)` — enqueue an `__ExportJob` and return its
+/// id, or null when the caller should run the export inline: always under
+/// `APP_ENV=test`, and, with a warning, when the queue is unavailable.
+fn export_enqueue(args: Vec<Value>) -> Result<Value, String> {
+    if std::env::var("APP_ENV").is_ok_and(|env| env == "test") {
+        return Ok(Value::Null);
+    }
+    let mut enqueue_args = vec![
+        Value::String("__ExportJob".into()),
+        args.first().cloned().unwrap_or(Value::Null),
+    ];
+    if let Some(queue) = args.get(1) {
+        if !matches!(queue, Value::Null) {
+            enqueue_args.push(queue.clone());
+        }
+    }
+    match crate::interpreter::builtins::jobs::enqueue(enqueue_args) {
+        Ok(id) => Ok(id),
+        Err(e) => {
+            eprintln!("[export] generate: queue unavailable ({e}); exporting now");
+            Ok(Value::Null)
+        }
+    }
+}
+
+/// The Soli-level `Export` class and its job target.
+pub const EXPORT_PRELUDE: &str = r#"
+class Export {
+  static def register(name, source) { return __export_register(name, source); }
+
+  # Start an export of every registered source for `user_id`. Returns the
+  # export id that the `export.*` events carry.
+  static def generate(user_id, formats = ["json"], storage = "disk", dir = null, queue = null) {
+    let args = {"id": ulid(), "user_id": user_id, "formats": formats, "storage": storage, "dir": dir};
+    if __export_enqueue(args, queue).nil? { Export.run(args); }
+    return args["id"];
+  }
+
+  # Write the archive now. Called by the job; returns the stored archive's
+  # details, or null when the export failed.
+  static def run(args) {
+    let id = args["id"];
+    let user_id = args["user_id"];
+    try {
+      __export_begin(id, args["formats"]);
+      Events.publish("export.started", {"id": id, "user_id": user_id});
+      for pair in __export_sources() {
+        let source = pair[0];
+        let rows = pair[1](user_id);
+        if type(rows) == "array" {
+          let total = __export_write(id, source, rows);
+          Events.publish("export.progress", {"id": id, "user_id": user_id, "source": source, "rows": total});
+        } else {
+          let offset = 0;
+          let done = false;
+          while !done {
+            let page = rows.offset(offset).limit(__EXPORT_PAGE_SIZE).all;
+            let total = __export_write(id, source, page);
+            Events.publish("export.progress", {"id": id, "user_id": user_id, "source": source, "rows": total});
+            offset = offset + __EXPORT_PAGE_SIZE;
+            done = len(page) < __EXPORT_PAGE_SIZE;
+          }
+        }
+      }
+      let result = __export_finish(id, user_id, args["storage"] ?? "disk", args["dir"]);
+      result["user_id"] = user_id;
+      Events.publish("export.completed", result);
+      return result;
+    } catch e {
+      __export_abort(id);
+      Events.publish("export.failed", {"id": id, "user_id": user_id, "error": str(e)});
+      return null;
+    }
+  }
+}
+
+# Background job target: the Job queue worker POSTs back to
+# /_jobs/run/__ExportJob, which writes the archive on the job worker.
+class __ExportJob {
+  static def perform(args) { return Export.run(args); }
+}
+"#;
+
+/// Define the `Export` prelude class in `interpreter` if not already present.
+/// Needs the `Events` prelude, which it publishes progress on.
+pub fn ensure_prelude(interpreter: &mut crate::interpreter::Interpreter) {
+    if interpreter.global_env().borrow().get("Export").is_some() {
+        return;
+    }
+    crate::interpreter::builtins::events::ensure_prelude(interpreter);
+    interpreter
+        .global_env()
+        .borrow_mut()
+        .define("__EXPORT_PAGE_SIZE".to_string(), Value::Int(PAGE_SIZE));
+    if let Err(e) = interpreter.interpret_synthetic("export_prelude", EXPORT_PRELUDE) {
+        eprintln!("export prelude error: {e}");
+        return;
+    }
+
+    if let Some(class) = interpreter.global_env().borrow().get("__ExportJob") {
+        crate::interpreter::builtins::jobs::register_job_class_in_registry("__ExportJob", class);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(pairs: &[(&str, Value)]) -> Value {
+        let mut h = HashPairs::default();
+        for (k, v) in pairs {
+            h.insert(HashKey::String((*k).into()), v.clone());
+        }
+        Value::Hash(Rc::new(RefCell::new(h)))
+    }
+
+    fn unpack(path: &Path) -> HashMap<String, String> {
+        let gz = flate2::read::GzDecoder::new(File::open(path).unwrap());
+        let mut tar = tar::Archive::new(gz);
+        let mut files = HashMap::new();
+        for entry in tar.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let name = entry.path().unwrap().to_string_lossy().to_string();
+            let mut body = String::new();
+            std::io::Read::read_to_string(&mut entry, &mut body).unwrap();
+            files.insert(name, body);
+        }
+        files
+    }
+
+    #[test]
+    fn pages_append_to_one_file_per_source_and_format() {
+        let mut archive = ExportArchive::new(vec![Format::Csv, Format::Json]).unwrap();
+        let page = |names: &[&str]| -> Vec<Value> {
+            names
+                .iter()
+                .map(|n| hash(&[("name", Value::String((*n).into())), ("n", Value::Int(1))]))
+                .collect()
+        };
+        assert_eq!(archive.write("users", &page(&["a, b", "c"])).unwrap(), 2);
+        assert_eq!(archive.write("users", &page(&["d"])).unwrap(), 3);
+        assert_eq!(archive.write("orders", &[]).unwrap(), 0);
+
+        let out = tempfile::tempdir().unwrap();
+        let dest = out.path().join("x.tar.gz");
+        let files = archive.pack(serde_json::json!({"id": "x"}), &dest).unwrap();
+        assert_eq!(
+            files,
+            [
+                "users.csv",
+                "users.json",
+                "orders.csv",
+                "orders.json",
+                "manifest.json"
+            ]
+        );
+
+        let unpacked = unpack(&dest);
+        assert_eq!(unpacked["users.csv"], "n,name\n1,\"a, b\"\n1,c\n1,d\n");
+        let users: serde_json::Value = serde_json::from_str(&unpacked["users.json"]).unwrap();
+        assert_eq!(users.as_array().unwrap().len(), 3);
+        assert_eq!(unpacked["orders.json"], "[]\n");
+        assert_eq!(unpacked["orders.csv"], "");
+    }
+
+    #[test]
+    fn rejects_unknown_formats_and_unsafe_source_names() {
+        let formats = Value::Array(Rc::new(RefCell::new(vec![Value::String("xml".into())])));
+        assert!(parse_formats(Some(&formats))
+            .unwrap_err()
+            .contains("unknown format 'xml'"));
+        assert!(validate_source_name("../etc").is_err());
+        assert!(validate_source_name("order_items").is_ok());
+    }
+}
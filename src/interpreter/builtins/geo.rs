@@ -25,12 +25,26 @@
 //! The box is deliberately a little generous — it is a square around a circle,
 //! so it returns corners the radius excludes. Filter those out with
 //! `Geo.distance` afterwards; that is the point of the two-step.
+//!
+//! # Points
+//!
+//! `Point.new(lat, lon)` is a position as a value. Its fields are the
+//! `{ "lat", "lon" }` shape a model's `geo_index` reads, so a point saves and
+//! serializes to JSON as-is. `to_geojson()` and `Geo.feature_collection(...)`
+//! produce the GeoJSON that map libraries consume:
+//!
+//! ```soli
+//! store.location = Point.new(48.8566, 2.3522)
+//! store.location.distance_to(Point.new(51.5074, -0.1278))   # metres
+//! render_json(Geo.feature_collection(Store.near(here, km: 5), "location"))
+//! ```
 
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 
 use crate::interpreter::environment::Environment;
-use crate::interpreter::value::{Class, HashKey, HashPairs, NativeFunction, Value};
+use crate::interpreter::value::{Class, HashKey, HashPairs, Instance, NativeFunction, Value};
 
 /// Mean Earth radius in metres (IUGG). Good to ~0.5% anywhere on the globe,
 /// which is far better than consumer GPS.
@@ -246,6 +260,230 @@ fn hash_value(pairs: HashPairs) -> Value {
     Value::Hash(Rc::new(std::cell::RefCell::new(pairs)))
 }
 
+thread_local! {
+    /// The registered `Point` class, shared by every point native code hands
+    /// back (same scheme as the DateTime instance class).
+    static POINT_CLASS: RefCell<Option<Rc<Class>>> = const { RefCell::new(None) };
+}
+
+/// A `Point` at `lat`/`lon`, validated.
+pub fn point_value(lat: f64, lon: f64, function: &str) -> Result<Value, String> {
+    check_coordinates(lat, lon, function)?;
+    let class = POINT_CLASS
+        .with(|c| c.borrow().clone())
+        .ok_or_else(|| "Point class not registered on this thread".to_string())?;
+    let mut inst = Instance::new(class);
+    inst.set("lat".to_string(), Value::Float(lat));
+    inst.set("lon".to_string(), Value::Float(lon));
+    Ok(Value::Instance(Rc::new(RefCell::new(inst))))
+}
+
+/// `(lat, lon)` of a `Point`, a `{ "lat", "lon" }` (or `"lng"`) hash, or a
+/// GeoJSON `{ "type": "Point", "coordinates": [lon, lat] }`.
+pub fn coordinates_of(value: &Value) -> Option<(f64, f64)> {
+    fn num(value: Option<Value>) -> Option<f64> {
+        match value? {
+            Value::Int(n) => Some(n as f64),
+            Value::Float(f) => Some(f),
+            _ => None,
+        }
+    }
+    match value {
+        Value::Instance(inst) => {
+            let inst = inst.borrow();
+            if inst.class.name != "Point" {
+                return None;
+            }
+            Some((num(inst.get("lat"))?, num(inst.get("lon"))?))
+        }
+        Value::Hash(hash) => {
+            let hash = hash.borrow();
+            let get = |key: &str| hash.get(&HashKey::String(key.into())).cloned();
+            if let Some(Value::Array(coordinates)) = get("coordinates") {
+                if !matches!(get("type"), Some(Value::String(t)) if &*t == "Point") {
+                    return None;
+                }
+                let coordinates = coordinates.borrow();
+                return Some((
+                    num(coordinates.get(1).cloned())?,
+                    num(coordinates.first().cloned())?,
+                ));
+            }
+            Some((num(get("lat"))?, num(get("lon").or_else(|| get("lng")))?))
+        }
+        _ => None,
+    }
+}
+
+/// GeoJSON geometry for a position — note the `[lon, lat]` order.
+fn geojson_point(lat: f64, lon: f64) -> serde_json::Value {
+    serde_json::json!({ "type": "Point", "coordinates": [lon, lat] })
+}
+
+/// A GeoJSON FeatureCollection with one Feature per record (model instance
+/// or hash) whose `field` holds a position; the other serializable fields
+/// become the Feature's `properties`. Records without a position are skipped.
+fn feature_collection(records: &[Value], field: &str) -> Result<Value, String> {
+    let mut features = Vec::with_capacity(records.len());
+    for record in records {
+        let position = match record {
+            Value::Instance(inst) => inst.borrow().get(field),
+            Value::Hash(hash) => hash.borrow().get(&HashKey::String(field.into())).cloned(),
+            _ => None,
+        };
+        let Some((lat, lon)) = position.as_ref().and_then(coordinates_of) else {
+            continue;
+        };
+        let mut properties = match crate::interpreter::value::value_to_json(record)? {
+            serde_json::Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        };
+        properties.remove(field);
+        let mut feature = serde_json::json!({
+            "type": "Feature",
+            "geometry": geojson_point(lat, lon),
+        });
+        if let Some(id) = properties.get("_key").or_else(|| properties.get("id")) {
+            feature["id"] = id.clone();
+        }
+        feature["properties"] = serde_json::Value::Object(properties);
+        features.push(feature);
+    }
+    crate::interpreter::value::json_to_value(serde_json::json!({
+        "type": "FeatureCollection",
+        "features": features,
+    }))
+}
+
+fn register_point_class(env: &mut Environment) {
+    fn this_point(args: &[Value], method: &str) -> Result<(f64, f64), String> {
+        args.first()
+            .and_then(coordinates_of)
+            .ok_or_else(|| format!("Point.{}() called on non-Point", method))
+    }
+
+    let mut statics: HashMap<String, Rc<NativeFunction>> = HashMap::new();
+
+    // Point.new(lat, lon) -> Point
+    statics.insert(
+        "new".to_string(),
+        Rc::new(NativeFunction::new("Point.new", Some(2), |args| {
+            let lat = number(&args[0], "Point.new", "lat")?;
+            let lon = number(&args[1], "Point.new", "lon")?;
+            point_value(lat, lon, "Point.new")
+        })),
+    );
+
+    // Point.cast(point | {lat, lon} | GeoJSON | null) -> Point or null
+    statics.insert(
+        "cast".to_string(),
+        Rc::new(NativeFunction::new("Point.cast", Some(1), |args| {
+            if matches!(args[0], Value::Null) {
+                return Ok(Value::Null);
+            }
+            let (lat, lon) = coordinates_of(&args[0]).ok_or_else(|| {
+                format!(
+                    "Point.cast() expects a Point, a {{ lat, lon }} hash or a GeoJSON Point, got {}",
+                    args[0].type_name()
+                )
+            })?;
+            point_value(lat, lon, "Point.cast")
+        })),
+    );
+
+    let mut methods: HashMap<String, Rc<NativeFunction>> = HashMap::new();
+
+    // point.lng — alias of the `lon` field, matching the Geo.* naming
+    methods.insert(
+        "lng".to_string(),
+        Rc::new(NativeFunction::new("Point.lng", Some(0), |args| {
+            Ok(Value::Float(this_point(&args, "lng")?.1))
+        })),
+    );
+
+    // point.distance_to(other) -> Float (metres)
+    methods.insert(
+        "distance_to".to_string(),
+        Rc::new(NativeFunction::new("Point.distance_to", Some(1), |args| {
+            let (lat, lon) = this_point(&args, "distance_to")?;
+            let (other_lat, other_lon) = args.get(1).and_then(coordinates_of).ok_or_else(|| {
+                "Point.distance_to() expects a Point or a { lat, lon } hash".to_string()
+            })?;
+            Ok(Value::Float(haversine_metres(
+                lat, lon, other_lat, other_lon,
+            )))
+        })),
+    );
+
+    // point.to_geojson() -> { "type": "Point", "coordinates": [lon, lat] }
+    methods.insert(
+        "to_geojson".to_string(),
+        Rc::new(NativeFunction::new("Point.to_geojson", Some(0), |args| {
+            let (lat, lon) = this_point(&args, "to_geojson")?;
+            crate::interpreter::value::json_to_value(geojson_point(lat, lon))
+        })),
+    );
+
+    // point.to_h() -> { "lat": ..., "lon": ... }
+    methods.insert(
+        "to_h".to_string(),
+        Rc::new(NativeFunction::new("Point.to_h", Some(0), |args| {
+            let (lat, lon) = this_point(&args, "to_h")?;
+            let mut pairs = HashPairs::default();
+            pairs.insert(HashKey::String("lat".into()), Value::Float(lat));
+            pairs.insert(HashKey::String("lon".into()), Value::Float(lon));
+            Ok(hash_value(pairs))
+        })),
+    );
+
+    // point.to_s() -> "lat,lon" — the form map URLs and templates want
+    methods.insert(
+        "to_s".to_string(),
+        Rc::new(NativeFunction::new("Point.to_s", Some(0), |args| {
+            let (lat, lon) = this_point(&args, "to_s")?;
+            Ok(Value::String(format!("{},{}", lat, lon).into()))
+        })),
+    );
+
+    // point.geohash(precision?) -> String
+    methods.insert(
+        "geohash".to_string(),
+        Rc::new(NativeFunction::new("Point.geohash", None, |args| {
+            let (lat, lon) = this_point(&args, "geohash")?;
+            let precision = match args.get(1) {
+                None | Some(Value::Null) => 9,
+                Some(value) => {
+                    let n = number(value, "Point.geohash", "precision")? as i64;
+                    if n < 1 || n as usize > MAX_PRECISION {
+                        return Err(format!(
+                            "Point.geohash(): precision must be between 1 and {}",
+                            MAX_PRECISION
+                        ));
+                    }
+                    n as usize
+                }
+            };
+            Ok(Value::String(geohash(lat, lon, precision).into()))
+        })),
+    );
+
+    let class = Rc::new(Class {
+        name: "Point".to_string(),
+        superclass: None,
+        methods: Rc::new(RefCell::new(HashMap::new())),
+        static_methods: HashMap::new(),
+        native_static_methods: statics,
+        native_methods: methods,
+        static_fields: Rc::new(RefCell::new(HashMap::new())),
+        fields: HashMap::new(),
+        constructor: None,
+        nested_classes: Rc::new(RefCell::new(HashMap::new())),
+        ..Default::default()
+    });
+    POINT_CLASS.with(|c| *c.borrow_mut() = Some(class.clone()));
+    env.define("Point".to_string(), Value::Class(class));
+}
+
 pub fn register_geo_builtins(env: &mut Environment) {
     let mut statics: HashMap<String, Rc<NativeFunction>> = HashMap::new();
 
@@ -341,6 +579,36 @@ pub fn register_geo_builtins(env: &mut Environment) {
         })),
     );
 
+    // Geo.feature_collection(records, field = "location") -> GeoJSON FeatureCollection
+    statics.insert(
+        "feature_collection".to_string(),
+        Rc::new(NativeFunction::new(
+            "Geo.feature_collection",
+            None,
+            |args| {
+                let records = match args.first() {
+                    Some(Value::Array(records)) => records.borrow().clone(),
+                    _ => {
+                        return Err(
+                            "Geo.feature_collection() expects an array of records".to_string()
+                        )
+                    }
+                };
+                let field = match args.get(1) {
+                    None | Some(Value::Null) => "location".to_string(),
+                    Some(Value::String(s)) | Some(Value::Symbol(s)) => s.to_string(),
+                    Some(other) => {
+                        return Err(format!(
+                            "Geo.feature_collection(): field must be a string, got {}",
+                            other.type_name()
+                        ))
+                    }
+                };
+                feature_collection(&records, &field)
+            },
+        )),
+    );
+
     let class = Rc::new(Class {
         name: "Geo".to_string(),
         superclass: None,
@@ -356,6 +624,7 @@ pub fn register_geo_builtins(env: &mut Environment) {
     });
 
     env.define("Geo".to_string(), Value::Class(class));
+    register_point_class(env);
 }
 
 #[cfg(test)]
@@ -493,4 +762,53 @@ mod tests {
         assert!(check_coordinates(0.0, 181.0, "test").is_err());
         assert!(check_coordinates(-90.0, 180.0, "test").is_ok());
     }
+
+    fn hash(entries: &[(&str, Value)]) -> Value {
+        let mut pairs = HashPairs::default();
+        for (key, value) in entries {
+            pairs.insert(HashKey::String((*key).into()), value.clone());
+        }
+        hash_value(pairs)
+    }
+
+    /// GeoJSON is `[lon, lat]` — the opposite of everything else — so a swap
+    /// here silently puts every store in the wrong hemisphere.
+    #[test]
+    fn coordinates_are_read_from_hashes_and_geojson() {
+        let plain = hash(&[("lat", Value::Float(48.0)), ("lon", Value::Float(2.0))]);
+        let lng = hash(&[("lat", Value::Int(48)), ("lng", Value::Int(2))]);
+        let geojson = crate::interpreter::value::json_to_value(geojson_point(48.0, 2.0)).unwrap();
+        for value in [plain, lng, geojson] {
+            assert_eq!(coordinates_of(&value), Some((48.0, 2.0)));
+        }
+        let polygon = hash(&[
+            ("type", Value::String("Polygon".into())),
+            ("coordinates", Value::Array(Rc::new(RefCell::new(vec![])))),
+        ]);
+        assert_eq!(coordinates_of(&polygon), None);
+        assert_eq!(coordinates_of(&Value::String("48,2".into())), None);
+    }
+
+    #[test]
+    fn feature_collections_skip_records_without_a_position() {
+        let records = [
+            hash(&[
+                ("_key", Value::String("a".into())),
+                ("name", Value::String("Louvre".into())),
+                (
+                    "location",
+                    hash(&[("lat", Value::Float(48.86)), ("lon", Value::Float(2.33))]),
+                ),
+            ]),
+            hash(&[("name", Value::String("Nowhere".into()))]),
+        ];
+        let collection = feature_collection(&records, "location").unwrap();
+        let json = crate::interpreter::value::value_to_json(&collection).unwrap();
+        let features = json["features"].as_array().unwrap();
+        assert_eq!(features.len(), 1);
+        assert_eq!(features[0]["id"], "a");
+        assert_eq!(features[0]["geometry"]["coordinates"][0], 2.33);
+        assert_eq!(features[0]["properties"]["name"], "Louvre");
+        assert!(features[0]["properties"].get("location").is_none());
+    }
 }
@@ -0,0 +1,451 @@
+//! HTTP caching helpers for controller actions: `fresh_when`, `stale?` and
+//! `expires_in`.
+//!
+//! `fresh_when(etag: ..., last_modified: ...)` sets the response's validators
+//! and checks them against the request's `If-None-Match` /
+//! `If-Modified-Since`. When the browser's copy is still current the response
+//! becomes a bodiless `304 Not Modified`: a later `render(...)` returns at once
+//! without rendering its view, and whatever the action returns is swapped for
+//! the 304 when the response is finalized. `stale?` runs the same check and
+//! returns whether the action should go on and render.
+//!
+//! Static files carry their own ETags (see `serve`), and rendered pages get a
+//! content-derived one in `template::html_response`; validators set here take
+//! precedence over the latter.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::interpreter::environment::Environment;
+use crate::interpreter::value::{HashKey, HashPairs, NativeFunction, Value};
+
+/// Validators and caching policy set by the action for its response.
+#[derive(Default)]
+struct CacheState {
+    etag: Option<String>,
+    /// Unix seconds.
+    last_modified: Option<i64>,
+    cache_control: Option<String>,
+    /// The request's validators matched: answer with a 304.
+    not_modified: bool,
+}
+
+thread_local! {
+    static CACHE_STATE: RefCell<CacheState> = RefCell::new(CacheState::default());
+}
+
+/// Drop the caching state a previous request on this thread left behind.
+pub fn reset_for_new_request() {
+    CACHE_STATE.with(|s| *s.borrow_mut() = CacheState::default());
+}
+
+/// Whether `fresh_when` / `stale?` found the client's copy current.
+pub fn is_not_modified() -> bool {
+    CACHE_STATE.with(|s| s.borrow().not_modified)
+}
+
+/// The response `render(...)` returns instead of rendering once the request
+/// is known to be fresh.
+pub fn not_modified_response() -> Value {
+    let mut result = HashPairs::default();
+    result.insert(HashKey::String("status".into()), Value::Int(304));
+    result.insert(
+        HashKey::String("headers".into()),
+        Value::Hash(Rc::new(RefCell::new(HashPairs::default()))),
+    );
+    result.insert(HashKey::String("body".into()), Value::String("".into()));
+    Value::Hash(Rc::new(RefCell::new(result)))
+}
+
+/// Apply this request's validators and `Cache-Control` to its response, and
+/// turn a successful response into a 304 when the client's copy is fresh.
+/// Redirects and errors are left alone. Called once per request, when the
+/// response is finalized.
+pub fn finalize_response(
+    status: &mut u16,
+    headers: &mut Vec<(String, String)>,
+    body: &mut Vec<u8>,
+) {
+    let state = CACHE_STATE.with(|s| std::mem::take(&mut *s.borrow_mut()));
+    let success = (200..300).contains(status);
+    if !success && *status != 304 {
+        return;
+    }
+    let mut set = |name: &str, value: String| {
+        headers.retain(|(k, _)| !k.eq_ignore_ascii_case(name));
+        headers.push((name.to_string(), value));
+    };
+    if let Some(etag) = state.etag {
+        set("ETag", etag);
+    }
+    if let Some(secs) = state.last_modified {
+        set("Last-Modified", http_date(secs));
+    }
+    if let Some(cache_control) = state.cache_control {
+        set("Cache-Control", cache_control);
+    }
+    if state.not_modified && success {
+        *status = 304;
+        body.clear();
+        headers.retain(|(k, _)| {
+            !k.eq_ignore_ascii_case("content-type") && !k.eq_ignore_ascii_case("content-length")
+        });
+    }
+}
+
+pub fn register_http_cache_builtins(env: &mut Environment) {
+    // fresh_when(etag: value, last_modified: time) -> Bool — set the
+    // validators; true (and a 304 response) when the client's copy is current.
+    env.define(
+        "fresh_when".to_string(),
+        Value::NativeFunction(NativeFunction::new("fresh_when", Some(1), |args| {
+            Ok(Value::Bool(check_freshness("fresh_when", &args[0])?))
+        })),
+    );
+
+    // stale?(etag: value, last_modified: time) -> Bool — the inverse: true
+    // when the action has to render.
+    env.define(
+        "stale?".to_string(),
+        Value::NativeFunction(NativeFunction::new("stale?", Some(1), |args| {
+            Ok(Value::Bool(!check_freshness("stale?", &args[0])?))
+        })),
+    );
+
+    // expires_in(seconds, shared: false, must_revalidate: false) — let caches
+    // reuse the response for `seconds` (an Int, Float or Duration). `shared`
+    // (`public` is a keyword) lets CDNs and proxies store it too.
+    env.define(
+        "expires_in".to_string(),
+        Value::NativeFunction(NativeFunction::new("expires_in", None, |args| {
+            let seconds = match args.first() {
+                Some(value) => duration_seconds(value)
+                    .ok_or("expires_in() expects a number of seconds or a Duration")?,
+                None => return Err("expires_in() expects a number of seconds".to_string()),
+            };
+            let option = |name: &str| match args.get(1) {
+                Some(Value::Hash(options)) => options
+                    .borrow()
+                    .get(&HashKey::String(name.into()))
+                    .is_some_and(Value::is_truthy),
+                _ => false,
+            };
+            let mut cache_control = format!(
+                "max-age={}, {}",
+                seconds.max(0),
+                if option("shared") {
+                    "public"
+                } else {
+                    "private"
+                }
+            );
+            if option("must_revalidate") {
+                cache_control.push_str(", must-revalidate");
+            }
+            CACHE_STATE.with(|s| s.borrow_mut().cache_control = Some(cache_control));
+            Ok(Value::Null)
+        })),
+    );
+}
+
+/// Record the validators `arg` describes and compare them with the current
+/// request's. `arg` is an options hash (`etag`, `last_modified`) or a record,
+/// whose ETag is derived from its contents and whose `updated_at`, if any,
+/// is its modification time.
+fn check_freshness(name: &str, arg: &Value) -> Result<bool, String> {
+    let field = |hash: &Value, key: &str| match hash {
+        Value::Hash(h) => h.borrow().get(&HashKey::String(key.into())).cloned(),
+        Value::Instance(inst) => inst.borrow().fields.get(key).cloned(),
+        _ => None,
+    };
+    let is_options = matches!(arg, Value::Hash(_))
+        && (field(arg, "etag").is_some() || field(arg, "last_modified").is_some());
+    let (etag, last_modified) = if is_options {
+        (field(arg, "etag"), field(arg, "last_modified"))
+    } else {
+        (Some(arg.clone()), field(arg, "updated_at"))
+    };
+
+    let etag = etag
+        .filter(|v| !matches!(v, Value::Null))
+        .map(|v| etag_for(&v));
+    let last_modified = match last_modified {
+        None | Some(Value::Null) => None,
+        Some(value) => Some(timestamp_seconds(&value).ok_or_else(|| {
+            format!(
+                "{}() last_modified must be a DateTime, a timestamp or a date string, got {}",
+                name,
+                value.type_name()
+            )
+        })?),
+    };
+
+    let fresh = request_is_fresh(etag.as_deref(), last_modified);
+    CACHE_STATE.with(|s| {
+        let mut state = s.borrow_mut();
+        state.etag = etag;
+        state.last_modified = last_modified;
+        state.not_modified = fresh;
+    });
+    Ok(fresh)
+}
+
+/// A weak ETag for `value`: a string is taken as-is, anything else by its
+/// JSON form.
+fn etag_for(value: &Value) -> String {
+    let source = match value {
+        Value::String(s) => s.to_string(),
+        other => crate::interpreter::value::stringify_to_string(other)
+            .unwrap_or_else(|_| other.to_string()),
+    };
+    crate::template::etag_for_body(&source)
+}
+
+/// RFC 9110 §13.2.2: `If-None-Match` decides when present; otherwise
+/// `If-Modified-Since` against the modification time. Only GET and HEAD
+/// are conditional.
+fn request_is_fresh(etag: Option<&str>, last_modified: Option<i64>) -> bool {
+    let Some(Value::Hash(req)) = super::template::get_current_request() else {
+        return false;
+    };
+    let req = req.borrow();
+    let method = match req.get(&HashKey::String("method".into())) {
+        Some(Value::String(m)) => m.to_ascii_uppercase(),
+        _ => return false,
+    };
+    if method != "GET" && method != "HEAD" {
+        return false;
+    }
+    let Some(Value::Hash(headers)) = req.get(&HashKey::String("headers".into())) else {
+        return false;
+    };
+    let headers = headers.borrow();
+    let header = |name: &str| match headers.get(&HashKey::String(name.into())) {
+        Some(Value::String(s)) => Some(s.to_string()),
+        _ => None,
+    };
+
+    if let Some(if_none_match) = header("if-none-match") {
+        let Some(etag) = etag else {
+            return false;
+        };
+        return if_none_match
+            .split(',')
+            .map(str::trim)
+            .any(|candidate| candidate == "*" || strip_weak(candidate) == strip_weak(etag));
+    }
+    match (header("if-modified-since"), last_modified) {
+        (Some(since), Some(modified)) => chrono::DateTime::parse_from_rfc2822(since.trim())
+            .is_ok_and(|since| modified <= since.timestamp()),
+        _ => false,
+    }
+}
+
+fn strip_weak(etag: &str) -> &str {
+    etag.trim_start_matches("W/").trim()
+}
+
+/// Unix seconds from a DateTime, an Int timestamp or a date string.
+fn timestamp_seconds(value: &Value) -> Option<i64> {
+    match value {
+        Value::Int(secs) => Some(*secs),
+        Value::Float(secs) => Some(*secs as i64),
+        Value::String(s) => super::datetime_class::parse_datetime_string(s)
+            .map(|nanos| nanos.div_euclid(1_000_000_000))
+            .ok()
+            .or_else(|| {
+                chrono::DateTime::parse_from_rfc2822(s.trim())
+                    .ok()
+                    .map(|dt| dt.timestamp())
+            }),
+        Value::Instance(inst) => match inst.borrow().fields.get("_ts") {
+            Some(Value::Int(nanos)) => Some(nanos.div_euclid(1_000_000_000)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn duration_seconds(value: &Value) -> Option<i64> {
+    match value {
+        Value::Int(secs) => Some(*secs),
+        Value::Float(secs) => Some(*secs as i64),
+        Value::Instance(inst) => match inst.borrow().fields.get("seconds") {
+            Some(Value::Float(secs)) => Some(*secs as i64),
+            Some(Value::Int(secs)) => Some(*secs),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// An HTTP-date (`Sun, 06 Nov 1994 08:49:37 GMT`).
+fn http_date(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .unwrap_or_default()
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::builtins::template::{clear_current_request, set_current_request};
+
+    fn request(method: &str, headers: &[(&str, &str)]) -> Value {
+        let mut header_pairs = HashPairs::default();
+        for (name, value) in headers {
+            header_pairs.insert(
+                HashKey::String((*name).into()),
+                Value::String((*value).into()),
+            );
+        }
+        let mut pairs = HashPairs::default();
+        pairs.insert(
+            HashKey::String("method".into()),
+            Value::String(method.into()),
+        );
+        pairs.insert(
+            HashKey::String("headers".into()),
+            Value::Hash(Rc::new(RefCell::new(header_pairs))),
+        );
+        Value::Hash(Rc::new(RefCell::new(pairs)))
+    }
+
+    fn options(etag: Option<&str>, last_modified: Option<i64>) -> Value {
+        let mut pairs = HashPairs::default();
+        if let Some(etag) = etag {
+            pairs.insert(HashKey::String("etag".into()), Value::String(etag.into()));
+        }
+        if let Some(secs) = last_modified {
+            pairs.insert(HashKey::String("last_modified".into()), Value::Int(secs));
+        }
+        Value::Hash(Rc::new(RefCell::new(pairs)))
+    }
+
+    fn finalized(status: u16) -> (u16, Vec<(String, String)>, Vec<u8>) {
+        let mut status = status;
+        let mut headers = vec![
+            ("Content-Type".to_string(), "text/html".to_string()),
+            ("ETag".to_string(), "W/\"body\"".to_string()),
+        ];
+        let mut body = b"<p>hi</p>".to_vec();
+        finalize_response(&mut status, &mut headers, &mut body);
+        (status, headers, body)
+    }
+
+    fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+        headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn matching_etag_turns_the_response_into_a_304() {
+        reset_for_new_request();
+        let etag = etag_for(&Value::String("post-1-v2".into()));
+        set_current_request(request("GET", &[("if-none-match", &etag)]));
+        assert!(check_freshness("fresh_when", &options(Some("post-1-v2"), None)).unwrap());
+        assert!(is_not_modified());
+        let (status, headers, body) = finalized(200);
+        clear_current_request();
+        assert_eq!(status, 304);
+        assert!(body.is_empty());
+        assert_eq!(header(&headers, "etag"), Some(etag.as_str()));
+        assert_eq!(header(&headers, "content-type"), None);
+    }
+
+    #[test]
+    fn changed_etag_renders_with_the_new_validator() {
+        reset_for_new_request();
+        set_current_request(request("GET", &[("if-none-match", "W/\"stale\"")]));
+        assert!(!check_freshness("stale?", &options(Some("post-1-v3"), Some(0))).unwrap());
+        let (status, headers, body) = finalized(200);
+        clear_current_request();
+        assert_eq!(status, 200);
+        assert!(!body.is_empty());
+        assert_eq!(
+            header(&headers, "etag"),
+            Some(etag_for(&Value::String("post-1-v3".into())).as_str())
+        );
+        assert_eq!(
+            header(&headers, "last-modified"),
+            Some("Thu, 01 Jan 1970 00:00:00 GMT")
+        );
+    }
+
+    #[test]
+    fn if_modified_since_compares_the_modification_time() {
+        let since = "Sun, 06 Nov 1994 08:49:37 GMT";
+        let at = chrono::DateTime::parse_from_rfc2822(since)
+            .unwrap()
+            .timestamp();
+        for (modified, fresh) in [(at, true), (at - 60, true), (at + 1, false)] {
+            reset_for_new_request();
+            set_current_request(request("GET", &[("if-modified-since", since)]));
+            assert_eq!(
+                check_freshness("fresh_when", &options(None, Some(modified))).unwrap(),
+                fresh
+            );
+            clear_current_request();
+        }
+    }
+
+    #[test]
+    fn only_get_and_head_are_conditional() {
+        reset_for_new_request();
+        let etag = etag_for(&Value::String("v1".into()));
+        set_current_request(request("POST", &[("if-none-match", &etag)]));
+        assert!(!check_freshness("fresh_when", &options(Some("v1"), None)).unwrap());
+        clear_current_request();
+        reset_for_new_request();
+    }
+
+    fn cache_control_after(source: &str) -> Option<String> {
+        reset_for_new_request();
+        let tokens = crate::lexer::Scanner::new(source).scan_tokens().unwrap();
+        let program = crate::parser::Parser::new(tokens).parse().unwrap();
+        crate::interpreter::Interpreter::new()
+            .interpret(&program)
+            .unwrap();
+        CACHE_STATE.with(|s| s.borrow_mut().cache_control.take())
+    }
+
+    #[test]
+    fn expires_in_is_private_unless_shared() {
+        assert_eq!(
+            cache_control_after("expires_in(3600)").as_deref(),
+            Some("max-age=3600, private")
+        );
+        assert_eq!(
+            cache_control_after("expires_in(3600, shared: true)").as_deref(),
+            Some("max-age=3600, public")
+        );
+        assert_eq!(
+            cache_control_after("expires_in(3600, shared: false)").as_deref(),
+            Some("max-age=3600, private")
+        );
+    }
+
+    #[test]
+    fn expires_in_can_require_revalidation() {
+        assert_eq!(
+            cache_control_after("expires_in(300, must_revalidate: true)").as_deref(),
+            Some("max-age=300, private, must-revalidate")
+        );
+        assert_eq!(
+            cache_control_after("expires_in(60, shared: true, must_revalidate: true)").as_deref(),
+            Some("max-age=60, public, must-revalidate")
+        );
+    }
+
+    #[test]
+    fn redirects_keep_their_headers() {
+        reset_for_new_request();
+        CACHE_STATE.with(|s| s.borrow_mut().cache_control = Some("max-age=60, public".into()));
+        let (status, headers, _) = finalized(302);
+        assert_eq!(status, 302);
+        assert_eq!(header(&headers, "cache-control"), None);
+    }
+}
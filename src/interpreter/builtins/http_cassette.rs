@@ -0,0 +1,450 @@
+//! VCR-style cassettes for the `HTTP.*` client.
+//!
+//! `with_cassette("github_api") { ... }` (see the `evaluate_call`
+//! interceptor) inserts a cassette for the duration of the block. Every
+//! outbound `HTTP.*` request then goes through [`send_with_cassette`]: a
+//! request that matches a recorded interaction is answered from the cassette
+//! without touching the network; one that doesn't is sent for real and
+//! recorded, if the record mode allows it. When the block ends the cassette
+//! is written to `tests/cassettes/<name>.yml`.
+//!
+//! Only the request's method, URL and body are recorded — never its headers,
+//! so an `Authorization` token doesn't end up committed alongside the
+//! fixtures.
+//!
+//! The active cassette is per thread, like the tokio handle it travels with.
+//! Code paths that hand a request to another thread (the future-returning
+//! fallbacks, `HTTP.get_all` / `HTTP.parallel`) carry it over with
+//! [`current`] / [`install`].
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+/// Directory cassettes are read from and written to, relative to the app
+/// root (the test runner's working directory).
+pub const CASSETTE_DIR: &str = "tests/cassettes";
+
+/// When a cassette may hit the network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordMode {
+    /// Record if the cassette file doesn't exist yet; otherwise replay only.
+    Once,
+    /// Replay what matches, record anything new.
+    NewEpisodes,
+    /// Never hit the network; a missing cassette or interaction is an error.
+    None,
+    /// Always hit the network and overwrite the cassette.
+    All,
+}
+
+impl RecordMode {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "once" => Ok(RecordMode::Once),
+            "new_episodes" => Ok(RecordMode::NewEpisodes),
+            "none" => Ok(RecordMode::None),
+            "all" => Ok(RecordMode::All),
+            other => Err(format!(
+                "with_cassette(): unknown record mode '{}' (expected once, new_episodes, none or all)",
+                other
+            )),
+        }
+    }
+}
+
+/// Which parts of a request must agree for a recorded interaction to answer it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchOn {
+    Method,
+    Uri,
+    Host,
+    Path,
+    Query,
+    Body,
+}
+
+impl MatchOn {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "method" => Ok(MatchOn::Method),
+            "uri" => Ok(MatchOn::Uri),
+            "host" => Ok(MatchOn::Host),
+            "path" => Ok(MatchOn::Path),
+            "query" => Ok(MatchOn::Query),
+            "body" => Ok(MatchOn::Body),
+            other => Err(format!(
+                "with_cassette(): unknown match_on '{}' (expected method, uri, host, path, query or body)",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    #[serde(default)]
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug)]
+pub struct Cassette {
+    name: String,
+    path: PathBuf,
+    record: RecordMode,
+    match_on: Vec<MatchOn>,
+    /// Interactions loaded from disk, and whether each has been played back.
+    /// A recorded interaction answers one request, so a call made twice needs
+    /// two recordings — same as VCR.
+    recorded: Vec<(Interaction, bool)>,
+    /// Interactions recorded during this insertion.
+    fresh: Vec<Interaction>,
+    /// Whether the file existed when the cassette was inserted.
+    existed: bool,
+}
+
+pub type CassetteHandle = Arc<Mutex<Cassette>>;
+
+thread_local! {
+    static ACTIVE: RefCell<Option<CassetteHandle>> = const { RefCell::new(None) };
+}
+
+/// The cassette active on this thread, if any.
+pub fn current() -> Option<CassetteHandle> {
+    ACTIVE.with(|a| a.borrow().clone())
+}
+
+/// Make `cassette` the active one on this thread, returning the previous one
+/// so nested `with_cassette` blocks can restore it.
+pub fn install(cassette: Option<CassetteHandle>) -> Option<CassetteHandle> {
+    ACTIVE.with(|a| std::mem::replace(&mut *a.borrow_mut(), cassette))
+}
+
+/// Reject names that would escape the cassette directory. Slashes are fine —
+/// `github/repos` files the cassette under a subdirectory.
+fn validate_name(name: &str) -> Result<(), String> {
+    let ok = !name.is_empty()
+        && !name.starts_with('/')
+        && name.split('/').all(|part| {
+            !part.is_empty()
+                && part != "."
+                && part != ".."
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        });
+    if ok {
+        Ok(())
+    } else {
+        Err(format!(
+            "with_cassette(): invalid cassette name '{}' (use letters, digits, _ - . and /)",
+            name
+        ))
+    }
+}
+
+impl Cassette {
+    /// Load `<dir>/<name>.yml`, or start an empty cassette if it doesn't exist.
+    pub fn load(
+        dir: &Path,
+        name: &str,
+        record: RecordMode,
+        match_on: Vec<MatchOn>,
+    ) -> Result<Self, String> {
+        validate_name(name)?;
+        let path = dir.join(format!("{}.yml", name));
+        let existed = path.is_file();
+        if !existed && record == RecordMode::None {
+            return Err(format!(
+                "with_cassette(\"{}\"): {} does not exist and record mode is \"none\"",
+                name,
+                path.display()
+            ));
+        }
+        let recorded = if existed && record != RecordMode::All {
+            let text = std::fs::read_to_string(&path)
+                .map_err(|e| format!("with_cassette(\"{}\"): {}", name, e))?;
+            let file: CassetteFile = serde_yaml::from_str(&text).map_err(|e| {
+                format!(
+                    "with_cassette(\"{}\"): malformed {}: {}",
+                    name,
+                    path.display(),
+                    e
+                )
+            })?;
+            file.interactions.into_iter().map(|i| (i, false)).collect()
+        } else {
+            Vec::new()
+        };
+        Ok(Cassette {
+            name: name.to_string(),
+            path,
+            record,
+            match_on,
+            recorded,
+            fresh: Vec::new(),
+            existed,
+        })
+    }
+
+    fn may_record(&self) -> bool {
+        match self.record {
+            RecordMode::Once => !self.existed,
+            RecordMode::NewEpisodes | RecordMode::All => true,
+            RecordMode::None => false,
+        }
+    }
+
+    fn matches(&self, recorded: &RecordedRequest, request: &RecordedRequest) -> bool {
+        let parsed = (
+            reqwest::Url::parse(&recorded.uri).ok(),
+            reqwest::Url::parse(&request.uri).ok(),
+        );
+        self.match_on.iter().all(|part| match part {
+            MatchOn::Method => recorded.method.eq_ignore_ascii_case(&request.method),
+            MatchOn::Uri => recorded.uri == request.uri,
+            MatchOn::Body => recorded.body == request.body,
+            MatchOn::Host | MatchOn::Path | MatchOn::Query => match &parsed {
+                (Some(a), Some(b)) => match part {
+                    MatchOn::Host => a.host_str() == b.host_str() && a.port() == b.port(),
+                    MatchOn::Path => a.path() == b.path(),
+                    _ => a.query() == b.query(),
+                },
+                _ => recorded.uri == request.uri,
+            },
+        })
+    }
+
+    /// Take the first unplayed recording that answers `request`.
+    fn play(&mut self, request: &RecordedRequest) -> Option<RecordedResponse> {
+        let index = self
+            .recorded
+            .iter()
+            .position(|(i, played)| !played && self.matches(&i.request, request))?;
+        self.recorded[index].1 = true;
+        Some(self.recorded[index].0.response.clone())
+    }
+
+    /// Write the cassette back if anything was recorded. Under `all` the file is
+    /// replaced by this run's interactions; otherwise they're appended.
+    pub fn eject(&mut self) -> Result<(), String> {
+        if self.fresh.is_empty() {
+            return Ok(());
+        }
+        let mut interactions: Vec<Interaction> =
+            self.recorded.iter().map(|(i, _)| i.clone()).collect();
+        interactions.append(&mut self.fresh);
+        let text = serde_yaml::to_string(&CassetteFile { interactions })
+            .map_err(|e| format!("with_cassette(\"{}\"): {}", self.name, e))?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("with_cassette(\"{}\"): {}", self.name, e))?;
+        }
+        std::fs::write(&self.path, text).map_err(|e| {
+            format!(
+                "with_cassette(\"{}\"): could not write {}: {}",
+                self.name,
+                self.path.display(),
+                e
+            )
+        })
+    }
+}
+
+fn to_response(recorded: RecordedResponse) -> Result<reqwest::Response, String> {
+    let mut builder = hyper::Response::builder().status(recorded.status);
+    for (name, value) in &recorded.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    builder
+        .body(recorded.body)
+        .map(reqwest::Response::from)
+        .map_err(|e| format!("cassette response: {}", e))
+}
+
+/// Send `builder`, answering from the active cassette when there is one.
+/// Without a cassette this is exactly `builder.send()`.
+pub async fn send_with_cassette(
+    builder: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, String> {
+    let Some(cassette) = current() else {
+        return builder.send().await.map_err(|e| e.to_string());
+    };
+
+    let (client, request) = builder.build_split();
+    let request = request.map_err(|e| e.to_string())?;
+    let key = RecordedRequest {
+        method: request.method().as_str().to_string(),
+        uri: request.url().as_str().to_string(),
+        body: request
+            .body()
+            .and_then(|b| b.as_bytes())
+            .map(|b| String::from_utf8_lossy(b).into_owned())
+            .unwrap_or_default(),
+    };
+
+    {
+        let mut guard = cassette.lock().map_err(|e| e.to_string())?;
+        if let Some(recorded) = guard.play(&key) {
+            return to_response(recorded);
+        }
+        if !guard.may_record() {
+            return Err(format!(
+                "with_cassette(\"{}\"): no recorded interaction matches {} {} — \
+                 delete {} to re-record it, or pass {{ \"record\": \"new_episodes\" }}",
+                guard.name,
+                key.method,
+                key.uri,
+                guard.path.display()
+            ));
+        }
+    }
+
+    let resp = client.execute(request).await.map_err(|e| e.to_string())?;
+    let status = resp.status().as_u16();
+    let mut headers: BTreeMap<String, String> = BTreeMap::new();
+    for (name, value) in resp.headers() {
+        // The recorded body is already decoded and complete, so framing and
+        // encoding headers would describe a body the cassette doesn't hold.
+        if matches!(
+            name.as_str(),
+            "content-length" | "content-encoding" | "transfer-encoding" | "connection"
+        ) {
+            continue;
+        }
+        let Ok(value) = value.to_str() else { continue };
+        headers
+            .entry(name.as_str().to_string())
+            .and_modify(|v| {
+                v.push_str(", ");
+                v.push_str(value);
+            })
+            .or_insert_with(|| value.to_string());
+    }
+    let body = super::http_class::read_capped_text_async(resp).await?;
+    let recorded = RecordedResponse {
+        status,
+        headers,
+        body,
+    };
+    cassette
+        .lock()
+        .map_err(|e| e.to_string())?
+        .fresh
+        .push(Interaction {
+            request: key,
+            response: recorded.clone(),
+        });
+    to_response(recorded)
+}
+
+/// `.send_with_cassette()` in place of `.send()` on a request builder.
+pub trait SendWithCassette {
+    fn send_with_cassette(
+        self,
+    ) -> impl std::future::Future<Output = Result<reqwest::Response, String>>;
+}
+
+impl SendWithCassette for reqwest::RequestBuilder {
+    fn send_with_cassette(
+        self,
+    ) -> impl std::future::Future<Output = Result<reqwest::Response, String>> {
+        send_with_cassette(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, uri: &str, body: &str) -> RecordedRequest {
+        RecordedRequest {
+            method: method.to_string(),
+            uri: uri.to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    fn cassette(match_on: Vec<MatchOn>, recorded: Vec<RecordedRequest>) -> Cassette {
+        Cassette {
+            name: "t".to_string(),
+            path: PathBuf::from("t.yml"),
+            record: RecordMode::None,
+            match_on,
+            recorded: recorded
+                .into_iter()
+                .map(|request| {
+                    (
+                        Interaction {
+                            request,
+                            response: RecordedResponse {
+                                status: 200,
+                                headers: BTreeMap::new(),
+                                body: String::new(),
+                            },
+                        },
+                        false,
+                    )
+                })
+                .collect(),
+            fresh: Vec::new(),
+            existed: true,
+        }
+    }
+
+    #[test]
+    fn test_each_recording_plays_once() {
+        let mut c = cassette(
+            vec![MatchOn::Method, MatchOn::Uri],
+            vec![request("GET", "http://api.test/a", "")],
+        );
+        assert!(c.play(&request("GET", "http://api.test/a", "")).is_some());
+        assert!(c.play(&request("GET", "http://api.test/a", "")).is_none());
+    }
+
+    #[test]
+    fn test_match_on_path_ignores_query() {
+        let mut c = cassette(
+            vec![MatchOn::Method, MatchOn::Path],
+            vec![request("GET", "http://api.test/a?page=1", "")],
+        );
+        assert!(c.play(&request("POST", "http://api.test/a", "")).is_none());
+        assert!(c
+            .play(&request("GET", "http://api.test/a?page=2", ""))
+            .is_some());
+    }
+
+    #[test]
+    fn test_cassette_names_stay_inside_the_directory() {
+        assert!(validate_name("github/repos").is_ok());
+        assert!(validate_name("../secrets").is_err());
+        assert!(validate_name("/etc/passwd").is_err());
+        assert!(validate_name("").is_err());
+    }
+}
@@ -28,6 +28,7 @@ use std::thread;
 
 use reqwest::Client;
 
+use crate::interpreter::builtins::http_cassette::{self, SendWithCassette};
 use crate::interpreter::environment::Environment;
 use crate::interpreter::value::{
     hash_from_pairs, Class, FutureState, HashKey, HashPairs, HttpFutureKind, NativeFunction, Value,
@@ -537,16 +538,39 @@ pub fn ureq_agent() -> &'static ureq::Agent {
     })
 }
 
+/// The server runtime to block on, or `None` to hand back a future instead:
+/// outside a server, and inside an async task, where the scheduler runs the
+/// other queued tasks while the request is in flight.
+fn blocking_tokio_handle() -> Option<tokio::runtime::Handle> {
+    if crate::interpreter::executor::scheduler::in_task() {
+        None
+    } else {
+        get_tokio_handle()
+    }
+}
+
 #[allow(clippy::arc_with_non_send_sync)]
 fn spawn_http_future<F>(f: F, kind: HttpFutureKind) -> Value
 where
     F: FnOnce() -> Result<String, String> + Send + 'static,
 {
     let (tx, rx) = mpsc::channel();
-    thread::spawn(move || {
+    // The request runs on another thread (the server runtime's blocking
+    // pool when there is one); take the caller's cassette along.
+    let cassette = http_cassette::current();
+    let job = move || {
+        http_cassette::install(cassette);
         let result = f();
         let _ = tx.send(result);
-    });
+    };
+    match get_tokio_handle() {
+        Some(rt) => {
+            rt.spawn_blocking(job);
+        }
+        None => {
+            thread::spawn(job);
+        }
+    }
     Value::Future(Arc::new(Mutex::new(FutureState::Pending {
         receiver: rx,
         kind,
@@ -642,7 +666,7 @@ async fn send_logged(
     let logging = crate::interpreter::builtins::http_log::is_enabled()
         || crate::serve::span_log::is_enabled();
     let start = logging.then(std::time::Instant::now);
-    match builder.send().await {
+    match builder.send_with_cassette().await {
         Ok(resp) => {
             if let Some(s) = start {
                 let dur = s.elapsed().as_secs_f64() * 1000.0;
@@ -665,8 +689,7 @@ async fn send_logged(
             }
             Ok(resp)
         }
-        Err(e) => {
-            let msg = e.to_string();
+        Err(msg) => {
             if let Some(s) = start {
                 let dur = s.elapsed().as_secs_f64() * 1000.0;
                 let span_name = format!("{} {}", method, url);
@@ -717,7 +740,7 @@ pub fn register_http_class(env: &mut Environment) {
 
             let timeout = extract_timeout(args.get(1))?;
 
-            match get_tokio_handle() {
+            match blocking_tokio_handle() {
                 Some(rt) => {
                     let client = get_user_http_client().clone();
                     match http_block_on(&rt, async move {
@@ -741,7 +764,7 @@ pub fn register_http_class(env: &mut Environment) {
                     move || {
                         run_user_http_request(move |client| async move {
                             let resp = apply_timeout(client.get(&*url), timeout)
-                                .send()
+                                .send_with_cassette()
                                 .await
                                 .map_err(|e| format!("HTTP request failed: {}", e))?;
                             let status = resp.status();
@@ -795,7 +818,7 @@ pub fn register_http_class(env: &mut Environment) {
 
             let timeout = extract_timeout(args.get(2))?;
 
-            match get_tokio_handle() {
+            match blocking_tokio_handle() {
                 Some(rt) => {
                     let client = get_user_http_client().clone();
                     match http_block_on(&rt, async move {
@@ -830,7 +853,7 @@ pub fn register_http_class(env: &mut Environment) {
                                     .body(body.to_string()),
                                 timeout,
                             )
-                            .send()
+                            .send_with_cassette()
                             .await
                             .map_err(|e| format!("HTTP request failed: {}", e))?;
                             let status = resp.status();
@@ -884,7 +907,7 @@ pub fn register_http_class(env: &mut Environment) {
 
             let timeout = extract_timeout(args.get(2))?;
 
-            match get_tokio_handle() {
+            match blocking_tokio_handle() {
                 Some(rt) => {
                     let client = get_user_http_client().clone();
                     match http_block_on(&rt, async move {
@@ -919,7 +942,7 @@ pub fn register_http_class(env: &mut Environment) {
                                     .body(body.to_string()),
                                 timeout,
                             )
-                            .send()
+                            .send_with_cassette()
                             .await
                             .map_err(|e| format!("HTTP request failed: {}", e))?;
                             let status = resp.status();
@@ -973,7 +996,7 @@ pub fn register_http_class(env: &mut Environment) {
 
             let timeout = extract_timeout(args.get(2))?;
 
-            match get_tokio_handle() {
+            match blocking_tokio_handle() {
                 Some(rt) => {
                     let client = get_user_http_client().clone();
                     match http_block_on(&rt, async move {
@@ -1008,7 +1031,7 @@ pub fn register_http_class(env: &mut Environment) {
                                     .body(body.to_string()),
                                 timeout,
                             )
-                            .send()
+                            .send_with_cassette()
                             .await
                             .map_err(|e| format!("HTTP request failed: {}", e))?;
                             let status = resp.status();
@@ -1045,7 +1068,7 @@ pub fn register_http_class(env: &mut Environment) {
 
             let timeout = extract_timeout(args.get(1))?;
 
-            match get_tokio_handle() {
+            match blocking_tokio_handle() {
                 Some(rt) => {
                     let client = get_user_http_client().clone();
                     match http_block_on(&rt, async move {
@@ -1072,7 +1095,7 @@ pub fn register_http_class(env: &mut Environment) {
                     move || {
                         run_user_http_request(move |client| async move {
                             let resp = apply_timeout(client.delete(&*url), timeout)
-                                .send()
+                                .send_with_cassette()
                                 .await
                                 .map_err(|e| format!("HTTP request failed: {}", e))?;
                             let status = resp.status();
@@ -1109,7 +1132,7 @@ pub fn register_http_class(env: &mut Environment) {
 
             let timeout = extract_timeout(args.get(1))?;
 
-            match get_tokio_handle() {
+            match blocking_tokio_handle() {
                 Some(rt) => {
                     let client = get_user_http_client().clone();
                     match http_block_on(&rt, async move {
@@ -1131,7 +1154,7 @@ pub fn register_http_class(env: &mut Environment) {
                     move || {
                         run_user_http_request(move |client| async move {
                             let resp = apply_timeout(client.head(&*url), timeout)
-                                .send()
+                                .send_with_cassette()
                                 .await
                                 .map_err(|e| format!("HTTP request failed: {}", e))?;
                             let status = resp.status();
@@ -1168,7 +1191,7 @@ pub fn register_http_class(env: &mut Environment) {
 
             let timeout = extract_timeout(args.get(1))?;
 
-            match get_tokio_handle() {
+            match blocking_tokio_handle() {
                 Some(rt) => {
                     let client = get_user_http_client().clone();
                     match http_block_on(&rt, async move {
@@ -1201,7 +1224,7 @@ pub fn register_http_class(env: &mut Environment) {
                                 client.get(&*url).header("Accept", "application/json"),
                                 timeout,
                             )
-                            .send()
+                            .send_with_cassette()
                             .await
                             .map_err(|e| format!("HTTP request failed: {}", e))?;
                             let status = resp.status();
@@ -1241,7 +1264,7 @@ pub fn register_http_class(env: &mut Environment) {
 
             let timeout = extract_timeout(args.get(1))?;
 
-            match get_tokio_handle() {
+            match blocking_tokio_handle() {
                 Some(rt) => {
                     let client = get_user_http_client().clone();
                     match http_block_on(&rt, async move {
@@ -1269,7 +1292,7 @@ pub fn register_http_class(env: &mut Environment) {
                     move || {
                         run_user_http_request(move |client| async move {
                             let resp = apply_timeout(client.get(&*url), timeout)
-                                .send()
+                                .send_with_cassette()
                                 .await
                                 .map_err(|e| format!("HTTP request failed: {}", e))?;
                             let status = resp.status();
@@ -1308,7 +1331,7 @@ pub fn register_http_class(env: &mut Environment) {
 
             let timeout = extract_timeout(args.get(2))?;
 
-            match get_tokio_handle() {
+            match blocking_tokio_handle() {
                 Some(rt) => {
                     let client = get_user_http_client().clone();
                     match http_block_on(&rt, async move {
@@ -1347,7 +1370,7 @@ pub fn register_http_class(env: &mut Environment) {
                                     .body(json_body),
                                 timeout,
                             )
-                            .send()
+                            .send_with_cassette()
                             .await
                             .map_err(|e| format!("HTTP request failed: {}", e))?;
                             let status = resp.status();
@@ -1386,7 +1409,7 @@ pub fn register_http_class(env: &mut Environment) {
 
             let timeout = extract_timeout(args.get(2))?;
 
-            match get_tokio_handle() {
+            match blocking_tokio_handle() {
                 Some(rt) => {
                     let client = get_user_http_client().clone();
                     match http_block_on(&rt, async move {
@@ -1425,7 +1448,7 @@ pub fn register_http_class(env: &mut Environment) {
                                     .body(json_body),
                                 timeout,
                             )
-                            .send()
+                            .send_with_cassette()
                             .await
                             .map_err(|e| format!("HTTP request failed: {}", e))?;
                             let status = resp.status();
@@ -1464,7 +1487,7 @@ pub fn register_http_class(env: &mut Environment) {
 
             let timeout = extract_timeout(args.get(2))?;
 
-            match get_tokio_handle() {
+            match blocking_tokio_handle() {
                 Some(rt) => {
                     let client = get_user_http_client().clone();
                     match http_block_on(&rt, async move {
@@ -1503,7 +1526,7 @@ pub fn register_http_class(env: &mut Environment) {
                                     .body(json_body),
                                 timeout,
                             )
-                            .send()
+                            .send_with_cassette()
                             .await
                             .map_err(|e| format!("HTTP request failed: {}", e))?;
                             let status = resp.status();
@@ -1584,7 +1607,7 @@ pub fn register_http_class(env: &mut Environment) {
                 None
             };
 
-            match get_tokio_handle() {
+            match blocking_tokio_handle() {
                 Some(rt) => {
                     let client = get_user_http_client().clone();
                     let method_clone = method.clone();
@@ -1666,7 +1689,7 @@ pub fn register_http_class(env: &mut Environment) {
 
                                 let request = apply_timeout(request, timeout);
                                 let resp = request
-                                    .send()
+                                    .send_with_cassette()
                                     .await
                                     .map_err(|e| format!("HTTP request failed: {}", e))?;
 
@@ -2084,7 +2107,9 @@ fn run_parallel_gets(
             chunk
                 .into_iter()
                 .map(|url| {
+                    let cassette = http_cassette::current();
                     thread::spawn(move || {
+                        http_cassette::install(cassette);
                         let start = std::time::Instant::now();
                         let url_for_call = url.clone();
                         // SEC-015a: route through the SSRF-aware reqwest client so
@@ -2094,7 +2119,7 @@ fn run_parallel_gets(
                             match run_user_http_request::<_, _, (u16, Result<String, String>)>(
                                 move |client| async move {
                                     let resp = apply_timeout(client.get(&url_for_call), timeout)
-                                        .send()
+                                        .send_with_cassette()
                                         .await
                                         .map_err(|e| format!("Request failed: {}", e))?;
                                     let code = resp.status().as_u16();
@@ -2172,7 +2197,9 @@ fn run_parallel_gets_json(
             chunk
                 .into_iter()
                 .map(|url| {
+                    let cassette = http_cassette::current();
                     thread::spawn(move || {
+                        http_cassette::install(cassette);
                         let start = std::time::Instant::now();
                         let url_for_call = url.clone();
                         // SEC-015a: SSRF-aware reqwest client.
@@ -2185,7 +2212,7 @@ fn run_parallel_gets_json(
                                             .header("Accept", "application/json"),
                                         timeout,
                                     )
-                                    .send()
+                                    .send_with_cassette()
                                     .await
                                     .map_err(|e| format!("Request failed: {}", e))?;
                                     let code = resp.status().as_u16();
@@ -2264,7 +2291,9 @@ fn run_parallel_requests(requests: Vec<RequestConfig>) -> Vec<Result<HttpRespons
         let handles: Vec<_> = chunk
             .into_iter()
             .map(|config| {
+                let cassette = http_cassette::current();
                 thread::spawn(move || {
+                    http_cassette::install(cassette);
                     let method = config.method.clone();
                     let url = config.url.clone();
                     let start = std::time::Instant::now();
@@ -2333,7 +2362,7 @@ fn execute_request(config: RequestConfig) -> Result<HttpResponse, String> {
 
         let request = apply_timeout(request, config.timeout);
         let resp = request
-            .send()
+            .send_with_cassette()
             .await
             .map_err(|e| format!("Request failed: {}", e))?;
 
@@ -0,0 +1,547 @@
+//! `Request` and `Response` built-in classes for typed controller actions.
+//!
+//! An action declared as `fn show(req: Request) -> Response` receives a
+//! `Request` wrapping the usual request hash (`req.param("id")`,
+//! `req.header("accept")`) and returns a `Response` built with
+//! `Response.json(data, status: 201)` and friends. Untyped actions keep
+//! receiving the raw hash; the server converts a returned `Response` back
+//! into the response-hash shape before anything downstream sees it.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::ast::TypeKind;
+use crate::interpreter::environment::Environment;
+use crate::interpreter::value::{
+    value_to_json, Class, Function, HashKey, HashPairs, Instance, NativeFunction, Value,
+};
+
+thread_local! {
+    /// The registered classes, so Rust-side constructors (`wrap_request`,
+    /// the `Response.*` builders) hand out instances with the full method map.
+    static REQUEST_CLASS: RefCell<Option<Rc<Class>>> = const { RefCell::new(None) };
+    static RESPONSE_CLASS: RefCell<Option<Rc<Class>>> = const { RefCell::new(None) };
+}
+
+/// Field holding the wrapped request hash on a `Request` instance.
+const REQUEST_FIELD: &str = "_req";
+
+/// Register the `Request` and `Response` classes.
+pub fn register_http_type_classes(env: &mut Environment) {
+    let request_class = Rc::new(Class {
+        name: "Request".to_string(),
+        native_methods: request_methods(),
+        ..Default::default()
+    });
+    let response_class = Rc::new(Class {
+        name: "Response".to_string(),
+        native_static_methods: response_static_methods(),
+        native_methods: response_methods(),
+        ..Default::default()
+    });
+    REQUEST_CLASS.with(|c| *c.borrow_mut() = Some(request_class.clone()));
+    RESPONSE_CLASS.with(|c| *c.borrow_mut() = Some(response_class.clone()));
+    env.define("Request".to_string(), Value::Class(request_class));
+    env.define("Response".to_string(), Value::Class(response_class));
+}
+
+/// True when an action's first parameter is annotated `Request`, i.e. it
+/// wants the typed wrapper rather than the raw request hash.
+pub fn wants_typed_request(func: &Function) -> bool {
+    func.params.first().is_some_and(
+        |p| matches!(&p.type_annotation.kind, TypeKind::Named(name) if name == "Request"),
+    )
+}
+
+/// Wrap a request hash in a `Request` instance. Falls back to the hash
+/// itself if the class isn't registered on this thread.
+pub fn wrap_request(request_hash: &Value) -> Value {
+    match REQUEST_CLASS.with(|c| c.borrow().clone()) {
+        Some(class) => {
+            let mut inst = Instance::new(class);
+            inst.set(REQUEST_FIELD.to_string(), request_hash.clone());
+            Value::Instance(Rc::new(RefCell::new(inst)))
+        }
+        None => request_hash.clone(),
+    }
+}
+
+/// The argument to pass an action: the typed `Request` when it asks for one,
+/// the raw request hash otherwise.
+pub fn request_argument(func: &Value, request_hash: &Value) -> Value {
+    match func {
+        Value::Function(f) if wants_typed_request(f) => wrap_request(request_hash),
+        _ => request_hash.clone(),
+    }
+}
+
+/// Convert a returned `Response` into the `{status, headers, body}` hash the
+/// server pipeline understands. Any other value passes through unchanged.
+pub fn into_response_value(value: Value) -> Value {
+    match response_to_hash(&value) {
+        Some(hash) => hash,
+        None => value,
+    }
+}
+
+/// The response hash for a `Response` instance, or `None` for anything else.
+/// Matches the registered class by identity so a user class that happens to
+/// be called `Response` is left alone.
+pub fn response_to_hash(value: &Value) -> Option<Value> {
+    let Value::Instance(inst) = value else {
+        return None;
+    };
+    let inst = inst.borrow();
+    let registered = RESPONSE_CLASS.with(|c| c.borrow().clone())?;
+    if !Rc::ptr_eq(&inst.class, &registered) {
+        return None;
+    }
+    let mut pairs = HashPairs::default();
+    for key in ["status", "headers", "body"] {
+        if let Some(v) = inst.get(key) {
+            pairs.insert(HashKey::String(key.into()), v);
+        }
+    }
+    Some(Value::Hash(Rc::new(RefCell::new(pairs))))
+}
+
+fn request_hash(args: &[Value], method: &str) -> Result<Value, String> {
+    match args.first() {
+        Some(Value::Instance(inst)) => inst
+            .borrow()
+            .get(REQUEST_FIELD)
+            .ok_or_else(|| format!("Request.{}() called on a non-request instance", method)),
+        _ => Err(format!("Request.{}() called on non-Request", method)),
+    }
+}
+
+fn hash_field(hash: &Value, key: &str) -> Value {
+    match hash {
+        Value::Hash(h) => h
+            .borrow()
+            .get(&HashKey::String(key.into()))
+            .cloned()
+            .unwrap_or(Value::Null),
+        _ => Value::Null,
+    }
+}
+
+fn string_arg(args: &[Value], index: usize, method: &str) -> Result<String, String> {
+    match args.get(index) {
+        Some(Value::String(s)) => Ok(s.to_string()),
+        Some(Value::Symbol(s)) => Ok(s.to_string()),
+        Some(other) => Err(format!(
+            "Request.{}() expects a string name, got {}",
+            method,
+            other.type_name()
+        )),
+        None => Err(format!("Request.{}() requires a name", method)),
+    }
+}
+
+fn empty_hash() -> Value {
+    Value::Hash(Rc::new(RefCell::new(HashPairs::default())))
+}
+
+fn request_methods() -> HashMap<String, Rc<NativeFunction>> {
+    let mut methods: HashMap<String, Rc<NativeFunction>> = HashMap::new();
+
+    // Zero-arg accessors straight off the request hash.
+    for (name, key) in [
+        ("method", "method"),
+        ("path", "path"),
+        ("body", "body"),
+        ("json", "json"),
+        ("form", "form"),
+        ("files", "files"),
+        ("remote_addr", "remote_addr"),
+    ] {
+        methods.insert(
+            name.to_string(),
+            Rc::new(NativeFunction::new(
+                format!("Request.{}", name),
+                Some(0),
+                move |args| Ok(hash_field(&request_hash(&args, name)?, key)),
+            )),
+        );
+    }
+
+    // Hash-valued accessors default to `{}` so callers can index freely.
+    // `params` is the merged route + query + body view (`req["all"]`).
+    for (name, key) in [
+        ("params", "all"),
+        ("query", "query"),
+        ("headers", "headers"),
+        ("cookies", "cookies"),
+        ("session", "session"),
+    ] {
+        methods.insert(
+            name.to_string(),
+            Rc::new(NativeFunction::new(
+                format!("Request.{}", name),
+                Some(0),
+                move |args| match hash_field(&request_hash(&args, name)?, key) {
+                    Value::Null => Ok(empty_hash()),
+                    v => Ok(v),
+                },
+            )),
+        );
+    }
+
+    // req.param("id") — one merged parameter, null when absent.
+    methods.insert(
+        "param".to_string(),
+        Rc::new(NativeFunction::new("Request.param", Some(1), |args| {
+            let name = string_arg(&args, 1, "param")?;
+            let all = hash_field(&request_hash(&args, "param")?, "all");
+            Ok(hash_field(&all, &name))
+        })),
+    );
+
+    // req.header("Accept") — header names are case-insensitive.
+    methods.insert(
+        "header".to_string(),
+        Rc::new(NativeFunction::new("Request.header", Some(1), |args| {
+            let name = string_arg(&args, 1, "header")?;
+            let headers = hash_field(&request_hash(&args, "header")?, "headers");
+            let Value::Hash(h) = headers else {
+                return Ok(Value::Null);
+            };
+            let found = h.borrow().iter().find_map(|(k, v)| match k {
+                HashKey::String(k) if k.eq_ignore_ascii_case(&name) => Some(v.clone()),
+                _ => None,
+            });
+            Ok(found.unwrap_or(Value::Null))
+        })),
+    );
+
+    methods.insert(
+        "cookie".to_string(),
+        Rc::new(NativeFunction::new("Request.cookie", Some(1), |args| {
+            let name = string_arg(&args, 1, "cookie")?;
+            let cookies = hash_field(&request_hash(&args, "cookie")?, "cookies");
+            Ok(hash_field(&cookies, &name))
+        })),
+    );
+
+    // req.to_h — the underlying request hash, for code written against it.
+    methods.insert(
+        "to_h".to_string(),
+        Rc::new(NativeFunction::new("Request.to_h", Some(0), |args| {
+            request_hash(&args, "to_h")
+        })),
+    );
+
+    methods
+}
+
+/// `(status, headers)` from a builder's trailing argument: a bare status
+/// (`Response.json(data, 201)`) or the named-args hash
+/// (`Response.json(data, status: 201, headers: {...})`).
+fn response_options(
+    opts: Option<&Value>,
+    default_status: i64,
+    builder: &str,
+) -> Result<(i64, HashPairs), String> {
+    let mut headers = HashPairs::default();
+    let status = match opts {
+        None | Some(Value::Null) => default_status,
+        Some(Value::Int(n)) => *n,
+        Some(Value::Hash(h)) => {
+            let h = h.borrow();
+            if let Some(Value::Hash(extra)) = h.get(&HashKey::String("headers".into())) {
+                for (k, v) in extra.borrow().iter() {
+                    headers.insert(k.clone(), v.clone());
+                }
+            }
+            match h.get(&HashKey::String("status".into())) {
+                None | Some(Value::Null) => default_status,
+                Some(Value::Int(n)) => *n,
+                Some(other) => {
+                    return Err(format!(
+                        "Response.{}() status must be an Int, got {}",
+                        builder,
+                        other.type_name()
+                    ))
+                }
+            }
+        }
+        Some(other) => {
+            return Err(format!(
+                "Response.{}() expects a status or options, got {}",
+                builder,
+                other.type_name()
+            ))
+        }
+    };
+    if !(100..=599).contains(&status) {
+        return Err(format!(
+            "Response.{}() status {} is not a valid HTTP status",
+            builder, status
+        ));
+    }
+    Ok((status, headers))
+}
+
+fn build_response(
+    status: i64,
+    mut headers: HashPairs,
+    content_type: Option<&str>,
+    body: String,
+) -> Result<Value, String> {
+    let class = RESPONSE_CLASS
+        .with(|c| c.borrow().clone())
+        .ok_or_else(|| "Response class not registered on this thread".to_string())?;
+    if let Some(ct) = content_type {
+        let has_content_type = headers
+            .keys()
+            .any(|k| matches!(k, HashKey::String(k) if k.eq_ignore_ascii_case("content-type")));
+        if !has_content_type {
+            headers.insert(
+                HashKey::String("Content-Type".into()),
+                Value::String(ct.into()),
+            );
+        }
+    }
+    let mut inst = Instance::new(class);
+    inst.set("status".to_string(), Value::Int(status));
+    inst.set(
+        "headers".to_string(),
+        Value::Hash(Rc::new(RefCell::new(headers))),
+    );
+    inst.set("body".to_string(), Value::String(body.into()));
+    Ok(Value::Instance(Rc::new(RefCell::new(inst))))
+}
+
+fn body_string(value: &Value, builder: &str) -> Result<String, String> {
+    match value {
+        Value::String(s) => Ok(s.to_string()),
+        Value::Null => Ok(String::new()),
+        other => Err(format!(
+            "Response.{}() expects a string body, got {}",
+            builder,
+            other.type_name()
+        )),
+    }
+}
+
+fn response_static_methods() -> HashMap<String, Rc<NativeFunction>> {
+    let mut methods: HashMap<String, Rc<NativeFunction>> = HashMap::new();
+
+    // Response.json(data, status: 201) — a string is sent as-is, anything
+    // else is serialized.
+    methods.insert(
+        "json".to_string(),
+        Rc::new(NativeFunction::new("Response.json", None, |args| {
+            let data = args
+                .first()
+                .ok_or_else(|| "Response.json() requires data".to_string())?;
+            let body = match data {
+                Value::String(s) => s.to_string(),
+                other => value_to_json(other)?.to_string(),
+            };
+            let (status, headers) = response_options(args.get(1), 200, "json")?;
+            build_response(
+                status,
+                headers,
+                Some("application/json; charset=utf-8"),
+                body,
+            )
+        })),
+    );
+
+    for (name, content_type) in [
+        ("text", "text/plain; charset=utf-8"),
+        ("html", "text/html; charset=utf-8"),
+    ] {
+        methods.insert(
+            name.to_string(),
+            Rc::new(NativeFunction::new(
+                format!("Response.{}", name),
+                None,
+                move |args| {
+                    let body = body_string(args.first().unwrap_or(&Value::Null), name)?;
+                    let (status, headers) = response_options(args.get(1), 200, name)?;
+                    build_response(status, headers, Some(content_type), body)
+                },
+            )),
+        );
+    }
+
+    // Response.redirect("/posts", status: 303) — local paths only, like
+    // `redirect()`.
+    methods.insert(
+        "redirect".to_string(),
+        Rc::new(NativeFunction::new("Response.redirect", None, |args| {
+            let url = match args.first() {
+                Some(Value::String(s)) => s.to_string(),
+                Some(other) => {
+                    return Err(format!(
+                        "Response.redirect() expects a string URL, got {}",
+                        other.type_name()
+                    ))
+                }
+                None => return Err("Response.redirect() requires a URL".to_string()),
+            };
+            crate::interpreter::builtins::template::validate_local_redirect_url(&url)?;
+            let (status, mut headers) = response_options(args.get(1), 302, "redirect")?;
+            headers.insert(
+                HashKey::String("Location".into()),
+                Value::String(url.into()),
+            );
+            build_response(status, headers, None, String::new())
+        })),
+    );
+
+    // Response.empty(204) — no body, just a status (and headers).
+    methods.insert(
+        "empty".to_string(),
+        Rc::new(NativeFunction::new("Response.empty", None, |args| {
+            let (status, headers) = response_options(args.first(), 204, "empty")?;
+            build_response(status, headers, None, String::new())
+        })),
+    );
+
+    methods
+}
+
+fn response_methods() -> HashMap<String, Rc<NativeFunction>> {
+    let mut methods: HashMap<String, Rc<NativeFunction>> = HashMap::new();
+
+    // resp.with_header("X-Request-Id", id) — a copy with one header set.
+    methods.insert(
+        "with_header".to_string(),
+        Rc::new(NativeFunction::new(
+            "Response.with_header",
+            Some(2),
+            |args| {
+                let Some(Value::Instance(inst)) = args.first() else {
+                    return Err("Response.with_header() called on non-Response".to_string());
+                };
+                let (name, value) = match (args.get(1), args.get(2)) {
+                    (Some(Value::String(n)), Some(Value::String(v))) => (n.clone(), v.clone()),
+                    _ => {
+                        return Err(
+                            "Response.with_header() expects a string name and value".to_string()
+                        )
+                    }
+                };
+                let inst = inst.borrow();
+                let mut headers = match inst.get("headers") {
+                    Some(Value::Hash(h)) => h.borrow().clone(),
+                    _ => HashPairs::default(),
+                };
+                headers.insert(HashKey::String(name), Value::String(value));
+                let mut copy = Instance::new(inst.class.clone());
+                copy.fields = inst.fields.clone();
+                copy.set(
+                    "headers".to_string(),
+                    Value::Hash(Rc::new(RefCell::new(headers))),
+                );
+                Ok(Value::Instance(Rc::new(RefCell::new(copy))))
+            },
+        )),
+    );
+
+    methods
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> Environment {
+        let mut env = Environment::new();
+        register_http_type_classes(&mut env);
+        env
+    }
+
+    fn static_method(env: &Environment, class: &str, name: &str) -> Rc<NativeFunction> {
+        match env.get(class) {
+            Some(Value::Class(c)) => c.native_static_methods.get(name).unwrap().clone(),
+            _ => panic!("{} not registered", class),
+        }
+    }
+
+    fn named(pairs: &[(&str, Value)]) -> Value {
+        let mut map = HashPairs::default();
+        for (k, v) in pairs {
+            map.insert(HashKey::String((*k).into()), v.clone());
+        }
+        Value::Hash(Rc::new(RefCell::new(map)))
+    }
+
+    #[test]
+    fn json_builder_converts_to_response_hash() {
+        let env = setup();
+        let json = static_method(&env, "Response", "json");
+        let resp = (json.func)(vec![
+            named(&[("ok", Value::Bool(true))]),
+            named(&[("status", Value::Int(201))]),
+        ])
+        .unwrap();
+        let hash = response_to_hash(&resp).expect("a Response converts");
+        assert_eq!(hash_field(&hash, "status"), Value::Int(201));
+        assert_eq!(
+            hash_field(&hash, "body"),
+            Value::String("{\"ok\":true}".into())
+        );
+        assert_eq!(
+            hash_field(&hash_field(&hash, "headers"), "Content-Type"),
+            Value::String("application/json; charset=utf-8".into())
+        );
+    }
+
+    #[test]
+    fn redirect_builder_rejects_external_urls() {
+        let env = setup();
+        let redirect = static_method(&env, "Response", "redirect");
+        assert!((redirect.func)(vec![Value::String("https://evil.test".into())]).is_err());
+        let resp = (redirect.func)(vec![Value::String("/posts".into())]).unwrap();
+        let hash = response_to_hash(&resp).unwrap();
+        assert_eq!(hash_field(&hash, "status"), Value::Int(302));
+    }
+
+    #[test]
+    fn request_wrapper_reads_params_and_headers_case_insensitively() {
+        let _env = setup();
+        let req = wrap_request(&named(&[
+            ("all", named(&[("id", Value::String("7".into()))])),
+            (
+                "headers",
+                named(&[("accept", Value::String("text/html".into()))]),
+            ),
+        ]));
+        let Value::Instance(inst) = &req else {
+            panic!("expected a Request instance");
+        };
+        let class = inst.borrow().class.clone();
+        let call = |name: &str, args: Vec<Value>| {
+            let mut all = vec![req.clone()];
+            all.extend(args);
+            (class.find_native_method(name).unwrap().func)(all).unwrap()
+        };
+        assert_eq!(
+            call("param", vec![Value::String("id".into())]),
+            Value::String("7".into())
+        );
+        assert_eq!(
+            call("header", vec![Value::String("Accept".into())]),
+            Value::String("text/html".into())
+        );
+        assert_eq!(
+            call("param", vec![Value::String("nope".into())]),
+            Value::Null
+        );
+        assert!(matches!(call("query", vec![]), Value::Hash(_)));
+    }
+
+    #[test]
+    fn non_response_values_pass_through() {
+        let _env = setup();
+        let hash = named(&[("status", Value::Int(200))]);
+        assert!(response_to_hash(&hash).is_none());
+        assert_eq!(into_response_value(Value::Int(1)), Value::Int(1));
+    }
+}
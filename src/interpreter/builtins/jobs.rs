@@ -10,7 +10,8 @@
 
 use std::collections::HashMap;
 use std::rc::Rc;
-use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
 
 use crate::interpreter::environment::Environment;
 use crate::interpreter::value::{empty_hash, value_to_json, Class, NativeFunction, Value};
@@ -78,7 +79,7 @@ fn jobs_config() -> &'static JobsConfig {
 
 /// Build a SolidB client wired up with the configured database and credentials
 /// from the existing model DB config (so users don't have to set up auth twice).
-fn make_client() -> Result<SoliDBClient, String> {
+pub(crate) fn make_client() -> Result<SoliDBClient, String> {
     use crate::interpreter::builtins::model::core::{
         get_api_key, get_basic_auth, get_jwt_token, DB_CONFIG,
     };
@@ -107,6 +108,48 @@ fn make_client() -> Result<SoliDBClient, String> {
     Ok(client)
 }
 
+/// Whether this instance may point cron schedules at itself. Always true
+/// unless a scheduler lease is in play (`serve::scheduler_lease`), where only
+/// the lease holder is.
+static SCHEDULER_LEADER: AtomicBool = AtomicBool::new(true);
+
+/// `static cron` declarations loaded by web worker 0, as (cron name,
+/// expression, handler class), for a new lease holder to register.
+static STATIC_CRONS: Mutex<Vec<(String, String, String)>> = Mutex::new(Vec::new());
+
+pub fn is_scheduler_leader() -> bool {
+    SCHEDULER_LEADER.load(Ordering::Relaxed)
+}
+
+pub fn set_scheduler_leader(leader: bool) {
+    SCHEDULER_LEADER.store(leader, Ordering::Relaxed);
+}
+
+/// Record a `static cron` declaration, replacing an earlier one of the same
+/// name (hot reload), so [`sync_static_crons`] can register it later.
+pub fn remember_static_cron(name: &str, expr: &str, handler: &str) {
+    let mut crons = STATIC_CRONS.lock().unwrap_or_else(|e| e.into_inner());
+    crons.retain(|(existing, _, _)| existing != name);
+    crons.push((name.to_string(), expr.to_string(), handler.to_string()));
+}
+
+/// Upsert every remembered `static cron` to SolidB, pointing them at this
+/// instance's callback URL. Returns the failures, as (handler, error).
+pub fn sync_static_crons() -> Vec<(String, String)> {
+    let crons = STATIC_CRONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    crons
+        .into_iter()
+        .filter_map(|(name, expr, handler)| {
+            register_static_cron(&name, &expr, &handler)
+                .err()
+                .map(|e| (handler, e))
+        })
+        .collect()
+}
+
 fn callback_for(handler: &str) -> String {
     let base = jobs_config().callback_url.trim_end_matches('/');
     format!("{}/{}", base, handler)
@@ -181,11 +224,8 @@ fn parse_duration_str(s: &str) -> Result<i64, String> {
 }
 
 fn iso_now_plus_seconds(secs: i64) -> String {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_secs() as i64)
-        .unwrap_or(0);
+    // Read the freezable clock so `travel()` in a spec moves `run_at` too.
+    let now = super::datetime::helpers::datetime_now();
     format_iso_utc(now + secs)
 }
 
@@ -798,6 +838,12 @@ fn register_cron_class(env: &mut Environment) {
         "delete".to_string(),
         Rc::new(NativeFunction::new("Cron.delete", Some(1), cron_delete)),
     );
+    statics.insert(
+        "is_leader".to_string(),
+        Rc::new(NativeFunction::new("Cron.is_leader", Some(0), |_| {
+            Ok(Value::Bool(is_scheduler_leader()))
+        })),
+    );
     statics.insert(
         "every".to_string(),
         Rc::new(NativeFunction::new("Cron.every", Some(1), |args| {
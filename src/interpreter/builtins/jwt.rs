@@ -5,7 +5,6 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 use jsonwebtoken::{
     decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
@@ -65,12 +64,25 @@ fn string_list_option(value: &Value, func: &str, option: &str) -> Result<Vec<Str
     }
 }
 
+/// Check `exp` / `nbf` against the frozen test clock, as jsonwebtoken does.
+fn check_time_claims(claims: &Claims, leeway: u64) -> Result<(), String> {
+    let now = current_timestamp();
+    if let Some(exp) = claims.exp {
+        if exp < now.saturating_sub(leeway) {
+            return Err("ExpiredSignature".to_string());
+        }
+    }
+    if let Some(nbf) = claims.data.get("nbf").and_then(JsonValue::as_u64) {
+        if nbf > now + leeway {
+            return Err("ImmatureSignature".to_string());
+        }
+    }
+    Ok(())
+}
+
 /// Get current Unix timestamp.
 fn current_timestamp() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs()
+    super::datetime::helpers::datetime_now().max(0) as u64
 }
 
 /// Register JWT builtins in the given environment.
@@ -446,10 +458,26 @@ pub fn register_jwt_builtins(env: &mut Environment) {
             if let Some(secs) = leeway {
                 validation.leeway = secs;
             }
+            // Under a frozen test clock the library's wall-clock `exp`/`nbf`
+            // checks would disagree with the `exp` that `jwt_sign` just stamped
+            // from the frozen time, so check both against it ourselves.
+            let frozen_clock = super::datetime::helpers::is_datetime_frozen();
+            if frozen_clock {
+                validation.validate_exp = false;
+                validation.validate_nbf = false;
+            }
 
             // Try to decode and verify the token.
             let decoding_key = build_decoding_key(&token_alg, &secret, pem_key.as_deref())?;
-            match decode::<Claims>(&token, &decoding_key, &validation) {
+            let decoded = decode::<Claims>(&token, &decoding_key, &validation)
+                .map_err(|e| e.to_string())
+                .and_then(|token_data| {
+                    if frozen_clock {
+                        check_time_claims(&token_data.claims, validation.leeway)?;
+                    }
+                    Ok(token_data)
+                });
+            match decoded {
                 Ok(token_data) => {
                     // Convert claims to Soli Value
                     let claims = token_data.claims;
@@ -481,10 +509,7 @@ pub fn register_jwt_builtins(env: &mut Environment) {
                     // See www/docs/authentication.md for the correct pattern.
                     let mut error_pairs: HashPairs = HashPairs::default();
                     error_pairs.insert(HashKey::String("error".into()), Value::Bool(true));
-                    error_pairs.insert(
-                        HashKey::String("message".into()),
-                        Value::String(format!("{}", e).into()),
-                    );
+                    error_pairs.insert(HashKey::String("message".into()), Value::String(e.into()));
                     Ok(Value::Hash(Rc::new(RefCell::new(error_pairs))))
                 }
             }
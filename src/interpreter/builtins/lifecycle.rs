@@ -0,0 +1,175 @@
+//! `at_exit` and the server lifecycle hooks.
+//!
+//! `at_exit { ... }` runs a block when the program finishes — after the last
+//! statement or after an uncaught error — in reverse registration order, the
+//! way teardown mirrors setup. `on_boot`, `on_worker_start` and `on_shutdown`
+//! are registered from `config/boot.sl` and run by `soli serve`: `on_boot`
+//! once before any worker starts, `on_worker_start` in each worker before it
+//! takes traffic, and `on_shutdown` (then `at_exit`) in each worker when the
+//! server stops on SIGTERM/SIGINT.
+//!
+//! Hooks hold interpreter values, so they live per thread: each worker
+//! evaluates `config/boot.sl` itself and runs only the hooks it registered.
+
+use std::cell::RefCell;
+
+use crate::interpreter::environment::Environment;
+use crate::interpreter::executor::Interpreter;
+use crate::interpreter::value::{NativeFunction, Value};
+use crate::span::Span;
+
+/// When a registered block runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hook {
+    AtExit,
+    Boot,
+    WorkerStart,
+    Shutdown,
+}
+
+impl Hook {
+    const ALL: [Hook; 4] = [Hook::AtExit, Hook::Boot, Hook::WorkerStart, Hook::Shutdown];
+
+    fn builtin_name(self) -> &'static str {
+        match self {
+            Hook::AtExit => "at_exit",
+            Hook::Boot => "on_boot",
+            Hook::WorkerStart => "on_worker_start",
+            Hook::Shutdown => "on_shutdown",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Teardown hooks run last-registered first.
+    fn runs_in_reverse(self) -> bool {
+        matches!(self, Hook::AtExit | Hook::Shutdown)
+    }
+}
+
+thread_local! {
+    static HOOKS: RefCell<[Vec<Value>; 4]> = RefCell::new(Default::default());
+}
+
+/// Register `at_exit`, `on_boot`, `on_worker_start` and `on_shutdown`.
+pub fn register_lifecycle_builtins(env: &mut Environment) {
+    for hook in Hook::ALL {
+        let name = hook.builtin_name();
+        env.define(
+            name.to_string(),
+            Value::NativeFunction(NativeFunction::new(name, Some(1), move |args| {
+                let block = args.into_iter().next().unwrap_or(Value::Null);
+                if !block.is_callable() {
+                    return Err(format!("{}() expects a block: {} {{ ... }}", name, name));
+                }
+                HOOKS.with(|hooks| hooks.borrow_mut()[hook.index()].push(block));
+                Ok(Value::Null)
+            })),
+        );
+    }
+}
+
+/// Remove and return the blocks registered for `hook` on this thread, in the
+/// order they should run.
+pub fn take_hooks(hook: Hook) -> Vec<Value> {
+    let mut blocks = HOOKS.with(|hooks| std::mem::take(&mut hooks.borrow_mut()[hook.index()]));
+    if hook.runs_in_reverse() {
+        blocks.reverse();
+    }
+    blocks
+}
+
+/// Forget every hook registered on this thread.
+pub fn clear_hooks() {
+    HOOKS.with(|hooks| hooks.borrow_mut().iter_mut().for_each(Vec::clear));
+}
+
+/// Run (and unregister) the blocks for `hook`. A failing block is reported
+/// on stderr and the rest still run — one broken flush must not skip the
+/// others.
+pub fn run_hooks(interpreter: &mut Interpreter, hook: Hook) {
+    for block in take_hooks(hook) {
+        let span = match &block {
+            Value::Function(func) => func.span.unwrap_or_default(),
+            _ => Span::default(),
+        };
+        if let Err(e) = interpreter.call_value(block, Vec::new(), span) {
+            eprintln!("Error in {} hook: {}", hook.builtin_name(), e);
+        }
+    }
+}
+
+/// [`run_hooks`] for a program executed by the bytecode VM.
+pub fn run_hooks_vm(vm: &mut crate::vm::Vm, hook: Hook) {
+    for block in take_hooks(hook) {
+        vm.reset();
+        if let Err(e) = vm.invoke_callable(block, Vec::new(), Span::default()) {
+            eprintln!("Error in {} hook: {}", hook.builtin_name(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(source: &str) -> Interpreter {
+        clear_hooks();
+        let tokens = crate::lexer::Scanner::new(source).scan_tokens().unwrap();
+        let program = crate::parser::Parser::new(tokens).parse().unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.interpret(&program).unwrap();
+        interpreter
+    }
+
+    fn global(interpreter: &Interpreter, name: &str) -> String {
+        interpreter
+            .environment
+            .borrow()
+            .get(name)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn at_exit_runs_in_reverse_and_survives_a_failing_block() {
+        let mut interpreter = run(r#"
+            let log = [];
+            at_exit { log.push("first") }
+            at_exit do
+              throw "boom"
+            end
+            at_exit(fn() { log.push("last") })
+            log.push("main");
+        "#);
+        assert_eq!(global(&interpreter, "log"), "[main]");
+
+        run_hooks(&mut interpreter, Hook::AtExit);
+        assert_eq!(global(&interpreter, "log"), "[main, last, first]");
+
+        // Running a hook unregisters it.
+        run_hooks(&mut interpreter, Hook::AtExit);
+        assert_eq!(global(&interpreter, "log"), "[main, last, first]");
+    }
+
+    #[test]
+    fn each_hook_keeps_its_own_blocks() {
+        let mut interpreter = run(r#"
+            let log = [];
+            on_boot { log.push("boot") }
+            on_worker_start { log.push("start 1") }
+            on_worker_start { log.push("start 2") }
+            on_shutdown { log.push("stop 1") }
+            on_shutdown { log.push("stop 2") }
+        "#);
+        run_hooks(&mut interpreter, Hook::WorkerStart);
+        run_hooks(&mut interpreter, Hook::Shutdown);
+        assert_eq!(
+            global(&interpreter, "log"),
+            "[start 1, start 2, stop 2, stop 1]"
+        );
+        assert_eq!(take_hooks(Hook::Boot).len(), 1);
+    }
+}
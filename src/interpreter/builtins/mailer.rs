@@ -898,22 +898,8 @@ pub fn ensure_prelude(interpreter: &mut crate::interpreter::Interpreter) {
     if interpreter.global_env().borrow().get("Mailer").is_some() {
         return;
     }
-    let tokens = match crate::lexer::Scanner::new(MAILER_PRELUDE).scan_tokens() {
-        Ok(t) => t,
-        Err(e) => {
-            eprintln!("mailer prelude lex error: {e}");
-            return;
-        }
-    };
-    let program = match crate::parser::Parser::new(tokens).parse() {
-        Ok(p) => p,
-        Err(e) => {
-            eprintln!("mailer prelude parse error: {e}");
-            return;
-        }
-    };
-    if let Err(e) = interpreter.interpret(&program) {
-        eprintln!("mailer prelude execute error: {e}");
+    if let Err(e) = interpreter.interpret_synthetic("mailer_prelude", MAILER_PRELUDE) {
+        eprintln!("mailer prelude error: {e}");
         return;
     }
 
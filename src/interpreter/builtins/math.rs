@@ -95,8 +95,7 @@ fn register_math_class(env: &mut Environment) {
             // requests with different random values under one cache key.
             crate::template::response_cache::mark_data_dirty();
             use rand::Rng;
-            let mut rng = rand::thread_rng();
-            Ok(Value::Float(rng.r#gen::<f64>()))
+            Ok(Value::Float(super::rng::with_rng(|rng| rng.r#gen::<f64>())))
         })),
     );
 
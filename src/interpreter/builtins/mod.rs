@@ -4,7 +4,6 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::io::{self, Write};
 use std::rc::Rc;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::interpreter::environment::Environment;
 use crate::interpreter::value::{NativeFunction, Value};
@@ -36,10 +35,12 @@ pub fn current_action_name() -> String {
 }
 
 // Re-export submodules
+pub mod api_contract;
 pub mod apns;
 pub mod app_links;
 pub mod assertions;
 pub mod assigns_helpers;
+pub mod bench;
 pub mod body_limit;
 pub mod browser;
 pub mod cache;
@@ -54,7 +55,9 @@ pub mod deflate;
 pub mod dotenv;
 pub mod encoding;
 pub mod env;
+pub mod events;
 pub mod expectations;
+pub mod export;
 pub mod factories;
 pub mod fcm;
 pub mod file;
@@ -62,8 +65,11 @@ pub mod geo;
 pub mod hash;
 pub mod hex;
 pub mod html;
+pub mod http_cache;
+pub mod http_cassette;
 pub mod http_class;
 pub mod http_log;
+pub mod http_types;
 pub mod i18n;
 pub mod image;
 pub mod imap;
@@ -72,6 +78,7 @@ pub mod json;
 pub mod jwt;
 pub mod kv;
 pub mod kv_log;
+pub mod lifecycle;
 pub mod mail_parse;
 pub mod mailer;
 pub mod markdown;
@@ -95,6 +102,7 @@ pub mod request_helpers;
 pub mod resp;
 pub mod respond_to;
 pub mod response_helpers;
+pub mod rng;
 pub mod router;
 pub mod rsa_key;
 pub mod s3;
@@ -114,6 +122,7 @@ pub mod spreadsheet;
 pub mod streaming;
 pub mod strings;
 pub mod system;
+pub mod task;
 pub mod template;
 pub mod test_dsl;
 pub mod test_helpers;
@@ -121,6 +130,7 @@ pub mod test_server;
 pub mod trust_proxy;
 pub mod types;
 pub mod ulid;
+pub mod upload_fixtures;
 pub mod uploads;
 pub mod uuid;
 pub mod validation;
@@ -184,6 +194,42 @@ fn write_captured_or_stdout(s: &str) {
     }
 }
 
+/// The hash `runtime_stats()` returns. Byte and allocation counts are the
+/// calling thread's (a serve worker's), and zero unless the global allocator
+/// is `CountingAlloc`, as in the `soli` binary.
+fn runtime_stats() -> Value {
+    use crate::interpreter::value::{HashKey, HashPairs};
+
+    let heap = crate::interpreter::heap_counters();
+    let (instances, scopes) = crate::interpreter::cycles::live_counts();
+    let count = |n: u64| Value::Int(n.min(i64::MAX as u64) as i64);
+
+    let mut by_class = HashPairs::default();
+    for (class, n) in &instances {
+        by_class.insert(HashKey::String(class.as_str().into()), count(*n as u64));
+    }
+    let mut stats = HashPairs::default();
+    for (key, value) in [
+        ("heap_bytes", count(heap.live_bytes())),
+        ("allocated_bytes", count(heap.allocated_bytes)),
+        ("freed_bytes", count(heap.freed_bytes)),
+        ("allocations", count(heap.allocations)),
+        (
+            "rss_bytes",
+            crate::interpreter::executor::resident_bytes().map_or(Value::Null, count),
+        ),
+        (
+            "instance_count",
+            count(instances.values().sum::<usize>() as u64),
+        ),
+        ("instances", Value::Hash(Rc::new(RefCell::new(by_class)))),
+        ("closure_scopes", count(scopes as u64)),
+    ] {
+        stats.insert(HashKey::String(key.into()), value);
+    }
+    Value::Hash(Rc::new(RefCell::new(stats)))
+}
+
 /// Register all built-in functions in the given environment.
 /// When `include_test_builtins` is false, test-only modules (factories, assertions,
 /// test_dsl, test_server) are skipped to save memory in serve mode.
@@ -239,6 +285,19 @@ pub fn register_builtins(env: &mut Environment, include_test_builtins: bool) {
         })),
     );
 
+    // connected_to("replica") { ... } — run the block's model queries on a
+    // named database connection. Like `grouped`, the block runs in the
+    // `evaluate_call` interceptor; this placeholder only catches misuse.
+    env.define(
+        "connected_to".to_string(),
+        Value::NativeFunction(NativeFunction::new("connected_to", Some(2), |_args| {
+            Err(
+                "connected_to() expects a connection name and a block: connected_to(\"replica\") { ... }"
+                    .to_string(),
+            )
+        })),
+    );
+
     // __sdql_exec(query, binds) — runtime backing for `@sdbql{ ... }` blocks.
     // The tree-walking interpreter executes the block inline; the VM compiler
     // lowers a block to a call to this global instead (it cannot inline the
@@ -368,6 +427,7 @@ pub fn register_builtins(env: &mut Environment, include_test_builtins: bool) {
             let resolved = args.into_iter().next().unwrap().resolve()?;
             match &resolved {
                 Value::Array(arr) => Ok(Value::Int(arr.borrow().len() as i64)),
+                Value::Range(range) => Ok(Value::Int(range.len() as i64)),
                 Value::String(s) => Ok(Value::Int(s.chars().count() as i64)),
                 Value::Hash(hash) => Ok(Value::Int(hash.borrow().len() as i64)),
                 Value::QueryBuilder(qb) => Ok(
@@ -392,8 +452,20 @@ pub fn register_builtins(env: &mut Environment, include_test_builtins: bool) {
             // key would otherwise group requests with different
             // timestamps under one cached body.
             crate::template::response_cache::mark_data_dirty();
-            let duration = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-            Ok(Value::Float(duration.as_secs_f64()))
+            let nanos = datetime::helpers::clock_now_nanos();
+            Ok(Value::Float(nanos as f64 / 1_000_000_000.0))
+        })),
+    );
+
+    // ===== Runtime introspection =====
+
+    // runtime_stats() - This thread's heap: allocator counters, resident
+    // memory, and live instances by class.
+    env.define(
+        "runtime_stats".to_string(),
+        Value::NativeFunction(NativeFunction::new("runtime_stats", Some(0), |_| {
+            crate::template::response_cache::mark_data_dirty();
+            Ok(runtime_stats())
         })),
     );
 
@@ -431,6 +503,12 @@ pub fn register_builtins(env: &mut Environment, include_test_builtins: bool) {
     // Register HTTP class
     http_class::register_http_class(env);
 
+    // HTTP caching helpers (fresh_when, stale?, expires_in)
+    http_cache::register_http_cache_builtins(env);
+
+    // Request/Response classes for typed controller actions
+    http_types::register_http_type_classes(env);
+
     // Register S3 class
     s3::register_s3_class(env);
 
@@ -545,7 +623,10 @@ pub fn register_builtins(env: &mut Environment, include_test_builtins: bool) {
         assertions::register_assertions(env);
         expectations::register_expectation_class(env);
         test_dsl::register_test_builtins(env);
+        bench::register_bench_builtins(env);
+        task::register_task_builtins(env);
         test_server::register_test_server_builtins(env);
+        upload_fixtures::register_upload_fixture_builtins(env);
         mock_http::register_mock_http_builtins(env);
         // Browser helpers are test-only: `visit`/`click` are ordinary enough
         // names that leaking them into a served app would be a real hazard.
@@ -607,6 +688,18 @@ pub fn register_builtins(env: &mut Environment, include_test_builtins: bool) {
     // Register clock builtins (sleep, microtime)
     clock::register_clock_builtins(env);
 
+    // Register at_exit and the serve lifecycle hooks (on_boot, on_worker_start,
+    // on_shutdown)
+    lifecycle::register_lifecycle_builtins(env);
+
+    // Register the Events pub/sub natives. The `Events` class itself is
+    // defined by a Soli prelude (events::ensure_prelude).
+    events::register_events_builtins(env);
+
+    // Register the Export natives; the `Export` class is a Soli prelude
+    // (export::ensure_prelude).
+    export::register_export_builtins(env);
+
     // Register collection classes (String, Array, Hash, Set, Range, Base64)
     collections::register_collection_classes(env);
 
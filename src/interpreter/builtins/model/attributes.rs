@@ -0,0 +1,139 @@
+//! Typed attribute schema for document models.
+//!
+//! SolidB collections are schemaless, so a model opts into a schema by
+//! declaring its attributes in the class body:
+//!
+//! ```soli
+//! class Post < Model
+//!   attribute "title", "string"
+//!   attribute "views", "int"
+//!   attribute "published", "bool"
+//!   attribute "tags", "array"
+//!   attribute "location", "point"
+//! end
+//! ```
+//!
+//! Once a model declares at least one attribute:
+//! - the type checker types `post.title` as `String` and rejects a typo'd
+//!   `post.tilte` at check time (it only sees literal declarations);
+//! - `post.views = "ten"` errors at runtime, and create/update/save report a
+//!   `"must be of type int"` validation error for a mismatched value;
+//! - reading an undeclared, unset field raises instead of yielding `null`.
+//!
+//! Models with no `attribute` declarations keep the old schemaless behavior.
+
+use crate::interpreter::value::Value;
+
+/// Accepted `attribute` type names. Aliases normalize via `normalize_type`.
+pub const ATTRIBUTE_TYPES: &[&str] = &[
+    "string",
+    "text",
+    "int",
+    "integer",
+    "float",
+    "number",
+    "decimal",
+    "bool",
+    "boolean",
+    "array",
+    "hash",
+    "json",
+    "object",
+    "datetime",
+    "timestamp",
+    "point",
+    "any",
+];
+
+/// Canonical name for a declared type, or `None` when it isn't recognized.
+pub fn normalize_type(data_type: &str) -> Option<&'static str> {
+    Some(match data_type.to_lowercase().as_str() {
+        "string" | "text" => "string",
+        "int" | "integer" => "int",
+        "float" | "number" | "decimal" => "float",
+        "bool" | "boolean" => "bool",
+        "array" => "array",
+        "hash" | "json" | "object" => "hash",
+        "datetime" | "timestamp" => "datetime",
+        "point" => "point",
+        "any" => "any",
+        _ => return None,
+    })
+}
+
+/// Whether `value` may be stored in an attribute of (normalized) `data_type`.
+/// `null` always fits — requiredness is `validates presence:`'s job.
+pub fn value_matches(data_type: &str, value: &Value) -> bool {
+    let value = value.force_deferred();
+    match (data_type, &value) {
+        (_, Value::Null) | ("any", _) => true,
+        ("string", v) => matches!(v, Value::String(_) | Value::Symbol(_)),
+        ("int", v) => matches!(v, Value::Int(_)),
+        ("float", v) => matches!(v, Value::Int(_) | Value::Float(_) | Value::Decimal(_)),
+        ("bool", v) => matches!(v, Value::Bool(_)),
+        ("array", v) => matches!(v, Value::Array(_)),
+        ("hash", v) => matches!(v, Value::Hash(_)),
+        // Stored as an ISO string or epoch int; DateTime instances serialize.
+        ("datetime", Value::Instance(inst)) => inst.borrow().class.name == "DateTime",
+        ("datetime", v) => matches!(v, Value::String(_) | Value::Int(_)),
+        // A Point, or anything `Point.cast` accepts; reads hydrate to Point.
+        ("point", v) => crate::interpreter::builtins::geo::coordinates_of(v).is_some(),
+        _ => true,
+    }
+}
+
+/// Runtime error for `instance.field = value` against a declared attribute.
+/// `None` when the model doesn't declare `field` or the value fits.
+pub fn check_assignment(class_name: &str, field: &str, value: &Value) -> Option<String> {
+    let data_type = super::registry::get_attribute_type(class_name, field)?;
+    if value_matches(&data_type, value) {
+        return None;
+    }
+    Some(format!(
+        "{}.{} is declared as {}, got {}",
+        class_name,
+        field,
+        data_type,
+        value.type_name()
+    ))
+}
+
+/// Fields every document carries whether or not the schema lists them.
+const IMPLICIT_FIELDS: &[&str] = &["id", "created_at", "updated_at", "deleted_at"];
+
+/// True when `class_name` declares a schema and `field` isn't part of it —
+/// the read of an unset `field` is then a typo rather than a missing value.
+pub fn is_undeclared(class_name: &str, field: &str) -> bool {
+    if field.starts_with('_') || IMPLICIT_FIELDS.contains(&field) {
+        return false;
+    }
+    let attributes = super::registry::get_attributes(class_name);
+    !attributes.is_empty() && !attributes.iter().any(|a| a.name == field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aliases_normalize_and_unknown_types_are_rejected() {
+        assert_eq!(normalize_type("Integer"), Some("int"));
+        assert_eq!(normalize_type("text"), Some("string"));
+        assert_eq!(normalize_type("json"), Some("hash"));
+        assert_eq!(normalize_type("uuid"), None);
+        for t in ATTRIBUTE_TYPES {
+            assert!(normalize_type(t).is_some(), "{} should normalize", t);
+        }
+    }
+
+    #[test]
+    fn values_are_matched_against_declared_types() {
+        assert!(value_matches("string", &Value::String("a".into())));
+        assert!(!value_matches("string", &Value::Int(1)));
+        assert!(value_matches("float", &Value::Int(1)));
+        assert!(!value_matches("int", &Value::Float(1.5)));
+        assert!(value_matches("bool", &Value::Null));
+        assert!(value_matches("any", &Value::Int(1)));
+        assert!(!value_matches("point", &Value::String("48.8,2.3".into())));
+    }
+}
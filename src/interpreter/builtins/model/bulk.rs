@@ -0,0 +1,441 @@
+//! Bulk writes: `Model.insert_all`, `Model.upsert_all` and the static
+//! `Model.update_all(where:, set:)`.
+//!
+//! Each one sends batched SDBQL statements (`FOR d IN @rows INSERT …` /
+//! `UPDATE …`) instead of one document request per row, so importing ten
+//! thousand rows costs a handful of round-trips rather than ten thousand.
+//!
+//! Like `where(...).update_all` and `delete_all`, these are raw writes: they
+//! skip validations, lifecycle callbacks and counter caches. They do apply the
+//! `attr_accessible` whitelist and `encrypts` at-rest encryption, which guard
+//! the data itself rather than the model lifecycle.
+//!
+//! Keys are assigned client-side (UUIDv7, so `_key` order is insertion order)
+//! for rows that don't carry one, which is how the ids come back without a
+//! read-back. Timeseries models keep their server-generated keys, so
+//! `insert_all` reports no ids for them there.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::interpreter::value::{Class, HashKey, HashPairs, Value};
+
+use super::core::{
+    build_safe_filter_from_hash, class_name_to_collection, filter_mass_assign,
+    timeseries_insert_only_error, validate_field_name,
+};
+use super::crud::{exec_with_auto_collection, get_current_tx_id};
+use super::query::{execute_query_builder_update_all, QueryBuilder};
+
+/// Rows per INSERT/UPDATE statement, so one request never carries an
+/// unbounded payload.
+const BULK_CHUNK: usize = 1000;
+
+/// Bind name of the row batch — reserved so it can't collide with user binds.
+const ROWS_BIND: &str = "__soli_rows";
+
+/// `Model.insert_all(rows)` — insert every row in batched statements.
+/// Returns `{ "inserted": n, "ids": [...] }` (ids in row order; omitted for
+/// timeseries models).
+pub fn insert_all(class_name: &str, rows: &Value) -> Result<Value, String> {
+    outside_transaction("insert_all")?;
+    let collection = class_name_to_collection(class_name);
+    let timeseries = super::registry::is_timeseries_model(class_name);
+    let mut docs = rows_to_docs(class_name, rows, "insert_all")?;
+
+    let mut ids = Vec::with_capacity(docs.len());
+    if !timeseries {
+        for doc in &mut docs {
+            ids.push(assign_key(doc, "insert_all")?);
+        }
+    }
+    let inserted = docs.len();
+    run_batched(
+        &format!("FOR d IN @{} INSERT d INTO {}", ROWS_BIND, collection),
+        &collection,
+        docs,
+    )
+    .map_err(|e| format!("insert_all failed: {}", e))?;
+
+    let mut result = vec![("inserted", Value::Int(inserted as i64))];
+    if !timeseries {
+        result.push(("ids", string_array(ids)));
+    }
+    Ok(hash_of(result))
+}
+
+/// `Model.upsert_all(rows, unique_by: "email")` — update the rows whose
+/// `unique_by` field(s) match an existing document, insert the rest. One
+/// lookup statement, then at most one batched UPDATE and one batched INSERT
+/// (SolidB has no `UPSERT`, so the partition happens here). `unique_by`
+/// defaults to `_key`. Returns `{ "inserted": n, "updated": m, "ids": [...] }`.
+pub fn upsert_all(class_name: &str, rows: &Value, unique_by: &[String]) -> Result<Value, String> {
+    outside_transaction("upsert_all")?;
+    if super::registry::is_timeseries_model(class_name) {
+        return Err(timeseries_insert_only_error(class_name, "upsert_all"));
+    }
+    if unique_by.is_empty() {
+        return Err("upsert_all() unique_by: needs at least one field".to_string());
+    }
+    for field in unique_by {
+        validate_field_name(field, "upsert_all")?;
+    }
+    let collection = class_name_to_collection(class_name);
+    let docs = rows_to_docs(class_name, rows, "upsert_all")?;
+
+    // Each row's identity: its unique_by values, in order.
+    let mut identities = Vec::with_capacity(docs.len());
+    let mut seen = HashMap::new();
+    for (i, doc) in docs.iter().enumerate() {
+        let mut values = Vec::with_capacity(unique_by.len());
+        for field in unique_by {
+            match doc.get(field) {
+                Some(v) if !v.is_null() => values.push(v.clone()),
+                _ => {
+                    return Err(format!(
+                        "upsert_all() row {} is missing unique_by field '{}'",
+                        i, field
+                    ))
+                }
+            }
+        }
+        let identity = serde_json::Value::Array(values);
+        if let Some(first) = seen.insert(identity.to_string(), i) {
+            return Err(format!(
+                "upsert_all() rows {} and {} share the same unique_by value {}",
+                first, i, identity
+            ));
+        }
+        identities.push(identity);
+    }
+
+    let existing = existing_keys(&collection, unique_by, &identities)
+        .map_err(|e| format!("upsert_all failed: {}", e))?;
+
+    let mut ids = Vec::with_capacity(docs.len());
+    let mut inserts = Vec::new();
+    let mut updates = Vec::new();
+    for (mut doc, identity) in docs.into_iter().zip(&identities) {
+        if let Some(key) = existing.get(&identity.to_string()) {
+            doc.insert("_key".to_string(), serde_json::Value::String(key.clone()));
+            ids.push(key.clone());
+            updates.push(doc);
+        } else {
+            ids.push(assign_key(&mut doc, "upsert_all")?);
+            inserts.push(doc);
+        }
+    }
+
+    let (inserted, updated) = (inserts.len(), updates.len());
+    run_batched(
+        &format!(
+            "FOR d IN @{} UPDATE d._key WITH d IN {}",
+            ROWS_BIND, collection
+        ),
+        &collection,
+        updates,
+    )
+    .and_then(|_| {
+        run_batched(
+            &format!("FOR d IN @{} INSERT d INTO {}", ROWS_BIND, collection),
+            &collection,
+            inserts,
+        )
+    })
+    .map_err(|e| format!("upsert_all failed: {}", e))?;
+
+    Ok(hash_of(vec![
+        ("inserted", Value::Int(inserted as i64)),
+        ("updated", Value::Int(updated as i64)),
+        ("ids", string_array(ids)),
+    ]))
+}
+
+/// `Model.update_all(where: {...}, set: {...})` — the static spelling of
+/// `Model.where({...}).update_all({...})`: one UPDATE over every match.
+/// `where:` is the safe hash form (`{}` matches every row).
+pub fn update_all(class: &Rc<Class>, options: &Value) -> Result<Value, String> {
+    let class_name = class.name.clone();
+    if super::registry::is_timeseries_model(&class_name) {
+        return Err(timeseries_insert_only_error(&class_name, "update_all"));
+    }
+    let usage = "update_all() expects where: and set: hashes, e.g. \
+                 update_all(where: { \"active\": false }, set: { \"archived\": true })";
+    let Value::Hash(options) = options else {
+        return Err(usage.to_string());
+    };
+    let option = |name: &str| options.borrow().get(&HashKey::String(name.into())).cloned();
+    let (Some(Value::Hash(filter)), Some(set @ Value::Hash(_))) = (option("where"), option("set"))
+    else {
+        return Err(usage.to_string());
+    };
+    let (filter, binds) = build_safe_filter_from_hash(&filter, "update_all")?;
+    let set = crate::interpreter::value::value_to_json(&set)?;
+
+    let collection = class_name_to_collection(&class_name);
+    let mut qb = QueryBuilder::new_with_class(class_name, collection, class.clone());
+    qb.set_filter(filter, binds);
+    Ok(execute_query_builder_update_all(&qb, set))
+}
+
+/// Bulk statements go straight to the cursor endpoint, outside any open
+/// transaction — refuse rather than silently escaping its rollback.
+fn outside_transaction(method: &str) -> Result<(), String> {
+    if get_current_tx_id().is_some() {
+        return Err(format!(
+            "{}() can't run inside a transaction — bulk statements bypass it. \
+             Use create/upsert per row inside the transaction block.",
+            method
+        ));
+    }
+    Ok(())
+}
+
+/// Validate `rows` and convert each to a JSON document, applying the
+/// `attr_accessible` whitelist per row (`_key` always passes).
+fn rows_to_docs(
+    class_name: &str,
+    rows: &Value,
+    method: &str,
+) -> Result<Vec<serde_json::Map<String, serde_json::Value>>, String> {
+    let rows = match rows {
+        Value::Array(arr) => arr.borrow().clone(),
+        other => {
+            return Err(format!(
+                "{}() expects an array of hashes, got {}",
+                method,
+                other.type_name()
+            ))
+        }
+    };
+    let mut docs = Vec::with_capacity(rows.len());
+    for (i, row) in rows.iter().enumerate() {
+        if !matches!(row, Value::Hash(_)) {
+            return Err(format!(
+                "{}() row {} must be a hash, got {}",
+                method,
+                i,
+                row.type_name()
+            ));
+        }
+        let filtered = filter_mass_assign(class_name, row);
+        let serde_json::Value::Object(mut map) =
+            crate::interpreter::value::value_to_json(&filtered)?
+        else {
+            unreachable!()
+        };
+        // The whitelist drops `_`-prefixed keys, but a caller-chosen
+        // `_key` is the row's identity here, not a mass-assigned field.
+        if let Value::Hash(pairs) = row {
+            if let Some(key) = pairs.borrow().get(&HashKey::String("_key".into())) {
+                map.insert(
+                    "_key".to_string(),
+                    crate::interpreter::value::value_to_json(key)?,
+                );
+            }
+        }
+        docs.push(map);
+    }
+    Ok(docs)
+}
+
+/// The row's `_key`, generating a UUIDv7 one when it has none.
+fn assign_key(
+    doc: &mut serde_json::Map<String, serde_json::Value>,
+    method: &str,
+) -> Result<String, String> {
+    match doc.get("_key") {
+        Some(serde_json::Value::String(key)) => Ok(key.clone()),
+        Some(other) => Err(format!("{}() _key must be a string, got {}", method, other)),
+        None => {
+            let key = uuid::Uuid::now_v7().to_string();
+            doc.insert("_key".to_string(), serde_json::Value::String(key.clone()));
+            Ok(key)
+        }
+    }
+}
+
+/// Identity (JSON-encoded unique_by values) → `_key` of the documents that
+/// already exist. Each field narrows with `IN`, and the exact tuple match
+/// happens here, which keeps the statement to plain `FILTER … IN` clauses.
+fn existing_keys(
+    collection: &str,
+    unique_by: &[String],
+    identities: &[serde_json::Value],
+) -> Result<HashMap<String, String>, String> {
+    if identities.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let mut binds = HashMap::new();
+    let mut clauses = Vec::with_capacity(unique_by.len());
+    for (i, field) in unique_by.iter().enumerate() {
+        let values: Vec<serde_json::Value> = identities.iter().map(|id| id[i].clone()).collect();
+        binds.insert(format!("__soli_k{}", i), serde_json::Value::Array(values));
+        clauses.push(format!("doc.{} IN @__soli_k{}", field, i));
+    }
+    let fields: Vec<String> = unique_by.iter().map(|f| format!("doc.{}", f)).collect();
+    let query = format!(
+        "FOR doc IN {} FILTER {} RETURN {{ \"_key\": doc._key, \"v\": [{}] }}",
+        collection,
+        clauses.join(" AND "),
+        fields.join(", ")
+    );
+    let rows = exec_with_auto_collection(query, Some(binds), collection)?;
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            let key = row.get("_key")?.as_str()?.to_string();
+            Some((row.get("v")?.to_string(), key))
+        })
+        .collect())
+}
+
+/// Run `query` once per chunk of `docs`, bound as `@__soli_rows`. Documents
+/// are encrypted here — the bulk path skips `exec_insert`, which normally
+/// does it — and live queries on the collection are woken once at the end.
+fn run_batched(
+    query: &str,
+    collection: &str,
+    docs: Vec<serde_json::Map<String, serde_json::Value>>,
+) -> Result<(), String> {
+    if docs.is_empty() {
+        return Ok(());
+    }
+    let mut docs: Vec<serde_json::Value> =
+        docs.into_iter().map(serde_json::Value::Object).collect();
+    for doc in &mut docs {
+        super::registry::encrypt_document_fields(collection, doc)?;
+    }
+    for chunk in docs.chunks(BULK_CHUNK) {
+        let mut binds = HashMap::new();
+        binds.insert(
+            ROWS_BIND.to_string(),
+            serde_json::Value::Array(chunk.to_vec()),
+        );
+        exec_with_auto_collection(query.to_string(), Some(binds), collection)?;
+    }
+    crate::live::live_query::notify_change(collection, None);
+    Ok(())
+}
+
+fn string_array(items: Vec<String>) -> Value {
+    Value::Array(Rc::new(RefCell::new(
+        items.into_iter().map(|s| Value::String(s.into())).collect(),
+    )))
+}
+
+fn hash_of(entries: Vec<(&str, Value)>) -> Value {
+    let mut pairs = HashPairs::default();
+    for (k, v) in entries {
+        pairs.insert(HashKey::String(k.into()), v);
+    }
+    Value::Hash(Rc::new(RefCell::new(pairs)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(items: Vec<Value>) -> Value {
+        Value::Array(Rc::new(RefCell::new(items)))
+    }
+
+    fn hash(entries: Vec<(&str, Value)>) -> Value {
+        hash_of(entries)
+    }
+
+    #[test]
+    fn rows_must_be_an_array_of_hashes() {
+        let err = rows_to_docs("BulkTestUser", &Value::Int(1), "insert_all").unwrap_err();
+        assert!(err.contains("expects an array of hashes"), "{err}");
+        let err =
+            rows_to_docs("BulkTestUser", &rows(vec![Value::Int(1)]), "insert_all").unwrap_err();
+        assert!(err.contains("row 0 must be a hash"), "{err}");
+    }
+
+    #[test]
+    fn assign_key_keeps_a_supplied_key_and_generates_the_rest() {
+        let docs = rows_to_docs(
+            "BulkTestUser",
+            &rows(vec![
+                hash(vec![("_key", Value::String("u1".into()))]),
+                hash(vec![("name", Value::String("Bo".into()))]),
+            ]),
+            "insert_all",
+        )
+        .unwrap();
+        let mut docs = docs.into_iter();
+        assert_eq!(
+            assign_key(&mut docs.next().unwrap(), "insert_all").unwrap(),
+            "u1"
+        );
+        let mut second = docs.next().unwrap();
+        let key = assign_key(&mut second, "insert_all").unwrap();
+        assert_eq!(key.len(), 36);
+        assert_eq!(second["_key"], serde_json::Value::String(key));
+    }
+
+    #[test]
+    fn upsert_all_rejects_missing_and_duplicate_identities() {
+        let missing = rows(vec![hash(vec![("name", Value::String("a".into()))])]);
+        let err = upsert_all("BulkTestUser", &missing, &["email".to_string()]).unwrap_err();
+        assert!(err.contains("missing unique_by field 'email'"), "{err}");
+
+        let email = || Value::String("a@x.io".into());
+        let dupes = rows(vec![
+            hash(vec![("email", email())]),
+            hash(vec![("email", email())]),
+        ]);
+        let err = upsert_all("BulkTestUser", &dupes, &["email".to_string()]).unwrap_err();
+        assert!(err.contains("rows 0 and 1 share"), "{err}");
+    }
+
+    #[test]
+    fn upsert_all_updates_matches_and_inserts_the_rest() {
+        use super::super::crud::{clear_query_mocks, register_query_mock};
+        let collection = class_name_to_collection("BulkTestUpsert");
+        register_query_mock(
+            format!(
+                "FOR doc IN {} FILTER doc.email IN @__soli_k0 RETURN {{ \"_key\": doc._key, \"v\": [doc.email] }}",
+                collection
+            ),
+            vec![serde_json::json!({ "_key": "k1", "v": ["a@x.io"] })],
+        );
+        register_query_mock(
+            format!(
+                "FOR d IN @__soli_rows UPDATE d._key WITH d IN {}",
+                collection
+            ),
+            vec![],
+        );
+        register_query_mock(
+            format!("FOR d IN @__soli_rows INSERT d INTO {}", collection),
+            vec![],
+        );
+
+        let result = upsert_all(
+            "BulkTestUpsert",
+            &rows(vec![
+                hash(vec![("email", Value::String("a@x.io".into()))]),
+                hash(vec![("email", Value::String("b@x.io".into()))]),
+            ]),
+            &["email".to_string()],
+        );
+        clear_query_mocks();
+
+        let Value::Hash(result) = result.unwrap() else {
+            panic!("expected a hash");
+        };
+        let result = result.borrow();
+        let get = |k: &str| result.get(&HashKey::String(k.into())).cloned().unwrap();
+        assert!(matches!(get("inserted"), Value::Int(1)));
+        assert!(matches!(get("updated"), Value::Int(1)));
+        let Value::Array(ids) = get("ids") else {
+            panic!("expected ids");
+        };
+        let ids = ids.borrow();
+        assert!(matches!(&ids[0], Value::String(k) if k.as_str() == "k1"));
+        assert_eq!(ids.len(), 2);
+    }
+}
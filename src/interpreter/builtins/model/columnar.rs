@@ -73,6 +73,9 @@ pub fn is_document_api_method(name: &str) -> bool {
             | "find_or_create_by"
             | "create"
             | "create_many"
+            | "insert_all"
+            | "upsert_all"
+            | "update_all"
             | "update"
             | "upsert"
             | "delete"
@@ -112,6 +115,7 @@ pub fn is_document_api_method(name: &str) -> bool {
             | "graph_rag"
             | "near"
             | "within"
+            | "within_box"
     )
 }
 
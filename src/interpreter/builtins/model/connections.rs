@@ -0,0 +1,331 @@
+//! Named database connections and read/write splitting for the model layer.
+//!
+//! The primary connection is the one `SOLIDB_HOST` / `SOLIDB_DATABASE`
+//! describe. Any other name is configured with the same variables under a
+//! `SOLIDB_<NAME>_` prefix (`SOLIDB_REPLICA_HOST`, `SOLIDB_ANALYTICS_DATABASE`,
+//! ...), typically in `.env.{APP_ENV}`; whatever a connection leaves unset is
+//! taken from the primary, credentials included.
+//!
+//! `connected_to("analytics") { ... }` sends every model query in the block
+//! to that connection. Outside such a block, read-only AQL goes to the read
+//! connection (`SOLIDB_READ_FROM`, default `replica` when one is configured)
+//! and everything else to the primary. A request that has written, or is in
+//! a transaction, keeps reading from the primary so it sees its own writes.
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use super::db_config::{get_cursor_url, get_database_name, parse_solidb_host};
+
+/// Name of the connection `SOLIDB_HOST` / `SOLIDB_DATABASE` configure.
+pub const PRIMARY: &str = "primary";
+
+/// Credentials a named connection sets for itself.
+#[derive(Debug, Clone, PartialEq)]
+enum ConnectionAuth {
+    ApiKey(String),
+    Basic(String),
+}
+
+/// A database connection other than the primary.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbConnection {
+    pub name: String,
+    scheme: String,
+    host: String,
+    database: String,
+    /// `None` when the connection sets no credentials of its own and
+    /// authenticates like the primary (JWT, API key or basic auth).
+    auth: Option<ConnectionAuth>,
+}
+
+impl DbConnection {
+    /// Read connection `name` from `var`. `None` unless it sets a host or a
+    /// database of its own.
+    fn from_vars(name: &str, var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let prefix = format!("SOLIDB_{}_", env_name(name));
+        let own = |key: &str| var(&format!("{}{}", prefix, key));
+        let host = own("HOST");
+        let database = own("DATABASE");
+        if host.is_none() && database.is_none() {
+            return None;
+        }
+        let raw_host = host
+            .or_else(|| var("SOLIDB_HOST"))
+            .unwrap_or_else(|| "http://localhost:6745".to_string());
+        let (scheme, host) = parse_solidb_host(&raw_host);
+        let database = database
+            .or_else(|| var("SOLIDB_DATABASE"))
+            .unwrap_or_else(|| "default".to_string());
+        let auth = match (own("API_KEY"), own("USERNAME"), own("PASSWORD")) {
+            (Some(key), _, _) => Some(ConnectionAuth::ApiKey(key)),
+            (None, Some(user), Some(password)) => {
+                use base64::Engine;
+                Some(ConnectionAuth::Basic(format!(
+                    "Basic {}",
+                    base64::engine::general_purpose::STANDARD
+                        .encode(format!("{}:{}", user, password))
+                )))
+            }
+            _ => None,
+        };
+        Some(Self {
+            name: name.to_string(),
+            scheme,
+            host,
+            database,
+            auth,
+        })
+    }
+
+    pub fn database(&self) -> &str {
+        &self.database
+    }
+
+    /// `{scheme}{host}{path}` on this connection's server.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}{}", self.scheme, self.host, path)
+    }
+
+    pub fn cursor_url(&self) -> String {
+        self.url(&format!("/_api/database/{}/cursor", self.database))
+    }
+
+    /// Whether the connection sets credentials of its own instead of
+    /// authenticating like the primary.
+    pub fn has_own_auth(&self) -> bool {
+        self.auth.is_some()
+    }
+
+    /// Attach this connection's own credentials, if it has any.
+    pub fn apply_auth(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.auth {
+            Some(ConnectionAuth::ApiKey(key)) => builder.header("X-API-Key", key),
+            Some(ConnectionAuth::Basic(basic)) => builder.header("Authorization", basic),
+            None => builder,
+        }
+    }
+}
+
+/// `read-replica` → `READ_REPLICA`, the form connection names take in
+/// variable names.
+fn env_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+static CONNECTIONS: OnceLock<Mutex<HashMap<String, Option<Arc<DbConnection>>>>> = OnceLock::new();
+
+/// Look up connection `name`: `Ok(None)` for the primary, an error naming
+/// the variables to set when it is not configured.
+pub fn connection(name: &str) -> Result<Option<Arc<DbConnection>>, String> {
+    if name == PRIMARY {
+        return Ok(None);
+    }
+    let mut cache = CONNECTIONS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .map_err(|_| "database connection cache poisoned".to_string())?;
+    let found = cache
+        .entry(name.to_string())
+        .or_insert_with(|| {
+            DbConnection::from_vars(name, |key| std::env::var(key).ok()).map(Arc::new)
+        })
+        .clone();
+    match found {
+        Some(conn) => Ok(Some(conn)),
+        None => {
+            let prefix = env_name(name);
+            Err(format!(
+                "unknown database connection '{}' — set SOLIDB_{}_HOST and/or SOLIDB_{}_DATABASE",
+                name, prefix, prefix
+            ))
+        }
+    }
+}
+
+/// The connection read-only model queries go to when nothing else decides:
+/// `SOLIDB_READ_FROM`, or `replica` if that one is configured.
+fn read_from() -> Option<Arc<DbConnection>> {
+    static READ_FROM: OnceLock<Option<String>> = OnceLock::new();
+    match READ_FROM.get_or_init(|| std::env::var("SOLIDB_READ_FROM").ok()) {
+        Some(name) => match connection(name) {
+            Ok(conn) => conn,
+            Err(e) => {
+                eprintln!("Warning: SOLIDB_READ_FROM: {}; reading from the primary", e);
+                None
+            }
+        },
+        None => connection("replica").ok().flatten(),
+    }
+}
+
+thread_local! {
+    /// `connected_to` blocks being run, innermost last. `None` entries are
+    /// `connected_to("primary")`.
+    static SELECTED: RefCell<Vec<Option<Arc<DbConnection>>>> = const { RefCell::new(Vec::new()) };
+    /// Whether this request has written through the automatic routing, which
+    /// pins its reads to the primary.
+    static WROTE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Enter a `connected_to` block.
+pub fn push_connection(conn: Option<Arc<DbConnection>>) {
+    SELECTED.with(|selected| selected.borrow_mut().push(conn));
+}
+
+/// Leave the innermost `connected_to` block.
+pub fn pop_connection() {
+    SELECTED.with(|selected| {
+        selected.borrow_mut().pop();
+    });
+}
+
+/// The connection writes (and everything outside the read routing) use:
+/// the innermost `connected_to`, else the primary (`None`).
+pub fn selected_connection() -> Option<Arc<DbConnection>> {
+    SELECTED.with(|selected| selected.borrow().last().cloned().flatten())
+}
+
+fn in_connected_to() -> bool {
+    SELECTED.with(|selected| !selected.borrow().is_empty())
+}
+
+/// Record a write, so the rest of the request reads from the primary.
+pub fn note_write() {
+    WROTE.with(|wrote| wrote.set(true));
+}
+
+/// Forget the previous request's routing state on a reused worker thread.
+pub fn reset_request() {
+    SELECTED.with(|selected| selected.borrow_mut().clear());
+    WROTE.with(|wrote| wrote.set(false));
+}
+
+/// The connection an AQL query goes to. Inside `connected_to` that is the
+/// block's connection; otherwise a write goes to the primary, and so does a
+/// read once the request has written or while a transaction is open. Any
+/// other read goes to the read connection, if one is configured.
+pub fn query_connection(sdbql: &str) -> Option<Arc<DbConnection>> {
+    if in_connected_to() {
+        return selected_connection();
+    }
+    if is_write_query(sdbql) {
+        note_write();
+        return None;
+    }
+    if WROTE.with(Cell::get) || super::crud::has_active_tx() {
+        return None;
+    }
+    read_from()
+}
+
+pub fn database_name(conn: Option<&DbConnection>) -> String {
+    conn.map_or_else(get_database_name, |c| c.database.clone())
+}
+
+pub fn cursor_url(conn: Option<&DbConnection>) -> String {
+    conn.map_or_else(get_cursor_url, DbConnection::cursor_url)
+}
+
+pub fn db_url(conn: Option<&DbConnection>, path: &str) -> String {
+    conn.map_or_else(|| super::db_config::db_url(path), |c| c.url(path))
+}
+
+/// Whether an AQL query writes (a lexical check for the write keywords).
+pub fn is_write_query(sql: &str) -> bool {
+    let upper = sql.to_uppercase();
+    ["INSERT", "UPDATE", "REPLACE", "REMOVE", "UPSERT"]
+        .iter()
+        .any(|kw| {
+            upper
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .any(|tok| tok == *kw)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let map: HashMap<String, String> = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| map.get(key).cloned()
+    }
+
+    #[test]
+    fn named_connection_falls_back_to_the_primary() {
+        let env = vars(&[
+            ("SOLIDB_HOST", "https://db.example.com"),
+            ("SOLIDB_DATABASE", "shop"),
+            ("SOLIDB_REPLICA_HOST", "replica.internal:6745"),
+            ("SOLIDB_ANALYTICS_DATABASE", "shop_analytics"),
+            ("SOLIDB_ANALYTICS_API_KEY", "k"),
+        ]);
+        let replica = DbConnection::from_vars("replica", &env).unwrap();
+        assert_eq!(
+            replica.cursor_url(),
+            "https://replica.internal:6745/_api/database/shop/cursor"
+        );
+        assert_eq!(replica.auth, None);
+
+        let analytics = DbConnection::from_vars("analytics", &env).unwrap();
+        assert_eq!(analytics.url("/x"), "https://db.example.com/x");
+        assert_eq!(analytics.database(), "shop_analytics");
+        assert_eq!(
+            analytics.auth,
+            Some(ConnectionAuth::ApiKey("k".to_string()))
+        );
+
+        assert!(DbConnection::from_vars("reporting", &env).is_none());
+    }
+
+    #[test]
+    fn connection_names_map_to_variable_prefixes() {
+        let env = vars(&[("SOLIDB_READ_REPLICA_DATABASE", "ro")]);
+        assert!(DbConnection::from_vars("read-replica", &env).is_some());
+        assert!(connection(PRIMARY).unwrap().is_none());
+        let err = connection("nowhere-configured").unwrap_err();
+        assert!(err.contains("SOLIDB_NOWHERE_CONFIGURED_HOST"), "{}", err);
+    }
+
+    #[test]
+    fn reads_after_a_write_stay_on_the_primary() {
+        reset_request();
+        let replica = Arc::new(
+            DbConnection::from_vars("replica", vars(&[("SOLIDB_REPLICA_HOST", "r:1")])).unwrap(),
+        );
+        push_connection(Some(replica.clone()));
+        assert_eq!(query_connection("INSERT {} INTO c"), Some(replica));
+        push_connection(None);
+        assert_eq!(query_connection("FOR d IN c RETURN d"), None);
+        pop_connection();
+        pop_connection();
+        assert!(!WROTE.with(Cell::get));
+
+        assert_eq!(query_connection("FOR d IN c REMOVE d IN c"), None);
+        assert!(WROTE.with(Cell::get));
+        reset_request();
+        assert!(!WROTE.with(Cell::get));
+    }
+
+    #[test]
+    fn write_keywords_are_matched_as_words() {
+        assert!(is_write_query("FOR d IN posts REMOVE d IN posts"));
+        assert!(is_write_query("update posts set x=1"));
+        assert!(is_write_query("UPSERT {a:1} INSERT {} UPDATE {} IN c"));
+        assert!(!is_write_query("FOR d IN updates RETURN d"));
+        assert!(!is_write_query("FOR d IN posts RETURN d.inserted_at"));
+    }
+}
@@ -4,6 +4,7 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 
+use crate::interpreter::builtins::datetime::helpers::clock_now_utc;
 use crate::interpreter::environment::Environment;
 use crate::interpreter::value::{Class, Function, NativeFunction, SoliStr, Value};
 
@@ -662,7 +663,7 @@ pub fn duration_to_cutoff_rfc3339(value: &str) -> Result<String, String> {
         "w" => n * 604_800,
         _ => unreachable!(),
     };
-    let cutoff = chrono::Utc::now() - chrono::Duration::seconds(seconds as i64);
+    let cutoff = clock_now_utc() - chrono::Duration::seconds(seconds as i64);
     Ok(cutoff.to_rfc3339())
 }
 
@@ -957,7 +958,7 @@ fn instance_fields_to_hash(
 /// We always allocate a fresh `Value::Hash` rather than mutating in place
 /// so the caller's original input is preserved (validation and error
 /// reporting still see the request's full shape if they want it).
-fn filter_mass_assign(class_name: &str, data: &Value) -> Value {
+pub(super) fn filter_mass_assign(class_name: &str, data: &Value) -> Value {
     use crate::interpreter::value::{HashKey, HashPairs};
     let pairs = match data {
         Value::Hash(p) => p,
@@ -3034,6 +3035,71 @@ impl Model {
             })),
         );
 
+        // Model.insert_all(rows) - Bulk insert in batched statements (see bulk.rs)
+        native_static_methods.insert(
+            "insert_all".to_string(),
+            Rc::new(NativeFunction::new("Model.insert_all", Some(2), |args| {
+                let class_name = get_class_name_from_class(&args)?;
+                super::bulk::insert_all(&class_name, args.get(1).unwrap_or(&Value::Null))
+            })),
+        );
+
+        // Model.upsert_all(rows, unique_by: "email") - Bulk insert-or-update
+        native_static_methods.insert(
+            "upsert_all".to_string(),
+            Rc::new(NativeFunction::new("Model.upsert_all", None, |args| {
+                use crate::interpreter::value::HashKey;
+                let class_name = get_class_name_from_class(&args)?;
+                let unique_by = match args.get(2) {
+                    None => vec!["_key".to_string()],
+                    Some(Value::Hash(opts)) => {
+                        match opts.borrow().get(&HashKey::String("unique_by".into())) {
+                            None => vec!["_key".to_string()],
+                            Some(Value::String(s)) | Some(Value::Symbol(s)) => vec![s.to_string()],
+                            Some(Value::Array(fields)) => fields
+                                .borrow()
+                                .iter()
+                                .map(|f| match f {
+                                    Value::String(s) | Value::Symbol(s) => Ok(s.to_string()),
+                                    other => Err(format!(
+                                        "upsert_all() unique_by: fields must be strings, got {}",
+                                        other.type_name()
+                                    )),
+                                })
+                                .collect::<Result<_, _>>()?,
+                            Some(other) => {
+                                return Err(format!(
+                                    "upsert_all() unique_by: expects a field name or an array \
+                                     of them, got {}",
+                                    other.type_name()
+                                ))
+                            }
+                        }
+                    }
+                    Some(other) => {
+                        return Err(format!(
+                            "upsert_all(rows, unique_by: ...) expects options, got {}",
+                            other.type_name()
+                        ))
+                    }
+                };
+                super::bulk::upsert_all(
+                    &class_name,
+                    args.get(1).unwrap_or(&Value::Null),
+                    &unique_by,
+                )
+            })),
+        );
+
+        // Model.update_all(where: {...}, set: {...}) - One bulk UPDATE
+        native_static_methods.insert(
+            "update_all".to_string(),
+            Rc::new(NativeFunction::new("Model.update_all", Some(2), |args| {
+                let class = get_class_rc_from_args(&args)?;
+                super::bulk::update_all(&class, args.get(1).unwrap_or(&Value::Null))
+            })),
+        );
+
         // Model.scope(name, query_fn) - Register a named scope on the model.
         //
         // In a class body the class is auto-prepended as args[0] (see
@@ -3599,8 +3665,9 @@ impl Model {
         );
 
         // Model.search("query"[, options]) — fulltext search over the fields
-        // of the declared `fulltext_index`. Options: field:, distance:,
-        // limit:, highlight:. Returns ranked instances with _search_score.
+        // of the declared `fulltext_index`. Options: field:, fields:,
+        // distance:, limit:, highlight:. Returns ranked instances with
+        // _search_score.
         native_static_methods.insert(
             "search".to_string(),
             Rc::new(NativeFunction::new("Model.search", None, |args| {
@@ -3627,9 +3694,19 @@ impl Model {
                     .first()
                     .cloned()
                     .unwrap_or_else(|| "".to_string());
+                let mut fields: Option<Vec<String>> = None;
                 let mut distance: usize = 2;
                 let mut limit: usize = 10;
                 let mut highlight = false;
+                let not_covered = |s: &str| {
+                    format!(
+                        "{}.search field '{}' is not covered by the \
+                         fulltext_index (declared: {})",
+                        class_name,
+                        s,
+                        declared.fields.join(", ")
+                    )
+                };
                 if let Some(Value::Hash(opts)) = args.get(2) {
                     use crate::interpreter::value::HashKey;
                     for (k, v) in opts.borrow().iter() {
@@ -3641,15 +3718,36 @@ impl Model {
                             ("field", Value::String(s)) => {
                                 let s = s.to_string();
                                 if !declared.fields.iter().any(|f| f == &s) {
+                                    return Err(not_covered(&s));
+                                }
+                                field = s;
+                            }
+                            ("fields", Value::Array(arr)) => {
+                                let mut names = Vec::new();
+                                for v in arr.borrow().iter() {
+                                    let s = match v {
+                                        Value::String(s) => s.to_string(),
+                                        Value::Symbol(s) => s.to_string(),
+                                        other => {
+                                            return Err(format!(
+                                                "{}.search fields: expects field names, got {}",
+                                                class_name,
+                                                other.type_name()
+                                            ))
+                                        }
+                                    };
+                                    if !declared.fields.iter().any(|f| f == &s) {
+                                        return Err(not_covered(&s));
+                                    }
+                                    names.push(s);
+                                }
+                                if names.is_empty() {
                                     return Err(format!(
-                                        "{}.search field '{}' is not covered by the \
-                                         fulltext_index (declared: {})",
-                                        class_name,
-                                        s,
-                                        declared.fields.join(", ")
+                                        "{}.search fields: must name at least one field",
+                                        class_name
                                     ));
                                 }
-                                field = s;
+                                fields = Some(names);
                             }
                             ("distance", Value::Int(n)) if *n >= 0 => distance = *n as usize,
                             ("limit", Value::Int(n)) if *n > 0 => limit = *n as usize,
@@ -3657,7 +3755,7 @@ impl Model {
                             (other, _) => {
                                 return Err(format!(
                                     "search() unknown/invalid option '{}': expected field:, \
-                                     distance:, limit:, or highlight:",
+                                     fields:, distance:, limit:, or highlight:",
                                     other
                                 ))
                             }
@@ -3665,6 +3763,19 @@ impl Model {
                     }
                 }
 
+                if let Some(fields) = fields {
+                    return super::search::exec_multi_field_search(
+                        &collection,
+                        &class,
+                        &fields,
+                        &query_text,
+                        distance,
+                        limit,
+                        highlight,
+                        is_soft_delete(&class_name),
+                    );
+                }
+
                 super::search::exec_fulltext_search(
                     &collection,
                     &class,
@@ -3931,55 +4042,87 @@ impl Model {
 
         // Model.near(lat, lon[, options]) / Model.within(lat, lon, radius) —
         // geo queries over the declared `geo_index` field. Results carry
-        // `_distance` (meters).
-        fn geo_args(
-            args: &[Value],
-            method: &str,
-        ) -> Result<(String, String, Rc<Class>, f64, f64), String> {
+        // `_distance` (meters). A `Point` (or `{lat, lon}` hash) may stand in
+        // for the `lat, lon` pair.
+        /// (collection, field, class, lat, lon, index of the next argument)
+        type GeoArgs = (String, String, Rc<Class>, f64, f64, usize);
+        fn geo_args(args: &[Value], method: &str) -> Result<GeoArgs, String> {
             let class = get_class_rc_from_args(args)?;
             let class_name = class.name.clone();
             let collection = class_name_to_collection(&class_name);
-            let geo = super::registry::get_geo_indexes(&class_name);
-            let field = geo.first().map(|g| g.field.clone()).ok_or_else(|| {
-                format!(
-                    "{}.{} requires a `geo_index` declaration in the class body, e.g. \
-                         geo_index \"location\"",
-                    class_name, method
-                )
-            })?;
+            let field = geo_field(&class_name, method)?;
+            if let Some((lat, lon)) = args
+                .get(1)
+                .and_then(crate::interpreter::builtins::geo::coordinates_of)
+            {
+                return Ok((collection, field, class, lat, lon, 2));
+            }
             let num = |v: Option<&Value>, what: &str| -> Result<f64, String> {
                 match v {
                     Some(Value::Int(n)) => Ok(*n as f64),
                     Some(Value::Float(f)) => Ok(*f),
                     _ => Err(format!(
-                        "{}.{} expects numeric {}",
+                        "{}.{} expects numeric {} (or a Point)",
                         class_name, method, what
                     )),
                 }
             };
             let lat = num(args.get(1), "lat")?;
             let lon = num(args.get(2), "lon")?;
-            Ok((collection, field, class, lat, lon))
+            Ok((collection, field, class, lat, lon, 3))
+        }
+
+        fn geo_field(class_name: &str, method: &str) -> Result<String, String> {
+            let geo = super::registry::get_geo_indexes(class_name);
+            geo.first().map(|g| g.field.clone()).ok_or_else(|| {
+                format!(
+                    "{}.{} requires a `geo_index` declaration in the class body, e.g. \
+                         geo_index \"location\"",
+                    class_name, method
+                )
+            })
+        }
+
+        // Radius in meters from a `km:` / `meters:` / `miles:` option value.
+        fn radius_meters(unit: &str, value: &Value) -> Option<f64> {
+            let n = match value {
+                Value::Int(n) if *n > 0 => *n as f64,
+                Value::Float(f) if *f > 0.0 => *f,
+                _ => return None,
+            };
+            Some(match unit {
+                "km" => n * 1000.0,
+                "miles" => n * 1609.344,
+                _ => n,
+            })
         }
 
         native_static_methods.insert(
             "near".to_string(),
             Rc::new(NativeFunction::new("Model.near", None, |args| {
                 let class_name = get_class_name_from_class(&args)?;
-                let (collection, field, class, lat, lon) = geo_args(&args, "near")?;
-                let mut limit: f64 = 10.0;
-                if let Some(Value::Hash(opts)) = args.get(3) {
+                let (collection, field, class, lat, lon, rest) = geo_args(&args, "near")?;
+                let mut limit: Option<usize> = None;
+                let mut radius: Option<f64> = None;
+                if let Some(Value::Hash(opts)) = args.get(rest) {
                     use crate::interpreter::value::HashKey;
                     for (k, v) in opts.borrow().iter() {
                         match (k, v) {
                             (HashKey::String(key), Value::Int(n))
                                 if key.as_str() == "limit" && *n > 0 =>
                             {
-                                limit = *n as f64
+                                limit = Some(*n as usize)
+                            }
+                            (HashKey::String(key), v)
+                                if matches!(key.as_str(), "km" | "meters" | "miles")
+                                    && radius_meters(key.as_str(), v).is_some() =>
+                            {
+                                radius = radius_meters(key.as_str(), v)
                             }
                             (HashKey::String(key), _) => {
                                 return Err(format!(
-                                    "near() unknown/invalid option '{}': expected limit:",
+                                    "near() unknown/invalid option '{}': expected limit:, \
+                                     km:, meters: or miles:",
                                     key
                                 ))
                             }
@@ -3987,16 +4130,46 @@ impl Model {
                         }
                     }
                 }
-                super::search::exec_geo_query(
+                // With a radius: everything inside it, nearest first, capped
+                // only by an explicit `limit:`. Without: the 10 (or `limit:`)
+                // nearest, however far.
+                let Some(radius) = radius else {
+                    return super::search::exec_geo_query(
+                        &collection,
+                        &class,
+                        &field,
+                        "near",
+                        lat,
+                        lon,
+                        ("limit", limit.unwrap_or(10) as f64),
+                        is_soft_delete(&class_name),
+                    );
+                };
+                let found = super::search::exec_geo_query(
                     &collection,
                     &class,
                     &field,
-                    "near",
+                    "within",
                     lat,
                     lon,
-                    ("limit", limit),
+                    ("radius", radius),
                     is_soft_delete(&class_name),
-                )
+                )?;
+                if let Value::Array(rows) = &found {
+                    let mut rows = rows.borrow_mut();
+                    let distance = |v: &Value| match v {
+                        Value::Instance(inst) => match inst.borrow().get("_distance") {
+                            Some(Value::Float(d)) => d,
+                            _ => f64::INFINITY,
+                        },
+                        _ => f64::INFINITY,
+                    };
+                    rows.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+                    if let Some(limit) = limit {
+                        rows.truncate(limit);
+                    }
+                }
+                Ok(found)
             })),
         );
 
@@ -4004,13 +4177,13 @@ impl Model {
             "within".to_string(),
             Rc::new(NativeFunction::new("Model.within", None, |args| {
                 let class_name = get_class_name_from_class(&args)?;
-                let (collection, field, class, lat, lon) = geo_args(&args, "within")?;
-                let radius = match args.get(3) {
+                let (collection, field, class, lat, lon, rest) = geo_args(&args, "within")?;
+                let radius = match args.get(rest) {
                     Some(Value::Int(n)) if *n > 0 => *n as f64,
                     Some(Value::Float(f)) if *f > 0.0 => *f,
                     _ => {
                         return Err(format!(
-                            "{}.within expects a radius in meters as the third argument",
+                            "{}.within expects a radius in meters after the position",
                             class_name
                         ))
                     }
@@ -4028,6 +4201,81 @@ impl Model {
             })),
         );
 
+        // Model.within_box(box) / Model.within_box(min_lat, min_lng, max_lat,
+        // max_lng) — documents whose `geo_index` position falls inside the
+        // box, e.g. the visible map viewport or a `Geo.bounding_box(...)`
+        // result. Returns a QueryBuilder, so it chains with `.where`/`.limit`.
+        native_static_methods.insert(
+            "within_box".to_string(),
+            Rc::new(NativeFunction::new("Model.within_box", None, |args| {
+                let class = get_class_rc_from_args(&args)?;
+                let class_name = class.name.clone();
+                let collection = class_name_to_collection(&class_name);
+                let field = geo_field(&class_name, "within_box")?;
+                let num = |v: Option<Value>, what: &str| -> Result<f64, String> {
+                    match v {
+                        Some(Value::Int(n)) => Ok(n as f64),
+                        Some(Value::Float(f)) => Ok(f),
+                        _ => Err(format!(
+                            "{}.within_box expects numeric {}",
+                            class_name, what
+                        )),
+                    }
+                };
+                let [min_lat, min_lng, max_lat, max_lng] = match args.get(1) {
+                    Some(Value::Hash(bbox)) => {
+                        use crate::interpreter::value::HashKey;
+                        let bbox = bbox.borrow();
+                        let get = |k: &str| bbox.get(&HashKey::String(k.into())).cloned();
+                        [
+                            num(get("min_lat"), "min_lat")?,
+                            num(get("min_lng").or_else(|| get("min_lon")), "min_lng")?,
+                            num(get("max_lat"), "max_lat")?,
+                            num(get("max_lng").or_else(|| get("max_lon")), "max_lng")?,
+                        ]
+                    }
+                    _ => [
+                        num(args.get(1).cloned(), "min_lat")?,
+                        num(args.get(2).cloned(), "min_lng")?,
+                        num(args.get(3).cloned(), "max_lat")?,
+                        num(args.get(4).cloned(), "max_lng")?,
+                    ],
+                };
+                if min_lat > max_lat {
+                    return Err(format!(
+                        "{}.within_box: min_lat ({}) is greater than max_lat ({})",
+                        class_name, min_lat, max_lat
+                    ));
+                }
+                // A box whose min_lng is east of its max_lng crosses the
+                // antimeridian: match either side of it.
+                let lng_clause = if min_lng <= max_lng {
+                    format!(
+                        "doc.{0}.lon >= @__soli_min_lng AND doc.{0}.lon <= @__soli_max_lng",
+                        field
+                    )
+                } else {
+                    format!(
+                        "(doc.{0}.lon >= @__soli_min_lng OR doc.{0}.lon <= @__soli_max_lng)",
+                        field
+                    )
+                };
+                let filter = format!(
+                    "doc.{0}.lat >= @__soli_min_lat AND doc.{0}.lat <= @__soli_max_lat AND {1}",
+                    field, lng_clause
+                );
+                let binds = HashMap::from([
+                    ("__soli_min_lat".to_string(), serde_json::json!(min_lat)),
+                    ("__soli_max_lat".to_string(), serde_json::json!(max_lat)),
+                    ("__soli_min_lng".to_string(), serde_json::json!(min_lng)),
+                    ("__soli_max_lng".to_string(), serde_json::json!(max_lng)),
+                ]);
+                let mut qb = QueryBuilder::new_with_class(class_name, collection, class);
+                qb.set_filter(filter, binds);
+                Ok(Value::QueryBuilder(Rc::new(RefCell::new(qb))))
+            })),
+        );
+
         // Model.time_bucket(interval[, aggregates]) — bucketed aggregation for
         // timeseries models. Returns a QueryBuilder (chain .all to execute).
         native_static_methods.insert(
@@ -4614,7 +4862,7 @@ impl Model {
                         instance.borrow().get("deleted_at"),
                         None | Some(Value::Null)
                     );
-                    let now = chrono::Utc::now().to_rfc3339();
+                    let now = clock_now_utc().to_rfc3339();
                     let mut map = serde_json::Map::new();
                     map.insert(
                         "deleted_at".to_string(),
@@ -4727,7 +4975,7 @@ impl Model {
                 };
                 drop(inst_ref);
 
-                let now = chrono::Utc::now().to_rfc3339();
+                let now = clock_now_utc().to_rfc3339();
                 let mut map = serde_json::Map::new();
                 map.insert(
                     "_updated_at".to_string(),
@@ -4745,6 +4993,24 @@ impl Model {
             })),
         );
 
+        // instance.cache_key() - "collection/key-updated_at", the key fragment
+        // caching derives from a model (changes on every save or touch)
+        native_methods.insert(
+            "cache_key".to_string(),
+            Rc::new(NativeFunction::new(
+                "Model#cache_key",
+                Some(0),
+                |args| match &args[0] {
+                    Value::Instance(inst) => Ok(Value::String(
+                        crate::template::fragment_cache::instance_cache_key(&inst.borrow())
+                            .unwrap_or_default()
+                            .into(),
+                    )),
+                    _ => Err("Expected instance".to_string()),
+                },
+            )),
+        );
+
         // instance.errors - Return the list of errors from last save/update
         native_methods.insert(
             "errors".to_string(),
@@ -5494,6 +5760,55 @@ pub fn register_model_builtins(env: &mut Environment) {
         })),
     );
 
+    // attribute "name", "type" — declare one typed attribute of a document
+    // model. See attributes.rs for what a declared schema turns on.
+    env.define(
+        "attribute".to_string(),
+        Value::NativeFunction(NativeFunction::new("attribute", None, |args| {
+            let class_name = get_class_name_from_class(&args)?;
+
+            let name =
+                match args.get(1) {
+                    Some(Value::String(s)) => s.to_string(),
+                    Some(Value::Symbol(s)) => s.to_string(),
+                    _ => return Err(
+                        "attribute expects a name and a type, e.g. attribute \"title\", \"string\""
+                            .to_string(),
+                    ),
+                };
+            validate_field_name(&name, "attribute")?;
+
+            let declared = match args.get(2) {
+                Some(Value::String(s)) => s.to_string(),
+                Some(Value::Symbol(s)) => s.to_string(),
+                _ => {
+                    return Err(format!(
+                        "attribute \"{}\" requires a type: one of {}",
+                        name,
+                        super::attributes::ATTRIBUTE_TYPES.join(", ")
+                    ))
+                }
+            };
+            let data_type = super::attributes::normalize_type(&declared).ok_or_else(|| {
+                format!(
+                    "attribute \"{}\": unknown type {:?} — expected one of {}",
+                    name,
+                    declared,
+                    super::attributes::ATTRIBUTE_TYPES.join(", ")
+                )
+            })?;
+
+            super::registry::add_attribute(
+                &class_name,
+                super::registry::AttributeDef {
+                    name,
+                    data_type: data_type.to_string(),
+                },
+            );
+            Ok(Value::Null)
+        })),
+    );
+
     // column "name", "type"[, nullable: true, indexed: true] — declare one
     // column of a columnar model. Types are validated against the server
     // whitelist because unknown types silently degrade to String there.
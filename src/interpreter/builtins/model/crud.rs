@@ -7,16 +7,20 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 
-use super::core::{
-    db_url, force_refresh_jwt_token, get_api_key, get_basic_auth, get_cursor_url,
-    get_database_name, get_jwt_token,
-};
+use super::connections::{self, DbConnection};
+use super::core::{force_refresh_jwt_token, get_api_key, get_basic_auth, get_jwt_token};
 #[allow(unused_imports)]
 use super::registry::{clear_model_classes, get_model_class, register_model_class};
 
-/// Apply DB authentication headers.
-/// Priority: JWT (fastest) > API key > Basic auth fallback.
-fn apply_db_auth(builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+/// Apply DB authentication headers: a named connection's own credentials,
+/// else the primary's. Priority: JWT (fastest) > API key > Basic auth fallback.
+fn apply_db_auth(
+    builder: reqwest::RequestBuilder,
+    conn: Option<&DbConnection>,
+) -> reqwest::RequestBuilder {
+    if let Some(conn) = conn.filter(|c| c.has_own_auth()) {
+        return conn.apply_auth(builder);
+    }
     if let Some(jwt) = get_jwt_token() {
         builder.header("Authorization", format!("Bearer {}", jwt))
     } else if let Some(key) = get_api_key() {
@@ -39,18 +43,45 @@ fn apply_db_auth(builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
 /// retry covers the corner cases where the token *was* still valid at
 /// request-build time but the request itself comes back unauthorised
 /// (server-side revocation, clock skew between client and DB, etc.).
+///
+/// Authenticates for the connection selected by `connected_to`, read when
+/// the future is first polled; [`send_on`] takes it explicitly.
 async fn send_with_db_auth_retry<F>(make_request: F) -> Result<reqwest::Response, reqwest::Error>
 where
     F: Fn() -> reqwest::RequestBuilder,
 {
-    let resp = apply_db_auth(make_request()).send().await?;
+    send_on(connections::selected_connection(), make_request).await
+}
+
+/// [`send_with_db_auth_retry`] for a request to `conn` (`None` = primary).
+async fn send_on<F>(
+    conn: Option<std::sync::Arc<DbConnection>>,
+    make_request: F,
+) -> Result<reqwest::Response, reqwest::Error>
+where
+    F: Fn() -> reqwest::RequestBuilder,
+{
+    let resp = apply_db_auth(make_request(), conn.as_deref())
+        .send()
+        .await?;
     if resp.status() == reqwest::StatusCode::UNAUTHORIZED {
         force_refresh_jwt_token();
-        return apply_db_auth(make_request()).send().await;
+        return apply_db_auth(make_request(), conn.as_deref()).send().await;
     }
     Ok(resp)
 }
 
+/// Database of the connection writes go to (the primary outside
+/// `connected_to`).
+fn get_database_name() -> String {
+    connections::database_name(connections::selected_connection().as_deref())
+}
+
+/// `path` on the server of the connection writes go to.
+fn db_url(path: &str) -> String {
+    connections::db_url(connections::selected_connection().as_deref(), path)
+}
+
 // Fallback tokio runtime for DB operations outside of a server context
 // (e.g., REPL, scripts). Uses a lightweight current-thread runtime instead
 // of a full multi-thread runtime to save ~1-2MB of RSS.
@@ -158,6 +189,7 @@ pub fn clear_current_tx() {
 
 /// Begin a new transaction.
 pub fn begin_transaction(isolation_level: Option<&str>) -> Result<String, String> {
+    connections::note_write();
     let host = super::core::DB_CONFIG.host.clone();
     let database = get_database_name().to_string();
     // SEC-027: use the configured scheme; was forcing http:// regardless.
@@ -375,11 +407,27 @@ pub fn json_doc_to_instance(class: &Rc<Class>, json: &serde_json::Value) -> Valu
             instance.set(field, enum_value);
         }
     }
+    // Hydrate declared `point` attributes: the stored `{ lat, lon }` hash
+    // comes back as a Point.
+    for attribute in super::registry::get_attributes(&target_class.name) {
+        if attribute.data_type != "point" {
+            continue;
+        }
+        if let Some((lat, lon)) = instance
+            .get(&attribute.name)
+            .as_ref()
+            .and_then(crate::interpreter::builtins::geo::coordinates_of)
+        {
+            if let Ok(point) = crate::interpreter::builtins::geo::point_value(lat, lon, "Point") {
+                instance.set(attribute.name, point);
+            }
+        }
+    }
     // Dirty-tracking baseline: what this record looked like in the database.
     if target_class.is_model_subclass() {
         super::dirty::seed_snapshot(&mut instance);
     }
-    Value::Instance(Rc::new(RefCell::new(instance)))
+    Value::Instance(crate::interpreter::cycles::new_instance(instance))
 }
 
 /// Resolve the correct class for a JSON document based on its `type`
@@ -451,6 +499,103 @@ pub fn exec_auto_collection_as_instances_with_binds(
     }
 }
 
+/// POST `sdbql` to the cursor endpoint and return its result rows.
+async fn cursor_query(
+    conn: Option<std::sync::Arc<DbConnection>>,
+    url: String,
+    client: reqwest::Client,
+    sdbql: String,
+    bind_vars: Option<HashMap<String, serde_json::Value>>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let mut payload = serde_json::json!({ "query": sdbql });
+    if let Some(bv) = bind_vars {
+        payload["bindVars"] = serde_json::json!(bv);
+    }
+    let body_str = payload.to_string();
+
+    let resp = send_on(conn, || {
+        client
+            .post(&url)
+            .header("Content-Type", "application/json")
+            .body(body_str.clone())
+    })
+    .await
+    .map_err(|e| format!("HTTP error: {}", e))?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = crate::interpreter::builtins::http_class::read_capped_text_async(resp)
+            .await
+            .unwrap_or_default();
+        return Err(format!("Query failed: {} - {}", status, body));
+    }
+
+    let json: serde_json::Value = resp
+        .json()
+        .await
+        .map_err(|e| format!("JSON error: {}", e))?;
+    Ok(json
+        .get("result")
+        .and_then(|r| r.as_array())
+        .cloned()
+        .unwrap_or_default())
+}
+
+/// Like [`exec_async_query_with_binds`], but returns a Future instead of
+/// blocking: the query runs on the server's tokio runtime (or its own thread
+/// outside a server) and resolves to the rows, or to the same `"Error: ..."`
+/// string the blocking path returns. Used inside async tasks, where the
+/// scheduler keeps running other tasks while the query is in flight.
+#[allow(clippy::arc_with_non_send_sync)]
+pub fn spawn_query_with_binds(
+    sdbql: String,
+    bind_vars: Option<HashMap<String, serde_json::Value>>,
+) -> Value {
+    use crate::interpreter::value::{FutureState, HttpFutureKind};
+    use std::sync::{mpsc, Arc, Mutex};
+
+    let conn = connections::query_connection(&sdbql);
+    let query = cursor_query(
+        conn.clone(),
+        connections::cursor_url(conn.as_deref()),
+        get_http_client().clone(),
+        sdbql,
+        bind_vars,
+    );
+    let job = async move {
+        let started = std::time::Instant::now();
+        let rows = match query.await {
+            Ok(rows) => serde_json::Value::Array(rows),
+            Err(e) => serde_json::Value::String(format!("Error: {}", e)),
+        };
+        crate::metrics::Metrics::global().record_db_queries(started.elapsed());
+        serde_json::to_string(&rows).map_err(|e| e.to_string())
+    };
+
+    let (tx, rx) = mpsc::channel();
+    match get_tokio_handle() {
+        Some(rt) => {
+            rt.spawn(async move {
+                let _ = tx.send(job.await);
+            });
+        }
+        None => {
+            std::thread::spawn(move || {
+                let result = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(|e| format!("Failed to build tokio runtime: {}", e))
+                    .and_then(|rt| rt.block_on(job));
+                let _ = tx.send(result);
+            });
+        }
+    }
+    Value::Future(Arc::new(Mutex::new(FutureState::Pending {
+        receiver: rx,
+        kind: HttpFutureKind::Json,
+    })))
+}
+
 /// Fast async query execution - uses server's tokio runtime.
 /// Uses same HTTP client as HTTP.request for consistency.
 pub fn exec_async_query_with_binds(
@@ -458,7 +603,8 @@ pub fn exec_async_query_with_binds(
     bind_vars: Option<HashMap<String, serde_json::Value>>,
 ) -> Result<Vec<serde_json::Value>, String> {
     // Get cached values (initialized on first use after .env is loaded)
-    let url = get_cursor_url();
+    let conn = connections::query_connection(&sdbql);
+    let url = connections::cursor_url(conn.as_deref());
 
     let client = get_http_client().clone();
 
@@ -476,42 +622,7 @@ pub fn exec_async_query_with_binds(
         None
     };
 
-    let future = async move {
-        let mut payload = serde_json::json!({ "query": sdbql });
-        if let Some(bv) = bind_vars {
-            payload["bindVars"] = serde_json::json!(bv);
-        }
-        let body_str = payload.to_string();
-
-        let resp = send_with_db_auth_retry(|| {
-            client
-                .post(&url)
-                .header("Content-Type", "application/json")
-                .body(body_str.clone())
-        })
-        .await
-        .map_err(|e| format!("HTTP error: {}", e))?;
-
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let body = crate::interpreter::builtins::http_class::read_capped_text_async(resp)
-                .await
-                .unwrap_or_default();
-            return Err(format!("Query failed: {} - {}", status, body));
-        }
-
-        let json: serde_json::Value = resp
-            .json()
-            .await
-            .map_err(|e| format!("JSON error: {}", e))?;
-        Ok(json
-            .get("result")
-            .and_then(|r| r.as_array())
-            .cloned()
-            .unwrap_or_default())
-    };
-
-    let result = run_db_future(future);
+    let result = run_db_future(cursor_query(conn, url, client, sdbql, bind_vars));
 
     let db_duration = if let (Some(q), Some(t0)) = (log_query, started) {
         let elapsed = t0.elapsed().as_secs_f64() * 1000.0;
@@ -553,7 +664,8 @@ pub fn exec_async_query(sdbql: String) -> Value {
 /// Uses same HTTP client as HTTP.request for consistency.
 pub fn exec_async_query_raw(sdbql: String) -> Value {
     // Get cached values (initialized on first use after .env is loaded)
-    let url = get_cursor_url();
+    let conn = connections::query_connection(&sdbql);
+    let url = connections::cursor_url(conn.as_deref());
     // SEC-036: build the JSON body with serde_json so the SDBQL value is
     // escaped correctly. The previous `format!` used a `r#"\"#` replacement
     // (a single backslash), which produced malformed JSON for any quoted
@@ -577,7 +689,7 @@ pub fn exec_async_query_raw(sdbql: String) -> Value {
 
     let client = get_http_client().clone();
     let result = match run_db_future(async move {
-        let resp = send_with_db_auth_retry(|| {
+        let resp = send_on(conn, || {
             client
                 .post(&url)
                 .header("Content-Type", "application/json")
@@ -1057,6 +1169,9 @@ fn exec_document_request_with_headers(
         extra_headers.iter().map(|(k, v)| (*k, v.clone())).collect();
 
     let body_str = body.as_ref().map(|b| b.to_string());
+    if method != reqwest::Method::GET {
+        connections::note_write();
+    }
 
     run_db_future(async move {
         let resp = send_with_db_auth_retry(|| {
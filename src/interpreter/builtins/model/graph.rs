@@ -374,9 +374,8 @@ pub fn parse_traverse_options(args: &[Value]) -> Result<TraverseSpec, String> {
                                 )),
                             })
                             .collect::<Result<_, _>>()?;
-                        // A Soli range literal (1..3) materializes to an
-                        // exclusive-end array [1, 2]; min/max keeps both the
-                        // [min, max] pair form and range form sensible.
+                        // min/max keeps both the [min, max] pair form and
+                        // a spelled-out list of depths sensible.
                         let (min, max) = match (ints.iter().min(), ints.iter().max()) {
                             (Some(&min), Some(&max)) => (min, max),
                             _ => return Err("traverse() depth array is empty".to_string()),
@@ -387,6 +386,18 @@ pub fn parse_traverse_options(args: &[Value]) -> Result<TraverseSpec, String> {
                         min_depth = min as usize;
                         max_depth = max as usize;
                     }
+                    // `depth: 1..=3` — the range's first and last values.
+                    Value::Range(range) => {
+                        let (min, max) = match (range.get(0), range.get(-1)) {
+                            (Some(min), Some(max)) => (min, max),
+                            _ => return Err("traverse() depth range is empty".to_string()),
+                        };
+                        if min < 1 {
+                            return Err("traverse() depth must be >= 1".to_string());
+                        }
+                        min_depth = min as usize;
+                        max_depth = max as usize;
+                    }
                     other => {
                         return Err(format!(
                             "traverse() depth must be an Int or [min, max] array, got {}",
@@ -598,6 +609,12 @@ mod tests {
         assert_eq!((spec.min_depth, spec.max_depth), (2, 3));
         assert_eq!(spec.direction, TraversalDirection::Any);
 
+        // depth: 2..=4 range.
+        let range = crate::interpreter::value::RangeValue::new(2, 4, true);
+        let opts = hash(&[("depth", Value::Range(Rc::new(range)))]);
+        let spec = parse_traverse_options(&[Value::String("follows".into()), opts]).unwrap();
+        assert_eq!((spec.min_depth, spec.max_depth), (2, 4));
+
         // Unknown option errors.
         let opts = hash(&[("dir", Value::String("in".into()))]);
         assert!(parse_traverse_options(&[Value::String("follows".into()), opts]).is_err());
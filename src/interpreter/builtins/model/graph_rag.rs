@@ -230,6 +230,17 @@ pub fn parse_graph_rag_options(
                 min_depth = min as usize;
                 max_depth = max as usize;
             }
+            ("depth", Value::Range(range)) => {
+                let (min, max) = match (range.get(0), range.get(-1)) {
+                    (Some(min), Some(max)) => (min, max),
+                    _ => return Err("graph_rag() depth range is empty".to_string()),
+                };
+                if min < 1 {
+                    return Err("graph_rag() depth must be >= 1".to_string());
+                }
+                min_depth = min as usize;
+                max_depth = max as usize;
+            }
             ("field", Value::String(s)) => field = s.to_string(),
             ("seed_k", Value::Int(n)) if *n > 0 => seed_k = *n as usize,
             ("limit", Value::Int(n)) if *n > 0 => limit = *n as usize,
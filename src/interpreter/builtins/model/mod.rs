@@ -268,9 +268,12 @@
 //! string form `Model.transaction("AQL")` and the manual handle form
 //! `Model.transaction()` remain native calls.
 
+pub mod attributes;
 pub mod batch;
+pub mod bulk;
 pub mod callbacks;
 pub mod columnar;
+pub mod connections;
 pub mod core;
 pub mod counter_cache;
 pub mod crud;
@@ -294,13 +297,13 @@ pub mod validation;
 
 pub use callbacks::{register_callback, ModelCallbacks};
 pub use core::{
-    build_safe_filter_from_hash, class_name_to_collection, duration_to_cutoff_rfc3339,
-    ensure_scalar_bind_value, ensure_string_form_bind_value, get_model_engine_context,
-    get_or_create_metadata, get_translated_fields, init_db_config, init_jwt_token,
-    is_translated_field, register_model_builtins, register_translation, set_model_engine_context,
-    timeseries_insert_only_error, update_metadata, validate_field_name, validate_order_direction,
-    validate_retention_duration, EngineContextGuard, Model, ModelMetadata, DB_CONFIG,
-    MODEL_REGISTRY,
+    build_safe_filter_from_hash, class_name_to_collection, class_name_to_collection_with_engine,
+    duration_to_cutoff_rfc3339, ensure_scalar_bind_value, ensure_string_form_bind_value,
+    get_model_engine_context, get_or_create_metadata, get_translated_fields, init_db_config,
+    init_jwt_token, is_translated_field, register_model_builtins, register_translation,
+    set_model_engine_context, timeseries_insert_only_error, update_metadata, validate_field_name,
+    validate_order_direction, validate_retention_duration, EngineContextGuard, Model,
+    ModelMetadata, DB_CONFIG, MODEL_REGISTRY,
 };
 pub use crud::{
     exec_async_query, exec_async_query_raw, exec_async_query_with_binds, exec_auto_collection,
@@ -315,10 +318,11 @@ pub use query::{
     ThroughClause, TimeBucketSpec, THROUGH_FK_BIND,
 };
 pub use registry::{
-    clear_all_model_registries, clear_model_classes, get_collection_type, get_columnar_schema,
-    get_edge_spec, get_model_class, get_timeseries_spec, is_columnar_model, is_edge_model,
-    is_soft_delete, is_timeseries_model, register_collection_type, register_model_class,
-    ColumnarColumnDef, ColumnarSchemaDef, EdgeSpec, TimeseriesSpec,
+    clear_all_model_registries, clear_model_classes, get_attribute_type, get_attributes,
+    get_collection_type, get_columnar_schema, get_edge_spec, get_model_class, get_timeseries_spec,
+    is_columnar_model, is_edge_model, is_soft_delete, is_timeseries_model,
+    register_collection_type, register_model_class, AttributeDef, ColumnarColumnDef,
+    ColumnarSchemaDef, EdgeSpec, TimeseriesSpec,
 };
 pub use relations::{
     build_relation, classify, get_relation, get_relations, parse_relation_options,
@@ -51,6 +51,14 @@ pub struct ColumnarSchemaDef {
     pub compression: Option<String>,
 }
 
+/// One `attribute "name", "type"` declaration — the model's typed schema.
+#[derive(Debug, Clone)]
+pub struct AttributeDef {
+    pub name: String,
+    /// Normalized type name (string/int/float/bool/array/hash/datetime/point/any).
+    pub data_type: String,
+}
+
 /// A `vector_index "field", dimension: N` declaration (HNSW ANN index).
 #[derive(Debug, Clone)]
 pub struct VectorIndexDef {
@@ -111,6 +119,8 @@ pub struct ModelMetadata {
     pub timeseries: Option<TimeseriesSpec>,
     /// Set by the `columnar`/`column` class-body DSL.
     pub columnar: Option<ColumnarSchemaDef>,
+    /// Set by the `attribute` class-body DSL. Empty = schemaless model.
+    pub attributes: Vec<AttributeDef>,
     /// Set by the `vector_index` class-body DSL.
     pub vector_indexes: Vec<VectorIndexDef>,
     /// Set by the `fulltext_index` class-body DSL.
@@ -457,6 +467,33 @@ pub fn get_columnar_schema(class_name: &str) -> Option<ColumnarSchemaDef> {
     registry.get(class_name).and_then(|m| m.columnar.clone())
 }
 
+pub fn add_attribute(class_name: &str, def: AttributeDef) {
+    let mut registry = MODEL_REGISTRY.write().unwrap();
+    let metadata = registry.entry(class_name.to_string()).or_default();
+    metadata.attributes.retain(|d| d.name != def.name);
+    metadata.attributes.push(def);
+}
+
+pub fn get_attributes(class_name: &str) -> Vec<AttributeDef> {
+    let registry = MODEL_REGISTRY.read().unwrap();
+    registry
+        .get(class_name)
+        .map(|m| m.attributes.clone())
+        .unwrap_or_default()
+}
+
+/// Declared type of `field` on `class_name`, or `None` when the model doesn't
+/// declare it (or declares no schema at all).
+pub fn get_attribute_type(class_name: &str, field: &str) -> Option<String> {
+    let registry = MODEL_REGISTRY.read().unwrap();
+    registry.get(class_name).and_then(|m| {
+        m.attributes
+            .iter()
+            .find(|a| a.name == field)
+            .map(|a| a.data_type.clone())
+    })
+}
+
 pub fn is_timeseries_model(class_name: &str) -> bool {
     let registry = MODEL_REGISTRY.read().unwrap();
     registry
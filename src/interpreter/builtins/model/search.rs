@@ -68,6 +68,45 @@ pub fn exec_fulltext_search(
     highlight: bool,
     drop_soft_deleted: bool,
 ) -> Result<Value, String> {
+    let rows = fulltext_rows(collection, field, query_text, distance, limit, highlight)?;
+    Ok(rows_to_instances(class, &rows, drop_soft_deleted))
+}
+
+/// Fulltext search across several indexed fields (`search(q, fields: [...])`).
+/// Each field is queried on its own; a document hit in more than one field
+/// has its scores summed, so multi-field matches rank first. `_highlighted`
+/// comes from the field that scored the document highest.
+#[allow(clippy::too_many_arguments)]
+pub fn exec_multi_field_search(
+    collection: &str,
+    class: &Rc<Class>,
+    fields: &[String],
+    query_text: &str,
+    distance: usize,
+    limit: usize,
+    highlight: bool,
+    drop_soft_deleted: bool,
+) -> Result<Value, String> {
+    let mut per_field = Vec::with_capacity(fields.len());
+    for field in fields {
+        per_field.push(fulltext_rows(
+            collection, field, query_text, distance, limit, highlight,
+        )?);
+    }
+    let mut rows = merge_ranked(per_field);
+    rows.retain(|doc| !(drop_soft_deleted && is_soft_deleted(doc)));
+    rows.truncate(limit);
+    Ok(rows_to_instances(class, &rows, false))
+}
+
+fn fulltext_rows(
+    collection: &str,
+    field: &str,
+    query_text: &str,
+    distance: usize,
+    limit: usize,
+    highlight: bool,
+) -> Result<Vec<serde_json::Value>, String> {
     let return_expr = if highlight {
         format!(
             "MERGE(r.doc, {{_search_score: r.score, _highlighted: HIGHLIGHT(r.doc.{}, [@__soli_q])}})",
@@ -89,17 +128,65 @@ pub fn exec_fulltext_search(
         serde_json::Value::String(query_text.to_string()),
     );
 
-    let rows = super::crud::exec_with_auto_collection(sdbql, Some(binds), collection)?;
+    super::crud::exec_with_auto_collection(sdbql, Some(binds), collection)
+}
+
+/// Merge per-field hit lists by `_key`, summing `_search_score`, and order
+/// the result by descending score. Ties keep first-seen order.
+fn merge_ranked(per_field: Vec<Vec<serde_json::Value>>) -> Vec<serde_json::Value> {
+    fn score(doc: &serde_json::Value) -> f64 {
+        doc.get("_search_score")
+            .and_then(|s| s.as_f64())
+            .unwrap_or(0.0)
+    }
+
+    let mut merged: Vec<serde_json::Value> = Vec::new();
+    let mut positions: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    for rows in per_field {
+        for doc in rows {
+            let key = match doc.get("_key").and_then(|k| k.as_str()) {
+                Some(k) => k.to_string(),
+                None => {
+                    merged.push(doc);
+                    continue;
+                }
+            };
+            match positions.get(&key) {
+                Some(&i) => {
+                    let total = score(&merged[i]) + score(&doc);
+                    if score(&doc) > score(&merged[i]) {
+                        merged[i] = doc;
+                    }
+                    merged[i]["_search_score"] = serde_json::json!(total);
+                }
+                None => {
+                    positions.insert(key, merged.len());
+                    merged.push(doc);
+                }
+            }
+        }
+    }
+    merged.sort_by(|a, b| score(b).total_cmp(&score(a)));
+    merged
+}
+
+fn is_soft_deleted(doc: &serde_json::Value) -> bool {
+    doc.get("deleted_at").map(|v| !v.is_null()).unwrap_or(false)
+}
+
+fn rows_to_instances(
+    class: &Rc<Class>,
+    rows: &[serde_json::Value],
+    drop_soft_deleted: bool,
+) -> Value {
     let instances: Vec<Value> = rows
         .iter()
-        .filter(|doc| {
-            // FULLTEXT bypasses the FILTER pipeline, so soft-deleted rows are
-            // dropped client-side for soft-delete models.
-            !(drop_soft_deleted && doc.get("deleted_at").map(|v| !v.is_null()).unwrap_or(false))
-        })
+        // FULLTEXT bypasses the FILTER pipeline, so soft-deleted rows are
+        // dropped client-side for soft-delete models.
+        .filter(|doc| !(drop_soft_deleted && is_soft_deleted(doc)))
         .map(|doc| json_doc_to_instance(class, doc))
         .collect();
-    Ok(Value::Array(Rc::new(RefCell::new(instances))))
+    Value::Array(Rc::new(RefCell::new(instances)))
 }
 
 /// POST /_api/database/{db}/hybrid/{coll}/search — combined vector +
@@ -346,3 +433,26 @@ pub fn exec_rag(
     );
     Ok(Value::Hash(Rc::new(RefCell::new(result))))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn multi_field_hits_sum_scores_and_rank_by_relevance() {
+        let title = vec![
+            json!({"_key": "a", "_search_score": 2.0, "_highlighted": "title-a"}),
+            json!({"_key": "b", "_search_score": 1.0}),
+        ];
+        let body = vec![
+            json!({"_key": "b", "_search_score": 3.0, "_highlighted": "body-b"}),
+            json!({"_key": "c", "_search_score": 0.5}),
+        ];
+        let merged = merge_ranked(vec![title, body]);
+        let keys: Vec<&str> = merged.iter().map(|d| d["_key"].as_str().unwrap()).collect();
+        assert_eq!(keys, vec!["b", "a", "c"]);
+        assert_eq!(merged[0]["_search_score"], json!(4.0));
+        assert_eq!(merged[0]["_highlighted"], json!("body-b"));
+    }
+}
@@ -661,6 +661,18 @@ pub fn run_validations(
         }
     }
 
+    // Declared attribute types (`attribute "views", "int"`).
+    for attr in super::registry::get_attributes(class_name) {
+        if let Some(value) = lookup_field(data, &attr.name) {
+            if !super::attributes::value_matches(&attr.data_type, &value) {
+                errors.push(ValidationError::new(
+                    &attr.name,
+                    format!("must be of type {}", attr.data_type),
+                ));
+            }
+        }
+    }
+
     // Closure-based custom validators registered via `register_custom_validator`.
     let custom = custom_validators_for(class_name);
     for v in &custom {
@@ -743,6 +755,30 @@ mod tests {
         assert_eq!(errs[0].field, "email");
     }
 
+    #[test]
+    fn declared_attribute_types_are_validated() {
+        let class = "TestTypedAttrs__validation";
+        super::super::registry::add_attribute(
+            class,
+            super::super::registry::AttributeDef {
+                name: "views".to_string(),
+                data_type: "int".to_string(),
+            },
+        );
+        let mut pairs = HashPairs::default();
+        pairs.insert(HashKey::String("views".into()), Value::String("ten".into()));
+        let data = Value::Hash(Rc::new(RefCell::new(pairs)));
+        let errs = run_validations(class, &data, None).unwrap();
+        assert_eq!(errs.len(), 1);
+        assert_eq!(errs[0].field, "views");
+        assert_eq!(errs[0].message, "must be of type int");
+
+        // Missing and null values are presence's business, not the type's.
+        assert!(run_validations(class, &empty_hash(), None)
+            .unwrap()
+            .is_empty());
+    }
+
     fn empty_hash() -> Value {
         Value::Hash(Rc::new(RefCell::new(HashPairs::default())))
     }
@@ -9,6 +9,7 @@ use crate::interpreter::environment::Environment;
 use crate::interpreter::value::{HashKey, HashPairs, NativeFunction, Value};
 
 use super::test_server::get_test_server_port;
+use super::upload_fixtures::MultipartBody;
 
 /// `--fail-on-n1` guard. Armed process-wide by the test runner (see
 /// `cli::commands::test_runner::run_test`); every request spec's response is
@@ -296,21 +297,35 @@ fn http_request(
             }
         }
     }
+    // A frozen clock follows the request into the server process, so session
+    // expiry and anything else the handler times sees the spec's `travel()`.
+    if let Some(now) = super::datetime::helpers::frozen_datetime() {
+        all_headers.insert(
+            super::test_server::FROZEN_NOW_HEADER.to_string(),
+            now.to_string(),
+        );
+    }
     let cookies = COOKIES.with(|cell| cell.borrow().clone());
 
+    // Data holding a `fixture_file_upload(...)`, or wrapped in
+    // `multipart(...)`, goes out as multipart/form-data.
+    let multipart = match &body {
+        Some(body_val) => MultipartBody::from_value(body_val)?,
+        None => None,
+    };
     let body_str = match body {
+        _ if multipart.is_some() => None,
         None | Some(Value::Null) => None,
         Some(body_val) => Some(value_to_string(body_val)?),
     };
+    let request_body = match (&multipart, &body_str) {
+        (Some(multipart), _) => Some(RequestBody::Multipart(multipart)),
+        (None, Some(text)) => Some(RequestBody::Text(text)),
+        (None, None) => None,
+    };
 
-    let (status, response_headers, set_cookies, body_text) = raw_http_request(
-        port,
-        method,
-        path,
-        &all_headers,
-        &cookies,
-        body_str.as_deref(),
-    )?;
+    let (status, response_headers, set_cookies, body_text) =
+        raw_http_request(port, method, path, &all_headers, &cookies, request_body)?;
 
     // Update the thread-local cookie jar with any Set-Cookie values so the
     // next request carries them (session continuity across get/post calls).
@@ -351,6 +366,11 @@ fn http_request(
     let mut test_query_count: Option<i64> = None;
     let mut test_n1: Option<Value> = None;
 
+    let content_type = response_headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.clone());
+
     let mut header_pairs: HashPairs = HashPairs::default();
     for (name, value) in response_headers {
         if name.eq_ignore_ascii_case("x-soli-test-view-path") {
@@ -434,6 +454,19 @@ fn http_request(
         }
     }
 
+    // `--contract`: fail the spec when the response drifts from the app's
+    // OpenAPI document (status, content type, JSON body shape).
+    if super::api_contract::contract_mode_enabled() {
+        super::api_contract::check_response(
+            port,
+            method,
+            path,
+            status,
+            content_type.as_deref(),
+            &body_text,
+        )?;
+    }
+
     Ok(response)
 }
 
@@ -490,14 +523,23 @@ fn value_to_json(value: &Value) -> String {
     }
 }
 
+/// What a test request carries after its headers.
+pub(crate) enum RequestBody<'a> {
+    /// Sent as-is, as JSON unless the headers name another content type.
+    Text(&'a str),
+    /// Sent with `Expect: 100-continue`, so a server that rejects the
+    /// upload up front (413, 415) answers before any file is streamed.
+    Multipart(&'a MultipartBody),
+}
+
 #[allow(clippy::type_complexity)]
-fn raw_http_request(
+pub(crate) fn raw_http_request(
     port: u16,
     method: &str,
     path: &str,
     all_headers: &HashMap<String, String>,
     cookies: &str,
-    body: Option<&str>,
+    body: Option<RequestBody>,
 ) -> Result<(u16, HashMap<String, String>, Vec<String>, String), String> {
     use std::io::{Read, Write};
     use std::net::TcpStream;
@@ -523,8 +565,15 @@ fn raw_http_request(
     let has_ct = all_headers
         .keys()
         .any(|k| k.eq_ignore_ascii_case("content-type"));
-    if body.is_some() && !has_ct {
-        req_headers.push_str("Content-Type: application/json\r\n");
+    let (default_ct, content_length) = match &body {
+        None => (None, 0),
+        Some(RequestBody::Text(text)) => (Some("application/json".to_string()), text.len() as u64),
+        Some(RequestBody::Multipart(multipart)) => {
+            (Some(multipart.content_type()), multipart.content_length()?)
+        }
+    };
+    if let Some(content_type) = default_ct.filter(|_| !has_ct) {
+        req_headers.push_str(&format!("Content-Type: {}\r\n", content_type));
     }
     for (name, value) in all_headers {
         req_headers.push_str(&format!("{}: {}\r\n", name, value));
@@ -532,22 +581,50 @@ fn raw_http_request(
     if !cookies.is_empty() {
         req_headers.push_str(&format!("Cookie: {}\r\n", cookies));
     }
-    let body_bytes = body.unwrap_or("");
-    req_headers.push_str(&format!("Content-Length: {}\r\n", body_bytes.len()));
+    if matches!(body, Some(RequestBody::Multipart(_))) {
+        req_headers.push_str("Expect: 100-continue\r\n");
+    }
+    req_headers.push_str(&format!("Content-Length: {}\r\n", content_length));
     req_headers.push_str("\r\n");
 
     stream
         .write_all(req_headers.as_bytes())
         .map_err(|e| format!("write headers failed: {}", e))?;
-    if !body_bytes.is_empty() {
-        stream
-            .write_all(body_bytes.as_bytes())
-            .map_err(|e| format!("write body failed: {}", e))?;
-    }
-    stream.flush().map_err(|e| format!("flush failed: {}", e))?;
 
     let mut raw = Vec::with_capacity(4096);
     let mut buf = [0u8; 8192];
+    match body {
+        Some(RequestBody::Text(text)) if !text.is_empty() => {
+            stream
+                .write_all(text.as_bytes())
+                .map_err(|e| format!("write body failed: {}", e))?;
+        }
+        Some(RequestBody::Multipart(multipart)) => {
+            // Wait for the interim `100 Continue`; anything else is the
+            // final response and the files are never sent.
+            let interim_end = loop {
+                if let Some(end) = raw.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break end + 4;
+                }
+                match stream.read(&mut buf) {
+                    Ok(0) => return Err("connection closed before a response".to_string()),
+                    Ok(n) => raw.extend_from_slice(&buf[..n]),
+                    Err(e) => return Err(format!("read response failed: {}", e)),
+                }
+            };
+            if raw.starts_with(b"HTTP/1.1 100") {
+                raw.drain(..interim_end);
+                let mut writer = std::io::BufWriter::new(&mut stream);
+                multipart
+                    .write_to(&mut writer)
+                    .and_then(|_| writer.flush())
+                    .map_err(|e| format!("write body failed: {}", e))?;
+            }
+        }
+        _ => {}
+    }
+    stream.flush().map_err(|e| format!("flush failed: {}", e))?;
+
     loop {
         match stream.read(&mut buf) {
             Ok(0) => break,
@@ -0,0 +1,99 @@
+//! Random source shared by the randomness builtins.
+//!
+//! `Math.random`, `Array#sample` and `Array#shuffle` draw from here rather
+//! than calling `rand::thread_rng()` directly, so `soli test --seed N` can make
+//! them reproducible. Without a seed every call goes straight to the OS-seeded
+//! thread RNG, exactly as before. With one, each thread owns a `StdRng` that
+//! the test runner reseeds per file and per test from `(seed, name…)`, which
+//! makes a test's random values depend on the seed and its own name only —
+//! not on which tests happened to run before it on the same worker.
+//!
+//! Crypto helpers (`random_hex`, `random_token`, key generation, ...) keep
+//! using `OsRng`: a seed must never make a token predictable.
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+static SEEDED: AtomicBool = AtomicBool::new(false);
+static SEED: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    static SEEDED_RNG: RefCell<Option<StdRng>> = const { RefCell::new(None) };
+}
+
+/// Arm seeded randomness for this (test-runner) process.
+pub fn set_test_seed(seed: u64) {
+    SEED.store(seed, Ordering::SeqCst);
+    SEEDED.store(true, Ordering::SeqCst);
+}
+
+/// The `--seed` this process runs with, if any.
+pub fn test_seed() -> Option<u64> {
+    if SEEDED.load(Ordering::Relaxed) {
+        Some(SEED.load(Ordering::Relaxed))
+    } else {
+        None
+    }
+}
+
+/// Mix `parts` into `seed` with FNV-1a. Stable across platforms and releases,
+/// unlike `std`'s `DefaultHasher`, so a printed seed reproduces the same run
+/// on any machine.
+pub fn derive_seed(seed: u64, parts: &[&str]) -> u64 {
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325 ^ seed;
+    for part in parts {
+        for byte in part.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+        // Separator, so ["ab", "c"] and ["a", "bc"] differ.
+        hash ^= 0xff;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// A fresh `StdRng` for `(test seed, parts)`, or `None` outside a seeded run.
+pub fn derived_rng(parts: &[&str]) -> Option<StdRng> {
+    test_seed().map(|seed| StdRng::seed_from_u64(derive_seed(seed, parts)))
+}
+
+/// Reseed this thread's random source from `(test seed, parts)`. No-op
+/// outside a seeded run.
+pub fn reseed(parts: &[&str]) {
+    if let Some(rng) = derived_rng(parts) {
+        SEEDED_RNG.with(|cell| *cell.borrow_mut() = Some(rng));
+    }
+}
+
+/// Run `f` with the current random source: the thread's seeded RNG during a
+/// seeded run, the OS-seeded thread RNG otherwise.
+pub fn with_rng<R>(f: impl FnOnce(&mut dyn RngCore) -> R) -> R {
+    if let Some(seed) = test_seed() {
+        return SEEDED_RNG.with(|cell| {
+            let mut slot = cell.borrow_mut();
+            // A thread that never went through `reseed` (a helper thread, a
+            // file's top-level code before its first test) still gets a
+            // deterministic stream rather than silently falling back.
+            let rng = slot.get_or_insert_with(|| StdRng::seed_from_u64(seed));
+            f(rng)
+        });
+    }
+    f(&mut rand::thread_rng())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_seed_is_stable_and_separates_parts() {
+        assert_eq!(derive_seed(42, &["a", "b"]), derive_seed(42, &["a", "b"]));
+        assert_ne!(derive_seed(42, &["a", "b"]), derive_seed(43, &["a", "b"]));
+        assert_ne!(derive_seed(42, &["ab", "c"]), derive_seed(42, &["a", "bc"]));
+    }
+}
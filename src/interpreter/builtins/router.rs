@@ -19,7 +19,7 @@ use std::collections::HashMap;
 /// Everything else falls through unchanged. See `www/docs/routing.md`
 /// (Plural-to-singular limitations) for the documented edge cases that
 /// require choosing a different resource name (`news`, `species`, ...).
-fn singularize(name: &str) -> String {
+pub(crate) fn singularize(name: &str) -> String {
     // Irregulars table: lowercase plural → lowercase singular. Matched on a
     // lowercased copy of `name` so `People` and `people` both work, while
     // preserving any user-chosen casing on the result is not attempted
@@ -420,15 +420,16 @@ pub fn register_router_builtins(env: &mut Environment) {
         })),
     );
 
-    // router_match(method, path, action, name?)
+    // router_match(method, path, action, name?, returns?)
     // Variadic so the DSL can pass a 4th `name` arg for `*_path` / `*_url`
-    // helper generation while older callers continue to pass 3.
+    // helper generation and a 5th `returns` response shape while older
+    // callers continue to pass 3.
     env.define(
         "router_match".to_string(),
         Value::NativeFunction(NativeFunction::new("router_match", None, |args| {
-            if args.len() < 3 || args.len() > 4 {
+            if args.len() < 3 || args.len() > 5 {
                 return Err(format!(
-                    "router_match expects 3 or 4 arguments, got {}",
+                    "router_match expects 3 to 5 arguments, got {}",
                     args.len()
                 ));
             }
@@ -439,6 +440,9 @@ pub fn register_router_builtins(env: &mut Environment) {
                 Some(Value::String(s)) if !s.is_empty() => Some(s.clone()),
                 _ => None,
             };
+            if let Some(returns) = args.get(4).filter(|v| !matches!(v, Value::Null)) {
+                crate::serve::response_shape::set_route_shape(&method, &action, returns)?;
+            }
 
             ROUTER_CONTEXT.with(|ctx| {
                 let stack = ctx.borrow();
@@ -478,6 +482,24 @@ pub fn register_router_builtins(env: &mut Environment) {
         })),
     );
 
+    // router_shape(name, fields)
+    env.define(
+        "router_shape".to_string(),
+        Value::NativeFunction(NativeFunction::new("router_shape", Some(2), |args| {
+            let name = match &args[0] {
+                Value::String(s) if !s.is_empty() => s.to_string(),
+                other => {
+                    return Err(format!(
+                        "shape() expects a name string, got {}",
+                        other.type_name()
+                    ))
+                }
+            };
+            crate::serve::response_shape::define_shape(&name, &args[1])?;
+            Ok(Value::Null)
+        })),
+    );
+
     // router_member_enter()
     env.define(
         "router_member_enter".to_string(),
@@ -863,6 +885,81 @@ pub fn register_router_builtins(env: &mut Environment) {
             Ok(Value::Null)
         })),
     );
+
+    // limit_concurrency(path_pattern, max, options?) — at most `max` requests
+    // under the path at once; the excess gets 503 + Retry-After instead of
+    // queueing for a worker. limit_concurrency(max, options?) sets the
+    // ceiling for the whole server instead (0 lifts it).
+    //
+    // Examples (config/routes.sl):
+    //   limit_concurrency("/reports/*", 2)
+    //   limit_concurrency("/exports/*", 1, {"retry_after": 30})
+    //   limit_concurrency(200)
+    //
+    // Options: retry_after (Int seconds, default 1).
+    env.define(
+        "limit_concurrency".to_string(),
+        Value::NativeFunction(NativeFunction::new("limit_concurrency", None, |args| {
+            let (pattern, rest) = match args.first() {
+                Some(Value::String(s)) => (Some(s.to_string()), &args[1..]),
+                Some(Value::Int(_)) => (None, &args[..]),
+                Some(other) => {
+                    return Err(format!(
+                        "limit_concurrency() expects a path pattern or a limit, got {}",
+                        other.type_name()
+                    ))
+                }
+                None => return Err("limit_concurrency() requires a limit".to_string()),
+            };
+            let max = match rest.first() {
+                Some(Value::Int(n)) if *n >= 0 => *n as usize,
+                Some(other) => {
+                    return Err(format!(
+                        "limit_concurrency() expects a non-negative integer limit, got {}",
+                        other.type_name()
+                    ))
+                }
+                None => return Err("limit_concurrency() requires a limit".to_string()),
+            };
+
+            let mut retry_after = crate::serve::concurrency::DEFAULT_RETRY_AFTER_SECS;
+            if let Some(options) = rest.get(1) {
+                let Value::Hash(map) = options else {
+                    return Err(format!(
+                        "limit_concurrency() expects an options hash, got {}",
+                        options.type_name()
+                    ));
+                };
+                for (key, value) in map.borrow().iter() {
+                    match key.to_value().to_string().as_str() {
+                        "retry_after" => match value {
+                            Value::Int(n) if *n >= 0 => retry_after = *n as u64,
+                            other => {
+                                return Err(format!(
+                                    "limit_concurrency() option 'retry_after' expects a non-negative integer, got {}",
+                                    other.type_name()
+                                ))
+                            }
+                        },
+                        unknown => {
+                            return Err(format!(
+                                "limit_concurrency() got unknown option '{}' (expected retry_after)",
+                                unknown
+                            ))
+                        }
+                    }
+                }
+            }
+
+            match pattern {
+                Some(pattern) => {
+                    crate::serve::concurrency::set_route_limit(pattern, max, retry_after)
+                }
+                None => crate::serve::concurrency::set_global_limit(max, retry_after),
+            }
+            Ok(Value::Null)
+        })),
+    );
 }
 
 #[cfg(test)]
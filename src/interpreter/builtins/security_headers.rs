@@ -1,6 +1,9 @@
 use crate::interpreter::environment::Environment;
 use crate::interpreter::value::{HashKey, HashPairs, NativeFunction, Value};
+use base64::Engine as _;
 use lazy_static::lazy_static;
+use rand_core::RngCore;
+use sha2::{Digest, Sha256};
 use std::cell::RefCell;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::RwLock;
@@ -26,6 +29,21 @@ thread_local! {
         const { RefCell::new((0, Vec::new())) };
 }
 
+/// Inline `<script>` / `<style>` sources the page being rendered relies on:
+/// the request's CSP nonce (minted on first use) and the hash of every
+/// element `javascript_tag` / `style_tag` produced. Merged into the CSP
+/// header by [`apply_inline_sources`] when the response is finalized.
+#[derive(Default)]
+struct InlineSources {
+    nonce: Option<String>,
+    script_hashes: Vec<String>,
+    style_hashes: Vec<String>,
+}
+
+thread_local! {
+    static INLINE_SOURCES: RefCell<InlineSources> = RefCell::new(InlineSources::default());
+}
+
 /// Bump the global version to invalidate all thread-local caches.
 fn invalidate_security_headers_cache() {
     SECURITY_HEADERS_VERSION.fetch_add(1, Ordering::Release);
@@ -54,6 +72,13 @@ struct HstsConfig {
 }
 
 pub fn register_security_headers_builtins(env: &mut Environment) {
+    env.define(
+        "csp_nonce".to_string(),
+        Value::NativeFunction(NativeFunction::new("csp_nonce", Some(0), |_args| {
+            Ok(Value::String(csp_nonce().into()))
+        })),
+    );
+
     env.define(
         "enable_security_headers".to_string(),
         Value::NativeFunction(NativeFunction::new(
@@ -696,6 +721,130 @@ fn build_security_headers_vec() -> Vec<(String, String)> {
     headers
 }
 
+/// The current request's CSP nonce, minted on first use. A page carrying a
+/// nonce can't be replayed from the response cache (the next request gets a
+/// new one), so minting also opts the request out of it.
+pub fn csp_nonce() -> String {
+    INLINE_SOURCES.with(|sources| {
+        sources
+            .borrow_mut()
+            .nonce
+            .get_or_insert_with(|| {
+                crate::template::response_cache::mark_response_dirty();
+                let mut bytes = [0u8; 16];
+                rand_core::OsRng.fill_bytes(&mut bytes);
+                base64::engine::general_purpose::STANDARD.encode(bytes)
+            })
+            .clone()
+    })
+}
+
+/// Wrap `content` in a `<script>` or `<style>` element that carries the
+/// request's nonce, and register its hash for the CSP header.
+pub fn inline_tag(element: &str, content: &str) -> String {
+    let nonce = csp_nonce();
+    let digest = Sha256::digest(content.as_bytes());
+    let hash = format!(
+        "'sha256-{}'",
+        base64::engine::general_purpose::STANDARD.encode(digest)
+    );
+    INLINE_SOURCES.with(|sources| {
+        let mut sources = sources.borrow_mut();
+        let hashes = if element == "script" {
+            &mut sources.script_hashes
+        } else {
+            &mut sources.style_hashes
+        };
+        if !hashes.contains(&hash) {
+            hashes.push(hash);
+        }
+    });
+    format!("<{element} nonce=\"{nonce}\">{content}</{element}>")
+}
+
+/// Forget the previous request's nonce and hashes. Called at the top of
+/// `handle_request`.
+pub fn reset_inline_sources() {
+    INLINE_SOURCES.with(|sources| *sources.borrow_mut() = InlineSources::default());
+}
+
+/// Add this request's nonce and inline hashes to the CSP headers in
+/// `headers`, then reset them. A request that rendered no inline tag (and
+/// never asked for `csp_nonce()`) leaves the policy untouched.
+pub fn apply_inline_sources(headers: &mut [(String, String)]) {
+    let sources = INLINE_SOURCES.with(|sources| std::mem::take(&mut *sources.borrow_mut()));
+    let Some(nonce) = sources.nonce else {
+        return;
+    };
+    let nonce = format!("'nonce-{}'", nonce);
+    let script: Vec<&str> = std::iter::once(nonce.as_str())
+        .chain(sources.script_hashes.iter().map(String::as_str))
+        .collect();
+    let style: Vec<&str> = std::iter::once(nonce.as_str())
+        .chain(sources.style_hashes.iter().map(String::as_str))
+        .collect();
+    for (name, value) in headers.iter_mut() {
+        if name == "Content-Security-Policy" || name == "Content-Security-Policy-Report-Only" {
+            let policy = add_inline_sources(value, "script", &script);
+            *value = add_inline_sources(&policy, "style", &style);
+        }
+    }
+}
+
+/// Append `sources` to the `<kind>-src` directive of `policy` (and to
+/// `<kind>-src-elem`, which takes precedence for elements, when present).
+/// Without a `<kind>-src` the policy falls back to `default-src`, so one is
+/// added that copies it. A directive that is `'none'` or allows
+/// `'unsafe-inline'` is left alone: adding a nonce to the latter would make
+/// browsers ignore `'unsafe-inline'` and block the app's other inline code.
+fn add_inline_sources(policy: &str, kind: &str, sources: &[&str]) -> String {
+    let mut directives: Vec<String> = policy
+        .split(';')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(str::to_string)
+        .collect();
+    let name_of = |d: &str| {
+        d.split_whitespace()
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase()
+    };
+    let accepts = |d: &str| {
+        !d.split_whitespace()
+            .any(|t| t.eq_ignore_ascii_case("'none'") || t.eq_ignore_ascii_case("'unsafe-inline'"))
+    };
+    let extend = |d: &mut String| {
+        for source in sources {
+            if !d.split_whitespace().any(|t| t == *source) {
+                d.push(' ');
+                d.push_str(source);
+            }
+        }
+    };
+
+    let src = format!("{}-src", kind);
+    let elem = format!("{}-src-elem", kind);
+    if !directives.iter().any(|d| name_of(d) == src) {
+        let Some(default) = directives.iter().find(|d| name_of(d) == "default-src") else {
+            // No fallback either: inline code isn't restricted.
+            return directives.join("; ");
+        };
+        let copied = default
+            .split_whitespace()
+            .skip(1)
+            .fold(src.clone(), |acc, t| acc + " " + t);
+        directives.push(copied);
+    }
+    for directive in directives.iter_mut() {
+        let name = name_of(directive);
+        if (name == src || name == elem) && accepts(directive) {
+            extend(directive);
+        }
+    }
+    directives.join("; ")
+}
+
 /// SEC-056: flip the global on/off switch from Rust. Used by the
 /// server boot to turn headers off in `--dev` mode (so the dev bar's
 /// inline scripts and the dev REPL aren't second-guessed by a CSP that
@@ -798,6 +947,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn inline_sources_join_the_matching_directives() {
+        let sources = ["'nonce-abc'", "'sha256-x'"];
+        assert_eq!(
+            add_inline_sources("default-src 'self'; script-src 'self'", "script", &sources),
+            "default-src 'self'; script-src 'self' 'nonce-abc' 'sha256-x'"
+        );
+        // Falls back to a copy of default-src.
+        assert_eq!(
+            add_inline_sources("default-src 'self' cdn.example", "style", &sources),
+            "default-src 'self' cdn.example; style-src 'self' cdn.example 'nonce-abc' 'sha256-x'"
+        );
+        // Unrestricted, 'none' and 'unsafe-inline' policies are left as they are.
+        assert_eq!(
+            add_inline_sources("img-src *", "script", &sources),
+            "img-src *"
+        );
+        assert_eq!(
+            add_inline_sources("script-src 'none'", "script", &sources),
+            "script-src 'none'"
+        );
+        assert_eq!(
+            add_inline_sources("script-src 'self' 'unsafe-inline'", "script", &sources),
+            "script-src 'self' 'unsafe-inline'"
+        );
+    }
+
+    #[test]
+    fn inline_tag_nonce_and_hash_reach_the_csp_header() {
+        reset_inline_sources();
+        let tag = inline_tag("script", "alert(1)");
+        let nonce = csp_nonce();
+        assert_eq!(tag, format!("<script nonce=\"{nonce}\">alert(1)</script>"));
+
+        let mut headers = vec![
+            (
+                "Content-Security-Policy".to_string(),
+                "default-src 'self'".to_string(),
+            ),
+            ("X-Frame-Options".to_string(), "SAMEORIGIN".to_string()),
+        ];
+        apply_inline_sources(&mut headers);
+        // echo -n 'alert(1)' | openssl sha256 -binary | base64
+        assert_eq!(
+            headers[0].1,
+            format!(
+                "default-src 'self'; script-src 'self' 'nonce-{nonce}' \
+                 'sha256-bhHHL3z2vDgxUt0W3dWQOrprscmda2Y5pLsLg4GF+pI='; \
+                 style-src 'self' 'nonce-{nonce}'"
+            )
+        );
+
+        // Applying resets: the next request starts without a nonce.
+        let mut next = headers.clone();
+        apply_inline_sources(&mut next);
+        assert_eq!(next, headers);
+        assert_ne!(csp_nonce(), nonce);
+        reset_inline_sources();
+    }
+
     #[test]
     fn set_security_headers_enabled_false_emits_no_headers() {
         // SEC-056: dev mode (and an explicit operator opt-out) must be
@@ -1074,6 +1074,21 @@ pub fn parse_form_urlencoded_body(body: &str) -> Option<Value> {
     )))))
 }
 
+/// The wire headers as the `req["headers"]` hash, built once per request
+/// straight off hyper's `HeaderMap`. Non-UTF-8 values are skipped.
+pub fn wire_header_pairs(headers: &hyper::HeaderMap) -> HashPairs {
+    let mut pairs = HashPairs::with_capacity_and_hasher(headers.keys_len(), AHasher::default());
+    for (name, value) in headers {
+        if let Ok(v) = value.to_str() {
+            pairs.insert(
+                HashKey::String(name.as_str().into()),
+                Value::String(v.into()),
+            );
+        }
+    }
+    pairs
+}
+
 /// Build a request hash from HTTP request data.
 /// Uses thread-local cached keys to avoid repeated String allocations.
 pub fn build_request_hash(
@@ -1331,6 +1346,8 @@ pub fn extract_response(response: Value) -> (u16, Vec<(String, String)>, Vec<u8>
     if let Some(fast) = take_fast_path_response() {
         return (fast.status, fast.headers, fast.body.into_bytes());
     }
+    // A typed action's `Response` reads exactly like a response hash.
+    let response = super::http_types::into_response_value(response);
 
     let mut status = 200u16;
     let mut headers = Vec::new();
@@ -1447,8 +1464,123 @@ fn decode_body_base64(b64: &str) -> Vec<u8> {
     }
 }
 
-/// Register URL utility functions (url_encode, url_decode) in the given environment.
+/// Build the request `internal_request(method, path, params, headers?)`
+/// dispatches. `params` become the query string of a GET/HEAD/DELETE and a
+/// JSON body otherwise; a query already on `path` is kept.
+fn internal_request_from_args(
+    args: &[Value],
+) -> Result<crate::serve::internal_request::InternalRequest, String> {
+    let method = match args.first() {
+        Some(Value::String(m)) => m.to_string().to_ascii_uppercase(),
+        _ => return Err("internal_request() expects a method string".to_string()),
+    };
+    let target = match args.get(1) {
+        Some(Value::String(p)) if p.starts_with('/') => p.to_string(),
+        _ => return Err("internal_request() expects a path starting with '/'".to_string()),
+    };
+    let (path, mut query) = match target.split_once('?') {
+        Some((path, query)) => (path.to_string(), parse_query_pairs(query)),
+        None => (target, Vec::new()),
+    };
+
+    let mut headers = hyper::header::HeaderMap::new();
+    match args.get(3) {
+        None | Some(Value::Null) => {}
+        Some(Value::Hash(hash)) => {
+            for (key, value) in hash.borrow().iter() {
+                let name = hyper::header::HeaderName::from_bytes(key.to_string().as_bytes())
+                    .map_err(|_| format!("internal_request(): invalid header name '{}'", key))?;
+                let value =
+                    hyper::header::HeaderValue::from_str(&value.to_string()).map_err(|_| {
+                        format!("internal_request(): invalid value for header '{}'", key)
+                    })?;
+                headers.insert(name, value);
+            }
+        }
+        Some(other) => {
+            return Err(format!(
+                "internal_request() expects headers as a hash, got {}",
+                other.type_name()
+            ))
+        }
+    }
+
+    let mut body = String::new();
+    match args.get(2) {
+        None | Some(Value::Null) => {}
+        Some(Value::Hash(params)) if matches!(method.as_str(), "GET" | "HEAD" | "DELETE") => {
+            for (key, value) in params.borrow().iter() {
+                let value = match value {
+                    Value::String(s) => s.to_string(),
+                    other => other.to_string(),
+                };
+                query.push((key.to_string(), value));
+            }
+        }
+        Some(params @ Value::Hash(_)) => {
+            body = stringify_to_string(params)
+                .map_err(|e| format!("internal_request() could not serialize params: {}", e))?;
+            headers
+                .entry(hyper::header::CONTENT_TYPE)
+                .or_insert(hyper::header::HeaderValue::from_static("application/json"));
+        }
+        Some(other) => {
+            return Err(format!(
+                "internal_request() expects params as a hash, got {}",
+                other.type_name()
+            ))
+        }
+    }
+
+    Ok(crate::serve::internal_request::InternalRequest {
+        method,
+        path,
+        query,
+        headers,
+        body,
+    })
+}
+
+/// Register URL utility functions (url_encode, url_decode) and
+/// `internal_request` in the given environment.
 pub fn register_server_builtins(env: &mut Environment) {
+    // internal_request(method, path, params, headers?) -> {status, headers, body}
+    // Runs the request through the router, middleware and controller
+    // in-process (no TCP) on a separate worker, so it works from a controller
+    // action as well as from a job. Only available under `soli serve`.
+    env.define(
+        "internal_request".to_string(),
+        Value::NativeFunction(NativeFunction::new("internal_request", None, |args| {
+            if !(2..=4).contains(&args.len()) {
+                return Err(format!(
+                    "internal_request() expects 2 to 4 arguments (method, path, params, headers), got {}",
+                    args.len()
+                ));
+            }
+            let request = internal_request_from_args(&args)?;
+            let response = crate::serve::internal_request::dispatch(request)?;
+
+            let mut headers = HashPairs::default();
+            for (key, value) in response.headers {
+                headers.insert(HashKey::String(key.into()), Value::String(value.into()));
+            }
+            let mut result = HashPairs::default();
+            result.insert(
+                HashKey::String("status".into()),
+                Value::Int(response.status as i64),
+            );
+            result.insert(
+                HashKey::String("headers".into()),
+                Value::Hash(Rc::new(RefCell::new(headers))),
+            );
+            result.insert(
+                HashKey::String("body".into()),
+                Value::String(String::from_utf8_lossy(&response.body).into_owned().into()),
+            );
+            Ok(Value::Hash(Rc::new(RefCell::new(result))))
+        })),
+    );
+
     // url_encode(s) -> String
     // Strict RFC 3986 component encoding: percent-encodes every reserved char,
     // including `/`, `?`, `&`, `=`, `#`, space. Use this for query values,
@@ -1949,6 +2081,75 @@ pub fn register_websocket_builtins(env: &mut Environment) {
     );
 }
 
+#[cfg(test)]
+mod internal_request_tests {
+    use super::*;
+
+    fn hash(pairs: Vec<(&str, Value)>) -> Value {
+        let mut map = HashPairs::default();
+        for (k, v) in pairs {
+            map.insert(HashKey::String(k.into()), v);
+        }
+        Value::Hash(Rc::new(RefCell::new(map)))
+    }
+
+    fn string(s: &str) -> Value {
+        Value::String(s.into())
+    }
+
+    #[test]
+    fn get_params_join_the_query_string() {
+        let request = internal_request_from_args(&[
+            string("get"),
+            string("/posts?page=2"),
+            hash(vec![("tag", string("rust")), ("limit", Value::Int(5))]),
+        ])
+        .unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/posts");
+        assert_eq!(
+            request.query,
+            vec![
+                ("page".to_string(), "2".to_string()),
+                ("tag".to_string(), "rust".to_string()),
+                ("limit".to_string(), "5".to_string()),
+            ]
+        );
+        assert!(request.body.is_empty());
+    }
+
+    #[test]
+    fn post_params_become_a_json_body() {
+        let request = internal_request_from_args(&[
+            string("POST"),
+            string("/api/posts"),
+            hash(vec![("title", string("Hello"))]),
+            hash(vec![("Authorization", string("Bearer t"))]),
+        ])
+        .unwrap();
+        assert_eq!(request.body, r#"{"title":"Hello"}"#);
+        assert_eq!(request.headers["content-type"], "application/json");
+        assert_eq!(request.headers["authorization"], "Bearer t");
+    }
+
+    #[test]
+    fn relative_paths_and_non_hash_params_are_rejected() {
+        assert!(internal_request_from_args(&[string("GET"), string("posts")]).is_err());
+        assert!(
+            internal_request_from_args(&[string("GET"), string("/posts"), Value::Int(1)]).is_err()
+        );
+    }
+
+    #[test]
+    fn dispatch_outside_serve_is_an_error() {
+        let request = internal_request_from_args(&[string("GET"), string("/")]).unwrap();
+        let Err(err) = crate::serve::internal_request::dispatch(request) else {
+            panic!("dispatched without a server");
+        };
+        assert!(err.contains("only available in `soli serve`"), "{}", err);
+    }
+}
+
 #[cfg(test)]
 mod body_base64_tests {
     use super::*;
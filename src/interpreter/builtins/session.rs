@@ -17,6 +17,7 @@ use lazy_static::lazy_static;
 use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
+use crate::interpreter::builtins::datetime::helpers::clock_now_millis;
 use crate::interpreter::environment::Environment;
 use crate::interpreter::value::{NativeFunction, Value};
 
@@ -603,14 +604,16 @@ pub fn get_current_store() -> Arc<dyn SessionStore> {
 #[derive(Clone)]
 struct Session {
     data: HashMap<String, JsonValue>,
+    /// Epoch milliseconds from the (freezable) test clock, so specs can
+    /// expire a session with `travel()` rather than sleeping past the TTL.
     #[allow(dead_code)]
-    created_at: Instant,
-    last_accessed: Instant,
+    created_at: i64,
+    last_accessed: i64,
 }
 
 impl Session {
     fn new() -> Self {
-        let now = Instant::now();
+        let now = clock_now_millis();
         Self {
             data: HashMap::new(),
             created_at: now,
@@ -619,11 +622,12 @@ impl Session {
     }
 
     fn touch(&mut self) {
-        self.last_accessed = Instant::now();
+        self.last_accessed = clock_now_millis();
     }
 
     fn is_expired(&self, max_age: Duration) -> bool {
-        self.last_accessed.elapsed() > max_age
+        let age_ms = clock_now_millis().saturating_sub(self.last_accessed);
+        age_ms > max_age.as_millis() as i64
     }
 }
 
@@ -689,17 +693,27 @@ impl SessionStore for InMemorySessionStore {
             self.cleanup();
         }
 
+        // A session past its TTL is gone even before the next sweep, like
+        // the disk store's, so expiry doesn't wait on the cleanup cadence.
         {
             let sessions = self.sessions.read().unwrap();
-            if sessions.contains_key(session_id) {
-                return session_id.to_string();
+            if let Some(session) = sessions.get(session_id) {
+                if !session.is_expired(self.max_age) {
+                    return session_id.to_string();
+                }
             }
         }
 
         let mut sessions = self.sessions.write().unwrap();
 
-        if sessions.contains_key(session_id) {
-            return session_id.to_string();
+        match sessions.get(session_id) {
+            Some(session) if !session.is_expired(self.max_age) => {
+                return session_id.to_string();
+            }
+            Some(_) => {
+                sessions.remove(session_id);
+            }
+            None => {}
         }
 
         let new_id = Uuid::new_v4().to_string();
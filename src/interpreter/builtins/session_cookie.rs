@@ -24,7 +24,6 @@
 
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 use base64::engine::general_purpose::URL_SAFE_NO_PAD;
 use base64::Engine as _;
@@ -95,10 +94,7 @@ pub fn is_plausible_sealed_value(value: &str) -> bool {
 }
 
 fn now_unix_secs() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_secs())
-        .unwrap_or(0)
+    super::datetime::helpers::datetime_now().max(0) as u64
 }
 
 fn fresh_state(replaced: bool) -> CookieSessionState {
@@ -2,12 +2,13 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
+use super::datetime::helpers::datetime_now;
 use super::session::SessionStore;
 
 /// Reject session IDs that could escape the session directory or contain
@@ -32,10 +33,7 @@ struct SessionFile {
 
 impl SessionFile {
     fn new() -> Self {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let now = datetime_now().max(0) as u64;
         Self {
             data: HashMap::new(),
             created_at: now,
@@ -44,18 +42,12 @@ impl SessionFile {
     }
 
     fn touch(&mut self) {
-        self.last_accessed = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        self.last_accessed = datetime_now().max(0) as u64;
     }
 
     fn is_expired(&self, max_age_secs: u64) -> bool {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        now - self.last_accessed > max_age_secs
+        let now = datetime_now().max(0) as u64;
+        now.saturating_sub(self.last_accessed) > max_age_secs
     }
 }
 
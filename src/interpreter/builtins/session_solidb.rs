@@ -8,6 +8,7 @@ use uuid::Uuid;
 
 use crate::solidb_http::{SoliDBClient, SoliDBError};
 
+use super::datetime::helpers::clock_now_millis;
 use super::session::SessionStore;
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -21,7 +22,7 @@ struct SessionDocument {
 
 impl SessionDocument {
     fn new(key: String) -> Self {
-        let now = chrono::Utc::now().timestamp_millis();
+        let now = clock_now_millis();
         Self {
             key,
             data: HashMap::new(),
@@ -31,7 +32,7 @@ impl SessionDocument {
     }
 
     fn touch(&mut self) {
-        self.last_accessed = chrono::Utc::now().timestamp_millis();
+        self.last_accessed = clock_now_millis();
     }
 }
 
@@ -151,7 +152,7 @@ impl SolidbSessionStore {
         let api_key = self.api_key.clone();
         let username = self.username.clone();
         let password = self.password.clone();
-        let cutoff_ms = chrono::Utc::now().timestamp_millis() - self.max_age.as_millis() as i64;
+        let cutoff_ms = clock_now_millis() - self.max_age.as_millis() as i64;
 
         std::thread::spawn(move || {
             match Self::build_client(&host, &database, &api_key, &username, &password) {
@@ -208,7 +209,7 @@ impl SessionStore for SolidbSessionStore {
         }
 
         if let Ok(Some(session)) = self.load_session(session_id) {
-            let now = chrono::Utc::now().timestamp_millis();
+            let now = clock_now_millis();
             let age = (now - session.last_accessed) as u64;
             if Duration::from_millis(age) < self.max_age {
                 return session_id.to_string();
@@ -293,7 +294,7 @@ impl SessionStore for SolidbSessionStore {
 
     fn cleanup(&self) {
         if let Ok(client) = self.create_client() {
-            let cutoff_ms = chrono::Utc::now().timestamp_millis() - self.max_age.as_millis() as i64;
+            let cutoff_ms = clock_now_millis() - self.max_age.as_millis() as i64;
             Self::purge_expired(&client, &self.collection, cutoff_ms);
         }
     }
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use uuid::Uuid;
 
+use super::datetime::helpers::clock_now_millis;
 use super::resp::{RespPool, RespValue};
 use super::session::SessionStore;
 
@@ -20,7 +21,7 @@ struct SessionData {
 
 impl SessionData {
     fn new() -> Self {
-        let now = chrono::Utc::now().timestamp_millis();
+        let now = clock_now_millis();
         Self {
             data: HashMap::new(),
             created_at: now,
@@ -29,7 +30,7 @@ impl SessionData {
     }
 
     fn touch(&mut self) {
-        self.last_accessed = chrono::Utc::now().timestamp_millis();
+        self.last_accessed = clock_now_millis();
     }
 }
 
@@ -98,7 +99,7 @@ impl SessionStore for SolikvSessionStore {
         }
 
         if let Some(session) = self.load_session(session_id) {
-            let now = chrono::Utc::now().timestamp_millis();
+            let now = clock_now_millis();
             let age_ms = (now - session.last_accessed) as u64;
             if Duration::from_millis(age_ms) < Duration::from_secs(self.ttl) {
                 return session_id.to_string();
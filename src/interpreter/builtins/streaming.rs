@@ -7,6 +7,8 @@
 //!    **runs the block on the worker thread** — `out.emit`/`out.write` push
 //!    frames as it executes. Good for finite, active streams (an agent run, an
 //!    export); the worker is held for the stream's lifetime.
+//!    `render_stream(view, locals)` takes the same path with a template render
+//!    in place of the block: the layout head is sent before the view renders.
 //!
 //! 2. **Async pub/sub** (`sse_subscribe(req, topic)` + `sse_broadcast(topic,
 //!    data, event?)`): the worker registers the chunk sender under the topic
@@ -27,6 +29,7 @@ use std::sync::Mutex;
 use lazy_static::lazy_static;
 use tokio::sync::mpsc::Sender;
 
+use crate::interpreter::builtins::template::StreamRender;
 use crate::interpreter::environment::Environment;
 use crate::interpreter::value::{Class, Instance, NativeFunction, Value};
 
@@ -155,6 +158,8 @@ pub struct StreamSpec {
     /// the chunk sender under this topic and returns immediately (no block,
     /// no worker held). Events arrive via `sse_broadcast`.
     pub subscribe_topic: Option<String>,
+    /// A `render_stream` page, rendered in place of a block.
+    pub render: Option<StreamRender>,
 }
 
 thread_local! {
@@ -296,6 +301,7 @@ fn sse_builtin(args: Vec<Value>) -> Result<Value, String> {
         ],
         sse: true,
         subscribe_topic: None,
+        render: None,
     });
     Ok(sentinel())
 }
@@ -319,6 +325,7 @@ fn stream_builtin(args: Vec<Value>) -> Result<Value, String> {
         ],
         sse: false,
         subscribe_topic: None,
+        render: None,
     });
     Ok(sentinel())
 }
@@ -348,6 +355,7 @@ fn sse_subscribe_builtin(args: Vec<Value>) -> Result<Value, String> {
         ],
         sse: true,
         subscribe_topic: Some(topic),
+        render: None,
     });
     Ok(sentinel())
 }
@@ -380,6 +388,26 @@ fn sse_subscribers_builtin(args: Vec<Value>) -> Result<Value, String> {
     Ok(Value::Int(subscriber_count(&topic) as i64))
 }
 
+/// Stash a `render_stream` page as the pending stream, returning the sentinel.
+pub fn set_pending_render(render: StreamRender, status: i64) -> Value {
+    set_pending(StreamSpec {
+        block: Value::Null,
+        status: status as u16,
+        headers: vec![
+            (
+                "Content-Type".to_string(),
+                "text/html; charset=utf-8".to_string(),
+            ),
+            ("Cache-Control".to_string(), "private, no-cache".to_string()),
+            ("X-Accel-Buffering".to_string(), "no".to_string()),
+        ],
+        sse: false,
+        subscribe_topic: None,
+        render: Some(render),
+    });
+    sentinel()
+}
+
 /// Benign value the request handler turns into a 200 — discarded by the worker
 /// once it sees the pending stream.
 fn sentinel() -> Value {
@@ -396,6 +424,12 @@ pub fn run_stream_block(
     spec: &StreamSpec,
     id: usize,
 ) {
+    if let Some(render) = &spec.render {
+        crate::interpreter::builtins::template::run_render_stream(render, &mut |bytes| {
+            send_chunk(id, bytes)
+        });
+        return;
+    }
     let func = match &spec.block {
         Value::Function(f) => f.clone(),
         _ => return,
@@ -9,11 +9,21 @@ use std::rc::Rc;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::OnceLock;
 use std::thread;
 
 use crate::interpreter::environment::Environment;
 use crate::interpreter::value::{Class, HashKey, HashPairs, NativeFunction, Value};
 
+/// Arguments after the command name of a `soli <command>` project script.
+static SCRIPT_ARGS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Record the arguments `System.args()` returns. Called once, by the CLI,
+/// before a project command's script runs.
+pub fn set_script_args(args: Vec<String>) {
+    let _ = SCRIPT_ARGS.set(args);
+}
+
 pub fn register_system_builtins(env: &mut Environment) {
     // System class
     env.define("System".to_string(), system_class());
@@ -70,6 +80,22 @@ fn system_class() -> Value {
         })),
     );
 
+    // System.args() - Arguments passed to a `soli <command>` project script.
+    methods.insert(
+        "args".to_string(),
+        Rc::new(NativeFunction::new("System.args", Some(0), |_args| {
+            let args = SCRIPT_ARGS
+                .get()
+                .map(|args| {
+                    args.iter()
+                        .map(|arg| Value::String(arg.as_str().into()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            Ok(Value::Array(Rc::new(RefCell::new(args))))
+        })),
+    );
+
     let class = Class {
         name: "System".to_string(),
         superclass: None,
@@ -0,0 +1,132 @@
+//! Task DSL: `task("build", deps: ["assets"]) { ... }`, run by `soli task`.
+//!
+//! Like `bench`, `task` only records its body while `tasks.sl` runs. The
+//! runner reads the recorded [`TaskSpec`]s to plan the dependency graph, then
+//! runs each body on a worker thread that loads `tasks.sl` afresh — values
+//! can't cross threads, so every worker takes its own copy of the file.
+
+use std::cell::RefCell;
+
+use crate::interpreter::environment::Environment;
+use crate::interpreter::value::{HashKey, NativeFunction, Value};
+
+/// Everything about a task but its body: plain data, so the runner can plan
+/// on one thread and run on others.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TaskSpec {
+    pub name: String,
+    /// Tasks that must finish first.
+    pub deps: Vec<String>,
+    /// Glob patterns for the files the task reads.
+    pub inputs: Vec<String>,
+    /// Glob patterns for the files the task writes.
+    pub outputs: Vec<String>,
+    /// One line for `soli task --list`.
+    pub desc: Option<String>,
+}
+
+/// A task declared with `task`.
+#[derive(Clone)]
+pub struct TaskDefinition {
+    pub spec: TaskSpec,
+    pub body: Value,
+}
+
+thread_local! {
+    static TASKS: RefCell<Vec<TaskDefinition>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn register_task_builtins(env: &mut Environment) {
+    env.define(
+        "task".to_string(),
+        Value::NativeFunction(NativeFunction::new("task", None, |args| {
+            let mut args = args.into_iter();
+            let name = match args.next() {
+                Some(Value::String(s)) => s.to_string(),
+                _ => return Err("task requires string name".to_string()),
+            };
+            let mut spec = TaskSpec {
+                name,
+                ..TaskSpec::default()
+            };
+            let mut body = None;
+            for arg in args {
+                match arg {
+                    Value::Hash(options) if body.is_none() => {
+                        for (key, value) in options.borrow().iter() {
+                            read_option(&mut spec, key, value)?;
+                        }
+                    }
+                    arg if arg.is_callable() && body.is_none() => body = Some(arg),
+                    other => {
+                        return Err(format!(
+                            "task '{}': unexpected {} argument",
+                            spec.name,
+                            other.type_name()
+                        ))
+                    }
+                }
+            }
+            let Some(body) = body else {
+                return Err(format!("task '{}' requires a block", spec.name));
+            };
+            TASKS.with(|tasks| tasks.borrow_mut().push(TaskDefinition { spec, body }));
+            Ok(Value::Null)
+        })),
+    );
+}
+
+fn read_option(spec: &mut TaskSpec, key: &HashKey, value: &Value) -> Result<(), String> {
+    let key = match key {
+        HashKey::String(s) => s.to_string(),
+        _ => {
+            return Err(format!(
+                "task '{}': option names must be strings",
+                spec.name
+            ))
+        }
+    };
+    match key.as_str() {
+        "deps" => spec.deps = string_list(&spec.name, &key, value)?,
+        "inputs" => spec.inputs = string_list(&spec.name, &key, value)?,
+        "outputs" => spec.outputs = string_list(&spec.name, &key, value)?,
+        "desc" => match value {
+            Value::String(s) => spec.desc = Some(s.to_string()),
+            _ => return Err(format!("task '{}': desc must be a string", spec.name)),
+        },
+        other => {
+            return Err(format!(
+                "task '{}': unknown option '{}' (expected deps, inputs, outputs or desc)",
+                spec.name, other
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// A string, or an array of strings, as a list.
+fn string_list(task: &str, key: &str, value: &Value) -> Result<Vec<String>, String> {
+    let error = || {
+        format!(
+            "task '{}': {} must be a string or an array of strings",
+            task, key
+        )
+    };
+    match value {
+        Value::String(s) => Ok(vec![s.to_string()]),
+        Value::Array(items) => items
+            .borrow()
+            .iter()
+            .map(|item| match item {
+                Value::String(s) => Ok(s.to_string()),
+                _ => Err(error()),
+            })
+            .collect(),
+        _ => Err(error()),
+    }
+}
+
+/// Hand back the tasks declared since the last call, in order.
+pub fn take_tasks() -> Vec<TaskDefinition> {
+    TASKS.with(|tasks| std::mem::take(&mut *tasks.borrow_mut()))
+}
@@ -29,6 +29,10 @@ static TEMPLATE_CACHE: OnceLock<Arc<TemplateCache>> = OnceLock::new();
 // Thread-local view context for debugging (stores the data passed to render())
 thread_local! {
     static VIEW_DEBUG_CONTEXT: RefCell<Option<Value>> = const { RefCell::new(None) };
+    // The variables in scope where a template last failed, for the dev
+    // error page's template overlay: `(name, value as shown)`.
+    static TEMPLATE_ERROR_LOCALS: RefCell<Option<Vec<(String, String)>>> =
+        const { RefCell::new(None) };
 }
 
 // Thread-local file mtime cache for public_path() performance
@@ -287,6 +291,72 @@ fn set_view_debug_context(data: Option<Value>) {
 /// request's view error. The server calls this at the start of each dispatch.
 pub fn clear_view_debug_context() {
     set_view_debug_context(None);
+    TEMPLATE_ERROR_LOCALS.with(|cell| *cell.borrow_mut() = None);
+}
+
+/// Longest value shown for one template-error local.
+const TEMPLATE_LOCAL_PREVIEW: usize = 300;
+
+/// Record the variables in scope where a template tag failed: loop
+/// variables and template `let`s first, innermost scope first, then the
+/// locals passed to `render()`. The first failure recorded wins, so the
+/// templates and blocks it unwinds through don't replace it with their own
+/// scope.
+pub fn record_template_error_locals(env: &Rc<RefCell<Environment>>) {
+    if TEMPLATE_ERROR_LOCALS.with(|cell| cell.borrow().is_some()) {
+        return;
+    }
+    let mut seen = std::collections::HashSet::new();
+    let mut locals = Vec::new();
+    let mut push = |name: &str, value: &Value| {
+        if name == "locals"
+            || name.starts_with("__")
+            || matches!(
+                value,
+                Value::Function(_) | Value::NativeFunction(_) | Value::Class(_)
+            )
+            || !seen.insert(name.to_string())
+        {
+            return;
+        }
+        let mut shown = crate::interpreter::value_stringify::stringify_to_string(value)
+            .unwrap_or_else(|_| value.to_string());
+        if shown.chars().count() > TEMPLATE_LOCAL_PREVIEW {
+            shown = shown
+                .chars()
+                .take(TEMPLATE_LOCAL_PREVIEW)
+                .collect::<String>()
+                + "…";
+        }
+        locals.push((name.to_string(), shown));
+    };
+    // The outermost scope holds the builtins, not template state.
+    let mut scope = Some(env.clone());
+    while let Some(current) = scope {
+        let current = current.borrow();
+        let Some(enclosing) = current.enclosing() else {
+            break;
+        };
+        let mut own: Vec<_> = current.local_bindings().collect();
+        own.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, value) in own {
+            push(name, value);
+        }
+        if let Some(data) = current.data_hash_ref() {
+            for (key, value) in data.borrow().iter() {
+                if let HashKey::String(name) = key {
+                    push(name, value);
+                }
+            }
+        }
+        scope = Some(enclosing);
+    }
+    TEMPLATE_ERROR_LOCALS.with(|cell| *cell.borrow_mut() = Some(locals));
+}
+
+/// Take the locals recorded by [`record_template_error_locals`].
+pub fn take_template_error_locals() -> Option<Vec<(String, String)>> {
+    TEMPLATE_ERROR_LOCALS.with(|cell| cell.borrow_mut().take())
 }
 
 // Global views directory for initialization
@@ -324,6 +394,17 @@ pub fn clear_template_cache() {
     }
 }
 
+/// `(parsed templates, resolved paths)` in the shared template cache, or
+/// `None` before any worker has initialized it.
+pub fn template_cache_sizes() -> Option<(usize, usize)> {
+    TEMPLATE_CACHE.get().map(|tc| tc.sizes())
+}
+
+/// Number of view helpers loaded on this thread.
+pub fn view_helper_count() -> usize {
+    VIEW_HELPERS.with(|h| h.borrow().len())
+}
+
 /// Check if templates have changes (for hot reload).
 pub fn templates_have_changes() -> bool {
     TEMPLATE_CACHE
@@ -630,26 +711,17 @@ pub fn inject_controller_instance_vars(data: &Value) {
 }
 
 /// Pure-Soli form-builder layer (`form_with` / `FormBuilder` / `csrf_field`
-/// / `csrf_meta_tag` / `button_to`), evaluated into the shared template
-/// builtins environment at seed time (see `core_eval::get_builtins_rc`).
+/// / `csrf_meta_tag` / `button_to` / `javascript_tag` / `style_tag`),
+/// evaluated into the shared template builtins environment at seed time (see `core_eval::get_builtins_rc`).
 const FORM_BUILDER_SOURCE: &str = include_str!("form_builder.sl");
 
 /// Evaluate the embedded form-builder Soli source into the template builtins
 /// environment so `form_with(...)` and friends resolve in every view.
 pub fn register_form_builder(env: &Rc<RefCell<Environment>>) -> Result<(), String> {
-    let tokens = crate::lexer::Scanner::new(FORM_BUILDER_SOURCE)
-        .scan_tokens()
-        .map_err(|e| format!("form builder lexer error: {}", e))?;
-    let program = crate::parser::Parser::new(tokens)
-        .parse()
-        .map_err(|e| format!("form builder parser error: {}", e))?;
     let mut interpreter = crate::interpreter::Interpreter::with_environment(env.clone());
-    for stmt in &program.statements {
-        interpreter
-            .execute(stmt)
-            .map_err(|e| format!("form builder eval error: {}", e))?;
-    }
-    Ok(())
+    interpreter
+        .interpret_synthetic("form_builder", FORM_BUILDER_SOURCE)
+        .map_err(|e| format!("form builder error: {}", e))
 }
 
 /// Register static template helpers into an Environment (called once per thread).
@@ -681,6 +753,30 @@ pub fn register_static_template_helpers(env: &mut Environment) {
         })),
     );
 
+    // __soli_inline_tag(element, content) — internal support for
+    // `javascript_tag` / `style_tag`: wraps `content` in a nonced element and
+    // registers its hash for the CSP header.
+    env.define(
+        "__soli_inline_tag".to_string(),
+        Value::NativeFunction(NativeFunction::new("__soli_inline_tag", Some(2), |args| {
+            let element = match &args[0] {
+                Value::String(s) if s.as_str() == "script" || s.as_str() == "style" => {
+                    s.to_string()
+                }
+                other => return Err(format!("__soli_inline_tag(): bad element {}", other)),
+            };
+            let content = match &args[1] {
+                Value::String(s) => s.to_string(),
+                Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            Ok(Value::String(
+                crate::interpreter::builtins::security_headers::inline_tag(&element, &content)
+                    .into(),
+            ))
+        })),
+    );
+
     env.define(
         "range".to_string(),
         Value::NativeFunction(NativeFunction::new("range", None, |args| {
@@ -773,6 +869,52 @@ pub fn register_static_template_helpers(env: &mut Environment) {
         })),
     );
 
+    // javascript_importmap_tags(entry?) — the import map for the packages
+    // vendored with `soli js add`, then, given an entry module under public/
+    // ("js/application.js"), the module script that imports them by name.
+    env.define(
+        "javascript_importmap_tags".to_string(),
+        Value::NativeFunction(NativeFunction::new(
+            "javascript_importmap_tags",
+            None,
+            |args| {
+                let entry = match args.first() {
+                    None | Some(Value::Null) => None,
+                    Some(Value::String(s)) => Some(s.trim_start_matches('/').to_string()),
+                    Some(other) => {
+                        return Err(format!(
+                            "javascript_importmap_tags() expects a string entry, got {}",
+                            other.type_name()
+                        ))
+                    }
+                };
+                let public_dir = match PUBLIC_DIR.lock() {
+                    Ok(dir_guard) => dir_guard.clone(),
+                    _ => None,
+                };
+                let public_dir = public_dir.unwrap_or_else(|| PathBuf::from("public"));
+                let app_dir = public_dir.parent().unwrap_or(Path::new(""));
+                let pins = crate::module::importmap::app_pins(app_dir);
+                // A `</script>` in the JSON would end the element early.
+                let map = crate::module::importmap::import_map(&pins).replace("</", "<\\/");
+                let mut html =
+                    crate::interpreter::builtins::security_headers::inline_tag("script", &map)
+                        .replacen("<script ", "<script type=\"importmap\" ", 1);
+                if let Some(entry) = entry {
+                    let src = match get_file_mtime_cached(&public_dir.join(&entry)) {
+                        Ok(mtime) => format!("/{}?v={}", entry, mtime),
+                        Err(_) => format!("/{}", entry),
+                    };
+                    html.push_str(&format!(
+                        "\n<script type=\"module\" src=\"{}\"></script>",
+                        super::html::html_escape(&src)
+                    ));
+                }
+                Ok(Value::String(html.into()))
+            },
+        )),
+    );
+
     // camera_preview(options?) — a <video> wired to the camera.
     //
     // Showing a camera is six lines of getUserMedia, so this exists for what
@@ -982,17 +1124,7 @@ pub fn register_static_template_helpers(env: &mut Environment) {
     env.define(
         "freeze_time".to_string(),
         Value::NativeFunction(NativeFunction::new("freeze_time", Some(1), |args| {
-            let timestamp = match &args[0] {
-                Value::Int(n) => *n,
-                Value::String(s) => datetime_helpers::datetime_parse(s)
-                    .ok_or_else(|| format!("freeze_time(): invalid date string {:?}", s))?,
-                other => {
-                    return Err(format!(
-                        "freeze_time() expects timestamp (int) or date string, got {}",
-                        other.type_name()
-                    ));
-                }
-            };
+            let timestamp = super::datetime::freeze_target("freeze_time", &args[0])?;
             datetime_helpers::freeze_datetime(timestamp);
             Ok(Value::Int(timestamp))
         })),
@@ -1001,22 +1133,20 @@ pub fn register_static_template_helpers(env: &mut Environment) {
     env.define(
         "travel_to".to_string(),
         Value::NativeFunction(NativeFunction::new("travel_to", Some(1), |args| {
-            let timestamp = match &args[0] {
-                Value::Int(n) => *n,
-                Value::String(s) => datetime_helpers::datetime_parse(s)
-                    .ok_or_else(|| format!("travel_to(): invalid date string {:?}", s))?,
-                other => {
-                    return Err(format!(
-                        "travel_to() expects timestamp (int) or date string, got {}",
-                        other.type_name()
-                    ));
-                }
-            };
+            let timestamp = super::datetime::freeze_target("travel_to", &args[0])?;
             datetime_helpers::freeze_datetime(timestamp);
             Ok(Value::Int(timestamp))
         })),
     );
 
+    env.define(
+        "travel".to_string(),
+        Value::NativeFunction(NativeFunction::new("travel", Some(1), |args| {
+            let seconds = super::datetime::travel_seconds(&args[0])?;
+            Ok(Value::Int(datetime_helpers::travel_datetime(seconds)))
+        })),
+    );
+
     env.define(
         "unfreeze_time".to_string(),
         Value::NativeFunction(NativeFunction::new("unfreeze_time", Some(0), |_args| {
@@ -1462,8 +1592,11 @@ pub fn register_static_template_helpers(env: &mut Environment) {
 
             // Fragment caching (scalar form): a "cache" option memoizes the
             // rendered HTML in the KV cache. `"cache": "key"` uses an explicit
-            // key; `"cache": true` derives one from the data. `"cache_ttl"` sets
-            // the TTL. Best-effort — any cache error falls through to a normal
+            // key, a model (or array of models/keys) keys on their `updated_at`,
+            // and `"cache": true` derives one from the data. Every key also
+            // carries the component's template digest, which covers its nested
+            // includes (see `template::fragment_cache`). `"cache_ttl"` sets the
+            // TTL. Best-effort — any cache error falls through to a normal
             // render, and only side-effect-free renders are stored.
             let cache_opt = opt_value(&data, "cache");
             let cache_ttl = opt_int(&data, "cache_ttl").map(|n| n as u64);
@@ -1488,13 +1621,20 @@ pub fn register_static_template_helpers(env: &mut Environment) {
                 data
             };
             let cache_key = match &cache_opt {
-                None => None,
-                Some(Value::String(k)) => Some(format!("component:{}:{}", name, k)),
-                Some(_) => Some(format!(
-                    "component:{}:{}",
-                    name,
-                    crate::template::response_cache::data_signature(&data)
-                )),
+                None | Some(Value::Bool(false)) | Some(Value::Null) => None,
+                Some(opt) => {
+                    let part = match opt {
+                        Value::Bool(true) => {
+                            format!("{}", crate::template::response_cache::data_signature(&data))
+                        }
+                        other => crate::template::fragment_cache::value_cache_key(other),
+                    };
+                    // An unresolvable component fails in the render below.
+                    cache
+                        .component_digest(&name)
+                        .ok()
+                        .map(|digest| format!("component:{}:{:016x}:{}", name, digest, part))
+                }
             };
             if let Some(key) = &cache_key {
                 if let Ok(Value::String(html)) =
@@ -1551,7 +1691,7 @@ fn has_redirect_control_chars(url: &str) -> bool {
     url.chars().any(char::is_control)
 }
 
-fn validate_local_redirect_url(url: &str) -> Result<(), String> {
+pub(crate) fn validate_local_redirect_url(url: &str) -> Result<(), String> {
     if url.is_empty() || has_redirect_control_chars(url) {
         return Err("redirect() expects a non-empty local path".to_string());
     }
@@ -1979,6 +2119,192 @@ mod assigns_capture_tests {
     }
 }
 
+/// A `render_stream` call, run by the worker once the response has started.
+pub struct StreamRender {
+    template_name: String,
+    data: Value,
+    layout: Option<Option<String>>,
+    request: Option<Value>,
+    request_host: Option<(String, String)>,
+}
+
+/// Render a `render_stream` page, handing each piece to `send` as it's ready.
+/// `send` returns false once the client has disconnected.
+pub fn run_render_stream(render: &StreamRender, send: &mut dyn FnMut(Vec<u8>) -> bool) {
+    let cache = match get_template_cache() {
+        Ok(cache) => cache,
+        Err(e) => {
+            eprintln!("[render_stream] {e}");
+            return;
+        }
+    };
+    if let Some(req) = &render.request {
+        set_current_request(req.clone());
+    }
+    if let Some((scheme, host)) = render.request_host.clone() {
+        crate::interpreter::builtins::named_routes::set_current_request_host(scheme, host);
+    }
+
+    let mut head_sent = false;
+    let mut connected = true;
+    let layout = render.layout.as_ref().map(|l| l.as_deref());
+    let result =
+        cache.render_streaming(&render.template_name, &render.data, layout, &mut |chunk| {
+            connected = chunk.is_empty() || send(chunk.into_bytes());
+            if !head_sent {
+                head_sent = true;
+                resolve_futures_in_data(&render.data);
+            }
+            connected
+        });
+    if let Err(e) = result {
+        // The status line is long gone; all that's left is to say so in the page.
+        eprintln!("[render_stream] {}: {e}", render.template_name);
+        if connected && is_dev_mode() {
+            let message = crate::template::renderer::html_escape(&e);
+            send(format!("<pre class=\"soli-render-error\">{message}</pre>").into_bytes());
+        }
+    }
+
+    clear_current_request();
+    crate::interpreter::builtins::named_routes::clear_current_request_host();
+}
+
+/// Resolve the futures among a render's locals in place, so an interpreter
+/// already reading from the hash sees the values.
+fn resolve_futures_in_data(data: &Value) {
+    let Value::Hash(hash) = data else { return };
+    let pending: Vec<HashKey> = hash
+        .borrow()
+        .iter()
+        .filter(|(_, v)| could_contain_futures(v))
+        .map(|(k, _)| k.clone())
+        .collect();
+    for key in pending {
+        let value = hash.borrow().get(&key).cloned();
+        if let Some(value) = value {
+            let resolved = resolve_futures_in_value(value);
+            hash.borrow_mut().insert(key, resolved);
+        }
+    }
+}
+
+/// The arguments shared by `render` and `render_stream`.
+struct RenderArgs {
+    template_name: String,
+    data: Value,
+    /// `Some(None)` for `layout: false`, `None` for the default layout
+    layout: Option<Option<String>>,
+    status: i64,
+}
+
+/// Parse `(template, data?, options?)`, falling back to the data hash and then
+/// the controller's registered layout when `options` names none.
+fn render_args(name: &str, args: &[Value]) -> Result<RenderArgs, String> {
+    if args.is_empty() {
+        return Err(format!(
+            "{name}() requires at least 1 argument (template name)"
+        ));
+    }
+
+    // Get template name
+    let template_name = match &args[0] {
+        Value::String(s) => s.to_string(),
+        other => {
+            return Err(format!(
+                "{name}() template name must be a string, got {}",
+                other.type_name()
+            ))
+        }
+    };
+
+    // Get data (default to empty hash)
+    let data = if args.len() > 1 {
+        args[1].clone()
+    } else {
+        Value::Hash(Rc::new(RefCell::new(HashPairs::default())))
+    };
+
+    // Validate data is a hash
+    if !matches!(data, Value::Hash(_)) {
+        return Err(format!(
+            "{name}() data must be a hash, got {}",
+            data.type_name()
+        ));
+    }
+
+    // Get options (layout, status, etc.)
+    let options = if args.len() > 2 {
+        match &args[2] {
+            Value::Hash(h) => Some(h.clone()),
+            other => {
+                return Err(format!(
+                    "{name}() options must be a hash, got {}",
+                    other.type_name()
+                ))
+            }
+        }
+    } else {
+        None
+    };
+
+    // Extract layout option - check options hash first, then data hash
+    let layout = if let Some(opts) = &options {
+        opts.borrow().get(&StrKey("layout")).cloned()
+    } else {
+        None
+    };
+
+    // If not found in options, check data hash for layout key
+    let layout = if layout.is_none() {
+        if let Value::Hash(data_hash) = &data {
+            data_hash.borrow().get(&StrKey("layout")).cloned()
+        } else {
+            None
+        }
+    } else {
+        layout
+    };
+
+    // Still nothing? Fall back to the controller's registered
+    // layout — the `static { this.layout = "..." }` declaration on
+    // the controller class. This is the last stop before the
+    // "application" default, so an explicit `layout` in the
+    // `render()` call or data hash always wins.
+    let layout = if layout.is_none() {
+        get_current_controller_registered_layout()
+    } else {
+        layout
+    };
+
+    // Process layout value
+    let layout = match layout {
+        Some(Value::String(s)) => Some(Some(s.to_string())),
+        Some(Value::Bool(false)) => Some(None), // layout: false
+        Some(Value::Null) => Some(None),
+        None => None, // No layout specified
+        _ => None,
+    };
+
+    // Extract status option (default 200)
+    let status = if let Some(opts) = &options {
+        if let Some(Value::Int(n)) = opts.borrow().get(&StrKey("status")) {
+            *n
+        } else {
+            200
+        }
+    } else {
+        200
+    };
+
+    Ok(RenderArgs {
+        template_name,
+        data,
+        layout,
+        status,
+    })
+}
+
 /// Register template-related builtin functions.
 pub fn register_template_builtins(env: &mut Environment) {
     // render(template, data, options?) - Render a template with data
@@ -1986,34 +2312,15 @@ pub fn register_template_builtins(env: &mut Environment) {
     env.define(
         "render".to_string(),
         Value::NativeFunction(NativeFunction::new("render", None, |args| {
-            if args.is_empty() {
-                return Err("render() requires at least 1 argument (template name)".to_string());
-            }
-
-            // Get template name
-            let template_name = match &args[0] {
-                Value::String(s) => s.clone(),
-                other => {
-                    return Err(format!(
-                        "render() template name must be a string, got {}",
-                        other.type_name()
-                    ))
-                }
-            };
-
-            // Get data (default to empty hash)
-            let data = if args.len() > 1 {
-                args[1].clone()
-            } else {
-                Value::Hash(Rc::new(RefCell::new(HashPairs::default())))
-            };
-
-            // Validate data is a hash
-            if !matches!(data, Value::Hash(_)) {
-                return Err(format!(
-                    "render() data must be a hash, got {}",
-                    data.type_name()
-                ));
+            let RenderArgs {
+                template_name,
+                data,
+                layout,
+                status,
+            } = render_args("render", &args)?;
+            // `fresh_when` / `stale?` found the client's copy current.
+            if super::http_cache::is_not_modified() {
+                return Ok(super::http_cache::not_modified_response());
             }
 
             // Resolve any futures in the data before rendering
@@ -2032,70 +2339,6 @@ pub fn register_template_builtins(env: &mut Environment) {
                     None
                 };
 
-            // Get options (layout, status, etc.)
-            let options = if args.len() > 2 {
-                match &args[2] {
-                    Value::Hash(h) => Some(h.clone()),
-                    other => {
-                        return Err(format!(
-                            "render() options must be a hash, got {}",
-                            other.type_name()
-                        ))
-                    }
-                }
-            } else {
-                None
-            };
-
-            // Extract layout option - check options hash first, then data hash
-            let layout = if let Some(opts) = &options {
-                opts.borrow().get(&StrKey("layout")).cloned()
-            } else {
-                None
-            };
-
-            // If not found in options, check data hash for layout key
-            let layout = if layout.is_none() {
-                if let Value::Hash(data_hash) = &data {
-                    data_hash.borrow().get(&StrKey("layout")).cloned()
-                } else {
-                    None
-                }
-            } else {
-                layout
-            };
-
-            // Still nothing? Fall back to the controller's registered
-            // layout — the `static { this.layout = "..." }` declaration on
-            // the controller class. This is the last stop before the
-            // "application" default, so an explicit `layout` in the
-            // `render()` call or data hash always wins.
-            let layout = if layout.is_none() {
-                get_current_controller_registered_layout()
-            } else {
-                layout
-            };
-
-            // Process layout value
-            let layout = match layout {
-                Some(Value::String(s)) => Some(Some(s)),
-                Some(Value::Bool(false)) => Some(None), // layout: false
-                Some(Value::Null) => Some(None),
-                None => None, // No layout specified
-                _ => None,
-            };
-
-            // Extract status option (default 200)
-            let status = if let Some(opts) = &options {
-                if let Some(Value::Int(n)) = opts.borrow().get(&StrKey("status")) {
-                    *n
-                } else {
-                    200
-                }
-            } else {
-                200
-            };
-
             // Get template cache and render
             let cache = get_template_cache()?;
 
@@ -2151,6 +2394,46 @@ pub fn register_template_builtins(env: &mut Environment) {
         })),
     );
 
+    // render_stream(template, data, options?) - Like render, but the response
+    // is streamed: the layout head goes out before the view renders
+    env.define(
+        "render_stream".to_string(),
+        Value::NativeFunction(NativeFunction::new("render_stream", None, |args| {
+            let RenderArgs {
+                template_name,
+                data,
+                layout,
+                status,
+            } = render_args("render_stream", &args)?;
+            // `fresh_when` / `stale?` found the client's copy current.
+            if super::http_cache::is_not_modified() {
+                return Ok(super::http_cache::not_modified_response());
+            }
+            get_template_cache()?;
+
+            // The view renders after the action has returned, so everything
+            // it needs from the request is gathered now. Futures are left
+            // to resolve once the head has been sent.
+            inject_request_context(&data);
+            inject_controller_instance_vars(&data);
+            inject_template_helpers(&data);
+            let request = get_current_request();
+            clear_current_request();
+
+            Ok(crate::interpreter::builtins::streaming::set_pending_render(
+                StreamRender {
+                    template_name,
+                    data,
+                    layout,
+                    request,
+                    request_host: crate::interpreter::builtins::named_routes::current_request_host(
+                    ),
+                },
+                status,
+            ))
+        })),
+    );
+
     // render_partial(name, data?) - Render a partial template (no layout).
     // Also exposed as the shorter alias `partial(...)`.
     let render_partial_fn = NativeFunction::new("render_partial", None, |args| {
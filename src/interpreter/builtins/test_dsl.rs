@@ -11,6 +11,51 @@ use std::rc::Rc;
 pub struct TestDefinition {
     pub name: String,
     pub body: Value,
+    /// The enclosing suite names, outermost first, then `name`, joined by
+    /// spaces: what `soli test --filter` matches against.
+    pub full_name: String,
+    /// Tags from `tags: [...]` on the test and on every enclosing suite.
+    pub tags: Vec<String>,
+    /// Set when the test is declared not to run.
+    pub mark: Option<TestMark>,
+    /// What the body is called with: one case of an `each` table.
+    pub args: Vec<Value>,
+}
+
+/// Why a declared test does not run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TestMark {
+    /// `xit`/`xtest`/`xspecify`, or a test in an `xdescribe`/`xcontext`.
+    Skip,
+    /// `todo("name")`: a test still to be written.
+    Todo,
+}
+
+impl TestDefinition {
+    /// Declare a test named `name` in `suite`.
+    pub fn new(name: String, tags: Vec<String>, body: Value, suite: &TestSuite) -> Self {
+        Self {
+            full_name: qualified_name(&suite.full_name, &name),
+            tags: merged_tags(&suite.tags, tags),
+            mark: suite.skipped.then_some(TestMark::Skip),
+            args: Vec::new(),
+            name,
+            body,
+        }
+    }
+
+    /// The same test, marked not to run (a skipped suite's mark wins).
+    pub fn marked(mut self, mark: TestMark) -> Self {
+        self.mark.get_or_insert(mark);
+        self
+    }
+
+    /// Whether the `soli test` filter, if any, selects this test.
+    pub fn is_selected(&self) -> bool {
+        TEST_FILTER
+            .get()
+            .is_none_or(|filter| filter.selects(&self.full_name, &self.tags))
+    }
 }
 
 #[derive(Clone)]
@@ -26,6 +71,240 @@ pub struct TestSuite {
     /// every test in it (and every suite nested in it) starts in. `None` means
     /// the default size.
     pub viewport: Option<crate::interpreter::builtins::browser::Viewport>,
+    /// The rest of the suite body — `let`s, helper `fn`s — run once in the
+    /// suite's own scope before its tests. Tests, hooks and nested suites
+    /// close over that scope, so a `let` in a `describe` is visible (and
+    /// shared) inside every `test` in it.
+    pub scope: Vec<crate::ast::Stmt>,
+    /// The `describe` closure itself, recorded when the suite is declared
+    /// at run time rather than read off the AST. The VM test runner calls it
+    /// to populate the suite; the interpreter runner leaves it `None`.
+    pub body: Option<Value>,
+    /// The enclosing suite names and this one's, joined by spaces.
+    pub full_name: String,
+    /// Tags from `tags: [...]` on this suite and every enclosing one.
+    pub tags: Vec<String>,
+    /// `xdescribe`/`xcontext`, or nested in one: none of its tests run.
+    pub skipped: bool,
+}
+
+impl TestSuite {
+    /// An empty suite named `name`, declared in `parent` (`None` at the top
+    /// level).
+    pub fn new(
+        name: String,
+        tags: Vec<String>,
+        parent: Option<&TestSuite>,
+        body: Option<Value>,
+    ) -> Self {
+        let (parent_name, parent_tags) = match parent {
+            Some(parent) => (parent.full_name.as_str(), parent.tags.as_slice()),
+            None => ("", &[][..]),
+        };
+        Self {
+            full_name: qualified_name(parent_name, &name),
+            tags: merged_tags(parent_tags, tags),
+            skipped: parent.is_some_and(|parent| parent.skipped),
+            name,
+            tests: Vec::new(),
+            before_each: None,
+            after_each: None,
+            before_all: None,
+            after_all: None,
+            nested_suites: Vec::new(),
+            viewport: None,
+            scope: Vec::new(),
+            body,
+        }
+    }
+
+    /// Whether the filter selects any test in this suite or one nested in
+    /// it. Suites declared while running a suite body (the VM runner) are
+    /// only known once that body has run.
+    pub fn has_selected_tests(&self) -> bool {
+        self.tests.iter().any(TestDefinition::is_selected)
+            || self.nested_suites.iter().any(TestSuite::has_selected_tests)
+    }
+}
+
+fn qualified_name(parent: &str, name: &str) -> String {
+    if parent.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} {}", parent, name)
+    }
+}
+
+fn merged_tags(inherited: &[String], own: Vec<String>) -> Vec<String> {
+    let mut tags = inherited.to_vec();
+    for tag in own {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Which tests `soli test --filter/--tag/--exclude-tag` runs.
+#[derive(Debug, Default)]
+pub struct TestFilter {
+    /// Matched, case-insensitively, against a test's full name.
+    pattern: Option<regex::Regex>,
+    /// A test must carry at least one of these, when there are any.
+    tags: Vec<String>,
+    /// A test carrying any of these is left out.
+    exclude_tags: Vec<String>,
+}
+
+impl TestFilter {
+    pub fn new(
+        pattern: Option<&str>,
+        tags: Vec<String>,
+        exclude_tags: Vec<String>,
+    ) -> Result<Self, String> {
+        let pattern = pattern
+            .map(|p| {
+                regex::RegexBuilder::new(p)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| format!("invalid --filter pattern: {}", e))
+            })
+            .transpose()?;
+        Ok(Self {
+            pattern,
+            tags,
+            exclude_tags,
+        })
+    }
+
+    pub fn selects(&self, full_name: &str, tags: &[String]) -> bool {
+        self.pattern.as_ref().is_none_or(|p| p.is_match(full_name))
+            && (self.tags.is_empty() || self.tags.iter().any(|t| tags.contains(t)))
+            && !self.exclude_tags.iter().any(|t| tags.contains(t))
+    }
+}
+
+static TEST_FILTER: std::sync::OnceLock<TestFilter> = std::sync::OnceLock::new();
+
+/// Restrict the tests this (test-runner) process runs. Process-global, like
+/// `--seed`: every worker thread reads it.
+pub fn set_test_filter(filter: TestFilter) {
+    let _ = TEST_FILTER.set(filter);
+}
+
+/// The tests of a file that were skipped or left pending rather than run,
+/// by full name (with the reason given to `skip`/`pending`, if any).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TestOutcomes {
+    pub skipped: Vec<String>,
+    pub pending: Vec<String>,
+}
+
+thread_local! {
+    static TEST_OUTCOMES: RefCell<TestOutcomes> = RefCell::new(TestOutcomes::default());
+}
+
+/// Record `test` as not run: marked, or ended by `skip`/`pending`.
+pub fn record_not_run(test: &TestDefinition, mark: TestMark, reason: &str) {
+    let name = if reason.is_empty() {
+        test.full_name.clone()
+    } else {
+        format!("{} ({})", test.full_name, reason)
+    };
+    TEST_OUTCOMES.with(|outcomes| {
+        let mut outcomes = outcomes.borrow_mut();
+        match mark {
+            TestMark::Skip => outcomes.skipped.push(name),
+            TestMark::Todo => outcomes.pending.push(name),
+        }
+    });
+}
+
+/// Record every selected test of `suite`, and of the suites nested in it, as
+/// skipped.
+pub fn record_skipped_suite(suite: &TestSuite) {
+    for test in suite.tests.iter().filter(|t| t.is_selected()) {
+        record_not_run(test, test.mark.unwrap_or(TestMark::Skip), "");
+    }
+    for nested in &suite.nested_suites {
+        record_skipped_suite(nested);
+    }
+}
+
+/// What this thread's test run skipped or left pending since the last call.
+pub fn take_test_outcomes() -> TestOutcomes {
+    TEST_OUTCOMES.with(|outcomes| std::mem::take(&mut *outcomes.borrow_mut()))
+}
+
+/// Why a test ended early without failing, if `error` is a `skip(...)` or
+/// `pending(...)` rather than a failure.
+pub fn not_run_reason(error: &crate::error::RuntimeError) -> Option<(TestMark, String)> {
+    error
+        .skipped_test_reason()
+        .map(|reason| (TestMark::Skip, reason))
+        .or_else(|| {
+            error
+                .pending_test_reason()
+                .map(|reason| (TestMark::Todo, reason))
+        })
+}
+
+/// Split a `test`/`it`/`describe` call into its name, `tags: [...]` and body:
+/// `(name, body)` or `(name, {"tags": [...]}, body)` — named arguments reach a
+/// native as a trailing hash, ahead of a trailing block.
+fn declaration_args(
+    builtin: &str,
+    args: Vec<Value>,
+) -> Result<(String, Vec<String>, Value), String> {
+    let mut args = args.into_iter();
+    let name = match args.next() {
+        Some(Value::String(s)) => s.to_string(),
+        _ => return Err(format!("{} requires string name", builtin)),
+    };
+    let (tags, body) = tags_and_body(builtin, args.collect())
+        .map_err(|_| format!("{} takes a name, optional tags: [...] and a body", builtin))?;
+    Ok((name, tags, body))
+}
+
+/// The `tags: [...]` and body that end a declaration's arguments, in either
+/// order.
+fn tags_and_body(builtin: &str, rest: Vec<Value>) -> Result<(Vec<String>, Value), String> {
+    let (options, body) = match rest.as_slice() {
+        [body] => (None, body.clone()),
+        [Value::Hash(options), body] | [body, Value::Hash(options)] => {
+            (Some(options.clone()), body.clone())
+        }
+        _ => return Err(format!("{} takes optional tags: [...] and a body", builtin)),
+    };
+    let mut tags = Vec::new();
+    if let Some(options) = options {
+        match options.borrow().get(&HashKey::String("tags".into())) {
+            Some(Value::Array(items)) => {
+                for item in items.borrow().iter() {
+                    match item {
+                        Value::String(s) => tags.push(s.to_string()),
+                        Value::Symbol(s) => tags.push(s.to_string()),
+                        other => {
+                            return Err(format!(
+                                "{} tags must be strings, got {}",
+                                builtin,
+                                other.type_name()
+                            ))
+                        }
+                    }
+                }
+            }
+            Some(other) => {
+                return Err(format!(
+                    "{} tags must be an array, got {}",
+                    builtin,
+                    other.type_name()
+                ))
+            }
+            None => {}
+        }
+    }
+    Ok((tags, body))
 }
 
 thread_local! {
@@ -431,6 +710,7 @@ pub fn register_expectation_class(env: &mut Environment) {
     let expectation_class = Class {
         name: "Expectation".to_string(),
         superclass: None,
+        interfaces: Vec::new(),
         methods: Rc::new(RefCell::new(HashMap::new())),
         static_methods: HashMap::new(),
         native_static_methods: HashMap::new(),
@@ -447,6 +727,8 @@ pub fn register_expectation_class(env: &mut Environment) {
         vm_methods: Rc::new(RefCell::new(HashMap::default())),
         vm_static_methods: Rc::new(RefCell::new(HashMap::default())),
         model_subclass_memo: std::cell::Cell::new(None),
+        struct_fields: None,
+        decorators: None,
     };
 
     let expectation_class_rc = Rc::new(expectation_class);
@@ -461,86 +743,150 @@ pub fn register_expectation_class(env: &mut Environment) {
     );
 }
 
+/// `describe`/`context`: record a nested suite, with its body closure, on
+/// the suite currently being populated. Outside a suite body (the top-level
+/// pass the interpreter runner makes) there is nothing to attach to, and the
+/// call is a no-op — that runner reads its suites off the AST instead.
+/// `xdescribe`/`xcontext` declare a skipped one.
+fn declare_suite(builtin: &str, args: Vec<Value>, skipped: bool) -> Result<Value, String> {
+    let (name, tags, body) = declaration_args(builtin, args)?;
+    TEST_SUITES.with(|suites| {
+        if let Some(current) = suites.borrow_mut().last_mut() {
+            let mut suite = TestSuite::new(name, tags, Some(current), Some(body));
+            suite.skipped |= skipped;
+            current.nested_suites.push(suite);
+        }
+    });
+    Ok(Value::Null)
+}
+
+/// `test`/`it`/`specify`: record a test on the suite being populated.
+/// `xtest`/`xit`/`xspecify` record a skipped one.
+fn declare_test(builtin: &str, args: Vec<Value>, mark: Option<TestMark>) -> Result<Value, String> {
+    let (name, tags, body) = declaration_args(builtin, args)?;
+    push_test(name, tags, body, mark);
+    Ok(Value::Null)
+}
+
+fn push_test(name: String, tags: Vec<String>, body: Value, mark: Option<TestMark>) {
+    TEST_SUITES.with(|suites| {
+        if let Some(current) = suites.borrow_mut().last_mut() {
+            let mut test = TestDefinition::new(name, tags, body, current);
+            if let Some(mark) = mark {
+                test = test.marked(mark);
+            }
+            current.tests.push(test);
+        }
+    });
+}
+
+/// `each(cases, body)` / `it_each(name, cases, body)`: record one test per
+/// case of the table, calling the body with that case's values (an array case
+/// is spread into the parameters). Each is named after its arguments, so a
+/// failure says which case it was.
+fn declare_cases(builtin: &str, args: Vec<Value>, named: bool) -> Result<Value, String> {
+    let mut args = args.into_iter();
+    let name = if named {
+        match args.next() {
+            Some(Value::String(s)) => Some(s.to_string()),
+            _ => return Err(format!("{} requires string name", builtin)),
+        }
+    } else {
+        None
+    };
+    let cases = match args.next() {
+        Some(Value::Array(cases)) => cases.borrow().clone(),
+        _ => return Err(format!("{} requires an array of cases", builtin)),
+    };
+    let (tags, body) = tags_and_body(builtin, args.collect())?;
+    TEST_SUITES.with(|suites| {
+        if let Some(current) = suites.borrow_mut().last_mut() {
+            for case in cases {
+                let args = case_args(case);
+                let name = case_name(name.as_deref(), &args);
+                let mut test = TestDefinition::new(name, tags.clone(), body.clone(), current);
+                test.args = args;
+                current.tests.push(test);
+            }
+        }
+    });
+    Ok(Value::Null)
+}
+
+/// What the body of an `each` table is called with for `case`: an array
+/// case spread into the parameters, any other value as the only argument.
+pub fn case_args(case: Value) -> Vec<Value> {
+    match case {
+        Value::Array(values) => values.borrow().clone(),
+        value => vec![value],
+    }
+}
+
+/// The name of the case of an `each`/`it_each` table called with `args`:
+/// `adds (4, 5, 9)`, or just the arguments when the table has no name.
+pub fn case_name(name: Option<&str>, args: &[Value]) -> String {
+    let label = case_label(args);
+    match name {
+        Some(name) => format!("{} {}", name, label),
+        None => label,
+    }
+}
+
+/// `(1, "two", [3])`: a case's arguments, strings quoted.
+fn case_label(args: &[Value]) -> String {
+    let args: Vec<String> = args
+        .iter()
+        .map(|arg| match arg {
+            Value::String(s) => format!("{:?}", s.as_str()),
+            other => other.to_string(),
+        })
+        .collect();
+    format!("({})", args.join(", "))
+}
+
+/// `skip(reason)` / `pending(reason)`: end the running test as skipped or
+/// pending, with an optional reason.
+fn not_run_signal(builtin: &str, marker: &str, args: &[Value]) -> Result<Value, String> {
+    let reason = match args {
+        [] => String::new(),
+        [Value::String(reason)] => reason.to_string(),
+        _ => return Err(format!("{} takes an optional reason string", builtin)),
+    };
+    Err(format!("{}{}", marker, reason))
+}
+
+/// Open `suite` for population: `test`, the hooks and nested `describe`s
+/// called until the matching [`close_suite`] attach to it.
+pub fn open_suite(suite: TestSuite) {
+    TEST_SUITES.with(|suites| suites.borrow_mut().push(suite));
+}
+
+/// Close the suite opened last and hand it back, populated.
+pub fn close_suite() -> Option<TestSuite> {
+    TEST_SUITES.with(|suites| suites.borrow_mut().pop())
+}
+
 pub fn register_test_builtins(env: &mut Environment) {
     register_expectation_class(env);
 
     env.define(
         "test".to_string(),
-        Value::NativeFunction(NativeFunction::new("test", Some(2), |args| {
-            if args.len() >= 2 {
-                let test_name = match &args[0] {
-                    Value::String(s) => s.clone(),
-                    _ => return Err("test requires string name".to_string()),
-                };
-                let test_body = args[1].clone();
-
-                TEST_SUITES.with(|suites| {
-                    let mut suites = suites.borrow_mut();
-                    if let Some(current) = suites.last_mut() {
-                        current.tests.push(TestDefinition {
-                            name: test_name.to_string(),
-                            body: test_body,
-                        });
-                    }
-                });
-            }
-            Ok(Value::Null)
+        Value::NativeFunction(NativeFunction::new("test", None, |args| {
+            declare_test("test", args, None)
         })),
     );
 
     env.define(
         "describe".to_string(),
-        Value::NativeFunction(NativeFunction::new("describe", Some(2), |args| {
-            if args.len() >= 2 {
-                let suite_name = match &args[0] {
-                    Value::String(s) => s.clone(),
-                    _ => return Err("describe requires string name".to_string()),
-                };
-
-                let new_suite = TestSuite {
-                    name: suite_name.clone().to_string(),
-                    tests: Vec::new(),
-                    before_each: None,
-                    after_each: None,
-                    before_all: None,
-                    after_all: None,
-                    nested_suites: Vec::new(),
-                    viewport: None,
-                };
-
-                TEST_SUITES.with(|suites| {
-                    suites.borrow_mut().push(new_suite);
-                });
-            }
-            Ok(Value::Null)
+        Value::NativeFunction(NativeFunction::new("describe", None, |args| {
+            declare_suite("describe", args, false)
         })),
     );
 
     env.define(
         "context".to_string(),
-        Value::NativeFunction(NativeFunction::new("context", Some(2), |args| {
-            if args.len() >= 2 {
-                let suite_name = match &args[0] {
-                    Value::String(s) => s.clone(),
-                    _ => return Err("context requires string name".to_string()),
-                };
-
-                let new_suite = TestSuite {
-                    name: suite_name.clone().to_string(),
-                    tests: Vec::new(),
-                    before_each: None,
-                    after_each: None,
-                    before_all: None,
-                    after_all: None,
-                    nested_suites: Vec::new(),
-                    viewport: None,
-                };
-
-                TEST_SUITES.with(|suites| {
-                    suites.borrow_mut().push(new_suite);
-                });
-            }
-            Ok(Value::Null)
+        Value::NativeFunction(NativeFunction::new("context", None, |args| {
+            declare_suite("context", args, false)
         })),
     );
 
@@ -606,66 +952,84 @@ pub fn register_test_builtins(env: &mut Environment) {
 
     env.define(
         "pending".to_string(),
-        Value::NativeFunction(NativeFunction::new("pending", Some(0), |_args| {
-            Err("PENDING".to_string())
+        Value::NativeFunction(NativeFunction::new("pending", None, |args| {
+            not_run_signal(
+                "pending",
+                crate::error::RuntimeError::PENDING_TEST_MARKER,
+                &args,
+            )
+        })),
+    );
+
+    env.define(
+        "todo".to_string(),
+        Value::NativeFunction(NativeFunction::new("todo", None, |args| {
+            let mut args = args.into_iter();
+            let name = match args.next() {
+                Some(Value::String(s)) => s.to_string(),
+                _ => return Err("todo requires string name".to_string()),
+            };
+            if args.next().is_some() {
+                return Err("todo takes only a name: it declares a test still to write".to_string());
+            }
+            push_test(name, Vec::new(), Value::Null, Some(TestMark::Todo));
+            Ok(Value::Null)
         })),
     );
 
     env.define(
         "skip".to_string(),
-        Value::NativeFunction(NativeFunction::new("skip", Some(0), |_args| {
-            Err("SKIPPED".to_string())
+        Value::NativeFunction(NativeFunction::new("skip", None, |args| {
+            not_run_signal("skip", crate::error::RuntimeError::SKIP_TEST_MARKER, &args)
         })),
     );
 
     env.define(
         "it".to_string(),
-        Value::NativeFunction(NativeFunction::new("it", Some(2), |args| {
-            if args.len() >= 2 {
-                let test_name = match &args[0] {
-                    Value::String(s) => s.clone(),
-                    _ => return Err("it requires string name".to_string()),
-                };
-                let test_body = args[1].clone();
-
-                TEST_SUITES.with(|suites| {
-                    let mut suites = suites.borrow_mut();
-                    if let Some(current) = suites.last_mut() {
-                        current.tests.push(TestDefinition {
-                            name: test_name.to_string(),
-                            body: test_body,
-                        });
-                    }
-                });
-            }
-            Ok(Value::Null)
+        Value::NativeFunction(NativeFunction::new("it", None, |args| {
+            declare_test("it", args, None)
         })),
     );
 
     env.define(
         "specify".to_string(),
-        Value::NativeFunction(NativeFunction::new("specify", Some(2), |args| {
-            if args.len() >= 2 {
-                let test_name = match &args[0] {
-                    Value::String(s) => s.clone(),
-                    _ => return Err("specify requires string name".to_string()),
-                };
-                let test_body = args[1].clone();
+        Value::NativeFunction(NativeFunction::new("specify", None, |args| {
+            declare_test("specify", args, None)
+        })),
+    );
 
-                TEST_SUITES.with(|suites| {
-                    let mut suites = suites.borrow_mut();
-                    if let Some(current) = suites.last_mut() {
-                        current.tests.push(TestDefinition {
-                            name: test_name.to_string(),
-                            body: test_body,
-                        });
-                    }
-                });
-            }
-            Ok(Value::Null)
+    env.define(
+        "each".to_string(),
+        Value::NativeFunction(NativeFunction::new("each", None, |args| {
+            declare_cases("each", args, false)
         })),
     );
 
+    for builtin in ["test_each", "it_each"] {
+        env.define(
+            builtin.to_string(),
+            Value::NativeFunction(NativeFunction::new(builtin, None, move |args| {
+                declare_cases(builtin, args, true)
+            })),
+        );
+    }
+
+    for (builtin, test) in [("xtest", true), ("xit", true), ("xspecify", true)]
+        .into_iter()
+        .chain([("xdescribe", false), ("xcontext", false)])
+    {
+        env.define(
+            builtin.to_string(),
+            Value::NativeFunction(NativeFunction::new(builtin, None, move |args| {
+                if test {
+                    declare_test(builtin, args, Some(TestMark::Skip))
+                } else {
+                    declare_suite(builtin, args, true)
+                }
+            })),
+        );
+    }
+
     env.define(
         "expect".to_string(),
         Value::NativeFunction(NativeFunction::new("expect", Some(1), |args| {
@@ -16,4 +16,13 @@ pub fn register_test_helpers(env: &mut Environment) {
             )
         })),
     );
+
+    // with_cassette("name") { ... } — record/replay the block's HTTP calls.
+    // Handled by the `evaluate_call` interceptor, like with_transaction.
+    env.define(
+        "with_cassette".to_string(),
+        Value::NativeFunction(NativeFunction::new("with_cassette", None, |_args| {
+            Err("with_cassette() expects a block: with_cassette(\"name\") { ... }".to_string())
+        })),
+    );
 }
@@ -156,6 +156,11 @@ pub fn is_test_runner_process() -> bool {
     crate::interpreter::builtins::http_class::ssrf_test_mode()
 }
 
+/// Request header the test runner uses to carry a frozen clock
+/// (`freeze_time`, `travel_to`, `travel`) into the test server, whose workers
+/// pin their clock to it for the length of the request.
+pub const FROZEN_NOW_HEADER: &str = "x-soli-test-frozen-now";
+
 /// Get the test server port. Prefers the thread-local override (set by
 /// the parallel test runner) so each worker reaches its own subprocess.
 pub fn get_test_server_port() -> Option<u16> {
@@ -0,0 +1,363 @@
+//! Upload fixtures for request specs.
+//!
+//! `fixture_file_upload("tests/files/avatar.png", "image/png")` names a file
+//! on disk. When the data hash given to `post`/`put`/`patch`/`request` holds
+//! one, or is wrapped in `multipart(...)`, the request goes out as
+//! `multipart/form-data` and each file is streamed from disk into the socket
+//! instead of being read into the spec first.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::interpreter::environment::Environment;
+use crate::interpreter::value::{HashKey, HashPairs, NativeFunction, Value};
+
+/// Marks a hash as an upload fixture.
+const UPLOAD_MARKER: &str = "__upload__";
+/// Wraps a data hash that must be sent as multipart even without files.
+const MULTIPART_MARKER: &str = "__multipart__";
+
+pub fn register_upload_fixture_builtins(env: &mut Environment) {
+    // fixture_file_upload(path, content_type = null, filename = null)
+    env.define(
+        "fixture_file_upload".to_string(),
+        Value::NativeFunction(NativeFunction::new("fixture_file_upload", None, |args| {
+            let path = match args.first() {
+                Some(Value::String(s)) => s.to_string(),
+                _ => return Err("fixture_file_upload(path) expects a string path".to_string()),
+            };
+            let content_type = optional_string(args.get(1), "content_type")?;
+            let filename = optional_string(args.get(2), "filename")?;
+            fixture_file_upload(&path, content_type, filename)
+        })),
+    );
+
+    // multipart(fields) - send `fields` as multipart/form-data
+    env.define(
+        "multipart".to_string(),
+        Value::NativeFunction(NativeFunction::new("multipart", Some(1), |args| {
+            if !matches!(args[0], Value::Hash(_)) {
+                return Err("multipart(fields) expects a hash".to_string());
+            }
+            let mut wrapper = HashPairs::default();
+            wrapper.insert(HashKey::String(MULTIPART_MARKER.into()), args[0].clone());
+            Ok(Value::Hash(Rc::new(RefCell::new(wrapper))))
+        })),
+    );
+}
+
+fn optional_string(value: Option<&Value>, name: &str) -> Result<Option<String>, String> {
+    match value {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.to_string())),
+        Some(_) => Err(format!("fixture_file_upload: {} must be a string", name)),
+    }
+}
+
+fn fixture_file_upload(
+    path: &str,
+    content_type: Option<String>,
+    filename: Option<String>,
+) -> Result<Value, String> {
+    let file_path = Path::new(path);
+    let size = std::fs::metadata(file_path)
+        .map_err(|e| format!("fixture_file_upload: cannot read {}: {}", path, e))?
+        .len();
+    // Guessed from the extension, without the `; charset=` the static file
+    // server adds for text types.
+    let content_type = content_type.unwrap_or_else(|| {
+        let mime = crate::serve::server_constants::get_mime_type(file_path);
+        mime.split(';').next().unwrap_or(mime).to_string()
+    });
+    let filename = filename.unwrap_or_else(|| {
+        file_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string())
+    });
+
+    let mut hash = HashPairs::default();
+    hash.insert(HashKey::String(UPLOAD_MARKER.into()), Value::Bool(true));
+    hash.insert(
+        HashKey::String("path".into()),
+        Value::String(path.to_string().into()),
+    );
+    hash.insert(
+        HashKey::String("filename".into()),
+        Value::String(filename.into()),
+    );
+    hash.insert(
+        HashKey::String("content_type".into()),
+        Value::String(content_type.into()),
+    );
+    hash.insert(HashKey::String("size".into()), Value::Int(size as i64));
+    Ok(Value::Hash(Rc::new(RefCell::new(hash))))
+}
+
+/// A `multipart/form-data` body whose file parts stay on disk until sent.
+#[derive(Debug)]
+pub(crate) struct MultipartBody {
+    boundary: String,
+    parts: Vec<Part>,
+}
+
+#[derive(Debug)]
+struct Part {
+    head: String,
+    body: PartBody,
+}
+
+#[derive(Debug)]
+enum PartBody {
+    Text(String),
+    File(PathBuf),
+}
+
+impl MultipartBody {
+    /// The multipart body for request data, or `None` when the data holds no
+    /// upload fixture and isn't wrapped in `multipart(...)`.
+    pub(crate) fn from_value(data: &Value) -> Result<Option<MultipartBody>, String> {
+        let fields = match data {
+            Value::Hash(hash) => {
+                let hash = hash.borrow();
+                match hash.get(&HashKey::String(MULTIPART_MARKER.into())) {
+                    Some(fields) => fields.clone(),
+                    None if contains_upload(data) => data.clone(),
+                    None => return Ok(None),
+                }
+            }
+            _ => return Ok(None),
+        };
+        let mut body = MultipartBody {
+            boundary: format!(
+                "----SoliTestBoundary{:x}",
+                std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_nanos())
+                    .unwrap_or(0)
+            ),
+            parts: Vec::new(),
+        };
+        if let Value::Hash(hash) = &fields {
+            for (key, value) in hash.borrow().iter() {
+                body.add(&key_name(key), value)?;
+            }
+        }
+        Ok(Some(body))
+    }
+
+    pub(crate) fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// The exact byte length `write_to` will produce.
+    pub(crate) fn content_length(&self) -> Result<u64, String> {
+        let mut total = 0u64;
+        for part in &self.parts {
+            total += self.delimiter().len() as u64 + part.head.len() as u64 + 2;
+            total += match &part.body {
+                PartBody::Text(text) => text.len() as u64,
+                PartBody::File(path) => std::fs::metadata(path)
+                    .map_err(|e| format!("cannot read {}: {}", path.display(), e))?
+                    .len(),
+            };
+        }
+        Ok(total + self.closing().len() as u64)
+    }
+
+    pub(crate) fn write_to(&self, out: &mut impl Write) -> io::Result<()> {
+        for part in &self.parts {
+            out.write_all(self.delimiter().as_bytes())?;
+            out.write_all(part.head.as_bytes())?;
+            match &part.body {
+                PartBody::Text(text) => out.write_all(text.as_bytes())?,
+                PartBody::File(path) => {
+                    io::copy(&mut File::open(path)?, out)?;
+                }
+            }
+            out.write_all(b"\r\n")?;
+        }
+        out.write_all(self.closing().as_bytes())
+    }
+
+    fn delimiter(&self) -> String {
+        format!("--{}\r\n", self.boundary)
+    }
+
+    fn closing(&self) -> String {
+        format!("--{}--\r\n", self.boundary)
+    }
+
+    /// Add `value` under `name`: arrays repeat the name, nested hashes use
+    /// Rack-style `name[key]` names, as the server's form parser expects.
+    fn add(&mut self, name: &str, value: &Value) -> Result<(), String> {
+        match value {
+            Value::Hash(hash) if is_upload(value) => {
+                let hash = hash.borrow();
+                let field = |key: &str| match hash.get(&HashKey::String(key.into())) {
+                    Some(Value::String(s)) => s.to_string(),
+                    _ => String::new(),
+                };
+                self.parts.push(Part {
+                    head: format!(
+                        "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+                        quote(name),
+                        quote(&field("filename")),
+                        field("content_type")
+                    ),
+                    body: PartBody::File(PathBuf::from(field("path"))),
+                });
+            }
+            Value::Hash(hash) => {
+                for (key, value) in hash.borrow().iter() {
+                    self.add(&format!("{}[{}]", name, key_name(key)), value)?;
+                }
+            }
+            Value::Array(items) => {
+                for item in items.borrow().iter() {
+                    self.add(name, item)?;
+                }
+            }
+            Value::Null => self.add_text(name, String::new()),
+            Value::String(s) => self.add_text(name, s.to_string()),
+            Value::Int(_) | Value::Float(_) | Value::Bool(_) | Value::Decimal(_) => {
+                self.add_text(name, value.to_string())
+            }
+            other => {
+                return Err(format!(
+                    "cannot send a {} as a multipart field ({})",
+                    other.type_name(),
+                    name
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    fn add_text(&mut self, name: &str, text: String) {
+        self.parts.push(Part {
+            head: format!(
+                "Content-Disposition: form-data; name=\"{}\"\r\n\r\n",
+                quote(name)
+            ),
+            body: PartBody::Text(text),
+        });
+    }
+}
+
+fn is_upload(value: &Value) -> bool {
+    match value {
+        Value::Hash(hash) => hash
+            .borrow()
+            .get(&HashKey::String(UPLOAD_MARKER.into()))
+            .is_some_and(|marker| matches!(marker, Value::Bool(true))),
+        _ => false,
+    }
+}
+
+fn contains_upload(value: &Value) -> bool {
+    if is_upload(value) {
+        return true;
+    }
+    match value {
+        Value::Hash(hash) => hash.borrow().iter().any(|(_, v)| contains_upload(v)),
+        Value::Array(items) => items.borrow().iter().any(contains_upload),
+        _ => false,
+    }
+}
+
+fn key_name(key: &HashKey) -> String {
+    match key {
+        HashKey::String(s) | HashKey::Symbol(s) => s.to_string(),
+        HashKey::Int(i) => i.to_string(),
+        HashKey::Bool(b) => b.to_string(),
+        HashKey::Decimal(d) => d.to_string(),
+        HashKey::Null => "null".to_string(),
+    }
+}
+
+fn quote(name: &str) -> String {
+    name.replace('"', "%22").replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(pairs: Vec<(&str, Value)>) -> Value {
+        let mut hash = HashPairs::default();
+        for (key, value) in pairs {
+            hash.insert(HashKey::String(key.into()), value);
+        }
+        Value::Hash(Rc::new(RefCell::new(hash)))
+    }
+
+    fn fixture(contents: &[u8]) -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("avatar.png");
+        std::fs::write(&path, contents).unwrap();
+        (dir, path.to_string_lossy().to_string())
+    }
+
+    #[test]
+    fn plain_data_is_not_multipart() {
+        let data = hash(vec![("name", Value::String("Ann".into()))]);
+        assert!(MultipartBody::from_value(&data).unwrap().is_none());
+    }
+
+    #[test]
+    fn upload_fields_stream_the_file_and_nest_like_rack() {
+        let (_dir, path) = fixture(b"\x89PNG\r\n\x00bytes");
+        let upload = fixture_file_upload(&path, Some("image/png".into()), None).unwrap();
+        let data = hash(vec![(
+            "user",
+            hash(vec![
+                ("name", Value::String("Ann".into())),
+                ("avatar", upload),
+            ]),
+        )]);
+        let body = MultipartBody::from_value(&data).unwrap().unwrap();
+        let mut out = Vec::new();
+        body.write_to(&mut out).unwrap();
+        assert_eq!(out.len() as u64, body.content_length().unwrap());
+
+        let text = String::from_utf8_lossy(&out);
+        assert!(text.contains("name=\"user[name]\"\r\n\r\nAnn\r\n"));
+        assert!(text.contains(
+            "name=\"user[avatar]\"; filename=\"avatar.png\"\r\nContent-Type: image/png\r\n\r\n"
+        ));
+        assert!(out.windows(12).any(|w| w == b"\x89PNG\r\n\x00bytes"));
+        assert!(text.ends_with(&format!("--{}--\r\n", body.boundary)));
+    }
+
+    #[test]
+    fn multipart_wrapper_forces_multipart_and_content_type_is_guessed() {
+        let (_dir, path) = fixture(b"x");
+        let upload = fixture_file_upload(&path, None, Some("me.png".into())).unwrap();
+        if let Value::Hash(h) = &upload {
+            let h = h.borrow();
+            assert_eq!(
+                h.get(&HashKey::String("content_type".into())),
+                Some(&Value::String("image/png".into()))
+            );
+            assert_eq!(h.get(&HashKey::String("size".into())), Some(&Value::Int(1)));
+        }
+
+        let wrapped = hash(vec![(
+            MULTIPART_MARKER,
+            hash(vec![(
+                "tags",
+                Value::Array(Rc::new(RefCell::new(vec![
+                    Value::String("a".into()),
+                    Value::String("b".into()),
+                ]))),
+            )]),
+        )]);
+        let body = MultipartBody::from_value(&wrapped).unwrap().unwrap();
+        assert_eq!(body.parts.len(), 2);
+        assert!(fixture_file_upload("missing/file.png", None, None).is_err());
+    }
+}
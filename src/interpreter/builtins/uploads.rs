@@ -469,6 +469,22 @@ fn upload_blob_to_solidb(
     collection: &str,
     file: &ParsedFile,
 ) -> Result<UploadResult, String> {
+    let client = blob_client(solidb_addr)?;
+
+    let raw_bytes = STANDARD
+        .decode(&file.data_base64)
+        .map_err(|e| format!("Failed to decode multipart data: {}", e))?;
+
+    let blob_id = client
+        .store_blob(collection, &raw_bytes, &file.filename, &file.content_type)
+        .map_err(|e| format!("Failed to store blob: {}", e))?;
+
+    Ok(UploadResult { blob_id })
+}
+
+/// A SoliDB client for blob storage, on `SOLIDB_DATABASE` and authenticated
+/// the way the model layer is.
+pub(crate) fn blob_client(solidb_addr: &str) -> Result<crate::solidb_http::SoliDBClient, String> {
     use crate::solidb_http::SoliDBClient;
 
     let mut client = SoliDBClient::connect(solidb_addr)
@@ -491,16 +507,7 @@ fn upload_blob_to_solidb(
     ) {
         client = client.with_basic_auth(&user, &pass);
     }
-
-    let raw_bytes = STANDARD
-        .decode(&file.data_base64)
-        .map_err(|e| format!("Failed to decode multipart data: {}", e))?;
-
-    let blob_id = client
-        .store_blob(collection, &raw_bytes, &file.filename, &file.content_type)
-        .map_err(|e| format!("Failed to store blob: {}", e))?;
-
-    Ok(UploadResult { blob_id })
+    Ok(client)
 }
 
 /// Image transform query keys that `upload_url` accepts in its options
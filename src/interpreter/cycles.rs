@@ -0,0 +1,475 @@
+//! Cycle collection for reference-counted values.
+//!
+//! Instances, arrays, hashes and scopes are `Rc`s, so data that refers back
+//! to itself (a parent model holding its children, each child holding its
+//! parent; a scope holding a closure that captured it) is never freed. A
+//! short script doesn't notice, but a serve worker leaks every such graph its
+//! requests build.
+//!
+//! The collector finds those graphs by trial deletion. Instances of user
+//! classes and scopes captured by closures are tracked (weakly) as they're
+//! created, since every cycle worth collecting goes through one of them.
+//! A collection walks everything reachable from the tracked values that are
+//! still alive and counts, for each container, the references that come from
+//! inside the walked graph. A container with more references than that is
+//! held from outside (a variable, the stack, a Rust cache), and so is
+//! everything it reaches. The rest is only reachable from itself: each of
+//! those containers is emptied, which breaks the cycles and lets the
+//! reference counts free them.
+//!
+//! Only a reference the walk followed counts as internal, so anything it
+//! doesn't understand keeps a value alive rather than freeing it. Collection
+//! has to run while no Soli code does: `soli serve` workers collect between
+//! requests, once enough tracked values were created since the last run.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::{Rc, Weak};
+
+use crate::interpreter::environment::Environment;
+use crate::interpreter::value::{Function, HashPairs, Instance, Value};
+use crate::vm::upvalue::{Upvalue, VmClosure};
+
+/// Fewest tracked creations between two collections. After a collection the
+/// gap grows to the number of containers it walked, so a worker with a large
+/// live heap doesn't walk it over and over.
+const COLLECT_FLOOR: usize = 10_000;
+/// Fewest tracked entries before dead ones are dropped from the list.
+const PRUNE_FLOOR: usize = 1024;
+
+enum Root {
+    Instance(Weak<RefCell<Instance>>),
+    Scope(Weak<RefCell<Environment>>),
+}
+
+struct Tracker {
+    roots: Vec<Root>,
+    created: usize,
+    collect_at: usize,
+    prune_at: usize,
+}
+
+thread_local! {
+    static TRACKER: RefCell<Tracker> = const {
+        RefCell::new(Tracker {
+            roots: Vec::new(),
+            created: 0,
+            collect_at: COLLECT_FLOOR,
+            prune_at: PRUNE_FLOOR,
+        })
+    };
+}
+
+fn track(root: Root) {
+    TRACKER.with(|tracker| {
+        let mut tracker = tracker.borrow_mut();
+        if tracker.roots.len() >= tracker.prune_at {
+            tracker.roots.retain(|root| match root {
+                Root::Instance(weak) => weak.strong_count() > 0,
+                Root::Scope(weak) => weak.strong_count() > 0,
+            });
+            tracker.prune_at = (tracker.roots.len() * 2).max(PRUNE_FLOOR);
+        }
+        tracker.roots.push(root);
+        tracker.created += 1;
+    });
+}
+
+/// A new instance of a user class, tracked for collection.
+pub fn new_instance(instance: Instance) -> Rc<RefCell<Instance>> {
+    let instance = Rc::new(RefCell::new(instance));
+    track(Root::Instance(Rc::downgrade(&instance)));
+    instance
+}
+
+/// Track `scope`, which a closure being created captures.
+pub fn track_scope(scope: &Rc<RefCell<Environment>>) {
+    // A run of definitions in one scope tracks it once.
+    let repeated = TRACKER.with(|tracker| {
+        matches!(
+            tracker.borrow().roots.last(),
+            Some(Root::Scope(last)) if std::ptr::eq(last.as_ptr(), Rc::as_ptr(scope))
+        )
+    });
+    if !repeated {
+        track(Root::Scope(Rc::downgrade(scope)));
+    }
+}
+
+/// Collect if enough tracked values were created since the last collection.
+/// Returns how many containers were freed.
+pub fn maybe_collect() -> usize {
+    let due = TRACKER.with(|tracker| {
+        let tracker = tracker.borrow();
+        tracker.created >= tracker.collect_at
+    });
+    if due {
+        collect()
+    } else {
+        0
+    }
+}
+
+/// Live instances of user classes on this thread, by class name, and the
+/// number of live scopes captured by closures.
+pub fn live_counts() -> (BTreeMap<String, usize>, usize) {
+    TRACKER.with(|tracker| {
+        let mut instances = BTreeMap::new();
+        let mut scopes = 0;
+        for root in &tracker.borrow().roots {
+            match root {
+                Root::Instance(weak) => {
+                    // An instance mid-update is still alive; it just can't
+                    // be named right now.
+                    if let Some(instance) = weak.upgrade() {
+                        let name = instance
+                            .try_borrow()
+                            .map(|inst| inst.class.name.clone())
+                            .unwrap_or_default();
+                        *instances.entry(name).or_insert(0) += 1;
+                    }
+                }
+                Root::Scope(weak) => {
+                    if weak.strong_count() > 0 {
+                        scopes += 1;
+                    }
+                }
+            }
+        }
+        (instances, scopes)
+    })
+}
+
+/// Free every container that is only reachable from itself. Returns how
+/// many were freed. Must not run while Soli code is executing.
+pub fn collect() -> usize {
+    let roots = TRACKER.with(|tracker| {
+        let mut tracker = tracker.borrow_mut();
+        let mut alive = Vec::new();
+        tracker.roots.retain(|root| match root {
+            Root::Instance(weak) => weak
+                .upgrade()
+                .map(|rc| alive.push(Node::Instance(rc)))
+                .is_some(),
+            Root::Scope(weak) => weak
+                .upgrade()
+                .map(|rc| alive.push(Node::Scope(rc)))
+                .is_some(),
+        });
+        tracker.created = 0;
+        alive
+    });
+
+    let mut graph = Graph::default();
+    for root in roots {
+        graph.add(root);
+    }
+    let mut next = 0;
+    while next < graph.nodes.len() {
+        graph.walk(next);
+        next += 1;
+    }
+    let freed = graph.free_garbage();
+
+    TRACKER.with(|tracker| {
+        tracker.borrow_mut().collect_at = graph.nodes.len().max(COLLECT_FLOOR);
+    });
+    freed
+}
+
+/// A container the walk reached, held by exactly one strong reference.
+enum Node {
+    Instance(Rc<RefCell<Instance>>),
+    Array(Rc<RefCell<Vec<Value>>>),
+    Hash(Rc<RefCell<HashPairs>>),
+    Scope(Rc<RefCell<Environment>>),
+    Function(Rc<Function>),
+    VmClosure(Rc<VmClosure>),
+    Upvalue(Rc<RefCell<Upvalue>>),
+}
+
+impl Node {
+    fn from_value(value: &Value) -> Option<Node> {
+        Some(match value {
+            Value::Instance(rc) => Node::Instance(Rc::clone(rc)),
+            Value::Array(rc) => Node::Array(Rc::clone(rc)),
+            Value::Hash(rc) => Node::Hash(Rc::clone(rc)),
+            Value::Function(rc) => Node::Function(Rc::clone(rc)),
+            Value::VmClosure(rc) => Node::VmClosure(Rc::clone(rc)),
+            _ => return None,
+        })
+    }
+
+    fn address(&self) -> usize {
+        match self {
+            Node::Instance(rc) => Rc::as_ptr(rc) as *const () as usize,
+            Node::Array(rc) => Rc::as_ptr(rc) as *const () as usize,
+            Node::Hash(rc) => Rc::as_ptr(rc) as *const () as usize,
+            Node::Scope(rc) => Rc::as_ptr(rc) as *const () as usize,
+            Node::Function(rc) => Rc::as_ptr(rc) as *const () as usize,
+            Node::VmClosure(rc) => Rc::as_ptr(rc) as *const () as usize,
+            Node::Upvalue(rc) => Rc::as_ptr(rc) as *const () as usize,
+        }
+    }
+
+    fn strong_count(&self) -> usize {
+        match self {
+            Node::Instance(rc) => Rc::strong_count(rc),
+            Node::Array(rc) => Rc::strong_count(rc),
+            Node::Hash(rc) => Rc::strong_count(rc),
+            Node::Scope(rc) => Rc::strong_count(rc),
+            Node::Function(rc) => Rc::strong_count(rc),
+            Node::VmClosure(rc) => Rc::strong_count(rc),
+            Node::Upvalue(rc) => Rc::strong_count(rc),
+        }
+    }
+
+    /// The containers this one references, or `None` when it's borrowed
+    /// and can't be read.
+    fn children(&self) -> Option<Vec<Node>> {
+        let mut children = Vec::new();
+        match self {
+            Node::Instance(rc) => {
+                let instance = rc.try_borrow().ok()?;
+                children.extend(instance.fields.values().filter_map(Node::from_value));
+                if let Some(original) = &instance.original_fields {
+                    children.extend(original.values().filter_map(Node::from_value));
+                }
+                if let Some(changes) = &instance.previous_changes {
+                    for (_, old, new) in changes.iter() {
+                        children.extend(Node::from_value(old));
+                        children.extend(Node::from_value(new));
+                    }
+                }
+            }
+            Node::Array(rc) => {
+                children.extend(rc.try_borrow().ok()?.iter().filter_map(Node::from_value));
+            }
+            Node::Hash(rc) => {
+                children.extend(rc.try_borrow().ok()?.values().filter_map(Node::from_value));
+            }
+            Node::Scope(rc) => {
+                let scope = rc.try_borrow().ok()?;
+                children.extend(
+                    scope
+                        .local_bindings()
+                        .filter_map(|(_, value)| Node::from_value(value)),
+                );
+                if let Some(enclosing) = scope.enclosing_ref() {
+                    children.push(Node::Scope(Rc::clone(enclosing)));
+                }
+                if let Some(data) = scope.data_hash_ref() {
+                    children.push(Node::Hash(Rc::clone(data)));
+                }
+            }
+            Node::Function(func) => {
+                children.push(Node::Scope(Rc::clone(&func.closure)));
+                if let Some(cached) = func.cached_env.try_borrow().ok()?.as_ref() {
+                    children.push(Node::Scope(Rc::clone(cached)));
+                }
+            }
+            Node::VmClosure(closure) => {
+                children.extend(closure.upvalues.iter().cloned().map(Node::Upvalue));
+            }
+            Node::Upvalue(rc) => {
+                if let Upvalue::Closed(value) = &*rc.try_borrow().ok()? {
+                    children.extend(Node::from_value(value));
+                }
+            }
+        }
+        Some(children)
+    }
+
+    /// Drop everything this container holds.
+    fn empty(&self) {
+        match self {
+            Node::Instance(rc) => {
+                let taken = {
+                    let mut instance = rc.borrow_mut();
+                    (
+                        std::mem::take(&mut instance.fields),
+                        instance.original_fields.take(),
+                        instance.previous_changes.take(),
+                    )
+                };
+                drop(taken);
+            }
+            Node::Array(rc) => drop(std::mem::take(&mut *rc.borrow_mut())),
+            Node::Hash(rc) => drop(std::mem::take(&mut *rc.borrow_mut())),
+            Node::Scope(rc) => drop(std::mem::take(&mut *rc.borrow_mut())),
+            Node::Function(func) => drop(func.cached_env.borrow_mut().take()),
+            // Immutable; its upvalues are nodes of their own.
+            Node::VmClosure(_) => {}
+            Node::Upvalue(rc) => drop(std::mem::replace(
+                &mut *rc.borrow_mut(),
+                Upvalue::Closed(Value::Null),
+            )),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Graph {
+    nodes: Vec<Node>,
+    index: HashMap<usize, usize>,
+    /// References to each node from walked nodes.
+    internal: Vec<usize>,
+    /// Nodes that couldn't be read, which count as held from outside.
+    pinned: Vec<bool>,
+    edges: Vec<Vec<usize>>,
+}
+
+impl Graph {
+    /// The index of `node`, adding it if it's new. A node already present
+    /// drops the extra reference right away, so every node is held once.
+    fn add(&mut self, node: Node) -> usize {
+        let address = node.address();
+        if let Some(&i) = self.index.get(&address) {
+            return i;
+        }
+        let i = self.nodes.len();
+        self.nodes.push(node);
+        self.index.insert(address, i);
+        self.internal.push(0);
+        self.pinned.push(false);
+        self.edges.push(Vec::new());
+        i
+    }
+
+    fn walk(&mut self, i: usize) {
+        let Some(children) = self.nodes[i].children() else {
+            self.pinned[i] = true;
+            return;
+        };
+        for child in children {
+            let j = self.add(child);
+            self.internal[j] += 1;
+            self.edges[i].push(j);
+        }
+    }
+
+    fn free_garbage(&self) -> usize {
+        let mut live = vec![false; self.nodes.len()];
+        let mut stack: Vec<usize> = (0..self.nodes.len())
+            .filter(|&i| {
+                // The graph's own reference is one of the strong ones.
+                self.pinned[i] || self.nodes[i].strong_count() - 1 > self.internal[i]
+            })
+            .collect();
+        while let Some(i) = stack.pop() {
+            if !live[i] {
+                live[i] = true;
+                stack.extend(self.edges[i].iter().copied().filter(|&j| !live[j]));
+            }
+        }
+
+        let mut freed = 0;
+        for (node, live) in self.nodes.iter().zip(live) {
+            if !live {
+                node.empty();
+                freed += 1;
+            }
+        }
+        freed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::value::Class;
+    use crate::interpreter::Interpreter;
+
+    fn instance() -> Rc<RefCell<Instance>> {
+        new_instance(Instance::new(Rc::new(Class::default())))
+    }
+
+    fn run(interpreter: &mut Interpreter, source: &str) -> Value {
+        let tokens = crate::lexer::Scanner::new(source).scan_tokens().unwrap();
+        let program = crate::parser::Parser::new(tokens).parse().unwrap();
+        interpreter.interpret(&program).unwrap();
+        interpreter
+            .environment
+            .borrow()
+            .get("result")
+            .unwrap_or(Value::Null)
+    }
+
+    #[test]
+    fn an_unreachable_parent_child_cycle_is_freed() {
+        let parent = instance();
+        let child = instance();
+        let children = Rc::new(RefCell::new(vec![Value::Instance(child.clone())]));
+        parent
+            .borrow_mut()
+            .set("children".to_string(), Value::Array(children));
+        child
+            .borrow_mut()
+            .set("parent".to_string(), Value::Instance(parent.clone()));
+        let (weak_parent, weak_child) = (Rc::downgrade(&parent), Rc::downgrade(&child));
+        drop((parent, child));
+        assert!(
+            weak_parent.upgrade().is_some(),
+            "the cycle keeps itself alive"
+        );
+
+        assert_eq!(collect(), 3);
+        assert!(weak_parent.upgrade().is_none());
+        assert!(weak_child.upgrade().is_none());
+    }
+
+    #[test]
+    fn a_cycle_held_from_outside_is_left_alone() {
+        let parent = instance();
+        let child = instance();
+        parent
+            .borrow_mut()
+            .set("child".to_string(), Value::Instance(child.clone()));
+        child
+            .borrow_mut()
+            .set("parent".to_string(), Value::Instance(parent.clone()));
+        let weak_child = Rc::downgrade(&child);
+        drop(child);
+
+        assert_eq!(collect(), 0);
+        assert!(weak_child.upgrade().is_some());
+        assert!(parent.borrow().get("child").is_some());
+    }
+
+    #[test]
+    fn cycles_built_by_soli_code_are_freed() {
+        let mut interpreter = Interpreter::new();
+        let result = run(
+            &mut interpreter,
+            r#"
+            class Node {
+                parent: Any;
+                children: Any;
+                new() {
+                    this.parent = null
+                    this.children = []
+                }
+            }
+            fn build() {
+                let root = Node()
+                let leaf = Node()
+                leaf.parent = root
+                root.children.push(leaf)
+                let counter = 0
+                fn bump() { counter = counter + 1 }
+            }
+            build()
+            let kept = Node()
+            kept.children.push(kept)
+            let result = kept
+            "#,
+        );
+        // `root`, `leaf`, their two `children` arrays, `build`'s scope and
+        // the `bump` closure that captured it.
+        assert_eq!(collect(), 6);
+        let Value::Instance(kept) = result else {
+            panic!("expected an instance");
+        };
+        assert!(kept.borrow().get("children").is_some());
+    }
+}
@@ -0,0 +1,152 @@
+//! Decorator values: the evaluated `@name(...)` annotations on functions,
+//! classes and methods.
+//!
+//! ```soli
+//! @route("/users", method: "GET")
+//! fn index(req) { ... }
+//!
+//! index.decorators        # [{"name" => "route", "args" => ["/users"], "options" => {"method" => "GET"}}]
+//! index.decorator("route")["args"][0]   # "/users"
+//! ```
+//!
+//! Arguments are evaluated once, when the declaration runs. Decorators have
+//! no built-in behaviour; this module only shapes them for reflection.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::interpreter::value::{Class, HashKey, HashPairs, NativeFunction, Value};
+
+/// One evaluated decorator: positional arguments in `args`, named ones in
+/// `options` (in source order).
+#[derive(Debug, Clone)]
+pub struct DecoratorValue {
+    pub name: String,
+    pub args: Vec<Value>,
+    pub options: Vec<(String, Value)>,
+}
+
+/// The decorators of one declaration. `None` when it has none, so undecorated
+/// functions and classes carry no allocation.
+pub type Decorators = Option<Rc<Vec<DecoratorValue>>>;
+
+impl DecoratorValue {
+    /// `{"name" => ..., "args" => [...], "options" => {...}}`.
+    pub fn to_value(&self) -> Value {
+        let mut options = HashPairs::default();
+        for (key, value) in &self.options {
+            options.insert(HashKey::String(key.as_str().into()), value.clone());
+        }
+        let mut pairs = HashPairs::default();
+        pairs.insert(
+            HashKey::String("name".into()),
+            Value::String(self.name.as_str().into()),
+        );
+        pairs.insert(
+            HashKey::String("args".into()),
+            Value::Array(Rc::new(RefCell::new(self.args.clone()))),
+        );
+        pairs.insert(
+            HashKey::String("options".into()),
+            Value::Hash(Rc::new(RefCell::new(options))),
+        );
+        Value::Hash(Rc::new(RefCell::new(pairs)))
+    }
+}
+
+/// `decorators` as an array of hashes (empty when undecorated).
+pub fn decorators_value(decorators: &Decorators) -> Value {
+    let items = decorators
+        .iter()
+        .flat_map(|d| d.iter())
+        .map(DecoratorValue::to_value)
+        .collect();
+    Value::Array(Rc::new(RefCell::new(items)))
+}
+
+/// The first decorator called `name`, if any.
+pub fn find<'a>(decorators: &'a Decorators, name: &str) -> Option<&'a DecoratorValue> {
+    decorators.as_deref()?.iter().find(|d| d.name == name)
+}
+
+/// The reflection members shared by functions and classes: `decorators` and
+/// `decorator(name)`. `None` for any other member name.
+pub fn reflect(decorators: &Decorators, member: &str) -> Option<Value> {
+    match member {
+        "decorators" => Some(decorators_value(decorators)),
+        "decorator" => {
+            let decorators = decorators.clone();
+            Some(Value::NativeFunction(NativeFunction::new(
+                "decorator",
+                Some(1),
+                move |args| {
+                    let name = decorator_name_arg("decorator", &args)?;
+                    Ok(find(&decorators, &name).map_or(Value::Null, DecoratorValue::to_value))
+                },
+            )))
+        }
+        _ => None,
+    }
+}
+
+/// Class-only reflection: `method_decorators(method)` and
+/// `decorated_methods(decorator)`. Methods are looked up through the
+/// superclass chain, instance methods before static ones.
+pub fn reflect_methods(class: &Rc<Class>, member: &str) -> Option<Value> {
+    match member {
+        "method_decorators" => {
+            let class = class.clone();
+            Some(Value::NativeFunction(NativeFunction::new(
+                "method_decorators",
+                Some(1),
+                move |args| {
+                    let method = decorator_name_arg("method_decorators", &args)?;
+                    let decorators = class
+                        .find_method(&method)
+                        .or_else(|| class.find_static_method(&method))
+                        .and_then(|f| f.decorators.clone());
+                    Ok(decorators_value(&decorators))
+                },
+            )))
+        }
+        "decorated_methods" => {
+            let class = class.clone();
+            Some(Value::NativeFunction(NativeFunction::new(
+                "decorated_methods",
+                Some(1),
+                move |args| {
+                    let name = decorator_name_arg("decorated_methods", &args)?;
+                    let mut methods: Vec<String> = Vec::new();
+                    let mut current = Some(&class);
+                    while let Some(c) = current {
+                        let instance = c.methods.borrow();
+                        for (method, func) in instance.iter().chain(c.static_methods.iter()) {
+                            if find(&func.decorators, &name).is_some() && !methods.contains(method)
+                            {
+                                methods.push(method.clone());
+                            }
+                        }
+                        current = c.superclass.as_ref();
+                    }
+                    methods.sort();
+                    Ok(Value::Array(Rc::new(RefCell::new(
+                        methods
+                            .into_iter()
+                            .map(|m| Value::String(m.into()))
+                            .collect(),
+                    ))))
+                },
+            )))
+        }
+        _ => None,
+    }
+}
+
+fn decorator_name_arg(method: &str, args: &[Value]) -> Result<String, String> {
+    match args.first() {
+        Some(Value::String(s)) | Some(Value::Symbol(s)) => {
+            Ok(s.trim_start_matches('@').to_string())
+        }
+        _ => Err(format!("{}() expects a name string", method)),
+    }
+}
@@ -3,9 +3,12 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::sync::Arc;
 
 use ahash::AHashMap;
 
+use crate::ast::expr::FrameSlot;
+use crate::interpreter::resolver::FrameLayout;
 use crate::interpreter::value::{HashPairs, StrKey, Value};
 
 /// Result of an `Environment::assign` call.
@@ -25,6 +28,15 @@ pub enum AssignResult {
 /// Internal storage uses `ahash::AHashMap` rather than `std::HashMap` (SipHash)
 /// — variable lookups are on the hot path of every expression evaluation, and
 /// ahash is ~3× faster for short string keys like identifiers.
+///
+/// A function call frame also has a [`FrameLayout`]: its parameters and
+/// top-level `let`s live in `slots`, and the nodes the resolver tied to them
+/// index the frame directly (see [`Environment::get_resolved`]). By-name
+/// reads and assignments see slots too, so closures, `defined()` and the
+/// debugger work the same on both; by-name definitions don't, since every
+/// binding of a slotted name goes through its slot. Template scopes, which answer from a data hash whose keys
+/// are only known at render time, and the REPL's live top-level scope have
+/// no layout.
 #[derive(Debug, Clone)]
 pub struct Environment {
     values: AHashMap<String, Value>,
@@ -34,6 +46,10 @@ pub struct Environment {
     /// Checked during get() before walking the enclosing chain.
     /// Avoids copying all data fields into the HashMap.
     data_hash: Option<Rc<RefCell<HashPairs>>>,
+    /// The slot layout of a function call frame.
+    layout: Option<Arc<FrameLayout>>,
+    /// One value per `layout` slot; `None` until the name is bound.
+    slots: Vec<Option<Value>>,
 }
 
 impl Environment {
@@ -43,6 +59,8 @@ impl Environment {
             consts: AHashMap::new(),
             enclosing: None,
             data_hash: None,
+            layout: None,
+            slots: Vec::new(),
         }
     }
 
@@ -54,6 +72,8 @@ impl Environment {
             consts: AHashMap::new(),
             enclosing: None,
             data_hash: None,
+            layout: None,
+            slots: Vec::new(),
         }
     }
 
@@ -63,6 +83,8 @@ impl Environment {
             consts: AHashMap::new(),
             enclosing: Some(enclosing),
             data_hash: None,
+            layout: None,
+            slots: Vec::new(),
         }
     }
 
@@ -78,6 +100,28 @@ impl Environment {
             consts: AHashMap::new(),
             enclosing: Some(enclosing),
             data_hash: Some(data_hash),
+            layout: None,
+            slots: Vec::new(),
+        }
+    }
+
+    /// Create a function call frame. With a `layout`, the names it lists
+    /// are bound in slots instead of the name-keyed map.
+    pub fn for_frame(
+        enclosing: Rc<RefCell<Environment>>,
+        layout: Option<Arc<FrameLayout>>,
+    ) -> Self {
+        let slots = match &layout {
+            Some(layout) => vec![None; layout.slot_count()],
+            None => Vec::new(),
+        };
+        Self {
+            values: AHashMap::new(),
+            consts: AHashMap::new(),
+            enclosing: Some(enclosing),
+            data_hash: None,
+            layout,
+            slots,
         }
     }
 
@@ -86,6 +130,7 @@ impl Environment {
     #[inline]
     pub fn reset_for_reuse(&mut self, data_hash: Option<Rc<RefCell<HashPairs>>>) {
         self.values.clear();
+        self.slots.fill(None);
         self.data_hash = data_hash;
     }
 
@@ -97,6 +142,21 @@ impl Environment {
     pub fn reset_for_call(&mut self) {
         self.values.clear();
         self.consts.clear();
+        self.slots.fill(None);
+    }
+
+    /// The frame slot holding `name` in this scope, if it has one.
+    #[inline]
+    fn frame_slot(&self, name: &str) -> Option<usize> {
+        self.layout.as_ref()?.slot(name)
+    }
+
+    /// This scope's bound slots, with their names.
+    fn slot_bindings(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.layout
+            .iter()
+            .flat_map(move |layout| layout.names().iter().zip(&self.slots))
+            .filter_map(|(name, value)| value.as_ref().map(|value| (name, value)))
     }
 
     /// Define a new variable in the current scope.
@@ -116,6 +176,28 @@ impl Environment {
         }
     }
 
+    /// Define `name` for a `let` whose node is `node`: in its slot when this
+    /// scope is the frame the node was resolved for, by name otherwise.
+    #[inline]
+    pub(crate) fn define_resolved(&mut self, name: &str, node: &FrameSlot, value: Value) {
+        if let Some(slot) = self.layout.as_ref().and_then(|l| l.slot_of(node)) {
+            self.slots[slot] = Some(value);
+        } else {
+            self.define_or_update(name, value);
+        }
+    }
+
+    /// Bind the parameter at `index` of the function this frame was laid out
+    /// for.
+    #[inline]
+    pub(crate) fn define_param(&mut self, index: usize, name: &str, value: Value) {
+        if let Some(slot) = self.layout.as_ref().and_then(|l| l.param_slot(index)) {
+            self.slots[slot] = Some(value);
+        } else {
+            self.define_or_update(name, value);
+        }
+    }
+
     /// Define a constant in the current scope.
     pub fn define_const(&mut self, name: String, value: Value) {
         self.consts.insert(name, value);
@@ -144,6 +226,11 @@ impl Environment {
                 return Some(value.clone());
             }
         }
+        if let Some(slot) = self.frame_slot(name) {
+            if let Some(value) = &self.slots[slot] {
+                return Some(value.clone());
+            }
+        }
         if let Some(value) = self.values.get(name) {
             return Some(value.clone());
         }
@@ -159,6 +246,84 @@ impl Environment {
         None
     }
 
+    /// Read the variable `name` through its node: a node the resolver tied
+    /// to a slot walks to the nearest call frame and indexes it. Where that
+    /// slot isn't bound yet, the name is looked up from the frame on; no
+    /// scope between the node and its frame can bind it. An unresolved node
+    /// is looked up by name, as is one whose frame isn't the nearest (a
+    /// builtin ran its block in a plain scope, or a template data hash is in
+    /// the way).
+    #[inline]
+    pub(crate) fn get_resolved(&self, name: &str, node: &FrameSlot) -> Option<Value> {
+        if node.get().is_none() {
+            return self.get(name);
+        }
+        match self.frame_read(name, node) {
+            Some(found) => found,
+            None => self.get(name),
+        }
+    }
+
+    /// [`Environment::get_resolved`]'s walk: `None` if the nearest frame
+    /// isn't the node's.
+    fn frame_read(&self, name: &str, node: &FrameSlot) -> Option<Option<Value>> {
+        if let Some(layout) = &self.layout {
+            let slot = layout.slot_of(node)?;
+            return Some(match &self.slots[slot] {
+                Some(value) => Some(value.clone()),
+                None => self.get(name),
+            });
+        }
+        if self.data_hash.is_some() {
+            return None;
+        }
+        self.enclosing.as_ref()?.borrow().frame_read(name, node)
+    }
+
+    /// Assign to the variable `name` through its node, walking like
+    /// [`Environment::get_resolved`].
+    pub(crate) fn assign_resolved(
+        &mut self,
+        name: &str,
+        node: &FrameSlot,
+        value: Value,
+    ) -> AssignResult {
+        if node.get().is_none() {
+            return self.assign(name, value);
+        }
+        match self.frame_assign(name, node, value) {
+            Ok(result) => result,
+            Err(value) => self.assign(name, value),
+        }
+    }
+
+    /// [`Environment::assign_resolved`]'s walk: hands the value back if the
+    /// nearest frame isn't the node's.
+    fn frame_assign(
+        &mut self,
+        name: &str,
+        node: &FrameSlot,
+        value: Value,
+    ) -> Result<AssignResult, Value> {
+        if let Some(layout) = &self.layout {
+            let Some(slot) = layout.slot_of(node) else {
+                return Err(value);
+            };
+            if let Some(existing) = &mut self.slots[slot] {
+                *existing = value;
+                return Ok(AssignResult::Assigned);
+            }
+            return Ok(self.assign(name, value));
+        }
+        if self.data_hash.is_some() {
+            return Err(value);
+        }
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().frame_assign(name, node, value),
+            None => Err(value),
+        }
+    }
+
     /// Get a constant's value, searching up the scope chain.
     pub fn get_const(&self, name: &str) -> Option<Value> {
         if let Some(value) = self.consts.get(name) {
@@ -176,6 +341,12 @@ impl Environment {
         if !self.consts.is_empty() && self.consts.contains_key(name) {
             return AssignResult::IsConst;
         }
+        if let Some(slot) = self.frame_slot(name) {
+            if let Some(existing) = &mut self.slots[slot] {
+                *existing = value;
+                return AssignResult::Assigned;
+            }
+        }
         if let Some(slot) = self.values.get_mut(name) {
             *slot = value;
             return AssignResult::Assigned;
@@ -188,12 +359,20 @@ impl Environment {
 
     /// Check if a variable exists in the current scope only (values or consts).
     pub fn contains_local(&self, name: &str) -> bool {
-        self.values.contains_key(name) || self.consts.contains_key(name)
+        self.values.contains_key(name)
+            || self.consts.contains_key(name)
+            || self
+                .frame_slot(name)
+                .is_some_and(|slot| self.slots[slot].is_some())
     }
 
     /// Get all variable names in the current scope (for REPL introspection).
     pub fn get_var_names(&self) -> Vec<String> {
-        self.values.keys().cloned().collect()
+        self.values
+            .keys()
+            .chain(self.slot_bindings().map(|(name, _)| name))
+            .cloned()
+            .collect()
     }
 
     /// Get a variable from local scope only (no parent chain traversal).
@@ -202,6 +381,10 @@ impl Environment {
     pub fn get_local(&self, name: &str) -> Option<Value> {
         self.consts
             .get(name)
+            .or_else(|| {
+                self.frame_slot(name)
+                    .and_then(|slot| self.slots[slot].as_ref())
+            })
             .or_else(|| self.values.get(name))
             .cloned()
     }
@@ -214,7 +397,7 @@ impl Environment {
             return false;
         }
         // Always define in local scope - this is for loop variables
-        self.values.insert(name.to_string(), value);
+        self.define_or_update(name, value);
         true
     }
 
@@ -231,10 +414,31 @@ impl Environment {
             all.extend(enclosing.borrow().get_all_bindings());
         }
         all.extend(self.values.clone());
+        all.extend(self.slot_bindings().map(|(k, v)| (k.clone(), v.clone())));
         all.extend(self.consts.clone());
         all
     }
 
+    /// The enclosing scope, borrowed rather than cloned, so a walk over the
+    /// heap doesn't change its reference count.
+    pub(crate) fn enclosing_ref(&self) -> Option<&Rc<RefCell<Environment>>> {
+        self.enclosing.as_ref()
+    }
+
+    /// The template data hash, borrowed; see [`Environment::enclosing_ref`].
+    pub(crate) fn data_hash_ref(&self) -> Option<&Rc<RefCell<HashPairs>>> {
+        self.data_hash.as_ref()
+    }
+
+    /// This scope's own bindings (variables, then constants), without
+    /// walking the enclosing chain or copying the maps.
+    pub fn local_bindings(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.values
+            .iter()
+            .chain(self.slot_bindings())
+            .chain(self.consts.iter())
+    }
+
     /// Get all variables from this scope and all enclosing scopes.
     /// Used for debugging (breakpoints).
     pub fn get_all_variables(&self) -> HashMap<String, Value> {
@@ -247,6 +451,7 @@ impl Environment {
 
         // Then add/override with variables from current scope
         all_vars.extend(self.values.clone());
+        all_vars.extend(self.slot_bindings().map(|(k, v)| (k.clone(), v.clone())));
 
         all_vars
     }
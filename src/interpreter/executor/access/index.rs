@@ -29,6 +29,15 @@ impl Interpreter {
             other => other,
         };
 
+        if let Some(result) = self.call_operator_method(
+            &obj_val,
+            crate::ast::expr::INDEX_METHOD,
+            vec![idx_val.clone()],
+            span,
+        )? {
+            return Ok(result);
+        }
+
         match (&obj_val, &idx_val) {
             (Value::Array(arr), Value::Int(idx)) => {
                 let arr = arr.borrow();
@@ -46,6 +55,31 @@ impl Interpreter {
                         span,
                     })
             }
+            (Value::Tuple(items), Value::Int(idx)) => {
+                let idx_usize = if *idx < 0 {
+                    (items.len() as i64 + idx) as usize
+                } else {
+                    *idx as usize
+                };
+                items
+                    .get(idx_usize)
+                    .cloned()
+                    .ok_or_else(|| RuntimeError::IndexOutOfBounds {
+                        index: *idx,
+                        length: items.len(),
+                        span,
+                    })
+            }
+            (Value::Range(range), Value::Int(idx)) => {
+                range
+                    .get(*idx)
+                    .map(Value::Int)
+                    .ok_or(RuntimeError::IndexOutOfBounds {
+                        index: *idx,
+                        length: range.len(),
+                        span,
+                    })
+            }
             (Value::String(s), Value::Int(idx)) => {
                 let original_idx = *idx;
                 let idx_usize = if *idx < 0 {
@@ -95,6 +129,18 @@ impl Interpreter {
         let obj_val = self.evaluate(object)?;
         let idx_val = self.evaluate(index)?;
 
+        if self
+            .call_operator_method(
+                &obj_val,
+                crate::ast::expr::INDEX_SET_METHOD,
+                vec![idx_val.clone(), new_value.clone()],
+                span,
+            )?
+            .is_some()
+        {
+            return Ok(new_value);
+        }
+
         match (&obj_val, &idx_val) {
             (Value::Array(arr), Value::Int(idx)) => {
                 let mut arr = arr.borrow_mut();
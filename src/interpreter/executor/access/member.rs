@@ -14,11 +14,75 @@ use crate::interpreter::builtins::model::is_translated_field;
 use crate::interpreter::builtins::model::relations::RelationType;
 use crate::interpreter::builtins::model::uploaders::get_uploader;
 use crate::interpreter::builtins::model::value_to_json;
+use crate::interpreter::decorators;
 use crate::interpreter::environment::Environment;
+use crate::interpreter::executor::calls::method_registry::{
+    MethodDef, ARRAY_METHODS, ITERATOR_METHODS,
+};
+use crate::interpreter::executor::calls::range_methods::{
+    call_range_method_impl, is_range_method_with_args,
+};
 use crate::interpreter::executor::{Interpreter, RuntimeResult};
-use crate::interpreter::value::{Class, Function, Instance, NativeFunction, Value, ValueMethod};
+use crate::interpreter::value::{
+    Class, Function, Instance, NativeFunction, RangeValue, Value, ValueMethod,
+};
+use crate::interpreter::value_struct::{named_fields, StructValue};
 use crate::span::Span;
 
+/// Hash methods that `hash.name` (without a call) resolves to as a bound
+/// method. Any other name reads the key of that name, `null` when absent.
+pub(crate) fn is_hash_member_method(name: &str) -> bool {
+    matches!(
+        name,
+        "length"
+            | "len"
+            | "size"
+            | "map"
+            | "filter"
+            | "each"
+            | "get"
+            | "fetch"
+            | "invert"
+            | "transform_values"
+            | "transform_keys"
+            | "select"
+            | "reject"
+            | "slice"
+            | "except"
+            | "compact"
+            | "dig"
+            | "to_string"
+            | "to_json"
+            | "keys"
+            | "values"
+            | "has_key"
+            | "delete"
+            | "merge"
+            | "entries"
+            | "clear"
+            | "set"
+            | "empty?"
+            | "is_a?"
+            | "shift"
+            | "flatten"
+            | "values_at"
+            | "key"
+            | "has_value?"
+            | "value?"
+            | "to_h"
+            | "keep_if"
+            | "delete_if"
+            | "update"
+            | "all?"
+            | "any?"
+            | "assoc"
+            | "rassoc"
+            | "fetch_values"
+            | "each_key"
+            | "each_value"
+    )
+}
+
 /// Wrap a user-defined Soli function as a NativeFunction bound to a receiver
 /// value. Used to dispatch `Int.class_eval do define_method(:foo) { ... } end`-
 /// style methods on primitive types: when `3.foo` is evaluated, the per-type
@@ -356,8 +420,12 @@ impl Interpreter {
             Value::Class(ref class) => self.class_member_access(class, name, span, &obj_val),
             Value::Super(ref superclass) => self.super_member_access(superclass, name, span),
             Value::Array(ref _arr) => self.array_member_access(name, span, obj_val),
+            Value::Tuple(ref items) => Self::tuple_member_access(items, name, span),
+            Value::Struct(ref value) => Self::struct_member_access(value, name, span),
+            Value::Range(ref range) => self.range_member_access(range, name, span),
+            Value::Iterator(_) => Self::iterator_member_access(name, span, obj_val),
             Value::Hash(ref hash) => self.hash_member_access(hash, name, span, obj_val.clone()),
-            Value::QueryBuilder(_) => self.query_builder_member_access(name, span, obj_val),
+            Value::QueryBuilder(_) => Self::query_builder_member_access(name, span, obj_val),
             Value::String(ref _s) => self.string_member_access(name, span, obj_val),
             Value::Symbol(ref _s) => Self::symbol_member_access(_s, name, span),
             Value::Int(n) => Self::int_member_access(n, name, span),
@@ -369,7 +437,11 @@ impl Interpreter {
             // anything else. Without this, defensive view-partial patterns like
             // `type(x) != "function" && !x.nil?` crash because short-circuit
             // isn't in play and `.nil?` hits the catch-all NoSuchProperty.
-            Value::Function(_) | Value::NativeFunction(_) => {
+            Value::Function(ref f) => match decorators::reflect(&f.decorators, name) {
+                Some(value) => Ok(value),
+                None => Self::function_member_access(name, span, obj_val.type_name().to_string()),
+            },
+            Value::NativeFunction(_) => {
                 Self::function_member_access(name, span, obj_val.type_name().to_string())
             }
             _ => Err(RuntimeError::NoSuchProperty {
@@ -380,6 +452,151 @@ impl Interpreter {
         }
     }
 
+    /// Member access on tuples. Tuples are fixed-size and immutable, so only
+    /// the read-only accessors are exposed; `to_a` hands back an array copy
+    /// for anything richer.
+    pub(crate) fn tuple_member_access(
+        items: &Rc<Vec<Value>>,
+        name: &str,
+        span: Span,
+    ) -> RuntimeResult<Value> {
+        match name {
+            "class" => Ok(Value::String("tuple".into())),
+            "nil?" => Ok(Value::Bool(false)),
+            "blank?" => Ok(Value::Bool(items.is_empty())),
+            "present?" => Ok(Value::Bool(!items.is_empty())),
+            "length" | "len" | "size" => Ok(Value::Int(items.len() as i64)),
+            "first" => Ok(items.first().cloned().unwrap_or(Value::Null)),
+            "last" => Ok(items.last().cloned().unwrap_or(Value::Null)),
+            "to_a" | "to_array" => Ok(Value::Array(Rc::new(RefCell::new(items.to_vec())))),
+            "to_s" | "to_string" | "inspect" => Ok(Value::String(
+                Value::Tuple(items.clone()).to_string().into(),
+            )),
+            _ => Err(RuntimeError::NoSuchProperty {
+                value_type: "tuple".to_string(),
+                property: name.to_string(),
+                span,
+            }),
+        }
+    }
+
+    /// Member access on struct values. Declared fields come first, so a field
+    /// named `class` or `with` shadows the built-in; the value is otherwise
+    /// read-only, and `with(field: ...)` is the one way to get a changed copy.
+    pub(crate) fn struct_member_access(
+        value: &Rc<StructValue>,
+        name: &str,
+        span: Span,
+    ) -> RuntimeResult<Value> {
+        if let Some(field) = value.get(name) {
+            return Ok(field);
+        }
+        match name {
+            "class" => Ok(Value::String(value.class.name.clone().into())),
+            "nil?" | "blank?" => Ok(Value::Bool(false)),
+            "present?" => Ok(Value::Bool(true)),
+            "to_s" | "to_string" | "inspect" => Ok(Value::String(
+                Value::Struct(value.clone()).to_string().into(),
+            )),
+            "to_h" | "to_hash" => {
+                let value = value.clone();
+                Ok(Value::NativeFunction(NativeFunction::new(
+                    "to_h",
+                    Some(0),
+                    move |_args| Ok(value.to_hash()),
+                )))
+            }
+            // Named arguments to a native arrive as one trailing hash.
+            "with" => {
+                let value = value.clone();
+                Ok(Value::NativeFunction(NativeFunction::new(
+                    "with",
+                    None,
+                    move |args| match args.as_slice() {
+                        [] => Ok(Value::Struct(value.clone())),
+                        [Value::Hash(changes)] => {
+                            value.with(named_fields(&value.class, &changes.borrow())?)
+                        }
+                        _ => Err(format!(
+                            "{}.with() takes fields by name, e.g. with({}: ...)",
+                            value.class.name,
+                            value.fields().first().map_or("field", |f| f.name.as_str())
+                        )),
+                    },
+                )))
+            }
+            _ => Err(RuntimeError::NoSuchProperty {
+                value_type: value.class.name.clone(),
+                property: name.to_string(),
+                span,
+            }),
+        }
+    }
+
+    /// The members of a struct type itself. A struct type is constructed by
+    /// calling it, so `Money.new` is the type itself and takes the same
+    /// positional/named arguments; `Money.from_h(hash)` builds one from a hash.
+    pub(crate) fn struct_type_member(class: &Rc<Class>, name: &str) -> Option<Value> {
+        class.struct_fields.as_ref()?;
+        match name {
+            "new" => Some(Value::Class(class.clone())),
+            "from_h" => {
+                let class = class.clone();
+                Some(Value::NativeFunction(NativeFunction::new(
+                    "from_h",
+                    Some(1),
+                    move |args| match args.first() {
+                        Some(Value::Hash(pairs)) => StructValue::from_hash(&class, &pairs.borrow()),
+                        other => Err(format!(
+                            "{}.from_h() expects a Hash, got {}",
+                            class.name,
+                            other.map_or("nothing".to_string(), |v| v.type_name())
+                        )),
+                    },
+                )))
+            }
+            _ => None,
+        }
+    }
+
+    /// Member access on ranges: the range's own methods evaluate (or bind)
+    /// here, anything else is looked up on the equivalent array.
+    fn range_member_access(
+        &self,
+        range: &Rc<RangeValue>,
+        name: &str,
+        span: Span,
+    ) -> RuntimeResult<Value> {
+        if is_range_method_with_args(name) {
+            return Ok(Value::method(ValueMethod {
+                receiver: Box::new(Value::Range(range.clone())),
+                method_name: name.to_string(),
+            }));
+        }
+        if let Some(result) = call_range_method_impl(range, name, &[], span) {
+            return result;
+        }
+        let array = Value::Array(Rc::new(RefCell::new(range.to_vec())));
+        self.array_member_access(name, span, array)
+    }
+
+    /// Member access on iterators: every iterator (or array) method binds to
+    /// the iterator, so nothing is pulled until the method is called.
+    fn iterator_member_access(name: &str, span: Span, obj_val: Value) -> RuntimeResult<Value> {
+        let known = |methods: &[MethodDef]| methods.iter().any(|m| m.name == name);
+        if !known(ITERATOR_METHODS) && !known(ARRAY_METHODS) {
+            return Err(RuntimeError::NoSuchProperty {
+                value_type: "iterator".to_string(),
+                property: name.to_string(),
+                span,
+            });
+        }
+        Ok(Value::method(ValueMethod {
+            receiver: Box::new(obj_val),
+            method_name: name.to_string(),
+        }))
+    }
+
     /// Member access on Function/NativeFunction values. Only the universal
     /// "is this thing x?" predicates make sense — a function has no fields.
     fn function_member_access(name: &str, span: Span, type_name: String) -> RuntimeResult<Value> {
@@ -510,7 +727,10 @@ impl Interpreter {
                 }
                 let mut s = format!("<{}", inst_ref.class.name);
                 let mut first = true;
-                for (k, v) in inst_ref.fields.iter() {
+                // Sorted for the same reason as `Display for Value`.
+                let mut fields: Vec<_> = inst_ref.fields.iter().collect();
+                fields.sort_unstable_by(|a, b| a.0.cmp(b.0));
+                for (k, v) in fields {
                     // Hide _errors when empty
                     if k == "_errors" {
                         if let Value::Array(arr) = v {
@@ -549,15 +769,9 @@ impl Interpreter {
                             Value::String(s) => s.as_ref(),
                             _ => return Err("is_a? expects a string argument".to_string()),
                         };
-                        let inst_ref = inst_clone.borrow();
-                        let mut current_class: Option<&Class> = Some(&inst_ref.class);
-                        while let Some(c) = current_class {
-                            if c.name == class_name {
-                                return Ok(Value::Bool(true));
-                            }
-                            current_class = c.superclass.as_ref().map(|rc| rc.as_ref());
-                        }
-                        Ok(Value::Bool(false))
+                        Ok(Value::Bool(
+                            inst_clone.borrow().class.conforms_to(class_name),
+                        ))
                     },
                 )));
             }
@@ -704,9 +918,10 @@ impl Interpreter {
             // Metaprogramming: instance_variables
             "instance_variables" => {
                 let inst_ref = inst.borrow();
-                let vars: Vec<Value> = inst_ref
-                    .fields
-                    .keys()
+                let mut names: Vec<&String> = inst_ref.fields.keys().collect();
+                names.sort_unstable();
+                let vars: Vec<Value> = names
+                    .into_iter()
                     .map(|k| Value::String(format!("@{}", k).into()))
                     .collect();
                 return Ok(Value::Array(Rc::new(RefCell::new(vars))));
@@ -1446,10 +1661,13 @@ impl Interpreter {
                 body: method.body.clone(),
                 closure: Rc::new(RefCell::new(bound_env)),
                 is_method: true,
+                is_generator: method.is_generator,
+                is_async: method.is_async,
                 span: method.span,
                 source_path: method.source_path.clone(),
                 defining_superclass: None,
                 return_type: method.return_type.clone(),
+                decorators: method.decorators.clone(),
                 cached_env: RefCell::new(None),
                 jit_cache: RefCell::new(None),
             };
@@ -1506,7 +1724,20 @@ impl Interpreter {
         // than erroring — DB rows often lack optional columns, and views
         // written defensively ("<% if !org.industry.nil? %>") expect null
         // for unset attributes instead of a hard error.
+        // A model that declares its schema (`attribute "title", "string"`)
+        // only gets that leniency for declared attributes: a typo'd name
+        // raises instead of silently rendering as null.
         if inst_ref.class.is_model_subclass() {
+            if crate::interpreter::builtins::model::attributes::is_undeclared(
+                &inst_ref.class.name,
+                name,
+            ) {
+                return Err(RuntimeError::NoSuchProperty {
+                    value_type: class_name,
+                    property: name.to_string(),
+                    span,
+                });
+            }
             return Ok(Value::Null);
         }
 
@@ -1517,6 +1748,85 @@ impl Interpreter {
         })
     }
 
+    /// `ClassName.new` as a value: instantiate, with an optional hash of
+    /// attributes that are bulk-assigned to the new instance's fields.
+    /// Framework-internal `_`-prefixed keys are skipped.
+    pub(crate) fn class_new_member(class_val: &Value) -> Value {
+        let class_val = class_val.clone();
+        Value::NativeFunction(NativeFunction::new("new", None, move |args| {
+            if args.len() > 1 {
+                return Err(format!(
+                    "new() expects 0 or 1 arguments, got {}",
+                    args.len()
+                ));
+            }
+            let class_rc = match &class_val {
+                Value::Class(c) => c.clone(),
+                _ => unreachable!(),
+            };
+            let instance = Rc::new(RefCell::new(crate::interpreter::value::Instance::new(
+                class_rc,
+            )));
+            match args.first() {
+                None | Some(Value::Null) => {}
+                Some(Value::Hash(pairs)) => {
+                    use crate::interpreter::value::HashKey;
+                    let mut inst_mut = instance.borrow_mut();
+                    for (k, v) in pairs.borrow().iter() {
+                        if let HashKey::String(field) = k {
+                            if field.starts_with('_') {
+                                continue;
+                            }
+                            inst_mut.set(field.clone().to_string(), v.clone());
+                        }
+                    }
+                }
+                Some(other) => {
+                    return Err(format!(
+                        "new() expects a Hash of attributes, got {}",
+                        other.type_name()
+                    ));
+                }
+            }
+            Ok(Value::Instance(instance))
+        }))
+    }
+
+    /// Universal members every class value answers (`inspect`, `nil?`, ...).
+    pub(crate) fn class_universal_member(
+        class: &crate::interpreter::value::Class,
+        name: &str,
+    ) -> Option<Value> {
+        match name {
+            "inspect" | "to_s" | "to_string" => {
+                Some(Value::String(format!("<class {}>", class.name).into()))
+            }
+            "class" => Some(Value::String("class".into())),
+            "nil?" | "blank?" => Some(Value::Bool(false)),
+            "present?" => Some(Value::Bool(true)),
+            _ => None,
+        }
+    }
+
+    /// Dynamic finders on a model class (`User.find_by_email(...)`).
+    pub(crate) fn dynamic_finder_member(
+        class: &crate::interpreter::value::Class,
+        name: &str,
+    ) -> Option<Value> {
+        if !class.is_model_subclass() || !name.starts_with("find_by_") {
+            return None;
+        }
+        let (attributes, arity) = parse_dynamic_finder(name).ok()?;
+        let class_rc = Rc::new(class.clone());
+        Some(Value::NativeFunction(NativeFunction::new(
+            name,
+            Some(arity),
+            move |args: Vec<Value>| -> Result<Value, String> {
+                execute_dynamic_finder(&class_rc, &attributes, &args)
+            },
+        )))
+    }
+
     fn class_member_access(
         &self,
         class: &crate::interpreter::value::Class,
@@ -1567,81 +1877,33 @@ impl Interpreter {
         }
 
         // Check for static field (including inherited static fields)
-        fn find_static_field(
-            class: &crate::interpreter::value::Class,
-            name: &str,
-        ) -> Option<Value> {
-            if let Some(value) = class.static_fields.borrow().get(name) {
-                return Some(value.clone());
-            }
-            if let Some(ref superclass) = class.superclass {
-                return find_static_field(superclass, name);
-            }
-            None
+        if let Some(value) = class.find_static_field(name) {
+            return Ok(value);
         }
 
-        if let Some(value) = find_static_field(class, name) {
+        if let Some(value) = decorators::reflect(&class.decorators, name) {
             return Ok(value);
         }
+        if let Value::Class(class_rc) = class_val {
+            if let Some(value) = decorators::reflect_methods(class_rc, name) {
+                return Ok(value);
+            }
+        }
+
+        if let Value::Class(class_rc) = class_val {
+            if let Some(value) = Self::struct_type_member(class_rc, name) {
+                return Ok(value);
+            }
+        }
 
-        // ClassName.new() — instantiate, with an optional hash of attributes
-        // that are bulk-assigned to the new instance's fields. Framework-
-        // internal `_`-prefixed keys are skipped.
         if name == "new" {
-            let class_val = class_val.clone();
-            return Ok(Value::NativeFunction(NativeFunction::new(
-                "new",
-                None,
-                move |args| {
-                    if args.len() > 1 {
-                        return Err(format!(
-                            "new() expects 0 or 1 arguments, got {}",
-                            args.len()
-                        ));
-                    }
-                    let class_rc = match &class_val {
-                        Value::Class(c) => c.clone(),
-                        _ => unreachable!(),
-                    };
-                    let instance = Rc::new(RefCell::new(crate::interpreter::value::Instance::new(
-                        class_rc,
-                    )));
-                    match args.first() {
-                        None | Some(Value::Null) => {}
-                        Some(Value::Hash(pairs)) => {
-                            use crate::interpreter::value::HashKey;
-                            let mut inst_mut = instance.borrow_mut();
-                            for (k, v) in pairs.borrow().iter() {
-                                if let HashKey::String(field) = k {
-                                    if field.starts_with('_') {
-                                        continue;
-                                    }
-                                    inst_mut.set(field.clone().to_string(), v.clone());
-                                }
-                            }
-                        }
-                        Some(other) => {
-                            return Err(format!(
-                                "new() expects a Hash of attributes, got {}",
-                                other.type_name()
-                            ));
-                        }
-                    }
-                    Ok(Value::Instance(instance))
-                },
-            )));
+            return Ok(Self::class_new_member(class_val));
+        }
+        if let Some(value) = Self::class_universal_member(class, name) {
+            return Ok(value);
         }
 
-        // Universal methods on class values
         match name {
-            "inspect" => return Ok(Value::String(format!("<class {}>", class.name).into())),
-            "class" => return Ok(Value::String("class".into())),
-            "nil?" => return Ok(Value::Bool(false)),
-            "blank?" => return Ok(Value::Bool(false)),
-            "present?" => return Ok(Value::Bool(true)),
-            "to_s" | "to_string" => {
-                return Ok(Value::String(format!("<class {}>", class.name).into()))
-            }
             // Metaprogramming: respond_to? (checks if class has static method)
             "respond_to?" => {
                 let class_clone = class.clone();
@@ -1887,17 +2149,8 @@ impl Interpreter {
             _ => {}
         }
 
-        if class.is_model_subclass() && name.starts_with("find_by_") {
-            if let Ok((attributes, arity)) = parse_dynamic_finder(name) {
-                let class_rc = Rc::new(class.clone());
-                return Ok(Value::NativeFunction(NativeFunction::new(
-                    name,
-                    Some(arity),
-                    move |args: Vec<Value>| -> Result<Value, String> {
-                        execute_dynamic_finder(&class_rc, &attributes, &args)
-                    },
-                )));
-            }
+        if let Some(value) = Self::dynamic_finder_member(class, name) {
+            return Ok(value);
         }
 
         // Class-level `method_missing` fallback. When no static method, native
@@ -2000,10 +2253,13 @@ impl Interpreter {
                 body: method.body.clone(),
                 closure: Rc::new(RefCell::new(bound_env)),
                 is_method: true,
+                is_generator: method.is_generator,
+                is_async: method.is_async,
                 span: method.span,
                 source_path: method.source_path.clone(),
                 defining_superclass: None,
                 return_type: method.return_type.clone(),
+                decorators: method.decorators.clone(),
                 cached_env: RefCell::new(None),
                 jit_cache: RefCell::new(None),
             };
@@ -2115,30 +2371,21 @@ impl Interpreter {
             _ => {}
         }
         // First check if it's a known method
-        match name {
-            "length" | "len" | "size" | "map" | "filter" | "each" | "get" | "fetch" | "invert"
-            | "transform_values" | "transform_keys" | "select" | "reject" | "slice" | "except"
-            | "compact" | "dig" | "to_string" | "to_json" | "keys" | "values" | "has_key"
-            | "delete" | "merge" | "entries" | "clear" | "set" | "empty?" | "is_a?" | "shift"
-            | "flatten" | "values_at" | "key" | "has_value?" | "value?" | "to_h" | "keep_if"
-            | "delete_if" | "update" | "all?" | "any?" | "assoc" | "rassoc" | "fetch_values"
-            | "each_key" | "each_value" => Ok(Value::method(ValueMethod {
+        if is_hash_member_method(name) {
+            return Ok(Value::method(ValueMethod {
                 receiver: Box::new(obj_val),
                 method_name: name.to_string(),
-            })),
-            _ => {
-                // Try to access as a hash key (dot notation for hash access)
-                // Use StrKey for zero-allocation lookup (hashes identically to HashKey::String)
-                if let Some(v) = hash.borrow().get(&crate::interpreter::value::StrKey(name)) {
-                    return Ok(v.clone());
-                }
-                Ok(Value::Null)
-            }
+            }));
         }
+        // Try to access as a hash key (dot notation for hash access)
+        // Use StrKey for zero-allocation lookup (hashes identically to HashKey::String)
+        if let Some(v) = hash.borrow().get(&crate::interpreter::value::StrKey(name)) {
+            return Ok(v.clone());
+        }
+        Ok(Value::Null)
     }
 
-    fn query_builder_member_access(
-        &mut self,
+    pub(crate) fn query_builder_member_access(
         name: &str,
         span: Span,
         obj_val: Value,
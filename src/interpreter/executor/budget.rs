@@ -0,0 +1,343 @@
+//! Per-evaluation limits for code the host doesn't trust to terminate, such
+//! as the dev error page's REPL or `soli -e --timeout`: a step count, an
+//! allocation count, a wall-clock deadline, and caps on how much the thread's
+//! heap and the process's resident memory may grow while the code runs.
+//!
+//! The interpreter spends one step per statement. The VM spends one per loop
+//! iteration and per call, the points every runaway loop or recursion goes
+//! through, so an unlimited VM never pays for a check per instruction. Steps
+//! and allocations are deterministic: the same program stops at the same
+//! place on every run. A single native call (one huge `string * n`) can't be
+//! interrupted; the checks catch its result at the next step.
+
+use std::alloc::{GlobalAlloc, Layout};
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use crate::error::RuntimeError;
+use crate::span::Span;
+
+use super::{Interpreter, RuntimeResult};
+
+/// Steps between clock reads.
+const CLOCK_EVERY: u64 = 64;
+/// Steps between resident-memory reads, which cost a file read.
+const MEMORY_EVERY: u64 = 4096;
+
+/// What one thread has allocated so far, as counted by [`CountingAlloc`].
+/// All zero when the global allocator isn't one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeapCounters {
+    pub allocations: u64,
+    pub allocated_bytes: u64,
+    /// Bytes this thread freed, whichever thread allocated them.
+    pub freed_bytes: u64,
+}
+
+impl HeapCounters {
+    /// Bytes allocated and not freed by this thread.
+    pub fn live_bytes(&self) -> u64 {
+        self.allocated_bytes.saturating_sub(self.freed_bytes)
+    }
+}
+
+thread_local! {
+    static COUNTERS: Cell<HeapCounters> = const {
+        Cell::new(HeapCounters {
+            allocations: 0,
+            allocated_bytes: 0,
+            freed_bytes: 0,
+        })
+    };
+}
+
+/// A global allocator that counts the allocations each thread makes, and
+/// their bytes, which [`EvalBudget::with_max_allocations`] and
+/// [`EvalBudget::with_max_heap_growth`] limit. The `soli` binary installs it
+/// around mimalloc; an embedder that doesn't gets neither limit.
+pub struct CountingAlloc<A>(pub A);
+
+fn count(allocated: usize, freed: usize) {
+    // `try_with`: the allocator also runs while a thread's locals are torn
+    // down.
+    let _ = COUNTERS.try_with(|counters| {
+        let mut c = counters.get();
+        if allocated > 0 {
+            c.allocations += 1;
+            c.allocated_bytes += allocated as u64;
+        }
+        c.freed_bytes += freed as u64;
+        counters.set(c);
+    });
+}
+
+// SAFETY: every call is forwarded to the wrapped allocator unchanged.
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAlloc<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count(layout.size(), 0);
+        self.0.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count(layout.size(), 0);
+        self.0.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        count(0, layout.size());
+        self.0.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count(new_size, layout.size());
+        self.0.realloc(ptr, layout, new_size)
+    }
+}
+
+/// This thread's allocation counters.
+pub fn heap_counters() -> HeapCounters {
+    COUNTERS.with(Cell::get)
+}
+
+/// Limits on one evaluation; see [`Interpreter::set_budget`] and
+/// `Vm::set_budget`. Built from [`EvalBudget::default`] (no limits) with the
+/// `with_*` methods; each limit is measured from when the budget is set.
+#[derive(Debug, Clone, Default)]
+pub struct EvalBudget {
+    timeout: Option<Duration>,
+    max_memory_growth: Option<u64>,
+    max_steps: Option<u64>,
+    max_allocations: Option<u64>,
+    max_heap_growth: Option<u64>,
+    deadline: Option<Instant>,
+    baseline_rss: u64,
+    baseline_heap: HeapCounters,
+    steps: u64,
+}
+
+impl EvalBudget {
+    /// Allow `timeout` of wall-clock time and, when given,
+    /// `max_memory_growth` bytes of resident memory growth.
+    pub fn new(timeout: Duration, max_memory_growth: Option<u64>) -> Self {
+        let budget = Self::default().with_timeout(timeout);
+        match max_memory_growth {
+            Some(bytes) => budget.with_max_memory_growth(bytes),
+            None => budget,
+        }
+    }
+
+    /// Stop after `timeout` of wall-clock time.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Stop once the process's resident memory has grown by more than
+    /// `bytes`. Only applies where resident memory can be read (Linux).
+    pub fn with_max_memory_growth(mut self, bytes: u64) -> Self {
+        self.max_memory_growth = Some(bytes);
+        self
+    }
+
+    /// Stop after `steps` steps: statements in the interpreter, loop
+    /// iterations and calls in the VM.
+    pub fn with_max_steps(mut self, steps: u64) -> Self {
+        self.max_steps = Some(steps);
+        self
+    }
+
+    /// Stop after the evaluating thread has made `allocations` heap
+    /// allocations. Needs [`CountingAlloc`] as the global allocator.
+    pub fn with_max_allocations(mut self, allocations: u64) -> Self {
+        self.max_allocations = Some(allocations);
+        self
+    }
+
+    /// Stop once the evaluating thread holds more than `bytes` of heap beyond
+    /// what it held when the budget was set. Needs [`CountingAlloc`] as the
+    /// global allocator.
+    pub fn with_max_heap_growth(mut self, bytes: u64) -> Self {
+        self.max_heap_growth = Some(bytes);
+        self
+    }
+
+    /// Start measuring: the deadline and the baselines are taken now.
+    pub(crate) fn start(mut self) -> Self {
+        self.deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        if self.max_memory_growth.is_some() {
+            self.baseline_rss = resident_bytes().unwrap_or(0);
+        }
+        self.baseline_heap = heap_counters();
+        self.steps = 0;
+        self
+    }
+
+    /// Spend one step; the error says which limit ran out.
+    pub(crate) fn check(&mut self) -> Result<(), String> {
+        self.steps += 1;
+        if let Some(max) = self.max_steps {
+            if self.steps > max {
+                return Err(format!("evaluation exceeded its step limit ({})", max));
+            }
+        }
+        if self.max_allocations.is_some() || self.max_heap_growth.is_some() {
+            let heap = heap_counters();
+            if let Some(max) = self.max_allocations {
+                if heap.allocations - self.baseline_heap.allocations > max {
+                    return Err(format!(
+                        "evaluation exceeded its allocation limit ({})",
+                        max
+                    ));
+                }
+            }
+            if let Some(limit) = self.max_heap_growth {
+                if heap.live_bytes() > self.baseline_heap.live_bytes().saturating_add(limit) {
+                    return Err(format!(
+                        "evaluation exceeded its heap limit ({} MB)",
+                        limit / (1024 * 1024)
+                    ));
+                }
+            }
+        }
+        if let (Some(deadline), Some(timeout)) = (self.deadline, self.timeout) {
+            if self.steps.is_multiple_of(CLOCK_EVERY) && Instant::now() >= deadline {
+                return Err(format!(
+                    "evaluation timed out after {}ms",
+                    timeout.as_millis()
+                ));
+            }
+        }
+        if let Some(limit) = self.max_memory_growth {
+            if self.steps.is_multiple_of(MEMORY_EVERY) {
+                let grown = resident_bytes()
+                    .unwrap_or(0)
+                    .saturating_sub(self.baseline_rss);
+                if grown > limit {
+                    return Err(format!(
+                        "evaluation exceeded its memory limit ({} MB)",
+                        limit / (1024 * 1024)
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Resident memory of this process, from `/proc/self/status`. `None` where
+/// that file doesn't exist.
+pub(crate) fn resident_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kb: u64 = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+impl Interpreter {
+    /// Limit the evaluations that follow to `budget`, or lift the limit with
+    /// `None`. Running past it fails with a `RuntimeError` the host can
+    /// handle (`is_budget_exceeded()`), but that the script's own
+    /// `try`/`catch` can't intercept.
+    pub fn set_budget(&mut self, budget: Option<EvalBudget>) {
+        self.budget = budget.map(EvalBudget::start);
+    }
+
+    /// Fail once the budget is spent. Called before every statement.
+    #[inline]
+    pub(super) fn check_budget(&mut self, span: Span) -> RuntimeResult<()> {
+        match self.budget.as_mut().map(EvalBudget::check) {
+            Some(Err(message)) => Err(RuntimeError::BudgetExceeded(message, span)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(interpreter: &mut Interpreter, source: &str) -> RuntimeResult<()> {
+        let tokens = crate::lexer::Scanner::new(source).scan_tokens().unwrap();
+        let program = crate::parser::Parser::new(tokens).parse().unwrap();
+        interpreter.interpret(&program)
+    }
+
+    #[test]
+    fn a_runaway_loop_stops_at_the_deadline() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_budget(Some(EvalBudget::new(Duration::from_millis(50), None)));
+        let err = run(&mut interpreter, "let i = 0\nwhile (true) { i = i + 1 }").unwrap_err();
+        assert!(err.is_budget_exceeded(), "{}", err);
+        assert!(err.to_string().contains("timed out after 50ms"), "{}", err);
+    }
+
+    #[test]
+    fn try_catch_cannot_swallow_the_deadline() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_budget(Some(EvalBudget::new(Duration::from_millis(50), None)));
+        let source = "while (true) { try { let x = 1 } catch e { let y = 2 } }";
+        assert!(run(&mut interpreter, source)
+            .unwrap_err()
+            .is_budget_exceeded());
+    }
+
+    #[test]
+    fn no_budget_means_no_limit() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_budget(Some(EvalBudget::new(Duration::from_millis(50), None)));
+        interpreter.set_budget(None);
+        run(
+            &mut interpreter,
+            "let i = 0\nwhile (i < 10000) { i = i + 1 }",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn the_step_limit_stops_at_the_same_statement_every_run() {
+        let source = "let i = 0\nwhile (true) { i = i + 1 }";
+        let mut reached = Vec::new();
+        for _ in 0..2 {
+            let mut interpreter = Interpreter::new();
+            interpreter.set_budget(Some(EvalBudget::default().with_max_steps(1000)));
+            let err = run(&mut interpreter, source).unwrap_err();
+            assert!(err.to_string().contains("step limit (1000)"), "{}", err);
+            reached.push(interpreter.environment.borrow().get("i"));
+        }
+        assert_eq!(reached[0], reached[1]);
+    }
+
+    #[test]
+    fn the_heap_limit_stops_a_growing_loop_but_not_churn() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_budget(Some(
+            EvalBudget::default().with_max_heap_growth(4 * 1024 * 1024),
+        ));
+        let churn = "let i = 0\nwhile (i < 20000) { let s = \"x\" * 1000\n i = i + 1 }";
+        run(&mut interpreter, churn).unwrap();
+
+        let growth = "let items = []\nwhile (true) { items.push(\"x\" * 1000) }";
+        let err = run(&mut interpreter, growth).unwrap_err();
+        assert!(err.is_budget_exceeded(), "{}", err);
+        assert!(err.to_string().contains("heap limit (4 MB)"), "{}", err);
+    }
+
+    #[test]
+    fn the_allocation_limit_stops_a_growing_loop() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_budget(Some(EvalBudget::default().with_max_allocations(10_000)));
+        let source = "let items = []\nwhile (true) { items.push(\"x\" + str(items.length)) }";
+        let err = run(&mut interpreter, source).unwrap_err();
+        assert!(err.is_budget_exceeded(), "{}", err);
+        assert!(
+            err.to_string().contains("allocation limit (10000)"),
+            "{}",
+            err
+        );
+    }
+}
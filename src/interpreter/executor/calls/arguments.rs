@@ -0,0 +1,190 @@
+//! Matching a call's arguments to a user function's parameters.
+//!
+//! Positional arguments fill the parameters before the `*` marker in order,
+//! with any extras collected into a `...rest` parameter's array; named
+//! arguments fill any other non-block parameter by name, and whatever is left
+//! must declare a default. Defaults are not evaluated here: the binder leaves
+//! their slot empty and the callee evaluates the expression in its own scope
+//! when the frame is set up (see `Interpreter::bind_parameters`), so a default
+//! runs only when it is needed and can read the parameters before it
+//! (`def f(a, b = a * 2)`).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::ast::Parameter;
+use crate::error::RuntimeError;
+use crate::interpreter::value::Value;
+use crate::span::Span;
+
+/// One slot per parameter of `params`: `Some` for an argument the caller
+/// passed, `None` for one left to its default (or, for a block parameter,
+/// left unset).
+pub(crate) fn bind_arguments(
+    func_name: &str,
+    params: &[Parameter],
+    positional: Vec<Value>,
+    mut named: HashMap<String, Value>,
+    span: Span,
+) -> Result<Vec<Option<Value>>, RuntimeError> {
+    let mut slots: Vec<Option<Value>> = vec![None; params.len()];
+
+    let positional_count = positional.len();
+    let mut positional = positional.into_iter();
+    for (slot, param) in slots.iter_mut().zip(params) {
+        if param.is_block_param || param.is_keyword_only || param.is_variadic {
+            continue;
+        }
+        match positional.next() {
+            Some(value) => *slot = Some(value),
+            None => break,
+        }
+    }
+    if let Some(index) = params.iter().position(|p| p.is_variadic) {
+        slots[index] = Some(Value::Array(Rc::new(RefCell::new(positional.collect()))));
+    } else if positional.next().is_some() {
+        let accepted = params
+            .iter()
+            .filter(|p| !p.is_block_param && !p.is_keyword_only)
+            .count();
+        return Err(RuntimeError::wrong_arity(accepted, positional_count, span));
+    }
+
+    // Check names in parameter order so the error is deterministic. A
+    // variadic parameter can't be named: it only ever collects positionals.
+    for (slot, param) in slots.iter_mut().zip(params) {
+        if param.is_variadic {
+            continue;
+        }
+        let Some(value) = named.remove(&param.name) else {
+            continue;
+        };
+        if slot.is_some() {
+            return Err(RuntimeError::type_error(
+                format!(
+                    "{}() got argument '{}' both by position and by name",
+                    func_name, param.name
+                ),
+                span,
+            ));
+        }
+        *slot = Some(value);
+    }
+    if let Some(name) = named.keys().min() {
+        return Err(RuntimeError::type_error(
+            format!("{}() has no parameter named '{}'", func_name, name),
+            span,
+        ));
+    }
+
+    for (index, (slot, param)) in slots.iter().zip(params).enumerate() {
+        if slot.is_some() || param.default_value.is_some() || param.is_block_param {
+            continue;
+        }
+        if param.is_keyword_only {
+            return Err(RuntimeError::type_error(
+                format!(
+                    "{}() is missing keyword argument '{}'",
+                    func_name, param.name
+                ),
+                span,
+            ));
+        }
+        let required = params
+            .iter()
+            .filter(|p| {
+                p.default_value.is_none()
+                    && !p.is_block_param
+                    && !p.is_keyword_only
+                    && !p.is_variadic
+            })
+            .count();
+        let bound_before = slots[..index].iter().filter(|s| s.is_some()).count();
+        return Err(RuntimeError::wrong_arity(required, bound_before, span));
+    }
+
+    Ok(slots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(source: &str) -> Vec<Parameter> {
+        let tokens = crate::lexer::Scanner::new(source).scan_tokens().unwrap();
+        let program = crate::parser::Parser::new(tokens).parse().unwrap();
+        match &program.statements[0].kind {
+            crate::ast::StmtKind::Function(decl) => decl.params.clone(),
+            other => panic!("expected a function, got {:?}", other),
+        }
+    }
+
+    fn named(pairs: &[(&str, i64)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), Value::Int(*v)))
+            .collect()
+    }
+
+    fn bind(source: &str, positional: &[i64], pairs: &[(&str, i64)]) -> Result<String, String> {
+        let positional = positional.iter().map(|v| Value::Int(*v)).collect();
+        bind_arguments(
+            "f",
+            &params(source),
+            positional,
+            named(pairs),
+            Span::default(),
+        )
+        .map(|slots| {
+            slots
+                .iter()
+                .map(|s| s.as_ref().map_or("_".to_string(), |v| v.to_string()))
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn leaves_defaulted_slots_for_the_callee() {
+        let f = "def f(a, b = 2, c = 3) { return 0 }";
+        assert_eq!(bind(f, &[1], &[]).unwrap(), "1,_,_");
+        assert_eq!(bind(f, &[1], &[("c", 9)]).unwrap(), "1,_,9");
+        assert_eq!(bind(f, &[], &[("a", 1), ("b", 5)]).unwrap(), "1,5,_");
+    }
+
+    #[test]
+    fn keyword_only_parameters_take_names_only() {
+        let f = "def f(host, *, port = 80, tls) { return 0 }";
+        assert_eq!(bind(f, &[1], &[("tls", 0)]).unwrap(), "1,_,0");
+        assert!(bind(f, &[1, 2], &[("tls", 0)])
+            .unwrap_err()
+            .contains("expected 1"));
+        assert!(bind(f, &[1], &[])
+            .unwrap_err()
+            .contains("f() is missing keyword argument 'tls'"));
+    }
+
+    #[test]
+    fn variadic_parameter_collects_the_extra_positionals() {
+        let f = "def f(a, ...rest, flag = 0) { return 0 }";
+        assert_eq!(bind(f, &[1], &[]).unwrap(), "1,[],_");
+        assert_eq!(bind(f, &[1, 2, 3], &[("flag", 1)]).unwrap(), "1,[2, 3],1");
+        assert!(bind(f, &[1], &[("rest", 2)])
+            .unwrap_err()
+            .contains("f() has no parameter named 'rest'"));
+        assert!(bind(f, &[], &[]).unwrap_err().contains("expected 1"));
+    }
+
+    #[test]
+    fn rejects_unknown_and_repeated_names() {
+        let f = "def f(a, b = 2) { return 0 }";
+        assert!(bind(f, &[1], &[("nope", 1)])
+            .unwrap_err()
+            .contains("f() has no parameter named 'nope'"));
+        assert!(bind(f, &[1], &[("a", 1)])
+            .unwrap_err()
+            .contains("f() got argument 'a' both by position and by name"));
+    }
+}
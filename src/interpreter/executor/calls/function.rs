@@ -4,8 +4,10 @@ use crate::ast::expr::Argument;
 use crate::ast::{Expr, ExprKind};
 use crate::error::RuntimeError;
 use crate::interpreter::environment::Environment;
+use crate::interpreter::executor::calls::arguments::bind_arguments;
 use crate::interpreter::executor::{Interpreter, RuntimeResult};
 use crate::interpreter::value::{HashKey, Instance, Value};
+use crate::interpreter::value_struct::StructValue;
 use crate::span::Span;
 
 use std::cell::RefCell;
@@ -266,9 +268,20 @@ impl Interpreter {
         &mut self,
         inst: &Rc<RefCell<Instance>>,
         method: &Rc<crate::interpreter::value::Function>,
-        mut arguments: Vec<Value>,
+        arguments: Vec<Value>,
         span: Span,
     ) -> RuntimeResult<Value> {
+        let this = Some(Value::Instance(inst.clone()));
+        if method.needs_argument_binding() {
+            let slots = bind_arguments(
+                &method.name,
+                &method.params,
+                arguments,
+                HashMap::new(),
+                span,
+            )?;
+            return self.call_function_bound(method, this, slots);
+        }
         let required_arity = method.arity();
         let full_arity = method.full_arity();
 
@@ -282,16 +295,8 @@ impl Interpreter {
         if arguments.len() > full_arity {
             return Err(RuntimeError::wrong_arity(full_arity, arguments.len(), span));
         }
-        while arguments.len() < full_arity {
-            if let Some(default_expr) = method.param_default_value(arguments.len()) {
-                let default_value = self.evaluate(default_expr)?;
-                arguments.push(default_value);
-            } else {
-                return Err(RuntimeError::wrong_arity(full_arity, arguments.len(), span));
-            }
-        }
 
-        self.call_function_with_this(method, Some(Value::Instance(inst.clone())), arguments)
+        self.call_function_with_this(method, this, arguments)
     }
 
     /// Evaluate a function call expression.
@@ -305,7 +310,17 @@ impl Interpreter {
         // a custom dispatcher that can invoke user lambdas (a NativeFunction
         // can't reach `&mut Interpreter`). Intentionally checked before
         // `evaluate_callee` so a local `respond_to` can't shadow the magic.
-        if let ExprKind::Variable(name) = &callee.kind {
+        // A sandboxed interpreter (the dev REPL) only runs the magic forms of
+        // the builtins it kept; the others reach their refusing global.
+        let intercepted = match &callee.kind {
+            ExprKind::Variable(name, _)
+                if !self.sandboxed || crate::serve::repl_session::allows_function(name) =>
+            {
+                Some(name)
+            }
+            _ => None,
+        };
+        if let Some(name) = intercepted {
             if name == "respond_to" {
                 if let Some(result) = self.try_evaluate_respond_to(arguments, span)? {
                     return Ok(result);
@@ -335,6 +350,29 @@ impl Interpreter {
                     return Ok(result);
                 }
             }
+            // `connected_to("replica") { ... }` sends the block's model
+            // queries to a named database connection.
+            if name == "connected_to" {
+                if let Some(result) = self.try_evaluate_connected_to(arguments, span)? {
+                    return Ok(result);
+                }
+            }
+            // `freeze_time(t) { ... }`, `travel_to(t) { ... }` and
+            // `travel(secs) { ... }` pin the clock only for the block. The
+            // bare forms fall through to the natives, which freeze until
+            // `unfreeze_time()` (or the next test example).
+            if matches!(name.as_str(), "freeze_time" | "travel_to" | "travel") {
+                if let Some(result) = self.try_evaluate_time_block(name, arguments, span)? {
+                    return Ok(result);
+                }
+            }
+            // `with_cassette("name") { ... }` answers the block's `HTTP.*`
+            // calls from a recorded cassette (recording them on first run).
+            if name == "with_cassette" {
+                if let Some(result) = self.try_evaluate_with_cassette(arguments, span)? {
+                    return Ok(result);
+                }
+            }
             // `event :name do … end` inside a `state_machine` block. Scoped to an
             // active builder so a stray `event(...)` elsewhere falls through to
             // the native placeholder (which raises a clear error). The block must
@@ -352,8 +390,9 @@ impl Interpreter {
         // interpreter to invoke callable factory templates and persist via
         // Model.create — handled here before member dispatch.
         if let ExprKind::Member { object, name } = &callee.kind {
-            if let ExprKind::Variable(prefix) = &object.kind {
+            if let ExprKind::Variable(prefix, _) = &object.kind {
                 if prefix == "Factory"
+                    && !self.sandboxed
                     && matches!(
                         name.as_str(),
                         "create" | "create_with" | "create_list" | "insert"
@@ -657,12 +696,32 @@ impl Interpreter {
                 Argument::Block(expr) => {
                     block_arg = Some(self.evaluate(expr)?);
                 }
+                Argument::Spread(expr) => {
+                    arg_values.extend(self.evaluate_spread(expr)?);
+                }
             }
         }
 
         self.call_value_with_named(callee_val, arg_values, named_args, block_arg, span)
     }
 
+    /// Evaluate the operand of a `*expr` argument into the positional
+    /// arguments it stands for.
+    pub(crate) fn evaluate_spread(&mut self, expr: &Expr) -> RuntimeResult<Vec<Value>> {
+        match self.evaluate(expr)? {
+            Value::Array(arr) => Ok(arr.borrow().iter().cloned().collect()),
+            Value::Range(range) => Ok(range.to_vec()),
+            Value::Iterator(iter) => self.iterator_collect(&iter, expr.span),
+            other => Err(RuntimeError::type_error(
+                format!(
+                    "cannot spread {} into arguments, expected an array",
+                    other.type_name()
+                ),
+                expr.span,
+            )),
+        }
+    }
+
     /// Intercept `SomeModel.create(data)` / `SomeModel.update(id, data)` when
     /// the model has before_save / before_create / before_update callbacks
     /// registered via `before_save("normalize_email")` etc. Builds a temp
@@ -837,10 +896,13 @@ impl Interpreter {
                                 body: method.body.clone(),
                                 closure: Rc::new(RefCell::new(bound_env)),
                                 is_method: true,
+                                is_generator: method.is_generator,
+                                is_async: method.is_async,
                                 span: method.span,
                                 source_path: method.source_path.clone(),
                                 defining_superclass: None,
                                 return_type: method.return_type.clone(),
+                                decorators: method.decorators.clone(),
                                 cached_env: RefCell::new(None),
                                 jit_cache: RefCell::new(None),
                             };
@@ -869,10 +931,13 @@ impl Interpreter {
                                     body: closure.body.clone(),
                                     closure: Rc::new(RefCell::new(bound_env)),
                                     is_method: true,
+                                    is_generator: closure.is_generator,
+                                    is_async: closure.is_async,
                                     span: closure.span,
                                     source_path: closure.source_path.clone(),
                                     defining_superclass: None,
                                     return_type: closure.return_type.clone(),
+                                    decorators: closure.decorators.clone(),
                                     cached_env: RefCell::new(None),
                                     jit_cache: RefCell::new(None),
                                 };
@@ -1201,10 +1266,13 @@ impl Interpreter {
                 body: method.body.clone(),
                 closure: Rc::new(RefCell::new(bound_env)),
                 is_method: true,
+                is_generator: method.is_generator,
+                is_async: method.is_async,
                 span: method.span,
                 source_path: method.source_path.clone(),
                 defining_superclass: None,
                 return_type: method.return_type.clone(),
+                decorators: method.decorators.clone(),
                 cached_env: RefCell::new(None),
                 jit_cache: RefCell::new(None),
             };
@@ -1229,10 +1297,13 @@ impl Interpreter {
                     body: closure.body.clone(),
                     closure: Rc::new(RefCell::new(bound_env)),
                     is_method: true,
+                    is_generator: closure.is_generator,
+                    is_async: closure.is_async,
                     span: closure.span,
                     source_path: closure.source_path.clone(),
                     defining_superclass: None,
                     return_type: closure.return_type.clone(),
+                    decorators: closure.decorators.clone(),
                     cached_env: RefCell::new(None),
                     jit_cache: RefCell::new(None),
                 };
@@ -1533,10 +1604,13 @@ impl Interpreter {
             body: closure.body.clone(),
             closure: Rc::new(RefCell::new(bound_env)),
             is_method: true,
+            is_generator: closure.is_generator,
+            is_async: closure.is_async,
             span: closure.span,
             source_path: closure.source_path.clone(),
             defining_superclass: None,
             return_type: closure.return_type.clone(),
+            decorators: closure.decorators.clone(),
             cached_env: RefCell::new(None),
             jit_cache: RefCell::new(None),
         };
@@ -1679,6 +1753,195 @@ impl Interpreter {
         }
     }
 
+    /// Implement `connected_to(name) { ... }`: run the block with every model
+    /// query going to the named database connection (`"primary"` included),
+    /// then restore the previous one — also when the block throws.
+    ///
+    /// Returns `Ok(None)` unless the call is `(name, block)`.
+    fn try_evaluate_connected_to(
+        &mut self,
+        arguments: &[Argument],
+        span: Span,
+    ) -> RuntimeResult<Option<Value>> {
+        use crate::interpreter::builtins::model::connections;
+
+        let [Argument::Positional(name_expr), block_arg] = arguments else {
+            return Ok(None);
+        };
+        let block_expr = match block_arg {
+            Argument::Block(e) => e,
+            Argument::Positional(e) if matches!(e.kind, ExprKind::Lambda { .. }) => e,
+            _ => return Ok(None),
+        };
+        let name = match self.evaluate(name_expr)? {
+            Value::String(s) => s.to_string(),
+            other => {
+                return Err(RuntimeError::type_error(
+                    format!(
+                        "connected_to() expects a connection name string, got {}",
+                        other.type_name()
+                    ),
+                    span,
+                ))
+            }
+        };
+        let conn = connections::connection(&name).map_err(|message| RuntimeError::General {
+            message: format!("connected_to: {}", message),
+            span,
+        })?;
+        let block = self.evaluate(block_expr)?;
+
+        connections::push_connection(conn);
+        let result = self.call_value(block, Vec::new(), span);
+        connections::pop_connection();
+        result.map(Some)
+    }
+
+    /// Implement the block forms of the clock controls: evaluate the target
+    /// time, pin the clock there, run the block, then restore whatever freeze
+    /// (or none) was active before — also when the block throws, so a failing
+    /// assertion can't leak a frozen clock into the rest of the example.
+    ///
+    /// Returns `Ok(None)` unless the call is `(target, block)`.
+    fn try_evaluate_time_block(
+        &mut self,
+        name: &str,
+        arguments: &[Argument],
+        span: Span,
+    ) -> RuntimeResult<Option<Value>> {
+        use crate::interpreter::builtins::datetime::{freeze_target, helpers, travel_seconds};
+
+        if arguments.len() != 2 {
+            return Ok(None);
+        }
+        let target_expr = match &arguments[0] {
+            Argument::Positional(e) => e,
+            _ => return Ok(None),
+        };
+        let block_expr = match &arguments[1] {
+            Argument::Block(e) => e,
+            Argument::Positional(e) if matches!(e.kind, ExprKind::Lambda { .. }) => e,
+            _ => return Ok(None),
+        };
+
+        let target = self.evaluate(target_expr)?;
+        let timestamp = if name == "travel" {
+            travel_seconds(&target).map(|secs| helpers::datetime_now().saturating_add(secs))
+        } else {
+            freeze_target(name, &target)
+        }
+        .map_err(|message| RuntimeError::General { message, span })?;
+        let block = self.evaluate(block_expr)?;
+
+        let previous = helpers::frozen_datetime();
+        helpers::freeze_datetime(timestamp);
+        let result = self.call_value(block, Vec::new(), span);
+        helpers::restore_frozen_datetime(previous);
+        result.map(Some)
+    }
+
+    /// Implement `with_cassette(name, options?) { ... }`: load (or start)
+    /// the cassette, make it the active one for the block's HTTP calls, then
+    /// restore the previous one and write out anything recorded — also when
+    /// the block throws, so a failing assertion doesn't lose the recording.
+    ///
+    /// Returns `Ok(None)` unless the last argument is a block.
+    fn try_evaluate_with_cassette(
+        &mut self,
+        arguments: &[Argument],
+        span: Span,
+    ) -> RuntimeResult<Option<Value>> {
+        use crate::interpreter::builtins::http_cassette::{
+            self, Cassette, MatchOn, RecordMode, CASSETTE_DIR,
+        };
+
+        let (block_expr, rest) = match arguments.split_last() {
+            Some((Argument::Block(e), rest)) => (e, rest),
+            Some((Argument::Positional(e), rest)) if matches!(e.kind, ExprKind::Lambda { .. }) => {
+                (e, rest)
+            }
+            _ => return Ok(None),
+        };
+        let mut values = Vec::with_capacity(rest.len());
+        for arg in rest {
+            match arg {
+                Argument::Positional(e) => values.push(self.evaluate(e)?),
+                _ => return Ok(None),
+            }
+        }
+        let general = |message: String| RuntimeError::General { message, span };
+
+        let name = match values.first() {
+            Some(Value::String(s)) => s.to_string(),
+            _ => {
+                return Err(general(
+                    "with_cassette() expects a cassette name: with_cassette(\"name\") { ... }"
+                        .to_string(),
+                ))
+            }
+        };
+        let mut record = RecordMode::Once;
+        let mut match_on = vec![MatchOn::Method, MatchOn::Uri];
+        match values.get(1) {
+            None | Some(Value::Null) => {}
+            Some(Value::Hash(options)) => {
+                for (key, value) in options.borrow().iter() {
+                    match (key.to_string().as_str(), value) {
+                        ("record", Value::String(mode)) => {
+                            record = RecordMode::parse(mode).map_err(general)?;
+                        }
+                        ("match_on", Value::Array(parts)) => {
+                            match_on = parts
+                                .borrow()
+                                .iter()
+                                .map(|part| match part {
+                                    Value::String(p) => MatchOn::parse(p),
+                                    other => Err(format!(
+                                        "with_cassette(): match_on entries must be strings, got {}",
+                                        other.type_name()
+                                    )),
+                                })
+                                .collect::<Result<_, _>>()
+                                .map_err(general)?;
+                        }
+                        (key, other) => {
+                            return Err(general(format!(
+                                "with_cassette(): unsupported option '{}' ({})",
+                                key,
+                                other.type_name()
+                            )))
+                        }
+                    }
+                }
+            }
+            Some(other) => {
+                return Err(general(format!(
+                    "with_cassette() options must be a hash, got {}",
+                    other.type_name()
+                )))
+            }
+        }
+        if values.len() > 2 {
+            return Err(RuntimeError::wrong_arity(2, values.len(), span));
+        }
+
+        let cassette = Cassette::load(std::path::Path::new(CASSETTE_DIR), &name, record, match_on)
+            .map_err(general)?;
+        let handle = std::sync::Arc::new(std::sync::Mutex::new(cassette));
+        let block = self.evaluate(block_expr)?;
+
+        let previous = http_cassette::install(Some(handle.clone()));
+        let result = self.call_value(block, Vec::new(), span);
+        http_cassette::install(previous);
+        let ejected = handle
+            .lock()
+            .map_err(|e| e.to_string())
+            .and_then(|mut c| c.eject());
+        let value = result?;
+        ejected.map_err(general)?;
+        Ok(Some(value))
+    }
+
     fn try_evaluate_factory_call(
         &mut self,
         method: &str,
@@ -1693,6 +1956,7 @@ impl Interpreter {
                 Argument::Positional(expr) | Argument::Block(expr) => {
                     arg_values.push(self.evaluate(expr)?);
                 }
+                Argument::Spread(expr) => arg_values.extend(self.evaluate_spread(expr)?),
                 Argument::Named(_) => return Ok(None),
             }
         }
@@ -1974,8 +2238,10 @@ impl Interpreter {
         let mut new_args = Vec::with_capacity(arguments.len());
         new_args.push(serialised);
         for arg in arguments.iter().skip(1) {
-            if let Argument::Positional(e) = arg {
-                new_args.push(self.evaluate(e)?);
+            match arg {
+                Argument::Positional(e) => new_args.push(self.evaluate(e)?),
+                Argument::Spread(e) => new_args.extend(self.evaluate_spread(e)?),
+                _ => {}
             }
         }
         let result = self.call_value(render_json_val, new_args, span)?;
@@ -1993,89 +2259,15 @@ impl Interpreter {
     ) -> RuntimeResult<Value> {
         match callee {
             Value::Function(func) => {
-                // Filter out block parameters from the regular params for arity calculation
-                let non_block_params: Vec<_> =
-                    func.params.iter().filter(|p| !p.is_block_param).collect();
-                let required_arity = non_block_params
-                    .iter()
-                    .filter(|p| p.default_value.is_none())
-                    .count();
-                let full_arity = non_block_params.len();
-
-                let param_names: Vec<String> =
-                    non_block_params.iter().map(|p| p.name.clone()).collect();
-
-                // Find block parameter if any
-                let block_param_index = func.params.iter().position(|p| p.is_block_param);
-                let block_param_name = block_param_index.map(|i| func.params[i].name.clone());
-
-                // Check for unknown named arguments
-                for name in named_args.keys() {
-                    if !param_names.contains(name) {
-                        return Err(RuntimeError::undefined_variable(name.clone(), span));
-                    }
-                }
-
-                // Build final argument list
-                let mut final_args = Vec::new();
-                let mut used_params = std::collections::HashSet::new();
-
-                // Add positional arguments
-                for (i, arg_val) in positional_args.iter().enumerate() {
-                    if i < param_names.len() {
-                        final_args.push(arg_val.clone());
-                        used_params.insert(param_names[i].clone());
-                    } else {
-                        return Err(RuntimeError::wrong_arity(
-                            full_arity,
-                            positional_args.len() + named_args.len(),
-                            span,
-                        ));
-                    }
-                }
-
-                // Fill in named arguments and defaults
-                for (i, param_name) in param_names.iter().enumerate() {
-                    if used_params.contains(param_name) {
-                        continue;
-                    }
-
-                    if let Some(named_val) = named_args.get(param_name) {
-                        final_args.push(named_val.clone());
-                    } else if let Some(default_expr) = func.param_default_value(i) {
-                        let default_value = self.evaluate(default_expr)?;
-                        final_args.push(default_value);
-                    } else {
-                        return Err(RuntimeError::wrong_arity(
-                            required_arity,
-                            final_args.len(),
-                            span,
-                        ));
-                    }
-                }
-
-                // Handle block argument - bind to block parameter if exists
-                if let Some(ref block_param) = block_param_name {
-                    if let Some(block_val) = block_arg {
-                        // Add block as a named argument for the block parameter
-                        let block_idx = func
-                            .params
-                            .iter()
-                            .position(|n| n.name == *block_param)
-                            .unwrap();
-                        if block_idx < final_args.len() {
-                            final_args[block_idx] = block_val;
-                        } else {
-                            final_args.push(block_val);
-                        }
-                    } else if !named_args.contains_key(block_param) {
-                        // Block param exists but no block was passed
-                        // This is OK - block will be nil/null
-                        final_args.push(Value::Null);
+                let mut slots =
+                    bind_arguments(&func.name, &func.params, positional_args, named_args, span)?;
+                // A trailing block binds to the block parameter, if any.
+                if let Some(block_val) = block_arg {
+                    if let Some(index) = func.params.iter().position(|p| p.is_block_param) {
+                        slots[index] = Some(block_val);
                     }
                 }
-
-                self.call_function(&func, final_args)
+                self.call_function_bound(&func, None, slots)
             }
 
             Value::NativeFunction(native) => {
@@ -2122,67 +2314,26 @@ impl Interpreter {
                 Ok(result)
             }
 
+            Value::Class(class) if class.struct_fields.is_some() => {
+                StructValue::construct(&class, positional_args, named_args)
+                    .map_err(|msg| RuntimeError::type_error(msg, span))
+            }
+
             Value::Class(class) => {
                 // Class instantiation
-                let instance = Rc::new(RefCell::new(Instance::new(class.clone())));
+                let instance =
+                    crate::interpreter::cycles::new_instance(Instance::new(class.clone()));
                 self.initialize_instance_fields(&class, &instance)?;
 
                 if let Some(ref ctor) = class.constructor {
-                    let required_arity = ctor.arity();
-                    let full_arity = ctor.full_arity();
-
-                    let param_names: Vec<String> =
-                        ctor.params.iter().map(|p| p.name.clone()).collect();
-
-                    for name in named_args.keys() {
-                        if !param_names.contains(name) {
-                            return Err(RuntimeError::undefined_variable(name.clone(), span));
-                        }
-                    }
-
-                    let mut ctor_args = Vec::new();
-                    let mut used_params = std::collections::HashSet::new();
-
-                    for (i, arg_val) in positional_args.iter().enumerate() {
-                        if i < param_names.len() {
-                            ctor_args.push(arg_val.clone());
-                            used_params.insert(param_names[i].clone());
-                        } else {
-                            return Err(RuntimeError::wrong_arity(
-                                full_arity,
-                                positional_args.len() + named_args.len(),
-                                span,
-                            ));
-                        }
-                    }
-
-                    for (i, param_name) in param_names.iter().enumerate() {
-                        if used_params.contains(param_name) {
-                            continue;
-                        }
-                        if let Some(named_val) = named_args.get(param_name) {
-                            ctor_args.push(named_val.clone());
-                        } else if let Some(default_expr) = ctor.param_default_value(i) {
-                            let default_value = self.evaluate(default_expr)?;
-                            ctor_args.push(default_value);
-                        } else {
-                            return Err(RuntimeError::wrong_arity(
-                                required_arity,
-                                ctor_args.len(),
-                                span,
-                            ));
-                        }
-                    }
-
-                    let ctor_env = Environment::with_enclosing(ctor.closure.clone());
-                    let mut ctor_env = ctor_env;
-                    ctor_env.define("this".to_string(), Value::Instance(instance.clone()));
-
-                    for (param, value) in ctor.params.iter().zip(ctor_args.iter()) {
-                        ctor_env.define(param.name.clone(), value.clone());
-                    }
-
-                    self.execute_constructor_body(ctor, ctor_env);
+                    let slots = bind_arguments(
+                        &class.name,
+                        &ctor.params,
+                        positional_args,
+                        named_args,
+                        span,
+                    )?;
+                    self.run_constructor(ctor, &instance, slots.into_iter())?;
                 }
 
                 Ok(Value::Instance(instance))
@@ -2193,59 +2344,16 @@ impl Interpreter {
                 match inst.borrow().get_method("call") {
                     Some(method) => match method {
                         Value::Function(func) => {
-                            let required_arity = func.arity();
-                            let full_arity = func.full_arity();
-
-                            let param_names: Vec<String> =
-                                func.params.iter().map(|p| p.name.clone()).collect();
-
-                            for name in named_args.keys() {
-                                if !param_names.contains(name) {
-                                    return Err(RuntimeError::undefined_variable(
-                                        name.clone(),
-                                        span,
-                                    ));
-                                }
-                            }
-
-                            let mut method_args = vec![Value::Instance(inst.clone())];
-                            let mut used_params = std::collections::HashSet::new();
-
-                            for (i, arg_val) in positional_args.iter().enumerate() {
-                                if i + 1 < param_names.len() {
-                                    method_args.push(arg_val.clone());
-                                    used_params.insert(param_names[i + 1].clone());
-                                } else {
-                                    return Err(RuntimeError::wrong_arity(
-                                        full_arity,
-                                        positional_args.len() + named_args.len() + 1,
-                                        span,
-                                    ));
-                                }
-                            }
-
-                            for (i, param_name) in param_names.iter().enumerate() {
-                                if i == 0 {
-                                    continue;
-                                }
-                                if used_params.contains(param_name) {
-                                    continue;
-                                }
-                                if let Some(named_val) = named_args.get(param_name) {
-                                    method_args.push(named_val.clone());
-                                } else if let Some(default_expr) = func.param_default_value(i) {
-                                    let default_value = self.evaluate(default_expr)?;
-                                    method_args.push(default_value);
-                                } else {
-                                    return Err(RuntimeError::wrong_arity(
-                                        required_arity,
-                                        method_args.len() - 1,
-                                        span,
-                                    ));
-                                }
-                            }
-
-                            self.call_function(&func, method_args)
+                            // The first parameter receives the instance itself.
+                            let mut slots = bind_arguments(
+                                "call",
+                                func.params.get(1..).unwrap_or_default(),
+                                positional_args,
+                                named_args,
+                                span,
+                            )?;
+                            slots.insert(0, Some(Value::Instance(inst.clone())));
+                            self.call_function_bound(&func, None, slots)
                         }
                         _ => Err(RuntimeError::type_error(
                             "callable object method is not a function",
@@ -2269,61 +2377,14 @@ impl Interpreter {
                 };
 
                 if let Some(ref ctor) = superclass.constructor {
-                    let required_arity = ctor.arity();
-                    let full_arity = ctor.full_arity();
-
-                    let param_names: Vec<String> =
-                        ctor.params.iter().map(|p| p.name.clone()).collect();
-
-                    for name in named_args.keys() {
-                        if !param_names.contains(name) {
-                            return Err(RuntimeError::undefined_variable(name.clone(), span));
-                        }
-                    }
-
-                    let mut ctor_args = Vec::new();
-                    let mut used_params = std::collections::HashSet::new();
-
-                    for (i, arg_val) in positional_args.iter().enumerate() {
-                        if i < param_names.len() {
-                            ctor_args.push(arg_val.clone());
-                            used_params.insert(param_names[i].clone());
-                        } else {
-                            return Err(RuntimeError::wrong_arity(
-                                full_arity,
-                                positional_args.len() + named_args.len(),
-                                span,
-                            ));
-                        }
-                    }
-
-                    for (i, param_name) in param_names.iter().enumerate() {
-                        if used_params.contains(param_name) {
-                            continue;
-                        }
-                        if let Some(named_val) = named_args.get(param_name) {
-                            ctor_args.push(named_val.clone());
-                        } else if let Some(default_expr) = ctor.param_default_value(i) {
-                            let default_value = self.evaluate(default_expr)?;
-                            ctor_args.push(default_value);
-                        } else {
-                            return Err(RuntimeError::wrong_arity(
-                                required_arity,
-                                ctor_args.len(),
-                                span,
-                            ));
-                        }
-                    }
-
-                    let ctor_env = Environment::with_enclosing(ctor.closure.clone());
-                    let mut ctor_env = ctor_env;
-                    ctor_env.define("this".to_string(), Value::Instance(instance.clone()));
-
-                    for (param, value) in ctor.params.iter().zip(ctor_args) {
-                        ctor_env.define(param.name.clone(), value);
-                    }
-
-                    self.execute_constructor_body(ctor, ctor_env);
+                    let slots = bind_arguments(
+                        &superclass.name,
+                        &ctor.params,
+                        positional_args,
+                        named_args,
+                        span,
+                    )?;
+                    self.run_constructor(ctor, &instance, slots.into_iter())?;
                 }
 
                 Ok(Value::Null)
@@ -2360,11 +2421,16 @@ impl Interpreter {
     pub(crate) fn call_value(
         &mut self,
         callee: Value,
-        mut arguments: Vec<Value>,
+        arguments: Vec<Value>,
         span: Span,
     ) -> RuntimeResult<Value> {
         match callee {
             Value::Function(func) => {
+                if func.needs_argument_binding() {
+                    let slots =
+                        bind_arguments(&func.name, &func.params, arguments, HashMap::new(), span)?;
+                    return self.call_function_bound(&func, None, slots);
+                }
                 let required_arity = func.arity();
                 let full_arity = func.full_arity();
 
@@ -2380,15 +2446,7 @@ impl Interpreter {
                     return Err(RuntimeError::wrong_arity(full_arity, arguments.len(), span));
                 }
 
-                while arguments.len() < full_arity {
-                    if let Some(default_expr) = func.param_default_value(arguments.len()) {
-                        let default_value = self.evaluate(default_expr)?;
-                        arguments.push(default_value);
-                    } else {
-                        return Err(RuntimeError::wrong_arity(full_arity, arguments.len(), span));
-                    }
-                }
-
+                // Omitted trailing parameters take their defaults in the callee.
                 self.call_function(&func, arguments)
             }
 
@@ -2409,49 +2467,20 @@ impl Interpreter {
                 Ok(result)
             }
 
+            Value::Class(class) if class.struct_fields.is_some() => {
+                StructValue::construct(&class, arguments, HashMap::new())
+                    .map_err(|msg| RuntimeError::type_error(msg, span))
+            }
+
             Value::Class(class) => {
-                let instance = Rc::new(RefCell::new(Instance::new(class.clone())));
+                let instance =
+                    crate::interpreter::cycles::new_instance(Instance::new(class.clone()));
                 self.initialize_instance_fields(&class, &instance)?;
 
                 if let Some(ref ctor) = class.constructor {
-                    let required_arity = ctor.arity();
-                    let full_arity = ctor.full_arity();
-
-                    if arguments.len() < required_arity {
-                        return Err(RuntimeError::wrong_arity(
-                            required_arity,
-                            arguments.len(),
-                            span,
-                        ));
-                    }
-
-                    if arguments.len() > full_arity {
-                        return Err(RuntimeError::wrong_arity(full_arity, arguments.len(), span));
-                    }
-
-                    let mut final_args = arguments.clone();
-                    while final_args.len() < full_arity {
-                        if let Some(default_expr) = ctor.param_default_value(final_args.len()) {
-                            let default_value = self.evaluate(default_expr)?;
-                            final_args.push(default_value);
-                        } else {
-                            return Err(RuntimeError::wrong_arity(
-                                full_arity,
-                                final_args.len(),
-                                span,
-                            ));
-                        }
-                    }
-
-                    let ctor_env = Environment::with_enclosing(ctor.closure.clone());
-                    let mut ctor_env = ctor_env;
-                    ctor_env.define("this".to_string(), Value::Instance(instance.clone()));
-
-                    for (param, value) in ctor.params.iter().zip(final_args) {
-                        ctor_env.define(param.name.clone(), value);
-                    }
-
-                    self.execute_constructor_body(ctor, ctor_env);
+                    let slots =
+                        bind_arguments(&class.name, &ctor.params, arguments, HashMap::new(), span)?;
+                    self.run_constructor(ctor, &instance, slots.into_iter())?;
                 }
 
                 Ok(Value::Instance(instance))
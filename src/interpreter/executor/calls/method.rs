@@ -11,6 +11,8 @@ use crate::interpreter::value::{
 };
 use crate::span::Span;
 
+use super::range_methods::call_range_method_impl;
+
 impl Interpreter {
     pub(crate) fn call_hash_method_on_rc(
         &mut self,
@@ -227,9 +229,21 @@ impl Interpreter {
             Value::Array(ref arr) => {
                 self.call_array_method_on_rc(arr, &method.method_name, arguments, span)
             }
+            Value::Range(ref range) => {
+                if let Some(result) =
+                    call_range_method_impl(range, &method.method_name, &arguments, span)
+                {
+                    return result;
+                }
+                let arr = Rc::new(RefCell::new(range.to_vec()));
+                self.call_array_method_on_rc(&arr, &method.method_name, arguments, span)
+            }
             Value::Hash(ref hash) => {
                 self.call_hash_method_on_rc(hash, &method.method_name, arguments, span)
             }
+            Value::Iterator(ref iter) => {
+                self.call_iterator_method(iter, &method.method_name, arguments, span)
+            }
             Value::QueryBuilder(ref qb) => {
                 self.call_query_builder_method(qb.clone(), &method.method_name, arguments, span)
             }
@@ -1887,9 +1901,9 @@ impl Interpreter {
             return Ok(Value::Null);
         }
         use rand::seq::SliceRandom;
-        use rand::thread_rng;
-        let mut rng = thread_rng();
-        Ok(items.choose(&mut rng).cloned().unwrap_or(Value::Null))
+        Ok(crate::interpreter::builtins::rng::with_rng(|rng| {
+            items.choose(rng).cloned().unwrap_or(Value::Null)
+        }))
     }
 
     fn array_shuffle(
@@ -1902,10 +1916,8 @@ impl Interpreter {
             return Err(RuntimeError::wrong_arity(0, arguments.len(), span));
         }
         use rand::seq::SliceRandom;
-        use rand::thread_rng;
         let mut result = items.to_vec();
-        let mut rng = thread_rng();
-        result.shuffle(&mut rng);
+        crate::interpreter::builtins::rng::with_rng(|rng| result.shuffle(rng));
         Ok(Value::Array(Rc::new(RefCell::new(result))))
     }
 
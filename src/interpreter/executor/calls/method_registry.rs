@@ -1839,6 +1839,86 @@ pub const QUERY_BUILDER_METHODS: &[MethodDef] = &[
     },
 ];
 
+/// Iterator methods. Anything else collects the iterator and runs the
+/// array method of that name.
+pub const ITERATOR_METHODS: &[MethodDef] = &[
+    MethodDef {
+        name: "class",
+        zero_arg: true,
+        ret: "string",
+    },
+    MethodDef {
+        name: "drop",
+        zero_arg: false,
+        ret: "",
+    },
+    MethodDef {
+        name: "each",
+        zero_arg: false,
+        ret: "null",
+    },
+    MethodDef {
+        name: "filter",
+        zero_arg: false,
+        ret: "",
+    },
+    MethodDef {
+        name: "find",
+        zero_arg: false,
+        ret: "",
+    },
+    MethodDef {
+        name: "first",
+        zero_arg: true,
+        ret: "",
+    },
+    MethodDef {
+        name: "inspect",
+        zero_arg: true,
+        ret: "string",
+    },
+    MethodDef {
+        name: "map",
+        zero_arg: false,
+        ret: "",
+    },
+    MethodDef {
+        name: "next",
+        zero_arg: true,
+        ret: "",
+    },
+    MethodDef {
+        name: "nil?",
+        zero_arg: true,
+        ret: "bool",
+    },
+    MethodDef {
+        name: "reject",
+        zero_arg: false,
+        ret: "",
+    },
+    MethodDef {
+        name: "select",
+        zero_arg: false,
+        ret: "",
+    },
+    MethodDef {
+        name: "take",
+        zero_arg: false,
+        ret: "",
+    },
+    MethodDef {
+        name: "to_a",
+        zero_arg: true,
+        ret: "array",
+    },
+    MethodDef {
+        name: "to_s",
+        zero_arg: true,
+        ret: "string",
+    },
+];
+
 // ---------------------------------------------------------------------------
 // Lookup functions
 // ---------------------------------------------------------------------------
@@ -1856,6 +1936,7 @@ pub fn known_methods(type_name: &str) -> &'static [MethodDef] {
         "array" => ARRAY_METHODS,
         "hash" => HASH_METHODS,
         "query_builder" => QUERY_BUILDER_METHODS,
+        "iterator" => ITERATOR_METHODS,
         _ => &[],
     }
 }
@@ -1897,6 +1978,13 @@ pub fn is_zero_arg_method(method_name: &str, receiver: &Value) -> bool {
         Value::Array(_) => "array",
         Value::Hash(_) => "hash",
         Value::QueryBuilder(_) => "query_builder",
+        Value::Iterator(_) => {
+            return ITERATOR_METHODS
+                .iter()
+                .chain(ARRAY_METHODS)
+                .find(|m| m.name == method_name)
+                .is_some_and(|m| m.zero_arg)
+        }
         _ => return false,
     };
     known_methods(type_name)
@@ -1918,6 +2006,7 @@ pub fn method_return_type(type_name: &str, method_name: &str) -> Option<&'static
         "array" => "array",
         "hash" => "hash",
         "query_builder" => "query_builder",
+        "iterator" => "iterator",
         _ => return None,
     };
     known_methods(static_type)
@@ -1,5 +1,6 @@
 //! Call expression modules.
 
+pub(crate) mod arguments;
 pub(crate) mod array_ops;
 pub(crate) mod bool_methods;
 mod cascade;
@@ -12,6 +13,7 @@ mod method;
 pub mod method_registry;
 pub(crate) mod null_methods;
 mod pipeline;
-mod query_builder_methods;
+pub(crate) mod query_builder_methods;
+pub(crate) mod range_methods;
 pub(crate) mod string_methods;
 pub mod user_methods;
@@ -21,41 +21,59 @@ impl Interpreter {
 
         match &right.kind {
             ExprKind::Call { callee, arguments } => {
-                // Check for array methods: map, filter, each
-                if let ExprKind::Variable(name) = &callee.kind {
+                // Check for array / iterator methods: map, filter, each
+                if let ExprKind::Variable(name, _) = &callee.kind {
                     if matches!(name.as_str(), "map" | "filter" | "each") {
                         let resolved = left_val
                             .resolve()
                             .map_err(|e| RuntimeError::type_error(e, span))?;
-                        if let Value::Array(arr) = resolved {
-                            let items: Vec<Value> = arr.borrow().clone();
-                            let mut args = Vec::new();
-                            for arg in arguments {
-                                match arg {
-                                    Argument::Positional(expr) => {
-                                        args.push(self.evaluate(expr)?);
-                                    }
-                                    Argument::Named(_) => {
-                                        return Err(RuntimeError::type_error(
-                                            "pipeline method does not support named arguments",
-                                            span,
-                                        ));
-                                    }
-                                    Argument::Block(_) => {
-                                        return Err(RuntimeError::type_error(
-                                            "pipeline method does not support block arguments",
-                                            span,
-                                        ));
-                                    }
-                                }
-                            }
-                            return self.call_array_method(&items, name, args, span);
-                        } else {
+                        let items = match &resolved {
+                            Value::Array(arr) => Some(arr.borrow().clone()),
+                            Value::Range(range) => Some(range.to_vec()),
+                            _ => None,
+                        };
+                        if items.is_none() && !matches!(resolved, Value::Iterator(_)) {
                             return Err(RuntimeError::type_error(
-                                format!("{}() expects array, got {}", name, resolved.type_name()),
+                                format!(
+                                    "{}() expects an array or iterator, got {}",
+                                    name,
+                                    resolved.type_name()
+                                ),
                                 span,
                             ));
                         }
+                        let mut args = Vec::new();
+                        for arg in arguments {
+                            match arg {
+                                Argument::Positional(expr) => {
+                                    args.push(self.evaluate(expr)?);
+                                }
+                                Argument::Spread(expr) => {
+                                    args.extend(self.evaluate_spread(expr)?);
+                                }
+                                Argument::Named(_) => {
+                                    return Err(RuntimeError::type_error(
+                                        "pipeline method does not support named arguments",
+                                        span,
+                                    ));
+                                }
+                                Argument::Block(_) => {
+                                    return Err(RuntimeError::type_error(
+                                        "pipeline method does not support block arguments",
+                                        span,
+                                    ));
+                                }
+                            }
+                        }
+                        // An iterator stays lazy through `map` / `filter`;
+                        // `each` drains it.
+                        return match (items, &resolved) {
+                            (Some(items), _) => self.call_array_method(&items, name, args, span),
+                            (None, Value::Iterator(iter)) => {
+                                self.call_iterator_method(iter, name, args, span)
+                            }
+                            (None, _) => unreachable!("checked above"),
+                        };
                     }
                 }
 
@@ -66,6 +84,9 @@ impl Interpreter {
                         Argument::Positional(expr) => {
                             new_args.push(self.evaluate(expr)?);
                         }
+                        Argument::Spread(expr) => {
+                            new_args.extend(self.evaluate_spread(expr)?);
+                        }
                         Argument::Named(_) => {
                             return Err(RuntimeError::type_error(
                                 "pipeline method does not support named arguments",
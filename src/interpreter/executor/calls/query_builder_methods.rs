@@ -13,6 +13,44 @@ use crate::interpreter::executor::{Interpreter, RuntimeResult};
 use crate::interpreter::value::Value;
 use crate::span::Span;
 
+/// Array methods a QueryBuilder answers by materializing its rows first.
+pub(crate) fn is_query_builder_passthrough(method_name: &str) -> bool {
+    matches!(
+        method_name,
+        "length"
+            | "len"
+            | "size"
+            | "each"
+            | "map"
+            | "filter"
+            | "reduce"
+            | "find"
+            | "any?"
+            | "all?"
+            | "sort"
+            | "sort_by"
+            | "reverse"
+            | "uniq"
+            | "compact"
+            | "compact_blank"
+            | "flatten"
+            | "last"
+            | "empty?"
+            | "includes?"
+            | "contains"
+            | "sample"
+            | "shuffle"
+            | "take"
+            | "drop"
+            | "zip"
+            | "to_string"
+            | "to_json"
+            | "is_a?"
+            | "to_a"
+            | "to_array"
+    )
+}
+
 impl Interpreter {
     /// Handle QueryBuilder methods for chaining: where, order, limit, offset, all, first, count
     pub(crate) fn call_query_builder_method(
@@ -21,6 +59,35 @@ impl Interpreter {
         method_name: &str,
         arguments: Vec<Value>,
         span: Span,
+    ) -> RuntimeResult<Value> {
+        match method_name {
+            "create" => self.qb_create(qb, arguments, span),
+            // Array passthrough: materialize the QueryBuilder once, then
+            // dispatch the method to the resulting array. Lets has_many
+            // relations behave Enumerable-style — user.posts.each(...),
+            // user.posts.map(...), user.posts.length, etc.
+            _ if is_query_builder_passthrough(method_name) => {
+                let materialized =
+                    crate::interpreter::builtins::model::execute_query_builder(&qb.borrow());
+                let method = Rc::new(crate::interpreter::value::ValueMethod {
+                    receiver: Box::new(materialized),
+                    method_name: method_name.to_string(),
+                });
+                self.call_method(method, arguments, span)
+            }
+            _ => Self::call_query_builder_chain(qb, method_name, arguments, span),
+        }
+    }
+
+    /// The QueryBuilder methods that only build or run the query — no user
+    /// code, so the VM dispatches them here too. `create` (which saves
+    /// through the model callbacks) and the array passthrough are handled
+    /// by the caller.
+    pub(crate) fn call_query_builder_chain(
+        qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
+        method_name: &str,
+        arguments: Vec<Value>,
+        span: Span,
     ) -> RuntimeResult<Value> {
         // Traversal mode (instance.traverse(...)) composes with
         // where/order/limit/offset/select/pluck and the terminals, but not
@@ -46,55 +113,38 @@ impl Interpreter {
         }
 
         match method_name {
-            "create" => self.qb_create(qb, arguments, span),
-            "where" => self.qb_where(qb, arguments, span),
-            "order" => self.qb_order(qb, arguments, span),
-            "limit" => self.qb_limit(qb, arguments, span),
-            "offset" => self.qb_offset(qb, arguments, span),
-            "includes" => self.qb_includes(qb, arguments, span),
-            "includes_count" => self.qb_includes_count(qb, arguments, span),
-            "select" | "fields" => self.qb_select(qb, arguments, span),
-            "join" => self.qb_join(qb, arguments, span),
-            "all" => self.qb_all(qb, arguments, span),
-            "first" => self.qb_first(qb, arguments, span),
-            "count" => self.qb_count(qb, arguments, span),
-            "paginate" => self.qb_paginate(qb, arguments, span),
-            "delete_all" => self.qb_delete_all(qb, arguments, span),
-            "update_all" => self.qb_update_all(qb, arguments, span),
-            "exists" => self.qb_exists(qb, arguments, span),
-            "pluck" => self.qb_pluck(qb, arguments, span),
-            "similar" => self.qb_similar(qb, arguments, span),
-            "sum" => self.qb_aggregate(qb, arguments, span, AggregationFunc::Sum),
-            "avg" => self.qb_aggregate(qb, arguments, span, AggregationFunc::Avg),
-            "min" => self.qb_aggregate(qb, arguments, span, AggregationFunc::Min),
-            "max" => self.qb_aggregate(qb, arguments, span, AggregationFunc::Max),
-            "median" => self.qb_aggregate(qb, arguments, span, AggregationFunc::Median),
-            "stddev" => self.qb_aggregate(qb, arguments, span, AggregationFunc::Stddev),
-            "variance" => self.qb_aggregate(qb, arguments, span, AggregationFunc::Variance),
+            "where" => Self::qb_where(qb, arguments, span),
+            "order" => Self::qb_order(qb, arguments, span),
+            "limit" => Self::qb_limit(qb, arguments, span),
+            "offset" => Self::qb_offset(qb, arguments, span),
+            "includes" => Self::qb_includes(qb, arguments, span),
+            "includes_count" => Self::qb_includes_count(qb, arguments, span),
+            "select" | "fields" => Self::qb_select(qb, arguments, span),
+            "join" => Self::qb_join(qb, arguments, span),
+            "all" => Self::qb_all(qb, arguments, span),
+            "first" => Self::qb_first(qb, arguments, span),
+            "count" => Self::qb_count(qb, arguments, span),
+            "paginate" => Self::qb_paginate(qb, arguments, span),
+            "delete_all" => Self::qb_delete_all(qb, arguments, span),
+            "update_all" => Self::qb_update_all(qb, arguments, span),
+            "exists" => Self::qb_exists(qb, arguments, span),
+            "pluck" => Self::qb_pluck(qb, arguments, span),
+            "similar" => Self::qb_similar(qb, arguments, span),
+            "sum" => Self::qb_aggregate(qb, arguments, span, AggregationFunc::Sum),
+            "avg" => Self::qb_aggregate(qb, arguments, span, AggregationFunc::Avg),
+            "min" => Self::qb_aggregate(qb, arguments, span, AggregationFunc::Min),
+            "max" => Self::qb_aggregate(qb, arguments, span, AggregationFunc::Max),
+            "median" => Self::qb_aggregate(qb, arguments, span, AggregationFunc::Median),
+            "stddev" => Self::qb_aggregate(qb, arguments, span, AggregationFunc::Stddev),
+            "variance" => Self::qb_aggregate(qb, arguments, span, AggregationFunc::Variance),
             "count_distinct" => {
-                self.qb_aggregate(qb, arguments, span, AggregationFunc::CountDistinct)
-            }
-            "aggregate" => self.qb_aggregate_spec(qb, arguments, span),
-            "having" => self.qb_having(qb, arguments, span),
-            "group_by" => self.qb_group_by(qb, arguments, span),
-            "time_bucket" => self.qb_time_bucket(qb, arguments, span),
-            "to_query" => self.qb_to_query(qb, arguments, span),
-            // Array passthrough: materialize the QueryBuilder once, then
-            // dispatch the method to the resulting array. Lets has_many
-            // relations behave Enumerable-style — user.posts.each(...),
-            // user.posts.map(...), user.posts.length, etc.
-            "length" | "len" | "size" | "each" | "map" | "filter" | "reduce" | "find" | "any?"
-            | "all?" | "sort" | "sort_by" | "reverse" | "uniq" | "compact" | "compact_blank"
-            | "flatten" | "last" | "empty?" | "includes?" | "contains" | "sample" | "shuffle"
-            | "take" | "drop" | "zip" | "to_string" | "to_json" | "is_a?" | "to_a" | "to_array" => {
-                let materialized =
-                    crate::interpreter::builtins::model::execute_query_builder(&qb.borrow());
-                let method = Rc::new(crate::interpreter::value::ValueMethod {
-                    receiver: Box::new(materialized),
-                    method_name: method_name.to_string(),
-                });
-                self.call_method(method, arguments, span)
+                Self::qb_aggregate(qb, arguments, span, AggregationFunc::CountDistinct)
             }
+            "aggregate" => Self::qb_aggregate_spec(qb, arguments, span),
+            "having" => Self::qb_having(qb, arguments, span),
+            "group_by" => Self::qb_group_by(qb, arguments, span),
+            "time_bucket" => Self::qb_time_bucket(qb, arguments, span),
+            "to_query" => Self::qb_to_query(qb, arguments, span),
             _ => Err(RuntimeError::NoSuchProperty {
                 value_type: "QueryBuilder".to_string(),
                 property: method_name.to_string(),
@@ -189,7 +239,6 @@ persisted record (e.g. user.posts.create({...})) — use Model.create for plain
     }
 
     fn qb_where(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -276,7 +325,6 @@ persisted record (e.g. user.posts.create({...})) — use Model.create for plain
     }
 
     fn qb_order(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -327,7 +375,6 @@ persisted record (e.g. user.posts.create({...})) — use Model.create for plain
     }
 
     fn qb_limit(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -351,7 +398,6 @@ persisted record (e.g. user.posts.create({...})) — use Model.create for plain
     }
 
     fn qb_offset(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -375,7 +421,6 @@ persisted record (e.g. user.posts.create({...})) — use Model.create for plain
     }
 
     fn qb_includes(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -558,7 +603,6 @@ persisted record (e.g. user.posts.create({...})) — use Model.create for plain
     }
 
     fn qb_includes_count(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -599,7 +643,6 @@ persisted record (e.g. user.posts.create({...})) — use Model.create for plain
     }
 
     fn qb_select(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -634,7 +677,6 @@ persisted record (e.g. user.posts.create({...})) — use Model.create for plain
     }
 
     fn qb_join(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -698,7 +740,6 @@ persisted record (e.g. user.posts.create({...})) — use Model.create for plain
     }
 
     fn qb_all(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -733,7 +774,6 @@ persisted record (e.g. user.posts.create({...})) — use Model.create for plain
     }
 
     fn qb_first(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -775,7 +815,6 @@ persisted record (e.g. user.posts.create({...})) — use Model.create for plain
     }
 
     fn qb_count(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -787,7 +826,6 @@ persisted record (e.g. user.posts.create({...})) — use Model.create for plain
     }
 
     fn qb_paginate(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -853,7 +891,6 @@ persisted record (e.g. user.posts.create({...})) — use Model.create for plain
     }
 
     fn qb_exists(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -868,7 +905,6 @@ persisted record (e.g. user.posts.create({...})) — use Model.create for plain
     }
 
     fn qb_delete_all(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -888,7 +924,6 @@ target rows, not join rows; delete the through relation's records instead"
     }
 
     fn qb_update_all(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -933,7 +968,6 @@ through relation's records instead"
     }
 
     fn qb_pluck(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -967,7 +1001,6 @@ through relation's records instead"
     }
 
     fn qb_aggregate(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -995,7 +1028,6 @@ through relation's records instead"
     }
 
     fn qb_group_by(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -1118,7 +1150,6 @@ through relation's records instead"
     /// grouped mode (with or without group_by). Repeated calls extend the
     /// spec list.
     fn qb_aggregate_spec(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -1143,7 +1174,6 @@ through relation's records instead"
     /// group/aggregate aliases. String is developer-trusted like string-form
     /// where(); binds merge into the query's bind vars.
     fn qb_having(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -1204,7 +1234,6 @@ through relation's records instead"
     /// sum/avg/min/max keys, plus `count: true`. Keyword style works too:
     /// `.time_bucket("1h", avg: "value")` (named args collapse to a hash).
     fn qb_time_bucket(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -1251,7 +1280,6 @@ through relation's records instead"
     }
 
     fn qb_to_query(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
@@ -1296,7 +1324,6 @@ through relation's records instead"
     /// Options: `exact: true` (force client-side exact cosine even with a
     /// declared vector index), `ef_search: n` (ANN search width).
     fn qb_similar(
-        &mut self,
         qb: Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
         arguments: Vec<Value>,
         span: Span,
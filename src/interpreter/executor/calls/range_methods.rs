@@ -0,0 +1,65 @@
+//! Range method implementations.
+//!
+//! A range answers size, bound and membership questions from its endpoints;
+//! every other method runs on the equivalent array (the caller materializes
+//! it when `call_range_method_impl` returns `None`). Shared by the
+//! tree-walker and the VM.
+
+use crate::error::RuntimeError;
+use crate::interpreter::executor::RuntimeResult;
+use crate::interpreter::value::{RangeValue, Value};
+use crate::span::Span;
+
+/// Range methods that take arguments. Member access hands these back as a
+/// bound method instead of evaluating them.
+pub(crate) fn is_range_method_with_args(name: &str) -> bool {
+    matches!(name, "include?" | "includes?" | "includes" | "contains")
+}
+
+/// Range method dispatch shared by the tree-walker and the VM. `None` means
+/// the range has no method of that name, so it falls through to arrays.
+pub(crate) fn call_range_method_impl(
+    range: &RangeValue,
+    method_name: &str,
+    arguments: &[Value],
+    span: Span,
+) -> Option<RuntimeResult<Value>> {
+    if is_range_method_with_args(method_name) {
+        if arguments.len() != 1 {
+            return Some(Err(RuntimeError::wrong_arity(1, arguments.len(), span)));
+        }
+        let found = matches!(arguments[0], Value::Int(n) if range.contains(n));
+        return Some(Ok(Value::Bool(found)));
+    }
+    if !arguments.is_empty() {
+        return None;
+    }
+    let first = || range.get(0).map_or(Value::Null, Value::Int);
+    let last = || range.get(-1).map_or(Value::Null, Value::Int);
+    let result = match method_name {
+        "class" => Value::String("range".into()),
+        "nil?" => Value::Bool(false),
+        "blank?" | "empty?" => Value::Bool(range.is_empty()),
+        "present?" => Value::Bool(!range.is_empty()),
+        "length" | "len" | "size" | "count" => Value::Int(range.len() as i64),
+        "first" | "min" => first(),
+        "last" | "max" => last(),
+        "sum" => return Some(range_sum(range, span)),
+        "to_s" | "to_string" | "inspect" => Value::String(range.to_string().into()),
+        _ => return None,
+    };
+    Some(Ok(result))
+}
+
+/// Arithmetic series sum, so `(1..=1_000_000).sum` never walks the range.
+fn range_sum(range: &RangeValue, span: Span) -> RuntimeResult<Value> {
+    if range.is_empty() {
+        return Ok(Value::Int(0));
+    }
+    let n = range.len() as i128;
+    let first = range.start as i128;
+    let last = first + n - 1;
+    i64::try_from(n * (first + last) / 2)
+        .map(Value::Int)
+        .map_err(|_| RuntimeError::type_error("range sum overflows Int", span))
+}
@@ -1,10 +1,32 @@
 //! Lambda/anonymous function evaluation.
+//!
+//! A lambda captures by reference: its closure *is* the scope it was created
+//! in, so it reads the variables' current values when it runs, assignments
+//! inside it are visible outside (and the other way round), and two lambdas
+//! made in the same scope share the same variables. Nested lambdas see through
+//! every enclosing scope the same way. Loops give each iteration a fresh
+//! scope (see `loop_capture`), so a lambda made in a loop body keeps that
+//! iteration's loop-locals.
+//!
+//! `move fn() { ... }` captures by value instead: every variable it uses
+//! that is local to an enclosing function or block is copied into a scope of
+//! its own when the lambda is created. The lambda keeps its copies between
+//! calls — assigning to one changes only the lambda's copy — and later
+//! changes outside don't reach it. The copy is of the binding, not the value:
+//! an array or hash is still the same object. Top-level variables are globals
+//! and stay shared, exactly as in the bytecode VM.
 
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::rc::Rc;
+use std::sync::Arc;
 
-use crate::ast::Parameter;
+use crate::ast::expr::{Argument, Expr, ExprKind, HashComprehension, InterpolatedPart};
+use crate::ast::stmt::StmtKind;
 use crate::ast::Stmt;
 use crate::ast::TypeAnnotation;
+use crate::ast::{Body, Parameter};
+use crate::interpreter::environment::Environment;
 use crate::interpreter::executor::{Interpreter, RuntimeResult};
 use crate::interpreter::value::{Function, Value};
 use crate::span::Span;
@@ -14,16 +36,25 @@ impl Interpreter {
     pub(crate) fn evaluate_lambda(
         &mut self,
         params: &[Parameter],
-        body: &[Stmt],
+        body: &Arc<Body>,
         return_type: &Option<Box<TypeAnnotation>>,
+        is_move: bool,
         span: Span,
     ) -> RuntimeResult<Value> {
+        let closure = if is_move {
+            self.move_capture_env(params, body)
+        } else {
+            self.environment.clone()
+        };
+        crate::interpreter::cycles::track_scope(&closure);
         let func = Function {
             name: "<lambda>".to_string(),
             params: params.to_vec().into(),
-            body: body.to_vec().into(),
-            closure: self.environment.clone(),
+            body: Arc::clone(body),
+            closure,
             is_method: false,
+            is_generator: false,
+            is_async: false,
             span: Some(span),
             source_path: self
                 .current_source_path
@@ -31,9 +62,261 @@ impl Interpreter {
                 .map(|p| p.to_string_lossy().to_string()),
             defining_superclass: None,
             return_type: return_type.as_deref().cloned(),
+            decorators: None,
             cached_env: std::cell::RefCell::new(None),
             jit_cache: std::cell::RefCell::new(None),
         };
         Ok(Value::Function(Rc::new(func)))
     }
+
+    /// The closure scope of a `move` lambda: a copy of every non-global
+    /// variable the body mentions, in front of the current scope (which
+    /// still resolves globals, `this`, and names not bound yet).
+    fn move_capture_env(&self, params: &[Parameter], body: &[Stmt]) -> Rc<RefCell<Environment>> {
+        let mut names = HashSet::new();
+        for param in params {
+            if let Some(default) = &param.default_value {
+                expr_names(default, &mut names);
+            }
+        }
+        for stmt in body {
+            stmt_names(stmt, &mut names);
+        }
+        for param in params {
+            names.remove(&param.name);
+        }
+
+        let mut captured = Environment::with_enclosing(self.environment.clone());
+        for name in names {
+            if let Some(value) = local_binding(&self.environment, &name) {
+                captured.define(name, value);
+            }
+        }
+        Rc::new(RefCell::new(captured))
+    }
+}
+
+/// `name`'s value in the innermost scope that binds it, unless that scope is
+/// the global one.
+fn local_binding(env: &Rc<RefCell<Environment>>, name: &str) -> Option<Value> {
+    let mut scope = env.clone();
+    loop {
+        let next = {
+            let current = scope.borrow();
+            let enclosing = current.enclosing()?;
+            if let Some(value) = current.get_local(name) {
+                return Some(value);
+            }
+            enclosing
+        };
+        scope = next;
+    }
+}
+
+/// Collect every variable name `stmt` reads or assigns, nested functions and
+/// lambdas included. Over-approximates (a body's own locals are collected
+/// too), which is harmless: a name is only copied if an enclosing scope
+/// binds it, and the body's own `let` shadows the copy.
+fn stmt_names(stmt: &Stmt, names: &mut HashSet<String>) {
+    match &stmt.kind {
+        StmtKind::Expression(e) | StmtKind::Throw(e) | StmtKind::Yield(e) => expr_names(e, names),
+        StmtKind::Let { initializer, .. } => {
+            if let Some(e) = initializer {
+                expr_names(e, names);
+            }
+        }
+        StmtKind::Const { initializer, .. } | StmtKind::LetTuple { initializer, .. } => {
+            expr_names(initializer, names)
+        }
+        StmtKind::Return(e) => {
+            if let Some(e) = e {
+                expr_names(e, names);
+            }
+        }
+        StmtKind::Block(stmts) => stmts.iter().for_each(|s| stmt_names(s, names)),
+        StmtKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            expr_names(condition, names);
+            stmt_names(then_branch, names);
+            if let Some(eb) = else_branch {
+                stmt_names(eb, names);
+            }
+        }
+        StmtKind::While { condition, body } => {
+            expr_names(condition, names);
+            stmt_names(body, names);
+        }
+        StmtKind::For { iterable, body, .. } => {
+            expr_names(iterable, names);
+            stmt_names(body, names);
+        }
+        StmtKind::Try {
+            try_block,
+            catch_clauses,
+            finally_block,
+        } => {
+            stmt_names(try_block, names);
+            for clause in catch_clauses {
+                stmt_names(&clause.body, names);
+            }
+            if let Some(fb) = finally_block {
+                stmt_names(fb, names);
+            }
+        }
+        StmtKind::Function(decl) => decl.body.iter().for_each(|s| stmt_names(s, names)),
+        StmtKind::Export(inner) => stmt_names(inner, names),
+        // Class bodies have their own scope rules; nothing else names a
+        // variable.
+        StmtKind::Break
+        | StmtKind::Class(_)
+        | StmtKind::Enum(_)
+        | StmtKind::Struct(_)
+        | StmtKind::Interface(_)
+        | StmtKind::TypeAlias(_)
+        | StmtKind::Import(_) => {}
+    }
+}
+
+fn expr_names(expr: &Expr, names: &mut HashSet<String>) {
+    match &expr.kind {
+        ExprKind::Variable(name, _) => {
+            names.insert(name.clone());
+        }
+        ExprKind::Binary { left, right, .. }
+        | ExprKind::Pipeline { left, right }
+        | ExprKind::LogicalAnd { left, right }
+        | ExprKind::LogicalOr { left, right }
+        | ExprKind::NullishCoalescing { left, right }
+        | ExprKind::Index {
+            object: left,
+            index: right,
+        }
+        | ExprKind::Assign {
+            target: left,
+            value: right,
+        }
+        | ExprKind::CompoundAssign {
+            target: left,
+            value: right,
+            ..
+        }
+        | ExprKind::Rescue {
+            expr: left,
+            fallback: right,
+        } => {
+            expr_names(left, names);
+            expr_names(right, names);
+        }
+        ExprKind::Unary { operand: e, .. }
+        | ExprKind::Grouping(e)
+        | ExprKind::Spread(e)
+        | ExprKind::Throw(e)
+        | ExprKind::Await(e)
+        | ExprKind::PostfixIncrement(e)
+        | ExprKind::PostfixDecrement(e)
+        | ExprKind::Member { object: e, .. }
+        | ExprKind::SafeMember { object: e, .. }
+        | ExprKind::QualifiedName { qualifier: e, .. } => expr_names(e, names),
+        ExprKind::Call { callee, arguments }
+        | ExprKind::New {
+            class_expr: callee,
+            arguments,
+        } => {
+            expr_names(callee, names);
+            for arg in arguments {
+                match arg {
+                    Argument::Positional(e) | Argument::Block(e) | Argument::Spread(e) => {
+                        expr_names(e, names)
+                    }
+                    Argument::Named(named) => expr_names(&named.value, names),
+                }
+            }
+        }
+        ExprKind::Array(items) | ExprKind::Tuple(items) => {
+            items.iter().for_each(|e| expr_names(e, names))
+        }
+        ExprKind::Hash(pairs) => {
+            for (k, v) in pairs {
+                expr_names(k, names);
+                expr_names(v, names);
+            }
+        }
+        ExprKind::Block(stmts) => stmts.iter().for_each(|s| stmt_names(s, names)),
+        ExprKind::Lambda { params, body, .. } => {
+            for param in params {
+                if let Some(default) = &param.default_value {
+                    expr_names(default, names);
+                }
+            }
+            body.iter().for_each(|s| stmt_names(s, names));
+        }
+        ExprKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            expr_names(condition, names);
+            expr_names(then_branch, names);
+            if let Some(eb) = else_branch {
+                expr_names(eb, names);
+            }
+        }
+        ExprKind::Match { expression, arms } => {
+            expr_names(expression, names);
+            for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    expr_names(guard, names);
+                }
+                expr_names(&arm.body, names);
+            }
+        }
+        ExprKind::ListComprehension {
+            element,
+            iterable,
+            condition,
+            ..
+        } => {
+            expr_names(element, names);
+            expr_names(iterable, names);
+            if let Some(c) = condition {
+                expr_names(c, names);
+            }
+        }
+        ExprKind::HashComprehension(comp) => {
+            let HashComprehension {
+                key,
+                value,
+                iterable,
+                condition,
+                ..
+            } = &**comp;
+            expr_names(key, names);
+            expr_names(value, names);
+            expr_names(iterable, names);
+            if let Some(c) = condition {
+                expr_names(c, names);
+            }
+        }
+        ExprKind::InterpolatedString(parts) => {
+            for part in parts {
+                if let InterpolatedPart::Expression(e) = part {
+                    expr_names(e, names);
+                }
+            }
+        }
+        ExprKind::IntLiteral(_)
+        | ExprKind::FloatLiteral(_)
+        | ExprKind::DecimalLiteral(_)
+        | ExprKind::StringLiteral(_)
+        | ExprKind::CommandSubstitution(_)
+        | ExprKind::SdqlBlock { .. }
+        | ExprKind::BoolLiteral(_)
+        | ExprKind::Symbol(_)
+        | ExprKind::Null
+        | ExprKind::This
+        | ExprKind::Super => {}
+    }
 }
@@ -7,6 +7,7 @@ use crate::error::RuntimeError;
 use crate::interpreter::builtins::model::is_translated_field;
 use crate::interpreter::environment::Environment;
 use crate::interpreter::value::{HashKey, Value};
+use crate::interpreter::value_struct::StructValue;
 use crate::span::Span;
 
 use std::cell::RefCell;
@@ -51,8 +52,8 @@ impl Interpreter {
             ExprKind::Null => Ok(Value::Null),
 
             // Variables
-            ExprKind::Variable(name) => {
-                let val = self.evaluate_variable(name, expr)?;
+            ExprKind::Variable(name, slot) => {
+                let val = self.evaluate_variable(name, slot, expr)?;
                 self.try_auto_invoke(val, expr.span, AutoInvokeContext::Variable)
             }
 
@@ -115,6 +116,14 @@ impl Interpreter {
 
             ExprKind::Array(elements) => self.evaluate_array(elements),
 
+            ExprKind::Tuple(elements) => {
+                let mut values = Vec::with_capacity(elements.len());
+                for elem in elements {
+                    values.push(self.evaluate(elem)?);
+                }
+                Ok(Value::Tuple(Rc::new(values)))
+            }
+
             ExprKind::Hash(pairs) => self.evaluate_hash(pairs),
 
             // Block
@@ -123,10 +132,7 @@ impl Interpreter {
                 match self.execute_block(statements, env)? {
                     ControlFlow::Normal(v) => Ok(v),
                     ControlFlow::Return(v) => Ok(v),
-                    ControlFlow::Throw(e) => Err(RuntimeError::General {
-                        message: format!("Unhandled exception: {}", e),
-                        span: expr.span,
-                    }),
+                    ControlFlow::Throw(e) => Err(self.escape_throw(e, expr.span)),
                     ControlFlow::Continue | ControlFlow::Break => Ok(Value::Null),
                 }
             }
@@ -156,7 +162,8 @@ impl Interpreter {
                 params,
                 body,
                 return_type,
-            } => self.evaluate_lambda(params, body, return_type, expr.span),
+                is_move,
+            } => self.evaluate_lambda(params, body, return_type, *is_move, expr.span),
 
             // Control flow expressions
             ExprKind::If {
@@ -201,18 +208,12 @@ impl Interpreter {
                 self.evaluate_list_comprehension(element, variable, iterable, condition.as_deref())
             }
 
-            ExprKind::HashComprehension {
-                key,
-                value,
-                variable,
-                iterable,
-                condition,
-            } => self.evaluate_hash_comprehension(
-                key,
-                value,
-                variable,
-                iterable,
-                condition.as_deref(),
+            ExprKind::HashComprehension(comp) => self.evaluate_hash_comprehension(
+                &comp.key,
+                &comp.value,
+                &comp.variable,
+                &comp.iterable,
+                comp.condition.as_ref(),
             ),
 
             // Spread — only valid inside an array/hash literal, where it is
@@ -225,17 +226,28 @@ impl Interpreter {
             }),
 
             // Throw expression
+            ExprKind::Await(inner) => {
+                let value = self.evaluate(inner)?;
+                self.await_value(value, expr.span)
+            }
+
             ExprKind::Throw(value) => {
                 let error_value = self.evaluate(value)?;
-                Err(RuntimeError::General {
+                let error = RuntimeError::General {
                     message: format!("{}", error_value),
                     span: expr.span,
-                })
+                };
+                self.escaped_throw = Some((error.to_string(), error_value));
+                Err(error)
             }
 
             // Postfix rescue
             ExprKind::Rescue { expr, fallback } => {
-                let expr_result = self.evaluate(expr);
+                // A pending request fails when it's resolved, so resolve it
+                // here or the failure escapes the rescue.
+                let expr_result = self
+                    .evaluate(expr)
+                    .and_then(|v| v.resolve().map_err(|e| RuntimeError::new(e, expr.span)));
                 match expr_result {
                     Ok(value) => Ok(value),
                     Err(_) => self.evaluate(fallback),
@@ -249,15 +261,15 @@ impl Interpreter {
         let new_value = self.evaluate(value)?;
 
         match &target.kind {
-            ExprKind::Variable(name) => {
+            ExprKind::Variable(name, slot) => {
                 use crate::interpreter::environment::AssignResult;
                 // Single chain walk: distinguish reassignment-of-const (error)
                 // from not-yet-defined (fall through to define). Avoids a
                 // separate is_const pre-check that walked the chain twice.
-                let result = self
-                    .environment
-                    .borrow_mut()
-                    .assign(name, new_value.clone());
+                let result =
+                    self.environment
+                        .borrow_mut()
+                        .assign_resolved(name, slot, new_value.clone());
                 match result {
                     AssignResult::Assigned => {}
                     AssignResult::IsConst => {
@@ -293,6 +305,18 @@ impl Interpreter {
                                 target.span,
                             ));
                         }
+                        if inst.borrow().class.is_model_subclass() {
+                            let class_name = inst.borrow().class.name.clone();
+                            if let Some(msg) =
+                                crate::interpreter::builtins::model::attributes::check_assignment(
+                                    &class_name,
+                                    name,
+                                    &new_value,
+                                )
+                            {
+                                return Err(RuntimeError::type_error(msg, target.span));
+                            }
+                        }
 
                         // Handle translated fields: store in _pending_translations instead of raw field
                         let class_name = inst.borrow().class.name.clone();
@@ -358,6 +382,7 @@ impl Interpreter {
                             .insert(name.clone(), new_value.clone());
                         Ok(new_value)
                     }
+                    Value::Struct(value) => Err(struct_assign_error(&value, name, target.span)),
                     _ => Err(RuntimeError::type_error(
                         format!("cannot set property on {}", obj_val.type_name()),
                         target.span,
@@ -418,19 +443,19 @@ impl Interpreter {
         let rhs = self.evaluate(value)?;
         let result = match op {
             CompoundOp::Add => {
-                self.evaluate_binary_values(&current, crate::ast::BinaryOp::Add, &rhs, span)?
+                self.evaluate_binary_operator(&current, crate::ast::BinaryOp::Add, &rhs, span)?
             }
             CompoundOp::Subtract => {
-                self.evaluate_binary_values(&current, crate::ast::BinaryOp::Subtract, &rhs, span)?
+                self.evaluate_binary_operator(&current, crate::ast::BinaryOp::Subtract, &rhs, span)?
             }
             CompoundOp::Multiply => {
-                self.evaluate_binary_values(&current, crate::ast::BinaryOp::Multiply, &rhs, span)?
+                self.evaluate_binary_operator(&current, crate::ast::BinaryOp::Multiply, &rhs, span)?
             }
             CompoundOp::Divide => {
-                self.evaluate_binary_values(&current, crate::ast::BinaryOp::Divide, &rhs, span)?
+                self.evaluate_binary_operator(&current, crate::ast::BinaryOp::Divide, &rhs, span)?
             }
             CompoundOp::Modulo => {
-                self.evaluate_binary_values(&current, crate::ast::BinaryOp::Modulo, &rhs, span)?
+                self.evaluate_binary_operator(&current, crate::ast::BinaryOp::Modulo, &rhs, span)?
             }
             CompoundOp::Or | CompoundOp::And | CompoundOp::Coalesce => unreachable!(),
         };
@@ -444,7 +469,7 @@ impl Interpreter {
         let old_value = self.evaluate(target)?;
         let one = Value::Int(1);
         let new_value =
-            self.evaluate_binary_values(&old_value, crate::ast::BinaryOp::Add, &one, span)?;
+            self.evaluate_binary_operator(&old_value, crate::ast::BinaryOp::Add, &one, span)?;
         self.assign_to_target(target, new_value, span)?;
         Ok(old_value)
     }
@@ -454,7 +479,7 @@ impl Interpreter {
         let old_value = self.evaluate(target)?;
         let one = Value::Int(1);
         let new_value =
-            self.evaluate_binary_values(&old_value, crate::ast::BinaryOp::Subtract, &one, span)?;
+            self.evaluate_binary_operator(&old_value, crate::ast::BinaryOp::Subtract, &one, span)?;
         self.assign_to_target(target, new_value, span)?;
         Ok(old_value)
     }
@@ -462,10 +487,14 @@ impl Interpreter {
     /// Assign a value to a target expression (variable, member, or index).
     fn assign_to_target(&mut self, target: &Expr, value: Value, span: Span) -> RuntimeResult<()> {
         match &target.kind {
-            ExprKind::Variable(name) => {
+            ExprKind::Variable(name, slot) => {
                 use crate::interpreter::environment::AssignResult;
                 let value_for_define = value.clone();
-                match self.environment.borrow_mut().assign(name, value) {
+                let result = self
+                    .environment
+                    .borrow_mut()
+                    .assign_resolved(name, slot, value);
+                match result {
                     AssignResult::Assigned => Ok(()),
                     AssignResult::IsConst => Err(RuntimeError::type_error(
                         format!("cannot reassign constant '{}'", name),
@@ -498,6 +527,18 @@ impl Interpreter {
                                 target.span,
                             ));
                         }
+                        if inst.borrow().class.is_model_subclass() {
+                            let class_name = inst.borrow().class.name.clone();
+                            if let Some(msg) =
+                                crate::interpreter::builtins::model::attributes::check_assignment(
+                                    &class_name,
+                                    name,
+                                    &value,
+                                )
+                            {
+                                return Err(RuntimeError::type_error(msg, target.span));
+                            }
+                        }
                         inst.borrow_mut().set(name.clone(), value);
                         Ok(())
                     }
@@ -516,6 +557,7 @@ impl Interpreter {
                         class.static_fields.borrow_mut().insert(name.clone(), value);
                         Ok(())
                     }
+                    Value::Struct(value) => Err(struct_assign_error(&value, name, target.span)),
                     _ => Err(RuntimeError::type_error(
                         format!("cannot set property on {}", obj_val.type_name()),
                         target.span,
@@ -575,6 +617,8 @@ impl Interpreter {
         let iter_value = self.evaluate(iterable)?;
         let items: Vec<Value> = match iter_value {
             Value::Array(arr) => arr.borrow().iter().cloned().collect(),
+            Value::Range(range) => range.to_vec(),
+            Value::Iterator(iter) => self.iterator_collect(&iter, iterable.span)?,
             _ => {
                 return Err(RuntimeError::type_error("expected array", iterable.span));
             }
@@ -621,6 +665,8 @@ impl Interpreter {
         let iter_value = self.evaluate(iterable)?;
         let items: Vec<Value> = match iter_value {
             Value::Array(arr) => arr.borrow().iter().cloned().collect(),
+            Value::Range(range) => range.to_vec(),
+            Value::Iterator(iter) => self.iterator_collect(&iter, iterable.span)?,
             _ => {
                 return Err(RuntimeError::type_error("expected array", iterable.span));
             }
@@ -703,7 +749,7 @@ impl Interpreter {
     /// the raw function reference rather than the auto-invoked result.
     pub(crate) fn evaluate_callee(&mut self, expr: &Expr) -> RuntimeResult<Value> {
         match &expr.kind {
-            ExprKind::Variable(name) => self.evaluate_variable(name, expr),
+            ExprKind::Variable(name, slot) => self.evaluate_variable(name, slot, expr),
             ExprKind::Member { object, name } => self.evaluate_member(object, name, expr.span),
             ExprKind::SafeMember { object, name } => {
                 self.evaluate_safe_member(object, name, expr.span)
@@ -713,12 +759,19 @@ impl Interpreter {
     }
 
     #[allow(clippy::arc_with_non_send_sync)]
-    pub(crate) fn evaluate_system_run(&mut self, cmd: &str, _span: Span) -> RuntimeResult<Value> {
+    pub(crate) fn evaluate_system_run(&mut self, cmd: &str, span: Span) -> RuntimeResult<Value> {
         use crate::interpreter::value::HttpFutureKind;
         use std::sync::mpsc::{self, Receiver, Sender};
         use std::sync::{Arc, Mutex};
         use std::thread;
 
+        if self.sandboxed {
+            return Err(RuntimeError::General {
+                message: "command substitution is not available in the dev REPL".to_string(),
+                span,
+            });
+        }
+
         let (program, args_vec) = crate::interpreter::builtins::system::parse_backtick(cmd);
 
         let (tx, rx): (Sender<Result<String, String>>, Receiver<_>) = mpsc::channel();
@@ -740,6 +793,17 @@ impl Interpreter {
     }
 }
 
+/// Struct values are immutable; point the user at `with` instead.
+fn struct_assign_error(value: &StructValue, field: &str, span: Span) -> RuntimeError {
+    RuntimeError::type_error(
+        format!(
+            "cannot assign to '{}' of struct {} — use .with({}: ...) for a changed copy",
+            field, value.class.name, field
+        ),
+        span,
+    )
+}
+
 /// Check if a built-in method can be called with zero arguments.
 fn is_zero_arg_builtin_method(method_name: &str, receiver: &Value) -> bool {
     super::calls::method_registry::is_zero_arg_method(method_name, receiver)
@@ -0,0 +1,547 @@
+//! Generator functions and lazy iterators.
+//!
+//! Calling a function whose body contains `yield` doesn't run it: it returns
+//! an iterator holding a suspended generator. Each pull resumes the body
+//! until the next `yield` hands back a value, or the body finishes.
+//!
+//! Only the statements that lead to a `yield` (blocks, `if`s and loops whose
+//! bodies yield) are stepped through frame by frame, so they can be paused
+//! halfway; every other statement runs through `execute` as usual.
+//!
+//! Stages chained onto an iterator (`map`, `filter`, `take`, ...) are lazy
+//! too: they wrap the inner iterator and only pull from it when pulled from,
+//! which is what makes infinite generators usable.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::ast::{Body, Stmt, StmtKind};
+use crate::error::RuntimeError;
+use crate::interpreter::environment::Environment;
+use crate::interpreter::value::{Function, Value};
+use crate::span::Span;
+
+use super::statements::{LoopBinding, LoopCursor};
+use super::{ControlFlow, Interpreter, RuntimeResult};
+
+/// The iterator behind `Value::Iterator`. Once exhausted (or failed) it
+/// stays done.
+pub struct LazyIterator {
+    source: Source,
+    done: bool,
+}
+
+impl fmt::Debug for LazyIterator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<iterator>")
+    }
+}
+
+enum Source {
+    Generator(Box<Generator>),
+    Map {
+        inner: Rc<RefCell<LazyIterator>>,
+        func: Value,
+    },
+    /// `filter` / `select` keep items the function accepts, `reject` the rest.
+    Filter {
+        inner: Rc<RefCell<LazyIterator>>,
+        func: Value,
+        keep: bool,
+    },
+    Take {
+        inner: Rc<RefCell<LazyIterator>>,
+        remaining: usize,
+    },
+    Drop {
+        inner: Rc<RefCell<LazyIterator>>,
+        skip: usize,
+    },
+}
+
+/// A suspended generator call: the body plus the stack of frames it is
+/// paused in. No frames left means the body has finished.
+struct Generator {
+    name: String,
+    span: Span,
+    source_path: Option<String>,
+    body: Arc<Body>,
+    frames: Vec<Frame>,
+}
+
+/// One statement list (or loop) the generator is paused inside. `path`
+/// leads from the function body to the frame's statement list: each step is
+/// a statement index plus the branch of that statement to descend into.
+struct Frame {
+    path: Vec<(usize, Branch)>,
+    env: Rc<RefCell<Environment>>,
+    kind: FrameKind,
+}
+
+enum FrameKind {
+    /// Running the list's statements; `next` is the one to run next.
+    Block { next: usize },
+    /// Looping over the `while` statement at index `at` of the list.
+    While { at: usize },
+    /// Looping over the `for` statement at index `at` of the list.
+    For {
+        at: usize,
+        cursor: LoopCursor,
+        index: i64,
+    },
+}
+
+#[derive(Clone, Copy)]
+enum Branch {
+    /// The statement itself (a block).
+    Block,
+    Then,
+    Else,
+    /// A loop's body.
+    Body,
+}
+
+fn child(stmt: &Stmt, branch: Branch) -> &Stmt {
+    match (&stmt.kind, branch) {
+        (StmtKind::If { then_branch, .. }, Branch::Then) => then_branch,
+        (
+            StmtKind::If {
+                else_branch: Some(else_branch),
+                ..
+            },
+            Branch::Else,
+        ) => else_branch,
+        (StmtKind::While { body, .. } | StmtKind::For { body, .. }, Branch::Body) => body,
+        _ => stmt,
+    }
+}
+
+/// A block's statements; any other statement is a list of one.
+fn statements_of(stmt: &Stmt) -> &[Stmt] {
+    match &stmt.kind {
+        StmtKind::Block(stmts) => stmts,
+        _ => std::slice::from_ref(stmt),
+    }
+}
+
+fn block_at<'a>(body: &'a [Stmt], path: &[(usize, Branch)]) -> &'a [Stmt] {
+    path.iter().fold(body, |list, &(i, branch)| {
+        statements_of(child(&list[i], branch))
+    })
+}
+
+fn extend(path: &[(usize, Branch)], at: usize, branch: Branch) -> Vec<(usize, Branch)> {
+    let mut path = path.to_vec();
+    path.push((at, branch));
+    path
+}
+
+fn child_env(env: &Rc<RefCell<Environment>>) -> Rc<RefCell<Environment>> {
+    Rc::new(RefCell::new(Environment::with_enclosing(env.clone())))
+}
+
+/// What the top frame does next, worked out before anything runs so the
+/// frame stack isn't borrowed while the interpreter is.
+enum Step {
+    Pop,
+    Push(Frame),
+    Run(usize),
+}
+
+impl Interpreter {
+    /// Start a generator call: `env` is the call environment with the
+    /// parameters already bound. Nothing in the body runs until the first
+    /// pull.
+    pub(crate) fn start_generator(&self, func: &Function, env: Rc<RefCell<Environment>>) -> Value {
+        let generator = Generator {
+            name: func.name.clone(),
+            span: func.span.unwrap_or_default(),
+            source_path: func.source_path.clone(),
+            body: func.body.clone(),
+            frames: vec![Frame {
+                path: Vec::new(),
+                env,
+                kind: FrameKind::Block { next: 0 },
+            }],
+        };
+        Value::Iterator(Rc::new(RefCell::new(LazyIterator {
+            source: Source::Generator(Box::new(generator)),
+            done: false,
+        })))
+    }
+
+    /// Pull the next item, or `None` once the iterator is exhausted.
+    pub(crate) fn iterator_next(
+        &mut self,
+        iter: &Rc<RefCell<LazyIterator>>,
+        span: Span,
+    ) -> RuntimeResult<Option<Value>> {
+        let mut iter = iter.try_borrow_mut().map_err(|_| RuntimeError::General {
+            message: "iterator is already running".to_string(),
+            span,
+        })?;
+        if iter.done {
+            return Ok(None);
+        }
+        let result = self.pull(&mut iter.source, span);
+        if !matches!(result, Ok(Some(_))) {
+            iter.done = true;
+        }
+        result
+    }
+
+    /// Drain the iterator into an array's items.
+    pub(crate) fn iterator_collect(
+        &mut self,
+        iter: &Rc<RefCell<LazyIterator>>,
+        span: Span,
+    ) -> RuntimeResult<Vec<Value>> {
+        let mut items = Vec::new();
+        while let Some(item) = self.iterator_next(iter, span)? {
+            items.push(item);
+        }
+        Ok(items)
+    }
+
+    fn pull(&mut self, source: &mut Source, span: Span) -> RuntimeResult<Option<Value>> {
+        match source {
+            Source::Generator(generator) => self.resume_generator(generator),
+            Source::Map { inner, func } => match self.iterator_next(inner, span)? {
+                Some(item) => Ok(Some(self.call_value(func.clone(), vec![item], span)?)),
+                None => Ok(None),
+            },
+            Source::Filter { inner, func, keep } => {
+                while let Some(item) = self.iterator_next(inner, span)? {
+                    let accepted = self
+                        .call_value(func.clone(), vec![item.clone()], span)?
+                        .is_truthy();
+                    if accepted == *keep {
+                        return Ok(Some(item));
+                    }
+                }
+                Ok(None)
+            }
+            // Stop at the limit without pulling again, so `take` ends an
+            // infinite generator.
+            Source::Take { inner, remaining } => {
+                if *remaining == 0 {
+                    return Ok(None);
+                }
+                *remaining -= 1;
+                self.iterator_next(inner, span)
+            }
+            Source::Drop { inner, skip } => {
+                while *skip > 0 {
+                    *skip -= 1;
+                    if self.iterator_next(inner, span)?.is_none() {
+                        return Ok(None);
+                    }
+                }
+                self.iterator_next(inner, span)
+            }
+        }
+    }
+
+    /// Wrap `iter` in a lazy stage. `take` and `drop` take a count, the
+    /// rest a function.
+    pub(crate) fn iterator_stage(
+        &self,
+        iter: Rc<RefCell<LazyIterator>>,
+        name: &str,
+        arguments: Vec<Value>,
+        span: Span,
+    ) -> RuntimeResult<Value> {
+        if arguments.len() != 1 {
+            return Err(RuntimeError::wrong_arity(1, arguments.len(), span));
+        }
+        let arg = arguments.into_iter().next().unwrap_or(Value::Null);
+        let count = |arg: &Value| match arg {
+            Value::Int(n) if *n >= 0 => Ok(*n as usize),
+            other => Err(RuntimeError::type_error(
+                format!(
+                    "{}() expects a non-negative int, got {}",
+                    name,
+                    other.type_name()
+                ),
+                span,
+            )),
+        };
+        let source = match name {
+            "map" => Source::Map {
+                inner: iter,
+                func: arg,
+            },
+            "filter" | "select" | "reject" => Source::Filter {
+                inner: iter,
+                func: arg,
+                keep: name != "reject",
+            },
+            "take" => Source::Take {
+                inner: iter,
+                remaining: count(&arg)?,
+            },
+            "drop" => Source::Drop {
+                inner: iter,
+                skip: count(&arg)?,
+            },
+            _ => {
+                return Err(RuntimeError::NoSuchProperty {
+                    value_type: "iterator".to_string(),
+                    property: name.to_string(),
+                    span,
+                })
+            }
+        };
+        Ok(Value::Iterator(Rc::new(RefCell::new(LazyIterator {
+            source,
+            done: false,
+        }))))
+    }
+
+    /// Methods on an iterator. The stages stay lazy; the rest pull items,
+    /// and methods only arrays have collect the iterator first.
+    pub(crate) fn call_iterator_method(
+        &mut self,
+        iter: &Rc<RefCell<LazyIterator>>,
+        method_name: &str,
+        arguments: Vec<Value>,
+        span: Span,
+    ) -> RuntimeResult<Value> {
+        match (method_name, arguments.len()) {
+            ("map" | "filter" | "select" | "reject" | "take" | "drop", _) => {
+                self.iterator_stage(iter.clone(), method_name, arguments, span)
+            }
+            ("class", 0) => Ok(Value::String("iterator".into())),
+            ("nil?", 0) => Ok(Value::Bool(false)),
+            ("inspect" | "to_s", 0) => Ok(Value::String("<iterator>".into())),
+            ("next" | "first", 0) => Ok(self.iterator_next(iter, span)?.unwrap_or(Value::Null)),
+            ("first", 1) => {
+                let taken = self.iterator_stage(iter.clone(), "take", arguments, span)?;
+                let Value::Iterator(taken) = taken else {
+                    unreachable!("take returns an iterator")
+                };
+                let items = self.iterator_collect(&taken, span)?;
+                Ok(Value::Array(Rc::new(RefCell::new(items))))
+            }
+            ("to_a", 0) => {
+                let items = self.iterator_collect(iter, span)?;
+                Ok(Value::Array(Rc::new(RefCell::new(items))))
+            }
+            ("each", 1) => {
+                let func = arguments.into_iter().next().unwrap_or(Value::Null);
+                while let Some(item) = self.iterator_next(iter, span)? {
+                    self.call_value(func.clone(), vec![item], span)?;
+                }
+                Ok(Value::Null)
+            }
+            // Stops pulling at the first match.
+            ("find", 1) => {
+                let func = arguments.into_iter().next().unwrap_or(Value::Null);
+                while let Some(item) = self.iterator_next(iter, span)? {
+                    if self
+                        .call_value(func.clone(), vec![item.clone()], span)?
+                        .is_truthy()
+                    {
+                        return Ok(item);
+                    }
+                }
+                Ok(Value::Null)
+            }
+            _ => {
+                let items = self.iterator_collect(iter, span)?;
+                self.call_array_method(&items, method_name, arguments, span)
+            }
+        }
+    }
+
+    /// Run the generator until its next `yield`.
+    fn resume_generator(&mut self, generator: &mut Generator) -> RuntimeResult<Option<Value>> {
+        self.push_frame(
+            &generator.name,
+            generator.span,
+            generator.source_path.clone(),
+        );
+        let previous = self.environment.clone();
+        let result = self.run_generator(generator);
+        self.environment = previous;
+        self.pop_frame();
+        if result.is_err() {
+            generator.frames.clear();
+        }
+        result
+    }
+
+    fn run_generator(&mut self, generator: &mut Generator) -> RuntimeResult<Option<Value>> {
+        let body = generator.body.clone();
+        while let Some(frame) = generator.frames.last_mut() {
+            self.environment = frame.env.clone();
+            let list = block_at(&body, &frame.path);
+            let step = match &mut frame.kind {
+                FrameKind::Block { next } if *next >= list.len() => Step::Pop,
+                FrameKind::Block { next } => {
+                    *next += 1;
+                    Step::Run(*next - 1)
+                }
+                FrameKind::While { at } => {
+                    let at = *at;
+                    let StmtKind::While { condition, .. } = &list[at].kind else {
+                        unreachable!("while frame points at a while loop")
+                    };
+                    if self.evaluate(condition)?.is_truthy() {
+                        Step::Push(Frame {
+                            path: extend(&frame.path, at, Branch::Body),
+                            env: child_env(&frame.env),
+                            kind: FrameKind::Block { next: 0 },
+                        })
+                    } else {
+                        Step::Pop
+                    }
+                }
+                FrameKind::For { at, cursor, index } => {
+                    let StmtKind::For {
+                        variable,
+                        index_variable,
+                        destructure,
+                        iterable,
+                        ..
+                    } = &list[*at].kind
+                    else {
+                        unreachable!("for frame points at a for loop")
+                    };
+                    let entry = cursor.yields_entries();
+                    match self.loop_next(cursor, iterable.span)? {
+                        Some(item) => {
+                            let binding = LoopBinding {
+                                variable,
+                                index_variable: index_variable.as_deref(),
+                                destructure: destructure.as_deref(),
+                                span: iterable.span,
+                            };
+                            let env = child_env(&frame.env);
+                            binding.bind(&mut env.borrow_mut(), item, *index, entry)?;
+                            *index += 1;
+                            Step::Push(Frame {
+                                path: extend(&frame.path, *at, Branch::Body),
+                                env,
+                                kind: FrameKind::Block { next: 0 },
+                            })
+                        }
+                        None => Step::Pop,
+                    }
+                }
+            };
+            let at = match step {
+                Step::Pop => {
+                    generator.frames.pop();
+                    continue;
+                }
+                Step::Push(frame) => {
+                    generator.frames.push(frame);
+                    continue;
+                }
+                Step::Run(at) => at,
+            };
+
+            let stmt = &list[at];
+            if !stmt.yields() {
+                let flow = self.execute(stmt)?;
+                self.unwind_generator(generator, flow)?;
+                continue;
+            }
+            self.record_line_hit(stmt);
+            self.check_budget(stmt.span)?;
+            let frame = &generator.frames[generator.frames.len() - 1];
+            let pushed = match &stmt.kind {
+                StmtKind::Yield(expr) => return self.evaluate(expr).map(Some),
+                StmtKind::Block(_) => Frame {
+                    path: extend(&frame.path, at, Branch::Block),
+                    env: child_env(&frame.env),
+                    kind: FrameKind::Block { next: 0 },
+                },
+                StmtKind::If {
+                    condition,
+                    else_branch,
+                    ..
+                } => {
+                    let branch = if self.evaluate(condition)?.is_truthy() {
+                        Branch::Then
+                    } else if else_branch.is_some() {
+                        Branch::Else
+                    } else {
+                        continue;
+                    };
+                    Frame {
+                        path: extend(&frame.path, at, branch),
+                        env: child_env(&frame.env),
+                        kind: FrameKind::Block { next: 0 },
+                    }
+                }
+                StmtKind::While { .. } => Frame {
+                    path: frame.path.clone(),
+                    env: frame.env.clone(),
+                    kind: FrameKind::While { at },
+                },
+                StmtKind::For {
+                    destructure,
+                    iterable,
+                    ..
+                } => {
+                    let value = self.evaluate(iterable)?;
+                    let cursor = self.loop_cursor(value, destructure.is_some(), iterable.span)?;
+                    Frame {
+                        path: frame.path.clone(),
+                        env: frame.env.clone(),
+                        kind: FrameKind::For {
+                            at,
+                            cursor,
+                            index: 0,
+                        },
+                    }
+                }
+                // `yield` inside a `try`: `execute` reports it.
+                _ => {
+                    let flow = self.execute(stmt)?;
+                    self.unwind_generator(generator, flow)?;
+                    continue;
+                }
+            };
+            generator.frames.push(pushed);
+        }
+        Ok(None)
+    }
+
+    /// Apply a statement's control flow to the generator's frames: `return`
+    /// finishes the body, `break` / `continue` unwind to the innermost loop
+    /// frame, and a thrown value escapes the generator like it escapes a
+    /// function call.
+    fn unwind_generator(
+        &mut self,
+        generator: &mut Generator,
+        flow: ControlFlow,
+    ) -> RuntimeResult<()> {
+        let is_loop = |frame: &Frame| !matches!(frame.kind, FrameKind::Block { .. });
+        match flow {
+            ControlFlow::Normal(_) => {}
+            ControlFlow::Return(_) => generator.frames.clear(),
+            ControlFlow::Throw(e) => {
+                generator.frames.clear();
+                return Err(self.escape_throw(e, Span::default()));
+            }
+            ControlFlow::Break => {
+                while let Some(frame) = generator.frames.pop() {
+                    if is_loop(&frame) {
+                        break;
+                    }
+                }
+            }
+            ControlFlow::Continue => {
+                while generator.frames.last().is_some_and(|f| !is_loop(f)) {
+                    generator.frames.pop();
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -113,7 +113,7 @@ impl Interpreter {
 /// the result is a `"Error: ..."` string (callers guard with `type(rows)`).
 pub(crate) fn run_sdql_block(query: &str, binds: &[(String, Value)]) -> Value {
     use crate::interpreter::builtins::model::crud::{
-        exec_async_query, exec_async_query_with_binds, json_to_value,
+        exec_async_query, exec_async_query_with_binds, json_to_value, spawn_query_with_binds,
     };
     use std::cell::RefCell;
     use std::rc::Rc;
@@ -127,6 +127,13 @@ pub(crate) fn run_sdql_block(query: &str, binds: &[(String, Value)]) -> Value {
         processed_query = processed_query.replace(&placeholder, &sdbql_var);
     }
 
+    // Inside an async task the query runs in the background and the block
+    // evaluates to its future (see `scheduler`).
+    if super::scheduler::in_task() {
+        let binds = (!bind_vars.is_empty()).then_some(bind_vars);
+        return spawn_query_with_binds(processed_query, binds);
+    }
+
     if bind_vars.is_empty() {
         exec_async_query(processed_query)
     } else {
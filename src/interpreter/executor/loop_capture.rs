@@ -13,7 +13,7 @@
 //! cost an allocation (and such bodies already allocate a closure), never
 //! correctness.
 
-use crate::ast::expr::{Argument, Expr, ExprKind, InterpolatedPart};
+use crate::ast::expr::{Argument, Expr, ExprKind, HashComprehension, InterpolatedPart};
 use crate::ast::stmt::{Stmt, StmtKind};
 
 /// Whether any statement in `body` creates a closure (lambda or nested `fn`).
@@ -25,11 +25,15 @@ pub(crate) fn stmt_creates_closures(stmt: &Stmt) -> bool {
     match &stmt.kind {
         // A nested function declaration is itself a closure.
         StmtKind::Function(_) => true,
-        StmtKind::Expression(e) | StmtKind::Throw(e) => expr_creates_closures(e),
+        StmtKind::Expression(e) | StmtKind::Throw(e) | StmtKind::Yield(e) => {
+            expr_creates_closures(e)
+        }
         StmtKind::Let { initializer, .. } => {
             initializer.as_ref().is_some_and(expr_creates_closures)
         }
-        StmtKind::Const { initializer, .. } => expr_creates_closures(initializer),
+        StmtKind::Const { initializer, .. } | StmtKind::LetTuple { initializer, .. } => {
+            expr_creates_closures(initializer)
+        }
         StmtKind::Break => false,
         StmtKind::Block(stmts) => body_creates_closures(stmts),
         StmtKind::If {
@@ -64,9 +68,12 @@ pub(crate) fn stmt_creates_closures(stmt: &Stmt) -> bool {
         StmtKind::Export(inner) => stmt_creates_closures(inner),
         // Class declarations carry their own method scopes; interfaces/imports
         // never create capturing closures in the loop's environment.
-        StmtKind::Class(_) | StmtKind::Enum(_) | StmtKind::Interface(_) | StmtKind::Import(_) => {
-            false
-        }
+        StmtKind::Class(_)
+        | StmtKind::Enum(_)
+        | StmtKind::Struct(_)
+        | StmtKind::Interface(_)
+        | StmtKind::TypeAlias(_)
+        | StmtKind::Import(_) => false,
     }
 }
 
@@ -90,6 +97,7 @@ fn expr_creates_closures(expr: &Expr) -> bool {
         ExprKind::Grouping(e)
         | ExprKind::Spread(e)
         | ExprKind::Throw(e)
+        | ExprKind::Await(e)
         | ExprKind::PostfixIncrement(e)
         | ExprKind::PostfixDecrement(e) => expr_creates_closures(e),
         ExprKind::Call { callee, arguments } => {
@@ -106,7 +114,7 @@ fn expr_creates_closures(expr: &Expr) -> bool {
         ExprKind::Index { object, index } => {
             expr_creates_closures(object) || expr_creates_closures(index)
         }
-        ExprKind::Array(elems) => elems.iter().any(expr_creates_closures),
+        ExprKind::Array(elems) | ExprKind::Tuple(elems) => elems.iter().any(expr_creates_closures),
         ExprKind::Hash(pairs) => pairs
             .iter()
             .any(|(k, v)| expr_creates_closures(k) || expr_creates_closures(v)),
@@ -139,17 +147,18 @@ fn expr_creates_closures(expr: &Expr) -> bool {
                 || expr_creates_closures(iterable)
                 || condition.as_ref().is_some_and(|c| expr_creates_closures(c))
         }
-        ExprKind::HashComprehension {
-            key,
-            value,
-            iterable,
-            condition,
-            ..
-        } => {
+        ExprKind::HashComprehension(comp) => {
+            let HashComprehension {
+                key,
+                value,
+                iterable,
+                condition,
+                ..
+            } = &**comp;
             expr_creates_closures(key)
                 || expr_creates_closures(value)
                 || expr_creates_closures(iterable)
-                || condition.as_ref().is_some_and(|c| expr_creates_closures(c))
+                || condition.as_ref().is_some_and(expr_creates_closures)
         }
         ExprKind::Rescue { expr, fallback } => {
             expr_creates_closures(expr) || expr_creates_closures(fallback)
@@ -168,7 +177,7 @@ fn expr_creates_closures(expr: &Expr) -> bool {
         | ExprKind::BoolLiteral(_)
         | ExprKind::Symbol(_)
         | ExprKind::Null
-        | ExprKind::Variable(_)
+        | ExprKind::Variable(_, _)
         | ExprKind::This
         | ExprKind::Super => false,
     }
@@ -176,7 +185,9 @@ fn expr_creates_closures(expr: &Expr) -> bool {
 
 fn arguments_create_closures(arguments: &[Argument]) -> bool {
     arguments.iter().any(|arg| match arg {
-        Argument::Positional(e) | Argument::Block(e) => expr_creates_closures(e),
+        Argument::Positional(e) | Argument::Block(e) | Argument::Spread(e) => {
+            expr_creates_closures(e)
+        }
         Argument::Named(named) => expr_creates_closures(&named.value),
     })
 }
@@ -1,13 +1,22 @@
 //! Tree-walking interpreter for Solilang.
 
+mod budget;
 mod expressions;
+pub(crate) mod generators;
 pub(crate) mod literals;
 mod loop_capture;
 mod operators;
 mod pattern_matching;
+mod request_state;
+pub(crate) mod scheduler;
+pub(crate) mod stack_guard;
 mod statements;
+mod tail_calls;
 mod variables;
 
+pub(crate) use budget::resident_bytes;
+pub use budget::{heap_counters, CountingAlloc, EvalBudget, HeapCounters};
+pub(crate) use pattern_matching::enum_variant_field_names;
 pub use variables::{
     clear_current_env, current_env_lookup, enter_template_lenient_vars, is_defined,
     set_current_env, template_lenient_vars_enabled, TemplateLenientVarsGuard,
@@ -21,6 +30,7 @@ pub mod objects;
 use std::cell::RefCell;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
 use crate::ast::*;
@@ -28,7 +38,8 @@ use crate::coverage::CoverageTracker;
 use crate::error::RuntimeError;
 use crate::interpreter::builtins::register_builtins;
 use crate::interpreter::environment::Environment;
-use crate::interpreter::value::{value_matches_type, Function, HashKey, Value};
+use crate::interpreter::observer::InterpreterObserver;
+use crate::interpreter::value::{value_matches_type, Function, HashKey, Instance, Value};
 use crate::span::Span;
 
 pub(crate) type RuntimeResult<T> = Result<T, RuntimeError>;
@@ -42,6 +53,88 @@ pub struct StackFrame {
     pub column: usize,
 }
 
+/// Default limit on nested function calls; see [`Interpreter::set_max_depth`].
+/// High enough for ordinary recursion, which ran unbounded before the limit
+/// existed. Unoptimized builds spend tens of KiB of Rust stack per tree-walked
+/// call, far more than an 8 MiB main thread holds at this depth, so code that
+/// runs an interpreter does it on a thread sized with [`stack_size_for_depth`].
+/// On any other thread a call fails with the same error once the stack is
+/// nearly full. Tail calls don't nest.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 10_000;
+
+/// Default limit for the threads of `soli serve`: request, internal, job and
+/// dev REPL workers. A server runs one per worker and each reserves stack for
+/// its limit, so they get a lower one than scripts: about 31 MiB of address
+/// space per thread in a release build. `SOLI_MAX_CALL_DEPTH` or
+/// `soli serve --max-call-depth N` opt into deeper recursion.
+pub const DEFAULT_WORKER_CALL_DEPTH: usize = 2_000;
+
+/// Highest limit `SOLI_MAX_CALL_DEPTH` can set. Each allowed call reserves
+/// [`STACK_BYTES_PER_CALL`] of address space, so this caps a thread's stack
+/// at a few GiB rather than asking the OS for more than it can map.
+pub const MAX_CALL_DEPTH_LIMIT: usize = 50_000;
+
+/// Rust stack to reserve per allowed call when spawning a thread that runs
+/// an interpreter. Unoptimized builds need several times what release builds
+/// do. At the default depth a script thread reserves 160 MiB of address
+/// space in a release build (640 MiB unoptimized), which the OS only backs
+/// with memory as recursion touches it. Under `ulimit -v` or strict
+/// overcommit, lower `SOLI_MAX_CALL_DEPTH` so threads can still spawn.
+const STACK_BYTES_PER_CALL: usize = if cfg!(debug_assertions) {
+    64 * 1024
+} else {
+    16 * 1024
+};
+
+/// Environment variable that overrides the call-depth limit of every
+/// interpreter, in scripts, `soli test` and `soli serve` alike.
+pub const MAX_CALL_DEPTH_ENV: &str = "SOLI_MAX_CALL_DEPTH";
+
+/// The limit `soli serve --max-call-depth` set for worker threads; 0 if none.
+static WORKER_CALL_DEPTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Call-depth limit from `SOLI_MAX_CALL_DEPTH`, or the default when it is
+/// unset or not a positive integer. Clamped to [`MAX_CALL_DEPTH_LIMIT`].
+pub fn max_call_depth_from_env() -> usize {
+    call_depth_from_env(DEFAULT_MAX_CALL_DEPTH)
+}
+
+/// Call-depth limit of the threads of `soli serve`: the one
+/// [`set_worker_call_depth`] set, else `SOLI_MAX_CALL_DEPTH`'s, else
+/// [`DEFAULT_WORKER_CALL_DEPTH`].
+pub fn worker_call_depth() -> usize {
+    match WORKER_CALL_DEPTH.load(Ordering::Relaxed) {
+        0 => call_depth_from_env(DEFAULT_WORKER_CALL_DEPTH),
+        depth => depth,
+    }
+}
+
+/// Limit the threads of `soli serve` to `depth` nested calls, whatever
+/// `SOLI_MAX_CALL_DEPTH` says. Takes effect for threads started afterwards.
+/// Clamped to [`MAX_CALL_DEPTH_LIMIT`].
+pub fn set_worker_call_depth(depth: usize) {
+    WORKER_CALL_DEPTH.store(depth.clamp(1, MAX_CALL_DEPTH_LIMIT), Ordering::Relaxed);
+}
+
+fn call_depth_from_env(default: usize) -> usize {
+    std::env::var(MAX_CALL_DEPTH_ENV)
+        .ok()
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .filter(|&depth| depth > 0)
+        .unwrap_or(default)
+        .min(MAX_CALL_DEPTH_LIMIT)
+}
+
+/// Stack size for a thread whose interpreter allows `depth` nested calls, so
+/// the depth limit trips before the stack runs out. `depth` is clamped to
+/// [`MAX_CALL_DEPTH_LIMIT`].
+pub fn stack_size_for_depth(depth: usize) -> usize {
+    depth
+        .min(MAX_CALL_DEPTH_LIMIT)
+        .saturating_mul(STACK_BYTES_PER_CALL)
+        .max(8 * 1024 * 1024)
+}
+
 /// Internal result type that can carry return values and exceptions.
 pub(crate) enum ControlFlow {
     Normal(Value),
@@ -58,6 +151,28 @@ pub struct Interpreter {
     pub(crate) current_source_path: Option<PathBuf>,
     pub(crate) call_stack: Vec<StackFrame>,
     pub assertion_count: i64,
+    /// The function call a `return f(...)` may replace, and the arguments
+    /// of a tail call waiting to rerun it (see `tail_calls`).
+    pub(crate) tail_target: Option<tail_calls::TailTarget>,
+    pub(crate) tail_call: Option<Vec<Value>>,
+    /// Calls allowed on `call_stack` before the next one fails with
+    /// "maximum call depth exceeded" instead of overflowing the Rust stack.
+    pub(crate) max_depth: usize,
+    /// Time and memory the current evaluation may still use, if limited.
+    pub(crate) budget: Option<EvalBudget>,
+    /// Tracing hooks, if a tool set them (see [`Interpreter::set_observer`]).
+    pub(crate) observer: Option<Box<dyn InterpreterObserver>>,
+    /// Whether the observer has been told about the error now unwinding, so
+    /// the statements it passes through don't report it again.
+    pub(crate) error_observed: bool,
+    /// A value thrown out of a function body, with the text of the error
+    /// carrying it across the call (see [`Interpreter::escape_throw`]).
+    pub(crate) escaped_throw: Option<(String, Value)>,
+    /// Whether this interpreter runs untrusted code (the dev REPL). Denying
+    /// globals isn't enough there: backtick command substitution reaches the
+    /// host without going through one, so it is refused outright, and calls
+    /// the interpreter dispatches by name skip the builtins the REPL denies.
+    pub(crate) sandboxed: bool,
 }
 
 impl Interpreter {
@@ -71,10 +186,19 @@ impl Interpreter {
             current_source_path: None,
             call_stack: Vec::new(),
             assertion_count: 0,
+            tail_target: None,
+            tail_call: None,
+            max_depth: max_call_depth_from_env(),
+            budget: None,
+            observer: None,
+            error_observed: false,
+            escaped_throw: None,
+            sandboxed: false,
         }
     }
 
-    /// Create an interpreter for serve mode (skips test builtins to save memory).
+    /// Create an interpreter for serve mode (skips test builtins to save
+    /// memory), limited to [`worker_call_depth`] nested calls.
     pub fn new_for_serve() -> Self {
         let globals = Rc::new(RefCell::new(Environment::with_builtins_capacity()));
         register_builtins(&mut globals.borrow_mut(), false);
@@ -85,6 +209,14 @@ impl Interpreter {
             current_source_path: None,
             call_stack: Vec::new(),
             assertion_count: 0,
+            tail_target: None,
+            tail_call: None,
+            max_depth: worker_call_depth(),
+            budget: None,
+            observer: None,
+            error_observed: false,
+            escaped_throw: None,
+            sandboxed: false,
         }
     }
 
@@ -97,6 +229,14 @@ impl Interpreter {
             current_source_path: None,
             call_stack: Vec::new(),
             assertion_count: 0,
+            tail_target: None,
+            tail_call: None,
+            max_depth: max_call_depth_from_env(),
+            budget: None,
+            observer: None,
+            error_observed: false,
+            escaped_throw: None,
+            sandboxed: false,
         }
     }
 
@@ -110,9 +250,47 @@ impl Interpreter {
             current_source_path: None,
             call_stack: Vec::new(),
             assertion_count: 0,
+            tail_target: None,
+            tail_call: None,
+            max_depth: max_call_depth_from_env(),
+            budget: None,
+            observer: None,
+            error_observed: false,
+            escaped_throw: None,
+            sandboxed: false,
         }
     }
 
+    /// Limit nested Soli function calls to `depth`. A call past the limit
+    /// fails with a "maximum call depth exceeded" error carrying the stack
+    /// trace, instead of overflowing the Rust stack and aborting the process.
+    pub fn set_max_depth(&mut self, depth: usize) {
+        self.max_depth = depth.max(1);
+    }
+
+    /// Refuse constructs that run host processes without going through a
+    /// global, for interpreters that evaluate untrusted code.
+    pub(crate) fn set_sandboxed(&mut self) {
+        self.sandboxed = true;
+    }
+
+    /// The current call-depth limit.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Report calls, statements and errors to `observer` from now on,
+    /// replacing any observer set before.
+    pub fn set_observer(&mut self, observer: Box<dyn InterpreterObserver>) {
+        self.observer = Some(observer);
+    }
+
+    /// Stop reporting, handing back the observer so a tool can read what it
+    /// collected.
+    pub fn take_observer(&mut self) -> Option<Box<dyn InterpreterObserver>> {
+        self.observer.take()
+    }
+
     pub fn set_coverage_tracker(&mut self, tracker: Arc<Mutex<CoverageTracker>>) {
         self.coverage_tracker = Some(tracker);
     }
@@ -129,6 +307,9 @@ impl Interpreter {
     #[inline(always)]
     pub fn record_coverage(&self, line: usize) {
         if let Some(ref path) = self.current_file_path() {
+            if crate::source_map::is_synthetic(path) {
+                return;
+            }
             if let Some(ref tracker) = self.coverage_tracker {
                 if let Ok(guard) = tracker.lock() {
                     guard.record_line_hit(path, line);
@@ -309,6 +490,14 @@ impl Interpreter {
                     arr.borrow().iter().map(|v| self.value_to_json(v)).collect();
                 format!("[{}]", items.join(", "))
             }
+            Value::Tuple(items) => {
+                let items: Vec<String> = items.iter().map(|v| self.value_to_json(v)).collect();
+                format!("[{}]", items.join(", "))
+            }
+            Value::Range(range) => {
+                let items: Vec<String> = range.iter().map(|n| n.to_string()).collect();
+                format!("[{}]", items.join(", "))
+            }
             Value::Hash(hash) => {
                 let pairs: Vec<String> = hash
                     .borrow()
@@ -341,10 +530,18 @@ impl Interpreter {
                     )
                 }
             }
+            Value::Struct(s) => {
+                let fields: Vec<String> = s
+                    .entries()
+                    .map(|(k, v)| format!(r#""{}": {}"#, k, self.value_to_json(v)))
+                    .collect();
+                format!("{{{}}}", fields.join(", "))
+            }
             Value::Function(_) => "\"<function>\"".to_string(),
             Value::NativeFunction(_) => "\"<native function>\"".to_string(),
             Value::Class(c) => format!("\"<class {}>\"", c.name),
             Value::Future(_) => "\"<future>\"".to_string(),
+            Value::Iterator(_) => "\"<iterator>\"".to_string(),
             Value::Method(_) => "\"<method>\"".to_string(),
             Value::Breakpoint => "\"<breakpoint>\"".to_string(),
             Value::Continue => "\"<continue>\"".to_string(),
@@ -392,12 +589,83 @@ impl Interpreter {
             }
         });
         crate::serve::span_log::push_fn(function_name, meta);
+        crate::interpreter::profiler::enter(function_name);
+        if let (Some(observer), Some(frame)) = (self.observer.as_mut(), self.call_stack.last()) {
+            observer.on_function_enter(frame);
+        }
+    }
+
+    /// Fail a call that would nest deeper than `max_depth`, or that the
+    /// thread's stack has no room left for. The trace keeps
+    /// the outermost and innermost frames of what is usually a long run of
+    /// the same recursive call.
+    fn check_call_depth(&self, span: Span) -> RuntimeResult<()> {
+        let stack_full = stack_guard::stack_exhausted();
+        if self.call_stack.len() < self.max_depth && !stack_full {
+            return Ok(());
+        }
+        const KEEP_OUTER: usize = 5;
+        const KEEP_INNER: usize = 15;
+        let mut stack_trace = self.get_stack_trace();
+        if stack_trace.len() > KEEP_OUTER + KEEP_INNER {
+            let omitted = stack_trace.len() - KEEP_OUTER - KEEP_INNER;
+            stack_trace.splice(
+                KEEP_OUTER..KEEP_OUTER + omitted,
+                std::iter::once(format!("... {} more frames ...", omitted)),
+            );
+        }
+        let captured_env = self.environment.borrow().get_all_variables();
+        let message = if stack_full && self.call_stack.len() < self.max_depth {
+            format!(
+                "maximum call depth exceeded ({} nested calls fill this thread's stack)",
+                self.call_stack.len()
+            )
+        } else {
+            format!(
+                "maximum call depth exceeded ({} nested calls)",
+                self.max_depth
+            )
+        };
+        Err(RuntimeError::with_env(
+            message,
+            span,
+            self.serialize_environment(&captured_env),
+            stack_trace,
+        ))
+    }
+
+    /// The error that carries a value thrown out of a function body (or a
+    /// block expression) across the call. The value is kept so a `catch`
+    /// further up receives it as thrown rather than the error's text.
+    pub(crate) fn escape_throw(&mut self, value: Value, span: Span) -> RuntimeError {
+        let error = RuntimeError::General {
+            message: format!("Unhandled exception: {}", value),
+            span,
+        };
+        self.escaped_throw = Some((error.to_string(), value));
+        error
+    }
+
+    /// What a `catch` binds for `error`: the value it carries out of a call
+    /// (see [`Interpreter::escape_throw`]), or else its text. Wrapping an
+    /// error on the way up (e.g. with the environment) keeps its text, which
+    /// is what ties the two together.
+    pub(crate) fn caught_value(&mut self, error: &RuntimeError) -> Value {
+        let text = error.to_string();
+        match self.escaped_throw.take() {
+            Some((escaped, value)) if escaped == text => value,
+            _ => Value::String(text.into()),
+        }
     }
 
     /// Pop a frame from the call stack.
     pub(crate) fn pop_frame(&mut self) {
-        self.call_stack.pop();
+        let frame = self.call_stack.pop();
+        if let (Some(observer), Some(frame)) = (self.observer.as_mut(), frame.as_ref()) {
+            observer.on_function_exit(frame);
+        }
         crate::serve::span_log::pop_fn();
+        crate::interpreter::profiler::exit();
     }
 
     /// Get the current file path from the call stack (top frame) or fallback to current_source_path.
@@ -430,7 +698,32 @@ impl Interpreter {
         for stmt in &program.statements {
             self.execute(stmt)?;
         }
-        Ok(())
+        self.run_pending_tasks()
+    }
+
+    /// Lex, parse and run Soli code embedded in the runtime (a DSL prelude),
+    /// registered as the synthetic source `@soli/<name>.sl` so the functions
+    /// it defines report locations in it; see [`crate::source_map`].
+    pub(crate) fn interpret_synthetic(
+        &mut self,
+        name: &str,
+        source: &'static str,
+    ) -> RuntimeResult<()> {
+        let path = crate::source_map::register(name, source);
+        let located = |message: String, span: Span| RuntimeError::General {
+            message: format!("{} in {}", message, path),
+            span,
+        };
+        let tokens = crate::lexer::Scanner::new(source)
+            .scan_tokens()
+            .map_err(|e| located(format!("Lexer error: {}", e), e.span()))?;
+        let program = crate::parser::Parser::new(tokens)
+            .parse()
+            .map_err(|e| located(format!("Parser error: {}", e), e.span()))?;
+        let outer = self.current_source_path.replace(PathBuf::from(&path));
+        let result = self.interpret(&program);
+        self.current_source_path = outer;
+        result
     }
 
     /// Execute `statements` in an environment already wrapped in Rc<RefCell<>>.
@@ -554,10 +847,43 @@ impl Interpreter {
         func: &Function,
         this: Option<Value>,
         arguments: Vec<Value>,
+    ) -> RuntimeResult<Value> {
+        self.invoke_function(func, this, arguments.into_iter().map(Some))
+    }
+
+    /// Like [`Self::call_function_with_this`], for arguments already matched
+    /// to the parameters by `calls::arguments::bind_arguments`. `None` slots
+    /// take their default.
+    pub(crate) fn call_function_bound(
+        &mut self,
+        func: &Function,
+        this: Option<Value>,
+        slots: Vec<Option<Value>>,
+    ) -> RuntimeResult<Value> {
+        self.invoke_function(func, this, slots.into_iter())
+    }
+
+    /// Run `func` with one optional argument per parameter; a missing
+    /// trailing argument counts as `None`.
+    fn invoke_function(
+        &mut self,
+        func: &Function,
+        this: Option<Value>,
+        arguments: impl Iterator<Item = Option<Value>>,
     ) -> RuntimeResult<Value> {
         // Push stack frame with the function's source path (where it was defined)
         let span = func.span.unwrap_or_else(|| Span::new(0, 0, 1, 1));
+        self.check_call_depth(span)?;
         self.push_frame(&func.name, span, func.source_path.clone());
+        if crate::interpreter::profiler::is_enabled() {
+            match &this {
+                Some(Value::Instance(inst)) => {
+                    crate::interpreter::profiler::qualify(&inst.borrow().class.name)
+                }
+                Some(Value::Class(class)) => crate::interpreter::profiler::qualify(&class.name),
+                _ => {}
+            }
+        }
 
         // Try to take the cached call env; on a recursive call the slot is
         // None and we fall back to allocating a fresh one. For instance
@@ -569,45 +895,68 @@ impl Interpreter {
                 cached.borrow_mut().reset_for_call();
                 cached
             }
-            None => Rc::new(RefCell::new(Environment::with_enclosing(
+            None => Rc::new(RefCell::new(Environment::for_frame(
                 func.closure.clone(),
+                func.frame_layout(),
             ))),
         };
 
-        {
-            let mut call_env_inner = call_env_rc.borrow_mut();
-            if let Some(this_val) = this {
-                call_env_inner.define("this".to_string(), this_val);
-            }
-            for (param, value) in func.params.iter().zip(arguments) {
-                call_env_inner.define(param.name.clone(), value);
-            }
-
-            // Store defining_superclass for super calls
-            if let Some(ref sc) = func.defining_superclass {
-                call_env_inner.define(
-                    "__defining_superclass__".to_string(),
-                    Value::Class(sc.clone()),
-                );
-            }
+        // A tail call rebinds `this` too.
+        let this_for_tail = this.clone();
+        if let Err(e) = self.bind_call_env(func, this, &call_env_rc, arguments) {
+            self.pop_frame();
+            return Err(e);
+        }
+        // A generator call hands back an iterator over the suspended body.
+        // The call env now belongs to it, so it isn't re-cached.
+        if func.is_generator {
+            self.pop_frame();
+            return Ok(self.start_generator(func, call_env_rc));
+        }
+        // An async call queues the body and hands back its future.
+        if func.is_async {
+            self.pop_frame();
+            return Ok(self.spawn_task(func, call_env_rc));
         }
 
         // Store reference to capture environment on error and to re-cache after.
-        let env_for_capture = call_env_rc.clone();
+        let mut env_for_capture = call_env_rc;
 
-        // Execute the function body — reuse call_env_rc directly rather than
+        // Execute the function body — reuse the call env directly rather than
         // cloning the inner Environment (which would allocate 2 fresh HashMaps
-        // per call only to throw them away).
-        let result = match self.execute_block_in(&func.body, call_env_rc) {
+        // per call only to throw them away). A self-recursive `return f(...)`
+        // comes back as a parked tail call: rebind and run the body again.
+        let outer_target = self.tail_target;
+        let body_result = loop {
+            self.tail_target = Some(tail_calls::TailTarget::new(func, &env_for_capture));
+            let step = self.execute_block_in(&func.body, env_for_capture.clone());
+            let Some(args) = self.tail_call.take() else {
+                break step;
+            };
+            // Closures made by the last round still hold its env.
+            if Rc::strong_count(&env_for_capture) == 1 {
+                env_for_capture.borrow_mut().reset_for_call();
+            } else {
+                env_for_capture = Rc::new(RefCell::new(Environment::for_frame(
+                    func.closure.clone(),
+                    func.frame_layout(),
+                )));
+            }
+            let args = args.into_iter().map(Some);
+            if let Err(e) = self.bind_call_env(func, this_for_tail.clone(), &env_for_capture, args)
+            {
+                break Err(e);
+            }
+        };
+        self.tail_target = outer_target;
+
+        let result = match body_result {
             Ok(ControlFlow::Normal(v)) => Ok(v),
             Ok(ControlFlow::Return(return_value)) => Ok(return_value),
             // A stray `break` outside a loop stops the body; it must not escape
             // the call boundary.
             Ok(ControlFlow::Continue) | Ok(ControlFlow::Break) => Ok(Value::Null),
-            Ok(ControlFlow::Throw(e)) => Err(RuntimeError::General {
-                message: format!("Unhandled exception: {}", e),
-                span: Span::default(),
-            }),
+            Ok(ControlFlow::Throw(e)) => Err(self.escape_throw(e, Span::default())),
             Err(e) => {
                 // Preserve errors that already have captured environment (breakpoint or WithEnv)
                 if e.is_breakpoint() || e.breakpoint_env_json().is_some() {
@@ -693,19 +1042,93 @@ impl Interpreter {
         result
     }
 
+    /// Bind `this`, the defining superclass and the parameters in a call env.
+    fn bind_call_env(
+        &mut self,
+        func: &Function,
+        this: Option<Value>,
+        env: &Rc<RefCell<Environment>>,
+        arguments: impl Iterator<Item = Option<Value>>,
+    ) -> RuntimeResult<()> {
+        {
+            let mut env_inner = env.borrow_mut();
+            if let Some(this_val) = this {
+                env_inner.define("this".to_string(), this_val);
+            }
+
+            // Store defining_superclass for super calls
+            if let Some(ref sc) = func.defining_superclass {
+                env_inner.define(
+                    "__defining_superclass__".to_string(),
+                    Value::Class(sc.clone()),
+                );
+            }
+        }
+        self.bind_parameters(&func.params, env, arguments)
+    }
+
     /// Execute a constructor body with a call-stack frame carrying the
     /// constructor's defining file, so coverage, stack traces, and the
     /// flamegraph attribute constructor-body lines to the class's source
     /// file — exactly like `call_function_with_this` does for methods.
     /// Without the frame, constructor hits attribute to the *caller* and
     /// are dropped from coverage as test-directory lines.
-    pub(crate) fn execute_constructor_body(&mut self, ctor: &Function, ctor_env: Environment) {
+    ///
+    /// `arguments` holds one optional value per parameter, as for
+    /// [`Self::call_function_bound`]; defaults are evaluated with `this`
+    /// already bound.
+    pub(crate) fn run_constructor(
+        &mut self,
+        ctor: &Function,
+        instance: &Rc<RefCell<Instance>>,
+        arguments: impl Iterator<Item = Option<Value>>,
+    ) -> RuntimeResult<()> {
         let span = ctor.span.unwrap_or_else(|| Span::new(0, 0, 1, 1));
+        self.check_call_depth(span)?;
         self.push_frame(&ctor.name, span, ctor.source_path.clone());
+        let mut ctor_env = Environment::for_frame(ctor.closure.clone(), ctor.frame_layout());
+        ctor_env.define("this".to_string(), Value::Instance(instance.clone()));
+        let ctor_env = Rc::new(RefCell::new(ctor_env));
+        if let Err(e) = self.bind_parameters(&ctor.params, &ctor_env, arguments) {
+            self.pop_frame();
+            return Err(e);
+        }
         // Result intentionally discarded (pre-existing constructor behavior);
         // no `?` between push and pop, so the frame is always popped.
-        let _ = self.execute_block(&ctor.body, ctor_env);
+        let _ = self.execute_block_in(&ctor.body, ctor_env);
         self.pop_frame();
+        Ok(())
+    }
+
+    /// Define `params` in `env`, evaluating the default of each parameter
+    /// left without an argument. Defaults run in `env` itself, after the
+    /// parameters before them are bound, so `def f(a, b = a * 2)` works and a
+    /// default like `[]` is a fresh value on every call. An unset block
+    /// parameter is `null`, an unset `...rest` an empty array.
+    pub(crate) fn bind_parameters(
+        &mut self,
+        params: &[Parameter],
+        env: &Rc<RefCell<Environment>>,
+        mut arguments: impl Iterator<Item = Option<Value>>,
+    ) -> RuntimeResult<()> {
+        for (index, param) in params.iter().enumerate() {
+            let value = match arguments.next().flatten() {
+                Some(value) => value,
+                None => match &param.default_value {
+                    Some(default) => {
+                        let previous = std::mem::replace(&mut self.environment, env.clone());
+                        let value = self.evaluate(default);
+                        self.environment = previous;
+                        value?
+                    }
+                    None if param.is_block_param => Value::Null,
+                    None if param.is_variadic => Value::Array(Rc::new(RefCell::new(Vec::new()))),
+                    None => continue,
+                },
+            };
+            env.borrow_mut().define_param(index, &param.name, value);
+        }
+        Ok(())
     }
 }
 
@@ -984,6 +1407,41 @@ mod helper_error_path_tests {
             stack_trace
         );
     }
+
+    /// Functions defined by runtime-embedded Soli (the routing DSL) report
+    /// their synthetic source, not the file that was loading when they ran.
+    #[test]
+    fn error_in_synthetic_source_reports_its_path() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_source_path(PathBuf::from("/fake/app/config/routes.sl"));
+        interpreter
+            .interpret_synthetic(
+                "test_dsl",
+                "\nfn resources(name) {\n  return missing_var;\n}\n",
+            )
+            .unwrap();
+        assert_eq!(
+            interpreter.current_source_path,
+            Some(PathBuf::from("/fake/app/config/routes.sl"))
+        );
+
+        let caller_tokens = Scanner::new("resources(\"posts\");").scan_tokens().unwrap();
+        let caller_program = Parser::new(caller_tokens).parse().unwrap();
+        let err = interpreter
+            .interpret(&caller_program)
+            .expect_err("expected an UndefinedVariable error from inside the DSL");
+        let stack_trace = err
+            .breakpoint_stack_trace()
+            .expect("error should carry a captured stack trace");
+        assert!(
+            stack_trace
+                .iter()
+                .any(|f| f.contains("@soli/test_dsl.sl:3")),
+            "stack trace should point into the DSL source, got: {:?}",
+            stack_trace
+        );
+        assert!(crate::source_map::get("@soli/test_dsl.sl").is_some());
+    }
 }
 
 #[cfg(test)]
@@ -1083,3 +1541,77 @@ let result = u&.name ?? "default"
         assert_eq!(val, Value::String("Eve".into()));
     }
 }
+
+#[cfg(test)]
+mod call_depth_tests {
+    use super::*;
+    use crate::lexer::Scanner;
+    use crate::parser::Parser;
+
+    #[test]
+    fn runaway_recursion_fails_with_a_trimmed_trace() {
+        // On a test thread's 2 MiB stack the stack guard would stop an
+        // unoptimized build first.
+        std::thread::Builder::new()
+            .stack_size(stack_size_for_depth(30))
+            .spawn(|| {
+                let tokens = Scanner::new("fn down(n) { return 1 + down(n + 1) }\ndown(0)")
+                    .scan_tokens()
+                    .unwrap();
+                let program = Parser::new(tokens).parse().unwrap();
+                let mut interpreter = Interpreter::new();
+                interpreter.set_max_depth(30);
+
+                let err = interpreter.interpret(&program).unwrap_err();
+                assert!(err
+                    .to_string()
+                    .contains("maximum call depth exceeded (30 nested calls)"));
+                let trace = err.breakpoint_stack_trace().unwrap();
+                assert_eq!(trace.len(), 21);
+                assert_eq!(trace[5], "... 10 more frames ...");
+                assert!(interpreter.call_stack.is_empty());
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn recursion_stops_at_the_stack_a_small_thread_has() {
+        let outcome = std::thread::Builder::new()
+            .stack_size(2 * 1024 * 1024)
+            .spawn(|| {
+                let tokens = Scanner::new("fn f() { return 1 + f() }\nf()")
+                    .scan_tokens()
+                    .unwrap();
+                let program = Parser::new(tokens).parse().unwrap();
+                let mut interpreter = Interpreter::new();
+                interpreter.set_max_depth(DEFAULT_MAX_CALL_DEPTH);
+                interpreter.interpret(&program).unwrap_err().to_string()
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert!(
+            outcome.contains("fill this thread's stack"),
+            "error: {}",
+            outcome
+        );
+    }
+
+    #[test]
+    fn serve_interpreters_get_the_lower_worker_limit() {
+        if std::env::var_os(MAX_CALL_DEPTH_ENV).is_some() {
+            return;
+        }
+        assert_eq!(
+            Interpreter::new_for_serve().max_depth(),
+            DEFAULT_WORKER_CALL_DEPTH
+        );
+        assert_eq!(Interpreter::new().max_depth(), DEFAULT_MAX_CALL_DEPTH);
+        assert!(
+            stack_size_for_depth(worker_call_depth())
+                < stack_size_for_depth(max_call_depth_from_env())
+        );
+    }
+}
@@ -22,6 +22,10 @@ impl Interpreter {
                             let arr = arr.borrow();
                             values.extend(arr.clone());
                         }
+                        Value::Range(ref range) => values.extend(range.iter().map(Value::Int)),
+                        Value::Iterator(ref iter) => {
+                            values.extend(self.iterator_collect(iter, inner.span)?)
+                        }
                         _ => {
                             return Err(RuntimeError::type_error(
                                 "cannot spread non-array value",
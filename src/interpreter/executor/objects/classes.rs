@@ -1,15 +1,16 @@
 //! Class instantiation evaluation (new expression).
 
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use crate::ast::expr::Argument;
 use crate::ast::Expr;
 use crate::error::RuntimeError;
-use crate::interpreter::environment::Environment;
+use crate::interpreter::executor::calls::arguments::bind_arguments;
 use crate::interpreter::executor::{Interpreter, RuntimeResult};
 use crate::interpreter::value::{Instance, Value};
+use crate::interpreter::value_struct::StructValue;
 use crate::span::Span;
 
 impl Interpreter {
@@ -32,8 +33,14 @@ impl Interpreter {
             }
         };
 
+        if class.struct_fields.is_some() {
+            let (positional_args, named_args) = self.evaluate_constructor_args(arguments, span)?;
+            return StructValue::construct(&class, positional_args, named_args)
+                .map_err(|msg| RuntimeError::type_error(msg, span));
+        }
+
         // Create instance
-        let instance = Rc::new(RefCell::new(Instance::new(class.clone())));
+        let instance = crate::interpreter::cycles::new_instance(Instance::new(class.clone()));
 
         // Initialize fields from class field declarations (including inherited fields)
         self.initialize_instance_fields(&class, &instance)?;
@@ -41,93 +48,53 @@ impl Interpreter {
         // Call constructor if present
         if let Some(ctor) = class.find_constructor() {
             let ctor = &ctor;
-            let mut positional_args = Vec::new();
-            let mut named_args = HashMap::new();
+            let (positional_args, named_args) = self.evaluate_constructor_args(arguments, span)?;
 
-            for arg in arguments {
-                match arg {
-                    Argument::Positional(expr) => {
-                        positional_args.push(self.evaluate(expr)?);
-                    }
-                    Argument::Named(named) => {
-                        if named_args.contains_key(&named.name) {
-                            return Err(RuntimeError::type_error(
-                                format!("duplicate named argument '{}'", named.name),
-                                named.span,
-                            ));
-                        }
-                        named_args.insert(named.name.clone(), self.evaluate(&named.value)?);
-                    }
-                    Argument::Block(_) => {
-                        return Err(RuntimeError::type_error(
-                            "constructor does not support block arguments",
-                            span,
-                        ));
-                    }
-                }
-            }
+            let slots =
+                bind_arguments(&class.name, &ctor.params, positional_args, named_args, span)?;
+            // Execute constructor body (pushes a frame so coverage/stack
+            // traces attribute constructor lines to the class's own file).
+            self.run_constructor(ctor, &instance, slots.into_iter())?;
+        }
 
-            let param_names: Vec<String> = ctor.params.iter().map(|p| p.name.clone()).collect();
+        Ok(Value::Instance(instance))
+    }
 
-            // Check for unknown named arguments
-            for name in named_args.keys() {
-                if !param_names.contains(name) {
-                    return Err(RuntimeError::undefined_variable(name.clone(), span));
+    /// Evaluate `new` arguments into positional values and named values.
+    fn evaluate_constructor_args(
+        &mut self,
+        arguments: &[Argument],
+        span: Span,
+    ) -> RuntimeResult<(Vec<Value>, HashMap<String, Value>)> {
+        let mut positional_args = Vec::new();
+        let mut named_args = HashMap::new();
+
+        for arg in arguments {
+            match arg {
+                Argument::Positional(expr) => {
+                    positional_args.push(self.evaluate(expr)?);
                 }
-            }
-
-            // Build constructor arguments
-            let mut ctor_args = Vec::new();
-            let mut used_params = HashSet::new();
-
-            // Positional arguments first
-            for (i, arg_val) in positional_args.iter().enumerate() {
-                if i < param_names.len() {
-                    ctor_args.push(arg_val.clone());
-                    used_params.insert(param_names[i].clone());
-                } else {
-                    return Err(RuntimeError::wrong_arity(
-                        ctor.full_arity(),
-                        positional_args.len() + named_args.len(),
-                        span,
-                    ));
+                Argument::Named(named) => {
+                    if named_args.contains_key(&named.name) {
+                        return Err(RuntimeError::type_error(
+                            format!("duplicate named argument '{}'", named.name),
+                            named.span,
+                        ));
+                    }
+                    named_args.insert(named.name.clone(), self.evaluate(&named.value)?);
                 }
-            }
-
-            // Named arguments and defaults
-            for (i, param_name) in param_names.iter().enumerate() {
-                if used_params.contains(param_name) {
-                    continue;
+                Argument::Spread(expr) => {
+                    positional_args.extend(self.evaluate_spread(expr)?);
                 }
-                if let Some(named_val) = named_args.get(param_name) {
-                    ctor_args.push(named_val.clone());
-                } else if let Some(default_expr) = ctor.param_default_value(i) {
-                    let default_value = self.evaluate(default_expr)?;
-                    ctor_args.push(default_value);
-                } else {
-                    return Err(RuntimeError::wrong_arity(
-                        ctor.arity(),
-                        ctor_args.len(),
+                Argument::Block(_) => {
+                    return Err(RuntimeError::type_error(
+                        "constructor does not support block arguments",
                         span,
                     ));
                 }
             }
-
-            // Create constructor environment
-            let ctor_env = Environment::with_enclosing(ctor.closure.clone());
-            let mut ctor_env = ctor_env;
-            ctor_env.define("this".to_string(), Value::Instance(instance.clone()));
-
-            for (param, value) in ctor.params.iter().zip(ctor_args.iter()) {
-                ctor_env.define(param.name.clone(), value.clone());
-            }
-
-            // Execute constructor body (pushes a frame so coverage/stack
-            // traces attribute constructor lines to the class's own file).
-            self.execute_constructor_body(ctor, ctor_env);
         }
-
-        Ok(Value::Instance(instance))
+        Ok((positional_args, named_args))
     }
 
     /// Initialize instance fields from class field declarations, including
@@ -7,7 +7,7 @@ use rust_decimal::prelude::ToPrimitive;
 
 use crate::ast::*;
 use crate::error::RuntimeError;
-use crate::interpreter::value::Value;
+use crate::interpreter::value::{RangeValue, Value};
 use crate::span::Span;
 
 use super::{Interpreter, RuntimeResult};
@@ -46,7 +46,57 @@ impl Interpreter {
             .resolve()
             .map_err(|e| RuntimeError::new(e, span))?;
 
-        self.evaluate_binary_values(&left_val, op, &right_val, span)
+        self.evaluate_binary_operator(&left_val, op, &right_val, span)
+    }
+
+    /// [`Self::evaluate_binary_values`], unless the left operand is an
+    /// instance whose class overloads `op` (`op_add` for `+`, see
+    /// [`BinaryOp::method_name`]) — then the result is that method's.
+    /// Comparisons take the method's result by truthiness, and `!=` negates
+    /// `op_eq`. Only the left operand is consulted: `2 * v` stays an error
+    /// when `v * 2` is overloaded.
+    pub(crate) fn evaluate_binary_operator(
+        &mut self,
+        left_val: &Value,
+        op: BinaryOp,
+        right_val: &Value,
+        span: Span,
+    ) -> RuntimeResult<Value> {
+        if let (Value::Instance(_), Some(name)) = (left_val, op.method_name()) {
+            if let Some(result) =
+                self.call_operator_method(left_val, name, vec![right_val.clone()], span)?
+            {
+                return Ok(match op {
+                    BinaryOp::NotEqual => Value::Bool(!result.is_truthy()),
+                    _ if op.is_comparison() => Value::Bool(result.is_truthy()),
+                    _ => result,
+                });
+            }
+        }
+        self.evaluate_binary_values(left_val, op, right_val, span)
+    }
+
+    /// Call `receiver.<name>(arguments)` when `receiver` is an instance whose
+    /// class defines `name` — the magic methods behind operator overloading.
+    /// `None` when it doesn't, so the caller falls back to the built-in
+    /// meaning of the operator.
+    pub(crate) fn call_operator_method(
+        &mut self,
+        receiver: &Value,
+        name: &str,
+        arguments: Vec<Value>,
+        span: Span,
+    ) -> RuntimeResult<Option<Value>> {
+        let Value::Instance(inst) = receiver else {
+            return Ok(None);
+        };
+        let method = inst.borrow().class.find_method(name);
+        match method {
+            Some(method) => self
+                .invoke_instance_method(inst, &method, arguments, span)
+                .map(Some),
+            None => Ok(None),
+        }
     }
 
     fn eval_shovel(&mut self, left: &Expr, right: &Expr, span: Span) -> RuntimeResult<Value> {
@@ -256,7 +306,8 @@ create the record first, or use {}.create({{...}})",
                 }
                 self.compare_values(left_val, right_val, span, |a, b| a >= b)
             }
-            BinaryOp::Range => self.eval_range(left_val, right_val, span),
+            BinaryOp::Range => self.eval_range(left_val, right_val, false, span),
+            BinaryOp::RangeInclusive => self.eval_range(left_val, right_val, true, span),
             BinaryOp::Shovel => match left_val {
                 Value::Array(arr) => {
                     arr.borrow_mut().push(right_val.clone());
@@ -273,15 +324,21 @@ create the record first, or use {}.create({{...}})",
         }
     }
 
-    fn eval_range(&self, left: &Value, right: &Value, span: Span) -> RuntimeResult<Value> {
+    fn eval_range(
+        &self,
+        left: &Value,
+        right: &Value,
+        inclusive: bool,
+        span: Span,
+    ) -> RuntimeResult<Value> {
         match (left, right) {
-            (Value::Int(start), Value::Int(end)) => {
-                let arr: Vec<Value> = (*start..*end).map(Value::Int).collect();
-                Ok(Value::Array(Rc::new(RefCell::new(arr))))
-            }
+            (Value::Int(start), Value::Int(end)) => Ok(Value::Range(Rc::new(RangeValue::new(
+                *start, *end, inclusive,
+            )))),
             _ => Err(RuntimeError::type_error(
                 format!(
-                    "range (..) expects two integers, got {} and {}",
+                    "range ({}) expects two integers, got {} and {}",
+                    if inclusive { "..=" } else { ".." },
                     left.type_name(),
                     right.type_name()
                 ),
@@ -628,6 +685,14 @@ create the record first, or use {}.create({{...}})",
     ) -> RuntimeResult<Value> {
         let val = self.evaluate(operand)?;
 
+        if op == UnaryOp::Negate {
+            if let Some(result) =
+                self.call_operator_method(&val, crate::ast::expr::NEGATE_METHOD, Vec::new(), span)?
+            {
+                return Ok(result);
+            }
+        }
+
         match op {
             UnaryOp::Negate => match val {
                 Value::Int(n) => Ok(Value::Int(-n)),
@@ -30,6 +30,8 @@ impl Interpreter {
                     "Bool" => matches!(value, Value::Bool(_)),
                     "String" => matches!(value, Value::String(_)),
                     "Void" => matches!(value, Value::Null),
+                    "Array" => matches!(value, Value::Array(_)),
+                    "Hash" => matches!(value, Value::Hash(_)),
                     _ => {
                         if let Value::Instance(inst) = value {
                             inst.borrow().class.name == *type_name
@@ -94,6 +96,22 @@ impl Interpreter {
                 Ok(Some(bindings))
             }
 
+            MatchPattern::Tuple(elements) => {
+                let items = match value {
+                    Value::Tuple(items) if items.len() == elements.len() => items.clone(),
+                    _ => return Ok(None),
+                };
+
+                let mut bindings = Vec::new();
+                for (item, element_pattern) in items.iter().zip(elements) {
+                    match self.match_pattern(item, element_pattern)? {
+                        Some(element_bindings) => bindings.extend(element_bindings),
+                        None => return Ok(None),
+                    }
+                }
+                Ok(Some(bindings))
+            }
+
             MatchPattern::Hash { fields, rest } => {
                 let hash = match value {
                     Value::Hash(hash) => hash.borrow().clone(),
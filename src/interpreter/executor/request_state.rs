@@ -0,0 +1,89 @@
+//! Per-request state a `soli serve` worker must not carry over.
+//!
+//! A worker thread owns one interpreter and serves request after request on
+//! it. Besides the interpreter's own bookkeeping (source path, call stack,
+//! pending tail call), builtins keep the current request's session,
+//! controller, cookies, cache validators and CSP sources in thread-locals.
+//! [`Interpreter::reset_request_state`] clears all of it in one place, so a
+//! request can never see (or re-emit) what the previous one on the same
+//! worker left behind — even when that request failed halfway through.
+
+use crate::interpreter::builtins::{
+    controller::registry, cookie_jar, http_cache, model::connections, named_routes,
+    security_headers, session, session_cookie, template, test_server,
+};
+
+use super::Interpreter;
+
+impl Interpreter {
+    /// Forget everything the last request left on this interpreter and its
+    /// thread. The serve worker calls this between requests; globals,
+    /// loaded classes and caches that don't depend on the request survive.
+    pub fn reset_request_state(&mut self) {
+        self.current_source_path = None;
+        self.call_stack.clear();
+        self.tail_target = None;
+        self.tail_call = None;
+        self.error_observed = false;
+
+        session::set_current_session_id(None);
+        session::clear_response_cookies();
+        session_cookie::clear_request_state();
+        cookie_jar::install_request_cookie_header(None);
+        registry::clear_current_controller();
+        template::clear_view_debug_context();
+        named_routes::clear_current_request_host();
+        http_cache::reset_for_new_request();
+        crate::template::response_cache::reset_for_new_request();
+        security_headers::reset_inline_sources();
+        // Reads must not stay pinned to the primary because an earlier
+        // request wrote.
+        connections::reset_request();
+        crate::coverage::flush_current_coverage();
+        // Hooks added or removed by a hot reload apply from the next request.
+        crate::serve::forget_stale_controller_hooks();
+        // A single atomic load outside the test runner.
+        if test_server::is_test_runner_process() {
+            test_server::clear_captured_render();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::value::Value;
+    use crate::span::Span;
+    use std::path::PathBuf;
+
+    #[test]
+    fn nothing_from_the_previous_request_survives() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_source_path(PathBuf::from("/app/controllers/posts_controller.sl"));
+        interpreter.push_frame("PostsController#show", Span::default(), None);
+        interpreter.error_observed = true;
+        session::set_current_session_id(Some("visitor-a".to_string()));
+        registry::set_current_controller(Value::Int(1));
+
+        interpreter.reset_request_state();
+
+        assert!(interpreter.current_source_path.is_none());
+        assert!(interpreter.call_stack.is_empty());
+        assert!(!interpreter.error_observed);
+        assert_eq!(session::get_current_session_id(), None);
+        assert!(registry::get_current_controller().is_none());
+    }
+
+    #[test]
+    fn globals_defined_by_the_app_survive() {
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .environment
+            .borrow_mut()
+            .define("APP_NAME".to_string(), Value::String("blog".into()));
+
+        interpreter.reset_request_state();
+
+        assert!(interpreter.environment.borrow().get("APP_NAME").is_some());
+    }
+}
@@ -0,0 +1,200 @@
+//! Async functions and the task scheduler.
+//!
+//! Calling an `async fn` binds its arguments and queues the body as a task,
+//! handing back a future for its result. Nothing runs until something
+//! awaits: `await` on a task's future runs that task, and `await` on a
+//! pending I/O future (HTTP, `@sdbql{}`, `System.run`) first runs the other
+//! queued tasks, so they start their own requests while this one is in
+//! flight, and only then blocks. A task is never paused halfway; the
+//! concurrency is in the I/O the tasks have in flight at the same time.
+//!
+//! While a task runs, HTTP calls and `@sdbql{}` queries hand back futures
+//! instead of blocking (see [`in_task`]); inside a server they run on the
+//! worker's tokio runtime (`serve::get_tokio_handle`).
+//!
+//! Tasks nobody awaits run when the program (or the request) finishes.
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use crate::ast::{Body, TypeAnnotation};
+use crate::error::RuntimeError;
+use crate::interpreter::environment::Environment;
+use crate::interpreter::value::{value_matches_type, Function, FutureState, Value};
+use crate::span::Span;
+
+use super::{ControlFlow, Interpreter, RuntimeResult};
+
+/// A queued `async fn` call: the body and its bound call environment, plus
+/// the future its result goes to.
+struct Task {
+    id: u64,
+    name: String,
+    span: Span,
+    source_path: Option<String>,
+    body: Arc<Body>,
+    env: Rc<RefCell<Environment>>,
+    return_type: Option<TypeAnnotation>,
+    future: Arc<Mutex<FutureState>>,
+}
+
+thread_local! {
+    static QUEUE: RefCell<VecDeque<Task>> = const { RefCell::new(VecDeque::new()) };
+    static NEXT_ID: Cell<u64> = const { Cell::new(1) };
+    static RUNNING: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Whether a task body is running on this thread. I/O builtins return
+/// futures instead of blocking while it is, so the scheduler can run other
+/// tasks during the wait.
+pub fn in_task() -> bool {
+    RUNNING.with(|running| running.get() > 0)
+}
+
+fn take_task(id: u64) -> Option<Task> {
+    QUEUE.with(|queue| {
+        let mut queue = queue.borrow_mut();
+        let at = queue.iter().position(|task| task.id == id)?;
+        queue.remove(at)
+    })
+}
+
+fn next_task() -> Option<Task> {
+    QUEUE.with(|queue| queue.borrow_mut().pop_front())
+}
+
+/// The message a failed task's future keeps; `await` raises it again.
+fn error_message(error: &RuntimeError) -> String {
+    match error {
+        RuntimeError::General { message, .. } | RuntimeError::TypeError { message, .. } => {
+            message.clone()
+        }
+        other => other.to_string(),
+    }
+}
+
+impl Interpreter {
+    /// Queue a call to an `async fn` whose arguments are already bound in
+    /// `env`, and return the future for its result.
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub(crate) fn spawn_task(&self, func: &Function, env: Rc<RefCell<Environment>>) -> Value {
+        let id = NEXT_ID.with(|next| {
+            let id = next.get();
+            next.set(id + 1);
+            id
+        });
+        let future = Arc::new(Mutex::new(FutureState::Task {
+            id,
+            name: func.name.clone(),
+        }));
+        let task = Task {
+            id,
+            name: func.name.clone(),
+            span: func.span.unwrap_or_default(),
+            source_path: func.source_path.clone(),
+            body: func.body.clone(),
+            env,
+            return_type: func.return_type.clone(),
+            future: future.clone(),
+        };
+        QUEUE.with(|queue| queue.borrow_mut().push_back(task));
+        Value::Future(future)
+    }
+
+    /// `await value`: the result of a future, or of each future in an array
+    /// of them. Anything else is returned as is.
+    pub(crate) fn await_value(&mut self, value: Value, span: Span) -> RuntimeResult<Value> {
+        match value {
+            Value::Array(items) => {
+                let items = items.borrow().clone();
+                let mut results = Vec::with_capacity(items.len());
+                for item in items {
+                    results.push(self.await_value(item, span)?);
+                }
+                Ok(Value::Array(Rc::new(RefCell::new(results))))
+            }
+            Value::Future(ref state) => {
+                let task = match &*state
+                    .lock()
+                    .map_err(|_| RuntimeError::new("Future lock poisoned", span))?
+                {
+                    FutureState::Task { id, name } => Some((*id, name.clone())),
+                    _ => None,
+                };
+                if let Some((id, name)) = task {
+                    let Some(task) = take_task(id) else {
+                        return Err(RuntimeError::new(
+                            format!("async fn '{}' awaits its own result", name),
+                            span,
+                        ));
+                    };
+                    self.run_task(task)?;
+                }
+                while value.future_is_waiting() {
+                    let Some(task) = next_task() else {
+                        break;
+                    };
+                    self.run_task(task)?;
+                }
+                value.resolve().map_err(|e| RuntimeError::new(e, span))
+            }
+            other => Ok(other),
+        }
+    }
+
+    /// Run every queued task, including any they queue in turn. The first
+    /// failure is returned once the queue is empty, since nothing else will
+    /// ever see it.
+    pub fn run_pending_tasks(&mut self) -> RuntimeResult<()> {
+        let mut failure = None;
+        while let Some(task) = next_task() {
+            let future = task.future.clone();
+            let span = task.span;
+            self.run_task(task)?;
+            if failure.is_none() {
+                if let FutureState::Error(message) = &*future.lock().unwrap() {
+                    failure = Some(RuntimeError::new(message.clone(), span));
+                }
+            }
+        }
+        failure.map_or(Ok(()), Err)
+    }
+
+    /// Run a task body to the end and settle its future. Only a breakpoint
+    /// escapes; every other failure is kept in the future.
+    fn run_task(&mut self, task: Task) -> RuntimeResult<()> {
+        self.push_frame(&task.name, task.span, task.source_path.clone());
+        RUNNING.with(|running| running.set(running.get() + 1));
+        let result = self.execute_block_in(&task.body, task.env);
+        RUNNING.with(|running| running.set(running.get() - 1));
+        self.pop_frame();
+
+        let settled = match result {
+            Ok(ControlFlow::Normal(value)) | Ok(ControlFlow::Return(value)) => {
+                match &task.return_type {
+                    Some(expected) if !value_matches_type(&value, expected) => {
+                        FutureState::Error(format!(
+                            "function '{}' expected to return {}, got {}",
+                            task.name,
+                            expected,
+                            value.type_name()
+                        ))
+                    }
+                    _ => FutureState::Resolved(value),
+                }
+            }
+            Ok(ControlFlow::Continue) | Ok(ControlFlow::Break) => {
+                FutureState::Resolved(Value::Null)
+            }
+            Ok(ControlFlow::Throw(e)) => FutureState::Error(format!("{}", e)),
+            Err(e) if e.is_breakpoint() => return Err(e),
+            Err(e) => FutureState::Error(error_message(&e)),
+        };
+        if let Ok(mut state) = task.future.lock() {
+            *state = settled;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+//! How much of the current thread's stack is left.
+//!
+//! The call-depth limit assumes the interpreter runs on a thread sized with
+//! [`super::stack_size_for_depth`]. An embedder calling `Interpreter::new()`
+//! on its own thread, or the main thread, may have far less, and a Rust stack
+//! overflow aborts the process. A call is refused once less than
+//! [`STACK_RESERVE`] remains, so the limit the thread can actually hold wins.
+
+use std::cell::Cell;
+
+/// Stack kept free below the point a call is refused: room for the frames
+/// one tree-walked call and the error it fails with use in an unoptimized
+/// build.
+const STACK_RESERVE: usize = 256 * 1024;
+
+thread_local! {
+    /// Lowest address of this thread's stack, looked up on first use.
+    /// `Some(0)` when the platform can't say.
+    static STACK_LOW: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Whether the current thread's stack is too close to full for another call.
+/// Always `false` where the stack bounds are unknown.
+#[inline]
+pub(crate) fn stack_exhausted() -> bool {
+    let low = STACK_LOW.with(|cell| match cell.get() {
+        Some(low) => low,
+        None => {
+            let low = stack_low_address().unwrap_or(0);
+            cell.set(Some(low));
+            low
+        }
+    });
+    if low == 0 {
+        return false;
+    }
+    let marker = 0u8;
+    let here = std::hint::black_box(&marker) as *const u8 as usize;
+    here.saturating_sub(low) < STACK_RESERVE
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn stack_low_address() -> Option<usize> {
+    // SAFETY: `attr` is initialised by `pthread_getattr_np` before it is read
+    // and destroyed exactly once.
+    unsafe {
+        let mut attr: libc::pthread_attr_t = std::mem::zeroed();
+        if libc::pthread_getattr_np(libc::pthread_self(), &mut attr) != 0 {
+            return None;
+        }
+        let mut addr: *mut libc::c_void = std::ptr::null_mut();
+        let mut size: libc::size_t = 0;
+        let found = libc::pthread_attr_getstack(&attr, &mut addr, &mut size) == 0;
+        libc::pthread_attr_destroy(&mut attr);
+        found.then_some(addr as usize)
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn stack_low_address() -> Option<usize> {
+    // SAFETY: both calls only read the current thread's descriptor.
+    unsafe {
+        let thread = libc::pthread_self();
+        let top = libc::pthread_get_stackaddr_np(thread) as usize;
+        let size = libc::pthread_get_stacksize_np(thread);
+        top.checked_sub(size)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos")))]
+fn stack_low_address() -> Option<usize> {
+    None
+}
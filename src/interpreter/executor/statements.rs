@@ -3,39 +3,145 @@
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::ast::expr::Argument;
 use crate::ast::*;
 use crate::error::RuntimeError;
+use crate::interpreter::decorators::{DecoratorValue, Decorators};
 use crate::interpreter::environment::Environment;
 use crate::interpreter::value::{Class, Function, HashKey, HashPairs, Value};
+use crate::interpreter::value_struct::StructField;
 use crate::span::Span;
 
+use super::generators::LazyIterator;
 use super::{ControlFlow, Interpreter, RuntimeResult};
 
+/// How a `for` loop binds each item: the loop variable, the optional index
+/// counter, and for `for (a, b) in ...` the two names the item is split into.
+pub(super) struct LoopBinding<'a> {
+    pub(super) variable: &'a str,
+    pub(super) index_variable: Option<&'a str>,
+    pub(super) destructure: Option<&'a [String]>,
+    pub(super) span: Span,
+}
+
+impl LoopBinding<'_> {
+    /// Bind `item`, the loop's `index`-th, into the loop environment.
+    /// `entry` says `item` is a hash's `(key, value)` entry.
+    pub(super) fn bind(
+        &self,
+        env: &mut Environment,
+        item: Value,
+        index: i64,
+        entry: bool,
+    ) -> RuntimeResult<()> {
+        self.bind_destructured(env, &item, index, entry)?;
+        env.define_or_update(self.variable, item);
+        if let Some(idx_var) = self.index_variable {
+            env.define_or_update(idx_var, Value::Int(index));
+        }
+        Ok(())
+    }
+
+    /// `for (k, v) in hash` names each entry's key and value. Over anything
+    /// else `for (i, item) in items` enumerates: the first name is the index,
+    /// whatever the item is, tuples included.
+    fn bind_destructured(
+        &self,
+        env: &mut Environment,
+        item: &Value,
+        index: i64,
+        entry: bool,
+    ) -> RuntimeResult<()> {
+        if let Some(names) = self.destructure {
+            let items = if entry {
+                item.destructure(names.len())
+            } else {
+                Value::Tuple(Rc::new(vec![Value::Int(index), item.clone()]))
+                    .destructure(names.len())
+            }
+            .map_err(|e| RuntimeError::type_error(e, self.span))?;
+            for (name, value) in names.iter().zip(items) {
+                env.define_or_update(name, value);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where a `for` loop takes its items from. Arrays and hashes are read by
+/// position as the loop goes, never snapshotted, so a body that mutates the
+/// collection is safe (and observed live, matching the VM's iterator).
+pub(super) enum LoopCursor {
+    Array(Rc<RefCell<Vec<Value>>>, usize),
+    Tuple(Rc<Vec<Value>>, usize),
+    /// Ranges are walked in place, never collected into an array.
+    Range(std::ops::Range<i64>),
+    /// `for (k, v) in hash` walks the entries as `(key, value)` tuples.
+    HashEntries(Rc<RefCell<HashPairs>>, usize),
+    Iterator(Rc<RefCell<LazyIterator>>),
+}
+
+impl LoopCursor {
+    /// Whether the items are a hash's `(key, value)` entries.
+    pub(super) fn yields_entries(&self) -> bool {
+        matches!(self, LoopCursor::HashEntries(..))
+    }
+}
+
 impl Interpreter {
     /// Execute a statement, returning control flow information.
+    #[inline]
     pub(crate) fn execute(&mut self, stmt: &Stmt) -> RuntimeResult<ControlFlow> {
-        let source_path = stmt
-            .source_path
-            .clone()
-            .or_else(|| self.current_source_path.clone());
-        if let Some(ref path) = source_path {
-            if let Some(ref tracker) = self.coverage_tracker {
-                if let Ok(guard) = tracker.lock() {
-                    guard.record_line_hit(path, stmt.span.line_usize());
-                }
-            } else if let Some(global) = crate::coverage::get_global_coverage_tracker() {
-                if let Ok(guard) = global.lock() {
-                    guard.record_line_hit(path, stmt.span.line_usize());
+        if self.observer.is_none() {
+            return self.execute_statement(stmt);
+        }
+        self.observe_statement(stmt)
+    }
+
+    /// [`execute`](Self::execute) with the observer told about the
+    /// statement, and about the error it raises, if any.
+    fn observe_statement(&mut self, stmt: &Stmt) -> RuntimeResult<ControlFlow> {
+        // Running a statement means any earlier error was caught.
+        self.error_observed = false;
+        if let Some(mut observer) = self.observer.take() {
+            observer.on_statement(stmt);
+            observer.before_statement(stmt, self);
+            self.observer = Some(observer);
+        }
+        let result = self.execute_statement(stmt);
+        if let Err(error) = &result {
+            if !self.error_observed {
+                self.error_observed = true;
+                if let Some(observer) = self.observer.as_mut() {
+                    observer.on_error(error);
                 }
             }
         }
+        result
+    }
+
+    fn execute_statement(&mut self, stmt: &Stmt) -> RuntimeResult<ControlFlow> {
+        self.record_line_hit(stmt);
+        self.check_budget(stmt.span)?;
         match &stmt.kind {
             StmtKind::Expression(expr) => {
+                if self.tail_target.is_some_and(|t| t.ends_body(stmt))
+                    && self.defer_tail_call(stmt, expr)?
+                {
+                    return Ok(ControlFlow::Return(Value::Null));
+                }
                 let value = self.evaluate(expr)?;
                 // Check for breakpoint marker
                 if matches!(value, Value::Breakpoint) {
+                    if let Some(mut observer) = self.observer.take() {
+                        let resume = observer.on_debug_call(stmt, self);
+                        self.observer = Some(observer);
+                        if resume {
+                            return Ok(ControlFlow::Normal(Value::Null));
+                        }
+                    }
                     let env_json = self.serialize_environment_for_debug();
                     let mut stack_trace = self.get_stack_trace();
                     let file = self
@@ -58,18 +164,37 @@ impl Interpreter {
             }
 
             StmtKind::Let {
-                name, initializer, ..
+                name,
+                initializer,
+                slot,
+                ..
             } => {
                 let value = if let Some(init) = initializer {
                     self.evaluate(init)?
                 } else {
                     Value::Null
                 };
-                // define_or_update avoids a String allocation per execution when
-                // the slot already exists — matters when `let` runs in a lambda
-                // body that's invoked repeatedly through a reused environment
-                // (array_map/filter/each iteration).
-                self.environment.borrow_mut().define_or_update(name, value);
+                // By name, define_or_update avoids a String allocation per
+                // execution when the binding already exists — matters when
+                // `let` runs in a lambda body that's invoked repeatedly through
+                // a reused environment (array_map/filter/each iteration).
+                self.environment
+                    .borrow_mut()
+                    .define_resolved(name, slot, value);
+                Ok(ControlFlow::Normal(Value::Null))
+            }
+
+            StmtKind::LetTuple {
+                names, initializer, ..
+            } => {
+                let value = self.evaluate(initializer)?;
+                let items = value
+                    .destructure(names.len())
+                    .map_err(|e| RuntimeError::type_error(e, initializer.span))?;
+                let mut env = self.environment.borrow_mut();
+                for (name, item) in names.iter().zip(items) {
+                    env.define_or_update(name, item);
+                }
                 Ok(ControlFlow::Normal(Value::Null))
             }
 
@@ -148,14 +273,26 @@ impl Interpreter {
             StmtKind::For {
                 variable,
                 index_variable,
+                destructure,
                 iterable,
                 body,
-            } => self.execute_for_loop(variable, index_variable.as_deref(), iterable, body),
+            } => self.execute_for_loop(
+                variable,
+                index_variable.as_deref(),
+                destructure.as_deref(),
+                iterable,
+                body,
+            ),
 
             StmtKind::Break => Ok(ControlFlow::Break),
 
             StmtKind::Return(value) => {
                 let return_value = if let Some(expr) = value {
+                    if self.defer_tail_call(stmt, expr)? {
+                        // `invoke_function` reruns the body with the parked
+                        // arguments; the value is never seen.
+                        return Ok(ControlFlow::Return(Value::Null));
+                    }
                     self.evaluate(expr)?
                 } else {
                     Value::Null
@@ -168,7 +305,9 @@ impl Interpreter {
                     .current_source_path
                     .as_ref()
                     .map(|p| p.to_string_lossy().to_string());
-                let func = Function::from_decl(decl, self.environment.clone(), source_path);
+                crate::interpreter::cycles::track_scope(&self.environment);
+                let mut func = Function::from_decl(decl, self.environment.clone(), source_path);
+                func.decorators = self.evaluate_decorators(&decl.decorators)?;
                 self.environment
                     .borrow_mut()
                     .define(decl.name.clone(), Value::Function(Rc::new(func)));
@@ -186,10 +325,26 @@ impl Interpreter {
                 Ok(ControlFlow::Normal(Value::Null))
             }
 
+            StmtKind::Struct(decl) => {
+                self.execute_struct(decl)?;
+                Ok(ControlFlow::Normal(Value::Null))
+            }
+
             StmtKind::Interface(_) => {
                 // Interfaces are handled at type-check time, no runtime effect
                 Ok(ControlFlow::Normal(Value::Null))
             }
+            StmtKind::TypeAlias(decl) => {
+                // Only annotations checked at runtime (return types) see it
+                if !crate::interpreter::value::define_type_alias(&decl.name, &decl.type_annotation)
+                {
+                    return Err(RuntimeError::General {
+                        message: format!("type alias '{}' refers to itself", decl.name),
+                        span: decl.span,
+                    });
+                }
+                Ok(ControlFlow::Normal(Value::Null))
+            }
 
             StmtKind::Import(import_decl) => {
                 // Module imports are resolved before execution by the ModuleResolver
@@ -211,9 +366,21 @@ impl Interpreter {
 
             StmtKind::Throw(value) => {
                 let error_value = self.evaluate(value)?;
+                if let Some(observer) = self.observer.as_mut() {
+                    observer.on_throw(&error_value, stmt.span);
+                }
                 Ok(ControlFlow::Throw(error_value))
             }
 
+            // Generators step through their yielding statements themselves
+            // (see `generators`); reaching one here means it's somewhere a
+            // generator can't pause.
+            StmtKind::Yield(_) => Err(RuntimeError::General {
+                message: "yield is only allowed in a generator function's body, `if`s and loops"
+                    .to_string(),
+                span: stmt.span,
+            }),
+
             StmtKind::Try {
                 try_block,
                 catch_clauses,
@@ -238,10 +405,8 @@ impl Interpreter {
                         }
                         ControlFlow::Throw(error) => Some(error),
                     },
-                    Err(e) => {
-                        let error_value = Value::String(format!("{}", e).into());
-                        Some(error_value)
-                    }
+                    Err(e) if e.is_budget_exceeded() => return Err(e),
+                    Err(e) => Some(self.caught_value(&e)),
                 };
 
                 if let Some(error) = throw_value {
@@ -342,53 +507,55 @@ impl Interpreter {
         false
     }
 
+    /// Record a coverage hit for `stmt`'s line, if coverage is on.
+    pub(super) fn record_line_hit(&self, stmt: &Stmt) {
+        let source_path = stmt
+            .source_path
+            .as_deref()
+            .or(self.current_source_path.as_deref());
+        if let Some(path) = source_path {
+            if crate::source_map::is_synthetic(path) {
+                return;
+            }
+            if let Some(ref tracker) = self.coverage_tracker {
+                if let Ok(guard) = tracker.lock() {
+                    guard.record_line_hit(path, stmt.span.line_usize());
+                }
+            } else if let Some(global) = crate::coverage::get_global_coverage_tracker() {
+                if let Ok(guard) = global.lock() {
+                    guard.record_line_hit(path, stmt.span.line_usize());
+                }
+            }
+        }
+    }
+
     fn execute_for_loop(
         &mut self,
         variable: &str,
         index_variable: Option<&str>,
+        destructure: Option<&[String]>,
         iterable: &Expr,
         body: &Stmt,
     ) -> RuntimeResult<ControlFlow> {
-        // Range fast path: `for i in a..b` iterates directly instead of
-        // materializing the range into an array first (eval_range collects
-        // the whole range into a Vec, which is wasteful for large ranges).
-        if let ExprKind::Binary {
-            left,
-            operator: BinaryOp::Range,
-            right,
-        } = &iterable.kind
-        {
-            let left_val = self.evaluate(left)?;
-            let right_val = self.evaluate(right)?;
-            match (&left_val, &right_val) {
-                (Value::Int(start), Value::Int(end)) => {
-                    let mut current = *start;
-                    let end = *end;
-                    return self.run_for_loop(variable, index_variable, body, || {
-                        if current < end {
-                            let val = Value::Int(current);
-                            current += 1;
-                            Some(val)
-                        } else {
-                            None
-                        }
-                    });
-                }
-                _ => {
-                    // Same error eval_range would produce for non-int operands.
-                    return Err(RuntimeError::type_error(
-                        format!(
-                            "range (..) expects two integers, got {} and {}",
-                            left_val.type_name(),
-                            right_val.type_name()
-                        ),
-                        iterable.span,
-                    ));
-                }
-            }
-        }
-
+        let binding = LoopBinding {
+            variable,
+            index_variable,
+            destructure,
+            span: iterable.span,
+        };
         let iter_value = self.evaluate(iterable)?;
+        let cursor = self.loop_cursor(iter_value, destructure.is_some(), iterable.span)?;
+        self.run_for_loop(&binding, body, cursor)
+    }
+
+    /// Turn a `for` loop's iterable into a cursor over its items.
+    /// `entries` is set for `for (k, v) in ...`, which is how hashes opt in.
+    pub(super) fn loop_cursor(
+        &self,
+        iter_value: Value,
+        entries: bool,
+        span: Span,
+    ) -> RuntimeResult<LoopCursor> {
         // QueryBuilder is iterable: materialize it into an Array up front.
         let iter_value = match iter_value {
             Value::QueryBuilder(qb) => {
@@ -397,24 +564,11 @@ impl Interpreter {
             other => other,
         };
         match iter_value {
-            Value::Array(arr) => {
-                // Live bounds-checked indexing: no upfront snapshot clone of
-                // the whole array. The borrow is taken only long enough to
-                // clone the current item, then released before the body runs,
-                // so a body that mutates the array is safe (and observed
-                // live, matching the VM's iterator).
-                let mut i = 0usize;
-                self.run_for_loop(variable, index_variable, body, || {
-                    let items = arr.borrow();
-                    if i < items.len() {
-                        let item = items[i].clone();
-                        i += 1;
-                        Some(item)
-                    } else {
-                        None
-                    }
-                })
-            }
+            Value::Array(arr) => Ok(LoopCursor::Array(arr, 0)),
+            Value::Tuple(items) => Ok(LoopCursor::Tuple(items, 0)),
+            Value::Range(range) => Ok(LoopCursor::Range(range.iter())),
+            Value::Hash(hash) if entries => Ok(LoopCursor::HashEntries(hash, 0)),
+            Value::Iterator(iter) => Ok(LoopCursor::Iterator(iter)),
             _ => {
                 // Include the offending value in the message so the user can
                 // see *which* string (or scalar) snuck into the iterable slot.
@@ -439,12 +593,46 @@ impl Interpreter {
                     Value::Bool(b) => format!("cannot iterate over bool {}", b),
                     other => format!("cannot iterate over {}", other.type_name()),
                 };
-                Err(RuntimeError::type_error(message, iterable.span))
+                Err(RuntimeError::type_error(message, span))
             }
         }
     }
 
-    /// Drive a for-loop body over the values produced by `next`.
+    /// Advance a loop cursor. Array and hash borrows are held only long
+    /// enough to clone the current item, then released before the body runs.
+    pub(super) fn loop_next(
+        &mut self,
+        cursor: &mut LoopCursor,
+        span: Span,
+    ) -> RuntimeResult<Option<Value>> {
+        Ok(match cursor {
+            LoopCursor::Array(arr, i) => {
+                let item = arr.borrow().get(*i).cloned();
+                *i += 1;
+                item
+            }
+            LoopCursor::Tuple(items, i) => {
+                let item = items.get(*i).cloned();
+                *i += 1;
+                item
+            }
+            LoopCursor::Range(range) => range.next().map(Value::Int),
+            LoopCursor::HashEntries(hash, i) => {
+                let entry = hash
+                    .borrow()
+                    .get_index(*i)
+                    .map(|(k, v)| Value::Tuple(Rc::new(vec![k.to_value(), v.clone()])));
+                *i += 1;
+                entry
+            }
+            LoopCursor::Iterator(iter) => {
+                let iter = iter.clone();
+                self.iterator_next(&iter, span)?
+            }
+        })
+    }
+
+    /// Drive a for-loop body over the items of `cursor`.
     ///
     /// Handles both execution strategies:
     /// - closure-capturing bodies get a fresh environment per iteration so
@@ -453,18 +641,24 @@ impl Interpreter {
     ///   same-slot writes instead of fresh HashMap allocations).
     fn run_for_loop(
         &mut self,
-        variable: &str,
-        index_variable: Option<&str>,
+        binding: &LoopBinding,
         body: &Stmt,
-        mut next: impl FnMut() -> Option<Value>,
+        mut cursor: LoopCursor,
     ) -> RuntimeResult<ControlFlow> {
+        let LoopBinding {
+            variable,
+            index_variable,
+            ..
+        } = *binding;
+        let entries = cursor.yields_entries();
         if super::loop_capture::stmt_creates_closures(body) {
             let outer = self.environment.clone();
             let mut i: i64 = 0;
-            while let Some(item) = next() {
+            while let Some(item) = self.loop_next(&mut cursor, binding.span)? {
                 let iter_env = Rc::new(RefCell::new(Environment::with_enclosing(outer.clone())));
                 {
                     let mut env = iter_env.borrow_mut();
+                    binding.bind_destructured(&mut env, &item, i, entries)?;
                     env.define(variable.to_string(), item);
                     if let Some(idx_var) = index_variable {
                         env.define(idx_var.to_string(), Value::Int(i));
@@ -500,9 +694,22 @@ impl Interpreter {
         let prev_env = std::mem::replace(&mut self.environment, loop_env_rc.clone());
 
         let mut i: i64 = 0;
-        while let Some(item) = next() {
+        loop {
+            let item = match self.loop_next(&mut cursor, binding.span) {
+                Ok(Some(item)) => item,
+                Ok(None) => break,
+                Err(e) => {
+                    self.environment = prev_env;
+                    return Err(e);
+                }
+            };
             {
                 let mut env = loop_env_rc.borrow_mut();
+                if let Err(e) = binding.bind_destructured(&mut env, &item, i, entries) {
+                    drop(env);
+                    self.environment = prev_env;
+                    return Err(e);
+                }
                 env.define_or_update(variable, item);
                 if let Some(idx_var) = index_variable {
                     env.define_or_update(idx_var, Value::Int(i));
@@ -532,6 +739,65 @@ impl Interpreter {
         Ok(ControlFlow::Normal(Value::Null))
     }
 
+    /// Evaluate a declaration's decorator arguments: positional ones (and
+    /// spreads) into `args`, named ones into `options`.
+    fn evaluate_decorators(&mut self, decorators: &[Decorator]) -> RuntimeResult<Decorators> {
+        if decorators.is_empty() {
+            return Ok(None);
+        }
+        let mut values = Vec::with_capacity(decorators.len());
+        for decorator in decorators {
+            let mut args = Vec::new();
+            let mut options = Vec::new();
+            for argument in &decorator.arguments {
+                match argument {
+                    Argument::Positional(expr) => args.push(self.evaluate(expr)?),
+                    Argument::Spread(expr) => args.extend(self.evaluate_spread(expr)?),
+                    Argument::Named(named) => {
+                        options.push((named.name.clone(), self.evaluate(&named.value)?))
+                    }
+                    Argument::Block(_) => {
+                        return Err(RuntimeError::type_error(
+                            format!("@{} does not take a block", decorator.name),
+                            decorator.span,
+                        ))
+                    }
+                }
+            }
+            values.push(DecoratorValue {
+                name: decorator.name.clone(),
+                args,
+                options,
+            });
+        }
+        Ok(Some(Rc::new(values)))
+    }
+
+    /// Define a struct type. Field defaults are evaluated here, once; each
+    /// value that omits a field gets its own copy of the default.
+    fn execute_struct(&mut self, decl: &StructDecl) -> RuntimeResult<()> {
+        let mut fields = Vec::with_capacity(decl.fields.len());
+        for field in &decl.fields {
+            let default = match &field.default_value {
+                Some(expr) => Some(self.evaluate(expr)?),
+                None => None,
+            };
+            fields.push(StructField {
+                name: field.name.clone(),
+                default,
+            });
+        }
+        let class = Class {
+            name: decl.name.clone(),
+            struct_fields: Some(Rc::new(fields)),
+            ..Default::default()
+        };
+        self.environment
+            .borrow_mut()
+            .define(decl.name.clone(), Value::Class(Rc::new(class)));
+        Ok(())
+    }
+
     pub(super) fn execute_class(&mut self, decl: &ClassDecl) -> RuntimeResult<()> {
         let superclass = if let Some(ref superclass_name) = decl.superclass {
             match self.environment.borrow().get(superclass_name) {
@@ -572,8 +838,11 @@ impl Interpreter {
             .as_ref()
             .map(|p| p.to_string_lossy().to_string());
 
+        let decorators = self.evaluate_decorators(&decl.decorators)?;
         for method_decl in &decl.methods {
-            let func = Function::from_method(method_decl, method_env.clone(), source_path.clone());
+            let mut func =
+                Function::from_method(method_decl, method_env.clone(), source_path.clone());
+            func.decorators = self.evaluate_decorators(&method_decl.decorators)?;
             if method_decl.is_static {
                 static_methods.insert(method_decl.name.clone(), Rc::new(func));
             } else {
@@ -586,9 +855,11 @@ impl Interpreter {
             Rc::new(Function {
                 name: "new".to_string(),
                 params: ctor.params.clone().into(),
-                body: ctor.body.clone().into(),
+                body: Arc::clone(&ctor.body),
                 closure: method_env.clone(),
                 is_method: true,
+                is_generator: false,
+                is_async: false,
                 span: Some(ctor.span),
                 source_path: self
                     .current_source_path
@@ -596,6 +867,7 @@ impl Interpreter {
                     .map(|p| p.to_string_lossy().to_string()),
                 defining_superclass: None,
                 return_type: None,
+                decorators: None,
                 cached_env: RefCell::new(None),
                 jit_cache: RefCell::new(None),
             })
@@ -613,12 +885,10 @@ impl Interpreter {
         };
 
         let mut fields = HashMap::new();
-        let mut static_field_initializers = HashMap::new();
         let mut const_fields = HashSet::new();
         let mut static_const_fields = HashSet::new();
         for field in &decl.fields {
             if field.is_static {
-                static_field_initializers.insert(field.name.clone(), field.initializer.clone());
                 if field.is_const {
                     static_const_fields.insert(field.name.clone());
                 }
@@ -633,6 +903,7 @@ impl Interpreter {
         let class = Class {
             name: decl.name.clone(),
             superclass,
+            interfaces: decl.interfaces.clone(),
             methods: Rc::new(RefCell::new(methods)),
             static_methods,
             native_static_methods,
@@ -643,6 +914,7 @@ impl Interpreter {
             nested_classes: Rc::new(RefCell::new(HashMap::new())),
             const_fields,
             static_const_fields,
+            decorators,
             ..Default::default()
         };
 
@@ -672,17 +944,16 @@ impl Interpreter {
             register_model_class(&class_rc.name, class_rc.clone());
         }
 
-        // Initialize static fields
-        for (field_name, field_initializer) in static_field_initializers {
-            let value = if let Some(init_expr) = field_initializer {
-                self.evaluate(&init_expr)?
-            } else {
-                Value::Null
+        // Initialize static fields, in declaration order.
+        for field in decl.fields.iter().filter(|field| field.is_static) {
+            let value = match &field.initializer {
+                Some(init_expr) => self.evaluate(init_expr)?,
+                None => Value::Null,
             };
             class_rc
                 .static_fields
                 .borrow_mut()
-                .insert(field_name, value);
+                .insert(field.name.clone(), value);
         }
 
         // Execute static block if present
@@ -717,7 +988,7 @@ impl Interpreter {
                         // `event`/`transition`/`guard` calls run), so it can't be a
                         // plain native. Route it to `define_state_machine` and skip
                         // the generic class-statement dispatch below.
-                        if let crate::ast::ExprKind::Variable(fname) = &callee.kind {
+                        if let crate::ast::ExprKind::Variable(fname, _) = &callee.kind {
                             if fname == "state_machine" {
                                 let block = arguments.iter().find_map(|a| match a {
                                     Argument::Block(e) => Some(e),
@@ -769,6 +1040,9 @@ impl Interpreter {
                                     Argument::Positional(expr) => {
                                         args.push(self.evaluate(expr)?);
                                     }
+                                    Argument::Spread(expr) => {
+                                        args.extend(self.evaluate_spread(expr)?);
+                                    }
                                     Argument::Named(named) => {
                                         let val = self.evaluate(&named.value)?;
                                         hash_pairs.insert(
@@ -791,6 +1065,9 @@ impl Interpreter {
                                     Argument::Positional(expr) => {
                                         args.push(self.evaluate(expr)?);
                                     }
+                                    Argument::Spread(expr) => {
+                                        args.extend(self.evaluate_spread(expr)?);
+                                    }
                                     Argument::Named(_) => {
                                         return Err(RuntimeError::type_error(
                                             "model validation does not support named arguments",
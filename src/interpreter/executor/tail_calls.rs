@@ -0,0 +1,135 @@
+//! Self-recursive tail calls.
+//!
+//! `return f(...)` inside `f` doesn't recurse: the `return` evaluates the
+//! arguments, parks them in `Interpreter::tail_call` and unwinds to
+//! `invoke_function`, which rebinds the parameters and runs the body again
+//! in the same call-stack frame. An accumulator-style loop written as
+//! recursion then runs in constant Rust stack, however deep it goes.
+//!
+//! A function's last statement, when it is an expression, is its implicit
+//! return and qualifies the same way.
+//!
+//! Only a `return` that belongs to the running call qualifies: the statement
+//! must sit in the function's own body (not in a lambda or nested function
+//! written inside it, and not in a `try`, whose handler would be gone by the
+//! time the callee ran), and the current scope must descend from the call's
+//! environment (not from a second, non-recursive run of the same body).
+
+use std::cell::RefCell;
+
+use crate::ast::expr::Argument;
+use crate::ast::{Expr, ExprKind, Stmt, StmtKind};
+use crate::interpreter::environment::Environment;
+use crate::interpreter::value::{Function, Value};
+
+use super::{Interpreter, RuntimeResult};
+
+/// The call `invoke_function` is currently running, compared by address.
+#[derive(Clone, Copy)]
+pub(crate) struct TailTarget {
+    func: *const Function,
+    env: *const RefCell<Environment>,
+    /// The body's last statement — an implicit return when an expression.
+    last: *const Stmt,
+}
+
+impl TailTarget {
+    pub(crate) fn new(func: &Function, env: &std::rc::Rc<RefCell<Environment>>) -> Self {
+        Self {
+            func,
+            env: std::rc::Rc::as_ptr(env),
+            last: func
+                .body
+                .last()
+                .map_or(std::ptr::null(), |s| s as *const Stmt),
+        }
+    }
+
+    /// Whether `stmt` is the running body's implicit return.
+    pub(crate) fn ends_body(&self, stmt: &Stmt) -> bool {
+        std::ptr::eq(stmt, self.last)
+    }
+}
+
+/// Whether `target` is one of `body`'s statements, looking through blocks,
+/// branches and loops but not into `try` or nested declarations.
+fn in_tail_body(body: &[Stmt], target: &Stmt) -> bool {
+    body.iter().any(|stmt| stmt_contains(stmt, target))
+}
+
+fn stmt_contains(stmt: &Stmt, target: &Stmt) -> bool {
+    if std::ptr::eq(stmt, target) {
+        return true;
+    }
+    match &stmt.kind {
+        StmtKind::Block(stmts) => in_tail_body(stmts, target),
+        StmtKind::If {
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            stmt_contains(then_branch, target)
+                || else_branch
+                    .as_ref()
+                    .is_some_and(|e| stmt_contains(e, target))
+        }
+        StmtKind::While { body, .. } | StmtKind::For { body, .. } => stmt_contains(body, target),
+        _ => false,
+    }
+}
+
+impl Interpreter {
+    /// If `return expr` (statement `stmt`, or the implicit return) is a tail
+    /// call of the running function, evaluate its arguments into
+    /// `self.tail_call` and return true; the caller then unwinds with a
+    /// placeholder `Return`.
+    pub(crate) fn defer_tail_call(&mut self, stmt: &Stmt, expr: &Expr) -> RuntimeResult<bool> {
+        let Some(target) = self.tail_target else {
+            return Ok(false);
+        };
+        let ExprKind::Call { callee, arguments } = &expr.kind else {
+            return Ok(false);
+        };
+        let ExprKind::Variable(name, _) = &callee.kind else {
+            return Ok(false);
+        };
+        if !arguments
+            .iter()
+            .all(|a| matches!(a, Argument::Positional(_)))
+        {
+            return Ok(false);
+        }
+        let func = match self.environment.borrow().get(name) {
+            Some(Value::Function(func)) if std::ptr::eq(&*func, target.func) => func,
+            _ => return Ok(false),
+        };
+        if func.needs_argument_binding()
+            || arguments.len() < func.arity()
+            || arguments.len() > func.full_arity()
+            || !in_tail_body(&func.body, stmt)
+            || !self.scope_descends_from(target.env)
+        {
+            return Ok(false);
+        }
+
+        let mut values = Vec::with_capacity(arguments.len());
+        for arg in arguments {
+            if let Argument::Positional(expr) = arg {
+                values.push(self.evaluate(expr)?);
+            }
+        }
+        self.tail_call = Some(values);
+        Ok(true)
+    }
+
+    fn scope_descends_from(&self, env: *const RefCell<Environment>) -> bool {
+        let mut scope = Some(self.environment.clone());
+        while let Some(current) = scope {
+            if std::ptr::eq(std::rc::Rc::as_ptr(&current), env) {
+                return true;
+            }
+            scope = current.borrow().enclosing();
+        }
+        false
+    }
+}
@@ -3,6 +3,7 @@
 use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
+use crate::ast::expr::FrameSlot;
 use crate::ast::Expr;
 use crate::error::RuntimeError;
 use crate::interpreter::environment::Environment;
@@ -82,8 +83,13 @@ impl Drop for TemplateLenientVarsGuard {
 
 impl Interpreter {
     /// Evaluate variable access expressions.
-    pub(crate) fn evaluate_variable(&mut self, name: &str, expr: &Expr) -> RuntimeResult<Value> {
-        let looked_up = self.environment.borrow().get(name);
+    pub(crate) fn evaluate_variable(
+        &mut self,
+        name: &str,
+        slot: &FrameSlot,
+        expr: &Expr,
+    ) -> RuntimeResult<Value> {
+        let looked_up = self.environment.borrow().get_resolved(name, slot);
         if let Some(v) = looked_up {
             // Reading a `grouped {}` deferred result forces it: flush the batch
             // (auto-flush) and yield the materialised value. Flush/transform
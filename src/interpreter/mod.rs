@@ -1,21 +1,28 @@
 //! Interpreter module for Solilang.
 
 pub mod builtins;
+pub mod cycles;
+pub mod decorators;
 pub mod environment;
 pub mod executor;
 pub mod hidden_class;
 pub mod inline_cache;
 pub mod jsonp;
+pub mod observer;
+pub mod profiler;
+pub mod resolver;
 pub mod symbol;
 pub mod value;
 pub mod value_json;
 pub mod value_stringify;
+pub mod value_struct;
 
 pub use environment::Environment;
-pub use executor::Interpreter;
+pub use executor::{heap_counters, CountingAlloc, EvalBudget, HeapCounters, Interpreter};
 pub use hidden_class::{
     HiddenClass, HiddenClassObject, HiddenClassRegistry, HIDDEN_CLASS_REGISTRY,
 };
 pub use inline_cache::{HiddenClassId, MethodInlineCache, PropertyInlineCache, INLINE_CACHE};
+pub use observer::InterpreterObserver;
 pub use symbol::{get_symbol, symbol_string, SymbolId};
 pub use value::{unwrap_value, Value};
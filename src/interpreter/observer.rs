@@ -0,0 +1,129 @@
+//! Tracing hooks for tools that watch the interpreter run.
+//!
+//! An [`InterpreterObserver`] set with [`Interpreter::set_observer`] hears
+//! about every Soli function call and return, every statement before it
+//! runs, and every error — runtime errors and `throw`n values alike — so a
+//! debugger, coverage collector, profiler or tracing exporter can be built
+//! outside the interpreter. Every callback has an empty default; implement
+//! only the ones you need. Without an observer the hooks cost one branch.
+//!
+//! [`before_statement`](InterpreterObserver::before_statement) and
+//! [`on_debug_call`](InterpreterObserver::on_debug_call) also hand over the
+//! interpreter, for a debugger to inspect and to pause in. The observer is
+//! detached while they run, so code they evaluate isn't observed.
+//!
+//! [`Interpreter::set_observer`]: crate::interpreter::Interpreter::set_observer
+
+use crate::ast::Stmt;
+use crate::error::RuntimeError;
+use crate::interpreter::executor::StackFrame;
+use crate::interpreter::value::Value;
+use crate::interpreter::Interpreter;
+use crate::span::Span;
+
+/// Callbacks for interpreter events. See the [module docs](self).
+pub trait InterpreterObserver {
+    /// A Soli function, method or lambda was entered. `frame` is the new
+    /// innermost frame of the call stack.
+    fn on_function_enter(&mut self, _frame: &StackFrame) {}
+
+    /// The innermost function returned, or an error unwound out of it.
+    fn on_function_exit(&mut self, _frame: &StackFrame) {}
+
+    /// A statement is about to run.
+    fn on_statement(&mut self, _stmt: &Stmt) {}
+
+    /// Right after [`on_statement`](Self::on_statement), with the
+    /// interpreter: its call stack and environment can be read, and
+    /// expressions evaluated with it, until this returns and the statement
+    /// runs.
+    fn before_statement(&mut self, _stmt: &Stmt, _interpreter: &mut Interpreter) {}
+
+    /// A `debug()` call was reached. Return true to carry on past it, as a
+    /// debugger that paused there does; false keeps its usual behaviour of
+    /// ending the program with a breakpoint error.
+    fn on_debug_call(&mut self, _stmt: &Stmt, _interpreter: &mut Interpreter) -> bool {
+        false
+    }
+
+    /// A runtime error was raised. Reported once, from the statement that
+    /// raised it, not again by each statement it unwinds through.
+    fn on_error(&mut self, _error: &RuntimeError) {}
+
+    /// A `throw` statement threw `value`.
+    fn on_throw(&mut self, _value: &Value, _span: Span) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Recorder(Rc<RefCell<Vec<String>>>);
+
+    impl InterpreterObserver for Recorder {
+        fn on_function_enter(&mut self, frame: &StackFrame) {
+            self.0
+                .borrow_mut()
+                .push(format!("enter {}", frame.function_name));
+        }
+        fn on_function_exit(&mut self, frame: &StackFrame) {
+            self.0
+                .borrow_mut()
+                .push(format!("exit {}", frame.function_name));
+        }
+        fn on_statement(&mut self, stmt: &Stmt) {
+            self.0.borrow_mut().push(format!("line {}", stmt.span.line));
+        }
+        fn on_error(&mut self, error: &RuntimeError) {
+            self.0.borrow_mut().push(format!("error {}", error));
+        }
+        fn on_throw(&mut self, value: &Value, _span: Span) {
+            self.0.borrow_mut().push(format!("throw {}", value));
+        }
+    }
+
+    fn run(source: &str) -> (Vec<String>, bool) {
+        let tokens = crate::lexer::Scanner::new(source).scan_tokens().unwrap();
+        let program = crate::parser::Parser::new(tokens).parse().unwrap();
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut interpreter = Interpreter::new();
+        interpreter.set_observer(Box::new(Recorder(events.clone())));
+        let ok = interpreter.interpret(&program).is_ok();
+        assert!(interpreter.take_observer().is_some());
+        let events = events.borrow().clone();
+        (events, ok)
+    }
+
+    #[test]
+    fn reports_calls_statements_and_throws() {
+        let (events, ok) = run(
+            "fn double(n)\n  return n * 2\nend\nlet x = double(2)\ntry\n  throw \"boom\"\ncatch e\n  x = 0\nend",
+        );
+        assert!(ok);
+        let position = |event: &str| {
+            events
+                .iter()
+                .position(|e| e == event)
+                .unwrap_or_else(|| panic!("no '{}' in {:?}", event, events))
+        };
+        assert!(position("line 4") < position("enter double"));
+        assert!(position("enter double") < position("line 2"));
+        assert!(position("line 2") < position("exit double"));
+        assert!(position("throw boom") < position("line 8"));
+    }
+
+    #[test]
+    fn reports_a_runtime_error_once() {
+        let (events, ok) = run(
+            "fn inner()\n  return missing_thing\nend\nfn outer()\n  return inner()\nend\nouter()",
+        );
+        assert!(!ok);
+        let errors: Vec<_> = events.iter().filter(|e| e.starts_with("error")).collect();
+        assert_eq!(errors.len(), 1, "{:?}", events);
+        assert!(errors[0].contains("missing_thing"));
+        assert!(events.contains(&"exit inner".to_string()));
+    }
+}
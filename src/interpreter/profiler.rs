@@ -0,0 +1,289 @@
+//! Instrumentation profiler behind `soli profile`.
+//!
+//! Every Soli function call the interpreter makes enters and leaves a frame
+//! here (hooked into `push_frame` / `pop_frame`). Time is split into each
+//! function's *self* time — spent in its own body, natives included — and
+//! its *total* time, which adds the Soli calls it made. Self time is also
+//! accumulated per call stack, which is exactly the collapsed-stack format
+//! `flamegraph.pl`, inferno and speedscope read.
+//!
+//! Off by default: the hooks cost one thread-local read until [`start`].
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io;
+use std::time::{Duration, Instant};
+
+/// Name of the frame that holds top-level script time.
+pub const ROOT_FRAME: &str = "<main>";
+
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    static STATE: RefCell<State> = RefCell::new(State::default());
+}
+
+#[derive(Default)]
+struct State {
+    stack: Vec<OpenFrame>,
+    /// Self time per `;`-joined call stack.
+    folded: HashMap<String, Duration>,
+    functions: HashMap<String, FunctionStats>,
+}
+
+struct OpenFrame {
+    name: String,
+    start: Instant,
+    /// Time spent in Soli calls made from this frame.
+    children: Duration,
+}
+
+/// Time spent in one function over the whole run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FunctionStats {
+    pub calls: u64,
+    /// Wall time inside the function, callees included. Recursive calls
+    /// count once, from the outermost one.
+    pub total: Duration,
+    /// Wall time in the function's own body.
+    pub self_time: Duration,
+}
+
+/// The result of a profiled run.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    /// `(stack, self time)`, stacks root-first and `;`-joined.
+    pub stacks: Vec<(String, Duration)>,
+    /// Per-function figures, by descending self time.
+    pub functions: Vec<(String, FunctionStats)>,
+    pub elapsed: Duration,
+}
+
+#[inline]
+pub fn is_enabled() -> bool {
+    ENABLED.with(Cell::get)
+}
+
+/// Begin profiling on this thread, discarding any earlier profile.
+pub fn start() {
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        *state = State::default();
+        state.stack.push(OpenFrame {
+            name: ROOT_FRAME.to_string(),
+            start: Instant::now(),
+            children: Duration::ZERO,
+        });
+    });
+    ENABLED.with(|enabled| enabled.set(true));
+}
+
+/// Stop profiling and return what was recorded since [`start`]. Frames
+/// still open (an error unwound past them) are closed now.
+pub fn finish() -> Profile {
+    if !is_enabled() {
+        return Profile::default();
+    }
+    let now = Instant::now();
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        let elapsed = state
+            .stack
+            .first()
+            .map(|root| now - root.start)
+            .unwrap_or_default();
+        while !state.stack.is_empty() {
+            state.close(now);
+        }
+        let state = std::mem::take(&mut *state);
+        let mut stacks: Vec<_> = state.folded.into_iter().collect();
+        stacks.sort();
+        let mut functions: Vec<_> = state.functions.into_iter().collect();
+        functions.sort_by(|a, b| b.1.self_time.cmp(&a.1.self_time).then(a.0.cmp(&b.0)));
+        ENABLED.with(|enabled| enabled.set(false));
+        Profile {
+            stacks,
+            functions,
+            elapsed,
+        }
+    })
+}
+
+/// A Soli function call began.
+pub fn enter(name: &str) {
+    if !is_enabled() {
+        return;
+    }
+    // Collapsed stacks split frames on `;` and the count on the last space.
+    let name = if name.is_empty() {
+        "<anonymous>".to_string()
+    } else {
+        name.replace([';', ' '], "_")
+    };
+    STATE.with(|state| {
+        state.borrow_mut().stack.push(OpenFrame {
+            name,
+            start: Instant::now(),
+            children: Duration::ZERO,
+        })
+    });
+}
+
+/// Prefix the innermost frame with its receiver's class, so `index` reads
+/// `PostsController.index` and methods of different classes stay apart.
+pub fn qualify(owner: &str) {
+    if !is_enabled() {
+        return;
+    }
+    STATE.with(|state| {
+        if let Some(frame) = state.borrow_mut().stack.last_mut() {
+            frame.name = format!("{}.{}", owner, frame.name);
+        }
+    });
+}
+
+/// The innermost Soli function call returned.
+pub fn exit() {
+    if !is_enabled() {
+        return;
+    }
+    STATE.with(|state| {
+        let mut state = state.borrow_mut();
+        // Never close the root before `finish`.
+        if state.stack.len() > 1 {
+            state.close(Instant::now());
+        }
+    });
+}
+
+impl State {
+    fn close(&mut self, now: Instant) {
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+        let total = now - frame.start;
+        let self_time = total.saturating_sub(frame.children);
+        if let Some(parent) = self.stack.last_mut() {
+            parent.children += total;
+        }
+
+        let mut key = String::new();
+        for open in &self.stack {
+            key.push_str(&open.name);
+            key.push(';');
+        }
+        key.push_str(&frame.name);
+        *self.folded.entry(key).or_default() += self_time;
+
+        let recursive = self.stack.iter().any(|open| open.name == frame.name);
+        let stats = self.functions.entry(frame.name).or_default();
+        stats.calls += 1;
+        stats.self_time += self_time;
+        if !recursive {
+            stats.total += total;
+        }
+    }
+}
+
+impl Profile {
+    /// Write the collapsed stacks, one `stack microseconds` line each.
+    /// Stacks that rounded to zero microseconds are left out.
+    pub fn write_folded(&self, out: &mut impl io::Write) -> io::Result<()> {
+        for (stack, time) in &self.stacks {
+            let micros = time.as_micros();
+            if micros > 0 {
+                writeln!(out, "{} {}", stack, micros)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// A table of the `top` functions by self time.
+    pub fn summary(&self, top: usize) -> String {
+        let elapsed = self.elapsed.as_secs_f64().max(f64::EPSILON);
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{:>7}  {:>10}  {:>10}  {:>9}  function",
+            "self%", "self", "total", "calls"
+        );
+        for (name, stats) in self.functions.iter().take(top) {
+            let _ = writeln!(
+                out,
+                "{:>6.1}%  {:>10}  {:>10}  {:>9}  {}",
+                stats.self_time.as_secs_f64() * 100.0 / elapsed,
+                format_duration(stats.self_time),
+                format_duration(stats.total),
+                stats.calls,
+                name
+            );
+        }
+        if self.functions.len() > top {
+            let _ = writeln!(out, "  ... {} more", self.functions.len() - top);
+        }
+        let _ = writeln!(out, "total {}", format_duration(self.elapsed));
+        out
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_micros();
+    if micros >= 1_000_000 {
+        format!("{:.2}s", duration.as_secs_f64())
+    } else if micros >= 1_000 {
+        format!("{:.2}ms", micros as f64 / 1_000.0)
+    } else {
+        format!("{}µs", micros)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spin(duration: Duration) {
+        let start = Instant::now();
+        while start.elapsed() < duration {}
+    }
+
+    #[test]
+    fn self_time_excludes_callees_and_recursion_counts_once() {
+        start();
+        enter("outer");
+        qualify("Report");
+        spin(Duration::from_millis(2));
+        enter("fib");
+        enter("fib");
+        spin(Duration::from_millis(2));
+        exit();
+        exit();
+        exit();
+        let profile = finish();
+
+        assert!(!is_enabled());
+        let stats: HashMap<_, _> = profile.functions.iter().cloned().collect();
+        let outer = &stats["Report.outer"];
+        let fib = &stats["fib"];
+        assert_eq!(outer.calls, 1);
+        assert_eq!(fib.calls, 2);
+        assert!(outer.total >= outer.self_time + fib.total);
+        assert!(fib.total <= outer.total);
+        let stacks: Vec<_> = profile.stacks.iter().map(|(s, _)| s.as_str()).collect();
+        assert!(stacks.contains(&"<main>;Report.outer;fib;fib"));
+
+        let mut folded = Vec::new();
+        profile.write_folded(&mut folded).unwrap();
+        let folded = String::from_utf8(folded).unwrap();
+        assert!(folded
+            .lines()
+            .any(|line| line.starts_with("<main>;Report.outer;fib;fib ")));
+        assert!(profile.summary(10).contains("Report.outer"));
+    }
+
+    #[test]
+    fn hooks_do_nothing_until_started() {
+        enter("ignored");
+        exit();
+        assert!(finish().functions.is_empty());
+    }
+}
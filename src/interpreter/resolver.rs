@@ -0,0 +1,578 @@
+//! Resolves a function body's locals to frame slots.
+//!
+//! The parameters and the `let`s written directly in a function or method
+//! body get one slot each in the call's [`Environment`]. Every variable read
+//! or assigned in the body that names one of them, and every such `let`, is
+//! tied to its slot in the AST node itself ([`FrameSlot`]), so running it
+//! walks the scope chain to the nearest frame and indexes it without hashing
+//! the name. The layout is worked out once per [`Body`], on the first call,
+//! and every function value made from that body shares it.
+//!
+//! A name keeps its by-name binding when anything inside the body could bind
+//! it again in an inner scope: a nested `let` or `const`, a loop or `catch`
+//! variable, a match pattern, a comprehension variable, or a nested
+//! declaration. Names a top-level `let (a, b) = ...` binds stay by name too.
+//! A body with an `import` isn't resolved at all, since what it brings in is
+//! only known at run time. Lambda and nested function bodies are resolved on
+//! their own. Template data-hash scopes and the REPL's top-level scope have
+//! no layout and look every name up by name.
+//!
+//! A node records the id of the layout it was resolved against, and a frame
+//! only answers for nodes of its own layout: a builtin that runs a block in a
+//! plain scope (`map`, `each`, ...) leaves the block's nodes to by-name
+//! lookup instead of reading some enclosing frame's slots.
+//!
+//! [`Environment`]: crate::interpreter::environment::Environment
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use ahash::{AHashMap, AHashSet};
+
+use crate::ast::expr::{
+    Argument, Expr, ExprKind, FrameSlot, HashComprehension, InterpolatedPart, MatchPattern,
+};
+use crate::ast::stmt::{Body, Parameter, Stmt, StmtKind};
+
+/// The id the next layout gets. 0 marks an unresolved [`FrameSlot`].
+static NEXT_LAYOUT_ID: AtomicU32 = AtomicU32::new(1);
+
+/// The slots of a function's call frame.
+#[derive(Debug)]
+pub struct FrameLayout {
+    /// What the nodes tied to this layout record.
+    id: u32,
+    /// Slot → name.
+    names: Vec<String>,
+    /// Name → slot, for by-name lookups (closures, `defined()`, the
+    /// debugger).
+    slots: AHashMap<String, usize>,
+    /// Parameter index → its slot, if it has one.
+    params: Vec<Option<usize>>,
+}
+
+impl Body {
+    /// The call-frame layout of this body for a function with `params`,
+    /// resolved on first use.
+    pub fn frame_layout(&self, params: &[Parameter]) -> Option<Arc<FrameLayout>> {
+        self.layout
+            .get_or_init(|| FrameLayout::resolve(params, self))
+            .clone()
+    }
+}
+
+impl FrameLayout {
+    /// Lay out the frame of a function with these parameters and body, and
+    /// tie the body's nodes to the slots. `None` when nothing in it can be
+    /// resolved.
+    fn resolve(params: &[Parameter], body: &[Stmt]) -> Option<Arc<Self>> {
+        let mut scan = Scan::default();
+        for stmt in body {
+            match &stmt.kind {
+                StmtKind::Let {
+                    name,
+                    initializer,
+                    slot,
+                    ..
+                } => {
+                    scan.top.push((slot, name));
+                    if let Some(init) = initializer {
+                        scan.expr(init);
+                    }
+                }
+                _ => scan.stmt(stmt),
+            }
+        }
+        if scan.dynamic {
+            return None;
+        }
+
+        let mut names: Vec<String> = Vec::new();
+        let mut slots = AHashMap::new();
+        let declared = params
+            .iter()
+            .map(|p| p.name.as_str())
+            .chain(scan.top.iter().map(|&(_, name)| name));
+        for name in declared {
+            if scan.inner.contains(name) || slots.contains_key(name) {
+                continue;
+            }
+            slots.insert(name.to_string(), names.len());
+            names.push(name.to_string());
+        }
+        if names.is_empty() {
+            return None;
+        }
+
+        let id = NEXT_LAYOUT_ID.fetch_add(1, Ordering::Relaxed);
+        for (node, name) in scan.top.into_iter().chain(scan.reads) {
+            if let Some(&slot) = slots.get(name) {
+                node.set(id, slot);
+            }
+        }
+        let params = params.iter().map(|p| slots.get(&p.name).copied()).collect();
+        Some(Arc::new(Self {
+            id,
+            names,
+            slots,
+            params,
+        }))
+    }
+
+    /// The number of slots a frame with this layout has.
+    pub fn slot_count(&self) -> usize {
+        self.names.len()
+    }
+
+    /// The name each slot holds, in slot order.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// The slot holding `name`, if it has one.
+    #[inline]
+    pub fn slot(&self, name: &str) -> Option<usize> {
+        self.slots.get(name).copied()
+    }
+
+    /// The slot holding the parameter at `index`, if it has one.
+    #[inline]
+    pub fn param_slot(&self, index: usize) -> Option<usize> {
+        self.params.get(index).copied().flatten()
+    }
+
+    /// The slot `node` was tied to, if it was tied to this layout.
+    #[inline]
+    pub fn slot_of(&self, node: &FrameSlot) -> Option<usize> {
+        node.get()
+            .and_then(|(id, slot)| (id == self.id).then_some(slot))
+    }
+}
+
+/// What a walk over a body collected.
+#[derive(Default)]
+struct Scan<'a> {
+    /// The body's own top-level `let`s.
+    top: Vec<(&'a FrameSlot, &'a str)>,
+    /// Names something in an inner scope may bind.
+    inner: AHashSet<&'a str>,
+    /// Every variable read or assigned.
+    reads: Vec<(&'a FrameSlot, &'a str)>,
+    /// The body defines names that can't be known before it runs.
+    dynamic: bool,
+}
+
+impl<'a> Scan<'a> {
+    fn block(&mut self, stmts: &'a [Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &'a Stmt) {
+        match &stmt.kind {
+            StmtKind::Expression(e) | StmtKind::Throw(e) | StmtKind::Yield(e) => self.expr(e),
+            StmtKind::Let {
+                name, initializer, ..
+            } => {
+                self.inner.insert(name);
+                if let Some(init) = initializer {
+                    self.expr(init);
+                }
+            }
+            StmtKind::LetTuple {
+                names, initializer, ..
+            } => {
+                self.inner.extend(names.iter().map(String::as_str));
+                self.expr(initializer);
+            }
+            StmtKind::Const {
+                name, initializer, ..
+            } => {
+                self.inner.insert(name);
+                self.expr(initializer);
+            }
+            StmtKind::Block(stmts) => self.block(stmts),
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expr(condition);
+                self.stmt(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.stmt(else_branch);
+                }
+            }
+            StmtKind::While { condition, body } => {
+                self.expr(condition);
+                self.stmt(body);
+            }
+            StmtKind::For {
+                variable,
+                index_variable,
+                destructure,
+                iterable,
+                body,
+            } => {
+                self.inner.insert(variable);
+                if let Some(index) = index_variable {
+                    self.inner.insert(index);
+                }
+                if let Some(names) = destructure {
+                    self.inner.extend(names.iter().map(String::as_str));
+                }
+                self.expr(iterable);
+                self.stmt(body);
+            }
+            StmtKind::Return(e) => {
+                if let Some(e) = e {
+                    self.expr(e);
+                }
+            }
+            StmtKind::Try {
+                try_block,
+                catch_clauses,
+                finally_block,
+            } => {
+                self.stmt(try_block);
+                for clause in catch_clauses {
+                    if let Some(var) = &clause.var_name {
+                        self.inner.insert(var);
+                    }
+                    self.stmt(&clause.body);
+                }
+                if let Some(finally_block) = finally_block {
+                    self.stmt(finally_block);
+                }
+            }
+            // Declarations bind their name where they run; their bodies are
+            // resolved (or not) on their own.
+            StmtKind::Function(decl) => {
+                self.inner.insert(&decl.name);
+            }
+            StmtKind::Class(decl) => {
+                self.inner.insert(&decl.name);
+            }
+            StmtKind::Enum(decl) => {
+                self.inner.insert(&decl.name);
+            }
+            StmtKind::Struct(decl) => {
+                self.inner.insert(&decl.name);
+            }
+            StmtKind::Interface(decl) => {
+                self.inner.insert(&decl.name);
+            }
+            StmtKind::TypeAlias(decl) => {
+                self.inner.insert(&decl.name);
+            }
+            StmtKind::Export(inner) => self.stmt(inner),
+            StmtKind::Import(_) => self.dynamic = true,
+            StmtKind::Break => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &'a Expr) {
+        match &expr.kind {
+            ExprKind::Variable(name, slot) => self.reads.push((slot, name)),
+            ExprKind::Binary { left, right, .. }
+            | ExprKind::Pipeline { left, right }
+            | ExprKind::LogicalAnd { left, right }
+            | ExprKind::LogicalOr { left, right }
+            | ExprKind::NullishCoalescing { left, right }
+            | ExprKind::Index {
+                object: left,
+                index: right,
+            }
+            | ExprKind::Assign {
+                target: left,
+                value: right,
+            }
+            | ExprKind::CompoundAssign {
+                target: left,
+                value: right,
+                ..
+            }
+            | ExprKind::Rescue {
+                expr: left,
+                fallback: right,
+            } => {
+                self.expr(left);
+                self.expr(right);
+            }
+            ExprKind::Unary { operand: inner, .. }
+            | ExprKind::Grouping(inner)
+            | ExprKind::Spread(inner)
+            | ExprKind::Throw(inner)
+            | ExprKind::Await(inner)
+            | ExprKind::PostfixIncrement(inner)
+            | ExprKind::PostfixDecrement(inner)
+            | ExprKind::Member { object: inner, .. }
+            | ExprKind::SafeMember { object: inner, .. }
+            | ExprKind::QualifiedName {
+                qualifier: inner, ..
+            } => self.expr(inner),
+            ExprKind::Call { callee, arguments }
+            | ExprKind::New {
+                class_expr: callee,
+                arguments,
+            } => {
+                self.expr(callee);
+                for argument in arguments {
+                    match argument {
+                        Argument::Positional(e) | Argument::Block(e) | Argument::Spread(e) => {
+                            self.expr(e)
+                        }
+                        Argument::Named(named) => self.expr(&named.value),
+                    }
+                }
+            }
+            ExprKind::Array(items) | ExprKind::Tuple(items) => {
+                for item in items {
+                    self.expr(item);
+                }
+            }
+            ExprKind::Hash(pairs) => {
+                for (key, value) in pairs {
+                    self.expr(key);
+                    self.expr(value);
+                }
+            }
+            ExprKind::InterpolatedString(parts) => {
+                for part in parts {
+                    if let InterpolatedPart::Expression(e) = part {
+                        self.expr(e);
+                    }
+                }
+            }
+            ExprKind::Block(stmts) => self.block(stmts),
+            ExprKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expr(condition);
+                self.expr(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.expr(else_branch);
+                }
+            }
+            ExprKind::Match { expression, arms } => {
+                self.expr(expression);
+                for arm in arms {
+                    self.pattern(&arm.pattern);
+                    if let Some(guard) = &arm.guard {
+                        self.expr(guard);
+                    }
+                    self.expr(&arm.body);
+                }
+            }
+            ExprKind::ListComprehension {
+                element,
+                variable,
+                iterable,
+                condition,
+            } => {
+                self.inner.insert(variable);
+                self.expr(element);
+                self.expr(iterable);
+                if let Some(condition) = condition {
+                    self.expr(condition);
+                }
+            }
+            ExprKind::HashComprehension(comp) => {
+                let HashComprehension {
+                    key,
+                    value,
+                    variable,
+                    iterable,
+                    condition,
+                } = &**comp;
+                self.inner.insert(variable);
+                self.expr(key);
+                self.expr(value);
+                self.expr(iterable);
+                if let Some(condition) = condition {
+                    self.expr(condition);
+                }
+            }
+            // A lambda runs in its own frame.
+            ExprKind::Lambda { .. } => {}
+            ExprKind::IntLiteral(_)
+            | ExprKind::FloatLiteral(_)
+            | ExprKind::DecimalLiteral(_)
+            | ExprKind::StringLiteral(_)
+            | ExprKind::CommandSubstitution(_)
+            | ExprKind::SdqlBlock { .. }
+            | ExprKind::BoolLiteral(_)
+            | ExprKind::Symbol(_)
+            | ExprKind::Null
+            | ExprKind::This
+            | ExprKind::Super => {}
+        }
+    }
+
+    fn pattern(&mut self, pattern: &'a MatchPattern) {
+        match pattern {
+            MatchPattern::Wildcard | MatchPattern::Literal(_) => {}
+            MatchPattern::Variable(name) | MatchPattern::Typed { name, .. } => {
+                self.inner.insert(name);
+            }
+            MatchPattern::Array { elements, rest } => {
+                for element in elements {
+                    self.pattern(element);
+                }
+                if let Some(rest) = rest {
+                    self.inner.insert(rest);
+                }
+            }
+            MatchPattern::Hash { fields, rest } => {
+                for (_, field) in fields {
+                    self.pattern(field);
+                }
+                if let Some(rest) = rest {
+                    self.inner.insert(rest);
+                }
+            }
+            MatchPattern::Destructuring { fields, .. } => {
+                for (_, field) in fields {
+                    self.pattern(field);
+                }
+            }
+            MatchPattern::Tuple(patterns)
+            | MatchPattern::EnumVariant {
+                bindings: patterns, ..
+            }
+            | MatchPattern::And(patterns)
+            | MatchPattern::Or(patterns) => {
+                for p in patterns {
+                    self.pattern(p);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::stmt::{ImportDecl, ImportSpecifier};
+    use crate::lexer::Scanner;
+    use crate::parser::Parser;
+    use crate::span::Span;
+
+    fn function(source: &str) -> crate::ast::FunctionDecl {
+        let tokens = Scanner::new(source).scan_tokens().unwrap();
+        let mut program = Parser::new(tokens).parse().unwrap();
+        let StmtKind::Function(decl) = program.statements.remove(0).kind else {
+            panic!("expected a function declaration");
+        };
+        *decl
+    }
+
+    /// How many reads and `let`s of `body` are tied to `layout`.
+    fn tied(body: &[Stmt], layout: &FrameLayout) -> usize {
+        let mut scan = Scan::default();
+        for stmt in body {
+            match &stmt.kind {
+                StmtKind::Let {
+                    name,
+                    initializer,
+                    slot,
+                    ..
+                } => {
+                    scan.top.push((slot, name));
+                    if let Some(init) = initializer {
+                        scan.expr(init);
+                    }
+                }
+                _ => scan.stmt(stmt),
+            }
+        }
+        scan.top
+            .iter()
+            .chain(&scan.reads)
+            .filter(|(node, _)| layout.slot_of(node).is_some())
+            .count()
+    }
+
+    #[test]
+    fn params_and_top_level_lets_get_slots() {
+        let decl = function("fn f(a, b) { let c = a + b\n return c * a }");
+        let layout = decl.body.frame_layout(&decl.params).unwrap();
+        assert_eq!(layout.names(), ["a", "b", "c"]);
+        assert_eq!(layout.param_slot(1), Some(1));
+        // The `let c`, `a` twice, `b` and `c` once each.
+        assert_eq!(tied(&decl.body, &layout), 5);
+    }
+
+    #[test]
+    fn names_an_inner_scope_may_bind_stay_by_name() {
+        let decl = function(
+            "fn f(a, i, x) { let e = 0\n for i in [1] { let a = i }\n \
+             try { e = 1 } catch e { }\n return match x { x => x } }",
+        );
+        assert!(decl.body.frame_layout(&decl.params).is_none());
+    }
+
+    #[test]
+    fn lambda_bodies_are_not_part_of_the_frame() {
+        let decl = function("fn f(a) { let g = fn(b) { a + b }\n return g(a) }");
+        let layout = decl.body.frame_layout(&decl.params).unwrap();
+        assert_eq!(layout.names(), ["a", "g"]);
+        // The `let g`, then `g` and `a` in the call; the reads inside the
+        // lambda are its own.
+        assert_eq!(tied(&decl.body, &layout), 3);
+    }
+
+    #[test]
+    fn the_layout_is_resolved_once_per_body() {
+        let decl = function("fn f(a) { return a }");
+        let first = decl.body.frame_layout(&decl.params).unwrap();
+        let second = decl.body.frame_layout(&decl.params).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(tied(&decl.body, &first), 1);
+        // A copy is another body, with nodes of its own.
+        let copy = (*decl.body).clone();
+        assert_eq!(tied(&copy, &first), 0);
+    }
+
+    #[test]
+    fn slotted_frames_run_like_named_ones() {
+        use crate::interpreter::{Interpreter, Value};
+
+        let source = "fn outer(n, m = n + 1) {\n  let total = 0\n  \
+                      let add = fn(k) { total = total + k }\n  \
+                      add(n)\n  add(m)\n  return total\n}\n\
+                      fn fib(n) { if n < 2 { return n }\n  return fib(n - 1) + fib(n - 2) }\n\
+                      fn doubled(xs) { return xs.map(fn(x) { let y = x * 2\n y }) }\n\
+                      let result = outer(3) * 1000 + outer(4) * 100 + fib(10) + doubled([1, 2])[1]";
+        let tokens = Scanner::new(source).scan_tokens().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.interpret(&program).unwrap();
+        let result = interpreter.environment.borrow().get("result");
+        assert_eq!(result, Some(Value::Int(7000 + 900 + 55 + 4)));
+    }
+
+    #[test]
+    fn a_body_with_an_import_is_not_resolved() {
+        // The parser only takes imports at the top level, but a deserialized
+        // bundle can hold one anywhere.
+        let decl = function("fn f(a) { return a }");
+        let mut body = (*decl.body).clone();
+        body.insert(
+            0,
+            Stmt::new(
+                StmtKind::Import(ImportDecl {
+                    path: "./x.sl".to_string(),
+                    specifier: ImportSpecifier::All,
+                    span: Span::default(),
+                }),
+                Span::default(),
+                None,
+            ),
+        );
+        assert!(body.frame_layout(&decl.params).is_none());
+    }
+}
@@ -14,15 +14,17 @@ use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde::ser::{SerializeMap, SerializeSeq};
 
-use crate::ast::{Expr, FunctionDecl, MethodDecl, Parameter, Stmt, TypeAnnotation};
+use crate::ast::{Body, Expr, FunctionDecl, MethodDecl, Parameter, TypeAnnotation};
 use crate::interpreter::builtins::model::QueryBuilder;
 use crate::interpreter::environment::Environment;
+use crate::interpreter::executor::generators::LazyIterator;
+use crate::interpreter::resolver::FrameLayout;
 use crate::span::Span;
 use crate::vm::upvalue::VmClosure;
 
 /// A Decimal value wrapper for financial calculations.
 /// Uses rust_decimal for exact decimal arithmetic.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub struct DecimalValue(pub Decimal, pub u32); // (value, precision)
 
 impl DecimalValue {
@@ -69,12 +71,15 @@ impl Hash for DecimalValue {
 /// flow into `HashKey` and the VM constant pool, which cross threads via the
 /// compiled-module cache. (A plain `Arc<str>` was benchmarked first and lost:
 /// `Arc<str>::from(String)` re-copies the bytes on every construction, which
-/// dominated the clone savings on construction-heavy workloads.)
+/// dominated the clone savings on construction-heavy workloads. Interning
+/// the request headers through a per-thread table was tried too: the lookup
+/// cost what the allocations it saved did — see the `request` group in
+/// `benches/interpreter.rs`.)
 pub type SoliStr = ecow::EcoString;
 
 /// A hashable key type for use in IndexMap.
 /// This wraps primitive Value types that can be used as hash keys.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum HashKey {
     Int(i64),
     Decimal(DecimalValue), // Hashable Decimal
@@ -284,6 +289,71 @@ pub fn hash_contains_value(hash: &HashPairs, key: &Value) -> bool {
     hash_get_value(hash, key).is_some()
 }
 
+/// An integer range: `start..end` excludes `end`, `start..=end` includes it.
+/// Loops walk it directly; array methods see it via `to_vec`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeValue {
+    pub start: i64,
+    pub end: i64,
+    pub inclusive: bool,
+}
+
+impl RangeValue {
+    pub fn new(start: i64, end: i64, inclusive: bool) -> Self {
+        Self {
+            start,
+            end,
+            inclusive,
+        }
+    }
+
+    /// The first value past the range.
+    pub fn stop(&self) -> i64 {
+        if self.inclusive {
+            self.end.saturating_add(1)
+        } else {
+            self.end
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.stop().saturating_sub(self.start).max(0) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, n: i64) -> bool {
+        n >= self.start && n < self.stop()
+    }
+
+    /// The element at `index`; negative indexes count from the end.
+    pub fn get(&self, index: i64) -> Option<i64> {
+        let index = if index < 0 {
+            index + self.len() as i64
+        } else {
+            index
+        };
+        (index >= 0 && (index as usize) < self.len()).then(|| self.start + index)
+    }
+
+    pub fn iter(&self) -> std::ops::Range<i64> {
+        self.start..self.stop()
+    }
+
+    pub fn to_vec(&self) -> Vec<Value> {
+        self.iter().map(Value::Int).collect()
+    }
+}
+
+impl std::fmt::Display for RangeValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let op = if self.inclusive { "..=" } else { ".." };
+        write!(f, "{}{}{}", self.start, op, self.end)
+    }
+}
+
 /// A runtime value in Solilang.
 #[derive(Debug, Clone)]
 pub enum Value {
@@ -303,6 +373,15 @@ pub enum Value {
     Null,
     /// Array value
     Array(Rc<RefCell<Vec<Value>>>),
+    /// Tuple value (fixed-size, immutable)
+    Tuple(Rc<Vec<Value>>),
+    /// Struct value (immutable; fields inline, compared by value)
+    Struct(Rc<crate::interpreter::value_struct::StructValue>),
+    /// Integer range (`0..10`, `0..=10`), iterated without materializing
+    Range(Rc<RangeValue>),
+    /// Lazy iterator: a suspended generator call, or a `map`/`filter`/...
+    /// stage chained onto one. Values are produced only when pulled.
+    Iterator(Rc<RefCell<LazyIterator>>),
     /// Hash/Map value (ordered, O(1) lookup using IndexMap with ahash)
     Hash(Rc<RefCell<HashPairs>>),
     /// Function value (closure)
@@ -343,6 +422,34 @@ pub enum Value {
 }
 
 impl Value {
+    /// The elements of a tuple (or array) being destructured into `arity`
+    /// names by `let (a, b) = ...` / `for (k, v) in ...`. A hash entry is
+    /// already a `(key, value)` tuple by the time it gets here.
+    pub fn destructure(&self, arity: usize) -> Result<Vec<Value>, String> {
+        let items = match self {
+            Value::Tuple(items) => items.to_vec(),
+            Value::Array(arr) => arr.borrow().clone(),
+            Value::Range(range) => range.to_vec(),
+            Value::Deferred(_) => return self.force_deferred().destructure(arity),
+            other => {
+                return Err(format!(
+                    "cannot destructure {} into {} names",
+                    other.type_name(),
+                    arity
+                ))
+            }
+        };
+        if items.len() != arity {
+            return Err(format!(
+                "cannot destructure {} of {} elements into {} names",
+                self.type_name(),
+                items.len(),
+                arity
+            ));
+        }
+        Ok(items)
+    }
+
     /// Construct a `Value::Method`, boxing the receiver+name into the `Rc`
     /// the variant now holds so the variant stays pointer-sized.
     #[inline]
@@ -441,6 +548,9 @@ pub enum FutureState {
         receiver: Receiver<Result<String, String>>,
         kind: HttpFutureKind,
     },
+    /// An `async fn` call queued on the scheduler. Only `await` can run it
+    /// (see `executor::scheduler`).
+    Task { id: u64, name: String },
     /// Result is ready
     Resolved(Value),
     /// Error occurred
@@ -451,6 +561,7 @@ impl std::fmt::Debug for FutureState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             FutureState::Pending { .. } => write!(f, "FutureState::Pending"),
+            FutureState::Task { id, name } => write!(f, "FutureState::Task({}, {:?})", id, name),
             FutureState::Resolved(v) => write!(f, "FutureState::Resolved({:?})", v),
             FutureState::Error(e) => write!(f, "FutureState::Error({:?})", e),
         }
@@ -521,10 +632,14 @@ impl Value {
             Value::Null => "null".to_string(),
             Value::Array(_) => "array".to_string(),
             Value::Hash(_) => "hash".to_string(),
+            Value::Tuple(_) => "tuple".to_string(),
+            Value::Range(_) => "range".to_string(),
+            Value::Iterator(_) => "iterator".to_string(),
             Value::Function(_) => "Function".to_string(),
             Value::NativeFunction(_) => "Function".to_string(),
             Value::Class(_) => "Class".to_string(),
             Value::Instance(i) => i.borrow().class.name.clone(),
+            Value::Struct(s) => s.class.name.clone(),
             Value::Future(_) => "Future".to_string(),
             Value::Method(_) => "Method".to_string(),
             Value::Breakpoint => "Breakpoint".to_string(),
@@ -585,6 +700,11 @@ impl Value {
                             Err(_) => Err("Future channel closed".into()),
                         }
                     }
+                    FutureState::Task { id, name } => {
+                        let message = format!("the result of async fn '{}' must be awaited", name);
+                        *guard = FutureState::Task { id, name };
+                        Err(message)
+                    }
                     FutureState::Resolved(value) => {
                         *guard = FutureState::Resolved(value.clone());
                         Ok(value)
@@ -599,6 +719,33 @@ impl Value {
         }
     }
 
+    /// Settle a pending I/O future if its result has already arrived,
+    /// without blocking. Returns whether it is still waiting.
+    pub fn future_is_waiting(&self) -> bool {
+        let Value::Future(state) = self else {
+            return false;
+        };
+        let Ok(mut guard) = state.lock() else {
+            return false;
+        };
+        let FutureState::Pending { receiver, kind } = &*guard else {
+            return false;
+        };
+        let settled = match receiver.try_recv() {
+            Ok(Ok(raw_data)) => match convert_future_result(&raw_data, kind) {
+                Ok(value) => FutureState::Resolved(value),
+                Err(e) => FutureState::Error(e),
+            },
+            Ok(Err(e)) => FutureState::Error(e),
+            Err(std::sync::mpsc::TryRecvError::Empty) => return true,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                FutureState::Error("Future channel closed".into())
+            }
+        };
+        *guard = settled;
+        false
+    }
+
     /// Check if this value is a Future
     pub fn is_future(&self) -> bool {
         matches!(self, Value::Future(_))
@@ -637,6 +784,7 @@ impl Value {
             Value::String(s) if s.is_empty() => false,
             Value::Array(arr) if arr.borrow().is_empty() => false,
             Value::Hash(hash) if hash.borrow().is_empty() => false,
+            Value::Range(range) if range.is_empty() => false,
             Value::Future(_) => true,
             Value::VmClosure(_) => true,
             // Truthiness follows the resolved query result (e.g. an empty
@@ -710,6 +858,21 @@ impl Value {
                 }
                 len + 1
             }
+            Value::Tuple(items) => {
+                let mut len = 2;
+                for (i, v) in items.iter().enumerate() {
+                    len += v.display_len();
+                    if i > 0 {
+                        len += 2;
+                    }
+                }
+                if items.len() == 1 {
+                    len += 1;
+                }
+                len
+            }
+            Value::Range(range) => range.to_string().len(),
+            Value::Iterator(_) => 10,
             Value::Hash(hash) => {
                 let hash = hash.borrow();
                 if hash.is_empty() {
@@ -733,6 +896,7 @@ impl Value {
                 let inst = inst.borrow();
                 inst.class.name.len() + 15
             }
+            Value::Struct(_) => self.to_string().len(),
             Value::Future(_) => 7,
             Value::Method(_) => 8,
             Value::Breakpoint => 10,
@@ -770,6 +934,21 @@ impl Value {
                 }
                 s.push(']');
             }
+            Value::Tuple(items) => {
+                s.push('(');
+                for (i, v) in items.iter().enumerate() {
+                    if i > 0 {
+                        s.push_str(", ");
+                    }
+                    v.write_to_string(s);
+                }
+                if items.len() == 1 {
+                    s.push(',');
+                }
+                s.push(')');
+            }
+            Value::Range(range) => s.push_str(&range.to_string()),
+            Value::Iterator(_) => s.push_str("<iterator>"),
             Value::Hash(hash) => {
                 s.push('{');
                 let hash = hash.borrow();
@@ -804,6 +983,7 @@ impl Value {
                 s.push_str(&inst.class.name);
                 s.push_str(" instance>");
             }
+            Value::Struct(_) => s.push_str(&self.to_string()),
             Value::Future(_) => s.push_str("<Future>"),
             Value::Method(_) => s.push_str("<Method>"),
             Value::Breakpoint => s.push_str("<Breakpoint>"),
@@ -847,6 +1027,21 @@ impl PartialEq for Value {
                 }
                 a_ref.iter().zip(b_ref.iter()).all(|(x, y)| x == y)
             }
+            (Value::Tuple(a), Value::Tuple(b)) => a == b,
+            (Value::Struct(a), Value::Struct(b)) => a == b,
+            // Ranges compare by the integers they cover, so `0..3`, `0..=2`
+            // and `[0, 1, 2]` are all equal.
+            (Value::Range(a), Value::Range(b)) => {
+                a.len() == b.len() && (a.is_empty() || a.start == b.start)
+            }
+            (Value::Range(r), Value::Array(arr)) | (Value::Array(arr), Value::Range(r)) => {
+                let arr = arr.borrow();
+                arr.len() == r.len()
+                    && arr
+                        .iter()
+                        .zip(r.iter())
+                        .all(|(v, n)| matches!(v, Value::Int(i) if *i == n))
+            }
             (Value::Hash(a), Value::Hash(b)) => {
                 // Use structural equality for hashes (O(n) with IndexMap)
                 let a_ref = a.borrow();
@@ -869,6 +1064,7 @@ impl PartialEq for Value {
             (Value::Method(a), Value::Method(b)) => {
                 *a.receiver == *b.receiver && a.method_name == b.method_name
             }
+            (Value::Iterator(a), Value::Iterator(b)) => Rc::ptr_eq(a, b),
             _ => false,
         }
     }
@@ -895,6 +1091,33 @@ impl fmt::Display for Value {
                 }
                 write!(f, "]")
             }
+            Value::Range(range) => write!(f, "{}", range),
+            Value::Iterator(_) => write!(f, "<iterator>"),
+            // `Money(amount: 5, currency: EUR)` — the named construction form.
+            Value::Struct(value) => {
+                write!(f, "{}(", value.class.name)?;
+                for (i, (name, val)) in value.entries().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", name, val)?;
+                }
+                write!(f, ")")
+            }
+            Value::Tuple(items) => {
+                write!(f, "(")?;
+                for (i, val) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", val)?;
+                }
+                // `(1,)` — a bare `(1)` would read back as a grouped scalar.
+                if items.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
             Value::Hash(hash) => {
                 write!(f, "{{")?;
                 let hash = hash.borrow();
@@ -911,12 +1134,25 @@ impl fmt::Display for Value {
             Value::Class(class) => write!(f, "<class {}>", class.name),
             Value::Instance(inst) => {
                 let inst_ref = inst.borrow();
+                // A Point prints as "lat,lon" — what templates and map URLs
+                // want — rather than the generic field dump.
+                if inst_ref.class.name == "Point" {
+                    if let (Some(lat), Some(lon)) =
+                        (inst_ref.fields.get("lat"), inst_ref.fields.get("lon"))
+                    {
+                        return write!(f, "{},{}", lat, lon);
+                    }
+                }
                 if inst_ref.fields.is_empty() {
                     write!(f, "<{} instance>", inst_ref.class.name)
                 } else {
                     write!(f, "<{}", inst_ref.class.name)?;
                     let mut first = true;
-                    for (k, v) in inst_ref.fields.iter() {
+                    // `fields` is a randomly keyed ahash map, so sort: the same
+                    // object must print the same way on every run.
+                    let mut fields: Vec<_> = inst_ref.fields.iter().collect();
+                    fields.sort_unstable_by(|a, b| a.0.cmp(b.0));
+                    for (k, v) in fields {
                         // Hide _errors when empty
                         if k == "_errors" {
                             if let Value::Array(arr) = v {
@@ -944,6 +1180,7 @@ impl fmt::Display for Value {
                 let guard = state.lock().unwrap();
                 match &*guard {
                     FutureState::Pending { .. } => write!(f, "<pending future>"),
+                    FutureState::Task { name, .. } => write!(f, "<async {}>", name),
                     FutureState::Resolved(val) => write!(f, "{}", val),
                     FutureState::Error(e) => write!(f, "<error: {}>", e),
                 }
@@ -981,14 +1218,21 @@ impl fmt::Display for Value {
 #[derive(Debug, Clone)]
 pub struct Function {
     pub name: String,
-    // `Rc<[..]>` so binding `this` (which rebuilds a `Function` from an existing
-    // method on every call) is an O(1) refcount bump instead of a deep clone of
-    // the whole parameter list / method-body AST. Built once from the AST via
-    // `from_decl`/`from_method`; never mutated after construction.
+    // Shared pointers so binding `this` (which rebuilds a `Function` from an
+    // existing method on every call) is an O(1) refcount bump instead of a deep
+    // clone of the whole parameter list / method-body AST. Never mutated after
+    // construction. The body is the declaration's own `Arc`, so every worker
+    // that loads the same parsed file runs the same body AST.
     pub params: Rc<[Parameter]>,
-    pub body: Rc<[Stmt]>,
+    pub body: Arc<Body>,
     pub closure: Rc<RefCell<Environment>>,
     pub is_method: bool,
+    /// The body contains a `yield`: calling the function returns a lazy
+    /// iterator instead of running it. Worked out once, from the declaration.
+    pub is_generator: bool,
+    /// An `async fn`: calling it queues the body on the scheduler and
+    /// returns a future instead of running it.
+    pub is_async: bool,
     pub span: Option<Span>,
     pub source_path: Option<String>,
     /// The superclass of the class where this method was defined.
@@ -997,6 +1241,8 @@ pub struct Function {
     /// The declared return type annotation, if any.
     /// Used for runtime return type enforcement.
     pub return_type: Option<TypeAnnotation>,
+    /// Evaluated `@decorators` on the declaration, for reflection.
+    pub decorators: crate::interpreter::decorators::Decorators,
     /// Single-slot cache of the lambda's call environment.
     ///
     /// Taken on entry to `call_function`, cleared, re-populated with the new
@@ -1014,13 +1260,16 @@ impl Default for Function {
         Self {
             name: String::new(),
             params: Vec::new().into(),
-            body: Vec::new().into(),
+            body: Arc::new(Body::default()),
             closure: Rc::new(RefCell::new(Environment::new())),
             is_method: false,
+            is_generator: false,
+            is_async: false,
             span: None,
             source_path: None,
             defining_superclass: None,
             return_type: None,
+            decorators: None,
             cached_env: RefCell::new(None),
             jit_cache: RefCell::new(None),
         }
@@ -1036,13 +1285,16 @@ impl Function {
         Self {
             name: decl.name.clone(),
             params: decl.params.clone().into(),
-            body: decl.body.clone().into(),
+            body: Arc::clone(&decl.body),
             closure,
             is_method: false,
+            is_generator: crate::ast::stmt::body_yields(&decl.body),
+            is_async: decl.is_async,
             span: Some(decl.span),
             source_path,
             defining_superclass: None,
             return_type: decl.return_type.clone(),
+            decorators: None,
             cached_env: RefCell::new(None),
             jit_cache: RefCell::new(None),
         }
@@ -1056,18 +1308,27 @@ impl Function {
         Self {
             name: decl.name.clone(),
             params: decl.params.clone().into(),
-            body: decl.body.clone().into(),
+            body: Arc::clone(&decl.body),
             closure,
             is_method: true,
+            is_generator: crate::ast::stmt::body_yields(&decl.body),
+            is_async: decl.is_async,
             span: Some(decl.span),
             source_path,
             defining_superclass: None,
             return_type: decl.return_type.clone(),
+            decorators: None,
             cached_env: RefCell::new(None),
             jit_cache: RefCell::new(None),
         }
     }
 
+    /// The call-frame layout of the body, resolved on first use and shared
+    /// by every function made from the same body.
+    pub fn frame_layout(&self) -> Option<Arc<FrameLayout>> {
+        self.body.frame_layout(&self.params)
+    }
+
     pub fn arity(&self) -> usize {
         // Return the number of required parameters (params without defaults)
         self.params
@@ -1081,6 +1342,15 @@ impl Function {
         self.params.len()
     }
 
+    /// Whether positional arguments can't just fill the parameters in order:
+    /// some parameter can only be passed by name, or a `...rest` parameter
+    /// collects the extras.
+    pub fn needs_argument_binding(&self) -> bool {
+        self.params
+            .iter()
+            .any(|p| p.is_keyword_only || p.is_variadic)
+    }
+
     /// Check if a parameter at index has a default value
     pub fn param_has_default(&self, index: usize) -> bool {
         self.params
@@ -1088,13 +1358,6 @@ impl Function {
             .map(|p| p.default_value.is_some())
             .unwrap_or(false)
     }
-
-    /// Get the default value expression for a parameter at index
-    pub fn param_default_value(&self, index: usize) -> Option<&Expr> {
-        self.params
-            .get(index)
-            .and_then(|p| p.default_value.as_ref())
-    }
 }
 
 /// A native/builtin function. Newtype over `Rc<NativeFunctionInner>` so the
@@ -1214,6 +1477,9 @@ pub const PRIM_TYPE_COUNT: usize = 9;
 pub struct Class {
     pub name: String,
     pub superclass: Option<Rc<Class>>,
+    /// Interfaces named in this class's `implements` clause (not inherited
+    /// ones — see [`Class::conforms_to`]).
+    pub interfaces: Vec<String>,
     /// Instance methods - using RefCell for interior mutability to support define_method
     pub methods: Rc<RefCell<HashMap<String, Rc<Function>>>>,
     pub static_methods: HashMap<String, Rc<Function>>,
@@ -1259,6 +1525,11 @@ pub struct Class {
     /// outside this module — always leave it `Cell::new(None)`; reading goes
     /// through [`Class::is_model_subclass`].
     pub model_subclass_memo: Cell<Option<bool>>,
+    /// `Some` when this Class is a `struct` type: its fields in declaration
+    /// order. Calling it builds a `Value::Struct`, not an `Instance`.
+    pub struct_fields: Option<Rc<Vec<crate::interpreter::value_struct::StructField>>>,
+    /// Evaluated `@decorators` on the class declaration, for reflection.
+    pub decorators: crate::interpreter::decorators::Decorators,
 }
 
 impl Default for Class {
@@ -1266,6 +1537,7 @@ impl Default for Class {
         Self {
             name: String::new(),
             superclass: None,
+            interfaces: Vec::new(),
             methods: Rc::new(RefCell::new(HashMap::new())),
             static_methods: HashMap::new(),
             native_static_methods: HashMap::new(),
@@ -1282,6 +1554,8 @@ impl Default for Class {
             vm_methods: Rc::new(RefCell::new(HashMap::default())),
             vm_static_methods: Rc::new(RefCell::new(HashMap::default())),
             model_subclass_memo: Cell::new(None),
+            struct_fields: None,
+            decorators: None,
         }
     }
 }
@@ -1304,6 +1578,7 @@ impl Class {
         Self {
             name,
             superclass,
+            interfaces: Vec::new(),
             methods: Rc::new(RefCell::new(methods)),
             static_methods,
             native_static_methods,
@@ -1320,7 +1595,22 @@ impl Class {
             vm_methods: Rc::new(RefCell::new(HashMap::default())),
             vm_static_methods: Rc::new(RefCell::new(HashMap::default())),
             model_subclass_memo: Cell::new(None),
+            struct_fields: None,
+            decorators: None,
+        }
+    }
+
+    /// Whether an instance of this class is a `name`: the class itself, a
+    /// superclass, or an interface any of them `implements`.
+    pub fn conforms_to(&self, name: &str) -> bool {
+        let mut current = Some(self);
+        while let Some(class) = current {
+            if class.name == name || class.interfaces.iter().any(|i| i == name) {
+                return true;
+            }
+            current = class.superclass.as_deref();
         }
+        false
     }
 
     /// Find a bytecode instance method in this class or its superclass chain.
@@ -1443,6 +1733,17 @@ impl Class {
             .and_then(|cache| cache.get(name).cloned())
     }
 
+    /// Find a static field in this class or its superclass chain.
+    pub fn find_static_field(&self, name: &str) -> Option<Value> {
+        if let Some(value) = self.static_fields.borrow().get(name) {
+            return Some(value.clone());
+        }
+        if let Some(ref superclass) = self.superclass {
+            return superclass.find_static_field(name);
+        }
+        None
+    }
+
     /// Find a static method in this class or its superclass chain.
     pub fn find_static_method(&self, name: &str) -> Option<Rc<Function>> {
         if let Some(method) = self.static_methods.get(name) {
@@ -1671,6 +1972,20 @@ impl serde::Serialize for Value {
                 }
                 seq.end()
             }
+            Value::Range(range) => {
+                let mut seq = serializer.serialize_seq(Some(range.len()))?;
+                for n in range.iter() {
+                    seq.serialize_element(&n)?;
+                }
+                seq.end()
+            }
+            Value::Tuple(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for v in items.iter() {
+                    seq.serialize_element(v)?;
+                }
+                seq.end()
+            }
             Value::Hash(hash) => {
                 let borrow = hash.borrow();
                 let mut map = serializer.serialize_map(Some(borrow.len()))?;
@@ -1684,6 +1999,13 @@ impl serde::Serialize for Value {
                 }
                 map.end()
             }
+            Value::Struct(value) => {
+                let mut map = serializer.serialize_map(Some(value.values.len()))?;
+                for (name, v) in value.entries() {
+                    map.serialize_entry(name, v)?;
+                }
+                map.end()
+            }
             Value::Instance(inst) => {
                 let borrow = inst.borrow();
                 // Enum value → a DB/JSON-friendly shape: a bare tag string for
@@ -2069,6 +2391,25 @@ pub fn unwrap_value(value: &Value) -> Value {
 
 use crate::ast::TypeKind;
 
+thread_local! {
+    /// `type Name = ...` declarations executed so far, so an annotation
+    /// naming an alias checks against the type it stands for.
+    static TYPE_ALIASES: RefCell<HashMap<String, TypeAnnotation>> = RefCell::new(HashMap::new());
+}
+
+/// Record a `type Name = ...` declaration for runtime type checks. Returns
+/// false, recording nothing, when the alias would refer to itself.
+pub fn define_type_alias(name: &str, ty: &TypeAnnotation) -> bool {
+    TYPE_ALIASES.with(|aliases| {
+        let mut aliases = aliases.borrow_mut();
+        if ty.refers_to(name, &|n| aliases.get(n)) {
+            return false;
+        }
+        aliases.insert(name.to_string(), ty.clone());
+        true
+    })
+}
+
 /// Check if a runtime value matches an expected type annotation.
 /// Used for runtime return type enforcement.
 pub fn value_matches_type(value: &Value, expected: &TypeAnnotation) -> bool {
@@ -2082,14 +2423,21 @@ pub fn value_matches_type(value: &Value, expected: &TypeAnnotation) -> bool {
                 "decimal" => matches!(value, Value::Decimal(_)),
                 "string" => matches!(value, Value::String(_)),
                 "bool" => matches!(value, Value::Bool(_)),
-                "array" => matches!(value, Value::Array(_)),
+                "array" => matches!(value, Value::Array(_) | Value::Range(_)),
+                "range" => matches!(value, Value::Range(_)),
+                "iterator" => matches!(value, Value::Iterator(_)),
                 "hash" => matches!(value, Value::Hash(_)),
+                "tuple" => matches!(value, Value::Tuple(_)),
                 "function" => matches!(value, Value::Function(_) | Value::NativeFunction(_)),
                 "void" | "null" => matches!(value, Value::Null),
-                // Class instance check
-                _ => match value {
-                    Value::Instance(inst) => inst.borrow().class.name == *name,
-                    _ => false,
+                // A type alias, else a class instance check
+                _ => match TYPE_ALIASES.with(|aliases| aliases.borrow().get(name).cloned()) {
+                    Some(aliased) => value_matches_type(value, &aliased),
+                    None => match value {
+                        Value::Instance(inst) => inst.borrow().class.conforms_to(name),
+                        Value::Struct(s) => s.class.name == *name,
+                        _ => false,
+                    },
                 },
             }
         }
@@ -2097,11 +2445,34 @@ pub fn value_matches_type(value: &Value, expected: &TypeAnnotation) -> bool {
         TypeKind::Nullable(inner) => {
             matches!(value, Value::Null) || value_matches_type(value, inner)
         }
-        TypeKind::Array(_) => matches!(value, Value::Array(_)),
+        TypeKind::Array(_) => matches!(value, Value::Array(_) | Value::Range(_)),
         TypeKind::Hash { .. } => matches!(value, Value::Hash(_)),
+        TypeKind::Tuple(elements) => match value {
+            Value::Tuple(items) => {
+                items.len() == elements.len()
+                    && items
+                        .iter()
+                        .zip(elements)
+                        .all(|(item, ty)| value_matches_type(item, ty))
+            }
+            _ => false,
+        },
         TypeKind::Function { .. } => {
             matches!(value, Value::Function(_) | Value::NativeFunction(_))
         }
+        // Every field present with its type; a nullable field may be missing.
+        TypeKind::Record(fields) => match value {
+            Value::Hash(hash) => {
+                let hash = hash.borrow();
+                fields
+                    .iter()
+                    .all(|field| match hash.get(&StrKey(&field.name)) {
+                        Some(item) => value_matches_type(item, &field.type_annotation),
+                        None => matches!(field.type_annotation.kind, TypeKind::Nullable(_)),
+                    })
+            }
+            _ => false,
+        },
     }
 }
 
@@ -2530,6 +2901,31 @@ mod return_type_tests {
         assert!(!value_matches_type(&Value::String("x".into()), &ty));
     }
 
+    #[test]
+    fn test_record_checks_fields() {
+        use crate::ast::RecordField;
+        let field = |name: &str, kind: TypeKind| RecordField {
+            name: name.to_string(),
+            type_annotation: make_type(kind),
+            span: Span::default(),
+        };
+        let email = TypeKind::Nullable(Box::new(make_type(TypeKind::Named("String".into()))));
+        let ty = make_type(TypeKind::Record(vec![
+            field("id", TypeKind::Named("Int".into())),
+            field("email", email),
+        ]));
+        let mut map = HashPairs::default();
+        map.insert(HashKey::String("id".into()), Value::Int(1));
+        let hash = Value::Hash(Rc::new(RefCell::new(map)));
+        assert!(value_matches_type(&hash, &ty));
+        if let Value::Hash(h) = &hash {
+            h.borrow_mut()
+                .insert(HashKey::String("email".into()), Value::Int(2));
+        }
+        assert!(!value_matches_type(&hash, &ty));
+        assert!(!value_matches_type(&Value::Int(1), &ty));
+    }
+
     #[test]
     fn test_void_matches_null() {
         let ty = make_type(TypeKind::Void);
@@ -2847,7 +3243,7 @@ mod value_misc_tests {
     // ---------------------------------------------------------------------
     #[test]
     fn size_guards() {
-        use crate::ast::expr::{Expr, ExprKind};
+        use crate::ast::expr::{Argument, Expr, ExprKind};
         use crate::ast::stmt::{Stmt, StmtKind};
         use crate::ast::types::TypeAnnotation;
         use crate::span::Span;
@@ -2868,6 +3264,7 @@ mod value_misc_tests {
         eprintln!("size_of::<Span>()           = {}", size_of::<Span>());
         eprintln!("size_of::<Expr>()           = {}", size_of::<Expr>());
         eprintln!("size_of::<ExprKind>()       = {}", size_of::<ExprKind>());
+        eprintln!("size_of::<Argument>()       = {}", size_of::<Argument>());
         eprintln!("size_of::<Stmt>()           = {}", size_of::<Stmt>());
         eprintln!("size_of::<StmtKind>()       = {}", size_of::<StmtKind>());
         eprintln!(
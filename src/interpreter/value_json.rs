@@ -103,6 +103,19 @@ pub fn value_to_json(value: &Value) -> Result<serde_json::Value, String> {
             }
             Ok(serde_json::Value::Array(vec))
         }
+        Value::Tuple(items) => Ok(serde_json::Value::Array(
+            items.iter().map(value_to_json).collect::<Result<_, _>>()?,
+        )),
+        Value::Struct(value) => {
+            let mut map = serde_json::Map::with_capacity(value.values.len());
+            for (name, v) in value.entries() {
+                map.insert(name.to_string(), value_to_json(v)?);
+            }
+            Ok(serde_json::Value::Object(map))
+        }
+        Value::Range(range) => Ok(serde_json::Value::Array(
+            range.iter().map(serde_json::Value::from).collect(),
+        )),
         Value::Hash(hash) => {
             let borrow = hash.borrow();
             let mut map = serde_json::Map::with_capacity(borrow.len());
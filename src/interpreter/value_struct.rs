@@ -0,0 +1,287 @@
+//! Struct values: immutable value types declared with `struct`.
+//!
+//! ```soli
+//! struct Money
+//!   amount: Int
+//!   currency: String = "EUR"
+//! end
+//!
+//! let price = Money(amount: 5)
+//! let total = price.with(amount: price.amount * 3)   # price is untouched
+//! ```
+//!
+//! A struct value is its fields, inline and in declaration order, behind a
+//! plain `Rc` — never a `RefCell`. Nothing can mutate one in place, so
+//! sharing the `Rc` is indistinguishable from copying it, and passing or
+//! storing a struct costs a refcount bump. Arrays and hashes are the one way
+//! shared state could leak in, so they are copied on the way in (construction
+//! and `with`) and on the way out (field reads): mutating what you passed in,
+//! or what you read back, never changes the struct.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::interpreter::value::{Class, HashKey, HashPairs, Value};
+
+/// A declared struct field. `default` is evaluated once, when the `struct`
+/// statement runs, and copied into every value that omits the field.
+#[derive(Debug, Clone)]
+pub struct StructField {
+    pub name: String,
+    pub default: Option<Value>,
+}
+
+/// One struct value: its type and its field values, in declaration order.
+#[derive(Debug, Clone)]
+pub struct StructValue {
+    pub class: Rc<Class>,
+    pub values: Vec<Value>,
+}
+
+impl StructValue {
+    /// The declared fields of this value's struct type.
+    pub fn fields(&self) -> &[StructField] {
+        self.class
+            .struct_fields
+            .as_deref()
+            .map_or(&[], |f| f.as_slice())
+    }
+
+    /// The value of field `name`, detached from the struct (see module docs).
+    pub fn get(&self, name: &str) -> Option<Value> {
+        let index = self.fields().iter().position(|f| f.name == name)?;
+        Some(detach(&self.values[index]))
+    }
+
+    /// `(name, value)` pairs in declaration order, sharing the stored values.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.fields()
+            .iter()
+            .map(|f| f.name.as_str())
+            .zip(self.values.iter())
+    }
+
+    /// Build a value of struct type `class` from constructor arguments:
+    /// positional in declaration order, then named. Omitted fields take their
+    /// default; a field with no default is required.
+    pub fn construct(
+        class: &Rc<Class>,
+        positional: Vec<Value>,
+        mut named: HashMap<String, Value>,
+    ) -> Result<Value, String> {
+        let fields = class.struct_fields.as_deref().map_or(&[][..], |f| f);
+        if positional.len() > fields.len() {
+            return Err(format!(
+                "{} has {} field(s), got {} positional arguments",
+                class.name,
+                fields.len(),
+                positional.len()
+            ));
+        }
+        check_named(class, &named)?;
+        let mut positional = positional.into_iter();
+        let mut values = Vec::with_capacity(fields.len());
+        for field in fields {
+            let value = match (positional.next(), named.remove(&field.name)) {
+                (Some(_), Some(_)) => {
+                    return Err(format!(
+                        "{} field '{}' given both positionally and by name",
+                        class.name, field.name
+                    ))
+                }
+                (Some(value), None) | (None, Some(value)) => value,
+                (None, None) => match &field.default {
+                    Some(default) => default.clone(),
+                    None => {
+                        return Err(format!("missing field '{}' for {}", field.name, class.name))
+                    }
+                },
+            };
+            values.push(detach(&value));
+        }
+        Ok(Value::Struct(Rc::new(StructValue {
+            class: class.clone(),
+            values,
+        })))
+    }
+
+    /// Like [`StructValue::construct`] with a hash of fields — `Money.new({...})`
+    /// and `Money.from_h(...)`. Symbol and string keys are both accepted.
+    pub fn from_hash(class: &Rc<Class>, hash: &HashPairs) -> Result<Value, String> {
+        Self::construct(class, Vec::new(), named_fields(class, hash)?)
+    }
+
+    /// A copy of this value with the `changes` fields replaced.
+    pub fn with(&self, changes: HashMap<String, Value>) -> Result<Value, String> {
+        check_named(&self.class, &changes)?;
+        let values = self
+            .entries()
+            .map(|(name, value)| match changes.get(name) {
+                Some(changed) => detach(changed),
+                None => value.clone(),
+            })
+            .collect();
+        Ok(Value::Struct(Rc::new(StructValue {
+            class: self.class.clone(),
+            values,
+        })))
+    }
+
+    /// The fields as a hash (detached copies).
+    pub fn to_hash(&self) -> Value {
+        let mut pairs = HashPairs::default();
+        for (name, value) in self.entries() {
+            pairs.insert(HashKey::String(name.into()), detach(value));
+        }
+        Value::Hash(Rc::new(RefCell::new(pairs)))
+    }
+}
+
+impl PartialEq for StructValue {
+    /// Value equality: same struct type, equal fields.
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.class, &other.class) && self.values == other.values
+    }
+}
+
+/// The entries of `hash` keyed by field name, for `from_h` and `with`.
+pub fn named_fields(class: &Class, hash: &HashPairs) -> Result<HashMap<String, Value>, String> {
+    let mut named = HashMap::new();
+    for (key, value) in hash.iter() {
+        match key {
+            HashKey::String(k) | HashKey::Symbol(k) => {
+                named.insert(k.to_string(), value.clone());
+            }
+            other => {
+                return Err(format!(
+                    "{} fields must be named by strings, got {}",
+                    class.name,
+                    other.to_value().type_name()
+                ))
+            }
+        }
+    }
+    Ok(named)
+}
+
+fn check_named(class: &Class, named: &HashMap<String, Value>) -> Result<(), String> {
+    let fields = class.struct_fields.as_deref().map_or(&[][..], |f| f);
+    match named
+        .keys()
+        .find(|name| !fields.iter().any(|f| &f.name == *name))
+    {
+        Some(unknown) => Err(format!("{} has no field '{}'", class.name, unknown)),
+        None => Ok(()),
+    }
+}
+
+/// A copy of `value` that shares no mutable container with it: arrays and
+/// hashes are copied, recursively. Everything else is immutable (or, for
+/// class instances, shared by design) and clones as-is.
+pub fn detach(value: &Value) -> Value {
+    match value {
+        Value::Array(items) => Value::Array(Rc::new(RefCell::new(
+            items.borrow().iter().map(detach).collect(),
+        ))),
+        Value::Hash(pairs) => {
+            let mut copy = HashPairs::default();
+            for (key, value) in pairs.borrow().iter() {
+                copy.insert(key.clone(), detach(value));
+            }
+            Value::Hash(Rc::new(RefCell::new(copy)))
+        }
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn money() -> Rc<Class> {
+        Rc::new(Class {
+            name: "Money".to_string(),
+            struct_fields: Some(Rc::new(vec![
+                StructField {
+                    name: "amount".to_string(),
+                    default: None,
+                },
+                StructField {
+                    name: "currency".to_string(),
+                    default: Some(Value::String("EUR".into())),
+                },
+            ])),
+            ..Default::default()
+        })
+    }
+
+    fn named(entries: &[(&str, Value)]) -> HashMap<String, Value> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn construction_fills_defaults_and_rejects_bad_fields() {
+        let class = money();
+        let Value::Struct(value) =
+            StructValue::construct(&class, vec![Value::Int(5)], HashMap::new()).unwrap()
+        else {
+            panic!("expected a struct");
+        };
+        assert_eq!(value.get("currency"), Some(Value::String("EUR".into())));
+
+        let missing = StructValue::construct(&class, Vec::new(), HashMap::new());
+        assert!(missing.unwrap_err().contains("missing field 'amount'"));
+        let unknown =
+            StructValue::construct(&class, Vec::new(), named(&[("amout", Value::Int(1))]));
+        assert!(unknown.unwrap_err().contains("no field 'amout'"));
+        let twice = StructValue::construct(
+            &class,
+            vec![Value::Int(1)],
+            named(&[("amount", Value::Int(2))]),
+        );
+        assert!(twice.is_err());
+    }
+
+    /// The point of the type: nothing reachable from outside aliases the
+    /// struct's own storage.
+    #[test]
+    fn arrays_are_copied_in_and_out() {
+        let class = money();
+        let shared = Value::Array(Rc::new(RefCell::new(vec![Value::Int(1)])));
+        let Value::Struct(value) =
+            StructValue::construct(&class, vec![shared.clone()], HashMap::new()).unwrap()
+        else {
+            panic!("expected a struct");
+        };
+        if let Value::Array(items) = &shared {
+            items.borrow_mut().push(Value::Int(2));
+        }
+        let read = value.get("amount").unwrap();
+        if let Value::Array(items) = &read {
+            assert_eq!(items.borrow().len(), 1);
+            items.borrow_mut().push(Value::Int(3));
+        }
+        assert_eq!(
+            value.get("amount"),
+            Some(Value::Array(Rc::new(RefCell::new(vec![Value::Int(1)]))))
+        );
+    }
+
+    #[test]
+    fn with_copies_and_equality_is_by_value() {
+        let class = money();
+        let five = StructValue::construct(&class, vec![Value::Int(5)], HashMap::new()).unwrap();
+        let Value::Struct(five_ref) = &five else {
+            panic!("expected a struct");
+        };
+        let ten = five_ref.with(named(&[("amount", Value::Int(10))])).unwrap();
+        assert_ne!(five, ten);
+        assert_eq!(five_ref.get("amount"), Some(Value::Int(5)));
+        let again = StructValue::construct(&class, vec![Value::Int(5)], HashMap::new()).unwrap();
+        assert_eq!(five, again);
+    }
+}
@@ -0,0 +1,284 @@
+//! `soli introspect`: what a project defines, as data.
+//!
+//! Editor plugins and the dev error page REPL offer completion from this
+//! dump instead of embedding the type checker. It lists the top-level
+//! functions, classes (with their public fields and methods) and variables
+//! of every source file, the builtin signatures from the bundled stubs, and
+//! the app's route table when it has `config/routes.sl`.
+//!
+//! ```text
+//! {
+//!   "version": "0.9.0",
+//!   "files": [{"path": "app/models/user.sl", "functions": [...], "classes": [...], "variables": [...]}],
+//!   "builtins": {"functions": [...], "classes": [...], "variables": [...]},
+//!   "routes": [{"method": "GET", "path": "/users", "handler": "users#index", ...}]
+//! }
+//! ```
+//!
+//! A function is `{name, signature, params, returns, static, doc}`, with each
+//! parameter as `{name, type, default, block, variadic, keyword_only}`; a
+//! class is `{name, superclass, doc, fields, methods}`. Paths are relative to
+//! the project folder and files come in path order.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value as Json};
+
+use crate::lexer::Scanner;
+use crate::parser::Parser;
+use crate::types::stubs::{builtin_stubs, StubClass, StubField, StubFile, StubFunction};
+
+/// Folders of an app that hold code the rest of it can call. A folder with
+/// none of them is scanned whole.
+const APP_SOURCE_DIRS: &[&str] = &["app", "config", "lib"];
+
+/// The symbols one source file defines.
+#[derive(Debug)]
+pub struct FileSymbols {
+    /// Relative to the project folder, with `/` separators.
+    pub path: String,
+    pub stubs: StubFile,
+}
+
+/// Everything `soli introspect` reports for a project.
+#[derive(Debug)]
+pub struct Introspection {
+    pub files: Vec<FileSymbols>,
+    /// The route table as `soli routes --json` prints it; empty when the
+    /// folder isn't an app.
+    pub routes: Json,
+}
+
+/// Collect the symbols of every `.sl` file under `root` and, for an app, its
+/// routes. Files that don't parse are skipped with a warning, like a route
+/// table that fails to load: a partial dump still completes most names.
+pub fn introspect(root: &Path) -> Result<Introspection, String> {
+    if !root.is_dir() {
+        return Err(format!("Folder '{}' does not exist", root.display()));
+    }
+
+    let mut paths = Vec::new();
+    let app_dirs: Vec<PathBuf> = APP_SOURCE_DIRS
+        .iter()
+        .map(|dir| root.join(dir))
+        .filter(|dir| dir.is_dir())
+        .collect();
+    if app_dirs.is_empty() {
+        collect_sources(root, &mut paths);
+    } else {
+        for dir in &app_dirs {
+            collect_sources(dir, &mut paths);
+        }
+    }
+    paths.sort();
+
+    let mut files = Vec::new();
+    for path in paths {
+        let Ok(source) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let program = match Scanner::new(&source)
+            .scan_tokens()
+            .map_err(crate::error::SolilangError::from)
+            .and_then(|tokens| {
+                Parser::new(tokens)
+                    .parse()
+                    .map_err(crate::error::SolilangError::from)
+            }) {
+            Ok(program) => program,
+            Err(e) => {
+                eprintln!("Warning: skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let stubs = StubFile::from_program(&program, &source);
+        if stubs.is_empty() {
+            continue;
+        }
+        files.push(FileSymbols {
+            path: relative_path(root, &path),
+            stubs,
+        });
+    }
+
+    let routes = if root.join("config").join("routes.sl").is_file() {
+        match crate::serve::route_listing::collect_routes(root) {
+            Ok(listing) => crate::serve::route_listing::routes_json(&listing, None),
+            Err(e) => {
+                eprintln!("Warning: {}", e);
+                Json::Array(Vec::new())
+            }
+        }
+    } else {
+        Json::Array(Vec::new())
+    };
+
+    Ok(Introspection { files, routes })
+}
+
+impl Introspection {
+    /// The JSON document described in the module docs.
+    pub fn to_json(&self) -> Json {
+        json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "files": self
+                .files
+                .iter()
+                .map(|file| {
+                    let mut symbols = stubs_json(&file.stubs);
+                    symbols["path"] = json!(file.path);
+                    symbols
+                })
+                .collect::<Vec<_>>(),
+            "builtins": stubs_json(builtin_stubs()),
+            "routes": self.routes,
+        })
+    }
+
+    /// A one-screen summary, for a run without `--json`.
+    pub fn summary(&self) -> String {
+        let count =
+            |f: fn(&StubFile) -> usize| self.files.iter().map(|s| f(&s.stubs)).sum::<usize>();
+        let builtins = builtin_stubs();
+        format!(
+            "  {} files: {} functions, {} classes, {} variables\n  {} builtin functions, {} builtin classes\n  {} routes\n\n  Run with --json for the full dump.\n",
+            self.files.len(),
+            count(|s| s.functions.len()),
+            count(|s| s.classes.len()),
+            count(|s| s.globals.len()),
+            builtins.functions.len(),
+            builtins.classes.len(),
+            self.routes.as_array().map_or(0, Vec::len),
+        )
+    }
+}
+
+fn stubs_json(stubs: &StubFile) -> Json {
+    json!({
+        "functions": stubs.functions.iter().map(function_json).collect::<Vec<_>>(),
+        "classes": stubs.classes.iter().map(class_json).collect::<Vec<_>>(),
+        "variables": stubs.globals.iter().map(field_json).collect::<Vec<_>>(),
+    })
+}
+
+fn function_json(function: &StubFunction) -> Json {
+    json!({
+        "name": function.name,
+        "signature": function.signature(),
+        "params": function
+            .params
+            .iter()
+            .map(|param| json!({
+                "name": param.name,
+                "type": param.type_annotation.to_string(),
+                "default": param.default,
+                "block": param.is_block,
+                "variadic": param.is_variadic,
+                "keyword_only": param.is_keyword_only,
+            }))
+            .collect::<Vec<_>>(),
+        "returns": function.return_type.to_string(),
+        "static": function.is_static,
+        "doc": function.doc,
+    })
+}
+
+fn class_json(class: &StubClass) -> Json {
+    json!({
+        "name": class.name,
+        "superclass": class.superclass,
+        "doc": class.doc,
+        "fields": class.fields.iter().map(field_json).collect::<Vec<_>>(),
+        "methods": class.methods.iter().map(function_json).collect::<Vec<_>>(),
+    })
+}
+
+fn field_json(field: &StubField) -> Json {
+    json!({
+        "name": field.name,
+        "type": field.type_annotation.to_string(),
+        "static": field.is_static,
+        "doc": field.doc,
+    })
+}
+
+/// `.sl` files under `dir`, skipping hidden and `_`-prefixed folders and
+/// installed packages.
+fn collect_sources(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_file() {
+            if path.extension().and_then(|e| e.to_str()) == Some("sl")
+                && !crate::types::stubs::is_declaration_file(&path)
+            {
+                out.push(path);
+            }
+        } else if path.is_dir() {
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if name.starts_with('.') || name.starts_with('_') || name == "node_modules" {
+                continue;
+            }
+            collect_sources(&path, out);
+        }
+    }
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn dumps_symbols_builtins_and_routes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("app/models")).unwrap();
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::write(
+            root.join("app/models/user.sl"),
+            "# A signed-up user.\nclass User extends Model {\n    fn display_name(greeting: String = \"Hi\") -> String {\n        return greeting\n    }\n\n    private fn secret() {\n        return null\n    }\n}\n\nconst ADMIN_ROLE = \"admin\"\n\nfn find_admin(*, active: Bool = true) {\n    return null\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("app/models/broken.sl"), "fn (").unwrap();
+        fs::write(
+            root.join("config/routes.sl"),
+            "get(\"/users\", \"users#index\")\n",
+        )
+        .unwrap();
+
+        let json = introspect(root).unwrap().to_json();
+
+        let files = json["files"].as_array().unwrap();
+        assert_eq!(files.len(), 1);
+        let user = &files[0];
+        assert_eq!(user["path"], "app/models/user.sl");
+        assert_eq!(user["classes"][0]["name"], "User");
+        assert_eq!(user["classes"][0]["doc"], "A signed-up user.");
+        let methods = user["classes"][0]["methods"].as_array().unwrap();
+        assert_eq!(methods.len(), 1);
+        assert_eq!(
+            methods[0]["signature"],
+            "display_name(greeting: String = \"Hi\") -> String"
+        );
+        assert_eq!(user["functions"][0]["params"][0]["keyword_only"], true);
+        assert_eq!(user["variables"][0]["name"], "ADMIN_ROLE");
+
+        let builtins = json["builtins"]["functions"].as_array().unwrap();
+        assert!(builtins.iter().any(|f| f["name"] == "describe"));
+
+        assert_eq!(json["routes"][0]["path"], "/users");
+        assert_eq!(json["routes"][0]["handler"], "users#index");
+    }
+}
@@ -1,7 +1,7 @@
 //! Lexer/Scanner for Solilang source code.
 
 use crate::error::LexerError;
-use crate::lexer::token::{Token, TokenKind};
+use crate::lexer::token::{StringPart, Token, TokenKind};
 use crate::metrics::Metrics;
 use crate::span::Span;
 use std::time::Instant;
@@ -43,6 +43,11 @@ fn ends_value(kind: &TokenKind) -> bool {
     )
 }
 
+/// True when `c` can start an identifier.
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
 impl<'a> Scanner<'a> {
     pub fn new(source: &'a str) -> Self {
         Self {
@@ -136,6 +141,8 @@ impl<'a> Scanner<'a> {
                 if self.match_char('.') {
                     if self.match_char('.') {
                         Ok(self.make_token(TokenKind::Spread)) // ...
+                    } else if self.match_char('=') {
+                        Ok(self.make_token(TokenKind::RangeInclusive)) // ..=
                     } else {
                         Ok(self.make_token(TokenKind::Range)) // ..
                     }
@@ -165,6 +172,10 @@ impl<'a> Scanner<'a> {
                     } else {
                         Ok(self.make_token(TokenKind::NullishCoalescing))
                     }
+                } else if self.peek() == Some('.') && self.peek_next().is_some_and(is_ident_start) {
+                    // `user?.name` — same as `user&.name`
+                    self.advance();
+                    Ok(self.make_token(TokenKind::SafeNavigation))
                 } else {
                     Ok(self.make_token(TokenKind::Question))
                 }
@@ -414,66 +425,42 @@ impl<'a> Scanner<'a> {
     fn scan_string(&mut self) -> Result<Token, LexerError> {
         let start_position = self.current_pos;
         let start_line = self.line;
-        let _start_column = self.column;
         let quote_char = if self.current_pos > 0 {
             self.source[self.current_pos - 1..].chars().next().unwrap()
         } else {
             '"'
         };
         let mut value = String::new();
-        let mut has_interpolation = false;
-        let mut paren_depth = 0; // Track depth when inside interpolation
+        let mut parts = Vec::new();
 
         loop {
             match self.peek() {
                 None | Some('\n') => {
                     return Err(LexerError::unterminated_string(self.current_span()));
                 }
-                Some('"') => {
-                    // Only treat " as terminator if we're not inside parentheses in interpolation
-                    if has_interpolation && paren_depth > 0 {
-                        // Inside interpolation expression - " is just a character
-                        self.advance();
-                        value.push('"');
-                    } else if quote_char == '"' {
-                        // Not inside interpolation or paren_depth is 0 - this is the closing "
-                        self.advance();
-                        break;
-                    } else {
-                        // This is a " inside a single-quoted string - just a character
-                        self.advance();
-                        value.push('"');
-                    }
+                Some(c) if c == quote_char => {
+                    self.advance();
+                    break;
                 }
-                Some('\'') => {
-                    // Only treat ' as terminator if we're not inside parentheses in interpolation
-                    if has_interpolation && paren_depth > 0 {
-                        // Inside interpolation expression - ' is just a character
-                        self.advance();
-                        value.push('\'');
-                    } else if quote_char == '\'' {
-                        // Not inside interpolation or paren_depth is 0 - this is the closing '
-                        self.advance();
-                        break;
-                    } else {
-                        // This is a ' inside a double-quoted string - just a character
-                        self.advance();
-                        value.push('\'');
+                Some('#') if self.peek_next() == Some('{') => {
+                    self.advance(); // #
+                    self.advance(); // {
+                    let code_span =
+                        Span::new(self.current_pos, self.current_pos, self.line, self.column);
+                    let source = self.scan_interpolation_code()?;
+                    if source.trim().is_empty() {
+                        return Err(LexerError::empty_interpolation(code_span));
                     }
-                }
-                Some('#') => {
-                    // Check for #{ interpolation start
-                    if self.peek_next() == Some('{') {
-                        // Start of interpolation
-                        has_interpolation = true;
-                        paren_depth = 1; // Start counting braces
-                        value.push_str("#{");
-                        self.advance(); // #
-                        self.advance(); // {
-                    } else {
-                        value.push('#');
-                        self.advance();
+                    let span = Span::new(
+                        code_span.start_usize(),
+                        code_span.start_usize() + source.len(),
+                        code_span.line as usize,
+                        code_span.column as usize,
+                    );
+                    if !value.is_empty() {
+                        parts.push(StringPart::Literal(std::mem::take(&mut value)));
                     }
+                    parts.push(StringPart::Code { source, span });
                 }
                 Some('\\') => {
                     self.advance();
@@ -502,6 +489,11 @@ impl<'a> Scanner<'a> {
                             self.advance();
                             value.push('\'');
                         }
+                        // `\#{` writes a literal `#{` instead of interpolating.
+                        Some('#') => {
+                            self.advance();
+                            value.push('#');
+                        }
                         Some(c) => {
                             return Err(LexerError::invalid_escape(c, self.current_span()));
                         }
@@ -510,21 +502,6 @@ impl<'a> Scanner<'a> {
                         }
                     }
                 }
-                Some('{') => {
-                    if has_interpolation {
-                        paren_depth += 1;
-                    }
-                    self.advance();
-                    value.push('{');
-                }
-                Some('}') => {
-                    if has_interpolation && paren_depth > 0 {
-                        paren_depth -= 1;
-                    }
-                    // Don't break here - the " will break the loop when we see it
-                    self.advance();
-                    value.push('}');
-                }
                 Some(c) => {
                     self.advance();
                     value.push(c);
@@ -533,16 +510,85 @@ impl<'a> Scanner<'a> {
         }
 
         let end_position = self.current_pos;
-        let _end_line = self.line;
         let end_column = self.column;
         let span = Span::new(start_position, end_position, start_line, end_column);
 
-        if has_interpolation {
-            // Parse the string for interpolation markers
-            let parts = Self::parse_interpolation_parts(&value);
-            Ok(Token::new(TokenKind::InterpolatedString(parts), span))
-        } else {
+        if parts.is_empty() {
             Ok(Token::new(TokenKind::StringLiteral(value), span))
+        } else {
+            if !value.is_empty() {
+                parts.push(StringPart::Literal(value));
+            }
+            Ok(Token::new(TokenKind::InterpolatedString(parts), span))
+        }
+    }
+
+    /// Scan the expression of a `#{...}` interpolation (the `#{` already
+    /// consumed) up to its matching `}`, which is consumed but not returned.
+    /// Braces and string literals inside the expression — including strings
+    /// with interpolations of their own — are skipped as units, so
+    /// `"#{h["}"]}"` and `"#{"in #{name}"}"` both close where they should.
+    fn scan_interpolation_code(&mut self) -> Result<String, LexerError> {
+        let mut code = String::new();
+        let mut depth = 0usize;
+        loop {
+            match self.peek() {
+                None | Some('\n') => {
+                    return Err(LexerError::unterminated_string(self.current_span()));
+                }
+                Some('}') if depth == 0 => {
+                    self.advance();
+                    return Ok(code);
+                }
+                Some(quote @ ('"' | '\'')) => {
+                    self.advance();
+                    code.push(quote);
+                    self.copy_nested_string(quote, &mut code)?;
+                }
+                Some(c) => {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    self.advance();
+                    code.push(c);
+                }
+            }
+        }
+    }
+
+    /// Copy a string literal nested in interpolation code verbatim (escapes
+    /// and all — the expression is re-scanned later), through its closing quote.
+    fn copy_nested_string(&mut self, quote: char, code: &mut String) -> Result<(), LexerError> {
+        loop {
+            match self.peek() {
+                None | Some('\n') => {
+                    return Err(LexerError::unterminated_string(self.current_span()));
+                }
+                Some('\\') => {
+                    self.advance();
+                    code.push('\\');
+                    if let Some(c) = self.peek().filter(|c| *c != '\n') {
+                        self.advance();
+                        code.push(c);
+                    }
+                }
+                Some('#') if self.peek_next() == Some('{') => {
+                    self.advance();
+                    self.advance();
+                    code.push_str("#{");
+                    code.push_str(&self.scan_interpolation_code()?);
+                    code.push('}');
+                }
+                Some(c) => {
+                    self.advance();
+                    code.push(c);
+                    if c == quote {
+                        return Ok(());
+                    }
+                }
+            }
         }
     }
 
@@ -984,55 +1030,6 @@ impl<'a> Scanner<'a> {
         interpolations
     }
 
-    fn parse_interpolation_parts(s: &str) -> Vec<String> {
-        let mut parts = Vec::new();
-        let mut current = String::new();
-        let mut chars = s.chars().peekable();
-
-        while let Some(c) = chars.next() {
-            if c == '#' {
-                if chars.peek() == Some(&'{') {
-                    // Start of interpolation #{
-                    if !current.is_empty() {
-                        parts.push(current);
-                    }
-                    current = String::new();
-                    chars.next(); // consume {
-                    let mut brace_depth = 1;
-                    // Read until matching }
-                    for c2 in chars.by_ref() {
-                        if c2 == '{' {
-                            brace_depth += 1;
-                            current.push(c2);
-                        } else if c2 == '}' {
-                            brace_depth -= 1;
-                            if brace_depth == 0 {
-                                break;
-                            }
-                            current.push(c2);
-                        } else {
-                            current.push(c2);
-                        }
-                    }
-                    // The expression is in current - push it as-is for parser to handle
-                    // Add a special marker to indicate this is an expression
-                    parts.push(format!("#{{{}}}", current));
-                    current = String::new();
-                } else {
-                    current.push(c);
-                }
-            } else {
-                current.push(c);
-            }
-        }
-
-        if !current.is_empty() {
-            parts.push(current);
-        }
-
-        parts
-    }
-
     fn scan_number(&mut self, first: char) -> Result<Token, LexerError> {
         let mut value = String::from(first);
         let mut is_float = false;
@@ -1143,8 +1140,11 @@ impl<'a> Scanner<'a> {
         // and trailing ! (for bang methods like insert!, delete!). No keyword
         // carries such a suffix, so when present we skip the keyword lookup
         // entirely — the token is unconditionally an identifier.
+        // `name?.member` is safe navigation on `name`, not a predicate name.
         let mut has_suffix = false;
-        if self.peek() == Some('?') {
+        let safe_navigation =
+            self.peek_at(1) == Some('.') && self.peek_at(2).is_some_and(is_ident_start);
+        if self.peek() == Some('?') && !safe_navigation {
             value.push('?');
             self.advance();
             has_suffix = true;
@@ -1356,6 +1356,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_range_operators() {
+        assert_eq!(
+            scan("0..10 0..=10 ...xs"),
+            vec![
+                TokenKind::IntLiteral(0),
+                TokenKind::Range,
+                TokenKind::IntLiteral(10),
+                TokenKind::IntLiteral(0),
+                TokenKind::RangeInclusive,
+                TokenKind::IntLiteral(10),
+                TokenKind::Spread,
+                TokenKind::Identifier("xs".into()),
+                TokenKind::Eof,
+            ]
+        );
+    }
+
     #[test]
     fn test_pipeline() {
         assert_eq!(
@@ -1371,6 +1389,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_question_dot_safe_navigation() {
+        let ident = |s: &str| TokenKind::Identifier(s.to_string());
+        assert_eq!(
+            scan("user?.name"),
+            vec![
+                ident("user"),
+                TokenKind::SafeNavigation,
+                ident("name"),
+                TokenKind::Eof,
+            ]
+        );
+        assert_eq!(
+            scan("f()?.x"),
+            vec![
+                ident("f"),
+                TokenKind::LeftParen,
+                TokenKind::RightParen,
+                TokenKind::SafeNavigation,
+                ident("x"),
+                TokenKind::Eof,
+            ]
+        );
+        // Predicate names and ternaries are unaffected.
+        assert_eq!(scan("empty?")[0], ident("empty?"));
+        assert_eq!(scan("a ? b : c")[1], TokenKind::Question);
+    }
+
     #[test]
     fn test_comments() {
         assert_eq!(
@@ -1392,11 +1438,85 @@ mod tests {
         );
     }
 
+    fn interpolation_parts(source: &str) -> Vec<String> {
+        match scan(source).remove(0) {
+            TokenKind::InterpolatedString(parts) => parts
+                .into_iter()
+                .map(|p| match p {
+                    StringPart::Literal(s) => format!("lit:{}", s),
+                    StringPart::Code { source, .. } => format!("code:{}", source),
+                })
+                .collect(),
+            other => panic!("expected an interpolated string, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_interpolated_string() {
-        let tokens = scan(r#" "Hello #{name}!" "#);
-        println!("TEST OUTPUT: {:?}", tokens);
-        // Should have: Literal("Hello "), InterpolatedString marker, Literal("name"), Literal("!")
+        assert_eq!(
+            interpolation_parts(r##""Hello #{name}, you have #{count} items""##),
+            vec![
+                "lit:Hello ",
+                "code:name",
+                "lit:, you have ",
+                "code:count",
+                "lit: items"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interpolation_skips_nested_strings_and_braces() {
+        assert_eq!(
+            interpolation_parts(r##""a #{"in #{x}"} b #{h["}"]} #{ {"k": 1}["k"] }""##),
+            vec![
+                "lit:a ",
+                r##"code:"in #{x}""##,
+                "lit: b ",
+                r##"code:h["}"]"##,
+                "lit: ",
+                r##"code: {"k": 1}["k"] "##,
+            ]
+        );
+        // Escapes inside the expression stay raw for the parser to decode.
+        assert_eq!(
+            interpolation_parts(r##""#{"a\"b"}!""##),
+            vec![r##"code:"a\"b""##, "lit:!"]
+        );
+    }
+
+    #[test]
+    fn test_interpolation_escape_and_plain_braces() {
+        assert_eq!(
+            scan(r##""\#{x} {y} #z""##)[0],
+            TokenKind::StringLiteral("#{x} {y} #z".to_string())
+        );
+        assert_eq!(
+            interpolation_parts(r##""#{x} {""##),
+            vec!["code:x", "lit: {"]
+        );
+    }
+
+    #[test]
+    fn test_interpolation_code_span_and_errors() {
+        let tokens = Scanner::new("  \"ab#{x}\"").scan_tokens().unwrap();
+        match &tokens[0].kind {
+            TokenKind::InterpolatedString(parts) => match &parts[1] {
+                StringPart::Code { span, .. } => {
+                    assert_eq!((span.start, span.end, span.line, span.column), (7, 8, 1, 8));
+                }
+                other => panic!("expected code, got {:?}", other),
+            },
+            other => panic!("expected an interpolated string, got {:?}", other),
+        }
+        assert!(matches!(
+            Scanner::new("\"#{}\"").scan_tokens(),
+            Err(LexerError::EmptyInterpolation(_))
+        ));
+        assert!(matches!(
+            Scanner::new("\"#{x\"").scan_tokens(),
+            Err(LexerError::UnterminatedString(_))
+        ));
     }
 
     #[test]
@@ -10,6 +10,17 @@ pub struct SdqlInterpolation {
     pub end: usize,
 }
 
+/// One piece of an interpolated string literal: `"Hi #{name}!"` lexes as
+/// `[Literal("Hi "), Code("name"), Literal("!")]`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    /// Literal text, escapes already decoded.
+    Literal(String),
+    /// The raw source between `#{` and `}`, with the span of that source so
+    /// the parser can report errors inside it at their real position.
+    Code { source: String, span: Span },
+}
+
 /// All token types in Solilang.
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenKind {
@@ -18,8 +29,8 @@ pub enum TokenKind {
     FloatLiteral(f64),
     DecimalLiteral(String), // String representation of decimal value (e.g., "19.99")
     StringLiteral(String),
-    InterpolatedString(Vec<String>), // Parts for interpolation
-    BacktickString(String),          // Command substitution: `command`
+    InterpolatedString(Vec<StringPart>),
+    BacktickString(String), // Command substitution: `command`
     BoolLiteral(bool),
     SymbolLiteral(String), // :name
 
@@ -55,6 +66,7 @@ pub enum TokenKind {
     Implements,
     Interface,
     Enum,
+    Struct,
     New,
     This,
     SelfKeyword,
@@ -68,6 +80,7 @@ pub enum TokenKind {
     Catch,
     Finally,
     Throw,
+    Yield,
     Rescue,
     Not,
     Match,
@@ -139,10 +152,11 @@ pub enum TokenKind {
     Colon,
     Semicolon,
     Question,
-    Arrow,    // ->
-    FatArrow, // =>
-    Spread,   // ...
-    Range,    // ..
+    Arrow,          // ->
+    FatArrow,       // =>
+    Spread,         // ...
+    Range,          // ..
+    RangeInclusive, // ..=
 
     // Special
     Eof,
@@ -173,6 +187,7 @@ impl TokenKind {
             "implements" => Some(TokenKind::Implements),
             "interface" => Some(TokenKind::Interface),
             "enum" => Some(TokenKind::Enum),
+            "struct" => Some(TokenKind::Struct),
             "new" => Some(TokenKind::New),
             "this" => Some(TokenKind::This),
             "self" => Some(TokenKind::SelfKeyword),
@@ -188,6 +203,7 @@ impl TokenKind {
             "catch" => Some(TokenKind::Catch),
             "finally" | "ensure" => Some(TokenKind::Finally),
             "throw" => Some(TokenKind::Throw),
+            "yield" => Some(TokenKind::Yield),
             "rescue" => Some(TokenKind::Rescue),
             "not" => Some(TokenKind::Not),
             "and" => Some(TokenKind::And),
@@ -222,7 +238,14 @@ impl std::fmt::Display for TokenKind {
             TokenKind::DecimalLiteral(s) => write!(f, "{}", s),
             TokenKind::StringLiteral(s) => write!(f, "\"{}\"", s),
             TokenKind::InterpolatedString(parts) => {
-                write!(f, "interp\"{}\"", parts.join("...("))
+                write!(f, "interp\"")?;
+                for part in parts {
+                    match part {
+                        StringPart::Literal(s) => write!(f, "{}", s)?,
+                        StringPart::Code { source, .. } => write!(f, "#{{{}}}", source)?,
+                    }
+                }
+                write!(f, "\"")
             }
             TokenKind::BacktickString(s) => write!(f, "`{}`", s),
             TokenKind::SdqlBlock { query, .. } => {
@@ -251,6 +274,7 @@ impl std::fmt::Display for TokenKind {
             TokenKind::Implements => write!(f, "implements"),
             TokenKind::Interface => write!(f, "interface"),
             TokenKind::Enum => write!(f, "enum"),
+            TokenKind::Struct => write!(f, "struct"),
             TokenKind::New => write!(f, "new"),
             TokenKind::This => write!(f, "this"),
             TokenKind::SelfKeyword => write!(f, "self"),
@@ -264,6 +288,7 @@ impl std::fmt::Display for TokenKind {
             TokenKind::Catch => write!(f, "catch"),
             TokenKind::Finally => write!(f, "finally"),
             TokenKind::Throw => write!(f, "throw"),
+            TokenKind::Yield => write!(f, "yield"),
             TokenKind::Rescue => write!(f, "rescue"),
             TokenKind::Not => write!(f, "not"),
             TokenKind::Match => write!(f, "match"),
@@ -331,6 +356,7 @@ impl std::fmt::Display for TokenKind {
             TokenKind::FatArrow => write!(f, "=>"),
             TokenKind::Spread => write!(f, "..."),
             TokenKind::Range => write!(f, ".."),
+            TokenKind::RangeInclusive => write!(f, "..="),
             TokenKind::Eof => write!(f, "EOF"),
         }
     }
@@ -526,6 +552,7 @@ mod tests {
             TokenKind::Implements,
             TokenKind::Interface,
             TokenKind::Enum,
+            TokenKind::Struct,
             TokenKind::New,
             TokenKind::This,
             TokenKind::SelfKeyword,
@@ -539,6 +566,7 @@ mod tests {
             TokenKind::Catch,
             TokenKind::Finally,
             TokenKind::Throw,
+            TokenKind::Yield,
             TokenKind::Rescue,
             TokenKind::Not,
             TokenKind::Match,
@@ -611,16 +639,21 @@ mod tests {
         assert_eq!(TokenKind::FatArrow.to_string(), "=>");
         assert_eq!(TokenKind::Spread.to_string(), "...");
         assert_eq!(TokenKind::Range.to_string(), "..");
+        assert_eq!(TokenKind::RangeInclusive.to_string(), "..=");
         assert_eq!(TokenKind::Eof.to_string(), "EOF");
     }
 
     #[test]
     fn display_interpolated_string_joins_parts() {
-        let kind = TokenKind::InterpolatedString(vec!["a=".into(), "x".into(), " b".into()]);
-        // Concrete format is implementation detail — pin the salient bits.
-        let s = kind.to_string();
-        assert!(s.starts_with("interp\""));
-        assert!(s.contains("a=...(x...( b"));
+        let kind = TokenKind::InterpolatedString(vec![
+            StringPart::Literal("a=".into()),
+            StringPart::Code {
+                source: "x".into(),
+                span: Span::default(),
+            },
+            StringPart::Literal(" b".into()),
+        ]);
+        assert_eq!(kind.to_string(), "interp\"a=#{x} b\"");
     }
 
     #[test]
@@ -10,8 +10,10 @@ pub mod ast;
 pub mod bundle;
 pub mod cdp;
 pub mod cleanup;
+pub mod codemod;
 pub mod compiled_cache;
 pub mod coverage;
+pub mod dap;
 pub mod desktop;
 pub mod embedding;
 pub mod error;
@@ -20,6 +22,7 @@ pub mod generation;
 pub mod graph;
 pub mod inflect;
 pub mod interpreter;
+pub mod introspect;
 pub mod lexer;
 pub mod lint;
 pub mod live;
@@ -27,6 +30,7 @@ pub mod lsp;
 pub mod metrics;
 pub mod migration;
 pub mod module;
+pub mod optimize;
 pub mod parser;
 pub mod platform;
 pub mod regex_cache;
@@ -35,14 +39,22 @@ pub mod repl_highlight;
 pub mod repl_simple;
 pub mod repl_tui;
 pub mod scaffold;
+pub mod self_bench;
 pub mod serve;
 pub mod solidb_http;
+pub mod source_map;
 pub mod span;
 pub mod template;
 pub mod types;
 pub mod virtual_fs;
 pub mod vm;
 
+/// Lets the unit tests exercise allocation budgets, as the `soli` binary does.
+#[cfg(test)]
+#[global_allocator]
+static TEST_ALLOCATOR: interpreter::CountingAlloc<std::alloc::System> =
+    interpreter::CountingAlloc(std::alloc::System);
+
 use ast::expr::Argument;
 use error::SolilangError;
 use interpreter::Value;
@@ -72,12 +84,33 @@ pub fn run_file(path: &std::path::Path, type_check: bool) -> Result<(), Solilang
     run_with_path(&source, Some(path), type_check)
 }
 
+/// [`run_file`] with the interpreter's profiler on. The profile comes back
+/// even when the program fails. Powers `soli profile`.
+pub fn run_file_profiled(
+    path: &std::path::Path,
+    type_check: bool,
+) -> (interpreter::profiler::Profile, Result<(), SolilangError>) {
+    interpreter::profiler::start();
+    let result = run_file(path, type_check);
+    (interpreter::profiler::finish(), result)
+}
+
 /// Run a Solilang program with optional source path for module resolution.
 pub fn run_with_path(
     source: &str,
     source_path: Option<&std::path::Path>,
     type_check: bool,
 ) -> Result<(), SolilangError> {
+    run_with_budget(source, source_path, type_check, None)
+}
+
+/// Lex, parse, resolve imports (given a path), optionally type-check and
+/// constant-fold `source`, ready for the interpreter.
+fn prepare_program(
+    source: &str,
+    source_path: Option<&std::path::Path>,
+    type_check: bool,
+) -> Result<ast::Program, SolilangError> {
     // Lexing
     let tokens = lexer::Scanner::new(source).scan_tokens()?;
 
@@ -100,15 +133,152 @@ pub fn run_with_path(
     if type_check {
         let mut checker = types::TypeChecker::new();
         if let Err(errors) = checker.check(&program) {
-            return Err(errors.into_iter().next().unwrap().into());
+            return Err(errors.into());
         }
     }
 
+    optimize::fold_constants(&mut program);
+    Ok(program)
+}
+
+/// [`run_with_path`] under `budget`: code that runs past it stops with a
+/// `RuntimeError` whose `is_budget_exceeded()` is true. Powers `soli -e
+/// --timeout/--max-steps/--max-allocations`.
+pub fn run_with_budget(
+    source: &str,
+    source_path: Option<&std::path::Path>,
+    type_check: bool,
+    budget: Option<interpreter::EvalBudget>,
+) -> Result<(), SolilangError> {
+    let program = prepare_program(source, source_path, type_check)?;
+
     // Execute with tree-walking interpreter
     let mut interpreter = interpreter::Interpreter::new();
     interpreter::builtins::mailer::ensure_prelude(&mut interpreter);
+    interpreter::builtins::events::ensure_prelude(&mut interpreter);
+    interpreter::builtins::export::ensure_prelude(&mut interpreter);
+    interpreter.set_budget(budget);
+    let result = interpreter.interpret(&program);
+    interpreter.set_budget(None);
+    interpreter::builtins::lifecycle::run_hooks(
+        &mut interpreter,
+        interpreter::builtins::lifecycle::Hook::AtExit,
+    );
+    result?;
+
+    Ok(())
+}
+
+/// Run a benchmark file and time each `bench` it declares — on the VM when
+/// `use_vm`, else the interpreter. Powers `soli bench`. The file itself
+/// failing is an `Err`; a benchmark whose body fails is reported in its slot
+/// and the rest still run.
+pub fn run_benches(
+    source: &str,
+    source_path: Option<&std::path::Path>,
+    use_vm: bool,
+    config: &interpreter::builtins::bench::BenchConfig,
+) -> Result<Vec<interpreter::builtins::bench::BenchOutcome>, SolilangError> {
+    use interpreter::builtins::bench;
+
+    let _ = bench::take_benches();
+    let span = span::Span::new(0, 0, 1, 1);
+    let mut results = Vec::new();
+    if use_vm {
+        let mut vm_instance = seeded_vm();
+        let module = compiled_cache::get_or_compile(source, source_path, false)?;
+        let executed = vm_instance.execute(&module.main);
+        vm_instance.reset();
+        executed?;
+        for declared in bench::take_benches() {
+            let result = bench::measure(&declared.name, config, || {
+                vm_instance
+                    .invoke_callable(declared.body.clone(), Vec::new(), span)
+                    .map(drop)
+                    .map_err(|e| e.to_string())
+            });
+            vm_instance.reset();
+            results.push((declared.name, result));
+        }
+    } else {
+        let program = prepare_program(source, source_path, false)?;
+        let mut interpreter = interpreter::Interpreter::new();
+        interpreter::builtins::mailer::ensure_prelude(&mut interpreter);
+        interpreter::builtins::events::ensure_prelude(&mut interpreter);
+        interpreter::builtins::export::ensure_prelude(&mut interpreter);
+        if let Some(path) = source_path {
+            interpreter.set_source_path(path.to_path_buf());
+        }
+        interpreter.interpret(&program)?;
+        for declared in bench::take_benches() {
+            let result = bench::measure(&declared.name, config, || {
+                interpreter
+                    .call_value(declared.body.clone(), Vec::new(), span)
+                    .map(drop)
+                    .map_err(|e| e.to_string())
+            });
+            results.push((declared.name, result));
+        }
+    }
+    Ok(results)
+}
+
+/// Run a `tasks.sl` file on the interpreter, returning it with the tasks it
+/// declared.
+fn evaluate_tasks(
+    source: &str,
+    source_path: Option<&std::path::Path>,
+) -> Result<
+    (
+        interpreter::Interpreter,
+        Vec<interpreter::builtins::task::TaskDefinition>,
+    ),
+    SolilangError,
+> {
+    use interpreter::builtins::task;
+
+    let _ = task::take_tasks();
+    let program = prepare_program(source, source_path, false)?;
+    let mut interpreter = interpreter::Interpreter::new();
+    interpreter::builtins::mailer::ensure_prelude(&mut interpreter);
+    interpreter::builtins::events::ensure_prelude(&mut interpreter);
+    interpreter::builtins::export::ensure_prelude(&mut interpreter);
+    if let Some(path) = source_path {
+        interpreter.set_source_path(path.to_path_buf());
+    }
     interpreter.interpret(&program)?;
+    Ok((interpreter, task::take_tasks()))
+}
 
+/// The tasks a `tasks.sl` file declares, for `soli task` to plan with.
+pub fn load_tasks(
+    source: &str,
+    source_path: Option<&std::path::Path>,
+) -> Result<Vec<interpreter::builtins::task::TaskSpec>, SolilangError> {
+    let (_, tasks) = evaluate_tasks(source, source_path)?;
+    Ok(tasks.into_iter().map(|declared| declared.spec).collect())
+}
+
+/// Run one task's body from a `tasks.sl` file. Its dependencies are the
+/// caller's business.
+pub fn run_task(
+    source: &str,
+    source_path: Option<&std::path::Path>,
+    name: &str,
+) -> Result<(), SolilangError> {
+    let (mut interpreter, tasks) = evaluate_tasks(source, source_path)?;
+    let span = span::Span::new(0, 0, 1, 1);
+    let Some(declared) = tasks
+        .into_iter()
+        .find(|declared| declared.spec.name == name)
+    else {
+        return Err(error::RuntimeError::General {
+            message: format!("no task named '{}'", name),
+            span,
+        }
+        .into());
+    };
+    interpreter.call_value(declared.body, Vec::new(), span)?;
     Ok(())
 }
 
@@ -122,6 +292,67 @@ pub fn type_check_source(
     source: &str,
     source_path: Option<&std::path::Path>,
 ) -> Result<Vec<String>, Vec<SolilangError>> {
+    let (program, mut checker) = type_checker_for(source, source_path)?;
+    let (result, warnings) = checker.check_collecting_warnings(&program);
+    match result {
+        Ok(()) => Ok(warnings.into_iter().map(|w| w.to_string()).collect()),
+        Err(errs) => Err(errs.into_iter().map(Into::into).collect()),
+    }
+}
+
+/// Type-check `source` like [`type_check_source`], returning every error
+/// and warning as a [`types::Diagnostic`] located in `path` (what
+/// `soli check --format json|sarif` reports).
+pub fn type_check_diagnostics(
+    source: &str,
+    source_path: Option<&std::path::Path>,
+    path: &str,
+) -> Vec<types::Diagnostic> {
+    let (program, mut checker) = match type_checker_for(source, source_path) {
+        Ok(setup) => setup,
+        Err(errs) => {
+            return errs
+                .iter()
+                .flat_map(|e| types::Diagnostic::from_error(path, e))
+                .collect()
+        }
+    };
+    let (result, warnings) = checker.check_collecting_warnings(&program);
+    let mut diagnostics: Vec<types::Diagnostic> = result
+        .err()
+        .unwrap_or_default()
+        .iter()
+        .map(|e| types::Diagnostic::from_type_error(path, e))
+        .collect();
+    diagnostics.extend(
+        warnings
+            .iter()
+            .map(|w| types::Diagnostic::from_type_error(path, w)),
+    );
+    diagnostics
+}
+
+/// How much of a program the type checker can type: the share of its
+/// expressions whose inferred type isn't `Any`. Imported modules are
+/// resolved (when a path is given) but don't count. Type errors don't fail
+/// it — what they leave untyped counts as `Any`. Powers
+/// `soli types --coverage`.
+pub fn type_coverage_source(
+    source: &str,
+    source_path: Option<&std::path::Path>,
+) -> Result<types::TypeCoverage, Vec<SolilangError>> {
+    let (program, checker) = type_checker_for(source, source_path)?;
+    let mut checker = checker.with_coverage();
+    let _ = checker.check(&program);
+    Ok(checker.take_coverage())
+}
+
+/// Parse `source`, resolve its imports (when a path is given) and set up a
+/// checker with the project, package and module stubs that apply to it.
+fn type_checker_for(
+    source: &str,
+    source_path: Option<&std::path::Path>,
+) -> Result<(ast::Program, types::TypeChecker), Vec<SolilangError>> {
     let tokens = lexer::Scanner::new(source)
         .scan_tokens()
         .map_err(|e| vec![e.into()])?;
@@ -129,6 +360,19 @@ pub fn type_check_source(
         .parse()
         .map_err(|e| vec![e.into()])?;
 
+    let stub_error = |e: String| -> Vec<SolilangError> {
+        vec![error::RuntimeError::General {
+            message: format!("Stub error: {}", e),
+            span: span::Span::new(0, 0, 1, 1),
+        }
+        .into()]
+    };
+    let mut stubs = Vec::new();
+    if let Some(path) = source_path {
+        let base_dir = path.parent().unwrap_or(std::path::Path::new("."));
+        stubs.extend(types::stubs::project_stubs(base_dir).map_err(stub_error)?);
+    }
+
     if let Some(path) = source_path.filter(|_| has_imports(&program)) {
         let base_dir = path.parent().unwrap_or(std::path::Path::new("."));
         let mut resolver = module::ModuleResolver::new(base_dir);
@@ -139,18 +383,103 @@ pub fn type_check_source(
             }
             .into()]
         })?;
+        stubs.extend(
+            types::stubs::package_stubs(base_dir, resolver.loaded_modules()).map_err(stub_error)?,
+        );
+        stubs.extend(types::stubs::module_stubs(resolver.loaded_modules()).map_err(stub_error)?);
     }
 
     let mut checker = types::TypeChecker::new();
-    let (result, warnings) = checker.check_collecting_warnings(&program);
-    match result {
-        Ok(()) => Ok(warnings.into_iter().map(|w| w.to_string()).collect()),
-        Err(errs) => Err(errs.into_iter().map(Into::into).collect()),
+    for stub in stubs {
+        checker.add_stubs(stub);
     }
+    Ok((program, checker))
+}
+
+/// Type-check the embedded code of the template at `path` (`.slv`/`.erb`)
+/// against the locals its app's `render(...)` calls pass. Powers
+/// `soli check --templates`. Error spans point into the template source.
+/// Templates outside an `app/views/` tree, and code the core parser reads
+/// differently from the template engine, are skipped.
+pub fn type_check_template(
+    source: &str,
+    path: &std::path::Path,
+) -> Result<Vec<String>, Vec<SolilangError>> {
+    let (code, lines) = template::parser::extract_lintable_code_with_lines(source)
+        .map_err(|e| vec![SolilangError::Template(e)])?;
+    let Ok(program) = parse(&code) else {
+        return Ok(Vec::new());
+    };
+    let Some(scope) = lint::templates::template_scope(path, &program) else {
+        return Ok(Vec::new());
+    };
+    let mut checker = types::TypeChecker::new();
+    match checker.check_template(&program, &scope) {
+        Ok(()) => Ok(Vec::new()),
+        Err(errs) => Err(errs
+            .into_iter()
+            .map(|e| {
+                e.map_spans(&mut |span| template_span(source, &code, &lines, span))
+                    .into()
+            })
+            .collect()),
+    }
+}
+
+/// Map `span`, a position in the code extracted from the template `source`
+/// (`lines` giving each code line's template line), back onto the template:
+/// the code line is found in its template line and the offset carried over.
+fn template_span(source: &str, code: &str, lines: &[usize], span: span::Span) -> span::Span {
+    let start = span.start_usize().min(code.len());
+    let code_line_start = code[..start].rfind('\n').map_or(0, |i| i + 1);
+    let code_line_end = code[start..].find('\n').map_or(code.len(), |i| start + i);
+    let code_line = &code[code_line_start..code_line_end];
+    let index = code[..start].matches('\n').count();
+    let line = lines.get(index).copied().unwrap_or(index + 1);
+
+    let line_start: usize = source
+        .split_inclusive('\n')
+        .take(line.saturating_sub(1))
+        .map(str::len)
+        .sum();
+    let line_text = source[line_start..].lines().next().unwrap_or("");
+    let offset = match line_text.find(code_line.trim_start()) {
+        Some(found) => {
+            let leading = code_line.len() - code_line.trim_start().len();
+            (found + (start - code_line_start)).saturating_sub(leading)
+        }
+        None => line_text.len() - line_text.trim_start().len(),
+    };
+    let new_start = line_start + offset;
+    let width = span.end_usize().saturating_sub(span.start_usize());
+    let column = line_text[..offset.min(line_text.len())].chars().count() + 1;
+    span::Span::new(new_start, new_start + width, line, column)
 }
 
 /// Run a Solilang program through the bytecode VM (faster execution).
+///
+/// A `.slc` file runs as compiled. For a source file, the `.slc` that
+/// `soli build` left next to it is used instead of compiling, as long as
+/// none of the files it was built from changed since.
 pub fn run_file_vm(path: &std::path::Path, type_check: bool) -> Result<(), SolilangError> {
+    if path
+        .extension()
+        .is_some_and(|ext| ext == vm::bytecode::BYTECODE_EXTENSION)
+    {
+        let data = std::fs::read(path).map_err(|e| error::RuntimeError::General {
+            message: format!("Failed to read file '{}': {}", path.display(), e),
+            span: span::Span::new(0, 0, 1, 1),
+        })?;
+        let module = vm::bytecode::decode(&data).map_err(|e| error::RuntimeError::General {
+            message: format!("{}: {}", path.display(), e),
+            span: span::Span::new(0, 0, 1, 1),
+        })?;
+        return execute_module_vm(&module);
+    }
+    if let Some(module) = vm::bytecode::load_fresh(&vm::bytecode::cache_path(path)) {
+        return execute_module_vm(&module);
+    }
+
     let source = std::fs::read_to_string(path).map_err(|e| error::RuntimeError::General {
         message: format!("Failed to read file '{}': {}", path.display(), e),
         span: span::Span::new(0, 0, 1, 1),
@@ -166,7 +495,24 @@ pub fn run_vm(
     type_check: bool,
 ) -> Result<(), SolilangError> {
     let module = compiled_cache::get_or_compile(source, source_path, type_check)?;
+    execute_module_vm(&module)
+}
+
+fn execute_module_vm(module: &vm::CompiledModule) -> Result<(), SolilangError> {
+    let mut vm_instance = seeded_vm();
+
+    // Execute the compiled module
+    let result = vm_instance.execute(&module.main);
+    interpreter::builtins::lifecycle::run_hooks_vm(
+        &mut vm_instance,
+        interpreter::builtins::lifecycle::Hook::AtExit,
+    );
+    result?;
 
+    Ok(())
+}
+
+fn seeded_vm() -> vm::Vm {
     let mut vm_instance = vm::Vm::new();
 
     // Seed the VM with the full builtin environment, exactly like a
@@ -176,17 +522,203 @@ pub fn run_vm(
     // production-mode execution instead of a hand-rolled subset.
     let mut interpreter = interpreter::Interpreter::new();
     interpreter::builtins::mailer::ensure_prelude(&mut interpreter);
+    interpreter::builtins::events::ensure_prelude(&mut interpreter);
+    interpreter::builtins::export::ensure_prelude(&mut interpreter);
     let all_globals = interpreter.environment.borrow().get_all_bindings();
     for (name, value) in all_globals {
         vm_instance.globals.insert(name, value);
     }
+    vm_instance
+}
 
-    // Execute the compiled module
-    vm_instance.execute(&module.main)?;
+/// Run a spec file's tests on the bytecode VM — the `soli test --vm`
+/// counterpart of [`run_with_path_and_coverage`], without coverage.
+///
+/// Rather than reading the suites off the AST, the program runs as compiled
+/// and each `describe` body is then called to populate its suite, so every
+/// test, hook and suite-level `let` is VM bytecode. `preamble_files` run
+/// first, in the same VM.
+pub fn run_tests_vm(
+    source: &str,
+    source_path: Option<&std::path::Path>,
+    preamble_files: &[(std::path::PathBuf, String)],
+) -> (i64, Result<(), SolilangError>) {
+    interpreter::builtins::test_dsl::clear_test_suites();
+    interpreter::builtins::lifecycle::clear_hooks();
+    interpreter::builtins::events::clear_subscriptions();
+    interpreter::builtins::export::clear_sources();
+    let _ = interpreter::builtins::assertions::get_and_reset_assertion_count();
+    let _ = interpreter::builtins::test_dsl::take_test_outcomes();
+
+    let result = run_tests_vm_inner(source, source_path, preamble_files);
+
+    interpreter::builtins::test_dsl::clear_test_suites();
+    let assertion_count = interpreter::builtins::assertions::get_and_reset_assertion_count();
+    (assertion_count, result)
+}
+
+fn run_tests_vm_inner(
+    source: &str,
+    source_path: Option<&std::path::Path>,
+    preamble_files: &[(std::path::PathBuf, String)],
+) -> Result<(), SolilangError> {
+    use interpreter::builtins::test_dsl;
+
+    let mut vm_instance = seeded_vm();
+    for (preamble_path, preamble_source) in preamble_files {
+        let module = compiled_cache::get_or_compile(preamble_source, Some(preamble_path), false)?;
+        vm_instance.execute(&module.main)?;
+        vm_instance.reset();
+    }
+    let module = compiled_cache::get_or_compile(source, source_path, false)?;
+
+    // Top-level `describe`s attach to this root while the program runs.
+    test_dsl::open_suite(test_dsl::TestSuite::new(
+        String::new(),
+        Vec::new(),
+        None,
+        None,
+    ));
+    let executed = vm_instance.execute(&module.main);
+    vm_instance.reset();
+    let root = test_dsl::close_suite();
+    executed?;
+
+    let suites = root.map(|root| root.nested_suites).unwrap_or_default();
+    let (failed_count, failed_tests) = execute_test_suites_vm(&mut vm_instance, &suites, "");
+    interpreter::builtins::lifecycle::run_hooks_vm(
+        &mut vm_instance,
+        interpreter::builtins::lifecycle::Hook::AtExit,
+    );
+
+    if failed_count > 0 {
+        let error_msg = if failed_tests.len() == 1 {
+            format!("Test failed: {}", failed_tests[0])
+        } else {
+            format!(
+                "{} tests failed:\n  - {}",
+                failed_count,
+                failed_tests.join("\n  - ")
+            )
+        };
+        return Err(SolilangError::Runtime(error::RuntimeError::General {
+            message: error_msg,
+            span: span::Span::new(0, 0, 1, 1),
+        }));
+    }
 
     Ok(())
 }
 
+/// The VM side of [`execute_test_suites`]: call each suite's body to
+/// populate it, then run its hooks and tests the same way and in the same
+/// order. The body's locals are captured by the closures it declares, so
+/// no rebinding is needed.
+fn execute_test_suites_vm(
+    vm_instance: &mut vm::Vm,
+    suites: &[interpreter::builtins::test_dsl::TestSuite],
+    scope: &str,
+) -> (i64, Vec<String>) {
+    use interpreter::builtins::test_dsl;
+
+    let mut failed_count = 0i64;
+    let mut failed_tests = Vec::new();
+    fn call_with(
+        vm_instance: &mut vm::Vm,
+        callable: &Value,
+        args: Vec<Value>,
+    ) -> Result<Value, error::RuntimeError> {
+        let result =
+            vm_instance.invoke_callable(callable.clone(), args, span::Span::new(0, 0, 1, 1));
+        vm_instance.reset();
+        result
+    }
+    fn call(vm_instance: &mut vm::Vm, callable: &Value) -> Result<Value, error::RuntimeError> {
+        call_with(vm_instance, callable, Vec::new())
+    }
+
+    for declared in seeded_order(suites, scope) {
+        test_dsl::open_suite(test_dsl::TestSuite {
+            nested_suites: Vec::new(),
+            ..declared.clone()
+        });
+        let setup = declared
+            .body
+            .as_ref()
+            .map_or(Ok(Value::Null), |body| call(vm_instance, body));
+        let Some(suite) = test_dsl::close_suite() else {
+            continue;
+        };
+        // Skipped whole: its body only ran to declare its tests, which are
+        // reported; nothing that went wrong there counts as a failure.
+        if suite.skipped {
+            for test in suite.tests.iter().filter(|t| t.is_selected()) {
+                test_dsl::record_not_run(test, test_dsl::TestMark::Skip, "");
+            }
+            let (nested_failed, mut nested_errors) =
+                execute_test_suites_vm(vm_instance, &suite.nested_suites, &suite.name);
+            failed_count += nested_failed;
+            failed_tests.append(&mut nested_errors);
+            continue;
+        }
+        if let Err(e) = setup {
+            failed_count += 1;
+            failed_tests.push(format!("{}: {}", suite.name, e));
+            continue;
+        }
+        // Its tests are only known now that the body has run, and those of
+        // the suites nested in it once theirs has.
+        if suite.nested_suites.is_empty() && !suite.has_selected_tests() {
+            continue;
+        }
+
+        if let Some(before_all) = &suite.before_all {
+            let _ = call(vm_instance, before_all);
+        }
+
+        for test in seeded_order(&suite.tests, &suite.name) {
+            if !test.is_selected() {
+                continue;
+            }
+            if let Some(mark) = test.mark {
+                test_dsl::record_not_run(test, mark, "");
+                continue;
+            }
+            crate::interpreter::builtins::datetime::helpers::unfreeze_datetime();
+            crate::interpreter::builtins::rng::reseed(&[&suite.name, &test.name]);
+            crate::interpreter::builtins::browser::set_active_viewport(suite.viewport);
+            crate::interpreter::builtins::browser::reset_browser_state();
+
+            if let Some(before_each) = &suite.before_each {
+                let _ = call(vm_instance, before_each);
+            }
+
+            if let Err(e) = call_with(vm_instance, &test.body, test.args.clone()) {
+                if let Some((mark, reason)) = test_dsl::not_run_reason(&e) {
+                    test_dsl::record_not_run(test, mark, &reason);
+                } else {
+                    failed_count += 1;
+                    failed_tests.push(format!("{}: {}", test.name, e));
+                }
+            }
+
+            if let Some(after_each) = &suite.after_each {
+                let _ = call(vm_instance, after_each);
+            }
+        }
+
+        let (nested_failed, mut nested_errors) =
+            execute_test_suites_vm(vm_instance, &suite.nested_suites, &suite.name);
+        failed_count += nested_failed;
+        failed_tests.append(&mut nested_errors);
+
+        if let Some(after_all) = &suite.after_all {
+            let _ = call(vm_instance, after_all);
+        }
+    }
+    (failed_count, failed_tests)
+}
+
 /// Run a Solilang program with optional coverage tracking.
 ///
 /// `preamble_files` is a list of `(path, source)` pairs that are loaded into the
@@ -205,7 +737,11 @@ pub fn run_with_path_and_coverage(
     preamble_files: &[(std::path::PathBuf, String)],
 ) -> (i64, Result<(), SolilangError>) {
     interpreter::builtins::test_dsl::clear_test_suites();
+    interpreter::builtins::lifecycle::clear_hooks();
+    interpreter::builtins::events::clear_subscriptions();
+    interpreter::builtins::export::clear_sources();
     let _ = interpreter::builtins::assertions::get_and_reset_assertion_count();
+    let _ = interpreter::builtins::test_dsl::take_test_outcomes();
 
     let result = run_with_path_and_coverage_inner(
         source,
@@ -233,6 +769,8 @@ fn run_with_path_and_coverage_inner(
         interpreter.set_coverage_tracker(tracker.clone());
     }
     interpreter::builtins::mailer::ensure_prelude(&mut interpreter);
+    interpreter::builtins::events::ensure_prelude(&mut interpreter);
+    interpreter::builtins::export::ensure_prelude(&mut interpreter);
 
     for (preamble_path, preamble_source) in preamble_files {
         let tokens = lexer::Scanner::new(preamble_source).scan_tokens()?;
@@ -254,10 +792,11 @@ fn run_with_path_and_coverage_inner(
         if type_check {
             let mut checker = types::TypeChecker::new();
             if let Err(errors) = checker.check(&program) {
-                return Err(errors.into_iter().next().unwrap().into());
+                return Err(errors.into());
             }
         }
 
+        optimize::fold_constants(&mut program);
         interpreter.set_source_path(preamble_path.clone());
         interpreter.interpret(&program)?;
     }
@@ -280,10 +819,11 @@ fn run_with_path_and_coverage_inner(
     if type_check {
         let mut checker = types::TypeChecker::new();
         if let Err(errors) = checker.check(&program) {
-            return Err(errors.into_iter().next().unwrap().into());
+            return Err(errors.into());
         }
     }
 
+    optimize::fold_constants(&mut program);
     let test_suites = extract_test_definitions(&program);
 
     if let Some(path) = source_file_path {
@@ -291,7 +831,13 @@ fn run_with_path_and_coverage_inner(
     }
     interpreter.interpret(&program)?;
 
-    let (failed_count, failed_tests) = execute_test_suites(&mut interpreter, &test_suites)?;
+    let globals = interpreter.environment.clone();
+    let suites_result = execute_test_suites(&mut interpreter, &test_suites, "", &globals);
+    interpreter::builtins::lifecycle::run_hooks(
+        &mut interpreter,
+        interpreter::builtins::lifecycle::Hook::AtExit,
+    );
+    let (failed_count, failed_tests) = suites_result?;
 
     if failed_count > 0 {
         let error_msg = if failed_tests.len() == 1 {
@@ -320,10 +866,10 @@ fn extract_test_definitions(
         if let ast::StmtKind::Expression(expr) = &stmt.kind {
             if let ast::ExprKind::Call { callee, arguments } = &expr.kind {
                 // Check if this is a describe call
-                if let ast::ExprKind::Variable(name) = &callee.kind {
-                    if name == "describe" || name == "context" {
-                        if let Some(mut suite) = extract_suite_from_call(name, arguments, stmt.span)
-                        {
+                if let ast::ExprKind::Variable(name, _) = &callee.kind {
+                    let skipped = matches!(name.as_str(), "xdescribe" | "xcontext");
+                    if skipped || name == "describe" || name == "context" {
+                        if let Some(mut suite) = extract_suite_from_call(arguments, None, skipped) {
                             push_viewport_down(&mut suite);
                             suites.push(suite);
                         }
@@ -396,52 +942,61 @@ fn extract_viewport(arguments: &[Argument]) -> Option<interpreter::builtins::bro
     Some(viewport)
 }
 
+/// The name, `tags: [...]` and body lambda of a `describe`/`test` call:
+/// `describe("X", fn() { ... })`, `describe("X") do ... end`, either with a
+/// `tags: ["slow"]` named argument. Only literals, as for `viewport(...)`:
+/// the tests are read off the AST, not evaluated.
+fn extract_declaration(arguments: &[Argument]) -> Option<(String, Vec<String>, &ast::Expr)> {
+    let name = match arguments.first()? {
+        Argument::Positional(expr) => match &expr.kind {
+            ast::ExprKind::StringLiteral(s) => s.clone(),
+            _ => return None,
+        },
+        Argument::Named(_) | Argument::Block(_) | Argument::Spread(_) => return None,
+    };
+    let mut tags = Vec::new();
+    let mut body = None;
+    for arg in &arguments[1..] {
+        match arg {
+            Argument::Positional(expr) | Argument::Block(expr) if body.is_none() => {
+                if !matches!(expr.kind, ast::ExprKind::Lambda { .. }) {
+                    return None;
+                }
+                body = Some(expr);
+            }
+            Argument::Named(named) if named.name == "tags" => {
+                if let ast::ExprKind::Array(items) = &named.value.kind {
+                    for item in items {
+                        match &item.kind {
+                            ast::ExprKind::StringLiteral(tag) | ast::ExprKind::Symbol(tag) => {
+                                tags.push(tag.to_string())
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some((name, tags, body?))
+}
+
 fn extract_suite_from_call(
-    _name: &str,
     arguments: &[Argument],
-    _span: span::Span,
+    parent: Option<&interpreter::builtins::test_dsl::TestSuite>,
+    skipped: bool,
 ) -> Option<interpreter::builtins::test_dsl::TestSuite> {
-    if arguments.len() < 2 {
+    let (name, tags, body) = extract_declaration(arguments)?;
+    let ast::ExprKind::Lambda { body, .. } = &body.kind else {
         return None;
-    }
-
-    // First argument should be the suite name
-    let first_arg = match &arguments[0] {
-        Argument::Positional(expr) => expr,
-        Argument::Named(_) => return None,
-        Argument::Block(_) => return None,
-    };
-    let suite_name = match &first_arg.kind {
-        ast::ExprKind::StringLiteral(s) => s.clone(),
-        _ => return None,
     };
 
-    // Second argument should be a lambda (the suite body) — accept either a
-    // positional lambda (`describe("X", fn() { ... })`) or a trailing block
-    // (`describe("X") do ... end`).
-    let second_arg = match &arguments[1] {
-        Argument::Positional(expr) => expr,
-        Argument::Block(expr) => expr,
-        Argument::Named(_) => return None,
-    };
-    let suite_body = match &second_arg.kind {
-        ast::ExprKind::Lambda { body, .. } => body.clone(),
-        _ => return None,
-    };
-
-    let mut suite = interpreter::builtins::test_dsl::TestSuite {
-        name: suite_name,
-        tests: Vec::new(),
-        before_each: None,
-        after_each: None,
-        before_all: None,
-        after_all: None,
-        nested_suites: Vec::new(),
-        viewport: None,
-    };
+    let mut suite = interpreter::builtins::test_dsl::TestSuite::new(name, tags, parent, None);
+    suite.skipped |= skipped;
 
     // Extract tests and nested suites from the lambda body
-    extract_tests_from_block(&suite_body, &mut suite);
+    extract_tests_from_block(body, &mut suite);
 
     Some(suite)
 }
@@ -453,7 +1008,7 @@ fn first_callback_expr(arguments: &[Argument]) -> Option<&ast::Expr> {
     match arguments.first()? {
         Argument::Positional(expr) => Some(expr),
         Argument::Block(expr) => Some(expr),
-        Argument::Named(_) => None,
+        Argument::Named(_) | Argument::Spread(_) => None,
     }
 }
 
@@ -461,41 +1016,79 @@ fn extract_tests_from_block(
     statements: &[ast::Stmt],
     suite: &mut interpreter::builtins::test_dsl::TestSuite,
 ) {
+    use interpreter::builtins::test_dsl;
+
     for stmt in statements {
-        if let ast::StmtKind::Expression(expr) = &stmt.kind {
-            if let ast::ExprKind::Call { callee, arguments } = &expr.kind {
-                if let ast::ExprKind::Variable(name) = &callee.kind {
-                    if name == "test" || name == "it" || name == "specify" {
-                        if let Some(test) = extract_test_from_call(arguments, stmt.span) {
-                            suite.tests.push(test);
-                        }
-                    } else if name == "describe" || name == "context" {
-                        if let Some(nested) = extract_suite_from_call(name, arguments, stmt.span) {
-                            suite.nested_suites.push(nested);
-                        }
-                    } else if name == "before_each" {
-                        if let Some(callback) = first_callback_expr(arguments) {
-                            suite.before_each = Some(ast_expr_to_value(callback));
-                        }
-                    } else if name == "after_each" {
-                        if let Some(callback) = first_callback_expr(arguments) {
-                            suite.after_each = Some(ast_expr_to_value(callback));
-                        }
-                    } else if name == "before_all" {
-                        if let Some(callback) = first_callback_expr(arguments) {
-                            suite.before_all = Some(ast_expr_to_value(callback));
-                        }
-                    } else if name == "after_all" {
-                        if let Some(callback) = first_callback_expr(arguments) {
-                            suite.after_all = Some(ast_expr_to_value(callback));
-                        }
-                    } else if name == "viewport" {
-                        if let Some(viewport) = extract_viewport(arguments) {
-                            suite.viewport = Some(viewport);
-                        }
+        let call = match &stmt.kind {
+            ast::StmtKind::Expression(expr) => match &expr.kind {
+                ast::ExprKind::Call { callee, arguments } => match &callee.kind {
+                    ast::ExprKind::Variable(name, _) => Some((name.as_str(), arguments)),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        };
+        let Some((name, arguments)) = call else {
+            suite.scope.push(stmt.clone());
+            continue;
+        };
+        match name {
+            "test" | "it" | "specify" => {
+                if let Some(test) = extract_test_from_call(arguments, stmt.span, suite) {
+                    suite.tests.push(test);
+                }
+            }
+            "xtest" | "xit" | "xspecify" => {
+                if let Some(test) = extract_test_from_call(arguments, stmt.span, suite) {
+                    suite.tests.push(test.marked(test_dsl::TestMark::Skip));
+                }
+            }
+            "todo" => {
+                if let Some(Argument::Positional(expr)) = arguments.first() {
+                    if let ast::ExprKind::StringLiteral(name) = &expr.kind {
+                        let test = test_dsl::TestDefinition::new(
+                            name.clone(),
+                            Vec::new(),
+                            Value::Null,
+                            suite,
+                        );
+                        suite.tests.push(test.marked(test_dsl::TestMark::Todo));
                     }
                 }
             }
+            "describe" | "context" | "xdescribe" | "xcontext" => {
+                let skipped = name.starts_with('x');
+                if let Some(nested) = extract_suite_from_call(arguments, Some(suite), skipped) {
+                    suite.nested_suites.push(nested);
+                }
+            }
+            "before_each" => {
+                if let Some(callback) = first_callback_expr(arguments) {
+                    suite.before_each = Some(ast_expr_to_value(callback));
+                }
+            }
+            "after_each" => {
+                if let Some(callback) = first_callback_expr(arguments) {
+                    suite.after_each = Some(ast_expr_to_value(callback));
+                }
+            }
+            "before_all" => {
+                if let Some(callback) = first_callback_expr(arguments) {
+                    suite.before_all = Some(ast_expr_to_value(callback));
+                }
+            }
+            "after_all" => {
+                if let Some(callback) = first_callback_expr(arguments) {
+                    suite.after_all = Some(ast_expr_to_value(callback));
+                }
+            }
+            "viewport" => {
+                if let Some(viewport) = extract_viewport(arguments) {
+                    suite.viewport = Some(viewport);
+                }
+            }
+            _ => suite.scope.push(stmt.clone()),
         }
     }
 }
@@ -503,65 +1096,33 @@ fn extract_tests_from_block(
 fn extract_test_from_call(
     arguments: &[Argument],
     span: span::Span,
+    suite: &interpreter::builtins::test_dsl::TestSuite,
 ) -> Option<interpreter::builtins::test_dsl::TestDefinition> {
-    if arguments.len() < 2 {
+    let (name, tags, body) = extract_declaration(arguments)?;
+    let ast::ExprKind::Lambda {
+        params,
+        return_type,
+        body,
+        ..
+    } = &body.kind
+    else {
         return None;
-    }
-
-    let first_arg = match &arguments[0] {
-        Argument::Positional(expr) => expr,
-        Argument::Named(_) => return None,
-        Argument::Block(_) => return None,
-    };
-    let test_name = match &first_arg.kind {
-        ast::ExprKind::StringLiteral(s) => s.clone(),
-        _ => return None,
-    };
-
-    // Try to get second argument as either Positional(lambda) or Block
-    let test_body = match &arguments[1] {
-        Argument::Positional(expr) => match &expr.kind {
-            ast::ExprKind::Lambda {
-                params,
-                return_type,
-                body,
-            } => create_function_value(
-                params.clone(),
-                return_type.as_deref().cloned(),
-                body.clone(),
-                span,
-            ),
-            _ => return None,
-        },
-        Argument::Block(block_expr) => {
-            // Convert block expression to lambda function
-            match &block_expr.kind {
-                ast::ExprKind::Lambda {
-                    params,
-                    return_type,
-                    body,
-                } => create_function_value(
-                    params.clone(),
-                    return_type.as_deref().cloned(),
-                    body.clone(),
-                    span,
-                ),
-                _ => return None,
-            }
-        }
-        Argument::Named(_) => return None,
     };
-
-    Some(interpreter::builtins::test_dsl::TestDefinition {
-        name: test_name,
-        body: test_body,
-    })
+    let body = create_function_value(
+        params.clone(),
+        return_type.as_deref().cloned(),
+        body.clone(),
+        span,
+    );
+    Some(interpreter::builtins::test_dsl::TestDefinition::new(
+        name, tags, body, suite,
+    ))
 }
 
 fn create_function_value(
     params: Vec<ast::stmt::Parameter>,
     return_type: Option<ast::types::TypeAnnotation>,
-    body: Vec<ast::Stmt>,
+    body: std::sync::Arc<ast::Body>,
     span: span::Span,
 ) -> Value {
     use interpreter::value::Function;
@@ -577,6 +1138,8 @@ fn create_function_value(
         params,
         return_type,
         body,
+        decorators: Vec::new(),
+        is_async: false,
         span,
     };
     let closure = Rc::new(RefCell::new(env));
@@ -589,6 +1152,7 @@ fn ast_expr_to_value(expr: &ast::Expr) -> Value {
             params,
             return_type,
             body,
+            ..
         } => create_function_value(
             params.clone(),
             return_type.as_deref().cloned(),
@@ -599,22 +1163,85 @@ fn ast_expr_to_value(expr: &ast::Expr) -> Value {
     }
 }
 
+/// Run `suites`, each in a scope nested in `env` (the program's globals for
+/// top-level suites, the enclosing suite's scope for nested ones).
 fn execute_test_suites(
     interpreter: &mut interpreter::Interpreter,
     suites: &[interpreter::builtins::test_dsl::TestSuite],
+    scope: &str,
+    env: &std::rc::Rc<std::cell::RefCell<interpreter::environment::Environment>>,
 ) -> Result<(i64, Vec<String>), error::RuntimeError> {
+    use interpreter::builtins::test_dsl;
+    use interpreter::executor::ControlFlow;
+
     let mut failed_count = 0i64;
     let mut failed_tests = Vec::new();
 
-    for suite in suites {
+    for suite in seeded_order(suites, scope) {
+        // Filtered out whole: don't run its body or hooks for nothing.
+        if !suite.has_selected_tests() && !may_select_cases(suite) {
+            continue;
+        }
+        // Skipped whole: its tests are reported, no hook or test runs.
+        if suite.skipped {
+            skip_suite(interpreter, suite, env);
+            continue;
+        }
+        // The suite body runs once, like the closure it is; its tests and
+        // hooks are then bound to the scope it leaves behind. `each` tables
+        // are only known once it has: they add their cases to the suite
+        // opened around it.
+        let suite_env = std::rc::Rc::new(std::cell::RefCell::new(
+            interpreter::environment::Environment::with_enclosing(env.clone()),
+        ));
+        test_dsl::open_suite(test_dsl::TestSuite {
+            tests: Vec::new(),
+            nested_suites: Vec::new(),
+            ..suite.clone()
+        });
+        let setup = match interpreter.execute_block_in(&suite.scope, suite_env.clone()) {
+            Ok(ControlFlow::Throw(value)) => Err(format!("uncaught exception: {}", value)),
+            Ok(_) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        };
+        let cases = test_dsl::close_suite().map(|s| s.tests).unwrap_or_default();
+        if let Err(e) = setup {
+            failed_count += 1;
+            failed_tests.push(format!("{}: {}", suite.name, e));
+            continue;
+        }
+        // Rebind the test bodies to the suite scope, so the suite's own
+        // `let`s and the top-level `def` functions (e.g.
+        // register_test_user) are both visible. The cases' body is a closure
+        // of that scope already.
+        let tests: Vec<test_dsl::TestDefinition> = suite
+            .tests
+            .iter()
+            .map(|test| test_dsl::TestDefinition {
+                body: rebind_closure(&test.body, &suite_env),
+                ..test.clone()
+            })
+            .chain(cases)
+            .collect();
+
         // Run before_all if defined
         if let Some(before_all) = &suite.before_all {
-            let rebound = rebind_closure(before_all, &interpreter.environment);
+            let rebound = rebind_closure(before_all, &suite_env);
             let _ = interpreter.call_value(rebound, Vec::new(), span::Span::new(0, 0, 1, 1));
         }
 
-        for test in &suite.tests {
+        for test in seeded_order(&tests, &suite.name) {
+            if !test.is_selected() {
+                continue;
+            }
+            if let Some(mark) = test.mark {
+                test_dsl::record_not_run(test, mark, "");
+                continue;
+            }
             crate::interpreter::builtins::datetime::helpers::unfreeze_datetime();
+            // Keyed on the test's own name, so under `--seed` its random
+            // values don't shift when the shuffled order puts it elsewhere.
+            crate::interpreter::builtins::rng::reseed(&[&suite.name, &test.name]);
             // The browser outlives a single test on purpose — relaunching one
             // per test would dominate the runtime — so the errors it collected
             // must be cleared, or the first failing page fails every test after
@@ -625,44 +1252,200 @@ fn execute_test_suites(
 
             // Run before_each if defined
             if let Some(before_each) = &suite.before_each {
-                let rebound = rebind_closure(before_each, &interpreter.environment);
+                let rebound = rebind_closure(before_each, &suite_env);
                 let _ = interpreter.call_value(rebound, Vec::new(), span::Span::new(0, 0, 1, 1));
             }
 
-            // Rebind test body closure to interpreter's environment so
-            // top-level `def` functions (e.g. register_test_user) are accessible.
-            let test_body = rebind_closure(&test.body, &interpreter.environment);
-
             // Execute the test body and track failures
-            let result = interpreter.call_value(test_body, Vec::new(), span::Span::new(0, 0, 1, 1));
+            let result = interpreter.call_value(
+                test.body.clone(),
+                test.args.clone(),
+                span::Span::new(0, 0, 1, 1),
+            );
 
             if let Err(e) = result {
-                failed_count += 1;
-                failed_tests.push(format!("{}: {}", test.name, e));
+                if let Some((mark, reason)) = test_dsl::not_run_reason(&e) {
+                    test_dsl::record_not_run(test, mark, &reason);
+                } else {
+                    failed_count += 1;
+                    failed_tests.push(format!("{}: {}", test.name, e));
+                }
             }
 
             // Run after_each if defined
             if let Some(after_each) = &suite.after_each {
-                let rebound = rebind_closure(after_each, &interpreter.environment);
+                let rebound = rebind_closure(after_each, &suite_env);
                 let _ = interpreter.call_value(rebound, Vec::new(), span::Span::new(0, 0, 1, 1));
             }
         }
 
         // Run nested suites
         let (nested_failed, mut nested_errors) =
-            execute_test_suites(interpreter, &suite.nested_suites)?;
+            execute_test_suites(interpreter, &suite.nested_suites, &suite.name, &suite_env)?;
         failed_count += nested_failed;
         failed_tests.append(&mut nested_errors);
 
         // Run after_all if defined
         if let Some(after_all) = &suite.after_all {
-            let rebound = rebind_closure(after_all, &interpreter.environment);
+            let rebound = rebind_closure(after_all, &suite_env);
             let _ = interpreter.call_value(rebound, Vec::new(), span::Span::new(0, 0, 1, 1));
         }
     }
     Ok((failed_count, failed_tests))
 }
 
+/// Report the selected tests of a skipped suite, and of the suites nested in
+/// it, as skipped. No test or hook of it runs, and neither does its body —
+/// unless it has an `each` table the filter may select, whose cases are only
+/// known once the body has run. Even then nothing that goes wrong there
+/// counts as a failure.
+fn skip_suite(
+    interpreter: &mut interpreter::Interpreter,
+    suite: &interpreter::builtins::test_dsl::TestSuite,
+    env: &std::rc::Rc<std::cell::RefCell<interpreter::environment::Environment>>,
+) {
+    use interpreter::builtins::test_dsl;
+
+    if !may_select_cases(suite) {
+        test_dsl::record_skipped_suite(suite);
+        return;
+    }
+    let suite_env = std::rc::Rc::new(std::cell::RefCell::new(
+        interpreter::environment::Environment::with_enclosing(env.clone()),
+    ));
+    test_dsl::open_suite(test_dsl::TestSuite {
+        tests: Vec::new(),
+        nested_suites: Vec::new(),
+        ..suite.clone()
+    });
+    let _ = interpreter.execute_block_in(&suite.scope, suite_env.clone());
+    let cases = test_dsl::close_suite().map(|s| s.tests).unwrap_or_default();
+    for test in suite.tests.iter().chain(&cases).filter(|t| t.is_selected()) {
+        test_dsl::record_not_run(test, test.mark.unwrap_or(test_dsl::TestMark::Skip), "");
+    }
+    for nested in &suite.nested_suites {
+        skip_suite(interpreter, nested, &suite_env);
+    }
+}
+
+/// Whether the filter may select a case of an `each`/`it_each` table in
+/// `suite` or a suite nested in it: tests that only exist once the body has
+/// run. A table of literals is checked case by case; any other one can't be
+/// known without running the body, so it may.
+fn may_select_cases(suite: &interpreter::builtins::test_dsl::TestSuite) -> bool {
+    use interpreter::builtins::test_dsl::TestDefinition;
+
+    let may_select = |stmt: &ast::Stmt| {
+        let ast::StmtKind::Expression(expr) = &stmt.kind else {
+            return false;
+        };
+        let ast::ExprKind::Call { callee, arguments } = &expr.kind else {
+            return false;
+        };
+        let ast::ExprKind::Variable(builtin, _) = &callee.kind else {
+            return false;
+        };
+        let named = match builtin.as_str() {
+            "each" => false,
+            "it_each" | "test_each" => true,
+            _ => return false,
+        };
+        literal_cases(arguments, named, suite)
+            .is_none_or(|cases| cases.iter().any(TestDefinition::is_selected))
+    };
+    suite.scope.iter().any(may_select) || suite.nested_suites.iter().any(may_select_cases)
+}
+
+/// The cases an `each`/`it_each` call in `suite` declares, when its name,
+/// table and tags are all literals.
+fn literal_cases(
+    arguments: &[Argument],
+    named: bool,
+    suite: &interpreter::builtins::test_dsl::TestSuite,
+) -> Option<Vec<interpreter::builtins::test_dsl::TestDefinition>> {
+    use interpreter::builtins::test_dsl;
+
+    let mut positional = arguments.iter().filter_map(|arg| match arg {
+        Argument::Positional(expr) => Some(expr),
+        _ => None,
+    });
+    let name = if named {
+        match &positional.next()?.kind {
+            ast::ExprKind::StringLiteral(name) => Some(name.as_str()),
+            _ => return None,
+        }
+    } else {
+        None
+    };
+    let ast::ExprKind::Array(table) = &positional.next()?.kind else {
+        return None;
+    };
+    let mut tags = Vec::new();
+    for arg in arguments {
+        if let Argument::Named(named) = arg {
+            if named.name != "tags" {
+                continue;
+            }
+            let ast::ExprKind::Array(items) = &named.value.kind else {
+                return None;
+            };
+            for item in items {
+                match &item.kind {
+                    ast::ExprKind::StringLiteral(tag) | ast::ExprKind::Symbol(tag) => {
+                        tags.push(tag.to_string())
+                    }
+                    _ => return None,
+                }
+            }
+        }
+    }
+    table
+        .iter()
+        .map(|case| {
+            let args = test_dsl::case_args(literal_value(case)?);
+            let name = test_dsl::case_name(name, &args);
+            Some(test_dsl::TestDefinition::new(
+                name,
+                tags.clone(),
+                Value::Null,
+                suite,
+            ))
+        })
+        .collect()
+}
+
+/// The value of a literal expression, arrays of literals included.
+fn literal_value(expr: &ast::Expr) -> Option<Value> {
+    Some(match &expr.kind {
+        ast::ExprKind::IntLiteral(n) => Value::Int(*n),
+        ast::ExprKind::FloatLiteral(n) => Value::Float(*n),
+        ast::ExprKind::StringLiteral(s) => Value::String(s.clone().into()),
+        ast::ExprKind::Symbol(s) => Value::Symbol(s.clone().into()),
+        ast::ExprKind::BoolLiteral(b) => Value::Bool(*b),
+        ast::ExprKind::Null => Value::Null,
+        ast::ExprKind::Array(items) => {
+            let items = items
+                .iter()
+                .map(literal_value)
+                .collect::<Option<Vec<_>>>()?;
+            Value::Array(std::rc::Rc::new(std::cell::RefCell::new(items)))
+        }
+        _ => return None,
+    })
+}
+
+/// The order to run `items` in. Declaration order normally; under
+/// `soli test --seed N`, a shuffle derived from the seed and `scope` (the
+/// enclosing suite's name), so the same seed replays the same order.
+fn seeded_order<'a, T>(items: &'a [T], scope: &str) -> Vec<&'a T> {
+    use rand::seq::SliceRandom;
+    let mut order: Vec<&T> = items.iter().collect();
+    if let Some(mut rng) = interpreter::builtins::rng::derived_rng(&["order", scope]) {
+        order.shuffle(&mut rng);
+    }
+    order
+}
+
 /// Rebind a test function's closure to use the interpreter's environment,
 /// so that top-level definitions (def, let) are accessible inside tests.
 fn rebind_closure(
@@ -678,10 +1461,13 @@ fn rebind_closure(
                 body: func.body.clone(),
                 closure: env.clone(),
                 is_method: func.is_method,
+                is_generator: func.is_generator,
+                is_async: func.is_async,
                 span: func.span,
                 source_path: func.source_path.clone(),
                 defining_superclass: func.defining_superclass.clone(),
                 return_type: func.return_type.clone(),
+                decorators: func.decorators.clone(),
                 cached_env: std::cell::RefCell::new(None),
                 jit_cache: std::cell::RefCell::new(None),
             };
@@ -709,9 +1495,30 @@ pub fn parse(source: &str) -> Result<ast::Program, SolilangError> {
 
 /// Lint source code and return diagnostics.
 pub fn lint(source: &str) -> Result<Vec<lint::LintDiagnostic>, SolilangError> {
+    lint_with_rules(source, &lint::LintConfig::default())
+}
+
+/// [`lint`] under a project's `[lint]` config: its opt-in rules are checked
+/// too, and its levels applied.
+pub fn lint_with_config(
+    source: &str,
+    config: &lint::LintConfig,
+) -> Result<Vec<lint::LintDiagnostic>, SolilangError> {
+    Ok(config.apply(lint_with_rules(source, config)?))
+}
+
+fn lint_with_rules(
+    source: &str,
+    config: &lint::LintConfig,
+) -> Result<Vec<lint::LintDiagnostic>, SolilangError> {
     let tokens = lexer::Scanner::new(source).scan_tokens()?;
-    let program = parser::Parser::new(tokens).parse()?;
-    Ok(lint::Linter::new(source).lint(&program))
+    let program = parser::Parser::new(tokens)
+        .keeping_platform_code()
+        .parse()?;
+    Ok(lint::Linter::new(source)
+        .with_opt_in_rules(&config.opt_in_rules())
+        .with_csrf_skips(config.csrf_skips())
+        .lint(&program))
 }
 
 /// Lint source with the file path available to path-sensitive rules.
@@ -722,11 +1529,34 @@ pub fn lint(source: &str) -> Result<Vec<lint::LintDiagnostic>, SolilangError> {
 /// `style/empty-block` is dropped for templates because control-flow bodies
 /// that contain only HTML legitimately have no Soli statements.
 pub fn lint_file(source: &str, path: &str) -> Result<Vec<lint::LintDiagnostic>, SolilangError> {
-    if !path.ends_with(".slv") {
+    lint_file_with_rules(source, path, &lint::LintConfig::default())
+}
+
+/// [`lint_file`] under a project's `[lint]` config: its opt-in rules are
+/// checked too, and its levels applied.
+pub fn lint_file_with_config(
+    source: &str,
+    path: &str,
+    config: &lint::LintConfig,
+) -> Result<Vec<lint::LintDiagnostic>, SolilangError> {
+    Ok(config.apply(lint_file_with_rules(source, path, config)?))
+}
+
+fn lint_file_with_rules(
+    source: &str,
+    path: &str,
+    config: &lint::LintConfig,
+) -> Result<Vec<lint::LintDiagnostic>, SolilangError> {
+    let opt_in = config.opt_in_rules();
+    if !path.ends_with(".slv") && !path.ends_with(".erb") {
         let tokens = lexer::Scanner::new(source).scan_tokens()?;
-        let program = parser::Parser::new(tokens).parse()?;
+        let program = parser::Parser::new(tokens)
+            .keeping_platform_code()
+            .parse()?;
         return Ok(lint::Linter::new(source)
             .with_file_path(path)
+            .with_opt_in_rules(&opt_in)
+            .with_csrf_skips(config.csrf_skips())
             .lint(&program));
     }
 
@@ -742,14 +1572,27 @@ pub fn lint_file(source: &str, path: &str) -> Result<Vec<lint::LintDiagnostic>,
     let Ok(tokens) = lexer::Scanner::new(&code).scan_tokens() else {
         return Ok(Vec::new());
     };
-    let Ok(program) = parser::Parser::new(tokens).parse() else {
+    let Ok(program) = parser::Parser::new(tokens).keeping_platform_code().parse() else {
         return Ok(Vec::new());
     };
 
-    let mut diagnostics = lint::Linter::new(&code).with_file_path(path).lint(&program);
+    let mut diagnostics = lint::Linter::new(&code)
+        .with_file_path(path)
+        .with_opt_in_rules(&opt_in)
+        .lint(&program);
     // HTML-only control-flow bodies (`<% if x %>…markup…<% end %>`) extract to
     // empty blocks; that isn't a real empty block in the template.
     diagnostics.retain(|d| d.rule != "style/empty-block");
+    // Cross-file checks against the app's controllers, helpers and partials.
+    diagnostics.extend(lint::templates::check_template(
+        path, source, &code, &program,
+    ));
+    diagnostics.extend(lint::rules::security::check_template_source(source, &code));
+    // Fixes point into the extracted code, not the template.
+    for d in &mut diagnostics {
+        d.fix = None;
+    }
+    diagnostics.sort_by_key(|d| (d.span.line, d.span.column));
     Ok(diagnostics)
 }
 
@@ -861,3 +1704,73 @@ mod suite_extraction_tests {
         assert!(extracted[0].viewport.is_none());
     }
 }
+
+#[cfg(test)]
+mod bench_tests {
+    use super::*;
+    use interpreter::builtins::bench::BenchConfig;
+
+    #[test]
+    fn both_engines_time_each_bench_and_report_failures_in_place() {
+        let source = r#"
+            let words = ["a", "b"]
+            bench("join") do
+                words.join(",")
+            end
+            bench("fails") do
+                undefined_thing()
+            end
+        "#;
+        let config = BenchConfig {
+            warmup: std::time::Duration::ZERO,
+            measure: std::time::Duration::ZERO,
+            iterations: Some(20),
+        };
+        for use_vm in [false, true] {
+            let results = run_benches(source, None, use_vm, &config).unwrap();
+            let names: Vec<_> = results.iter().map(|(name, _)| name.as_str()).collect();
+            assert_eq!(names, ["join", "fails"]);
+            let join = results[0].1.as_ref().unwrap();
+            assert_eq!(join.iterations, 20);
+            assert!(join.allocations_per_iter > 0.0, "vm: {}", use_vm);
+            assert!(results[1].1.is_err());
+        }
+    }
+}
+
+#[cfg(test)]
+mod task_tests {
+    use super::*;
+
+    #[test]
+    fn tasks_declare_their_options_and_run_one_body_at_a_time() {
+        let source = r#"
+            task("assets", deps: "deps", inputs: ["src/*.scss"], outputs: ["public/app.css"], desc: "Compile CSS") do
+                print("assets")
+            end
+            task("deps") do
+                print("deps")
+            end
+            task("broken") do
+                undefined_thing()
+            end
+        "#;
+        let specs = load_tasks(source, None).unwrap();
+        let names: Vec<_> = specs.iter().map(|spec| spec.name.as_str()).collect();
+        assert_eq!(names, ["assets", "deps", "broken"]);
+        assert_eq!(specs[0].deps, ["deps"]);
+        assert_eq!(specs[0].inputs, ["src/*.scss"]);
+        assert_eq!(specs[0].outputs, ["public/app.css"]);
+        assert_eq!(specs[0].desc.as_deref(), Some("Compile CSS"));
+
+        assert!(run_task(source, None, "deps").is_ok());
+        assert!(run_task(source, None, "broken").is_err());
+        assert!(run_task(source, None, "missing").is_err());
+
+        let bad = load_tasks(r#"task("x", needs: ["y"]) do end"#, None);
+        assert!(bad
+            .unwrap_err()
+            .to_string()
+            .contains("unknown option 'needs'"));
+    }
+}
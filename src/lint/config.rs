@@ -0,0 +1,218 @@
+//! Per-project rule levels, from the `[lint]` section of soli.toml.
+//!
+//! A key is a rule (`"style/line-length"`), a category (`style`) or
+//! `default`, and its value is `off`, `warn` or `error`. The most specific
+//! key wins; a rule nothing mentions is a warning. An opt-in rule
+//! (`lint::OPT_IN_RULES`) is off until its own key turns it on. `soli lint`
+//! drops the `off` findings and exits non-zero only for `error` ones.
+//!
+//! The config also carries what path-sensitive rules need from the rest of
+//! the project, so that no rule reads other files itself: the `skip_csrf`
+//! patterns of `config/application.sl`, for `security/missing-csrf`.
+//!
+//! ```toml
+//! [lint]
+//! default = "error"
+//! style = "warn"
+//! "style/line-length" = "off"
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::lint::rules::security::csrf_skip_patterns;
+use crate::lint::{LintDiagnostic, Severity, OPT_IN_RULES};
+use crate::module::Package;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    Off,
+    Warn,
+    Error,
+}
+
+impl LintLevel {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "off" => Some(Self::Off),
+            "warn" => Some(Self::Warn),
+            "error" => Some(Self::Error),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    levels: BTreeMap<String, LintLevel>,
+    csrf_skips: Vec<String>,
+}
+
+impl LintConfig {
+    /// The levels declared by a package's `[lint]` section.
+    pub fn from_package(package: &Package) -> Result<Self, String> {
+        let mut levels = BTreeMap::new();
+        for (key, value) in &package.lint {
+            let level = LintLevel::parse(value).ok_or_else(|| {
+                format!(
+                    "lint.{}: unknown level '{}' (expected off, warn or error)",
+                    key, value
+                )
+            })?;
+            levels.insert(key.clone(), level);
+        }
+        Ok(Self {
+            levels,
+            csrf_skips: Vec::new(),
+        })
+    }
+
+    /// The config of the soli.toml at or above `start_dir`; the default
+    /// (every rule a warning) when there is none. The `skip_csrf` patterns
+    /// come from the nearest `config/application.sl` at or above it.
+    pub fn find(start_dir: &Path) -> Result<Self, String> {
+        let mut config = match Package::find(start_dir) {
+            Some(manifest) => {
+                let package = Package::load(&manifest)
+                    .map_err(|e| format!("{}: {}", manifest.display(), e))?;
+                Self::from_package(&package)
+                    .map_err(|e| format!("{}: {}", manifest.display(), e))?
+            }
+            None => Self::default(),
+        };
+        config.csrf_skips = start_dir
+            .ancestors()
+            .map(|dir| dir.join("config").join("application.sl"))
+            .find(|path| path.is_file())
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|source| crate::parse(&source).ok())
+            .map(|program| csrf_skip_patterns(&program.statements))
+            .unwrap_or_default();
+        Ok(config)
+    }
+
+    /// The `skip_csrf` patterns the app's `config/application.sl` declares.
+    pub fn csrf_skips(&self) -> &[String] {
+        &self.csrf_skips
+    }
+
+    /// The level of `rule`: its own entry, else its category's, else
+    /// `default`, else `warn`. An opt-in rule only has its own entry, and is
+    /// `off` without one.
+    pub fn level(&self, rule: &str) -> LintLevel {
+        if OPT_IN_RULES.contains(&rule) {
+            return self.levels.get(rule).copied().unwrap_or(LintLevel::Off);
+        }
+        let category = rule.split_once('/').map(|(c, _)| c);
+        self.levels
+            .get(rule)
+            .or_else(|| category.and_then(|c| self.levels.get(c)))
+            .or_else(|| self.levels.get("default"))
+            .copied()
+            .unwrap_or(LintLevel::Warn)
+    }
+
+    /// The opt-in rules this config turns on, for `Linter::with_opt_in_rules`.
+    pub fn opt_in_rules(&self) -> Vec<&'static str> {
+        OPT_IN_RULES
+            .iter()
+            .copied()
+            .filter(|rule| self.level(rule) != LintLevel::Off)
+            .collect()
+    }
+
+    /// Drop the diagnostics whose rule is `off` and set the severity of the
+    /// rest.
+    pub fn apply(&self, diagnostics: Vec<LintDiagnostic>) -> Vec<LintDiagnostic> {
+        diagnostics
+            .into_iter()
+            .filter_map(|mut d| {
+                d.severity = match self.level(d.rule) {
+                    LintLevel::Off => return None,
+                    LintLevel::Warn => Severity::Warning,
+                    LintLevel::Error => Severity::Error,
+                };
+                Some(d)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::span::Span;
+
+    fn config(entries: &[(&str, &str)]) -> LintConfig {
+        let mut package = Package::new("app");
+        for (k, v) in entries {
+            package.lint.insert(k.to_string(), v.to_string());
+        }
+        LintConfig::from_package(&package).unwrap()
+    }
+
+    fn diagnostic(rule: &'static str) -> LintDiagnostic {
+        LintDiagnostic {
+            rule,
+            message: String::new(),
+            span: Span::default(),
+            severity: Severity::Warning,
+            fix: None,
+        }
+    }
+
+    #[test]
+    fn unconfigured_rules_are_warnings() {
+        assert_eq!(
+            LintConfig::default().level("style/line-length"),
+            LintLevel::Warn
+        );
+    }
+
+    #[test]
+    fn rule_beats_category_beats_default() {
+        let c = config(&[
+            ("default", "error"),
+            ("style", "warn"),
+            ("style/line-length", "off"),
+        ]);
+        assert_eq!(c.level("style/line-length"), LintLevel::Off);
+        assert_eq!(c.level("style/empty-block"), LintLevel::Warn);
+        assert_eq!(c.level("smell/deep-nesting"), LintLevel::Error);
+    }
+
+    #[test]
+    fn apply_drops_off_rules_and_sets_severity() {
+        let c = config(&[("style", "off"), ("smell/empty-catch", "error")]);
+        let out = c.apply(vec![
+            diagnostic("style/empty-block"),
+            diagnostic("smell/empty-catch"),
+            diagnostic("naming/snake-case"),
+        ]);
+        let got: Vec<_> = out.iter().map(|d| (d.rule, d.severity.clone())).collect();
+        assert_eq!(
+            got,
+            vec![
+                ("smell/empty-catch", Severity::Error),
+                ("naming/snake-case", Severity::Warning),
+            ]
+        );
+    }
+
+    #[test]
+    fn opt_in_rules_need_their_own_key() {
+        let c = config(&[("default", "error"), ("style", "warn")]);
+        assert_eq!(c.level("style/redundant-return"), LintLevel::Off);
+        assert!(c.opt_in_rules().is_empty());
+        let c = config(&[("style/redundant-return", "warn")]);
+        assert_eq!(c.level("style/redundant-return"), LintLevel::Warn);
+        assert_eq!(c.opt_in_rules(), vec!["style/redundant-return"]);
+    }
+
+    #[test]
+    fn unknown_level_is_rejected() {
+        let mut package = Package::new("app");
+        package.lint.insert("style".into(), "fatal".into());
+        assert!(LintConfig::from_package(&package).is_err());
+    }
+}
@@ -0,0 +1,543 @@
+//! `soli lint --dead-code` — whole-project reachability.
+//!
+//! Unlike the per-file rules, this looks at a project as one module set:
+//! every `.sl` file under `app/`, `config/`, `db/` and `lib/` (or, outside an
+//! app, every `.sl` file under the folder) plus the templates in `app/views/`.
+//! Starting from the roots, it follows references until nothing new is
+//! reached; top-level functions, classes, view helpers and templates that
+//! were never reached are reported.
+//!
+//! Roots are what the runtime enters without a reference in code:
+//!
+//! - top-level statements of every file (they run on load) and everything in
+//!   `config/` and `db/`;
+//! - route handlers from `config/routes.sl` (every controller when the app
+//!   routes by convention);
+//! - middleware, jobs and policies, which the server looks up by convention;
+//! - layouts and `errors/` pages;
+//! - outside an app, the exports of files no other file imports.
+//!
+//! A reference is any identifier, member name or string literal in reached
+//! code or templates — so `User.find`, `has_many("comments")`,
+//! `render("posts/show")` and `const_get("AdminPolicy")` all count. A
+//! reached class keeps all of its methods alive, and each method also
+//! reaches the template of the same name (`posts/index` for
+//! `PostsController#index`, `user_mailer/welcome` for `UserMailer#welcome`).
+//! The analysis leans towards keeping code: a name used anywhere reachable
+//! keeps every definition of that name. Code only called from `tests/` is
+//! reported.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::ast::expr::ExprKind;
+use crate::ast::stmt::{Program, Stmt, StmtKind};
+use crate::lexer::TokenKind;
+use crate::lint::templates::{
+    collect_partial_nodes, files_under, for_each_expr, parse_code_file, partial_path,
+    strip_template_extension, template_program, TEMPLATE_EXTENSIONS,
+};
+use crate::lint::{suppress, LintDiagnostic, Severity};
+use crate::span::Span;
+
+/// Directories whose definitions the server enters by convention.
+const CONVENTION_DIRS: &[&str] = &["app/middleware", "app/jobs", "app/policies", "config", "db"];
+
+/// One unused definition.
+#[derive(Debug, Clone)]
+pub struct DeadCode {
+    pub file: PathBuf,
+    pub diagnostic: LintDiagnostic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DefKind {
+    Function,
+    Helper,
+    Class,
+}
+
+struct Def<'p> {
+    kind: DefKind,
+    name: &'p str,
+    file: usize,
+    stmt: &'p Stmt,
+}
+
+struct SourceFile {
+    path: PathBuf,
+    /// `/`-separated path relative to the project root.
+    rel: String,
+    source: String,
+    /// `None` when the file doesn't parse; its identifiers still count as
+    /// references so a syntax error never makes live code look dead.
+    program: Option<Program>,
+}
+
+struct Template {
+    path: PathBuf,
+    source: String,
+}
+
+/// Find the unused functions, classes, helpers and templates of the project
+/// at `root`, sorted by file and line.
+pub fn find_dead_code(root: &Path) -> Vec<DeadCode> {
+    let app = root.join("app").is_dir();
+    let files = load_files(root, app);
+    let views_dir = root.join("app").join("views");
+    let templates: HashMap<String, Template> = if app {
+        files_under(&views_dir, TEMPLATE_EXTENSIONS, None)
+            .into_iter()
+            // `README.md` / `CLAUDE.md` next to the views are notes, not views.
+            .filter(|path| {
+                !path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(|c: char| c.is_ascii_uppercase()))
+            })
+            .filter_map(|path| {
+                let rel = relpath(&views_dir, &path);
+                let source = std::fs::read_to_string(&path).ok()?;
+                Some((
+                    strip_template_extension(&rel).to_string(),
+                    Template { path, source },
+                ))
+            })
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    let mut defs = Vec::new();
+    for (index, file) in files.iter().enumerate() {
+        let Some(program) = &file.program else {
+            continue;
+        };
+        for stmt in &program.statements {
+            if let Some((kind, name)) = definition(stmt, &file.rel) {
+                defs.push(Def {
+                    kind,
+                    name,
+                    file: index,
+                    stmt,
+                });
+            }
+        }
+    }
+
+    let mut reach = Reach {
+        root,
+        files: &files,
+        templates: &templates,
+        defs: &defs,
+        by_name: HashMap::new(),
+        live_defs: vec![false; defs.len()],
+        live_templates: HashSet::new(),
+        seen_names: HashSet::new(),
+        pending: Vec::new(),
+    };
+    for (index, def) in defs.iter().enumerate() {
+        reach.by_name.entry(def.name).or_default().push(index);
+    }
+    reach.seed(app);
+    reach.run();
+
+    let mut found = Vec::new();
+    for (index, def) in defs.iter().enumerate() {
+        if reach.live_defs[index] {
+            continue;
+        }
+        let (rule, message) = match def.kind {
+            DefKind::Function => (
+                "dead-code/unused-function",
+                format!("function '{}' is never called", def.name),
+            ),
+            DefKind::Helper => (
+                "dead-code/unused-helper",
+                format!("helper '{}' is never used by a view or app code", def.name),
+            ),
+            DefKind::Class => (
+                "dead-code/unused-class",
+                format!("class '{}' is never referenced", def.name),
+            ),
+        };
+        let file = &files[def.file];
+        let line = def.stmt.span.line;
+        if suppress::collect_suppressions(&file.source).suppresses(line, rule) {
+            continue;
+        }
+        found.push(DeadCode {
+            file: file.path.clone(),
+            diagnostic: LintDiagnostic {
+                rule,
+                message,
+                span: Span::new(0, 0, line as usize, 1),
+                severity: Severity::Warning,
+                fix: None,
+            },
+        });
+    }
+    for (name, template) in &templates {
+        if reach.live_templates.contains(name) {
+            continue;
+        }
+        found.push(DeadCode {
+            file: template.path.clone(),
+            diagnostic: LintDiagnostic {
+                rule: "dead-code/unused-template",
+                message: format!("template '{}' is never rendered", name),
+                span: Span::new(0, 0, 1, 1),
+                severity: Severity::Warning,
+                fix: None,
+            },
+        });
+    }
+    found.sort_by(|a, b| (&a.file, a.diagnostic.span.line).cmp(&(&b.file, b.diagnostic.span.line)));
+    found
+}
+
+/// The module set: everything the server loads for an app, or every `.sl`
+/// file for a plain project. `tests/` is never part of it.
+fn load_files(root: &Path, app: bool) -> Vec<SourceFile> {
+    let paths: Vec<PathBuf> = if app {
+        ["app", "config", "db", "lib"]
+            .iter()
+            .flat_map(|dir| files_under(&root.join(dir), &[".sl"], None))
+            .collect()
+    } else {
+        files_under(root, &[".sl"], Some(&root.join("tests")))
+    };
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let source = std::fs::read_to_string(&path).ok()?;
+            Some(SourceFile {
+                rel: relpath(root, &path),
+                program: parse_code_file(&path),
+                source,
+                path,
+            })
+        })
+        .collect()
+}
+
+fn relpath(base: &Path, path: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .components()
+        .filter_map(|c| c.as_os_str().to_str())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// The reportable definition a top-level statement introduces, if any.
+fn definition<'p>(stmt: &'p Stmt, rel: &str) -> Option<(DefKind, &'p str)> {
+    match &stmt.kind {
+        StmtKind::Function(decl) if rel.starts_with("app/helpers/") => {
+            Some((DefKind::Helper, &decl.name))
+        }
+        StmtKind::Function(decl) => Some((DefKind::Function, &decl.name)),
+        StmtKind::Class(decl) => Some((DefKind::Class, &decl.name)),
+        StmtKind::Export(inner) => definition(inner, rel),
+        _ => None,
+    }
+}
+
+/// Reachability state over the module set.
+struct Reach<'p> {
+    root: &'p Path,
+    files: &'p [SourceFile],
+    templates: &'p HashMap<String, Template>,
+    defs: &'p [Def<'p>],
+    by_name: HashMap<&'p str, Vec<usize>>,
+    live_defs: Vec<bool>,
+    live_templates: HashSet<String>,
+    seen_names: HashSet<String>,
+    /// Code still to scan, with the controller key that bare
+    /// `render("index")` calls in it resolve against.
+    pending: Vec<(&'p [Stmt], Option<String>)>,
+}
+
+impl<'p> Reach<'p> {
+    fn seed(&mut self, app: bool) {
+        let files = self.files;
+        for file in files {
+            let Some(program) = &file.program else {
+                self.scan_tokens(&file.source);
+                continue;
+            };
+            // Top-level code runs when the file loads.
+            for stmt in &program.statements {
+                if definition(stmt, &file.rel).is_none() {
+                    self.pending
+                        .push((std::slice::from_ref(stmt), controller_key(&file.rel)));
+                }
+            }
+        }
+
+        let defs = self.defs;
+        let entries = if app {
+            HashSet::new()
+        } else {
+            self.entry_files()
+        };
+        for (index, def) in defs.iter().enumerate() {
+            let rel = &files[def.file].rel;
+            let by_convention = app
+                && CONVENTION_DIRS
+                    .iter()
+                    .any(|dir| rel.starts_with(&format!("{}/", dir)));
+            let exported =
+                !app && entries.contains(&def.file) && matches!(def.stmt.kind, StmtKind::Export(_));
+            if by_convention || exported {
+                self.mark_def(index);
+            }
+        }
+        if !app {
+            return;
+        }
+
+        let templates = self.templates;
+        for name in templates.keys() {
+            if name.starts_with("layouts/") || name.starts_with("errors/") {
+                self.mark_template(name);
+            }
+        }
+        self.seed_routes();
+    }
+
+    /// Route handlers: the controller classes (or action functions) behind
+    /// every `controller#action` in `config/routes.sl`, or every controller
+    /// when the app routes by convention.
+    fn seed_routes(&mut self) {
+        let convention = !self.root.join("config").join("routes.sl").is_file();
+        let handlers: Option<Vec<String>> = if convention {
+            None
+        } else {
+            // routes.sl is app code and may print; keep lint output clean.
+            let _capture = crate::interpreter::builtins::StdoutCaptureGuard::start();
+            let listing = crate::serve::route_listing::collect_routes(self.root);
+            Some(match listing {
+                Ok(listing) => listing
+                    .routes
+                    .iter()
+                    .map(|r| r.handler_name.clone())
+                    .chain(listing.websockets.iter().map(|w| w.handler_name.clone()))
+                    .collect(),
+                // A routes file that fails to load can't say what's routed —
+                // treat every controller as routed rather than guess.
+                Err(_) => return self.seed_all_controllers(),
+            })
+        };
+        let Some(handlers) = handlers else {
+            return self.seed_all_controllers();
+        };
+        let defs = self.defs;
+        for handler in handlers {
+            let Some((key, action)) = handler.split_once('#') else {
+                self.reference(&handler, None);
+                continue;
+            };
+            for (index, def) in defs.iter().enumerate() {
+                if controller_key(&self.files[def.file].rel).as_deref() != Some(key) {
+                    continue;
+                }
+                if def.kind == DefKind::Class || action == "*" || def.name == action {
+                    self.mark_def(index);
+                }
+            }
+        }
+    }
+
+    fn seed_all_controllers(&mut self) {
+        let defs = self.defs;
+        for (index, def) in defs.iter().enumerate() {
+            if self.files[def.file].rel.starts_with("app/controllers/") {
+                self.mark_def(index);
+            }
+        }
+    }
+
+    /// Files no other file imports — the entry points of a plain project.
+    fn entry_files(&self) -> HashSet<usize> {
+        let mut imported = HashSet::new();
+        for file in self.files {
+            let Some(program) = &file.program else {
+                continue;
+            };
+            let dir = file.path.parent().unwrap_or(Path::new(""));
+            for stmt in &program.statements {
+                if let StmtKind::Import(import) = &stmt.kind {
+                    let mut target = dir.join(&import.path);
+                    if target.extension().is_none() {
+                        target.set_extension("sl");
+                    }
+                    if let Ok(target) = target.canonicalize() {
+                        imported.insert(target);
+                    }
+                }
+            }
+        }
+        (0..self.files.len())
+            .filter(|&i| {
+                self.files[i]
+                    .path
+                    .canonicalize()
+                    .map_or(true, |p| !imported.contains(&p))
+            })
+            .collect()
+    }
+
+    fn run(&mut self) {
+        while let Some((stmts, key)) = self.pending.pop() {
+            let names = referenced_names(stmts);
+            for name in names {
+                self.reference(name, key.as_deref());
+            }
+        }
+    }
+
+    /// A name seen in reached code: every definition of it, the class a
+    /// relation string names (`"comments"` → `Comment`) and any template it
+    /// resolves to.
+    fn reference(&mut self, name: &str, key: Option<&str>) {
+        let scope = format!("{}\0{}", key.unwrap_or_default(), name);
+        if !self.seen_names.insert(scope) {
+            return;
+        }
+        let mut targets: Vec<usize> = self.by_name.get(name).cloned().unwrap_or_default();
+        if name.chars().all(|c| c.is_ascii_lowercase() || c == '_') && !name.is_empty() {
+            let class = crate::interpreter::builtins::model::relations::classify(name);
+            targets.extend(self.by_name.get(class.as_str()).into_iter().flatten());
+        }
+        for index in targets {
+            self.mark_def(index);
+        }
+
+        if self.templates.is_empty() || name.contains(char::is_whitespace) {
+            return;
+        }
+        let stripped = strip_template_extension(name);
+        let mut candidates = vec![
+            stripped.to_string(),
+            partial_path(stripped),
+            format!("components/{}", stripped),
+            format!("layouts/{}", stripped),
+        ];
+        if let Some(key) = key {
+            candidates.push(format!("{}/{}", key, stripped));
+        }
+        for candidate in candidates {
+            if self.templates.contains_key(&candidate) {
+                self.mark_template(&candidate);
+            }
+        }
+    }
+
+    fn mark_def(&mut self, index: usize) {
+        if std::mem::replace(&mut self.live_defs[index], true) {
+            return;
+        }
+        let (defs, files) = (self.defs, self.files);
+        let def = &defs[index];
+        let file = &files[def.file];
+        let key = controller_key(&file.rel);
+        let stmt = match &def.stmt.kind {
+            StmtKind::Export(inner) => inner.as_ref(),
+            _ => def.stmt,
+        };
+        if let StmtKind::Class(decl) = &stmt.kind {
+            if let Some(superclass) = &decl.superclass {
+                self.reference(superclass, None);
+            }
+            for interface in &decl.interfaces {
+                self.reference(interface, None);
+            }
+            // Each method may render the template of the same name: the
+            // controller's `<key>/<action>` or a mailer's `<class>/<method>`.
+            let prefix = key.clone().unwrap_or_else(|| {
+                crate::interpreter::builtins::model::relations::to_snake_case(&decl.name)
+            });
+            for method in &decl.methods {
+                let template = format!("{}/{}", prefix, method.name);
+                if self.templates.contains_key(&template) {
+                    self.mark_template(&template);
+                }
+            }
+        }
+        // A function-style action renders `<key>/<action>` the same way.
+        if let (StmtKind::Function(decl), Some(key)) = (&stmt.kind, &key) {
+            let template = format!("{}/{}", key, decl.name);
+            if self.templates.contains_key(&template) {
+                self.mark_template(&template);
+            }
+        }
+        self.pending.push((std::slice::from_ref(stmt), key));
+    }
+
+    fn mark_template(&mut self, name: &str) {
+        if !self.live_templates.insert(name.to_string()) {
+            return;
+        }
+        let templates = self.templates;
+        let template = &templates[name];
+        let mut partials = Vec::new();
+        let nodes = crate::template::parser::parse_template(&template.source).unwrap_or_default();
+        collect_partial_nodes(&nodes, &mut partials);
+        let partials: Vec<String> = partials.iter().map(|(n, _, _)| n.to_string()).collect();
+        for partial in partials {
+            self.reference(&partial, None);
+        }
+        match template_program(&template.source) {
+            Some(program) => {
+                // The template's program is local to this call; scan it now
+                // rather than queueing a borrow of it.
+                for name in referenced_names(&program.statements) {
+                    self.reference(name, None);
+                }
+            }
+            None => {
+                let code = crate::template::parser::extract_lintable_code(&template.source)
+                    .unwrap_or_default();
+                self.scan_tokens(&code);
+            }
+        }
+    }
+
+    /// Fallback for code that doesn't parse: every identifier and string
+    /// literal counts as a reference.
+    fn scan_tokens(&mut self, source: &str) {
+        let Ok(tokens) = crate::lexer::Scanner::new(source).scan_tokens() else {
+            return;
+        };
+        for token in tokens {
+            if let TokenKind::Identifier(name) | TokenKind::StringLiteral(name) = token.kind {
+                self.reference(&name, None);
+            }
+        }
+    }
+}
+
+/// `admin/users` for `app/controllers/admin/users_controller.sl`.
+fn controller_key(rel: &str) -> Option<String> {
+    let rel = rel.strip_prefix("app/controllers/")?;
+    Some(crate::serve::app_loader::controller_key_from_path(
+        Path::new(""),
+        Path::new(rel),
+    ))
+}
+
+/// Every identifier, member name and string literal under `stmts`.
+fn referenced_names(stmts: &[Stmt]) -> Vec<&str> {
+    let mut names = Vec::new();
+    for_each_expr(stmts, &mut |expr| match &expr.kind {
+        ExprKind::Variable(name, _)
+        | ExprKind::StringLiteral(name)
+        | ExprKind::Symbol(name)
+        | ExprKind::Member { name, .. }
+        | ExprKind::SafeMember { name, .. }
+        | ExprKind::QualifiedName { name, .. } => names.push(name.as_str()),
+        _ => {}
+    });
+    names
+}
@@ -1,4 +1,4 @@
-use crate::ast::expr::{Argument, Expr, ExprKind, InterpolatedPart};
+use crate::ast::expr::{Argument, Expr, ExprKind, HashComprehension, InterpolatedPart};
 
 use super::rules;
 use super::Linter;
@@ -13,6 +13,12 @@ impl Linter {
             self.file_path.as_deref(),
             &mut self.diagnostics,
         );
+        rules::security::check_interpolated_query(expr, &mut self.diagnostics);
+        rules::security::check_secret_literal(
+            expr,
+            self.file_path.as_deref(),
+            &mut self.diagnostics,
+        );
 
         match &expr.kind {
             ExprKind::IntLiteral(_)
@@ -24,7 +30,7 @@ impl Linter {
             | ExprKind::Null
             | ExprKind::This
             | ExprKind::Super
-            | ExprKind::Variable(_)
+            | ExprKind::Variable(_, _)
             | ExprKind::CommandSubstitution(_)
             | ExprKind::SdqlBlock { .. } => {}
 
@@ -55,8 +61,18 @@ impl Linter {
                     expr.span,
                     &mut self.diagnostics,
                 );
-                self.lint_expr(left);
-                self.lint_expr(right);
+                if rules::idiom::check_prefer_interpolation(
+                    expr,
+                    &self.source,
+                    &mut self.diagnostics,
+                ) {
+                    for operand in rules::idiom::concat_operands(expr) {
+                        self.lint_expr(operand);
+                    }
+                } else {
+                    self.lint_expr(left);
+                    self.lint_expr(right);
+                }
             }
 
             ExprKind::Unary { operand, .. } => self.lint_expr(operand),
@@ -92,7 +108,7 @@ impl Linter {
                 self.lint_arguments(arguments);
             }
 
-            ExprKind::Array(elements) => {
+            ExprKind::Array(elements) | ExprKind::Tuple(elements) => {
                 for elem in elements {
                     self.lint_expr(elem);
                 }
@@ -100,6 +116,14 @@ impl Linter {
 
             ExprKind::Hash(pairs) => {
                 for (key, value) in pairs {
+                    if let ExprKind::StringLiteral(name) = &key.kind {
+                        rules::security::check_hardcoded_secret(
+                            name,
+                            value,
+                            self.file_path.as_deref(),
+                            &mut self.diagnostics,
+                        );
+                    }
                     self.lint_expr(key);
                     self.lint_expr(value);
                 }
@@ -117,6 +141,22 @@ impl Linter {
             }
 
             ExprKind::Assign { target, value } => {
+                let name = match &target.kind {
+                    ExprKind::Variable(name, _) | ExprKind::Member { name, .. } => Some(name),
+                    ExprKind::Index { index, .. } => match &index.kind {
+                        ExprKind::StringLiteral(key) => Some(key),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                if let Some(name) = name {
+                    rules::security::check_hardcoded_secret(
+                        name,
+                        value,
+                        self.file_path.as_deref(),
+                        &mut self.diagnostics,
+                    );
+                }
                 self.lint_expr(target);
                 self.lint_expr(value);
             }
@@ -146,11 +186,7 @@ impl Linter {
                 self.lint_expr(right);
             }
 
-            ExprKind::Lambda {
-                params,
-                return_type: _,
-                body,
-            } => {
+            ExprKind::Lambda { params, body, .. } => {
                 self.depth += 1;
                 rules::smell::check_deep_nesting(self.depth, expr.span, &mut self.diagnostics);
                 for param in params {
@@ -171,6 +207,12 @@ impl Linter {
             } => {
                 self.depth += 1;
                 rules::smell::check_deep_nesting(self.depth, expr.span, &mut self.diagnostics);
+                rules::smell::check_constant_condition(
+                    "if",
+                    condition,
+                    else_branch.is_some(),
+                    &mut self.diagnostics,
+                );
                 self.lint_expr(condition);
                 self.lint_expr(then_branch);
                 if let Some(else_b) = else_branch {
@@ -182,6 +224,7 @@ impl Linter {
             ExprKind::Match { expression, arms } => {
                 self.depth += 1;
                 rules::smell::check_deep_nesting(self.depth, expr.span, &mut self.diagnostics);
+                rules::smell::check_unreachable_arms(arms, &mut self.diagnostics);
                 self.lint_expr(expression);
                 for arm in arms {
                     if let Some(guard) = &arm.guard {
@@ -206,13 +249,14 @@ impl Linter {
                 }
             }
 
-            ExprKind::HashComprehension {
-                key,
-                value,
-                variable,
-                iterable,
-                condition,
-            } => {
+            ExprKind::HashComprehension(comp) => {
+                let HashComprehension {
+                    key,
+                    value,
+                    variable,
+                    iterable,
+                    condition,
+                } = &**comp;
                 rules::naming::check_variable_name(variable, expr.span, &mut self.diagnostics);
                 self.lint_expr(key);
                 self.lint_expr(value);
@@ -222,7 +266,7 @@ impl Linter {
                 }
             }
 
-            ExprKind::Spread(inner) | ExprKind::Throw(inner) => {
+            ExprKind::Spread(inner) | ExprKind::Throw(inner) | ExprKind::Await(inner) => {
                 self.lint_expr(inner);
             }
 
@@ -248,6 +292,7 @@ impl Linter {
                 Argument::Positional(expr) => self.lint_expr(expr),
                 Argument::Named(named) => self.lint_expr(&named.value),
                 Argument::Block(expr) => self.lint_expr(expr),
+                Argument::Spread(expr) => self.lint_expr(expr),
             }
         }
     }
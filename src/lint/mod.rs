@@ -1,20 +1,29 @@
+pub mod config;
+pub mod dead_code;
 pub mod expressions;
 pub mod rules;
 pub mod statements;
 pub mod suppress;
+pub mod templates;
 
 use crate::ast::Program;
 use crate::span::Span;
 
+pub use config::{LintConfig, LintLevel};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Severity {
     Warning,
+    /// Raised from a warning by the project's `[lint]` config; only these
+    /// fail `soli lint`.
+    Error,
 }
 
 impl std::fmt::Display for Severity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
         }
     }
 }
@@ -25,8 +34,52 @@ pub struct LintDiagnostic {
     pub message: String,
     pub span: Span,
     pub severity: Severity,
+    /// A machine-applicable rewrite that resolves the diagnostic, applied by
+    /// `soli lint --fix`.
+    pub fix: Option<LintFix>,
+}
+
+/// Replace the source bytes `span.start..span.end` with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintFix {
+    pub span: Span,
+    pub replacement: String,
+}
+
+/// Apply the fixes carried by `diagnostics` to `source`, returning the new
+/// source and how many fixes were applied. A fix that overlaps one already
+/// applied is left out; linting the result again picks it up.
+pub fn apply_fixes(source: &str, diagnostics: &[LintDiagnostic]) -> (String, usize) {
+    let mut fixes: Vec<&LintFix> = diagnostics.iter().filter_map(|d| d.fix.as_ref()).collect();
+    fixes.sort_by_key(|f| (f.span.start, f.span.end));
+
+    let mut out = String::with_capacity(source.len());
+    let mut cursor = 0;
+    let mut applied = 0;
+    for fix in fixes {
+        let (start, end) = (fix.span.start_usize(), fix.span.end_usize());
+        if start < cursor
+            || end < start
+            || end > source.len()
+            || !source.is_char_boundary(start)
+            || !source.is_char_boundary(end)
+        {
+            continue;
+        }
+        out.push_str(&source[cursor..start]);
+        out.push_str(&fix.replacement);
+        cursor = end;
+        applied += 1;
+    }
+    out.push_str(&source[cursor..]);
+    (out, applied)
 }
 
+/// Rules that are off unless a project's `[lint]` config names them: style
+/// choices the generated app code doesn't follow (it ends actions with
+/// `return render(...)`).
+pub const OPT_IN_RULES: &[&str] = &["style/redundant-return"];
+
 pub struct Linter {
     source: String,
     pub(crate) file_path: Option<String>,
@@ -37,6 +90,11 @@ pub struct Linter {
     /// rules to distinguish "truly undefined" from "defined elsewhere in
     /// this file". Populated at the start of `lint()`.
     pub(crate) program_names: std::collections::HashSet<String>,
+    /// The rules of [`OPT_IN_RULES`] this run checks as well.
+    opt_in: Vec<&'static str>,
+    /// The `skip_csrf` patterns of the app's `config/application.sl`, which
+    /// apply to its `config/routes.sl` too.
+    csrf_skips: Vec<String>,
 }
 
 impl Linter {
@@ -47,9 +105,24 @@ impl Linter {
             diagnostics: Vec::new(),
             depth: 0,
             program_names: std::collections::HashSet::new(),
+            opt_in: Vec::new(),
+            csrf_skips: Vec::new(),
         }
     }
 
+    /// Also check `rules`, those of [`OPT_IN_RULES`] a project turned on.
+    pub fn with_opt_in_rules(mut self, rules: &[&'static str]) -> Self {
+        self.opt_in = rules.to_vec();
+        self
+    }
+
+    /// Check routes against the app-wide `skip_csrf` `patterns` as well, as
+    /// [`LintConfig::csrf_skips`] lists them.
+    pub fn with_csrf_skips(mut self, patterns: &[String]) -> Self {
+        self.csrf_skips = patterns.to_vec();
+        self
+    }
+
     pub fn with_file_path(mut self, path: impl Into<String>) -> Self {
         self.file_path = Some(path.into());
         self
@@ -59,6 +132,18 @@ impl Linter {
         rules::style::check_line_lengths(&self.source, &mut self.diagnostics);
         rules::props::check_component_props(&program.statements, &mut self.diagnostics);
         rules::scope::collect_program_names(&program.statements, &mut self.program_names);
+        rules::scope::check_unused_imports(
+            &program.statements,
+            &self.source,
+            self.file_path.as_deref(),
+            &mut self.diagnostics,
+        );
+        rules::security::check_csrf_skipped_routes(
+            &program.statements,
+            self.file_path.as_deref(),
+            &self.csrf_skips,
+            &mut self.diagnostics,
+        );
         self.collect_sibling_definitions();
 
         for stmt in &program.statements {
@@ -133,7 +218,10 @@ impl Linter {
                 let Ok(tokens) = crate::lexer::Scanner::new(&source).scan_tokens() else {
                     continue;
                 };
-                let Ok(sibling_program) = crate::parser::Parser::new(tokens).parse() else {
+                let Ok(sibling_program) = crate::parser::Parser::new(tokens)
+                    .keeping_platform_code()
+                    .parse()
+                else {
                     continue;
                 };
                 rules::scope::collect_program_names(
@@ -146,6 +234,10 @@ impl Linter {
 
     fn lint_body(&mut self, stmts: &[crate::ast::Stmt]) {
         rules::smell::check_unreachable_code(stmts, &mut self.diagnostics);
+        if self.opt_in.contains(&"style/redundant-return") {
+            rules::style::check_redundant_return(stmts, &self.source, &mut self.diagnostics);
+        }
+        rules::scope::check_unused_variables(stmts, &self.source, &mut self.diagnostics);
         rules::idiom::check_manual_find_guard(stmts, &mut self.diagnostics);
         for stmt in stmts {
             self.lint_stmt(stmt);
@@ -1,11 +1,12 @@
 //! Soli-specific idiom rules. These flag code that is *correct* but not
 //! idiomatic — the patterns CLAUDE.md calls out as fluency tells: comparing
-//! against `null`/`""` instead of `.nil?`/`.blank?`, and chained `==`/`!=`
-//! membership tests instead of `.includes?`.
+//! against `null`/`""` instead of `.nil?`/`.blank?`, chained `==`/`!=`
+//! membership tests instead of `.includes?`, and `+` concatenation instead
+//! of `#{...}` interpolation.
 
 use crate::ast::expr::{BinaryOp, Expr, ExprKind};
 use crate::ast::stmt::{Stmt, StmtKind};
-use crate::lint::{LintDiagnostic, Severity};
+use crate::lint::{LintDiagnostic, LintFix, Severity};
 use crate::span::Span;
 
 /// `x == null` / `x != null` → prefer `x.nil?` / `x.present?`.
@@ -40,6 +41,7 @@ pub fn check_nil_comparison(
         ),
         span,
         severity: Severity::Warning,
+        fix: None,
     });
 }
 
@@ -70,6 +72,7 @@ pub fn check_prefer_blank(
         ),
         span,
         severity: Severity::Warning,
+        fix: None,
     });
 }
 
@@ -122,9 +125,111 @@ pub fn check_prefer_includes(
         ),
         span,
         severity: Severity::Warning,
+        fix: None,
     });
 }
 
+/// String building with `+`:
+///   `"Hello " + name + "!"`   → `"Hello #{name}!"`
+///
+/// Called on every `Binary` node; returns whether the chain under it was
+/// flagged, so the caller lints the operands rather than the inner links of
+/// the chain. `+` is left-associative, so the chain is only string
+/// concatenation from the start when one of its first two operands is a
+/// string literal — `a + b + "x"` adds first and is left alone. Chains whose
+/// pieces don't print cleanly inside `"..."` (quotes, line breaks, non-`"`
+/// literals) aren't flagged.
+pub fn check_prefer_interpolation(
+    expr: &Expr,
+    source: &str,
+    diagnostics: &mut Vec<LintDiagnostic>,
+) -> bool {
+    let operands = concat_operands(expr);
+    let is_literal = |e: &&Expr| matches!(e.kind, ExprKind::StringLiteral(_));
+    if operands.len() < 2
+        || !operands[..2].iter().any(is_literal)
+        || operands.iter().all(is_literal)
+    {
+        return false;
+    }
+
+    let bytes = source.as_bytes();
+    let mut body = String::new();
+    for operand in &operands {
+        if is_literal(operand) {
+            // The literal as written, escapes and all. Its span starts
+            // after the opening quote and ends after the closing one.
+            let (start, end) = (operand.span.start_usize(), operand.span.end_usize());
+            if start == 0 || bytes.get(start - 1) != Some(&b'"') || end <= start {
+                return false;
+            }
+            // `r"..."` keeps backslashes literal; interpolated text wouldn't.
+            if start >= 2 && bytes[start - 2].is_ascii_alphanumeric() {
+                return false;
+            }
+            let Some(raw) = source.get(start..end - 1) else {
+                return false;
+            };
+            if bytes.get(end - 1) != Some(&b'"')
+                || raw.contains(['"', '\n'])
+                || (body.ends_with('#') && raw.starts_with('{'))
+            {
+                return false;
+            }
+            body.push_str(raw);
+        } else {
+            let printed = crate::fmt::format_expr(operand, source);
+            let printed = printed.trim_end();
+            if printed.contains(['"', '\n']) {
+                return false;
+            }
+            body.push_str(&format!("#{{{}}}", printed));
+        }
+    }
+
+    let mut start = expr.span.start_usize();
+    if is_literal(&operands[0]) {
+        start -= 1;
+    } else if start > 0 && matches!(bytes.get(start - 1), Some(b'"' | b'\'')) {
+        // The first operand begins with a string we can't see the start of.
+        return false;
+    }
+    diagnostics.push(LintDiagnostic {
+        rule: "idiom/prefer-interpolation",
+        message: "prefer string interpolation (`\"...#{x}...\"`) over concatenating with `+`"
+            .to_string(),
+        span: expr.span,
+        severity: Severity::Warning,
+        fix: Some(LintFix {
+            span: Span::new(
+                start,
+                expr.span.end_usize(),
+                expr.span.line_usize(),
+                expr.span.column_usize(),
+            ),
+            replacement: format!("\"{}\"", body),
+        }),
+    });
+    true
+}
+
+/// The operands of a left-associative `+` chain, in order:
+/// `a + b + c` is `[a, b, c]`.
+pub fn concat_operands(expr: &Expr) -> Vec<&Expr> {
+    match &expr.kind {
+        ExprKind::Binary {
+            left,
+            operator: BinaryOp::Add,
+            right,
+        } => {
+            let mut operands = concat_operands(left);
+            operands.push(right);
+            operands
+        }
+        _ => vec![expr],
+    }
+}
+
 /// Dead nil-guard right after a `.find(...)`:
 ///   record = User.find(id)
 ///   return not_found() if record.nil?   # <- never runs: .find raises RecordNotFound
@@ -154,6 +259,7 @@ pub fn check_manual_find_guard(stmts: &[Stmt], diagnostics: &mut Vec<LintDiagnos
             ),
             span: guard_span,
             severity: Severity::Warning,
+            fix: None,
         });
     }
 }
@@ -257,7 +363,7 @@ fn comparison_subject(expr: &Expr, op: BinaryOp) -> Option<String> {
 /// two different expressions are "the same value".
 fn simple_path_key(expr: &Expr) -> Option<String> {
     match &expr.kind {
-        ExprKind::Variable(name) => Some(name.clone()),
+        ExprKind::Variable(name, _) => Some(name.clone()),
         ExprKind::This => Some("this".to_string()),
         ExprKind::Member { object, name } | ExprKind::SafeMember { object, name } => {
             Some(format!("{}.{}", simple_path_key(object)?, name))
@@ -289,7 +395,7 @@ mod tests {
     use super::*;
 
     fn var(name: &str) -> Expr {
-        Expr::new(ExprKind::Variable(name.to_string()), Span::new(0, 0, 1, 1))
+        Expr::new(ExprKind::variable(name.to_string()), Span::new(0, 0, 1, 1))
     }
     fn string(s: &str) -> Expr {
         Expr::new(
@@ -412,6 +518,7 @@ mod tests {
                 name: name.to_string(),
                 type_annotation: None,
                 initializer: Some(init),
+                slot: Default::default(),
             },
             Span::new(0, 0, 1, 1),
             None,
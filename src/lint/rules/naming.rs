@@ -12,6 +12,7 @@ pub fn check_variable_name(name: &str, span: Span, diagnostics: &mut Vec<LintDia
             message: format!("variable '{}' should use snake_case", name),
             span,
             severity: Severity::Warning,
+            fix: None,
         });
     }
 }
@@ -32,6 +33,7 @@ pub fn check_constant_name(name: &str, span: Span, diagnostics: &mut Vec<LintDia
             ),
             span,
             severity: Severity::Warning,
+            fix: None,
         });
     }
 }
@@ -43,6 +45,7 @@ pub fn check_function_name(name: &str, span: Span, diagnostics: &mut Vec<LintDia
             message: format!("function '{}' should use snake_case", name),
             span,
             severity: Severity::Warning,
+            fix: None,
         });
     }
 }
@@ -55,6 +58,7 @@ pub fn check_class_name(kind: &str, name: &str, span: Span, diagnostics: &mut Ve
             message: format!("{} '{}' should use PascalCase", kind, name),
             span,
             severity: Severity::Warning,
+            fix: None,
         });
     }
 }
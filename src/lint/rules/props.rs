@@ -20,7 +20,7 @@ pub fn check_component_props(statements: &[Stmt], diagnostics: &mut Vec<LintDiag
         let ExprKind::Call { callee, arguments } = &expr.kind else {
             continue;
         };
-        if !matches!(&callee.kind, ExprKind::Variable(name) if name == "props") {
+        if !matches!(&callee.kind, ExprKind::Variable(name, _) if name == "props") {
             continue;
         }
         let mut seen: HashSet<String> = HashSet::new();
@@ -31,6 +31,7 @@ pub fn check_component_props(statements: &[Stmt], diagnostics: &mut Vec<LintDiag
                     message: "props(...) takes positional string arguments only".to_string(),
                     span: expr.span,
                     severity: Severity::Warning,
+                    fix: None,
                 });
                 continue;
             };
@@ -42,6 +43,7 @@ pub fn check_component_props(statements: &[Stmt], diagnostics: &mut Vec<LintDiag
                             message: format!("duplicate prop \"{}\" in props(...)", name),
                             span: a.span,
                             severity: Severity::Warning,
+                            fix: None,
                         });
                     }
                 }
@@ -51,6 +53,7 @@ pub fn check_component_props(statements: &[Stmt], diagnostics: &mut Vec<LintDiag
                         .to_string(),
                     span: a.span,
                     severity: Severity::Warning,
+                    fix: None,
                 }),
             }
         }
@@ -11,9 +11,11 @@
 
 use std::collections::HashSet;
 
-use crate::ast::expr::{Argument, Expr, ExprKind, InterpolatedPart};
-use crate::ast::stmt::{ImportDecl, ImportSpecifier, Parameter, Stmt, StmtKind};
-use crate::lint::{LintDiagnostic, Severity};
+use crate::ast::expr::{Argument, Expr, ExprKind, HashComprehension, InterpolatedPart};
+use crate::ast::stmt::{
+    ClassDecl, ImportDecl, ImportSpecifier, Parameter, Stmt, StmtKind, Visibility,
+};
+use crate::lint::{LintDiagnostic, LintFix, Severity};
 
 fn insert_import_names(decl: &ImportDecl, out: &mut HashSet<String>) {
     match &decl.specifier {
@@ -82,10 +84,14 @@ const WELL_KNOWN_GLOBALS: &[&str] = &[
     "after_each",
     "before_all",
     "after_all",
+    "bench",
+    "task",
     "with_transaction",
     "freeze_time",
     "travel_to",
+    "travel",
     "unfreeze_time",
+    "with_cassette",
     // Test HTTP helpers
     "get",
     "post",
@@ -112,6 +118,8 @@ const WELL_KNOWN_GLOBALS: &[&str] = &[
     "signed_in?",
     "signed_out",
     "signed_out?",
+    "fixture_file_upload",
+    "multipart",
     // Browser test helpers (`soli test --browser`)
     "visit",
     "click",
@@ -145,10 +153,22 @@ const WELL_KNOWN_GLOBALS: &[&str] = &[
     "destroy_session",
     // Rendering/session helpers
     "render",
+    "render_stream",
     "render_partial",
     "redirect",
+    // HTTP caching helpers
+    "fresh_when",
+    "stale?",
+    "expires_in",
     // Request-coalescing block
     "grouped",
+    // Database connection switching
+    "connected_to",
+    // Exit and serve lifecycle hooks
+    "at_exit",
+    "on_boot",
+    "on_worker_start",
+    "on_shutdown",
     "h",
     "h!",
     "raw",
@@ -244,6 +264,9 @@ pub fn collect_program_names(stmts: &[Stmt], out: &mut HashSet<String>) {
             StmtKind::Enum(decl) => {
                 out.insert(decl.name.clone());
             }
+            StmtKind::Struct(decl) => {
+                out.insert(decl.name.clone());
+            }
             StmtKind::Interface(decl) => {
                 out.insert(decl.name.clone());
             }
@@ -272,7 +295,7 @@ pub fn check_undefined_locals(
     }
 }
 
-fn is_likely_global(name: &str) -> bool {
+pub(crate) fn is_likely_global(name: &str) -> bool {
     // Classes / modules are conventionally PascalCase and live at program
     // level. If we don't see them defined here they may come from imports
     // or be builtins — don't flag.
@@ -305,9 +328,258 @@ fn flag(
         ),
         span,
         severity: Severity::Warning,
+        fix: None,
     });
 }
 
+/// `let` bindings in a function body whose name never appears again in the
+/// body's text. Matching on the text rather than the AST errs towards
+/// "used": a mention in a string, a comment or a nested function keeps the
+/// binding. The fix renames it to `_name`, which marks it unused on purpose
+/// and keeps whatever side effects its initializer has.
+pub fn check_unused_variables(body: &[Stmt], source: &str, diagnostics: &mut Vec<LintDiagnostic>) {
+    let (Some(first), Some(last)) = (body.first(), body.last()) else {
+        return;
+    };
+    let Some(text) = source.get(first.span.start_usize()..last.span.end_usize()) else {
+        return;
+    };
+    let mut lets = Vec::new();
+    collect_lets(body, &mut lets);
+    for stmt in lets {
+        let StmtKind::Let { name, .. } = &stmt.kind else {
+            continue;
+        };
+        if name.starts_with('_') {
+            continue;
+        }
+        // Where the name sits in `let name`, when the statement is spelled
+        // that way in `source`.
+        let start = stmt.span.start_usize();
+        let Some(rest) = source.get(start..).and_then(|s| s.strip_prefix("let")) else {
+            continue;
+        };
+        let blanks = rest.len() - rest.trim_start().len();
+        if blanks == 0 || !rest[blanks..].starts_with(name.as_str()) {
+            continue;
+        }
+        let name_start = start + "let".len() + blanks;
+        let offset = first.span.start_usize();
+        let mentioned = word_occurrences(text, name).any(|at| at + offset != name_start);
+        if mentioned {
+            continue;
+        }
+        diagnostics.push(LintDiagnostic {
+            rule: "smell/unused-variable",
+            message: format!("variable '{}' is assigned but never used", name),
+            span: stmt.span,
+            severity: Severity::Warning,
+            fix: Some(LintFix {
+                span: crate::span::Span::new(
+                    name_start,
+                    name_start + name.len(),
+                    stmt.span.line_usize(),
+                    stmt.span.column_usize(),
+                ),
+                replacement: format!("_{}", name),
+            }),
+        });
+    }
+}
+
+/// Flag imports whose names the file never mentions. A whole-module or
+/// namespace import counts as used when any name the module exports (or
+/// the namespace) is mentioned; the module is resolved relative to
+/// `file_path`, and without one, or when it doesn't resolve, the import is
+/// left alone. An import none of whose names are used is removed by
+/// `--fix`.
+pub fn check_unused_imports(
+    stmts: &[Stmt],
+    source: &str,
+    file_path: Option<&str>,
+    diagnostics: &mut Vec<LintDiagnostic>,
+) {
+    let imports: Vec<(&Stmt, &ImportDecl)> = stmts
+        .iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::Import(decl) => Some((stmt, decl)),
+            _ => None,
+        })
+        .collect();
+    if imports.is_empty() {
+        return;
+    }
+    // The source with the import statements blanked out, so an import
+    // never counts as a use of its own names.
+    let mut rest = source.as_bytes().to_vec();
+    for (stmt, _) in &imports {
+        let end = stmt.span.end_usize().min(rest.len());
+        for byte in &mut rest[stmt.span.start_usize().min(end)..end] {
+            if *byte != b'\n' {
+                *byte = b' ';
+            }
+        }
+    }
+    let rest = String::from_utf8_lossy(&rest);
+    let mentioned = |name: &str| word_occurrences(&rest, name).next().is_some();
+
+    let mut resolver = file_path.map(|path| {
+        let path = std::path::Path::new(path);
+        (
+            crate::module::ModuleResolver::new(path.parent().unwrap_or(path)),
+            path,
+        )
+    });
+    for (stmt, decl) in imports {
+        let unused: Vec<(&str, crate::span::Span)> = match &decl.specifier {
+            ImportSpecifier::Named(items) => items
+                .iter()
+                .map(|item| (item.alias.as_deref().unwrap_or(&item.name), item.span))
+                .filter(|(name, _)| !mentioned(name))
+                .collect(),
+            ImportSpecifier::All | ImportSpecifier::Namespace(_) => {
+                let Some((resolver, path)) = resolver.as_mut() else {
+                    continue;
+                };
+                let Ok(exports) = resolver.exports_of(&decl.path, path) else {
+                    continue;
+                };
+                let namespace = match &decl.specifier {
+                    ImportSpecifier::Namespace(name) => Some(name.as_str()),
+                    _ => None,
+                };
+                if exports.is_empty()
+                    || namespace.is_some_and(&mentioned)
+                    || exports.iter().any(|name| mentioned(name))
+                {
+                    continue;
+                }
+                vec![(namespace.unwrap_or(decl.path.as_str()), stmt.span)]
+            }
+        };
+        let whole = match &decl.specifier {
+            ImportSpecifier::Named(items) => unused.len() == items.len(),
+            _ => true,
+        };
+        for (name, span) in unused {
+            let message = match &decl.specifier {
+                ImportSpecifier::All => format!("nothing imported from '{}' is used", name),
+                _ => format!("imported '{}' is never used", name),
+            };
+            diagnostics.push(LintDiagnostic {
+                rule: "smell/unused-import",
+                message,
+                span,
+                severity: Severity::Warning,
+                fix: whole.then(|| LintFix {
+                    span: statement_lines(source, stmt.span),
+                    replacement: String::new(),
+                }),
+            });
+        }
+    }
+}
+
+/// `span` widened to the whole lines it sits on, its newline included,
+/// when nothing else shares them.
+fn statement_lines(source: &str, span: crate::span::Span) -> crate::span::Span {
+    let (mut start, mut end) = (span.start_usize(), span.end_usize());
+    let line_start = source[..start].rfind('\n').map_or(0, |at| at + 1);
+    if source[line_start..start].trim().is_empty() {
+        start = line_start;
+    }
+    let tail = &source[end..];
+    let line_end = tail.find('\n').map_or(tail.len(), |at| at + 1);
+    if tail[..line_end]
+        .trim()
+        .trim_start_matches(';')
+        .trim()
+        .is_empty()
+    {
+        end += line_end;
+    }
+    crate::span::Span::new(start, end, span.line_usize(), span.column_usize())
+}
+
+/// Flag private methods nothing in their class mentions: only the class
+/// can call them, so an unmentioned one is dead.
+pub fn check_unused_private_methods(
+    class: &ClassDecl,
+    source: &str,
+    diagnostics: &mut Vec<LintDiagnostic>,
+) {
+    let Some(text) = source.get(class.span.start_usize()..class.span.end_usize()) else {
+        return;
+    };
+    for method in &class.methods {
+        if !matches!(method.visibility, Visibility::Private) {
+            continue;
+        }
+        // The declaration itself is one mention.
+        if word_occurrences(text, &method.name).nth(1).is_some() {
+            continue;
+        }
+        diagnostics.push(LintDiagnostic {
+            rule: "smell/unused-private-method",
+            message: format!(
+                "private method '{}' is never called in class '{}'",
+                method.name, class.name
+            ),
+            span: method.span,
+            severity: Severity::Warning,
+            fix: None,
+        });
+    }
+}
+
+/// The `let` statements of a body, through nested blocks but not nested
+/// functions or lambdas, whose bodies are linted on their own.
+fn collect_lets<'a>(stmts: &'a [Stmt], out: &mut Vec<&'a Stmt>) {
+    for stmt in stmts {
+        match &stmt.kind {
+            StmtKind::Let { .. } => out.push(stmt),
+            StmtKind::Block(inner) => collect_lets(inner, out),
+            StmtKind::If {
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                collect_lets(std::slice::from_ref(then_branch), out);
+                if let Some(e) = else_branch {
+                    collect_lets(std::slice::from_ref(e), out);
+                }
+            }
+            StmtKind::While { body, .. } | StmtKind::For { body, .. } => {
+                collect_lets(std::slice::from_ref(body), out)
+            }
+            StmtKind::Try {
+                try_block,
+                catch_clauses,
+                finally_block,
+            } => {
+                collect_lets(std::slice::from_ref(try_block), out);
+                for clause in catch_clauses {
+                    collect_lets(std::slice::from_ref(&clause.body), out);
+                }
+                if let Some(f) = finally_block {
+                    collect_lets(std::slice::from_ref(f), out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Byte offsets in `text` where `name` appears as a whole identifier.
+fn word_occurrences<'a>(text: &'a str, name: &'a str) -> impl Iterator<Item = usize> + 'a {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(name).filter_map(move |(at, _)| {
+        let before = text[..at].chars().next_back();
+        let after = text[at + name.len()..].chars().next();
+        (!before.is_some_and(is_ident) && !after.is_some_and(is_ident)).then_some(at)
+    })
+}
+
 fn check_stmt(
     stmt: &Stmt,
     defined: &HashSet<String>,
@@ -322,7 +594,7 @@ fn check_stmt(
                 check_expr(e, defined, program, diagnostics, reported);
             }
         }
-        StmtKind::Const { initializer, .. } => {
+        StmtKind::Const { initializer, .. } | StmtKind::LetTuple { initializer, .. } => {
             check_expr(initializer, defined, program, diagnostics, reported);
         }
         StmtKind::Break => {}
@@ -355,7 +627,9 @@ fn check_stmt(
                 check_expr(e, defined, program, diagnostics, reported);
             }
         }
-        StmtKind::Throw(e) => check_expr(e, defined, program, diagnostics, reported),
+        StmtKind::Throw(e) | StmtKind::Yield(e) => {
+            check_expr(e, defined, program, diagnostics, reported)
+        }
         StmtKind::Try {
             try_block,
             catch_clauses,
@@ -369,7 +643,12 @@ fn check_stmt(
                 check_stmt(f, defined, program, diagnostics, reported);
             }
         }
-        StmtKind::Function(_) | StmtKind::Class(_) | StmtKind::Enum(_) | StmtKind::Interface(_) => {
+        StmtKind::Function(_)
+        | StmtKind::Class(_)
+        | StmtKind::Enum(_)
+        | StmtKind::Struct(_)
+        | StmtKind::Interface(_)
+        | StmtKind::TypeAlias(_) => {
             // Nested definitions have their own scope; program-level already
             // records their names. Skip their bodies here — the top-level
             // linter walks into them separately.
@@ -386,7 +665,7 @@ fn check_expr(
     reported: &mut HashSet<(String, u32, u32)>,
 ) {
     match &expr.kind {
-        ExprKind::Variable(name) => {
+        ExprKind::Variable(name, _) => {
             if defined.contains(name) || program.contains(name) || is_likely_global(name) {
                 return;
             }
@@ -396,7 +675,7 @@ fn check_expr(
             // LHS Variable is a binding site; don't flag it. Still recurse
             // into more complex LHS (Member, Index) to check their bases.
             match &target.kind {
-                ExprKind::Variable(_) => {}
+                ExprKind::Variable(_, _) => {}
                 _ => check_expr(target, defined, program, diagnostics, reported),
             }
             check_expr(value, defined, program, diagnostics, reported);
@@ -425,7 +704,7 @@ fn check_expr(
             // don't flag undefined-local for it. Only check complex callee
             // expressions (Member, Index) for their base objects.
             match &callee.kind {
-                ExprKind::Variable(_) => {} // function call — not a variable read
+                ExprKind::Variable(_, _) => {} // function call — not a variable read
                 _ => check_expr(callee, defined, program, diagnostics, reported),
             }
             check_args(arguments, defined, program, diagnostics, reported);
@@ -450,7 +729,7 @@ fn check_expr(
             check_expr(class_expr, defined, program, diagnostics, reported);
             check_args(arguments, defined, program, diagnostics, reported);
         }
-        ExprKind::Array(elements) => {
+        ExprKind::Array(elements) | ExprKind::Tuple(elements) => {
             for e in elements {
                 check_expr(e, defined, program, diagnostics, reported);
             }
@@ -491,7 +770,7 @@ fn check_expr(
                 inner_defined.insert(p.name.clone());
             }
             collect_assigned_in_stmts(body, &mut inner_defined);
-            for s in body {
+            for s in body.iter() {
                 check_stmt(s, &inner_defined, program, diagnostics, reported);
             }
         }
@@ -524,14 +803,15 @@ fn check_expr(
                 check_expr(c, &inner_defined, program, diagnostics, reported);
             }
         }
-        ExprKind::HashComprehension {
-            key,
-            value,
-            variable,
-            iterable,
-            condition,
-            ..
-        } => {
+        ExprKind::HashComprehension(comp) => {
+            let HashComprehension {
+                key,
+                value,
+                variable,
+                iterable,
+                condition,
+                ..
+            } = &**comp;
             let mut inner_defined = defined.clone();
             inner_defined.insert(variable.clone());
             check_expr(iterable, defined, program, diagnostics, reported);
@@ -548,7 +828,9 @@ fn check_expr(
                 }
             }
         }
-        ExprKind::Throw(e) => check_expr(e, defined, program, diagnostics, reported),
+        ExprKind::Throw(e) | ExprKind::Await(e) => {
+            check_expr(e, defined, program, diagnostics, reported)
+        }
         ExprKind::Rescue { expr, fallback } => {
             check_expr(expr, defined, program, diagnostics, reported);
             check_expr(fallback, defined, program, diagnostics, reported);
@@ -580,7 +862,9 @@ fn check_args(
             Argument::Named(named) => {
                 check_expr(&named.value, defined, program, diagnostics, reported)
             }
-            Argument::Block(e) => check_expr(e, defined, program, diagnostics, reported),
+            Argument::Block(e) | Argument::Spread(e) => {
+                check_expr(e, defined, program, diagnostics, reported)
+            }
         }
     }
 }
@@ -611,6 +895,12 @@ fn collect_assigned_in_stmt(stmt: &Stmt, out: &mut HashSet<String>) {
             out.insert(name.clone());
             collect_assigned_in_expr(initializer, out);
         }
+        StmtKind::LetTuple {
+            names, initializer, ..
+        } => {
+            out.extend(names.iter().cloned());
+            collect_assigned_in_expr(initializer, out);
+        }
         StmtKind::Expression(e) => collect_assigned_in_expr(e, out),
         StmtKind::Block(stmts) => collect_assigned_in_stmts(stmts, out),
         StmtKind::If {
@@ -631,10 +921,14 @@ fn collect_assigned_in_stmt(stmt: &Stmt, out: &mut HashSet<String>) {
         StmtKind::For {
             variable,
             index_variable,
+            destructure,
             iterable,
             body,
         } => {
             out.insert(variable.clone());
+            if let Some(names) = destructure {
+                out.extend(names.iter().cloned());
+            }
             if let Some(idx) = index_variable {
                 out.insert(idx.clone());
             }
@@ -646,7 +940,7 @@ fn collect_assigned_in_stmt(stmt: &Stmt, out: &mut HashSet<String>) {
                 collect_assigned_in_expr(e, out);
             }
         }
-        StmtKind::Throw(e) => collect_assigned_in_expr(e, out),
+        StmtKind::Throw(e) | StmtKind::Yield(e) => collect_assigned_in_expr(e, out),
         StmtKind::Try {
             try_block,
             catch_clauses,
@@ -673,9 +967,15 @@ fn collect_assigned_in_stmt(stmt: &Stmt, out: &mut HashSet<String>) {
         StmtKind::Enum(decl) => {
             out.insert(decl.name.clone());
         }
+        StmtKind::Struct(decl) => {
+            out.insert(decl.name.clone());
+        }
         StmtKind::Interface(decl) => {
             out.insert(decl.name.clone());
         }
+        StmtKind::TypeAlias(decl) => {
+            out.insert(decl.name.clone());
+        }
         StmtKind::Import(decl) => insert_import_names(decl, out),
         StmtKind::Export(inner) => collect_assigned_in_stmt(inner, out),
     }
@@ -684,7 +984,7 @@ fn collect_assigned_in_stmt(stmt: &Stmt, out: &mut HashSet<String>) {
 fn collect_assigned_in_expr(expr: &Expr, out: &mut HashSet<String>) {
     match &expr.kind {
         ExprKind::Assign { target, value } => {
-            if let ExprKind::Variable(name) = &target.kind {
+            if let ExprKind::Variable(name, _) = &target.kind {
                 out.insert(name.clone());
             }
             collect_assigned_in_expr(value, out);
@@ -701,7 +1001,7 @@ fn collect_assigned_in_expr(expr: &Expr, out: &mut HashSet<String>) {
                 match arg {
                     Argument::Positional(e) => collect_assigned_in_expr(e, out),
                     Argument::Named(n) => collect_assigned_in_expr(&n.value, out),
-                    Argument::Block(e) => collect_assigned_in_expr(e, out),
+                    Argument::Block(e) | Argument::Spread(e) => collect_assigned_in_expr(e, out),
                 }
             }
         }
@@ -717,7 +1017,7 @@ fn collect_assigned_in_expr(expr: &Expr, out: &mut HashSet<String>) {
             collect_assigned_in_expr(right, out);
         }
         ExprKind::Unary { operand, .. } => collect_assigned_in_expr(operand, out),
-        ExprKind::Grouping(e) | ExprKind::Spread(e) | ExprKind::Throw(e) => {
+        ExprKind::Grouping(e) | ExprKind::Spread(e) | ExprKind::Throw(e) | ExprKind::Await(e) => {
             collect_assigned_in_expr(e, out);
         }
         ExprKind::Rescue { expr, fallback } => {
@@ -742,11 +1042,11 @@ fn collect_assigned_in_expr(expr: &Expr, out: &mut HashSet<String>) {
                 match arg {
                     Argument::Positional(e) => collect_assigned_in_expr(e, out),
                     Argument::Named(n) => collect_assigned_in_expr(&n.value, out),
-                    Argument::Block(e) => collect_assigned_in_expr(e, out),
+                    Argument::Block(e) | Argument::Spread(e) => collect_assigned_in_expr(e, out),
                 }
             }
         }
-        ExprKind::Array(elements) => {
+        ExprKind::Array(elements) | ExprKind::Tuple(elements) => {
             for e in elements {
                 collect_assigned_in_expr(e, out);
             }
@@ -796,9 +1096,10 @@ fn collect_assigned_in_expr(expr: &Expr, out: &mut HashSet<String>) {
             // referenced afterward.
             out.insert(variable.clone());
         }
-        ExprKind::HashComprehension {
-            variable, iterable, ..
-        } => {
+        ExprKind::HashComprehension(comp) => {
+            let HashComprehension {
+                variable, iterable, ..
+            } = &**comp;
             collect_assigned_in_expr(iterable, out);
             out.insert(variable.clone());
         }
@@ -821,7 +1122,7 @@ fn collect_assigned_in_expr(expr: &Expr, out: &mut HashSet<String>) {
         | ExprKind::Null
         | ExprKind::This
         | ExprKind::Super
-        | ExprKind::Variable(_)
+        | ExprKind::Variable(_, _)
         | ExprKind::CommandSubstitution(_)
         | ExprKind::SdqlBlock { .. } => {}
     }
@@ -835,7 +1136,7 @@ fn collect_pattern_bindings(body: &Expr, out: &mut HashSet<String>) {
     // Walk the arm body and pretend any bare Variable is a binding. This
     // is intentionally over-inclusive for now.
     fn walk(e: &Expr, out: &mut HashSet<String>) {
-        if let ExprKind::Variable(name) = &e.kind {
+        if let ExprKind::Variable(name, _) = &e.kind {
             out.insert(name.clone());
         }
         match &e.kind {
@@ -8,9 +8,32 @@
 //! substitution" and suggests the safe alternative for each. Models,
 //! migrations, tests, and helpers are out of scope: those layers
 //! legitimately use these APIs against operator-supplied data.
+//!
+//! The `security/*` pack looks for web security smells anywhere in an app,
+//! each reported with the safer alternative:
+//!
+//! - `security/interpolated-query` — a query string (`where`, `having`,
+//!   `query`, `db_query_raw`, `solidb_query`) built by interpolation or
+//!   concatenation instead of bind vars;
+//! - `security/raw-request-data` — a raw `<%- %>` tag whose value comes
+//!   from `params` / `req` / headers / cookies, directly or through a
+//!   variable the template assigned from them;
+//! - `security/missing-csrf` — a hand-written non-GET `<form>` in a template
+//!   with no `csrf_field()`, or a state-changing route in `config/routes.sl`
+//!   that a `skip_csrf` pattern exempts from CSRF protection;
+//! - `security/hardcoded-secret` — a secret-looking string literal bound to a
+//!   `password` / `api_key` / `secret`-style name, or a known credential
+//!   format (AWS, Stripe, GitHub, Slack, PEM private keys), outside tests.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use std::collections::HashSet;
 
-use crate::ast::expr::{Expr, ExprKind};
-use crate::lint::{LintDiagnostic, Severity};
+use crate::ast::expr::{Argument, BinaryOp, Expr, ExprKind, InterpolatedPart};
+use crate::ast::{Stmt, StmtKind};
+use crate::lint::templates::{for_each_expr, walk_expr};
+use crate::lint::{suppress, LintDiagnostic, Severity};
 use crate::span::Span;
 
 /// Returns true when `file_path` lives in a request-handling MVC dir.
@@ -44,6 +67,7 @@ pub fn check_dangerous_server_builtin(
             message: rule_message.to_string(),
             span,
             severity: Severity::Warning,
+            fix: None,
         });
     }
 }
@@ -62,7 +86,7 @@ fn classify(expr: &Expr) -> Option<(&'static str, Span)> {
         )),
         ExprKind::Call { callee, .. } => match &callee.kind {
             // Bare-name builtins.
-            ExprKind::Variable(name) if name == "db_query_raw" => Some((
+            ExprKind::Variable(name, _) if name == "db_query_raw" => Some((
                 "db_query_raw splices its argument straight into a query — \
                  request-derived input becomes SQL/AQL injection. Prefer \
                  the parameterised `@sdbql{ ... #{value} ... }` block or \
@@ -72,7 +96,7 @@ fn classify(expr: &Expr) -> Option<(&'static str, Span)> {
             )),
             // Class.method calls — `Trusted.*` and `System.shell*`.
             ExprKind::Member { object, name } => match (&object.kind, name.as_str()) {
-                (ExprKind::Variable(class), _) if class == "Trusted" => Some((
+                (ExprKind::Variable(class, _), _) if class == "Trusted" => Some((
                     "Trusted.* bypasses the app-root filesystem jail — \
                      request-controlled paths become arbitrary file read/write. \
                      Prefer the jailed `File.*` API (File.read, File.write, \
@@ -80,7 +104,7 @@ fn classify(expr: &Expr) -> Option<(&'static str, Span)> {
                      root.",
                     expr.span,
                 )),
-                (ExprKind::Variable(class), method)
+                (ExprKind::Variable(class, _), method)
                     if class == "System" && (method == "shell" || method == "shell_sync") =>
                 {
                     Some((
@@ -99,6 +123,565 @@ fn classify(expr: &Expr) -> Option<(&'static str, Span)> {
     }
 }
 
+/// Query-builder methods whose string argument is spliced into AQL/SDBQL.
+const QUERY_METHODS: &[&str] = &["where", "or_where", "where_not", "having", "query"];
+
+/// Query builtins and the position of their query-string argument.
+const QUERY_BUILTINS: &[(&str, usize)] = &[
+    ("db_query_raw", 0),
+    ("db_query_hardcoded", 0),
+    ("solidb_query", 2),
+];
+
+/// Names a handler's request data arrives under.
+const REQUEST_NAMES: &[&str] = &["params", "req", "request", "headers", "cookies"];
+
+lazy_static! {
+    /// Binding names that hold credentials.
+    static ref SECRET_NAME: Regex = Regex::new(
+        r"(?i)(secret|passw(or)?d|api_?key|access_?key|auth_?token|access_?token|private_?key|client_?secret)"
+    )
+    .unwrap();
+    /// Credential formats that are secrets whatever they're bound to.
+    static ref SECRET_VALUE: Regex = Regex::new(
+        r"^(AKIA[0-9A-Z]{16}|sk_live_[0-9A-Za-z]{16,}|rk_live_[0-9A-Za-z]{16,}|gh[pousr]_[0-9A-Za-z]{30,}|xox[abpr]-[0-9A-Za-z-]{10,})$|-----BEGIN [A-Z ]*PRIVATE KEY-----"
+    )
+    .unwrap();
+    /// A hand-written form tag and its method.
+    static ref FORM_TAG: Regex =
+        Regex::new(r#"(?is)<form\b[^>]*?\bmethod\s*=\s*["']?(\w+)"#).unwrap();
+}
+
+fn warn(rule: &'static str, message: String, span: Span) -> LintDiagnostic {
+    LintDiagnostic {
+        rule,
+        message,
+        span,
+        severity: Severity::Warning,
+        fix: None,
+    }
+}
+
+/// Is `expr` a string assembled from non-literal parts?
+fn is_built_string(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::InterpolatedString(parts) => parts
+            .iter()
+            .any(|p| matches!(p, InterpolatedPart::Expression(_))),
+        ExprKind::Binary {
+            left,
+            operator: BinaryOp::Add,
+            right,
+        } => {
+            let literal = |e: &Expr| matches!(e.kind, ExprKind::StringLiteral(_));
+            let stringy = |e: &Expr| {
+                literal(e)
+                    || matches!(e.kind, ExprKind::InterpolatedString(_))
+                    || is_built_string(e)
+            };
+            (stringy(left) || stringy(right)) && !(literal(left) && literal(right))
+        }
+        ExprKind::Grouping(inner) => is_built_string(inner),
+        _ => false,
+    }
+}
+
+/// Does `expr` read request data (`params[...]`, `req["body"]`, ...)?
+fn reads_request_data(expr: &Expr) -> bool {
+    let mut found = false;
+    walk_expr(expr, &mut |e| {
+        if let ExprKind::Variable(name, _) = &e.kind {
+            found |= REQUEST_NAMES.contains(&name.as_str());
+        }
+    });
+    found
+}
+
+fn first_positional(arguments: &[Argument]) -> Option<&Expr> {
+    match arguments.first() {
+        Some(Argument::Positional(e)) => Some(e),
+        _ => None,
+    }
+}
+
+/// `security/interpolated-query`: `Post.where("title == '#{q}'")`,
+/// `db_query_raw("FOR u IN users FILTER u.name == '" + name + "'")`.
+pub fn check_interpolated_query(expr: &Expr, diagnostics: &mut Vec<LintDiagnostic>) {
+    let ExprKind::Call { callee, arguments } = &expr.kind else {
+        return;
+    };
+    if let ExprKind::Variable(name, _) = &callee.kind {
+        let Some(&(builtin, position)) = QUERY_BUILTINS.iter().find(|(b, _)| b == name) else {
+            return;
+        };
+        let query = match arguments.get(position) {
+            Some(Argument::Positional(e)) => e,
+            _ => return,
+        };
+        if is_built_string(query) {
+            diagnostics.push(warn(
+                "security/interpolated-query",
+                format!(
+                    "query string passed to {}() is built by interpolation — a value \
+                     from the request becomes AQL injection. Use an @sdbql{{ ... }} \
+                     block, which binds every #{{...}} value, or a model query with \
+                     bind vars: Model.where(\"doc.name == @name\", {{ \"name\": name }}).",
+                    builtin
+                ),
+                query.span,
+            ));
+        }
+        return;
+    }
+    let method = match &callee.kind {
+        ExprKind::Member { name, .. } | ExprKind::SafeMember { name, .. } => name.as_str(),
+        _ => return,
+    };
+    if !QUERY_METHODS.contains(&method) {
+        return;
+    }
+    if first_positional(arguments).is_some_and(is_built_string) {
+        diagnostics.push(warn(
+            "security/interpolated-query",
+            format!(
+                "query string passed to {}() is built by interpolation — a value \
+                 from the request becomes AQL injection. Use a placeholder and bind \
+                 vars instead: {}(\"doc.title == @title\", {{ \"title\": title }}), \
+                 or the hash form {}({{ \"title\": title }}).",
+                method, method, method
+            ),
+            expr.span,
+        ));
+    }
+}
+
+/// `security/hardcoded-secret` for a binding `name = value` (let, const,
+/// assignment or hash entry). Test code is exempt — fixtures need
+/// passwords.
+pub fn check_hardcoded_secret(
+    name: &str,
+    value: &Expr,
+    file_path: Option<&str>,
+    diagnostics: &mut Vec<LintDiagnostic>,
+) {
+    if file_path.is_some_and(is_test_path) {
+        return;
+    }
+    let ExprKind::StringLiteral(literal) = &value.kind else {
+        return;
+    };
+    // Credential formats are reported by `check_secret_literal` whatever
+    // they're bound to; this catches the rest by name.
+    if SECRET_NAME.is_match(name) && looks_like_secret(literal) && !SECRET_VALUE.is_match(literal) {
+        diagnostics.push(warn(
+            "security/hardcoded-secret",
+            format!(
+                "'{}' is a hardcoded credential — anyone with the repository has it, \
+                 and rotating it needs a deploy. Read it from the environment instead: \
+                 getenv(\"{}\"), set in .env (kept out of git) or your host's secret store.",
+                name,
+                name.to_ascii_uppercase()
+            ),
+            value.span,
+        ));
+    }
+}
+
+/// `security/hardcoded-secret` for a credential-format literal in any position.
+pub fn check_secret_literal(
+    expr: &Expr,
+    file_path: Option<&str>,
+    diagnostics: &mut Vec<LintDiagnostic>,
+) {
+    if let ExprKind::StringLiteral(literal) = &expr.kind {
+        if SECRET_VALUE.is_match(literal) && !file_path.is_some_and(is_test_path) {
+            diagnostics.push(warn(
+                "security/hardcoded-secret",
+                "string literal is a credential (access key / token / private key) — \
+                 anyone with the repository has it. Read it from the environment \
+                 with getenv(...) and rotate the leaked one."
+                    .to_string(),
+                expr.span,
+            ));
+        }
+    }
+}
+
+/// A value worth flagging under a secret-ish name: long enough, no spaces
+/// (a label or prompt), not an env var name or an obvious placeholder.
+fn looks_like_secret(value: &str) -> bool {
+    let placeholder = [
+        "changeme",
+        "change_me",
+        "password",
+        "secret",
+        "xxx",
+        "todo",
+        "example",
+    ]
+    .iter()
+    .any(|p| value.to_ascii_lowercase().contains(p))
+        || value.starts_with('<')
+        || value.contains("${");
+    let env_name = value
+        .chars()
+        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    value.len() >= 8 && !value.contains(char::is_whitespace) && !placeholder && !env_name
+}
+
+fn is_test_path(file: &str) -> bool {
+    let normalised = file.replace('\\', "/");
+    normalised.starts_with("tests/")
+        || normalised.contains("/tests/")
+        || normalised.ends_with("_spec.sl")
+        || normalised.ends_with("_test.sl")
+}
+
+/// Template-source rules: `security/missing-csrf` on hand-written forms and
+/// `security/raw-request-data` on `<%- %>` tags. `code` is the extracted
+/// embedded code, which carries the `# soli-lint-disable` comments.
+pub fn check_template_source(source: &str, code: &str) -> Vec<LintDiagnostic> {
+    let mut diagnostics = Vec::new();
+    let line_of = |offset: usize| source[..offset].matches('\n').count() + 1;
+
+    for cap in FORM_TAG.captures_iter(source) {
+        let method = cap[1].to_ascii_lowercase();
+        if method == "get" || method == "dialog" {
+            continue;
+        }
+        let start = cap.get(0).unwrap().start();
+        let body_end = source[start..]
+            .find("</form")
+            .map_or(source.len(), |end| start + end);
+        let body = &source[start..body_end];
+        if body.contains("csrf_field") || body.contains("csrf_token") {
+            continue;
+        }
+        diagnostics.push(warn(
+            "security/missing-csrf",
+            format!(
+                "<form method=\"{}\"> has no CSRF token — with SOLI_CSRF_TOKENS=require \
+                 the post is rejected, and without it the form relies on the Origin \
+                 check alone. Add <%- csrf_field() %> inside the form, or build it \
+                 with form_with(...), which adds one.",
+                method
+            ),
+            Span::new(0, 0, line_of(start), 1),
+        ));
+    }
+
+    let tainted = crate::parse(code)
+        .map(|program| request_derived_names(&program.statements))
+        .unwrap_or_default();
+    for (snippet, line) in crate::template::parser::raw_output_snippets(source) {
+        let Ok(program) = crate::parse(&snippet) else {
+            continue;
+        };
+        let reads = program.statements.iter().any(|stmt| {
+            matches!(&stmt.kind, crate::ast::StmtKind::Expression(e)
+                if reads_request_data(e) || mentions_any(e, &tainted))
+        });
+        if reads {
+            diagnostics.push(warn(
+                "security/raw-request-data",
+                "<%- %> outputs request data without HTML escaping — a crafted parameter \
+                 becomes stored or reflected XSS. Use <%= %> (escaped), or sanitize_html() \
+                 when some markup must survive."
+                    .to_string(),
+                Span::new(0, 0, line, 1),
+            ));
+        }
+    }
+
+    let suppressions = suppress::collect_suppressions(code);
+    diagnostics.retain(|d| !suppressions.suppresses(d.span.line, d.rule));
+    diagnostics
+}
+
+/// Variables a template assigns from request data (`<% q = params["q"] %>`),
+/// followed through later assignments (`<% label = "Results for " + q %>`).
+fn request_derived_names(stmts: &[Stmt]) -> HashSet<String> {
+    let mut assignments: Vec<(&str, &Expr)> = Vec::new();
+    for stmt in stmts {
+        if let StmtKind::Let {
+            name,
+            initializer: Some(value),
+            ..
+        } = &stmt.kind
+        {
+            assignments.push((name, value));
+        }
+    }
+    for_each_expr(stmts, &mut |expr| {
+        if let ExprKind::Assign { target, value } = &expr.kind {
+            if let ExprKind::Variable(name, _) = &target.kind {
+                assignments.push((name, value));
+            }
+        }
+    });
+
+    let mut tainted = HashSet::new();
+    loop {
+        let before = tainted.len();
+        for (name, value) in &assignments {
+            if reads_request_data(value) || mentions_any(value, &tainted) {
+                tainted.insert(name.to_string());
+            }
+        }
+        if tainted.len() == before {
+            return tainted;
+        }
+    }
+}
+
+fn mentions_any(expr: &Expr, names: &HashSet<String>) -> bool {
+    let mut found = false;
+    walk_expr(expr, &mut |e| {
+        if let ExprKind::Variable(name, _) = &e.kind {
+            found |= names.contains(name);
+        }
+    });
+    found
+}
+
+/// Path segments of routes that legitimately skip CSRF: webhooks and
+/// callbacks carry no session cookie for a forged form to ride.
+const CSRF_EXEMPT_SEGMENTS: &[&str] = &[
+    "webhook",
+    "webhooks",
+    "hooks",
+    "callback",
+    "callbacks",
+    "oauth",
+];
+
+/// Whether `path` is a webhook, callback or token authenticated API route:
+/// one of its segments is in [`CSRF_EXEMPT_SEGMENTS`], or its first is `api`.
+/// Only whole segments count: `/capital` and `/hooks_admin` are neither.
+fn is_csrf_exempt_path(path: &str) -> bool {
+    let mut segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_ascii_lowercase);
+    let first = segments.next();
+    first.as_deref() == Some("api")
+        || first
+            .into_iter()
+            .chain(segments)
+            .any(|segment| CSRF_EXEMPT_SEGMENTS.contains(&segment.as_str()))
+}
+
+/// The `skip_csrf` patterns `stmts` declare, for `LintConfig` to carry the
+/// ones of an app's `config/application.sl`.
+pub fn csrf_skip_patterns(stmts: &[Stmt]) -> Vec<String> {
+    let mut walk = RouteWalk::default();
+    walk.statements(stmts, "", None);
+    walk.skips
+}
+
+/// `security/missing-csrf` for `config/routes.sl`: a `post`/`put`/`patch`/
+/// `delete` route (or a `resources` block's) that a `skip_csrf` pattern
+/// exempts, when its path doesn't look like a webhook or API endpoint.
+/// Patterns come from the routes file and from `app_skips`, those of the
+/// app's `config/application.sl`.
+pub fn check_csrf_skipped_routes(
+    stmts: &[Stmt],
+    file_path: Option<&str>,
+    app_skips: &[String],
+    diagnostics: &mut Vec<LintDiagnostic>,
+) {
+    let Some(file) = file_path else {
+        return;
+    };
+    let normalised = file.replace('\\', "/");
+    if !(normalised == "config/routes.sl" || normalised.ends_with("/config/routes.sl")) {
+        return;
+    }
+
+    let mut walk = RouteWalk::default();
+    walk.statements(stmts, "", None);
+    walk.skips.extend(app_skips.iter().cloned());
+
+    for (routes, span) in &walk.routes {
+        let covered = routes.iter().find_map(|(method, path)| {
+            if is_csrf_exempt_path(path) {
+                return None;
+            }
+            walk.skips
+                .iter()
+                .find(|pattern| csrf_pattern_covers(pattern, path))
+                .map(|pattern| (method, path, pattern))
+        });
+        if let Some((method, path, pattern)) = covered {
+            diagnostics.push(warn(
+                "security/missing-csrf",
+                format!(
+                    "{} {} is exempt from CSRF protection by skip_csrf(\"{}\") — a form on \
+                     another site can submit it with the user's session cookie. Narrow the \
+                     pattern to the webhook or API paths that need it, and let browser \
+                     forms post with csrf_field() / form_with(...).",
+                    method, path, pattern
+                ),
+                *span,
+            ));
+        }
+    }
+}
+
+/// State-changing routes and `skip_csrf` patterns declared by a routes file.
+#[derive(Default)]
+struct RouteWalk {
+    skips: Vec<String>,
+    /// The routes each DSL call declares, reported at the call.
+    routes: Vec<(Vec<(&'static str, String)>, Span)>,
+}
+
+impl RouteWalk {
+    /// `prefix` is the enclosing namespace/resource path; `resource` the
+    /// enclosing `resources` base path, for `member` / `collection` blocks.
+    fn statements(&mut self, stmts: &[Stmt], prefix: &str, resource: Option<&str>) {
+        for stmt in stmts {
+            match &stmt.kind {
+                StmtKind::Expression(expr) => self.call(expr, prefix, resource),
+                StmtKind::Block(body) => self.statements(body, prefix, resource),
+                StmtKind::If {
+                    then_branch,
+                    else_branch,
+                    ..
+                } => {
+                    self.statements(std::slice::from_ref(then_branch), prefix, resource);
+                    if let Some(other) = else_branch {
+                        self.statements(std::slice::from_ref(other), prefix, resource);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn call(&mut self, expr: &Expr, prefix: &str, resource: Option<&str>) {
+        let ExprKind::Call { callee, arguments } = &expr.kind else {
+            return;
+        };
+        let ExprKind::Variable(name, _) = &callee.kind else {
+            return;
+        };
+        let literal = |index: usize| match arguments.get(index) {
+            Some(Argument::Positional(e)) => match &e.kind {
+                ExprKind::StringLiteral(s) => Some(s.as_str()),
+                _ => None,
+            },
+            _ => None,
+        };
+        let block = arguments.iter().find_map(|arg| match arg {
+            Argument::Positional(e) => match &e.kind {
+                ExprKind::Lambda { body, .. } => Some(body.as_slice()),
+                _ => None,
+            },
+            _ => None,
+        });
+        match name.as_str() {
+            "skip_csrf" => {
+                if let Some(pattern) = literal(0) {
+                    self.skips.push(pattern.to_string());
+                }
+            }
+            "post" | "put" | "patch" | "delete" => {
+                if let Some(path) = literal(0) {
+                    let method = match name.as_str() {
+                        "post" => "POST",
+                        "put" => "PUT",
+                        "patch" => "PATCH",
+                        _ => "DELETE",
+                    };
+                    self.routes
+                        .push((vec![(method, join_route(prefix, path))], expr.span));
+                }
+            }
+            "uploads" => {
+                if let (Some(resource), Some(field)) = (literal(0), literal(1)) {
+                    let path = join_route(prefix, &format!("{}/:id/{}", resource, field));
+                    self.routes
+                        .push((vec![("POST", path.clone()), ("DELETE", path)], expr.span));
+                }
+            }
+            "resources" => {
+                let Some(resource_name) = literal(0) else {
+                    return;
+                };
+                let base = join_route(prefix, resource_name);
+                let member = format!("{}/:id", base);
+                self.routes.push((
+                    vec![
+                        ("POST", base.clone()),
+                        ("PUT", member.clone()),
+                        ("PATCH", member.clone()),
+                        ("DELETE", member.clone()),
+                        ("POST", format!("{}/update", member)),
+                        ("POST", format!("{}/delete", member)),
+                    ],
+                    expr.span,
+                ));
+                if let Some(body) = block {
+                    let child = format!(
+                        "{}/:{}_id",
+                        base,
+                        crate::interpreter::builtins::router::singularize(resource_name)
+                    );
+                    self.statements(body, &child, Some(&base));
+                }
+            }
+            "namespace" => {
+                if let (Some(segment), Some(body)) = (literal(0), block) {
+                    self.statements(body, &join_route(prefix, segment), None);
+                }
+            }
+            "member" | "collection" => {
+                if let (Some(base), Some(body)) = (resource, block) {
+                    let scope = if name == "member" {
+                        format!("{}/:id", base)
+                    } else {
+                        base.to_string()
+                    };
+                    self.statements(body, &scope, resource);
+                }
+            }
+            "middleware" => {
+                if let Some(body) = block {
+                    self.statements(body, prefix, resource);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn join_route(prefix: &str, path: &str) -> String {
+    format!(
+        "{}/{}",
+        prefix.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    )
+}
+
+/// Does `skip_csrf(pattern)` cover `route`? Mirrors the server's matching
+/// (`prefix/*`, trailing `*`, exact) with `:param` segments matching any
+/// concrete segment of an exact pattern.
+fn csrf_pattern_covers(pattern: &str, route: &str) -> bool {
+    if let Some(prefix) = pattern.strip_suffix("/*") {
+        route == prefix
+            || route
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with('/'))
+    } else if let Some(prefix) = pattern.strip_suffix('*') {
+        route.starts_with(prefix)
+    } else {
+        let (a, b): (Vec<&str>, Vec<&str>) =
+            (pattern.split('/').collect(), route.split('/').collect());
+        a.len() == b.len() && a.iter().zip(&b).all(|(p, r)| p == r || r.starts_with(':'))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -109,7 +692,7 @@ mod tests {
     }
 
     fn variable(name: &str) -> Expr {
-        Expr::new(ExprKind::Variable(name.to_string()), span())
+        Expr::new(ExprKind::variable(name.to_string()), span())
     }
 
     fn call(callee: Expr) -> Expr {
@@ -1,24 +1,97 @@
 use std::collections::HashSet;
 
+use crate::ast::expr::{Expr, ExprKind, MatchArm, MatchPattern};
 use crate::ast::stmt::{ClassDecl, Stmt, StmtKind};
 use crate::lint::{LintDiagnostic, Severity};
 use crate::span::Span;
 
 pub fn check_unreachable_code(stmts: &[Stmt], diagnostics: &mut Vec<LintDiagnostic>) {
-    let mut found_return = false;
+    let mut exit = None;
     for stmt in stmts {
-        if found_return {
+        if let Some(exit) = exit {
             diagnostics.push(LintDiagnostic {
                 rule: "smell/unreachable-code",
-                message: "unreachable code after return statement".to_string(),
+                message: format!("unreachable code after {}", exit),
                 span: stmt.span,
                 severity: Severity::Warning,
+                fix: None,
             });
             break; // Only report once per block
         }
-        if matches!(stmt.kind, StmtKind::Return(_)) {
-            found_return = true;
+        exit = exit_kind(stmt);
+    }
+}
+
+/// What makes `stmt` always leave its block, if it does: a `return`,
+/// `throw` or `break`, a block holding one, or an `if`/`else` whose every
+/// branch does.
+fn exit_kind(stmt: &Stmt) -> Option<&'static str> {
+    match &stmt.kind {
+        StmtKind::Return(_) => Some("return statement"),
+        StmtKind::Throw(_) => Some("throw statement"),
+        StmtKind::Break => Some("break statement"),
+        StmtKind::Block(stmts) => stmts.iter().find_map(exit_kind),
+        StmtKind::If {
+            then_branch,
+            else_branch: Some(else_branch),
+            ..
+        } => {
+            exit_kind(then_branch)?;
+            exit_kind(else_branch)?;
+            Some("an if/else whose branches all exit")
         }
+        _ => None,
+    }
+}
+
+/// `if (false)`, `if (true) … else`, `while (false)`: a branch whose
+/// constant condition means it never runs.
+pub fn check_constant_condition(
+    keyword: &str,
+    condition: &Expr,
+    has_else: bool,
+    diagnostics: &mut Vec<LintDiagnostic>,
+) {
+    let mut condition = condition;
+    while let ExprKind::Grouping(inner) = &condition.kind {
+        condition = inner;
+    }
+    let ExprKind::BoolLiteral(value) = condition.kind else {
+        return;
+    };
+    let message = match (keyword, value) {
+        ("while", false) => "'while (false)' body never runs",
+        ("if", false) => "'if (false)' branch never runs",
+        ("if", true) if has_else => "'else' branch of 'if (true)' never runs",
+        _ => return,
+    };
+    diagnostics.push(LintDiagnostic {
+        rule: "smell/unreachable-branch",
+        message: message.to_string(),
+        span: condition.span,
+        severity: Severity::Warning,
+        fix: None,
+    });
+}
+
+/// Match arms after an unguarded `_` or bare binding, which matches
+/// everything, never run.
+pub fn check_unreachable_arms(arms: &[MatchArm], diagnostics: &mut Vec<LintDiagnostic>) {
+    let catch_all = arms.iter().position(|arm| {
+        arm.guard.is_none()
+            && matches!(
+                arm.pattern,
+                MatchPattern::Wildcard | MatchPattern::Variable(_)
+            )
+    });
+    if let Some(arm) = catch_all.and_then(|at| arms.get(at + 1)) {
+        diagnostics.push(LintDiagnostic {
+            rule: "smell/unreachable-branch",
+            message: "match arm after a catch-all arm never runs".to_string(),
+            span: arm.span,
+            severity: Severity::Warning,
+            fix: None,
+        });
     }
 }
 
@@ -30,6 +103,7 @@ pub fn check_empty_catch(catch_block: &Stmt, diagnostics: &mut Vec<LintDiagnosti
                 message: "empty catch block".to_string(),
                 span: catch_block.span,
                 severity: Severity::Warning,
+                fix: None,
             });
         }
     }
@@ -47,6 +121,7 @@ pub fn check_duplicate_methods(class: &ClassDecl, diagnostics: &mut Vec<LintDiag
                 ),
                 span: method.span,
                 severity: Severity::Warning,
+                fix: None,
             });
         }
     }
@@ -59,6 +134,7 @@ pub fn check_deep_nesting(depth: usize, span: Span, diagnostics: &mut Vec<LintDi
             message: format!("nesting depth {} exceeds maximum of 4", depth),
             span,
             severity: Severity::Warning,
+            fix: None,
         });
     }
 }
@@ -66,7 +142,6 @@ pub fn check_deep_nesting(depth: usize, span: Span, diagnostics: &mut Vec<LintDi
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ast::expr::{Expr, ExprKind};
     use crate::ast::stmt::{MethodDecl, Visibility};
 
     fn span() -> Span {
@@ -124,6 +199,68 @@ mod tests {
         assert_eq!(d.len(), 1);
     }
 
+    #[test]
+    fn test_unreachable_after_throw_and_exhaustive_if() {
+        let expr = || {
+            Stmt::new(
+                StmtKind::Expression(Expr::new(ExprKind::IntLiteral(1), span())),
+                span(),
+                None,
+            )
+        };
+        let throw = || {
+            Stmt::new(
+                StmtKind::Throw(Expr::new(ExprKind::IntLiteral(1), span())),
+                span(),
+                None,
+            )
+        };
+        let if_else = |else_branch: Stmt| {
+            Stmt::new(
+                StmtKind::If {
+                    condition: Expr::new(ExprKind::BoolLiteral(true), span()),
+                    then_branch: Box::new(Stmt::new(StmtKind::Return(None), span(), None)),
+                    else_branch: Some(Box::new(else_branch)),
+                },
+                span(),
+                None,
+            )
+        };
+
+        let mut d = Vec::new();
+        check_unreachable_code(&[throw(), expr()], &mut d);
+        assert_eq!(d[0].message, "unreachable code after throw statement");
+
+        let mut d = Vec::new();
+        check_unreachable_code(&[if_else(throw()), expr()], &mut d);
+        assert_eq!(
+            d[0].message,
+            "unreachable code after an if/else whose branches all exit"
+        );
+
+        // One branch falls through, so what follows runs.
+        let mut d = Vec::new();
+        check_unreachable_code(&[if_else(expr()), expr()], &mut d);
+        assert!(d.is_empty());
+    }
+
+    #[test]
+    fn test_constant_conditions() {
+        let literal = |b| Expr::new(ExprKind::BoolLiteral(b), span());
+        let mut d = Vec::new();
+        check_constant_condition("if", &literal(false), false, &mut d);
+        check_constant_condition("while", &literal(false), false, &mut d);
+        check_constant_condition("if", &literal(true), true, &mut d);
+        assert_eq!(d.len(), 3);
+        assert!(d.iter().all(|d| d.rule == "smell/unreachable-branch"));
+
+        // `while (true)` loops on purpose and `if (true)` alone runs.
+        let mut d = Vec::new();
+        check_constant_condition("while", &literal(true), false, &mut d);
+        check_constant_condition("if", &literal(true), false, &mut d);
+        assert!(d.is_empty());
+    }
+
     #[test]
     fn test_empty_catch_detected() {
         let catch = Stmt::new(StmtKind::Block(vec![]), span(), None);
@@ -159,7 +296,9 @@ mod tests {
                     name: "bar".to_string(),
                     params: vec![],
                     return_type: None,
-                    body: vec![],
+                    body: Default::default(),
+                    decorators: vec![],
+                    is_async: false,
                     span: span(),
                 },
                 MethodDecl {
@@ -168,7 +307,9 @@ mod tests {
                     name: "bar".to_string(),
                     params: vec![],
                     return_type: None,
-                    body: vec![],
+                    body: Default::default(),
+                    decorators: vec![],
+                    is_async: false,
                     span: Span::new(0, 0, 5, 1),
                 },
             ],
@@ -176,6 +317,7 @@ mod tests {
             static_block: None,
             class_statements: vec![],
             nested_classes: vec![],
+            decorators: vec![],
             span: span(),
         };
         let mut d = Vec::new();
@@ -198,7 +340,9 @@ mod tests {
                     name: "bar".to_string(),
                     params: vec![],
                     return_type: None,
-                    body: vec![],
+                    body: Default::default(),
+                    decorators: vec![],
+                    is_async: false,
                     span: span(),
                 },
                 MethodDecl {
@@ -207,7 +351,9 @@ mod tests {
                     name: "baz".to_string(),
                     params: vec![],
                     return_type: None,
-                    body: vec![],
+                    body: Default::default(),
+                    decorators: vec![],
+                    is_async: false,
                     span: span(),
                 },
             ],
@@ -215,6 +361,7 @@ mod tests {
             static_block: None,
             class_statements: vec![],
             nested_classes: vec![],
+            decorators: vec![],
             span: span(),
         };
         let mut d = Vec::new();
@@ -1,4 +1,6 @@
-use crate::lint::{LintDiagnostic, Severity};
+use crate::ast::expr::ExprKind;
+use crate::ast::stmt::{Stmt, StmtKind};
+use crate::lint::{LintDiagnostic, LintFix, Severity};
 use crate::span::Span;
 
 pub fn check_empty_block(span: Span, diagnostics: &mut Vec<LintDiagnostic>) {
@@ -7,6 +9,7 @@ pub fn check_empty_block(span: Span, diagnostics: &mut Vec<LintDiagnostic>) {
         message: "empty block".to_string(),
         span,
         severity: Severity::Warning,
+        fix: None,
     });
 }
 
@@ -18,11 +21,53 @@ pub fn check_line_lengths(source: &str, diagnostics: &mut Vec<LintDiagnostic>) {
                 message: format!("line exceeds 120 characters ({} chars)", line.len()),
                 span: Span::new(0, 0, i + 1, 1),
                 severity: Severity::Warning,
+                fix: None,
             });
         }
     }
 }
 
+/// `return x` as the last statement of a function body: the last expression
+/// is the function's value already. Called on function, method and lambda
+/// bodies. The fix drops the keyword; a returned hash literal is left alone,
+/// since `{ ... }` on its own would parse as a block.
+pub fn check_redundant_return(body: &[Stmt], source: &str, diagnostics: &mut Vec<LintDiagnostic>) {
+    let Some(last) = body.last() else {
+        return;
+    };
+    let StmtKind::Return(Some(value)) = &last.kind else {
+        return;
+    };
+    if matches!(value.kind, ExprKind::Hash(_)) {
+        return;
+    }
+    // `return` and the blanks after it, when the statement is spelled that
+    // way in `source` (the parser also synthesizes returns).
+    let start = last.span.start_usize();
+    let Some(rest) = source.get(start..).and_then(|s| s.strip_prefix("return")) else {
+        return;
+    };
+    let blanks = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    if blanks == 0 || rest[blanks..].starts_with(['\n', '\r', ';']) {
+        return;
+    }
+    diagnostics.push(LintDiagnostic {
+        rule: "style/redundant-return",
+        message: "redundant `return` — the last expression of a function is its value".to_string(),
+        span: last.span,
+        severity: Severity::Warning,
+        fix: Some(LintFix {
+            span: Span::new(
+                start,
+                start + "return".len() + blanks,
+                last.span.line_usize(),
+                last.span.column_usize(),
+            ),
+            replacement: String::new(),
+        }),
+    });
+}
+
 /// Warn when a controller file imports a model from `app/models/`. Models
 /// are auto-loaded by `soli serve` and the REPL (see `load_models()` in
 /// `src/serve/app_loader.rs`), so the import is redundant.
@@ -47,6 +92,7 @@ pub fn check_redundant_model_import(
             .to_string(),
         span,
         severity: Severity::Warning,
+        fix: None,
     });
 }
 
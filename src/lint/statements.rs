@@ -14,19 +14,41 @@ impl Linter {
                 name,
                 type_annotation: _,
                 initializer,
+                ..
             } => {
                 rules::naming::check_variable_name(name, stmt.span, &mut self.diagnostics);
                 if let Some(init) = initializer {
+                    rules::security::check_hardcoded_secret(
+                        name,
+                        init,
+                        self.file_path.as_deref(),
+                        &mut self.diagnostics,
+                    );
                     self.lint_expr(init);
                 }
             }
 
+            StmtKind::LetTuple {
+                names, initializer, ..
+            } => {
+                for name in names {
+                    rules::naming::check_variable_name(name, stmt.span, &mut self.diagnostics);
+                }
+                self.lint_expr(initializer);
+            }
+
             StmtKind::Const {
                 name,
                 type_annotation: _,
                 initializer,
             } => {
                 rules::naming::check_constant_name(name, stmt.span, &mut self.diagnostics);
+                rules::security::check_hardcoded_secret(
+                    name,
+                    initializer,
+                    self.file_path.as_deref(),
+                    &mut self.diagnostics,
+                );
                 self.lint_expr(initializer);
             }
 
@@ -48,6 +70,12 @@ impl Linter {
             } => {
                 self.depth += 1;
                 rules::smell::check_deep_nesting(self.depth, stmt.span, &mut self.diagnostics);
+                rules::smell::check_constant_condition(
+                    "if",
+                    condition,
+                    else_branch.is_some(),
+                    &mut self.diagnostics,
+                );
                 self.lint_expr(condition);
                 self.lint_stmt(then_branch);
                 if let Some(else_b) = else_branch {
@@ -59,6 +87,12 @@ impl Linter {
             StmtKind::While { condition, body } => {
                 self.depth += 1;
                 rules::smell::check_deep_nesting(self.depth, stmt.span, &mut self.diagnostics);
+                rules::smell::check_constant_condition(
+                    "while",
+                    condition,
+                    false,
+                    &mut self.diagnostics,
+                );
                 self.lint_expr(condition);
                 self.lint_stmt(body);
                 self.depth -= 1;
@@ -67,10 +101,26 @@ impl Linter {
             StmtKind::For {
                 variable,
                 index_variable,
+                destructure,
                 iterable,
                 body,
             } => {
-                rules::naming::check_variable_name(variable, stmt.span, &mut self.diagnostics);
+                match destructure {
+                    Some(names) => {
+                        for name in names {
+                            rules::naming::check_variable_name(
+                                name,
+                                stmt.span,
+                                &mut self.diagnostics,
+                            );
+                        }
+                    }
+                    None => rules::naming::check_variable_name(
+                        variable,
+                        stmt.span,
+                        &mut self.diagnostics,
+                    ),
+                }
                 if let Some(idx_var) = index_variable {
                     rules::naming::check_variable_name(idx_var, stmt.span, &mut self.diagnostics);
                 }
@@ -87,7 +137,7 @@ impl Linter {
                 }
             }
 
-            StmtKind::Throw(expr) => self.lint_expr(expr),
+            StmtKind::Throw(expr) | StmtKind::Yield(expr) => self.lint_expr(expr),
 
             StmtKind::Try {
                 try_block,
@@ -127,6 +177,22 @@ impl Linter {
 
             StmtKind::Class(decl) => self.lint_class_decl(decl),
 
+            StmtKind::Struct(decl) => {
+                rules::naming::check_class_name(
+                    "struct",
+                    &decl.name,
+                    decl.span,
+                    &mut self.diagnostics,
+                );
+                for field in &decl.fields {
+                    rules::naming::check_variable_name(
+                        &field.name,
+                        field.span,
+                        &mut self.diagnostics,
+                    );
+                }
+            }
+
             StmtKind::Enum(decl) => {
                 rules::naming::check_class_name(
                     "enum",
@@ -168,6 +234,15 @@ impl Linter {
                 );
             }
 
+            StmtKind::TypeAlias(decl) => {
+                rules::naming::check_class_name(
+                    "type",
+                    &decl.name,
+                    decl.span,
+                    &mut self.diagnostics,
+                );
+            }
+
             StmtKind::Import(decl) => {
                 rules::style::check_redundant_model_import(
                     &decl.path,
@@ -184,6 +259,7 @@ impl Linter {
     fn lint_class_decl(&mut self, decl: &ClassDecl) {
         rules::naming::check_class_name("class", &decl.name, decl.span, &mut self.diagnostics);
         rules::smell::check_duplicate_methods(decl, &mut self.diagnostics);
+        rules::scope::check_unused_private_methods(decl, &self.source, &mut self.diagnostics);
 
         // Lint field initializers
         for field in &decl.fields {
@@ -0,0 +1,926 @@
+//! Cross-file template checks: `template/undefined-local`,
+//! `template/undefined-helper` and `template/missing-partial`.
+//!
+//! Templates render leniently — an undefined name reads as `null` — so a typo
+//! in a view, or a local a controller forgot to pass, only shows up as missing
+//! output. These rules resolve what each template can actually see:
+//!
+//! - the keys of every literal data hash some `render(...)` passes to it,
+//!   from controllers, helpers and other templates (`render "x", {...}`,
+//!   `render_partial(...)`), plus the `@fields` of the controllers involved;
+//! - an action's implicit render of `controller/action`;
+//! - the template's own assignments, loop variables and definitions;
+//! - builtins and template helpers (the render env itself), app-level
+//!   definitions (`app/helpers`, models, services ...) and `*_path` /
+//!   `*_url` route helpers.
+//!
+//! Undefined locals are only reported when every render site is known —
+//! a dynamic data hash, a template nobody renders statically, a layout or a
+//! `props(...)` component is skipped rather than guessed at. The index is
+//! built once per app and cached for the rest of the lint run.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::ast::expr::{Argument, Expr, ExprKind, HashComprehension, InterpolatedPart};
+use crate::ast::stmt::{ClassDecl, Program, Stmt, StmtKind, Visibility};
+use crate::lint::rules::scope;
+use crate::lint::{suppress, LintDiagnostic, Severity};
+use crate::span::Span;
+use crate::template::parser::TemplateNode;
+use crate::types::type_repr::Type;
+
+/// Extensions a template name resolves through, in the renderer's order.
+pub(crate) const TEMPLATE_EXTENSIONS: &[&str] =
+    &[".html.slv", ".slv", ".html.md", ".md", ".html.erb", ".erb"];
+
+thread_local! {
+    static INDEXES: RefCell<HashMap<PathBuf, Rc<ViewIndex>>> = RefCell::new(HashMap::new());
+}
+
+/// Where a template's data comes from at one render site.
+enum Data {
+    /// A literal hash (or no hash): exactly these keys, with the type of
+    /// each value written as a literal (`Any` otherwise).
+    Keys(HashMap<String, Type>),
+    /// A computed hash — anything could be in it.
+    Unknown,
+    /// `render "x"` with no context: the caller's own locals.
+    Inherit(String),
+}
+
+/// Whose `@fields` are exposed at one render site.
+enum Fields {
+    Own(HashSet<String>),
+    Caller(String),
+}
+
+struct Site {
+    data: Data,
+    fields: Fields,
+}
+
+/// Every static render site in an app, keyed by template name
+/// (`posts/_form`, views-relative, no extension).
+struct ViewIndex {
+    views_dir: PathBuf,
+    engines_dir: PathBuf,
+    /// Builtins, template helpers and top-level app definitions.
+    known: HashSet<String>,
+    sites: HashMap<String, Vec<Site>>,
+}
+
+/// Run the template rules over one template. `code` / `program` are the
+/// extracted embedded code the style rules already parsed. Templates outside
+/// an `app/views/` tree have no render sites to resolve and are skipped.
+pub fn check_template(
+    path: &str,
+    source: &str,
+    code: &str,
+    program: &Program,
+) -> Vec<LintDiagnostic> {
+    let Some(app_dir) = app_dir_for(Path::new(path)) else {
+        return Vec::new();
+    };
+    let index = index_for(app_dir);
+    let Some(name) = index.template_name(Path::new(path)) else {
+        return Vec::new();
+    };
+
+    let mut diagnostics = Vec::new();
+    index.check_partials(source, program, &mut diagnostics);
+
+    let mut defined: HashSet<String> = ["locals", "content"].map(String::from).into();
+    scope::collect_program_names(&program.statements, &mut defined);
+    scope::collect_assigned_in_stmts(&program.statements, &mut defined);
+    let locals = if name.starts_with("layouts/") || declares_props(program) {
+        None
+    } else {
+        index.locals(&name)
+    };
+    if let Some(locals) = &locals {
+        let mut visible = index.known.clone();
+        visible.extend(defined.iter().cloned());
+        visible.extend(locals.iter().cloned());
+        let mut found = Vec::new();
+        scope::check_undefined_locals(&[], &program.statements, &visible, &mut found);
+        for d in found {
+            let local = d.message.split('\'').nth(1).unwrap_or_default();
+            diagnostics.push(LintDiagnostic {
+                rule: "template/undefined-local",
+                message: format!(
+                    "'{}' is not a local of '{}': no render passes it and the template never assigns it",
+                    local, name
+                ),
+                span: d.span,
+                severity: Severity::Warning,
+                fix: None,
+            });
+        }
+    }
+
+    for_each_expr(&program.statements, &mut |expr| {
+        let ExprKind::Call { callee, .. } = &expr.kind else {
+            return;
+        };
+        let ExprKind::Variable(helper, _) = &callee.kind else {
+            return;
+        };
+        let helper = helper.as_str();
+        if index.known.contains(helper)
+            || defined.contains(helper)
+            || locals.as_ref().is_some_and(|l| l.contains(helper))
+            || scope::is_likely_global(helper)
+            || helper.ends_with("_path")
+            || helper.ends_with("_url")
+        {
+            return;
+        }
+        diagnostics.push(LintDiagnostic {
+            rule: "template/undefined-helper",
+            message: format!(
+                "'{}' is not a builtin, an app helper or a local — define it in app/helpers/",
+                helper
+            ),
+            span: callee.span,
+            severity: Severity::Warning,
+            fix: None,
+        });
+    });
+
+    let suppressions = suppress::collect_suppressions(code);
+    diagnostics.retain(|d| !suppressions.suppresses(d.span.line, d.rule));
+    diagnostics
+}
+
+/// What a template under an `app/views/` tree can see, for type-checking
+/// its embedded code.
+pub struct TemplateScope {
+    /// The template name (`posts/index`).
+    pub name: String,
+    /// Builtins, template helpers and top-level app definitions.
+    pub known: HashSet<String>,
+    /// The locals every render passes, typed from literal values; `None`
+    /// when some render site's data isn't statically known (or for a
+    /// layout or a `props(...)` component).
+    pub locals: Option<HashMap<String, Type>>,
+    /// The `@fields` of the controllers that render it.
+    pub fields: HashSet<String>,
+}
+
+/// Resolve the scope of the template at `path` against its app's render
+/// sites; `None` outside an `app/views/` tree. Shares the lint run's index.
+pub fn template_scope(path: &Path, program: &Program) -> Option<TemplateScope> {
+    let app_dir = app_dir_for(path)?;
+    let index = index_for(app_dir);
+    let name = index.template_name(path)?;
+    let locals = if name.starts_with("layouts/") || declares_props(program) {
+        None
+    } else {
+        index.data(&name, &mut HashSet::new())
+    };
+    Some(TemplateScope {
+        fields: index.fields(&name, &mut HashSet::new()),
+        known: index.known.clone(),
+        locals,
+        name,
+    })
+}
+
+/// The render-site index of the app at `app_dir`, built on first use.
+fn index_for(app_dir: PathBuf) -> Rc<ViewIndex> {
+    INDEXES.with(|cache| {
+        cache
+            .borrow_mut()
+            .entry(app_dir.clone())
+            .or_insert_with(|| Rc::new(ViewIndex::build(&app_dir)))
+            .clone()
+    })
+}
+
+/// The `app/` directory of a template under `app/views/`, if it exists.
+fn app_dir_for(path: &Path) -> Option<PathBuf> {
+    path.ancestors().find_map(|dir| {
+        let parent = dir.parent()?;
+        (dir.file_name()? == "views" && parent.file_name()? == "app" && dir.is_dir())
+            .then(|| parent.to_path_buf())
+    })
+}
+
+impl ViewIndex {
+    fn build(app_dir: &Path) -> Self {
+        let mut index = ViewIndex {
+            views_dir: app_dir.join("views"),
+            engines_dir: app_dir
+                .parent()
+                .map(|root| root.join("engines"))
+                .unwrap_or_default(),
+            known: crate::template::core_eval::builtin_names()
+                .into_iter()
+                .collect(),
+            sites: HashMap::new(),
+        };
+
+        let mut programs = Vec::new();
+        for path in files_under(app_dir, &[".sl"], Some(&index.views_dir)) {
+            if let Some(program) = parse_code_file(&path) {
+                scope::collect_program_names(&program.statements, &mut index.known);
+                programs.push((path, program));
+            }
+        }
+
+        // Each class's own `@field` assignments, then folded down the
+        // superclass chain so a subclass also exposes what its parents set.
+        let mut classes: HashMap<String, (Option<String>, HashSet<String>)> = HashMap::new();
+        for (_, program) in &programs {
+            for stmt in &program.statements {
+                if let StmtKind::Class(decl) = &stmt.kind {
+                    classes.insert(
+                        decl.name.clone(),
+                        (
+                            decl.superclass.clone(),
+                            assigned_fields(std::slice::from_ref(stmt)),
+                        ),
+                    );
+                }
+            }
+        }
+        let fields_of = |class: &str| {
+            let mut out = HashSet::new();
+            let mut seen = HashSet::new();
+            let mut next = Some(class.to_string());
+            while let Some(name) = next.take() {
+                if !seen.insert(name.clone()) {
+                    break;
+                }
+                if let Some((superclass, fields)) = classes.get(&name) {
+                    out.extend(fields.iter().cloned());
+                    next = superclass.clone();
+                }
+            }
+            out
+        };
+
+        let controllers_dir = app_dir.join("controllers");
+        for (path, program) in &programs {
+            for stmt in &program.statements {
+                let body = std::slice::from_ref(stmt);
+                let StmtKind::Class(decl) = &stmt.kind else {
+                    index.add_code_sites(body, &HashSet::new());
+                    continue;
+                };
+                let fields = fields_of(&decl.name);
+                index.add_code_sites(body, &fields);
+                if !path.starts_with(&controllers_dir) {
+                    continue;
+                }
+                // An action that returns no response renders
+                // `<controller>/<action>` with just the instance fields.
+                let key =
+                    crate::serve::app_loader::controller_key_from_path(&controllers_dir, path);
+                for method in &decl.methods {
+                    if method.is_static
+                        || method.visibility != Visibility::Public
+                        || method.name.starts_with('_')
+                    {
+                        continue;
+                    }
+                    if let Some(template) = index.resolve(&format!("{}/{}", key, method.name)) {
+                        index.sites.entry(template).or_default().push(Site {
+                            data: Data::Keys(HashMap::new()),
+                            fields: Fields::Own(fields.clone()),
+                        });
+                    }
+                }
+            }
+        }
+
+        let views_dir = index.views_dir.clone();
+        for path in files_under(&views_dir, TEMPLATE_EXTENSIONS, None) {
+            let (Some(caller), Ok(source)) =
+                (index.template_name(&path), std::fs::read_to_string(&path))
+            else {
+                continue;
+            };
+            let nodes = crate::template::parser::parse_template(&source).unwrap_or_default();
+            let mut partials = Vec::new();
+            collect_partial_nodes(&nodes, &mut partials);
+            for (partial, context, line) in partials {
+                if !is_quoted_on_line(&source, line, partial) {
+                    continue;
+                }
+                if let Some(template) = index.resolve(&partial_path(partial)) {
+                    let data = match context {
+                        None => Data::Inherit(caller.clone()),
+                        Some(expr) => literal_keys(expr).map_or(Data::Unknown, Data::Keys),
+                    };
+                    index.sites.entry(template).or_default().push(Site {
+                        data,
+                        fields: Fields::Caller(caller.clone()),
+                    });
+                }
+            }
+            if let Some(program) = template_program(&source) {
+                for (partial, data, _) in
+                    render_calls(&program.statements, &["render_partial", "partial"])
+                {
+                    if let Some(template) = index.resolve(&partial_path(&partial)) {
+                        index.sites.entry(template).or_default().push(Site {
+                            data: call_data(data),
+                            fields: Fields::Caller(caller.clone()),
+                        });
+                    }
+                }
+            }
+        }
+        index
+    }
+
+    /// Record the `render` / `render_stream` / `render_partial` calls under `stmts` (code
+    /// files), with `fields` as the instance fields in scope there.
+    fn add_code_sites(&mut self, stmts: &[Stmt], fields: &HashSet<String>) {
+        let full = render_calls(stmts, &["render", "render_stream"])
+            .into_iter()
+            .map(|(name, data, _)| (name, data));
+        let partial = render_calls(stmts, &["render_partial", "partial"])
+            .into_iter()
+            .map(|(name, data, _)| (partial_path(&name), data));
+        let sites: Vec<_> = full
+            .chain(partial)
+            .filter_map(|(name, data)| Some((self.resolve(&name)?, call_data(data))))
+            .collect();
+        for (template, data) in sites {
+            self.sites.entry(template).or_default().push(Site {
+                data,
+                fields: Fields::Own(fields.clone()),
+            });
+        }
+    }
+
+    /// The template name a file under `views_dir` answers to.
+    fn template_name(&self, path: &Path) -> Option<String> {
+        let rel = path.strip_prefix(&self.views_dir).ok()?;
+        let rel = rel
+            .components()
+            .filter_map(|c| c.as_os_str().to_str())
+            .collect::<Vec<_>>()
+            .join("/");
+        Some(strip_template_extension(&rel).to_string())
+    }
+
+    /// Resolve a render name to a template name, the way the renderer looks
+    /// it up on disk. `None` when no such file exists.
+    fn resolve(&self, name: &str) -> Option<String> {
+        if name.contains("..") {
+            return None;
+        }
+        if TEMPLATE_EXTENSIONS
+            .iter()
+            .any(|ext| self.views_dir.join(format!("{}{}", name, ext)).is_file())
+        {
+            return Some(name.to_string());
+        }
+        self.views_dir
+            .join(name)
+            .is_file()
+            .then(|| strip_template_extension(name).to_string())
+    }
+
+    /// Everything the template's data hash can hold, or `None` when that
+    /// isn't statically known.
+    fn locals(&self, template: &str) -> Option<HashSet<String>> {
+        let mut out: HashSet<String> = self
+            .data(template, &mut HashSet::new())?
+            .into_keys()
+            .collect();
+        out.extend(self.fields(template, &mut HashSet::new()));
+        Some(out)
+    }
+
+    /// The keys of the template's data hash and their types, merged over
+    /// every render site (a key whose sites disagree is `Any`).
+    fn data(
+        &self,
+        template: &str,
+        visiting: &mut HashSet<String>,
+    ) -> Option<HashMap<String, Type>> {
+        let sites = self.sites.get(template)?;
+        // A partial that (indirectly) renders itself adds nothing new.
+        if !visiting.insert(template.to_string()) {
+            return Some(HashMap::new());
+        }
+        let mut out = Some(HashMap::new());
+        for site in sites {
+            let keys = match &site.data {
+                Data::Keys(keys) => Some(keys.clone()),
+                Data::Unknown => None,
+                Data::Inherit(caller) => self.data(caller, visiting),
+            };
+            match (keys, out.as_mut()) {
+                (Some(keys), Some(acc)) => {
+                    for (key, ty) in keys {
+                        acc.entry(key)
+                            .and_modify(|seen: &mut Type| {
+                                if *seen != ty {
+                                    *seen = Type::Any;
+                                }
+                            })
+                            .or_insert(ty);
+                    }
+                }
+                _ => {
+                    out = None;
+                    break;
+                }
+            }
+        }
+        visiting.remove(template);
+        out
+    }
+
+    fn fields(&self, template: &str, visiting: &mut HashSet<String>) -> HashSet<String> {
+        let mut out = HashSet::new();
+        let Some(sites) = self.sites.get(template) else {
+            return out;
+        };
+        if !visiting.insert(template.to_string()) {
+            return out;
+        }
+        for site in sites {
+            match &site.fields {
+                Fields::Own(fields) => out.extend(fields.iter().cloned()),
+                Fields::Caller(caller) => out.extend(self.fields(caller, visiting)),
+            }
+        }
+        visiting.remove(template);
+        out
+    }
+
+    /// `template/missing-partial`: a literal partial name with no file
+    /// behind it. Engine-prefixed names resolve elsewhere and are skipped.
+    fn check_partials(&self, source: &str, program: &Program, out: &mut Vec<LintDiagnostic>) {
+        let mut missing = Vec::new();
+        if let Ok(nodes) = crate::template::parser::parse_template(source) {
+            let mut partials = Vec::new();
+            collect_partial_nodes(&nodes, &mut partials);
+            for (name, _, line) in partials {
+                if is_quoted_on_line(source, line, name) {
+                    missing.push((name.to_string(), Span::new(0, 0, line, 1)));
+                }
+            }
+        }
+        for (name, _, span) in render_calls(&program.statements, &["render_partial", "partial"]) {
+            missing.push((name, span));
+        }
+        for (name, span) in missing {
+            let path = partial_path(&name);
+            let engine = name
+                .split('/')
+                .next()
+                .is_some_and(|seg| name.contains('/') && self.engines_dir.join(seg).is_dir());
+            if engine || self.resolve(&path).is_some() {
+                continue;
+            }
+            out.push(LintDiagnostic {
+                rule: "template/missing-partial",
+                message: format!(
+                    "partial '{}' not found (expected app/views/{}.html.slv)",
+                    name, path
+                ),
+                span,
+                severity: Severity::Warning,
+                fix: None,
+            });
+        }
+    }
+}
+
+/// On-disk name for a partial, as the renderer resolves it: `users/card`
+/// → `users/_card`, `card` → `_card`; `components/` paths stay as-is.
+pub(crate) fn partial_path(name: &str) -> String {
+    if name.starts_with("components/") || name.contains("/components/") {
+        return name.to_string();
+    }
+    match name.rsplit_once('/') {
+        Some((dir, base)) => format!("{}/_{}", dir, base),
+        None => format!("_{}", name),
+    }
+}
+
+pub(crate) fn strip_template_extension(name: &str) -> &str {
+    TEMPLATE_EXTENSIONS
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext))
+        .unwrap_or(name)
+}
+
+/// The partial DSL strips quotes from its name, so `render user` and
+/// `render "user"` parse alike; only the quoted form names a file.
+fn is_quoted_on_line(source: &str, line: usize, name: &str) -> bool {
+    source
+        .lines()
+        .nth(line.saturating_sub(1))
+        .is_some_and(|text| {
+            text.contains(&format!("\"{}\"", name)) || text.contains(&format!("'{}'", name))
+        })
+}
+
+pub(crate) fn collect_partial_nodes<'a>(
+    nodes: &'a [TemplateNode],
+    out: &mut Vec<(&'a str, Option<&'a Expr>, usize)>,
+) {
+    for node in nodes {
+        match node {
+            TemplateNode::Partial {
+                name,
+                context,
+                line,
+            } => out.push((name, context.as_ref(), *line)),
+            TemplateNode::If {
+                body, else_body, ..
+            } => {
+                collect_partial_nodes(body, out);
+                if let Some(else_body) = else_body {
+                    collect_partial_nodes(else_body, out);
+                }
+            }
+            TemplateNode::For { body, .. }
+            | TemplateNode::ContentFor { body, .. }
+            | TemplateNode::InlineTag { body, .. }
+            | TemplateNode::FormWith { body, .. }
+            | TemplateNode::Component { body, .. } => collect_partial_nodes(body, out),
+            _ => {}
+        }
+    }
+}
+
+/// A template's embedded code, parsed — `None` when it doesn't parse.
+pub(crate) fn template_program(source: &str) -> Option<Program> {
+    let code = crate::template::parser::extract_lintable_code(source).ok()?;
+    crate::parse(&code).ok()
+}
+
+pub(crate) fn parse_code_file(path: &Path) -> Option<Program> {
+    let bytes = std::fs::read(path).ok()?;
+    if crate::bundle::is_ast_blob(&bytes) {
+        crate::bundle::deserialize_program(&bytes).ok()
+    } else {
+        crate::parse(&String::from_utf8_lossy(&bytes)).ok()
+    }
+}
+
+/// Files under `dir` (recursive) ending in one of `suffixes`, skipping `skip`
+/// and dot-directories.
+pub(crate) fn files_under(dir: &Path, suffixes: &[&str], skip: Option<&Path>) -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return out;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            let hidden = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with('.'));
+            if !hidden && Some(path.as_path()) != skip {
+                out.extend(files_under(&path, suffixes, skip));
+            }
+        } else if path
+            .to_str()
+            .is_some_and(|p| suffixes.iter().any(|s| p.ends_with(s)))
+        {
+            out.push(path);
+        }
+    }
+    out.sort();
+    out
+}
+
+fn declares_props(program: &Program) -> bool {
+    program.statements.iter().any(|stmt| {
+        matches!(&stmt.kind, StmtKind::Expression(expr)
+            if matches!(&expr.kind, ExprKind::Call { callee, .. }
+                if matches!(&callee.kind, ExprKind::Variable(n, _) if n == "props")))
+    })
+}
+
+/// Keys of a literal hash, with their value types; `None` for anything
+/// computed.
+fn literal_keys(expr: &Expr) -> Option<HashMap<String, Type>> {
+    let ExprKind::Hash(pairs) = &expr.kind else {
+        return None;
+    };
+    pairs
+        .iter()
+        .map(|(key, value)| match &key.kind {
+            ExprKind::StringLiteral(k) | ExprKind::Symbol(k) => {
+                Some((k.clone(), literal_type(value)))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The type of a value written as a literal; `Any` for anything computed,
+/// whose type depends on code the index doesn't check.
+fn literal_type(expr: &Expr) -> Type {
+    match &expr.kind {
+        ExprKind::IntLiteral(_) => Type::Int,
+        ExprKind::FloatLiteral(_) => Type::Float,
+        ExprKind::StringLiteral(_) | ExprKind::InterpolatedString(_) => Type::String,
+        ExprKind::BoolLiteral(_) => Type::Bool,
+        ExprKind::Array(items) if !items.is_empty() => Type::Array(Box::new(Type::common(
+            &items.iter().map(literal_type).collect::<Vec<_>>(),
+        ))),
+        _ => Type::Any,
+    }
+}
+
+fn call_data(data: Option<&Expr>) -> Data {
+    match data {
+        None => Data::Keys(HashMap::new()),
+        Some(expr) => literal_keys(expr).map_or(Data::Unknown, Data::Keys),
+    }
+}
+
+/// `name("literal", data?)` calls to any of `names` under `stmts`:
+/// (template name, data argument, call span).
+fn render_calls<'a>(stmts: &'a [Stmt], names: &[&str]) -> Vec<(String, Option<&'a Expr>, Span)> {
+    let mut out = Vec::new();
+    for_each_expr(stmts, &mut |expr| {
+        let ExprKind::Call { callee, arguments } = &expr.kind else {
+            return;
+        };
+        if !matches!(&callee.kind, ExprKind::Variable(n, _) if names.contains(&n.as_str())) {
+            return;
+        }
+        let mut positional = arguments.iter().filter_map(|arg| match arg {
+            Argument::Positional(e) => Some(e),
+            _ => None,
+        });
+        if let Some(Expr {
+            kind: ExprKind::StringLiteral(name),
+            ..
+        }) = positional.next()
+        {
+            out.push((name.clone(), positional.next(), expr.span));
+        }
+    });
+    out
+}
+
+/// Names assigned through `@name = ...` / `this.name = ...` under `stmts`.
+fn assigned_fields(stmts: &[Stmt]) -> HashSet<String> {
+    let mut out = HashSet::new();
+    for_each_expr(stmts, &mut |expr| {
+        let (ExprKind::Assign { target, .. } | ExprKind::CompoundAssign { target, .. }) =
+            &expr.kind
+        else {
+            return;
+        };
+        if let ExprKind::Member { object, name } = &target.kind {
+            if matches!(object.kind, ExprKind::This) {
+                out.insert(name.clone());
+            }
+        }
+    });
+    out
+}
+
+/// Visit every expression under `stmts`, nested function and class bodies
+/// included.
+pub(crate) fn for_each_expr<'a>(stmts: &'a [Stmt], f: &mut dyn FnMut(&'a Expr)) {
+    for stmt in stmts {
+        walk_stmt(stmt, f);
+    }
+}
+
+fn walk_stmt<'a>(stmt: &'a Stmt, f: &mut dyn FnMut(&'a Expr)) {
+    match &stmt.kind {
+        StmtKind::Expression(e) | StmtKind::Throw(e) | StmtKind::Yield(e) => walk_expr(e, f),
+        StmtKind::Const { initializer, .. } | StmtKind::LetTuple { initializer, .. } => {
+            walk_expr(initializer, f)
+        }
+        StmtKind::Let { initializer, .. } => {
+            if let Some(e) = initializer {
+                walk_expr(e, f);
+            }
+        }
+        StmtKind::Return(e) => {
+            if let Some(e) = e {
+                walk_expr(e, f);
+            }
+        }
+        StmtKind::Block(stmts) => for_each_expr(stmts, f),
+        StmtKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            walk_expr(condition, f);
+            walk_stmt(then_branch, f);
+            if let Some(e) = else_branch {
+                walk_stmt(e, f);
+            }
+        }
+        StmtKind::While { condition, body } => {
+            walk_expr(condition, f);
+            walk_stmt(body, f);
+        }
+        StmtKind::For { iterable, body, .. } => {
+            walk_expr(iterable, f);
+            walk_stmt(body, f);
+        }
+        StmtKind::Try {
+            try_block,
+            catch_clauses,
+            finally_block,
+        } => {
+            walk_stmt(try_block, f);
+            for clause in catch_clauses {
+                walk_stmt(&clause.body, f);
+            }
+            if let Some(s) = finally_block {
+                walk_stmt(s, f);
+            }
+        }
+        StmtKind::Function(decl) => for_each_expr(&decl.body, f),
+        StmtKind::Class(decl) => walk_class(decl, f),
+        StmtKind::Enum(decl) => {
+            for method in &decl.methods {
+                for_each_expr(&method.body, f);
+            }
+        }
+        StmtKind::Struct(decl) => {
+            for field in &decl.fields {
+                if let Some(default) = &field.default_value {
+                    walk_expr(default, f);
+                }
+            }
+        }
+        StmtKind::Export(inner) => walk_stmt(inner, f),
+        StmtKind::Break | StmtKind::Interface(_) | StmtKind::TypeAlias(_) | StmtKind::Import(_) => {
+        }
+    }
+}
+
+fn walk_class<'a>(decl: &'a ClassDecl, f: &mut dyn FnMut(&'a Expr)) {
+    for field in &decl.fields {
+        if let Some(init) = &field.initializer {
+            walk_expr(init, f);
+        }
+    }
+    for method in &decl.methods {
+        for_each_expr(&method.body, f);
+    }
+    if let Some(ctor) = &decl.constructor {
+        for_each_expr(&ctor.body, f);
+    }
+    if let Some(block) = &decl.static_block {
+        for_each_expr(block, f);
+    }
+    for_each_expr(&decl.class_statements, f);
+    for nested in &decl.nested_classes {
+        walk_class(nested, f);
+    }
+}
+
+pub(crate) fn walk_expr<'a>(expr: &'a Expr, f: &mut dyn FnMut(&'a Expr)) {
+    f(expr);
+    match &expr.kind {
+        ExprKind::Binary { left, right, .. }
+        | ExprKind::Pipeline { left, right }
+        | ExprKind::LogicalAnd { left, right }
+        | ExprKind::LogicalOr { left, right }
+        | ExprKind::NullishCoalescing { left, right }
+        | ExprKind::Index {
+            object: left,
+            index: right,
+        }
+        | ExprKind::Assign {
+            target: left,
+            value: right,
+        }
+        | ExprKind::CompoundAssign {
+            target: left,
+            value: right,
+            ..
+        }
+        | ExprKind::Rescue {
+            expr: left,
+            fallback: right,
+        } => {
+            walk_expr(left, f);
+            walk_expr(right, f);
+        }
+        ExprKind::Unary { operand: inner, .. }
+        | ExprKind::Grouping(inner)
+        | ExprKind::Spread(inner)
+        | ExprKind::Throw(inner)
+        | ExprKind::Await(inner)
+        | ExprKind::PostfixIncrement(inner)
+        | ExprKind::PostfixDecrement(inner)
+        | ExprKind::Member { object: inner, .. }
+        | ExprKind::SafeMember { object: inner, .. }
+        | ExprKind::QualifiedName {
+            qualifier: inner, ..
+        } => walk_expr(inner, f),
+        ExprKind::Call { callee, arguments }
+        | ExprKind::New {
+            class_expr: callee,
+            arguments,
+        } => {
+            walk_expr(callee, f);
+            for arg in arguments {
+                match arg {
+                    Argument::Positional(e) | Argument::Block(e) | Argument::Spread(e) => {
+                        walk_expr(e, f)
+                    }
+                    Argument::Named(named) => walk_expr(&named.value, f),
+                }
+            }
+        }
+        ExprKind::Array(items) | ExprKind::Tuple(items) => {
+            for e in items {
+                walk_expr(e, f);
+            }
+        }
+        ExprKind::Hash(pairs) => {
+            for (k, v) in pairs {
+                walk_expr(k, f);
+                walk_expr(v, f);
+            }
+        }
+        ExprKind::Block(stmts) => for_each_expr(stmts, f),
+        ExprKind::Lambda { body, .. } => for_each_expr(body, f),
+        ExprKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            walk_expr(condition, f);
+            walk_expr(then_branch, f);
+            if let Some(e) = else_branch {
+                walk_expr(e, f);
+            }
+        }
+        ExprKind::Match { expression, arms } => {
+            walk_expr(expression, f);
+            for arm in arms {
+                if let Some(guard) = &arm.guard {
+                    walk_expr(guard, f);
+                }
+                walk_expr(&arm.body, f);
+            }
+        }
+        ExprKind::ListComprehension {
+            element,
+            iterable,
+            condition,
+            ..
+        } => {
+            walk_expr(element, f);
+            walk_expr(iterable, f);
+            if let Some(c) = condition {
+                walk_expr(c, f);
+            }
+        }
+        ExprKind::HashComprehension(comp) => {
+            let HashComprehension {
+                key,
+                value,
+                iterable,
+                condition,
+                ..
+            } = &**comp;
+            walk_expr(key, f);
+            walk_expr(value, f);
+            walk_expr(iterable, f);
+            if let Some(c) = condition {
+                walk_expr(c, f);
+            }
+        }
+        ExprKind::InterpolatedString(parts) => {
+            for part in parts {
+                if let InterpolatedPart::Expression(e) = part {
+                    walk_expr(e, f);
+                }
+            }
+        }
+        ExprKind::IntLiteral(_)
+        | ExprKind::FloatLiteral(_)
+        | ExprKind::DecimalLiteral(_)
+        | ExprKind::StringLiteral(_)
+        | ExprKind::BoolLiteral(_)
+        | ExprKind::Symbol(_)
+        | ExprKind::Null
+        | ExprKind::Variable(_, _)
+        | ExprKind::This
+        | ExprKind::Super
+        | ExprKind::CommandSubstitution(_)
+        | ExprKind::SdqlBlock { .. } => {}
+    }
+}
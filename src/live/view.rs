@@ -198,6 +198,16 @@ impl LiveRegistry {
         }
     }
 
+    /// Number of live instances per component, for heap diagnostics.
+    pub fn counts_by_component(&self) -> std::collections::BTreeMap<String, usize> {
+        let views = self.views.lock().unwrap();
+        let mut counts = std::collections::BTreeMap::new();
+        for view in views.values() {
+            *counts.entry(view.component.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
     pub fn update(&self, instance: LiveViewInstance) {
         let mut views = self.views.lock().unwrap();
         views.insert(instance.id.clone(), instance);
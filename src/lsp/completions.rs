@@ -58,6 +58,8 @@ const KEYWORDS: &[&str] = &[
     "after_each",
     "before_all",
     "after_all",
+    "bench",
+    "task",
     "expect",
     "to",
     "eq",
@@ -106,6 +108,42 @@ pub fn get_completions(
         });
     }
 
+    let builtins = crate::types::stubs::builtin_stubs();
+    for function in builtins
+        .functions
+        .iter()
+        .filter(|f| f.name.starts_with(&prefix) && !KEYWORDS.contains(&f.name.as_str()))
+    {
+        items.push(CompletionItem {
+            label: function.name.clone(),
+            kind: Some(CompletionItemKind::FUNCTION),
+            insert_text: Some(function.name.clone()),
+            detail: Some(function.signature()),
+            documentation: function
+                .doc
+                .clone()
+                .map(tower_lsp::lsp_types::Documentation::String),
+            ..Default::default()
+        });
+    }
+    for class in builtins
+        .classes
+        .iter()
+        .filter(|c| c.name.starts_with(&prefix) && !TYPES.contains(&c.name.as_str()))
+    {
+        items.push(CompletionItem {
+            label: class.name.clone(),
+            kind: Some(CompletionItemKind::CLASS),
+            insert_text: Some(class.name.clone()),
+            detail: Some(format!("class {}", class.name)),
+            documentation: class
+                .doc
+                .clone()
+                .map(tower_lsp::lsp_types::Documentation::String),
+            ..Default::default()
+        });
+    }
+
     if let Some(table) = table {
         for scoped in table
             .symbols
@@ -6,7 +6,9 @@ pub fn get_hover(source: &str, position: Position) -> Option<Hover> {
     let offset = position_to_offset(source, position)?;
     let table = crate::lsp::symbols::build_symbol_table(source)?;
 
-    let symbol = table.find_at_position(offset)?;
+    let Some(symbol) = table.find_at_position(offset) else {
+        return builtin_hover(source, offset);
+    };
 
     let mut contents = Vec::new();
 
@@ -68,12 +70,69 @@ fn get_builtin_docs(name: &str) -> Option<String> {
         "Duration" => "Duration class for time differences.\n\n```\nDuration.between(start: DateTime, end: DateTime): Duration\nDuration.of_seconds(s: Float): Duration\nDuration.of_minutes(m: Float): Duration\n```",
         "Regex" => "Regex class for pattern matching.\n\n```\nRegex.matches(pattern: String, string: String): Bool\nRegex.find(pattern: String, string: String): Any\nRegex.replace(pattern: String, string: String, replacement: String): String\n```",
         "JSON" => "JSON class for JSON operations.\n\n```\nJSON.parse(json: String): Any\nJSON.stringify(value: Any): String\n```",
-        _ => return None,
+        _ => return stub_docs(name),
     };
 
     Some(docs.to_string())
 }
 
+/// Hover for a builtin named at `offset` that the document doesn't declare.
+fn builtin_hover(source: &str, offset: usize) -> Option<Hover> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '?' || c == '!';
+    let start = source[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_word(*c))
+        .last()
+        .map_or(offset, |(i, _)| i);
+    let end = source[offset..]
+        .char_indices()
+        .find(|(_, c)| !is_word(*c))
+        .map_or(source.len(), |(i, _)| offset + i);
+    let name = &source[start..end];
+    let docs = get_builtin_docs(name)?;
+
+    let line = source[..start].matches('\n').count();
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let column = source[line_start..start].chars().count();
+    let range = Range {
+        start: Position::new(line as u32, column as u32),
+        end: Position::new(line as u32, (column + name.chars().count()) as u32),
+    };
+    Some(Hover {
+        contents: HoverContents::Array(vec![MarkedString::String(docs)]),
+        range: Some(range),
+    })
+}
+
+/// Docs for a builtin from the bundled stubs: its doc comment and signature.
+fn stub_docs(name: &str) -> Option<String> {
+    let builtins = crate::types::stubs::builtin_stubs();
+    let (doc, signature) = if let Some(function) = builtins.function(name) {
+        (function.doc.as_deref(), function.signature())
+    } else {
+        let class = builtins.class(name)?;
+        let members = class
+            .methods
+            .iter()
+            .map(|m| {
+                let receiver = if m.is_static || m.name == "new" {
+                    format!("{}.", class.name)
+                } else {
+                    String::new()
+                };
+                format!("{}{}", receiver, m.signature())
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        (class.doc.as_deref(), members)
+    };
+    Some(match doc {
+        Some(doc) => format!("{}\n\n```\n{}\n```", doc, signature),
+        None => format!("```\n{}\n```", signature),
+    })
+}
+
 fn position_to_offset(source: &str, position: Position) -> Option<usize> {
     let mut offset = 0;
 
@@ -287,7 +287,15 @@ impl Backend {
         use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
 
         if let Some(text) = self.get_document(&uri) {
-            let diagnostics: Vec<Diagnostic> = crate::lint(&text)
+            // The project's `[lint]` levels, when the document is a file
+            // under a soli.toml; a manifest that doesn't load is ignored.
+            let config = uri
+                .to_file_path()
+                .ok()
+                .and_then(|path| path.parent().map(crate::lint::LintConfig::find))
+                .and_then(Result::ok)
+                .unwrap_or_default();
+            let diagnostics: Vec<Diagnostic> = crate::lint_with_config(&text, &config)
                 .unwrap_or_default()
                 .into_iter()
                 .map(|d| {
@@ -301,7 +309,10 @@ impl Backend {
                     );
                     Diagnostic {
                         range: tower_lsp::lsp_types::Range::new(start, end),
-                        severity: Some(DiagnosticSeverity::WARNING),
+                        severity: Some(match d.severity {
+                            crate::lint::Severity::Error => DiagnosticSeverity::ERROR,
+                            crate::lint::Severity::Warning => DiagnosticSeverity::WARNING,
+                        }),
                         message: d.message,
                         code: Some(tower_lsp::lsp_types::NumberOrString::String(
                             d.rule.to_string(),
@@ -63,7 +63,10 @@ impl SymbolTable {
 
 pub fn build_symbol_table(source: &str) -> Option<SymbolTable> {
     let tokens = crate::lexer::Scanner::new(source).scan_tokens().ok()?;
-    let program = crate::parser::Parser::new(tokens).parse().ok()?;
+    let program = crate::parser::Parser::new(tokens)
+        .keeping_platform_code()
+        .parse()
+        .ok()?;
 
     let mut table = SymbolTable::default();
     let mut scope_level = 0;
@@ -271,7 +274,8 @@ fn extract_symbols_from_expr(expr: &crate::ast::Expr, table: &mut SymbolTable, s
                     crate::ast::expr::Argument::Named(named) => {
                         extract_symbols_from_expr(&named.value, table, scope_level);
                     }
-                    crate::ast::expr::Argument::Block(expr) => {
+                    crate::ast::expr::Argument::Block(expr)
+                    | crate::ast::expr::Argument::Spread(expr) => {
                         extract_symbols_from_expr(expr, table, scope_level);
                     }
                 }
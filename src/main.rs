@@ -3,9 +3,11 @@
 mod cli;
 
 use mimalloc::MiMalloc;
+use solilang::interpreter::CountingAlloc;
 
+/// Counting wraps mimalloc so evaluation budgets can limit allocations.
 #[global_allocator]
-static GLOBAL: MiMalloc = MiMalloc;
+static GLOBAL: CountingAlloc<MiMalloc> = CountingAlloc(MiMalloc);
 
 /// Install a SIGTERM/SIGINT handler that exits via `process::exit(0)` so atexit
 /// handlers run on graceful shutdown — including the LLVM coverage profile
@@ -40,6 +40,9 @@ pub struct Metrics {
     /// A non-zero value means some production handlers run on the slower
     /// engine; set `SOLI_ENGINE_LOG=1` to log which handler and why.
     pub vm_handler_demotions_total: AtomicU64,
+    /// Requests answered 503 by a concurrency limit (`limit_concurrency` or
+    /// the in-flight ceiling) instead of reaching a worker.
+    pub http_requests_shed_total: AtomicU64,
     start_time: std::sync::OnceLock<Instant>,
 }
 
@@ -60,6 +63,7 @@ impl Metrics {
             db_query_duration_ns_total: AtomicU64::new(0),
             db_query_count: AtomicU64::new(0),
             vm_handler_demotions_total: AtomicU64::new(0),
+            http_requests_shed_total: AtomicU64::new(0),
             start_time: std::sync::OnceLock::new(),
         }
     }
@@ -189,6 +193,15 @@ impl Metrics {
             self.vm_handler_demotions_total.load(Ordering::Relaxed)
         ));
 
+        out.push_str(
+            "# HELP soli_http_requests_shed_total Requests answered 503 by a concurrency limit instead of reaching a worker.\n",
+        );
+        out.push_str("# TYPE soli_http_requests_shed_total counter\n");
+        out.push_str(&format!(
+            "soli_http_requests_shed_total {}\n",
+            self.http_requests_shed_total.load(Ordering::Relaxed)
+        ));
+
         out
     }
 
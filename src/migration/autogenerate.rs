@@ -0,0 +1,532 @@
+//! `soli db:migrate autogenerate`: write a migration from model changes.
+//!
+//! SolidB collections are schemaless, so the schema a migration moves from is
+//! a snapshot, `db/schema.json`, of what the models declared the last time a
+//! migration was generated:
+//!
+//! ```json
+//! { "collections": { "posts": { "title": "string", "views": "int" } } }
+//! ```
+//!
+//! Each model class (anything extending `Model`, directly or through another
+//! model) contributes its collection and its `attribute "name", "type"`
+//! declarations; STI subclasses add theirs to the base collection. Comparing
+//! that against the snapshot yields:
+//!
+//! - `db.create_collection` / `db.drop_collection` for added / removed models;
+//! - `db.add_field` / `db.remove_field` / `db.change_field` for added,
+//!   removed and retyped attributes.
+//!
+//! `down()` undoes them in reverse order. The migration is written for review,
+//! not run, and the snapshot is rewritten to match the models. The first run
+//! has nothing to compare against, so it only records the snapshot: the
+//! existing collections are the baseline.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ast::expr::{Argument, Expr, ExprKind};
+use crate::ast::{ClassDecl, StmtKind};
+use crate::interpreter::builtins::model::attributes::normalize_type;
+use crate::interpreter::builtins::model::class_name_to_collection_with_engine;
+
+/// Collection name → attribute name → normalized type.
+pub type Schema = BTreeMap<String, BTreeMap<String, String>>;
+
+/// Class name → (superclass, `attribute` declarations as written).
+type ModelClasses = HashMap<String, (Option<String>, Vec<(String, String)>)>;
+
+/// One operation of a generated migration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaChange {
+    CreateCollection(String),
+    DropCollection(String),
+    AddField {
+        collection: String,
+        field: String,
+    },
+    RemoveField {
+        collection: String,
+        field: String,
+    },
+    ChangeField {
+        collection: String,
+        field: String,
+        from: String,
+        to: String,
+    },
+}
+
+/// What `autogenerate_migration` did.
+pub enum Autogenerated {
+    /// No snapshot existed: the current models were recorded as the baseline.
+    Baseline(PathBuf),
+    /// The models match the snapshot.
+    NoChanges,
+    /// A migration was written and the snapshot updated.
+    Migration {
+        path: PathBuf,
+        changes: Vec<SchemaChange>,
+    },
+}
+
+/// Diff the models under `app/models` against `db/schema.json` and write the
+/// migration for the difference.
+pub fn autogenerate_migration(app_path: &Path) -> Result<Autogenerated, String> {
+    let declared = declared_schema(&app_path.join("app").join("models"))?;
+    let snapshot_path = app_path.join("db").join("schema.json");
+
+    if !snapshot_path.exists() {
+        write_snapshot(&snapshot_path, &declared)?;
+        return Ok(Autogenerated::Baseline(snapshot_path));
+    }
+
+    let recorded = read_snapshot(&snapshot_path)?;
+    let changes = diff_schemas(&recorded, &declared);
+    if changes.is_empty() {
+        return Ok(Autogenerated::NoChanges);
+    }
+
+    let name = migration_name(&changes);
+    let path = super::new_migration_path(app_path, &name)?;
+    let source = render_migration(&name, &changes);
+    fs::write(&path, source).map_err(|e| format!("Failed to write migration file: {}", e))?;
+    write_snapshot(&snapshot_path, &declared)?;
+
+    Ok(Autogenerated::Migration { path, changes })
+}
+
+/// The schema the model files declare. Only literal `attribute` declarations
+/// count, as the models are parsed, not run.
+pub fn declared_schema(models_dir: &Path) -> Result<Schema, String> {
+    let mut files = Vec::new();
+    collect_model_files(models_dir, &mut files)?;
+    files.sort();
+
+    let mut classes = ModelClasses::new();
+    for path in &files {
+        let source = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let program = crate::parse(&source).map_err(|e| format!("{}: {}", path.display(), e))?;
+        for stmt in &program.statements {
+            let decl = match &stmt.kind {
+                StmtKind::Class(decl) => decl,
+                StmtKind::Export(inner) => match &inner.kind {
+                    StmtKind::Class(decl) => decl,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            classes.insert(
+                decl.name.clone(),
+                (decl.superclass.clone(), declared_attributes(decl)),
+            );
+        }
+    }
+
+    let mut schema = Schema::new();
+    let mut names: Vec<&String> = classes.keys().collect();
+    names.sort();
+    for name in names {
+        let Some(root) = model_root(name, &classes) else {
+            continue;
+        };
+        let collection = class_name_to_collection_with_engine(root, None);
+        let fields = schema.entry(collection).or_default();
+        for (field, declared) in &classes[name].1 {
+            let data_type = normalize_type(declared).ok_or_else(|| {
+                format!(
+                    "{}: attribute \"{}\" has unknown type {:?}",
+                    name, field, declared
+                )
+            })?;
+            fields.insert(field.clone(), data_type.to_string());
+        }
+    }
+    Ok(schema)
+}
+
+/// The operations that turn `recorded` into `declared`: new collections,
+/// then field changes per collection, then dropped collections.
+pub fn diff_schemas(recorded: &Schema, declared: &Schema) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+    for (collection, fields) in declared {
+        let Some(old_fields) = recorded.get(collection) else {
+            changes.push(SchemaChange::CreateCollection(collection.clone()));
+            continue;
+        };
+        for (field, data_type) in fields {
+            match old_fields.get(field) {
+                None => changes.push(SchemaChange::AddField {
+                    collection: collection.clone(),
+                    field: field.clone(),
+                }),
+                Some(old_type) if old_type != data_type => {
+                    changes.push(SchemaChange::ChangeField {
+                        collection: collection.clone(),
+                        field: field.clone(),
+                        from: old_type.clone(),
+                        to: data_type.clone(),
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        for field in old_fields.keys().filter(|f| !fields.contains_key(*f)) {
+            changes.push(SchemaChange::RemoveField {
+                collection: collection.clone(),
+                field: field.clone(),
+            });
+        }
+    }
+    for collection in recorded.keys().filter(|c| !declared.contains_key(*c)) {
+        changes.push(SchemaChange::DropCollection(collection.clone()));
+    }
+    changes
+}
+
+/// `add_subtitle_to_posts` for a single change, `update_schema` otherwise.
+pub fn migration_name(changes: &[SchemaChange]) -> String {
+    match changes {
+        [SchemaChange::CreateCollection(c)] => format!("create_{}", c),
+        [SchemaChange::DropCollection(c)] => format!("drop_{}", c),
+        [SchemaChange::AddField { collection, field }] => {
+            format!("add_{}_to_{}", field, collection)
+        }
+        [SchemaChange::RemoveField { collection, field }] => {
+            format!("remove_{}_from_{}", field, collection)
+        }
+        [SchemaChange::ChangeField {
+            collection, field, ..
+        }] => format!("change_{}_in_{}", field, collection),
+        _ => "update_schema".to_string(),
+    }
+}
+
+/// The migration source for `changes`.
+pub fn render_migration(name: &str, changes: &[SchemaChange]) -> String {
+    let up: Vec<String> = changes.iter().map(up_statement).collect();
+    let down: Vec<String> = changes.iter().rev().map(down_statement).collect();
+    format!(
+        r#"// Migration: {}
+// Created: {}
+// Generated by `soli db:migrate autogenerate` from model changes. Review it
+// before running: add_field only backfills when given a default.
+
+fn up(db: Any) -> Any {{
+{}
+}}
+
+fn down(db: Any) -> Any {{
+{}
+}}
+"#,
+        name,
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        indent(&up),
+        indent(&down)
+    )
+}
+
+fn up_statement(change: &SchemaChange) -> String {
+    match change {
+        SchemaChange::CreateCollection(c) => format!("db.create_collection({:?});", c),
+        SchemaChange::DropCollection(c) => format!("db.drop_collection({:?});", c),
+        SchemaChange::AddField { collection, field } => {
+            format!("db.add_field({:?}, {:?}, null);", collection, field)
+        }
+        SchemaChange::RemoveField { collection, field } => {
+            format!("db.remove_field({:?}, {:?});", collection, field)
+        }
+        SchemaChange::ChangeField {
+            collection,
+            field,
+            from,
+            to,
+        } => format!(
+            "db.change_field({:?}, {:?}, {:?}, {:?});",
+            collection, field, from, to
+        ),
+    }
+}
+
+fn down_statement(change: &SchemaChange) -> String {
+    match change {
+        SchemaChange::CreateCollection(c) => format!("db.drop_collection({:?});", c),
+        SchemaChange::DropCollection(c) => {
+            format!(
+                "db.create_collection({:?}); // documents are not restored",
+                c
+            )
+        }
+        SchemaChange::AddField { collection, field } => {
+            format!("db.remove_field({:?}, {:?});", collection, field)
+        }
+        SchemaChange::RemoveField { collection, field } => format!(
+            "db.add_field({:?}, {:?}, null); // values are not restored",
+            collection, field
+        ),
+        SchemaChange::ChangeField {
+            collection,
+            field,
+            from,
+            to,
+        } => format!(
+            "db.change_field({:?}, {:?}, {:?}, {:?});",
+            collection, field, to, from
+        ),
+    }
+}
+
+fn indent(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| format!("    {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn read_snapshot(path: &Path) -> Result<Schema, String> {
+    #[derive(serde::Deserialize)]
+    struct Snapshot {
+        collections: Schema,
+    }
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let snapshot: Snapshot = serde_json::from_str(&text)
+        .map_err(|e| format!("Invalid schema snapshot {}: {}", path.display(), e))?;
+    Ok(snapshot.collections)
+}
+
+fn write_snapshot(path: &Path, schema: &Schema) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_string_pretty(&serde_json::json!({ "collections": schema }))
+        .map_err(|e| e.to_string())?;
+    fs::write(path, json + "\n").map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn collect_model_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in
+        fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+    {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.is_dir() {
+            collect_model_files(&path, out)?;
+        } else if path.extension().is_some_and(|ext| ext == "sl") {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// `attribute "name", "type"` calls in a class body, as written.
+fn declared_attributes(decl: &ClassDecl) -> Vec<(String, String)> {
+    let literal = |arg: Option<&Argument>| match arg {
+        Some(Argument::Positional(Expr {
+            kind: ExprKind::StringLiteral(s) | ExprKind::Symbol(s),
+            ..
+        })) => Some(s.clone()),
+        _ => None,
+    };
+    decl.class_statements
+        .iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::Expression(Expr {
+                kind: ExprKind::Call { callee, arguments },
+                ..
+            }) if matches!(&callee.kind, ExprKind::Variable(n, _) if n == "attribute") => {
+                Some((literal(arguments.first())?, literal(arguments.get(1))?))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The class directly under `Model` that `name` descends from, or `None` when
+/// `name` is not a model (or its chain leaves the scanned files).
+fn model_root<'a>(mut name: &'a str, classes: &'a ModelClasses) -> Option<&'a str> {
+    for _ in 0..classes.len() {
+        match classes.get(name)?.0.as_deref() {
+            Some("Model") => return Some(name),
+            Some(parent) => name = parent,
+            None => return None,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema(entries: &[(&str, &[(&str, &str)])]) -> Schema {
+        entries
+            .iter()
+            .map(|(collection, fields)| {
+                let fields = fields
+                    .iter()
+                    .map(|(f, t)| (f.to_string(), t.to_string()))
+                    .collect();
+                (collection.to_string(), fields)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn diff_reports_collection_and_field_changes() {
+        let recorded = schema(&[
+            (
+                "posts",
+                &[("title", "string"), ("views", "string"), ("legacy", "any")],
+            ),
+            ("comments", &[]),
+        ]);
+        let declared = schema(&[
+            (
+                "posts",
+                &[
+                    ("title", "string"),
+                    ("views", "int"),
+                    ("subtitle", "string"),
+                ],
+            ),
+            ("users", &[("email", "string")]),
+        ]);
+        let changes = diff_schemas(&recorded, &declared);
+        assert_eq!(
+            changes,
+            vec![
+                SchemaChange::AddField {
+                    collection: "posts".into(),
+                    field: "subtitle".into(),
+                },
+                SchemaChange::ChangeField {
+                    collection: "posts".into(),
+                    field: "views".into(),
+                    from: "string".into(),
+                    to: "int".into(),
+                },
+                SchemaChange::RemoveField {
+                    collection: "posts".into(),
+                    field: "legacy".into(),
+                },
+                SchemaChange::CreateCollection("users".into()),
+                SchemaChange::DropCollection("comments".into()),
+            ]
+        );
+        assert_eq!(migration_name(&changes), "update_schema");
+        assert_eq!(migration_name(&changes[..1]), "add_subtitle_to_posts");
+        assert!(diff_schemas(&declared, &declared).is_empty());
+    }
+
+    #[test]
+    fn down_undoes_up_in_reverse() {
+        let changes = vec![
+            SchemaChange::CreateCollection("users".into()),
+            SchemaChange::ChangeField {
+                collection: "posts".into(),
+                field: "views".into(),
+                from: "string".into(),
+                to: "int".into(),
+            },
+        ];
+        let source = render_migration("update_schema", &changes);
+        let up = source.find("fn up").unwrap();
+        let down = source.find("fn down").unwrap();
+        assert!(source[up..down].contains(
+            "    db.create_collection(\"users\");\n    db.change_field(\"posts\", \"views\", \"string\", \"int\");"
+        ));
+        assert!(source[down..].contains(
+            "    db.change_field(\"posts\", \"views\", \"int\", \"string\");\n    db.drop_collection(\"users\");"
+        ));
+        crate::parse(&source).unwrap();
+    }
+
+    #[test]
+    fn declared_schema_reads_models_and_sti_subclasses() {
+        let dir = tempfile::tempdir().unwrap();
+        let models = dir.path().join("app").join("models");
+        fs::create_dir_all(&models).unwrap();
+        fs::write(
+            models.join("user.sl"),
+            "class User < Model\n  attribute \"email\", \"string\"\n  attribute \"age\", \"integer\"\nend\n",
+        )
+        .unwrap();
+        fs::write(
+            models.join("admin.sl"),
+            "class Admin < User\n  attribute \"level\", \"int\"\nend\n",
+        )
+        .unwrap();
+        fs::write(
+            models.join("helper.sl"),
+            "class Helper\n  fn run() { 1 }\nend\n",
+        )
+        .unwrap();
+
+        let declared = declared_schema(&models).unwrap();
+        assert_eq!(
+            declared,
+            schema(&[(
+                "users",
+                &[("age", "int"), ("email", "string"), ("level", "int")]
+            )])
+        );
+
+        fs::write(
+            models.join("helper.sl"),
+            "class Tag < Model\n  attribute \"name\", \"varchar\"\nend\n",
+        )
+        .unwrap();
+        let err = declared_schema(&models).unwrap_err();
+        assert!(err.contains("unknown type \"varchar\""), "{}", err);
+    }
+
+    #[test]
+    fn first_run_records_a_baseline_then_migrations_follow_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let models = dir.path().join("app").join("models");
+        fs::create_dir_all(&models).unwrap();
+        let post = |fields: &str| format!("class Post < Model\n{}end\n", fields);
+        fs::write(
+            models.join("post.sl"),
+            post("  attribute \"title\", \"string\"\n"),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            autogenerate_migration(dir.path()).unwrap(),
+            Autogenerated::Baseline(_)
+        ));
+        assert!(matches!(
+            autogenerate_migration(dir.path()).unwrap(),
+            Autogenerated::NoChanges
+        ));
+
+        fs::write(
+            models.join("post.sl"),
+            post("  attribute \"title\", \"string\"\n  attribute \"views\", \"int\"\n"),
+        )
+        .unwrap();
+        let Autogenerated::Migration { path, changes } =
+            autogenerate_migration(dir.path()).unwrap()
+        else {
+            panic!("expected a migration");
+        };
+        assert!(path.to_string_lossy().ends_with("_add_views_to_posts.sl"));
+        assert_eq!(changes.len(), 1);
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .contains("db.add_field(\"posts\", \"views\", null);"));
+        assert!(matches!(
+            autogenerate_migration(dir.path()).unwrap(),
+            Autogenerated::NoChanges
+        ));
+    }
+}
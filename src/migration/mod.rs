@@ -40,10 +40,23 @@
 //!   - `options`: Hash with `unique` and/or `sparse` booleans
 //! - `db.drop_index(collection, name)` - Drop an index
 //! - `db.list_indexes(collection)` - List indexes for a collection
+//! - `db.create_fulltext_index(collection, name, fields)` - Create a fulltext
+//!   index for `Model.search`; `db.drop_fulltext_index(collection, name)` drops it
+//!
+//! ### Fields
+//! Collections are schemaless, so these rewrite existing documents. They are
+//! what `soli db:migrate autogenerate` (see [`autogenerate`]) writes.
+//! - `db.add_field(collection, field, default?)` - Set `default` on documents
+//!   that lack the field (a no-op without a default)
+//! - `db.remove_field(collection, field)` - Unset the field on every document
+//! - `db.change_field(collection, field, from, to)` - Convert stored values to
+//!   `to` when it is `string`, `int`, `float` or `bool`
 //!
 //! ### Raw queries
 //! - `db.query(sdbql)` - Execute a raw SDBQL query
 
+pub mod autogenerate;
+
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -465,6 +478,15 @@ class MigrationDb {{
         return solidb_list_indexes(_db, collection);
     }}
 
+    // Fulltext index management — backs `Model.search`
+    fn create_fulltext_index(collection: String, name: String, fields: Any) -> Any {{
+        return solidb_create_index(_db, collection, name, fields, {{ "type": "fulltext" }});
+    }}
+
+    fn drop_fulltext_index(collection: String, name: String) -> Any {{
+        return solidb_drop_index(_db, collection, name);
+    }}
+
     // Vector index management
     fn create_vector_index(collection: String, name: String, field: String, dimension: Int, options: Any = "cosine") -> Any {{
         return solidb_create_vector_index(_db, collection, name, field, dimension, options);
@@ -473,6 +495,36 @@ class MigrationDb {{
     fn drop_vector_index(collection: String, name: String) -> Any {{
         return solidb_drop_vector_index(_db, collection, name);
     }}
+
+    // Field management — collections are schemaless, so these rewrite the
+    // existing documents. Written by `soli db:migrate autogenerate`.
+    fn add_field(collection: String, field: String, default_value: Any = null) -> Any {{
+        if (default_value == null) {{
+            return null;
+        }}
+        return _db.query("FOR doc IN " + collection + " FILTER doc." + field + " == null UPDATE doc WITH {{ " + field + ": @value }} IN " + collection, {{ "value": default_value }});
+    }}
+
+    fn remove_field(collection: String, field: String) -> Any {{
+        return _db.query("FOR doc IN " + collection + " REPLACE doc WITH UNSET(doc, @field) IN " + collection, {{ "field": field }});
+    }}
+
+    // Converts stored values for scalar targets; other types only change
+    // what the model declares.
+    fn change_field(collection: String, field: String, from_type: String, to_type: String) -> Any {{
+        let value = "doc." + field;
+        let conversions = {{
+            "string": "TO_STRING(" + value + ")",
+            "int": "FLOOR(TO_NUMBER(" + value + "))",
+            "float": "TO_NUMBER(" + value + ")",
+            "bool": "TO_BOOL(" + value + ")"
+        }};
+        let converted = conversions[to_type];
+        if (converted == null) {{
+            return null;
+        }}
+        return _db.query("FOR doc IN " + collection + " FILTER " + value + " != null UPDATE doc WITH {{ " + field + ": " + converted + " }} IN " + collection);
+    }}
 }}
 
 let db = MigrationDb();
@@ -606,8 +658,9 @@ pub struct MigrationStatusEntry {
     pub applied: bool,
 }
 
-/// Generate a new migration file
-pub fn generate_migration(app_path: &Path, name: &str) -> Result<PathBuf, String> {
+/// Path for a new migration called `name`, `db/migrations/YYYYMMDDHHMMSS_name.sl`.
+/// Creates the migrations directory if needed.
+pub(crate) fn new_migration_path(app_path: &Path, name: &str) -> Result<PathBuf, String> {
     let migrations_path = app_path.join("db/migrations");
 
     // Create migrations directory if it doesn't exist
@@ -623,8 +676,12 @@ pub fn generate_migration(app_path: &Path, name: &str) -> Result<PathBuf, String
         .map(|c| if c.is_alphanumeric() { c } else { '_' })
         .collect();
 
-    let filename = format!("{}_{}.sl", timestamp, safe_name);
-    let filepath = migrations_path.join(&filename);
+    Ok(migrations_path.join(format!("{}_{}.sl", timestamp, safe_name)))
+}
+
+/// Generate a new migration file
+pub fn generate_migration(app_path: &Path, name: &str) -> Result<PathBuf, String> {
+    let filepath = new_migration_path(app_path, name)?;
 
     // Generate migration template
     let template = format!(
@@ -0,0 +1,189 @@
+//! `soli deps check`: compare a project's `import`s against `soli.toml`.
+//!
+//! An import's first path segment names a dependency when soli.toml declares
+//! one by that name — exactly the rule `ModuleResolver::resolve_path` uses.
+//! Relative imports (`./x`, `../x`) are local by definition, and a bare import
+//! that isn't a dependency resolves under the project root, so it only counts
+//! as uncovered when no such file exists there either.
+//!
+//! Imports are found from the token stream rather than the AST, so a file
+//! with an unrelated syntax error still has its imports counted.
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::lexer::{Scanner, TokenKind};
+
+use super::package::{Dependency, Package};
+
+/// An import that neither a dependency nor a project file satisfies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UncoveredImport {
+    pub file: PathBuf,
+    pub line: usize,
+    pub path: String,
+}
+
+/// Result of checking a project's imports against its dependencies.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DepsReport {
+    /// Declared dependencies no file imports, sorted by name.
+    pub unused: Vec<String>,
+    /// Imports no dependency or project file covers, in file order.
+    pub uncovered: Vec<UncoveredImport>,
+}
+
+impl DepsReport {
+    pub fn is_clean(&self) -> bool {
+        self.unused.is_empty() && self.uncovered.is_empty()
+    }
+}
+
+/// Check every `.sl` file under `root` (the directory holding soli.toml)
+/// against `pkg`'s dependencies. Hidden directories and the roots of path
+/// dependencies vendored inside the project are skipped: a dependency's own
+/// imports are its business, not the project's.
+pub fn check_dependencies(pkg: &Package, root: &Path) -> DepsReport {
+    let dep_roots: Vec<PathBuf> = pkg
+        .dependencies
+        .values()
+        .filter_map(|dep| match dep {
+            Dependency::Path(path) => root.join(path).canonicalize().ok(),
+            _ => None,
+        })
+        .collect();
+
+    let mut used = BTreeSet::new();
+    let mut uncovered = Vec::new();
+    for file in source_files(root, &dep_roots) {
+        let Ok(source) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        for (line, path) in imports_in(&source) {
+            if path.starts_with('.') {
+                continue;
+            }
+            let head = path.split('/').next().unwrap_or(&path);
+            if pkg.dependencies.contains_key(head) {
+                used.insert(head.to_string());
+            } else if !local_module_exists(root, &path) {
+                uncovered.push(UncoveredImport {
+                    file: file.clone(),
+                    line,
+                    path,
+                });
+            }
+        }
+    }
+
+    let mut unused: Vec<String> = pkg
+        .dependencies
+        .keys()
+        .filter(|name| !used.contains(*name))
+        .cloned()
+        .collect();
+    unused.sort();
+    DepsReport { unused, uncovered }
+}
+
+/// `(line, path)` of every `import ... "path"` in `source`.
+fn imports_in(source: &str) -> Vec<(usize, String)> {
+    let Ok(tokens) = Scanner::new(source).scan_tokens() else {
+        return Vec::new();
+    };
+    let mut out = Vec::new();
+    let mut iter = tokens.iter().peekable();
+    while let Some(token) = iter.next() {
+        if token.kind != TokenKind::Import {
+            continue;
+        }
+        // `import "p"`, `import { a } from "p"`, `import * as m from "p"`:
+        // the path is the first string literal before the next import.
+        while let Some(next) = iter.peek() {
+            match &next.kind {
+                TokenKind::StringLiteral(path) => {
+                    out.push((token.span.line as usize, path.clone()));
+                    iter.next();
+                    break;
+                }
+                TokenKind::Import | TokenKind::Eof => break,
+                _ => {
+                    iter.next();
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Whether a bare import resolves to a file under the project root, using
+/// the resolver's candidates: as written, `.sl`, `index.sl`, `mod.sl`.
+fn local_module_exists(root: &Path, import_path: &str) -> bool {
+    let base = root.join(import_path);
+    [
+        base.clone(),
+        base.with_extension("sl"),
+        base.join("index.sl"),
+        base.join("mod.sl"),
+    ]
+    .iter()
+    .any(|candidate| candidate.is_file())
+}
+
+fn source_files(root: &Path, skip: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| {
+            let hidden = e.depth() > 0 && e.file_name().to_string_lossy().starts_with('.');
+            let vendored =
+                e.file_type().is_dir() && e.path().canonicalize().is_ok_and(|p| skip.contains(&p));
+            !hidden && !vendored
+        })
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|x| x == "sl"))
+        .map(|e| e.into_path())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Remove `names` from `pkg` and `soli.lock`, then write both back — what
+/// `soli remove` does for a single dependency.
+pub fn prune_dependencies(
+    pkg: &mut Package,
+    toml_path: &Path,
+    names: &[String],
+) -> Result<(), String> {
+    let lock_path = toml_path.with_file_name("soli.lock");
+    let mut lock = super::lockfile::LockFile::load(&lock_path).unwrap_or_default();
+    for name in names {
+        super::installer::remove_dependency(pkg, name, &mut lock);
+    }
+    std::fs::write(toml_path, pkg.to_toml())
+        .map_err(|e| format!("Failed to write soli.toml: {}", e))?;
+    if lock_path.exists() {
+        lock.save(&lock_path)
+            .map_err(|e| format!("Failed to update lock file: {}", e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_every_import_form() {
+        let source = "import \"a\";\nimport { x, y } from \"b/c\";\n\nimport * as m from './d';\nlet s = \"import\";\n";
+        assert_eq!(
+            imports_in(source),
+            vec![
+                (1, "a".to_string()),
+                (2, "b/c".to_string()),
+                (4, "./d".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! Vendored JavaScript and the import map that serves it (`soli js`).
+//!
+//! `soli js add <package>` downloads a package's ESM build from jsDelivr into
+//! `vendor/javascript`, along with every package that build imports, and pins
+//! each one under `[javascript]` in soli.toml. jsDelivr writes a build's
+//! imports as CDN paths (`/npm/dep@1.2.0/+esm`); they are rewritten to bare
+//! specifiers (`dep`), which the import map emitted by the
+//! `javascript_importmap_tags()` view helper resolves to the vendored files.
+//! The browser loads everything from the app — no npm, no bundler, no CDN at
+//! runtime.
+//!
+//! An import map holds one URL per name, so when two packages import
+//! different versions of a dependency the first one vendored is kept.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
+
+use regex::Regex;
+
+use super::Package;
+
+/// Where vendored files live, relative to the app directory.
+pub const VENDOR_DIR: &str = "vendor/javascript";
+
+/// The URL path the server serves [`VENDOR_DIR`] under.
+pub const URL_PREFIX: &str = "/vendor/javascript/";
+
+const CDN_URL: &str = "https://cdn.jsdelivr.net/npm/";
+const RESOLVE_URL: &str = "https://data.jsdelivr.com/v1/packages/npm/";
+
+/// A quoted jsDelivr import: quote, package, version, subpath, quote.
+static CDN_IMPORT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(["'])/npm/((?:@[^/"']+/)?[^@/"']+)@([^/"']+)((?:/[^"']*?)?)/\+esm(["'])"#)
+        .unwrap()
+});
+
+/// Split `name@version` into the name and the version, if any. A leading
+/// `@` is the scope of the name: `@hotwired/stimulus@3` is
+/// `("@hotwired/stimulus", Some("3"))`.
+pub fn parse_spec(spec: &str) -> (String, Option<String>) {
+    match spec.rfind('@') {
+        Some(at) if at > 0 => (spec[..at].to_string(), Some(spec[at + 1..].to_string())),
+        _ => (spec.to_string(), None),
+    }
+}
+
+/// The package a pin belongs to and the subpath within it:
+/// `preact/hooks` is `("preact", "/hooks")`.
+fn split_pin(name: &str) -> (&str, &str) {
+    let package_end = if name.starts_with('@') {
+        name.match_indices('/').nth(1).map(|(i, _)| i)
+    } else {
+        name.find('/')
+    };
+    match package_end {
+        Some(end) => (&name[..end], &name[end..]),
+        None => (name, ""),
+    }
+}
+
+/// The file a pin is vendored as: `@hotwired/stimulus` is
+/// `@hotwired--stimulus.js`.
+pub fn file_name(name: &str) -> String {
+    format!("{}.js", name.replace('/', "--"))
+}
+
+/// Rewrite the CDN imports in a jsDelivr build to bare specifiers and drop
+/// its source map comment. Returns the new source and the pins it imports,
+/// with their versions.
+fn rewrite_imports(source: &str) -> (String, Vec<(String, String)>) {
+    let mut imports = Vec::new();
+    let rewritten = CDN_IMPORT.replace_all(source, |caps: &regex::Captures| {
+        let pin = format!("{}{}", &caps[2], &caps[4]);
+        imports.push((pin.clone(), caps[3].to_string()));
+        format!("{}{}{}", &caps[1], pin, &caps[5])
+    });
+    let rewritten = rewritten
+        .lines()
+        .filter(|line| !line.starts_with("//# sourceMappingURL="))
+        .collect::<Vec<_>>()
+        .join("\n");
+    (rewritten, imports)
+}
+
+/// The exact version of `package` that `range` (a version, a range or a
+/// dist-tag) resolves to; the latest when `range` is `None`.
+fn resolve_version(
+    package: &str,
+    range: Option<&str>,
+    fetch: &mut dyn FnMut(&str) -> Result<String, String>,
+) -> Result<String, String> {
+    let range = range.unwrap_or("latest");
+    let url = format!(
+        "{}{}/resolved?specifier={}",
+        RESOLVE_URL,
+        package,
+        urlencoding::encode(range)
+    );
+    let body = fetch(&url)?;
+    let json: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|e| format!("Unexpected response resolving {}: {}", package, e))?;
+    json["version"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| format!("No version of {} matches '{}'", package, range))
+}
+
+/// Download `name` at `version` (latest when `None`) and everything it
+/// imports into `vendor_dir`. Returns what was vendored, name -> exact
+/// version; dependencies already in `pinned` at the version asked for are
+/// kept as they are. `fetch` GETs a URL and returns its body.
+pub fn vendor(
+    name: &str,
+    version: Option<&str>,
+    vendor_dir: &Path,
+    pinned: &BTreeMap<String, String>,
+    fetch: &mut dyn FnMut(&str) -> Result<String, String>,
+) -> Result<BTreeMap<String, String>, String> {
+    let (package, _) = split_pin(name);
+    let exact = resolve_version(package, version, fetch)?;
+    fs::create_dir_all(vendor_dir)
+        .map_err(|e| format!("Cannot create {}: {}", vendor_dir.display(), e))?;
+
+    let mut vendored = BTreeMap::new();
+    let mut seen = HashSet::new();
+    let mut queue = vec![(name.to_string(), exact)];
+    while let Some((pin, version)) = queue.pop() {
+        if !seen.insert(pin.clone()) {
+            continue;
+        }
+        let is_root = pin == name;
+        if !is_root
+            && pinned.get(&pin) == Some(&version)
+            && vendor_dir.join(file_name(&pin)).is_file()
+        {
+            continue;
+        }
+        let (package, subpath) = split_pin(&pin);
+        let url = format!("{}{}@{}{}/+esm", CDN_URL, package, version, subpath);
+        let (source, imports) = rewrite_imports(&fetch(&url)?);
+        let path = vendor_dir.join(file_name(&pin));
+        fs::write(&path, source).map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+        queue.extend(imports);
+        vendored.insert(pin, version);
+    }
+    Ok(vendored)
+}
+
+/// GET `url` for [`vendor`].
+pub fn http_fetch(url: &str) -> Result<String, String> {
+    // CLI-only, like `soli install`: the developer asked for the package, and
+    // jsDelivr redirects ranges to exact versions, so follow redirects.
+    ureq::get(url)
+        .set("User-Agent", "soli-package-manager")
+        .call()
+        .map_err(|e| format!("Failed to fetch {}", e))?
+        .into_string()
+        .map_err(|e| format!("Failed to read {}: {}", url, e))
+}
+
+/// The import map JSON for `pins`. The version in each URL busts caches
+/// when a pin is upgraded.
+pub fn import_map(pins: &BTreeMap<String, String>) -> String {
+    let imports: serde_json::Map<String, serde_json::Value> = pins
+        .iter()
+        .map(|(name, version)| {
+            (
+                name.clone(),
+                format!("{}{}?v={}", URL_PREFIX, file_name(name), version).into(),
+            )
+        })
+        .collect();
+    serde_json::json!({ "imports": imports }).to_string()
+}
+
+/// Pins by manifest path, with the manifest's mtime when they were read.
+type PinCache = HashMap<PathBuf, (SystemTime, BTreeMap<String, String>)>;
+
+static PIN_CACHE: LazyLock<Mutex<PinCache>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The `[javascript]` pins of the soli.toml in `app_dir`, re-read only when
+/// the file changes. Empty when there is no readable manifest.
+pub fn app_pins(app_dir: &Path) -> BTreeMap<String, String> {
+    let manifest = app_dir.join("soli.toml");
+    let Ok(modified) = fs::metadata(&manifest).and_then(|m| m.modified()) else {
+        return BTreeMap::new();
+    };
+    let mut cache = PIN_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((at, pins)) = cache.get(&manifest) {
+        if *at == modified {
+            return pins.clone();
+        }
+    }
+    let pins = Package::load(&manifest)
+        .map(|pkg| pkg.javascript)
+        .unwrap_or_default();
+    cache.insert(manifest, (modified, pins.clone()));
+    pins
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specs_and_pins_split_around_scopes() {
+        assert_eq!(parse_spec("lodash-es"), ("lodash-es".to_string(), None));
+        assert_eq!(
+            parse_spec("@hotwired/stimulus@3.2"),
+            ("@hotwired/stimulus".to_string(), Some("3.2".to_string()))
+        );
+        assert_eq!(split_pin("preact/hooks"), ("preact", "/hooks"));
+        assert_eq!(
+            split_pin("@scope/pkg/sub/path"),
+            ("@scope/pkg", "/sub/path")
+        );
+        assert_eq!(split_pin("@scope/pkg"), ("@scope/pkg", ""));
+        assert_eq!(file_name("@hotwired/stimulus"), "@hotwired--stimulus.js");
+    }
+
+    #[test]
+    fn vendors_a_package_and_its_imports_as_bare_specifiers() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut fetched = Vec::new();
+        let mut fetch = |url: &str| -> Result<String, String> {
+            fetched.push(url.to_string());
+            Ok(match url {
+                u if u.contains("resolved?specifier=latest") => r#"{"version":"10.1.0"}"#,
+                u if u.ends_with("/preact@10.1.0/hooks/+esm") => {
+                    "import{h}from\"/npm/preact@10.1.0/+esm\";export const useState=1;\n//# sourceMappingURL=/sm/x.map"
+                }
+                u if u.ends_with("/preact@10.1.0/+esm") => "export const h=1;",
+                other => return Err(format!("unexpected {}", other)),
+            }
+            .to_string())
+        };
+
+        let vendored = vendor(
+            "preact/hooks",
+            None,
+            dir.path(),
+            &BTreeMap::new(),
+            &mut fetch,
+        )
+        .unwrap();
+        assert_eq!(vendored.len(), 2);
+        assert_eq!(vendored["preact"], "10.1.0");
+        let hooks = fs::read_to_string(dir.path().join("preact--hooks.js")).unwrap();
+        assert_eq!(hooks, "import{h}from\"preact\";export const useState=1;");
+        assert!(fetched[0].contains("/npm/preact/resolved"), "{:?}", fetched);
+
+        let map = import_map(&vendored);
+        assert!(
+            map.contains(r#""preact/hooks":"/vendor/javascript/preact--hooks.js?v=10.1.0""#),
+            "{}",
+            map
+        );
+    }
+}
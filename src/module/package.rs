@@ -1,6 +1,6 @@
 //! Package file (soli.toml) parsing.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -20,8 +20,29 @@ pub struct Package {
     /// Minimum required Soli interpreter version (e.g. "1.16.0").
     /// When set, `soli serve`/`test`/`run` refuse to start on an older soli.
     pub soli_version: Option<String>,
+    /// Allow import cycles: an import that closes a cycle binds its names
+    /// lazily instead of failing resolution.
+    pub lazy_imports: bool,
+    /// Declaration file (`.d.sl`) describing what the package provides,
+    /// relative to the package directory (`types = "index.d.sl"`). The type
+    /// checker reads it when a module of the package is imported.
+    pub types: Option<String>,
     /// Dependencies: name -> path or version
     pub dependencies: HashMap<String, Dependency>,
+    /// Pages `soli export-static` renders instead of the app's GET routes
+    /// (`[export] paths = ["/", "/about"]`).
+    pub export_paths: Vec<String>,
+    /// Project subcommands: `soli <name>` runs the script, a path relative to
+    /// the package directory (`[commands] deploy = "scripts/deploy.sl"`).
+    pub commands: BTreeMap<String, String>,
+    /// Vendored JavaScript packages pinned in the import map: npm name ->
+    /// version (`[javascript] "@hotwired/stimulus" = "3.2.2"`). Managed by
+    /// `soli js add`; the files live in `vendor/javascript`.
+    pub javascript: BTreeMap<String, String>,
+    /// Lint rule levels: a rule (`"style/line-length"`), a category
+    /// (`style`) or `default` -> `off`, `warn` or `error` (`[lint]`). Read
+    /// by `soli lint` through `lint::LintConfig`.
+    pub lint: BTreeMap<String, String>,
     /// Directory containing soli.toml (set by Package::load)
     pub package_dir: Option<PathBuf>,
 }
@@ -77,7 +98,13 @@ impl Package {
             description: None,
             main: "app.sl".to_string(),
             soli_version: None,
+            lazy_imports: false,
+            types: None,
             dependencies: HashMap::new(),
+            export_paths: Vec::new(),
+            commands: BTreeMap::new(),
+            javascript: BTreeMap::new(),
+            lint: BTreeMap::new(),
             package_dir: None,
         }
     }
@@ -93,8 +120,13 @@ impl Package {
     /// Parse a soli.toml content string.
     ///
     /// Simple TOML subset parser supporting:
-    /// - [package] section with name, version, description, main
+    /// - [package] section with name, version, description, main,
+    ///   soli_version, lazy_imports, types
     /// - [dependencies] section with name = "path" or name = { path = "..." }
+    /// - [export] section with paths = ["/", ...]
+    /// - [commands] section with name = "script.sl"
+    /// - [javascript] section with "npm-name" = "version"
+    /// - [lint] section with "rule/name" = "off" | "warn" | "error"
     pub fn parse(content: &str) -> Result<Self, PackageError> {
         let mut package = Package::default();
         let mut current_section: Option<&str> = None;
@@ -113,6 +145,10 @@ impl Package {
                 current_section = Some(match section {
                     "package" => "package",
                     "dependencies" => "dependencies",
+                    "export" => "export",
+                    "commands" => "commands",
+                    "javascript" => "javascript",
+                    "lint" => "lint",
                     _ => {
                         return Err(PackageError::ParseError(format!(
                             "Unknown section: {}",
@@ -129,6 +165,17 @@ impl Package {
                 let value = value.trim();
 
                 match current_section {
+                    Some("package") if key == "lazy_imports" => {
+                        package.lazy_imports = match value {
+                            "true" => true,
+                            "false" => false,
+                            _ => {
+                                return Err(PackageError::InvalidField(
+                                    "package.lazy_imports (expected true or false)".to_string(),
+                                ))
+                            }
+                        };
+                    }
                     Some("package") => {
                         let value = parse_string_value(value)?;
                         match key {
@@ -137,6 +184,7 @@ impl Package {
                             "description" => package.description = Some(value),
                             "main" => package.main = value,
                             "soli_version" => package.soli_version = Some(value),
+                            "types" => package.types = Some(value),
                             _ => {
                                 return Err(PackageError::InvalidField(format!("package.{}", key)))
                             }
@@ -146,6 +194,36 @@ impl Package {
                         let dep = parse_dependency(value)?;
                         package.dependencies.insert(key.to_string(), dep);
                     }
+                    Some("export") => match key {
+                        "paths" => package.export_paths = parse_string_array(value)?,
+                        _ => return Err(PackageError::InvalidField(format!("export.{}", key))),
+                    },
+                    Some("commands") => {
+                        let script = parse_string_value(value)?;
+                        if !script.ends_with(".sl") {
+                            return Err(PackageError::InvalidField(format!(
+                                "commands.{} (expected a .sl script)",
+                                key
+                            )));
+                        }
+                        package.commands.insert(key.to_string(), script);
+                    }
+                    Some("javascript") => {
+                        let name = parse_string_value(key)?;
+                        let version = parse_string_value(value)?;
+                        package.javascript.insert(name, version);
+                    }
+                    Some("lint") => {
+                        let rule = parse_string_value(key)?;
+                        let level = parse_string_value(value)?;
+                        if !matches!(level.as_str(), "off" | "warn" | "error") {
+                            return Err(PackageError::InvalidField(format!(
+                                "lint.{} (expected off, warn or error)",
+                                rule
+                            )));
+                        }
+                        package.lint.insert(rule, level);
+                    }
                     None => {
                         return Err(PackageError::ParseError(
                             "Key-value outside of section".to_string(),
@@ -197,6 +275,21 @@ fn parse_string_value(value: &str) -> Result<String, PackageError> {
     }
 }
 
+/// Parse a single-line array of strings: `["a", 'b']`.
+fn parse_string_array(value: &str) -> Result<Vec<String>, PackageError> {
+    let inner = value
+        .trim()
+        .strip_prefix('[')
+        .and_then(|v| v.strip_suffix(']'))
+        .ok_or_else(|| PackageError::ParseError(format!("Expected an array: {}", value)))?;
+    inner
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(parse_string_value)
+        .collect()
+}
+
 /// Parse a dependency value.
 fn parse_dependency(value: &str) -> Result<Dependency, PackageError> {
     let value = value.trim();
@@ -311,9 +404,15 @@ impl Package {
         if let Some(ref desc) = self.description {
             out.push_str(&format!("description = \"{}\"\n", desc));
         }
-        if self.main != "app.sl" {
+        if !self.main.is_empty() && self.main != "app.sl" {
             out.push_str(&format!("main = \"{}\"\n", self.main));
         }
+        if self.lazy_imports {
+            out.push_str("lazy_imports = true\n");
+        }
+        if let Some(ref types) = self.types {
+            out.push_str(&format!("types = \"{}\"\n", types));
+        }
 
         if !self.dependencies.is_empty() {
             out.push_str("\n[dependencies]\n");
@@ -350,6 +449,52 @@ impl Package {
             }
         }
 
+        if !self.export_paths.is_empty() {
+            let paths: Vec<String> = self
+                .export_paths
+                .iter()
+                .map(|p| format!("\"{}\"", p))
+                .collect();
+            out.push_str(&format!("\n[export]\npaths = [{}]\n", paths.join(", ")));
+        }
+
+        if !self.commands.is_empty() {
+            out.push_str("\n[commands]\n");
+            for (name, script) in &self.commands {
+                out.push_str(&format!("{} = \"{}\"\n", name, script));
+            }
+        }
+
+        if !self.javascript.is_empty() {
+            out.push_str("\n[javascript]\n");
+            for (name, version) in &self.javascript {
+                // Scoped names (`@hotwired/stimulus`) aren't bare TOML keys.
+                if name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                {
+                    out.push_str(&format!("{} = \"{}\"\n", name, version));
+                } else {
+                    out.push_str(&format!("\"{}\" = \"{}\"\n", name, version));
+                }
+            }
+        }
+
+        if !self.lint.is_empty() {
+            out.push_str("\n[lint]\n");
+            for (rule, level) in &self.lint {
+                // Rule names (`style/line-length`) aren't bare TOML keys.
+                if rule
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                {
+                    out.push_str(&format!("{} = \"{}\"\n", rule, level));
+                } else {
+                    out.push_str(&format!("\"{}\" = \"{}\"\n", rule, level));
+                }
+            }
+        }
+
         out
     }
 
@@ -420,6 +565,43 @@ pub fn enforce_min_soli_version(start_dir: &Path) -> Result<(), String> {
     pkg.check_soli_version(env!("CARGO_PKG_VERSION"))
 }
 
+/// Walk up from `start_dir` for a `soli.toml` and resolve its `[commands]`
+/// entry `name` to the script's path.
+///
+/// `Ok(None)` when there is no manifest or it declares no such command; `Err`
+/// when the command is declared but its manifest can't be read or its script
+/// is missing, so `soli <name>` can say why instead of treating the name as a
+/// file to run.
+pub fn find_command_script(start_dir: &Path, name: &str) -> Result<Option<PathBuf>, String> {
+    let Some(manifest) = Package::find(start_dir) else {
+        return Ok(None);
+    };
+    let pkg = Package::load(&manifest).map_err(|e| format!("{}: {}", manifest.display(), e))?;
+    let Some(script) = pkg.commands.get(name) else {
+        return Ok(None);
+    };
+    let dir = pkg.package_dir.unwrap_or_default();
+    let path = dir.join(script);
+    if !path.is_file() {
+        return Err(format!(
+            "command '{}' in {} points at {}, which doesn't exist",
+            name,
+            manifest.display(),
+            path.display()
+        ));
+    }
+    Ok(Some(path))
+}
+
+/// The `[commands]` of the `soli.toml` above `start_dir`, for `soli --help`.
+/// Empty when there is no manifest or it can't be read.
+pub fn project_commands(start_dir: &Path) -> BTreeMap<String, String> {
+    Package::find(start_dir)
+        .and_then(|manifest| Package::load(&manifest).ok())
+        .map(|pkg| pkg.commands)
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -589,6 +771,52 @@ utils = { path = "../utils" }
         assert_eq!(reparsed.soli_version.as_deref(), Some("1.16.0"));
     }
 
+    #[test]
+    fn test_parse_types() {
+        let content = r#"
+[package]
+name = "money"
+types = "index.d.sl"
+"#;
+
+        let pkg = Package::parse(content).unwrap();
+        assert_eq!(pkg.types.as_deref(), Some("index.d.sl"));
+        let reparsed = Package::parse(&pkg.to_toml()).unwrap();
+        assert_eq!(reparsed.types.as_deref(), Some("index.d.sl"));
+    }
+
+    #[test]
+    fn test_parse_lazy_imports() {
+        let content = r#"
+[package]
+name = "my-app"
+lazy_imports = true
+"#;
+
+        let pkg = Package::parse(content).unwrap();
+        assert!(pkg.lazy_imports);
+        assert!(Package::parse(&pkg.to_toml()).unwrap().lazy_imports);
+        assert!(!Package::new("my-app").lazy_imports);
+        assert!(Package::parse("[package]\nname = \"x\"\nlazy_imports = \"yes\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_export_paths() {
+        let content = r#"
+[package]
+name = "docs"
+
+[export]
+paths = ["/", '/about', "/feed.xml",]
+"#;
+
+        let pkg = Package::parse(content).unwrap();
+        assert_eq!(pkg.export_paths, vec!["/", "/about", "/feed.xml"]);
+        let reparsed = Package::parse(&pkg.to_toml()).unwrap();
+        assert_eq!(reparsed.export_paths, pkg.export_paths);
+        assert!(Package::parse("[package]\nname = \"x\"\n[export]\npaths = \"/\"").is_err());
+    }
+
     #[test]
     fn test_check_soli_version_gate() {
         let mut pkg = Package::new("my-app");
@@ -607,4 +835,73 @@ utils = { path = "../utils" }
         assert!(pkg.check_soli_version("1.20.0").is_ok());
         assert!(pkg.check_soli_version("2.0.0").is_ok());
     }
+
+    #[test]
+    fn test_parse_commands() {
+        let content = r#"
+[package]
+name = "shop"
+
+[commands]
+deploy = "scripts/deploy.sl"
+seed-demo = 'scripts/demo.sl'
+"#;
+
+        let pkg = Package::parse(content).unwrap();
+        assert_eq!(pkg.commands.len(), 2);
+        assert_eq!(pkg.commands["deploy"], "scripts/deploy.sl");
+        let reparsed = Package::parse(&pkg.to_toml()).unwrap();
+        assert_eq!(reparsed.commands, pkg.commands);
+        assert!(Package::parse("[package]\nname = \"x\"\n[commands]\nbad = \"run.sh\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_javascript_pins() {
+        let content = r#"
+[package]
+name = "shop"
+
+[javascript]
+"@hotwired/stimulus" = "3.2.2"
+lodash-es = "4.17.21"
+"#;
+
+        let pkg = Package::parse(content).unwrap();
+        assert_eq!(pkg.javascript["@hotwired/stimulus"], "3.2.2");
+        assert_eq!(pkg.javascript["lodash-es"], "4.17.21");
+        let toml = pkg.to_toml();
+        assert!(
+            toml.contains("\"@hotwired/stimulus\" = \"3.2.2\""),
+            "{}",
+            toml
+        );
+        assert!(toml.contains("\nlodash-es = \"4.17.21\""), "{}", toml);
+        assert_eq!(Package::parse(&toml).unwrap().javascript, pkg.javascript);
+    }
+
+    #[test]
+    fn test_parse_lint_levels() {
+        let content = r#"
+[package]
+name = "shop"
+
+[lint]
+default = "warn"
+style = "off"
+"smell/unused-variable" = "error"
+"#;
+
+        let pkg = Package::parse(content).unwrap();
+        assert_eq!(pkg.lint["default"], "warn");
+        assert_eq!(pkg.lint["style"], "off");
+        assert_eq!(pkg.lint["smell/unused-variable"], "error");
+        let toml = pkg.to_toml();
+        assert!(
+            toml.contains("\"smell/unused-variable\" = \"error\""),
+            "{}",
+            toml
+        );
+        assert_eq!(Package::parse(&toml).unwrap().lint, pkg.lint);
+        assert!(Package::parse("[package]\nname = \"x\"\n[lint]\nstyle = \"fatal\"").is_err());
+    }
 }
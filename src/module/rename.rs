@@ -0,0 +1,639 @@
+//! Scope-aware renaming of a module's top-level names.
+//!
+//! An import copies a module's declarations into the importing program. On
+//! the way, each declaration takes the name it has there — an imported item
+//! its local name, anything else a name qualified by its module — and every
+//! reference to it inside the copy is rewritten to match. Parameters, locals
+//! and other bindings that shadow a top-level name are left alone.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::ast::expr::{
+    Argument, Expr, ExprKind, HashComprehension, InterpolatedPart, MatchPattern,
+};
+use crate::ast::stmt::{ClassDecl, Parameter, Stmt, StmtKind};
+use crate::ast::types::{TypeAnnotation, TypeKind};
+
+/// Rename a top-level declaration and the references inside it according to
+/// `names` (old name → new name).
+pub(super) fn rename_declaration(stmt: &mut Stmt, names: &HashMap<String, String>) {
+    let renamed = |name: &mut String| {
+        if let Some(new_name) = names.get(name.as_str()) {
+            *name = new_name.clone();
+        }
+    };
+    match &mut stmt.kind {
+        StmtKind::Function(decl) => renamed(&mut decl.name),
+        StmtKind::Class(decl) => renamed(&mut decl.name),
+        StmtKind::Enum(decl) => renamed(&mut decl.name),
+        StmtKind::Struct(decl) => renamed(&mut decl.name),
+        StmtKind::Interface(decl) => renamed(&mut decl.name),
+        StmtKind::TypeAlias(decl) => renamed(&mut decl.name),
+        StmtKind::Let { name, .. } | StmtKind::Const { name, .. } => renamed(name),
+        _ => {}
+    }
+    Renamer {
+        names,
+        scopes: Vec::new(),
+    }
+    .stmt(stmt);
+}
+
+struct Renamer<'a> {
+    names: &'a HashMap<String, String>,
+    /// Names bound by enclosing functions, blocks, loops and patterns.
+    scopes: Vec<HashSet<String>>,
+}
+
+impl Renamer<'_> {
+    fn value_name(&self, name: &mut String) {
+        if self
+            .scopes
+            .iter()
+            .any(|scope| scope.contains(name.as_str()))
+        {
+            return;
+        }
+        if let Some(new_name) = self.names.get(name.as_str()) {
+            *name = new_name.clone();
+        }
+    }
+
+    /// Class, enum and interface names in type positions. Locals can't
+    /// shadow these.
+    fn type_name(&self, name: &mut String) {
+        if let Some(new_name) = self.names.get(name.as_str()) {
+            *name = new_name.clone();
+        }
+    }
+
+    /// Bind `name` in the innermost scope. At the top level the caller has
+    /// already given the declaration its new name.
+    fn bind(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string());
+        }
+    }
+
+    fn scoped(&mut self, bound: HashSet<String>, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(bound);
+        f(self);
+        self.scopes.pop();
+    }
+
+    fn stmt(&mut self, stmt: &mut Stmt) {
+        match &mut stmt.kind {
+            StmtKind::Expression(expr) | StmtKind::Throw(expr) | StmtKind::Yield(expr) => {
+                self.expr(expr)
+            }
+            StmtKind::Let {
+                name,
+                type_annotation,
+                initializer,
+                ..
+            } => {
+                if let Some(ty) = type_annotation {
+                    self.ty(ty);
+                }
+                if let Some(init) = initializer {
+                    self.expr(init);
+                }
+                self.bind(name);
+            }
+            StmtKind::Const {
+                name,
+                type_annotation,
+                initializer,
+            } => {
+                if let Some(ty) = type_annotation {
+                    self.ty(ty);
+                }
+                self.expr(initializer);
+                self.bind(name);
+            }
+            StmtKind::LetTuple {
+                names,
+                type_annotation,
+                initializer,
+            } => {
+                if let Some(ty) = type_annotation {
+                    self.ty(ty);
+                }
+                self.expr(initializer);
+                for name in names {
+                    self.bind(name);
+                }
+            }
+            StmtKind::Block(stmts) => self.scoped(HashSet::new(), |r| r.stmts(stmts)),
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expr(condition);
+                self.stmt(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.stmt(else_branch);
+                }
+            }
+            StmtKind::While { condition, body } => {
+                self.expr(condition);
+                self.stmt(body);
+            }
+            StmtKind::For {
+                variable,
+                index_variable,
+                destructure,
+                iterable,
+                body,
+            } => {
+                self.expr(iterable);
+                let mut bound = HashSet::from([variable.clone()]);
+                bound.extend(index_variable.clone());
+                bound.extend(destructure.iter().flatten().cloned());
+                self.scoped(bound, |r| r.stmt(body));
+            }
+            StmtKind::Return(value) => {
+                if let Some(value) = value {
+                    self.expr(value);
+                }
+            }
+            StmtKind::Break | StmtKind::Import(_) => {}
+            StmtKind::Try {
+                try_block,
+                catch_clauses,
+                finally_block,
+            } => {
+                self.stmt(try_block);
+                for clause in catch_clauses {
+                    if let Some(type_name) = &mut clause.type_name {
+                        self.type_name(type_name);
+                    }
+                    let bound = clause.var_name.iter().cloned().collect();
+                    self.scoped(bound, |r| r.stmt(&mut clause.body));
+                }
+                if let Some(finally_block) = finally_block {
+                    self.stmt(finally_block);
+                }
+            }
+            StmtKind::Function(decl) => {
+                self.bind(&decl.name);
+                self.function(
+                    &mut decl.params,
+                    decl.return_type.as_mut(),
+                    Arc::make_mut(&mut decl.body).as_mut_slice(),
+                );
+            }
+            StmtKind::Class(decl) => {
+                self.bind(&decl.name);
+                self.class(decl);
+            }
+            StmtKind::Enum(decl) => {
+                self.bind(&decl.name);
+                for variant in &mut decl.variants {
+                    for field in &mut variant.payload {
+                        if let Some(ty) = &mut field.type_annotation {
+                            self.ty(ty);
+                        }
+                    }
+                }
+                for method in &mut decl.methods {
+                    self.function(
+                        &mut method.params,
+                        method.return_type.as_mut(),
+                        Arc::make_mut(&mut method.body).as_mut_slice(),
+                    );
+                }
+            }
+            StmtKind::Struct(decl) => {
+                self.bind(&decl.name);
+                for field in &mut decl.fields {
+                    if let Some(ty) = &mut field.type_annotation {
+                        self.ty(ty);
+                    }
+                    if let Some(default) = &mut field.default_value {
+                        self.expr(default);
+                    }
+                }
+            }
+            StmtKind::Interface(decl) => {
+                self.bind(&decl.name);
+                for method in &mut decl.methods {
+                    for param in &mut method.params {
+                        self.ty(&mut param.type_annotation);
+                    }
+                    if let Some(ty) = &mut method.return_type {
+                        self.ty(ty);
+                    }
+                }
+            }
+            StmtKind::TypeAlias(decl) => {
+                self.bind(&decl.name);
+                self.ty(&mut decl.type_annotation);
+            }
+            StmtKind::Export(inner) => self.stmt(inner),
+        }
+    }
+
+    fn stmts(&mut self, stmts: &mut [Stmt]) {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+    }
+
+    fn function(
+        &mut self,
+        params: &mut [Parameter],
+        return_type: Option<&mut TypeAnnotation>,
+        body: &mut [Stmt],
+    ) {
+        for param in params.iter_mut() {
+            self.ty(&mut param.type_annotation);
+            if let Some(default) = &mut param.default_value {
+                self.expr(default);
+            }
+        }
+        if let Some(ty) = return_type {
+            self.ty(ty);
+        }
+        let bound = params.iter().map(|p| p.name.clone()).collect();
+        self.scoped(bound, |r| r.stmts(body));
+    }
+
+    fn class(&mut self, decl: &mut ClassDecl) {
+        if let Some(superclass) = &mut decl.superclass {
+            self.type_name(superclass);
+        }
+        for interface in &mut decl.interfaces {
+            self.type_name(interface);
+        }
+        // A bare call inside a method may name another method of the class.
+        let bound = decl.methods.iter().map(|m| m.name.clone()).collect();
+        self.scoped(bound, |r| {
+            for field in &mut decl.fields {
+                if let Some(ty) = &mut field.type_annotation {
+                    r.ty(ty);
+                }
+                if let Some(init) = &mut field.initializer {
+                    r.expr(init);
+                }
+            }
+            for method in &mut decl.methods {
+                r.function(
+                    &mut method.params,
+                    method.return_type.as_mut(),
+                    Arc::make_mut(&mut method.body).as_mut_slice(),
+                );
+            }
+            if let Some(ctor) = &mut decl.constructor {
+                r.function(
+                    &mut ctor.params,
+                    None,
+                    Arc::make_mut(&mut ctor.body).as_mut_slice(),
+                );
+            }
+            if let Some(block) = &mut decl.static_block {
+                r.scoped(HashSet::new(), |r| r.stmts(block));
+            }
+            r.stmts(&mut decl.class_statements);
+            for nested in &mut decl.nested_classes {
+                r.class(nested);
+            }
+        });
+    }
+
+    fn ty(&self, ty: &mut TypeAnnotation) {
+        match &mut ty.kind {
+            TypeKind::Named(name) => self.type_name(name),
+            TypeKind::Void => {}
+            TypeKind::Array(inner) | TypeKind::Nullable(inner) => self.ty(inner),
+            TypeKind::Hash {
+                key_type,
+                value_type,
+            } => {
+                self.ty(key_type);
+                self.ty(value_type);
+            }
+            TypeKind::Function {
+                params,
+                return_type,
+            } => {
+                for param in params {
+                    self.ty(param);
+                }
+                self.ty(return_type);
+            }
+            TypeKind::Tuple(elements) => {
+                for element in elements {
+                    self.ty(element);
+                }
+            }
+            TypeKind::Record(fields) => {
+                for field in fields {
+                    self.ty(&mut field.type_annotation);
+                }
+            }
+        }
+    }
+
+    fn args(&mut self, args: &mut [Argument]) {
+        for arg in args {
+            match arg {
+                Argument::Positional(expr) | Argument::Block(expr) | Argument::Spread(expr) => {
+                    self.expr(expr)
+                }
+                Argument::Named(named) => self.expr(&mut named.value),
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &mut Expr) {
+        match &mut expr.kind {
+            ExprKind::Variable(name, _) => self.value_name(name),
+            ExprKind::InterpolatedString(parts) => {
+                for part in parts {
+                    if let InterpolatedPart::Expression(expr) = part {
+                        self.expr(expr);
+                    }
+                }
+            }
+            ExprKind::Binary { left, right, .. }
+            | ExprKind::Pipeline { left, right }
+            | ExprKind::LogicalAnd { left, right }
+            | ExprKind::LogicalOr { left, right }
+            | ExprKind::NullishCoalescing { left, right } => {
+                self.expr(left);
+                self.expr(right);
+            }
+            ExprKind::Unary { operand: inner, .. }
+            | ExprKind::Grouping(inner)
+            | ExprKind::Member { object: inner, .. }
+            | ExprKind::SafeMember { object: inner, .. }
+            | ExprKind::QualifiedName {
+                qualifier: inner, ..
+            }
+            | ExprKind::PostfixIncrement(inner)
+            | ExprKind::PostfixDecrement(inner)
+            | ExprKind::Spread(inner)
+            | ExprKind::Throw(inner)
+            | ExprKind::Await(inner) => self.expr(inner),
+            ExprKind::Call { callee, arguments } => {
+                self.expr(callee);
+                self.args(arguments);
+            }
+            ExprKind::New {
+                class_expr,
+                arguments,
+            } => {
+                self.expr(class_expr);
+                self.args(arguments);
+            }
+            ExprKind::Index { object, index } => {
+                self.expr(object);
+                self.expr(index);
+            }
+            ExprKind::Array(items) | ExprKind::Tuple(items) => {
+                for item in items {
+                    self.expr(item);
+                }
+            }
+            ExprKind::Hash(pairs) => {
+                for (key, value) in pairs {
+                    self.expr(key);
+                    self.expr(value);
+                }
+            }
+            ExprKind::Block(stmts) => self.scoped(HashSet::new(), |r| r.stmts(stmts)),
+            ExprKind::Assign { target, value } | ExprKind::CompoundAssign { target, value, .. } => {
+                self.expr(target);
+                self.expr(value);
+            }
+            ExprKind::Lambda {
+                params,
+                return_type,
+                body,
+                ..
+            } => self.function(
+                params,
+                return_type.as_deref_mut(),
+                Arc::make_mut(body).as_mut_slice(),
+            ),
+            ExprKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expr(condition);
+                self.expr(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.expr(else_branch);
+                }
+            }
+            ExprKind::Match { expression, arms } => {
+                self.expr(expression);
+                for arm in arms {
+                    let mut bound = HashSet::new();
+                    self.pattern(&mut arm.pattern, &mut bound);
+                    self.scoped(bound, |r| {
+                        if let Some(guard) = &mut arm.guard {
+                            r.expr(guard);
+                        }
+                        r.expr(&mut arm.body);
+                    });
+                }
+            }
+            ExprKind::ListComprehension {
+                element,
+                variable,
+                iterable,
+                condition,
+            } => {
+                self.expr(iterable);
+                self.scoped(HashSet::from([variable.clone()]), |r| {
+                    r.expr(element);
+                    if let Some(condition) = condition {
+                        r.expr(condition);
+                    }
+                });
+            }
+            ExprKind::HashComprehension(comp) => {
+                let HashComprehension {
+                    key,
+                    value,
+                    variable,
+                    iterable,
+                    condition,
+                } = &mut **comp;
+                self.expr(iterable);
+                self.scoped(HashSet::from([variable.clone()]), |r| {
+                    r.expr(key);
+                    r.expr(value);
+                    if let Some(condition) = condition {
+                        r.expr(condition);
+                    }
+                });
+            }
+            ExprKind::Rescue { expr, fallback } => {
+                self.expr(expr);
+                self.expr(fallback);
+            }
+            // SDQL interpolations are kept as source text and evaluated in
+            // the caller's scope; they aren't rewritten.
+            ExprKind::IntLiteral(_)
+            | ExprKind::FloatLiteral(_)
+            | ExprKind::DecimalLiteral(_)
+            | ExprKind::StringLiteral(_)
+            | ExprKind::CommandSubstitution(_)
+            | ExprKind::SdqlBlock { .. }
+            | ExprKind::BoolLiteral(_)
+            | ExprKind::Symbol(_)
+            | ExprKind::Null
+            | ExprKind::This
+            | ExprKind::Super => {}
+        }
+    }
+
+    /// Rename the type names in a match pattern and collect the names it binds.
+    fn pattern(&self, pattern: &mut MatchPattern, bound: &mut HashSet<String>) {
+        match pattern {
+            MatchPattern::Wildcard | MatchPattern::Literal(_) => {}
+            MatchPattern::Variable(name) => {
+                bound.insert(name.clone());
+            }
+            MatchPattern::Typed { name, type_name } => {
+                bound.insert(name.clone());
+                self.type_name(type_name);
+            }
+            MatchPattern::Array { elements, rest } => {
+                for element in elements {
+                    self.pattern(element, bound);
+                }
+                bound.extend(rest.clone());
+            }
+            MatchPattern::Tuple(elements) => {
+                for element in elements {
+                    self.pattern(element, bound);
+                }
+            }
+            MatchPattern::Hash { fields, rest } => {
+                for (_, field) in fields {
+                    self.pattern(field, bound);
+                }
+                bound.extend(rest.clone());
+            }
+            MatchPattern::Destructuring { type_name, fields } => {
+                self.type_name(type_name);
+                for (_, field) in fields {
+                    self.pattern(field, bound);
+                }
+            }
+            MatchPattern::EnumVariant {
+                enum_name,
+                bindings,
+                ..
+            } => {
+                self.type_name(enum_name);
+                for binding in bindings {
+                    self.pattern(binding, bound);
+                }
+            }
+            MatchPattern::And(patterns) | MatchPattern::Or(patterns) => {
+                for pattern in patterns {
+                    self.pattern(pattern, bound);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Scanner;
+    use crate::parser::Parser;
+
+    fn renamed(source: &str, pairs: &[(&str, &str)]) -> Vec<Stmt> {
+        let tokens = Scanner::new(source).scan_tokens().unwrap();
+        let mut program = Parser::new(tokens).parse().unwrap();
+        let names: HashMap<String, String> = pairs
+            .iter()
+            .map(|(old, new)| (old.to_string(), new.to_string()))
+            .collect();
+        for stmt in &mut program.statements {
+            rename_declaration(stmt, &names);
+        }
+        program.statements
+    }
+
+    fn function_body(stmt: &Stmt) -> &[Stmt] {
+        match &stmt.kind {
+            StmtKind::Function(decl) => &decl.body,
+            other => panic!("expected a function, got {:?}", other),
+        }
+    }
+
+    fn returned_callee(stmt: &Stmt) -> &str {
+        match &stmt.kind {
+            StmtKind::Return(Some(Expr {
+                kind: ExprKind::Call { callee, .. },
+                ..
+            })) => match &callee.kind {
+                ExprKind::Variable(name, _) => name,
+                other => panic!("expected a variable callee, got {:?}", other),
+            },
+            other => panic!("expected `return f(...)`, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn renames_declaration_and_references() {
+        let stmts = renamed(
+            "fn helper(x) { return x }\nfn render(x) { return helper(x) }",
+            &[("helper", "views::helper"), ("render", "show")],
+        );
+        match &stmts[0].kind {
+            StmtKind::Function(decl) => assert_eq!(decl.name, "views::helper"),
+            other => panic!("{:?}", other),
+        }
+        match &stmts[1].kind {
+            StmtKind::Function(decl) => assert_eq!(decl.name, "show"),
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(
+            returned_callee(&function_body(&stmts[1])[0]),
+            "views::helper"
+        );
+    }
+
+    #[test]
+    fn shadowing_bindings_are_left_alone() {
+        let stmts = renamed(
+            "fn a(helper) { return helper(1) }\nfn b() { let helper = fn(x) { return x }; return helper(2) }",
+            &[("helper", "views::helper")],
+        );
+        assert_eq!(returned_callee(&function_body(&stmts[0])[0]), "helper");
+        assert_eq!(returned_callee(&function_body(&stmts[1])[1]), "helper");
+    }
+
+    #[test]
+    fn renames_class_references() {
+        let stmts = renamed(
+            "class Base {}\nclass Child extends Base {}\nfn make() -> Base { return new Base() }",
+            &[("Base", "views::Base")],
+        );
+        match &stmts[1].kind {
+            StmtKind::Class(decl) => assert_eq!(decl.superclass.as_deref(), Some("views::Base")),
+            other => panic!("{:?}", other),
+        }
+        match &stmts[2].kind {
+            StmtKind::Function(decl) => {
+                assert_eq!(
+                    decl.return_type.as_ref().unwrap().to_string(),
+                    "views::Base"
+                )
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+}
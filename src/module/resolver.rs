@@ -2,10 +2,12 @@
 
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use crate::ast::{ImportDecl, ImportSpecifier, Program, Stmt, StmtKind};
 use crate::lexer::Scanner;
 use crate::parser::Parser;
+use crate::span::Span;
 
 use super::lockfile::LockFile;
 use super::package::Package;
@@ -28,8 +30,8 @@ pub struct ResolvedModule {
 pub enum ResolveError {
     /// File not found
     NotFound(String),
-    /// Circular dependency detected
-    CircularDependency(Vec<String>),
+    /// Circular dependency detected: the imports that close the cycle, in order
+    CircularDependency(Vec<ImportStep>),
     /// Import error (item not exported)
     ImportError(String),
     /// Parse error in module
@@ -38,12 +40,36 @@ pub enum ResolveError {
     IoError(std::io::Error),
 }
 
+/// One import along a chain of modules.
+#[derive(Debug, Clone)]
+pub struct ImportStep {
+    /// The importing file, relative to the project where possible
+    pub file: String,
+    /// The import path as written
+    pub import: String,
+    pub span: Span,
+}
+
 impl std::fmt::Display for ResolveError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ResolveError::NotFound(path) => write!(f, "Module not found: {}", path),
-            ResolveError::CircularDependency(cycle) => {
-                write!(f, "Circular dependency: {}", cycle.join(" -> "))
+            ResolveError::CircularDependency(steps) => {
+                let mut files: Vec<&str> = steps.iter().map(|s| s.file.as_str()).collect();
+                files.extend(files.first().copied());
+                writeln!(f, "Circular dependency: {}", files.join(" -> "))?;
+                for step in steps {
+                    writeln!(
+                        f,
+                        "  {}:{}:{} imports \"{}\"",
+                        step.file, step.span.line, step.span.column, step.import
+                    )?;
+                }
+                write!(
+                    f,
+                    "If the modules only use each other's names inside functions, set \
+                     `lazy_imports = true` under [package] in soli.toml to allow the cycle"
+                )
             }
             ResolveError::ImportError(msg) => write!(f, "Import error: {}", msg),
             ResolveError::ParseError(msg) => write!(f, "Parse error: {}", msg),
@@ -72,11 +98,32 @@ pub struct ModuleResolver {
     cache: HashMap<PathBuf, ResolvedModule>,
     /// Currently resolving stack (for cycle detection)
     resolving: Vec<PathBuf>,
+    /// The import each module on `resolving` is currently following
+    steps: Vec<ImportStep>,
+    /// Exported declarations (name -> span start) of the modules on `resolving`
+    in_progress: HashMap<PathBuf, HashMap<String, u32>>,
+    /// Let an import that closes a cycle through (`lazy_imports` in soli.toml)
+    lazy_imports: bool,
+    /// Names bound by imports that closed a cycle, fixed up once the entry
+    /// module is resolved
+    lazy_bindings: Vec<LazyBinding>,
+}
+
+/// A name an import took from a module that was still being resolved. Until
+/// the entry module is done, references to it use `placeholder`, the
+/// module-qualified name of the declaration.
+struct LazyBinding {
+    placeholder: String,
+    module: PathBuf,
+    name: String,
+    /// Span start of the declaration in `module`, to find where it landed
+    span_start: u32,
 }
 
 impl ModuleResolver {
     /// Create a new module resolver.
     pub fn new(base_dir: &Path) -> Self {
+        let base_dir = non_empty_dir(base_dir);
         // Try to find and load package file
         let (package, lock) = match Package::find(base_dir) {
             Some(pkg_path) => {
@@ -89,27 +136,37 @@ impl ModuleResolver {
             None => (None, None),
         };
 
+        let lazy_imports = package.as_ref().is_some_and(|p| p.lazy_imports);
         ModuleResolver {
             base_dir: base_dir.to_path_buf(),
             package,
             lock,
             cache: HashMap::new(),
             resolving: Vec::new(),
+            steps: Vec::new(),
+            in_progress: HashMap::new(),
+            lazy_imports,
+            lazy_bindings: Vec::new(),
         }
     }
 
     /// Create a module resolver with an explicit package.
     pub fn with_package(base_dir: &Path, package: Package) -> Self {
+        let base_dir = non_empty_dir(base_dir);
         // Try to load lock file
         let lock_path = base_dir.join("soli.lock");
         let lock = LockFile::load(&lock_path).ok();
 
         ModuleResolver {
             base_dir: base_dir.to_path_buf(),
+            lazy_imports: package.lazy_imports,
             package: Some(package),
             lock,
             cache: HashMap::new(),
             resolving: Vec::new(),
+            steps: Vec::new(),
+            in_progress: HashMap::new(),
+            lazy_bindings: Vec::new(),
         }
     }
 
@@ -124,17 +181,52 @@ impl ModuleResolver {
         source_path: &Path,
     ) -> Result<Program, ResolveError> {
         let canonical = self.canonicalize(source_path)?;
+        let is_entry = self.resolving.is_empty();
+        if is_entry {
+            self.resolving.push(canonical.clone());
+        }
+        self.in_progress
+            .insert(canonical.clone(), exported_declarations(&program));
         let mut combined_statements = Vec::new();
+        // Qualified names already brought in, so a module imported twice
+        // (directly or through another module) defines its internals once.
+        let mut qualified = HashSet::new();
+        // Local names bound by imports that closed a cycle -> placeholders
+        let mut lazy_names = HashMap::new();
 
         // First pass: collect all imports and their resolved modules
         for stmt in &program.statements {
             if let StmtKind::Import(import) = &stmt.kind {
-                let module = self.resolve_import(import, &canonical)?;
+                self.steps.push(ImportStep {
+                    file: self.display_path(&canonical),
+                    import: import.path.clone(),
+                    span: import.span,
+                });
+                let module_path = self.resolve_path(&import.path, &canonical)?;
+                if self.lazy_imports && self.resolving.contains(&module_path) {
+                    self.bind_lazily(import, &module_path, &mut lazy_names)?;
+                    self.steps.pop();
+                    continue;
+                }
+                let module = self.load_module(module_path)?;
+                self.steps.pop();
+                let prefix = self.module_prefix(&module.path);
 
                 // Add the imported definitions to the combined program
-                let (imported_stmts, module_path) = get_imported_statements(&module, import)?;
+                let (imported_stmts, module_path) =
+                    get_imported_statements(&module, import, &prefix)?;
+                let module_path: Arc<Path> = module_path.into();
                 for mut imported_stmt in imported_stmts {
-                    imported_stmt = set_stmt_source_path(&imported_stmt, module_path.clone());
+                    if let Some(name) = get_declaration_name(&imported_stmt) {
+                        if is_qualified(&name) && !qualified.insert(name) {
+                            continue;
+                        }
+                    }
+                    // Declarations a module took from its own imports keep
+                    // the source path they already carry.
+                    if imported_stmt.source_path.is_none() {
+                        set_stmt_source_path(&mut imported_stmt, &module_path);
+                    }
                     combined_statements.push(imported_stmt);
                 }
             }
@@ -156,26 +248,185 @@ impl ModuleResolver {
             }
         }
 
+        if !lazy_names.is_empty() {
+            for stmt in &mut combined_statements {
+                super::rename::rename_declaration(stmt, &lazy_names);
+            }
+        }
+        self.in_progress.remove(&canonical);
+        if is_entry {
+            self.resolving.pop();
+            self.finish_lazy_bindings(&canonical, &mut combined_statements)?;
+        }
+
         Ok(Program::new(combined_statements))
     }
 
-    /// Resolve an import declaration.
-    fn resolve_import(
+    /// The files of every module an import pulled in so far, in no
+    /// particular order. A cache of the resolved program is stale as soon
+    /// as one of them changes.
+    pub fn loaded_modules(&self) -> impl Iterator<Item = &Path> {
+        self.cache.keys().map(PathBuf::as_path)
+    }
+
+    /// The names exported by the module `import_path` points at, resolved
+    /// the way an import in `from_path` would be.
+    pub fn exports_of(
         &mut self,
-        import: &ImportDecl,
+        import_path: &str,
         from_path: &Path,
-    ) -> Result<ResolvedModule, ResolveError> {
-        let module_path = self.resolve_path(&import.path, from_path)?;
+    ) -> Result<HashSet<String>, ResolveError> {
+        // Only the directory has to exist: the importer may be an unsaved
+        // buffer.
+        let dir = self.canonicalize(non_empty_dir(
+            from_path.parent().unwrap_or_else(|| Path::new("")),
+        ))?;
+        let from = dir.join(from_path.file_name().unwrap_or_default());
+        let module_path = self.resolve_path(import_path, &from)?;
+        Ok(self.load_module(module_path)?.exports)
+    }
 
-        // Check for circular dependency
-        if self.resolving.contains(&module_path) {
-            let cycle: Vec<String> = self
-                .resolving
+    /// Bind the names an import takes from `module_path`, which is still
+    /// being resolved further up, to placeholders instead of following the
+    /// import around the cycle again.
+    fn bind_lazily(
+        &mut self,
+        import: &ImportDecl,
+        module_path: &Path,
+        lazy_names: &mut HashMap<String, String>,
+    ) -> Result<(), ResolveError> {
+        let exports = self
+            .in_progress
+            .get(module_path)
+            .cloned()
+            .unwrap_or_default();
+        let items: Vec<(String, String)> = match &import.specifier {
+            ImportSpecifier::Named(items) => items
                 .iter()
-                .map(|p| p.display().to_string())
-                .chain(std::iter::once(module_path.display().to_string()))
-                .collect();
-            return Err(ResolveError::CircularDependency(cycle));
+                .map(|item| {
+                    let local = item.alias.as_ref().unwrap_or(&item.name);
+                    (item.name.clone(), local.clone())
+                })
+                .collect(),
+            ImportSpecifier::All | ImportSpecifier::Namespace(_) => exports
+                .keys()
+                .map(|name| (name.clone(), name.clone()))
+                .collect(),
+        };
+        let prefix = self.module_prefix(module_path);
+        for (name, local) in items {
+            let Some(&span_start) = exports.get(&name) else {
+                return Err(ResolveError::ImportError(format!(
+                    "'{}' is not exported from '{}'",
+                    name, import.path
+                )));
+            };
+            let placeholder = format!("{}::{}", prefix, name);
+            lazy_names.insert(local, placeholder.clone());
+            self.lazy_bindings.push(LazyBinding {
+                placeholder,
+                module: module_path.to_path_buf(),
+                name,
+                span_start,
+            });
+        }
+        Ok(())
+    }
+
+    /// Point every placeholder left by a cycle at the name its declaration
+    /// ended up with in the entry program.
+    fn finish_lazy_bindings(
+        &mut self,
+        entry: &Path,
+        statements: &mut [Stmt],
+    ) -> Result<(), ResolveError> {
+        let bindings = std::mem::take(&mut self.lazy_bindings);
+        if bindings.is_empty() {
+            return Ok(());
+        }
+        let declared: HashSet<String> =
+            statements.iter().filter_map(get_declaration_name).collect();
+        let mut names = HashMap::new();
+        for binding in bindings {
+            if declared.contains(&binding.placeholder) {
+                continue;
+            }
+            let target = if binding.module == entry {
+                Some(binding.name.clone()).filter(|name| declared.contains(name))
+            } else {
+                statements
+                    .iter()
+                    .find(|stmt| {
+                        stmt.source_path.as_deref() == Some(binding.module.as_path())
+                            && stmt.span.start == binding.span_start
+                    })
+                    .and_then(get_declaration_name)
+            };
+            let Some(target) = target else {
+                return Err(ResolveError::ImportError(format!(
+                    "'{}' from '{}' is imported through a cycle but never defined",
+                    binding.name,
+                    self.display_path(&binding.module)
+                )));
+            };
+            names.insert(binding.placeholder, target);
+        }
+        if !names.is_empty() {
+            for stmt in statements {
+                super::rename::rename_declaration(stmt, &names);
+            }
+        }
+        Ok(())
+    }
+
+    /// A module path for messages: relative to the project when inside it.
+    fn display_path(&self, path: &Path) -> String {
+        let base = self
+            .base_dir
+            .canonicalize()
+            .unwrap_or_else(|_| self.base_dir.clone());
+        path.strip_prefix(&base)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+
+    /// The qualifier for a module's names that aren't imported under a name
+    /// of their own: its path without the extension, relative to the project
+    /// (or, for a dependency, to the package's root).
+    fn module_prefix(&self, module_path: &Path) -> String {
+        let base = self
+            .base_dir
+            .canonicalize()
+            .unwrap_or_else(|_| self.base_dir.clone());
+        let relative = match module_path.strip_prefix(&base) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => {
+                let root = self.containment_root_for(module_path);
+                let relative = module_path.strip_prefix(&root).unwrap_or(module_path);
+                match root.file_name() {
+                    Some(package) => Path::new(package).join(relative),
+                    None => relative.to_path_buf(),
+                }
+            }
+        };
+        relative
+            .with_extension("")
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Load and resolve the module an import points at.
+    fn load_module(&mut self, module_path: PathBuf) -> Result<ResolvedModule, ResolveError> {
+        // Check for circular dependency. `steps` runs parallel to
+        // `resolving`, so the cycle is the imports from the module's own
+        // entry on the stack up to the one being followed now.
+        if let Some(start) = self.resolving.iter().position(|p| *p == module_path) {
+            return Err(ResolveError::CircularDependency(
+                self.steps[start..].to_vec(),
+            ));
         }
 
         // Check cache
@@ -411,6 +662,16 @@ impl ModuleResolver {
     }
 }
 
+/// The parent of a bare file name (`soli main.sl`) is the empty path, which
+/// doesn't canonicalize; it stands for the current directory.
+fn non_empty_dir(dir: &Path) -> &Path {
+    if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    }
+}
+
 /// Normalize a path string by resolving . and .. components.
 fn normalize_path(path: &Path) -> PathBuf {
     if let Some(s) = path.to_str() {
@@ -446,6 +707,18 @@ fn normalize_path(path: &Path) -> PathBuf {
     }
 }
 
+/// Exported declarations of a program, with the span start of each.
+fn exported_declarations(program: &Program) -> HashMap<String, u32> {
+    program
+        .statements
+        .iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::Export(inner) => get_declaration_name(inner).map(|n| (n, inner.span.start)),
+            _ => None,
+        })
+        .collect()
+}
+
 /// Collect exported names from a program.
 fn collect_exports(program: &Program) -> HashSet<String> {
     let mut exports = HashSet::new();
@@ -461,27 +734,21 @@ fn collect_exports(program: &Program) -> HashSet<String> {
     exports
 }
 
-/// Get the statements to import from a module based on the import specifier.
+/// Get the declarations an import brings in from a module.
+///
+/// Every top-level declaration of the module comes along, since the imported
+/// ones may depend on the rest. Imported names take their local name (the
+/// alias, if one is given); the others are qualified by `prefix`, so they
+/// never collide with the importer's own names or another module's.
 fn get_imported_statements(
     module: &ResolvedModule,
     import: &ImportDecl,
+    prefix: &str,
 ) -> Result<(Vec<Stmt>, PathBuf), ResolveError> {
     let module_path = module.path.clone();
-    let stmts = match &import.specifier {
-        ImportSpecifier::All => {
-            // Import all exported definitions
-            let mut stmts = Vec::new();
-            for stmt in &module.original_program.statements {
-                if let StmtKind::Export(inner) = &stmt.kind {
-                    stmts.push((**inner).clone());
-                }
-            }
-            stmts
-        }
-
+    let mut names: HashMap<String, String> = match &import.specifier {
         ImportSpecifier::Named(items) => {
-            // Import specific named items
-            let mut stmts = Vec::new();
+            let mut names = HashMap::new();
             for item in items {
                 if !module.exports.contains(&item.name) {
                     return Err(ResolveError::ImportError(format!(
@@ -489,41 +756,46 @@ fn get_imported_statements(
                         item.name, import.path
                     )));
                 }
-
-                // Find the exported statement in the original program
-                for stmt in &module.original_program.statements {
-                    if let StmtKind::Export(inner) = &stmt.kind {
-                        if let Some(name) = get_declaration_name(inner) {
-                            if name == item.name {
-                                if let Some(ref alias) = item.alias {
-                                    // Rename the declaration
-                                    let renamed = rename_declaration(inner, alias);
-                                    stmts.push(renamed);
-                                } else {
-                                    stmts.push((**inner).clone());
-                                }
-                                break;
-                            }
-                        }
-                    }
-                }
+                let local = item.alias.as_ref().unwrap_or(&item.name);
+                names.insert(item.name.clone(), local.clone());
             }
-            stmts
-        }
+            names
+        }
+        // Namespace imports create a module object
+        // For now, we just import all exports
+        // TODO: Implement proper namespace object
+        ImportSpecifier::All | ImportSpecifier::Namespace(_) => module
+            .exports
+            .iter()
+            .map(|name| (name.clone(), name.clone()))
+            .collect(),
+    };
 
-        ImportSpecifier::Namespace(_name) => {
-            // Namespace imports create a module object
-            // For now, we just import all exports
-            // TODO: Implement proper namespace object
-            let mut stmts = Vec::new();
-            for stmt in &module.original_program.statements {
-                if let StmtKind::Export(inner) = &stmt.kind {
-                    stmts.push((**inner).clone());
-                }
+    // `module.program` holds the module's own imports already resolved, so
+    // names it qualified for its dependencies keep their qualification.
+    let declarations: Vec<&Stmt> = module
+        .program
+        .statements
+        .iter()
+        .filter(|stmt| get_declaration_name(stmt).is_some())
+        .collect();
+    for stmt in &declarations {
+        if let Some(name) = get_declaration_name(stmt) {
+            if !names.contains_key(&name) && !is_qualified(&name) {
+                let qualified = format!("{}::{}", prefix, name);
+                names.insert(name, qualified);
             }
-            stmts
         }
-    };
+    }
+
+    let stmts = declarations
+        .into_iter()
+        .map(|stmt| {
+            let mut stmt = stmt.clone();
+            super::rename::rename_declaration(&mut stmt, &names);
+            stmt
+        })
+        .collect();
     Ok((stmts, module_path))
 }
 
@@ -532,65 +804,48 @@ fn get_declaration_name(stmt: &Stmt) -> Option<String> {
     match &stmt.kind {
         StmtKind::Function(decl) => Some(decl.name.clone()),
         StmtKind::Class(decl) => Some(decl.name.clone()),
+        StmtKind::Enum(decl) => Some(decl.name.clone()),
         StmtKind::Interface(decl) => Some(decl.name.clone()),
-        StmtKind::Let { name, .. } => Some(name.clone()),
+        StmtKind::TypeAlias(decl) => Some(decl.name.clone()),
+        StmtKind::Let { name, .. } | StmtKind::Const { name, .. } => Some(name.clone()),
         _ => None,
     }
 }
 
-/// Rename a declaration.
-fn rename_declaration(stmt: &Stmt, new_name: &str) -> Stmt {
-    let mut new_stmt = stmt.clone();
-
-    match &mut new_stmt.kind {
-        StmtKind::Function(decl) => {
-            decl.name = new_name.to_string();
-        }
-        StmtKind::Class(decl) => {
-            decl.name = new_name.to_string();
-        }
-        StmtKind::Interface(decl) => {
-            decl.name = new_name.to_string();
-        }
-        StmtKind::Let { name, .. } => {
-            *name = new_name.to_string();
-        }
-        _ => {}
-    }
-
-    new_stmt
+/// Whether a name was qualified by its module on the way in.
+fn is_qualified(name: &str) -> bool {
+    name.contains("::")
 }
 
-/// Set source_path on a statement and all nested statements.
-fn set_stmt_source_path(stmt: &Stmt, source_path: PathBuf) -> Stmt {
-    let mut new_stmt = stmt.clone();
-    new_stmt.source_path = Some(source_path.clone());
+/// Set source_path on a statement and all nested statements, in place.
+fn set_stmt_source_path(stmt: &mut Stmt, source_path: &Arc<Path>) {
+    stmt.source_path = Some(Arc::clone(source_path));
 
     use crate::ast::StmtKind::*;
-    match &mut new_stmt.kind {
+    match &mut stmt.kind {
         Function(decl) => {
-            for s in &mut decl.body {
-                *s = set_stmt_source_path(s, source_path.clone());
+            for s in Arc::make_mut(&mut decl.body).iter_mut() {
+                set_stmt_source_path(s, source_path);
             }
         }
         Class(decl) => {
             for s in &mut decl.class_statements {
-                *s = set_stmt_source_path(s, source_path.clone());
+                set_stmt_source_path(s, source_path);
             }
             if let Some(ref mut ctor) = decl.constructor {
-                for s in &mut ctor.body {
-                    *s = set_stmt_source_path(s, source_path.clone());
+                for s in Arc::make_mut(&mut ctor.body).iter_mut() {
+                    set_stmt_source_path(s, source_path);
                 }
             }
             for method in &mut decl.methods {
-                for s in &mut method.body {
-                    *s = set_stmt_source_path(s, source_path.clone());
+                for s in Arc::make_mut(&mut method.body).iter_mut() {
+                    set_stmt_source_path(s, source_path);
                 }
             }
         }
         Block(stmts) => {
             for s in stmts {
-                *s = set_stmt_source_path(s, source_path.clone());
+                set_stmt_source_path(s, source_path);
             }
         }
         If {
@@ -598,37 +853,27 @@ fn set_stmt_source_path(stmt: &Stmt, source_path: PathBuf) -> Stmt {
             else_branch,
             ..
         } => {
-            **then_branch = set_stmt_source_path(then_branch, source_path.clone());
+            set_stmt_source_path(then_branch, source_path);
             if let Some(else_stmt) = else_branch {
-                *else_branch = Some(Box::new(set_stmt_source_path(
-                    else_stmt,
-                    source_path.clone(),
-                )));
+                set_stmt_source_path(else_stmt, source_path);
             }
         }
-        While { body, .. } => {
-            **body = set_stmt_source_path(body, source_path.clone());
-        }
-        For { body, .. } => {
-            **body = set_stmt_source_path(body, source_path.clone());
-        }
+        While { body, .. } | For { body, .. } => set_stmt_source_path(body, source_path),
         Try {
             try_block,
             catch_clauses,
             finally_block,
         } => {
-            **try_block = set_stmt_source_path(try_block, source_path.clone());
+            set_stmt_source_path(try_block, source_path);
             for clause in catch_clauses {
-                *clause.body = set_stmt_source_path(&clause.body, source_path.clone());
+                set_stmt_source_path(&mut clause.body, source_path);
             }
             if let Some(finally) = finally_block {
-                *finally_block = Some(Box::new(set_stmt_source_path(finally, source_path.clone())));
+                set_stmt_source_path(finally, source_path);
             }
         }
         _ => {}
     }
-
-    new_stmt
 }
 
 #[cfg(test)]
@@ -747,4 +992,10 @@ mod tests {
             .expect("bare-name import under base must resolve");
         assert!(resolved.ends_with("helper.sl"));
     }
+
+    #[test]
+    fn bare_file_name_resolves_from_current_directory() {
+        assert_eq!(non_empty_dir(Path::new("")), Path::new("."));
+        assert_eq!(non_empty_dir(Path::new("app")), Path::new("app"));
+    }
 }
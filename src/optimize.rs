@@ -0,0 +1,727 @@
+//! Compile-time constant folding.
+//!
+//! Runs over a program once it is parsed, resolved and type-checked, before
+//! the interpreter or the bytecode compiler sees it. Operators whose operands
+//! are all literals are replaced by their result (`60 * 60 * 24` becomes
+//! `86400`), and a `const` bound to a literal is substituted at every read
+//! that can only see that constant. Anything whose result depends on the
+//! runtime — an overflow, a division by zero, operands of different kinds
+//! the interpreter compares specially — is left for the runtime to evaluate,
+//! so folding never changes what a program does or which error it raises.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::ast::expr::{
+    Argument, BinaryOp, Expr, ExprKind, HashComprehension, InterpolatedPart, MatchPattern, UnaryOp,
+};
+use crate::ast::stmt::{ClassDecl, Parameter, Program, Stmt, StmtKind};
+
+/// Fold the constant expressions of a whole program.
+pub fn fold_constants(program: &mut Program) {
+    fold_stmts(&mut program.statements);
+}
+
+/// Fold a statement list on its own, as the body of a template code block.
+pub(crate) fn fold_stmts(stmts: &mut [Stmt]) {
+    Folder::default().stmts(stmts, true);
+}
+
+/// Fold a single expression, as a template output or condition.
+pub(crate) fn fold_expr(expr: &mut Expr) {
+    Folder::default().expr(expr);
+}
+
+struct Folder {
+    /// Names bound by enclosing blocks, functions, loops and patterns. A
+    /// constant maps to its literal value; any other binding to `None`, so
+    /// it hides a constant of the same name further out.
+    scopes: Vec<HashMap<String, Option<ExprKind>>>,
+}
+
+impl Default for Folder {
+    fn default() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+        }
+    }
+}
+
+impl Folder {
+    fn lookup(&self, name: &str) -> Option<&ExprKind> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .and_then(Option::as_ref)
+    }
+
+    fn bind(&mut self, name: &str, value: Option<ExprKind>) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), value);
+        }
+    }
+
+    fn scoped(&mut self, bound: Vec<String>, f: impl FnOnce(&mut Self)) {
+        self.scopes
+            .push(bound.into_iter().map(|name| (name, None)).collect());
+        f(self);
+        self.scopes.pop();
+    }
+
+    /// Fold a statement list. With `consts`, a `const` the list declares
+    /// exactly once is substituted into the statements after it.
+    fn stmts(&mut self, stmts: &mut [Stmt], consts: bool) {
+        // Declarations are visible to the whole list (functions and classes
+        // are hoisted), so they hide outer constants from the first statement.
+        let mut declared: HashMap<String, usize> = HashMap::new();
+        for stmt in stmts.iter() {
+            for name in declared_names(stmt) {
+                *declared.entry(name.to_string()).or_default() += 1;
+            }
+        }
+        for name in declared.keys() {
+            self.bind(name, None);
+        }
+        for stmt in stmts {
+            self.stmt(stmt);
+            if let StmtKind::Const {
+                name, initializer, ..
+            } = &stmt.kind
+            {
+                if consts && declared.get(name) == Some(&1) && is_literal(&initializer.kind) {
+                    self.bind(name, Some(initializer.kind.clone()));
+                }
+            }
+        }
+    }
+
+    fn stmt(&mut self, stmt: &mut Stmt) {
+        match &mut stmt.kind {
+            StmtKind::Expression(expr) | StmtKind::Throw(expr) | StmtKind::Yield(expr) => {
+                self.expr(expr)
+            }
+            StmtKind::Let { initializer, .. } => {
+                if let Some(init) = initializer {
+                    self.expr(init);
+                }
+            }
+            StmtKind::Const { initializer, .. } | StmtKind::LetTuple { initializer, .. } => {
+                self.expr(initializer)
+            }
+            StmtKind::Block(stmts) => self.scoped(Vec::new(), |f| f.stmts(stmts, true)),
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expr(condition);
+                self.stmt(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.stmt(else_branch);
+                }
+            }
+            StmtKind::While { condition, body } => {
+                self.expr(condition);
+                self.stmt(body);
+            }
+            StmtKind::For {
+                variable,
+                index_variable,
+                destructure,
+                iterable,
+                body,
+            } => {
+                self.expr(iterable);
+                let mut bound = vec![variable.clone()];
+                bound.extend(index_variable.clone());
+                bound.extend(destructure.iter().flatten().cloned());
+                self.scoped(bound, |f| f.stmt(body));
+            }
+            StmtKind::Return(value) => {
+                if let Some(value) = value {
+                    self.expr(value);
+                }
+            }
+            StmtKind::Break
+            | StmtKind::Import(_)
+            | StmtKind::Interface(_)
+            | StmtKind::TypeAlias(_) => {}
+            StmtKind::Try {
+                try_block,
+                catch_clauses,
+                finally_block,
+            } => {
+                self.stmt(try_block);
+                for clause in catch_clauses {
+                    let bound = clause.var_name.iter().cloned().collect();
+                    self.scoped(bound, |f| f.stmt(&mut clause.body));
+                }
+                if let Some(finally_block) = finally_block {
+                    self.stmt(finally_block);
+                }
+            }
+            StmtKind::Function(decl) => self.function(
+                &mut decl.params,
+                Arc::make_mut(&mut decl.body).as_mut_slice(),
+            ),
+            StmtKind::Class(decl) => self.class(decl),
+            StmtKind::Enum(decl) => {
+                for method in &mut decl.methods {
+                    self.function(
+                        &mut method.params,
+                        Arc::make_mut(&mut method.body).as_mut_slice(),
+                    );
+                }
+            }
+            StmtKind::Struct(decl) => {
+                for field in &mut decl.fields {
+                    if let Some(default) = &mut field.default_value {
+                        self.expr(default);
+                    }
+                }
+            }
+            StmtKind::Export(inner) => self.stmt(inner),
+        }
+    }
+
+    fn function(&mut self, params: &mut [Parameter], body: &mut [Stmt]) {
+        for param in params.iter_mut() {
+            if let Some(default) = &mut param.default_value {
+                self.expr(default);
+            }
+        }
+        let bound = params.iter().map(|p| p.name.clone()).collect();
+        self.scoped(bound, |f| f.stmts(body, true));
+    }
+
+    fn class(&mut self, decl: &mut ClassDecl) {
+        // Inside a method, a bare name may be another method of the class or
+        // one of its class-level declarations (class constants among them).
+        let mut bound: Vec<String> = decl.methods.iter().map(|m| m.name.clone()).collect();
+        bound.extend(
+            decl.class_statements
+                .iter()
+                .flat_map(declared_names)
+                .map(str::to_string),
+        );
+        self.scoped(bound, |f| {
+            for field in &mut decl.fields {
+                if let Some(init) = &mut field.initializer {
+                    f.expr(init);
+                }
+            }
+            for method in &mut decl.methods {
+                f.function(
+                    &mut method.params,
+                    Arc::make_mut(&mut method.body).as_mut_slice(),
+                );
+            }
+            if let Some(ctor) = &mut decl.constructor {
+                f.function(
+                    &mut ctor.params,
+                    Arc::make_mut(&mut ctor.body).as_mut_slice(),
+                );
+            }
+            if let Some(block) = &mut decl.static_block {
+                f.scoped(Vec::new(), |f| f.stmts(block, true));
+            }
+            f.stmts(&mut decl.class_statements, false);
+            for nested in &mut decl.nested_classes {
+                f.class(nested);
+            }
+        });
+    }
+
+    fn args(&mut self, args: &mut [Argument]) {
+        for arg in args {
+            match arg {
+                Argument::Positional(expr) | Argument::Block(expr) | Argument::Spread(expr) => {
+                    self.expr(expr)
+                }
+                Argument::Named(named) => self.expr(&mut named.value),
+            }
+        }
+    }
+
+    /// An assignment target: a plain variable is written, not read, so it
+    /// keeps its name.
+    fn target(&mut self, target: &mut Expr) {
+        if !matches!(target.kind, ExprKind::Variable(_, _)) {
+            self.expr(target);
+        }
+    }
+
+    fn expr(&mut self, expr: &mut Expr) {
+        match &mut expr.kind {
+            ExprKind::Variable(name, _) => {
+                if let Some(value) = self.lookup(name) {
+                    expr.kind = value.clone();
+                }
+            }
+            ExprKind::InterpolatedString(parts) => {
+                for part in parts {
+                    if let InterpolatedPart::Expression(expr) = part {
+                        self.expr(expr);
+                    }
+                }
+            }
+            ExprKind::Binary {
+                left,
+                operator: op,
+                right,
+            } => {
+                self.expr(left);
+                self.expr(right);
+                if let Some(folded) = fold_binary(&left.kind, *op, &right.kind) {
+                    expr.kind = folded;
+                }
+            }
+            ExprKind::Unary {
+                operator: op,
+                operand,
+            } => {
+                self.expr(operand);
+                if let Some(folded) = fold_unary(*op, &operand.kind) {
+                    expr.kind = folded;
+                }
+            }
+            ExprKind::Grouping(inner) => {
+                self.expr(inner);
+                if is_literal(&inner.kind) {
+                    expr.kind = take(inner);
+                }
+            }
+            ExprKind::LogicalAnd { left, right } => {
+                self.expr(left);
+                self.expr(right);
+                // `&&` yields its left operand when that is falsy.
+                match left.kind {
+                    ExprKind::BoolLiteral(true) => expr.kind = take(right),
+                    ExprKind::BoolLiteral(false) | ExprKind::Null => expr.kind = take(left),
+                    _ => {}
+                }
+            }
+            ExprKind::LogicalOr { left, right } => {
+                self.expr(left);
+                self.expr(right);
+                match left.kind {
+                    ExprKind::BoolLiteral(true) => expr.kind = take(left),
+                    ExprKind::BoolLiteral(false) | ExprKind::Null => expr.kind = take(right),
+                    _ => {}
+                }
+            }
+            ExprKind::NullishCoalescing { left, right } => {
+                self.expr(left);
+                self.expr(right);
+                if matches!(left.kind, ExprKind::Null) {
+                    expr.kind = take(right);
+                } else if is_literal(&left.kind) {
+                    expr.kind = take(left);
+                }
+            }
+            ExprKind::Pipeline { left, right } => {
+                self.expr(left);
+                self.expr(right);
+            }
+            ExprKind::Member { object: inner, .. }
+            | ExprKind::SafeMember { object: inner, .. }
+            | ExprKind::QualifiedName {
+                qualifier: inner, ..
+            }
+            | ExprKind::Spread(inner)
+            | ExprKind::Throw(inner)
+            | ExprKind::Await(inner) => self.expr(inner),
+            ExprKind::PostfixIncrement(target) | ExprKind::PostfixDecrement(target) => {
+                self.target(target)
+            }
+            ExprKind::Call { callee, arguments } => {
+                self.expr(callee);
+                self.args(arguments);
+            }
+            ExprKind::New {
+                class_expr,
+                arguments,
+            } => {
+                self.expr(class_expr);
+                self.args(arguments);
+            }
+            ExprKind::Index { object, index } => {
+                self.expr(object);
+                self.expr(index);
+            }
+            ExprKind::Array(items) | ExprKind::Tuple(items) => {
+                for item in items {
+                    self.expr(item);
+                }
+            }
+            ExprKind::Hash(pairs) => {
+                for (key, value) in pairs {
+                    self.expr(key);
+                    self.expr(value);
+                }
+            }
+            ExprKind::Block(stmts) => self.scoped(Vec::new(), |f| f.stmts(stmts, true)),
+            ExprKind::Assign { target, value } | ExprKind::CompoundAssign { target, value, .. } => {
+                self.target(target);
+                self.expr(value);
+            }
+            ExprKind::Lambda { params, body, .. } => {
+                self.function(params, Arc::make_mut(body).as_mut_slice())
+            }
+            ExprKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expr(condition);
+                self.expr(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.expr(else_branch);
+                }
+                match (&condition.kind, else_branch) {
+                    (ExprKind::BoolLiteral(true), _) => expr.kind = take(then_branch),
+                    (ExprKind::BoolLiteral(false), Some(else_branch)) => {
+                        expr.kind = take(else_branch)
+                    }
+                    _ => {}
+                }
+            }
+            ExprKind::Match { expression, arms } => {
+                self.expr(expression);
+                for arm in arms {
+                    let mut bound = Vec::new();
+                    pattern_bindings(&arm.pattern, &mut bound);
+                    self.scoped(bound, |f| {
+                        if let Some(guard) = &mut arm.guard {
+                            f.expr(guard);
+                        }
+                        f.expr(&mut arm.body);
+                    });
+                }
+            }
+            ExprKind::ListComprehension {
+                element,
+                variable,
+                iterable,
+                condition,
+            } => {
+                self.expr(iterable);
+                self.scoped(vec![variable.clone()], |f| {
+                    f.expr(element);
+                    if let Some(condition) = condition {
+                        f.expr(condition);
+                    }
+                });
+            }
+            ExprKind::HashComprehension(comp) => {
+                let HashComprehension {
+                    key,
+                    value,
+                    variable,
+                    iterable,
+                    condition,
+                } = &mut **comp;
+                self.expr(iterable);
+                self.scoped(vec![variable.clone()], |f| {
+                    f.expr(key);
+                    f.expr(value);
+                    if let Some(condition) = condition {
+                        f.expr(condition);
+                    }
+                });
+            }
+            ExprKind::Rescue { expr, fallback } => {
+                self.expr(expr);
+                self.expr(fallback);
+            }
+            // SDQL interpolations are kept as source text and evaluated in
+            // the caller's scope, which still holds the constant.
+            ExprKind::IntLiteral(_)
+            | ExprKind::FloatLiteral(_)
+            | ExprKind::DecimalLiteral(_)
+            | ExprKind::StringLiteral(_)
+            | ExprKind::CommandSubstitution(_)
+            | ExprKind::SdqlBlock { .. }
+            | ExprKind::BoolLiteral(_)
+            | ExprKind::Symbol(_)
+            | ExprKind::Null
+            | ExprKind::This
+            | ExprKind::Super => {}
+        }
+    }
+}
+
+/// Move an operand's expression out, leaving `null` behind.
+fn take(expr: &mut Expr) -> ExprKind {
+    std::mem::replace(&mut expr.kind, ExprKind::Null)
+}
+
+/// The name a statement declares in its scope, if any.
+fn declared_names(stmt: &Stmt) -> Vec<&str> {
+    match &stmt.kind {
+        StmtKind::Let { name, .. } | StmtKind::Const { name, .. } => vec![name],
+        StmtKind::LetTuple { names, .. } => names.iter().map(String::as_str).collect(),
+        StmtKind::Function(decl) => vec![&decl.name],
+        StmtKind::Class(decl) => vec![&decl.name],
+        StmtKind::Enum(decl) => vec![&decl.name],
+        StmtKind::Struct(decl) => vec![&decl.name],
+        StmtKind::Export(inner) => declared_names(inner),
+        _ => Vec::new(),
+    }
+}
+
+fn pattern_bindings(pattern: &MatchPattern, bound: &mut Vec<String>) {
+    match pattern {
+        MatchPattern::Wildcard | MatchPattern::Literal(_) => {}
+        MatchPattern::Variable(name) | MatchPattern::Typed { name, .. } => bound.push(name.clone()),
+        MatchPattern::Array { elements, rest } => {
+            for element in elements {
+                pattern_bindings(element, bound);
+            }
+            bound.extend(rest.clone());
+        }
+        MatchPattern::Tuple(elements) => {
+            for element in elements {
+                pattern_bindings(element, bound);
+            }
+        }
+        MatchPattern::Hash { fields, rest } => {
+            for (_, field) in fields {
+                pattern_bindings(field, bound);
+            }
+            bound.extend(rest.clone());
+        }
+        MatchPattern::Destructuring { fields, .. } => {
+            for (_, field) in fields {
+                pattern_bindings(field, bound);
+            }
+        }
+        MatchPattern::EnumVariant { bindings, .. } => {
+            for binding in bindings {
+                pattern_bindings(binding, bound);
+            }
+        }
+        MatchPattern::And(patterns) | MatchPattern::Or(patterns) => {
+            for pattern in patterns {
+                pattern_bindings(pattern, bound);
+            }
+        }
+    }
+}
+
+fn is_literal(kind: &ExprKind) -> bool {
+    matches!(
+        kind,
+        ExprKind::IntLiteral(_)
+            | ExprKind::FloatLiteral(_)
+            | ExprKind::DecimalLiteral(_)
+            | ExprKind::StringLiteral(_)
+            | ExprKind::BoolLiteral(_)
+            | ExprKind::Symbol(_)
+            | ExprKind::Null
+    )
+}
+
+/// A numeric literal as a float, for mixed Int/Float arithmetic and
+/// comparisons (which the interpreter does in `f64`).
+fn as_float(kind: &ExprKind) -> Option<f64> {
+    match kind {
+        ExprKind::IntLiteral(n) => Some(*n as f64),
+        ExprKind::FloatLiteral(f) => Some(*f),
+        _ => None,
+    }
+}
+
+fn fold_binary(left: &ExprKind, op: BinaryOp, right: &ExprKind) -> Option<ExprKind> {
+    use ExprKind::{BoolLiteral, FloatLiteral, IntLiteral, Null, StringLiteral};
+
+    match (left, right) {
+        (IntLiteral(a), IntLiteral(b)) => {
+            let (a, b) = (*a, *b);
+            let int = |n: Option<i64>| n.map(IntLiteral);
+            return match op {
+                BinaryOp::Add => int(a.checked_add(b)),
+                BinaryOp::Subtract => int(a.checked_sub(b)),
+                BinaryOp::Multiply => int(a.checked_mul(b)),
+                BinaryOp::Divide => int(a.checked_div(b)),
+                BinaryOp::Modulo => int(a.checked_rem(b)),
+                BinaryOp::Equal => Some(BoolLiteral(a == b)),
+                BinaryOp::NotEqual => Some(BoolLiteral(a != b)),
+                _ => compare(op, a as f64, b as f64),
+            };
+        }
+        (StringLiteral(a), StringLiteral(b)) => {
+            return match op {
+                BinaryOp::Add => Some(StringLiteral(format!("{}{}", a, b))),
+                BinaryOp::Equal => Some(BoolLiteral(a == b)),
+                BinaryOp::NotEqual => Some(BoolLiteral(a != b)),
+                _ => compare(op, a, b),
+            };
+        }
+        (BoolLiteral(a), BoolLiteral(b)) => {
+            return match op {
+                BinaryOp::Equal => Some(BoolLiteral(a == b)),
+                BinaryOp::NotEqual => Some(BoolLiteral(a != b)),
+                _ => None,
+            };
+        }
+        (Null, Null) => {
+            return match op {
+                BinaryOp::Equal => Some(BoolLiteral(true)),
+                BinaryOp::NotEqual => Some(BoolLiteral(false)),
+                _ => None,
+            };
+        }
+        _ => {}
+    }
+
+    // Float with Float, or mixed with Int.
+    let (a, b) = (as_float(left)?, as_float(right)?);
+    match op {
+        BinaryOp::Add => Some(FloatLiteral(a + b)),
+        BinaryOp::Subtract => Some(FloatLiteral(a - b)),
+        BinaryOp::Multiply => Some(FloatLiteral(a * b)),
+        BinaryOp::Divide if b != 0.0 => Some(FloatLiteral(a / b)),
+        BinaryOp::Modulo if b != 0.0 => Some(FloatLiteral(a % b)),
+        // `==` between an Int and a Float goes through the runtime's own
+        // equality; only Float with Float is folded.
+        BinaryOp::Equal | BinaryOp::NotEqual => match (left, right) {
+            (FloatLiteral(_), FloatLiteral(_)) => {
+                Some(BoolLiteral((a == b) == (op == BinaryOp::Equal)))
+            }
+            _ => None,
+        },
+        _ => compare(op, a, b),
+    }
+}
+
+fn compare<T: PartialOrd>(op: BinaryOp, a: T, b: T) -> Option<ExprKind> {
+    let result = match op {
+        BinaryOp::Less => a < b,
+        BinaryOp::LessEqual => a <= b,
+        BinaryOp::Greater => a > b,
+        BinaryOp::GreaterEqual => a >= b,
+        _ => return None,
+    };
+    Some(ExprKind::BoolLiteral(result))
+}
+
+fn fold_unary(op: UnaryOp, operand: &ExprKind) -> Option<ExprKind> {
+    match (op, operand) {
+        (UnaryOp::Negate, ExprKind::IntLiteral(n)) => n.checked_neg().map(ExprKind::IntLiteral),
+        (UnaryOp::Negate, ExprKind::FloatLiteral(f)) => Some(ExprKind::FloatLiteral(-f)),
+        (UnaryOp::Not, ExprKind::BoolLiteral(b)) => Some(ExprKind::BoolLiteral(!b)),
+        (UnaryOp::Not, ExprKind::Null) => Some(ExprKind::BoolLiteral(true)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Scanner;
+    use crate::parser::Parser;
+
+    fn folded(source: &str) -> Vec<Stmt> {
+        let tokens = Scanner::new(source).scan_tokens().unwrap();
+        let mut program = Parser::new(tokens).parse().unwrap();
+        fold_constants(&mut program);
+        program.statements
+    }
+
+    fn value(stmt: &Stmt) -> &ExprKind {
+        match &stmt.kind {
+            StmtKind::Let {
+                initializer: Some(init),
+                ..
+            } => &init.kind,
+            StmtKind::Expression(expr) => &expr.kind,
+            StmtKind::Return(Some(expr)) => &expr.kind,
+            other => panic!("expected a value, got {:?}", other),
+        }
+    }
+
+    fn function_body(stmt: &Stmt) -> &[Stmt] {
+        match &stmt.kind {
+            StmtKind::Function(decl) => &decl.body,
+            other => panic!("expected a function, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn folds_literal_arithmetic() {
+        let stmts = folded(
+            "let a = 60 * 60 * 24\nlet b = (1 + 2) * 3.0\nlet c = \"a\" + \"b\"\nlet d = -(2 + 3)\nlet e = 7 / 2",
+        );
+        assert_eq!(value(&stmts[0]), &ExprKind::IntLiteral(86400));
+        assert_eq!(value(&stmts[1]), &ExprKind::FloatLiteral(9.0));
+        assert_eq!(value(&stmts[2]), &ExprKind::StringLiteral("ab".into()));
+        assert_eq!(value(&stmts[3]), &ExprKind::IntLiteral(-5));
+        assert_eq!(value(&stmts[4]), &ExprKind::IntLiteral(3));
+    }
+
+    #[test]
+    fn folds_comparisons_and_logic() {
+        let stmts = folded(
+            "let a = 2 < 3.5\nlet b = !(1 == 1)\nlet c = false || \"x\"\nlet d = true && 1 > 2",
+        );
+        assert_eq!(value(&stmts[0]), &ExprKind::BoolLiteral(true));
+        assert_eq!(value(&stmts[1]), &ExprKind::BoolLiteral(false));
+        assert_eq!(value(&stmts[2]), &ExprKind::StringLiteral("x".into()));
+        assert_eq!(value(&stmts[3]), &ExprKind::BoolLiteral(false));
+    }
+
+    #[test]
+    fn leaves_runtime_errors_to_the_runtime() {
+        let stmts = folded(
+            "let a = 1 / 0\nlet b = 9223372036854775807 + 1\nlet c = 1.0 % 0\nlet d = 1 == 1.0\nlet e = \"n\" + 1",
+        );
+        for stmt in &stmts {
+            assert!(
+                matches!(value(stmt), ExprKind::Binary { .. }),
+                "{:?}",
+                value(stmt)
+            );
+        }
+    }
+
+    #[test]
+    fn substitutes_constants() {
+        let stmts = folded(
+            "const HOUR = 60 * 60\nconst DAY = HOUR * 24\nfn ttl(days) { return days * DAY }\nlet week = 7 * DAY",
+        );
+        assert!(matches!(
+            &stmts[1].kind,
+            StmtKind::Const { initializer, .. } if initializer.kind == ExprKind::IntLiteral(86400)
+        ));
+        match value(&function_body(&stmts[2])[0]) {
+            ExprKind::Binary { right, .. } => assert_eq!(right.kind, ExprKind::IntLiteral(86400)),
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(value(&stmts[3]), &ExprKind::IntLiteral(604800));
+    }
+
+    #[test]
+    fn shadowed_constants_are_left_alone() {
+        let stmts = folded(
+            "const N = 1\nfn a(N) { return N }\nfn b() { let N = 2; return N }\nlet c = [N for N in [5]]\nlet d = N",
+        );
+        assert_eq!(
+            value(&function_body(&stmts[1])[0]),
+            &ExprKind::variable("N")
+        );
+        assert_eq!(
+            value(&function_body(&stmts[2])[1]),
+            &ExprKind::variable("N")
+        );
+        assert_eq!(value(&stmts[4]), &ExprKind::IntLiteral(1));
+    }
+
+    #[test]
+    fn constants_declared_twice_are_not_substituted() {
+        let stmts = folded("const N = 1\nlet a = N\nlet N = 2\nlet b = N");
+        assert_eq!(value(&stmts[1]), &ExprKind::variable("N"));
+        assert_eq!(value(&stmts[3]), &ExprKind::variable("N"));
+    }
+}
@@ -5,6 +5,8 @@ use crate::error::ParserError;
 use crate::lexer::{Token, TokenKind};
 use crate::metrics::Metrics;
 use crate::span::Span;
+
+use super::platform::Platform;
 use std::time::Instant;
 
 pub type ParseResult<T> = Result<T, ParserError>;
@@ -25,6 +27,9 @@ pub struct Parser {
     /// enclosing `try`/`begin` body can treat it as a block-form catch clause rather
     /// than a postfix `rescue` modifier. Set only while parsing an end-form try body.
     pub(crate) in_try_body: bool,
+    /// The platform `@cfg(...)` / `if_platform(...)` guards are resolved
+    /// against once the program is parsed; `None` keeps every branch.
+    pub(crate) platform: Option<Platform>,
 }
 
 impl Parser {
@@ -35,9 +40,23 @@ impl Parser {
             no_trailing_brace: false,
             no_trailing_do: false,
             in_try_body: false,
+            platform: Some(Platform::current()),
         }
     }
 
+    /// Resolve platform guards against `platform` instead of the running one.
+    pub fn with_platform(mut self, platform: Platform) -> Self {
+        self.platform = Some(platform);
+        self
+    }
+
+    /// Keep the code of every platform, for tools that work on the source as
+    /// written (formatter, linter, LSP) rather than run it.
+    pub fn keeping_platform_code(mut self) -> Self {
+        self.platform = None;
+        self
+    }
+
     /// Parse an expression with trailing brace blocks suppressed.
     /// Used for if/while/for conditions where `{` starts the statement body.
     pub(crate) fn expression_no_trailing_brace(&mut self) -> ParseResult<Expr> {
@@ -56,11 +75,17 @@ impl Parser {
         while !self.is_at_end() {
             statements.push(self.declaration()?);
         }
+        if let Some(platform) = &self.platform {
+            super::platform::resolve(&mut statements, platform)?;
+        }
+
+        let mut program = Program::new(statements);
+        program.number_nodes();
 
         if let Some(start) = start {
             Metrics::global().record_parsing(start.elapsed());
         }
-        Ok(Program::new(statements))
+        Ok(program)
     }
 
     // ===== Token manipulation =====
@@ -1,34 +1,184 @@
 //! Declaration parsing: classes, functions, interfaces, variables.
 
+use std::sync::Arc;
+
 use crate::ast::*;
 use crate::error::ParserError;
 use crate::lexer::TokenKind;
+use crate::span::Span;
 
 use super::core::{ParseResult, Parser};
 
 impl Parser {
     pub(crate) fn declaration(&mut self) -> ParseResult<Stmt> {
-        if self.check(&TokenKind::Import) {
+        if self.at_decorators() {
+            self.decorated_declaration()
+        } else if self.check(&TokenKind::Import) {
             self.import_declaration()
         } else if self.check(&TokenKind::Export) {
             self.export_declaration()
-        } else if self.check(&TokenKind::Fn) {
+        } else if self.check(&TokenKind::Fn) || self.at_async_fn() {
             self.function_declaration()
         } else if self.check(&TokenKind::Class) {
             self.class_declaration()
         } else if self.check(&TokenKind::Enum) {
             self.enum_declaration()
+        } else if self.check(&TokenKind::Struct) {
+            self.struct_declaration()
         } else if self.check(&TokenKind::Interface) {
             self.interface_declaration()
         } else if self.check(&TokenKind::Let) {
             self.let_declaration()
         } else if self.check(&TokenKind::Const) {
             self.const_declaration()
+        } else if self.at_type_alias() {
+            self.type_alias_declaration()
         } else {
             self.statement()
         }
     }
 
+    /// Check if the cursor is on `type Name =`. `type` is only a keyword
+    /// there; everywhere else it's an identifier (the `type(x)` builtin).
+    pub(crate) fn at_type_alias(&self) -> bool {
+        matches!(&self.peek().kind, TokenKind::Identifier(name) if name == "type")
+            && matches!(&self.peek_nth(1).kind, TokenKind::Identifier(_))
+            && self.peek_nth(2).kind == TokenKind::Equal
+    }
+
+    /// `type User = { id: Int, name: String, email: String? }`
+    pub(crate) fn type_alias_declaration(&mut self) -> ParseResult<Stmt> {
+        let start_span = self.current_span();
+        self.advance(); // `type`
+        let name = self.expect_identifier()?;
+        self.expect(&TokenKind::Equal)?;
+        let type_annotation = self.parse_type()?;
+        let span = start_span.merge(&self.previous_span());
+        Ok(Stmt::new(
+            StmtKind::type_alias(TypeAliasDecl {
+                name,
+                type_annotation,
+                span,
+            }),
+            span,
+            None,
+        ))
+    }
+
+    /// Check if the cursor is on `async fn`. Like `move`, `async` is only a
+    /// keyword right before `fn` on the same line; anywhere else it's an
+    /// identifier.
+    pub(crate) fn at_async_fn(&self) -> bool {
+        matches!(&self.peek().kind, TokenKind::Identifier(name) if name == "async")
+            && self.peek_nth(1).kind == TokenKind::Fn
+            && self.peek_nth(1).span.line == self.peek().span.line
+    }
+
+    /// Consume a leading `async` (see [`Parser::at_async_fn`]).
+    fn match_async(&mut self) -> bool {
+        let is_async = self.at_async_fn();
+        if is_async {
+            self.advance();
+        }
+        is_async
+    }
+
+    /// Check if the cursor is on decorators: one or more `@name` / `@name(...)`
+    /// followed by the declaration they annotate. A bare `@name` is otherwise
+    /// an instance-variable read, so the declaration keyword must follow.
+    pub(crate) fn at_decorators(&self) -> bool {
+        let mut i = self.current;
+        let mut seen = false;
+        loop {
+            match self.tokens.get(i).map(|t| &t.kind) {
+                Some(TokenKind::Identifier(name)) if name.len() > 1 && name.starts_with('@') => {
+                    seen = true;
+                    i += 1;
+                    if matches!(
+                        self.tokens.get(i).map(|t| &t.kind),
+                        Some(TokenKind::LeftParen)
+                    ) {
+                        let mut depth = 0usize;
+                        loop {
+                            match self.tokens.get(i).map(|t| &t.kind) {
+                                Some(TokenKind::LeftParen) => depth += 1,
+                                Some(TokenKind::RightParen) => {
+                                    depth -= 1;
+                                    if depth == 0 {
+                                        break;
+                                    }
+                                }
+                                Some(TokenKind::Eof) | None => return false,
+                                _ => {}
+                            }
+                            i += 1;
+                        }
+                        i += 1;
+                    }
+                }
+                Some(TokenKind::Identifier(name)) if name == "async" => return seen,
+                Some(
+                    TokenKind::Fn
+                    | TokenKind::Class
+                    | TokenKind::Export
+                    | TokenKind::Public
+                    | TokenKind::Private
+                    | TokenKind::Protected
+                    | TokenKind::Static,
+                ) => return seen,
+                _ => return false,
+            }
+        }
+    }
+
+    /// Parse the decorators at the cursor (see [`Parser::at_decorators`]).
+    pub(crate) fn parse_decorators(&mut self) -> ParseResult<Vec<Decorator>> {
+        let mut decorators = Vec::new();
+        while let TokenKind::Identifier(name) = &self.peek().kind {
+            let Some(name) = name.strip_prefix('@') else {
+                break;
+            };
+            let name = name.to_string();
+            let start_span = self.current_span();
+            self.advance();
+            let arguments = if self.match_token(&TokenKind::LeftParen) {
+                let arguments = self.parse_arguments()?;
+                self.expect(&TokenKind::RightParen)?;
+                arguments
+            } else {
+                Vec::new()
+            };
+            decorators.push(Decorator {
+                name,
+                arguments,
+                span: start_span.merge(&self.previous_span()),
+            });
+        }
+        Ok(decorators)
+    }
+
+    /// Parse a decorated function or class declaration (optionally exported):
+    ///   @deprecated("use fetch")
+    ///   fn get(key) { ... }
+    fn decorated_declaration(&mut self) -> ParseResult<Stmt> {
+        let decorators = self.parse_decorators()?;
+        let mut stmt = if self.check(&TokenKind::Export) {
+            self.export_declaration()?
+        } else if self.check(&TokenKind::Fn) || self.at_async_fn() {
+            self.function_declaration()?
+        } else if self.check(&TokenKind::Class) {
+            self.class_declaration()?
+        } else {
+            return Err(ParserError::general(
+                "decorators must be followed by a function or class declaration",
+                self.current_span(),
+            ));
+        };
+
+        attach_decorators(&mut stmt, decorators)?;
+        Ok(stmt)
+    }
+
     /// Parse an import declaration.
     /// Syntax:
     ///   import "path";                     -- import all exports
@@ -127,7 +277,7 @@ impl Parser {
         self.expect(&TokenKind::Export)?;
 
         // Parse the declaration being exported
-        let inner = if self.check(&TokenKind::Fn) {
+        let inner = if self.check(&TokenKind::Fn) || self.at_async_fn() {
             self.function_declaration()?
         } else if self.check(&TokenKind::Class) {
             self.class_declaration()?
@@ -135,9 +285,11 @@ impl Parser {
             self.interface_declaration()?
         } else if self.check(&TokenKind::Let) {
             self.let_declaration()?
+        } else if self.at_type_alias() {
+            self.type_alias_declaration()?
         } else {
             return Err(ParserError::general(
-                "Expected 'fn', 'class', 'interface', or 'let' after 'export'",
+                "Expected 'fn', 'class', 'interface', 'type', or 'let' after 'export'",
                 self.current_span(),
             ));
         };
@@ -148,6 +300,7 @@ impl Parser {
 
     pub(crate) fn function_declaration(&mut self) -> ParseResult<Stmt> {
         let start_span = self.current_span();
+        let is_async = self.match_async();
         self.expect(&TokenKind::Fn)?;
 
         let name = self.expect_identifier()?;
@@ -161,13 +314,18 @@ impl Parser {
 
         let body = self.parse_function_body()?;
         let span = start_span.merge(&self.previous_span());
+        if is_async && crate::ast::stmt::body_yields(&body) {
+            return Err(ParserError::general("async functions cannot yield", span));
+        }
 
         Ok(Stmt::new(
             StmtKind::function(FunctionDecl {
                 name,
                 params,
                 return_type,
-                body,
+                body: Arc::new(body.into()),
+                decorators: Vec::new(),
+                is_async,
                 span,
             }),
             span,
@@ -212,6 +370,7 @@ impl Parser {
                     static_block: None,
                     class_statements: Vec::new(),
                     nested_classes: Vec::new(),
+                    decorators: Vec::new(),
                     span,
                 }),
                 span,
@@ -255,7 +414,22 @@ impl Parser {
                 }
             }
 
+            let decorators = if self.at_decorators() {
+                self.parse_decorators()?
+            } else {
+                Vec::new()
+            };
             let (visibility, is_static, is_const) = self.parse_modifiers();
+            if !decorators.is_empty()
+                && !self.check(&TokenKind::Fn)
+                && !self.at_async_fn()
+                && !self.check(&TokenKind::Class)
+            {
+                return Err(ParserError::general(
+                    "decorators must be followed by a method or class declaration",
+                    self.current_span(),
+                ));
+            }
 
             if self.check(&TokenKind::New) {
                 if constructor.is_some() {
@@ -268,11 +442,14 @@ impl Parser {
             } else if self.check(&TokenKind::Class) {
                 // Handle nested class declaration
                 let nested_class = self.class_declaration()?;
-                if let StmtKind::Class(nested_class_decl) = nested_class.kind {
+                if let StmtKind::Class(mut nested_class_decl) = nested_class.kind {
+                    nested_class_decl.decorators = decorators;
                     nested_classes.push(*nested_class_decl);
                 }
-            } else if self.check(&TokenKind::Fn) {
-                methods.push(self.parse_method(visibility, is_static)?);
+            } else if self.check(&TokenKind::Fn) || self.at_async_fn() {
+                let mut method = self.parse_method(visibility, is_static)?;
+                method.decorators = decorators;
+                methods.push(method);
             } else if self.is_class_level_statement() {
                 // Parse class-level statements like validates(...), before_save(...)
                 class_statements.push(self.parse_class_level_statement()?);
@@ -299,6 +476,7 @@ impl Parser {
                 static_block,
                 class_statements,
                 nested_classes,
+                decorators: Vec::new(),
                 span,
             }),
             span,
@@ -397,6 +575,66 @@ impl Parser {
         ))
     }
 
+    /// Parse a struct declaration:
+    ///   struct Money { amount: Int, currency: String = "EUR" }
+    /// Fields are comma/newline separated; the type and the default are both
+    /// optional. Closes with `}` or `end`.
+    pub(crate) fn struct_declaration(&mut self) -> ParseResult<Stmt> {
+        let start_span = self.current_span();
+        self.expect(&TokenKind::Struct)?;
+        let name = self.expect_identifier()?;
+
+        // Allow optional opening brace (Ruby `end` form also supported).
+        self.match_token(&TokenKind::LeftBrace);
+
+        let mut fields: Vec<StructFieldDecl> = Vec::new();
+        while !self.check(&TokenKind::RightBrace)
+            && !self.check(&TokenKind::End)
+            && !self.is_at_end()
+        {
+            if self.match_token(&TokenKind::Comma) || self.match_token(&TokenKind::Semicolon) {
+                continue;
+            }
+            let field_span = self.current_span();
+            let field_name = self.expect_identifier()?;
+            if fields.iter().any(|f| f.name == field_name) {
+                return Err(ParserError::general(
+                    format!("duplicate field '{}' in struct {}", field_name, name),
+                    field_span,
+                ));
+            }
+            let type_annotation = if self.match_token(&TokenKind::Colon) {
+                Some(self.parse_type()?)
+            } else {
+                None
+            };
+            let default_value = if self.match_token(&TokenKind::Equal) {
+                Some(self.expression()?)
+            } else {
+                None
+            };
+            fields.push(StructFieldDecl {
+                name: field_name,
+                type_annotation,
+                default_value,
+                span: field_span.merge(&self.previous_span()),
+            });
+        }
+
+        if self.match_token(&TokenKind::End) {
+            // Struct body ended with 'end'.
+        } else {
+            self.expect(&TokenKind::RightBrace)?;
+        }
+        let span = start_span.merge(&self.previous_span());
+
+        Ok(Stmt::new(
+            StmtKind::struct_decl(StructDecl { name, fields, span }),
+            span,
+            None,
+        ))
+    }
+
     /// Check if the current token starts a class-level statement (e.g., validates(...))
     fn is_class_level_statement(&self) -> bool {
         // Check for identifier followed by left paren
@@ -426,6 +664,7 @@ impl Parser {
                 "timeseries",
                 "columnar",
                 "column",
+                "attribute",
                 "index",
                 "vector_index",
                 "fulltext_index",
@@ -507,7 +746,7 @@ impl Parser {
                     arguments = self.parse_command_arguments()?;
                 }
                 let span = start_span.merge(&self.previous_span());
-                let callee = Expr::new(ExprKind::Variable(callee_name), start_span);
+                let callee = Expr::new(ExprKind::variable(callee_name), start_span);
                 let call = Expr::new(
                     ExprKind::Call {
                         callee: Box::new(callee),
@@ -533,7 +772,7 @@ impl Parser {
                 let span = start_span.merge(&self.previous_span());
                 self.match_token(&TokenKind::Semicolon);
                 // Wrap as a zero-arg Call so the class executor can process it
-                let callee = Expr::new(ExprKind::Variable(name), start_span);
+                let callee = Expr::new(ExprKind::variable(name), start_span);
                 let call = Expr::new(
                     ExprKind::Call {
                         callee: Box::new(callee),
@@ -565,7 +804,7 @@ impl Parser {
                         arguments.push(crate::ast::expr::Argument::Block(block));
                     }
                     let span = start_span.merge(&self.previous_span());
-                    let callee = Expr::new(ExprKind::Variable(callee_name), start_span);
+                    let callee = Expr::new(ExprKind::variable(callee_name), start_span);
                     let call = Expr::new(
                         ExprKind::Call {
                             callee: Box::new(callee),
@@ -617,16 +856,23 @@ impl Parser {
         {
             // Allow visibility modifiers; an explicit `static` is redundant
             // here but accepted silently — every method is static anyway.
+            let decorators = if self.at_decorators() {
+                self.parse_decorators()?
+            } else {
+                Vec::new()
+            };
             let (visibility, _is_static, _is_const) = self.parse_modifiers();
 
-            if !self.check(&TokenKind::Fn) {
+            if !self.check(&TokenKind::Fn) && !self.at_async_fn() {
                 return Err(ParserError::general(
                     "`class << self` blocks may only contain method declarations",
                     self.current_span(),
                 ));
             }
 
-            methods.push(self.parse_method(visibility, true)?);
+            let mut method = self.parse_method(visibility, true)?;
+            method.decorators = decorators;
+            methods.push(method);
         }
 
         if !self.match_token(&TokenKind::End) {
@@ -667,7 +913,11 @@ impl Parser {
         let body = self.parse_constructor_body()?;
         let span = start_span.merge(&self.previous_span());
 
-        Ok(ConstructorDecl { params, body, span })
+        Ok(ConstructorDecl {
+            params,
+            body: Arc::new(body.into()),
+            span,
+        })
     }
 
     fn parse_constructor_body(&mut self) -> ParseResult<Vec<Stmt>> {
@@ -702,6 +952,7 @@ impl Parser {
 
     fn parse_method(&mut self, visibility: Visibility, is_static: bool) -> ParseResult<MethodDecl> {
         let start_span = self.current_span();
+        let is_async = self.match_async();
         self.expect(&TokenKind::Fn)?;
 
         // Ruby-style `def self.foo(...)`: the `self.` prefix marks the method
@@ -729,6 +980,9 @@ impl Parser {
 
         let body = self.parse_function_body()?;
         let span = start_span.merge(&self.previous_span());
+        if is_async && crate::ast::stmt::body_yields(&body) {
+            return Err(ParserError::general("async functions cannot yield", span));
+        }
 
         Ok(MethodDecl {
             visibility,
@@ -736,7 +990,9 @@ impl Parser {
             name,
             params,
             return_type,
-            body,
+            body: Arc::new(body.into()),
+            decorators: Vec::new(),
+            is_async,
             span,
         })
     }
@@ -842,10 +1098,14 @@ impl Parser {
         let start_span = self.current_span();
         self.expect(&TokenKind::Let)?;
 
+        if self.check(&TokenKind::LeftParen) {
+            return self.let_tuple_declaration(start_span);
+        }
+
         let name = self.expect_identifier()?;
 
         let type_annotation = if self.match_token(&TokenKind::Colon) {
-            Some(self.parse_type()?)
+            Some(Box::new(self.parse_type()?))
         } else {
             None
         };
@@ -864,12 +1124,58 @@ impl Parser {
                 name,
                 type_annotation,
                 initializer,
+                slot: Default::default(),
+            },
+            span,
+            None,
+        ))
+    }
+
+    /// `let (a, b): (A, B) = expr` — the `let` is already consumed.
+    fn let_tuple_declaration(&mut self, start_span: Span) -> ParseResult<Stmt> {
+        let names = self.tuple_names()?;
+
+        let type_annotation = if self.match_token(&TokenKind::Colon) {
+            Some(Box::new(self.parse_type()?))
+        } else {
+            None
+        };
+
+        self.expect(&TokenKind::Equal)?;
+        let initializer = self.expression()?;
+
+        self.match_token(&TokenKind::Semicolon);
+        let span = start_span.merge(&self.previous_span());
+
+        Ok(Stmt::new(
+            StmtKind::LetTuple {
+                names,
+                type_annotation,
+                initializer,
             },
             span,
             None,
         ))
     }
 
+    /// A parenthesized list of at least two binding names: `(a, b)`.
+    pub(crate) fn tuple_names(&mut self) -> ParseResult<Vec<String>> {
+        let start_span = self.current_span();
+        self.expect(&TokenKind::LeftParen)?;
+        let mut names = vec![self.expect_identifier()?];
+        while self.match_token(&TokenKind::Comma) {
+            names.push(self.expect_identifier()?);
+        }
+        self.expect(&TokenKind::RightParen)?;
+        if names.len() < 2 {
+            return Err(ParserError::general(
+                "tuple destructuring needs at least two names".to_string(),
+                start_span.merge(&self.previous_span()),
+            ));
+        }
+        Ok(names)
+    }
+
     pub(crate) fn const_declaration(&mut self) -> ParseResult<Stmt> {
         let start_span = self.current_span();
         self.expect(&TokenKind::Const)?;
@@ -877,7 +1183,7 @@ impl Parser {
         let name = self.expect_identifier()?;
 
         let type_annotation = if self.match_token(&TokenKind::Colon) {
-            Some(self.parse_type()?)
+            Some(Box::new(self.parse_type()?))
         } else {
             None
         };
@@ -930,3 +1236,23 @@ impl Parser {
         }
     }
 }
+
+/// Hand parsed decorators to the declaration they precede, looking through
+/// `export`.
+fn attach_decorators(stmt: &mut Stmt, decorators: Vec<Decorator>) -> ParseResult<()> {
+    match &mut stmt.kind {
+        StmtKind::Export(inner) => attach_decorators(inner, decorators),
+        StmtKind::Function(decl) => {
+            decl.decorators = decorators;
+            Ok(())
+        }
+        StmtKind::Class(decl) => {
+            decl.decorators = decorators;
+            Ok(())
+        }
+        _ => Err(ParserError::general(
+            "decorators must be followed by a function or class declaration",
+            stmt.span,
+        )),
+    }
+}
@@ -1,8 +1,11 @@
 //! Expression parsing using Pratt precedence.
 
+use std::sync::Arc;
+
 use crate::ast::expr::{Argument, NamedArgument};
 use crate::ast::*;
 use crate::error::ParserError;
+use crate::lexer::token::StringPart;
 use crate::lexer::TokenKind;
 
 use super::core::{ParseResult, Parser};
@@ -160,6 +163,52 @@ impl Parser {
                     ));
                 }
 
+                // `await expr`: like `move`, `await` is only a keyword when an
+                // operand follows on the same line, so `await(future)` keeps
+                // working and `await` alone is still an identifier.
+                if name == "await"
+                    && self.peek().span.line == start_span.line
+                    && matches!(
+                        self.peek().kind,
+                        TokenKind::Identifier(_)
+                            | TokenKind::LeftParen
+                            | TokenKind::LeftBracket
+                            | TokenKind::BacktickString(_)
+                            | TokenKind::SdqlBlock { .. }
+                            | TokenKind::New
+                            | TokenKind::This
+                            | TokenKind::SelfKeyword
+                            | TokenKind::Super
+                    )
+                {
+                    let operand = self.parse_precedence(Precedence::Unary)?;
+                    let span = start_span.merge(&operand.span);
+                    return Ok(Expr::new(ExprKind::Await(Box::new(operand)), span));
+                }
+
+                // `move fn() { ... }`, `move |x| ...`, `move -> { ... }`: a lambda
+                // that captures by value. `move` is only a keyword right before
+                // a lambda on the same line; anywhere else it's an identifier.
+                if name == "move"
+                    && self.peek().span.line == start_span.line
+                    && matches!(
+                        self.peek().kind,
+                        TokenKind::Fn | TokenKind::Pipe | TokenKind::Arrow
+                    )
+                {
+                    let lambda_start = self.current_span();
+                    let mut lambda = match self.advance().kind {
+                        TokenKind::Fn => self.parse_anonymous_function(lambda_start)?,
+                        TokenKind::Pipe => self.parse_lambda(lambda_start)?,
+                        _ => self.parse_stabby_lambda(lambda_start)?,
+                    };
+                    if let ExprKind::Lambda { is_move, .. } = &mut lambda.kind {
+                        *is_move = true;
+                    }
+                    lambda.span = start_span.merge(&lambda.span);
+                    return Ok(lambda);
+                }
+
                 // Command-style calls with named args: greet name: "Alice"
                 // Same-line requirement matches the positional command-call branch below
                 // and prevents swallowing `name: ...` from the next line.
@@ -184,7 +233,7 @@ impl Parser {
                         return Ok(Expr::new(
                             ExprKind::Call {
                                 callee: Box::new(Expr::new(
-                                    ExprKind::Variable(name.clone()),
+                                    ExprKind::variable(name.clone()),
                                     start_span,
                                 )),
                                 arguments,
@@ -215,7 +264,7 @@ impl Parser {
                         return Ok(Expr::new(
                             ExprKind::Call {
                                 callee: Box::new(Expr::new(
-                                    ExprKind::Variable(name.clone()),
+                                    ExprKind::variable(name.clone()),
                                     start_span,
                                 )),
                                 arguments: args,
@@ -236,7 +285,7 @@ impl Parser {
                         return Ok(Expr::new(
                             ExprKind::Call {
                                 callee: Box::new(Expr::new(
-                                    ExprKind::Variable(name.clone()),
+                                    ExprKind::variable(name.clone()),
                                     start_span,
                                 )),
                                 arguments: args,
@@ -249,7 +298,7 @@ impl Parser {
                     return Ok(Expr::new(
                         ExprKind::Call {
                             callee: Box::new(Expr::new(
-                                ExprKind::Variable(name.clone()),
+                                ExprKind::variable(name.clone()),
                                 start_span,
                             )),
                             arguments,
@@ -257,7 +306,35 @@ impl Parser {
                         span,
                     ));
                 }
-                Ok(Expr::new(ExprKind::Variable(name.clone()), start_span))
+
+                // Bare call with only a block: `at_exit { ... }`,
+                // `at_exit do ... end`. Same line, and never in an
+                // if/while/for/match head where `{` opens the body.
+                let next = self.peek();
+                if next.span.line == start_span.line
+                    && ((!self.no_trailing_brace
+                        && next.kind == TokenKind::LeftBrace
+                        && !self.looks_like_hash_literal())
+                        || (!self.no_trailing_do && next.kind == TokenKind::Do))
+                {
+                    let block = if self.check(&TokenKind::Do) {
+                        self.parse_trailing_do_block()?
+                    } else {
+                        self.parse_trailing_brace_block()?
+                    };
+                    let span = start_span.merge(&self.previous_span());
+                    return Ok(Expr::new(
+                        ExprKind::Call {
+                            callee: Box::new(Expr::new(
+                                ExprKind::variable(name.clone()),
+                                start_span,
+                            )),
+                            arguments: vec![Argument::Block(block)],
+                        },
+                        span,
+                    ));
+                }
+                Ok(Expr::new(ExprKind::variable(name.clone()), start_span))
             }
 
             TokenKind::This | TokenKind::SelfKeyword => Ok(Expr::new(ExprKind::This, start_span)),
@@ -265,6 +342,19 @@ impl Parser {
 
             TokenKind::LeftParen => {
                 let expr = self.expression()?;
+                if self.match_token(&TokenKind::Comma) {
+                    // `(a, b)` is a tuple; a trailing comma is allowed.
+                    let mut elements = vec![expr];
+                    while !self.check(&TokenKind::RightParen) {
+                        elements.push(self.expression()?);
+                        if !self.match_token(&TokenKind::Comma) {
+                            break;
+                        }
+                    }
+                    self.expect(&TokenKind::RightParen)?;
+                    let span = start_span.merge(&self.previous_span());
+                    return Ok(Expr::new(ExprKind::Tuple(elements), span));
+                }
                 self.expect(&TokenKind::RightParen)?;
                 let span = start_span.merge(&self.previous_span());
                 Ok(Expr::new(ExprKind::Grouping(Box::new(expr)), span))
@@ -325,7 +415,7 @@ impl Parser {
                     Expr::new(
                         ExprKind::QualifiedName {
                             qualifier: Box::new(Expr::new(
-                                ExprKind::Variable(class_name),
+                                ExprKind::variable(class_name),
                                 name_span,
                             )),
                             name: nested_name,
@@ -333,7 +423,7 @@ impl Parser {
                         nested_span,
                     )
                 } else {
-                    Expr::new(ExprKind::Variable(class_name), name_span)
+                    Expr::new(ExprKind::variable(class_name), name_span)
                 };
 
                 self.expect(&TokenKind::LeftParen)?;
@@ -383,11 +473,13 @@ impl Parser {
                     default_value: None,
                     span: start_span,
                     is_block_param: false,
+                    is_keyword_only: false,
+                    is_variadic: false,
                 };
                 let body_expr = Expr::new(
                     ExprKind::Member {
                         object: Box::new(Expr::new(
-                            ExprKind::Variable("__it".to_string()),
+                            ExprKind::variable("__it".to_string()),
                             start_span,
                         )),
                         name: method_name,
@@ -399,7 +491,8 @@ impl Parser {
                     ExprKind::Lambda {
                         params: vec![param],
                         return_type: None,
-                        body: vec![body_stmt],
+                        body: Arc::new(vec![body_stmt].into()),
+                        is_move: false,
                     },
                     span,
                 ))
@@ -410,21 +503,21 @@ impl Parser {
             // can do `Int.class_eval do define_method(:double) { ... } end` or
             // call class methods on them. `Void` is intentionally excluded —
             // it's a return-type marker, not a runtime value.
-            TokenKind::Int => Ok(Expr::new(ExprKind::Variable("Int".to_string()), start_span)),
+            TokenKind::Int => Ok(Expr::new(ExprKind::variable("Int".to_string()), start_span)),
             TokenKind::Float => Ok(Expr::new(
-                ExprKind::Variable("Float".to_string()),
+                ExprKind::variable("Float".to_string()),
                 start_span,
             )),
             TokenKind::Bool => Ok(Expr::new(
-                ExprKind::Variable("Bool".to_string()),
+                ExprKind::variable("Bool".to_string()),
                 start_span,
             )),
             TokenKind::Decimal => Ok(Expr::new(
-                ExprKind::Variable("Decimal".to_string()),
+                ExprKind::variable("Decimal".to_string()),
                 start_span,
             )),
             TokenKind::String => Ok(Expr::new(
-                ExprKind::Variable("String".to_string()),
+                ExprKind::variable("String".to_string()),
                 start_span,
             )),
 
@@ -544,6 +637,7 @@ impl Parser {
             | TokenKind::For
             | TokenKind::Return
             | TokenKind::Throw
+            | TokenKind::Yield
             | TokenKind::Try
             | TokenKind::Fn
             | TokenKind::Class
@@ -588,7 +682,7 @@ impl Parser {
                 self.expect_hash_separator()?;
 
                 // Convert variable keys to string literals (shorthand syntax: {name: value} => {"name": value})
-                let key = if let ExprKind::Variable(name) = &key.kind {
+                let key = if let ExprKind::Variable(name, _) = &key.kind {
                     Expr::new(ExprKind::StringLiteral(name.clone()), key.span)
                 } else {
                     key
@@ -597,7 +691,7 @@ impl Parser {
                 // Shorthand: { name: } or { name:, age: } — value is the variable with the same name as the key
                 let value = if let ExprKind::StringLiteral(ref name) = key.kind {
                     if self.check(&TokenKind::Comma) || self.check(&TokenKind::RightBrace) {
-                        Expr::new(ExprKind::Variable(name.clone()), key.span)
+                        Expr::new(ExprKind::variable(name.clone()), key.span)
                     } else {
                         self.expression()?
                     }
@@ -649,6 +743,7 @@ impl Parser {
                 | Some(TokenKind::For)
                 | Some(TokenKind::Return)
                 | Some(TokenKind::Throw)
+                | Some(TokenKind::Yield)
                 | Some(TokenKind::Try)
                 | Some(TokenKind::Fn)
                 | Some(TokenKind::Class)
@@ -705,7 +800,7 @@ impl Parser {
 
         // Parse optional condition
         let condition = if self.match_token(&TokenKind::If) {
-            Some(Box::new(self.expression()?))
+            Some(self.expression()?)
         } else {
             None
         };
@@ -714,13 +809,13 @@ impl Parser {
         let span = start_span.merge(&self.previous_span());
 
         Ok(Expr::new(
-            ExprKind::HashComprehension {
-                key: Box::new(key_expr),
-                value: Box::new(value_expr),
+            ExprKind::HashComprehension(Box::new(HashComprehension {
+                key: key_expr,
+                value: value_expr,
                 variable: var_name,
-                iterable: Box::new(iterable),
+                iterable,
                 condition,
-            },
+            })),
             span,
         ))
     }
@@ -756,6 +851,9 @@ impl Parser {
             TokenKind::Greater => self.binary_expr(left, BinaryOp::Greater, precedence),
             TokenKind::GreaterEqual => self.binary_expr(left, BinaryOp::GreaterEqual, precedence),
             TokenKind::Range => self.binary_expr(left, BinaryOp::Range, precedence),
+            TokenKind::RangeInclusive => {
+                self.binary_expr(left, BinaryOp::RangeInclusive, precedence)
+            }
             TokenKind::LessLess => self.binary_expr(left, BinaryOp::Shovel, precedence),
 
             // Ternary operator: cond ? then_expr : else_expr
@@ -813,8 +911,38 @@ impl Parser {
 
             // Pipeline operator
             TokenKind::Pipeline => {
-                let right = self.parse_precedence(precedence.next())?;
+                // Method piping: `x |> .trim()` is `x.trim()`. Only the
+                // member is built here — the `(...)` and any further chain
+                // are picked up by the caller's infix loop like on any
+                // other member.
+                if self.check(&TokenKind::Dot) {
+                    return self.parse_infix(left, Precedence::Call);
+                }
+                let mut right = self.parse_precedence(precedence.next())?;
                 let span = start_span.merge(&right.span);
+                // Placeholder: `x |> f(a, _)` is `f(a, x)`.
+                if let ExprKind::Call { arguments, .. } = &mut right.kind {
+                    let mut slots = arguments.iter_mut().filter_map(|arg| match arg {
+                        Argument::Positional(expr)
+                            if matches!(&expr.kind, ExprKind::Variable(n, _) if n == "_") =>
+                        {
+                            Some(expr)
+                        }
+                        _ => None,
+                    });
+                    if let Some(slot) = slots.next() {
+                        if let Some(extra) = slots.next() {
+                            return Err(ParserError::General {
+                                message: "a pipeline step can use the `_` placeholder only once"
+                                    .to_string(),
+                                span: extra.span,
+                            });
+                        }
+                        *slot = left;
+                        right.span = span;
+                        return Ok(right);
+                    }
+                }
                 Ok(Expr::new(
                     ExprKind::Pipeline {
                         left: Box::new(left),
@@ -830,7 +958,7 @@ impl Parser {
                 let span = start_span.merge(&value.span);
 
                 match &left.kind {
-                    ExprKind::Variable(_) | ExprKind::Member { .. } | ExprKind::Index { .. } => {
+                    ExprKind::Variable(_, _) | ExprKind::Member { .. } | ExprKind::Index { .. } => {
                         Ok(Expr::new(
                             ExprKind::Assign {
                                 target: Box::new(left),
@@ -881,7 +1009,7 @@ impl Parser {
                 let span = start_span.merge(&value.span);
 
                 match &left.kind {
-                    ExprKind::Variable(_) | ExprKind::Member { .. } | ExprKind::Index { .. } => {
+                    ExprKind::Variable(_, _) | ExprKind::Member { .. } | ExprKind::Index { .. } => {
                         Ok(Expr::new(
                             ExprKind::CompoundAssign {
                                 target: Box::new(left),
@@ -899,7 +1027,7 @@ impl Parser {
             TokenKind::PlusPlus => {
                 let span = start_span.merge(&token.span);
                 match &left.kind {
-                    ExprKind::Variable(_) | ExprKind::Member { .. } | ExprKind::Index { .. } => {
+                    ExprKind::Variable(_, _) | ExprKind::Member { .. } | ExprKind::Index { .. } => {
                         Ok(Expr::new(ExprKind::PostfixIncrement(Box::new(left)), span))
                     }
                     _ => Err(ParserError::invalid_assignment_target(left.span)),
@@ -908,7 +1036,7 @@ impl Parser {
             TokenKind::MinusMinus => {
                 let span = start_span.merge(&token.span);
                 match &left.kind {
-                    ExprKind::Variable(_) | ExprKind::Member { .. } | ExprKind::Index { .. } => {
+                    ExprKind::Variable(_, _) | ExprKind::Member { .. } | ExprKind::Index { .. } => {
                         Ok(Expr::new(ExprKind::PostfixDecrement(Box::new(left)), span))
                     }
                     _ => Err(ParserError::invalid_assignment_target(left.span)),
@@ -1170,11 +1298,13 @@ impl Parser {
                             default_value: None,
                             span: start_span,
                             is_block_param: false,
+                            is_keyword_only: false,
+                            is_variadic: false,
                         };
                         let body_expr = Expr::new(
                             ExprKind::Member {
                                 object: Box::new(Expr::new(
-                                    ExprKind::Variable("__it".to_string()),
+                                    ExprKind::variable("__it".to_string()),
                                     start_span,
                                 )),
                                 name: method_name,
@@ -1190,7 +1320,8 @@ impl Parser {
                             ExprKind::Lambda {
                                 params: vec![param],
                                 return_type: None,
-                                body: vec![body_stmt],
+                                body: Arc::new(vec![body_stmt].into()),
+                                is_move: false,
                             },
                             span,
                         );
@@ -1214,11 +1345,13 @@ impl Parser {
                             default_value: None,
                             span: start_span,
                             is_block_param: false,
+                            is_keyword_only: false,
+                            is_variadic: false,
                         };
                         let body_expr = Expr::new(
                             ExprKind::Member {
                                 object: Box::new(Expr::new(
-                                    ExprKind::Variable("__it".to_string()),
+                                    ExprKind::variable("__it".to_string()),
                                     start_span,
                                 )),
                                 name: method_name,
@@ -1234,7 +1367,8 @@ impl Parser {
                             ExprKind::Lambda {
                                 params: vec![param],
                                 return_type: None,
-                                body: vec![body_stmt],
+                                body: Arc::new(vec![body_stmt].into()),
+                                is_move: false,
                             },
                             span,
                         );
@@ -1243,7 +1377,7 @@ impl Parser {
                         // Block reference: &identifier
                         let name = self.expect_identifier()?;
                         let span = start_span.merge(&self.previous_span());
-                        let var_expr = Expr::new(ExprKind::Variable(name), span);
+                        let var_expr = Expr::new(ExprKind::variable(name), span);
                         arguments.push(Argument::Block(var_expr));
                     } else {
                         return Err(ParserError::general(
@@ -1251,6 +1385,16 @@ impl Parser {
                             block_start,
                         ));
                     }
+                } else if self.match_token(&TokenKind::Star) {
+                    // Spread argument: `*array` passes the elements positionally.
+                    let expr = self.expression()?;
+                    if seen_named {
+                        return Err(ParserError::general(
+                            "positional argument cannot follow named argument".to_string(),
+                            start_span.merge(&expr.span),
+                        ));
+                    }
+                    arguments.push(Argument::Spread(expr));
                 } else {
                     // Named argument: a label followed by `:`. The label is an
                     // identifier, or a reserved word usable as a label
@@ -1270,7 +1414,11 @@ impl Parser {
                         self.advance(); // consume colon
                         let value = self.expression()?;
                         let span = start_span.merge(&value.span);
-                        arguments.push(Argument::Named(NamedArgument { name, value, span }));
+                        arguments.push(Argument::Named(Box::new(NamedArgument {
+                            name,
+                            value,
+                            span,
+                        })));
                         seen_named = true;
                     } else {
                         // Positional argument
@@ -1313,7 +1461,11 @@ impl Parser {
             self.advance(); // colon
             let value = self.expression()?;
             let span = start_span.merge(&value.span);
-            arguments.push(Argument::Named(NamedArgument { name, value, span }));
+            arguments.push(Argument::Named(Box::new(NamedArgument {
+                name,
+                value,
+                span,
+            })));
             if !self.match_token(&TokenKind::Comma) {
                 break;
             }
@@ -1357,7 +1509,8 @@ impl Parser {
             ExprKind::Lambda {
                 params,
                 return_type: None,
-                body: statements,
+                body: Arc::new(statements.into()),
+                is_move: false,
             },
             span,
         ))
@@ -1388,7 +1541,8 @@ impl Parser {
             ExprKind::Lambda {
                 params,
                 return_type: None,
-                body: statements,
+                body: Arc::new(statements.into()),
+                is_move: false,
             },
             span,
         ))
@@ -1443,6 +1597,8 @@ impl Parser {
                     default_value: None,
                     span: param_start.merge(&self.previous_span()),
                     is_block_param: false,
+                    is_keyword_only: false,
+                    is_variadic: false,
                 });
 
                 if !self.match_token(&TokenKind::Comma) {
@@ -1467,6 +1623,9 @@ impl Parser {
         if !self.check(end_token) {
             loop {
                 let param_start = self.current_span();
+                // `...rest` collects the remaining arguments; lambdas have no
+                // keyword-only parameters, so it must come last.
+                let is_variadic = self.match_token(&TokenKind::Spread);
                 let name = self.expect_identifier()?;
 
                 // Type annotation
@@ -1485,11 +1644,19 @@ impl Parser {
                     default_value: None,
                     span: param_start.merge(&self.previous_span()),
                     is_block_param: false,
+                    is_keyword_only: false,
+                    is_variadic,
                 });
 
                 if !self.match_token(&TokenKind::Comma) {
                     break;
                 }
+                if is_variadic {
+                    return Err(ParserError::general(
+                        "a variadic parameter must be the last parameter of a lambda".to_string(),
+                        self.previous_span(),
+                    ));
+                }
             }
         }
 
@@ -1560,7 +1727,8 @@ impl Parser {
             ExprKind::Lambda {
                 params,
                 return_type: return_type.map(Box::new),
-                body,
+                body: Arc::new(body.into()),
+                is_move: false,
             },
             span,
         ))
@@ -1629,7 +1797,33 @@ impl Parser {
         ))
     }
 
+    /// A pattern, with `|` between alternatives and `&` between patterns that
+    /// must all match; `&` binds tighter.
     fn parse_match_pattern(&mut self) -> ParseResult<MatchPattern> {
+        let mut alternatives = vec![self.parse_and_pattern()?];
+        while self.match_token(&TokenKind::Pipe) {
+            alternatives.push(self.parse_and_pattern()?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            MatchPattern::Or(alternatives)
+        })
+    }
+
+    fn parse_and_pattern(&mut self) -> ParseResult<MatchPattern> {
+        let mut patterns = vec![self.parse_primary_pattern()?];
+        while self.match_token(&TokenKind::Ampersand) {
+            patterns.push(self.parse_primary_pattern()?);
+        }
+        Ok(if patterns.len() == 1 {
+            patterns.pop().unwrap()
+        } else {
+            MatchPattern::And(patterns)
+        })
+    }
+
+    fn parse_primary_pattern(&mut self) -> ParseResult<MatchPattern> {
         use crate::lexer::TokenKind::*;
 
         let token_kind = self.peek().kind.clone();
@@ -1693,6 +1887,11 @@ impl Parser {
                 self.parse_array_pattern()
             }
 
+            LeftParen => {
+                self.advance();
+                self.parse_tuple_pattern()
+            }
+
             LeftBrace => {
                 self.advance();
                 self.parse_hash_pattern()
@@ -1728,34 +1927,45 @@ impl Parser {
                 })
             }
 
-            Identifier(s) if self.peek_nth(1).kind != TokenKind::Colon => {
+            Identifier(s)
+                if !matches!(
+                    self.peek_nth(1).kind,
+                    TokenKind::Colon | TokenKind::LeftBrace
+                ) =>
+            {
                 self.advance();
                 Ok(MatchPattern::Variable(s))
             }
 
-            Identifier(_) => {
+            Identifier(name) => {
                 self.advance();
-                if self.check(&TokenKind::LeftBrace) {
-                    let type_name = match &self.previous().kind {
-                        TokenKind::Identifier(s) => s.clone(),
-                        _ => {
-                            return Err(ParserError::unexpected_token(
-                                "type name".to_string(),
-                                format!("{}", self.previous().kind),
-                                self.previous().span,
-                            ))
-                        }
-                    };
+                // Destructuring: `Point { x: px, y: py }`.
+                if self.match_token(&TokenKind::LeftBrace) {
                     let fields = self.parse_hash_pattern_fields()?;
                     self.expect(&TokenKind::RightBrace)?;
-                    Ok(MatchPattern::Destructuring { type_name, fields })
-                } else {
-                    self.expect(&TokenKind::Colon)?;
-                    let type_name = self.expect_identifier()?;
                     Ok(MatchPattern::Destructuring {
-                        type_name: type_name.clone(),
-                        fields: Vec::new(),
+                        type_name: name,
+                        fields,
                     })
+                } else {
+                    // Typed binding: `n: Int`, `p: Point`.
+                    self.expect(&TokenKind::Colon)?;
+                    let keyword_type = match self.peek().kind {
+                        TokenKind::Int => Some("Int"),
+                        TokenKind::Float => Some("Float"),
+                        TokenKind::Bool => Some("Bool"),
+                        TokenKind::String => Some("String"),
+                        TokenKind::Void => Some("Void"),
+                        _ => None,
+                    };
+                    let type_name = match keyword_type {
+                        Some(type_name) => {
+                            self.advance();
+                            type_name.to_string()
+                        }
+                        None => self.expect_identifier()?,
+                    };
+                    Ok(MatchPattern::Typed { name, type_name })
                 }
             }
 
@@ -1833,6 +2043,25 @@ impl Parser {
         Ok(MatchPattern::Array { elements, rest })
     }
 
+    /// `(a, b)` is a tuple pattern (a trailing comma is allowed, and makes
+    /// `(a,)` a one-element tuple); `(p)` just groups `p`.
+    fn parse_tuple_pattern(&mut self) -> ParseResult<MatchPattern> {
+        let first = self.parse_match_pattern()?;
+        if !self.match_token(&TokenKind::Comma) {
+            self.expect(&TokenKind::RightParen)?;
+            return Ok(first);
+        }
+        let mut elements = vec![first];
+        while !self.check(&TokenKind::RightParen) {
+            elements.push(self.parse_match_pattern()?);
+            if !self.match_token(&TokenKind::Comma) {
+                break;
+            }
+        }
+        self.expect(&TokenKind::RightParen)?;
+        Ok(MatchPattern::Tuple(elements))
+    }
+
     fn parse_hash_pattern(&mut self) -> ParseResult<MatchPattern> {
         if self.check(&TokenKind::RightBrace) {
             self.advance();
@@ -1880,23 +2109,19 @@ impl Parser {
 
     fn parse_interpolated_string(
         &mut self,
-        parts: Vec<String>,
+        parts: Vec<StringPart>,
         start_span: crate::span::Span,
     ) -> ParseResult<Expr> {
         use crate::ast::expr::InterpolatedPart;
 
-        let mut interpolated_parts = Vec::new();
-
+        let mut interpolated_parts = Vec::with_capacity(parts.len());
         for part in parts {
-            if part.starts_with("#{") {
-                // This is an expression - parse it
-                // The format is #{expr}, so we need to extract expr and parse it
-                let expr_content = &part[2..part.len() - 1]; // Remove #{ and }
-                                                             // Parse the expression from the content
-                let expr = self.parse_expression_from_string(expr_content)?;
-                interpolated_parts.push(InterpolatedPart::Expression(expr));
-            } else {
-                interpolated_parts.push(InterpolatedPart::Literal(part));
+            match part {
+                StringPart::Literal(s) => interpolated_parts.push(InterpolatedPart::Literal(s)),
+                StringPart::Code { source, span } => {
+                    let expr = self.parse_expression_from_string(&source, span)?;
+                    interpolated_parts.push(InterpolatedPart::Expression(expr));
+                }
             }
         }
 
@@ -1907,8 +2132,25 @@ impl Parser {
         ))
     }
 
-    /// Parse an expression from a string content (for interpolated strings)
-    fn parse_expression_from_string(&mut self, content: &str) -> ParseResult<Expr> {
+    /// Parse the expression inside a `#{...}`. `origin` is where `content`
+    /// starts in the file; token spans are shifted onto it so errors and
+    /// AST spans inside the interpolation point at the real source.
+    fn parse_expression_from_string(
+        &mut self,
+        content: &str,
+        origin: crate::span::Span,
+    ) -> ParseResult<Expr> {
+        let shift = |span: crate::span::Span| crate::span::Span {
+            start: span.start + origin.start,
+            end: span.end + origin.start,
+            line: span.line + origin.line - 1,
+            column: if span.line == 1 {
+                span.column + origin.column - 1
+            } else {
+                span.column
+            },
+        };
+
         // Fast path: simple identifier (e.g. #{name}) — skip Scanner+Parser
         let trimmed = content.trim();
         if !trimmed.is_empty()
@@ -1917,17 +2159,33 @@ impl Parser {
                 .all(|b| b.is_ascii_alphanumeric() || b == b'_')
             && !trimmed.bytes().next().unwrap_or(0).is_ascii_digit()
         {
-            let span = self.previous_span();
-            return Ok(Expr::new(ExprKind::Variable(trimmed.to_string()), span));
+            let lead = content.len() - content.trim_start().len();
+            let span = crate::span::Span::new(lead, lead + trimmed.len(), 1, lead + 1);
+            return Ok(Expr::new(
+                ExprKind::variable(trimmed.to_string()),
+                shift(span),
+            ));
         }
 
         // General path: full Scanner+Parser for complex expressions
         use crate::lexer::scanner::Scanner;
         let mut scanner = Scanner::new(content);
-        let tokens = scanner.scan_tokens()?;
+        let mut tokens = scanner.scan_tokens().map_err(|e| e.map_span(shift))?;
+        for token in &mut tokens {
+            token.span = shift(token.span);
+        }
 
         let mut parser = crate::parser::Parser::new(tokens);
-        parser.expression()
+        let expr = parser.expression()?;
+        if !parser.is_at_end() {
+            let token = parser.peek();
+            return Err(ParserError::unexpected_token(
+                "'}' to close the interpolation",
+                token.kind.to_string(),
+                token.span,
+            ));
+        }
+        Ok(expr)
     }
 
     /// Check if a token is a valid first token for a command-style argument.
@@ -2024,11 +2282,11 @@ impl Parser {
             self.no_trailing_do = old_no_do;
             let value = value?;
             let span = start.merge(&self.previous_span());
-            return Ok(Argument::Named(NamedArgument {
+            return Ok(Argument::Named(Box::new(NamedArgument {
                 name: label,
                 value,
                 span,
-            }));
+            })));
         }
 
         let value = self.expression();
@@ -2177,7 +2435,7 @@ mod at_sigil_tests {
         let program = parse("transition from: a, to: b").expect("parses");
         match first_expr(&program) {
             ExprKind::Call { callee, arguments } => {
-                assert!(matches!(callee.kind, ExprKind::Variable(ref n) if n == "transition"));
+                assert!(matches!(callee.kind, ExprKind::Variable(ref n, _) if n == "transition"));
                 assert_eq!(arguments.len(), 2);
                 assert!(matches!(&arguments[0], Argument::Named(n) if n.name == "from"));
                 assert!(matches!(&arguments[1], Argument::Named(n) if n.name == "to"));
@@ -2192,7 +2450,7 @@ mod at_sigil_tests {
         let program = parse("event :pay do\n  transition from: a, to: b\nend").expect("parses");
         match first_expr(&program) {
             ExprKind::Call { callee, arguments } => {
-                assert!(matches!(callee.kind, ExprKind::Variable(ref n) if n == "event"));
+                assert!(matches!(callee.kind, ExprKind::Variable(ref n, _) if n == "event"));
                 assert!(matches!(&arguments[0], Argument::Positional(_)));
                 assert!(matches!(arguments.last(), Some(Argument::Block(_))));
             }
@@ -2207,7 +2465,7 @@ mod at_sigil_tests {
         match first_expr(&program) {
             ExprKind::Call { callee, arguments } => {
                 assert!(
-                    matches!(callee.kind, ExprKind::Variable(ref n) if n == "after_transition")
+                    matches!(callee.kind, ExprKind::Variable(ref n, _) if n == "after_transition")
                 );
                 assert!(matches!(&arguments[0], Argument::Named(n) if n.name == "to"));
                 assert!(matches!(arguments.last(), Some(Argument::Block(_))));
@@ -2222,7 +2480,7 @@ mod at_sigil_tests {
         let program = parse("guard fn() { this.total > 0 }").expect("parses");
         match first_expr(&program) {
             ExprKind::Call { callee, arguments } => {
-                assert!(matches!(callee.kind, ExprKind::Variable(ref n) if n == "guard"));
+                assert!(matches!(callee.kind, ExprKind::Variable(ref n, _) if n == "guard"));
                 assert!(
                     matches!(&arguments[0], Argument::Positional(e) if matches!(e.kind, ExprKind::Lambda { .. }))
                 );
@@ -3,6 +3,7 @@
 mod core;
 mod declarations;
 mod expressions;
+pub mod platform;
 mod precedence;
 mod statements;
 mod types;
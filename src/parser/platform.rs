@@ -0,0 +1,248 @@
+//! Platform guards, resolved right after parsing.
+//!
+//! ```text
+//! @cfg(os: "windows")
+//! fn separator() { return "\\" }
+//!
+//! @cfg(family: "unix")
+//! fn separator() { return "/" }
+//!
+//! if_platform("linux", "macos") {
+//!     import { spawn } from "./posix.sl"
+//! }
+//! ```
+//!
+//! A declaration whose `@cfg` doesn't match the platform is dropped, and an
+//! `if_platform(...) { ... }` statement is replaced by its body's statements
+//! (spliced into the enclosing list, so the declarations inside stay visible
+//! there) or by nothing. The type checker and both engines only ever see the
+//! code for the platform they run on. Tools that work on the source as
+//! written — the formatter, the linter, the LSP — keep both branches with
+//! [`Parser::keeping_platform_code`](super::Parser::keeping_platform_code).
+
+use std::sync::Arc;
+
+use crate::ast::expr::Argument;
+use crate::ast::{ClassDecl, Decorator, Expr, ExprKind, Stmt, StmtKind};
+use crate::error::ParserError;
+
+use super::core::ParseResult;
+
+/// The platform guards are resolved against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Platform {
+    /// `linux`, `macos`, `windows`, `freebsd`, ... (Rust's `target_os`).
+    pub os: String,
+    /// `unix` or `windows`.
+    pub family: String,
+    /// `x86_64`, `aarch64`, ...
+    pub arch: String,
+}
+
+impl Platform {
+    /// The platform soli is running on.
+    pub fn current() -> Self {
+        Self {
+            os: std::env::consts::OS.to_string(),
+            family: std::env::consts::FAMILY.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+        }
+    }
+
+    /// Whether `name` — an OS or an OS family — names this platform.
+    fn is(&self, name: &str) -> bool {
+        name == self.os || name == self.family
+    }
+
+    /// Whether a declaration's `@cfg(...)` decorators all match.
+    fn matches_cfg(&self, decorators: &[Decorator]) -> ParseResult<bool> {
+        for decorator in decorators.iter().filter(|d| d.name == "cfg") {
+            if decorator.arguments.is_empty() {
+                return Err(ParserError::general(
+                    "@cfg needs at least one of os:, family: or arch:",
+                    decorator.span,
+                ));
+            }
+            for arg in &decorator.arguments {
+                let Argument::Named(named) = arg else {
+                    return Err(ParserError::general(
+                        "@cfg takes named arguments: os:, family: or arch:",
+                        decorator.span,
+                    ));
+                };
+                let actual = match named.name.as_str() {
+                    "os" => &self.os,
+                    "family" => &self.family,
+                    "arch" => &self.arch,
+                    other => {
+                        return Err(ParserError::general(
+                            format!("unknown @cfg key '{}' (expected os, family or arch)", other),
+                            decorator.span,
+                        ))
+                    }
+                };
+                if !guard_values(&named.value)?.iter().any(|v| v == actual) {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// The names a guard lists: a string literal or an array of them.
+fn guard_values(expr: &Expr) -> ParseResult<Vec<&str>> {
+    match &expr.kind {
+        ExprKind::StringLiteral(s) => Ok(vec![s.as_str()]),
+        ExprKind::Array(items) => {
+            let mut values = Vec::new();
+            for item in items {
+                values.extend(guard_values(item)?);
+            }
+            Ok(values)
+        }
+        _ => Err(ParserError::general(
+            "platform guards take string literals",
+            expr.span,
+        )),
+    }
+}
+
+/// The decorators of `stmt`, looking through `export`.
+fn decorators_of(stmt: &mut Stmt) -> Option<&mut Vec<Decorator>> {
+    match &mut stmt.kind {
+        StmtKind::Export(inner) => decorators_of(inner),
+        StmtKind::Function(decl) => Some(&mut decl.decorators),
+        StmtKind::Class(decl) => Some(&mut decl.decorators),
+        _ => None,
+    }
+}
+
+/// `if_platform("a", "b") { body }`: the names and the body.
+fn if_platform(stmt: &mut Stmt) -> Option<(&[Argument], &mut Vec<Stmt>)> {
+    let StmtKind::Expression(expr) = &mut stmt.kind else {
+        return None;
+    };
+    let ExprKind::Call { callee, arguments } = &mut expr.kind else {
+        return None;
+    };
+    if !matches!(&callee.kind, ExprKind::Variable(name, _) if name == "if_platform") {
+        return None;
+    }
+    let (Argument::Block(block), names) = arguments.split_last_mut()? else {
+        return None;
+    };
+    let ExprKind::Lambda { params, body, .. } = &mut block.kind else {
+        return None;
+    };
+    let body = Arc::make_mut(body);
+    params.is_empty().then_some((&*names, &mut **body))
+}
+
+/// Resolve every guard in `stmts` and the bodies nested in them.
+pub(crate) fn resolve(stmts: &mut Vec<Stmt>, platform: &Platform) -> ParseResult<()> {
+    let mut resolved = Vec::with_capacity(stmts.len());
+    for mut stmt in std::mem::take(stmts) {
+        let span = stmt.span;
+        if let Some((names, body)) = if_platform(&mut stmt) {
+            let mut matched = false;
+            for name in names {
+                let Argument::Positional(expr) = name else {
+                    return Err(ParserError::general(
+                        "if_platform takes platform names, e.g. if_platform(\"windows\") { ... }",
+                        span,
+                    ));
+                };
+                matched |= guard_values(expr)?.iter().any(|n| platform.is(n));
+            }
+            if names.is_empty() {
+                return Err(ParserError::general(
+                    "if_platform needs at least one platform name",
+                    span,
+                ));
+            }
+            if matched {
+                let mut body = std::mem::take(body);
+                resolve(&mut body, platform)?;
+                resolved.extend(body);
+            }
+            continue;
+        }
+        if let Some(decorators) = decorators_of(&mut stmt) {
+            if !platform.matches_cfg(decorators)? {
+                continue;
+            }
+            decorators.retain(|d| d.name != "cfg");
+        }
+        resolve_stmt(&mut stmt, platform)?;
+        resolved.push(stmt);
+    }
+    *stmts = resolved;
+    Ok(())
+}
+
+fn resolve_stmt(stmt: &mut Stmt, platform: &Platform) -> ParseResult<()> {
+    match &mut stmt.kind {
+        StmtKind::Block(stmts) => resolve(stmts, platform),
+        StmtKind::If {
+            then_branch,
+            else_branch,
+            ..
+        } => {
+            resolve_stmt(then_branch, platform)?;
+            match else_branch {
+                Some(else_branch) => resolve_stmt(else_branch, platform),
+                None => Ok(()),
+            }
+        }
+        StmtKind::While { body, .. } | StmtKind::For { body, .. } => resolve_stmt(body, platform),
+        StmtKind::Try {
+            try_block,
+            catch_clauses,
+            finally_block,
+        } => {
+            resolve_stmt(try_block, platform)?;
+            for clause in catch_clauses {
+                resolve_stmt(&mut clause.body, platform)?;
+            }
+            match finally_block {
+                Some(finally_block) => resolve_stmt(finally_block, platform),
+                None => Ok(()),
+            }
+        }
+        StmtKind::Function(decl) => resolve(&mut *Arc::make_mut(&mut decl.body), platform),
+        StmtKind::Class(decl) => resolve_class(decl, platform),
+        StmtKind::Export(inner) => resolve_stmt(inner, platform),
+        _ => Ok(()),
+    }
+}
+
+fn resolve_class(decl: &mut ClassDecl, platform: &Platform) -> ParseResult<()> {
+    let mut methods = Vec::with_capacity(decl.methods.len());
+    for mut method in std::mem::take(&mut decl.methods) {
+        if platform.matches_cfg(&method.decorators)? {
+            method.decorators.retain(|d| d.name != "cfg");
+            resolve(&mut *Arc::make_mut(&mut method.body), platform)?;
+            methods.push(method);
+        }
+    }
+    decl.methods = methods;
+
+    let mut nested_classes = Vec::with_capacity(decl.nested_classes.len());
+    for mut nested in std::mem::take(&mut decl.nested_classes) {
+        if platform.matches_cfg(&nested.decorators)? {
+            nested.decorators.retain(|d| d.name != "cfg");
+            resolve_class(&mut nested, platform)?;
+            nested_classes.push(nested);
+        }
+    }
+    decl.nested_classes = nested_classes;
+
+    if let Some(ctor) = &mut decl.constructor {
+        resolve(&mut *Arc::make_mut(&mut ctor.body), platform)?;
+    }
+    if let Some(block) = &mut decl.static_block {
+        resolve(block, platform)?;
+    }
+    resolve(&mut decl.class_statements, platform)
+}
@@ -62,7 +62,7 @@ pub fn get_precedence(kind: &TokenKind) -> Precedence {
         TokenKind::Less | TokenKind::LessEqual | TokenKind::Greater | TokenKind::GreaterEqual => {
             Precedence::Comparison
         }
-        TokenKind::Range => Precedence::Comparison, // .. has same precedence as comparison
+        TokenKind::Range | TokenKind::RangeInclusive => Precedence::Comparison, // .. has same precedence as comparison
         TokenKind::Pipeline => Precedence::Pipeline,
         TokenKind::Plus | TokenKind::Minus | TokenKind::LessLess => Precedence::Term,
         TokenKind::Star | TokenKind::Slash | TokenKind::Percent => Precedence::Factor,
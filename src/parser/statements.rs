@@ -1,6 +1,7 @@
 //! Statement parsing: if, while, for, return, blocks.
 
 use crate::ast::*;
+use crate::error::ParserError;
 use crate::lexer::TokenKind;
 
 use super::core::{ParseResult, Parser};
@@ -9,7 +10,7 @@ impl Parser {
     pub(crate) fn statement(&mut self) -> ParseResult<Stmt> {
         if self.check(&TokenKind::Class) {
             self.class_declaration()
-        } else if self.check(&TokenKind::Fn) {
+        } else if self.check(&TokenKind::Fn) || self.at_async_fn() {
             self.function_declaration()
         } else if self.check(&TokenKind::Let) {
             self.let_declaration()
@@ -27,6 +28,8 @@ impl Parser {
             self.break_statement()
         } else if self.check(&TokenKind::Throw) {
             self.throw_statement()
+        } else if self.check(&TokenKind::Yield) {
+            self.yield_statement()
         } else if self.check(&TokenKind::Try) {
             self.try_statement()
         } else if self.check(&TokenKind::LeftBrace) {
@@ -155,6 +158,35 @@ impl Parser {
         let start_span = self.current_span();
         self.expect(&TokenKind::For)?;
 
+        // `for (i, item) in items` enumerates; `for (k, v) in hash` walks
+        // the entries.
+        if self.at_for_tuple_pattern() {
+            let pattern_start = self.current_span();
+            let names = self.tuple_names()?;
+            if names.len() != 2 {
+                return Err(ParserError::general(
+                    "for (a, b) in ... takes two names: (index, item), or (key, value) over a hash"
+                        .to_string(),
+                    pattern_start.merge(&self.previous_span()),
+                ));
+            }
+            self.expect(&TokenKind::In)?;
+            let iterable = self.expression_no_trailing_brace()?;
+            let body = self.parse_block_body()?;
+            let span = start_span.merge(&self.previous_span());
+            return Ok(Stmt::new(
+                StmtKind::For {
+                    variable: format!("({})", names.join(", ")),
+                    index_variable: None,
+                    destructure: Some(names),
+                    iterable: Box::new(iterable),
+                    body,
+                },
+                span,
+                None,
+            ));
+        }
+
         // Parentheses are optional around the for clause
         let has_paren = self.match_token(&TokenKind::LeftParen);
         let variable = self.expect_identifier()?;
@@ -183,7 +215,8 @@ impl Parser {
             StmtKind::For {
                 variable,
                 index_variable,
-                iterable,
+                destructure: None,
+                iterable: Box::new(iterable),
                 body,
             },
             span,
@@ -191,6 +224,27 @@ impl Parser {
         ))
     }
 
+    /// `(a, b) in` — a tuple pattern, as opposed to the parenthesized
+    /// `(x in iter)` / `(x, i in iter)` clause.
+    fn at_for_tuple_pattern(&self) -> bool {
+        if !self.check(&TokenKind::LeftParen) {
+            return false;
+        }
+        let mut n = 1;
+        loop {
+            if !matches!(self.peek_nth(n).kind, TokenKind::Identifier(_)) {
+                return false;
+            }
+            match self.peek_nth(n + 1).kind {
+                TokenKind::Comma => n += 2,
+                TokenKind::RightParen => {
+                    return n > 1 && matches!(self.peek_nth(n + 2).kind, TokenKind::In)
+                }
+                _ => return false,
+            }
+        }
+    }
+
     fn break_statement(&mut self) -> ParseResult<Stmt> {
         let start_span = self.current_span();
         self.expect(&TokenKind::Break)?;
@@ -251,7 +305,7 @@ impl Parser {
 
         // Bare `return` terminates at semicolon, postfix `if`/`unless`, or
         // any token that can't start an expression: `end`, `}`, `else`,
-        // `elsif`, `catch`, `finally`, EOF. Otherwise we try to parse a
+        // `elsif`, `catch`, `finally`, `yield`, EOF. Otherwise we try to parse a
         // return value. Without this, `return\nend` (a common pattern after
         // dropped semicolons) fails to parse.
         let value = if matches!(
@@ -265,6 +319,7 @@ impl Parser {
                 | TokenKind::Elsif
                 | TokenKind::Catch
                 | TokenKind::Finally
+                | TokenKind::Yield
                 | TokenKind::Eof
         ) {
             None
@@ -395,6 +450,59 @@ impl Parser {
         Ok(throw_stmt)
     }
 
+    /// `yield expr`, with the same postfix `if`/`unless` guards as `return`.
+    fn yield_statement(&mut self) -> ParseResult<Stmt> {
+        let start_span = self.current_span();
+        self.expect(&TokenKind::Yield)?;
+
+        let value = self.expression()?;
+        let yield_end_line = self.previous_span().line;
+        let yield_stmt = Stmt::new(
+            StmtKind::Yield(value),
+            start_span.merge(&self.previous_span()),
+            None,
+        );
+
+        let negate = if self.check(&TokenKind::If) {
+            false
+        } else if self.check(&TokenKind::Unless) {
+            true
+        } else {
+            self.match_token(&TokenKind::Semicolon);
+            return Ok(yield_stmt);
+        };
+        if self.peek().span.line != yield_end_line {
+            self.match_token(&TokenKind::Semicolon);
+            return Ok(yield_stmt);
+        }
+        self.advance();
+        let has_paren = self.match_token(&TokenKind::LeftParen);
+        let mut condition = self.expression()?;
+        if has_paren {
+            self.expect(&TokenKind::RightParen)?;
+        }
+        self.match_token(&TokenKind::Semicolon);
+        let span = start_span.merge(&self.previous_span());
+        if negate {
+            condition = Expr::new(
+                ExprKind::Unary {
+                    operator: crate::ast::expr::UnaryOp::Not,
+                    operand: Box::new(condition),
+                },
+                span,
+            );
+        }
+        Ok(Stmt::new(
+            StmtKind::If {
+                condition,
+                then_branch: Box::new(yield_stmt),
+                else_branch: None,
+            },
+            span,
+            None,
+        ))
+    }
+
     fn try_statement(&mut self) -> ParseResult<Stmt> {
         use crate::ast::stmt::CatchClause;
 
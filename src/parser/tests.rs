@@ -2,7 +2,7 @@
 
 #[cfg(test)]
 mod parser_tests {
-    use crate::ast::expr::Argument;
+    use crate::ast::expr::{Argument, InterpolatedPart};
     use crate::ast::*;
     use crate::lexer::Scanner;
     use crate::parser::Parser;
@@ -55,6 +55,31 @@ mod parser_tests {
         }
     }
 
+    #[test]
+    fn test_pipeline_placeholder_and_method_piping() {
+        let expr = parse_expr("x |> add(1, _);");
+        match expr.kind {
+            ExprKind::Call { arguments, .. } => match &arguments[1] {
+                Argument::Positional(e) => {
+                    assert!(matches!(&e.kind, ExprKind::Variable(n, _) if n == "x"))
+                }
+                _ => panic!("Expected positional argument"),
+            },
+            _ => panic!("Expected placeholder pipeline to desugar into a call"),
+        }
+
+        let expr = parse_expr("s |> .trim() |> .upcase();");
+        match expr.kind {
+            ExprKind::Call { callee, .. } => {
+                assert!(matches!(&callee.kind, ExprKind::Member { name, .. } if name == "upcase"))
+            }
+            _ => panic!("Expected method pipeline to desugar into a method call"),
+        }
+
+        let mut parser = Parser::new(Scanner::new("x |> add(_, _);").scan_tokens().unwrap());
+        assert!(parser.parse().is_err());
+    }
+
     #[test]
     fn test_call() {
         let expr = parse_expr("foo(1, 2);");
@@ -152,6 +177,27 @@ mod parser_tests {
         }
     }
 
+    #[test]
+    fn test_match_tuple_pattern() {
+        let expr = parse_expr("match t { (x, (a, _)) => x, (y,) => y, (z) => z };");
+        match expr.kind {
+            ExprKind::Match { arms, .. } => {
+                match &arms[0].pattern {
+                    MatchPattern::Tuple(elements) => {
+                        assert_eq!(elements.len(), 2);
+                        assert!(
+                            matches!(&elements[1], MatchPattern::Tuple(inner) if inner.len() == 2)
+                        );
+                    }
+                    _ => panic!("Expected tuple pattern"),
+                }
+                assert!(matches!(&arms[1].pattern, MatchPattern::Tuple(e) if e.len() == 1));
+                assert!(matches!(&arms[2].pattern, MatchPattern::Variable(name) if name == "z"));
+            }
+            _ => panic!("Expected match expression"),
+        }
+    }
+
     #[test]
     fn test_not_keyword() {
         let expr = parse_expr("not true;");
@@ -329,7 +375,7 @@ mod parser_tests {
         let stmts = parse_stmts(source);
         match stmts.into_iter().next().unwrap() {
             StmtKind::Let { initializer, .. } => match initializer.unwrap().kind {
-                ExprKind::Lambda { params, body, .. } => (params, body),
+                ExprKind::Lambda { params, body, .. } => (params, body.to_vec()),
                 other => panic!("Expected lambda, got {:?}", other),
             },
             other => panic!("Expected let, got {:?}", other),
@@ -339,7 +385,7 @@ mod parser_tests {
     /// Extract function body from a single function declaration
     fn parse_fn_body(source: &str) -> Vec<Stmt> {
         match parse_stmts(source).into_iter().next().unwrap() {
-            StmtKind::Function(f) => f.body,
+            StmtKind::Function(f) => f.body.to_vec(),
             other => panic!("Expected function, got {:?}", other),
         }
     }
@@ -739,7 +785,7 @@ mod parser_tests {
                 match &arguments[0] {
                     Argument::Positional(arg_expr) => {
                         let (params, body) = match &arg_expr.kind {
-                            ExprKind::Lambda { params, body, .. } => (params, body),
+                            ExprKind::Lambda { params, body, .. } => (params, body.to_vec()),
                             other => panic!("Expected lambda, got {:?}", other),
                         };
                         assert_eq!(params.len(), 1);
@@ -844,6 +890,115 @@ mod parser_tests {
         }
     }
 
+    #[test]
+    fn test_move_lambda() {
+        let lambda = |source: &str| match parse_stmts(source).into_iter().next().unwrap() {
+            StmtKind::Let { initializer, .. } => initializer.unwrap().kind,
+            other => panic!("Expected let, got {:?}", other),
+        };
+        for source in [
+            "let f = move fn(x) { x };",
+            "let f = move |x| { x };",
+            "let f = move -> x { x };",
+        ] {
+            match lambda(source) {
+                ExprKind::Lambda {
+                    params, is_move, ..
+                } => {
+                    assert!(is_move, "{source}");
+                    assert_eq!(params.len(), 1);
+                }
+                other => panic!("Expected lambda, got {:?}", other),
+            }
+        }
+        assert!(matches!(
+            lambda("let f = fn(x) { x };"),
+            ExprKind::Lambda { is_move: false, .. }
+        ));
+        // Anywhere else `move` is an identifier.
+        assert!(matches!(
+            lambda("let y = move + 1;"),
+            ExprKind::Binary { .. }
+        ));
+    }
+
+    #[test]
+    fn test_keyword_only_params() {
+        let params = match parse_stmts("def f(a, b = 1, *, c, d = 2, &blk) { return 0 }")
+            .into_iter()
+            .next()
+            .unwrap()
+        {
+            StmtKind::Function(f) => f.params,
+            other => panic!("Expected function, got {:?}", other),
+        };
+        let keyword_only: Vec<_> = params
+            .iter()
+            .filter(|p| p.is_keyword_only)
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(keyword_only, ["c", "d"]);
+        assert!(params[1].default_value.is_some());
+        assert!(params[4].is_block_param && !params[4].is_keyword_only);
+
+        assert!(parse_should_fail("def f(a, *, b, *, c) { return 0 }"));
+        assert!(parse_should_fail("def f(a, *) { return 0 }"));
+    }
+
+    #[test]
+    fn test_variadic_params_and_spread_args() {
+        let params = match parse_stmts("def f(a, ...rest: Array<Int>, flag = 0) { return 0 }")
+            .into_iter()
+            .next()
+            .unwrap()
+        {
+            StmtKind::Function(f) => f.params,
+            other => panic!("Expected function, got {:?}", other),
+        };
+        assert!(params[1].is_variadic && !params[1].is_keyword_only);
+        assert!(params[2].is_keyword_only);
+
+        assert!(parse_should_fail("def f(...a, ...b) { return 0 }"));
+        assert!(parse_should_fail("def f(a, *, ...b) { return 0 }"));
+        assert!(parse_should_fail("def f(...a = []) { return 0 }"));
+
+        match parse_expr("f(1, *xs, key: 2)").kind {
+            ExprKind::Call { arguments, .. } => assert!(matches!(
+                &arguments[..],
+                [
+                    Argument::Positional(_),
+                    Argument::Spread(_),
+                    Argument::Named(_)
+                ]
+            )),
+            other => panic!("Expected call, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_bare_call_with_block() {
+        for source in ["at_exit { flush() }", "at_exit do\n  flush()\nend"] {
+            match parse_expr(source).kind {
+                ExprKind::Call { callee, arguments } => {
+                    assert!(matches!(&callee.kind, ExprKind::Variable(n, _) if n == "at_exit"));
+                    assert!(matches!(&arguments[..], [Argument::Block(_)]));
+                }
+                other => panic!("Expected call, got {:?}", other),
+            }
+        }
+        // In a loop head the brace still opens the body.
+        match parse_stmts("while running { tick() }")
+            .into_iter()
+            .next()
+            .unwrap()
+        {
+            StmtKind::While { condition, .. } => {
+                assert!(matches!(condition.kind, ExprKind::Variable(_, _)));
+            }
+            other => panic!("Expected while, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_inline_lambda_with_comparison() {
         // Lambda body is a comparison expression
@@ -1307,7 +1462,7 @@ mod parser_tests {
         match expr.kind {
             ExprKind::SafeMember { object, name } => {
                 assert_eq!(name, "name");
-                assert!(matches!(object.kind, ExprKind::Variable(ref v) if v == "user"));
+                assert!(matches!(object.kind, ExprKind::Variable(ref v, _) if v == "user"));
             }
             _ => panic!("Expected SafeMember, got {:?}", expr.kind),
         }
@@ -1322,7 +1477,7 @@ mod parser_tests {
                 match callee.kind {
                     ExprKind::SafeMember { object, name } => {
                         assert_eq!(name, "greet");
-                        assert!(matches!(object.kind, ExprKind::Variable(ref v) if v == "user"));
+                        assert!(matches!(object.kind, ExprKind::Variable(ref v, _) if v == "user"));
                     }
                     _ => panic!("Expected SafeMember callee, got {:?}", callee.kind),
                 }
@@ -1343,7 +1498,7 @@ mod parser_tests {
                         name: inner_name,
                     } => {
                         assert_eq!(inner_name, "address");
-                        assert!(matches!(inner.kind, ExprKind::Variable(ref v) if v == "user"));
+                        assert!(matches!(inner.kind, ExprKind::Variable(ref v, _) if v == "user"));
                     }
                     _ => panic!("Expected inner SafeMember"),
                 }
@@ -1417,6 +1572,18 @@ mod parser_tests {
         assert!(matches!(stmts[1], StmtKind::If { .. }));
     }
 
+    #[test]
+    fn test_yield_statement_and_postfix_yield() {
+        let stmts = parse_stmts("def count()\n  yield 1\n  yield 2 if true\nend\n");
+        let decl = match &stmts[0] {
+            StmtKind::Function(decl) => decl,
+            other => panic!("expected function, got {:?}", other),
+        };
+        assert!(matches!(decl.body[0].kind, StmtKind::Yield(_)));
+        assert!(matches!(decl.body[1].kind, StmtKind::If { .. }));
+        assert!(crate::ast::stmt::body_yields(&decl.body));
+    }
+
     #[test]
     fn test_expression_followed_by_if_block_with_return() {
         // Full pattern from controllers: expr, if/end, return
@@ -1504,6 +1671,39 @@ mod parser_tests {
         }
     }
 
+    #[test]
+    fn test_destructuring_and_combined_match_patterns() {
+        let stmts = parse_stmts(
+            "let r = match p {\n  Point { x: 0, y: y } => y,\n  1 | 2 => 3,\n  n: Int & 5 => n,\n  _ => 0,\n}\n",
+        );
+        let arms = match &stmts[0] {
+            StmtKind::Let {
+                initializer: Some(expr),
+                ..
+            } => match &expr.kind {
+                ExprKind::Match { arms, .. } => arms,
+                other => panic!("expected match expression, got {:?}", other),
+            },
+            other => panic!("expected let with match, got {:?}", other),
+        };
+        match &arms[0].pattern {
+            MatchPattern::Destructuring { type_name, fields } => {
+                assert_eq!(type_name, "Point");
+                assert_eq!(fields.len(), 2);
+                assert_eq!(fields[1].0, "y");
+            }
+            other => panic!("expected destructuring pattern, got {:?}", other),
+        }
+        assert!(matches!(&arms[1].pattern, MatchPattern::Or(alts) if alts.len() == 2));
+        match &arms[2].pattern {
+            MatchPattern::And(patterns) => {
+                assert!(matches!(patterns[0], MatchPattern::Typed { .. }));
+                assert!(matches!(patterns[1], MatchPattern::Literal(_)));
+            }
+            other => panic!("expected and-pattern, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_match_accepts_both_brace_and_end_forms() {
         // Brace form.
@@ -1565,4 +1765,241 @@ mod parser_tests {
             other => panic!("expected let, got {:?}", other),
         }
     }
+
+    #[test]
+    fn test_interpolated_string_parts_and_spans() {
+        let expr = parse_expr("\"n=#{a + 1}!\";");
+        match expr.kind {
+            ExprKind::InterpolatedString(parts) => {
+                assert_eq!(parts.len(), 3);
+                match &parts[1] {
+                    InterpolatedPart::Expression(e) => {
+                        assert!(matches!(e.kind, ExprKind::Binary { .. }));
+                        // Spans point into the original source, not the fragment.
+                        assert_eq!((e.span.line, e.span.column), (1, 6));
+                    }
+                    other => panic!("expected expression part, got {:?}", other),
+                }
+            }
+            other => panic!("expected interpolated string, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_interpolation_errors_report_source_position() {
+        let parse_err = |source: &str| {
+            let tokens = Scanner::new(source).scan_tokens().unwrap();
+            Parser::new(tokens).parse().unwrap_err()
+        };
+        let err = parse_err("let s = \"x #{1 +} y\";");
+        assert_eq!((err.span().line, err.span().column), (1, 16));
+        let err = parse_err("let s = \"x #{1 2} y\";");
+        assert!(err.to_string().contains("to close the interpolation"));
+        assert_eq!((err.span().line, err.span().column), (1, 16));
+    }
+
+    #[test]
+    fn test_tuple_literal_and_destructuring() {
+        let expr = parse_expr("(1, \"a\",);");
+        assert!(matches!(expr.kind, ExprKind::Tuple(ref items) if items.len() == 2));
+        // A single parenthesised expression stays a grouping.
+        let expr = parse_expr("(1);");
+        assert!(!matches!(expr.kind, ExprKind::Tuple(_)));
+
+        let tokens = Scanner::new("let (a, b) = pair; for (k, v) in h { print(k); }")
+            .scan_tokens()
+            .unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        match &program.statements[0].kind {
+            StmtKind::LetTuple { names, .. } => assert_eq!(names, &["a", "b"]),
+            other => panic!("expected LetTuple, got {:?}", other),
+        }
+        match &program.statements[1].kind {
+            StmtKind::For { destructure, .. } => {
+                assert_eq!(
+                    destructure.as_deref(),
+                    Some(&["k".to_string(), "v".to_string()][..])
+                )
+            }
+            other => panic!("expected For, got {:?}", other),
+        }
+
+        let tokens = Scanner::new("for (a, b, c) in rows { print(a); }")
+            .scan_tokens()
+            .unwrap();
+        let err = Parser::new(tokens).parse().unwrap_err();
+        assert!(err.to_string().contains("takes two names"), "{}", err);
+    }
+
+    #[test]
+    fn test_struct_declaration() {
+        let tokens = Scanner::new("struct Money { amount: Int, currency: String = \"EUR\" }")
+            .scan_tokens()
+            .unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        match &program.statements[0].kind {
+            StmtKind::Struct(decl) => {
+                assert_eq!(decl.name, "Money");
+                let names: Vec<_> = decl.fields.iter().map(|f| f.name.as_str()).collect();
+                assert_eq!(names, ["amount", "currency"]);
+                assert!(decl.fields[0].default_value.is_none());
+                assert!(decl.fields[1].default_value.is_some());
+            }
+            other => panic!("expected Struct, got {:?}", other),
+        }
+
+        let tokens = Scanner::new("struct P\n  x: Int\n  x: Int\nend")
+            .scan_tokens()
+            .unwrap();
+        let err = Parser::new(tokens).parse().unwrap_err();
+        assert!(err.to_string().contains("duplicate field 'x'"));
+    }
+
+    #[test]
+    fn test_decorators() {
+        let source = "@cached\n@route(\"/users\", method: \"GET\")\nfn index(req) { req }\n\n@route(\"/admin\")\nexport class Admin\n  @deprecated(\"old\")\n  static def stats() 1 end\nend";
+        let tokens = Scanner::new(source).scan_tokens().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        match &program.statements[0].kind {
+            StmtKind::Function(decl) => {
+                let names: Vec<_> = decl.decorators.iter().map(|d| d.name.as_str()).collect();
+                assert_eq!(names, ["cached", "route"]);
+                assert!(decl.decorators[0].arguments.is_empty());
+                assert_eq!(decl.decorators[1].arguments.len(), 2);
+            }
+            other => panic!("expected Function, got {:?}", other),
+        }
+        match &program.statements[1].kind {
+            StmtKind::Export(inner) => match &inner.kind {
+                StmtKind::Class(decl) => {
+                    assert_eq!(decl.decorators[0].name, "route");
+                    assert_eq!(decl.methods[0].decorators[0].name, "deprecated");
+                    assert!(decl.methods[0].is_static);
+                }
+                other => panic!("expected Class, got {:?}", other),
+            },
+            other => panic!("expected Export, got {:?}", other),
+        }
+
+        let tokens = Scanner::new("@cached\nexport let x = 1")
+            .scan_tokens()
+            .unwrap();
+        let err = Parser::new(tokens).parse().unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("decorators must be followed by a function or class declaration"));
+    }
+
+    #[test]
+    fn test_async_and_await() {
+        let source = "async fn load(id) { await fetch(id) }\nclass Api\n  @cached\n  async def all() await(1) end\nend\nlet async = 1\nlet v = await";
+        let tokens = Scanner::new(source).scan_tokens().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        match &program.statements[0].kind {
+            StmtKind::Function(decl) => {
+                assert!(decl.is_async);
+                match &decl.body[0].kind {
+                    StmtKind::Expression(expr) => {
+                        assert!(matches!(expr.kind, ExprKind::Await(_)))
+                    }
+                    other => panic!("expected await expression, got {:?}", other),
+                }
+            }
+            other => panic!("expected Function, got {:?}", other),
+        }
+        match &program.statements[1].kind {
+            StmtKind::Class(decl) => {
+                assert!(decl.methods[0].is_async);
+                assert_eq!(decl.methods[0].decorators[0].name, "cached");
+            }
+            other => panic!("expected Class, got {:?}", other),
+        }
+        // Without `fn` / an operand after them, both stay identifiers.
+        assert!(matches!(program.statements[2].kind, StmtKind::Let { .. }));
+        match &program.statements[3].kind {
+            StmtKind::Let {
+                initializer: Some(init),
+                ..
+            } => assert!(matches!(init.kind, ExprKind::Variable(_, _))),
+            other => panic!("expected Let, got {:?}", other),
+        }
+
+        let tokens = Scanner::new("async fn gen() { yield 1; }")
+            .scan_tokens()
+            .unwrap();
+        let err = Parser::new(tokens).parse().unwrap_err();
+        assert!(err.to_string().contains("async functions cannot yield"));
+    }
+
+    #[test]
+    fn test_platform_guards() {
+        use crate::parser::platform::Platform;
+
+        let source = "@cfg(os: \"windows\")\nfn sep() { \"\\\\\" }\n@cfg(family: [\"unix\"])\nfn sep() { \"/\" }\nclass Proc\n  @cfg(os: \"linux\", arch: \"aarch64\")\n  def arm() 1 end\nend\nif_platform(\"windows\", \"macos\") {\n  let home = 1\n}\nlet x = 2";
+        let linux = Platform {
+            os: "linux".to_string(),
+            family: "unix".to_string(),
+            arch: "x86_64".to_string(),
+        };
+        let parse = |platform: Option<Platform>| {
+            let tokens = Scanner::new(source).scan_tokens().unwrap();
+            let parser = Parser::new(tokens);
+            let mut parser = match platform {
+                Some(platform) => parser.with_platform(platform),
+                None => parser.keeping_platform_code(),
+            };
+            parser.parse().unwrap()
+        };
+
+        let program = parse(Some(linux.clone()));
+        assert_eq!(program.statements.len(), 3);
+        match &program.statements[0].kind {
+            StmtKind::Function(decl) => assert!(decl.decorators.is_empty()),
+            other => panic!("expected Function, got {:?}", other),
+        }
+        match &program.statements[1].kind {
+            StmtKind::Class(decl) => assert!(decl.methods.is_empty()),
+            other => panic!("expected Class, got {:?}", other),
+        }
+
+        let macos = Platform {
+            os: "macos".to_string(),
+            ..linux
+        };
+        let program = parse(Some(macos));
+        assert_eq!(program.statements.len(), 4);
+        assert!(
+            matches!(&program.statements[2].kind, StmtKind::Let { name, .. } if name == "home")
+        );
+
+        assert_eq!(parse(None).statements.len(), 5);
+
+        let tokens = Scanner::new("@cfg(platform: \"linux\")\nfn f() { 1 }")
+            .scan_tokens()
+            .unwrap();
+        let err = Parser::new(tokens).parse().unwrap_err();
+        assert!(err.to_string().contains("unknown @cfg key 'platform'"));
+    }
+
+    #[test]
+    fn test_type_alias_of_record() {
+        match parse_stmt("type User = { id: Int, \"content-type\": String, email: String? }") {
+            StmtKind::TypeAlias(decl) => {
+                assert_eq!(decl.name, "User");
+                assert_eq!(
+                    decl.type_annotation.to_string(),
+                    "{ id: Int, content-type: String, email: String? }"
+                );
+            }
+            other => panic!("expected TypeAlias, got {:?}", other),
+        }
+        // Anywhere else `type` is still the builtin.
+        assert!(matches!(parse_stmt("type(1)"), StmtKind::Expression(_)));
+
+        let tokens = Scanner::new("type T = { id: Int, id: String }")
+            .scan_tokens()
+            .unwrap();
+        let err = Parser::new(tokens).parse().unwrap_err();
+        assert!(err.to_string().contains("duplicate field 'id'"));
+    }
 }
@@ -37,6 +37,12 @@ impl Parser {
                 self.advance();
                 if name == "Fn" && self.check(&TokenKind::LeftParen) {
                     self.parse_function_type(start_span)?
+                } else if name == "Array" && self.match_token(&TokenKind::Less) {
+                    // `Array<T>` spells `T[]`.
+                    let element = self.parse_type()?;
+                    self.expect(&TokenKind::Greater)?;
+                    let span = start_span.merge(&self.previous_span());
+                    TypeAnnotation::new(TypeKind::Array(Box::new(element)), span)
                 } else if name.ends_with('?') {
                     // Handle nullable suffix attached by lexer (e.g., "String?" -> Nullable(Named("String")))
                     let base_name = name[..name.len() - 1].to_string();
@@ -50,6 +56,7 @@ impl Parser {
                 self.advance();
                 self.parse_function_type(start_span)?
             }
+            TokenKind::LeftBrace => self.parse_record_type(start_span)?,
             TokenKind::LeftParen => {
                 self.advance();
                 let mut params = Vec::new();
@@ -60,16 +67,25 @@ impl Parser {
                     }
                 }
                 self.expect(&TokenKind::RightParen)?;
-                self.expect(&TokenKind::Arrow)?;
-                let return_type = Box::new(self.parse_type()?);
-                let span = start_span.merge(&return_type.span);
-                TypeAnnotation::new(
-                    TypeKind::Function {
-                        params,
-                        return_type,
-                    },
-                    span,
-                )
+                if params.len() >= 2 && !self.check(&TokenKind::Arrow) {
+                    // `(A, B)` with no `-> R` is a tuple.
+                    let span = start_span.merge(&self.previous_span());
+                    TypeAnnotation::new(TypeKind::Tuple(params), span)
+                } else if params.len() == 1 && !self.check(&TokenKind::Arrow) {
+                    // `(T)` just groups, e.g. `(Int[])?`.
+                    params.pop().unwrap()
+                } else {
+                    self.expect(&TokenKind::Arrow)?;
+                    let return_type = Box::new(self.parse_type()?);
+                    let span = start_span.merge(&return_type.span);
+                    TypeAnnotation::new(
+                        TypeKind::Function {
+                            params,
+                            return_type,
+                        },
+                        span,
+                    )
+                }
             }
             _ => {
                 return Err(ParserError::unexpected_token(
@@ -119,16 +135,108 @@ impl Parser {
         ))
     }
 
+    /// `{ id: Int, "content-type": String, email: String? }` — a hash whose
+    /// keys are known. Keys are identifiers or string literals.
+    fn parse_record_type(&mut self, start_span: Span) -> ParseResult<TypeAnnotation> {
+        self.expect(&TokenKind::LeftBrace)?;
+        let mut fields: Vec<RecordField> = Vec::new();
+        while !self.check(&TokenKind::RightBrace) && !self.is_at_end() {
+            let field_span = self.current_span();
+            let name = if let TokenKind::StringLiteral(name) = &self.peek().kind {
+                let name = name.clone();
+                self.advance();
+                name
+            } else {
+                self.expect_identifier()?
+            };
+            if fields.iter().any(|f| f.name == name) {
+                return Err(ParserError::general(
+                    format!("duplicate field '{}' in record type", name),
+                    field_span,
+                ));
+            }
+            self.expect(&TokenKind::Colon)?;
+            let type_annotation = self.parse_type()?;
+            fields.push(RecordField {
+                name,
+                type_annotation,
+                span: field_span.merge(&self.previous_span()),
+            });
+            if !self.match_token(&TokenKind::Comma) {
+                break;
+            }
+        }
+        self.expect(&TokenKind::RightBrace)?;
+        let span = start_span.merge(&self.previous_span());
+        Ok(TypeAnnotation::new(TypeKind::Record(fields), span))
+    }
+
     pub(crate) fn parse_parameters(&mut self) -> ParseResult<Vec<Parameter>> {
         if !self.match_token(&TokenKind::LeftParen) {
             return Ok(Vec::new());
         }
 
         let mut params = Vec::new();
+        // A bare `*` ends the positional parameters: everything after it is
+        // keyword-only (`def connect(host, *, port = 5432)`). So does a
+        // variadic `...rest`, which also collects the extra positionals.
+        let mut keyword_only = false;
         if !self.check(&TokenKind::RightParen) {
-            params.push(self.parse_parameter()?);
-            while self.match_token(&TokenKind::Comma) {
-                params.push(self.parse_parameter()?);
+            loop {
+                if self.check(&TokenKind::Spread) {
+                    let spread_span = self.current_span();
+                    self.advance();
+                    if keyword_only {
+                        return Err(ParserError::general(
+                            "a variadic parameter must come before `*` and keyword-only parameters"
+                                .to_string(),
+                            spread_span,
+                        ));
+                    }
+                    let mut param = self.parse_parameter()?;
+                    if param.is_block_param || param.default_value.is_some() {
+                        return Err(ParserError::general(
+                            format!(
+                                "variadic parameter '{}' can't be a block or have a default",
+                                param.name
+                            ),
+                            param.span,
+                        ));
+                    }
+                    param.is_variadic = true;
+                    param.span = spread_span.merge(&param.span);
+                    params.push(param);
+                    keyword_only = true;
+                    if !self.match_token(&TokenKind::Comma) {
+                        break;
+                    }
+                    continue;
+                }
+                if self.check(&TokenKind::Star) {
+                    let star_span = self.current_span();
+                    self.advance();
+                    if keyword_only {
+                        return Err(ParserError::general(
+                            "only one `*` or variadic parameter is allowed in a parameter list"
+                                .to_string(),
+                            star_span,
+                        ));
+                    }
+                    if !self.match_token(&TokenKind::Comma) {
+                        return Err(ParserError::general(
+                            "`*` must be followed by keyword-only parameters".to_string(),
+                            star_span,
+                        ));
+                    }
+                    keyword_only = true;
+                    continue;
+                }
+                let mut param = self.parse_parameter()?;
+                param.is_keyword_only = keyword_only && !param.is_block_param;
+                params.push(param);
+                if !self.match_token(&TokenKind::Comma) {
+                    break;
+                }
             }
         }
 
@@ -166,6 +274,8 @@ impl Parser {
             default_value,
             span,
             is_block_param,
+            is_keyword_only: false,
+            is_variadic: false,
         })
     }
 }
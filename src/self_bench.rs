@@ -0,0 +1,224 @@
+//! Engine benchmarks shared by `soli self-bench` and `benches/engine.rs`.
+//!
+//! Each [`Workload`] is a representative program — template-style
+//! rendering, JSON handling, ORM-style loops — that defines a `run()`
+//! function. A [`Bench`] prepares one workload for every [`Phase`], so each
+//! phase times only its own stage: lexing the source, parsing its tokens,
+//! type-checking and compiling its AST, or calling `run()` on an interpreter
+//! or VM that has already loaded the program. Criterion drives the same
+//! phases in `cargo bench --bench engine`; `soli self-bench` times them with
+//! [`measure`] so a release binary can report its own numbers.
+
+use std::time::{Duration, Instant};
+
+use crate::ast::Program;
+use crate::interpreter::value::Value;
+use crate::interpreter::Interpreter;
+use crate::lexer::{Scanner, Token};
+use crate::parser::Parser;
+use crate::span::Span;
+use crate::types::TypeChecker;
+use crate::vm::{Compiler, Vm};
+
+/// A benchmark program. Its `run()` function is the timed part.
+#[derive(Debug, Clone, Copy)]
+pub struct Workload {
+    pub name: &'static str,
+    pub source: &'static str,
+}
+
+/// The programs `soli self-bench` measures.
+pub const WORKLOADS: &[Workload] = &[
+    Workload {
+        name: "template",
+        source: include_str!("../benches/programs/workload_template.sl"),
+    },
+    Workload {
+        name: "json",
+        source: include_str!("../benches/programs/workload_json.sl"),
+    },
+    Workload {
+        name: "orm",
+        source: include_str!("../benches/programs/workload_orm.sl"),
+    },
+];
+
+/// A stage of the engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Lex,
+    Parse,
+    Typecheck,
+    Compile,
+    Interpreter,
+    Vm,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 6] = [
+        Phase::Lex,
+        Phase::Parse,
+        Phase::Typecheck,
+        Phase::Compile,
+        Phase::Interpreter,
+        Phase::Vm,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Lex => "lex",
+            Phase::Parse => "parse",
+            Phase::Typecheck => "typecheck",
+            Phase::Compile => "compile",
+            Phase::Interpreter => "interpreter",
+            Phase::Vm => "vm",
+        }
+    }
+}
+
+/// One workload, loaded and ready to run any phase.
+pub struct Bench {
+    workload: Workload,
+    tokens: Vec<Token>,
+    program: Program,
+    interpreter: Interpreter,
+    interpreter_run: Value,
+    vm: Vm,
+    vm_run: Value,
+}
+
+impl Bench {
+    /// Lex, parse and check the workload, and load it into both engines.
+    pub fn new(workload: Workload) -> Result<Self, String> {
+        let fail = |stage: &str, e: String| format!("{}: {}: {}", workload.name, stage, e);
+        let tokens = Scanner::new(workload.source)
+            .scan_tokens()
+            .map_err(|e| fail("lex", e.to_string()))?;
+        let program = Parser::new(tokens.clone())
+            .parse()
+            .map_err(|e| fail("parse", e.to_string()))?;
+        check(&program).map_err(|e| fail("typecheck", e))?;
+
+        let mut interpreter = Interpreter::new();
+        interpreter
+            .interpret(&program)
+            .map_err(|e| fail("interpreter", e.to_string()))?;
+        let interpreter_run = interpreter
+            .environment
+            .borrow()
+            .get("run")
+            .ok_or_else(|| fail("interpreter", "no run() function".to_string()))?;
+
+        let module = Compiler::compile(&program).map_err(|e| fail("compile", e.to_string()))?;
+        let mut vm = crate::seeded_vm();
+        let executed = vm.execute(&module.main);
+        vm.reset();
+        executed.map_err(|e| fail("vm", e.to_string()))?;
+        let vm_run = vm
+            .globals
+            .get("run")
+            .cloned()
+            .ok_or_else(|| fail("vm", "no run() function".to_string()))?;
+
+        Ok(Self {
+            workload,
+            tokens,
+            program,
+            interpreter,
+            interpreter_run,
+            vm,
+            vm_run,
+        })
+    }
+
+    pub fn workload(&self) -> Workload {
+        self.workload
+    }
+
+    /// Run one phase once. `Parse` includes copying the token stream, since
+    /// the parser consumes it.
+    pub fn run(&mut self, phase: Phase) -> Result<(), String> {
+        let span = Span::new(0, 0, 1, 1);
+        match phase {
+            Phase::Lex => Scanner::new(self.workload.source)
+                .scan_tokens()
+                .map(drop)
+                .map_err(|e| e.to_string()),
+            Phase::Parse => Parser::new(self.tokens.clone())
+                .parse()
+                .map(drop)
+                .map_err(|e| e.to_string()),
+            Phase::Typecheck => check(&self.program),
+            Phase::Compile => Compiler::compile(&self.program)
+                .map(drop)
+                .map_err(|e| e.to_string()),
+            Phase::Interpreter => self
+                .interpreter
+                .call_value(self.interpreter_run.clone(), Vec::new(), span)
+                .map(drop)
+                .map_err(|e| e.to_string()),
+            Phase::Vm => {
+                let result = self
+                    .vm
+                    .invoke_callable(self.vm_run.clone(), Vec::new(), span);
+                self.vm.reset();
+                result.map(drop).map_err(|e| e.to_string())
+            }
+        }
+    }
+}
+
+fn check(program: &Program) -> Result<(), String> {
+    TypeChecker::new().check(program).map_err(|errors| {
+        errors
+            .first()
+            .map(|e| e.to_string())
+            .unwrap_or_else(|| "type error".to_string())
+    })
+}
+
+/// What [`measure`] found for one phase of one workload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseResult {
+    pub workload: &'static str,
+    pub phase: Phase,
+    pub iterations: u64,
+    pub ns_per_iter: f64,
+}
+
+/// Time `phase` of `bench`: one untimed run, then as many as fit in `time`.
+pub fn measure(bench: &mut Bench, phase: Phase, time: Duration) -> Result<PhaseResult, String> {
+    bench.run(phase)?;
+    let start = Instant::now();
+    let mut iterations = 0u64;
+    loop {
+        bench.run(phase)?;
+        iterations += 1;
+        if start.elapsed() >= time {
+            break;
+        }
+    }
+    Ok(PhaseResult {
+        workload: bench.workload.name,
+        phase,
+        iterations,
+        ns_per_iter: start.elapsed().as_nanos() as f64 / iterations as f64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_workload_runs_every_phase() {
+        for workload in WORKLOADS {
+            let mut bench = Bench::new(*workload).unwrap();
+            for phase in Phase::ALL {
+                let result = measure(&mut bench, phase, Duration::ZERO)
+                    .unwrap_or_else(|e| panic!("{} {}: {}", workload.name, phase.name(), e));
+                assert_eq!(result.iterations, 1);
+            }
+        }
+    }
+}
@@ -15,6 +15,7 @@ use crate::serve::middleware::{
     scan_middleware_files,
 };
 use crate::serve::router::{derive_routes_from_controller, to_pascal_case_controller};
+use crate::serve::unit_cache;
 use crate::span::Span;
 
 /// Scan for all controller files in the controllers directory, walking
@@ -163,7 +164,7 @@ pub(crate) fn sort_controllers_by_dependency(controllers: &mut Vec<PathBuf>) {
 
 /// Derive routes from a controller file that may be source text or a
 /// serialized AST (protected bundle).
-fn derive_routes_for_file(
+pub(crate) fn derive_routes_for_file(
     path: &Path,
     route_input: &str,
 ) -> Result<Vec<crate::serve::router::ControllerRoute>, RuntimeError> {
@@ -286,22 +287,8 @@ pub(crate) fn job_class_name_from_path(job_path: &Path) -> String {
 /// controller action, and register `POST /_jobs/run/:name` to dispatch to it.
 fn register_jobs_callback(worker_id: usize, interpreter: &mut Interpreter) {
     let source = crate::interpreter::builtins::jobs::JOBS_CALLBACK_PRELUDE;
-    let tokens = match crate::lexer::Scanner::new(source).scan_tokens() {
-        Ok(t) => t,
-        Err(e) => {
-            eprintln!("Worker {}: jobs prelude lex error: {}", worker_id, e);
-            return;
-        }
-    };
-    let program = match crate::parser::Parser::new(tokens).parse() {
-        Ok(p) => p,
-        Err(e) => {
-            eprintln!("Worker {}: jobs prelude parse error: {}", worker_id, e);
-            return;
-        }
-    };
-    if let Err(e) = interpreter.interpret(&program) {
-        eprintln!("Worker {}: jobs prelude execute error: {}", worker_id, e);
+    if let Err(e) = interpreter.interpret_synthetic("jobs_prelude", source) {
+        eprintln!("Worker {}: jobs prelude error: {}", worker_id, e);
         return;
     }
 
@@ -382,7 +369,7 @@ pub(crate) fn load_jobs_in_worker(
                 "Worker {}: Error loading {}: {}",
                 worker_id,
                 path.display(),
-                e
+                e.report()
             );
             continue;
         }
@@ -414,11 +401,21 @@ pub(crate) fn load_jobs_in_worker(
             class_value,
         );
 
-        // Worker 0 only: upsert `static cron` schedules to SolidB.
+        // Worker 0 only: upsert `static cron` schedules to SolidB. Under a
+        // scheduler lease a follower only remembers them, for when it takes
+        // the lease over.
         if worker_id == 0 && sync_cron {
             if let Some(expr) = crate::interpreter::builtins::jobs::read_static_cron(&class_rc) {
                 let cron_name =
                     crate::interpreter::builtins::jobs::class_name_to_snake(&expected_class);
+                crate::interpreter::builtins::jobs::remember_static_cron(
+                    &cron_name,
+                    &expr,
+                    &expected_class,
+                );
+                if !crate::interpreter::builtins::jobs::is_scheduler_leader() {
+                    continue;
+                }
                 if let Err(e) = crate::interpreter::builtins::jobs::register_static_cron(
                     &cron_name,
                     &expr,
@@ -657,57 +654,130 @@ pub(crate) fn load_controller(
     Ok(())
 }
 
-/// Execute a Soli file with the given interpreter.
+/// Execute a Soli file with the given interpreter. The parse is shared with
+/// the other workers through the unit cache, so only a file whose content
+/// changed is parsed again.
 pub(crate) fn execute_file(interpreter: &mut Interpreter, path: &Path) -> Result<(), RuntimeError> {
     let bytes = std::fs::read(path).map_err(|e| RuntimeError::General {
         message: format!("Failed to read file '{}': {}", path.display(), e),
         span: Span::default(),
     })?;
+    let unit = unit_cache::get_or_parse(path, &bytes, || load_unit(path, &bytes))?;
+
+    interpreter.set_source_path(path.to_path_buf());
+    if !crate::has_imports(&unit) {
+        return interpreter.interpret(&unit);
+    }
+
+    // Module resolution reads the imported files, which the cache entry's
+    // hash doesn't cover, so it runs on every load.
+    let base_dir = path.parent().unwrap_or(std::path::Path::new("."));
+    let mut resolver = crate::module::ModuleResolver::new(base_dir);
+    let mut program =
+        resolver
+            .resolve((*unit).clone(), path)
+            .map_err(|e| RuntimeError::General {
+                message: format!("Module resolution error in {}: {}", path.display(), e),
+                span: Span::default(),
+            })?;
+    crate::optimize::fold_constants(&mut program);
+
+    // Execute (skip type checking for flexibility)
+    interpreter.interpret(&program)
+}
+
+/// A file's unit: the one `soli build --precompile` saved next to it when it
+/// was built from this exact content, otherwise parsed from the content.
+fn load_unit(path: &Path, bytes: &[u8]) -> Result<crate::ast::Program, RuntimeError> {
+    let compiled = crate::vm::bytecode::cache_path(path);
+    match crate::vm::bytecode::load_fresh_unit(&compiled, bytes) {
+        Some(program) => Ok(program),
+        None => parse_unit(path, bytes),
+    }
+}
+
+/// The app directories whose `.sl` files serve loads through [`execute_file`].
+const PRECOMPILED_DIRS: &[&str] = &["app", "config", "engines"];
+
+/// `soli build --precompile`: save the unit of every `.sl` file serve loads
+/// from `app_dir` as a `.slc` next to it, so workers skip lexing and parsing
+/// it while its content is unchanged. Returns each file written and its size.
+pub fn precompile_app(app_dir: &Path) -> Result<Vec<(PathBuf, usize)>, String> {
+    fn walk(dir: &Path, out: &mut Vec<PathBuf>) -> std::io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                walk(&path, out)?;
+            } else if path.extension().is_some_and(|ext| ext == "sl") {
+                out.push(path);
+            }
+        }
+        Ok(())
+    }
 
-    let mut program = if crate::bundle::is_ast_blob(&bytes) {
+    let mut sources = Vec::new();
+    for dir in PRECOMPILED_DIRS {
+        let dir = app_dir.join(dir);
+        if dir.is_dir() {
+            walk(&dir, &mut sources)
+                .map_err(|e| format!("Failed to read '{}': {}", dir.display(), e))?;
+        }
+    }
+    sources.sort();
+
+    let mut written = Vec::with_capacity(sources.len());
+    for source in sources {
+        let bytes = std::fs::read(&source)
+            .map_err(|e| format!("Failed to read '{}': {}", source.display(), e))?;
+        let program = parse_unit(&source, &bytes).map_err(|e| e.report())?;
+        let out = crate::vm::bytecode::cache_path(&source);
+        let size = crate::vm::bytecode::write_unit(&program, &source, &bytes, &out)?;
+        written.push((out, size));
+    }
+    Ok(written)
+}
+
+/// Parse a file's content: a protected bundle's serialized AST, or source.
+/// Files without imports are constant-folded here, once; the others are
+/// folded after their imports are resolved.
+fn parse_unit(path: &Path, bytes: &[u8]) -> Result<crate::ast::Program, RuntimeError> {
+    let mut program = if crate::bundle::is_ast_blob(bytes) {
         // Protected bundle: the `.sl` file is a serialized AST, not source.
-        crate::bundle::deserialize_program(&bytes).map_err(|e| RuntimeError::General {
+        crate::bundle::deserialize_program(bytes).map_err(|e| RuntimeError::General {
             message: format!("Failed to load '{}': {}", path.display(), e),
             span: Span::default(),
         })?
     } else {
-        let source = String::from_utf8(bytes).map_err(|e| RuntimeError::General {
+        let source = std::str::from_utf8(bytes).map_err(|e| RuntimeError::General {
             message: format!("File '{}' is not valid UTF-8: {}", path.display(), e),
             span: Span::default(),
         })?;
 
         // Lex
-        let tokens = crate::lexer::Scanner::new(&source)
+        let tokens = crate::lexer::Scanner::new(source)
             .scan_tokens()
-            .map_err(|e| RuntimeError::General {
-                message: format!("Lexer error in {}: {}", path.display(), e),
-                span: Span::default(),
+            .map_err(|e| {
+                RuntimeError::caused_by(
+                    format!("Failed to parse {}", path.display()),
+                    Span::default(),
+                    e,
+                )
             })?;
 
         // Parse
-        crate::parser::Parser::new(tokens)
-            .parse()
-            .map_err(|e| RuntimeError::General {
-                message: format!("Parser error in {}: {}", path.display(), e),
-                span: Span::default(),
-            })?
+        crate::parser::Parser::new(tokens).parse().map_err(|e| {
+            RuntimeError::caused_by(
+                format!("Failed to parse {}", path.display()),
+                Span::default(),
+                e,
+            )
+        })?
     };
 
-    // Module resolution (if the file has imports)
-    if crate::has_imports(&program) {
-        let base_dir = path.parent().unwrap_or(std::path::Path::new("."));
-        let mut resolver = crate::module::ModuleResolver::new(base_dir);
-        program = resolver
-            .resolve(program, path)
-            .map_err(|e| RuntimeError::General {
-                message: format!("Module resolution error in {}: {}", path.display(), e),
-                span: Span::default(),
-            })?;
+    if !crate::has_imports(&program) {
+        crate::optimize::fold_constants(&mut program);
     }
-
-    // Execute (skip type checking for flexibility)
-    interpreter.set_source_path(path.to_path_buf());
-    interpreter.interpret(&program)
+    Ok(program)
 }
 
 /// Recursively track view files for hot reload.
@@ -730,7 +800,7 @@ pub(crate) fn track_view_files(
             let path = entry.path();
             if path.is_dir() {
                 track_recursive(&path, file_tracker)?;
-            } else if path.extension().is_some_and(|ext| ext == "erb") {
+            } else if crate::template::engines::is_template_file(&path) {
                 file_tracker.track(&path);
             }
         }
@@ -789,7 +859,7 @@ pub(crate) fn load_controllers_in_worker(
                 "Worker {}: Error loading {}: {}",
                 worker_id,
                 path.display(),
-                e
+                e.report()
             );
         }
     }
@@ -807,7 +877,7 @@ pub(crate) fn load_controllers_in_worker(
                     "Worker {}: Error loading {}: {}",
                     worker_id,
                     path.display(),
-                    e
+                    e.report()
                 );
                 false
             }
@@ -899,11 +969,13 @@ pub(crate) const ROUTES_DSL_SOURCE: &str = r#"
             router_middleware_scope_exit();
         }
 
-        fn get(path: Any, action: Any, name: Any = null) { router_match("GET", path, action, name); }
-        fn post(path: Any, action: Any, name: Any = null) { router_match("POST", path, action, name); }
-        fn put(path: Any, action: Any, name: Any = null) { router_match("PUT", path, action, name); }
-        fn delete(path: Any, action: Any, name: Any = null) { router_match("DELETE", path, action, name); }
-        fn patch(path: Any, action: Any, name: Any = null) { router_match("PATCH", path, action, name); }
+        fn get(path: Any, action: Any, name: Any = null, returns: Any = null) { router_match("GET", path, action, name, returns); }
+        fn post(path: Any, action: Any, name: Any = null, returns: Any = null) { router_match("POST", path, action, name, returns); }
+        fn put(path: Any, action: Any, name: Any = null, returns: Any = null) { router_match("PUT", path, action, name, returns); }
+        fn delete(path: Any, action: Any, name: Any = null, returns: Any = null) { router_match("DELETE", path, action, name, returns); }
+        fn patch(path: Any, action: Any, name: Any = null, returns: Any = null) { router_match("PATCH", path, action, name, returns); }
+
+        fn shape(name: Any, fields: Any) { router_shape(name, fields); }
 
         fn websocket(path: Any, action: Any) { router_websocket(path, action); }
 
@@ -919,20 +991,7 @@ pub(crate) const ROUTES_DSL_SOURCE: &str = r#"
 /// Define DSL helpers for routes in the interpreter.
 /// This must be called before routes.sl can be executed.
 pub(crate) fn define_routes_dsl(interpreter: &mut Interpreter) -> Result<(), RuntimeError> {
-    let tokens = crate::lexer::Scanner::new(ROUTES_DSL_SOURCE)
-        .scan_tokens()
-        .map_err(|e| RuntimeError::General {
-            message: format!("DSL Lexer error: {}", e),
-            span: Span::default(),
-        })?;
-    let program =
-        crate::parser::Parser::new(tokens)
-            .parse()
-            .map_err(|e| RuntimeError::General {
-                message: format!("DSL Parser error: {}", e),
-                span: Span::default(),
-            })?;
-    interpreter.interpret(&program)
+    interpreter.interpret_synthetic("routes_dsl", ROUTES_DSL_SOURCE)
 }
 
 /// Reload all controllers in a worker thread.
@@ -962,7 +1021,7 @@ pub(crate) fn reload_controllers_in_worker(
                 "Worker {}: Error reloading controller {}: {}",
                 worker_id,
                 controller_path.display(),
-                e
+                e.report()
             );
         }
     }
@@ -1004,6 +1063,7 @@ pub(crate) fn reload_routes_in_worker(
 
     // 4. Clear old routes and re-execute routes.sl
     crate::interpreter::builtins::server::clear_routes();
+    crate::serve::response_shape::clear();
     crate::serve::websocket::clear_websocket_routes();
 
     // Define route DSL functions before executing routes.sl
@@ -1018,6 +1078,9 @@ pub(crate) fn reload_routes_in_worker(
         return;
     }
 
+    // `limit_concurrency` declarations are collected afresh, so a limit the
+    // routes no longer declare stops applying.
+    crate::serve::concurrency::begin_routes_load();
     let reload_result = if routes_file.exists() {
         execute_file(interpreter, routes_file)
     } else {
@@ -1028,6 +1091,11 @@ pub(crate) fn reload_routes_in_worker(
     if let Err(e) = crate::serve::engine_loader::load_engine_routes(interpreter) {
         eprintln!("Worker {}: Error loading engine routes: {}", worker_id, e);
     }
+    crate::serve::concurrency::finish_routes_load(reload_result.is_ok());
+
+    // 5a. Routes declared with `@route` on controller actions (the registry
+    // was rescanned in step 3).
+    crate::interpreter::builtins::controller::registry::register_annotated_routes();
 
     // 5b. Preserve the background-jobs callback route across the reload.
     // `POST /_jobs/run/:name` (→ `_jobs#run`) is registered per-worker by
@@ -1064,7 +1132,8 @@ pub(crate) fn reload_routes_in_worker(
     if let Err(e) = reload_result {
         eprintln!(
             "Worker {}: Error reloading routes: {} - restoring previous routes",
-            worker_id, e
+            worker_id,
+            e.report()
         );
         crate::interpreter::builtins::server::restore_routes(saved_routes);
         crate::serve::websocket::restore_websocket_routes(saved_ws_routes);
@@ -29,6 +29,7 @@ use crossbeam::channel;
 use super::app_loader::{load_jobs_in_worker, load_models};
 use super::set_tokio_handle;
 use super::uploads_prelude;
+use super::worker_stack_size;
 use super::FileTracker;
 use crate::interpreter::builtins::server::{set_worker_routes, WorkerRoute};
 use crate::interpreter::builtins::{mailer, named_routes, template};
@@ -41,6 +42,9 @@ use crate::span::Span;
 struct BackgroundJob {
     class_name: String,
     args_json: String,
+    /// The enqueuing thread's frozen clock (`freeze_time` / `travel` in a
+    /// spec, carried into the request), so the job runs at the same "now".
+    frozen_now: Option<i64>,
 }
 
 /// Set once when the pool starts. `enqueue` returns `false` while unset so
@@ -88,6 +92,7 @@ pub fn enqueue(class_name: String, args_json: String) -> bool {
             .send(BackgroundJob {
                 class_name,
                 args_json,
+                frozen_now: crate::interpreter::builtins::datetime::helpers::frozen_datetime(),
             })
             .is_ok(),
         None => false,
@@ -110,7 +115,9 @@ pub fn start_pool(config: PoolConfig) {
     for id in 0..config.num_workers {
         let rx = rx.clone();
         let config = config.clone();
-        let builder = thread::Builder::new().name(format!("bg-job-{}", id));
+        let builder = thread::Builder::new()
+            .name(format!("bg-job-{}", id))
+            .stack_size(worker_stack_size());
         if let Err(e) = builder.spawn(move || run_pool_worker(id, rx, config)) {
             eprintln!("Failed to spawn background job worker {}: {}", id, e);
         }
@@ -187,16 +194,29 @@ fn build_job_interpreter(id: usize, interpreter: &mut Interpreter, config: &Pool
 
     // Mailer/Message/__MailDelivery base classes (used by `deliver_later` jobs).
     mailer::ensure_prelude(interpreter);
+    // Events/Export + their job classes (`Events.publish_later`,
+    // `Export.generate`).
+    crate::interpreter::builtins::events::ensure_prelude(interpreter);
+    crate::interpreter::builtins::export::ensure_prelude(interpreter);
 
     if let Err(e) = load_models(interpreter, &config.models_dir) {
-        eprintln!("Background job worker {}: error loading models: {}", id, e);
+        eprintln!(
+            "Background job worker {}: error loading models: {}",
+            id,
+            e.report()
+        );
     }
     if let Some(parent) = config.models_dir.parent() {
         for sub in ["services", "policies", "mailers"] {
             let dir = parent.join(sub);
             if dir.exists() {
                 if let Err(e) = load_models(interpreter, &dir) {
-                    eprintln!("Background job worker {}: error loading {}: {}", id, sub, e);
+                    eprintln!(
+                        "Background job worker {}: error loading {}: {}",
+                        id,
+                        sub,
+                        e.report()
+                    );
                 }
             }
         }
@@ -227,22 +247,8 @@ fn build_job_interpreter(id: usize, interpreter: &mut Interpreter, config: &Pool
 /// Lex/parse/execute `JOBS_BACKGROUND_RUNNER` into the interpreter, mirroring
 /// `mailer::ensure_prelude`.
 fn define_bg_runner(id: usize, interpreter: &mut Interpreter) {
-    let tokens = match crate::lexer::Scanner::new(JOBS_BACKGROUND_RUNNER).scan_tokens() {
-        Ok(t) => t,
-        Err(e) => {
-            eprintln!("Background job worker {}: runner lex error: {}", id, e);
-            return;
-        }
-    };
-    let program = match crate::parser::Parser::new(tokens).parse() {
-        Ok(p) => p,
-        Err(e) => {
-            eprintln!("Background job worker {}: runner parse error: {}", id, e);
-            return;
-        }
-    };
-    if let Err(e) = interpreter.interpret(&program) {
-        eprintln!("Background job worker {}: runner execute error: {}", id, e);
+    if let Err(e) = interpreter.interpret_synthetic("jobs_runner", JOBS_BACKGROUND_RUNNER) {
+        eprintln!("Background job worker {}: runner error: {}", id, e);
     }
 }
 
@@ -270,7 +276,10 @@ fn worker_recv_loop(
 
         let start = Instant::now();
         let call_args = vec![Value::String(job.class_name.clone().into()), args];
-        match interpreter.call_value(runner.clone(), call_args, Span::default()) {
+        crate::interpreter::builtins::datetime::helpers::restore_frozen_datetime(job.frozen_now);
+        let result = interpreter.call_value(runner.clone(), call_args, Span::default());
+        crate::interpreter::builtins::datetime::helpers::unfreeze_datetime();
+        match result {
             Ok(_) => {
                 let ms = start.elapsed().as_millis();
                 println!("[bg-job] {} finished in {}ms", job.class_name, ms);
@@ -0,0 +1,350 @@
+//! Concurrency limits, checked when a request is dispatched to a worker.
+//!
+//! Apps cap slow endpoints in `config/routes.sl`, and can put a ceiling on
+//! everything the server works on at once:
+//!
+//! ```soli
+//! limit_concurrency("/reports/*", 2)
+//! limit_concurrency("/exports/*", 1, {"retry_after": 30})
+//! limit_concurrency(200)   # in-flight ceiling for the whole server
+//! ```
+//!
+//! `SOLI_MAX_IN_FLIGHT` sets the ceiling without touching the app. A request
+//! over a limit is answered `503` with `Retry-After` before its body is read,
+//! instead of queueing behind the others: a burst on one slow route then
+//! can't occupy every worker while the rest of the app waits. A request
+//! holds its slots until its worker replies. Static files and the framework's
+//! own endpoints aren't counted.
+//!
+//! Patterns match like `skip_csrf` and `cors`: exact, `/prefix/*` or
+//! `prefix*`; the first declared pattern that matches applies.
+//!
+//! Every load of the routes declares the limits afresh (see
+//! [`begin_routes_load`]): a limit the routes no longer declare stops
+//! applying on the next hot reload, not at the next restart.
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Once, RwLock};
+
+use super::cors::pattern_matches;
+
+/// `Retry-After` seconds when a limit doesn't say.
+pub const DEFAULT_RETRY_AFTER_SECS: u64 = 1;
+
+struct Limit {
+    /// Most requests at once; 0 is no limit.
+    max: AtomicUsize,
+    retry_after: AtomicU64,
+    in_flight: AtomicUsize,
+}
+
+impl Limit {
+    const fn new(max: usize, retry_after: u64) -> Self {
+        Self {
+            max: AtomicUsize::new(max),
+            retry_after: AtomicU64::new(retry_after),
+            in_flight: AtomicUsize::new(0),
+        }
+    }
+
+    fn try_enter(&self) -> bool {
+        let max = self.max.load(Ordering::Relaxed);
+        self.in_flight
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (max == 0 || n < max).then_some(n + 1)
+            })
+            .is_ok()
+    }
+
+    fn leave(&self) {
+        self.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+struct Rule {
+    pattern: String,
+    limit: Arc<Limit>,
+}
+
+/// Route limits. Written at boot and on routes hot-reload, read per request.
+static RULES: RwLock<Vec<Rule>> = RwLock::new(Vec::new());
+static GLOBAL: Limit = Limit::new(0, DEFAULT_RETRY_AFTER_SECS);
+/// The ceiling `SOLI_MAX_IN_FLIGHT` sets, which a routes load that declares
+/// none goes back to.
+static ENV_MAX: AtomicUsize = AtomicUsize::new(0);
+/// Whether any limit was ever set, so apps without one skip the lookup.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static ENV_INIT: Once = Once::new();
+
+/// The limits declared by a routes load in progress on this thread.
+#[derive(Default)]
+struct Declared {
+    /// `(pattern, max, retry_after)`, in declaration order.
+    routes: Vec<(String, usize, u64)>,
+    global: Option<(usize, u64)>,
+}
+
+thread_local! {
+    static LOADING: RefCell<Option<Declared>> = const { RefCell::new(None) };
+}
+
+/// The in-flight ceiling, seeded from `SOLI_MAX_IN_FLIGHT` on first use.
+fn global() -> &'static Limit {
+    ENV_INIT.call_once(|| {
+        let max = std::env::var("SOLI_MAX_IN_FLIGHT")
+            .ok()
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+        if max > 0 {
+            ENV_MAX.store(max, Ordering::Relaxed);
+            GLOBAL.max.store(max, Ordering::Relaxed);
+            ACTIVE.store(true, Ordering::Release);
+        }
+    });
+    &GLOBAL
+}
+
+/// Start a load of the routes on this thread. Until [`finish_routes_load`],
+/// the limits it declares are collected while the current ones keep
+/// applying.
+pub fn begin_routes_load() {
+    LOADING.with(|loading| *loading.borrow_mut() = Some(Declared::default()));
+}
+
+/// End the routes load this thread began. When it `succeeded`, its limits
+/// replace the current ones, so a limit it no longer declares stops
+/// applying; a failed load leaves them as they were. A pattern declared
+/// before keeps its count of the requests running under it, which release
+/// their slots into it as they finish.
+pub fn finish_routes_load(succeeded: bool) {
+    let Some(declared) = LOADING.with(|loading| loading.borrow_mut().take()) else {
+        return;
+    };
+    if !succeeded {
+        return;
+    }
+    if let Ok(mut rules) = RULES.write() {
+        let mut previous = std::mem::take(&mut *rules);
+        for (pattern, max, retry_after) in declared.routes {
+            let limit = match previous.iter().position(|rule| rule.pattern == pattern) {
+                Some(i) => previous.swap_remove(i).limit,
+                None => Arc::new(Limit::new(max, retry_after)),
+            };
+            limit.max.store(max, Ordering::Relaxed);
+            limit.retry_after.store(retry_after, Ordering::Relaxed);
+            rules.push(Rule { pattern, limit });
+        }
+    }
+    let global = global();
+    let (max, retry_after) = declared
+        .global
+        .unwrap_or((ENV_MAX.load(Ordering::Relaxed), DEFAULT_RETRY_AFTER_SECS));
+    global.max.store(max, Ordering::Relaxed);
+    global.retry_after.store(retry_after, Ordering::Relaxed);
+}
+
+/// Allow at most `max` requests under `pattern` at once. Declaring a
+/// pattern again updates its limit in place, without losing count of the
+/// requests already running.
+pub fn set_route_limit(pattern: String, max: usize, retry_after: u64) {
+    ACTIVE.store(true, Ordering::Release);
+    let staged = LOADING.with(|loading| match loading.borrow_mut().as_mut() {
+        Some(declared) => {
+            match declared.routes.iter_mut().find(|(p, _, _)| *p == pattern) {
+                Some(route) => *route = (pattern.clone(), max, retry_after),
+                None => declared.routes.push((pattern.clone(), max, retry_after)),
+            }
+            true
+        }
+        None => false,
+    });
+    if staged {
+        return;
+    }
+    if let Ok(mut rules) = RULES.write() {
+        match rules.iter().find(|rule| rule.pattern == pattern) {
+            Some(rule) => {
+                rule.limit.max.store(max, Ordering::Relaxed);
+                rule.limit.retry_after.store(retry_after, Ordering::Relaxed);
+            }
+            None => rules.push(Rule {
+                pattern,
+                limit: Arc::new(Limit::new(max, retry_after)),
+            }),
+        }
+    }
+}
+
+/// Allow at most `max` requests in flight across the server; 0 lifts the
+/// ceiling.
+pub fn set_global_limit(max: usize, retry_after: u64) {
+    ACTIVE.store(true, Ordering::Release);
+    let staged = LOADING.with(|loading| match loading.borrow_mut().as_mut() {
+        Some(declared) => {
+            declared.global = Some((max, retry_after));
+            true
+        }
+        None => false,
+    });
+    if staged {
+        return;
+    }
+    let global = global();
+    global.max.store(max, Ordering::Relaxed);
+    global.retry_after.store(retry_after, Ordering::Relaxed);
+}
+
+/// The slots a dispatched request holds; dropping it frees them.
+pub struct Permit {
+    global: bool,
+    route: Option<Arc<Limit>>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if self.global {
+            GLOBAL.leave();
+        }
+        if let Some(limit) = &self.route {
+            limit.leave();
+        }
+    }
+}
+
+/// A request turned away: retry after this many seconds.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Busy {
+    pub retry_after: u64,
+}
+
+/// Take a slot in the ceiling and in the limit of the first route pattern
+/// matching `path`, or say how long to wait if either is full.
+pub fn acquire(path: &str) -> Result<Permit, Busy> {
+    let mut permit = Permit {
+        global: false,
+        route: None,
+    };
+    if !ACTIVE.load(Ordering::Acquire) {
+        return Ok(permit);
+    }
+
+    let global = global();
+    if global.max.load(Ordering::Relaxed) > 0 {
+        if !global.try_enter() {
+            return Err(Busy {
+                retry_after: global.retry_after.load(Ordering::Relaxed),
+            });
+        }
+        permit.global = true;
+    }
+
+    let limit = RULES.read().ok().and_then(|rules| {
+        rules
+            .iter()
+            .find(|rule| pattern_matches(&rule.pattern, path))
+            .map(|rule| Arc::clone(&rule.limit))
+    });
+    if let Some(limit) = limit {
+        if !limit.try_enter() {
+            // Dropping `permit` gives the ceiling's slot back.
+            return Err(Busy {
+                retry_after: limit.retry_after.load(Ordering::Relaxed),
+            });
+        }
+        permit.route = Some(limit);
+    }
+    Ok(permit)
+}
+
+/// A routes load replaces every route limit, so the tests that declare
+/// some take turns.
+#[cfg(test)]
+static RULES_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[cfg(test)]
+pub(crate) fn lock_rules() -> std::sync::MutexGuard<'static, ()> {
+    RULES_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_route_limit_sheds_the_excess_until_a_slot_frees() {
+        let _rules = lock_rules();
+        set_route_limit("/test-reports/*".to_string(), 2, 7);
+        let first = acquire("/test-reports/annual").unwrap();
+        let _second = acquire("/test-reports").unwrap();
+        assert_eq!(
+            acquire("/test-reports/monthly").err(),
+            Some(Busy { retry_after: 7 })
+        );
+        assert!(acquire("/elsewhere").is_ok(), "other routes aren't limited");
+
+        drop(first);
+        assert!(acquire("/test-reports/monthly").is_ok());
+    }
+
+    #[test]
+    fn redeclaring_a_pattern_keeps_its_running_count() {
+        let _rules = lock_rules();
+        set_route_limit("/test-exports".to_string(), 1, 1);
+        let running = acquire("/test-exports").unwrap();
+        set_route_limit("/test-exports".to_string(), 2, 30);
+        let _second = acquire("/test-exports").unwrap();
+        assert_eq!(
+            acquire("/test-exports").err(),
+            Some(Busy { retry_after: 30 })
+        );
+        drop(running);
+        assert!(acquire("/test-exports").is_ok());
+    }
+
+    #[test]
+    fn a_routes_reload_drops_the_limits_it_no_longer_declares() {
+        let _rules = lock_rules();
+        begin_routes_load();
+        set_route_limit("/test-reload-old".to_string(), 1, 1);
+        set_route_limit("/test-reload-kept".to_string(), 1, 1);
+        finish_routes_load(true);
+        let _old = acquire("/test-reload-old").unwrap();
+        let kept = acquire("/test-reload-kept").unwrap();
+
+        begin_routes_load();
+        set_route_limit("/test-reload-kept".to_string(), 1, 5);
+        // Until the load is over, the limits it replaces still apply.
+        assert_eq!(
+            acquire("/test-reload-old").err(),
+            Some(Busy { retry_after: 1 })
+        );
+        finish_routes_load(true);
+
+        assert!(acquire("/test-reload-old").is_ok(), "removed from routes");
+        assert_eq!(
+            acquire("/test-reload-kept").err(),
+            Some(Busy { retry_after: 5 }),
+            "the running request still counts"
+        );
+        drop(kept);
+        assert!(acquire("/test-reload-kept").is_ok());
+
+        // A load that fails leaves the limits alone.
+        begin_routes_load();
+        finish_routes_load(false);
+        let _kept = acquire("/test-reload-kept").unwrap();
+        assert!(acquire("/test-reload-kept").is_err());
+    }
+
+    #[test]
+    fn a_limit_of_zero_is_no_limit() {
+        let limit = Limit::new(0, 1);
+        assert!((0..1000).all(|_| limit.try_enter()));
+        let limit = Limit::new(1, 1);
+        assert!(limit.try_enter());
+        assert!(!limit.try_enter());
+        limit.leave();
+        assert!(limit.try_enter());
+    }
+}
@@ -0,0 +1,347 @@
+//! `config/schema.sl`: the environment an app needs, checked at boot.
+//!
+//! ```soli
+//! env("DATABASE_URL", "url")
+//! env("PORT", "int", default: 3000)
+//! env("SENTRY_DSN", required: false)
+//! env("LOG_LEVEL", one_of: ["debug", "info", "warn"], default: "info")
+//! ```
+//!
+//! `soli serve` and `soli test` evaluate the schema right after loading the
+//! `.env` files, before anything reads a variable. An unset variable takes its
+//! default, which is exported so `getenv` and the builtins see it. Every
+//! problem (a required variable that is unset or empty, a value that doesn't
+//! parse as its type) goes into one report, and the command stops there
+//! instead of failing on the first request that needs the value.
+
+use std::cell::RefCell;
+use std::path::Path;
+
+use crate::interpreter::value::{NativeFunction, Value};
+use crate::interpreter::Interpreter;
+
+/// Path of the schema, relative to the app root.
+pub const SCHEMA_FILE: &str = "config/schema.sl";
+
+const SCHEMA_DSL_SOURCE: &str = r#"
+    fn env(name: Any, type: Any = "string", default: Any = null, required: Any = null, one_of: Any = null) {
+        __config_schema_entry(name, type, default, required, one_of);
+    }
+"#;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    String,
+    Int,
+    Float,
+    Bool,
+    Url,
+}
+
+impl Kind {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "string" => Kind::String,
+            "int" => Kind::Int,
+            "float" => Kind::Float,
+            "bool" => Kind::Bool,
+            "url" => Kind::Url,
+            _ => return None,
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Kind::String => "string",
+            Kind::Int => "int",
+            Kind::Float => "float",
+            Kind::Bool => "bool",
+            Kind::Url => "url",
+        }
+    }
+
+    fn accepts(self, value: &str) -> bool {
+        match self {
+            Kind::String => true,
+            Kind::Int => value.parse::<i64>().is_ok(),
+            Kind::Float => value.parse::<f64>().is_ok(),
+            Kind::Bool => matches!(
+                value.to_ascii_lowercase().as_str(),
+                "true" | "false" | "1" | "0" | "yes" | "no" | "on" | "off"
+            ),
+            Kind::Url => url::Url::parse(value).is_ok(),
+        }
+    }
+}
+
+/// One `env(...)` declaration.
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    name: String,
+    kind: Kind,
+    default: Option<String>,
+    required: bool,
+    one_of: Vec<String>,
+}
+
+thread_local! {
+    static ENTRIES: RefCell<Vec<Entry>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A default or `one_of` choice as the string the environment would hold.
+fn env_string(value: &Value) -> Result<String, String> {
+    match value {
+        Value::String(s) => Ok(s.to_string()),
+        Value::Int(_) | Value::Float(_) | Value::Bool(_) => Ok(value.to_string()),
+        other => Err(format!(
+            "env(): expected a string, number or bool, got {}",
+            other.type_name()
+        )),
+    }
+}
+
+fn declare(args: Vec<Value>) -> Result<Value, String> {
+    let Value::String(name) = &args[0] else {
+        return Err("env(): the variable name must be a string".to_string());
+    };
+    let Value::String(kind) = &args[1] else {
+        return Err(format!("env(\"{}\"): the type must be a string", name));
+    };
+    let kind = Kind::parse(kind).ok_or_else(|| {
+        format!(
+            "env(\"{}\"): unknown type \"{}\" (expected string, int, float, bool or url)",
+            name, kind
+        )
+    })?;
+    let default = match &args[2] {
+        Value::Null => None,
+        value => Some(env_string(value)?),
+    };
+    let required = match &args[3] {
+        Value::Null => default.is_none(),
+        Value::Bool(b) => *b,
+        _ => return Err(format!("env(\"{}\"): required must be a bool", name)),
+    };
+    let one_of = match &args[4] {
+        Value::Null => Vec::new(),
+        Value::Array(items) => items
+            .borrow()
+            .iter()
+            .map(env_string)
+            .collect::<Result<_, _>>()?,
+        _ => return Err(format!("env(\"{}\"): one_of must be an array", name)),
+    };
+    ENTRIES.with(|entries| {
+        entries.borrow_mut().push(Entry {
+            name: name.to_string(),
+            kind,
+            default,
+            required,
+            one_of,
+        })
+    });
+    Ok(Value::Null)
+}
+
+fn run(interpreter: &mut Interpreter, source: &str) -> Result<(), String> {
+    let tokens = crate::lexer::Scanner::new(source)
+        .scan_tokens()
+        .map_err(|e| e.to_string())?;
+    let program = crate::parser::Parser::new(tokens)
+        .parse()
+        .map_err(|e| e.to_string())?;
+    interpreter.interpret(&program).map_err(|e| e.to_string())
+}
+
+/// Evaluate a schema's source and return its declarations.
+fn load(source: &str) -> Result<Vec<Entry>, String> {
+    ENTRIES.with(|entries| entries.borrow_mut().clear());
+    let mut interpreter = Interpreter::new();
+    interpreter.environment.borrow_mut().define(
+        "__config_schema_entry".to_string(),
+        Value::NativeFunction(NativeFunction::new(
+            "__config_schema_entry",
+            Some(5),
+            declare,
+        )),
+    );
+    interpreter
+        .interpret_synthetic("config_schema_dsl", SCHEMA_DSL_SOURCE)
+        .map_err(|e| e.to_string())?;
+    let result = run(&mut interpreter, source);
+    let entries = ENTRIES.with(|entries| std::mem::take(&mut *entries.borrow_mut()));
+    result.map(|()| entries)
+}
+
+/// Check `entries` against the environment `lookup` reads. Returns the
+/// defaults to export, or every problem found.
+fn check(
+    entries: &[Entry],
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<Vec<(String, String)>, Vec<String>> {
+    let mut defaults = Vec::new();
+    let mut problems = Vec::new();
+    for entry in entries {
+        let value = match lookup(&entry.name).filter(|v| !v.is_empty()) {
+            Some(value) => value,
+            None => match &entry.default {
+                Some(default) => {
+                    defaults.push((entry.name.clone(), default.clone()));
+                    default.clone()
+                }
+                None => {
+                    if entry.required {
+                        problems.push(format!(
+                            "{} is required ({}) but not set",
+                            entry.name,
+                            entry.kind.name()
+                        ));
+                    }
+                    continue;
+                }
+            },
+        };
+        if !entry.kind.accepts(&value) {
+            problems.push(format!(
+                "{}={:?} is not a valid {}",
+                entry.name,
+                value,
+                entry.kind.name()
+            ));
+        } else if !entry.one_of.is_empty() && !entry.one_of.contains(&value) {
+            problems.push(format!(
+                "{}={:?} is not one of {}",
+                entry.name,
+                value,
+                entry.one_of.join(", ")
+            ));
+        }
+    }
+    if problems.is_empty() {
+        Ok(defaults)
+    } else {
+        Err(problems)
+    }
+}
+
+/// Validate the process environment against `<app>/config/schema.sl` and
+/// export the defaults of unset variables. Apps without a schema pass. The
+/// error is the full report, ready to print.
+///
+/// Must run at single-threaded boot: exporting defaults uses `set_var`.
+pub fn validate(app_dir: &Path) -> Result<(), String> {
+    let path = app_dir.join(SCHEMA_FILE);
+    if !path.is_file() {
+        return Ok(());
+    }
+    let source = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", SCHEMA_FILE, e))?;
+    let entries = load(&source).map_err(|e| format!("Error in {}: {}", SCHEMA_FILE, e))?;
+
+    match check(&entries, |name| std::env::var(name).ok()) {
+        Ok(defaults) => {
+            for (name, value) in defaults {
+                // Safe for the same reason as `env_loader::load_env_file`:
+                // callers run before any worker thread exists.
+                unsafe { std::env::set_var(name, value) };
+            }
+            Ok(())
+        }
+        Err(problems) => {
+            let mut report = format!(
+                "{} problem{} with the environment declared in {}:",
+                problems.len(),
+                if problems.len() == 1 { "" } else { "s" },
+                SCHEMA_FILE
+            );
+            for problem in problems {
+                report.push_str("\n  - ");
+                report.push_str(&problem);
+            }
+            Err(report)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(k, _)| *k == name)
+                .map(|(_, v)| v.to_string())
+        }
+    }
+
+    #[test]
+    fn declarations_read_their_options() {
+        let entries = load(
+            r#"
+            env("DATABASE_URL", "url")
+            env("PORT", "int", default: 3000)
+            env("SENTRY_DSN", required: false)
+            env("LOG_LEVEL", one_of: ["debug", "info"], default: "info")
+            "#,
+        )
+        .unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[0].kind, Kind::Url);
+        assert!(entries[0].required);
+        assert_eq!(entries[1].default.as_deref(), Some("3000"));
+        assert!(!entries[1].required);
+        assert!(!entries[2].required);
+        assert_eq!(entries[3].one_of, ["debug", "info"]);
+    }
+
+    #[test]
+    fn unknown_types_are_schema_errors() {
+        let err = load(r#"env("PORT", "integer")"#).unwrap_err();
+        assert!(err.contains("unknown type \"integer\""), "{}", err);
+    }
+
+    #[test]
+    fn every_problem_is_reported_at_once() {
+        let entries = load(
+            r#"
+            env("DATABASE_URL", "url")
+            env("PORT", "int", default: 3000)
+            env("WORKERS", "int")
+            env("LOG_LEVEL", one_of: ["debug", "info"])
+            "#,
+        )
+        .unwrap();
+        let problems = check(
+            &entries,
+            lookup(&[
+                ("DATABASE_URL", ""),
+                ("WORKERS", "four"),
+                ("LOG_LEVEL", "trace"),
+            ]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            problems,
+            [
+                "DATABASE_URL is required (url) but not set",
+                "WORKERS=\"four\" is not a valid int",
+                "LOG_LEVEL=\"trace\" is not one of debug, info",
+            ]
+        );
+    }
+
+    #[test]
+    fn unset_variables_take_their_defaults() {
+        let entries = load(
+            r#"
+            env("PORT", "int", default: 3000)
+            env("DEBUG", "bool", default: false)
+            env("SENTRY_DSN", required: false)
+            "#,
+        )
+        .unwrap();
+        let defaults = check(&entries, lookup(&[("DEBUG", "yes")])).unwrap();
+        assert_eq!(defaults, [("PORT".to_string(), "3000".to_string())]);
+    }
+}
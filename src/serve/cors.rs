@@ -78,7 +78,7 @@ pub fn clear_cors_rules() {
 
 /// Same pattern semantics as `skip_csrf`: exact match, `/prefix/*` (which
 /// also covers `/prefix` itself), or a bare `prefix*`.
-fn pattern_matches(pattern: &str, path: &str) -> bool {
+pub(crate) fn pattern_matches(pattern: &str, path: &str) -> bool {
     if let Some(prefix) = pattern.strip_suffix("/*") {
         path == prefix || path.starts_with(&format!("{}/", prefix))
     } else if let Some(prefix) = pattern.strip_suffix('*') {
@@ -5,6 +5,7 @@
 
 use hyper::Response;
 
+use crate::interpreter::builtins::model::connections::is_write_query;
 use crate::interpreter::builtins::server::parse_query_string;
 
 use super::{dev_bar, html_ok, ResponseBody};
@@ -79,19 +80,6 @@ fn db_list_collection_names() -> Result<Vec<String>, String> {
     Ok(names)
 }
 
-/// Reject non-read queries (a lexical guard for the dev query box, paired with
-/// the collection allow-list; the endpoint is dev-gated regardless).
-fn is_write_query(sql: &str) -> bool {
-    let upper = sql.to_uppercase();
-    ["INSERT", "UPDATE", "REPLACE", "REMOVE", "UPSERT"]
-        .iter()
-        .any(|kw| {
-            upper
-                .split(|c: char| !c.is_ascii_alphanumeric())
-                .any(|tok| tok == *kw)
-        })
-}
-
 /// A collection name safe to interpolate into a query (it can't be a bind var).
 fn valid_collection_name(name: &str) -> bool {
     !name.is_empty()
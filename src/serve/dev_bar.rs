@@ -194,16 +194,7 @@ fn render_bar(ctx: &DevBarContext) -> String {
             .map(|(_, v)| *v)
             .sum()
     } else {
-        let id_set: std::collections::HashSet<u32> =
-            ctx.views.iter().map(|(id, _, _, _)| *id).collect();
-        ctx.views
-            .iter()
-            .filter(|(_, parent, _, _)| match parent {
-                Some(p) => !id_set.contains(p),
-                None => true,
-            })
-            .map(|(_, _, _, us)| *us)
-            .sum()
+        crate::serve::view_log::root_total_us(&ctx.views)
     };
     let h_us_total: u64 = ctx
         .http_requests
@@ -1121,7 +1112,7 @@ pub(crate) fn detect_n_plus_one(
     result
 }
 
-fn fmt_duration_us(us: u64) -> String {
+pub(crate) fn fmt_duration_us(us: u64) -> String {
     if us < 1_000 {
         format!("{}µs", us)
     } else if us < 1_000_000 {
@@ -325,7 +325,11 @@ fn load_engine_controller_directory(
         {
             let _guard = EngineContextGuard::enter(&engine.name);
             if let Err(e) = interp_execute_file(interpreter, controller_path) {
-                eprintln!("Error loading engine controller {}: {}", controller_key, e);
+                eprintln!(
+                    "Error loading engine controller {}: {}",
+                    controller_key,
+                    e.report()
+                );
             }
         }
 
@@ -434,7 +438,11 @@ fn load_engine_model_directory(
         if path.extension().is_some_and(|ext| ext == "sl") {
             let _guard = EngineContextGuard::enter(&engine.name);
             if let Err(e) = interp_execute_file(interpreter, &path) {
-                eprintln!("Error loading engine model {}: {}", path.display(), e);
+                eprintln!(
+                    "Error loading engine model {}: {}",
+                    path.display(),
+                    e.report()
+                );
             }
         }
     }
@@ -465,28 +473,19 @@ pub fn load_engine_routes(interpreter: &mut Interpreter) -> Result<(), RuntimeEr
         if routes_path.exists() {
             let _guard = EngineContextGuard::enter(&engine.name);
 
-            let tokens = crate::lexer::Scanner::new(ROUTES_DSL)
-                .scan_tokens()
-                .map_err(|e| RuntimeError::General {
-                    message: format!("DSL Lexer error: {}", e),
-                    span: Span::default(),
-                })?;
-            let program =
-                crate::parser::Parser::new(tokens)
-                    .parse()
-                    .map_err(|e| RuntimeError::General {
-                        message: format!("DSL Parser error: {}", e),
-                        span: Span::default(),
-                    })?;
             interpreter
-                .interpret(&program)
+                .interpret_synthetic("engine_routes_dsl", ROUTES_DSL)
                 .map_err(|e| RuntimeError::General {
                     message: format!("Failed to define engine routes DSL: {}", e),
                     span: Span::default(),
                 })?;
 
             if let Err(e) = interp_execute_file(interpreter, &routes_path) {
-                eprintln!("Error loading engine routes {}: {}", engine.name, e);
+                eprintln!(
+                    "Error loading engine routes {}: {}",
+                    engine.name,
+                    e.report()
+                );
             }
         }
     }
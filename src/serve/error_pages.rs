@@ -8,12 +8,22 @@ use super::RequestData;
 /// Helper function to render error page with full details.
 pub(super) fn render_error_page(
     error_msg: &str,
+    error_chain: &[String],
     interpreter: &Interpreter,
     request_data: &RequestData,
     stack_trace: &[String],
     breakpoint_env_json: Option<&str>,
 ) -> String {
-    let error_type = if breakpoint_env_json.is_some() {
+    let app_root = crate::live::component::get_app_root();
+    // A failing template tag gets the overlay: its source around the tag and
+    // the variables in scope there, instead of the controller frame alone.
+    let template_site = super::template_error::locate(error_msg, |file| {
+        super::template_error::read_template(file, &app_root)
+    });
+    let template_locals = crate::interpreter::builtins::template::take_template_error_locals();
+    let error_type = if template_site.is_some() {
+        "TemplateError"
+    } else if breakpoint_env_json.is_some() {
         "Breakpoint"
     } else {
         "RuntimeError"
@@ -66,6 +76,22 @@ pub(super) fn render_error_page(
         .unwrap_or_else(|| "unknown".to_string());
 
     let location = format!("{}:{}", error_file, error_line);
+    let template_overlay = template_site
+        .as_ref()
+        .map(|site| {
+            let display_file = Path::new(&site.file)
+                .strip_prefix(&app_root)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|_| site.file.clone());
+            let source = super::template_error::read_template(&site.file, &app_root);
+            super::template_error::render_overlay(
+                site,
+                &display_file,
+                source.as_deref(),
+                template_locals.as_deref().unwrap_or(&[]),
+            )
+        })
+        .unwrap_or_default();
     full_stack_trace.push(format!("Error: {}", actual_error));
     full_stack_trace.extend(embedded_stack);
     full_stack_trace.extend(stack_trace.iter().cloned());
@@ -135,7 +161,6 @@ pub(super) fn render_error_page(
     }
 
     let mut source_files: HashMap<String, String> = HashMap::new();
-    let app_root = crate::live::component::get_app_root();
     static SOURCE_FILE_RE: std::sync::LazyLock<regex::Regex> = std::sync::LazyLock::new(|| {
         regex::Regex::new(
             r"([./a-zA-Z0-9_@-]+(?:\.html\.slv|\.slv|\.html\.md|\.md|\.html\.erb|\.erb|\.sl)):(\d+)",
@@ -145,6 +170,14 @@ pub(super) fn render_error_page(
         if let Some(caps) = SOURCE_FILE_RE.captures(frame) {
             if let Some(file_match) = caps.get(1) {
                 let file_str = file_match.as_str();
+                // Runtime-embedded code (the routing DSL, preludes) has no
+                // file on disk; its source is registered by path instead.
+                if let Some(content) = crate::source_map::get(file_str) {
+                    if let Ok(json) = serde_json::to_string(&source_lines(content)) {
+                        source_files.insert(file_str.to_string(), json);
+                    }
+                    continue;
+                }
                 if !source_files.contains_key(file_str) {
                     let candidates = [
                         std::path::Path::new(file_str).to_path_buf(),
@@ -153,12 +186,7 @@ pub(super) fn render_error_page(
                     for candidate in &candidates {
                         if candidate.exists() {
                             if let Ok(content) = std::fs::read_to_string(candidate) {
-                                let lines_map: HashMap<usize, String> = content
-                                    .lines()
-                                    .enumerate()
-                                    .map(|(i, l)| (i + 1, l.to_string()))
-                                    .collect();
-                                if let Ok(json) = serde_json::to_string(&lines_map) {
+                                if let Ok(json) = serde_json::to_string(&source_lines(&content)) {
                                     source_files.insert(file_str.to_string(), json);
                                 }
                                 break;
@@ -184,6 +212,7 @@ pub(super) fn render_error_page(
 
     render_dev_error_page(
         &actual_error,
+        error_chain,
         error_type,
         &location,
         &full_stack_trace,
@@ -191,9 +220,20 @@ pub(super) fn render_error_page(
         env_json_for_render,
         &source_files,
         peer_trusted,
+        &template_overlay,
     )
 }
 
+/// A source's lines keyed by 1-based line number, as the page's code
+/// viewer takes them.
+fn source_lines(content: &str) -> HashMap<usize, String> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, l)| (i + 1, l.to_string()))
+        .collect()
+}
+
 fn extract_json_field(json: &str, field: &str) -> Option<String> {
     let pattern = format!(r#""{}":"#, field);
     if let Some(start) = json.find(&pattern) {
@@ -325,6 +365,7 @@ fn extract_file_from_frame(frame: &str) -> Option<String> {
 #[allow(clippy::too_many_arguments)]
 pub(super) fn render_dev_error_page(
     error: &str,
+    error_chain: &[String],
     error_type: &str,
     location: &str,
     stack_trace: &[String],
@@ -332,8 +373,30 @@ pub(super) fn render_dev_error_page(
     breakpoint_env_json: Option<&str>,
     preloaded_sources: &HashMap<String, String>,
     peer_trusted: bool,
+    template_overlay: &str,
 ) -> String {
     let error_message = escape_html(error);
+    // Context frames and "Caused by:" lines from the typed error chain,
+    // listed under the headline message.
+    let error_chain = if error_chain.is_empty() {
+        String::new()
+    } else {
+        let items: Vec<String> = error_chain
+            .iter()
+            .map(|line| {
+                let class = if line.starts_with("Caused by: ") {
+                    "text-red-300"
+                } else {
+                    "text-gray-400 pl-4"
+                };
+                format!(r#"<li class="{}">{}</li>"#, class, escape_html(line))
+            })
+            .collect();
+        format!(
+            r#"<ul class="font-mono text-sm space-y-1 mb-2">{}</ul>"#,
+            items.join("")
+        )
+    };
     let error_type = escape_html(error_type);
     let error_location = escape_html(location);
     let mut stack_frames = Vec::new();
@@ -508,8 +571,10 @@ pub(super) fn render_dev_error_page(
                 <span class="text-gray-500">Development Mode</span>
             </div>
             <h1 class="text-3xl font-bold text-white mb-2">{error_message}</h1>
+            {error_chain}
             <p class="text-gray-400">{error_location}</p>
         </div>
+        {template_overlay}
         <div class="mb-8 rounded-xl bg-gray-900 border border-white/10 overflow-hidden">
             <div class="flex items-center justify-between px-4 py-3 bg-gray-800 border-b border-white/10">
                 <div class="flex items-center gap-2">
@@ -518,7 +583,10 @@ pub(super) fn render_dev_error_page(
                     </svg>
                     <span class="font-semibold text-white">Interactive REPL</span>
                 </div>
-                <button onclick="clearRepl()" class="text-gray-400 hover:text-white text-sm">Clear</button>
+                <div class="flex items-center gap-4">
+                    <button onclick="resetRepl()" class="text-gray-400 hover:text-white text-sm" title="Discard variables defined in this REPL session">Reset session</button>
+                    <button onclick="clearRepl()" class="text-gray-400 hover:text-white text-sm">Clear</button>
+                </div>
             </div>
             <div class="p-4">
                 <div class="flex gap-2 mb-3">
@@ -694,9 +762,10 @@ pub(super) fn render_dev_error_page(
                 const response = await fetch('/__dev/repl', {{
                     method: 'POST',
                     headers: {{ 'Content-Type': 'application/json', 'X-Soli-Dev-Token': devReplToken }},
-                    body: JSON.stringify({{ code: code, request_data: currentRequestData, breakpoint_env: breakpointEnv }})
+                    body: JSON.stringify({{ code: code, request_data: currentRequestData, breakpoint_env: breakpointEnv, repl_session_id: replSessionId }})
                 }});
                 const result = await response.json();
+                if (result.repl_session_id) {{ replSessionId = result.repl_session_id; }}
                 if (result.error) {{
                     output.innerHTML += '<div class="text-red-400 mt-2">❌ ' + escapeHtml(result.error) + '</div>';
                     lastResult = null;
@@ -719,6 +788,7 @@ pub(super) fn render_dev_error_page(
         let history = [];
         let historyIndex = -1;
         let lastResult = null;
+        let replSessionId = '';
         function navigateHistory(direction) {{
             const input = document.getElementById('repl-input');
             if (history.length === 0) return;
@@ -737,6 +807,22 @@ pub(super) fn render_dev_error_page(
             document.getElementById('repl-input').value = expanded;
             executeRepl();
         }}
+        async function resetRepl() {{
+            const output = document.getElementById('repl-output');
+            try {{
+                await fetch('/__dev/repl', {{
+                    method: 'POST',
+                    headers: {{ 'Content-Type': 'application/json', 'X-Soli-Dev-Token': devReplToken }},
+                    body: JSON.stringify({{ reset: true, repl_session_id: replSessionId }})
+                }});
+                replSessionId = '';
+                lastResult = null;
+                output.innerHTML += '<div class="text-gray-500 italic mt-2">// Session reset. Variables from earlier commands are gone.</div>';
+            }} catch (e) {{
+                output.innerHTML += '<div class="text-red-400 mt-2">❌ Error: ' + escapeHtml(e.message) + '</div>';
+            }}
+            output.scrollTop = output.scrollHeight;
+        }}
         function clearRepl() {{
             document.getElementById('repl-output').innerHTML = '<div class="text-gray-500 italic">// REPL cleared.</div>';
             history = [];
@@ -836,6 +922,7 @@ pub(super) fn render_dev_error_page(
 </html>"#,
         error_type = error_type,
         error_message = error_message,
+        error_chain = error_chain,
         error_location = error_location,
         stack_frames = stack_frames.join("\n"),
         request_data_json = escape_for_script_tag(request_data_json),
@@ -848,6 +935,56 @@ pub(super) fn render_dev_error_page(
     )
 }
 
+/// The dev-mode page shown when an action's response drifts from the
+/// route's `returns:` shape: one row per mismatch, then the body it sent.
+pub(super) fn render_response_shape_page(
+    method: &str,
+    path: &str,
+    handler: &str,
+    shape: &str,
+    drifts: &[String],
+    body: &[u8],
+) -> String {
+    let rows: String = drifts
+        .iter()
+        .map(|d| format!("<li><code>{}</code></li>", escape_html(d)))
+        .collect();
+    let body = String::from_utf8_lossy(body);
+    let preview: String = body.chars().take(4000).collect();
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>Response shape mismatch</title>
+<style>
+body {{ font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; background: #0f172a; color: #e2e8f0; margin: 0; padding: 2rem; }}
+h1 {{ color: #f87171; font-size: 1.5rem; margin: 0 0 .5rem; }}
+p {{ color: #94a3b8; }}
+code, pre {{ font-family: ui-monospace, SFMono-Regular, Menlo, monospace; }}
+ul {{ background: #1e293b; border-left: 4px solid #f87171; padding: 1rem 1rem 1rem 2rem; border-radius: 4px; }}
+li {{ margin: .25rem 0; }}
+pre {{ background: #1e293b; padding: 1rem; border-radius: 4px; overflow: auto; max-height: 24rem; }}
+</style>
+</head>
+<body>
+<h1>Response shape mismatch</h1>
+<p><code>{method} {path}</code> &rarr; <code>{handler}</code> declares <code>returns: {shape}</code>, but its response drifted in {count} place(s):</p>
+<ul>{rows}</ul>
+<h2>Response body</h2>
+<pre>{preview}</pre>
+</body>
+</html>"#,
+        method = escape_html(method),
+        path = escape_html(path),
+        handler = escape_html(handler),
+        shape = escape_html(shape),
+        count = drifts.len(),
+        rows = rows,
+        preview = escape_html(&preview),
+    )
+}
+
 fn extract_controller_name(path: &str) -> String {
     std::path::Path::new(path)
         .file_stem()
@@ -5,7 +5,7 @@ use std::path::Path;
 use crate::serve::hot_reload::FileTracker;
 use crate::serve::server_constants;
 
-/// Recursively track view files (.erb) in a directory
+/// Recursively track view files (any registered template extension) in a directory
 ///
 /// SEC-049: skip symlinked entries — both files and directories. An
 /// attacker who can drop a symlink into a watched tree would otherwise
@@ -23,10 +23,7 @@ pub fn track_views_recursive(dir: &Path, tracker: &mut FileTracker) {
             let path = entry.path();
             if file_type.is_dir() {
                 track_views_recursive(&path, tracker);
-            } else if path
-                .extension()
-                .is_some_and(|ext| ext == "erb" || ext == "md")
-            {
+            } else if crate::template::engines::is_template_file(&path) {
                 tracker.track(&path);
             }
         }
@@ -0,0 +1,503 @@
+//! Per-worker heap statistics, for diagnosing memory growth in long-running
+//! `soli serve` daemons.
+//!
+//! Each worker owns its interpreter (`Rc` values never cross threads), so only
+//! the worker itself can walk its heap. Workers register a small request
+//! channel here and offer it to the `select` they already park on; a snapshot
+//! sends every worker a reply channel and collects what comes back. A worker
+//! busy with a long request simply misses the deadline and is listed as such —
+//! the snapshot never waits on one.
+//!
+//! Two ways in:
+//!
+//! - `kill -USR1 <pid>` prints a report to stderr (the daemon log under `-d`).
+//!   The signal handler only sets a flag; a watcher thread does the work.
+//! - `GET /__soli/heap` returns the same report as JSON, under `--dev` only.
+//!
+//! The walk starts at a worker's globals (and the VM's, in production), and
+//! follows arrays, hashes, instance fields, class static fields and closure
+//! environments, visiting each shared container once. Counts are reachable
+//! *values*, not bytes: a hash that keeps growing shows up as a number that
+//! keeps growing — and, if it's big, by name in the largest-containers list.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashSet};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crossbeam::channel;
+
+use crate::interpreter::environment::Environment;
+use crate::interpreter::value::{Class, Value};
+use crate::vm::upvalue::Upvalue;
+
+/// How many of the largest hashes and arrays a worker reports.
+const LARGEST_LIMIT: usize = 10;
+
+/// How long a snapshot waits for busy workers.
+pub const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A hash or array, named by the path it was first reached through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Container {
+    pub path: String,
+    pub len: usize,
+}
+
+/// One worker's view of its own heap.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkerHeap {
+    pub worker_id: usize,
+    /// Reachable values by type name; instances count under their class.
+    pub values_by_type: BTreeMap<String, usize>,
+    pub largest_hashes: Vec<Container>,
+    pub largest_arrays: Vec<Container>,
+    /// This worker's rendered-response cache: entries and body bytes.
+    pub response_cache_entries: usize,
+    pub response_cache_bytes: usize,
+    pub view_helpers: usize,
+}
+
+impl WorkerHeap {
+    pub fn total_values(&self) -> usize {
+        self.values_by_type.values().sum()
+    }
+}
+
+/// State shared by every worker: the parsed-template cache, the LiveView
+/// registry and the VM's global name table.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SharedHeap {
+    pub cached_templates: usize,
+    pub cached_template_paths: usize,
+    pub liveview_by_component: BTreeMap<String, usize>,
+    /// Names interned by `vm::globals`, which never shrinks.
+    pub vm_global_names: usize,
+}
+
+impl SharedHeap {
+    pub fn capture() -> Self {
+        let (cached_templates, cached_template_paths) =
+            crate::interpreter::builtins::template::template_cache_sizes().unwrap_or((0, 0));
+        Self {
+            cached_templates,
+            cached_template_paths,
+            liveview_by_component: crate::live::view::LIVE_REGISTRY.counts_by_component(),
+            vm_global_names: crate::vm::globals::interned_count(),
+        }
+    }
+
+    pub fn liveview_instances(&self) -> usize {
+        self.liveview_by_component.values().sum()
+    }
+}
+
+/// A full snapshot: every worker that answered, the ones that didn't, and
+/// the shared caches.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeapReport {
+    pub workers: Vec<WorkerHeap>,
+    /// Workers still busy when the snapshot deadline passed.
+    pub busy: Vec<usize>,
+    pub shared: SharedHeap,
+}
+
+type Reply = channel::Sender<WorkerHeap>;
+
+fn workers() -> &'static Mutex<BTreeMap<usize, channel::Sender<Reply>>> {
+    static WORKERS: OnceLock<Mutex<BTreeMap<usize, channel::Sender<Reply>>>> = OnceLock::new();
+    WORKERS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Register a worker (again, after a restart) and return the channel its
+/// loop must answer on. One pending request per worker is plenty: a second
+/// snapshot taken while the first is still queued gets its answer too late
+/// to matter.
+pub fn register_worker(worker_id: usize) -> channel::Receiver<Reply> {
+    let (tx, rx) = channel::bounded(1);
+    if let Ok(mut map) = workers().lock() {
+        map.insert(worker_id, tx);
+    }
+    rx
+}
+
+/// Ask every registered worker for its stats and wait up to `timeout`.
+pub fn snapshot(timeout: Duration) -> HeapReport {
+    let (reply_tx, reply_rx) = channel::unbounded();
+    let mut pending: HashSet<usize> = HashSet::new();
+    if let Ok(map) = workers().lock() {
+        for (&id, tx) in map.iter() {
+            // A full slot means an earlier request is still queued; its
+            // answer goes to a snapshot that's gone, so count it as busy.
+            let _ = tx.try_send(reply_tx.clone());
+            pending.insert(id);
+        }
+    }
+    drop(reply_tx);
+
+    let deadline = Instant::now() + timeout;
+    let mut report = HeapReport {
+        shared: SharedHeap::capture(),
+        ..HeapReport::default()
+    };
+    while !pending.is_empty() {
+        match reply_rx.recv_deadline(deadline) {
+            Ok(heap) => {
+                pending.remove(&heap.worker_id);
+                report.workers.push(heap);
+            }
+            Err(_) => break,
+        }
+    }
+    report.workers.sort_by_key(|w| w.worker_id);
+    report.busy = pending.into_iter().collect();
+    report.busy.sort_unstable();
+    report
+}
+
+/// Walk a worker's heap. `env` is the interpreter's global environment;
+/// `vm_globals` the bytecode VM's globals when the worker runs one.
+pub fn collect(
+    worker_id: usize,
+    env: &Rc<RefCell<Environment>>,
+    vm_globals: Option<&crate::vm::globals::Globals>,
+) -> WorkerHeap {
+    let mut walker = Walker::default();
+    walker.visit_env(env, "");
+    if let Some(globals) = vm_globals {
+        for (name, value) in globals.iter() {
+            walker.visit(value, name);
+        }
+    }
+    let (response_cache_entries, response_cache_bytes) = crate::template::response_cache::sizes();
+    WorkerHeap {
+        worker_id,
+        values_by_type: walker.counts,
+        largest_hashes: largest(walker.hashes),
+        largest_arrays: largest(walker.arrays),
+        response_cache_entries,
+        response_cache_bytes,
+        view_helpers: crate::interpreter::builtins::template::view_helper_count(),
+    }
+}
+
+fn largest(mut containers: Vec<Container>) -> Vec<Container> {
+    containers.sort_by(|a, b| b.len.cmp(&a.len).then_with(|| a.path.cmp(&b.path)));
+    containers.truncate(LARGEST_LIMIT);
+    containers
+}
+
+#[derive(Default)]
+struct Walker {
+    /// Addresses of shared containers already walked (cycles, aliases).
+    seen: HashSet<usize>,
+    counts: BTreeMap<String, usize>,
+    hashes: Vec<Container>,
+    arrays: Vec<Container>,
+}
+
+impl Walker {
+    fn first_visit<T: ?Sized>(&mut self, rc: &Rc<T>) -> bool {
+        self.seen.insert(Rc::as_ptr(rc) as *const () as usize)
+    }
+
+    fn visit_env(&mut self, env: &Rc<RefCell<Environment>>, path: &str) {
+        if !self.first_visit(env) {
+            return;
+        }
+        let env = env.borrow();
+        for (name, value) in env.local_bindings() {
+            self.visit(value, &join(path, name));
+        }
+        if let Some(enclosing) = env.enclosing() {
+            self.visit_env(&enclosing, path);
+        }
+    }
+
+    fn visit_class(&mut self, class: &Rc<Class>, path: &str) {
+        if !self.first_visit(class) {
+            return;
+        }
+        for (name, value) in class.static_fields.borrow().iter() {
+            self.visit(value, &format!("{}.{}", path, name));
+        }
+        for (name, nested) in class.nested_classes.borrow().iter() {
+            self.visit_class(nested, &format!("{}::{}", path, name));
+        }
+    }
+
+    fn visit(&mut self, value: &Value, path: &str) {
+        // Reading a deferred query's type would run the query.
+        let type_name = match value {
+            Value::Deferred(_) => "Deferred".to_string(),
+            other => other.type_name(),
+        };
+        *self.counts.entry(type_name).or_insert(0) += 1;
+
+        match value {
+            Value::Array(items) => {
+                if !self.first_visit(items) {
+                    return;
+                }
+                let items = items.borrow();
+                self.arrays.push(Container {
+                    path: path.to_string(),
+                    len: items.len(),
+                });
+                for (i, item) in items.iter().enumerate() {
+                    self.visit(item, &format!("{}[{}]", path, i));
+                }
+            }
+            Value::Hash(pairs) => {
+                if !self.first_visit(pairs) {
+                    return;
+                }
+                let pairs = pairs.borrow();
+                self.hashes.push(Container {
+                    path: path.to_string(),
+                    len: pairs.len(),
+                });
+                for (key, item) in pairs.iter() {
+                    self.visit(item, &format!("{}[{}]", path, key.to_value()));
+                }
+            }
+            Value::Instance(inst) => {
+                if !self.first_visit(inst) {
+                    return;
+                }
+                let inst = inst.borrow();
+                for (name, field) in inst.fields.iter() {
+                    self.visit(field, &format!("{}.{}", path, name));
+                }
+            }
+            Value::Class(class) | Value::Super(class) => self.visit_class(class, path),
+            Value::Function(func) => self.visit_env(&func.closure, path),
+            Value::VmClosure(closure) => {
+                if !self.first_visit(closure) {
+                    return;
+                }
+                for upvalue in &closure.upvalues {
+                    if let Upvalue::Closed(captured) = &*upvalue.borrow() {
+                        self.visit(captured, path);
+                    }
+                }
+            }
+            Value::Method(method) => self.visit(&method.receiver, path),
+            _ => {}
+        }
+    }
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", path, name)
+    }
+}
+
+/// Plain-text report, as printed on `SIGUSR1`.
+pub fn render_text(report: &HeapReport) -> String {
+    use std::fmt::Write;
+
+    let mut out = String::new();
+    let _ = writeln!(out, "=== soli heap stats (pid {}) ===", std::process::id());
+    let shared = &report.shared;
+    let _ = writeln!(
+        out,
+        "template cache: {} parsed, {} paths",
+        shared.cached_templates, shared.cached_template_paths
+    );
+    let _ = writeln!(out, "vm global names: {}", shared.vm_global_names);
+    let _ = writeln!(out, "liveview instances: {}", shared.liveview_instances());
+    for (component, count) in &shared.liveview_by_component {
+        let _ = writeln!(out, "  {:<32} {}", component, count);
+    }
+    for worker in &report.workers {
+        let _ = writeln!(
+            out,
+            "worker {}: {} values, response cache {} entries ({} bytes), {} view helpers",
+            worker.worker_id,
+            worker.total_values(),
+            worker.response_cache_entries,
+            worker.response_cache_bytes,
+            worker.view_helpers
+        );
+        let mut by_count: Vec<_> = worker.values_by_type.iter().collect();
+        by_count.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        for (type_name, count) in by_count {
+            let _ = writeln!(out, "  {:<32} {}", type_name, count);
+        }
+        for (label, containers) in [
+            ("largest hashes", &worker.largest_hashes),
+            ("largest arrays", &worker.largest_arrays),
+        ] {
+            if containers.is_empty() {
+                continue;
+            }
+            let _ = writeln!(out, "  {}:", label);
+            for c in containers {
+                let _ = writeln!(out, "    {:>8}  {}", c.len, c.path);
+            }
+        }
+    }
+    if !report.busy.is_empty() {
+        let busy: Vec<String> = report.busy.iter().map(|id| id.to_string()).collect();
+        let _ = writeln!(out, "busy (no answer in time): {}", busy.join(", "));
+    }
+    out
+}
+
+/// JSON report, as served at `GET /__soli/heap`.
+pub fn render_json(report: &HeapReport) -> serde_json::Value {
+    let containers = |list: &[Container]| -> Vec<serde_json::Value> {
+        list.iter()
+            .map(|c| serde_json::json!({ "path": c.path, "len": c.len }))
+            .collect()
+    };
+    let workers: Vec<serde_json::Value> = report
+        .workers
+        .iter()
+        .map(|w| {
+            serde_json::json!({
+                "worker_id": w.worker_id,
+                "total_values": w.total_values(),
+                "values_by_type": w.values_by_type,
+                "largest_hashes": containers(&w.largest_hashes),
+                "largest_arrays": containers(&w.largest_arrays),
+                "response_cache": {
+                    "entries": w.response_cache_entries,
+                    "bytes": w.response_cache_bytes,
+                },
+                "view_helpers": w.view_helpers,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "pid": std::process::id(),
+        "workers": workers,
+        "busy": report.busy,
+        "template_cache": {
+            "templates": report.shared.cached_templates,
+            "paths": report.shared.cached_template_paths,
+        },
+        "vm_global_names": report.shared.vm_global_names,
+        "liveview": {
+            "instances": report.shared.liveview_instances(),
+            "by_component": report.shared.liveview_by_component,
+        },
+    })
+}
+
+/// Set by the `SIGUSR1` handler; polled by the dump thread.
+static DUMP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Install the `SIGUSR1` handler and start the thread that answers it.
+/// Idempotent.
+#[cfg(unix)]
+pub fn install_signal_handler() {
+    use std::sync::Once;
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        use nix::sys::signal::{sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal};
+
+        // Storing to an atomic is all a signal handler may safely do; the
+        // snapshot itself locks and allocates, so it runs on the thread below.
+        extern "C" fn handle(_sig: i32) {
+            DUMP_REQUESTED.store(true, Ordering::SeqCst);
+        }
+
+        let action = SigAction::new(
+            SigHandler::Handler(handle),
+            SaFlags::SA_RESTART,
+            SigSet::empty(),
+        );
+        // Best-effort: without the handler SIGUSR1 keeps its default action.
+        if unsafe { sigaction(Signal::SIGUSR1, &action) }.is_err() {
+            return;
+        }
+
+        std::thread::Builder::new()
+            .name("soli-heap-stats".to_string())
+            .spawn(|| loop {
+                if DUMP_REQUESTED.swap(false, Ordering::SeqCst) {
+                    eprint!("{}", render_text(&snapshot(SNAPSHOT_TIMEOUT)));
+                }
+                std::thread::sleep(Duration::from_millis(200));
+            })
+            .ok();
+    });
+}
+
+#[cfg(not(unix))]
+pub fn install_signal_handler() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+
+    fn run(source: &str) -> Interpreter {
+        let tokens = crate::lexer::Scanner::new(source).scan_tokens().unwrap();
+        let program = crate::parser::Parser::new(tokens).parse().unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.interpret(&program).unwrap();
+        interpreter
+    }
+
+    #[test]
+    fn counts_reachable_values_once_and_names_the_largest_containers() {
+        let interpreter = run(r#"
+            let cache = {"a": [1, 2, 3], "b": "x"};
+            let alias = cache;
+            let seen = [cache, cache];
+            class Store {
+                static memo: Hash = {"k": 1};
+            }
+            fn remember() { return cache; }
+        "#);
+        let heap = collect(0, &interpreter.environment, None);
+
+        // `cache` is walked once however many names reach it.
+        assert_eq!(heap.largest_hashes.iter().filter(|c| c.len == 2).count(), 1);
+        assert!(heap
+            .largest_hashes
+            .iter()
+            .any(|c| c.path == "Store.memo" && c.len == 1));
+        assert!(heap.largest_arrays.iter().any(|c| c.len == 3));
+        assert!(heap.values_by_type["Class"] >= 1);
+        assert!(heap.values_by_type["hash"] >= 2);
+    }
+
+    #[test]
+    fn snapshot_collects_registered_workers_and_reports_busy_ones() {
+        let answering = register_worker(9_001);
+        let _busy = register_worker(9_002);
+        let worker = std::thread::spawn(move || {
+            let reply = answering.recv().unwrap();
+            reply
+                .send(WorkerHeap {
+                    worker_id: 9_001,
+                    ..WorkerHeap::default()
+                })
+                .unwrap();
+        });
+
+        let report = snapshot(Duration::from_millis(300));
+        worker.join().unwrap();
+        assert!(report.workers.iter().any(|w| w.worker_id == 9_001));
+        assert!(report.busy.contains(&9_002));
+        assert!(!report.busy.contains(&9_001));
+
+        let text = render_text(&report);
+        assert!(text.contains("worker 9001:"));
+        assert!(text.contains("busy (no answer in time):"));
+        let json = render_json(&report);
+        assert!(json["busy"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!(9_002)));
+    }
+}
@@ -0,0 +1,232 @@
+//! `internal_request(method, path, params)` — dispatch a request through the
+//! app's router, middleware and controllers in-process, with no socket.
+//!
+//! The request can't run on the calling worker: that worker's interpreter is
+//! busy executing the caller, and every request-scoped thread-local (session,
+//! current request, flash, logs) would be clobbered by the inner request. It
+//! can't go to the shared worker queue either, since a caller waiting on its
+//! own pool deadlocks once every worker is waiting. Internal requests get
+//! their own pool instead: ordinary workers, loaded and hot-reloaded like the
+//! others, that drain a private queue. The pool starts on the first call, so
+//! apps that never use `internal_request` don't pay for the extra
+//! interpreters; that first call waits for the app to load.
+//!
+//! An internal request counts against the app's concurrency limits like any
+//! other, and carries the peer IP of the request that made it. One made
+//! outside a request (from a job, say) has no peer IP: `req["remote_addr"]`
+//! is absent, and it is never taken for the local host.
+
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::sync::{Arc, Once, OnceLock};
+use std::time::Duration;
+
+use crossbeam::channel;
+use tokio::sync::oneshot;
+
+use super::{concurrency, server_constants};
+use super::{RequestData, ResponseData, WorkerResponse};
+
+/// Thread-name prefix of the internal workers.
+pub(crate) const WORKER_ROLE: &str = "internal-worker";
+
+/// Spawns worker `id` draining `work_rx`.
+pub(crate) type SpawnWorker =
+    Arc<dyn Fn(usize, channel::Receiver<RequestData>) + Send + Sync + 'static>;
+
+struct Pool {
+    sender: channel::Sender<RequestData>,
+    receiver: channel::Receiver<RequestData>,
+    spawn: SpawnWorker,
+    first_id: usize,
+    size: usize,
+}
+
+static POOL: OnceLock<Pool> = OnceLock::new();
+static STARTED: Once = Once::new();
+
+thread_local! {
+    static ON_INTERNAL_WORKER: Cell<bool> = const { Cell::new(false) };
+    /// The peer IP of the request this thread is handling; empty between
+    /// requests.
+    static ORIGIN_PEER: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Make the pool available. `spawn` is called `size` times on first use, with
+/// worker ids counting up from `first_id`. A `size` of 0 disables
+/// `internal_request`.
+pub(crate) fn install(spawn: SpawnWorker, first_id: usize, size: usize) {
+    if size == 0 {
+        return;
+    }
+    let (sender, receiver) = channel::bounded(size * server_constants::CAPACITY_PER_WORKER);
+    let _ = POOL.set(Pool {
+        sender,
+        receiver,
+        spawn,
+        first_id,
+        size,
+    });
+}
+
+/// Mark the current thread as an internal worker, whose requests can't make
+/// internal requests of their own: with every internal worker waiting on a
+/// nested one, the pool would deadlock.
+pub(crate) fn set_internal_worker(internal: bool) {
+    ON_INTERNAL_WORKER.with(|flag| flag.set(internal));
+}
+
+/// Record the peer IP of the request this thread starts handling, for the
+/// internal requests it makes; `""` once it is done.
+pub(crate) fn set_origin_peer(peer_ip: &str) {
+    ORIGIN_PEER.with(|peer| {
+        let mut peer = peer.borrow_mut();
+        peer.clear();
+        peer.push_str(peer_ip);
+    });
+}
+
+/// A request as `internal_request` hands it to the pool.
+pub(crate) struct InternalRequest {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    pub(crate) query: Vec<(String, String)>,
+    pub(crate) headers: hyper::header::HeaderMap,
+    pub(crate) body: String,
+}
+
+/// Run `request` on the internal pool and wait for its response.
+pub(crate) fn dispatch(request: InternalRequest) -> Result<ResponseData, String> {
+    if ON_INTERNAL_WORKER.with(Cell::get) {
+        return Err(
+            "internal_request() can't be called while handling an internal request".to_string(),
+        );
+    }
+    let Some(pool) = POOL.get() else {
+        return Err(
+            "internal_request() is only available in `soli serve` (and is disabled by SOLI_INTERNAL_WORKERS=0)"
+                .to_string(),
+        );
+    };
+    let Some(handle) = super::get_tokio_handle() else {
+        return Err("internal_request() has no runtime on this thread".to_string());
+    };
+    let _permit = admit(&request.path)?;
+    STARTED.call_once(|| {
+        for k in 0..pool.size {
+            (pool.spawn)(pool.first_id + k, pool.receiver.clone());
+        }
+    });
+
+    let (response_tx, response_rx) = oneshot::channel();
+    let data = request_data(request, response_tx);
+    let wait = Duration::from_secs(server_constants::RESPONSE_WAIT_TIMEOUT_SECS);
+    pool.sender
+        .send_timeout(data, wait)
+        .map_err(|_| "internal_request(): the internal workers are busy".to_string())?;
+
+    handle.block_on(async move {
+        let response = tokio::time::timeout(wait, response_rx)
+            .await
+            .map_err(|_| format!("internal_request(): no response within {}s", wait.as_secs()))?
+            .map_err(|_| "internal_request(): the worker dropped the request".to_string())?;
+        Ok(match response {
+            WorkerResponse::Buffered(data) => data,
+            WorkerResponse::Stream {
+                status,
+                headers,
+                mut rx,
+            } => {
+                let mut body = Vec::new();
+                while let Some(chunk) = rx.recv().await {
+                    body.extend_from_slice(&chunk);
+                }
+                ResponseData {
+                    status,
+                    headers,
+                    body,
+                }
+            }
+        })
+    })
+}
+
+/// Take the concurrency slots of a request to `path`, held until its
+/// response is in.
+fn admit(path: &str) -> Result<concurrency::Permit, String> {
+    concurrency::acquire(path).map_err(|busy| {
+        crate::metrics::Metrics::global()
+            .http_requests_shed_total
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        format!(
+            "internal_request(): {} is over its concurrency limit, retry after {}s",
+            path, busy.retry_after
+        )
+    })
+}
+
+/// The pool's form of `request`, from the peer of the request this thread
+/// is handling.
+fn request_data(
+    request: InternalRequest,
+    response_tx: oneshot::Sender<WorkerResponse>,
+) -> RequestData {
+    RequestData {
+        method: Cow::Owned(request.method),
+        path: request.path,
+        query: request.query,
+        headers: request.headers,
+        body: request.body,
+        body_bytes: None,
+        multipart_form: None,
+        multipart_files: None,
+        peer_ip: ORIGIN_PEER.with(|peer| peer.borrow().clone()),
+        enqueued_at: super::prod_log::channels()
+            .any()
+            .then(std::time::Instant::now),
+        replay: false,
+        response_tx,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(path: &str) -> InternalRequest {
+        InternalRequest {
+            method: "GET".to_string(),
+            path: path.to_string(),
+            query: Vec::new(),
+            headers: hyper::header::HeaderMap::new(),
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn internal_requests_carry_the_peer_of_the_request_making_them() {
+        set_origin_peer("203.0.113.7");
+        let data = request_data(request("/posts"), oneshot::channel().0);
+        assert_eq!(data.peer_ip, "203.0.113.7");
+
+        // Outside a request (a job, say) there is no peer, and in particular
+        // not the local host.
+        set_origin_peer("");
+        let data = request_data(request("/posts"), oneshot::channel().0);
+        assert_eq!(data.peer_ip, "");
+        assert!(!super::super::is_trusted_dev_peer_str(&data.peer_ip));
+    }
+
+    #[test]
+    fn internal_requests_count_against_concurrency_limits() {
+        let _rules = concurrency::lock_rules();
+        concurrency::set_route_limit("/internal-test/reports".to_string(), 1, 9);
+        let running = concurrency::acquire("/internal-test/reports").unwrap();
+        let refused = admit("/internal-test/reports").err().unwrap();
+        assert!(refused.contains("retry after 9s"), "{}", refused);
+        drop(running);
+        let admitted = admit("/internal-test/reports").unwrap();
+        assert!(concurrency::acquire("/internal-test/reports").is_err());
+        drop(admitted);
+    }
+}
@@ -0,0 +1,144 @@
+//! Orderly stop for `soli serve`, so `on_shutdown` and `at_exit` hooks run.
+//!
+//! The default SIGTERM/SIGINT handler (`main.rs`) exits on the spot. When an
+//! app has a `config/boot.sl`, serve replaces it with one that only sets a
+//! flag; a watcher thread then asks every worker to stop. Each worker answers
+//! between requests — finishing the one in flight first — by running its
+//! `on_shutdown` and `at_exit` hooks and leaving its loop. The watcher waits
+//! up to [`SHUTDOWN_TIMEOUT`] for all of them, then exits the process. A
+//! second signal exits immediately.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crossbeam::channel;
+
+/// How long a stop waits for workers to finish their hooks.
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A stop request: the worker replies with its id once its hooks have run.
+pub type Reply = channel::Sender<usize>;
+
+fn workers() -> &'static Mutex<BTreeMap<usize, channel::Sender<Reply>>> {
+    static WORKERS: OnceLock<Mutex<BTreeMap<usize, channel::Sender<Reply>>>> = OnceLock::new();
+    WORKERS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Register a worker (again, after a restart) and return the channel its
+/// loop must answer on.
+pub fn register_worker(worker_id: usize) -> channel::Receiver<Reply> {
+    let (tx, rx) = channel::bounded(1);
+    if let Ok(mut map) = workers().lock() {
+        map.insert(worker_id, tx);
+    }
+    rx
+}
+
+/// Ask every registered worker to stop and wait up to `timeout`. Returns the
+/// ids of the workers that didn't finish in time.
+pub fn stop_workers(timeout: Duration) -> Vec<usize> {
+    let (reply_tx, reply_rx) = channel::unbounded();
+    let mut pending: Vec<usize> = Vec::new();
+    if let Ok(map) = workers().lock() {
+        for (&id, tx) in map.iter() {
+            if tx.try_send(reply_tx.clone()).is_ok() {
+                pending.push(id);
+            }
+        }
+    }
+    drop(reply_tx);
+
+    let deadline = Instant::now() + timeout;
+    while !pending.is_empty() {
+        match reply_rx.recv_deadline(deadline) {
+            Ok(id) => pending.retain(|&p| p != id),
+            Err(_) => break,
+        }
+    }
+    pending
+}
+
+/// Set by the signal handler; polled by the watcher thread.
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Take over SIGTERM/SIGINT and start the thread that stops the workers.
+/// Idempotent.
+#[cfg(unix)]
+pub fn install_signal_handler() {
+    use std::sync::Once;
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        use nix::sys::signal::{sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal};
+
+        // The first signal only flags the stop; running hooks locks and
+        // allocates, so it happens on the thread below. A second signal means
+        // the operator won't wait.
+        extern "C" fn handle(_sig: i32) {
+            if STOP_REQUESTED.swap(true, Ordering::SeqCst) {
+                std::process::exit(0);
+            }
+        }
+
+        let action = SigAction::new(
+            SigHandler::Handler(handle),
+            SaFlags::SA_RESTART,
+            SigSet::empty(),
+        );
+        // Best-effort: without the handler the default one stays in place.
+        unsafe {
+            if sigaction(Signal::SIGTERM, &action).is_err()
+                || sigaction(Signal::SIGINT, &action).is_err()
+            {
+                return;
+            }
+        }
+
+        std::thread::Builder::new()
+            .name("soli-lifecycle".to_string())
+            .spawn(|| loop {
+                if STOP_REQUESTED.load(Ordering::SeqCst) {
+                    eprintln!("Shutting down: running on_shutdown hooks...");
+                    let late = stop_workers(SHUTDOWN_TIMEOUT);
+                    if !late.is_empty() {
+                        eprintln!("Shutdown timed out waiting for worker(s) {:?}", late);
+                    }
+                    // An in-process desktop shell registers its own cleanup
+                    // (database stop, decrypted tree); this handler replaced
+                    // the one that would have run it.
+                    crate::desktop::shutdown::run();
+                    std::process::exit(0);
+                }
+                std::thread::sleep(Duration::from_millis(100));
+            })
+            .ok();
+    });
+}
+
+#[cfg(not(unix))]
+pub fn install_signal_handler() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stop_waits_for_answering_workers_and_reports_the_rest() {
+        let answering = register_worker(901);
+        let _silent = register_worker(902);
+        let worker = std::thread::spawn(move || {
+            let reply = answering.recv().unwrap();
+            reply.send(901).unwrap();
+        });
+
+        let late = stop_workers(Duration::from_millis(300));
+        worker.join().unwrap();
+        assert_eq!(late, vec![902]);
+
+        if let Ok(mut map) = workers().lock() {
+            map.remove(&901);
+            map.remove(&902);
+        }
+    }
+}
@@ -8,13 +8,18 @@
 
 mod asset_cache;
 pub mod camera;
+pub mod concurrency;
+pub mod config_schema;
 pub mod cors;
 mod csrf;
 mod db_browser;
 pub mod dev_bar;
 mod dev_catalog;
 pub mod dev_store;
+pub mod heap_stats;
 mod hot_reload;
+pub mod internal_request;
+pub mod lifecycle;
 pub mod live_reload;
 mod live_reload_ws; // WebSocket-based live reload
 pub(crate) mod middleware;
@@ -26,10 +31,14 @@ mod origin;
 pub mod phase_log;
 pub mod prefetch;
 pub mod prod_log;
+pub mod profiler;
+pub mod response_shape;
+pub mod route_check;
 pub mod route_listing;
 pub mod route_log;
 mod router;
-mod server_constants;
+pub mod scheduler_lease;
+pub(crate) mod server_constants;
 pub mod span_log;
 pub mod template_warnings;
 mod uploads_prelude;
@@ -46,11 +55,15 @@ mod error_pages;
 mod file_tracker;
 pub(crate) mod file_upload;
 mod json;
-mod repl_session;
+pub(crate) mod repl_session;
 mod tailwind;
+mod tailwind_builtin;
+mod template_error;
+mod unit_cache;
 mod worker_pool;
 
 pub use crate::interpreter::builtins::router::{get_controllers, set_controllers};
+pub use app_loader::precompile_app;
 pub use hot_reload::FileTracker;
 pub use middleware::{
     clear_middleware, extract_middleware_functions, extract_middleware_result, get_middleware,
@@ -61,7 +74,7 @@ pub use router::{derive_routes_from_controller, ControllerRoute};
 pub use websocket::{
     clear_websocket_routes, get_runtime_handle, get_websocket_routes, match_websocket_route,
     register_websocket_route, restore_websocket_routes, set_runtime_handle, take_websocket_routes,
-    WebSocketConnection, WebSocketEvent, WebSocketHandlerAction, WebSocketRegistry,
+    SessionDrop, WebSocketConnection, WebSocketEvent, WebSocketHandlerAction, WebSocketRegistry,
 };
 
 use std::borrow::Cow;
@@ -183,6 +196,15 @@ fn dev_repl_secret_set() -> bool {
         .is_some_and(|s| !s.is_empty())
 }
 
+/// Stack for threads that run app code: enough for the workers' call-depth
+/// limit, so runaway recursion fails with a Soli error rather than
+/// overflowing the thread's stack.
+pub(crate) fn worker_stack_size() -> usize {
+    crate::interpreter::executor::stack_size_for_depth(
+        crate::interpreter::executor::worker_call_depth(),
+    )
+}
+
 fn boot_trace(phase: &str) {
     if std::env::var("SOLI_TRACE_BOOT").is_err() {
         return;
@@ -276,9 +298,8 @@ static LV_EVENT_TX: std::sync::OnceLock<channel::Sender<LiveViewEventData>> =
 use crate::interpreter::builtins::controller::controller::ControllerInfo;
 use crate::interpreter::builtins::controller::CONTROLLER_REGISTRY;
 use crate::interpreter::builtins::session::{
-    clear_response_cookies, ensure_session, finalize_session_cookie, get_current_session_id,
-    parse_cookie_pairs, session_id_from_cookie_pairs, set_current_session_id,
-    take_response_cookies,
+    ensure_session, finalize_session_cookie, get_current_session_id, parse_cookie_pairs,
+    session_id_from_cookie_pairs, set_current_session_id, take_response_cookies,
 };
 use crate::interpreter::builtins::template::{clear_template_cache, init_templates};
 use crate::interpreter::value::{HashKey, HashPairs};
@@ -354,6 +375,20 @@ pub(crate) struct ResponseData {
     pub(crate) body: Vec<u8>,
 }
 
+/// Headers for a streamed response: the stream's own, plus any cookies the
+/// request set (the session cookie, flash), which would otherwise be lost
+/// with the discarded buffered response.
+fn stream_headers(
+    spec: &crate::interpreter::builtins::streaming::StreamSpec,
+    resp_data: &ResponseData,
+) -> Vec<(String, String)> {
+    let cookies = resp_data
+        .headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("set-cookie"));
+    spec.headers.iter().chain(cookies).cloned().collect()
+}
+
 /// Worker → service reply. Buffered responses carry a complete `ResponseData`;
 /// streaming responses carry the status/headers plus a channel the worker feeds
 /// body chunks into (handler-driven SSE / chunked bodies).
@@ -571,6 +606,15 @@ pub fn serve_folder_with_options_and_hooks(
     load_env_files(folder);
     boot_trace("env loaded");
 
+    // Check the env against config/schema.sl (and export its defaults) before
+    // anything below reads a variable. Like an invalid SOLI_HOST, a bad env is
+    // the operator's to fix, so print the whole report and stop.
+    if let Err(report) = config_schema::validate(folder) {
+        eprintln!("{}", report);
+        std::process::exit(1);
+    }
+    boot_trace("config schema validated");
+
     // Cache SoliDB host/database/api-key/basic-auth derived from the env
     // we just loaded. Must run before `init_jwt_token` so the JWT login
     // and the cursor URL see the same `SOLIDB_HOST` parse.
@@ -644,6 +688,8 @@ pub fn serve_folder_with_options_and_hooks(
     // Define the Mailer/Message base classes before any app code that may
     // subclass Mailer (app/mailers/*.sl) loads.
     crate::interpreter::builtins::mailer::ensure_prelude(&mut interpreter);
+    crate::interpreter::builtins::events::ensure_prelude(&mut interpreter);
+    crate::interpreter::builtins::export::ensure_prelude(&mut interpreter);
     boot_trace("interpreter created");
 
     // Load models first (shared code)
@@ -831,7 +877,8 @@ pub fn serve_folder_with_options_and_hooks(
     // is handled.
     let application_file = folder.join("config").join("application.sl");
     if application_file.exists() {
-        execute_file(&mut interpreter, &application_file)?;
+        execute_file(&mut interpreter, &application_file)
+            .map_err(|e| e.with_context("while loading config/application.sl"))?;
     }
     boot_trace("application config loaded");
 
@@ -853,9 +900,11 @@ pub fn serve_folder_with_options_and_hooks(
 
         // Clear auto-derived routes to prefer explicit ones
         crate::interpreter::builtins::server::clear_routes();
+        crate::serve::response_shape::clear();
 
         // Execute routes file
-        execute_file(&mut interpreter, &routes_file)?;
+        execute_file(&mut interpreter, &routes_file)
+            .map_err(|e| e.with_context("while loading config/routes.sl"))?;
 
         // Load engine routes
         if let Err(e) = engine_loader::load_engine_routes(&mut interpreter) {
@@ -865,8 +914,29 @@ pub fn serve_folder_with_options_and_hooks(
         // Rebuild route index to include engine routes
         crate::interpreter::builtins::server::rebuild_route_index();
     }
+
+    // Routes declared with `@route` on controller actions, after routes.sl so
+    // an explicit route for the same path wins.
+    crate::interpreter::builtins::controller::registry::register_annotated_routes();
+    crate::interpreter::builtins::server::rebuild_route_index();
     boot_trace("routes loaded");
 
+    // config/boot.sl registers the lifecycle hooks. The boot thread runs its
+    // `on_boot` hooks here, once, before any worker exists; each worker
+    // evaluates the file again for its own `on_worker_start`, `on_shutdown`
+    // and `at_exit` hooks, so whatever this thread registered for those is
+    // dropped.
+    let boot_file = folder.join("config").join("boot.sl");
+    if boot_file.exists() {
+        use crate::interpreter::builtins::lifecycle::{clear_hooks, run_hooks, Hook};
+        execute_file(&mut interpreter, &boot_file)
+            .map_err(|e| e.with_context("while loading config/boot.sl"))?;
+        run_hooks(&mut interpreter, Hook::Boot);
+        clear_hooks();
+        lifecycle::install_signal_handler();
+    }
+    boot_trace("boot hooks run");
+
     // Reclaim the boot interpreter. Its only job was to populate the shared,
     // process-global registries (routes, controller/model metadata, the
     // template cache) that the router and worker pool read — it never serves a
@@ -1396,7 +1466,8 @@ fn run_hyper_server_worker_pool(
                     .into_iter()
                     .filter(|path| {
                         if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                            matches!(ext, "sl" | "erb" | "slv" | "md")
+                            ext == "sl"
+                                || crate::template::engines::is_template_extension(ext)
                                 || server_constants::is_tracked_static_extension(ext)
                         } else {
                             false
@@ -1478,10 +1549,7 @@ fn run_hyper_server_worker_pool(
                             models_changed = true;
                         } else if name.ends_with("_job.sl") && path.starts_with(&watch_jobs_dir) {
                             jobs_changed = true;
-                        } else if name.ends_with(".erb")
-                            || name.ends_with(".slv")
-                            || name.ends_with(".md")
-                        {
+                        } else if crate::template::engines::is_template_file(path) {
                             views_changed = true;
                         }
                     }
@@ -1816,20 +1884,18 @@ fn run_hyper_server_worker_pool(
         }
     }
 
-    for i in 0..num_workers {
-        // Role for this worker. When the pool isn't split, every worker drains
-        // all channels (prior behavior). Otherwise the first `num_http_workers`
-        // serve HTTP and the rest serve realtime events exclusively.
-        let (http_enabled, realtime_enabled, role_label) = if !split_realtime {
-            (true, true, "worker")
-        } else if i < num_http_workers {
-            (true, false, "worker")
-        } else {
-            (false, true, "rt-worker")
-        };
-        // Every worker shares the one queue (clones of the same receiver),
-        // competing to pull whichever request is next.
-        let work_rx = worker_queues.get_receiver(i);
+    // With several instances, only the scheduler lease holder points cron
+    // schedules at itself. Joined before the workers load `app/jobs`.
+    if jobs_dir.exists() && scheduler_lease::enabled() {
+        scheduler_lease::start();
+    }
+
+    // `kill -USR1 <pid>` dumps per-worker heap stats to stderr.
+    heap_stats::install_signal_handler();
+
+    // Spawns worker `i`, draining `work_rx`. Used for the pool below and for
+    // the internal pool that `internal_request()` starts on first use.
+    let spawn_worker = {
         let models_dir = models_dir.clone();
         let middleware_dir = middleware_dir.clone();
         let helpers_dir = helpers_dir.clone();
@@ -1844,16 +1910,12 @@ fn run_hyper_server_worker_pool(
         let runtime_handle = runtime_handle.clone();
         let routes_file = routes_file.clone();
         let jobs_dir = jobs_dir.clone();
-
-        let builder = thread::Builder::new().name(format!("{}-{}", role_label, i));
-        let handler = builder.spawn(move || {
-            // Set tokio runtime handle for this worker thread (used by HTTP builtins)
-            set_tokio_handle(runtime_handle.clone());
-
-            // Auto-restart loop: if the worker panics, recreate interpreter and resume
-            loop {
-                // Clone values for this iteration (cheap Arc/crossbeam clones)
-                let work_rx = work_rx.clone();
+        Arc::new(
+            move |i: usize,
+                  work_rx: channel::Receiver<RequestData>,
+                  http_enabled: bool,
+                  realtime_enabled: bool,
+                  role_label: &'static str| {
                 let models_dir = models_dir.clone();
                 let middleware_dir = middleware_dir.clone();
                 let helpers_dir = helpers_dir.clone();
@@ -1869,45 +1931,113 @@ fn run_hyper_server_worker_pool(
                 let routes_file = routes_file.clone();
                 let jobs_dir = jobs_dir.clone();
 
-                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                    let mut interpreter = Interpreter::new_for_serve();
-                    // Mailer/Message base classes available before app load.
-                    crate::interpreter::builtins::mailer::ensure_prelude(&mut interpreter);
-
-                    worker_loop(
-                        i,
-                        work_rx,
-                        models_dir,
-                        middleware_dir,
-                        helpers_dir,
-                        ws_event_rx,
-                        lv_event_rx,
-                        ws_registry,
-                        reload_tx,
-                        &mut interpreter,
-                        worker_routes,
-                        controllers_dir,
-                        views_dir,
-                        hot_reload_versions,
-                        runtime_handle,
-                        routes_file,
-                        dev_mode,
-                        jobs_dir,
-                        http_enabled,
-                        realtime_enabled,
+                let builder = thread::Builder::new()
+                    .name(format!("{}-{}", role_label, i))
+                    .stack_size(worker_stack_size());
+                builder.spawn(move || {
+                    // Set tokio runtime handle for this worker thread (used by HTTP builtins)
+                    set_tokio_handle(runtime_handle.clone());
+                    internal_request::set_internal_worker(
+                        role_label == internal_request::WORKER_ROLE,
                     );
-                }));
 
-                match result {
-                    Ok(_) => break, // Normal exit
-                    Err(_) => {
-                        eprintln!("Worker {} panicked, restarting...", i);
+                    // Auto-restart loop: if the worker panics, recreate interpreter and resume
+                    loop {
+                        // Clone values for this iteration (cheap Arc/crossbeam clones)
+                        let work_rx = work_rx.clone();
+                        let models_dir = models_dir.clone();
+                        let middleware_dir = middleware_dir.clone();
+                        let helpers_dir = helpers_dir.clone();
+                        let ws_event_rx = ws_event_rx.clone();
+                        let lv_event_rx = lv_event_rx.clone();
+                        let ws_registry = ws_registry.clone();
+                        let reload_tx = reload_tx.clone();
+                        let worker_routes = worker_routes.clone();
+                        let controllers_dir = controllers_dir.clone();
+                        let views_dir = views_dir.clone();
+                        let hot_reload_versions = hot_reload_versions.clone();
+                        let runtime_handle = runtime_handle.clone();
+                        let routes_file = routes_file.clone();
+                        let jobs_dir = jobs_dir.clone();
+
+                        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                            let mut interpreter = Interpreter::new_for_serve();
+                            // Mailer/Message base classes available before app load.
+                            crate::interpreter::builtins::mailer::ensure_prelude(&mut interpreter);
+                            crate::interpreter::builtins::events::ensure_prelude(&mut interpreter);
+                            crate::interpreter::builtins::export::ensure_prelude(&mut interpreter);
+
+                            worker_loop(
+                                i,
+                                work_rx,
+                                models_dir,
+                                middleware_dir,
+                                helpers_dir,
+                                ws_event_rx,
+                                lv_event_rx,
+                                ws_registry,
+                                reload_tx,
+                                &mut interpreter,
+                                worker_routes,
+                                controllers_dir,
+                                views_dir,
+                                hot_reload_versions,
+                                runtime_handle,
+                                routes_file,
+                                dev_mode,
+                                jobs_dir,
+                                http_enabled,
+                                realtime_enabled,
+                            );
+                        }));
+
+                        match result {
+                            Ok(_) => break, // Normal exit
+                            Err(_) => {
+                                eprintln!("Worker {} panicked, restarting...", i);
+                            }
+                        }
                     }
+                })
+            },
+        )
+    };
+
+    // `internal_request()` workers (`SOLI_INTERNAL_WORKERS`, default
+    // 2; 0 disables it). Ids follow the regular workers'.
+    {
+        let num_internal_workers = std::env::var("SOLI_INTERNAL_WORKERS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(server_constants::DEFAULT_INTERNAL_WORKERS);
+        let spawn_worker = spawn_worker.clone();
+        internal_request::install(
+            Arc::new(move |i, work_rx| {
+                let role = internal_request::WORKER_ROLE;
+                if let Err(e) = spawn_worker(i, work_rx, true, false, role) {
+                    eprintln!("Failed to spawn internal worker {}: {}", i, e);
                 }
-            }
-        });
+            }),
+            num_workers,
+            num_internal_workers,
+        );
+    }
 
-        match handler {
+    for i in 0..num_workers {
+        // Role for this worker. When the pool isn't split, every worker drains
+        // all channels (prior behavior). Otherwise the first `num_http_workers`
+        // serve HTTP and the rest serve realtime events exclusively.
+        let (http_enabled, realtime_enabled, role_label) = if !split_realtime {
+            (true, true, "worker")
+        } else if i < num_http_workers {
+            (true, false, "worker")
+        } else {
+            (false, true, "rt-worker")
+        };
+        // Every worker shares the one queue (clones of the same receiver),
+        // competing to pull whichever request is next.
+        let work_rx = worker_queues.get_receiver(i);
+        match spawn_worker(i, work_rx, http_enabled, realtime_enabled, role_label) {
             Ok(h) => workers.push(h),
             Err(e) => eprintln!("Failed to spawn worker {}: {}", i, e),
         }
@@ -1935,7 +2065,7 @@ fn warm_vm_handlers(worker_id: usize, vm: &crate::vm::Vm) {
     // Seed the compiler with the worker's full set of global names so bare
     // assignments inside handlers resolve local-vs-global exactly as the
     // tree-walking interpreter would.
-    let global_names: Vec<String> = vm.globals.keys().cloned().collect();
+    let global_names: Vec<String> = vm.globals.names().collect();
     for value in vm.globals.values() {
         if let crate::interpreter::value::Value::Function(f) = value {
             if crate::vm::vm_calls::jit_compile_function(f, global_names.iter().cloned()).is_ok() {
@@ -2016,8 +2146,11 @@ fn worker_loop(
     // Off in production unless an operator opts a channel in via SOLI_LOG;
     // otherwise the gate is a single relaxed atomic load.
     let log_channels = prod_log::channels();
+    // `SOLI_PROFILE` reads the query log and the phase/view timers for its
+    // `Server-Timing` breakdown, so it turns those three on as well.
+    let profiling = profiler::profile_enabled();
     crate::interpreter::builtins::model::query_log::set_enabled(
-        dev_mode || log_channels.collect_query(),
+        dev_mode || log_channels.collect_query() || profiling,
     );
 
     // Same for outgoing HTTP.* calls — feeds the dev bar's "http" panel.
@@ -2027,9 +2160,9 @@ fn worker_loop(
     crate::interpreter::builtins::kv_log::set_enabled(dev_mode || log_channels.collect_kv());
 
     // Phase timers (middleware/view) for the render-breakdown panel.
-    phase_log::set_enabled(dev_mode || log_channels.collect_timing());
+    phase_log::set_enabled(dev_mode || log_channels.collect_timing() || profiling);
     middleware_log::set_enabled(dev_mode || log_channels.collect_timing());
-    view_log::set_enabled(dev_mode || log_channels.collect_timing());
+    view_log::set_enabled(dev_mode || log_channels.collect_timing() || profiling);
     // The span flamegraph stays dev-only — it's a visualization the prod
     // log block doesn't consume, and it's the heaviest of the gates.
     span_log::set_enabled(dev_mode);
@@ -2117,9 +2250,30 @@ fn worker_loop(
         crate::interpreter::builtins::named_routes::register_named_route_helpers(&mut env);
     }
 
+    // This worker's lifecycle hooks. Loaded before the VM globals copy so
+    // anything boot.sl defines is visible to handlers; `on_boot` already ran
+    // on the boot thread.
+    let boot_file = routes_file.with_file_name("boot.sl");
+    if boot_file.exists() {
+        use crate::interpreter::builtins::lifecycle::{run_hooks, take_hooks, Hook};
+        match execute_file(interpreter, &boot_file) {
+            Ok(()) => {
+                take_hooks(Hook::Boot);
+                run_hooks(interpreter, Hook::WorkerStart);
+            }
+            Err(e) => eprintln!(
+                "Worker {}: Error loading {}: {}",
+                worker_id,
+                boot_file.display(),
+                e.report()
+            ),
+        }
+    }
+
     // Create VM for production mode (bytecode execution for handler calls)
     let mut vm: Option<crate::vm::Vm> = if !dev_mode {
         let mut vm = crate::vm::Vm::new();
+        vm.set_max_depth(interpreter.max_depth());
         // Copy all globals from interpreter environment into VM
         // This includes all native builtins, classes, and user-defined functions
         let all_globals = interpreter.environment.borrow().get_all_bindings();
@@ -2144,6 +2298,11 @@ fn worker_loop(
     let ws_registry_inner = realtime_enabled.then_some(ws_registry);
     let mut lv_event_rx_inner = realtime_enabled.then_some(lv_event_rx);
 
+    // Heap-stats requests (`SIGUSR1`, `/__soli/heap`) for this worker.
+    let heap_rx = heap_stats::register_worker(worker_id);
+    // Stop requests (SIGTERM/SIGINT when the app has a config/boot.sl).
+    let stop_rx = lifecycle::register_worker(worker_id);
+
     // Track last seen hot reload versions
     let mut last_generation = hot_reload_versions.generation.load(Ordering::Acquire);
     let mut last_controllers_version = hot_reload_versions.controllers.load(Ordering::Acquire);
@@ -2351,6 +2510,20 @@ fn worker_loop(
 
         // Drain all pending events non-blockingly before sleeping
 
+        if let Ok(reply) = stop_rx.try_recv() {
+            stop_worker(worker_id, interpreter, reply);
+            return;
+        }
+
+        if let Ok(reply) = heap_rx.try_recv() {
+            let vm_globals = vm.as_ref().map(|vm| &vm.globals);
+            let _ = reply.send(heap_stats::collect(
+                worker_id,
+                &interpreter.environment,
+                vm_globals,
+            ));
+        }
+
         // Process WebSocket events (quick non-blocking check)
         if let (Some(ref mut rx), Some(_registry)) =
             (ws_event_rx_inner.as_mut(), ws_registry_inner.as_ref())
@@ -2398,13 +2571,14 @@ fn worker_loop(
                 match work_rx.try_recv() {
                     Ok(mut data) => {
                         crate::interpreter::builtins::streaming::clear_pending_stream();
+                        interpreter.reset_request_state();
                         let resp_data = handle_request(interpreter, &mut vm, &mut data, dev_mode);
                         match crate::interpreter::builtins::streaming::take_pending_stream() {
                             Some(spec) => {
                                 let (tx, rx) = tokio::sync::mpsc::channel::<Vec<u8>>(64);
                                 let resp = WorkerResponse::Stream {
                                     status: spec.status,
-                                    headers: spec.headers.clone(),
+                                    headers: stream_headers(&spec, &resp_data),
                                     rx,
                                 };
                                 if let Some(topic) = spec.subscribe_topic.clone() {
@@ -2449,6 +2623,10 @@ fn worker_loop(
             }
         }
 
+        // Free the cyclic garbage the requests just handled left behind,
+        // while no Soli code runs on this worker.
+        crate::interpreter::cycles::maybe_collect();
+
         // Block waiting for events on any channel using crossbeam select.
         // This avoids busy-waiting: the thread sleeps until an event arrives
         // on any channel (or timeout fires for dev-mode hot reload checks).
@@ -2460,6 +2638,8 @@ fn worker_loop(
                 .filter(|_| ws_registry_inner.is_some())
                 .map(|rx| sel.recv(rx));
             let lv_idx = lv_event_rx_inner.as_ref().map(|rx| sel.recv(rx));
+            let heap_idx = sel.recv(&heap_rx);
+            let stop_idx = sel.recv(&stop_rx);
 
             let result = if dev_mode {
                 // Dev mode: use timeout so we periodically check hot reload versions
@@ -2471,7 +2651,21 @@ fn worker_loop(
 
             if let Ok(oper) = result {
                 let idx = oper.index();
-                if Some(idx) == work_idx {
+                if idx == stop_idx {
+                    if let Ok(reply) = oper.recv(&stop_rx) {
+                        stop_worker(worker_id, interpreter, reply);
+                        return;
+                    }
+                } else if idx == heap_idx {
+                    if let Ok(reply) = oper.recv(&heap_rx) {
+                        let vm_globals = vm.as_ref().map(|vm| &vm.globals);
+                        let _ = reply.send(heap_stats::collect(
+                            worker_id,
+                            &interpreter.environment,
+                            vm_globals,
+                        ));
+                    }
+                } else if Some(idx) == work_idx {
                     if let Ok(mut data) = oper.recv(&work_rx) {
                         // Check hot reload before handling: a parked worker
                         // serves this request before the loop-top version scan
@@ -2487,13 +2681,14 @@ fn worker_loop(
                             }
                         }
                         crate::interpreter::builtins::streaming::clear_pending_stream();
+                        interpreter.reset_request_state();
                         let resp_data = handle_request(interpreter, &mut vm, &mut data, dev_mode);
                         match crate::interpreter::builtins::streaming::take_pending_stream() {
                             Some(spec) => {
                                 let (tx, rx) = tokio::sync::mpsc::channel::<Vec<u8>>(64);
                                 let resp = WorkerResponse::Stream {
                                     status: spec.status,
-                                    headers: spec.headers.clone(),
+                                    headers: stream_headers(&spec, &resp_data),
                                     rx,
                                 };
                                 if let Some(topic) = spec.subscribe_topic.clone() {
@@ -2558,6 +2753,18 @@ fn worker_loop(
     }
 }
 
+/// Run this worker's `on_shutdown` then `at_exit` hooks and tell the stop
+/// watcher it's done. The caller leaves the worker loop right after.
+fn stop_worker(worker_id: usize, interpreter: &mut Interpreter, reply: lifecycle::Reply) {
+    use crate::interpreter::builtins::lifecycle::{run_hooks, Hook};
+    run_hooks(interpreter, Hook::Shutdown);
+    run_hooks(interpreter, Hook::AtExit);
+    if worker_id == 0 {
+        scheduler_lease::resign();
+    }
+    let _ = reply.send(worker_id);
+}
+
 /// Data for WebSocket events sent to the interpreter thread.
 struct WebSocketEventData {
     path: String,
@@ -2967,8 +3174,8 @@ async fn handle_hyper_request(
     }
 
     // Check for static file in public directory
-    if method == "GET" && public_dir.exists() {
-        match resolve_static_file(&path, &public_dir) {
+    if method == "GET" {
+        match resolve_asset_file(&path, &public_dir) {
             Err(()) => {
                 return Ok(Response::builder()
                     .status(StatusCode::FORBIDDEN)
@@ -3231,6 +3438,11 @@ async fn handle_hyper_request(
     if path == "/__soli/camera.js" && method == "GET" {
         return Ok(box_full(camera::handle_camera_js()));
     }
+
+    // Resumable WebSocket client (`SoliSocket`).
+    if path == "/__soli/ws.js" && method == "GET" {
+        return Ok(box_full(websocket::handle_ws_js()));
+    }
     if path == "/__soli/native/stream" && method == "GET" {
         return Ok(match native::topic_for_query(raw_query.as_deref()) {
             Some(topic) => native_stream_response(&topic),
@@ -3332,6 +3544,19 @@ async fn handle_hyper_request(
                 return Ok(handle_mailer_preview(rel));
             }
         }
+        // Per-worker heap stats as JSON. Waiting on the workers blocks, so it
+        // runs under block_in_place like the DB browser below.
+        if method == "GET" && path == "/__soli/heap" {
+            let report =
+                tokio::task::block_in_place(|| heap_stats::snapshot(heap_stats::SNAPSHOT_TIMEOUT));
+            return Ok(Response::builder()
+                .status(StatusCode::OK)
+                .header("Content-Type", "application/json")
+                .body(full(Bytes::from(
+                    heap_stats::render_json(&report).to_string(),
+                )))
+                .unwrap());
+        }
         // Database browser, dev-only: list collections, page rows, view a
         // document, run a read-only query. Sync DB calls are wrapped in
         // block_in_place inside the handlers (this is an async hyper task).
@@ -3387,6 +3612,23 @@ async fn handle_hyper_request(
             .unwrap());
     }
 
+    // Concurrency limits (`limit_concurrency` in config/routes.sl). Checked
+    // before the body is read, and held until the worker replies.
+    let _permit = match concurrency::acquire(&path) {
+        Ok(permit) => permit,
+        Err(busy) => {
+            crate::metrics::Metrics::global()
+                .http_requests_shed_total
+                .fetch_add(1, Ordering::Relaxed);
+            return Ok(Response::builder()
+                .status(StatusCode::SERVICE_UNAVAILABLE)
+                .header("Retry-After", busy.retry_after.to_string())
+                .header("Content-Type", "text/plain; charset=utf-8")
+                .body(full(Bytes::from("Server busy")))
+                .unwrap());
+        }
+    };
+
     let query_str = uri.query().unwrap_or("");
 
     // Parse query string into ordered pairs (order matters for bracket
@@ -3817,6 +4059,18 @@ async fn handle_websocket_upgrade(
         return Ok(forbidden_websocket_origin_response());
     }
 
+    // `?soli_session=new` opens a resumable session; `?soli_session=<token>
+    // &soli_seq=<n>` resumes one (see `websocket` module docs).
+    let mut session_request: Option<String> = None;
+    let mut last_seq: u64 = 0;
+    for (key, value) in parse_query_pairs(req.uri().query().unwrap_or("")) {
+        match key.as_str() {
+            "soli_session" if !value.is_empty() => session_request = Some(value),
+            "soli_seq" => last_seq = value.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+
     // Perform the WebSocket upgrade
     let ws_config = default_websocket_config();
     let (response, websocket) = match hyper_tungstenite::upgrade(&mut req, Some(ws_config)) {
@@ -3848,26 +4102,9 @@ async fn handle_websocket_upgrade(
         // Split the WebSocket stream into read and write halves
         let (mut ws_write, mut ws_read) = stream.split();
 
-        // Create connection in registry
         let (ws_tx, mut ws_rx) =
             tokio::sync::mpsc::channel::<Result<tungstenite::Message, tungstenite::Error>>(32);
         let ws_tx_arc = Arc::new(ws_tx);
-        let connection = WebSocketConnection::new(ws_tx_arc.clone());
-        let connection_id = connection.id;
-
-        ws_registry.register(connection).await;
-
-        // Send connect event
-        let (response_tx, _) = oneshot::channel();
-        let connect_event = WebSocketEventData {
-            path: path.clone(),
-            connection_id,
-            event_type: "connect".to_string(),
-            message: None,
-            channel: None,
-            response_tx,
-        };
-        let _ = ws_event_tx.send(connect_event);
 
         // Spawn task to forward messages from channel to WebSocket
         let write_task = tokio::spawn(async move {
@@ -3883,11 +4120,57 @@ async fn handle_websocket_upgrade(
             }
         });
 
+        // Take over a dropped session, or create the connection in the
+        // registry. A resumed connection keeps its ID, and the handler isn't
+        // told: it never saw the drop either.
+        let resumed = match session_request.as_deref() {
+            Some(token) if token != "new" => {
+                ws_registry
+                    .resume(token, &path, last_seq, ws_tx_arc.clone())
+                    .await
+            }
+            _ => None,
+        };
+        let (connection_id, session_generation) = match resumed {
+            Some((connection_id, generation)) => (connection_id, Some(generation)),
+            None => {
+                let connection = WebSocketConnection::new(ws_tx_arc.clone());
+                let connection_id = connection.id;
+                ws_registry.register(connection).await;
+
+                // A stale or unknown token gets a fresh session, and the
+                // client learns from `resumed: false` that state was lost.
+                let mut generation = None;
+                if session_request.is_some() {
+                    if let Some(token) = ws_registry.open_session(&connection_id, &path).await {
+                        let hello = WebSocketRegistry::build_session_frame(&token, false, false);
+                        let _ = ws_tx_arc.send(Ok(tungstenite::Message::text(hello))).await;
+                        generation = ws_registry.session_generation(&connection_id).await;
+                    }
+                }
+
+                // Send connect event
+                let (response_tx, _) = oneshot::channel();
+                let connect_event = WebSocketEventData {
+                    path: path.clone(),
+                    connection_id,
+                    event_type: "connect".to_string(),
+                    message: None,
+                    channel: None,
+                    response_tx,
+                };
+                let _ = ws_event_tx.send(connect_event);
+                (connection_id, generation)
+            }
+        };
+
         // Handle incoming messages
+        let mut closed_by_client = false;
         while let Some(msg_result) = ws_read.next().await {
             match msg_result {
                 Ok(msg) => {
                     if msg.is_close() {
+                        closed_by_client = true;
                         break;
                     }
                     if msg.is_text() || msg.is_binary() {
@@ -3911,26 +4194,59 @@ async fn handle_websocket_upgrade(
             }
         }
 
-        // Send disconnect event
-        let (response_tx, _) = oneshot::channel();
-        let disconnect_event = WebSocketEventData {
-            path: path.clone(),
-            connection_id,
-            event_type: "disconnect".to_string(),
-            message: None,
-            channel: None,
-            response_tx,
-        };
-        let _ = ws_event_tx.send(disconnect_event);
-
-        ws_registry.unregister(&connection_id).await;
         write_task.abort();
+
+        // A session whose socket dropped waits for its client; the handler
+        // only hears `disconnect` if nobody resumes it in time.
+        let dropped = match session_generation {
+            Some(generation) if !closed_by_client => {
+                ws_registry.detach(&connection_id, generation).await
+            }
+            _ => SessionDrop::Ended,
+        };
+        match dropped {
+            SessionDrop::Replaced => {}
+            SessionDrop::Ended => {
+                send_websocket_disconnect(&ws_registry, &ws_event_tx, path, connection_id).await
+            }
+            SessionDrop::Waiting => {
+                tokio::time::sleep(websocket::resume_ttl()).await;
+                if ws_registry
+                    .expire(&connection_id, session_generation.unwrap_or_default())
+                    .await
+                {
+                    send_websocket_disconnect(&ws_registry, &ws_event_tx, path, connection_id)
+                        .await;
+                }
+            }
+        }
     });
 
     // Return the upgrade response directly
     Ok(box_full(response))
 }
 
+/// Tell the handler a connection is gone, then forget it.
+async fn send_websocket_disconnect(
+    ws_registry: &WebSocketRegistry,
+    ws_event_tx: &channel::Sender<WebSocketEventData>,
+    path: String,
+    connection_id: Uuid,
+) {
+    let (response_tx, _) = oneshot::channel();
+    let disconnect_event = WebSocketEventData {
+        path,
+        connection_id,
+        event_type: "disconnect".to_string(),
+        message: None,
+        channel: None,
+        response_tx,
+    };
+    let _ = ws_event_tx.send(disconnect_event);
+
+    ws_registry.unregister(&connection_id).await;
+}
+
 /// Handle a WebSocket event by calling the handler function.
 fn handle_websocket_event(
     interpreter: &mut Interpreter,
@@ -4678,8 +4994,64 @@ fn record_vm_demotion(handler: &str, err: &RuntimeError) {
     }
 }
 
-/// Call the route handler with the request hash.
+/// The 500 for a handler the VM stopped at the request's budget.
+fn budget_exceeded_response(
+    interpreter: &mut Interpreter,
+    err: &RuntimeError,
+    request_data: &RequestData,
+    dev_mode: bool,
+) -> ResponseData {
+    let request_id = Uuid::new_v4().to_string();
+    let error_msg = err.to_string();
+    error_logging::log_production_error(&request_id, request_data, &error_msg, &[], None);
+    let error_html = if dev_mode {
+        error_pages::render_error_page(
+            &error_msg,
+            &err.chain(),
+            interpreter,
+            request_data,
+            &[],
+            None,
+        )
+    } else {
+        error_pages::render_production_error_page(500, &error_msg, &request_id)
+    };
+    ResponseData {
+        status: 500,
+        headers: vec![(
+            "Content-Type".to_string(),
+            "text/html; charset=utf-8".to_string(),
+        )],
+        body: error_html.into_bytes(),
+    }
+}
+
+/// Call the route handler with the request hash, then run any async tasks
+/// it started without awaiting, so none carry over to the worker's next
+/// request.
 fn call_handler(
+    interpreter: &mut Interpreter,
+    vm: Option<&mut crate::vm::Vm>,
+    handler_name: &str,
+    request_hash: Value,
+    dev_mode: bool,
+    request_data: &RequestData,
+) -> ResponseData {
+    let response = dispatch_handler(
+        interpreter,
+        vm,
+        handler_name,
+        request_hash,
+        dev_mode,
+        request_data,
+    );
+    if let Err(e) = interpreter.run_pending_tasks() {
+        eprintln!("[{}] async task failed: {}", handler_name, e);
+    }
+    response
+}
+
+fn dispatch_handler(
     interpreter: &mut Interpreter,
     mut vm: Option<&mut crate::vm::Vm>,
     handler_name: &str,
@@ -4719,9 +5091,7 @@ fn call_handler(
         .borrow_mut()
         .define_or_update("params", params_value.clone());
     if let Some(vm_ref) = vm.as_deref_mut() {
-        vm_ref
-            .globals
-            .insert("params".to_string(), params_value.clone());
+        vm_ref.globals.insert("params", params_value.clone());
     }
 
     // Expose parsed cookies as global `cookies` so handlers/view can reference
@@ -4733,9 +5103,7 @@ fn call_handler(
         .borrow_mut()
         .define_or_update("cookies", cookies_value.clone());
     if let Some(vm_ref) = vm.as_deref_mut() {
-        vm_ref
-            .globals
-            .insert("cookies".to_string(), cookies_value.clone());
+        vm_ref.globals.insert("cookies", cookies_value.clone());
     }
 
     // Expose the full request hash as a global `req` so actions can omit the
@@ -4745,9 +5113,7 @@ fn call_handler(
         .borrow_mut()
         .define_or_update("req", request_hash.clone());
     if let Some(vm_ref) = vm.as_deref_mut() {
-        vm_ref
-            .globals
-            .insert("req".to_string(), request_hash.clone());
+        vm_ref.globals.insert("req", request_hash.clone());
     }
 
     // Rebind request-scoped names on the view helpers' closure env so user
@@ -4812,7 +5178,10 @@ fn call_handler(
                 let call_result = if handler_wants_request {
                     vm.call_value_direct_one(
                         handler_value.clone(),
-                        request_hash.clone(),
+                        crate::interpreter::builtins::http_types::request_argument(
+                            handler_value,
+                            &request_hash,
+                        ),
                         Span::default(),
                     )
                 } else {
@@ -4828,6 +5197,13 @@ fn call_handler(
                             body,
                         };
                     }
+                    Err(err) if err.is_budget_exceeded() => {
+                        // The handler ran out of budget, not of VM support:
+                        // re-running it on the interpreter would only do so
+                        // again.
+                        vm.reset();
+                        return budget_exceeded_response(interpreter, &err, request_data, dev_mode);
+                    }
                     Err(err) => {
                         record_vm_demotion(handler_name, &err);
                         vm.failed_handlers.insert(handler_name.to_string());
@@ -4844,7 +5220,10 @@ fn call_handler(
     match handler_result {
         Ok(handler_value) => {
             let args = if handler_wants_request {
-                vec![request_hash]
+                vec![crate::interpreter::builtins::http_types::request_argument(
+                    &handler_value,
+                    &request_hash,
+                )]
             } else {
                 Vec::new()
             };
@@ -4899,6 +5278,7 @@ fn call_handler(
                     if dev_mode {
                         let error_html = error_pages::render_error_page(
                             &error_msg,
+                            &e.chain(),
                             interpreter,
                             request_data,
                             &stack_trace,
@@ -4945,6 +5325,7 @@ fn call_handler(
             if dev_mode {
                 let error_html = error_pages::render_error_page(
                     &error_msg,
+                    &[],
                     interpreter,
                     request_data,
                     &stack_trace,
@@ -4974,22 +5355,24 @@ fn call_handler(
     }
 }
 
-// Thread-local cache of controllers that have before/after action hooks.
-// None means not yet initialized; Some(set) means we've checked the registry.
+// Thread-local cache of controllers that have before/after action hooks,
+// tagged with the registry generation it was built from. None means not yet
+// initialized; Some((generation, set)) means we've checked the registry.
 thread_local! {
-    static CONTROLLERS_WITH_HOOKS: RefCell<Option<std::collections::HashSet<String>>> = const { RefCell::new(None) };
+    static CONTROLLERS_WITH_HOOKS: RefCell<Option<(u64, std::collections::HashSet<String>)>> = const { RefCell::new(None) };
 }
 
 /// Check if a controller has hooks, using thread-local cache to avoid RwLock reads.
 fn controller_has_hooks(controller_key: &str) -> bool {
     CONTROLLERS_WITH_HOOKS.with(|cache| {
         let cached = cache.borrow();
-        if let Some(ref set) = *cached {
+        if let Some((_, ref set)) = *cached {
             return set.contains(controller_key);
         }
         drop(cached);
 
-        // Build cache from registry (once per thread)
+        // Build cache from registry (once per thread and registry rescan)
+        let generation = crate::interpreter::builtins::controller::registry::registry_generation();
         let registry = CONTROLLER_REGISTRY.read().unwrap();
         let mut set = std::collections::HashSet::new();
         for (key, info) in registry.all().iter().map(|i| (&i.class_name, i)) {
@@ -4998,11 +5381,24 @@ fn controller_has_hooks(controller_key: &str) -> bool {
             }
         }
         let has_hooks = set.contains(controller_key);
-        *cache.borrow_mut() = Some(set);
+        *cache.borrow_mut() = Some((generation, set));
         has_hooks
     })
 }
 
+/// Drop this thread's hook cache if the controller registry was rescanned
+/// (a hot reload) since it was built, so a hook added or removed by the
+/// reload takes effect on the next request instead of after a restart.
+pub(crate) fn forget_stale_controller_hooks() {
+    let generation = crate::interpreter::builtins::controller::registry::registry_generation();
+    CONTROLLERS_WITH_HOOKS.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if matches!(*cache, Some((built, _)) if built != generation) {
+            *cache = None;
+        }
+    });
+}
+
 /// Call an OOP controller action (controller#action).
 /// Returns Some(ResponseData) if handled, None if not an OOP controller.
 fn call_oop_controller_action(
@@ -5084,6 +5480,7 @@ fn call_oop_controller_action(
             return Some(if dev_mode {
                 let error_html = error_pages::render_error_page(
                     &error_msg,
+                    &[],
                     interpreter,
                     request_data,
                     &stack_trace,
@@ -5221,6 +5618,7 @@ fn call_oop_controller_action(
                 if dev_mode {
                     let error_html = error_pages::render_error_page(
                         &error_msg,
+                        &e.chain(),
                         interpreter,
                         request_data,
                         &stack_trace,
@@ -5415,6 +5813,14 @@ fn call_class_method(
         let method_span = method
             .span
             .unwrap_or_else(|| crate::span::Span::new(0, 0, 1, 1));
+        // `def show(req: Request)` gets the typed wrapper; untyped actions
+        // keep receiving the raw hash.
+        let request_arg = if crate::interpreter::builtins::http_types::wants_typed_request(&method)
+        {
+            crate::interpreter::builtins::http_types::wrap_request(request_hash)
+        } else {
+            request_hash.clone()
+        };
 
         // Try VM execution in production mode
         if let Some(vm) = vm {
@@ -5425,13 +5831,17 @@ fn call_class_method(
                 match vm.call_method_bound(
                     &method,
                     instance.clone(),
-                    request_hash.clone(),
+                    request_arg.clone(),
                     Span::default(),
                 ) {
                     Ok(result) => {
                         vm.reset();
                         return Ok(result);
                     }
+                    Err(err) if err.is_budget_exceeded() => {
+                        vm.reset();
+                        return Err(err);
+                    }
                     Err(err) => {
                         record_vm_demotion(&handler_key, &err);
                         vm.failed_handlers.insert(handler_key);
@@ -5466,10 +5876,13 @@ fn call_class_method(
                 body: method.body.clone(),
                 closure: Rc::new(RefCell::new(bound_env)),
                 is_method: true,
+                is_generator: method.is_generator,
+                is_async: method.is_async,
                 span: method.span,
                 source_path: method.source_path.clone(),
                 defining_superclass: method.defining_superclass.clone(),
                 return_type: method.return_type.clone(),
+                decorators: method.decorators.clone(),
                 cached_env: RefCell::new(None),
                 jit_cache: RefCell::new(None),
             })
@@ -5480,7 +5893,7 @@ fn call_class_method(
         let action_args: Vec<Value> = if method.params.is_empty() {
             vec![]
         } else {
-            vec![request_hash.clone()]
+            vec![request_arg]
         };
         let result =
             interpreter.call_value(Value::Function(bound_method), action_args, method_span);
@@ -5697,6 +6110,9 @@ fn forbidden_response(err: &RuntimeError) -> Option<ResponseData> {
 }
 
 fn check_for_response(value: &Value) -> Option<ResponseData> {
+    if let Some(hash) = crate::interpreter::builtins::http_types::response_to_hash(value) {
+        return check_for_response(&hash);
+    }
     // A response is a Hash with a "status" field (and optionally headers, body)
     // A modified request hash has "method", "path", etc. but no "status"
     if let Value::Hash(hash) = value {
@@ -5770,6 +6186,9 @@ fn check_for_response(value: &Value) -> Option<ResponseData> {
 
 /// Check if a value is a response hash (has a "status" field).
 fn is_response_hash(value: &Value) -> bool {
+    if crate::interpreter::builtins::http_types::response_to_hash(value).is_some() {
+        return true;
+    }
     if let Value::Hash(hash) = value {
         hash.borrow()
             .iter()
@@ -6001,17 +6420,91 @@ fn parse_request_body(
 }
 
 /// Handle a single request (called on interpreter thread)
+/// Heap a request may add to its worker (`SOLI_MAX_REQUEST_MEMORY_MB`),
+/// or `None` for no cap.
+fn request_memory_cap() -> Option<u64> {
+    static CAP: OnceLock<Option<u64>> = OnceLock::new();
+    *CAP.get_or_init(|| {
+        std::env::var("SOLI_MAX_REQUEST_MEMORY_MB")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|&mb| mb > 0)
+            .map(|mb| mb * 1024 * 1024)
+    })
+}
+
 fn handle_request(
     interpreter: &mut Interpreter,
     vm: &mut Option<crate::vm::Vm>,
     data: &mut RequestData,
     dev_mode: bool,
+) -> ResponseData {
+    // Under `soli test`, a spec that froze or moved the clock sends it along;
+    // pin this worker's clock to it until the response is built.
+    let frozen_now = test_request_frozen_now(data);
+    let previous_now = crate::interpreter::builtins::datetime::helpers::frozen_datetime();
+    if let Some(now) = frozen_now {
+        crate::interpreter::builtins::datetime::helpers::freeze_datetime(now);
+    }
+    internal_request::set_origin_peer(&data.peer_ip);
+    let response = handle_request_capped(interpreter, vm, data, dev_mode);
+    internal_request::set_origin_peer("");
+    if frozen_now.is_some() {
+        crate::interpreter::builtins::datetime::helpers::restore_frozen_datetime(previous_now);
+    }
+    response
+}
+
+/// The frozen clock a test-runner request carries, if any. Ignored outside a
+/// test server so a client can't move a live server's clock.
+fn test_request_frozen_now(data: &RequestData) -> Option<i64> {
+    if !crate::interpreter::builtins::test_server::is_test_runner_process() {
+        return None;
+    }
+    data.headers
+        .get(crate::interpreter::builtins::test_server::FROZEN_NOW_HEADER)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
+
+/// Handle one request under the per-request memory cap, if one is set: a
+/// handler that grows the worker's heap past it stops with a 500, instead of
+/// growing until the kernel kills the worker and every request on it.
+fn handle_request_capped(
+    interpreter: &mut Interpreter,
+    vm: &mut Option<crate::vm::Vm>,
+    data: &mut RequestData,
+    dev_mode: bool,
+) -> ResponseData {
+    let Some(cap) = request_memory_cap() else {
+        return handle_request_unbounded(interpreter, vm, data, dev_mode);
+    };
+    let budget = crate::interpreter::EvalBudget::default().with_max_heap_growth(cap);
+    interpreter.set_budget(Some(budget.clone()));
+    if let Some(vm) = vm.as_mut() {
+        vm.set_budget(Some(budget));
+    }
+    let response = handle_request_unbounded(interpreter, vm, data, dev_mode);
+    interpreter.set_budget(None);
+    if let Some(vm) = vm.as_mut() {
+        vm.set_budget(None);
+    }
+    response
+}
+
+fn handle_request_unbounded(
+    interpreter: &mut Interpreter,
+    vm: &mut Option<crate::vm::Vm>,
+    data: &mut RequestData,
+    dev_mode: bool,
 ) -> ResponseData {
     // Reset the per-request AQL log so `dev_queries()` only returns this
     // request's queries. Cheap when dev mode is off (early-out on the flag).
     // Also clear when production logging is on, otherwise the thread-local
     // buffers would accumulate across requests on the same worker thread.
-    if dev_mode || prod_log::channels().has_detail() {
+    if dev_mode || prod_log::channels().has_detail() || profiler::profile_enabled() {
         crate::interpreter::builtins::model::query_log::clear();
         crate::interpreter::builtins::http_log::clear();
         crate::interpreter::builtins::kv_log::clear();
@@ -6023,13 +6516,6 @@ fn handle_request(
         template_warnings::clear();
     }
 
-    // E2E test client: clear any render captured by a prior request on this
-    // pooled worker thread, so assigns()/view_path()/render_template() reflect
-    // only the current request. A single atomic load in non-test processes.
-    if crate::interpreter::builtins::test_server::is_test_runner_process() {
-        crate::interpreter::builtins::test_server::clear_captured_render();
-    }
-
     let method = &data.method;
     let path = &data.path;
 
@@ -6135,6 +6621,9 @@ fn handle_request(
     // injected bar can show server-side render time. Cheap when off.
     let dev_started = if dev_mode { Some(Instant::now()) } else { None };
 
+    // Same, for the `SOLI_PROFILE` Server-Timing header / footer panel.
+    let profile_started = profiler::profile_enabled().then(Instant::now);
+
     // Anchor the span log to this request's start so every span's
     // `start_us` / `end_us` is encoded as microseconds-since-request-start.
     // Also open the synthetic root request span so the flamegraph has a
@@ -6154,18 +6643,16 @@ fn handle_request(
     let cookie_pairs = parse_cookie_pairs(header_str(&data.headers, "cookie"));
 
     // Hand the raw header to the cookie jar so `read_cookie` can verify/open
-    // sealed values on demand. Installing `None` doubles as the per-request
-    // clear, alongside the session-state clears below.
+    // sealed values on demand. The worker loop's `reset_request_state` already
+    // dropped the previous request's header and cookie-driver session state.
     crate::interpreter::builtins::cookie_jar::install_request_cookie_header(header_str(
         &data.headers,
         "cookie",
     ));
 
-    // Drop any cookie-driver session state a previous request left on this
-    // worker thread. Must happen before `ensure_session` installs this
-    // request's state — a no-cookie request would otherwise silently inherit
-    // (and re-emit) the previous visitor's session.
-    crate::interpreter::builtins::session_cookie::clear_request_state();
+    // Subscriptions a handler makes with `Events.subscribe` last until this
+    // request's response is finalized.
+    crate::interpreter::builtins::events::begin_request();
 
     // Resolve the session ID from the parsed cookies (if any). When no cookie
     // is sent, we leave the thread-local unset — session_set / session_regenerate
@@ -6182,15 +6669,6 @@ fn handle_request(
         set_current_session_id(None);
         None
     };
-    // Clear response cookies from any previous request on this thread.
-    clear_response_cookies();
-    // Reset the static-page response cacheability flags so this request
-    // starts clean. set_cookie / session_set trip `mark_response_dirty`
-    // and clock / random trip `mark_data_dirty` while the controller
-    // runs; the cache lookup in TemplateCache::render consults both
-    // and short-circuits to a cache hit only when neither is set.
-    crate::template::response_cache::reset_for_new_request();
-
     // Per-form CSRF token verification. The hyper layer's Origin/Referer
     // gate ran before the body was read; this second gate runs where the
     // session lives, so a request that carries a token (scaffolded forms and
@@ -6226,7 +6704,11 @@ fn handle_request(
     }
 
     // Find matching route using indexed lookup (O(1) for exact matches, O(m) for patterns)
-    let (route_handler_name, scoped_middleware, matched_params) = match find_route(method, path) {
+    let found_route = {
+        let _routing = phase_log::PhaseTimer::start("routing");
+        find_route(method, path)
+    };
+    let (route_handler_name, scoped_middleware, matched_params) = match found_route {
         Some(found) => found,
         None => {
             // Clear session context before returning
@@ -6286,6 +6768,14 @@ fn handle_request(
         }
     };
 
+    // `returns:` shapes are only checked where a mismatch page can help.
+    let response_shape =
+        if dev_mode || crate::interpreter::builtins::test_server::is_test_runner_process() {
+            response_shape::route_shape(method.as_ref(), &route_handler_name)
+        } else {
+            None
+        };
+
     // Expand wildcard action pattern (e.g., "docs#*" → "docs#routing")
     // Skip expansion entirely when handler doesn't use wildcards (common case)
     let handler_name = if !route_handler_name.ends_with("#*") {
@@ -6387,16 +6877,7 @@ fn handle_request(
     // from hyper's HeaderMap into the Soli HashPairs handlers see as
     // req["headers"] (non-UTF-8 values are skipped, as before).
     let wire_headers = std::mem::take(&mut data.headers);
-    let mut headers =
-        HashPairs::with_capacity_and_hasher(wire_headers.keys_len(), ahash::RandomState::default());
-    for (name, value) in &wire_headers {
-        if let Ok(v) = value.to_str() {
-            headers.insert(
-                HashKey::String(name.as_str().into()),
-                Value::String(v.into()),
-            );
-        }
-    }
+    let headers = crate::interpreter::builtins::server::wire_header_pairs(&wire_headers);
     let query = std::mem::take(&mut data.query);
 
     // The single cookie parse from above becomes `req["cookies"]`; keep an
@@ -6437,225 +6918,281 @@ fn handle_request(
         .borrow_mut()
         .define_or_update("params", middleware_params.clone());
     if let Some(vm_ref) = vm.as_mut() {
-        vm_ref
-            .globals
-            .insert("params".to_string(), middleware_params);
+        vm_ref.globals.insert("params", middleware_params);
     }
     interpreter
         .global_env()
         .borrow_mut()
         .define_or_update("cookies", cookies_value.clone());
     if let Some(vm_ref) = vm.as_mut() {
-        vm_ref.globals.insert("cookies".to_string(), cookies_value);
+        vm_ref.globals.insert("cookies", cookies_value);
     }
 
     // Helper to finalize response with session cookie and timing
-    let finalize_response = |mut resp: ResponseData| -> ResponseData {
-        // Drop the per-request scheme/host so a `<name>_url` call between
-        // requests (e.g. from a background timer) errors clearly instead of
-        // building a URL with a stale host.
-        crate::interpreter::builtins::named_routes::clear_current_request_host();
-        if let Some(cookie_value) = finalize_session_cookie(
-            get_current_session_id().as_deref(),
-            cookie_session_id.as_deref(),
-            cookie_secure,
-        ) {
-            resp.headers.push(("Set-Cookie".to_string(), cookie_value));
-        }
-        // Emit any response cookies accumulated via set_cookie()
-        for (name, value, attrs) in take_response_cookies() {
-            resp.headers.push((
-                "Set-Cookie".to_string(),
-                format!("{}={}{}", name, value, attrs),
-            ));
-        }
-        // Add security headers if enabled
-        {
-            use crate::interpreter::builtins::security_headers::get_security_headers;
-            let security_headers = get_security_headers();
-            for (name, value) in security_headers {
-                resp.headers.push((name, value));
-            }
-        }
-        // E2E test client: ship the render captured by render() back as
-        // response headers, so assigns()/view_path()/render_template() work
-        // across the test-runner -> server process boundary. The locals JSON
-        // is base64-encoded so arbitrary UTF-8 / control chars stay
-        // header-safe. Test-runner only; absent (no render) -> no headers ->
-        // render_template() reports false on redirects/JSON responses.
-        if crate::interpreter::builtins::test_server::is_test_runner_process() {
-            if let Some(captured) =
-                crate::interpreter::builtins::test_server::take_captured_render()
-            {
-                let assigns_b64 = base64::Engine::encode(
-                    &base64::engine::general_purpose::STANDARD,
-                    captured.assigns_json.as_bytes(),
-                );
-                resp.headers
-                    .push(("x-soli-test-view-path".to_string(), captured.view_path));
-                resp.headers
-                    .push(("x-soli-test-assigns".to_string(), assigns_b64));
-                if captured.partial {
-                    resp.headers
-                        .push(("x-soli-test-assigns-partial".to_string(), "1".to_string()));
+    let finalize_response =
+        |mut resp: ResponseData| -> ResponseData {
+            if let Some(shape) = &response_shape {
+                let drifts = response_shape::check(shape, resp.status, &resp.headers, &resp.body);
+                if !drifts.is_empty() {
+                    eprintln!(
+                        "[WARN] {} {} ({}) drifted from returns: {}:\n  {}",
+                        method,
+                        path,
+                        handler_name,
+                        shape.label,
+                        drifts.join("\n  ")
+                    );
+                    resp = ResponseData {
+                        status: 500,
+                        headers: vec![(
+                            "Content-Type".to_string(),
+                            "text/html; charset=utf-8".to_string(),
+                        )],
+                        body: error_pages::render_response_shape_page(
+                            method.as_ref(),
+                            path,
+                            &handler_name,
+                            &shape.label,
+                            &drifts,
+                            &resp.body,
+                        )
+                        .into_bytes(),
+                    };
                 }
             }
-            // Ship the AQL query count + any N+1 groups back to the test-runner
-            // process so `assert_query_count` / `assert_no_n_plus_one` can inspect
-            // them across the process boundary. The query log is a thread-local on
-            // this worker; snapshot it here (before we cross back to hyper) and
-            // reuse the dev bar's own detector so a spec sees exactly what the
-            // dev-bar badge would flag. The runner always runs the server with
-            // `--dev`, so the log is populated.
-            {
-                use crate::interpreter::builtins::model::query_log;
-                let queries = query_log::snapshot();
+            // Drop the per-request scheme/host so a `<name>_url` call between
+            // requests (e.g. from a background timer) errors clearly instead of
+            // building a URL with a stale host.
+            crate::interpreter::builtins::named_routes::clear_current_request_host();
+            crate::interpreter::builtins::events::end_request();
+            if let Some(cookie_value) = finalize_session_cookie(
+                get_current_session_id().as_deref(),
+                cookie_session_id.as_deref(),
+                cookie_secure,
+            ) {
+                resp.headers.push(("Set-Cookie".to_string(), cookie_value));
+            }
+            // Emit any response cookies accumulated via set_cookie()
+            for (name, value, attrs) in take_response_cookies() {
                 resp.headers.push((
-                    "x-soli-test-query-count".to_string(),
-                    queries.len().to_string(),
+                    "Set-Cookie".to_string(),
+                    format!("{}={}{}", name, value, attrs),
                 ));
-                let n1_groups = dev_bar::detect_n_plus_one(&queries, 2);
-                if !n1_groups.is_empty() {
-                    let arr: Vec<serde_json::Value> = n1_groups
+            }
+            // Validators and caching policy from `fresh_when` / `stale?` /
+            // `expires_in`; a fresh request's response becomes a 304.
+            crate::interpreter::builtins::http_cache::finalize_response(
+                &mut resp.status,
+                &mut resp.headers,
+                &mut resp.body,
+            );
+            // Add security headers if enabled, with the nonce and hashes of
+            // any javascript_tag / style_tag the page rendered.
+            {
+                use crate::interpreter::builtins::security_headers::{
+                    apply_inline_sources, get_security_headers,
+                };
+                let mut security_headers = get_security_headers();
+                apply_inline_sources(&mut security_headers);
+                for (name, value) in security_headers {
+                    resp.headers.push((name, value));
+                }
+            }
+            // E2E test client: ship the render captured by render() back as
+            // response headers, so assigns()/view_path()/render_template() work
+            // across the test-runner -> server process boundary. The locals JSON
+            // is base64-encoded so arbitrary UTF-8 / control chars stay
+            // header-safe. Test-runner only; absent (no render) -> no headers ->
+            // render_template() reports false on redirects/JSON responses.
+            if crate::interpreter::builtins::test_server::is_test_runner_process() {
+                if let Some(captured) =
+                    crate::interpreter::builtins::test_server::take_captured_render()
+                {
+                    let assigns_b64 = base64::Engine::encode(
+                        &base64::engine::general_purpose::STANDARD,
+                        captured.assigns_json.as_bytes(),
+                    );
+                    resp.headers
+                        .push(("x-soli-test-view-path".to_string(), captured.view_path));
+                    resp.headers
+                        .push(("x-soli-test-assigns".to_string(), assigns_b64));
+                    if captured.partial {
+                        resp.headers
+                            .push(("x-soli-test-assigns-partial".to_string(), "1".to_string()));
+                    }
+                }
+                // Ship the AQL query count + any N+1 groups back to the test-runner
+                // process so `assert_query_count` / `assert_no_n_plus_one` can inspect
+                // them across the process boundary. The query log is a thread-local on
+                // this worker; snapshot it here (before we cross back to hyper) and
+                // reuse the dev bar's own detector so a spec sees exactly what the
+                // dev-bar badge would flag. The runner always runs the server with
+                // `--dev`, so the log is populated.
+                {
+                    use crate::interpreter::builtins::model::query_log;
+                    let queries = query_log::snapshot();
+                    resp.headers.push((
+                        "x-soli-test-query-count".to_string(),
+                        queries.len().to_string(),
+                    ));
+                    let n1_groups = dev_bar::detect_n_plus_one(&queries, 2);
+                    if !n1_groups.is_empty() {
+                        let arr: Vec<serde_json::Value> = n1_groups
                         .iter()
                         .map(|(template, count, _total_us)| {
                             serde_json::json!({ "query": template, "count": count })
                         })
                         .collect();
-                    let b64 = base64::Engine::encode(
-                        &base64::engine::general_purpose::STANDARD,
-                        serde_json::Value::Array(arr).to_string().as_bytes(),
-                    );
-                    resp.headers.push(("x-soli-test-n1".to_string(), b64));
+                        let b64 = base64::Engine::encode(
+                            &base64::engine::general_purpose::STANDARD,
+                            serde_json::Value::Array(arr).to_string().as_bytes(),
+                        );
+                        resp.headers.push(("x-soli-test-n1".to_string(), b64));
+                    }
                 }
             }
-        }
-        // Inject the dev bar into HTML responses when running --dev. The bar
-        // is rendered here on the worker thread because the AQL query log is
-        // a thread-local, so the snapshot must happen before we cross the
-        // channel back to the hyper handler.
-        if let Some(start) = dev_started {
-            // Tag EVERY dev-mode response (HTML, JSON, HTMx fragment, …) with
-            // its matched route so the client-side fetch/XHR patch can attribute
-            // each request to a controller#action in the dev bar's requests
-            // panel. Set before the HTML-only guard below so XHR/HTMx responses
-            // — which never get a dev bar injected — still carry it.
-            let route = route_log::snapshot();
-            if let Some(handler) = &route {
+            // Inject the dev bar into HTML responses when running --dev. The bar
+            // is rendered here on the worker thread because the AQL query log is
+            // a thread-local, so the snapshot must happen before we cross the
+            // channel back to the hyper handler.
+            if let Some(start) = dev_started {
+                // Tag EVERY dev-mode response (HTML, JSON, HTMx fragment, …) with
+                // its matched route so the client-side fetch/XHR patch can attribute
+                // each request to a controller#action in the dev bar's requests
+                // panel. Set before the HTML-only guard below so XHR/HTMx responses
+                // — which never get a dev bar injected — still carry it.
+                let route = route_log::snapshot();
+                if let Some(handler) = &route {
+                    resp.headers
+                        .push(("X-Soli-Route".to_string(), handler.clone()));
+                }
+                // Stable per-request id: the requests-panel drill-down fetches
+                // `/__solidev/request/:id` to inspect any listed request's panels.
+                let request_id = Uuid::new_v4().to_string();
                 resp.headers
-                    .push(("X-Soli-Route".to_string(), handler.clone()));
-            }
-            // Stable per-request id: the requests-panel drill-down fetches
-            // `/__solidev/request/:id` to inspect any listed request's panels.
-            let request_id = Uuid::new_v4().to_string();
-            resp.headers
-                .push(("X-Soli-Request-Id".to_string(), request_id.clone()));
-
-            // Server-side handler time (µs). The requests panel shows this as
-            // each row's duration — the real time spent in the app — rather
-            // than the client-observed round-trip (which folds in queue +
-            // network + transfer and is what `performance.now()` would measure).
-            let elapsed_us = start.elapsed().as_micros() as u64;
-            resp.headers
-                .push(("X-Soli-Render-Us".to_string(), elapsed_us.to_string()));
-
-            let is_html = resp
-                .headers
-                .iter()
-                .any(|(k, v)| k.eq_ignore_ascii_case("content-type") && v.contains("text/html"));
-
-            // Build the per-request snapshot for EVERY dev response (not just
-            // HTML), so an XHR/HTMx call's panels can be inspected later via the
-            // requests panel. Close the root span first so the flamegraph has
-            // its top-level rectangle (span_log only records a span on close).
-            span_log::close_request_root();
-            let ctx = dev_bar::DevBarContext {
-                method: method.as_ref().to_string(),
-                path: path.clone(),
-                status: resp.status,
-                elapsed_us,
-                request_id: request_id.clone(),
-                route: route.clone(),
-                queries: crate::interpreter::builtins::model::query_log::snapshot(),
-                http_requests: crate::interpreter::builtins::http_log::snapshot(),
-                kv_calls: crate::interpreter::builtins::kv_log::snapshot(),
-                phases: phase_log::snapshot(),
-                middlewares: middleware_log::snapshot(),
-                views: view_log::snapshot(),
-                spans: span_log::snapshot(),
-                warnings: template_warnings::snapshot(),
-            };
+                    .push(("X-Soli-Request-Id".to_string(), request_id.clone()));
 
-            // Feed coarse totals into the always-on Prometheus metrics (Phase A).
-            // Kept HTML-scoped, matching the prior behavior.
-            if is_html {
-                let mw_total_us: u64 = ctx.middlewares.iter().map(|(_, us)| *us).sum();
-                if mw_total_us > 0 {
-                    crate::metrics::Metrics::global()
-                        .record_middleware(std::time::Duration::from_micros(mw_total_us));
-                }
-                let db_total_ns: u64 = ctx
-                    .queries
-                    .iter()
-                    .map(|q| (q.duration_ms * 1_000_000.0) as u64)
-                    .sum();
-                if db_total_ns > 0 {
-                    crate::metrics::Metrics::global()
-                        .record_db_queries(std::time::Duration::from_nanos(db_total_ns));
+                // Server-side handler time (µs). The requests panel shows this as
+                // each row's duration — the real time spent in the app — rather
+                // than the client-observed round-trip (which folds in queue +
+                // network + transfer and is what `performance.now()` would measure).
+                let elapsed_us = start.elapsed().as_micros() as u64;
+                resp.headers
+                    .push(("X-Soli-Render-Us".to_string(), elapsed_us.to_string()));
+
+                let is_html = resp.headers.iter().any(|(k, v)| {
+                    k.eq_ignore_ascii_case("content-type") && v.contains("text/html")
+                });
+
+                // Build the per-request snapshot for EVERY dev response (not just
+                // HTML), so an XHR/HTMx call's panels can be inspected later via the
+                // requests panel. Close the root span first so the flamegraph has
+                // its top-level rectangle (span_log only records a span on close).
+                span_log::close_request_root();
+                let ctx = dev_bar::DevBarContext {
+                    method: method.as_ref().to_string(),
+                    path: path.clone(),
+                    status: resp.status,
+                    elapsed_us,
+                    request_id: request_id.clone(),
+                    route: route.clone(),
+                    queries: crate::interpreter::builtins::model::query_log::snapshot(),
+                    http_requests: crate::interpreter::builtins::http_log::snapshot(),
+                    kv_calls: crate::interpreter::builtins::kv_log::snapshot(),
+                    phases: phase_log::snapshot(),
+                    middlewares: middleware_log::snapshot(),
+                    views: view_log::snapshot(),
+                    spans: span_log::snapshot(),
+                    warnings: template_warnings::snapshot(),
+                };
+
+                // Feed coarse totals into the always-on Prometheus metrics (Phase A).
+                // Kept HTML-scoped, matching the prior behavior.
+                if is_html {
+                    let mw_total_us: u64 = ctx.middlewares.iter().map(|(_, us)| *us).sum();
+                    if mw_total_us > 0 {
+                        crate::metrics::Metrics::global()
+                            .record_middleware(std::time::Duration::from_micros(mw_total_us));
+                    }
+                    let db_total_ns: u64 = ctx
+                        .queries
+                        .iter()
+                        .map(|q| (q.duration_ms * 1_000_000.0) as u64)
+                        .sum();
+                    if db_total_ns > 0 {
+                        crate::metrics::Metrics::global()
+                            .record_db_queries(std::time::Duration::from_nanos(db_total_ns));
+                    }
                 }
-            }
 
-            // Stash for the `/__solidev/request/:id` drill-down endpoint, and
-            // the raw request for the `/__solidev/replay/:id` replay button.
-            if let Some(raw) = &captured_raw {
-                dev_store::put_raw(request_id.clone(), raw.clone());
+                // Stash for the `/__solidev/request/:id` drill-down endpoint, and
+                // the raw request for the `/__solidev/replay/:id` replay button.
+                if let Some(raw) = &captured_raw {
+                    dev_store::put_raw(request_id.clone(), raw.clone());
+                }
+                dev_store::put(request_id, ctx.clone());
+
+                // Inject the bar only into full HTML pages. HTMx partial responses
+                // share the page that already carries the dev bar; injecting again
+                // would append a second one into the live DOM on each swap.
+                if is_html && !is_htmx_request {
+                    if let Ok(body_str) = std::str::from_utf8(&resp.body) {
+                        resp.body = dev_bar::inject_dev_bar(body_str, &ctx).into_bytes();
+                    }
+                }
             }
-            dev_store::put(request_id, ctx.clone());
-
-            // Inject the bar only into full HTML pages. HTMx partial responses
-            // share the page that already carries the dev bar; injecting again
-            // would append a second one into the live DOM on each swap.
-            if is_html && !is_htmx_request {
-                if let Ok(body_str) = std::str::from_utf8(&resp.body) {
-                    resp.body = dev_bar::inject_dev_bar(body_str, &ctx).into_bytes();
+            // `SOLI_PROFILE`: the per-phase breakdown as `Server-Timing`, plus the
+            // footer panel on full HTML pages — unless the dev bar was injected
+            // above, whose render breakdown already shows the same split.
+            if let Some(start) = profile_started {
+                let breakdown = profiler::Breakdown::capture(start.elapsed().as_micros() as u64);
+                resp.headers
+                    .push(("Server-Timing".to_string(), breakdown.server_timing()));
+                let is_html = resp.headers.iter().any(|(k, v)| {
+                    k.eq_ignore_ascii_case("content-type") && v.contains("text/html")
+                });
+                if is_html && !is_htmx_request && dev_started.is_none() {
+                    if let Ok(body_str) = std::str::from_utf8(&resp.body) {
+                        let panel = breakdown.panel(method.as_ref(), path, resp.status);
+                        resp.body = profiler::inject_panel(body_str, &panel).into_bytes();
+                    }
                 }
             }
-        }
-        // Log timing (skip health checks to avoid benchmark noise)
-        if log_requests && path != "/health" {
-            let elapsed_ms = start_time.unwrap().elapsed().as_secs_f64() * 1000.0;
-            if dev_mode {
-                // The injected dev bar already surfaces the per-request
-                // queries/http/timing detail; the terminal just gets the
-                // one-line access entry.
-                println!(
-                    "{} [LOG] {} {} - {} ({:.3}ms)",
-                    log_timestamp(),
-                    method,
-                    path,
-                    resp.status,
-                    elapsed_ms
-                );
-            } else {
-                // Production: emit the access line plus whatever detail
-                // channels the operator enabled via SOLI_LOG. `emit` also
-                // gates the SOLI_SLOW_REQUEST_MS full-detail block on
-                // queue + handler time and prints nothing for fast
-                // requests when only the slow threshold is configured.
-                prod_log::emit(
-                    method.as_ref(),
-                    path.as_str(),
-                    resp.status,
-                    elapsed_ms,
-                    queue_ms,
-                    log_channels,
-                );
+            // Log timing (skip health checks to avoid benchmark noise)
+            if log_requests && path != "/health" {
+                let elapsed_ms = start_time.unwrap().elapsed().as_secs_f64() * 1000.0;
+                if dev_mode {
+                    // The injected dev bar already surfaces the per-request
+                    // queries/http/timing detail; the terminal just gets the
+                    // one-line access entry.
+                    println!(
+                        "{} [LOG] {} {} - {} ({:.3}ms)",
+                        log_timestamp(),
+                        method,
+                        path,
+                        resp.status,
+                        elapsed_ms
+                    );
+                } else {
+                    // Production: emit the access line plus whatever detail
+                    // channels the operator enabled via SOLI_LOG. `emit` also
+                    // gates the SOLI_SLOW_REQUEST_MS full-detail block on
+                    // queue + handler time and prints nothing for fast
+                    // requests when only the slow threshold is configured.
+                    prod_log::emit(
+                        method.as_ref(),
+                        path.as_str(),
+                        resp.status,
+                        elapsed_ms,
+                        queue_ms,
+                        log_channels,
+                    );
+                }
             }
-        }
-        // Clear session context
-        set_current_session_id(None);
-        resp
-    };
+            // Clear session context
+            set_current_session_id(None);
+            resp
+        };
 
     // Fast path: no middleware at all (avoid cloning middleware list if empty)
     if scoped_middleware.is_empty() && !has_middleware() {
@@ -6710,6 +7247,7 @@ fn handle_request(
                         );
                         let error_html = error_pages::render_error_page(
                             &err,
+                            &[],
                             interpreter,
                             data,
                             &stack_trace,
@@ -6764,6 +7302,7 @@ fn handle_request(
                     }
                     let error_html = error_pages::render_error_page(
                         &e.to_string(),
+                        &e.chain(),
                         interpreter,
                         data,
                         &stack_trace,
@@ -6836,6 +7375,7 @@ fn handle_request(
                         );
                         let error_html = error_pages::render_error_page(
                             &err,
+                            &[],
                             interpreter,
                             data,
                             &stack_trace,
@@ -6886,6 +7426,7 @@ fn handle_request(
                     }
                     let error_html = error_pages::render_error_page(
                         &e.to_string(),
+                        &e.chain(),
                         interpreter,
                         data,
                         &stack_trace,
@@ -6975,10 +7516,27 @@ async fn handle_dev_repl(
         .unwrap_or("")
         .to_string();
 
-    // Execute the code using the interpreter.
-    // Use block_in_place so model DB queries can call block_on without panicking.
-    let (result, new_session_id) = tokio::task::block_in_place(|| {
-        execute_repl_code(&code, request_data, breakpoint_env, &repl_session_id)
+    // The error page's "Reset session" control: drop the interpreter so the
+    // next evaluation starts from a clean one.
+    if json.get("reset").and_then(|r| r.as_bool()).unwrap_or(false) {
+        let response_json = serde_json::json!({
+            "reset": REPL_STORE.reset(&repl_session_id),
+            "repl_session_id": null
+        });
+        return Ok(Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "application/json")
+            .body(full(Bytes::from(response_json.to_string())))
+            .unwrap());
+    }
+
+    // Execute the code on the REPL thread that owns the session.
+    let (new_session_id, result_rx) = REPL_STORE.run(&repl_session_id, move |session| {
+        execute_repl_code(session, &code, request_data, breakpoint_env)
+    });
+    let result = result_rx.await.unwrap_or_else(|_| ReplResult {
+        result: "null".to_string(),
+        error: Some("The REPL session crashed and was reset".to_string()),
     });
 
     let response_json = serde_json::json!({
@@ -7257,78 +7815,19 @@ async fn handle_dev_source(
             .unwrap());
     }
 
-    // Reject absolute paths - security measure
-    if std::path::Path::new(&file).is_absolute() {
-        return Ok(Response::builder()
-            .status(StatusCode::FORBIDDEN)
-            .header("Content-Type", "application/json")
-            .body(full(Bytes::from(
-                r#"{"error": "Absolute paths not allowed"}"#,
-            )))
-            .unwrap());
-    }
-
-    // Try to read the file - resolve relative to app root
-    let app_root = crate::live::component::get_app_root();
-    let joined = app_root.join(&file);
-
-    // Canonicalize and verify the path is within app_root
-    let canonical_path = match std::fs::canonicalize(&joined) {
-        Ok(p) => p,
-        Err(_) => {
-            return Ok(Response::builder()
-                .status(StatusCode::NOT_FOUND)
-                .header("Content-Type", "application/json")
-                .body(full(Bytes::from(r#"{"error": "File not found"}"#)))
-                .unwrap());
-        }
-    };
-
-    let canonical_root = match std::fs::canonicalize(&app_root) {
-        Ok(r) => r,
-        Err(_) => {
-            return Ok(Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .header("Content-Type", "application/json")
-                .body(full(Bytes::from(
-                    r#"{"error": "Could not determine app root"}"#,
-                )))
-                .unwrap());
-        }
-    };
-
-    // SEC-010: use `Path::starts_with` (segment-aware), not the string
-    // form. Plain string `starts_with` would treat
-    // `/home/me/app-secrets/x` as inside `/home/me/app` because the
-    // prefix matches character-by-character — exactly the leak this
-    // task was filed for. `resolve_static_file` already uses this idiom.
-    if !canonical_path.starts_with(&canonical_root) {
-        return Ok(Response::builder()
-            .status(StatusCode::FORBIDDEN)
-            .header("Content-Type", "application/json")
-            .body(full(Bytes::from(
-                r#"{"error": "Path outside app directory"}"#,
-            )))
-            .unwrap());
-    }
-
-    if !canonical_path.is_file() {
-        return Ok(Response::builder()
-            .status(StatusCode::NOT_FOUND)
-            .header("Content-Type", "application/json")
-            .body(full(Bytes::from(r#"{"error": "Not a file"}"#)))
-            .unwrap());
-    }
-
-    let content = match std::fs::read_to_string(&canonical_path) {
-        Ok(c) => c,
-        Err(_) => {
-            return Ok(Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .header("Content-Type", "application/json")
-                .body(full(Bytes::from(r#"{"error": "Could not read file"}"#)))
-                .unwrap());
-        }
+    // The routing DSL and the preludes are registered by path, not on disk.
+    let content = match crate::source_map::get(&file) {
+        Some(source) => source.to_string(),
+        None => match read_dev_source(&file) {
+            Ok(content) => content,
+            Err((status, error)) => {
+                return Ok(Response::builder()
+                    .status(status)
+                    .header("Content-Type", "application/json")
+                    .body(full(Bytes::from(error)))
+                    .unwrap())
+            }
+        },
     };
 
     // Parse line from query
@@ -7364,28 +7863,73 @@ async fn handle_dev_source(
         .unwrap())
 }
 
+/// Read `file`, relative to the app root and confined to it, for
+/// `handle_dev_source`; the status and JSON error body to send otherwise.
+fn read_dev_source(file: &str) -> Result<String, (StatusCode, &'static str)> {
+    // Reject absolute paths - security measure
+    if std::path::Path::new(file).is_absolute() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            r#"{"error": "Absolute paths not allowed"}"#,
+        ));
+    }
+
+    // Try to read the file - resolve relative to app root
+    let app_root = crate::live::component::get_app_root();
+    let joined = app_root.join(file);
+
+    // Canonicalize and verify the path is within app_root
+    let canonical_path = std::fs::canonicalize(&joined)
+        .map_err(|_| (StatusCode::NOT_FOUND, r#"{"error": "File not found"}"#))?;
+    let canonical_root = std::fs::canonicalize(&app_root).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            r#"{"error": "Could not determine app root"}"#,
+        )
+    })?;
+
+    // SEC-010: use `Path::starts_with` (segment-aware), not the string
+    // form. Plain string `starts_with` would treat
+    // `/home/me/app-secrets/x` as inside `/home/me/app` because the
+    // prefix matches character-by-character — exactly the leak this
+    // task was filed for. `resolve_static_file` already uses this idiom.
+    if !canonical_path.starts_with(&canonical_root) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            r#"{"error": "Path outside app directory"}"#,
+        ));
+    }
+
+    if !canonical_path.is_file() {
+        return Err((StatusCode::NOT_FOUND, r#"{"error": "Not a file"}"#));
+    }
+
+    std::fs::read_to_string(&canonical_path).map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            r#"{"error": "Could not read file"}"#,
+        )
+    })
+}
+
 struct ReplResult {
     result: String,
     error: Option<String>,
 }
 
 fn execute_repl_code(
+    session: &repl_session::ReplSession,
     code: &str,
     request_data: Option<serde_json::Value>,
     breakpoint_env: Option<serde_json::Value>,
-    repl_session_id: &str,
-) -> (ReplResult, String) {
-    let (session_id, session) = REPL_STORE.with(|store| store.get_or_create(repl_session_id));
+) -> ReplResult {
     let mut interpreter = session.interpreter.borrow_mut();
 
     if code.trim().is_empty() {
-        return (
-            ReplResult {
-                result: "null".to_string(),
-                error: None,
-            },
-            session_id,
-        );
+        return ReplResult {
+            result: "null".to_string(),
+            error: None,
+        };
     }
 
     // Load models into REPL session on first use
@@ -7408,6 +7952,7 @@ fn execute_repl_code(
 
     // Inject view helpers into REPL environment (same helpers available in templates)
     for (name, value) in crate::interpreter::builtins::template::get_view_helpers() {
+        let value = repl_session::sandbox_helper(&name, value);
         interpreter.environment.borrow_mut().define(name, value);
     }
 
@@ -7464,6 +8009,9 @@ fn execute_repl_code(
         }
     }
 
+    // Only the posted code runs against the budget, not the model loading.
+    interpreter.set_budget(Some(repl_session::eval_budget()));
+
     // Strip trailing semicolon for expression evaluation
     let code_trimmed = code.trim().trim_end_matches(';').trim();
 
@@ -7485,22 +8033,16 @@ fn execute_repl_code(
                     Some(v) => format!("{}", v),
                     None => "null".to_string(),
                 };
-                return (
-                    ReplResult {
-                        result: result_str,
-                        error: None,
-                    },
-                    session_id,
-                );
+                return ReplResult {
+                    result: result_str,
+                    error: None,
+                };
             }
             Err(e) => {
-                return (
-                    ReplResult {
-                        result: "null".to_string(),
-                        error: Some(format!("Execution error: {}", e)),
-                    },
-                    session_id,
-                );
+                return ReplResult {
+                    result: "null".to_string(),
+                    error: Some(format!("Execution error: {}", e)),
+                };
             }
         }
     }
@@ -7513,7 +8055,7 @@ fn execute_repl_code(
             .map_err(|e| format!("{:?}", e))
     });
 
-    let result = match parse_result {
+    match parse_result {
         Ok(program) => match interpreter.interpret(&program) {
             Ok(_) => ReplResult {
                 result: "ok".to_string(),
@@ -7528,9 +8070,7 @@ fn execute_repl_code(
             result: "null".to_string(),
             error: Some(format!("Parse error: {}", parse_errors)),
         },
-    };
-
-    (result, session_id)
+    }
 }
 
 /// Helper to convert JSON to Value, returning Null on error.
@@ -7584,6 +8124,25 @@ fn resolve_static_file(path: &str, public_dir: &Path) -> Result<Option<PathBuf>,
     Ok(Some(canonical_file))
 }
 
+/// [`resolve_static_file`] over `public/`, then, for `/vendor/javascript/…`,
+/// over the app's `vendor/javascript`, where `soli js add` puts packages.
+fn resolve_asset_file(path: &str, public_dir: &Path) -> Result<Option<PathBuf>, ()> {
+    if public_dir.exists() {
+        if let found @ (Err(()) | Ok(Some(_))) = resolve_static_file(path, public_dir) {
+            return found;
+        }
+    }
+    match (
+        path.strip_prefix(crate::module::importmap::URL_PREFIX),
+        public_dir.parent(),
+    ) {
+        (Some(file), Some(app_dir)) => {
+            resolve_static_file(file, &app_dir.join(crate::module::importmap::VENDOR_DIR))
+        }
+        _ => Ok(None),
+    }
+}
+
 /// Walk the MVC app directories that the test runner also walks for coverage
 /// (`app/`, `config/`, `lib/`) and pre-register every `.sl` file's executable
 /// lines on the server-side coverage tracker. Without this, lines that are
@@ -8043,6 +8602,28 @@ mod tests {
         assert!(result.unwrap().is_some());
     }
 
+    #[test]
+    fn test_resolve_asset_file_serves_vendored_javascript() {
+        let dir = tempfile::tempdir().unwrap();
+        let public = dir.path().join("public");
+        let vendor = dir.path().join("vendor/javascript");
+        fs::create_dir(&public).unwrap();
+        fs::create_dir_all(&vendor).unwrap();
+        fs::write(vendor.join("preact.js"), "export const h=1;").unwrap();
+        fs::write(dir.path().join("secret.txt"), "no").unwrap();
+
+        let found = resolve_asset_file("/vendor/javascript/preact.js", &public).unwrap();
+        assert_eq!(
+            found.unwrap(),
+            vendor.join("preact.js").canonicalize().unwrap()
+        );
+        assert_eq!(resolve_asset_file("/preact.js", &public), Ok(None));
+        assert_eq!(
+            resolve_asset_file("/vendor/javascript/../../secret.txt", &public),
+            Ok(None)
+        );
+    }
+
     #[test]
     fn test_resolve_static_file_root_path_falls_through() {
         let dir = tempfile::tempdir().unwrap();
@@ -8685,4 +9266,13 @@ mod tests {
 
         std::env::remove_var("SOLI_CSRF_TOKENS");
     }
+
+    #[test]
+    fn controller_hook_cache_is_rebuilt_after_a_rescan() {
+        let dir = tempfile::tempdir().unwrap();
+        controller_has_hooks("PostsController");
+        crate::interpreter::builtins::controller::registry::scan_controllers(dir.path()).unwrap();
+        forget_stale_controller_hooks();
+        assert!(CONTROLLERS_WITH_HOOKS.with(|cache| cache.borrow().is_none()));
+    }
 }
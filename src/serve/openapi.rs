@@ -8,8 +8,12 @@
 //! There is no annotation infrastructure to read types/bodies from, so the spec
 //! is structural: every route becomes a path + method with its handler as the
 //! operationId/summary, path params (`:id`) as required string parameters, and a
-//! generic `200`. It's a discoverability aid, not a hand-authored contract.
+//! generic `200`. Apps that want a real contract document responses in an
+//! overlay (`config/openapi.json` / `config/openapi.yml`) which is deep-merged
+//! over the generated document — that's what `soli test --contract` checks
+//! request specs against.
 
+use std::path::Path;
 use std::sync::OnceLock;
 
 use crate::interpreter::builtins::server::get_routes;
@@ -95,15 +99,24 @@ pub fn generate_spec() -> serde_json::Value {
             }
         }
         if !params.is_empty() {
+            let catch_all = route
+                .path_pattern
+                .rsplit('/')
+                .next()
+                .and_then(|seg| seg.strip_prefix('*'));
             let ps: Vec<Value> = params
                 .iter()
                 .map(|p| {
-                    json!({
+                    let mut param = json!({
                         "name": p,
                         "in": "path",
                         "required": true,
                         "schema": { "type": "string" }
-                    })
+                    });
+                    if catch_all == Some(p.as_str()) {
+                        param[CATCH_ALL] = json!(true);
+                    }
+                    param
                 })
                 .collect();
             op.insert("parameters".into(), json!(ps));
@@ -112,6 +125,10 @@ pub fn generate_spec() -> serde_json::Value {
             "responses".into(),
             json!({ "200": { "description": "OK" } }),
         );
+        // The generic 200 is a placeholder, not documentation; the marker
+        // tells contract checks to skip status/body for this operation until
+        // the overlay documents its responses (which clears it).
+        op.insert(GENERATED_RESPONSES.into(), json!(true));
 
         let entry = paths.entry(opath).or_insert_with(|| json!({}));
         if let Value::Object(m) = entry {
@@ -119,11 +136,101 @@ pub fn generate_spec() -> serde_json::Value {
         }
     }
 
-    json!({
+    let mut spec = json!({
         "openapi": "3.0.3",
         "info": { "title": spec_title(), "version": "1.0.0" },
         "paths": Value::Object(paths),
-    })
+    });
+    match load_overlay(&crate::live::component::get_app_root()) {
+        Ok(Some(overlay)) => merge_overlay(&mut spec, overlay),
+        Ok(None) => {}
+        // Surfaced in the document rather than swallowed: a broken overlay
+        // would otherwise make `--contract` silently check a weaker spec.
+        Err(message) => spec[OVERLAY_ERROR] = json!(message),
+    }
+    spec
+}
+
+/// Operation marker: its `responses` are the generator's placeholder `200`.
+pub const GENERATED_RESPONSES: &str = "x-soli-generated-responses";
+/// Path-parameter marker: a `*name` segment that matches the rest of the path.
+pub const CATCH_ALL: &str = "x-soli-catch-all";
+/// Top-level marker: the overlay exists but couldn't be read or parsed.
+pub const OVERLAY_ERROR: &str = "x-soli-overlay-error";
+
+/// Read the app's hand-written overlay: `config/openapi.json`, else
+/// `config/openapi.yml` / `.yaml`. Read on every spec request so edits show
+/// up without a restart in dev.
+fn load_overlay(app_root: &Path) -> Result<Option<serde_json::Value>, String> {
+    let config = app_root.join("config");
+    for name in ["openapi.json", "openapi.yml", "openapi.yaml"] {
+        let path = config.join(name);
+        let Ok(source) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let parsed = if name.ends_with(".json") {
+            serde_json::from_str(&source).map_err(|e| e.to_string())
+        } else {
+            serde_yaml::from_str(&source).map_err(|e| e.to_string())
+        };
+        return parsed
+            .map(Some)
+            .map_err(|e| format!("config/{}: {}", name, e));
+    }
+    Ok(None)
+}
+
+/// Deep-merge `overlay` over the generated `spec`. Overlay path keys may use
+/// either the OpenAPI (`/posts/{id}`) or the Soli (`/posts/:id`) form. An
+/// operation's `responses` is replaced wholesale rather than merged — the
+/// generic `200` must not linger next to the documented codes.
+fn merge_overlay(spec: &mut serde_json::Value, overlay: serde_json::Value) {
+    use serde_json::Value;
+
+    let Value::Object(mut overlay) = overlay else {
+        return;
+    };
+    if let Some(Value::Object(paths)) = overlay.remove("paths") {
+        for (key, item) in paths {
+            let key = if key.contains(':') || key.contains('*') {
+                openapi_path(&key).0
+            } else {
+                key
+            };
+            let target = spec["paths"]
+                .as_object_mut()
+                .expect("generated spec has paths")
+                .entry(key)
+                .or_insert_with(|| Value::Object(Default::default()));
+            let Value::Object(item) = item else {
+                continue;
+            };
+            for (method, op) in item {
+                let slot = &mut target[method.as_str()];
+                if let (Value::Object(existing), Some(responses)) =
+                    (&mut *slot, op.get("responses"))
+                {
+                    existing.insert("responses".into(), responses.clone());
+                    existing.remove(GENERATED_RESPONSES);
+                }
+                deep_merge(slot, op);
+            }
+        }
+    }
+    deep_merge(spec, Value::Object(overlay));
+}
+
+fn deep_merge(target: &mut serde_json::Value, source: serde_json::Value) {
+    use serde_json::Value;
+
+    match (target, source) {
+        (Value::Object(target), Value::Object(source)) => {
+            for (key, value) in source {
+                deep_merge(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (target, source) => *target = source,
+    }
 }
 
 /// The spec serialized as pretty JSON.
@@ -176,4 +283,67 @@ mod tests {
         assert_eq!(operation_id("GET", "posts#show"), "get_posts_show");
         assert_eq!(operation_id("post", "users#create"), "post_users_create");
     }
+
+    #[test]
+    fn overlay_replaces_placeholder_responses_and_merges_the_rest() {
+        use serde_json::json;
+
+        let mut spec = json!({
+            "openapi": "3.0.3",
+            "info": { "title": "Soli API", "version": "1.0.0" },
+            "paths": {
+                "/posts/{id}": {
+                    "get": {
+                        "operationId": "get_posts_show",
+                        "responses": { "200": { "description": "OK" } },
+                        GENERATED_RESPONSES: true
+                    }
+                }
+            }
+        });
+        merge_overlay(
+            &mut spec,
+            json!({
+                "info": { "version": "2.1.0" },
+                "paths": {
+                    "/posts/:id": {
+                        "get": { "responses": { "404": { "description": "Missing" } } }
+                    }
+                },
+                "components": { "schemas": { "Post": { "type": "object" } } }
+            }),
+        );
+
+        let op = &spec["paths"]["/posts/{id}"]["get"];
+        assert_eq!(op["operationId"], "get_posts_show");
+        assert_eq!(
+            op["responses"],
+            json!({ "404": { "description": "Missing" } })
+        );
+        assert!(op.get(GENERATED_RESPONSES).is_none());
+        assert_eq!(
+            spec["info"],
+            json!({ "title": "Soli API", "version": "2.1.0" })
+        );
+        assert_eq!(spec["components"]["schemas"]["Post"]["type"], "object");
+    }
+
+    #[test]
+    fn overlay_loads_yaml_and_reports_parse_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load_overlay(dir.path()), Ok(None));
+
+        std::fs::create_dir_all(dir.path().join("config")).unwrap();
+        std::fs::write(
+            dir.path().join("config/openapi.yml"),
+            "paths:\n  /health:\n    get:\n      summary: up\n",
+        )
+        .unwrap();
+        let overlay = load_overlay(dir.path()).unwrap().unwrap();
+        assert_eq!(overlay["paths"]["/health"]["get"]["summary"], "up");
+
+        std::fs::write(dir.path().join("config/openapi.json"), "{ nope").unwrap();
+        let err = load_overlay(dir.path()).unwrap_err();
+        assert!(err.starts_with("config/openapi.json: "), "got: {}", err);
+    }
 }
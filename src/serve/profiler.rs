@@ -0,0 +1,284 @@
+//! Opt-in request profiler (`SOLI_PROFILE`).
+//!
+//! Reports where a request's time went — routing, middleware, handler, DB and
+//! template — as a `Server-Timing` header (browser devtools chart it under
+//! Network → Timing) and, on HTML pages, as a collapsible panel spliced in
+//! before `</body>`.
+//!
+//! It reads the same per-request hooks the dev bar does (`phase_log`,
+//! `view_log` and the AQL `query_log`) but doesn't need `--dev`, so it
+//! measures the server as it really runs: bytecode VM, template cache on, no
+//! hot reload. Under `--dev` the dev bar already carries a richer breakdown,
+//! so only the header is added there.
+//!
+//! "handler" is what's left of the total once the other phases are taken
+//! out — controller code plus outgoing HTTP/KV calls — exactly as the dev
+//! bar derives its "controller" row.
+
+use std::sync::OnceLock;
+
+use crate::interpreter::builtins::model::query_log::LoggedQuery;
+use crate::serve::dev_bar::{fmt_duration_us, html_escape};
+use crate::serve::live_reload::rfind_ascii_case_insensitive;
+use crate::serve::view_log::ViewEntry;
+
+/// Marker so a page is never given two panels (e.g. nested layouts).
+const MARKER: &str = "__soli_profile";
+
+/// Whether profiling is on (`SOLI_PROFILE=1`/`true`). Read once,
+/// process-wide — mirrors `openapi_enabled()`.
+pub fn profile_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var("SOLI_PROFILE")
+            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+            .unwrap_or(false)
+    })
+}
+
+/// One request's time, split by phase. All values are microseconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Breakdown {
+    pub routing_us: u64,
+    pub middleware_us: u64,
+    pub handler_us: u64,
+    pub db_us: u64,
+    pub db_queries: usize,
+    pub template_us: u64,
+    pub total_us: u64,
+}
+
+impl Breakdown {
+    /// Snapshot this worker's per-request logs for a request that took
+    /// `total_us` so far.
+    pub fn capture(total_us: u64) -> Self {
+        Self::from_parts(
+            total_us,
+            &crate::serve::phase_log::snapshot(),
+            &crate::serve::view_log::snapshot(),
+            &crate::interpreter::builtins::model::query_log::snapshot(),
+        )
+    }
+
+    fn from_parts(
+        total_us: u64,
+        phases: &[(String, u64)],
+        views: &[ViewEntry],
+        queries: &[LoggedQuery],
+    ) -> Self {
+        let phase = |name: &str| -> u64 {
+            phases
+                .iter()
+                .filter(|(k, _)| k == name)
+                .map(|(_, v)| *v)
+                .sum()
+        };
+        let routing_us = phase("routing");
+        let middleware_us = phase("middleware");
+        // Same precedence as the dev bar: per-template roots when the view
+        // log has them, the coarse "view" phase otherwise.
+        let template_us = if views.is_empty() {
+            phase("view")
+        } else {
+            crate::serve::view_log::root_total_us(views)
+        };
+        let db_us: u64 = queries
+            .iter()
+            .map(|q| (q.duration_ms * 1000.0).max(0.0) as u64)
+            .sum();
+        let handler_us = total_us
+            .saturating_sub(routing_us)
+            .saturating_sub(middleware_us)
+            .saturating_sub(template_us)
+            .saturating_sub(db_us);
+        Breakdown {
+            routing_us,
+            middleware_us,
+            handler_us,
+            db_us,
+            db_queries: queries.len(),
+            template_us,
+            total_us,
+        }
+    }
+
+    fn rows(&self) -> [(&'static str, u64); 5] {
+        [
+            ("routing", self.routing_us),
+            ("middleware", self.middleware_us),
+            ("handler", self.handler_us),
+            ("db", self.db_us),
+            ("template", self.template_us),
+        ]
+    }
+
+    /// The `Server-Timing` header value, durations in milliseconds.
+    pub fn server_timing(&self) -> String {
+        let mut metrics: Vec<String> = self
+            .rows()
+            .iter()
+            .map(|(name, us)| {
+                if *name == "db" {
+                    format!(
+                        "db;desc=\"{} {}\";dur={}",
+                        self.db_queries,
+                        if self.db_queries == 1 {
+                            "query"
+                        } else {
+                            "queries"
+                        },
+                        ms(*us)
+                    )
+                } else {
+                    format!("{};dur={}", name, ms(*us))
+                }
+            })
+            .collect();
+        metrics.push(format!("total;dur={}", ms(self.total_us)));
+        metrics.join(", ")
+    }
+
+    /// The collapsible footer panel. Self-contained markup and inline styles,
+    /// so it works on any page without a template change.
+    pub fn panel(&self, method: &str, path: &str, status: u16) -> String {
+        let mut rows = String::new();
+        for (name, us) in self.rows() {
+            let pct = if self.total_us == 0 {
+                0.0
+            } else {
+                (us as f64 / self.total_us as f64 * 100.0).min(100.0)
+            };
+            let label = if name == "db" {
+                format!("db ({})", self.db_queries)
+            } else {
+                name.to_string()
+            };
+            rows.push_str(&format!(
+                "<tr><td style=\"padding:0.1rem 0.75rem 0.1rem 0;\">{label}</td>\
+<td style=\"padding:0.1rem 0.75rem 0.1rem 0;text-align:right;font-variant-numeric:tabular-nums;\">{dur}</td>\
+<td style=\"width:12rem;\"><div style=\"height:0.5rem;width:{pct:.1}%;background:#8be9fd;border-radius:0.125rem;\"></div></td></tr>",
+                label = label,
+                dur = fmt_duration_us(us),
+                pct = pct,
+            ));
+        }
+        format!(
+            "<details id=\"{marker}\" style=\"margin:0;padding:0.5rem 1rem;background:#0d1117;color:#c9d1d9;\
+font:12px/1.5 ui-monospace,SFMono-Regular,Menlo,monospace;border-top:1px solid #30363d;\">\
+<summary style=\"cursor:pointer;\">profile · {method} {path} · {status} · {total}</summary>\
+<table style=\"margin-top:0.5rem;border-collapse:collapse;\">{rows}</table></details>",
+            marker = MARKER,
+            method = html_escape(method),
+            path = html_escape(path),
+            status = status,
+            total = fmt_duration_us(self.total_us),
+            rows = rows,
+        )
+    }
+}
+
+/// Splice `panel` into an HTML page before `</body>` (appended when there is
+/// none). Idempotent: a page that already carries a panel is left alone.
+pub fn inject_panel(html: &str, panel: &str) -> String {
+    if html.contains(MARKER) {
+        return html.to_string();
+    }
+    match rfind_ascii_case_insensitive(html, b"</body>") {
+        Some(pos) => {
+            let mut out = String::with_capacity(html.len() + panel.len());
+            out.push_str(&html[..pos]);
+            out.push_str(panel);
+            out.push_str(&html[pos..]);
+            out
+        }
+        None => format!("{}{}", html, panel),
+    }
+}
+
+/// Microseconds as a Server-Timing millisecond value (`1.234`).
+fn ms(us: u64) -> String {
+    format!("{}.{:03}", us / 1000, us % 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(duration_ms: f64) -> LoggedQuery {
+        LoggedQuery {
+            query: "FOR p IN posts RETURN p".to_string(),
+            bind_vars: None,
+            duration_ms,
+        }
+    }
+
+    #[test]
+    fn breakdown_derives_handler_from_the_rest() {
+        let phases = vec![
+            ("middleware".to_string(), 1_000),
+            ("routing".to_string(), 40),
+            ("view".to_string(), 9_999),
+        ];
+        // A layout wrapping a view: only the root counts toward template time.
+        let views = vec![
+            (1, Some(0), "posts/index".to_string(), 2_000),
+            (0, None, "layouts/application".to_string(), 3_000),
+        ];
+        let b = Breakdown::from_parts(10_000, &phases, &views, &[query(1.5), query(0.5)]);
+        assert_eq!(
+            b,
+            Breakdown {
+                routing_us: 40,
+                middleware_us: 1_000,
+                handler_us: 3_960,
+                db_us: 2_000,
+                db_queries: 2,
+                template_us: 3_000,
+                total_us: 10_000,
+            }
+        );
+
+        // Overlapping logs never underflow the derived handler time.
+        let b = Breakdown::from_parts(500, &phases, &[], &[]);
+        assert_eq!((b.template_us, b.handler_us), (9_999, 0));
+    }
+
+    #[test]
+    fn server_timing_lists_every_phase_in_milliseconds() {
+        let b = Breakdown {
+            routing_us: 12,
+            middleware_us: 1_200,
+            handler_us: 3_456,
+            db_us: 2_000,
+            db_queries: 1,
+            template_us: 0,
+            total_us: 6_668,
+        };
+        assert_eq!(
+            b.server_timing(),
+            "routing;dur=0.012, middleware;dur=1.200, handler;dur=3.456, \
+             db;desc=\"1 query\";dur=2.000, template;dur=0.000, total;dur=6.668"
+        );
+    }
+
+    #[test]
+    fn panel_is_escaped_and_injected_once() {
+        let b = Breakdown {
+            total_us: 2_500,
+            handler_us: 2_500,
+            ..Breakdown::default()
+        };
+        let panel = b.panel("GET", "/search?q=<x>", 200);
+        assert!(panel.contains("profile · GET /search?q=&lt;x&gt; · 200 · 2.5ms"));
+        assert!(panel.contains(">db (0)<"));
+
+        let page = inject_panel("<html><body><p>hi</p></BODY></html>", &panel);
+        assert!(page.starts_with("<html><body><p>hi</p><details id=\"__soli_profile\""));
+        assert!(page.ends_with("</details></BODY></html>"));
+        assert_eq!(inject_panel(&page, &panel), page);
+        assert_eq!(
+            inject_panel("<p>bare</p>", &panel),
+            format!("<p>bare</p>{}", panel)
+        );
+    }
+}
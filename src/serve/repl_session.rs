@@ -1,27 +1,311 @@
 //! REPL Session Management
 //!
-//! This module provides thread-local REPL session storage for the development mode.
-//! Each tokio worker thread has its own store since Interpreter is !Send.
+//! This module provides REPL session storage for the development mode.
+//! An Interpreter is !Send, so a session can't follow a `/__dev/repl` request
+//! from one tokio worker to the next. Instead, sessions live on a small pool of
+//! dedicated REPL threads, and a shared index maps each session id to the
+//! thread that owns its interpreter. Every evaluation in a session is sent to
+//! that thread, whichever tokio worker received the request.
+//!
+//! The REPL runs whatever code the error page posts, so its interpreters are
+//! sandboxed: every evaluation gets a time and memory budget (plus step and
+//! allocation caps when configured), and only an allow-list of builtins —
+//! computation, reads and database access — keeps working; every other one,
+//! including any builtin added later, is replaced with one that refuses.
+//! Backtick command substitution, which spawns a process without any global,
+//! is refused too.
 
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::sync::{LazyLock, Mutex, OnceLock};
+use std::thread;
 use std::time::{Duration, Instant};
+
+use crossbeam::channel;
+use tokio::sync::oneshot;
 use uuid::Uuid;
 
-use crate::interpreter::Interpreter;
+use crate::interpreter::environment::Environment;
+use crate::interpreter::value::{NativeFunction, Value};
+use crate::interpreter::{EvalBudget, Interpreter};
+
+/// Threads holding REPL interpreters.
+const REPL_THREADS: usize = 2;
+
+/// Wall-clock time one evaluation may take, unless `SOLI_DEV_REPL_TIMEOUT_MS`
+/// says otherwise.
+const DEFAULT_TIMEOUT_MS: u64 = 5_000;
+
+/// Resident memory one evaluation may add, unless
+/// `SOLI_DEV_REPL_MAX_MEMORY_MB` says otherwise.
+const DEFAULT_MAX_MEMORY_MB: u64 = 256;
+
+/// Builtin functions the REPL keeps: computation, formatting, reads, the
+/// model DSL that `app/models` run as they load, and database access. Every
+/// other builtin function is replaced with one that refuses, so a builtin
+/// added later stays out of the REPL until it is listed here.
+const ALLOWED_FUNCTIONS: &[&str] = &[
+    "__enum_construct",
+    "__enum_from",
+    "after_create",
+    "after_delete",
+    "after_save",
+    "after_transition",
+    "after_update",
+    "argon2_hash",
+    "argon2_verify",
+    "attr_accessible",
+    "attribute",
+    "await",
+    "before_create",
+    "before_delete",
+    "before_save",
+    "before_transition",
+    "before_update",
+    "belongs_to",
+    "clock",
+    "column",
+    "columnar",
+    "const_get",
+    "contains",
+    "current_action",
+    "datetime_now",
+    "db_name",
+    "db_query_raw",
+    "debug",
+    "defined",
+    "ed25519_keypair",
+    "encrypts",
+    "ends_with",
+    "enum_field",
+    "file_exists",
+    "find_model_class_by_collection",
+    "float",
+    "fulltext_index",
+    "geo_index",
+    "getenv",
+    "grouped",
+    "h",
+    "has_and_belongs_to_many",
+    "has_key",
+    "has_many",
+    "has_one",
+    "hasenv",
+    "hash",
+    "hmac",
+    "html_escape",
+    "html_unescape",
+    "index",
+    "int",
+    "json_parse",
+    "json_stringify",
+    "jwt_decode",
+    "jwt_decode_unsafe",
+    "jwt_sign",
+    "jwt_verify",
+    "len",
+    "locale",
+    "md5",
+    "nanoid",
+    "password_hash",
+    "password_verify",
+    "print",
+    "println",
+    "puts",
+    "range",
+    "replace",
+    "runtime_stats",
+    "sanitize_html",
+    "scope",
+    "secure_compare",
+    "set_locale",
+    "sha256",
+    "sha512",
+    "slurp",
+    "slurp_json",
+    "soft_delete",
+    "starts_with",
+    "state_machine",
+    "str",
+    "strip_html",
+    "time_ago",
+    "timeseries",
+    "transition",
+    "type",
+    "ulid",
+    "uploader",
+    "url_decode",
+    "url_encode",
+    "uuid_v4",
+    "uuid_v7",
+    "validate",
+    "validates",
+    "vector_index",
+    "x25519",
+    "x25519_keypair",
+    "x25519_public_key",
+    "x25519_shared_secret",
+];
+
+/// Builtin classes the REPL keeps, each with the static methods it still
+/// refuses. Every static method of any other builtin class refuses.
+const ALLOWED_CLASSES: &[(&str, &[&str])] = &[
+    ("Array", &[]),
+    ("Base64", &[]),
+    ("Cache", &[]),
+    ("Crypto", &[]),
+    ("DateTime", &[]),
+    ("Duration", &[]),
+    ("Encoding", &[]),
+    ("File", &["write", "append", "delete", "copy", "rename"]),
+    ("Geo", &[]),
+    ("Hash", &[]),
+    ("Hex", &[]),
+    ("I18n", &[]),
+    ("JSON", &[]),
+    ("KV", &[]),
+    ("Markdown", &[]),
+    ("Math", &[]),
+    ("Model", &[]),
+    ("NanoID", &[]),
+    ("Point", &[]),
+    ("Range", &[]),
+    ("Regex", &[]),
+    ("Response", &[]),
+    ("RsaKey", &[]),
+    ("Set", &[]),
+    ("String", &[]),
+    ("ULID", &[]),
+    ("UUID", &[]),
+    ("V", &[]),
+    ("X509", &[]),
+    ("Xml", &[]),
+];
+
+/// The budget for one REPL evaluation. `SOLI_DEV_REPL_MAX_STEPS` and
+/// `SOLI_DEV_REPL_MAX_ALLOCATIONS` add deterministic limits on top of the
+/// time and memory ones.
+pub(crate) fn eval_budget() -> EvalBudget {
+    let timeout_ms = env_u64("SOLI_DEV_REPL_TIMEOUT_MS").unwrap_or(DEFAULT_TIMEOUT_MS);
+    let memory_mb = env_u64("SOLI_DEV_REPL_MAX_MEMORY_MB").unwrap_or(DEFAULT_MAX_MEMORY_MB);
+    let mut budget = EvalBudget::new(
+        Duration::from_millis(timeout_ms),
+        Some(memory_mb * 1024 * 1024),
+    );
+    if let Some(steps) = env_u64("SOLI_DEV_REPL_MAX_STEPS") {
+        budget = budget.with_max_steps(steps);
+    }
+    if let Some(allocations) = env_u64("SOLI_DEV_REPL_MAX_ALLOCATIONS") {
+        budget = budget.with_max_allocations(allocations);
+    }
+    budget
+}
+
+/// A positive integer from the environment.
+fn env_u64(name: &str) -> Option<u64> {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|&n| n > 0)
+}
+
+/// Replace every builtin in `env` that isn't allowed with one that fails
+/// when called. Runs on a fresh interpreter, before any app class exists.
+fn sandbox(env: &mut Environment) {
+    let globals: Vec<(String, Value)> = env
+        .local_bindings()
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    for (name, value) in globals {
+        if let Some(value) = sandboxed(&name, value) {
+            env.define(name, value);
+        }
+    }
+}
+
+/// Whether the REPL keeps the builtin function `name`.
+pub(crate) fn allows_function(name: &str) -> bool {
+    ALLOWED_FUNCTIONS.contains(&name)
+}
+
+/// The REPL's replacement for the builtin `name`, or `None` when it is kept
+/// as is: a denied function outright, or a class with its denied static
+/// methods swapped out.
+fn sandboxed(name: &str, value: Value) -> Option<Value> {
+    match value {
+        Value::NativeFunction(_) if !allows_function(name) => {
+            Some(Value::NativeFunction(denied(name.to_string())))
+        }
+        Value::Class(class) => {
+            let allowed = ALLOWED_CLASSES.iter().find(|(allowed, _)| *allowed == name);
+            let is_denied =
+                |method: &str| allowed.is_none_or(|(_, denied)| denied.contains(&method));
+            if !class.native_static_methods.keys().any(|m| is_denied(m)) {
+                return None;
+            }
+            let mut class = (*class).clone();
+            for (method, func) in class.native_static_methods.iter_mut() {
+                if is_denied(method) {
+                    *func = Rc::new(denied(format!("{}.{}", name, method)));
+                }
+            }
+            Some(Value::Class(Rc::new(class)))
+        }
+        _ => None,
+    }
+}
+
+/// `value` as the REPL may see it under `name`: view helpers are injected
+/// after [`sandbox`] ran, so builtins among them go through the same policy.
+pub(crate) fn sandbox_helper(name: &str, value: Value) -> Value {
+    match value {
+        Value::NativeFunction(_) => sandboxed(name, value.clone()).unwrap_or(value),
+        value => value,
+    }
+}
 
-/// REPL session data containing interpreter and last access time
+fn denied(name: String) -> NativeFunction {
+    let message = format!("{} is not available in the dev REPL", name);
+    NativeFunction::new(name, None, move |_| Err(message.clone()))
+}
+
+/// REPL session data owned by one REPL thread
 pub(crate) struct ReplSession {
     pub interpreter: RefCell<Interpreter>,
-    pub last_accessed: RefCell<Instant>,
     pub models_loaded: RefCell<bool>,
 }
 
-/// Thread-local REPL session store
-/// Each tokio worker thread has its own store
+impl ReplSession {
+    fn new() -> Self {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_max_depth(crate::interpreter::executor::worker_call_depth());
+        sandbox(&mut interpreter.environment.borrow_mut());
+        interpreter.set_sandboxed();
+        Self {
+            interpreter: RefCell::new(interpreter),
+            models_loaded: RefCell::new(false),
+        }
+    }
+}
+
+type Job = Box<dyn FnOnce(&ReplSession) + Send>;
+
+enum Command {
+    Eval { session_id: String, job: Job },
+    Drop(String),
+}
+
+/// Where a session lives, and when it was last used.
+struct Entry {
+    thread: usize,
+    last_accessed: Instant,
+}
+
+/// Shared REPL session store
 pub(crate) struct ReplSessionStore {
-    sessions: RefCell<HashMap<String, Rc<ReplSession>>>,
+    sessions: Mutex<HashMap<String, Entry>>,
+    threads: OnceLock<Vec<channel::Sender<Command>>>,
     max_age: Duration,
     max_sessions: usize,
 }
@@ -29,83 +313,251 @@ pub(crate) struct ReplSessionStore {
 impl ReplSessionStore {
     pub(crate) fn new() -> Self {
         Self {
-            sessions: RefCell::new(HashMap::new()),
+            sessions: Mutex::new(HashMap::new()),
+            threads: OnceLock::new(),
             max_age: Duration::from_secs(30 * 60), // 30 minutes
             max_sessions: 100, // SEC-068: bound session count to prevent unbounded growth
         }
     }
 
-    /// Get existing session or create new one
-    pub(crate) fn get_or_create(&self, session_id: &str) -> (String, Rc<ReplSession>) {
-        // Try to get existing valid session
-        {
-            let sessions = self.sessions.borrow();
-            if let Some(session) = sessions.get(session_id) {
-                if !self.is_expired(session) {
-                    *session.last_accessed.borrow_mut() = Instant::now();
-                    return (session_id.to_string(), Rc::clone(session));
-                }
+    /// Run `job` against the session's interpreter on the thread that owns it.
+    /// An empty, unknown or expired id starts a new session. Returns the
+    /// session id and a receiver for the job's result; the receiver errors if
+    /// the job panicked, which also drops the session.
+    pub(crate) fn run<R, F>(&self, session_id: &str, job: F) -> (String, oneshot::Receiver<R>)
+    where
+        R: Send + 'static,
+        F: FnOnce(&ReplSession) -> R + Send + 'static,
+    {
+        let (session_id, thread, evicted) = self.route(session_id);
+        let threads = self.threads();
+        for (thread, id) in evicted {
+            let _ = threads[thread].send(Command::Drop(id));
+        }
+
+        let (tx, rx) = oneshot::channel();
+        let job: Job = Box::new(move |session| {
+            let _ = tx.send(job(session));
+        });
+        let _ = threads[thread].send(Command::Eval {
+            session_id: session_id.clone(),
+            job,
+        });
+        (session_id, rx)
+    }
+
+    /// Drop a session. The next evaluation with its id starts from a fresh
+    /// interpreter. Returns whether the session existed.
+    pub(crate) fn reset(&self, session_id: &str) -> bool {
+        let removed = self.sessions.lock().unwrap().remove(session_id);
+        match removed {
+            Some(entry) => {
+                let _ = self.threads()[entry.thread].send(Command::Drop(session_id.to_string()));
+                true
             }
+            None => false,
         }
+    }
 
-        // Cleanup expired sessions and evict LRU if over capacity
-        if rand::random::<u64>().is_multiple_of(50) {
-            self.cleanup();
+    /// Find the thread owning `session_id`, or register a new session on the
+    /// least loaded thread. Also returns the sessions evicted to make room.
+    fn route(&self, session_id: &str) -> (String, usize, Vec<(usize, String)>) {
+        let mut sessions = self.sessions.lock().unwrap();
+        if let Some(entry) = sessions.get_mut(session_id) {
+            if entry.last_accessed.elapsed() <= self.max_age {
+                entry.last_accessed = Instant::now();
+                return (session_id.to_string(), entry.thread, Vec::new());
+            }
+        }
+
+        // Cleanup expired sessions
+        let mut evicted: Vec<String> = sessions
+            .iter()
+            .filter(|(_, entry)| entry.last_accessed.elapsed() > self.max_age)
+            .map(|(id, _)| id.clone())
+            .collect();
+        // SEC-068: evict oldest session if at capacity
+        if sessions.len() - evicted.len() >= self.max_sessions {
+            if let Some((oldest, _)) = sessions
+                .iter()
+                .filter(|(id, _)| !evicted.contains(id))
+                .min_by_key(|(_, entry)| entry.last_accessed)
+            {
+                evicted.push(oldest.clone());
+            }
         }
+        let evicted: Vec<(usize, String)> = evicted
+            .into_iter()
+            .filter_map(|id| sessions.remove(&id).map(|entry| (entry.thread, id)))
+            .collect();
 
-        // Create new session
         let new_id = if session_id.is_empty() {
             Uuid::new_v4().to_string()
         } else {
             session_id.to_string()
         };
+        let mut load = [0usize; REPL_THREADS];
+        for entry in sessions.values() {
+            load[entry.thread] += 1;
+        }
+        let thread = (0..REPL_THREADS).min_by_key(|&t| load[t]).unwrap_or(0);
+        sessions.insert(
+            new_id.clone(),
+            Entry {
+                thread,
+                last_accessed: Instant::now(),
+            },
+        );
+        (new_id, thread, evicted)
+    }
 
-        let session = Rc::new(ReplSession {
-            interpreter: RefCell::new(Interpreter::new()),
-            last_accessed: RefCell::new(Instant::now()),
-            models_loaded: RefCell::new(false),
-        });
+    /// The REPL threads, spawned on first use.
+    fn threads(&self) -> &[channel::Sender<Command>] {
+        self.threads.get_or_init(|| {
+            // Model queries and HTTP builtins run on the server's runtime.
+            let runtime = tokio::runtime::Handle::try_current().ok();
+            (0..REPL_THREADS)
+                .map(|i| {
+                    let (tx, rx) = channel::unbounded();
+                    let runtime = runtime.clone();
+                    // Sized like a worker, so runaway recursion trips the
+                    // call-depth limit instead of aborting the dev server.
+                    thread::Builder::new()
+                        .name(format!("repl-{}", i))
+                        .stack_size(super::worker_stack_size())
+                        .spawn(move || {
+                            if let Some(handle) = runtime {
+                                super::set_tokio_handle(handle);
+                            }
+                            run_repl_thread(rx);
+                        })
+                        .expect("failed to spawn REPL thread");
+                    tx
+                })
+                .collect()
+        })
+    }
+}
 
-        {
-            let mut sessions = self.sessions.borrow_mut();
-            // SEC-068: evict oldest session if at capacity
-            if sessions.len() >= self.max_sessions {
-                if let Some((oldest_key, _)) = sessions
-                    .iter()
-                    .min_by_key(|(_, s)| *s.last_accessed.borrow())
-                {
-                    let key = oldest_key.clone();
-                    sessions.remove(&key);
+/// A REPL thread: owns the interpreters of the sessions routed to it.
+fn run_repl_thread(rx: channel::Receiver<Command>) {
+    let mut sessions: HashMap<String, ReplSession> = HashMap::new();
+    for command in rx {
+        match command {
+            Command::Eval { session_id, job } => {
+                let session = sessions
+                    .entry(session_id.clone())
+                    .or_insert_with(ReplSession::new);
+                let outcome =
+                    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| job(session)));
+                if outcome.is_err() {
+                    sessions.remove(&session_id);
                 }
             }
-            sessions.insert(new_id.clone(), Rc::clone(&session));
+            Command::Drop(session_id) => {
+                sessions.remove(&session_id);
+            }
         }
+    }
+}
+
+pub(crate) static REPL_STORE: LazyLock<ReplSessionStore> = LazyLock::new(ReplSessionStore::new);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(store: &ReplSessionStore, session_id: &str, code: &str) -> (String, String) {
+        let code = code.to_string();
+        let (id, rx) = store.run(session_id, move |session| {
+            let tokens = crate::lexer::Scanner::new(&code).scan_tokens().unwrap();
+            let program = crate::parser::Parser::new(tokens).parse().unwrap();
+            let mut interpreter = session.interpreter.borrow_mut();
+            interpreter.interpret(&program).unwrap();
+            let value = interpreter.environment.borrow().get("x");
+            value.map(|v| v.to_string()).unwrap_or_default()
+        });
+        (id, rx.blocking_recv().unwrap())
+    }
 
-        (new_id, session)
+    #[test]
+    fn evaluations_in_a_session_share_state() {
+        let store = ReplSessionStore::new();
+        let (id, _) = eval(&store, "", "let x = 1");
+        thread::scope(|s| {
+            for _ in 0..4 {
+                // Whichever thread asks, the session's own thread runs it.
+                s.spawn(|| assert_eq!(eval(&store, &id, "x = x + 1").0, id));
+            }
+        });
+        assert_eq!(eval(&store, &id, "x = x").1, "5");
     }
 
-    /// Check if a session is expired
-    fn is_expired(&self, session: &ReplSession) -> bool {
-        session.last_accessed.borrow().elapsed() > self.max_age
+    #[test]
+    fn reset_starts_a_fresh_interpreter() {
+        let store = ReplSessionStore::new();
+        let (id, _) = eval(&store, "", "let x = 1");
+        assert!(store.reset(&id));
+        assert!(!store.reset(&id));
+        let (same, x) = eval(&store, &id, "let y = 2");
+        assert_eq!(same, id);
+        assert_eq!(x, "");
     }
 
-    /// Cleanup expired sessions
-    fn cleanup(&self) {
-        let mut sessions = self.sessions.borrow_mut();
-        let expired_keys: Vec<String> = sessions
-            .iter()
-            .filter(|(_, session)| self.is_expired(session.as_ref()))
-            .map(|(k, _)| k.clone())
-            .collect();
-        for key in expired_keys {
-            sessions.remove(&key);
+    #[test]
+    fn process_and_write_builtins_are_denied() {
+        let store = ReplSessionStore::new();
+        for call in [
+            r#"System.run_sync(["ls"])"#,
+            r#"Trusted.read("/etc/hosts")"#,
+            r#"File.write("repl.txt", "x")"#,
+            r#"barf("repl.txt", "x")"#,
+            r#"setenv("REPL", "1")"#,
+            "`ls`",
+            r#"Spreadsheet.csv_write("repl.csv", [["a"]])"#,
+            r#"Spreadsheet.excel_write("repl.xlsx", [["a"]])"#,
+            r#"__export_begin("repl")"#,
+            r#"__export_finish("repl", "disk", "repl-exports")"#,
+            r#"with_cassette("repl", fn() { return 1 })"#,
+            r#"Image.new("repl.png")"#,
+            r#"screenshot("repl.png")"#,
+            r#"HTTP.get("http://127.0.0.1:1")"#,
+            r#"Job.enqueue("ReplJob", {})"#,
+        ] {
+            let code = format!("let x = \"\"\ntry {{ {} }} catch e {{ x = e }}", call);
+            let (_, x) = eval(&store, "", &code);
+            assert!(
+                x.contains("is not available in the dev REPL"),
+                "{}: {}",
+                call,
+                x
+            );
         }
+        // Reading and computing stay allowed.
+        assert_eq!(
+            eval(&store, "", r#"let x = File.exists("no-such-file")"#).1,
+            "false"
+        );
+        assert_eq!(eval(&store, "", "let x = len(str(sha256(\"a\")))").1, "64");
     }
-}
 
-// Thread-local storage for REPL sessions
-// Each thread gets its own store, which is fine since sessions are identified by UUID
-// and requests from the same browser tab will hit the same thread most of the time
-thread_local! {
-    pub(crate) static REPL_STORE: ReplSessionStore = ReplSessionStore::new();
+    #[test]
+    fn builtins_missing_from_the_allow_list_are_denied() {
+        let native = Value::NativeFunction(NativeFunction::new("new_builtin", None, |_| {
+            Ok(Value::Null)
+        }));
+        assert!(sandboxed("new_builtin", native.clone()).is_some());
+        assert!(sandboxed("len", native).is_none());
+    }
+
+    #[test]
+    fn oldest_session_is_evicted_at_capacity() {
+        let mut store = ReplSessionStore::new();
+        store.max_sessions = 2;
+        let (first, _) = eval(&store, "", "let x = 1");
+        eval(&store, "", "let x = 2");
+        eval(&store, "", "let x = 3");
+        assert!(!store.sessions.lock().unwrap().contains_key(&first));
+        assert_eq!(store.sessions.lock().unwrap().len(), 2);
+    }
 }
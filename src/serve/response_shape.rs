@@ -0,0 +1,369 @@
+//! `returns:` response shapes on routes.
+//!
+//! `get("/api/users", "users#index", returns: "Array<UserJson>")` declares
+//! the JSON body the action answers with, and `shape("UserJson", {...})` in
+//! `config/routes.sl` names an object shape. In dev mode and under the test
+//! runner, each 2xx response of such a route is checked against its shape;
+//! one that drifts is replaced by a 500 page listing every mismatch, so a
+//! renamed or dropped field shows up on the first request instead of in a
+//! client. Production never checks.
+//!
+//! A shape is a type string (`"Int"`, `"String?"`, `"Float[]"`,
+//! `"Array<UserJson>"`, a shape name), a one-element array (`[fields]`, an
+//! array of that shape) or a hash of field name to shape. A `?` type may be
+//! null; a field whose name ends in `?` may be absent. Extra fields are
+//! allowed. Shapes compile to JSON Schema and are checked by the
+//! `soli test --contract` validator.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, RwLock};
+
+use serde_json::{json, Map, Value as Json};
+
+use crate::ast::{TypeAnnotation, TypeKind};
+use crate::interpreter::value::{HashKey, Value};
+
+/// A route's declared response shape.
+#[derive(Debug)]
+pub(crate) struct RouteShape {
+    /// The shape as written, for the mismatch page (`Array<UserJson>`).
+    pub(crate) label: String,
+    schema: Json,
+}
+
+/// Named shapes, as JSON Schemas (`#/components/schemas/<name>`).
+static NAMED: LazyLock<RwLock<Map<String, Json>>> = LazyLock::new(|| RwLock::new(Map::new()));
+/// `(METHOD, handler)` -> shape. The handler as the route stores it, so the
+/// request path can look it up with what `find_route` returns.
+type RouteShapes = HashMap<(String, String), Arc<RouteShape>>;
+static ROUTES: LazyLock<RwLock<RouteShapes>> = LazyLock::new(|| RwLock::new(HashMap::new()));
+/// Skips the lock on every request of an app that declares no shapes.
+static ANY_ROUTE_SHAPES: AtomicBool = AtomicBool::new(false);
+
+/// `shape(name, fields)`: register a named shape.
+pub fn define_shape(name: &str, shape: &Value) -> Result<(), String> {
+    let schema = to_schema(shape).map_err(|e| format!("shape '{}': {}", name, e))?;
+    NAMED.write().unwrap().insert(name.to_string(), schema);
+    Ok(())
+}
+
+/// `returns:` on a route: check `method handler` responses against `shape`.
+pub fn set_route_shape(method: &str, handler: &str, shape: &Value) -> Result<(), String> {
+    let schema = to_schema(shape).map_err(|e| format!("returns: {}", e))?;
+    let label = match shape {
+        Value::String(s) => s.to_string(),
+        other => other.to_string(),
+    };
+    ROUTES.write().unwrap().insert(
+        (method.to_uppercase(), handler.to_string()),
+        Arc::new(RouteShape { label, schema }),
+    );
+    ANY_ROUTE_SHAPES.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Forget every shape, before routes.sl is evaluated again.
+pub fn clear() {
+    NAMED.write().unwrap().clear();
+    ROUTES.write().unwrap().clear();
+    ANY_ROUTE_SHAPES.store(false, Ordering::Relaxed);
+}
+
+/// The shape declared for `method handler`, if any.
+pub(crate) fn route_shape(method: &str, handler: &str) -> Option<Arc<RouteShape>> {
+    if !ANY_ROUTE_SHAPES.load(Ordering::Relaxed) {
+        return None;
+    }
+    ROUTES
+        .read()
+        .unwrap()
+        .get(&(method.to_uppercase(), handler.to_string()))
+        .cloned()
+}
+
+/// Check a response against `shape`: one line per mismatch, empty when it
+/// conforms. Only 2xx responses with a body are checked.
+pub(crate) fn check(
+    shape: &RouteShape,
+    status: u16,
+    headers: &[(String, String)],
+    body: &[u8],
+) -> Vec<String> {
+    if !(200..300).contains(&status) || status == 204 {
+        return Vec::new();
+    }
+    let content_type = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.split(';').next().unwrap_or("").trim().to_string())
+        .unwrap_or_default();
+    if content_type != "application/json" && !content_type.ends_with("+json") {
+        let got = if content_type.is_empty() {
+            "none".to_string()
+        } else {
+            content_type
+        };
+        return vec![format!(
+            "content-type: expected application/json, got {}",
+            got
+        )];
+    }
+    let value: Json = match serde_json::from_slice(body) {
+        Ok(value) => value,
+        Err(e) => return vec![format!("body: expected JSON, got invalid JSON ({})", e)],
+    };
+
+    let named = NAMED.read().unwrap().clone();
+    let missing = missing_shapes(&shape.schema, &named);
+    if !missing.is_empty() {
+        return missing
+            .into_iter()
+            .map(|name| {
+                format!(
+                    "returns: no shape named '{}' (declare it with shape(\"{}\", {{...}}))",
+                    name, name
+                )
+            })
+            .collect();
+    }
+    let spec = json!({ "components": { "schemas": named } });
+    crate::interpreter::builtins::api_contract::schema_drifts(&spec, &shape.schema, &value, "body")
+}
+
+/// Compile a shape value to a JSON Schema.
+fn to_schema(shape: &Value) -> Result<Json, String> {
+    match shape {
+        Value::String(text) => type_schema(text),
+        Value::Array(items) => {
+            let items = items.borrow();
+            match items.as_slice() {
+                [element] => Ok(json!({ "type": "array", "items": to_schema(element)? })),
+                _ => Err(format!(
+                    "an array shape holds exactly one element shape, got {}",
+                    items.len()
+                )),
+            }
+        }
+        Value::Hash(pairs) => {
+            let mut properties = Map::new();
+            let mut required = Vec::new();
+            for (key, field) in pairs.borrow().iter() {
+                let name = match key {
+                    HashKey::String(s) | HashKey::Symbol(s) => s.to_string(),
+                    _ => return Err("field names must be strings".to_string()),
+                };
+                let name = match name.strip_suffix('?') {
+                    Some(optional) => optional.to_string(),
+                    None => {
+                        required.push(Json::String(name.clone()));
+                        name
+                    }
+                };
+                let schema = to_schema(field).map_err(|e| format!("{}: {}", name, e))?;
+                properties.insert(name, schema);
+            }
+            Ok(json!({ "type": "object", "properties": properties, "required": required }))
+        }
+        other => Err(format!(
+            "expected a type string, a one-element array or a hash, got {}",
+            other.type_name()
+        )),
+    }
+}
+
+/// Compile a type string (`"Array<UserJson>"`, `"String?"`) with the type
+/// annotation parser.
+fn type_schema(text: &str) -> Result<Json, String> {
+    let invalid = |e: String| format!("invalid type '{}': {}", text, e);
+    let tokens = crate::lexer::Scanner::new(text)
+        .scan_tokens()
+        .map_err(|e| invalid(e.to_string()))?;
+    let mut parser = crate::parser::Parser::new(tokens);
+    let ty = parser.parse_type().map_err(|e| invalid(e.to_string()))?;
+    if !parser.is_at_end() {
+        return Err(invalid(format!("unexpected '{}'", parser.peek().kind)));
+    }
+    annotation_schema(&ty).map_err(invalid)
+}
+
+fn annotation_schema(ty: &TypeAnnotation) -> Result<Json, String> {
+    match &ty.kind {
+        TypeKind::Named(name) => Ok(match name.as_str() {
+            "Int" => json!({ "type": "integer" }),
+            "Float" | "Number" => json!({ "type": "number" }),
+            "String" => json!({ "type": "string" }),
+            "Bool" => json!({ "type": "boolean" }),
+            "Hash" => json!({ "type": "object" }),
+            "Array" => json!({ "type": "array" }),
+            "Null" => json!({ "type": "null" }),
+            "Any" => json!({}),
+            shape => json!({ "$ref": format!("#/components/schemas/{}", shape) }),
+        }),
+        TypeKind::Void => Ok(json!({ "type": "null" })),
+        TypeKind::Array(element) => {
+            Ok(json!({ "type": "array", "items": annotation_schema(element)? }))
+        }
+        TypeKind::Hash { value_type, .. } => Ok(json!({
+            "type": "object",
+            "additionalProperties": annotation_schema(value_type)?,
+        })),
+        TypeKind::Nullable(inner) => {
+            // `$ref` siblings are ignored, so a nullable shape wraps the ref.
+            let inner = annotation_schema(inner)?;
+            Ok(json!({ "anyOf": [inner, { "type": "null" }] }))
+        }
+        TypeKind::Record(fields) => {
+            let mut properties = Map::new();
+            let mut required = Vec::new();
+            for field in fields {
+                properties.insert(
+                    field.name.clone(),
+                    annotation_schema(&field.type_annotation)?,
+                );
+                if !matches!(field.type_annotation.kind, TypeKind::Nullable(_)) {
+                    required.push(Json::String(field.name.clone()));
+                }
+            }
+            Ok(json!({ "type": "object", "properties": properties, "required": required }))
+        }
+        TypeKind::Function { .. } | TypeKind::Tuple(_) => Err(format!("{} is not a JSON type", ty)),
+    }
+}
+
+/// The shape names `schema` refers to, directly or through other named
+/// shapes, that were never declared.
+fn missing_shapes(schema: &Json, named: &Map<String, Json>) -> Vec<String> {
+    fn walk(
+        node: &Json,
+        named: &Map<String, Json>,
+        seen: &mut HashSet<String>,
+        out: &mut Vec<String>,
+    ) {
+        match node {
+            Json::Object(fields) => {
+                if let Some(name) = fields
+                    .get("$ref")
+                    .and_then(Json::as_str)
+                    .and_then(|r| r.strip_prefix("#/components/schemas/"))
+                {
+                    if seen.insert(name.to_string()) {
+                        match named.get(name) {
+                            Some(target) => walk(target, named, seen, out),
+                            None => out.push(name.to_string()),
+                        }
+                    }
+                }
+                for field in fields.values() {
+                    walk(field, named, seen, out);
+                }
+            }
+            Json::Array(items) => {
+                for item in items {
+                    walk(item, named, seen, out);
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut out = Vec::new();
+    walk(schema, named, &mut HashSet::new(), &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::value::HashPairs;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    fn s(text: &str) -> Value {
+        Value::String(text.into())
+    }
+
+    fn hash(fields: &[(&str, Value)]) -> Value {
+        let mut pairs = HashPairs::default();
+        for (k, v) in fields {
+            pairs.insert(HashKey::String((*k).into()), v.clone());
+        }
+        Value::Hash(Rc::new(RefCell::new(pairs)))
+    }
+
+    fn json_headers() -> Vec<(String, String)> {
+        vec![(
+            "Content-Type".into(),
+            "application/json; charset=utf-8".into(),
+        )]
+    }
+
+    fn shape(value: &Value) -> RouteShape {
+        RouteShape {
+            label: String::new(),
+            schema: to_schema(value).unwrap(),
+        }
+    }
+
+    #[test]
+    fn type_strings_compile_to_schemas() {
+        assert_eq!(type_schema("Int").unwrap(), json!({ "type": "integer" }));
+        assert_eq!(
+            type_schema("Array<String>").unwrap(),
+            json!({ "type": "array", "items": { "type": "string" } })
+        );
+        assert_eq!(
+            type_schema("Float?").unwrap(),
+            json!({ "anyOf": [{ "type": "number" }, { "type": "null" }] })
+        );
+        assert!(type_schema("Int Int").is_err());
+        assert!(type_schema("(Int) -> Int").is_err());
+    }
+
+    #[test]
+    fn conforming_body_passes_and_drift_is_listed() {
+        let user = hash(&[
+            ("id", s("Int")),
+            ("name", s("String")),
+            ("email?", s("String?")),
+        ]);
+        let list = Value::Array(Rc::new(RefCell::new(vec![user])));
+        let shape = shape(&list);
+
+        let ok = br#"[{"id": 1, "name": "Ada"}, {"id": 2, "name": "Bo", "email": null}]"#;
+        assert!(check(&shape, 200, &json_headers(), ok).is_empty());
+
+        let drifted = br#"[{"id": "1", "name": "Ada"}, {"id": 2}]"#;
+        let drifts = check(&shape, 200, &json_headers(), drifted);
+        assert!(drifts.contains(&"body[0].id: expected integer, got string \"1\"".to_string()));
+        assert!(
+            drifts.contains(&"body[1].name: expected required property, got missing".to_string())
+        );
+    }
+
+    #[test]
+    fn non_json_and_error_responses() {
+        let shape = shape(&s("Hash"));
+        let html = vec![("Content-Type".to_string(), "text/html".to_string())];
+        assert_eq!(
+            check(&shape, 200, &html, b"<p>hi</p>"),
+            vec!["content-type: expected application/json, got text/html"]
+        );
+        // Redirects and errors aren't the declared success body.
+        assert!(check(&shape, 302, &html, b"").is_empty());
+        assert!(check(&shape, 500, &html, b"boom").is_empty());
+    }
+
+    #[test]
+    fn bad_shapes_are_rejected() {
+        assert!(to_schema(&Value::Int(1)).is_err());
+        let two = Value::Array(Rc::new(RefCell::new(vec![s("Int"), s("String")])));
+        assert!(to_schema(&two).is_err());
+    }
+
+    #[test]
+    fn missing_named_shapes_are_reported() {
+        let named = Map::new();
+        let schema = type_schema("Array<NoSuchShape>").unwrap();
+        assert_eq!(missing_shapes(&schema, &named), vec!["NoSuchShape"]);
+    }
+}
@@ -0,0 +1,1080 @@
+//! `soli check-routes` support: cross-reference an app's route table with the
+//! controllers it points at, without starting the server.
+//!
+//! Four kinds of problem are reported — the ones that otherwise only surface
+//! as a 500 (or a silently dead action) once the app is deployed:
+//!
+//! - **dangling routes** — the handler's controller or action doesn't exist;
+//! - **unreachable actions** — public actions no route leads to;
+//! - **duplicate paths** — two routes for the same method and path shape,
+//!   where one silently shadows the other;
+//! - **bad signatures** — routed actions whose declared parameters or return
+//!   type can't work with dispatch (one request argument in, a `Response`
+//!   or response hash out).
+//!
+//! The route table is loaded exactly as `soli routes` does (see
+//! `route_listing`); apps without `config/routes.sl` are checked against the
+//! convention-derived routes instead. Controllers are parsed, never executed,
+//! and resolved the way the request path resolves them: the class
+//! (`PostsController`, or the last-segment name for nested keys) with its
+//! superclass chain first, then the file's top-level functions, then any
+//! controller function of that name.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::ast::{Parameter, Program, StmtKind, TypeAnnotation, TypeKind, Visibility};
+use crate::interpreter::builtins::server::Route;
+
+use super::app_loader::{controller_key_from_path, derive_routes_for_file, scan_controllers};
+use super::router::to_pascal_case_controller;
+
+/// A route whose handler can't be resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DanglingRoute {
+    pub method: String,
+    pub path: String,
+    pub handler: String,
+    pub reason: String,
+}
+
+/// A public controller action that no route reaches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnreachableAction {
+    /// `PostsController#archive` for class actions, `posts#archive` for
+    /// function-style controllers.
+    pub action: String,
+    /// Controller file, relative to the app root.
+    pub file: String,
+}
+
+/// Two or more routes sharing a method and path shape (`:id` and `:post_id`
+/// are the same shape). Only `winner` is ever dispatched.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateRoute {
+    pub method: String,
+    pub path: String,
+    /// Handlers in registration order.
+    pub handlers: Vec<String>,
+    pub winner: String,
+}
+
+/// A routed action whose typed signature doesn't fit dispatch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BadSignature {
+    /// Same naming as [`UnreachableAction::action`].
+    pub action: String,
+    /// Controller file, relative to the app root.
+    pub file: String,
+    pub reason: String,
+}
+
+/// The result of a check. `is_clean()` is what the CLI exit code follows.
+#[derive(Debug, Default)]
+pub struct RouteCheck {
+    /// True when the app has no `config/routes.sl` and the convention-derived
+    /// routes were checked instead.
+    pub convention: bool,
+    pub routes_checked: usize,
+    pub controllers_checked: usize,
+    pub dangling: Vec<DanglingRoute>,
+    pub unreachable: Vec<UnreachableAction>,
+    pub duplicates: Vec<DuplicateRoute>,
+    pub signatures: Vec<BadSignature>,
+}
+
+impl RouteCheck {
+    pub fn problem_count(&self) -> usize {
+        self.dangling.len() + self.unreachable.len() + self.duplicates.len() + self.signatures.len()
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.problem_count() == 0
+    }
+}
+
+/// A class declared in a controller file.
+struct ClassInfo {
+    name: String,
+    superclass: Option<String>,
+    /// Instance methods: (name, is_public).
+    methods: Vec<(String, bool)>,
+    /// Method name → why its signature can't serve as an action.
+    bad_signatures: HashMap<String, String>,
+}
+
+/// One parsed controller file.
+struct ControllerFile {
+    key: String,
+    file: String,
+    /// Engine controllers resolve handlers but are never reported as
+    /// unreachable — they aren't the app's to prune.
+    engine: bool,
+    classes: Vec<ClassInfo>,
+    functions: Vec<String>,
+    /// Function name → why its signature can't serve as an action.
+    bad_signatures: HashMap<String, String>,
+}
+
+/// Everything `resolve` needs: parsed controllers plus the reachability marks
+/// each resolved route leaves behind.
+struct Controllers {
+    files: Vec<ControllerFile>,
+    /// Class name → (file index, class index).
+    classes: HashMap<String, (usize, usize)>,
+    reached_methods: HashSet<(String, String)>,
+    reached_functions: HashSet<(usize, String)>,
+}
+
+/// Check the app at `app_path`. Errors are the ones that stop the check
+/// altogether: a missing folder, a broken routes file, an unparsable
+/// controller.
+pub fn check_routes(app_path: &Path) -> Result<RouteCheck, String> {
+    if !app_path.is_dir() {
+        return Err(format!("Folder '{}' does not exist", app_path.display()));
+    }
+    let controllers_dir = app_path.join("app").join("controllers");
+    let convention = !app_path.join("config").join("routes.sl").is_file();
+
+    // Loading routes.sl also mounts engines, so their controllers are known
+    // by the time they're scanned below.
+    let explicit = if convention {
+        None
+    } else {
+        Some(super::route_listing::collect_routes(app_path)?.routes)
+    };
+
+    let mut files = Vec::new();
+    scan_dir(app_path, &controllers_dir, false, &mut files)?;
+    if !convention {
+        for engine in super::engine_loader::get_all_mounted_engines() {
+            scan_dir(
+                app_path,
+                &engine.path.join("app").join("controllers"),
+                true,
+                &mut files,
+            )?;
+        }
+    }
+
+    let routes = match explicit {
+        Some(routes) => routes,
+        None => convention_routes(&controllers_dir)?,
+    };
+
+    let mut controllers = Controllers::new(files);
+    let mut check = RouteCheck {
+        convention,
+        routes_checked: routes.len(),
+        controllers_checked: controllers.files.iter().filter(|f| !f.engine).count(),
+        ..RouteCheck::default()
+    };
+
+    for route in &routes {
+        if let Err(reason) = controllers.resolve(&route.handler_name) {
+            check.dangling.push(DanglingRoute {
+                method: route.method.clone(),
+                path: route.path_pattern.clone(),
+                handler: route.handler_name.clone(),
+                reason,
+            });
+        }
+    }
+    check.unreachable = controllers.unreachable();
+    check.duplicates = duplicates(&routes);
+    check.signatures = controllers.bad_signatures();
+    Ok(check)
+}
+
+fn scan_dir(
+    app_path: &Path,
+    controllers_dir: &Path,
+    engine: bool,
+    out: &mut Vec<ControllerFile>,
+) -> Result<(), String> {
+    if !controllers_dir.is_dir() {
+        return Ok(());
+    }
+    let paths = scan_controllers(controllers_dir).map_err(|e| e.to_string())?;
+    for path in paths {
+        let file = path
+            .strip_prefix(app_path)
+            .unwrap_or(&path)
+            .display()
+            .to_string();
+        let program = parse_controller(&path).map_err(|e| format!("Error in {}: {}", file, e))?;
+        let mut parsed = ControllerFile {
+            key: controller_key_from_path(controllers_dir, &path),
+            file,
+            engine,
+            classes: Vec::new(),
+            functions: Vec::new(),
+            bad_signatures: HashMap::new(),
+        };
+        let aliases: HashMap<&str, &TypeAnnotation> = program
+            .statements
+            .iter()
+            .filter_map(|stmt| match &stmt.kind {
+                StmtKind::TypeAlias(decl) => Some((decl.name.as_str(), &decl.type_annotation)),
+                _ => None,
+            })
+            .collect();
+        // The checker reports a self-referring alias; following one here
+        // would never end.
+        let aliases: HashMap<&str, &TypeAnnotation> = aliases
+            .iter()
+            .filter(|(name, ty)| !ty.refers_to(name, &|n| aliases.get(n).copied()))
+            .map(|(name, ty)| (*name, *ty))
+            .collect();
+        for stmt in &program.statements {
+            match &stmt.kind {
+                StmtKind::Function(decl) => {
+                    parsed.functions.push(decl.name.clone());
+                    if let Some(reason) =
+                        signature_problem(&decl.params, decl.return_type.as_ref(), &aliases)
+                    {
+                        parsed.bad_signatures.insert(decl.name.clone(), reason);
+                    }
+                }
+                StmtKind::Class(decl) => {
+                    let instance_methods = decl.methods.iter().filter(|m| !m.is_static);
+                    parsed.classes.push(ClassInfo {
+                        name: decl.name.clone(),
+                        superclass: decl.superclass.clone(),
+                        methods: instance_methods
+                            .clone()
+                            .map(|m| (m.name.clone(), m.visibility == Visibility::Public))
+                            .collect(),
+                        bad_signatures: instance_methods
+                            .filter_map(|m| {
+                                signature_problem(&m.params, m.return_type.as_ref(), &aliases)
+                                    .map(|reason| (m.name.clone(), reason))
+                            })
+                            .collect(),
+                    });
+                }
+                _ => {}
+            }
+        }
+        out.push(parsed);
+    }
+    Ok(())
+}
+
+/// Parse a controller file — source or protected-bundle AST blob.
+fn parse_controller(path: &Path) -> Result<Program, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    if crate::bundle::is_ast_blob(&bytes) {
+        crate::bundle::deserialize_program(&bytes)
+    } else {
+        crate::parse(&String::from_utf8_lossy(&bytes)).map_err(|e| e.to_string())
+    }
+}
+
+/// The routes the server derives when there is no `config/routes.sl`.
+fn convention_routes(controllers_dir: &Path) -> Result<Vec<Route>, String> {
+    if !controllers_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut routes = Vec::new();
+    for path in scan_controllers(controllers_dir).map_err(|e| e.to_string())? {
+        let key = controller_key_from_path(controllers_dir, &path);
+        let derived = derive_routes_for_file(&path, &format!("{}_controller", key))
+            .map_err(|e| e.to_string())?;
+        routes.extend(derived.into_iter().map(|r| Route {
+            method: r.method,
+            path_pattern: r.path,
+            handler_name: format!("{}#{}", key, r.function_name),
+            name: None,
+            middleware: Vec::new(),
+            middleware_names: Vec::new(),
+        }));
+    }
+    Ok(routes)
+}
+
+impl Controllers {
+    fn new(files: Vec<ControllerFile>) -> Self {
+        let mut classes = HashMap::new();
+        for (fi, file) in files.iter().enumerate() {
+            for (ci, class) in file.classes.iter().enumerate() {
+                classes.entry(class.name.clone()).or_insert((fi, ci));
+            }
+        }
+        Controllers {
+            files,
+            classes,
+            reached_methods: HashSet::new(),
+            reached_functions: HashSet::new(),
+        }
+    }
+
+    fn class(&self, name: &str) -> Option<&ClassInfo> {
+        self.classes
+            .get(name)
+            .map(|&(fi, ci)| &self.files[fi].classes[ci])
+    }
+
+    /// The class a controller key dispatches to, if it has one.
+    fn class_for_key(&self, key: &str) -> Option<String> {
+        let full = to_pascal_case_controller(key);
+        if self.classes.contains_key(&full) {
+            return Some(full);
+        }
+        let simple = to_pascal_case_controller(key.rsplit('/').next()?);
+        (key.contains('/') && self.classes.contains_key(&simple)).then_some(simple)
+    }
+
+    /// `class` and its superclasses, nearest first. Stops at the first class
+    /// that isn't a scanned controller (e.g. the built-in `Controller`).
+    fn chain(&self, class: &str) -> Vec<&ClassInfo> {
+        let mut chain = Vec::new();
+        let mut seen = HashSet::new();
+        let mut next = Some(class.to_string());
+        while let Some(name) = next {
+            if !seen.insert(name.clone()) {
+                break;
+            }
+            match self.class(&name) {
+                Some(info) => {
+                    next = info.superclass.clone();
+                    chain.push(info);
+                }
+                None => break,
+            }
+        }
+        chain
+    }
+
+    fn file_for_key(&self, key: &str) -> Option<usize> {
+        self.files.iter().position(|f| f.key == key)
+    }
+
+    /// Any controller file defining a top-level function `action` — the
+    /// global-environment fallback the server uses.
+    fn global_function(&self, action: &str) -> Option<usize> {
+        self.files
+            .iter()
+            .position(|f| f.functions.iter().any(|n| n == action))
+    }
+
+    /// Resolve a handler the way dispatch does, marking what it reaches.
+    /// `Err` carries a human-readable reason.
+    fn resolve(&mut self, handler: &str) -> Result<(), String> {
+        let Some((key, action)) = handler.split_once('#') else {
+            if handler == "*" {
+                return Ok(());
+            }
+            return match self.global_function(handler) {
+                Some(fi) => {
+                    self.reached_functions.insert((fi, handler.to_string()));
+                    Ok(())
+                }
+                None => Err(format!("no controller defines `{}`", handler)),
+            };
+        };
+
+        if let Some(class) = self.class_for_key(key) {
+            let mut marks = Vec::new();
+            for info in self.chain(&class) {
+                for (method, _) in &info.methods {
+                    if action == "*" || method == action {
+                        marks.push((info.name.clone(), method.clone()));
+                    }
+                }
+                if action != "*" && !marks.is_empty() {
+                    break;
+                }
+            }
+            if marks.is_empty() && action != "*" {
+                return Err(format!("{} has no `{}` action", class, action));
+            }
+            self.reached_methods.extend(marks);
+            return Ok(());
+        }
+
+        let file = self.file_for_key(key);
+        if action == "*" {
+            let fi = file.ok_or_else(|| missing_controller(key))?;
+            let marks: Vec<_> = self.files[fi]
+                .functions
+                .iter()
+                .map(|n| (fi, n.clone()))
+                .collect();
+            self.reached_functions.extend(marks);
+            return Ok(());
+        }
+        let defining = file
+            .filter(|&fi| self.files[fi].functions.iter().any(|n| n == action))
+            .or_else(|| self.global_function(action));
+        match (defining, file) {
+            (Some(fi), _) => {
+                self.reached_functions.insert((fi, action.to_string()));
+                Ok(())
+            }
+            (None, Some(fi)) => Err(format!(
+                "{} defines no `{}` action",
+                self.files[fi].file, action
+            )),
+            (None, None) => Err(missing_controller(key)),
+        }
+    }
+
+    /// Public actions of app controllers that no resolved route marked.
+    /// Base controllers (ones another controller extends) are skipped: their
+    /// public methods are usually helpers shared with subclasses, and any
+    /// action they do serve is reached through a subclass route.
+    fn unreachable(&self) -> Vec<UnreachableAction> {
+        let extended: HashSet<&str> = self
+            .files
+            .iter()
+            .flat_map(|f| &f.classes)
+            .filter_map(|c| c.superclass.as_deref())
+            .collect();
+
+        let mut out = Vec::new();
+        for (fi, file) in self.files.iter().enumerate() {
+            if file.engine {
+                continue;
+            }
+            for class in &file.classes {
+                if extended.contains(class.name.as_str()) {
+                    continue;
+                }
+                for (method, public) in &class.methods {
+                    if *public
+                        && !method.starts_with('_')
+                        && !self
+                            .reached_methods
+                            .contains(&(class.name.clone(), method.clone()))
+                    {
+                        out.push(UnreachableAction {
+                            action: format!("{}#{}", class.name, method),
+                            file: file.file.clone(),
+                        });
+                    }
+                }
+            }
+            for function in &file.functions {
+                if !function.starts_with('_')
+                    && !self.reached_functions.contains(&(fi, function.clone()))
+                {
+                    out.push(UnreachableAction {
+                        action: format!("{}#{}", file.key, function),
+                        file: file.file.clone(),
+                    });
+                }
+            }
+        }
+        out
+    }
+
+    /// Routed actions — the ones `resolve` marked — whose signature was
+    /// flagged while scanning. Unrouted methods are left alone: they may be
+    /// helpers with any shape.
+    fn bad_signatures(&self) -> Vec<BadSignature> {
+        let mut out = Vec::new();
+        for (fi, file) in self.files.iter().enumerate() {
+            for class in &file.classes {
+                for (method, _) in &class.methods {
+                    let Some(reason) = class.bad_signatures.get(method) else {
+                        continue;
+                    };
+                    if self
+                        .reached_methods
+                        .contains(&(class.name.clone(), method.clone()))
+                    {
+                        out.push(BadSignature {
+                            action: format!("{}#{}", class.name, method),
+                            file: file.file.clone(),
+                            reason: reason.clone(),
+                        });
+                    }
+                }
+            }
+            for function in &file.functions {
+                let Some(reason) = file.bad_signatures.get(function) else {
+                    continue;
+                };
+                if self.reached_functions.contains(&(fi, function.clone())) {
+                    out.push(BadSignature {
+                        action: format!("{}#{}", file.key, function),
+                        file: file.file.clone(),
+                        reason: reason.clone(),
+                    });
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Why an action declared with `params` and `return_type` can't be
+/// dispatched, if it can't. Dispatch passes at most one argument — the
+/// request, as a `Request` when the parameter asks for one or the raw hash
+/// otherwise — and reads a `Response` or response hash back (or renders the
+/// default view when the action returns nothing). Untyped parts always fit,
+/// and a record type (or a `type` alias of one) describes the request hash.
+fn signature_problem(
+    params: &[Parameter],
+    return_type: Option<&TypeAnnotation>,
+    aliases: &HashMap<&str, &TypeAnnotation>,
+) -> Option<String> {
+    let required: Vec<&Parameter> = params
+        .iter()
+        .filter(|p| p.default_value.is_none() && !p.is_block_param && !p.is_variadic)
+        .collect();
+    if let Some(p) = required.iter().find(|p| p.is_keyword_only) {
+        return Some(format!(
+            "requires keyword argument `{}`; an action only receives the request",
+            p.name
+        ));
+    }
+    if required.len() > 1 {
+        return Some(format!(
+            "takes {} required parameters; an action only receives the request",
+            required.len()
+        ));
+    }
+    if let Some(first) = params
+        .first()
+        .filter(|p| !p.is_block_param && !p.is_variadic && !p.is_keyword_only)
+    {
+        if !accepts_request(&first.type_annotation, aliases) {
+            return Some(format!(
+                "parameter `{}: {}` can't receive the request (use `Request` or `Hash`)",
+                first.name, first.type_annotation
+            ));
+        }
+    }
+    if let Some(ret) = return_type {
+        if !returns_response(ret, aliases) {
+            return Some(format!(
+                "returns `{}`; an action returns a `Response` or a response `Hash`",
+                ret
+            ));
+        }
+    }
+    None
+}
+
+fn accepts_request(ty: &TypeAnnotation, aliases: &HashMap<&str, &TypeAnnotation>) -> bool {
+    match &ty.kind {
+        TypeKind::Named(name) => match aliases.get(name.as_str()) {
+            Some(aliased) => accepts_request(aliased, aliases),
+            None => matches!(name.as_str(), "Any" | "Request" | "Hash"),
+        },
+        TypeKind::Hash { .. } | TypeKind::Record(_) => true,
+        TypeKind::Nullable(inner) => accepts_request(inner, aliases),
+        _ => false,
+    }
+}
+
+fn returns_response(ty: &TypeAnnotation, aliases: &HashMap<&str, &TypeAnnotation>) -> bool {
+    match &ty.kind {
+        TypeKind::Named(name) => match aliases.get(name.as_str()) {
+            Some(aliased) => returns_response(aliased, aliases),
+            None => matches!(name.as_str(), "Any" | "Response" | "Hash"),
+        },
+        TypeKind::Hash { .. } | TypeKind::Record(_) | TypeKind::Void => true,
+        TypeKind::Nullable(inner) => returns_response(inner, aliases),
+        _ => false,
+    }
+}
+
+fn missing_controller(key: &str) -> String {
+    format!(
+        "no controller `{}` (expected app/controllers/{}_controller.sl)",
+        key, key
+    )
+}
+
+/// Path shape used for duplicate detection: param and splat names dropped,
+/// trailing slash ignored (the router strips it before matching).
+fn path_shape(path: &str) -> String {
+    let trimmed = if path != "/" {
+        path.trim_end_matches('/')
+    } else {
+        path
+    };
+    trimmed
+        .split('/')
+        .map(|seg| {
+            if seg.starts_with(':') {
+                ":"
+            } else if seg.starts_with('*') {
+                "*"
+            } else {
+                seg
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Group routes by (method, shape). The route index keeps the *last* static
+/// route for a path (exact matches live in a map) but the *first* dynamic
+/// one (patterns are tried in order), so the winner depends on which it is.
+fn duplicates(routes: &[Route]) -> Vec<DuplicateRoute> {
+    let mut groups: Vec<((String, String), Vec<&Route>)> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    for route in routes {
+        let key = (route.method.clone(), path_shape(&route.path_pattern));
+        match index.get(&key) {
+            Some(&i) => groups[i].1.push(route),
+            None => {
+                index.insert(key.clone(), groups.len());
+                groups.push((key, vec![route]));
+            }
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter(|(_, group)| group.len() > 1)
+        .map(|((method, _), group)| {
+            let dynamic =
+                group[0].path_pattern.contains(':') || group[0].path_pattern.contains('*');
+            let winner = if dynamic {
+                group[0]
+            } else {
+                group[group.len() - 1]
+            };
+            DuplicateRoute {
+                method,
+                path: winner.path_pattern.clone(),
+                handlers: group.iter().map(|r| r.handler_name.clone()).collect(),
+                winner: winner.handler_name.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Render the report: one section per problem kind, then a summary line.
+pub fn format_report(check: &RouteCheck) -> String {
+    let mut out = String::new();
+    let source = if check.convention {
+        "convention routes"
+    } else {
+        "config/routes.sl"
+    };
+    out.push_str(&format!(
+        "\n  Checked {} {} ({}) against {} {}.\n",
+        check.routes_checked,
+        if check.routes_checked == 1 {
+            "route"
+        } else {
+            "routes"
+        },
+        source,
+        check.controllers_checked,
+        if check.controllers_checked == 1 {
+            "controller"
+        } else {
+            "controllers"
+        },
+    ));
+
+    if !check.dangling.is_empty() {
+        let rows: Vec<[String; 4]> = check
+            .dangling
+            .iter()
+            .map(|d| {
+                [
+                    d.method.clone(),
+                    d.path.clone(),
+                    d.handler.clone(),
+                    d.reason.clone(),
+                ]
+            })
+            .collect();
+        push_section(&mut out, "Dangling routes", &rows);
+    }
+    if !check.unreachable.is_empty() {
+        let rows: Vec<[String; 2]> = check
+            .unreachable
+            .iter()
+            .map(|u| [u.action.clone(), u.file.clone()])
+            .collect();
+        push_section(&mut out, "Unreachable actions", &rows);
+    }
+    if !check.duplicates.is_empty() {
+        let rows: Vec<[String; 4]> = check
+            .duplicates
+            .iter()
+            .map(|d| {
+                [
+                    d.method.clone(),
+                    d.path.clone(),
+                    d.handlers.join(", "),
+                    format!("{} wins", d.winner),
+                ]
+            })
+            .collect();
+        push_section(&mut out, "Duplicate paths", &rows);
+    }
+    if !check.signatures.is_empty() {
+        let rows: Vec<[String; 3]> = check
+            .signatures
+            .iter()
+            .map(|b| [b.action.clone(), b.file.clone(), b.reason.clone()])
+            .collect();
+        push_section(&mut out, "Bad handler signatures", &rows);
+    }
+
+    out.push('\n');
+    match check.problem_count() {
+        0 => out.push_str("  No problems found.\n"),
+        1 => out.push_str("  1 problem found.\n"),
+        n => out.push_str(&format!("  {} problems found.\n", n)),
+    }
+    out
+}
+
+/// A bold section heading followed by aligned rows (four-space indent).
+fn push_section<const N: usize>(out: &mut String, title: &str, rows: &[[String; N]]) {
+    let mut widths = [0usize; N];
+    for row in rows {
+        for (w, cell) in widths.iter_mut().zip(row) {
+            *w = (*w).max(cell.len());
+        }
+    }
+    out.push_str(&format!("\n  \x1b[1m{} ({})\x1b[0m\n", title, rows.len()));
+    for row in rows {
+        out.push_str("   ");
+        for (cell, width) in row.iter().zip(&widths) {
+            out.push_str(&format!(" {:<width$} ", cell, width = width));
+        }
+        while out.ends_with(' ') {
+            out.pop();
+        }
+        out.push('\n');
+    }
+}
+
+/// Render the report as pretty-printed JSON, for CI and tooling.
+pub fn format_json(check: &RouteCheck) -> String {
+    let value = serde_json::json!({
+        "source": if check.convention { "convention" } else { "config/routes.sl" },
+        "routes": check.routes_checked,
+        "controllers": check.controllers_checked,
+        "dangling": check.dangling.iter().map(|d| serde_json::json!({
+            "method": d.method,
+            "path": d.path,
+            "handler": d.handler,
+            "reason": d.reason,
+        })).collect::<Vec<_>>(),
+        "unreachable": check.unreachable.iter().map(|u| serde_json::json!({
+            "action": u.action,
+            "file": u.file,
+        })).collect::<Vec<_>>(),
+        "duplicates": check.duplicates.iter().map(|d| serde_json::json!({
+            "method": d.method,
+            "path": d.path,
+            "handlers": d.handlers,
+            "winner": d.winner,
+        })).collect::<Vec<_>>(),
+        "signatures": check.signatures.iter().map(|b| serde_json::json!({
+            "action": b.action,
+            "file": b.file,
+            "reason": b.reason,
+        })).collect::<Vec<_>>(),
+    });
+    serde_json::to_string_pretty(&value).unwrap_or_else(|_| "{}".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write_app(routes_sl: Option<&str>, controllers: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("config")).unwrap();
+        if let Some(routes) = routes_sl {
+            fs::write(dir.path().join("config").join("routes.sl"), routes).unwrap();
+        }
+        for (rel, source) in controllers {
+            let path = dir.path().join("app").join("controllers").join(rel);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+        dir
+    }
+
+    const POSTS: &str = r#"
+class PostsController < ApplicationController
+    def index
+        "all"
+    end
+
+    def show
+        "one"
+    end
+
+    def archive
+        "archived"
+    end
+
+    def _load_post
+        nil
+    end
+
+    private def audit
+        nil
+    end
+end
+"#;
+
+    const APPLICATION: &str = r#"
+class ApplicationController < Controller
+    def current_user
+        nil
+    end
+end
+"#;
+
+    #[test]
+    fn reports_dangling_unreachable_and_duplicates() {
+        let app = write_app(
+            Some(
+                r#"
+get("/posts", "posts#index");
+get("/posts/:id", "posts#show");
+get("/posts/:post_id", "posts#preview");
+get("/me", "posts#current_user");
+get("/comments", "comments#index");
+get("/status", "status");
+"#,
+            ),
+            &[
+                ("posts_controller.sl", POSTS),
+                ("application_controller.sl", APPLICATION),
+            ],
+        );
+        let check = check_routes(app.path()).unwrap();
+        assert!(!check.convention);
+        assert_eq!(check.routes_checked, 6);
+        assert_eq!(check.controllers_checked, 2);
+
+        let dangling: Vec<(&str, &str)> = check
+            .dangling
+            .iter()
+            .map(|d| (d.handler.as_str(), d.reason.as_str()))
+            .collect();
+        assert_eq!(
+            dangling,
+            vec![
+                ("posts#preview", "PostsController has no `preview` action"),
+                (
+                    "comments#index",
+                    "no controller `comments` (expected app/controllers/comments_controller.sl)"
+                ),
+                ("status", "no controller defines `status`"),
+            ]
+        );
+
+        // Inherited `current_user` resolves through the superclass; `_`
+        // and private methods are not actions; the base class is skipped.
+        assert_eq!(
+            check.unreachable,
+            vec![UnreachableAction {
+                action: "PostsController#archive".to_string(),
+                file: "app/controllers/posts_controller.sl".to_string(),
+            }]
+        );
+
+        assert_eq!(
+            check.duplicates,
+            vec![DuplicateRoute {
+                method: "GET".to_string(),
+                path: "/posts/:id".to_string(),
+                handlers: vec!["posts#show".to_string(), "posts#preview".to_string()],
+                winner: "posts#show".to_string(),
+            }]
+        );
+        assert_eq!(check.problem_count(), 5);
+    }
+
+    #[test]
+    fn function_controllers_nested_keys_and_wildcards_resolve() {
+        let app = write_app(
+            Some(
+                r#"
+get("/admin/merchants", "admin/merchants#index");
+get("/docs/*page", "docs#*");
+get("/ping", "pages#ping");
+get("/about", "pages#about");
+get("/about", "pages#about_v2");
+"#,
+            ),
+            &[
+                (
+                    "admin/merchants_controller.sl",
+                    "class MerchantsController < Controller\n    def index\n        \"m\"\n    end\nend\n",
+                ),
+                (
+                    "docs_controller.sl",
+                    "fn intro(req)\n    \"i\"\nend\nfn routing(req)\n    \"r\"\nend\n",
+                ),
+                (
+                    "pages_controller.sl",
+                    "fn about(req)\n    \"a\"\nend\nfn about_v2(req)\n    \"b\"\nend\nfn _helper()\n    nil\nend\n",
+                ),
+                ("health_controller.sl", "fn ping(req)\n    \"pong\"\nend\n"),
+            ],
+        );
+        let check = check_routes(app.path()).unwrap();
+        // `pages#ping` falls back to the global `ping` from another file.
+        assert!(check.dangling.is_empty(), "{:?}", check.dangling);
+        assert!(check.unreachable.is_empty(), "{:?}", check.unreachable);
+        // Static duplicates: the later registration overwrites the earlier.
+        assert_eq!(check.duplicates.len(), 1);
+        assert_eq!(check.duplicates[0].winner, "pages#about_v2");
+    }
+
+    #[test]
+    fn convention_apps_check_derived_routes() {
+        let app = write_app(
+            None,
+            &[
+                (
+                    "home_controller.sl",
+                    "class HomeController < Controller\n    def index\n        \"h\"\n    end\n    def posts\n        \"p\"\n    end\nend\n",
+                ),
+                (
+                    "posts_controller.sl",
+                    "class PostsController < Controller\n    def index\n        \"p\"\n    end\nend\n",
+                ),
+            ],
+        );
+        let check = check_routes(app.path()).unwrap();
+        assert!(check.convention);
+        assert_eq!(check.routes_checked, 3);
+        assert!(check.dangling.is_empty());
+        assert!(check.unreachable.is_empty());
+        // home#posts derives GET /posts, same as posts#index.
+        assert_eq!(check.duplicates.len(), 1);
+        assert_eq!(
+            check.duplicates[0].handlers,
+            vec!["home#posts".to_string(), "posts#index".to_string()]
+        );
+    }
+
+    #[test]
+    fn routed_actions_with_unfit_signatures_are_reported() {
+        let app = write_app(
+            Some(
+                r#"
+get("/users/:id", "users#show");
+post("/users", "users#create");
+get("/users", "users#index");
+get("/health", "health#ping");
+get("/health/deep", "health#deep");
+"#,
+            ),
+            &[
+                (
+                    "users_controller.sl",
+                    r#"
+class UsersController < Controller
+    def show(req: Request) -> Response
+        Response.json({"id": req.param("id")})
+    end
+
+    def create(req: Int) -> Response
+        Response.empty(201)
+    end
+
+    def index(req, page) -> String
+        "all"
+    end
+
+    def _format(user, style) -> String
+        ""
+    end
+end
+"#,
+                ),
+                (
+                    "health_controller.sl",
+                    "fn ping(req: Hash) -> Hash
+    {\"status\": 200}
+end
+fn deep(req) -> Int
+    1
+end
+",
+                ),
+            ],
+        );
+        let check = check_routes(app.path()).unwrap();
+        let bad: Vec<(&str, &str)> = check
+            .signatures
+            .iter()
+            .map(|b| (b.action.as_str(), b.reason.as_str()))
+            .collect();
+        // Files report in scan order. `_format` is flagged while scanning but
+        // never routed, so it stays quiet.
+        assert_eq!(
+            bad,
+            vec![
+                (
+                    "health#deep",
+                    "returns `Int`; an action returns a `Response` or a response `Hash`"
+                ),
+                (
+                    "UsersController#create",
+                    "parameter `req: Int` can't receive the request (use `Request` or `Hash`)"
+                ),
+                (
+                    "UsersController#index",
+                    "takes 2 required parameters; an action only receives the request"
+                ),
+            ]
+        );
+        assert_eq!(check.problem_count(), 3);
+        assert!(format_report(&check).contains("Bad handler signatures (3)"));
+        let json: serde_json::Value = serde_json::from_str(&format_json(&check)).unwrap();
+        assert_eq!(json["signatures"][0]["action"], "health#deep");
+    }
+
+    #[test]
+    fn broken_controller_and_missing_folder_error() {
+        let app = write_app(
+            Some("get(\"/\", \"home#index\");\n"),
+            &[("home_controller.sl", "class HomeController <\n")],
+        );
+        let err = check_routes(app.path()).unwrap_err();
+        assert!(err.starts_with("Error in app/controllers/home_controller.sl"));
+
+        let err = check_routes(Path::new("/definitely/not/here")).unwrap_err();
+        assert!(err.contains("does not exist"));
+    }
+
+    #[test]
+    fn report_and_json_carry_every_problem() {
+        let check = RouteCheck {
+            routes_checked: 2,
+            controllers_checked: 1,
+            dangling: vec![DanglingRoute {
+                method: "GET".to_string(),
+                path: "/x".to_string(),
+                handler: "x#y".to_string(),
+                reason: "no controller `x`".to_string(),
+            }],
+            ..RouteCheck::default()
+        };
+        let report = format_report(&check);
+        assert!(report.contains("Checked 2 routes (config/routes.sl) against 1 controller."));
+        assert!(report.contains("Dangling routes (1)"));
+        assert!(report.contains("GET  /x  x#y  no controller `x`"));
+        assert!(report.ends_with("  1 problem found.\n"));
+        assert!(format_report(&RouteCheck::default()).ends_with("  No problems found.\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&format_json(&check)).unwrap();
+        assert_eq!(json["dangling"][0]["handler"], "x#y");
+        assert_eq!(json["unreachable"].as_array().unwrap().len(), 0);
+        assert_eq!(json["source"], "config/routes.sl");
+    }
+}
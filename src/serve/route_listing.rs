@@ -10,6 +10,7 @@
 
 use std::path::Path;
 
+use crate::interpreter::builtins::controller::registry;
 use crate::interpreter::builtins::router;
 use crate::interpreter::builtins::server::{self, Route};
 use crate::interpreter::Interpreter;
@@ -84,21 +85,26 @@ pub fn collect_routes(app_path: &Path) -> Result<RouteListing, String> {
     let application_file = app_path.join("config").join("application.sl");
     if application_file.is_file() {
         if let Err(e) = execute_file(&mut interpreter, &application_file) {
-            eprintln!("Warning: config/application.sl failed: {}", e);
+            eprintln!("Warning: config/application.sl failed: {}", e.report());
         }
     }
 
     // The exact serve sequence: DSL → clear convention routes → routes.sl →
-    // engine routes.
+    // engine routes → `@route` annotations on controller actions.
     define_routes_dsl(&mut interpreter).map_err(|e| format!("Routes DSL error: {}", e))?;
     server::clear_routes();
     execute_file(&mut interpreter, &routes_file)
-        .map_err(|e| format!("Error in {}: {}", routes_file.display(), e))?;
+        .map_err(|e| format!("Error in {}: {}", routes_file.display(), e.report()))?;
     if has_engines {
         if let Err(e) = engine_loader::load_engine_routes(&mut interpreter) {
             eprintln!("Warning: Failed to load engine routes: {}", e);
         }
     }
+    let controllers_dir = app_path.join("app").join("controllers");
+    if let Err(e) = registry::scan_controllers(&controllers_dir) {
+        eprintln!("Warning: Failed to scan controller metadata: {}", e);
+    }
+    registry::register_annotated_routes();
 
     Ok(RouteListing {
         routes: server::get_routes(),
@@ -232,6 +238,11 @@ pub fn format_table(listing: &RouteListing, grep: Option<&str>) -> String {
 /// `{method, path, handler, name, middleware}` objects (WebSocket rows use
 /// `"method": "WS"`). Stable shape for tooling and agents.
 pub fn format_json(listing: &RouteListing, grep: Option<&str>) -> String {
+    serde_json::to_string_pretty(&routes_json(listing, grep)).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// The array [`format_json`] prints, for embedding in a larger document.
+pub fn routes_json(listing: &RouteListing, grep: Option<&str>) -> serde_json::Value {
     let rows = build_rows(listing, grep);
     let entries: Vec<serde_json::Value> = rows
         .iter()
@@ -255,8 +266,7 @@ pub fn format_json(listing: &RouteListing, grep: Option<&str>) -> String {
             })
         })
         .collect();
-    serde_json::to_string_pretty(&serde_json::Value::Array(entries))
-        .unwrap_or_else(|_| "[]".to_string())
+    serde_json::Value::Array(entries)
 }
 
 #[cfg(test)]
@@ -0,0 +1,367 @@
+//! Leader election for scheduled jobs across server instances.
+//!
+//! SolidB fires each cron entry once, at the callback URL of whichever
+//! instance last upserted it — so with several instances every boot re-points
+//! the schedules, and when that instance dies its crons POST into the void.
+//! With `SOLI_JOBS_LEASE=1` the instances compete for a lease document in
+//! SolidB instead. Only the holder syncs the `static cron` schedules, at its
+//! own `SOLI_JOBS_CALLBACK_URL`; it renews the lease every third of its TTL,
+//! and once it stops — crashed, cut off, shut down — another instance takes
+//! the lease over and points the schedules at itself. A holder that can't
+//! renew steps down a third of a TTL before the lease runs out, so two
+//! instances never both believe they lead. `Cron.is_leader()` tells app code
+//! whether this instance holds the lease.
+
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use crate::interpreter::builtins::jobs;
+use crate::solidb_http::SoliDBClient;
+
+/// Collection holding the lease document.
+const LEASE_COLLECTION: &str = "_soli_leases";
+/// `_key` of the lease document.
+const LEASE_KEY: &str = "scheduler";
+const DEFAULT_TTL_SECS: u64 = 30;
+
+/// Where the lease lives. Times are unix milliseconds.
+pub trait LeaseStore: Send {
+    /// Hold the lease for `holder` until `expires_at` if it is free, expired
+    /// at `now`, or already `holder`'s. Returns whether `holder` now holds it.
+    fn acquire(&mut self, holder: &str, now: i64, expires_at: i64) -> Result<bool, String>;
+
+    /// Give the lease up if `holder` has it.
+    fn release(&mut self, holder: &str) -> Result<(), String>;
+}
+
+/// The lease as a document in SolidB's jobs database.
+pub struct SolidbLeaseStore {
+    client: SoliDBClient,
+    collection_ready: bool,
+}
+
+impl SolidbLeaseStore {
+    pub fn new(client: SoliDBClient) -> Self {
+        Self {
+            client,
+            collection_ready: false,
+        }
+    }
+}
+
+impl LeaseStore for SolidbLeaseStore {
+    fn acquire(&mut self, holder: &str, now: i64, expires_at: i64) -> Result<bool, String> {
+        if !self.collection_ready {
+            // Fails when it already exists, as on every boot but the first.
+            let _ = self.client.create_collection(LEASE_COLLECTION, None);
+            self.collection_ready = true;
+        }
+        let bind_vars = [
+            ("key", serde_json::json!(LEASE_KEY)),
+            ("holder", serde_json::json!(holder)),
+            ("now", serde_json::json!(now)),
+            ("expires_at", serde_json::json!(expires_at)),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+        // Renew our lease or take over an expired one, in one statement so
+        // two instances can't both take it.
+        let updated = self
+            .client
+            .query(
+                &format!(
+                    "FOR l IN {c} FILTER l._key == @key AND (l.holder == @holder OR l.expires_at < @now) \
+                     UPDATE l WITH {{ holder: @holder, expires_at: @expires_at }} IN {c} RETURN NEW",
+                    c = LEASE_COLLECTION
+                ),
+                Some(bind_vars),
+            )
+            .map_err(|e| e.to_string())?;
+        if !updated.is_empty() {
+            return Ok(true);
+        }
+        // No lease yet: the first insert wins, the others hit the `_key`.
+        let document = serde_json::json!({ "holder": holder, "expires_at": expires_at });
+        Ok(self
+            .client
+            .insert(LEASE_COLLECTION, Some(LEASE_KEY), document)
+            .is_ok())
+    }
+
+    fn release(&mut self, holder: &str) -> Result<(), String> {
+        let bind_vars = [
+            ("key".to_string(), serde_json::json!(LEASE_KEY)),
+            ("holder".to_string(), serde_json::json!(holder)),
+        ]
+        .into_iter()
+        .collect();
+        self.client
+            .query(
+                &format!(
+                    "FOR l IN {c} FILTER l._key == @key AND l.holder == @holder REMOVE l IN {c}",
+                    c = LEASE_COLLECTION
+                ),
+                Some(bind_vars),
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// One instance's side of the election.
+pub struct Elector<S: LeaseStore> {
+    store: S,
+    holder: String,
+    ttl_ms: i64,
+    /// Until when we may act as leader: a third of a TTL short of the last
+    /// lease we were granted.
+    leading_until: Option<i64>,
+}
+
+impl<S: LeaseStore> Elector<S> {
+    pub fn new(store: S, holder: String, ttl: Duration) -> Self {
+        Self {
+            store,
+            holder,
+            ttl_ms: ttl.as_millis() as i64,
+            leading_until: None,
+        }
+    }
+
+    /// How often to [`tick`](Self::tick).
+    pub fn interval(&self) -> Duration {
+        Duration::from_millis((self.ttl_ms / 3).max(1) as u64)
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.leading_until.is_some()
+    }
+
+    /// Try to take or renew the lease at `now`. Returns `Some(true)` when
+    /// this instance just became leader, `Some(false)` when it just stopped
+    /// being one, `None` when nothing changed.
+    pub fn tick(&mut self, now: i64) -> Option<bool> {
+        let was_leader = self.is_leader();
+        match self.store.acquire(&self.holder, now, now + self.ttl_ms) {
+            Ok(true) => self.leading_until = Some(now + self.ttl_ms * 2 / 3),
+            Ok(false) => self.leading_until = None,
+            // Can't reach the store: keep leading only while the lease we
+            // were last granted is safely current.
+            Err(e) => {
+                if self.leading_until.is_some_and(|until| now >= until) {
+                    eprintln!("Scheduler lease: can't renew ({}), stepping down", e);
+                    self.leading_until = None;
+                }
+            }
+        }
+        (self.is_leader() != was_leader).then_some(self.is_leader())
+    }
+
+    /// Give the lease up, so another instance needn't wait out the TTL.
+    pub fn resign(&mut self) {
+        if self.leading_until.take().is_some() {
+            let _ = self.store.release(&self.holder);
+        }
+    }
+}
+
+type SharedElector = Arc<Mutex<Elector<Box<dyn LeaseStore>>>>;
+
+impl LeaseStore for Box<dyn LeaseStore> {
+    fn acquire(&mut self, holder: &str, now: i64, expires_at: i64) -> Result<bool, String> {
+        (**self).acquire(holder, now, expires_at)
+    }
+
+    fn release(&mut self, holder: &str) -> Result<(), String> {
+        (**self).release(holder)
+    }
+}
+
+static ELECTOR: OnceLock<SharedElector> = OnceLock::new();
+
+/// Whether `SOLI_JOBS_LEASE` turns the election on.
+pub fn enabled() -> bool {
+    matches!(
+        std::env::var("SOLI_JOBS_LEASE").ok().as_deref(),
+        Some("1") | Some("true") | Some("yes")
+    )
+}
+
+/// This instance's name in the lease: `SOLI_INSTANCE_ID`, else host and pid.
+fn instance_id() -> String {
+    std::env::var("SOLI_INSTANCE_ID")
+        .ok()
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| {
+            let host = std::env::var("HOSTNAME")
+                .ok()
+                .filter(|h| !h.is_empty())
+                .or_else(|| {
+                    std::fs::read_to_string("/etc/hostname")
+                        .ok()
+                        .map(|h| h.trim().to_string())
+                })
+                .unwrap_or_else(|| "localhost".to_string());
+            format!("{}:{}", host, std::process::id())
+        })
+}
+
+fn now_ms() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+/// Join the election: this instance stops syncing crons until it holds the
+/// lease, and a thread keeps taking or renewing it. Called once, before the
+/// web workers load `app/jobs`.
+pub fn start() {
+    let client = match jobs::make_client() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Scheduler lease disabled: {}", e);
+            return;
+        }
+    };
+    let ttl = std::env::var("SOLI_JOBS_LEASE_TTL")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(DEFAULT_TTL_SECS);
+    let store: Box<dyn LeaseStore> = Box::new(SolidbLeaseStore::new(client));
+    let elector = Arc::new(Mutex::new(Elector::new(
+        store,
+        instance_id(),
+        Duration::from_secs(ttl),
+    )));
+    if ELECTOR.set(elector.clone()).is_err() {
+        return;
+    }
+    jobs::set_scheduler_leader(false);
+
+    let spawned = thread::Builder::new()
+        .name("scheduler-lease".to_string())
+        .spawn(move || loop {
+            let (change, interval) = {
+                let mut elector = elector.lock().unwrap_or_else(|e| e.into_inner());
+                (elector.tick(now_ms()), elector.interval())
+            };
+            match change {
+                Some(true) => {
+                    jobs::set_scheduler_leader(true);
+                    println!("Scheduler lease acquired; syncing cron schedules");
+                    for (handler, e) in jobs::sync_static_crons() {
+                        eprintln!("Failed to register static cron for {}: {}", handler, e);
+                    }
+                }
+                Some(false) => {
+                    jobs::set_scheduler_leader(false);
+                    println!("Scheduler lease lost");
+                }
+                None => {}
+            }
+            thread::sleep(interval);
+        });
+    if let Err(e) = spawned {
+        eprintln!("Failed to start the scheduler lease thread: {}", e);
+    }
+}
+
+/// Release the lease on shutdown, if this instance holds it.
+pub fn resign() {
+    if let Some(elector) = ELECTOR.get() {
+        elector.lock().unwrap_or_else(|e| e.into_inner()).resign();
+        jobs::set_scheduler_leader(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A lease in memory, shared by the electors under test; `down` makes
+    /// every call fail, like an unreachable database.
+    #[derive(Default)]
+    struct Lease {
+        held: Option<(String, i64)>,
+        down: bool,
+    }
+
+    #[derive(Clone, Default)]
+    struct MemoryStore(Arc<Mutex<Lease>>);
+
+    impl LeaseStore for MemoryStore {
+        fn acquire(&mut self, holder: &str, now: i64, expires_at: i64) -> Result<bool, String> {
+            let mut state = self.0.lock().unwrap();
+            if state.down {
+                return Err("down".to_string());
+            }
+            match &state.held {
+                Some((current, until)) if current != holder && *until >= now => Ok(false),
+                _ => {
+                    state.held = Some((holder.to_string(), expires_at));
+                    Ok(true)
+                }
+            }
+        }
+
+        fn release(&mut self, holder: &str) -> Result<(), String> {
+            let mut state = self.0.lock().unwrap();
+            if state
+                .held
+                .as_ref()
+                .is_some_and(|(current, _)| current == holder)
+            {
+                state.held = None;
+            }
+            Ok(())
+        }
+    }
+
+    fn elector(store: &MemoryStore, name: &str) -> Elector<MemoryStore> {
+        Elector::new(store.clone(), name.to_string(), Duration::from_secs(30))
+    }
+
+    #[test]
+    fn one_instance_leads_and_another_takes_over_when_it_stops_renewing() {
+        let store = MemoryStore::default();
+        let mut a = elector(&store, "a");
+        let mut b = elector(&store, "b");
+
+        assert_eq!(a.tick(0), Some(true));
+        assert_eq!(b.tick(0), None);
+        assert_eq!(a.tick(10_000), None);
+        assert!(a.is_leader() && !b.is_leader());
+
+        // `a` dies after its last renewal at 10s; the lease runs to 40s.
+        assert_eq!(b.tick(39_000), None);
+        assert_eq!(b.tick(40_001), Some(true));
+        assert_eq!(a.tick(41_000), Some(false));
+    }
+
+    #[test]
+    fn a_leader_that_cannot_renew_steps_down_before_its_lease_expires() {
+        let store = MemoryStore::default();
+        let mut a = elector(&store, "a");
+        assert_eq!(a.tick(0), Some(true));
+
+        store.0.lock().unwrap().down = true;
+        assert_eq!(a.tick(10_000), None);
+        // Granted until 30s; it steps down at 20s, with a third to spare.
+        assert_eq!(a.tick(20_000), Some(false));
+    }
+
+    #[test]
+    fn resigning_frees_the_lease_at_once() {
+        let store = MemoryStore::default();
+        let mut a = elector(&store, "a");
+        let mut b = elector(&store, "b");
+        a.tick(0);
+        a.resign();
+        assert!(!a.is_leader());
+        assert_eq!(b.tick(1_000), Some(true));
+    }
+}
@@ -11,6 +11,11 @@ pub const DEFAULT_WORKER_COUNT: usize = 4;
 /// bump `SOLI_JOB_WORKERS` for higher background throughput.
 pub const DEFAULT_JOB_WORKERS: usize = 1;
 
+/// Default number of `internal_request()` worker threads (overridable via
+/// `SOLI_INTERNAL_WORKERS`). Each is a full interpreter copy, started on the
+/// first internal request.
+pub const DEFAULT_INTERNAL_WORKERS: usize = 2;
+
 /// Capacity per worker for request queue (bounded channels for backpressure)
 pub const CAPACITY_PER_WORKER: usize = 64;
 
@@ -2,6 +2,8 @@
 //!
 //! This module handles Tailwind CSS compilation for development.
 //! Uses the standalone Tailwind CSS CLI binary, downloading it automatically if needed.
+//! When no CLI can be found or downloaded (or `SOLI_TAILWIND=builtin` is set),
+//! it falls back to the pure-Rust subset in `tailwind_builtin`.
 
 use std::path::{Path, PathBuf};
 
@@ -387,13 +389,21 @@ pub(crate) fn compile_tailwind_css_once(folder: &Path) -> bool {
     let output_dir = folder.join("public/css");
     let _ = std::fs::create_dir_all(&output_dir);
 
+    if builtin_forced() {
+        println!("Compiling Tailwind CSS (built-in generator)...");
+        return compile_with_builtin(folder, &css_files, &output_dir);
+    }
+
     println!("Compiling Tailwind CSS (v{} toolchain)...", major);
 
     let tailwind_bin = match find_tailwind_binary(folder, major) {
         Some(bin) => bin,
         None => {
-            eprintln!("   ✗ Tailwind CSS CLI not found. Run 'npm install' in your project or check your internet connection.");
-            return false;
+            println!(
+                "   Tailwind CSS CLI not available; using the built-in generator \
+                 (a subset of Tailwind — run 'npm install' for the full compiler)."
+            );
+            return compile_with_builtin(folder, &css_files, &output_dir);
         }
     };
 
@@ -430,6 +440,45 @@ pub(crate) fn compile_tailwind_css_once(folder: &Path) -> bool {
     all_ok
 }
 
+/// `SOLI_TAILWIND=builtin` skips the CLI lookup (and its download) entirely.
+fn builtin_forced() -> bool {
+    std::env::var("SOLI_TAILWIND")
+        .map(|v| v.eq_ignore_ascii_case("builtin"))
+        .unwrap_or(false)
+}
+
+/// Compile every entry CSS file with the pure-Rust fallback.
+fn compile_with_builtin(folder: &Path, css_files: &[std::fs::DirEntry], output_dir: &Path) -> bool {
+    let mut all_ok = true;
+    for entry in css_files {
+        let input = entry.path();
+        let filename = input.file_name().unwrap();
+        let source = match std::fs::read_to_string(&input) {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("   ✗ {} failed: {}", filename.to_string_lossy(), e);
+                all_ok = false;
+                continue;
+            }
+        };
+        let compiled = super::tailwind_builtin::compile(folder, &source);
+        if let Err(e) = std::fs::write(output_dir.join(filename), &compiled.css) {
+            eprintln!("   ✗ {} failed: {}", filename.to_string_lossy(), e);
+            all_ok = false;
+            continue;
+        }
+        println!("   ✓ {} (built-in)", filename.to_string_lossy());
+        if !compiled.unknown_apply.is_empty() {
+            eprintln!(
+                "   ⚠ {}: @apply skipped unsupported classes: {}",
+                filename.to_string_lossy(),
+                compiled.unknown_apply.join(", ")
+            );
+        }
+    }
+    all_ok
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
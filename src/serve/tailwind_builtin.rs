@@ -0,0 +1,1783 @@
+//! Built-in Tailwind CSS fallback
+//!
+//! When neither `node_modules/.bin/tailwindcss` nor the pinned standalone CLI
+//! is available (offline machine, unsupported platform, no `curl`),
+//! `tailwind::compile_tailwind_css_once` falls back to this module so
+//! `soli serve --dev` still produces a usable stylesheet.
+//!
+//! It is a deliberately small, dependency-free subset of Tailwind:
+//! - class candidates are extracted from every text file under `app/`;
+//! - the common utilities (layout, flexbox/grid, spacing, sizing, typography,
+//!   colors, borders, shadows, transitions, …) are generated on the fly,
+//!   with responsive (`md:`), state (`hover:`), `dark:`, `group-hover:`,
+//!   negative (`-mt-2`), important (`!p-0`), opacity (`bg-black/50`) and
+//!   arbitrary-value (`w-[320px]`) forms;
+//! - the entry CSS is rewritten in place: `@tailwind` / `@import "tailwindcss"`
+//!   become a small preflight plus the generated utilities, `@theme` becomes
+//!   `:root` custom properties (and `--color-*` tokens become color
+//!   utilities), `@utility` becomes a class, and `@apply` is expanded using
+//!   native CSS nesting for variants.
+//!
+//! Anything outside that subset is skipped silently for classes found in
+//! markup, and reported for `@apply` so a missing style is never a mystery.
+
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+
+/// Result of running the built-in pipeline over one entry CSS file.
+pub(crate) struct BuiltinOutput {
+    pub css: String,
+    /// `@apply` classes the generator does not know, in source order.
+    pub unknown_apply: Vec<String>,
+}
+
+/// File extensions scanned for class candidates.
+const SOURCE_EXTENSIONS: &[&str] = &[
+    "slv", "html", "sl", "js", "ts", "jsx", "tsx", "md", "erb", "vue", "svelte",
+];
+
+/// Files larger than this are skipped (minified vendor bundles, fixtures).
+const MAX_SOURCE_BYTES: u64 = 2 * 1024 * 1024;
+
+const BREAKPOINTS: &[(&str, &str)] = &[
+    ("sm", "640px"),
+    ("md", "768px"),
+    ("lg", "1024px"),
+    ("xl", "1280px"),
+    ("2xl", "1536px"),
+];
+
+const PALETTE: &[(&str, [&str; 11])] = &[
+    (
+        "slate",
+        [
+            "#f8fafc", "#f1f5f9", "#e2e8f0", "#cbd5e1", "#94a3b8", "#64748b", "#475569", "#334155",
+            "#1e293b", "#0f172a", "#020617",
+        ],
+    ),
+    (
+        "gray",
+        [
+            "#f9fafb", "#f3f4f6", "#e5e7eb", "#d1d5db", "#9ca3af", "#6b7280", "#4b5563", "#374151",
+            "#1f2937", "#111827", "#030712",
+        ],
+    ),
+    (
+        "zinc",
+        [
+            "#fafafa", "#f4f4f5", "#e4e4e7", "#d4d4d8", "#a1a1aa", "#71717a", "#52525b", "#3f3f46",
+            "#27272a", "#18181b", "#09090b",
+        ],
+    ),
+    (
+        "red",
+        [
+            "#fef2f2", "#fee2e2", "#fecaca", "#fca5a5", "#f87171", "#ef4444", "#dc2626", "#b91c1c",
+            "#991b1b", "#7f1d1d", "#450a0a",
+        ],
+    ),
+    (
+        "orange",
+        [
+            "#fff7ed", "#ffedd5", "#fed7aa", "#fdba74", "#fb923c", "#f97316", "#ea580c", "#c2410c",
+            "#9a3412", "#7c2d12", "#431407",
+        ],
+    ),
+    (
+        "amber",
+        [
+            "#fffbeb", "#fef3c7", "#fde68a", "#fcd34d", "#fbbf24", "#f59e0b", "#d97706", "#b45309",
+            "#92400e", "#78350f", "#451a03",
+        ],
+    ),
+    (
+        "yellow",
+        [
+            "#fefce8", "#fef9c3", "#fef08a", "#fde047", "#facc15", "#eab308", "#ca8a04", "#a16207",
+            "#854d0e", "#713f12", "#422006",
+        ],
+    ),
+    (
+        "green",
+        [
+            "#f0fdf4", "#dcfce7", "#bbf7d0", "#86efac", "#4ade80", "#22c55e", "#16a34a", "#15803d",
+            "#166534", "#14532d", "#052e16",
+        ],
+    ),
+    (
+        "emerald",
+        [
+            "#ecfdf5", "#d1fae5", "#a7f3d0", "#6ee7b7", "#34d399", "#10b981", "#059669", "#047857",
+            "#065f46", "#064e3b", "#022c22",
+        ],
+    ),
+    (
+        "teal",
+        [
+            "#f0fdfa", "#ccfbf1", "#99f6e4", "#5eead4", "#2dd4bf", "#14b8a6", "#0d9488", "#0f766e",
+            "#115e59", "#134e4a", "#042f2e",
+        ],
+    ),
+    (
+        "sky",
+        [
+            "#f0f9ff", "#e0f2fe", "#bae6fd", "#7dd3fc", "#38bdf8", "#0ea5e9", "#0284c7", "#0369a1",
+            "#075985", "#0c4a6e", "#082f49",
+        ],
+    ),
+    (
+        "blue",
+        [
+            "#eff6ff", "#dbeafe", "#bfdbfe", "#93c5fd", "#60a5fa", "#3b82f6", "#2563eb", "#1d4ed8",
+            "#1e40af", "#1e3a8a", "#172554",
+        ],
+    ),
+    (
+        "indigo",
+        [
+            "#eef2ff", "#e0e7ff", "#c7d2fe", "#a5b4fc", "#818cf8", "#6366f1", "#4f46e5", "#4338ca",
+            "#3730a3", "#312e81", "#1e1b4b",
+        ],
+    ),
+    (
+        "purple",
+        [
+            "#faf5ff", "#f3e8ff", "#e9d5ff", "#d8b4fe", "#c084fc", "#a855f7", "#9333ea", "#7e22ce",
+            "#6b21a8", "#581c87", "#3b0764",
+        ],
+    ),
+    (
+        "pink",
+        [
+            "#fdf2f8", "#fce7f3", "#fbcfe8", "#f9a8d4", "#f472b6", "#ec4899", "#db2777", "#be185d",
+            "#9d174d", "#831843", "#500724",
+        ],
+    ),
+    (
+        "rose",
+        [
+            "#fff1f2", "#ffe4e6", "#fecdd3", "#fda4af", "#fb7185", "#f43f5e", "#e11d48", "#be123c",
+            "#9f1239", "#881337", "#4c0519",
+        ],
+    ),
+];
+
+const SHADES: [&str; 11] = [
+    "50", "100", "200", "300", "400", "500", "600", "700", "800", "900", "950",
+];
+
+/// Trimmed-down preflight: enough reset for utility-first markup to render
+/// the way it does under the real compiler.
+const PREFLIGHT: &str = "\
+*, ::before, ::after { box-sizing: border-box; margin: 0; padding: 0; border: 0 solid; }
+html, :host { line-height: 1.5; -webkit-text-size-adjust: 100%; tab-size: 4; font-family: var(--font-sans, ui-sans-serif, system-ui, sans-serif); }
+hr { height: 0; color: inherit; border-top-width: 1px; }
+h1, h2, h3, h4, h5, h6 { font-size: inherit; font-weight: inherit; }
+a { color: inherit; text-decoration: inherit; }
+b, strong { font-weight: bolder; }
+code, kbd, samp, pre { font-family: var(--font-mono, ui-monospace, SFMono-Regular, Menlo, monospace); font-size: 1em; }
+table { text-indent: 0; border-color: inherit; border-collapse: collapse; }
+ol, ul, menu { list-style: none; }
+img, svg, video, canvas, audio, iframe, embed, object { display: block; vertical-align: middle; }
+img, video { max-width: 100%; height: auto; }
+button, input, select, optgroup, textarea { font: inherit; color: inherit; background-color: transparent; border-radius: 0; }
+button, [role=\"button\"] { cursor: pointer; }
+textarea { resize: vertical; }
+::placeholder { opacity: 1; color: color-mix(in srgb, currentColor 50%, transparent); }
+[hidden]:where(:not([hidden=\"until-found\"])) { display: none !important; }
+";
+
+/// Design tokens declared in the entry CSS's `@theme` blocks.
+#[derive(Default)]
+struct Theme {
+    colors: HashSet<String>,
+    fonts: HashSet<String>,
+}
+
+/// One generated utility, before variants are applied.
+struct Utility {
+    /// Orders conflicting utilities the way Tailwind does: shorthands (`p-4`)
+    /// come before the longhands that override them (`px-2`, `pt-0`).
+    rank: u8,
+    /// Extra selector text after the class (`space-y-4` targets children).
+    suffix: &'static str,
+    body: String,
+    keyframes: Option<&'static str>,
+}
+
+impl Utility {
+    fn new(body: String) -> Self {
+        Utility {
+            rank: 10,
+            suffix: "",
+            body,
+            keyframes: None,
+        }
+    }
+
+    fn ranked(rank: u8, body: String) -> Self {
+        Utility {
+            rank,
+            ..Utility::new(body)
+        }
+    }
+}
+
+/// A class candidate split into its variants and utility name.
+struct Parsed<'a> {
+    media: Vec<String>,
+    media_rank: (usize, bool),
+    pseudo: String,
+    pseudo_element: String,
+    group: String,
+    important: bool,
+    utility: &'a str,
+}
+
+/// Compile one entry CSS file: rewrite Tailwind directives, expand `@apply`
+/// and generate utilities for every candidate found under `folder/app`.
+pub(crate) fn compile(folder: &Path, entry_css: &str) -> BuiltinOutput {
+    compile_with_candidates(entry_css, &collect_candidates(&folder.join("app")))
+}
+
+/// Walk `dir` and collect every whitespace/quote-delimited token that could
+/// be a class name.
+pub(crate) fn collect_candidates(dir: &Path) -> BTreeSet<String> {
+    let mut out = BTreeSet::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                // The entry CSS itself is rewritten, not scanned.
+                if !path.ends_with("assets/css") {
+                    stack.push(path);
+                }
+                continue;
+            }
+            let scannable = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| SOURCE_EXTENSIONS.contains(&e))
+                .unwrap_or(false);
+            let small = entry
+                .metadata()
+                .map(|m| m.len() <= MAX_SOURCE_BYTES)
+                .unwrap_or(false);
+            if !scannable || !small {
+                continue;
+            }
+            if let Ok(text) = std::fs::read_to_string(&path) {
+                extract_candidates(&text, &mut out);
+            }
+        }
+    }
+    out
+}
+
+fn extract_candidates(text: &str, out: &mut BTreeSet<String>) {
+    for token in text.split(|c: char| {
+        c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '<' | '>' | '{' | '}' | '=' | ';')
+    }) {
+        let token = token.trim_end_matches([',', '.', ')']);
+        if !token.is_empty() && token.len() <= 128 && token.is_ascii() {
+            out.insert(token.to_string());
+        }
+    }
+}
+
+/// Same as [`compile`], with the candidate set supplied by the caller.
+pub(crate) fn compile_with_candidates(
+    entry_css: &str,
+    candidates: &BTreeSet<String>,
+) -> BuiltinOutput {
+    let theme = parse_theme(entry_css);
+    let wants_base = entry_css.contains("@tailwind base")
+        || entry_css.contains("@import \"tailwindcss\"")
+        || entry_css.contains("@import 'tailwindcss'");
+    let wants_utilities = entry_css.contains("@tailwind utilities")
+        || entry_css.contains("@import \"tailwindcss\"")
+        || entry_css.contains("@import 'tailwindcss'");
+
+    let mut unknown_apply = Vec::new();
+    let rewritten = rewrite_directives(entry_css);
+    let rewritten = expand_apply(&rewritten, &theme, &mut unknown_apply);
+    let (imports, rest) = split_leading_imports(&rewritten);
+
+    let mut css = String::from("@layer theme, base, components, utilities;\n");
+    css.push_str(imports);
+    if wants_base {
+        css.push_str("@layer base {\n");
+        css.push_str(PREFLIGHT);
+        css.push_str("}\n");
+    }
+    css.push_str(rest.trim_start_matches('\n'));
+    if wants_utilities {
+        let generated = generate_utilities(candidates, &theme);
+        if !generated.is_empty() {
+            if !css.ends_with('\n') {
+                css.push('\n');
+            }
+            css.push_str("@layer utilities {\n");
+            css.push_str(&generated);
+            css.push_str("}\n");
+        }
+    }
+
+    BuiltinOutput { css, unknown_apply }
+}
+
+/// Render every recognised candidate as a rule, sorted into cascade order.
+fn generate_utilities(candidates: &BTreeSet<String>, theme: &Theme) -> String {
+    let mut rules = Vec::new();
+    let mut keyframes = BTreeSet::new();
+    for candidate in candidates {
+        let Some(parsed) = parse_candidate(candidate) else {
+            continue;
+        };
+        let Some(utility) = utility(parsed.utility, theme) else {
+            continue;
+        };
+        if let Some(k) = utility.keyframes {
+            keyframes.insert(k);
+        }
+        let class = format!(
+            ".{}{}{}{}",
+            escape_class(candidate),
+            parsed.pseudo,
+            utility.suffix,
+            parsed.pseudo_element
+        );
+        let selector = if parsed.group.is_empty() {
+            class
+        } else {
+            format!(":where({}) {}", parsed.group, class)
+        };
+        let body = if parsed.important {
+            make_important(&utility.body)
+        } else {
+            utility.body
+        };
+        let mut rule = format!("{} {{ {} }}", selector, body);
+        for query in parsed.media.iter().rev() {
+            rule = format!("@media {} {{ {} }}", query, rule);
+        }
+        let has_state = !parsed.pseudo.is_empty() || !parsed.group.is_empty();
+        rules.push(((parsed.media_rank, has_state, utility.rank), rule));
+    }
+    // Stable sort keeps the alphabetical candidate order within a bucket.
+    rules.sort_by_key(|(key, _)| *key);
+
+    let mut out = String::new();
+    for (_, rule) in rules {
+        out.push_str(&rule);
+        out.push('\n');
+    }
+    for k in keyframes {
+        out.push_str(k);
+        out.push('\n');
+    }
+    out
+}
+
+fn parse_candidate(candidate: &str) -> Option<Parsed<'_>> {
+    let parts = split_top_level(candidate, ':');
+    let (utility, variants) = parts.split_last()?;
+    let mut utility = *utility;
+    let mut important = false;
+    if let Some(rest) = utility.strip_prefix('!') {
+        utility = rest;
+        important = true;
+    } else if let Some(rest) = utility.strip_suffix('!') {
+        utility = rest;
+        important = true;
+    }
+    if utility.is_empty() {
+        return None;
+    }
+
+    let mut parsed = Parsed {
+        media: Vec::new(),
+        media_rank: (0, false),
+        pseudo: String::new(),
+        pseudo_element: String::new(),
+        group: String::new(),
+        important,
+        utility,
+    };
+    for variant in variants {
+        if let Some(i) = BREAKPOINTS.iter().position(|(name, _)| name == variant) {
+            parsed
+                .media
+                .push(format!("(min-width: {})", BREAKPOINTS[i].1));
+            parsed.media_rank.0 = parsed.media_rank.0.max(i + 1);
+        } else if *variant == "dark" {
+            parsed
+                .media
+                .push("(prefers-color-scheme: dark)".to_string());
+            parsed.media_rank.1 = true;
+        } else if *variant == "print" {
+            parsed.media.push("print".to_string());
+        } else if *variant == "motion-reduce" {
+            parsed
+                .media
+                .push("(prefers-reduced-motion: reduce)".to_string());
+        } else if *variant == "motion-safe" {
+            parsed
+                .media
+                .push("(prefers-reduced-motion: no-preference)".to_string());
+        } else if let Some(state) = variant.strip_prefix("group-") {
+            parsed.group = format!(".group{}", pseudo_class(state)?);
+        } else if *variant == "placeholder" {
+            parsed.pseudo_element = "::placeholder".to_string();
+        } else if *variant == "selection" {
+            parsed.pseudo_element = "::selection".to_string();
+        } else {
+            parsed.pseudo.push_str(pseudo_class(variant)?);
+        }
+    }
+    Some(parsed)
+}
+
+fn pseudo_class(variant: &str) -> Option<&'static str> {
+    Some(match variant {
+        "hover" => ":hover",
+        "focus" => ":focus",
+        "focus-visible" => ":focus-visible",
+        "focus-within" => ":focus-within",
+        "active" => ":active",
+        "visited" => ":visited",
+        "disabled" => ":disabled",
+        "enabled" => ":enabled",
+        "checked" => ":checked",
+        "required" => ":required",
+        "invalid" => ":invalid",
+        "first" => ":first-child",
+        "last" => ":last-child",
+        "only" => ":only-child",
+        "odd" => ":nth-child(odd)",
+        "even" => ":nth-child(even)",
+        "empty" => ":empty",
+        "open" => ":is([open], :popover-open)",
+        _ => return None,
+    })
+}
+
+/// Split on `sep`, ignoring separators inside `[...]` arbitrary values.
+fn split_top_level(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            c if c == sep && depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+/// CSS-escape a class name for use in a selector.
+fn escape_class(class: &str) -> String {
+    let mut out = String::with_capacity(class.len() + 8);
+    for (i, c) in class.chars().enumerate() {
+        if i == 0 && c.is_ascii_digit() {
+            out.push_str(&format!("\\3{} ", c));
+        } else if c.is_ascii_alphanumeric() || c == '-' || c == '_' || !c.is_ascii() {
+            out.push(c);
+        } else {
+            out.push('\\');
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn make_important(body: &str) -> String {
+    body.replace(';', " !important;")
+}
+
+fn decl(prop: &str, value: &str) -> String {
+    format!("{}: {};", prop, value)
+}
+
+fn decls(props: &[&str], value: &str) -> String {
+    props
+        .iter()
+        .map(|p| decl(p, value))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `[value]` → `value`, with `_` standing in for spaces as in Tailwind.
+fn arbitrary(v: &str) -> Option<String> {
+    let inner = v.strip_prefix('[')?.strip_suffix(']')?;
+    if inner.is_empty() {
+        return None;
+    }
+    Some(inner.replace('_', " "))
+}
+
+/// Format a float without a trailing `.0`.
+fn num(f: f64) -> String {
+    format!("{}", f)
+}
+
+/// Tailwind's numeric scale: `4` → `1rem` (multiples of 0.25rem).
+fn spacing(v: &str) -> Option<String> {
+    if let Some(a) = arbitrary(v) {
+        return Some(a);
+    }
+    match v {
+        "0" => return Some("0px".to_string()),
+        "px" => return Some("1px".to_string()),
+        _ => {}
+    }
+    if !v.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    let n: f64 = v.parse().ok()?;
+    if (n * 4.0).fract() != 0.0 {
+        return None;
+    }
+    Some(format!("{}rem", num(n * 0.25)))
+}
+
+/// Spacing plus the sizing keywords and fractions shared by `w-`/`h-`/…
+fn size(v: &str, axis: char) -> Option<String> {
+    let keyword = match v {
+        "auto" => "auto",
+        "full" => "100%",
+        "screen" => {
+            if axis == 'w' {
+                "100vw"
+            } else {
+                "100vh"
+            }
+        }
+        "dvh" => "100dvh",
+        "svh" => "100svh",
+        "lvh" => "100lvh",
+        "dvw" => "100dvw",
+        "min" => "min-content",
+        "max" => "max-content",
+        "fit" => "fit-content",
+        _ => "",
+    };
+    if !keyword.is_empty() {
+        return Some(keyword.to_string());
+    }
+    if let Some((n, d)) = v.split_once('/') {
+        let n: f64 = n.parse().ok()?;
+        let d: f64 = d.parse().ok()?;
+        if d == 0.0 {
+            return None;
+        }
+        return Some(format!("{}%", num((n / d * 100.0 * 1e6).round() / 1e6)));
+    }
+    spacing(v)
+}
+
+fn negate(value: String, neg: bool) -> String {
+    if neg {
+        format!("calc({} * -1)", value)
+    } else {
+        value
+    }
+}
+
+fn palette(name: &str) -> Option<&'static str> {
+    let (family, shade) = name.rsplit_once('-')?;
+    let (_, shades) = PALETTE.iter().find(|(f, _)| *f == family)?;
+    let idx = SHADES.iter().position(|s| *s == shade)?;
+    Some(shades[idx])
+}
+
+/// Resolve a color token (`red-500`, `white`, `brand` from `@theme`,
+/// `[#0af]`), with an optional `/NN` opacity modifier.
+fn color(v: &str, theme: &Theme) -> Option<String> {
+    let parts = split_top_level(v, '/');
+    let (base, alpha) = match parts.as_slice() {
+        [base] => (*base, None),
+        [base, alpha] => (*base, Some(*alpha)),
+        _ => return None,
+    };
+    let resolved = match base {
+        "inherit" => "inherit".to_string(),
+        "current" => "currentColor".to_string(),
+        "transparent" => "transparent".to_string(),
+        "black" => "#000".to_string(),
+        "white" => "#fff".to_string(),
+        _ if theme.colors.contains(base) => format!("var(--color-{})", base),
+        _ => {
+            if let Some(a) = arbitrary(base) {
+                if !looks_like_color(&a) {
+                    return None;
+                }
+                a
+            } else {
+                palette(base)?.to_string()
+            }
+        }
+    };
+    match alpha {
+        None => Some(resolved),
+        Some(a) => {
+            let pct = if let Some(arb) = arbitrary(a) {
+                arb
+            } else {
+                let n: u32 = a.parse().ok().filter(|n| *n <= 100)?;
+                format!("{}%", n)
+            };
+            Some(format!(
+                "color-mix(in srgb, {} {}, transparent)",
+                resolved, pct
+            ))
+        }
+    }
+}
+
+fn looks_like_color(v: &str) -> bool {
+    v.starts_with('#')
+        || v.starts_with("rgb")
+        || v.starts_with("hsl")
+        || v.starts_with("oklch")
+        || v.starts_with("oklab")
+        || v.starts_with("color(")
+        || v.starts_with("var(--color")
+}
+
+fn font_size(v: &str) -> Option<(&'static str, &'static str)> {
+    Some(match v {
+        "xs" => ("0.75rem", "1rem"),
+        "sm" => ("0.875rem", "1.25rem"),
+        "base" => ("1rem", "1.5rem"),
+        "lg" => ("1.125rem", "1.75rem"),
+        "xl" => ("1.25rem", "1.75rem"),
+        "2xl" => ("1.5rem", "2rem"),
+        "3xl" => ("1.875rem", "2.25rem"),
+        "4xl" => ("2.25rem", "2.5rem"),
+        "5xl" => ("3rem", "1"),
+        "6xl" => ("3.75rem", "1"),
+        "7xl" => ("4.5rem", "1"),
+        "8xl" => ("6rem", "1"),
+        "9xl" => ("8rem", "1"),
+        _ => return None,
+    })
+}
+
+fn radius(v: &str) -> Option<String> {
+    if let Some(a) = arbitrary(v) {
+        return Some(a);
+    }
+    Some(
+        match v {
+            "" => "0.25rem",
+            "none" => "0",
+            "sm" => "0.125rem",
+            "md" => "0.375rem",
+            "lg" => "0.5rem",
+            "xl" => "0.75rem",
+            "2xl" => "1rem",
+            "3xl" => "1.5rem",
+            "full" => "9999px",
+            _ => return None,
+        }
+        .to_string(),
+    )
+}
+
+fn shadow(v: &str) -> Option<&'static str> {
+    Some(match v {
+        "" => "0 1px 3px 0 rgb(0 0 0 / 0.1), 0 1px 2px -1px rgb(0 0 0 / 0.1)",
+        "sm" => "0 1px 2px 0 rgb(0 0 0 / 0.05)",
+        "md" => "0 4px 6px -1px rgb(0 0 0 / 0.1), 0 2px 4px -2px rgb(0 0 0 / 0.1)",
+        "lg" => "0 10px 15px -3px rgb(0 0 0 / 0.1), 0 4px 6px -4px rgb(0 0 0 / 0.1)",
+        "xl" => "0 20px 25px -5px rgb(0 0 0 / 0.1), 0 8px 10px -6px rgb(0 0 0 / 0.1)",
+        "2xl" => "0 25px 50px -12px rgb(0 0 0 / 0.25)",
+        "inner" => "inset 0 2px 4px 0 rgb(0 0 0 / 0.05)",
+        "none" => "0 0 #0000",
+        _ => return None,
+    })
+}
+
+fn max_width(v: &str) -> Option<String> {
+    let fixed = match v {
+        "none" => "none",
+        "xs" => "20rem",
+        "sm" => "24rem",
+        "md" => "28rem",
+        "lg" => "32rem",
+        "xl" => "36rem",
+        "2xl" => "42rem",
+        "3xl" => "48rem",
+        "4xl" => "56rem",
+        "5xl" => "64rem",
+        "6xl" => "72rem",
+        "7xl" => "80rem",
+        "prose" => "65ch",
+        _ => "",
+    };
+    if !fixed.is_empty() {
+        return Some(fixed.to_string());
+    }
+    if let Some(bp) = v.strip_prefix("screen-") {
+        return BREAKPOINTS
+            .iter()
+            .find(|(name, _)| *name == bp)
+            .map(|(_, w)| w.to_string());
+    }
+    size(v, 'w')
+}
+
+/// Declarations (and rank) for a padding/margin family: `p` sets
+/// `padding`, `px` the left/right pair, `pt` just the top, and so on.
+fn sides(axis: &str, base: &str, value: &str) -> Option<Utility> {
+    let (rank, suffixes): (u8, &[&str]) = match axis {
+        "" => (10, &[""]),
+        "x" => (11, &["-left", "-right"]),
+        "y" => (11, &["-top", "-bottom"]),
+        "s" => (12, &["-inline-start"]),
+        "e" => (12, &["-inline-end"]),
+        "t" => (12, &["-top"]),
+        "r" => (12, &["-right"]),
+        "b" => (12, &["-bottom"]),
+        "l" => (12, &["-left"]),
+        _ => return None,
+    };
+    let body = suffixes
+        .iter()
+        .map(|s| decl(&format!("{}{}", base, s), value))
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(Utility::ranked(rank, body))
+}
+
+fn utility(name: &str, theme: &Theme) -> Option<Utility> {
+    let (neg, name) = match name.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, name),
+    };
+    if neg {
+        return negatable(name, true);
+    }
+    if let Some(u) = static_utility(name) {
+        return Some(u);
+    }
+    if let Some(u) = negatable(name, false) {
+        return Some(u);
+    }
+
+    let (family, value) = name.split_once('-').unwrap_or((name, ""));
+    let u = |body: String| Some(Utility::new(body));
+    match family {
+        "p" | "px" | "py" | "ps" | "pe" | "pt" | "pr" | "pb" | "pl" => {
+            sides(&family[1..], "padding", &spacing(value)?)
+        }
+        "w" => u(decl("width", &size(value, 'w')?)),
+        "h" => u(decl("height", &size(value, 'h')?)),
+        "size" => u(decls(&["width", "height"], &size(value, 'w')?)),
+        "min" | "max" => {
+            let (axis, v) = value.split_once('-')?;
+            let prop = match (family, axis) {
+                ("min", "w") => "min-width",
+                ("min", "h") => "min-height",
+                ("max", "w") => "max-width",
+                ("max", "h") => "max-height",
+                _ => return None,
+            };
+            let v = if prop == "max-width" {
+                max_width(v)?
+            } else if v == "screen" {
+                if axis == "w" { "100vw" } else { "100vh" }.to_string()
+            } else if v == "none" {
+                "none".to_string()
+            } else {
+                size(v, axis.chars().next()?)?
+            };
+            u(decl(prop, &v))
+        }
+        "gap" => match value.split_once('-') {
+            Some(("x", v)) => Some(Utility::ranked(11, decl("column-gap", &spacing(v)?))),
+            Some(("y", v)) => Some(Utility::ranked(11, decl("row-gap", &spacing(v)?))),
+            _ => u(decl("gap", &spacing(value)?)),
+        },
+        "space" => {
+            let (axis, v) = value.split_once('-')?;
+            let prop = match axis {
+                "x" => "margin-inline-start",
+                "y" => "margin-top",
+                _ => return None,
+            };
+            Some(Utility {
+                suffix: " > :not(:first-child)",
+                ..Utility::new(decl(prop, &spacing(v)?))
+            })
+        }
+        "divide" => {
+            let (prop, v) = match value.split_once('-') {
+                Some(("x", v)) => ("border-left-width", v),
+                Some(("y", v)) => ("border-top-width", v),
+                None if value == "x" => ("border-left-width", "1"),
+                None if value == "y" => ("border-top-width", "1"),
+                _ => {
+                    return Some(Utility {
+                        suffix: " > :not(:first-child)",
+                        ..Utility::new(decl("border-color", &color(value, theme)?))
+                    })
+                }
+            };
+            let width: u32 = v.parse().ok()?;
+            Some(Utility {
+                suffix: " > :not(:first-child)",
+                ..Utility::new(decl(prop, &format!("{}px", width)))
+            })
+        }
+        "text" => {
+            if let Some((size, line)) = font_size(value) {
+                return u(format!(
+                    "{} {}",
+                    decl("font-size", size),
+                    decl("line-height", line)
+                ));
+            }
+            if let Some(a) = arbitrary(value) {
+                if !looks_like_color(&a) {
+                    return u(decl("font-size", &a));
+                }
+            }
+            u(decl("color", &color(value, theme)?))
+        }
+        "bg" => {
+            if let Some(dir) = value
+                .strip_prefix("gradient-to-")
+                .or_else(|| value.strip_prefix("linear-to-"))
+            {
+                let to = match dir {
+                    "t" => "top",
+                    "tr" => "top right",
+                    "r" => "right",
+                    "br" => "bottom right",
+                    "b" => "bottom",
+                    "bl" => "bottom left",
+                    "l" => "left",
+                    "tl" => "top left",
+                    _ => return None,
+                };
+                return u(decl(
+                    "background-image",
+                    &format!("linear-gradient(to {}, var(--tw-gradient-stops))", to),
+                ));
+            }
+            match value {
+                "cover" | "contain" => u(decl("background-size", value)),
+                "center" => u(decl("background-position", "center")),
+                "no-repeat" => u(decl("background-repeat", "no-repeat")),
+                "fixed" => u(decl("background-attachment", "fixed")),
+                "none" => u(decl("background-image", "none")),
+                _ => u(decl("background-color", &color(value, theme)?)),
+            }
+        }
+        "from" => u(format!(
+            "{} {}",
+            decl("--tw-gradient-from", &color(value, theme)?),
+            decl(
+                "--tw-gradient-stops",
+                "var(--tw-gradient-from), var(--tw-gradient-via, var(--tw-gradient-from)), var(--tw-gradient-to, transparent)"
+            )
+        )),
+        "via" => u(decl("--tw-gradient-via", &color(value, theme)?)),
+        "to" => u(decl("--tw-gradient-to", &color(value, theme)?)),
+        "fill" => u(decl("fill", &color(value, theme)?)),
+        "stroke" => u(decl("stroke", &color(value, theme)?)),
+        "accent" => u(decl("accent-color", &color(value, theme)?)),
+        "caret" => u(decl("caret-color", &color(value, theme)?)),
+        "decoration" => u(decl("text-decoration-color", &color(value, theme)?)),
+        "outline" => match value.parse::<u32>() {
+            Ok(w) => u(format!(
+                "{} {}",
+                decl("outline-style", "solid"),
+                decl("outline-width", &format!("{}px", w))
+            )),
+            Err(_) => u(decl("outline-color", &color(value, theme)?)),
+        },
+        "border" => border(value, theme),
+        "rounded" => rounded(value),
+        "shadow" => {
+            if let Some(s) = shadow(value) {
+                return u(format!(
+                    "{} {}",
+                    decl("--tw-shadow", s),
+                    decl(
+                        "box-shadow",
+                        "var(--tw-ring-shadow, 0 0 #0000), var(--tw-shadow)"
+                    )
+                ));
+            }
+            u(decl("--tw-shadow-color", &color(value, theme)?))
+        }
+        "ring" => {
+            let width = if value.is_empty() {
+                Some(1)
+            } else {
+                value.parse::<u32>().ok()
+            };
+            match width {
+                Some(w) => u(format!(
+                    "{} {}",
+                    decl(
+                        "--tw-ring-shadow",
+                        &format!("0 0 0 {}px var(--tw-ring-color, currentColor)", w)
+                    ),
+                    decl(
+                        "box-shadow",
+                        "var(--tw-ring-shadow), var(--tw-shadow, 0 0 #0000)"
+                    )
+                )),
+                None => u(decl("--tw-ring-color", &color(value, theme)?)),
+            }
+        }
+        "font" => {
+            let weight = match value {
+                "thin" => "100",
+                "extralight" => "200",
+                "light" => "300",
+                "normal" => "400",
+                "medium" => "500",
+                "semibold" => "600",
+                "bold" => "700",
+                "extrabold" => "800",
+                "black" => "900",
+                _ => "",
+            };
+            if !weight.is_empty() {
+                return u(decl("font-weight", weight));
+            }
+            let fallback = match value {
+                "sans" => "ui-sans-serif, system-ui, sans-serif",
+                "serif" => "ui-serif, Georgia, serif",
+                "mono" => "ui-monospace, SFMono-Regular, Menlo, monospace",
+                _ if theme.fonts.contains(value) => "inherit",
+                _ => return None,
+            };
+            u(decl(
+                "font-family",
+                &format!("var(--font-{}, {})", value, fallback),
+            ))
+        }
+        "leading" => {
+            let v = match value {
+                "none" => "1".to_string(),
+                "tight" => "1.25".to_string(),
+                "snug" => "1.375".to_string(),
+                "normal" => "1.5".to_string(),
+                "relaxed" => "1.625".to_string(),
+                "loose" => "2".to_string(),
+                _ => spacing(value)?,
+            };
+            Some(Utility::ranked(11, decl("line-height", &v)))
+        }
+        "tracking" => {
+            let v = match value {
+                "tighter" => "-0.05em".to_string(),
+                "tight" => "-0.025em".to_string(),
+                "normal" => "0em".to_string(),
+                "wide" => "0.025em".to_string(),
+                "wider" => "0.05em".to_string(),
+                "widest" => "0.1em".to_string(),
+                _ => arbitrary(value)?,
+            };
+            u(decl("letter-spacing", &v))
+        }
+        "opacity" => {
+            let n: u32 = value.parse().ok().filter(|n| *n <= 100)?;
+            u(decl("opacity", &format!("{}%", n)))
+        }
+        "z" => {
+            if value == "auto" {
+                return u(decl("z-index", "auto"));
+            }
+            let n: u32 = value.parse().ok()?;
+            u(decl("z-index", &n.to_string()))
+        }
+        "order" => {
+            let v = match value {
+                "first" => "-9999".to_string(),
+                "last" => "9999".to_string(),
+                "none" => "0".to_string(),
+                _ => value.parse::<u32>().ok()?.to_string(),
+            };
+            u(decl("order", &v))
+        }
+        "duration" => {
+            let n: u32 = value.parse().ok()?;
+            Some(Utility::ranked(
+                11,
+                decl("transition-duration", &format!("{}ms", n)),
+            ))
+        }
+        "delay" => {
+            let n: u32 = value.parse().ok()?;
+            u(decl("transition-delay", &format!("{}ms", n)))
+        }
+        "grid" => {
+            let (axis, v) = value.split_once('-')?;
+            let prop = match axis {
+                "cols" => "grid-template-columns",
+                "rows" => "grid-template-rows",
+                _ => return None,
+            };
+            let tracks = if v == "none" {
+                "none".to_string()
+            } else if let Some(a) = arbitrary(v) {
+                a
+            } else {
+                let n: u32 = v.parse().ok().filter(|n| *n > 0)?;
+                format!("repeat({}, minmax(0, 1fr))", n)
+            };
+            u(decl(prop, &tracks))
+        }
+        "col" | "row" => {
+            let prop = if family == "col" { "grid-column" } else { "grid-row" };
+            if value == "auto" {
+                return u(decl(prop, "auto"));
+            }
+            let v = value.strip_prefix("span-")?;
+            if v == "full" {
+                return u(decl(prop, "1 / -1"));
+            }
+            let n: u32 = v.parse().ok().filter(|n| *n > 0)?;
+            u(decl(prop, &format!("span {} / span {}", n, n)))
+        }
+        "basis" => u(decl("flex-basis", &size(value, 'w')?)),
+        "aspect" => {
+            let v = match value {
+                "auto" => "auto".to_string(),
+                "square" => "1 / 1".to_string(),
+                "video" => "16 / 9".to_string(),
+                _ => arbitrary(value)?,
+            };
+            u(decl("aspect-ratio", &v))
+        }
+        "line" => {
+            let n: u32 = value.strip_prefix("clamp-")?.parse().ok()?;
+            u(format!(
+                "{} {} {} {}",
+                decl("overflow", "hidden"),
+                decl("display", "-webkit-box"),
+                decl("-webkit-box-orient", "vertical"),
+                decl("-webkit-line-clamp", &n.to_string())
+            ))
+        }
+        "columns" => {
+            let n: u32 = value.parse().ok()?;
+            u(decl("columns", &n.to_string()))
+        }
+        _ => None,
+    }
+}
+
+/// Utilities that accept a leading `-` (margins, insets, translations).
+fn negatable(name: &str, neg: bool) -> Option<Utility> {
+    let (family, value) = name.split_once('-')?;
+    match family {
+        "m" | "mx" | "my" | "ms" | "me" | "mt" | "mr" | "mb" | "ml" => {
+            let v = if value == "auto" && !neg {
+                "auto".to_string()
+            } else {
+                negate(spacing(value)?, neg)
+            };
+            sides(&family[1..], "margin", &v)
+        }
+        "inset" => {
+            let (rank, props, v): (u8, &[&str], &str) = match value.split_once('-') {
+                Some(("x", v)) => (11, &["left", "right"], v),
+                Some(("y", v)) => (11, &["top", "bottom"], v),
+                _ => (10, &["inset"], value),
+            };
+            Some(Utility::ranked(
+                rank,
+                decls(props, &negate(size(v, 'w')?, neg)),
+            ))
+        }
+        "top" | "right" | "bottom" | "left" => Some(Utility::ranked(
+            12,
+            decl(family, &negate(size(value, 'w')?, neg)),
+        )),
+        "start" | "end" => Some(Utility::ranked(
+            12,
+            decl(
+                &format!("inset-inline-{}", family),
+                &negate(size(value, 'w')?, neg),
+            ),
+        )),
+        "translate" => {
+            let (axis, v) = value.split_once('-')?;
+            let var = match axis {
+                "x" => "--tw-translate-x",
+                "y" => "--tw-translate-y",
+                _ => return None,
+            };
+            Some(Utility::new(format!(
+                "{} {}",
+                decl(var, &negate(size(v, 'w')?, neg)),
+                decl(
+                    "translate",
+                    "var(--tw-translate-x, 0) var(--tw-translate-y, 0)"
+                )
+            )))
+        }
+        "rotate" => {
+            let n: u32 = value.parse().ok()?;
+            Some(Utility::new(decl(
+                "rotate",
+                &negate(format!("{}deg", n), neg),
+            )))
+        }
+        "scale" if !neg => {
+            let n: u32 = value.parse().ok()?;
+            Some(Utility::new(decl("scale", &format!("{}%", n))))
+        }
+        _ => None,
+    }
+}
+
+fn border(value: &str, theme: &Theme) -> Option<Utility> {
+    let width = |v: &str| -> Option<String> {
+        if v.is_empty() {
+            return Some("1px".to_string());
+        }
+        if let Some(a) = arbitrary(v) {
+            return Some(a);
+        }
+        let n: u32 = v.parse().ok()?;
+        Some(format!("{}px", n))
+    };
+    match value {
+        "solid" | "dashed" | "dotted" | "double" | "none" | "hidden" => {
+            return Some(Utility::new(decl("border-style", value)))
+        }
+        "collapse" | "separate" => return Some(Utility::new(decl("border-collapse", value))),
+        _ => {}
+    }
+    let (side, rest) = match value.split_once('-') {
+        Some((s, r)) if matches!(s, "x" | "y" | "t" | "r" | "b" | "l" | "s" | "e") => (s, r),
+        _ if matches!(value, "x" | "y" | "t" | "r" | "b" | "l" | "s" | "e") => (value, ""),
+        _ => ("", value),
+    };
+    if let Some(w) = width(rest) {
+        let props: &[&str] = match side {
+            "" => &["border-width"],
+            "x" => &["border-left-width", "border-right-width"],
+            "y" => &["border-top-width", "border-bottom-width"],
+            "t" => &["border-top-width"],
+            "r" => &["border-right-width"],
+            "b" => &["border-bottom-width"],
+            "l" => &["border-left-width"],
+            "s" => &["border-inline-start-width"],
+            "e" => &["border-inline-end-width"],
+            _ => return None,
+        };
+        let rank = if side.is_empty() { 10 } else { 11 };
+        return Some(Utility::ranked(rank, decls(props, &w)));
+    }
+    let c = color(rest, theme)?;
+    let props: &[&str] = match side {
+        "" => &["border-color"],
+        "x" => &["border-left-color", "border-right-color"],
+        "y" => &["border-top-color", "border-bottom-color"],
+        "t" => &["border-top-color"],
+        "r" => &["border-right-color"],
+        "b" => &["border-bottom-color"],
+        "l" => &["border-left-color"],
+        _ => return None,
+    };
+    let rank = if side.is_empty() { 10 } else { 11 };
+    Some(Utility::ranked(rank, decls(props, &c)))
+}
+
+fn rounded(value: &str) -> Option<Utility> {
+    let corners: &[(&str, u8, &[&str])] = &[
+        (
+            "t",
+            11,
+            &["border-top-left-radius", "border-top-right-radius"],
+        ),
+        (
+            "r",
+            11,
+            &["border-top-right-radius", "border-bottom-right-radius"],
+        ),
+        (
+            "b",
+            11,
+            &["border-bottom-right-radius", "border-bottom-left-radius"],
+        ),
+        (
+            "l",
+            11,
+            &["border-top-left-radius", "border-bottom-left-radius"],
+        ),
+        ("tl", 12, &["border-top-left-radius"]),
+        ("tr", 12, &["border-top-right-radius"]),
+        ("br", 12, &["border-bottom-right-radius"]),
+        ("bl", 12, &["border-bottom-left-radius"]),
+    ];
+    let (side, rest) = value.split_once('-').unwrap_or((value, ""));
+    if let Some((_, rank, props)) = corners.iter().find(|(c, _, _)| *c == side) {
+        return Some(Utility::ranked(*rank, decls(props, &radius(rest)?)));
+    }
+    Some(Utility::new(decl("border-radius", &radius(value)?)))
+}
+
+fn static_utility(name: &str) -> Option<Utility> {
+    let body = match name {
+        "block" | "inline-block" | "inline" | "flex" | "inline-flex" | "grid"
+        | "inline-grid" | "table" | "table-row" | "table-cell" | "contents" | "flow-root"
+        | "list-item" => decl("display", name),
+        "hidden" => decl("display", "none"),
+        "static" | "fixed" | "absolute" | "relative" | "sticky" => decl("position", name),
+        "visible" => decl("visibility", "visible"),
+        "invisible" => decl("visibility", "hidden"),
+        "isolate" => decl("isolation", "isolate"),
+        "flex-row" => decl("flex-direction", "row"),
+        "flex-row-reverse" => decl("flex-direction", "row-reverse"),
+        "flex-col" => decl("flex-direction", "column"),
+        "flex-col-reverse" => decl("flex-direction", "column-reverse"),
+        "flex-wrap" => decl("flex-wrap", "wrap"),
+        "flex-wrap-reverse" => decl("flex-wrap", "wrap-reverse"),
+        "flex-nowrap" => decl("flex-wrap", "nowrap"),
+        "flex-1" => decl("flex", "1 1 0%"),
+        "flex-auto" => decl("flex", "1 1 auto"),
+        "flex-initial" => decl("flex", "0 1 auto"),
+        "flex-none" => decl("flex", "none"),
+        "grow" => decl("flex-grow", "1"),
+        "grow-0" => decl("flex-grow", "0"),
+        "shrink" => decl("flex-shrink", "1"),
+        "shrink-0" => decl("flex-shrink", "0"),
+        "items-start" => decl("align-items", "flex-start"),
+        "items-end" => decl("align-items", "flex-end"),
+        "items-center" => decl("align-items", "center"),
+        "items-baseline" => decl("align-items", "baseline"),
+        "items-stretch" => decl("align-items", "stretch"),
+        "justify-start" => decl("justify-content", "flex-start"),
+        "justify-end" => decl("justify-content", "flex-end"),
+        "justify-center" => decl("justify-content", "center"),
+        "justify-between" => decl("justify-content", "space-between"),
+        "justify-around" => decl("justify-content", "space-around"),
+        "justify-evenly" => decl("justify-content", "space-evenly"),
+        "justify-items-center" => decl("justify-items", "center"),
+        "content-center" => decl("align-content", "center"),
+        "content-between" => decl("align-content", "space-between"),
+        "self-auto" => decl("align-self", "auto"),
+        "self-start" => decl("align-self", "flex-start"),
+        "self-end" => decl("align-self", "flex-end"),
+        "self-center" => decl("align-self", "center"),
+        "self-stretch" => decl("align-self", "stretch"),
+        "place-items-center" => decl("place-items", "center"),
+        "place-content-center" => decl("place-content", "center"),
+        "text-left" | "text-center" | "text-right" | "text-justify" | "text-start"
+        | "text-end" => decl("text-align", &name[5..]),
+        "italic" => decl("font-style", "italic"),
+        "not-italic" => decl("font-style", "normal"),
+        "uppercase" | "lowercase" | "capitalize" => decl("text-transform", name),
+        "normal-case" => decl("text-transform", "none"),
+        "underline" | "overline" | "line-through" => decl("text-decoration-line", name),
+        "no-underline" => decl("text-decoration-line", "none"),
+        "antialiased" => format!(
+            "{} {}",
+            decl("-webkit-font-smoothing", "antialiased"),
+            decl("-moz-osx-font-smoothing", "grayscale")
+        ),
+        "truncate" => format!(
+            "{} {} {}",
+            decl("overflow", "hidden"),
+            decl("text-overflow", "ellipsis"),
+            decl("white-space", "nowrap")
+        ),
+        "text-ellipsis" => decl("text-overflow", "ellipsis"),
+        "break-words" => decl("overflow-wrap", "break-word"),
+        "break-all" => decl("word-break", "break-all"),
+        "align-top" | "align-middle" | "align-bottom" | "align-baseline" => {
+            decl("vertical-align", &name[6..])
+        }
+        "list-none" | "list-disc" | "list-decimal" => decl("list-style-type", &name[5..]),
+        "list-inside" | "list-outside" => decl("list-style-position", &name[5..]),
+        "object-cover" | "object-contain" | "object-fill" | "object-none" => {
+            decl("object-fit", &name[7..])
+        }
+        "object-center" => decl("object-position", "center"),
+        "cursor-pointer" | "cursor-default" | "cursor-not-allowed" | "cursor-wait"
+        | "cursor-text" | "cursor-move" | "cursor-help" => decl("cursor", &name[7..]),
+        "select-none" | "select-text" | "select-all" | "select-auto" => {
+            decl("user-select", &name[7..])
+        }
+        "pointer-events-none" | "pointer-events-auto" => decl("pointer-events", &name[15..]),
+        "resize" => decl("resize", "both"),
+        "resize-none" => decl("resize", "none"),
+        "resize-y" => decl("resize", "vertical"),
+        "appearance-none" => decl("appearance", "none"),
+        "outline-none" | "outline-hidden" => format!(
+            "{} {}",
+            decl("outline", "2px solid transparent"),
+            decl("outline-offset", "2px")
+        ),
+        "sr-only" => [
+            decl("position", "absolute"),
+            decl("width", "1px"),
+            decl("height", "1px"),
+            decl("padding", "0"),
+            decl("margin", "-1px"),
+            decl("overflow", "hidden"),
+            decl("clip", "rect(0, 0, 0, 0)"),
+            decl("white-space", "nowrap"),
+            decl("border-width", "0"),
+        ]
+        .join(" "),
+        "transition" => transition(
+            "color, background-color, border-color, text-decoration-color, fill, stroke, opacity, box-shadow, transform, translate, scale, rotate, filter, backdrop-filter",
+        ),
+        "transition-colors" => transition(
+            "color, background-color, border-color, text-decoration-color, fill, stroke",
+        ),
+        "transition-all" => transition("all"),
+        "transition-opacity" => transition("opacity"),
+        "transition-shadow" => transition("box-shadow"),
+        "transition-transform" => transition("transform, translate, scale, rotate"),
+        "transition-none" => decl("transition-property", "none"),
+        "ease-linear" => decl("transition-timing-function", "linear"),
+        "ease-in" => decl("transition-timing-function", "cubic-bezier(0.4, 0, 1, 1)"),
+        "ease-out" => decl("transition-timing-function", "cubic-bezier(0, 0, 0.2, 1)"),
+        "ease-in-out" => decl("transition-timing-function", "cubic-bezier(0.4, 0, 0.2, 1)"),
+        "animate-none" => decl("animation", "none"),
+        "animate-spin" => {
+            return Some(Utility {
+                keyframes: Some("@keyframes spin { to { transform: rotate(360deg); } }"),
+                ..Utility::new(decl("animation", "spin 1s linear infinite"))
+            })
+        }
+        "animate-ping" => {
+            return Some(Utility {
+                keyframes: Some(
+                    "@keyframes ping { 75%, 100% { transform: scale(2); opacity: 0; } }",
+                ),
+                ..Utility::new(decl(
+                    "animation",
+                    "ping 1s cubic-bezier(0, 0, 0.2, 1) infinite",
+                ))
+            })
+        }
+        "animate-pulse" => {
+            return Some(Utility {
+                keyframes: Some("@keyframes pulse { 50% { opacity: 0.5; } }"),
+                ..Utility::new(decl(
+                    "animation",
+                    "pulse 2s cubic-bezier(0.4, 0, 0.6, 1) infinite",
+                ))
+            })
+        }
+        "container" => {
+            let mut body = decl("width", "100%");
+            for (_, w) in BREAKPOINTS {
+                body.push_str(&format!(
+                    " @media (min-width: {}) {{ max-width: {}; }}",
+                    w, w
+                ));
+            }
+            body
+        }
+        _ => {
+            if let Some(v) = name.strip_prefix("whitespace-") {
+                if !matches!(v, "normal" | "nowrap" | "pre" | "pre-line" | "pre-wrap" | "break-spaces") {
+                    return None;
+                }
+                return Some(Utility::ranked(11, decl("white-space", v)));
+            }
+            if let Some(rest) = name.strip_prefix("overflow-") {
+                let (prop, v, rank) = match rest.split_once('-') {
+                    Some(("x", v)) => ("overflow-x", v, 11),
+                    Some(("y", v)) => ("overflow-y", v, 11),
+                    _ => ("overflow", rest, 10),
+                };
+                if !matches!(v, "auto" | "hidden" | "clip" | "visible" | "scroll") {
+                    return None;
+                }
+                return Some(Utility::ranked(rank, decl(prop, v)));
+            }
+            return None;
+        }
+    };
+    Some(Utility::new(body))
+}
+
+fn transition(props: &str) -> String {
+    format!(
+        "{} {} {}",
+        decl("transition-property", props),
+        decl("transition-timing-function", "cubic-bezier(0.4, 0, 0.2, 1)"),
+        decl("transition-duration", "150ms")
+    )
+}
+
+/// Collect `--color-*` / `--font-*` token names from `@theme` blocks.
+fn parse_theme(css: &str) -> Theme {
+    let mut theme = Theme::default();
+    let mut search = css;
+    while let Some(pos) = search.find("@theme") {
+        let after = &search[pos..];
+        let Some(open) = after.find('{') else { break };
+        let Some(close) = matching_brace(after, open) else {
+            break;
+        };
+        for line in after[open + 1..close].split(';') {
+            let Some((prop, _)) = line.trim().split_once(':') else {
+                continue;
+            };
+            if let Some(name) = prop.trim().strip_prefix("--color-") {
+                theme.colors.insert(name.to_string());
+            } else if let Some(name) = prop.trim().strip_prefix("--font-") {
+                theme.fonts.insert(name.to_string());
+            }
+        }
+        search = &after[close..];
+    }
+    theme
+}
+
+/// Index of the `}` closing the `{` at `open`, skipping strings and comments.
+fn matching_brace(s: &str, open: usize) -> Option<usize> {
+    let bytes = s.as_bytes();
+    let mut depth = 0usize;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = s[i + 2..].find("*/").map(|p| i + 2 + p + 1)?;
+            }
+            q @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != q {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Drop Tailwind-only statements and turn `@theme` / `@utility` blocks into
+/// plain CSS.
+fn rewrite_directives(css: &str) -> String {
+    const DROPPED: &[&str] = &[
+        "@tailwind",
+        "@import \"tailwindcss",
+        "@import 'tailwindcss",
+        "@plugin",
+        "@config",
+        "@source",
+        "@custom-variant",
+        "@reference",
+    ];
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(at) = rest.find('@') {
+        out.push_str(&rest[..at]);
+        let tail = &rest[at..];
+        if DROPPED.iter().any(|d| tail.starts_with(d)) {
+            // Statement form ends at `;`; `@custom-variant x { … }` at its block.
+            let semi = tail.find(';');
+            let brace = tail.find('{');
+            rest = match (semi, brace) {
+                (Some(s), Some(b)) if b < s => match matching_brace(tail, b) {
+                    Some(end) => &tail[end + 1..],
+                    None => "",
+                },
+                (Some(s), _) => &tail[s + 1..],
+                (None, Some(b)) => match matching_brace(tail, b) {
+                    Some(end) => &tail[end + 1..],
+                    None => "",
+                },
+                (None, None) => "",
+            };
+            continue;
+        }
+        if tail.starts_with("@theme") {
+            if let Some(open) = tail.find('{') {
+                out.push_str("@layer theme {\n  :root ");
+                let end = matching_brace(tail, open).unwrap_or(tail.len() - 1);
+                out.push_str(&tail[open..=end]);
+                out.push_str("\n}");
+                rest = &tail[end + 1..];
+                continue;
+            }
+        }
+        if let Some(after) = tail.strip_prefix("@utility") {
+            if let Some(open) = after.find('{') {
+                let name = after[..open].trim();
+                out.push_str("@layer utilities {\n  .");
+                out.push_str(&escape_class(name));
+                out.push(' ');
+                let end = matching_brace(after, open).unwrap_or(after.len() - 1);
+                out.push_str(&after[open..=end]);
+                out.push_str("\n}");
+                rest = &after[end + 1..];
+                continue;
+            }
+        }
+        out.push('@');
+        rest = &tail[1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Replace every `@apply a b c;` with the utilities' declarations; variants
+/// become nested rules (`&:hover { … }`, `@media … { … }`).
+fn expand_apply(css: &str, theme: &Theme, unknown: &mut Vec<String>) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(at) = rest.find("@apply") {
+        out.push_str(&rest[..at]);
+        let tail = &rest[at + "@apply".len()..];
+        let end = tail.find([';', '}']).unwrap_or(tail.len());
+        let mut flat = Vec::new();
+        let mut nested = Vec::new();
+        for class in tail[..end].split_whitespace() {
+            match apply_one(class, theme) {
+                Some((true, text)) => flat.push(text),
+                Some((false, text)) => nested.push(text),
+                None => unknown.push(class.to_string()),
+            }
+        }
+        flat.extend(nested);
+        out.push_str(&flat.join(" "));
+        // Keep a closing `}` in place; swallow the `;`.
+        rest = match tail[end..].chars().next() {
+            Some(';') => &tail[end + 1..],
+            _ => &tail[end..],
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Returns `(is_flat, css)`: flat declarations go first, nested rules after.
+fn apply_one(class: &str, theme: &Theme) -> Option<(bool, String)> {
+    let parsed = parse_candidate(class)?;
+    let u = utility(parsed.utility, theme)?;
+    let body = if parsed.important {
+        make_important(&u.body)
+    } else {
+        u.body
+    };
+    let selector_tail = format!("{}{}{}", parsed.pseudo, u.suffix, parsed.pseudo_element);
+    let mut text = if selector_tail.is_empty() && parsed.group.is_empty() {
+        body
+    } else {
+        let sel = if parsed.group.is_empty() {
+            format!("&{}", selector_tail)
+        } else {
+            format!(":where({}) &{}", parsed.group, selector_tail)
+        };
+        format!("{} {{ {} }}", sel, body)
+    };
+    for query in parsed.media.iter().rev() {
+        text = format!("@media {} {{ {} }}", query, text);
+    }
+    let flat = parsed.media.is_empty()
+        && parsed.pseudo.is_empty()
+        && parsed.group.is_empty()
+        && u.suffix.is_empty()
+        && parsed.pseudo_element.is_empty();
+    Some((flat, text))
+}
+
+/// Split off the leading `@charset` / `@import` statements, which must stay
+/// ahead of every other rule for the browser to honour them.
+fn split_leading_imports(css: &str) -> (&str, &str) {
+    let mut pos = 0;
+    loop {
+        let rest = &css[pos..];
+        let trimmed = rest.trim_start();
+        let skipped = rest.len() - trimmed.len();
+        if trimmed.starts_with("/*") {
+            match trimmed.find("*/") {
+                Some(end) => pos += skipped + end + 2,
+                None => break,
+            }
+        } else if trimmed.starts_with("@import") || trimmed.starts_with("@charset") {
+            match trimmed.find(';') {
+                Some(end) => pos += skipped + end + 1,
+                None => break,
+            }
+        } else {
+            break;
+        }
+    }
+    // Keep the newline that ended the last import with the import block.
+    if css[pos..].starts_with('\n') {
+        pos += 1;
+    }
+    (&css[..pos], &css[pos..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(list: &[&str]) -> BTreeSet<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn generates_variants_in_cascade_order() {
+        let out = compile_with_candidates(
+            "@import \"tailwindcss\";",
+            &candidates(&["md:flex", "hidden", "hover:bg-blue-500", "px-2", "p-4"]),
+        );
+        let css = &out.css;
+        let pos = |needle: &str| {
+            css.find(needle)
+                .unwrap_or_else(|| panic!("{needle} in {css}"))
+        };
+        assert!(css.contains(".p-4 { padding: 1rem; }"));
+        assert!(css.contains(".hover\\:bg-blue-500:hover { background-color: #3b82f6; }"));
+        assert!(css.contains("@media (min-width: 768px) { .md\\:flex { display: flex; } }"));
+        // Longhands override shorthands; variants override plain utilities.
+        assert!(pos(".p-4 ") < pos(".px-2 "));
+        assert!(pos(".hidden ") < pos(".md\\:flex"));
+        assert!(pos(".px-2 ") < pos(".hover\\:bg-blue-500"));
+    }
+
+    #[test]
+    fn value_forms() {
+        let out = compile_with_candidates(
+            "@tailwind utilities;",
+            &candidates(&[
+                "-mt-2",
+                "w-1/2",
+                "w-[320px]",
+                "bg-black/50",
+                "!p-0",
+                "2xl:p-0.5",
+            ]),
+        );
+        let css = &out.css;
+        assert!(css.contains(".-mt-2 { margin-top: calc(0.5rem * -1); }"));
+        assert!(css.contains(".w-1\\/2 { width: 50%; }"));
+        assert!(css.contains(".w-\\[320px\\] { width: 320px; }"));
+        assert!(css.contains("color-mix(in srgb, #000 50%, transparent)"));
+        assert!(css.contains(".\\!p-0 { padding: 0px !important; }"));
+        assert!(css.contains(".\\32 xl\\:p-0\\.5 { padding: 0.125rem; }"));
+    }
+
+    #[test]
+    fn non_utilities_are_ignored() {
+        let out = compile_with_candidates(
+            "@tailwind utilities;",
+            &candidates(&["<div", "user.name", "bg-notacolor-500", "p-3.3", "hover:"]),
+        );
+        assert!(!out.css.contains("@layer utilities"), "{}", out.css);
+    }
+
+    #[test]
+    fn apply_expands_variants_as_nested_rules() {
+        let out = compile_with_candidates(
+            "a { @apply text-indigo-600 hover:text-indigo-500 md:p-4 bogus-class; }",
+            &BTreeSet::new(),
+        );
+        assert!(out.css.contains(
+            "a { color: #4f46e5; &:hover { color: #6366f1; } @media (min-width: 768px) { padding: 1rem; } }"
+        ), "{}", out.css);
+        assert_eq!(out.unknown_apply, vec!["bogus-class".to_string()]);
+    }
+
+    #[test]
+    fn theme_tokens_become_custom_properties_and_utilities() {
+        let out = compile_with_candidates(
+            "@import \"tailwindcss\";\n@theme { --color-brand: #0af; --font-display: Satoshi; }",
+            &candidates(&["bg-brand", "font-display"]),
+        );
+        assert!(
+            out.css.contains(":root { --color-brand: #0af;"),
+            "{}",
+            out.css
+        );
+        assert!(out
+            .css
+            .contains(".bg-brand { background-color: var(--color-brand); }"));
+        assert!(out
+            .css
+            .contains(".font-display { font-family: var(--font-display, inherit); }"));
+        assert!(!out.css.contains("@theme"));
+        assert!(!out.css.contains("@import \"tailwindcss\""));
+    }
+
+    #[test]
+    fn v3_directives_and_leading_imports() {
+        let src = "@import url(\"https://fonts.example/inter.css\");\n@tailwind base;\n@tailwind components;\n@tailwind utilities;\n.btn { @apply rounded-lg; }\n";
+        let out = compile_with_candidates(src, &candidates(&["flex"]));
+        let css = &out.css;
+        assert!(!css.contains("@tailwind"));
+        // `@import` must stay ahead of every rule, preflight included.
+        assert!(css.find("@import url").unwrap() < css.find("@layer base").unwrap());
+        assert!(css.contains(".btn { border-radius: 0.5rem; }"));
+        assert!(css.contains(".flex { display: flex; }"));
+    }
+
+    #[test]
+    fn utility_directive_becomes_a_class() {
+        let out = compile_with_candidates(
+            "@utility content-auto { content-visibility: auto; }",
+            &BTreeSet::new(),
+        );
+        assert!(
+            out.css
+                .contains(".content-auto { content-visibility: auto; }"),
+            "{}",
+            out.css
+        );
+    }
+
+    #[test]
+    fn collects_candidates_from_app_sources_only() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = tmp.path().join("app");
+        std::fs::create_dir_all(app.join("views/home")).unwrap();
+        std::fs::create_dir_all(app.join("assets/css")).unwrap();
+        std::fs::write(
+            app.join("views/home/index.html.slv"),
+            "<div class=\"flex <%= ok ? 'bg-red-500' : '' %>\">hi</div>",
+        )
+        .unwrap();
+        std::fs::write(app.join("assets/css/app.css"), ".from-css { }").unwrap();
+        std::fs::write(app.join("views/home/logo.png"), "p-8").unwrap();
+
+        let found = collect_candidates(&app);
+        assert!(found.contains("flex"));
+        assert!(found.contains("bg-red-500"));
+        assert!(!found.contains(".from-css"));
+        assert!(!found.contains("p-8"));
+    }
+}
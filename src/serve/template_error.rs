@@ -0,0 +1,270 @@
+//! The dev error page's template overlay.
+//!
+//! Template errors reach the server as strings that name the template and
+//! the line of the failing tag (`Evaluation error: ... at 1:5 at
+//! app/views/posts/show.html.slv:12`, `Parse error at line 3: ... at 1:4 in
+//! app/views/posts/show.html.slv`), with any position inside the tag's code
+//! relative to that code. [`locate`] turns that back into a template line
+//! and column, and [`render_overlay`] shows the template source around it
+//! with the variables that were in scope when the tag failed.
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// Lines of template shown above and below the failing one.
+const CONTEXT_LINES: usize = 4;
+
+/// Where in a template an error happened. `line` and `column` are 1-based;
+/// `column` is `None` when the line holds no tag to anchor it on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct TemplateErrorSite {
+    pub(super) file: String,
+    pub(super) line: usize,
+    pub(super) column: Option<usize>,
+    /// The error without its positions, shown under the caret.
+    pub(super) message: String,
+}
+
+static TEMPLATE_FILE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"([./a-zA-Z0-9_@-]+(?:\.html\.slv|\.slv|\.html\.md|\.html\.erb|\.erb))").unwrap()
+});
+static AT_FILE_LINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"at ([./a-zA-Z0-9_@-]+(?:\.html\.slv|\.slv|\.html\.md|\.html\.erb|\.erb)):(\d+)")
+        .unwrap()
+});
+static AT_LINE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"at line (\d+)").unwrap());
+static CODE_SPAN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r" at (\d+):(\d+)").unwrap());
+
+/// The template position an error message points at, or `None` when it
+/// doesn't name a template. `source` reads the template (relative names
+/// are tried under the app root too) to anchor the column on the tag.
+pub(super) fn locate(
+    error: &str,
+    source: impl Fn(&str) -> Option<String>,
+) -> Option<TemplateErrorSite> {
+    let (file, tag_line) = match AT_FILE_LINE.captures(error) {
+        Some(caps) => (caps[1].to_string(), caps[2].parse().ok()?),
+        None => {
+            let file = TEMPLATE_FILE.captures(error)?[1].to_string();
+            let line = AT_LINE
+                .captures(error)
+                .and_then(|caps| caps[1].parse().ok())
+                .unwrap_or(1);
+            (file, line)
+        }
+    };
+    // `at l:c` inside the tag's code, which starts on `tag_line`.
+    let code_span = CODE_SPAN.captures(error).and_then(|caps| {
+        Some((
+            caps[1].parse::<usize>().ok()?,
+            caps[2].parse::<usize>().ok()?,
+        ))
+    });
+    let (line, column) = match code_span {
+        Some((code_line, code_column)) if code_line > 1 => {
+            // Later lines of a multi-line tag keep their own indentation.
+            (tag_line + code_line - 1, Some(code_column))
+        }
+        other => {
+            let code_column = other.map_or(1, |(_, column)| column);
+            let column = source(&file)
+                .and_then(|text| {
+                    text.lines()
+                        .nth(tag_line.saturating_sub(1))
+                        .map(str::to_string)
+                })
+                .and_then(|text| tag_code_column(&text))
+                .map(|start| start + code_column - 1);
+            (tag_line, column)
+        }
+    };
+    let message = CODE_SPAN.replace_all(error, "");
+    let message = AT_FILE_LINE.replace_all(&message, "");
+    let message = message.replace(&format!(" in {}", file), "");
+    Some(TemplateErrorSite {
+        file,
+        line,
+        column,
+        message: message.trim().to_string(),
+    })
+}
+
+/// The 1-based column where the code of the first tag on `line` starts:
+/// past `<%`, its `=`, `==`, `-` or `#` marker and the blanks after it.
+fn tag_code_column(line: &str) -> Option<usize> {
+    let chars: Vec<char> = line.chars().collect();
+    let open = chars.windows(2).position(|w| w == ['<', '%'])?;
+    let mut at = open + 2;
+    while at < chars.len() && matches!(chars[at], '=' | '-' | '#') {
+        at += 1;
+    }
+    while at < chars.len() && chars[at].is_whitespace() {
+        at += 1;
+    }
+    Some(at + 1)
+}
+
+/// The overlay: the template lines around the error with the failing one
+/// marked, a caret under its column, and the variables in scope there.
+pub(super) fn render_overlay(
+    site: &TemplateErrorSite,
+    display_file: &str,
+    source: Option<&str>,
+    locals: &[(String, String)],
+) -> String {
+    let location = match site.column {
+        Some(column) => format!("{}:{}:{}", display_file, site.line, column),
+        None => format!("{}:{}", display_file, site.line),
+    };
+
+    let mut lines_html = String::new();
+    if let Some(source) = source {
+        let lines: Vec<&str> = source.lines().collect();
+        let first = site.line.saturating_sub(CONTEXT_LINES).max(1);
+        let last = (site.line + CONTEXT_LINES).min(lines.len());
+        for number in first..=last {
+            let text = lines.get(number - 1).copied().unwrap_or("");
+            let (row_class, number_class) = if number == site.line {
+                ("bg-red-500/15", "text-red-400")
+            } else {
+                ("", "text-gray-600")
+            };
+            lines_html.push_str(&format!(
+                r#"<div class="flex {}"><span class="w-12 shrink-0 text-right pr-4 select-none {}">{}</span><span class="whitespace-pre text-gray-200">{}</span></div>"#,
+                row_class,
+                number_class,
+                number,
+                escape_html(text)
+            ));
+            if number == site.line {
+                if let Some(column) = site.column {
+                    lines_html.push_str(&format!(
+                        r#"<div class="flex"><span class="w-12 shrink-0 pr-4 select-none"></span><span class="whitespace-pre text-red-400">{}^ {}</span></div>"#,
+                        " ".repeat(column.saturating_sub(1)),
+                        escape_html(&site.message)
+                    ));
+                }
+            }
+        }
+    } else {
+        lines_html.push_str(r#"<div class="text-gray-500">Template source not found.</div>"#);
+    }
+
+    let locals_html = if locals.is_empty() {
+        r#"<p class="text-sm text-gray-500">No template variables were in scope.</p>"#.to_string()
+    } else {
+        let rows: String = locals
+            .iter()
+            .map(|(name, value)| {
+                format!(
+                    r#"<tr class="border-t border-white/5"><td class="py-1.5 pr-6 align-top font-mono text-emerald-400 whitespace-nowrap">{}</td><td class="py-1.5 font-mono text-gray-300 break-all">{}</td></tr>"#,
+                    escape_html(name),
+                    escape_html(value)
+                )
+            })
+            .collect();
+        format!(r#"<table class="w-full text-sm">{}</table>"#, rows)
+    };
+
+    format!(
+        r#"<div id="template-error" class="mb-8 rounded-xl bg-gray-900 border border-red-500/40 overflow-hidden">
+            <div class="flex items-center justify-between px-4 py-3 bg-red-500/10 border-b border-white/10">
+                <span class="font-semibold text-white">Template error</span>
+                <span class="font-mono text-sm text-red-300">{location}</span>
+            </div>
+            <div class="code-editor p-4 overflow-x-auto text-sm">{lines_html}</div>
+            <div class="px-4 py-3 border-t border-white/10">
+                <h3 class="text-sm font-semibold text-gray-300 mb-2">Locals at this point</h3>
+                {locals_html}
+            </div>
+        </div>"#,
+        location = escape_html(&location),
+    )
+}
+
+/// Read a template named by an error, as given or under `app_root`.
+pub(super) fn read_template(file: &str, app_root: &Path) -> Option<String> {
+    std::fs::read_to_string(file)
+        .or_else(|_| std::fs::read_to_string(app_root.join(file)))
+        .ok()
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHOW: &str = "<h1><%= title %></h1>\n<ul>\n<% for item in items %>\n  <li><%= item.name.upcase() %></li>\n<% end %>\n</ul>\n";
+
+    fn show(_: &str) -> Option<String> {
+        Some(SHOW.to_string())
+    }
+
+    #[test]
+    fn evaluation_error_points_at_the_tag_code() {
+        let site = locate(
+            "Evaluation error: Cannot access property 'upcase' on null at 1:1 at app/views/posts/show.html.slv:4",
+            show,
+        )
+        .unwrap();
+        assert_eq!(
+            site,
+            TemplateErrorSite {
+                file: "app/views/posts/show.html.slv".into(),
+                line: 4,
+                column: Some(11),
+                message: "Evaluation error: Cannot access property 'upcase' on null".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_error_in_a_multi_line_tag_maps_to_its_line() {
+        let site = locate(
+            "Parse error at line 3: Unexpected token at 2:7 in app/views/posts/show.html.slv",
+            show,
+        )
+        .unwrap();
+        assert_eq!((site.line, site.column), (4, Some(7)));
+
+        let site = locate(
+            "Parse error at line 1: Unexpected token 'EOF', expected ) at 1:3 in app/views/posts/show.html.slv",
+            show,
+        )
+        .unwrap();
+        assert_eq!((site.line, site.column), (1, Some(11)));
+    }
+
+    #[test]
+    fn non_template_errors_have_no_site() {
+        assert!(locate("Undefined variable 'x' at 3:5 in app/models/user.sl", show).is_none());
+    }
+
+    #[test]
+    fn overlay_marks_the_line_and_escapes_locals() {
+        let site = TemplateErrorSite {
+            file: "show.html.slv".into(),
+            line: 4,
+            column: Some(11),
+            message: "Cannot access property 'upcase' on null".into(),
+        };
+        let html = render_overlay(
+            &site,
+            "app/views/posts/show.html.slv",
+            Some(SHOW),
+            &[("item".into(), r#"{"name": "<b>"}"#.into())],
+        );
+        assert!(html.contains("app/views/posts/show.html.slv:4:11"));
+        assert!(html.contains("&lt;li&gt;&lt;%= item.name.upcase() %&gt;&lt;/li&gt;"));
+        assert!(html.contains(&format!("{}^ Cannot access", " ".repeat(10))));
+        assert!(html.contains("{&quot;name&quot;: &quot;&lt;b&gt;&quot;}"));
+    }
+}
@@ -0,0 +1,93 @@
+//! Parsed-file cache shared by the serve workers.
+//!
+//! Every worker loads the same controllers, models and middleware, and a hot
+//! reload makes every worker re-load the whole directory a changed file lives
+//! in. The parse doesn't depend on the worker, so the first worker to load a
+//! file parses it and the others reuse the same immutable `Arc<Program>`, as
+//! does every later reload that finds the file's content unchanged. Entries
+//! are keyed by path and checked against a hash of the file's bytes: an
+//! edited file is parsed again, its untouched neighbours are not.
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use crate::ast::Program;
+use crate::error::RuntimeError;
+
+struct Entry {
+    hash: u64,
+    program: Arc<Program>,
+}
+
+static UNITS: LazyLock<Mutex<HashMap<PathBuf, Entry>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The program in `path`, whose content is `bytes`: the cached one if the
+/// content hasn't changed since it was parsed, otherwise `parse()`'s result,
+/// which is cached in its place. Parse errors are not cached.
+pub(crate) fn get_or_parse(
+    path: &Path,
+    bytes: &[u8],
+    parse: impl FnOnce() -> Result<Program, RuntimeError>,
+) -> Result<Arc<Program>, RuntimeError> {
+    let hash = content_hash(bytes);
+    if let Some(entry) = UNITS.lock().unwrap().get(path) {
+        if entry.hash == hash {
+            return Ok(Arc::clone(&entry.program));
+        }
+    }
+
+    // Parse without the lock so workers loading different files don't wait
+    // on each other.
+    let program = Arc::new(parse()?);
+    UNITS.lock().unwrap().insert(
+        path.to_path_buf(),
+        Entry {
+            hash,
+            program: Arc::clone(&program),
+        },
+    );
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source: &str) -> Result<Program, RuntimeError> {
+        let tokens = crate::lexer::Scanner::new(source).scan_tokens().unwrap();
+        Ok(crate::parser::Parser::new(tokens).parse().unwrap())
+    }
+
+    #[test]
+    fn unchanged_content_reuses_the_parse() {
+        let path = Path::new("/unit-cache-test/users_controller.sl");
+        let first = get_or_parse(path, b"let a = 1", || parse("let a = 1")).unwrap();
+        let again = get_or_parse(path, b"let a = 1", || panic!("parsed twice")).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+
+        let edited = get_or_parse(path, b"let a = 2", || parse("let a = 2")).unwrap();
+        assert!(!Arc::ptr_eq(&first, &edited));
+    }
+
+    #[test]
+    fn parse_errors_are_not_cached() {
+        let path = Path::new("/unit-cache-test/broken_controller.sl");
+        let failed = get_or_parse(path, b"let", || {
+            Err(RuntimeError::General {
+                message: "parse error".to_string(),
+                span: crate::span::Span::default(),
+            })
+        });
+        assert!(failed.is_err());
+        assert!(get_or_parse(path, b"let", || parse("let b = 1")).is_ok());
+    }
+}
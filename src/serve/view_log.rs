@@ -106,6 +106,18 @@ pub fn snapshot() -> Vec<ViewEntry> {
     LOG.with(|l| l.borrow().clone())
 }
 
+/// Total render time of a snapshot: the sum of its ROOT entries (those whose
+/// parent isn't in the snapshot). Each root already includes its nested
+/// partials/layout, so summing every entry would double-count.
+pub fn root_total_us(views: &[ViewEntry]) -> u64 {
+    let ids: std::collections::HashSet<u32> = views.iter().map(|(id, _, _, _)| *id).collect();
+    views
+        .iter()
+        .filter(|(_, parent, _, _)| parent.is_none_or(|p| !ids.contains(&p)))
+        .map(|(_, _, _, us)| *us)
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -4,13 +4,33 @@
 //! - Connection management with unique IDs
 //! - Channel/room support for targeted broadcasting
 //! - Single handler pattern for all WebSocket events (connect, message, disconnect)
+//! - Resumable sessions that replay missed frames after a reconnect
+//!
+//! ## Resumable sessions
+//!
+//! A client that connects with `?soli_session=new` (as `/__soli/ws.js` does)
+//! gets a session: the first frame it receives is
+//! `{"event":"session","payload":{"token":..,"resumed":false,"lost":false}}`,
+//! and every frame after it is wrapped as `{"seq":N,"data":"<frame>"}`. The
+//! registry keeps the last `SOLI_WS_OUTBOX` frames of each session. When the
+//! socket drops without a close, the connection stays registered (channels,
+//! presence, metadata) for `SOLI_WS_RESUME_TTL` seconds while frames for it are
+//! queued. Reconnecting with `?soli_session=<token>&soli_seq=<last seen>`
+//! takes the connection over under the same `connection_id` and replays what
+//! the client missed; `lost` says the outbox no longer reached back that far.
+//! The handler sees neither the drop nor the resume, only a `disconnect` once
+//! the TTL runs out. A close from either side ends the session for good.
 
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::rc::Rc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::Response;
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex as AsyncMutex;
 use tungstenite::Message;
@@ -18,6 +38,19 @@ use uuid::Uuid;
 
 use crate::interpreter::value::{stringify_to_string, HashKey, HashPairs, Value};
 
+/// Client JS for resumable sessions (`SoliSocket`), served at `/__soli/ws.js`.
+pub const WS_CLIENT_SCRIPT: &str = include_str!("ws.js");
+
+/// `GET /__soli/ws.js`.
+pub fn handle_ws_js() -> Response<Full<Bytes>> {
+    Response::builder()
+        .status(200)
+        .header("Content-Type", "application/javascript; charset=utf-8")
+        .header("Cache-Control", "public, max-age=3600")
+        .body(Full::new(Bytes::from_static(WS_CLIENT_SCRIPT.as_bytes())))
+        .unwrap()
+}
+
 /// Convert a handler-action payload to a wire string.
 /// `Value::String` passes through; anything else is JSON-encoded so handlers
 /// can return `{"broadcast": {...}}` without manually serializing.
@@ -30,6 +63,89 @@ fn action_payload_to_string(value: &Value) -> Option<String> {
 
 type ConnectionPresenceMap = HashMap<Uuid, Vec<(String, String)>>;
 
+/// The sending half of a connection's outgoing frame channel.
+pub type WebSocketSender = Arc<tokio::sync::mpsc::Sender<Result<Message, tungstenite::Error>>>;
+
+/// Frames kept per session for replay, unless `SOLI_WS_OUTBOX` says otherwise.
+pub const DEFAULT_OUTBOX_SIZE: usize = 256;
+/// Seconds a dropped session waits for its client, unless
+/// `SOLI_WS_RESUME_TTL` says otherwise.
+pub const DEFAULT_RESUME_TTL_SECS: u64 = 30;
+
+fn env_number(name: &str, default: u64) -> u64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+/// How long a dropped session is kept for its client to resume.
+pub fn resume_ttl() -> Duration {
+    static TTL: OnceLock<Duration> = OnceLock::new();
+    *TTL.get_or_init(|| {
+        Duration::from_secs(env_number("SOLI_WS_RESUME_TTL", DEFAULT_RESUME_TTL_SECS))
+    })
+}
+
+/// Resume state of one connection.
+struct Session {
+    token: String,
+    /// The route the session was opened on; a token only resumes there.
+    path: String,
+    /// Where the session's frames go: the socket that attached last.
+    sender: WebSocketSender,
+    next_seq: u64,
+    /// The most recent frames, oldest first.
+    outbox: VecDeque<(u64, String)>,
+    /// False while the socket is gone, or while a resume replays the outbox:
+    /// frames are queued but not sent.
+    attached: bool,
+    /// Bumped by every resume, so a socket that was taken over can't detach
+    /// its successor.
+    generation: u64,
+    /// Closed by the app: never resumed.
+    closed: bool,
+}
+
+impl Session {
+    /// Number `message` and keep it for replay. Returns the wire frame.
+    fn push(&mut self, message: &str, limit: usize) -> String {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let frame = serde_json::json!({ "seq": seq, "data": message }).to_string();
+        self.outbox.push_back((seq, frame.clone()));
+        while self.outbox.len() > limit {
+            self.outbox.pop_front();
+        }
+        frame
+    }
+}
+
+#[derive(Default)]
+struct SessionTable {
+    by_connection: HashMap<Uuid, Session>,
+    by_token: HashMap<String, Uuid>,
+}
+
+impl SessionTable {
+    fn remove(&mut self, connection_id: &Uuid) {
+        if let Some(session) = self.by_connection.remove(connection_id) {
+            self.by_token.remove(&session.token);
+        }
+    }
+}
+
+/// What happened to a session when its socket went away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionDrop {
+    /// Kept for [`resume_ttl`]; call [`WebSocketRegistry::expire`] after it.
+    Waiting,
+    /// Another socket resumed it already; this one has nothing to clean up.
+    Replaced,
+    /// No session to keep: disconnect as usual.
+    Ended,
+}
+
 /// Metadata for a single presence connection.
 /// Each user can have multiple connections (tabs/devices), each with its own meta.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -87,7 +203,7 @@ pub struct WebSocketConnection {
     /// Unique identifier for this connection
     pub id: Uuid,
     /// Channel sender for sending messages to this client
-    pub sender: Arc<tokio::sync::mpsc::Sender<Result<Message, tungstenite::Error>>>,
+    pub sender: WebSocketSender,
     /// Channels this connection is subscribed to
     pub channels: Vec<String>,
     /// User-defined metadata for this connection
@@ -96,9 +212,7 @@ pub struct WebSocketConnection {
 
 impl WebSocketConnection {
     /// Create a new WebSocket connection.
-    pub fn new(
-        sender: Arc<tokio::sync::mpsc::Sender<Result<Message, tungstenite::Error>>>,
-    ) -> Self {
+    pub fn new(sender: WebSocketSender) -> Self {
         Self {
             id: Uuid::new_v4(),
             sender,
@@ -121,6 +235,10 @@ pub struct WebSocketRegistry {
     connection_presence: Arc<AsyncMutex<ConnectionPresenceMap>>,
     /// Counter for generating unique phx_ref values
     ref_counter: Arc<AtomicU64>,
+    /// Resumable sessions, by connection ID and by token
+    sessions: Arc<AsyncMutex<SessionTable>>,
+    /// Frames kept per session for replay
+    outbox_limit: usize,
 }
 
 impl Default for WebSocketRegistry {
@@ -138,6 +256,8 @@ impl WebSocketRegistry {
             room_presence: Arc::new(AsyncMutex::new(HashMap::new())),
             connection_presence: Arc::new(AsyncMutex::new(HashMap::new())),
             ref_counter: Arc::new(AtomicU64::new(1)),
+            sessions: Arc::new(AsyncMutex::new(SessionTable::default())),
+            outbox_limit: env_number("SOLI_WS_OUTBOX", DEFAULT_OUTBOX_SIZE as u64) as usize,
         }
     }
 
@@ -155,6 +275,7 @@ impl WebSocketRegistry {
 
     /// Unregister a connection and clean up channel subscriptions.
     pub async fn unregister(&self, id: &Uuid) {
+        self.sessions.lock().await.remove(id);
         let mut connections = self.connections.lock().await;
         if let Some(conn) = connections.remove(id) {
             // Remove from all channels
@@ -243,7 +364,39 @@ impl WebSocketRegistry {
     // `connections` mutex across that await would let one slow/stalled
     // client block every other WS operation (joins, presence, other
     // broadcasts). All senders are therefore cloned out under the lock
-    // (cheap `Arc` bump) and the actual sends happen lock-free.
+    // (cheap `Arc` bump) and the actual sends happen lock-free. Frames for
+    // sessions are numbered under the `sessions` lock, which is likewise
+    // released before sending.
+
+    /// Turn a message for `targets` into the frames to send: sessions get it
+    /// numbered (and queued, while detached), other connections get it as is.
+    async fn frames_for(
+        &self,
+        targets: Vec<(Uuid, WebSocketSender)>,
+        message: &str,
+    ) -> Vec<(WebSocketSender, Message)> {
+        let plain = Message::text(message);
+        let mut sessions = self.sessions.lock().await;
+        if sessions.by_connection.is_empty() {
+            return targets
+                .into_iter()
+                .map(|(_, sender)| (sender, plain.clone()))
+                .collect();
+        }
+        let mut frames = Vec::with_capacity(targets.len());
+        for (id, sender) in targets {
+            match sessions.by_connection.get_mut(&id) {
+                Some(session) => {
+                    let frame = session.push(message, self.outbox_limit);
+                    if session.attached {
+                        frames.push((session.sender.clone(), Message::text(frame)));
+                    }
+                }
+                None => frames.push((sender, plain.clone())),
+            }
+        }
+        frames
+    }
 
     /// Send a message to a specific connection.
     pub async fn send_to(&self, id: &Uuid, message: &str) -> Result<(), tungstenite::Error> {
@@ -252,9 +405,10 @@ impl WebSocketRegistry {
             connections.get(id).map(|conn| conn.sender.clone())
         };
         if let Some(sender) = sender {
-            let msg = Message::text(message);
-            if let Err(_e) = sender.send(Ok(msg)).await {
-                return Err(tungstenite::Error::ConnectionClosed);
+            for (sender, msg) in self.frames_for(vec![(*id, sender)], message).await {
+                if let Err(_e) = sender.send(Ok(msg)).await {
+                    return Err(tungstenite::Error::ConnectionClosed);
+                }
             }
         }
         Ok(())
@@ -262,38 +416,46 @@ impl WebSocketRegistry {
 
     /// Broadcast a message to all connections.
     pub async fn broadcast_all(&self, message: &str) {
-        let senders: Vec<_> = {
+        let targets: Vec<_> = {
             let connections = self.connections.lock().await;
-            connections.values().map(|c| c.sender.clone()).collect()
+            connections
+                .values()
+                .map(|c| (c.id, c.sender.clone()))
+                .collect()
         };
-        let msg = Message::text(message);
-        for sender in senders {
-            let _ = sender.send(Ok(msg.clone())).await;
+        for (sender, msg) in self.frames_for(targets, message).await {
+            let _ = sender.send(Ok(msg)).await;
         }
     }
 
     /// Broadcast a message to all connections in a channel.
     pub async fn broadcast_to_channel(&self, channel: &str, message: &str) {
         let channel_ids = self.get_channel_ids(channel).await;
-        let senders: Vec<_> = {
+        let targets: Vec<_> = {
             let connections = self.connections.lock().await;
             channel_ids
                 .iter()
-                .filter_map(|id| connections.get(id).map(|c| c.sender.clone()))
+                .filter_map(|id| connections.get(id).map(|c| (c.id, c.sender.clone())))
                 .collect()
         };
-        let msg = Message::text(message);
-        for sender in senders {
-            let _ = sender.send(Ok(msg.clone())).await;
+        for (sender, msg) in self.frames_for(targets, message).await {
+            let _ = sender.send(Ok(msg)).await;
         }
     }
 
-    /// Close a specific connection.
+    /// Close a specific connection. A closed session can't be resumed.
     pub async fn close(&self, id: &Uuid, reason: &str) {
         let sender = {
             let connections = self.connections.lock().await;
             connections.get(id).map(|conn| conn.sender.clone())
         };
+        let sender = match self.sessions.lock().await.by_connection.get_mut(id) {
+            Some(session) => {
+                session.closed = true;
+                Some(session.sender.clone())
+            }
+            None => sender,
+        };
         if let Some(sender) = sender {
             let close_frame = tungstenite::protocol::CloseFrame {
                 code: tungstenite::protocol::frame::coding::CloseCode::Normal,
@@ -311,11 +473,155 @@ impl WebSocketRegistry {
         }
     }
 
-    /// Get the number of active connections.
+    /// Get the number of active connections, including sessions waiting to
+    /// be resumed.
     pub async fn connection_count(&self) -> usize {
         self.connections.lock().await.len()
     }
 
+    // ========== Session Methods ==========
+
+    /// Make a registered connection resumable on `path`. Returns its token.
+    pub async fn open_session(&self, connection_id: &Uuid, path: &str) -> Option<String> {
+        let sender = self.get(connection_id).await?.sender;
+        let token = Uuid::new_v4().simple().to_string();
+        let mut sessions = self.sessions.lock().await;
+        sessions.by_token.insert(token.clone(), *connection_id);
+        sessions.by_connection.insert(
+            *connection_id,
+            Session {
+                token: token.clone(),
+                path: path.to_string(),
+                sender,
+                next_seq: 1,
+                outbox: VecDeque::new(),
+                attached: true,
+                generation: 0,
+                closed: false,
+            },
+        );
+        Some(token)
+    }
+
+    /// The current generation of a connection's session, for [`Self::detach`].
+    pub async fn session_generation(&self, connection_id: &Uuid) -> Option<u64> {
+        let sessions = self.sessions.lock().await;
+        sessions
+            .by_connection
+            .get(connection_id)
+            .map(|session| session.generation)
+    }
+
+    /// Take a session over with a new socket. Sends the session frame, then
+    /// replays every kept frame numbered after `last_seq`, then lets new
+    /// frames through. Returns the connection ID and the session's new
+    /// generation, or `None` if no open session on `path` has this token.
+    /// Once taken over, the session belongs to the new socket even if it
+    /// fails mid-replay: its own drop detaches the session again.
+    pub async fn resume(
+        &self,
+        token: &str,
+        path: &str,
+        last_seq: u64,
+        sender: WebSocketSender,
+    ) -> Option<(Uuid, u64)> {
+        let (id, generation, old_sender, lost) = {
+            let mut sessions = self.sessions.lock().await;
+            let id = *sessions.by_token.get(token)?;
+            let session = sessions.by_connection.get_mut(&id)?;
+            if session.path != path || session.closed {
+                return None;
+            }
+            session.generation += 1;
+            session.attached = false;
+            let old_sender = std::mem::replace(&mut session.sender, sender.clone());
+            let oldest = session.outbox.front().map_or(session.next_seq, |f| f.0);
+            let lost = last_seq.saturating_add(1) < oldest;
+            (id, session.generation, old_sender, lost)
+        };
+        if let Some(conn) = self.connections.lock().await.get_mut(&id) {
+            conn.sender = sender.clone();
+        }
+        // The socket being replaced may not have noticed it is dead yet.
+        let _ = old_sender.try_send(Ok(Message::Close(None)));
+
+        let hello = Self::build_session_frame(token, true, lost);
+        if sender.send(Ok(Message::text(hello))).await.is_err() {
+            return Some((id, generation));
+        }
+        let mut after = last_seq;
+        loop {
+            let pending: Vec<(u64, String)> = {
+                let mut sessions = self.sessions.lock().await;
+                // Taken over again, or gone: nothing left to replay here.
+                let Some(session) = sessions
+                    .by_connection
+                    .get_mut(&id)
+                    .filter(|session| session.generation == generation)
+                else {
+                    return Some((id, generation));
+                };
+                let pending: Vec<_> = session
+                    .outbox
+                    .iter()
+                    .filter(|(seq, _)| *seq > after)
+                    .cloned()
+                    .collect();
+                if pending.is_empty() {
+                    session.attached = true;
+                }
+                pending
+            };
+            if pending.is_empty() {
+                return Some((id, generation));
+            }
+            for (seq, frame) in pending {
+                after = seq;
+                if sender.send(Ok(Message::text(frame))).await.is_err() {
+                    return Some((id, generation));
+                }
+            }
+        }
+    }
+
+    /// The socket of `generation` went away without a close. A session
+    /// starts waiting for its client; see [`SessionDrop`].
+    pub async fn detach(&self, connection_id: &Uuid, generation: u64) -> SessionDrop {
+        let mut sessions = self.sessions.lock().await;
+        match sessions.by_connection.get_mut(connection_id) {
+            Some(session) if session.generation != generation => SessionDrop::Replaced,
+            Some(session) if session.closed => SessionDrop::Ended,
+            Some(session) => {
+                session.attached = false;
+                SessionDrop::Waiting
+            }
+            None => SessionDrop::Ended,
+        }
+    }
+
+    /// Drop a session still waiting since `generation` was detached. True
+    /// when it was, and the connection should now disconnect.
+    pub async fn expire(&self, connection_id: &Uuid, generation: u64) -> bool {
+        let mut sessions = self.sessions.lock().await;
+        let waiting = sessions
+            .by_connection
+            .get(connection_id)
+            .is_some_and(|session| session.generation == generation && !session.attached);
+        if waiting {
+            sessions.remove(connection_id);
+        }
+        waiting
+    }
+
+    /// Build the frame that opens (or resumes) a session.
+    pub fn build_session_frame(token: &str, resumed: bool, lost: bool) -> String {
+        serde_json::json!({
+            "event": "session",
+            "payload": { "token": token, "resumed": resumed, "lost": lost }
+        })
+        .to_string()
+    }
+
     // ========== Presence Methods ==========
 
     /// Track a connection's presence in a channel.
@@ -531,17 +837,16 @@ impl WebSocketRegistry {
     /// Same clone-then-send locking discipline as `broadcast_to_channel`.
     pub async fn broadcast_to_channel_except(&self, channel: &str, message: &str, except: &Uuid) {
         let channel_ids = self.get_channel_ids(channel).await;
-        let senders: Vec<_> = {
+        let targets: Vec<_> = {
             let connections = self.connections.lock().await;
             channel_ids
                 .iter()
                 .filter(|id| *id != except)
-                .filter_map(|id| connections.get(id).map(|c| c.sender.clone()))
+                .filter_map(|id| connections.get(id).map(|c| (c.id, c.sender.clone())))
                 .collect()
         };
-        let msg = Message::text(message);
-        for sender in senders {
-            let _ = sender.send(Ok(msg.clone())).await;
+        for (sender, msg) in self.frames_for(targets, message).await {
+            let _ = sender.send(Ok(msg)).await;
         }
     }
 
@@ -1151,6 +1456,140 @@ mod tests {
         assert!(json.contains("user_123"));
     }
 
+    // ========== Session Tests ==========
+
+    type Receiver = tokio::sync::mpsc::Receiver<Result<Message, tungstenite::Error>>;
+
+    async fn session_connection(registry: &WebSocketRegistry) -> (Uuid, String, Receiver) {
+        let (tx, rx) = tokio::sync::mpsc::channel(64);
+        let connection = WebSocketConnection::new(Arc::new(tx));
+        let id = connection.id;
+        registry.register(connection).await;
+        let token = registry.open_session(&id, "/ws/chat").await.unwrap();
+        (id, token, rx)
+    }
+
+    fn texts(rx: &mut Receiver) -> Vec<String> {
+        let mut out = Vec::new();
+        while let Ok(Ok(msg)) = rx.try_recv() {
+            out.push(msg.to_text().unwrap().to_string());
+        }
+        out
+    }
+
+    #[tokio::test]
+    async fn test_session_replays_what_a_dropped_socket_missed() {
+        let registry = WebSocketRegistry::new();
+        let (id, token, mut rx) = session_connection(&registry).await;
+        registry.join_channel(&id, "room:lobby").await;
+
+        registry.broadcast_to_channel("room:lobby", "one").await;
+        assert_eq!(texts(&mut rx), [r#"{"data":"one","seq":1}"#]);
+
+        assert_eq!(registry.detach(&id, 0).await, SessionDrop::Waiting);
+        registry.broadcast_to_channel("room:lobby", "two").await;
+        registry.send_to(&id, "three").await.unwrap();
+        assert!(
+            texts(&mut rx).is_empty(),
+            "nothing goes to a dropped socket"
+        );
+
+        let (tx, mut resumed_rx) = tokio::sync::mpsc::channel(64);
+        let resumed = registry.resume(&token, "/ws/chat", 1, Arc::new(tx)).await;
+        assert_eq!(resumed, Some((id, 1)));
+        assert_eq!(
+            texts(&mut resumed_rx),
+            [
+                WebSocketRegistry::build_session_frame(&token, true, false),
+                r#"{"data":"two","seq":2}"#.to_string(),
+                r#"{"data":"three","seq":3}"#.to_string(),
+            ]
+        );
+
+        registry.broadcast_all("four").await;
+        assert_eq!(texts(&mut resumed_rx), [r#"{"data":"four","seq":4}"#]);
+        assert!(!registry.expire(&id, 1).await, "an attached session stays");
+    }
+
+    #[tokio::test]
+    async fn test_session_reports_frames_the_outbox_no_longer_holds() {
+        let registry = WebSocketRegistry::new();
+        let (id, token, _rx) = session_connection(&registry).await;
+        registry.detach(&id, 0).await;
+        for i in 0..DEFAULT_OUTBOX_SIZE + 10 {
+            registry.send_to(&id, &i.to_string()).await.unwrap();
+        }
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(DEFAULT_OUTBOX_SIZE + 1);
+        registry.resume(&token, "/ws/chat", 0, Arc::new(tx)).await;
+        let frames = texts(&mut rx);
+        assert_eq!(
+            frames[0],
+            WebSocketRegistry::build_session_frame(&token, true, true)
+        );
+        assert_eq!(frames.len(), DEFAULT_OUTBOX_SIZE + 1);
+        assert_eq!(frames[1], r#"{"data":"10","seq":11}"#);
+    }
+
+    #[tokio::test]
+    async fn test_session_resume_takes_any_client_seq() {
+        let registry = WebSocketRegistry::new();
+        let (id, token, _rx) = session_connection(&registry).await;
+        registry.detach(&id, 0).await;
+        registry.send_to(&id, "one").await.unwrap();
+
+        // `soli_seq` comes from the client, so it can be anything a u64 holds.
+        let (tx, mut rx) = tokio::sync::mpsc::channel(64);
+        let resumed = registry
+            .resume(&token, "/ws/chat", u64::MAX, Arc::new(tx))
+            .await;
+        assert_eq!(resumed, Some((id, 1)));
+        assert_eq!(
+            texts(&mut rx),
+            [WebSocketRegistry::build_session_frame(&token, true, false)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_resume_rules() {
+        let registry = WebSocketRegistry::new();
+        let (id, token, _rx) = session_connection(&registry).await;
+        let mut receivers = Vec::new();
+        let mut sender = || {
+            let (tx, rx) = tokio::sync::mpsc::channel(8);
+            receivers.push(rx);
+            Arc::new(tx)
+        };
+
+        assert_eq!(registry.resume("nope", "/ws/chat", 0, sender()).await, None);
+        assert_eq!(
+            registry.resume(&token, "/ws/other", 0, sender()).await,
+            None
+        );
+
+        // The socket a resume replaced can't detach its successor.
+        registry
+            .resume(&token, "/ws/chat", 0, sender())
+            .await
+            .unwrap();
+        assert_eq!(registry.detach(&id, 0).await, SessionDrop::Replaced);
+
+        // Expiry only takes a session still waiting since that drop.
+        assert_eq!(registry.detach(&id, 1).await, SessionDrop::Waiting);
+        assert!(registry.expire(&id, 1).await);
+        assert_eq!(registry.resume(&token, "/ws/chat", 0, sender()).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_closed_session_is_not_resumable() {
+        let registry = WebSocketRegistry::new();
+        let (id, token, _rx) = session_connection(&registry).await;
+        registry.close(&id, "bye").await;
+        assert_eq!(registry.detach(&id, 0).await, SessionDrop::Ended);
+        let sender = Arc::new(tokio::sync::mpsc::channel(8).0);
+        assert_eq!(registry.resume(&token, "/ws/chat", 0, sender).await, None);
+    }
+
     // ========== Room/Channel Tests ==========
 
     #[tokio::test]
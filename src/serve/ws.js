@@ -0,0 +1,126 @@
+// Soli resumable WebSocket client, served at `/__soli/ws.js`.
+//
+//   var socket = new SoliSocket("/ws/chat");
+//   socket.onmessage = function (event) { render(JSON.parse(event.data)); };
+//   socket.onresume = function (info) { if (info.lost) reloadHistory(); };
+//   socket.send(JSON.stringify({ text: "hi" }));
+//
+// Shaped like `WebSocket` (`onopen`, `onmessage`, `onclose`, `send`, `close`),
+// but a socket that drops without a close reconnects with backoff and resumes
+// its server session: the server replays the frames sent in between, so a
+// flaky mobile connection doesn't lose messages. `onresume` fires after each
+// resume; `lost` is true when more was missed than the server kept, and
+// `resumed` is false when the session had expired and a fresh one began.
+// Messages sent while offline are queued and go out on reconnect.
+(function () {
+  "use strict";
+
+  if (window.SoliSocket) return;
+
+  var MAX_QUEUED = 256;
+
+  function SoliSocket(path, options) {
+    options = options || {};
+    this.path = path;
+    this.minDelay = options.minDelay || 500;
+    this.maxDelay = options.maxDelay || 10000;
+    this.token = null;
+    this.lastSeq = 0;
+    this.queue = [];
+    this.attempts = 0;
+    this.closed = false;
+    this.socket = null;
+    this.onopen = null;
+    this.onmessage = null;
+    this.onresume = null;
+    this.onclose = null;
+    this.connect();
+  }
+
+  SoliSocket.prototype.url = function () {
+    var base = this.path;
+    if (!/^wss?:/.test(base)) {
+      var scheme = location.protocol === "https:" ? "wss:" : "ws:";
+      base = scheme + "//" + location.host + base;
+    }
+    var query = this.token
+      ? "soli_session=" + encodeURIComponent(this.token) + "&soli_seq=" + this.lastSeq
+      : "soli_session=new";
+    return base + (base.indexOf("?") === -1 ? "?" : "&") + query;
+  };
+
+  SoliSocket.prototype.connect = function () {
+    var self = this;
+    var socket = new WebSocket(this.url());
+    this.socket = socket;
+
+    socket.onmessage = function (event) {
+      var frame;
+      try {
+        frame = JSON.parse(event.data);
+      } catch (e) {
+        return self.deliver(event.data);
+      }
+      if (frame && typeof frame.seq === "number") {
+        // A replay can overlap what already arrived before the drop.
+        if (frame.seq <= self.lastSeq) return;
+        self.lastSeq = frame.seq;
+        return self.deliver(frame.data);
+      }
+      if (frame && frame.event === "session" && frame.payload) {
+        return self.started(frame.payload);
+      }
+      self.deliver(event.data);
+    };
+
+    socket.onclose = function (event) {
+      if (self.socket !== socket) return;
+      self.socket = null;
+      // 1000 is a close the server (or this client) asked for: don't resume.
+      if (self.closed || event.code === 1000) {
+        self.closed = true;
+        if (self.onclose) self.onclose(event);
+        return;
+      }
+      var delay = Math.min(self.maxDelay, self.minDelay * Math.pow(2, self.attempts));
+      self.attempts += 1;
+      setTimeout(function () {
+        if (!self.closed) self.connect();
+      }, delay * (0.5 + Math.random() / 2));
+    };
+  };
+
+  SoliSocket.prototype.started = function (session) {
+    var first = this.token === null;
+    if (!session.resumed) this.lastSeq = 0;
+    this.token = session.token;
+    this.attempts = 0;
+    while (this.queue.length && this.socket && this.socket.readyState === 1) {
+      this.socket.send(this.queue.shift());
+    }
+    if (first) {
+      if (this.onopen) this.onopen();
+    } else if (this.onresume) {
+      this.onresume({ resumed: !!session.resumed, lost: !!session.lost || !session.resumed });
+    }
+  };
+
+  SoliSocket.prototype.deliver = function (data) {
+    if (this.onmessage) this.onmessage({ data: data });
+  };
+
+  SoliSocket.prototype.send = function (data) {
+    if (this.socket && this.socket.readyState === 1 && this.token !== null) {
+      this.socket.send(data);
+    } else if (this.queue.length < MAX_QUEUED) {
+      this.queue.push(data);
+    }
+  };
+
+  SoliSocket.prototype.close = function () {
+    this.closed = true;
+    if (this.socket) this.socket.close(1000);
+  };
+
+  window.SoliSocket = SoliSocket;
+})();
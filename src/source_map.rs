@@ -0,0 +1,57 @@
+//! Synthetic sources: the Soli code the runtime embeds as Rust strings (the
+//! routing DSL, the jobs, events, export and mailer preludes, ...).
+//!
+//! Each is registered under a `@soli/<name>.sl` path and run with that path
+//! as its source path, so the functions it defines carry it into stack
+//! frames. A failure inside `resources(...)` then reads
+//! `resources at @soli/routes_dsl.sl:3` rather than borrowing the location
+//! of whichever file happened to be loading, and the dev error page can show
+//! the code by looking the path up here.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{LazyLock, RwLock};
+
+/// First component of every synthetic source path.
+pub const SYNTHETIC_ROOT: &str = "@soli";
+
+static SOURCES: LazyLock<RwLock<HashMap<String, &'static str>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Register `source` as `@soli/<name>.sl` and return that path.
+/// Re-registering a name replaces its source.
+pub fn register(name: &str, source: &'static str) -> String {
+    let path = format!("{}/{}.sl", SYNTHETIC_ROOT, name);
+    if let Ok(mut sources) = SOURCES.write() {
+        sources.insert(path.clone(), source);
+    }
+    path
+}
+
+/// The source registered under `path`, if it is a synthetic one.
+pub fn get(path: &str) -> Option<&'static str> {
+    if !is_synthetic(Path::new(path)) {
+        return None;
+    }
+    SOURCES.read().ok()?.get(path).copied()
+}
+
+/// Whether `path` names a synthetic source rather than a file on disk.
+pub fn is_synthetic(path: &Path) -> bool {
+    path.starts_with(SYNTHETIC_ROOT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registered_sources_are_found_by_path() {
+        let path = register("source_map_test", "fn f() {}\n");
+        assert_eq!(path, "@soli/source_map_test.sl");
+        assert_eq!(get(&path), Some("fn f() {}\n"));
+        assert!(is_synthetic(Path::new(&path)));
+        assert_eq!(get("@soli/never_registered.sl"), None);
+        assert!(!is_synthetic(Path::new("app/@soli/x.sl")));
+    }
+}
@@ -55,6 +55,12 @@ fn get_builtins_rc() -> Rc<RefCell<Environment>> {
     })
 }
 
+/// Every name the shared builtins env defines — what a template can call or
+/// read without a local. `soli lint` uses it to tell a typo from a helper.
+pub(crate) fn builtin_names() -> Vec<String> {
+    get_builtins_rc().borrow().get_var_names()
+}
+
 /// Drop the cached builtins env so the next template render rebuilds it.
 /// Called on helper hot reload so updated `app/helpers/*.sl` definitions
 /// take effect, and on routes hot reload so updated `<name>_path` /
@@ -298,7 +304,7 @@ pub fn translate_expr(expr: &Expr) -> ExprKind {
                 .collect(),
         ),
 
-        Expr::Var(name) => ExprKind::Variable(name.clone()),
+        Expr::Var(name) => ExprKind::variable(name.clone()),
 
         Expr::Field(base, field) => ExprKind::Member {
             object: boxed(translate_expr(base)),
@@ -375,7 +381,7 @@ pub fn translate_expr(expr: &Expr) -> ExprKind {
         },
 
         Expr::Call(name, args) => ExprKind::Call {
-            callee: boxed(ExprKind::Variable(name.clone())),
+            callee: boxed(ExprKind::variable(name.clone())),
             arguments: args
                 .iter()
                 .map(|a| {
@@ -385,7 +391,7 @@ pub fn translate_expr(expr: &Expr) -> ExprKind {
         },
 
         Expr::Assign(name, value) => ExprKind::Assign {
-            target: boxed(ExprKind::Variable(name.clone())),
+            target: boxed(ExprKind::variable(name.clone())),
             value: boxed(translate_expr(value)),
         },
 
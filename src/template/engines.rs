@@ -0,0 +1,111 @@
+//! Template engines, registered by file extension.
+//!
+//! A view's extension picks the engine that turns its source into the
+//! template AST and post-processes the rendered output. The built-ins are
+//! `.slv` and `.erb` (ERB-style tags) and `.md` (ERB tags, then markdown to
+//! HTML). Embedders and packages add their own with [`register_engine`] —
+//! e.g. a Jinja-like syntax that translates `{{ x }}` into the same nodes, or
+//! a builder that wraps a pure Soli expression in a single output node.
+//!
+//! Registration order is resolution order: `render("users/show")` tries
+//! `show.html.<ext>` then `show.<ext>` for each engine in turn, so a project
+//! that has both `show.html.slv` and `show.html.jinja` gets the `.slv` one.
+
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use lazy_static::lazy_static;
+
+use super::markdown_to_html_safe_urls;
+use super::parser::{parse_template, TemplateNode};
+
+/// Turns view source into template nodes and finishes the rendered output.
+pub trait TemplateEngine: Send + Sync {
+    /// Parse `source` into the nodes the renderer evaluates. The default is
+    /// the ERB-style parser shared by `.slv` and `.erb`.
+    fn parse(&self, source: &str) -> Result<Vec<TemplateNode>, String> {
+        parse_template(source)
+    }
+
+    /// Post-process the rendered output of one template (before it is
+    /// wrapped in a layout). The default returns it unchanged.
+    fn finish(&self, rendered: String) -> String {
+        rendered
+    }
+}
+
+/// `.slv` / `.erb`: ERB-style tags, output used as-is.
+pub struct ErbEngine;
+
+impl TemplateEngine for ErbEngine {}
+
+/// `.md`: ERB-style tags, then markdown to HTML. Uses the URL-neutralizing
+/// converter — the ERB pass already escaped interpolated data, so this only
+/// has to stop markdown link/image syntax from smuggling a `javascript:` URL.
+pub struct MarkdownEngine;
+
+impl TemplateEngine for MarkdownEngine {
+    fn finish(&self, rendered: String) -> String {
+        markdown_to_html_safe_urls(&rendered)
+    }
+}
+
+lazy_static! {
+    static ref ENGINES: RwLock<Vec<(String, Arc<dyn TemplateEngine>)>> = RwLock::new(vec![
+        (
+            "slv".to_string(),
+            Arc::new(ErbEngine) as Arc<dyn TemplateEngine>
+        ),
+        ("md".to_string(), Arc::new(MarkdownEngine)),
+        ("erb".to_string(), Arc::new(ErbEngine)),
+    ]);
+}
+
+/// Register `engine` for views ending in `.<ext>` (and `.html.<ext>`).
+/// Re-registering an extension replaces its engine and keeps its place in
+/// the resolution order; a new extension resolves after the existing ones.
+pub fn register_engine(ext: &str, engine: Arc<dyn TemplateEngine>) {
+    let ext = ext.trim_start_matches('.').to_string();
+    let mut engines = ENGINES.write().unwrap();
+    match engines.iter_mut().find(|(e, _)| *e == ext) {
+        Some(slot) => slot.1 = engine,
+        None => engines.push((ext, engine)),
+    }
+}
+
+/// Registered extensions, in resolution order.
+pub fn template_extensions() -> Vec<String> {
+    ENGINES
+        .read()
+        .unwrap()
+        .iter()
+        .map(|(ext, _)| ext.clone())
+        .collect()
+}
+
+/// Whether files ending in `.<ext>` are views (used by the hot-reload watcher).
+pub fn is_template_extension(ext: &str) -> bool {
+    ENGINES.read().unwrap().iter().any(|(e, _)| e == ext)
+}
+
+/// Whether `path` is a view, judged by its final extension.
+pub fn is_template_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(is_template_extension)
+}
+
+/// Engine for the template at `path`; extension-less files use ERB.
+pub fn engine_for_path(path: &Path) -> Arc<dyn TemplateEngine> {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .and_then(|ext| {
+            ENGINES
+                .read()
+                .unwrap()
+                .iter()
+                .find(|(e, _)| e == ext)
+                .map(|(_, engine)| engine.clone())
+        })
+        .unwrap_or_else(|| Arc::new(ErbEngine))
+}
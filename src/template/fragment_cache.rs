@@ -0,0 +1,187 @@
+//! Keys for Russian-doll fragment caching.
+//!
+//! A cached `component(..., {"cache": ...})` is stored under a key built from
+//! two parts:
+//!
+//! - **What it shows.** Models contribute `collection/key-updated_at`
+//!   (`instance_cache_key`), so saving or `touch()`ing a record changes every
+//!   key that embeds it. A collection's key covers all of its members, so an
+//!   outer fragment over `posts` misses when one post changes, while the
+//!   inner per-post fragments of the unchanged posts still hit.
+//! - **How it is drawn.** `TemplateCache::component_digest` hashes the
+//!   template's source together with the digests of every partial and
+//!   component it names with a literal (`render "x"`, `component("x")`, …).
+//!   Editing a nested partial busts the fragments of every template that
+//!   includes it, however deep.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::{include_template_name, IncludeKind, TemplateCache};
+use crate::interpreter::value::{Instance, Value};
+use crate::serve::vfs_read_to_string;
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+lazy_static! {
+    /// Include calls with a literal name — the edges of the digest graph.
+    static ref INCLUDE_REF: Regex = Regex::new(
+        r#"\b(render_partial|render|partial|component)\s*\(?\s*["']([A-Za-z0-9_./-]+)["']"#
+    )
+    .unwrap();
+}
+
+fn fnv(h: &mut u64, bytes: &[u8]) {
+    for &b in bytes {
+        *h ^= b as u64;
+        *h = h.wrapping_mul(FNV_PRIME);
+    }
+}
+
+/// `collection/key-updated_at` for a model instance, `collection/new` for an
+/// unsaved one; `None` for instances of non-model classes.
+pub fn instance_cache_key(inst: &Instance) -> Option<String> {
+    if !inst.class.is_model_subclass() {
+        return None;
+    }
+    let collection =
+        crate::interpreter::builtins::model::core::class_name_to_collection(&inst.class.name);
+    let field = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|n| inst.fields.get(*n))
+            .filter(|v| !matches!(v, Value::Null))
+            .map(|v| v.to_string())
+    };
+    Some(match field(&["_key", "id"]) {
+        None => format!("{}/new", collection),
+        Some(key) => match field(&["_updated_at", "updated_at"]) {
+            Some(stamp) => format!("{}/{}-{}", collection, key, stamp),
+            None => format!("{}/{}", collection, key),
+        },
+    })
+}
+
+/// Cache key for an explicit `"cache": value` — a model, an array of keys
+/// and models (joined with `/`), or any scalar.
+pub fn value_cache_key(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.to_string(),
+        Value::Instance(inst) => {
+            let inst = inst.borrow();
+            instance_cache_key(&inst).unwrap_or_else(|| {
+                format!(
+                    "{}/{:016x}",
+                    inst.class.name,
+                    super::response_cache::data_signature(value)
+                )
+            })
+        }
+        Value::Array(items) => items
+            .borrow()
+            .iter()
+            .map(value_cache_key)
+            .collect::<Vec<_>>()
+            .join("/"),
+        Value::Hash(_) => format!("{:016x}", super::response_cache::data_signature(value)),
+        other => other.to_string(),
+    }
+}
+
+impl TemplateCache {
+    /// Digest of component `name`: its source plus, recursively, the
+    /// digests of the templates it includes by literal name. Memoized until
+    /// the cache is cleared.
+    pub fn component_digest(&self, name: &str) -> Result<u64, String> {
+        let path =
+            self.resolve_template_path(&include_template_name(name, IncludeKind::Component))?;
+        Ok(self.template_digest(&path, &mut HashSet::new()))
+    }
+
+    fn template_digest(&self, path: &Path, visiting: &mut HashSet<PathBuf>) -> u64 {
+        if let Some(digest) = self.digests.read().ok().and_then(|d| d.get(path).copied()) {
+            return digest;
+        }
+        // A cycle (a partial that includes itself) contributes nothing more.
+        if !visiting.insert(path.to_path_buf()) {
+            return 0;
+        }
+        let source = vfs_read_to_string(&path.to_string_lossy()).unwrap_or_default();
+        let mut h = FNV_OFFSET;
+        fnv(&mut h, source.as_bytes());
+        for cap in INCLUDE_REF.captures_iter(&source) {
+            let kind = if &cap[1] == "component" {
+                IncludeKind::Component
+            } else {
+                IncludeKind::Partial
+            };
+            // An unresolvable name (dynamic suffix, typo) is left to fail at
+            // render time; it just doesn't feed the digest.
+            if let Ok(child) = self.resolve_template_path(&include_template_name(&cap[2], kind)) {
+                let child = self.template_digest(&child, visiting);
+                h ^= child;
+                h = h.wrapping_mul(FNV_PRIME);
+            }
+        }
+        visiting.remove(path);
+        if let Ok(mut digests) = self.digests.write() {
+            digests.insert(path.to_path_buf(), h);
+        }
+        h
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn editing_a_nested_partial_changes_the_outer_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let views = dir.path().join("views");
+        fs::create_dir_all(views.join("components")).unwrap();
+        fs::create_dir_all(views.join("posts")).unwrap();
+        fs::write(
+            views.join("components").join("feed.html.slv"),
+            "<ul><%- render \"posts/row\" %></ul>",
+        )
+        .unwrap();
+        fs::write(views.join("posts").join("_row.html.slv"), "<li>a</li>").unwrap();
+        fs::write(views.join("components").join("other.html.slv"), "x").unwrap();
+
+        let cache = TemplateCache::new(&views);
+        let feed = cache.component_digest("feed").unwrap();
+        let other = cache.component_digest("other").unwrap();
+        assert_ne!(feed, other);
+        assert_eq!(cache.component_digest("feed"), Ok(feed));
+
+        fs::write(views.join("posts").join("_row.html.slv"), "<li>b</li>").unwrap();
+        cache.clear();
+        assert_ne!(cache.component_digest("feed"), Ok(feed));
+    }
+
+    #[test]
+    fn self_including_partial_terminates() {
+        let dir = tempfile::tempdir().unwrap();
+        let views = dir.path().join("views");
+        fs::create_dir_all(&views).unwrap();
+        let tree = views.join("_tree.html.slv");
+        fs::write(&tree, "<%- render \"tree\" %>").unwrap();
+        let cache = TemplateCache::new(&views);
+        cache.template_digest(&tree, &mut HashSet::new());
+    }
+
+    #[test]
+    fn value_keys_join_arrays_and_pass_strings_through() {
+        let items = Value::Array(std::rc::Rc::new(std::cell::RefCell::new(vec![
+            Value::String("v1".into()),
+            Value::Int(3),
+        ])));
+        assert_eq!(value_cache_key(&items), "v1/3");
+    }
+}
@@ -11,6 +11,8 @@
 pub mod content_store;
 pub mod core_eval;
 pub mod declared_props;
+pub mod engines;
+pub mod fragment_cache;
 pub mod helpers;
 pub mod layout;
 pub mod parser;
@@ -25,7 +27,6 @@ use std::time::{Instant, SystemTime};
 
 use crate::interpreter::value::Value;
 use crate::serve::{vfs_exists, vfs_read_to_string};
-use parser::parse_template;
 use renderer::{render_nodes_with_path, render_with_interpreter};
 use std::rc::Rc;
 
@@ -71,6 +72,9 @@ pub struct TemplateCache {
     /// Cached path resolutions (template_name -> resolved_path).
     /// Arc so cache hits are pointer increments, not heap clones.
     path_cache: RwLock<HashMap<String, Arc<PathBuf>>>,
+    /// Fragment-cache digests (template path -> digest of it and its
+    /// includes). See `fragment_cache`.
+    digests: RwLock<HashMap<PathBuf, u64>>,
 }
 
 /// Which kind of nested include is being rendered — selects the path-resolution
@@ -89,6 +93,7 @@ impl TemplateCache {
             views_dir: views_dir.into(),
             cache: RwLock::new(HashMap::new()),
             path_cache: RwLock::new(HashMap::new()),
+            digests: RwLock::new(HashMap::new()),
         }
     }
 
@@ -194,15 +199,8 @@ impl TemplateCache {
             Some(&template_path_str),
         )?;
 
-        // If the template is a markdown file, convert to HTML. Use the
-        // URL-neutralizing converter: the ERB pass above already escaped
-        // interpolated data, so this only needs to stop markdown link/image
-        // syntax from smuggling a `javascript:` URL past that escaping.
-        let content = if is_markdown_template(template_path) {
-            markdown_to_html_safe_urls(&content)
-        } else {
-            content
-        };
+        // Let the view's engine finish its output (markdown becomes HTML).
+        let content = engines::engine_for_path(template_path).finish(content);
 
         // Wrap the view's own content in dev-bar marker comments so the
         // hover overlay can find this template's region in the page. Only
@@ -252,6 +250,119 @@ impl TemplateCache {
         result
     }
 
+    /// Render a template in pieces for a streamed response. `emit` first gets
+    /// the layout up to its `<%= yield %>`, before the view is rendered, then
+    /// the view, then the rest of the layout; it returns false once the client
+    /// is gone, which stops the render. The head renders before the view, so
+    /// a `yield "name"` there sees none of the view's `content_for` blocks.
+    ///
+    /// A page without a layout, or whose layout only yields inside a block,
+    /// can't be split: `emit` gets an empty head and then the whole page.
+    pub fn render_streaming(
+        &self,
+        template_name: &str,
+        data: &Value,
+        layout: Option<Option<&str>>,
+        emit: &mut dyn FnMut(String) -> bool,
+    ) -> Result<(), String> {
+        let template_path = self.resolve_template_path(template_name)?;
+        let layout_name = match layout {
+            Some(name) => name,
+            None if template_name.contains("/_") || template_name.starts_with('_') => None,
+            None => Some("application"),
+        };
+        let layout_path = layout_name.and_then(|name| {
+            let name = name
+                .trim_start_matches("layouts/")
+                .trim_start_matches("layouts");
+            self.resolve_template_path(&format!("layouts/{}", name))
+                .ok()
+        });
+        let layout_nodes = match &layout_path {
+            Some(path) => Some(self.get_or_load_template(path)?),
+            None => None,
+        };
+        let split = layout_nodes.as_ref().and_then(|nodes| {
+            nodes
+                .iter()
+                .position(|node| matches!(node, parser::TemplateNode::Yield(None)))
+        });
+        let (Some(layout_nodes), Some(layout_path), Some(split)) =
+            (layout_nodes, layout_path, split)
+        else {
+            if emit(String::new()) {
+                let page =
+                    self.render_uncached(template_name, data, layout, &template_path, None)?;
+                emit(inject_page_scripts(page));
+            }
+            return Ok(());
+        };
+
+        let _guard = crate::interpreter::executor::enter_template_lenient_vars();
+        let _content_frame = content_store::ensure_frame();
+        let _span = crate::serve::span_log::SpanGuard::start(
+            template_name,
+            crate::serve::span_log::SpanKind::View,
+        );
+        let render_start = crate::metrics::metrics_enabled().then(Instant::now);
+
+        let partial_renderer =
+            |name: &str, ctx: &Value| -> Result<String, String> { self.render_partial(name, ctx) };
+        let mut interpreter = core_eval::create_template_interpreter(data);
+        let layout_path_str = layout_path.to_string_lossy();
+        let render_layout_part = |interpreter: &mut crate::interpreter::executor::Interpreter,
+                                  nodes: &[parser::TemplateNode]|
+         -> Result<String, String> {
+            let mut output = String::new();
+            renderer::render_walker(
+                interpreter,
+                nodes,
+                data,
+                Some(&partial_renderer),
+                Some(&layout_path_str),
+                &mut output,
+                renderer::YieldMode::Layout { content: "" },
+            )?;
+            Ok(output)
+        };
+
+        // Everything sent so far, so the page scripts can be placed in the
+        // tail the same way `html_response` places them in a whole page.
+        let mut sent = render_layout_part(&mut interpreter, &layout_nodes[..split])?;
+        if !emit(sent.clone()) {
+            return Ok(());
+        }
+
+        let nodes = self.get_or_load_template(&template_path)?;
+        let template_path_str = template_path.to_string_lossy();
+        let content = render_with_interpreter(
+            &mut interpreter,
+            &nodes,
+            data,
+            Some(&partial_renderer),
+            Some(&template_path_str),
+        )?;
+        let content = engines::engine_for_path(&template_path).finish(content);
+        sent.push_str(&content);
+        if !emit(content) {
+            return Ok(());
+        }
+
+        let tail = render_layout_part(&mut interpreter, &layout_nodes[split + 1..])?;
+        let page = inject_page_scripts(format!("{}{}", sent, tail));
+        let tail = match page.strip_prefix(sent.as_str()) {
+            Some(rest) => rest.to_string(),
+            // A script landed in the part already sent; ship the tail as is.
+            None => tail,
+        };
+        emit(tail);
+
+        if let Some(render_start) = render_start {
+            crate::metrics::Metrics::global().record_template_render(render_start.elapsed());
+        }
+        Ok(())
+    }
+
     /// Render a partial template (no layout). Partials live at `_name` (or
     /// `dir/_name`), *except* `components/` paths which stay clean.
     pub fn render_partial(&self, name: &str, data: &Value) -> Result<String, String> {
@@ -275,36 +386,7 @@ impl TemplateCache {
         data: &Value,
         kind: IncludeKind,
     ) -> Result<String, String> {
-        // Resolve the on-disk name for this include kind.
-        let resolved_name = match kind {
-            IncludeKind::Component => {
-                // A `/`- or `.`-bearing name is app/views-relative and used
-                // verbatim; a bare name resolves under components/.
-                if name.contains('/') || name.contains('.') {
-                    name.to_string()
-                } else {
-                    format!("components/{}", name)
-                }
-            }
-            IncludeKind::Partial => {
-                // Partials start with `_`, except components/ paths (kept clean
-                // so the block form's `components/<name>` resolves like the
-                // function-call `component()` helper).
-                if name.starts_with("components/") || name.contains("/components/") {
-                    name.to_string()
-                } else if name.contains('/') {
-                    // e.g., "users/card" -> "users/_card"
-                    let parts: Vec<&str> = name.rsplitn(2, '/').collect();
-                    if parts.len() == 2 {
-                        format!("{}/_{}", parts[1], parts[0])
-                    } else {
-                        format!("_{}", name)
-                    }
-                } else {
-                    format!("_{}", name)
-                }
-            }
-        };
+        let resolved_name = include_template_name(name, kind);
 
         // Anything resolving under components/ is a component for the dev bar,
         // regardless of which syntax reached us (the block form arrives here as
@@ -388,14 +470,7 @@ impl TemplateCache {
             }
         }
 
-        // If the include is a markdown file, convert to HTML. URL-neutralizing
-        // converter — the ERB pass already escaped interpolated data (see the
-        // matching note in `render`).
-        let result = if is_markdown_template(&template_path) {
-            markdown_to_html_safe_urls(&content)
-        } else {
-            content
-        };
+        let result = engines::engine_for_path(&template_path).finish(content);
         let result = if let Some(id) = view_id {
             wrap_dev_marker(marker, id, name, &result)
         } else {
@@ -556,22 +631,18 @@ impl TemplateCache {
             return Err(format!("Template not found in {}", dir.display()));
         }
 
-        let extensions = [
-            ".html.slv",
-            ".slv",
-            ".html.md",
-            ".md",
-            ".html.erb",
-            ".erb",
-            "",
-        ];
-
-        for ext in extensions {
-            let path = if ext.is_empty() {
-                dir.join(name)
-            } else {
-                dir.join(format!("{}{}", name, ext))
-            };
+        let mut candidates: Vec<PathBuf> = engines::template_extensions()
+            .iter()
+            .flat_map(|ext| {
+                [
+                    dir.join(format!("{}.html.{}", name, ext)),
+                    dir.join(format!("{}.{}", name, ext)),
+                ]
+            })
+            .collect();
+        candidates.push(dir.join(name));
+
+        for path in candidates {
             let path_str = path.to_string_lossy().to_string();
             if vfs_exists(&path_str) {
                 return Ok(path);
@@ -609,8 +680,11 @@ impl TemplateCache {
         // tracking, and leave the user hunting a non-existent bug in the
         // controller. Stamping the view path on the message keeps the
         // diagnostic pointed at the offending template.
-        let nodes =
-            Arc::new(parse_template(&source).map_err(|e| format!("{} in {}", e, path.display()))?);
+        let mut nodes = engines::engine_for_path(path)
+            .parse(&source)
+            .map_err(|e| format!("{} in {}", e, path.display()))?;
+        parser::fold_constants(&mut nodes);
+        let nodes = Arc::new(nodes);
 
         // Update cache (with eviction if cache is too large)
         if let Ok(mut cache) = self.cache.write() {
@@ -637,6 +711,16 @@ impl TemplateCache {
         if let Ok(mut c) = self.path_cache.write() {
             c.clear();
         }
+        if let Ok(mut c) = self.digests.write() {
+            c.clear();
+        }
+    }
+
+    /// Number of parsed templates and resolved template paths held.
+    pub fn sizes(&self) -> (usize, usize) {
+        let templates = self.cache.read().map(|c| c.len()).unwrap_or(0);
+        let paths = self.path_cache.read().map(|c| c.len()).unwrap_or(0);
+        (templates, paths)
     }
 
     /// Check if any tracked templates have changed.
@@ -660,44 +744,46 @@ impl TemplateCache {
     }
 }
 
+/// On-disk template name for an include: partials get a `_` prefix on their
+/// last segment; components resolve under `components/` verbatim.
+fn include_template_name(name: &str, kind: IncludeKind) -> String {
+    match kind {
+        IncludeKind::Component => {
+            // A `/`- or `.`-bearing name is app/views-relative and used
+            // verbatim; a bare name resolves under components/.
+            if name.contains('/') || name.contains('.') {
+                name.to_string()
+            } else {
+                format!("components/{}", name)
+            }
+        }
+        IncludeKind::Partial => {
+            // Partials start with `_`, except components/ paths (kept clean
+            // so the block form's `components/<name>` resolves like the
+            // function-call `component()` helper).
+            if name.starts_with("components/") || name.contains("/components/") {
+                name.to_string()
+            } else if name.contains('/') {
+                // e.g., "users/card" -> "users/_card"
+                let parts: Vec<&str> = name.rsplitn(2, '/').collect();
+                if parts.len() == 2 {
+                    format!("{}/_{}", parts[1], parts[0])
+                } else {
+                    format!("_{}", name)
+                }
+            } else {
+                format!("_{}", name)
+            }
+        }
+    }
+}
+
 /// Create a response hash for rendered HTML content.
 pub fn html_response(body: String, status: i64) -> Value {
     use crate::interpreter::value::{HashKey, HashPairs};
     use ahash::RandomState as AHasher;
 
-    // Inject live reload script if enabled
-    let body = if crate::serve::live_reload::is_live_reload_enabled() {
-        crate::serve::live_reload::inject_live_reload_script(&body)
-    } else {
-        body
-    };
-
-    // Inject the instant-navigation script (body swap + pushState) unless
-    // disabled via `SOLI_NAV=off`. Nav subsumes hover prefetching with its own
-    // in-memory cache (a fetch() can't consume `<link rel="prefetch">`
-    // entries), so the two scripts are mutually exclusive: prefetch.js is only
-    // injected when nav is off, restoring the previous behavior unchanged.
-    let nav_on = crate::serve::nav::is_enabled();
-    let body = if nav_on {
-        crate::serve::nav::inject_nav_tag(&body)
-    } else {
-        body
-    };
-    let body = if !nav_on && crate::serve::prefetch::is_enabled() {
-        crate::serve::prefetch::inject_prefetch_tag(&body)
-    } else {
-        body
-    };
-
-    // Native bridge: injected only for pages that called `native_channel(...)`,
-    // which is what puts the `soli-native` meta tag in the HTML. Pages that
-    // want nothing from the shell get no script and open no stream.
-    let body = crate::serve::native::inject_native_tag(&body);
-
-    // Camera preview + scan loop, for pages carrying a `data-soli-camera`
-    // element. Independent of the native bridge: a page can show a camera
-    // without wanting notifications, and vice versa.
-    let body = crate::serve::camera::inject_camera_tag(&body);
+    let body = inject_page_scripts(body);
 
     // Compute a content-derived ETag so the shipped hover-prefetch feature
     // actually delivers "instant navigation": Chrome reuses the prefetched
@@ -732,6 +818,44 @@ pub fn html_response(body: String, status: i64) -> Value {
     Value::Hash(Rc::new(RefCell::new(result)))
 }
 
+/// Add the dev and navigation scripts a rendered HTML page gets before its
+/// `</body>`.
+pub(crate) fn inject_page_scripts(body: String) -> String {
+    // Inject live reload script if enabled
+    let body = if crate::serve::live_reload::is_live_reload_enabled() {
+        crate::serve::live_reload::inject_live_reload_script(&body)
+    } else {
+        body
+    };
+
+    // Inject the instant-navigation script (body swap + pushState) unless
+    // disabled via `SOLI_NAV=off`. Nav subsumes hover prefetching with its own
+    // in-memory cache (a fetch() can't consume `<link rel="prefetch">`
+    // entries), so the two scripts are mutually exclusive: prefetch.js is only
+    // injected when nav is off, restoring the previous behavior unchanged.
+    let nav_on = crate::serve::nav::is_enabled();
+    let body = if nav_on {
+        crate::serve::nav::inject_nav_tag(&body)
+    } else {
+        body
+    };
+    let body = if !nav_on && crate::serve::prefetch::is_enabled() {
+        crate::serve::prefetch::inject_prefetch_tag(&body)
+    } else {
+        body
+    };
+
+    // Native bridge: injected only for pages that called `native_channel(...)`,
+    // which is what puts the `soli-native` meta tag in the HTML. Pages that
+    // want nothing from the shell get no script and open no stream.
+    let body = crate::serve::native::inject_native_tag(&body);
+
+    // Camera preview + scan loop, for pages carrying a `data-soli-camera`
+    // element. Independent of the native bridge: a page can show a camera
+    // without wanting notifications, and vice versa.
+    crate::serve::camera::inject_camera_tag(&body)
+}
+
 /// Compute a deterministic ETag for an HTML response body using FNV-1a 64-bit.
 ///
 /// Deterministic within AND across processes — no random seed — so a prefetch
@@ -777,12 +901,6 @@ pub fn etag_for_body(body: &str) -> String {
     format!("W/\"{:016x}\"", hash)
 }
 
-/// Check if a template path is a markdown file.
-fn is_markdown_template(path: &Path) -> bool {
-    let s = path.to_string_lossy();
-    s.ends_with(".md")
-}
-
 /// Wrap rendered template output in HTML comment markers used by the dev
 /// bar's hover overlay. Only emitted when `view_log` is enabled (i.e.
 /// dev mode); production HTML is unchanged.
@@ -1084,16 +1202,13 @@ mod tests {
     }
 
     #[test]
-    fn test_is_markdown_template() {
-        assert!(is_markdown_template(Path::new("app/views/docs/index.md")));
-        assert!(is_markdown_template(Path::new(
-            "app/views/docs/index.html.md"
-        )));
-        assert!(!is_markdown_template(Path::new(
-            "app/views/docs/index.html.slv"
-        )));
-        assert!(!is_markdown_template(Path::new("app/views/docs/index.slv")));
-        assert!(!is_markdown_template(Path::new("app/views/docs/index.erb")));
+    fn test_engine_for_path_picks_markdown_by_extension() {
+        let md = engines::engine_for_path(Path::new("app/views/docs/index.html.md"));
+        assert!(md.finish("# Hi".to_string()).contains("<h1>Hi</h1>"));
+        for view in ["index.html.slv", "index.slv", "index.erb", "index"] {
+            let engine = engines::engine_for_path(Path::new(view));
+            assert_eq!(engine.finish("# Hi".to_string()), "# Hi");
+        }
     }
 
     #[test]
@@ -1328,6 +1443,28 @@ mod tests {
         assert!(resolved.to_string_lossy().ends_with(".html.slv"));
     }
 
+    #[test]
+    fn test_registered_engine_resolves_and_renders() {
+        struct Braces;
+        impl engines::TemplateEngine for Braces {
+            fn parse(&self, source: &str) -> Result<Vec<parser::TemplateNode>, String> {
+                let erb = source.replace("{{", "<%=").replace("}}", "%>");
+                parser::parse_template(&erb)
+            }
+        }
+        engines::register_engine("braces", Arc::new(Braces));
+        assert!(engines::is_template_file(Path::new("views/x.html.braces")));
+
+        let dir = tempfile::tempdir().unwrap();
+        let views = dir.path().join("views");
+        fs::create_dir_all(&views).unwrap();
+        fs::write(views.join("hello.html.braces"), "<p>{{ 1 + 2 }}</p>").unwrap();
+
+        let cache = TemplateCache::new(&views);
+        let result = cache.render("hello", &Value::Null, Some(None)).unwrap();
+        assert_eq!(result, "<p>3</p>");
+    }
+
     #[test]
     fn test_render_md_template() {
         let dir = tempfile::tempdir().unwrap();
@@ -1439,6 +1576,72 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_streaming_sends_the_layout_head_before_the_view() {
+        let dir = tempfile::tempdir().unwrap();
+        let views = dir.path().join("views");
+        fs::create_dir_all(views.join("layouts")).unwrap();
+        fs::write(
+            views.join("layouts").join("application.html.slv"),
+            "<head><%= yield \"head\" %></head><body><%= yield %></body>",
+        )
+        .unwrap();
+        fs::write(
+            views.join("page.html.slv"),
+            "<% content_for \"head\" do %><script></script><% end %><h1>Page</h1>",
+        )
+        .unwrap();
+
+        let cache = TemplateCache::new(&views);
+        let mut chunks = Vec::new();
+        cache
+            .render_streaming("page", &Value::Null, None, &mut |chunk| {
+                chunks.push(chunk);
+                true
+            })
+            .unwrap();
+        // The head goes out before the view has captured anything; the page
+        // scripts land in the tail.
+        assert_eq!(chunks[..2], ["<head></head><body>", "<h1>Page</h1>"]);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks[2].ends_with("</body>"));
+
+        // A disconnected client stops the render after the head.
+        let mut sent = 0;
+        cache
+            .render_streaming("page", &Value::Null, None, &mut |_| {
+                sent += 1;
+                false
+            })
+            .unwrap();
+        assert_eq!(sent, 1);
+    }
+
+    #[test]
+    fn render_streaming_sends_an_unsplittable_page_whole() {
+        let dir = tempfile::tempdir().unwrap();
+        let views = dir.path().join("views");
+        fs::create_dir_all(views.join("layouts")).unwrap();
+        fs::write(
+            views.join("layouts").join("application.html.slv"),
+            "<body><% if true %><%= yield %><% end %></body>",
+        )
+        .unwrap();
+        fs::write(views.join("page.html.slv"), "<h1>Page</h1>").unwrap();
+
+        let cache = TemplateCache::new(&views);
+        let mut chunks = Vec::new();
+        cache
+            .render_streaming("page", &Value::Null, None, &mut |chunk| {
+                chunks.push(chunk);
+                true
+            })
+            .unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0], "");
+        assert!(chunks[1].starts_with("<body><h1>Page</h1>"));
+    }
+
     #[test]
     fn content_for_in_partial_registers_in_same_store() {
         let dir = tempfile::tempdir().unwrap();
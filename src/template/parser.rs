@@ -199,6 +199,15 @@ pub enum TemplateNode {
         body: Vec<TemplateNode>,
         line: usize,
     },
+    /// Inline script/style block: `<%- javascript_tag do %> ... <%- end %>`
+    /// (or `style_tag`). The rendered body is wrapped in a `<script>` /
+    /// `<style>` element carrying the request's CSP nonce, and its hash is
+    /// registered for the CSP header.
+    InlineTag {
+        element: &'static str,
+        body: Vec<TemplateNode>,
+        line: usize,
+    },
     /// Form-builder block: `<% form_with(record) do |f| %> ... <% end %>`.
     /// Sugar for binding the builder and wrapping the body in `f.open()` /
     /// `f.close()`. The exprs live behind a Box so the enum stays small.
@@ -254,6 +263,60 @@ pub fn parse_template(source: &str) -> Result<Vec<TemplateNode>, String> {
     parse_tokens(&tokens)
 }
 
+/// Fold the constant expressions in a parsed template (see
+/// `crate::optimize`), once when it is loaded rather than on every render.
+pub(crate) fn fold_constants(nodes: &mut [TemplateNode]) {
+    use crate::optimize::{fold_expr, fold_stmts};
+    for node in nodes {
+        match node {
+            // Only core-language expressions are folded.
+            TemplateNode::Literal(_)
+            | TemplateNode::Yield(_)
+            | TemplateNode::Output { .. }
+            | TemplateNode::CodeBlock { .. } => {}
+            TemplateNode::CoreOutput { expr, .. } => fold_expr(expr),
+            TemplateNode::If {
+                condition,
+                body,
+                else_body,
+                ..
+            } => {
+                fold_expr(condition);
+                fold_constants(body);
+                if let Some(else_body) = else_body {
+                    fold_constants(else_body);
+                }
+            }
+            TemplateNode::For { iterable, body, .. } => {
+                fold_expr(iterable);
+                fold_constants(body);
+            }
+            TemplateNode::ContentFor { body, .. } | TemplateNode::InlineTag { body, .. } => {
+                fold_constants(body)
+            }
+            TemplateNode::FormWith { parts, body, .. } => {
+                fold_expr(&mut parts.builder_expr);
+                fold_expr(&mut parts.open_expr);
+                fold_expr(&mut parts.close_expr);
+                fold_constants(body);
+            }
+            TemplateNode::Component { parts, body, .. } => {
+                fold_expr(&mut parts.name);
+                if let Some(props) = &mut parts.props {
+                    fold_expr(props);
+                }
+                fold_constants(body);
+            }
+            TemplateNode::Partial { context, .. } => {
+                if let Some(context) = context {
+                    fold_expr(context);
+                }
+            }
+            TemplateNode::CoreCodeBlock { stmts, .. } => fold_stmts(stmts),
+        }
+    }
+}
+
 /// Rewrite a Ruby-style block-iteration opener — `xs.each do |x|` or
 /// `xs.each do |x, i|` — into the engine's `for` form (`for x in xs` /
 /// `for x, i in xs`). Returns `None` when the code isn't that shape (a
@@ -442,10 +505,11 @@ fn tokenize(source: &str) -> Result<Vec<Token>, String> {
             } else if tag_content == "end"
                 || form_with_block_parts(&tag_content).is_some()
                 || component_block_parts(&tag_content).is_some()
+                || inline_tag_block_open(&tag_content).is_some()
                 || is_slot_block_opener(&tag_content)
             {
-                // `form_with(...) do |f|`, `component ... do [|c|]`, `c.slot(...) do`
-                // block openers and their `end` read naturally as output tags
+                // `form_with(...) do |f|`, `component ... do [|c|]`, `c.slot(...) do`,
+                // `javascript_tag do` block openers and their `end` read naturally as output tags
                 // (`<%- %>` / `<%= %>`, Rails-style). Normalize them to Code tokens
                 // so the block dispatcher sees them regardless of tag style.
                 tokens.push(Token::Code(tag_content, tag_line));
@@ -496,6 +560,22 @@ fn tokenize(source: &str) -> Result<Vec<Token>, String> {
     Ok(tokens)
 }
 
+/// The expression text of every raw `<%- %>` tag, with its line — for the
+/// `security/raw-request-data` lint. Empty when the template doesn't tokenize.
+pub fn raw_output_snippets(source: &str) -> Vec<(String, usize)> {
+    tokenize(source)
+        .map(|tokens| {
+            tokens
+                .into_iter()
+                .filter_map(|token| match token {
+                    Token::OutputRaw(expr, line) => Some((expr.trim().to_string(), line)),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 /// Extract a line-preserving Soli source string from a `.slv` template: only
 /// the code inside `<% %>` / `<%= %>` / `<%- %>` regions is kept, with the
 /// literal HTML dropped. The linter feeds this to the normal Soli lexer/parser
@@ -512,8 +592,17 @@ fn tokenize(source: &str) -> Result<Vec<Token>, String> {
 /// extract to an empty block; the caller drops `style/empty-block` for
 /// templates so that isn't reported as a false positive.
 pub fn extract_lintable_code(source: &str) -> Result<String, String> {
+    extract_lintable_code_with_lines(source).map(|(code, _)| code)
+}
+
+/// [`extract_lintable_code`], plus the template line each line of the
+/// extracted code comes from (index 0 is the code's first line), so a
+/// position in the code can be mapped back exactly even where two tags
+/// shared a line.
+pub fn extract_lintable_code_with_lines(source: &str) -> Result<(String, Vec<usize>), String> {
     let tokens = tokenize(source)?;
     let mut out = String::new();
+    let mut lines: Vec<usize> = vec![1];
     let mut cur_line: usize = 1;
     let mut line_has_code = false;
 
@@ -527,6 +616,9 @@ pub fn extract_lintable_code(source: &str) -> Result<String, String> {
                     // consumed by the core parser — synthesize `if true` so
                     // the block stays balanced in the extracted source.
                     (std::borrow::Cow::Borrowed("if true"), *line)
+                } else if inline_tag_block_open(code).is_some() {
+                    // Same for a javascript_tag / style_tag opener.
+                    (std::borrow::Cow::Borrowed("if true"), *line)
                 } else if is_content_for_code(code) {
                     // Malformed capture (missing `do`); the template parser
                     // reports the friendly error, nothing to lint here.
@@ -564,18 +656,23 @@ pub fn extract_lintable_code(source: &str) -> Result<String, String> {
         while cur_line < line {
             out.push('\n');
             cur_line += 1;
+            lines.push(cur_line);
             line_has_code = false;
         }
         if line_has_code {
             out.push('\n');
             cur_line += 1;
+            lines.push(line);
         }
         out.push_str(&snippet);
         cur_line += snippet.matches('\n').count();
+        let last = lines.len() - 1;
+        lines[last] = line;
+        lines.extend((1..=snippet.matches('\n').count()).map(|k| line + k));
         line_has_code = true;
     }
 
-    Ok(out)
+    Ok((out, lines))
 }
 
 /// Parse tokens into an AST.
@@ -604,6 +701,11 @@ fn parse_tokens(tokens: &[Token]) -> Result<Vec<TemplateNode>, String> {
                     let (cf_node, consumed) = parse_content_for_block(&tokens[i..], *line)?;
                     nodes.push(cf_node);
                     i += consumed;
+                } else if inline_tag_block_open(code).is_some() {
+                    // Parse javascript_tag / style_tag block
+                    let (tag_node, consumed) = parse_inline_tag_block(&tokens[i..], *line)?;
+                    nodes.push(tag_node);
+                    i += consumed;
                 } else if form_with_block_parts(code).is_some() {
                     // Parse form_with builder block
                     let (fw_node, consumed) = parse_form_with_block(&tokens[i..], *line)?;
@@ -713,6 +815,15 @@ fn parse_if_block(
                         body.push(nested_cf);
                     }
                     i += consumed;
+                } else if inline_tag_block_open(code).is_some() {
+                    // Nested javascript_tag / style_tag block
+                    let (nested_tag, consumed) = parse_inline_tag_block(&tokens[i..], *line)?;
+                    if in_else {
+                        else_body.as_mut().unwrap().push(nested_tag);
+                    } else {
+                        body.push(nested_tag);
+                    }
+                    i += consumed;
                 } else if form_with_block_parts(code).is_some() {
                     // Nested form_with block
                     let (nested_fw, consumed) = parse_form_with_block(&tokens[i..], *line)?;
@@ -811,6 +922,11 @@ fn parse_for_block(tokens: &[Token], for_line: usize) -> Result<(TemplateNode, u
                     let (nested_cf, consumed) = parse_content_for_block(&tokens[i..], *line)?;
                     body.push(nested_cf);
                     i += consumed;
+                } else if inline_tag_block_open(code).is_some() {
+                    // Nested javascript_tag / style_tag block
+                    let (nested_tag, consumed) = parse_inline_tag_block(&tokens[i..], *line)?;
+                    body.push(nested_tag);
+                    i += consumed;
                 } else if form_with_block_parts(code).is_some() {
                     // Nested form_with block
                     let (nested_fw, consumed) = parse_form_with_block(&tokens[i..], *line)?;
@@ -1100,6 +1216,10 @@ fn parse_form_with_block(
                     let (nested_cf, consumed) = parse_content_for_block(&tokens[i..], *line)?;
                     body.push(nested_cf);
                     i += consumed;
+                } else if inline_tag_block_open(code).is_some() {
+                    let (nested_tag, consumed) = parse_inline_tag_block(&tokens[i..], *line)?;
+                    body.push(nested_tag);
+                    i += consumed;
                 } else if form_with_block_parts(code).is_some() {
                     let (nested_fw, consumed) = parse_form_with_block(&tokens[i..], *line)?;
                     body.push(nested_fw);
@@ -1190,7 +1310,8 @@ fn parse_component_block(
                             call_expr.span,
                         ))
                     }
-                    crate::ast::expr::Argument::Block(_) => None,
+                    crate::ast::expr::Argument::Block(_)
+                    | crate::ast::expr::Argument::Spread(_) => None,
                 }
             } else {
                 None
@@ -1237,6 +1358,10 @@ fn parse_component_block(
                     let (nested_cf, consumed) = parse_content_for_block(&tokens[i..], *line)?;
                     body.push(nested_cf);
                     i += consumed;
+                } else if inline_tag_block_open(code).is_some() {
+                    let (nested_tag, consumed) = parse_inline_tag_block(&tokens[i..], *line)?;
+                    body.push(nested_tag);
+                    i += consumed;
                 } else if form_with_block_parts(code).is_some() {
                     let (nested_fw, consumed) = parse_form_with_block(&tokens[i..], *line)?;
                     body.push(nested_fw);
@@ -1311,6 +1436,10 @@ fn parse_slot_block(
                     let (nested_cf, consumed) = parse_content_for_block(&tokens[i..], *line)?;
                     body.push(nested_cf);
                     i += consumed;
+                } else if inline_tag_block_open(code).is_some() {
+                    let (nested_tag, consumed) = parse_inline_tag_block(&tokens[i..], *line)?;
+                    body.push(nested_tag);
+                    i += consumed;
                 } else if form_with_block_parts(code).is_some() {
                     let (nested_fw, consumed) = parse_form_with_block(&tokens[i..], *line)?;
                     body.push(nested_fw);
@@ -1378,8 +1507,61 @@ fn parse_content_for_block(
         }
     };
 
+    let (body, consumed) = parse_capture_body(tokens, cf_line, "content_for")?;
+    Ok((
+        TemplateNode::ContentFor {
+            name,
+            body,
+            line: cf_line,
+        },
+        consumed,
+    ))
+}
+
+/// `javascript_tag do` / `style_tag do` openers: the element their body is
+/// wrapped in, or `None` when `code` isn't one.
+fn inline_tag_block_open(code: &str) -> Option<&'static str> {
+    match code.trim().strip_suffix("do")?.trim_end() {
+        "javascript_tag" => Some("script"),
+        "style_tag" => Some("style"),
+        _ => None,
+    }
+}
+
+/// Parse a `<%- javascript_tag do %> ... <%- end %>` (or `style_tag`) block
+/// starting at the given position. Returns the InlineTag node and the tokens
+/// consumed.
+fn parse_inline_tag_block(
+    tokens: &[Token],
+    tag_line: usize,
+) -> Result<(TemplateNode, usize), String> {
+    let element = match &tokens[0] {
+        Token::Code(code, _line) => inline_tag_block_open(code),
+        _ => None,
+    }
+    .ok_or_else(|| format!("Expected 'javascript_tag' block at line {}", tag_line))?;
+
+    let (body, consumed) = parse_capture_body(tokens, tag_line, "javascript_tag")?;
+    Ok((
+        TemplateNode::InlineTag {
+            element,
+            body,
+            line: tag_line,
+        },
+        consumed,
+    ))
+}
+
+/// Parse the body of a capturing block (`content_for`, `javascript_tag`)
+/// whose opener is `tokens[0]`, up to its `end`. Returns the body and the
+/// tokens consumed, `end` included.
+fn parse_capture_body(
+    tokens: &[Token],
+    open_line: usize,
+    keyword: &str,
+) -> Result<(Vec<TemplateNode>, usize), String> {
     let mut body = Vec::new();
-    let mut i = 1; // Skip the initial `content_for` token
+    let mut i = 1; // Skip the opener
 
     while i < tokens.len() {
         match &tokens[i] {
@@ -1387,14 +1569,7 @@ fn parse_content_for_block(
                 let code = code.trim();
 
                 if code == "end" {
-                    return Ok((
-                        TemplateNode::ContentFor {
-                            name,
-                            body,
-                            line: cf_line,
-                        },
-                        i + 1,
-                    ));
+                    return Ok((body, i + 1));
                 } else if let Some(rest) = code.strip_prefix("if ") {
                     let condition = parse_core_expr(rest.trim(), *line)?;
                     let (nested_if, consumed) = parse_if_block(&tokens[i..], condition, *line)?;
@@ -1408,6 +1583,10 @@ fn parse_content_for_block(
                     let (nested_cf, consumed) = parse_content_for_block(&tokens[i..], *line)?;
                     body.push(nested_cf);
                     i += consumed;
+                } else if inline_tag_block_open(code).is_some() {
+                    let (nested_tag, consumed) = parse_inline_tag_block(&tokens[i..], *line)?;
+                    body.push(nested_tag);
+                    i += consumed;
                 } else if form_with_block_parts(code).is_some() {
                     let (nested_fw, consumed) = parse_form_with_block(&tokens[i..], *line)?;
                     body.push(nested_fw);
@@ -1426,8 +1605,8 @@ fn parse_content_for_block(
     }
 
     Err(format!(
-        "Unclosed content_for block at line {} - missing 'end'",
-        cf_line
+        "Unclosed {} block at line {} - missing 'end'",
+        keyword, open_line
     ))
 }
 
@@ -2185,7 +2364,7 @@ mod tests {
                 line,
             } => {
                 assert!(
-                    matches!(&expr.kind, crate::ast::expr::ExprKind::Variable(n) if n == "name")
+                    matches!(&expr.kind, crate::ast::expr::ExprKind::Variable(n, _) if n == "name")
                 );
                 assert!(escaped);
                 assert_eq!(*line, 1);
@@ -2206,7 +2385,7 @@ mod tests {
                 line,
             } => {
                 assert!(
-                    matches!(&condition.kind, crate::ast::expr::ExprKind::Variable(n) if n == "show")
+                    matches!(&condition.kind, crate::ast::expr::ExprKind::Variable(n, _) if n == "show")
                 );
                 assert_eq!(body.len(), 1);
                 assert!(matches!(&body[0], TemplateNode::Literal(s) if s == "visible"));
@@ -2261,7 +2440,7 @@ mod tests {
                 assert_eq!(var, "item");
                 assert!(index_var.is_none());
                 assert!(
-                    matches!(&iterable.kind, crate::ast::expr::ExprKind::Variable(n) if n == "items")
+                    matches!(&iterable.kind, crate::ast::expr::ExprKind::Variable(n, _) if n == "items")
                 );
                 assert_eq!(body.len(), 1);
                 assert!(matches!(
@@ -2290,7 +2469,7 @@ mod tests {
                 assert_eq!(var, "item");
                 assert_eq!(index_var, &Some("i".to_string()));
                 assert!(
-                    matches!(&iterable.kind, crate::ast::expr::ExprKind::Variable(n) if n == "items")
+                    matches!(&iterable.kind, crate::ast::expr::ExprKind::Variable(n, _) if n == "items")
                 );
                 assert_eq!(body.len(), 3);
             }
@@ -2433,6 +2612,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_inline_tag_block() {
+        let nodes = parse_template(
+            "<%- javascript_tag do %>boot(<%= n %>)<%- end %><% if dark %><% style_tag do %>a{}<% end %><% end %>",
+        )
+        .unwrap();
+        match &nodes[0] {
+            TemplateNode::InlineTag { element, body, .. } => {
+                assert_eq!(*element, "script");
+                assert_eq!(body.len(), 3);
+            }
+            other => panic!("Expected InlineTag node, got {:?}", other),
+        }
+        match &nodes[1] {
+            TemplateNode::If { body, .. } => assert!(matches!(
+                &body[0],
+                TemplateNode::InlineTag {
+                    element: "style",
+                    ..
+                }
+            )),
+            other => panic!("Expected If node, got {:?}", other),
+        }
+
+        let err = parse_template("<%- javascript_tag do %>never closed").unwrap_err();
+        assert!(err.contains("Unclosed javascript_tag"), "got: {}", err);
+        // The call forms stay ordinary output.
+        let nodes = parse_template("<%- javascript_tag(\"x\") %>").unwrap();
+        assert!(matches!(
+            &nodes[0],
+            TemplateNode::CoreOutput { escaped: false, .. }
+        ));
+    }
+
     #[test]
     fn test_content_for_predicate_not_swallowed() {
         // `content_for?(...)` is the predicate builtin, not a directive —
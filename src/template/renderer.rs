@@ -7,6 +7,7 @@ use std::borrow::Cow;
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use crate::interpreter::builtins::security_headers;
 use crate::interpreter::executor::Interpreter;
 use crate::interpreter::value::{HashKey, HashPairs, Value};
 use crate::span::Span;
@@ -99,6 +100,7 @@ pub(crate) fn render_walker(
             TemplateNode::CoreCodeBlock { line, .. } => Some(*line),
             TemplateNode::CoreOutput { line, .. } => Some(*line),
             TemplateNode::ContentFor { line, .. } => Some(*line),
+            TemplateNode::InlineTag { line, .. } => Some(*line),
             TemplateNode::FormWith { line, .. } => Some(*line),
             TemplateNode::Component { line, .. } => Some(*line),
             _ => None,
@@ -265,6 +267,21 @@ pub(crate) fn render_walker(
                     )?;
                     crate::template::content_store::append(name, &captured);
                 }
+                TemplateNode::InlineTag { element, body, .. } => {
+                    // Render the body like a capture, then wrap it in a
+                    // nonced element whose hash joins the CSP header.
+                    let mut captured = String::with_capacity(256);
+                    render_walker(
+                        interpreter,
+                        body,
+                        data,
+                        partial_renderer,
+                        template_path,
+                        &mut captured,
+                        yield_mode,
+                    )?;
+                    output.push_str(&security_headers::inline_tag(element, &captured));
+                }
                 TemplateNode::FormWith {
                     parts,
                     body,
@@ -430,6 +447,9 @@ pub(crate) fn render_walker(
                     && !e.contains(".html.erb")
                     && !e.contains(".erb")
                 {
+                    crate::interpreter::builtins::template::record_template_error_locals(
+                        &interpreter.environment,
+                    );
                     if let Some(line) = node_line {
                         return Err(format!("{} at {}:{}", e, path, line));
                     }
@@ -802,6 +822,26 @@ mod tests {
         assert_eq!(result, "012");
     }
 
+    #[test]
+    fn test_inline_tag_is_nonced_in_block_and_call_forms() {
+        use crate::interpreter::builtins::security_headers;
+        security_headers::reset_inline_sources();
+
+        let nodes = parse_template(
+            "<%- javascript_tag do %>go(<%= n %>)<%- end %>|<%- style_tag(\"a{}\") %>|<%- javascript_tag { \"x\" } %>",
+        )
+        .unwrap();
+        let result = render_nodes(&nodes, &make_hash(vec![("n", Value::Int(1))]), None).unwrap();
+        let nonce = security_headers::csp_nonce();
+        assert_eq!(
+            result,
+            format!(
+                "<script nonce=\"{nonce}\">go(1)</script>|<style nonce=\"{nonce}\">a{{}}</style>|<script nonce=\"{nonce}\">x</script>"
+            )
+        );
+        security_headers::reset_inline_sources();
+    }
+
     #[test]
     fn test_content_for_captures_to_store_not_output() {
         use crate::template::content_store;
@@ -128,6 +128,14 @@ pub fn clear_cache() {
     RESPONSE_CACHE.with(|c| c.borrow_mut().clear());
 }
 
+/// This thread's cached bodies: `(entries, total body bytes)`.
+pub fn sizes() -> (usize, usize) {
+    RESPONSE_CACHE.with(|c| {
+        let c = c.borrow();
+        (c.len(), c.iter().map(|(_, v)| v.body.len()).sum())
+    })
+}
+
 /// Look up a cached response. Returns `None` if the request is
 /// marked dirty or the entry is missing.
 pub fn get(
@@ -246,7 +254,37 @@ fn fnv_value(h: &mut u64, v: &crate::interpreter::value::Value) {
                 fnv_value(h, v);
             }
         }
-        // Other variants (Function, Class, Instance, NativeFunction,
+        // Models hash by identity + `updated_at`, so a saved record changes
+        // the signature; other instances by class and (sorted) fields.
+        Value::Instance(inst) => {
+            *h ^= b'O' as u64;
+            *h = h.wrapping_mul(FNV_PRIME);
+            let inst = inst.borrow();
+            match super::fragment_cache::instance_cache_key(&inst) {
+                Some(key) => {
+                    for &b in key.as_bytes() {
+                        *h ^= b as u64;
+                        *h = h.wrapping_mul(FNV_PRIME);
+                    }
+                }
+                None => {
+                    for &b in inst.class.name.as_bytes() {
+                        *h ^= b as u64;
+                        *h = h.wrapping_mul(FNV_PRIME);
+                    }
+                    let mut fields: Vec<_> = inst.fields.iter().collect();
+                    fields.sort_by(|a, b| a.0.cmp(b.0));
+                    for (name, value) in fields {
+                        for &b in name.as_bytes() {
+                            *h ^= b as u64;
+                            *h = h.wrapping_mul(FNV_PRIME);
+                        }
+                        fnv_value(h, value);
+                    }
+                }
+            }
+        }
+        // Other variants (Function, Class, NativeFunction,
         // Method, QueryBuilder, Future, Super, VmClosure, Image, ...)
         // are not seen in render data — but hash them defensively by
         // a type tag so a future change that passes one through
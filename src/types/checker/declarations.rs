@@ -1,8 +1,11 @@
 //! Declaration type checking: classes, interfaces, and functions.
 
+use crate::ast::expr::Argument;
 use crate::ast::*;
+use crate::error::TypeError;
 use crate::types::type_repr::{
-    ClassType, EnumType, FieldInfo, InterfaceType, MethodInfo, MethodSignature, Type,
+    ClassType, EnumType, FieldInfo, InterfaceType, MethodInfo, MethodSignature, ParamSignature,
+    Type,
 };
 
 use super::TypeChecker;
@@ -39,19 +42,37 @@ impl TypeChecker {
             );
         }
 
+        // Model schema: `attribute "title", "string"` class-body calls
+        if class_type.extends_model() {
+            for (name, ty) in model_attributes(decl) {
+                class_type.fields.insert(
+                    name.clone(),
+                    FieldInfo {
+                        name: name.clone(),
+                        ty,
+                        is_private: false,
+                        is_static: false,
+                    },
+                );
+                class_type.attributes.push(name);
+            }
+        }
+
         // Add methods
         for method in &decl.methods {
             let params: Vec<(String, Type)> = method
                 .params
                 .iter()
-                .map(|p| (p.name.clone(), self.resolve_type(&p.type_annotation)))
+                .map(|p| (p.name.clone(), self.param_type(p)))
                 .collect();
-            let return_type = method
-                .return_type
-                .as_ref()
-                .map(|t| self.resolve_type(t))
-                .unwrap_or(Type::Void);
+            let return_type = Self::call_result_type(
+                method.is_async,
+                self.call_return_type(method.return_type.as_ref(), &method.body, Type::Void),
+            );
 
+            class_type
+                .signatures
+                .insert(method.name.clone(), ParamSignature::of(&method.params));
             class_type.methods.insert(
                 method.name.clone(),
                 MethodInfo {
@@ -64,6 +85,87 @@ impl TypeChecker {
             );
         }
 
+        if let Some(ref ctor) = decl.constructor {
+            class_type
+                .signatures
+                .insert("new".to_string(), ParamSignature::of(&ctor.params));
+        }
+
+        self.env.define_class(class_type);
+    }
+
+    /// Register a struct type. Its fields are typed from their annotations;
+    /// `new` (and calling the type directly) takes them positionally or by
+    /// name, and `with` takes any of them by name.
+    pub(crate) fn declare_struct(&mut self, decl: &StructDecl) {
+        let mut class_type = ClassType::new(decl.name.clone());
+        class_type.is_struct = true;
+        let this = Type::Class(ClassType::new(decl.name.clone()));
+
+        let mut params = Vec::new();
+        let mut signature = Vec::new();
+        for field in &decl.fields {
+            let ty = field
+                .type_annotation
+                .as_ref()
+                .map_or(Type::Any, |ta| self.resolve_type(ta));
+            class_type.fields.insert(
+                field.name.clone(),
+                FieldInfo {
+                    name: field.name.clone(),
+                    ty: ty.clone(),
+                    is_private: false,
+                    is_static: false,
+                },
+            );
+            params.push((field.name.clone(), ty));
+            signature.push(ParamSignature {
+                name: field.name.clone(),
+                has_default: field.default_value.is_some(),
+                is_keyword_only: false,
+                is_block: false,
+                is_variadic: false,
+            });
+        }
+
+        let with_signature = signature
+            .iter()
+            .map(|p| ParamSignature {
+                has_default: true,
+                is_keyword_only: true,
+                ..p.clone()
+            })
+            .collect();
+        for (name, is_static, signature, return_type) in [
+            ("new", true, signature, this.clone()),
+            ("with", false, with_signature, this),
+        ] {
+            class_type.signatures.insert(name.to_string(), signature);
+            class_type.methods.insert(
+                name.to_string(),
+                MethodInfo {
+                    name: name.to_string(),
+                    params: params.clone(),
+                    return_type,
+                    is_private: false,
+                    is_static,
+                },
+            );
+        }
+        class_type.methods.insert(
+            "to_h".to_string(),
+            MethodInfo {
+                name: "to_h".to_string(),
+                params: Vec::new(),
+                return_type: Type::Hash {
+                    key_type: Box::new(Type::String),
+                    value_type: Box::new(Type::Any),
+                },
+                is_private: false,
+                is_static: false,
+            },
+        );
+
         self.env.define_class(class_type);
     }
 
@@ -78,11 +180,7 @@ impl TypeChecker {
         let mut iface_type = InterfaceType::new(decl.name.clone());
 
         for method in &decl.methods {
-            let params: Vec<Type> = method
-                .params
-                .iter()
-                .map(|p| self.resolve_type(&p.type_annotation))
-                .collect();
+            let params: Vec<Type> = method.params.iter().map(|p| self.param_type(p)).collect();
             let return_type = method
                 .return_type
                 .as_ref()
@@ -102,17 +200,46 @@ impl TypeChecker {
         self.env.define_interface(iface_type);
     }
 
-    pub(crate) fn declare_function(&mut self, decl: &FunctionDecl) {
-        let params: Vec<Type> = decl
-            .params
+    /// Declare the `type Name = ...` aliases among `statements`, in order,
+    /// so an alias can name the ones before it. An alias that refers back to
+    /// itself is an error.
+    pub(crate) fn declare_type_aliases(&mut self, statements: &[Stmt]) {
+        let decls: Vec<&TypeAliasDecl> = statements
             .iter()
-            .map(|p| self.resolve_type(&p.type_annotation))
+            .filter_map(|stmt| match &stmt.kind {
+                StmtKind::TypeAlias(decl) => Some(&**decl),
+                StmtKind::Export(inner) => match &inner.kind {
+                    StmtKind::TypeAlias(decl) => Some(&**decl),
+                    _ => None,
+                },
+                _ => None,
+            })
             .collect();
-        let return_type = decl
-            .return_type
-            .as_ref()
-            .map(|t| self.resolve_type(t))
-            .unwrap_or(Type::Void);
+        let lookup = |name: &str| {
+            decls
+                .iter()
+                .find(|d| d.name == name)
+                .map(|d| &d.type_annotation)
+        };
+        for decl in &decls {
+            if decl.type_annotation.refers_to(&decl.name, &lookup) {
+                self.errors.push(TypeError::General {
+                    message: format!("type alias '{}' refers to itself", decl.name),
+                    span: decl.span,
+                });
+                continue;
+            }
+            let ty = self.resolve_type(&decl.type_annotation);
+            self.env.define_type_alias(decl.name.clone(), ty);
+        }
+    }
+
+    pub(crate) fn declare_function(&mut self, decl: &FunctionDecl) {
+        let params: Vec<Type> = decl.params.iter().map(|p| self.param_type(p)).collect();
+        let return_type = Self::call_result_type(
+            decl.is_async,
+            self.call_return_type(decl.return_type.as_ref(), &decl.body, Type::Void),
+        );
 
         self.env.define_function(
             decl.name.clone(),
@@ -121,5 +248,56 @@ impl TypeChecker {
                 return_type: Box::new(return_type),
             },
         );
+        self.env
+            .define_function_signature(decl.name.clone(), ParamSignature::of(&decl.params));
+    }
+}
+
+/// The literal `attribute "name", "type"` declarations in a model's class
+/// body, with the declared type mapped onto the checker's types. Unknown or
+/// non-literal types come back as `Any` (the runtime DSL rejects them).
+fn model_attributes(decl: &ClassDecl) -> Vec<(String, Type)> {
+    let literal = |arg: Option<&Argument>| match arg {
+        Some(Argument::Positional(Expr {
+            kind: ExprKind::StringLiteral(s) | ExprKind::Symbol(s),
+            ..
+        })) => Some(s.clone()),
+        _ => None,
+    };
+
+    let mut attributes = Vec::new();
+    for stmt in &decl.class_statements {
+        let StmtKind::Expression(Expr {
+            kind: ExprKind::Call { callee, arguments },
+            ..
+        }) = &stmt.kind
+        else {
+            continue;
+        };
+        if !matches!(&callee.kind, ExprKind::Variable(n, _) if n == "attribute") {
+            continue;
+        }
+        let Some(name) = literal(arguments.first()) else {
+            continue;
+        };
+        let ty = match literal(arguments.get(1))
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "string" | "text" => Type::String,
+            "int" | "integer" => Type::Int,
+            "float" | "number" | "decimal" => Type::Float,
+            "bool" | "boolean" => Type::Bool,
+            "array" => Type::Array(Box::new(Type::Any)),
+            "hash" | "json" | "object" => Type::Hash {
+                key_type: Box::new(Type::Any),
+                value_type: Box::new(Type::Any),
+            },
+            "point" => Type::Class(ClassType::new("Point".to_string())),
+            _ => Type::Any,
+        };
+        attributes.push((name, ty));
     }
+    attributes
 }
@@ -6,6 +6,7 @@ use crate::span::Span;
 use crate::types::type_repr::Type;
 
 use super::{TypeChecker, TypeResult};
+use crate::types::checker::nullability::null_dereference;
 
 impl TypeChecker {
     /// Check member access expression.
@@ -16,7 +17,35 @@ impl TypeChecker {
         name: &str,
     ) -> TypeResult<Type> {
         let obj_type = self.check_expr(object)?;
+        if let Type::Nullable(_) = obj_type {
+            // `nil?`, `present?`, `to_s`, ... are defined on null too
+            return universal_member(name)
+                .ok_or_else(|| null_dereference(&obj_type, &format!("'{}'", name), span));
+        }
+        self.member_type(span, obj_type, name)
+    }
+
+    /// Check `object&.name` / `object?.name`. The access is skipped when the
+    /// object is null, so on a `T?` it yields the member's type, nullable.
+    pub(crate) fn check_safe_member_expr(
+        &mut self,
+        span: Span,
+        object: &Expr,
+        name: &str,
+    ) -> TypeResult<Type> {
+        match self.check_expr(object)? {
+            Type::Nullable(inner) => Ok(Type::nullable(self.member_type(span, *inner, name)?)),
+            obj_type => self.member_type(span, obj_type, name),
+        }
+    }
 
+    /// The type of member `name` on a value of (non-null) type `obj_type`.
+    pub(crate) fn member_type(
+        &mut self,
+        span: Span,
+        obj_type: Type,
+        name: &str,
+    ) -> TypeResult<Type> {
         match obj_type {
             Type::Future(_inner) => Ok(Type::Any),
             Type::Class(class) => {
@@ -35,23 +64,50 @@ impl TypeChecker {
                     // Model subclasses have native_static_methods (where, all, includes, join, etc.)
                     // that are only known at runtime. Allow any member access on Model subclasses.
                     if class_def.extends_model() {
+                        let attributes = class_def.declared_attributes();
+                        // A schema-declaring model's lookups return a typed
+                        // record, so `Post.find(id).tilte` is caught too.
+                        if !attributes.is_empty() && matches!(name, "find" | "find_by") {
+                            return Ok(Type::Function {
+                                params: vec![Type::Any],
+                                return_type: Box::new(Type::Class(class_def.clone())),
+                            });
+                        }
+                        if let Some(attr) = attribute_typo(&attributes, name) {
+                            return Err(TypeError::General {
+                                message: format!(
+                                    "'{}' has no attribute '{}' — did you mean '{}'?",
+                                    class.name, name, attr
+                                ),
+                                span,
+                            });
+                        }
                         return Ok(Type::Any);
                     }
                 }
-                // Universal methods available on every value at runtime.
-                match name {
-                    "class" | "inspect" | "to_s" | "to_string" => return Ok(Type::String),
-                    "nil?" | "blank?" | "present?" => return Ok(Type::Bool),
-                    "is_a?" => {
-                        return Ok(Type::Function {
-                            params: vec![Type::String],
-                            return_type: Box::new(Type::Bool),
-                        })
-                    }
-                    _ => {}
+                universal_member(name)
+                    .or_else(|| decorator_member(name))
+                    .ok_or_else(|| TypeError::NoSuchMember {
+                        type_name: class.name,
+                        member: name.to_string(),
+                        span,
+                    })
+            }
+            // An interface-typed value only exposes what the interface declares:
+            // the concrete class behind it is unknown until runtime.
+            Type::Interface(iface) => {
+                let sig = self
+                    .env
+                    .get_interface(&iface.name)
+                    .and_then(|def| def.methods.get(name));
+                if let Some(sig) = sig {
+                    return Ok(collapse_zero_arg_method(Type::Function {
+                        params: sig.params.clone(),
+                        return_type: Box::new(sig.return_type.clone()),
+                    }));
                 }
-                Err(TypeError::NoSuchMember {
-                    type_name: class.name,
+                universal_member(name).ok_or_else(|| TypeError::NoSuchMember {
+                    type_name: iface.name,
                     member: name.to_string(),
                     span,
                 })
@@ -59,6 +115,9 @@ impl TypeChecker {
             Type::Array(inner_type) => self
                 .check_array_method(&inner_type, name, span)
                 .map(collapse_zero_arg_method),
+            Type::Iterator(inner_type) => self
+                .check_iterator_method(&inner_type, name, span)
+                .map(collapse_zero_arg_method),
             Type::Hash {
                 key_type,
                 value_type,
@@ -72,9 +131,36 @@ impl TypeChecker {
                 // (hash.key returns the value type for any key)
                 Ok((*value_type).clone())
             }
+            Type::Record(fields) => {
+                let value_type = Type::common(&record_types(&fields));
+                if let Ok(method_type) =
+                    self.check_hash_method(&Type::String, &value_type, name, span)
+                {
+                    return Ok(collapse_zero_arg_method(method_type));
+                }
+                match Type::record_field(&fields, name) {
+                    Some(field_type) => Ok(field_type.clone()),
+                    None => universal_member(name).ok_or_else(|| TypeError::NoSuchMember {
+                        type_name: format!("{}", Type::Record(fields.clone())),
+                        member: name.to_string(),
+                        span,
+                    }),
+                }
+            }
             Type::String => self
                 .check_string_method(name, span)
                 .map(collapse_zero_arg_method),
+            Type::Tuple(elements) => match name {
+                "length" | "len" | "size" => Ok(Type::Int),
+                "to_a" => Ok(Type::Array(Box::new(Type::common(&elements)))),
+                "first" => Ok(elements.first().cloned().unwrap_or(Type::Null)),
+                "last" => Ok(elements.last().cloned().unwrap_or(Type::Null)),
+                _ => universal_member(name).ok_or_else(|| TypeError::NoSuchMember {
+                    type_name: format!("{}", Type::Tuple(elements.clone())),
+                    member: name.to_string(),
+                    span,
+                }),
+            },
             Type::Any | Type::Unknown => Ok(Type::Any),
             // Primitive types support methods via the OO method dispatch system
             Type::Int | Type::Float | Type::Bool | Type::Null | Type::Decimal(_) | Type::Symbol => {
@@ -90,6 +176,36 @@ impl TypeChecker {
         }
     }
 
+    /// Check iterator method access. The lazy stages hand back iterators;
+    /// other methods collect the iterator, so they type as on an array.
+    fn check_iterator_method(&self, inner_type: &Type, name: &str, span: Span) -> TypeResult<Type> {
+        let iterator = |element: Type| Box::new(Type::Iterator(Box::new(element)));
+        match name {
+            "map" => Ok(Type::Function {
+                params: vec![Type::Any],
+                return_type: iterator(Type::Any),
+            }),
+            "filter" | "select" | "reject" => Ok(Type::Function {
+                params: vec![Type::Any],
+                return_type: iterator(inner_type.clone()),
+            }),
+            "take" | "drop" => Ok(Type::Function {
+                params: vec![Type::Int],
+                return_type: iterator(inner_type.clone()),
+            }),
+            "next" => Ok(Type::Function {
+                params: vec![],
+                return_type: Box::new(inner_type.clone()),
+            }),
+            "each" => Ok(Type::Function {
+                params: vec![Type::Any],
+                return_type: Box::new(Type::Null),
+            }),
+            "to_a" => Ok(Type::Array(Box::new(inner_type.clone()))),
+            _ => self.check_array_method(inner_type, name, span),
+        }
+    }
+
     /// Check array method access.
     fn check_array_method(&self, inner_type: &Type, name: &str, span: Span) -> TypeResult<Type> {
         match name {
@@ -474,6 +590,17 @@ impl TypeChecker {
     ) -> TypeResult<Type> {
         let obj_type = self.check_expr(object)?;
         let idx_type = self.check_expr(index)?;
+        if let Type::Nullable(_) = obj_type {
+            return Err(null_dereference(&obj_type, "an index", span));
+        }
+
+        if let Some(result_type) = self.operator_method_type(
+            &obj_type,
+            Some(crate::ast::expr::INDEX_METHOD),
+            &[(idx_type.clone(), index.span)],
+        )? {
+            return Ok(result_type);
+        }
 
         match &obj_type {
             Type::Array(inner) => {
@@ -496,6 +623,43 @@ impl TypeChecker {
                 }
                 Ok(Type::String)
             }
+            Type::Tuple(elements) => {
+                if !matches!(idx_type, Type::Int | Type::Any | Type::Unknown) {
+                    return Err(TypeError::mismatch(
+                        "Int",
+                        format!("{}", idx_type),
+                        index.span,
+                    ));
+                }
+                // A literal position picks out that element's type.
+                let position = match &index.kind {
+                    ExprKind::IntLiteral(n) => Some(*n),
+                    ExprKind::Unary {
+                        operator: UnaryOp::Negate,
+                        operand,
+                    } => match operand.kind {
+                        ExprKind::IntLiteral(n) => Some(-n),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                let Some(position) = position else {
+                    return Ok(Type::common(elements));
+                };
+                let len = elements.len() as i64;
+                let resolved = if position < 0 {
+                    position + len
+                } else {
+                    position
+                };
+                if !(0..len).contains(&resolved) {
+                    return Err(TypeError::General {
+                        message: format!("tuple index {} out of range for {}", position, obj_type),
+                        span: index.span,
+                    });
+                }
+                Ok(elements[resolved as usize].clone())
+            }
             Type::Hash {
                 key_type,
                 value_type,
@@ -509,6 +673,26 @@ impl TypeChecker {
                 }
                 Ok(*value_type.clone())
             }
+            Type::Record(fields) => {
+                if !idx_type.is_assignable_to(&Type::String) {
+                    return Err(TypeError::mismatch(
+                        "String",
+                        format!("{}", idx_type),
+                        index.span,
+                    ));
+                }
+                // A literal key picks out that field's type.
+                let ExprKind::StringLiteral(key) = &index.kind else {
+                    return Ok(Type::common(&record_types(fields)));
+                };
+                Type::record_field(fields, key)
+                    .cloned()
+                    .ok_or_else(|| TypeError::NoSuchMember {
+                        type_name: format!("{}", obj_type),
+                        member: key.clone(),
+                        span: index.span,
+                    })
+            }
             Type::Any | Type::Unknown => Ok(Type::Any),
             _ => Err(TypeError::General {
                 message: format!("cannot index {}", obj_type),
@@ -518,6 +702,11 @@ impl TypeChecker {
     }
 }
 
+/// The field types of a record, in order.
+fn record_types(fields: &[(String, Type)]) -> Vec<Type> {
+    fields.iter().map(|(_, ty)| ty.clone()).collect()
+}
+
 /// Zero-arg builtin/class methods auto-invoke on bare member access at
 /// runtime (`s.length` evaluates to an Int, not a function), so type them
 /// as their return type. The explicit call form (`s.length()`) is accepted
@@ -531,3 +720,72 @@ fn collapse_zero_arg_method(ty: Type) -> Type {
         other => other,
     }
 }
+
+/// Universal methods available on every object value at runtime.
+/// The declared attribute `name` is most likely a misspelling of. Only near
+/// misses count: any other name on a model may still be a runtime method
+/// (`save`, `errors`, relations, …) the checker can't see.
+fn attribute_typo<'a>(attributes: &[&'a str], name: &str) -> Option<&'a str> {
+    const IMPLICIT_FIELDS: &[&str] = &["id", "created_at", "updated_at", "deleted_at"];
+    if name.starts_with('_') || IMPLICIT_FIELDS.contains(&name) {
+        return None;
+    }
+    let max_distance = if name.chars().count() <= 4 { 1 } else { 2 };
+    attributes
+        .iter()
+        .map(|attr| (edit_distance(attr, name), *attr))
+        .filter(|(d, _)| *d > 0 && *d <= max_distance)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, attr)| attr)
+}
+
+/// Levenshtein distance over chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diag = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diag
+            } else {
+                1 + diag.min(above).min(row[j])
+            };
+            diag = above;
+        }
+    }
+    row[b.len()]
+}
+
+fn universal_member(name: &str) -> Option<Type> {
+    match name {
+        "class" | "inspect" | "to_s" | "to_string" => Some(Type::String),
+        "nil?" | "blank?" | "present?" => Some(Type::Bool),
+        "is_a?" => Some(Type::Function {
+            params: vec![Type::String],
+            return_type: Box::new(Type::Bool),
+        }),
+        _ => None,
+    }
+}
+
+/// The decorator reflection API on a class (see `interpreter::decorators`).
+fn decorator_member(name: &str) -> Option<Type> {
+    let hash = || Type::Hash {
+        key_type: Box::new(Type::String),
+        value_type: Box::new(Type::Any),
+    };
+    let lookup = |return_type: Type| Type::Function {
+        params: vec![Type::String],
+        return_type: Box::new(return_type),
+    };
+    match name {
+        "decorators" => Some(Type::Array(Box::new(hash()))),
+        "decorator" => Some(lookup(Type::nullable(hash()))),
+        "method_decorators" => Some(lookup(Type::Array(Box::new(hash())))),
+        "decorated_methods" => Some(lookup(Type::Array(Box::new(Type::String)))),
+        _ => None,
+    }
+}
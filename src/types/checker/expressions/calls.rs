@@ -4,7 +4,7 @@ use crate::ast::expr::Argument;
 use crate::ast::*;
 use crate::error::TypeError;
 use crate::span::Span;
-use crate::types::type_repr::Type;
+use crate::types::type_repr::{ParamSignature, Type};
 
 use super::{TypeChecker, TypeResult};
 
@@ -16,19 +16,58 @@ impl TypeChecker {
         callee: &Expr,
         arguments: &[Argument],
     ) -> TypeResult<Type> {
+        if let ExprKind::Member { object, name } = &callee.kind {
+            if let Some(result) = self.check_block_method_call(object, name, arguments, span)? {
+                return Ok(result);
+            }
+        }
+
         let callee_type = self.check_expr(callee)?;
 
+        // `a?.m(x)` skips the call when `a` is null, so it yields `R?`.
+        if let (Type::Nullable(inner), ExprKind::SafeMember { .. }) = (&callee_type, &callee.kind) {
+            let inner = (**inner).clone();
+            return Ok(Type::nullable(
+                self.check_call_with_callee(span, callee, inner, arguments)?,
+            ));
+        }
+        self.check_call_with_callee(span, callee, callee_type, arguments)
+    }
+
+    /// Check a call once the callee's type is known.
+    fn check_call_with_callee(
+        &mut self,
+        span: Span,
+        callee: &Expr,
+        callee_type: Type,
+        arguments: &[Argument],
+    ) -> TypeResult<Type> {
         match callee_type {
             Type::Function {
                 params,
                 return_type,
             } => {
+                if let Some((name, signature)) = self.callee_signature(callee)? {
+                    self.check_call_signature(&name, &signature, &params, arguments, span)?;
+                    return Ok(*return_type);
+                }
+
                 let total_args = arguments.len();
+                // A spread argument stands for an unknown number of values,
+                // so neither the count nor the positions after it can be
+                // checked.
+                let spread_at = arguments
+                    .iter()
+                    .position(|a| matches!(a, Argument::Spread(_)))
+                    .unwrap_or(total_args);
 
                 // Check argument count (allow fewer args for default parameters)
                 // Note: We only check upper bound since we can't easily know which params have defaults
                 // The runtime will handle default parameter filling
-                if total_args > params.len() && !params.iter().any(|p| matches!(p, Type::Any)) {
+                if total_args > params.len()
+                    && spread_at == total_args
+                    && !params.iter().any(|p| matches!(p, Type::Any))
+                {
                     return Err(TypeError::WrongArity {
                         expected: params.len(),
                         got: total_args,
@@ -36,20 +75,31 @@ impl TypeChecker {
                     });
                 }
 
-                // Get parameter names for named argument validation
-                // For now, we use index-based matching since we can't resolve callee name
-                let _param_count = params.len();
-                let param_names: Vec<String> = params
-                    .iter()
-                    .enumerate()
-                    .map(|(i, _)| format!("param_{}", i))
-                    .collect();
+                // A builtin's stub also says how many arguments it needs.
+                // Named arguments reach it as one trailing options hash.
+                if let Some(required) = self.builtin_required_args(callee) {
+                    let passed = arguments
+                        .iter()
+                        .filter(|a| matches!(a, Argument::Positional(_)))
+                        .count()
+                        + usize::from(arguments.iter().any(|a| matches!(a, Argument::Named(_))));
+                    if passed < required && spread_at == total_args {
+                        return Err(TypeError::WrongArity {
+                            expected: required,
+                            got: passed,
+                            span,
+                        });
+                    }
+                }
 
                 // Check argument types
                 for (i, arg) in arguments.iter().enumerate() {
                     match arg {
+                        Argument::Positional(expr) if i > spread_at => {
+                            self.check_expr(expr)?;
+                        }
                         Argument::Positional(expr) => {
-                            let arg_type = self.check_expr(expr)?;
+                            let arg_type = self.check_expr_expecting(expr, params.get(i))?;
                             if let Some(param_type) = params.get(i) {
                                 // Allow Any param type to accept any argument (for map/filter/each)
                                 if matches!(param_type, Type::Any) {
@@ -65,31 +115,16 @@ impl TypeChecker {
                             }
                         }
                         Argument::Named(named) => {
-                            // For named arguments, find the corresponding parameter
-                            if let Some(param_idx) =
-                                param_names.iter().position(|n| n == &named.name)
-                            {
-                                if let Some(param_type) = params.get(param_idx) {
-                                    let arg_type = self.check_expr(&named.value)?;
-                                    if matches!(param_type, Type::Any) {
-                                        continue;
-                                    }
-                                    if !arg_type.is_assignable_to(param_type) {
-                                        return Err(TypeError::mismatch(
-                                            format!("{}", param_type),
-                                            format!("{}", arg_type),
-                                            named.span,
-                                        ));
-                                    }
-                                }
-                            }
-                            // Unknown named argument - runtime will catch this
+                            // Without a declaration there are no names to
+                            // check against; the runtime validates them.
+                            self.check_expr(&named.value)?;
                         }
                         Argument::Block(expr) => {
                             // Block arguments - type check the block expression
                             self.check_expr(expr)?;
                             // For now, skip block param type checking
                         }
+                        Argument::Spread(expr) => self.check_spread(expr)?,
                     }
                 }
 
@@ -97,19 +132,7 @@ impl TypeChecker {
             }
             Type::Class(class) => {
                 // Constructor call
-                if let Some(ref ctor) = self
-                    .env
-                    .get_class(&class.name)
-                    .and_then(|c| c.methods.get("new").cloned())
-                {
-                    if ctor.params.len() != arguments.len() {
-                        return Err(TypeError::WrongArity {
-                            expected: ctor.params.len(),
-                            got: arguments.len(),
-                            span,
-                        });
-                    }
-                }
+                self.check_constructor_arguments(&class.name, arguments, span)?;
                 Ok(Type::Class(class))
             }
             Type::Any | Type::Unknown => Ok(Type::Any),
@@ -129,13 +152,328 @@ impl TypeChecker {
         }
     }
 
+    /// Check a constructor call's arguments against the class's `new`, if
+    /// it declares one.
+    pub(crate) fn check_constructor_arguments(
+        &mut self,
+        class_name: &str,
+        arguments: &[Argument],
+        span: Span,
+    ) -> TypeResult<()> {
+        let Some(class) = self.env.get_class(class_name) else {
+            return Ok(());
+        };
+        let Some(signature) = class.signatures.get("new").cloned() else {
+            return Ok(());
+        };
+        let param_types: Vec<Type> = class
+            .methods
+            .get("new")
+            .map(|m| m.params.iter().map(|(_, ty)| ty.clone()).collect())
+            .unwrap_or_default();
+        self.check_call_signature(class_name, &signature, &param_types, arguments, span)
+    }
+
+    /// The declared parameter list behind `callee`, when it is a function
+    /// declared by name or a method of a user-defined class.
+    fn callee_signature(
+        &mut self,
+        callee: &Expr,
+    ) -> TypeResult<Option<(String, Vec<ParamSignature>)>> {
+        match &callee.kind {
+            ExprKind::Variable(name, _) => Ok(self
+                .env
+                .get_signature(name)
+                .map(|signature| (name.clone(), signature.to_vec()))),
+            ExprKind::Member { object, name } => match self.check_expr(object)? {
+                Type::Class(class) => Ok(self
+                    .env
+                    .get_class(&class.name)
+                    .unwrap_or(&class)
+                    .find_signature(name)
+                    .map(|signature| (name.clone(), signature.to_vec()))),
+                _ => Ok(None),
+            },
+            _ => Ok(None),
+        }
+    }
+
+    /// How many parameters of the builtin `callee` names have neither a
+    /// default nor a `&block`/`...rest`/keyword-only form.
+    fn builtin_required_args(&self, callee: &Expr) -> Option<usize> {
+        let ExprKind::Variable(name, _) = &callee.kind else {
+            return None;
+        };
+        let signature = self.env.builtin_signature(name)?;
+        Some(
+            signature
+                .iter()
+                .filter(|p| !(p.has_default || p.is_block || p.is_variadic || p.is_keyword_only))
+                .count(),
+        )
+    }
+
+    /// Check a call against the declared parameters of `name`: positional
+    /// arguments only fill the parameters before the `*` (extra ones go to a
+    /// `...rest` parameter), each named argument names a parameter that
+    /// isn't already passed, and every parameter without a default gets a
+    /// value. `param_types` lines up with `signature`. Errors point at the
+    /// offending argument where there is one, otherwise at the call.
+    ///
+    /// A `*array` argument can fill any number of positional slots, so once
+    /// one appears positional arity is left to the runtime.
+    fn check_call_signature(
+        &mut self,
+        name: &str,
+        signature: &[ParamSignature],
+        param_types: &[Type],
+        arguments: &[Argument],
+        span: Span,
+    ) -> TypeResult<()> {
+        let positional_slots: Vec<usize> = (0..signature.len())
+            .filter(|&i| !signature[i].is_keyword_only && !signature[i].is_variadic)
+            .collect();
+        let variadic = signature.iter().position(|p| p.is_variadic);
+        let mut spread = false;
+        let positional_count = arguments
+            .iter()
+            .filter(|a| matches!(a, Argument::Positional(_)))
+            .count();
+        let mut filled = vec![false; signature.len()];
+        let mut named_seen: Vec<&str> = Vec::new();
+        let mut next_positional = 0;
+
+        for arg in arguments {
+            match arg {
+                Argument::Positional(expr) => {
+                    let slot = positional_slots.get(next_positional).copied();
+                    let expected = slot.filter(|_| !spread).and_then(|i| param_types.get(i));
+                    let arg_type = self.check_expr_expecting(expr, expected)?;
+                    if spread {
+                        continue;
+                    }
+                    let Some(index) = slot else {
+                        if let Some(index) = variadic {
+                            filled[index] = true;
+                            continue;
+                        }
+                        return Err(TypeError::WrongArity {
+                            expected: positional_slots.len(),
+                            got: positional_count,
+                            span: expr.span,
+                        });
+                    };
+                    next_positional += 1;
+                    filled[index] = true;
+                    check_argument_type(param_types.get(index), &arg_type, expr.span)?;
+                }
+                Argument::Named(named) => {
+                    let index = signature
+                        .iter()
+                        .position(|p| p.name == named.name && !p.is_variadic);
+                    let expected = index.and_then(|i| param_types.get(i));
+                    let arg_type = self.check_expr_expecting(&named.value, expected)?;
+                    let Some(index) = index else {
+                        return Err(TypeError::General {
+                            message: format!("{}() has no parameter named '{}'", name, named.name),
+                            span: named.span,
+                        });
+                    };
+                    if filled[index] {
+                        let message = if named_seen.contains(&named.name.as_str()) {
+                            format!("duplicate named argument '{}'", named.name)
+                        } else {
+                            format!(
+                                "{}() got argument '{}' both by position and by name",
+                                name, named.name
+                            )
+                        };
+                        return Err(TypeError::General {
+                            message,
+                            span: named.span,
+                        });
+                    }
+                    named_seen.push(&named.name);
+                    filled[index] = true;
+                    check_argument_type(param_types.get(index), &arg_type, named.span)?;
+                }
+                Argument::Block(expr) => {
+                    let index = signature.iter().position(|p| p.is_block);
+                    self.check_expr_expecting(expr, index.and_then(|i| param_types.get(i)))?;
+                    if let Some(index) = index {
+                        filled[index] = true;
+                    }
+                }
+                Argument::Spread(expr) => {
+                    self.check_spread(expr)?;
+                    spread = true;
+                }
+            }
+        }
+
+        for (param, filled) in signature.iter().zip(&filled) {
+            if *filled || param.has_default || param.is_block || param.is_variadic {
+                continue;
+            }
+            if param.is_keyword_only {
+                return Err(TypeError::General {
+                    message: format!("{}() is missing keyword argument '{}'", name, param.name),
+                    span,
+                });
+            }
+            if spread {
+                continue;
+            }
+            let required = signature
+                .iter()
+                .filter(|p| !p.has_default && !p.is_block && !p.is_keyword_only && !p.is_variadic)
+                .count();
+            return Err(TypeError::WrongArity {
+                expected: required,
+                got: positional_count,
+                span,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check `receiver.name(...)` when it passes a block to a method of an
+    /// array, iterator or hash: the block's parameters take the element (or
+    /// key and value) types, and `map`/`filter` results keep what the block
+    /// returns or the elements it keeps. `None` for any other call.
+    fn check_block_method_call(
+        &mut self,
+        receiver: &Expr,
+        name: &str,
+        arguments: &[Argument],
+        span: Span,
+    ) -> TypeResult<Option<Type>> {
+        let Some(block_arity) = arguments.iter().find_map(|arg| match arg {
+            Argument::Positional(expr) | Argument::Block(expr) => match &expr.kind {
+                ExprKind::Lambda { params, .. } => Some(params.len()),
+                _ => None,
+            },
+            _ => None,
+        }) else {
+            return Ok(None);
+        };
+        let receiver_type = self.check_expr(receiver)?;
+        let Some(block_params) = block_param_types(&receiver_type, name, block_arity) else {
+            return Ok(None);
+        };
+        let Type::Function { return_type, .. } =
+            self.member_type(span, receiver_type.clone(), name)?
+        else {
+            return Ok(None);
+        };
+
+        let expected = Type::Function {
+            params: block_params,
+            return_type: Box::new(Type::Any),
+        };
+        let mut block_returns = Type::Any;
+        for arg in arguments {
+            match arg {
+                Argument::Positional(expr) | Argument::Block(expr)
+                    if matches!(expr.kind, ExprKind::Lambda { .. }) =>
+                {
+                    if let Type::Function { return_type, .. } =
+                        self.check_expr_expecting(expr, Some(&expected))?
+                    {
+                        block_returns = *return_type;
+                    }
+                }
+                Argument::Positional(expr) | Argument::Block(expr) => {
+                    self.check_expr(expr)?;
+                }
+                Argument::Named(named) => {
+                    self.check_expr(&named.value)?;
+                }
+                Argument::Spread(expr) => self.check_spread(expr)?,
+            }
+        }
+
+        Ok(Some(match (&receiver_type, name) {
+            (Type::Array(_), "map") => Type::Array(Box::new(block_returns)),
+            (Type::Array(element), "filter" | "select") => Type::Array(element.clone()),
+            (Type::Iterator(_), "map") => Type::Iterator(Box::new(block_returns)),
+            _ => *return_type,
+        }))
+    }
+
+    /// Check the operand of a `*expr` argument, which must be an array.
+    fn check_spread(&mut self, expr: &Expr) -> TypeResult<()> {
+        match self.check_expr(expr)? {
+            Type::Array(_) | Type::Any | Type::Unknown => Ok(()),
+            other => Err(TypeError::mismatch(
+                "Array".to_string(),
+                format!("{}", other),
+                expr.span,
+            )),
+        }
+    }
+
     /// Check pipeline expression.
+    /// `items |> map(f)` / `filter` / `each` run the collection's own
+    /// method, as the interpreter does. An iterator stays lazy through
+    /// `map` and `filter`; `each` drains it.
+    fn check_pipeline_stage(
+        &mut self,
+        name: &str,
+        left_type: Type,
+        arguments: &[Argument],
+        span: Span,
+    ) -> TypeResult<Type> {
+        let block = match &left_type {
+            Type::Array(element) | Type::Iterator(element) => Some(Type::Function {
+                params: vec![(**element).clone()],
+                return_type: Box::new(Type::Any),
+            }),
+            _ => None,
+        };
+        let mut mapped = Type::Any;
+        for arg in arguments {
+            match arg {
+                Argument::Positional(expr) | Argument::Block(expr) => {
+                    if let Type::Function { return_type, .. } =
+                        self.check_expr_expecting(expr, block.as_ref())?
+                    {
+                        mapped = *return_type;
+                    }
+                }
+                Argument::Named(named) => {
+                    self.check_expr(&named.value)?;
+                }
+                Argument::Spread(expr) => self.check_spread(expr)?,
+            }
+        }
+        Ok(match (name, left_type) {
+            (_, Type::Any | Type::Unknown) => Type::Any,
+            ("map", Type::Array(_)) => Type::Array(Box::new(mapped)),
+            ("map", Type::Iterator(_)) => Type::Iterator(Box::new(mapped)),
+            ("each", Type::Iterator(_)) => Type::Null,
+            (_, collection @ (Type::Array(_) | Type::Iterator(_))) => collection,
+            (_, other) => {
+                return Err(TypeError::General {
+                    message: format!("{}() expects an array or iterator, got {}", name, other),
+                    span,
+                })
+            }
+        })
+    }
+
     pub(crate) fn check_pipeline_expr(&mut self, left: &Expr, right: &Expr) -> TypeResult<Type> {
         let left_type = self.check_expr(left)?;
 
         // Right side can be a call or a function value
         match &right.kind {
             ExprKind::Call { callee, arguments } => {
+                if let ExprKind::Variable(name, _) = &callee.kind {
+                    if matches!(name.as_str(), "map" | "filter" | "each") {
+                        return self.check_pipeline_stage(name, left_type, arguments, left.span);
+                    }
+                }
+
                 let callee_type = self.check_expr(callee)?;
 
                 match callee_type {
@@ -177,6 +515,7 @@ impl TypeChecker {
                                     // Block arguments in pipeline - skip type checking
                                     self.check_expr(expr)?;
                                 }
+                                Argument::Spread(expr) => self.check_spread(expr)?,
                             }
                         }
 
@@ -240,3 +579,50 @@ impl TypeChecker {
         }
     }
 }
+
+/// An argument of type `arg_type` must fit its parameter; `Any` (or an
+/// unknown parameter) accepts anything.
+fn check_argument_type(param_type: Option<&Type>, arg_type: &Type, span: Span) -> TypeResult<()> {
+    match param_type {
+        Some(param_type) if !matches!(param_type, Type::Any) => {
+            if arg_type.is_assignable_to(param_type) {
+                Ok(())
+            } else {
+                Err(TypeError::mismatch(
+                    format!("{}", param_type),
+                    format!("{}", arg_type),
+                    span,
+                ))
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The parameter types of a block passed to `name` on a `receiver`, by
+/// what the runtime hands it: each element of an array or iterator (and
+/// its index for `each_with_index`, the accumulator first for `reduce`),
+/// or the key and value of a hash entry to a two-parameter block.
+fn block_param_types(receiver: &Type, name: &str, arity: usize) -> Option<Vec<Type>> {
+    match receiver {
+        Type::Array(element) | Type::Iterator(element) => match name {
+            "map" | "filter" | "select" | "reject" | "each" | "find" | "any?" | "all?"
+            | "none?" | "one?" | "sort_by" | "count" | "take_while" | "drop_while" => {
+                Some(vec![(**element).clone()])
+            }
+            "each_with_index" => Some(vec![(**element).clone(), Type::Int]),
+            "reduce" | "fold" => Some(vec![Type::Any, (**element).clone()]),
+            _ => None,
+        },
+        Type::Hash {
+            key_type,
+            value_type,
+        } if arity == 2 => match name {
+            "map" | "filter" | "select" | "reject" | "each" => {
+                Some(vec![(**key_type).clone(), (**value_type).clone()])
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
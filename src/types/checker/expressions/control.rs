@@ -6,6 +6,7 @@ use crate::span::Span;
 use crate::types::type_repr::Type;
 
 use super::{TypeChecker, TypeResult};
+use crate::types::checker::nullability::always_exits;
 
 impl TypeChecker {
     /// Check 'this' expression.
@@ -30,21 +31,66 @@ impl TypeChecker {
         }
     }
 
-    /// Check lambda expression.
+    /// Check `expr` where a value of type `expected` is wanted. A lambda
+    /// takes the parameter types of an expected function type for the
+    /// parameters it doesn't annotate, and a hash literal checks against an
+    /// expected record; anything else checks as usual.
+    pub(crate) fn check_expr_expecting(
+        &mut self,
+        expr: &Expr,
+        expected: Option<&Type>,
+    ) -> TypeResult<Type> {
+        let ty = match (&expr.kind, expected) {
+            (
+                ExprKind::Lambda {
+                    params,
+                    return_type,
+                    body,
+                    ..
+                },
+                Some(Type::Function {
+                    params: expected, ..
+                }),
+            ) => self.check_lambda_expr(body, params, return_type, expected)?,
+            (ExprKind::Hash(pairs), Some(Type::Record(fields))) => {
+                self.check_record_literal(expr.span, pairs, fields)?
+            }
+            (ExprKind::Hash(_), Some(Type::Nullable(inner))) => {
+                return self.check_expr_expecting(expr, Some(inner));
+            }
+            _ => return self.check_expr(expr),
+        };
+        self.record_coverage(expr, &ty);
+        Ok(ty)
+    }
+
+    /// Check lambda expression. `expected_params` types the parameters
+    /// written without an annotation, by position (`items.map(fn(x) ...)`
+    /// passes the element type). Without an annotation, the return type is
+    /// inferred when every path returns the same type, counting a trailing
+    /// expression as the value returned.
     pub(crate) fn check_lambda_expr(
         &mut self,
         body: &[Stmt],
         params: &[Parameter],
         return_type: &Option<Box<TypeAnnotation>>,
+        expected_params: &[Type],
     ) -> TypeResult<Type> {
         self.env.push_scope();
 
         let param_types: Vec<Type> = params
             .iter()
-            .map(|param| {
-                let t = self.resolve_type(&param.type_annotation);
-                self.env.define(param.name.clone(), t.clone());
-                t
+            .enumerate()
+            .map(|(i, param)| match expected_params.get(i) {
+                Some(expected) if is_unannotated(param) && !param.is_variadic => {
+                    self.env.define(param.name.clone(), expected.clone());
+                    expected.clone()
+                }
+                _ => {
+                    self.env
+                        .define(param.name.clone(), self.param_local_type(param));
+                    self.param_type(param)
+                }
             })
             .collect();
 
@@ -53,22 +99,25 @@ impl TypeChecker {
             .map(|t| self.resolve_type(t))
             .unwrap_or(Type::Any);
 
-        self.env.set_return_type(Some(ret_type.clone()));
-
-        // Check body statements
         // Note: Implicit return logic is handled in parsing (last expr wrapped in Return)
         // or we rely on Return statements in body.
-        for stmt in body {
-            if let Err(e) = self.check_stmt(stmt) {
-                self.errors.push(e);
-            }
-        }
-
-        self.env.set_return_type(None);
+        let returned =
+            self.check_function_body(body, ret_type.clone(), return_type.as_ref().map(|t| t.span));
         self.env.pop_scope();
 
-        // Infer return type from body if not explicit?
-        // For now, we just validate against explicit return type (or Any).
+        let ret_type = match body.last() {
+            _ if return_type.is_some() || crate::ast::stmt::body_yields(body) => ret_type,
+            Some(Stmt {
+                kind: StmtKind::Expression(last),
+                ..
+            }) if !matches!(last.kind, ExprKind::Throw(_)) => {
+                let mut returned = returned;
+                returned.push(self.last_expression_type.take().unwrap_or(Type::Any));
+                Type::common(&returned)
+            }
+            Some(last) if always_exits(last) => Type::common(&returned),
+            _ => ret_type,
+        };
 
         Ok(Type::Function {
             params: param_types,
@@ -87,6 +136,25 @@ impl TypeChecker {
         self.check_expr(expr)?;
         Ok(Type::Any)
     }
+
+    /// `await` unwraps a future, or an array of futures element-wise.
+    pub(crate) fn check_await_expr(&mut self, expr: &Expr) -> TypeResult<Type> {
+        Ok(match self.check_expr(expr)? {
+            Type::Future(inner) => *inner,
+            Type::Array(inner) => match *inner {
+                Type::Future(element) => Type::Array(element),
+                other => Type::Array(Box::new(other)),
+            },
+            other => other,
+        })
+    }
+}
+
+/// A parameter written without a type: the parser fills in `Any` at the
+/// name itself, where a written annotation starts after the colon.
+fn is_unannotated(param: &Parameter) -> bool {
+    matches!(&param.type_annotation.kind, TypeKind::Named(name) if name == "Any")
+        && param.type_annotation.span.start == param.span.start
 }
 
 #[cfg(test)]
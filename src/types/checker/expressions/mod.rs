@@ -16,6 +16,12 @@ use super::{TypeChecker, TypeResult};
 impl TypeChecker {
     /// Main expression type checker - dispatches to specialized checkers.
     pub(crate) fn check_expr(&mut self, expr: &Expr) -> TypeResult<Type> {
+        let ty = self.check_expr_kind(expr)?;
+        self.record_coverage(expr, &ty);
+        Ok(ty)
+    }
+
+    fn check_expr_kind(&mut self, expr: &Expr) -> TypeResult<Type> {
         match &expr.kind {
             // Literals
             ExprKind::IntLiteral(_)
@@ -28,7 +34,7 @@ impl TypeChecker {
             | ExprKind::Null => self.check_literal(&expr.kind),
 
             // Variables and references
-            ExprKind::Variable(name) => self.check_variable(name, expr.span),
+            ExprKind::Variable(name, _) => self.check_variable(name, expr.span),
             ExprKind::QualifiedName { .. } => self.check_qualified_name(expr.span),
             ExprKind::Grouping(inner) => self.check_grouping(inner),
 
@@ -41,9 +47,8 @@ impl TypeChecker {
             ExprKind::Unary { operator, operand } => {
                 self.check_unary_expr(expr.span, operator, operand)
             }
-            ExprKind::LogicalAnd { left, right } | ExprKind::LogicalOr { left, right } => {
-                self.check_logical(left, right)
-            }
+            ExprKind::LogicalAnd { left, right } => self.check_logical(left, right, true),
+            ExprKind::LogicalOr { left, right } => self.check_logical(left, right, false),
             ExprKind::NullishCoalescing { left, right } => {
                 self.check_nullish_coalescing(left, right)
             }
@@ -55,8 +60,9 @@ impl TypeChecker {
             ExprKind::Pipeline { left, right } => self.check_pipeline_expr(left, right),
 
             // Access
-            ExprKind::Member { object, name } | ExprKind::SafeMember { object, name } => {
-                self.check_member_expr(expr.span, object, name)
+            ExprKind::Member { object, name } => self.check_member_expr(expr.span, object, name),
+            ExprKind::SafeMember { object, name } => {
+                self.check_safe_member_expr(expr.span, object, name)
             }
             ExprKind::Index { object, index } => self.check_index_expr(expr.span, object, index),
 
@@ -66,6 +72,12 @@ impl TypeChecker {
                 arguments,
             } => self.check_new_expr(expr.span, class_expr, arguments),
             ExprKind::Array(elements) => self.check_array_expr(expr.span, elements),
+            ExprKind::Tuple(elements) => Ok(Type::Tuple(
+                elements
+                    .iter()
+                    .map(|e| self.check_expr(e))
+                    .collect::<TypeResult<_>>()?,
+            )),
             ExprKind::Hash(pairs) => self.check_hash_expr(expr.span, pairs),
             ExprKind::Block(statements) => self.check_block_expr(statements),
             ExprKind::Assign { target, value } => self.check_assign_expr(expr.span, target, value),
@@ -81,15 +93,13 @@ impl TypeChecker {
                 iterable,
                 condition,
             } => self.check_list_comprehension(element, variable, iterable, condition.as_deref()),
-            ExprKind::HashComprehension {
-                key,
-                value,
-                variable,
-                iterable,
-                condition,
-            } => {
-                self.check_hash_comprehension(key, value, variable, iterable, condition.as_deref())
-            }
+            ExprKind::HashComprehension(comp) => self.check_hash_comprehension(
+                &comp.key,
+                &comp.value,
+                &comp.variable,
+                &comp.iterable,
+                comp.condition.as_ref(),
+            ),
             ExprKind::Spread(inner) => self.check_spread_expr(inner),
 
             // Control flow
@@ -99,8 +109,10 @@ impl TypeChecker {
                 params,
                 return_type,
                 body,
-            } => self.check_lambda_expr(body, params, return_type),
+                ..
+            } => self.check_lambda_expr(body, params, return_type, &[]),
             ExprKind::Throw(inner) => self.check_throw_expr(inner),
+            ExprKind::Await(inner) => self.check_await_expr(inner),
             ExprKind::Rescue { expr, fallback } => {
                 self.check_expr(expr)?;
                 self.check_expr(fallback)
@@ -114,12 +126,14 @@ impl TypeChecker {
 
             // Compound assignment returns the computed value type
             ExprKind::CompoundAssign { target, value, .. } => {
+                self.check_not_const(expr.span, target)?;
                 self.check_expr(target)?;
                 self.check_expr(value)
             }
 
             // Postfix increment/decrement returns the original numeric type
             ExprKind::PostfixIncrement(target) | ExprKind::PostfixDecrement(target) => {
+                self.check_not_const(expr.span, target)?;
                 self.check_expr(target)
             }
         }
@@ -149,6 +163,15 @@ impl TypeChecker {
         match (a, b) {
             (Type::Any, _) | (_, Type::Any) => Type::Any,
             (Type::Int, Type::Float) | (Type::Float, Type::Int) => Type::Float,
+            // `cond ? user : null` and friends stay nullable
+            (Type::Null, other) | (other, Type::Null) if matches!(other, Type::Nullable(_)) => {
+                other.clone()
+            }
+            (Type::Nullable(inner), other) | (other, Type::Nullable(inner))
+                if **inner == *other =>
+            {
+                Type::Nullable(inner.clone())
+            }
             (Type::Array(a_elem), Type::Array(b_elem)) => {
                 Type::Array(Box::new(self.widen_types(a_elem, b_elem)))
             }
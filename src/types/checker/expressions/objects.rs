@@ -7,6 +7,7 @@ use crate::span::Span;
 use crate::types::type_repr::Type;
 
 use super::{TypeChecker, TypeResult};
+use crate::types::checker::nullability::null_facts;
 
 impl TypeChecker {
     /// Check array expression.
@@ -83,26 +84,90 @@ impl TypeChecker {
         })
     }
 
+    /// Check a hash literal written where the record `fields` is expected:
+    /// every key must be a field, with a value of its type, and every field
+    /// that isn't nullable must be given.
+    pub(crate) fn check_record_literal(
+        &mut self,
+        span: Span,
+        pairs: &[(Expr, Expr)],
+        fields: &[(String, Type)],
+    ) -> TypeResult<Type> {
+        let record = Type::Record(fields.to_vec());
+        let mut given = Vec::new();
+        for (key_expr, val_expr) in pairs {
+            let ExprKind::StringLiteral(key) = &key_expr.kind else {
+                // A computed key could be any field; check the value alone.
+                self.check_expr(key_expr)?;
+                self.check_expr(val_expr)?;
+                continue;
+            };
+            let Some(field_type) = Type::record_field(fields, key) else {
+                return Err(TypeError::NoSuchMember {
+                    type_name: record.to_string(),
+                    member: key.clone(),
+                    span: key_expr.span,
+                });
+            };
+            let value_type = self.check_expr_expecting(val_expr, Some(field_type))?;
+            if !value_type.is_assignable_to(field_type) {
+                return Err(TypeError::mismatch(
+                    format!("{}", field_type),
+                    format!("{}", value_type),
+                    val_expr.span,
+                ));
+            }
+            given.push(key.as_str());
+        }
+        let computed_keys = pairs
+            .iter()
+            .any(|(key, _)| !matches!(key.kind, ExprKind::StringLiteral(_)));
+        if !computed_keys {
+            if let Some((missing, _)) = fields
+                .iter()
+                .find(|(name, ty)| !given.contains(&name.as_str()) && !is_optional(ty))
+            {
+                return Err(TypeError::General {
+                    message: format!("missing field '{}' for {}", missing, record),
+                    span,
+                });
+            }
+        }
+        Ok(record)
+    }
+
     /// Check new expression (constructor call).
     pub(crate) fn check_new_expr(
         &mut self,
-        _span: Span,
-        _class_expr: &Expr,
+        span: Span,
+        class_expr: &Expr,
         arguments: &[Argument],
     ) -> TypeResult<Type> {
+        if let ExprKind::Variable(name, _) = &class_expr.kind {
+            if let Some(class) = self.env.get_class(name).filter(|c| c.is_struct) {
+                let class = class.clone();
+                self.check_constructor_arguments(name, arguments, span)?;
+                return Ok(Type::Class(class));
+            }
+            if self
+                .env
+                .get_class(name)
+                .is_some_and(|c| c.signatures.contains_key("new"))
+            {
+                self.check_constructor_arguments(name, arguments, span)?;
+                return Ok(Type::Unknown);
+            }
+        }
         // For now, just check arguments and return an error type
         // Full type checking for qualified names would require runtime evaluation
         for arg in arguments {
             match arg {
-                Argument::Positional(expr) => {
+                Argument::Positional(expr) | Argument::Block(expr) | Argument::Spread(expr) => {
                     self.check_expr(expr)?;
                 }
                 Argument::Named(named) => {
                     self.check_expr(&named.value)?;
                 }
-                Argument::Block(expr) => {
-                    self.check_expr(expr)?;
-                }
             }
         }
         // Return an error type that will be resolved at runtime
@@ -112,12 +177,22 @@ impl TypeChecker {
 
     /// Check block expression.
     pub(crate) fn check_block_expr(&mut self, statements: &[Stmt]) -> TypeResult<Type> {
-        for stmt in statements {
-            self.check_stmt(stmt)?;
-        }
+        self.check_body(statements);
         Ok(Type::Null)
     }
 
+    /// Reject an assignment, compound assignment or `++`/`--` whose target
+    /// is a `const`.
+    pub(crate) fn check_not_const(&self, span: Span, target: &Expr) -> TypeResult<()> {
+        if let ExprKind::Variable(name, _) = &target.kind {
+            if let Some(declared) = self.env.const_span(name) {
+                return Err(TypeError::ConstReassignment(name.clone(), span)
+                    .with_note(declared, "declared as a constant here"));
+            }
+        }
+        Ok(())
+    }
+
     /// Check assignment expression.
     pub(crate) fn check_assign_expr(
         &mut self,
@@ -125,16 +200,53 @@ impl TypeChecker {
         target: &Expr,
         value: &Expr,
     ) -> TypeResult<Type> {
+        self.check_not_const(span, target)?;
         let value_type = self.check_expr(value)?;
 
-        // Auto-define: if target is an undefined variable, define it with the RHS type
-        if let ExprKind::Variable(name) = &target.kind {
-            if self.env.get(name).is_none() {
+        // Auto-define: if target is an undefined variable (or a builtin
+        // function it shadows), define it with the RHS type
+        if let ExprKind::Variable(name, _) = &target.kind {
+            if self.env.get(name).is_none() || self.env.is_builtin(name) {
                 self.env.define(name.clone(), value_type.clone());
                 return Ok(value_type);
             }
         }
 
+        // `object[index] = value` on a class that overloads `op_index_set`.
+        if let ExprKind::Index { object, index } = &target.kind {
+            let object_type = self.check_expr(object)?;
+            if let Type::Class(class) = &object_type {
+                if class
+                    .find_method(crate::ast::expr::INDEX_SET_METHOD)
+                    .is_some()
+                {
+                    let index_type = self.check_expr(index)?;
+                    self.operator_method_type(
+                        &object_type,
+                        Some(crate::ast::expr::INDEX_SET_METHOD),
+                        &[(index_type, index.span), (value_type.clone(), value.span)],
+                    )?;
+                    return Ok(value_type);
+                }
+            }
+        }
+
+        // Struct values are immutable: `money.amount = 1` is a type error.
+        if let ExprKind::Member { object, name } = &target.kind {
+            if let Type::Class(class) = self.check_expr(object)? {
+                if self.env.get_class(&class.name).is_some_and(|c| c.is_struct) {
+                    return Err(TypeError::General {
+                        message: format!(
+                            "cannot assign to '{}' of struct '{}' — use .with({}: ...) for \
+                             a changed copy",
+                            name, class.name, name
+                        ),
+                        span,
+                    });
+                }
+            }
+        }
+
         let target_type = self.check_expr(target)?;
 
         if !value_type.is_assignable_to(&target_type) {
@@ -156,7 +268,11 @@ impl TypeChecker {
         else_branch: Option<&Expr>,
     ) -> TypeResult<Type> {
         let cond_type = self.check_expr(condition)?;
-        if !matches!(cond_type, Type::Bool) {
+        // Same conditions as an `if` statement accepts.
+        if !matches!(
+            cond_type,
+            Type::Bool | Type::Any | Type::Unknown | Type::Nullable(_)
+        ) {
             return Err(TypeError::mismatch(
                 "Bool".to_string(),
                 format!("{}", cond_type),
@@ -164,10 +280,11 @@ impl TypeChecker {
             ));
         }
 
-        let then_type = self.check_expr(then_branch)?;
+        let facts = null_facts(condition);
+        let then_type = self.with_non_null(&facts.when_true, |c| c.check_expr(then_branch))?;
 
         if let Some(else_branch) = else_branch {
-            let else_type = self.check_expr(else_branch)?;
+            let else_type = self.with_non_null(&facts.when_false, |c| c.check_expr(else_branch))?;
             Ok(self.widen_types(&then_type, &else_type))
         } else {
             Ok(then_type)
@@ -194,66 +311,88 @@ impl TypeChecker {
     }
 
     /// Best-effort exhaustiveness check: when the scrutinee's static type is a
-    /// known enum and the arms cover neither all variants nor a `_`/binding
-    /// catch-all, record a non-blocking [`TypeError::ExhaustivenessWarning`].
-    /// Only fires when the enum type is statically inferable (dynamic Soli code
-    /// where the type is unknown is left alone).
+    /// known enum, `Bool` or an array, and the unguarded arms neither cover
+    /// every case nor include a `_`/binding catch-all, record a non-blocking
+    /// [`TypeError::ExhaustivenessWarning`]. Only fires when the type is
+    /// statically inferable (dynamic Soli code where the type is unknown is
+    /// left alone).
     fn check_match_exhaustiveness(&mut self, input_type: &Type, arms: &[MatchArm], span: Span) {
-        let enum_name = match input_type {
-            Type::Class(class) => class.name.clone(),
-            _ => return,
-        };
-        let all_variants = match self.env.get_enum(&enum_name) {
-            Some(enum_type) => enum_type.variants.clone(),
-            None => return,
-        };
-
-        // An unguarded `_` or bare-variable arm covers everything. A guarded
-        // arm guarantees nothing, so it doesn't count toward coverage.
-        let has_catch_all = arms.iter().any(|arm| {
-            arm.guard.is_none()
-                && matches!(
-                    arm.pattern,
-                    MatchPattern::Wildcard | MatchPattern::Variable(_)
-                )
-        });
-        if has_catch_all {
-            return;
-        }
-
-        let mut covered = std::collections::HashSet::new();
-        for arm in arms {
-            if arm.guard.is_some() {
-                continue;
-            }
-            if let MatchPattern::EnumVariant {
-                enum_name: arm_enum,
-                variant_name,
-                ..
-            } = &arm.pattern
-            {
-                if *arm_enum == enum_name {
-                    covered.insert(variant_name.clone());
-                }
-            }
-        }
-
-        let missing: Vec<String> = all_variants
-            .into_iter()
-            .filter(|variant| !covered.contains(variant))
+        // A guarded arm guarantees nothing, so it doesn't count toward coverage.
+        let patterns: Vec<&MatchPattern> = arms
+            .iter()
+            .filter(|arm| arm.guard.is_none())
+            .map(|arm| &arm.pattern)
             .collect();
+        let Some(missing) = self.missing_cases(input_type, &patterns) else {
+            return;
+        };
         if !missing.is_empty() {
             self.warnings.push(TypeError::ExhaustivenessWarning {
-                enum_name,
+                type_name: match input_type {
+                    Type::Class(class) => class.name.clone(),
+                    other => format!("{}", other),
+                },
                 missing: missing.join(", "),
                 span,
             });
         }
     }
 
+    /// The cases of `input_type` no pattern covers, or `None` when the type
+    /// isn't one the check can enumerate.
+    ///
+    /// A tuple counts as covered when, for some position, the arms that are
+    /// catch-alls everywhere else cover that position's type — `(true, _)`
+    /// with `(false, _)`; anything subtler is reported as the whole shape.
+    fn missing_cases(&self, input_type: &Type, patterns: &[&MatchPattern]) -> Option<Vec<String>> {
+        if patterns.iter().any(|p| covers_all(p, input_type)) {
+            return Some(Vec::new());
+        }
+        Some(match input_type {
+            Type::Class(class) => {
+                let enum_type = self.env.get_enum(&class.name)?;
+                missing_enum_variants(&class.name, &enum_type.variants, patterns)
+            }
+            Type::Bool => missing_bools(patterns),
+            Type::Array(_) => missing_array_shapes(patterns),
+            Type::Tuple(element_types) => {
+                let tuples: Vec<&[MatchPattern]> = patterns
+                    .iter()
+                    .filter_map(|p| match p {
+                        MatchPattern::Tuple(elements) if elements.len() == element_types.len() => {
+                            Some(elements.as_slice())
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                let covered = (0..element_types.len()).any(|i| {
+                    let column: Vec<&MatchPattern> = tuples
+                        .iter()
+                        .filter(|elements| {
+                            elements
+                                .iter()
+                                .zip(element_types)
+                                .enumerate()
+                                .all(|(j, (p, t))| j == i || covers_all(p, t))
+                        })
+                        .map(|elements| &elements[i])
+                        .collect();
+                    self.missing_cases(&element_types[i], &column)
+                        .is_some_and(|missing| missing.is_empty())
+                });
+                if covered {
+                    Vec::new()
+                } else {
+                    vec![tuple_shape(element_types.len())]
+                }
+            }
+            _ => return None,
+        })
+    }
+
     /// Check match arm.
     fn check_match_arm(&mut self, input_type: &Type, arm: &MatchArm) -> TypeResult<Type> {
-        self.check_match_pattern(input_type, &arm.pattern)?;
+        self.check_match_pattern(input_type, &arm.pattern, arm.span)?;
 
         if let Some(guard) = &arm.guard {
             let guard_type = self.check_expr(guard)?;
@@ -270,7 +409,12 @@ impl TypeChecker {
     }
 
     /// Check match pattern.
-    fn check_match_pattern(&mut self, input_type: &Type, pattern: &MatchPattern) -> TypeResult<()> {
+    fn check_match_pattern(
+        &mut self,
+        input_type: &Type,
+        pattern: &MatchPattern,
+        span: Span,
+    ) -> TypeResult<()> {
         match pattern {
             MatchPattern::Wildcard => Ok(()),
 
@@ -286,14 +430,16 @@ impl TypeChecker {
                     "Bool" => Type::Bool,
                     "String" => Type::String,
                     "Void" => Type::Void,
+                    "Array" => Type::Array(Box::new(Type::Any)),
+                    "Hash" => Type::Hash {
+                        key_type: Box::new(Type::Any),
+                        value_type: Box::new(Type::Any),
+                    },
                     _ => {
                         if let Some(class) = self.env.get_class(type_name).cloned() {
                             Type::Class(class)
                         } else {
-                            return Err(TypeError::UndefinedType(
-                                type_name.clone(),
-                                Span::default(),
-                            ));
+                            return Err(TypeError::UndefinedType(type_name.clone(), span));
                         }
                     }
                 };
@@ -302,11 +448,17 @@ impl TypeChecker {
                     return Err(TypeError::mismatch(
                         type_name.clone(),
                         format!("{}", input_type),
-                        Span::default(),
+                        span,
                     ));
                 }
 
-                self.env.define(name.clone(), input_type.clone());
+                // An untyped scrutinee is narrowed to the pattern's type.
+                let bound_type = if matches!(input_type, Type::Any) {
+                    expected_type
+                } else {
+                    input_type.clone()
+                };
+                self.env.define(name.clone(), bound_type);
                 Ok(())
             }
 
@@ -327,40 +479,73 @@ impl TypeChecker {
                     return Err(TypeError::mismatch(
                         format!("{}", input_type),
                         format!("{}", literal_type),
-                        Span::default(),
+                        span,
                     ));
                 }
                 Ok(())
             }
 
-            MatchPattern::Array { elements, rest: _ } => {
+            MatchPattern::Array { elements, rest } => {
                 // Allow Type::Any to match array patterns (e.g., untyped function parameters)
-                if !matches!(input_type, Type::Array(_) | Type::Any) {
-                    return Err(TypeError::mismatch(
-                        "Array".to_string(),
-                        format!("{}", input_type),
-                        Span::default(),
-                    ));
-                }
+                let element_type = match input_type {
+                    Type::Array(inner) => (**inner).clone(),
+                    Type::Any => Type::Any,
+                    _ => {
+                        return Err(TypeError::mismatch(
+                            "Array".to_string(),
+                            format!("{}", input_type),
+                            span,
+                        ))
+                    }
+                };
 
                 for element_pattern in elements {
-                    self.check_match_pattern(input_type, element_pattern)?;
+                    self.check_match_pattern(&element_type, element_pattern, span)?;
+                }
+                if let Some(rest) = rest {
+                    self.env.define(rest.clone(), input_type.clone());
                 }
                 Ok(())
             }
 
-            MatchPattern::Hash { fields, rest: _ } => {
-                // Allow Type::Any to match hash patterns (e.g., untyped function parameters)
-                if !matches!(input_type, Type::Hash { .. } | Type::Any) {
-                    return Err(TypeError::mismatch(
-                        "Hash".to_string(),
-                        format!("{}", input_type),
-                        Span::default(),
-                    ));
+            MatchPattern::Tuple(elements) => {
+                let element_types = match input_type {
+                    Type::Tuple(types) if types.len() == elements.len() => types.clone(),
+                    Type::Any | Type::Unknown => vec![Type::Any; elements.len()],
+                    _ => {
+                        return Err(TypeError::mismatch(
+                            tuple_shape(elements.len()),
+                            format!("{}", input_type),
+                            span,
+                        ))
+                    }
+                };
+
+                for (element_type, element_pattern) in element_types.iter().zip(elements) {
+                    self.check_match_pattern(element_type, element_pattern, span)?;
                 }
+                Ok(())
+            }
+
+            MatchPattern::Hash { fields, rest } => {
+                // Allow Type::Any to match hash patterns (e.g., untyped function parameters)
+                let value_type = match input_type {
+                    Type::Hash { value_type, .. } => (**value_type).clone(),
+                    Type::Any => Type::Any,
+                    _ => {
+                        return Err(TypeError::mismatch(
+                            "Hash".to_string(),
+                            format!("{}", input_type),
+                            span,
+                        ))
+                    }
+                };
 
                 for (_, field_pattern) in fields {
-                    self.check_match_pattern(input_type, field_pattern)?;
+                    self.check_match_pattern(&value_type, field_pattern, span)?;
+                }
+                if let Some(rest) = rest {
+                    self.env.define(rest.clone(), input_type.clone());
                 }
                 Ok(())
             }
@@ -371,16 +556,16 @@ impl TypeChecker {
                         return Err(TypeError::mismatch(
                             type_name.clone(),
                             format!("{}", input_type),
-                            Span::default(),
+                            span,
                         ));
                     }
 
                     for (_, field_pattern) in fields {
-                        self.check_match_pattern(input_type, field_pattern)?;
+                        self.check_match_pattern(input_type, field_pattern, span)?;
                     }
                     Ok(())
                 } else {
-                    Err(TypeError::UndefinedType(type_name.clone(), Span::default()))
+                    Err(TypeError::UndefinedType(type_name.clone(), span))
                 }
             }
 
@@ -389,21 +574,21 @@ impl TypeChecker {
                 // enum-aware checking + exhaustiveness lands with enum type
                 // registration (see `declare_enum`).
                 for binding in bindings {
-                    self.check_match_pattern(&Type::Any, binding)?;
+                    self.check_match_pattern(&Type::Any, binding, span)?;
                 }
                 Ok(())
             }
 
             MatchPattern::And(patterns) => {
                 for pattern in patterns {
-                    self.check_match_pattern(input_type, pattern)?;
+                    self.check_match_pattern(input_type, pattern, span)?;
                 }
                 Ok(())
             }
 
             MatchPattern::Or(patterns) => {
                 for pattern in patterns {
-                    self.check_match_pattern(input_type, pattern)?;
+                    self.check_match_pattern(input_type, pattern, span)?;
                 }
                 Ok(())
             }
@@ -484,3 +669,115 @@ impl TypeChecker {
         Ok(Type::Any)
     }
 }
+
+/// Whether `pattern` matches every value of `input_type`: `_`, a bare binding,
+/// or a typed binding of the scrutinee's own primitive type.
+fn covers_all(pattern: &MatchPattern, input_type: &Type) -> bool {
+    match pattern {
+        MatchPattern::Wildcard | MatchPattern::Variable(_) => true,
+        MatchPattern::Tuple(elements) => matches!(
+            input_type,
+            Type::Tuple(types) if types.len() == elements.len()
+                && elements.iter().zip(types).all(|(p, t)| covers_all(p, t))
+        ),
+        MatchPattern::Typed { type_name, .. } => matches!(
+            (type_name.as_str(), input_type),
+            ("Int", Type::Int)
+                | ("Float", Type::Float)
+                | ("Bool", Type::Bool)
+                | ("String", Type::String)
+                | ("Array", Type::Array(_))
+                | ("Hash", Type::Hash { .. })
+        ),
+        _ => false,
+    }
+}
+
+/// Variants of `enum_name` no `Enum.Variant` arm names.
+fn missing_enum_variants(
+    enum_name: &str,
+    variants: &[String],
+    patterns: &[&MatchPattern],
+) -> Vec<String> {
+    let covered: std::collections::HashSet<&str> = patterns
+        .iter()
+        .filter_map(|p| match p {
+            MatchPattern::EnumVariant {
+                enum_name: arm_enum,
+                variant_name,
+                ..
+            } if arm_enum == enum_name => Some(variant_name.as_str()),
+            _ => None,
+        })
+        .collect();
+    variants
+        .iter()
+        .filter(|variant| !covered.contains(variant.as_str()))
+        .cloned()
+        .collect()
+}
+
+fn missing_bools(patterns: &[&MatchPattern]) -> Vec<String> {
+    [true, false]
+        .into_iter()
+        .filter(|b| {
+            !patterns
+                .iter()
+                .any(|p| matches!(p, MatchPattern::Literal(ExprKind::BoolLiteral(v)) if v == b))
+        })
+        .map(|b| b.to_string())
+        .collect()
+}
+
+/// Array lengths no arm covers, rendered as patterns (`[]`, `[_, _]`, and
+/// `[_, ...]` for "this many or more"). Only arms whose elements are all
+/// catch-alls count: `[1, x]` covers some two-element arrays, not all of them.
+fn missing_array_shapes(patterns: &[&MatchPattern]) -> Vec<String> {
+    let mut exact = std::collections::HashSet::new();
+    let mut open_from: Option<usize> = None;
+    for pattern in patterns {
+        if let MatchPattern::Array { elements, rest } = pattern {
+            if !elements
+                .iter()
+                .all(|e| matches!(e, MatchPattern::Wildcard | MatchPattern::Variable(_)))
+            {
+                continue;
+            }
+            if rest.is_some() {
+                open_from = Some(open_from.map_or(elements.len(), |n| n.min(elements.len())));
+            } else {
+                exact.insert(elements.len());
+            }
+        }
+    }
+
+    let shape = |len: usize, open: bool| {
+        let mut parts = vec!["_"; len];
+        if open {
+            parts.push("...");
+        }
+        format!("[{}]", parts.join(", "))
+    };
+    let bound = open_from.unwrap_or_else(|| exact.iter().max().map_or(0, |n| n + 1));
+    let mut missing: Vec<String> = (0..bound)
+        .filter(|len| !exact.contains(len))
+        .map(|len| shape(len, false))
+        .collect();
+    if open_from.is_none() {
+        missing.push(shape(bound, true));
+    }
+    missing
+}
+
+/// `(_, _)` for a tuple of `len` elements.
+fn tuple_shape(len: usize) -> String {
+    format!("({})", vec!["_"; len].join(", "))
+}
+
+/// Whether a record field of type `ty` may be left out.
+fn is_optional(ty: &Type) -> bool {
+    matches!(
+        ty,
+        Type::Nullable(_) | Type::Null | Type::Any | Type::Unknown
+    )
+}
@@ -6,6 +6,7 @@ use crate::span::Span;
 use crate::types::type_repr::Type;
 
 use super::{TypeChecker, TypeResult};
+use crate::types::checker::nullability::null_facts;
 
 impl TypeChecker {
     /// Check binary expression.
@@ -19,6 +20,18 @@ impl TypeChecker {
         let left_type = self.check_expr(left)?;
         let right_type = self.check_expr(right)?;
 
+        if let Some(result_type) = self.operator_method_type(
+            &left_type,
+            operator.method_name(),
+            &[(right_type.clone(), right.span)],
+        )? {
+            return Ok(if operator.is_comparison() {
+                Type::Bool
+            } else {
+                result_type
+            });
+        }
+
         match operator {
             BinaryOp::Add => {
                 if matches!(left_type, Type::String) || matches!(right_type, Type::String) {
@@ -118,7 +131,9 @@ impl TypeChecker {
                     })
                 }
             }
-            BinaryOp::Range => {
+            // A range is typed as the `Int[]` it stands for: it's iterable,
+            // indexable and takes array methods, it just isn't materialized.
+            BinaryOp::Range | BinaryOp::RangeInclusive => {
                 if (matches!(left_type, Type::Int) && matches!(right_type, Type::Int))
                     || matches!(left_type, Type::Any | Type::Unknown)
                     || matches!(right_type, Type::Any | Type::Unknown)
@@ -127,8 +142,8 @@ impl TypeChecker {
                 } else {
                     Err(TypeError::General {
                         message: format!(
-                            "range (..) expects two integers, got {} and {}",
-                            left_type, right_type
+                            "range ({}) expects two integers, got {} and {}",
+                            operator, left_type, right_type
                         ),
                         span,
                     })
@@ -149,6 +164,13 @@ impl TypeChecker {
         operand: &Expr,
     ) -> TypeResult<Type> {
         let operand_type = self.check_expr(operand)?;
+        if *operator == UnaryOp::Negate {
+            if let Some(result_type) =
+                self.operator_method_type(&operand_type, Some(expr::NEGATE_METHOD), &[])?
+            {
+                return Ok(result_type);
+            }
+        }
         match operator {
             UnaryOp::Negate => {
                 if operand_type.is_numeric() || matches!(operand_type, Type::Any | Type::Unknown) {
@@ -164,10 +186,52 @@ impl TypeChecker {
         }
     }
 
+    /// The type of an operator applied to an instance of a class that
+    /// overloads it through the magic method `name` (see
+    /// [`BinaryOp::method_name`]), after checking the operands against the
+    /// method's parameters. `None` when `receiver` isn't such a class, so the
+    /// built-in rules apply.
+    pub(crate) fn operator_method_type(
+        &self,
+        receiver: &Type,
+        name: Option<&str>,
+        operands: &[(Type, Span)],
+    ) -> TypeResult<Option<Type>> {
+        let (Type::Class(class), Some(name)) = (receiver, name) else {
+            return Ok(None);
+        };
+        let Some(method) = class.find_method(name) else {
+            return Ok(None);
+        };
+        for ((operand_type, span), (_, param_type)) in operands.iter().zip(&method.params) {
+            if !matches!(param_type, Type::Any) && !operand_type.is_assignable_to(param_type) {
+                return Err(TypeError::mismatch(
+                    format!("{}", param_type),
+                    format!("{}", operand_type),
+                    *span,
+                ));
+            }
+        }
+        Ok(Some(method.return_type.clone()))
+    }
+
     /// Check logical AND/OR expression.
-    pub(crate) fn check_logical(&mut self, left: &Expr, right: &Expr) -> TypeResult<Type> {
+    /// Check `&&` (`is_and`) / `||`. The right side only runs once the left
+    /// side held (`&&`) or failed (`||`), so it sees the left's null checks.
+    pub(crate) fn check_logical(
+        &mut self,
+        left: &Expr,
+        right: &Expr,
+        is_and: bool,
+    ) -> TypeResult<Type> {
         self.check_expr(left)?;
-        self.check_expr(right)?;
+        let facts = null_facts(left);
+        let narrowed = if is_and {
+            facts.when_true
+        } else {
+            facts.when_false
+        };
+        self.with_non_null(&narrowed, |c| c.check_expr(right))?;
         Ok(Type::Bool)
     }
 
@@ -177,9 +241,14 @@ impl TypeChecker {
         left: &Expr,
         right: &Expr,
     ) -> TypeResult<Type> {
-        self.check_expr(left)?;
+        let left_type = self.check_expr(left)?;
         let right_type = self.check_expr(right)?;
-        // The result type is the right type (since if left is null, we return right)
-        Ok(right_type)
+        // `x ?? fallback` on a `T?` is a `T` when the fallback is one;
+        // otherwise the result type is the right type (since if left is
+        // null, we return right)
+        match left_type {
+            Type::Nullable(inner) if right_type.is_assignable_to(&inner) => Ok(*inner),
+            _ => Ok(right_type),
+        }
     }
 }
@@ -2,11 +2,17 @@
 
 mod declarations;
 mod expressions;
+mod nullability;
 mod statements;
+mod stubs;
+mod templates;
 
 use crate::ast::*;
 use crate::error::TypeError;
+use crate::span::Span;
+use crate::types::coverage::TypeCoverage;
 use crate::types::environment::TypeEnvironment;
+use crate::types::stubs::{builtin_stubs, StubFile};
 use crate::types::type_repr::{ClassType, Type};
 
 pub(crate) type TypeResult<T> = Result<T, TypeError>;
@@ -18,6 +24,23 @@ pub struct TypeChecker {
     /// Non-blocking diagnostics (e.g. enum match non-exhaustiveness). Surfaced
     /// by `soli check` but never fail the check or block execution.
     pub(crate) warnings: Vec<TypeError>,
+    /// Project and package stubs, applied over the program's declarations.
+    pub(crate) stubs: Vec<StubFile>,
+    /// Where the return type of the function being checked was declared,
+    /// for pointing a mismatched `return` back at it.
+    pub(crate) return_annotation: Option<Span>,
+    /// The types the `return`s of the function being checked produce, for
+    /// inferring a lambda's return type.
+    pub(crate) return_types: Vec<Type>,
+    /// The type of the expression statement checked last: a lambda whose
+    /// body ends in one returns its value.
+    pub(crate) last_expression_type: Option<Type>,
+    /// The inferred type of every expression of the program itself, when
+    /// collecting coverage (see [`TypeChecker::with_coverage`]).
+    pub(crate) coverage: Option<TypeCoverage>,
+    /// Inside a statement an import brought in, whose expressions don't
+    /// count towards the program's coverage.
+    pub(crate) in_import: bool,
 }
 
 impl TypeChecker {
@@ -26,10 +49,40 @@ impl TypeChecker {
         // Register Model as a built-in class so subclasses can inherit from it.
         // Model's methods are native_static_methods resolved at runtime.
         env.define_class(ClassType::new("Model".to_string()));
-        Self {
+        let mut checker = Self {
             env,
             errors: Vec::new(),
             warnings: Vec::new(),
+            stubs: Vec::new(),
+            return_annotation: None,
+            return_types: Vec::new(),
+            last_expression_type: None,
+            coverage: None,
+            in_import: false,
+        };
+        checker.declare_stubs(builtin_stubs(), true);
+        checker
+    }
+
+    /// Collect the inferred type of every expression checked, for
+    /// [`TypeChecker::take_coverage`].
+    pub fn with_coverage(mut self) -> Self {
+        self.coverage = Some(TypeCoverage::new());
+        self
+    }
+
+    /// The coverage collected so far; empty unless enabled with
+    /// [`TypeChecker::with_coverage`].
+    pub fn take_coverage(&mut self) -> TypeCoverage {
+        self.coverage.take().unwrap_or_default()
+    }
+
+    pub(crate) fn record_coverage(&mut self, expr: &Expr, ty: &Type) {
+        if self.in_import {
+            return;
+        }
+        if let Some(coverage) = &mut self.coverage {
+            coverage.record(expr, ty);
         }
     }
 
@@ -45,6 +98,10 @@ impl TypeChecker {
 
     /// Type check a complete program.
     pub fn check(&mut self, program: &Program) -> Result<(), Vec<TypeError>> {
+        // Type aliases first, so declarations can name them; again after
+        // the first pass, so aliases see the classes it declared.
+        self.declare_type_aliases(&program.statements);
+
         // First pass: collect all class and interface declarations
         for stmt in &program.statements {
             if let StmtKind::Class(decl) = &stmt.kind {
@@ -56,6 +113,8 @@ impl TypeChecker {
                 // tracked separately (see `declare_enum`).
                 self.declare_class(&decl.lower_to_class());
                 self.declare_enum(decl);
+            } else if let StmtKind::Struct(decl) = &stmt.kind {
+                self.declare_struct(decl);
             } else if let StmtKind::Interface(decl) = &stmt.kind {
                 self.declare_interface(decl);
             } else if let StmtKind::Function(decl) = &stmt.kind {
@@ -63,17 +122,73 @@ impl TypeChecker {
             }
         }
 
-        // Second pass: check all declarations
-        for stmt in &program.statements {
-            if let Err(e) = self.check_stmt(stmt) {
-                self.errors.push(e);
-            }
+        self.declare_type_aliases(&program.statements);
+
+        for stubs in std::mem::take(&mut self.stubs) {
+            self.declare_stubs(&stubs, false);
+            self.stubs.push(stubs);
         }
 
+        // Second pass: check all declarations
+        self.check_body(&program.statements);
+
         if self.errors.is_empty() {
             Ok(())
         } else {
-            Err(std::mem::take(&mut self.errors))
+            // A receiver is checked again when its call can't use the
+            // block-parameter types, so a lambda inside it reports twice.
+            let mut seen = std::collections::HashSet::new();
+            let mut errors = std::mem::take(&mut self.errors);
+            errors.retain(|e| seen.insert((e.span().start, e.span().end, e.to_string())));
+            Err(errors)
+        }
+    }
+
+    /// The type a caller sees for `param`. A variadic parameter soaks up any
+    /// number of arguments, so it reads as `Any` in the function's type.
+    pub(crate) fn param_type(&self, param: &Parameter) -> Type {
+        if param.is_variadic {
+            Type::Any
+        } else {
+            self.resolve_type(&param.type_annotation)
+        }
+    }
+
+    /// The type `param` has inside the body. A variadic parameter holds the
+    /// array of extra arguments: `...args: Array<Int>` as written, and
+    /// `...args` or `...args: Int` as an array of that type.
+    pub(crate) fn param_local_type(&self, param: &Parameter) -> Type {
+        let ty = self.resolve_type(&param.type_annotation);
+        if param.is_variadic && !matches!(ty, Type::Array(_)) {
+            Type::Array(Box::new(ty))
+        } else {
+            ty
+        }
+    }
+
+    /// The type a call returns, given the function's annotation and body. A
+    /// body that yields makes a generator: its calls return an iterator, and
+    /// the annotation names what each `yield` produces.
+    pub(crate) fn call_return_type(
+        &self,
+        annotation: Option<&TypeAnnotation>,
+        body: &[Stmt],
+        unannotated: Type,
+    ) -> Type {
+        if crate::ast::stmt::body_yields(body) {
+            let yielded = annotation.map_or(Type::Any, |t| self.resolve_type(t));
+            return Type::Iterator(Box::new(yielded));
+        }
+        annotation.map_or(unannotated, |t| self.resolve_type(t))
+    }
+
+    /// What a call hands back: an `async fn` returns a future for the type
+    /// its body returns.
+    pub(crate) fn call_result_type(is_async: bool, return_type: Type) -> Type {
+        if is_async {
+            Type::Future(Box::new(return_type))
+        } else {
+            return_type
         }
     }
 
@@ -86,7 +201,9 @@ impl TypeChecker {
                 "String" => Type::String,
                 "Any" => Type::Any,
                 _ => {
-                    if let Some(class) = self.env.get_class(name) {
+                    if let Some(alias) = self.env.get_type_alias(name) {
+                        alias.clone()
+                    } else if let Some(class) = self.env.get_class(name) {
                         Type::Class(class.clone())
                     } else if let Some(iface) = self.env.get_interface(name) {
                         Type::Interface(iface.clone())
@@ -104,10 +221,7 @@ impl TypeChecker {
                 params: params.iter().map(|p| self.resolve_type(p)).collect(),
                 return_type: Box::new(self.resolve_type(return_type)),
             },
-            TypeKind::Nullable(inner) => {
-                // For now, treat nullable as the inner type (simplification)
-                self.resolve_type(inner)
-            }
+            TypeKind::Nullable(inner) => Type::nullable(self.resolve_type(inner)),
             TypeKind::Hash {
                 key_type,
                 value_type,
@@ -115,6 +229,15 @@ impl TypeChecker {
                 key_type: Box::new(self.resolve_type(key_type)),
                 value_type: Box::new(self.resolve_type(value_type)),
             },
+            TypeKind::Tuple(elements) => {
+                Type::Tuple(elements.iter().map(|e| self.resolve_type(e)).collect())
+            }
+            TypeKind::Record(fields) => Type::Record(
+                fields
+                    .iter()
+                    .map(|f| (f.name.clone(), self.resolve_type(&f.type_annotation)))
+                    .collect(),
+            ),
         }
     }
 }
@@ -0,0 +1,167 @@
+//! Null-safety: narrowing `T?` variables to `T` where a null check proves
+//! them non-null.
+//!
+//! A condition yields the variables it proves non-null when it holds and
+//! when it fails. The checker re-binds those variables to their inner type
+//! in the branch that follows (`if`, `?:`, the right side of `&&` / `||`),
+//! and for the rest of the block after a guard that always exits:
+//!
+//! ```soli
+//! fn title(post: Post?) -> String
+//!   if post == null
+//!     return ""
+//!   end
+//!   post.title        # narrowed to Post
+//! end
+//! ```
+
+use crate::ast::*;
+use crate::error::TypeError;
+use crate::span::Span;
+use crate::types::type_repr::Type;
+
+use super::TypeChecker;
+
+/// Variables proven non-null when a condition holds / fails.
+#[derive(Debug, Default)]
+pub(crate) struct NullFacts {
+    pub when_true: Vec<String>,
+    pub when_false: Vec<String>,
+}
+
+impl NullFacts {
+    fn swapped(self) -> Self {
+        Self {
+            when_true: self.when_false,
+            when_false: self.when_true,
+        }
+    }
+}
+
+/// What `condition` proves about which variables are non-null.
+pub(crate) fn null_facts(condition: &Expr) -> NullFacts {
+    match &condition.kind {
+        ExprKind::Grouping(inner) => null_facts(inner),
+        // `if user` — null is falsy
+        ExprKind::Variable(name, _) => NullFacts {
+            when_true: vec![name.clone()],
+            when_false: Vec::new(),
+        },
+        ExprKind::Unary {
+            operator: UnaryOp::Not,
+            operand,
+        } => null_facts(operand).swapped(),
+        ExprKind::Binary {
+            left,
+            operator: operator @ (BinaryOp::Equal | BinaryOp::NotEqual),
+            right,
+        } => {
+            let checked = match (&left.kind, &right.kind) {
+                (ExprKind::Variable(name, _), ExprKind::Null)
+                | (ExprKind::Null, ExprKind::Variable(name, _)) => vec![name.clone()],
+                _ => return NullFacts::default(),
+            };
+            let facts = NullFacts {
+                when_true: checked,
+                when_false: Vec::new(),
+            };
+            if matches!(operator, BinaryOp::Equal) {
+                facts.swapped()
+            } else {
+                facts
+            }
+        }
+        // `user.nil?` / `user.present?`, with or without parens
+        ExprKind::Call { callee, arguments } if arguments.is_empty() => null_facts(callee),
+        ExprKind::Member { object, name } => match (&object.kind, name.as_str()) {
+            (ExprKind::Variable(var, _), "present?") => NullFacts {
+                when_true: vec![var.clone()],
+                when_false: Vec::new(),
+            },
+            (ExprKind::Variable(var, _), "nil?") => NullFacts {
+                when_true: Vec::new(),
+                when_false: vec![var.clone()],
+            },
+            _ => NullFacts::default(),
+        },
+        ExprKind::LogicalAnd { left, right } => {
+            let mut when_true = null_facts(left).when_true;
+            when_true.extend(null_facts(right).when_true);
+            NullFacts {
+                when_true,
+                when_false: Vec::new(),
+            }
+        }
+        ExprKind::LogicalOr { left, right } => {
+            let mut when_false = null_facts(left).when_false;
+            when_false.extend(null_facts(right).when_false);
+            NullFacts {
+                when_true: Vec::new(),
+                when_false,
+            }
+        }
+        _ => NullFacts::default(),
+    }
+}
+
+/// Error for reading `what` off a possibly-null value of type `ty`.
+pub(crate) fn null_dereference(ty: &Type, what: &str, span: Span) -> TypeError {
+    TypeError::General {
+        message: format!(
+            "cannot read {} of a possibly-null '{}' — check it for null first, \
+             or use `?.` / `??`",
+            what, ty
+        ),
+        span,
+    }
+}
+
+/// Whether running `stmt` never falls through to the next statement.
+pub(crate) fn always_exits(stmt: &Stmt) -> bool {
+    match &stmt.kind {
+        StmtKind::Return(_) | StmtKind::Throw(_) | StmtKind::Break => true,
+        StmtKind::Expression(Expr {
+            kind: ExprKind::Throw(_),
+            ..
+        }) => true,
+        StmtKind::Block(statements) => statements.last().is_some_and(always_exits),
+        StmtKind::If {
+            then_branch,
+            else_branch: Some(else_branch),
+            ..
+        } => always_exits(then_branch) && always_exits(else_branch),
+        _ => false,
+    }
+}
+
+impl TypeChecker {
+    /// Re-bind each nullable variable in `names` to its inner type in the
+    /// current scope.
+    pub(crate) fn narrow_non_null(&mut self, names: &[String]) {
+        for name in names {
+            if let Some(Type::Nullable(inner)) = self.env.get(name) {
+                if let Some(declared) = self.env.const_span(name) {
+                    self.env.define_const(name.clone(), *inner, declared);
+                } else {
+                    self.env.define(name.clone(), *inner);
+                }
+            }
+        }
+    }
+
+    /// Run `check` in a fresh scope where `names` are narrowed to non-null.
+    pub(crate) fn with_non_null<T>(
+        &mut self,
+        names: &[String],
+        check: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        if names.is_empty() {
+            return check(self);
+        }
+        self.env.push_scope();
+        self.narrow_non_null(names);
+        let result = check(self);
+        self.env.pop_scope();
+        result
+    }
+}
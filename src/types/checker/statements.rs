@@ -2,15 +2,17 @@
 
 use crate::ast::*;
 use crate::error::TypeError;
-use crate::types::type_repr::Type;
+use crate::types::type_repr::{ParamSignature, Type};
 
+use super::nullability::{always_exits, null_facts};
 use super::{TypeChecker, TypeResult};
 
 impl TypeChecker {
     pub(crate) fn check_stmt(&mut self, stmt: &Stmt) -> TypeResult<()> {
         match &stmt.kind {
             StmtKind::Expression(expr) => {
-                self.check_expr(expr)?;
+                self.last_expression_type = None;
+                self.last_expression_type = Some(self.check_expr(expr)?);
                 Ok(())
             }
 
@@ -20,10 +22,11 @@ impl TypeChecker {
                 name,
                 type_annotation,
                 initializer,
+                ..
             } => {
                 let declared_type = type_annotation.as_ref().map(|t| self.resolve_type(t));
                 let init_type = if let Some(init) = initializer {
-                    Some(self.check_expr(init)?)
+                    Some(self.check_expr_expecting(init, declared_type.as_ref())?)
                 } else {
                     None
                 };
@@ -31,10 +34,11 @@ impl TypeChecker {
                 let var_type = match (declared_type, init_type) {
                     (Some(decl), Some(init)) => {
                         if !init.is_assignable_to(&decl) {
-                            return Err(TypeError::mismatch(
-                                format!("{}", decl),
-                                format!("{}", init),
+                            return Err(annotation_mismatch(
+                                &decl,
+                                &init,
                                 stmt.span,
+                                type_annotation.as_deref(),
                             ));
                         }
                         decl
@@ -48,21 +52,51 @@ impl TypeChecker {
                 Ok(())
             }
 
+            StmtKind::LetTuple {
+                names,
+                type_annotation,
+                initializer,
+            } => {
+                let init_type = self.check_expr(initializer)?;
+                let tuple_type = match type_annotation {
+                    Some(annotation) => {
+                        let decl = self.resolve_type(annotation);
+                        if !init_type.is_assignable_to(&decl) {
+                            return Err(TypeError::mismatch(
+                                format!("{}", decl),
+                                format!("{}", init_type),
+                                stmt.span,
+                            )
+                            .with_note(annotation.span, annotation_note(&decl)));
+                        }
+                        decl
+                    }
+                    None => init_type,
+                };
+
+                let element_types = destructured_types(&tuple_type, names.len(), initializer.span)?;
+                for (name, ty) in names.iter().zip(element_types) {
+                    self.env.define(name.clone(), ty);
+                }
+                Ok(())
+            }
+
             StmtKind::Const {
                 name,
                 type_annotation,
                 initializer,
             } => {
                 let declared_type = type_annotation.as_ref().map(|t| self.resolve_type(t));
-                let init_type = self.check_expr(initializer)?;
+                let init_type = self.check_expr_expecting(initializer, declared_type.as_ref())?;
 
                 let const_type = match declared_type {
                     Some(decl) => {
                         if !init_type.is_assignable_to(&decl) {
-                            return Err(TypeError::mismatch(
-                                format!("{}", decl),
-                                format!("{}", init_type),
+                            return Err(annotation_mismatch(
+                                &decl,
+                                &init_type,
                                 stmt.span,
+                                type_annotation.as_deref(),
                             ));
                         }
                         decl
@@ -70,15 +104,13 @@ impl TypeChecker {
                     None => init_type,
                 };
 
-                self.env.define(name.clone(), const_type);
+                self.env.define_const(name.clone(), const_type, stmt.span);
                 Ok(())
             }
 
             StmtKind::Block(statements) => {
                 self.env.push_scope();
-                for s in statements {
-                    self.check_stmt(s)?;
-                }
+                self.check_body(statements);
                 self.env.pop_scope();
                 Ok(())
             }
@@ -89,43 +121,70 @@ impl TypeChecker {
                 else_branch,
             } => {
                 let cond_type = self.check_expr(condition)?;
-                if !matches!(cond_type, Type::Bool | Type::Any | Type::Unknown) {
+                // `if user` on a `User?` is a null check
+                if !matches!(
+                    cond_type,
+                    Type::Bool | Type::Any | Type::Unknown | Type::Nullable(_)
+                ) {
                     return Err(TypeError::mismatch(
                         "Bool",
                         format!("{}", cond_type),
                         condition.span,
                     ));
                 }
-                self.check_stmt(then_branch)?;
+                let facts = null_facts(condition);
+                self.with_non_null(&facts.when_true, |c| c.check_stmt(then_branch))?;
                 if let Some(else_br) = else_branch {
-                    self.check_stmt(else_br)?;
+                    self.with_non_null(&facts.when_false, |c| c.check_stmt(else_br))?;
+                }
+                // A guard that always exits (`if x == null { return }`)
+                // narrows the rest of the block.
+                let then_exits = always_exits(then_branch);
+                let else_exits = else_branch.as_deref().is_some_and(always_exits);
+                if then_exits && !else_exits {
+                    self.narrow_non_null(&facts.when_false);
+                } else if else_exits && !then_exits {
+                    self.narrow_non_null(&facts.when_true);
                 }
                 Ok(())
             }
 
             StmtKind::While { condition, body } => {
                 let cond_type = self.check_expr(condition)?;
-                if !matches!(cond_type, Type::Bool | Type::Any | Type::Unknown) {
+                if !matches!(
+                    cond_type,
+                    Type::Bool | Type::Any | Type::Unknown | Type::Nullable(_)
+                ) {
                     return Err(TypeError::mismatch(
                         "Bool",
                         format!("{}", cond_type),
                         condition.span,
                     ));
                 }
-                self.check_stmt(body)?;
+                let facts = null_facts(condition);
+                self.with_non_null(&facts.when_true, |c| c.check_stmt(body))?;
                 Ok(())
             }
 
             StmtKind::For {
                 variable,
                 index_variable,
+                destructure,
                 iterable,
                 body,
             } => {
                 let iter_type = self.check_expr(iterable)?;
+                // An `Any` iterable may be a hash, so its first name may
+                // be a key rather than an index.
+                let names_entries = matches!(iter_type, Type::Hash { .. } | Type::Any);
                 let elem_type = match iter_type {
-                    Type::Array(inner) => *inner,
+                    Type::Array(inner) | Type::Iterator(inner) => *inner,
                     Type::Any => Type::Any,
+                    Type::Tuple(elements) => Type::common(&elements),
+                    Type::Hash {
+                        key_type,
+                        value_type,
+                    } if destructure.is_some() => Type::Tuple(vec![*key_type, *value_type]),
                     _ => {
                         return Err(TypeError::General {
                             message: format!("cannot iterate over {}", iter_type),
@@ -135,6 +194,21 @@ impl TypeChecker {
                 };
 
                 self.env.push_scope();
+                if let Some(names) = destructure {
+                    // A hash's entries unpack into key and value; anything
+                    // else pairs with its index, so `for (i, item) in items`
+                    // binds `(Int, T)` whatever `T` is.
+                    let pattern_type = if names_entries {
+                        elem_type.clone()
+                    } else {
+                        Type::Tuple(vec![Type::Int, elem_type.clone()])
+                    };
+                    let element_types =
+                        destructured_types(&pattern_type, names.len(), iterable.span)?;
+                    for (name, ty) in names.iter().zip(element_types) {
+                        self.env.define(name.clone(), ty);
+                    }
+                }
                 self.env.define(variable.clone(), elem_type);
                 if let Some(idx_var) = index_variable {
                     self.env.define(idx_var.clone(), Type::Int);
@@ -145,19 +219,39 @@ impl TypeChecker {
             }
 
             StmtKind::Return(value) => {
+                // A generator's `return` only ends it; there's no caller
+                // waiting for a value.
+                if let (Some(Type::Iterator(_)), Some(expr)) = (self.env.return_type(), value) {
+                    return Err(TypeError::General {
+                        message: "a generator function can't return a value".to_string(),
+                        span: expr.span,
+                    });
+                }
+                if matches!(self.env.return_type(), Some(Type::Iterator(_))) {
+                    return Ok(());
+                }
                 let return_type = if let Some(expr) = value {
-                    self.check_expr(expr)?
+                    let expected = self.env.return_type().cloned();
+                    self.check_expr_expecting(expr, expected.as_ref())?
                 } else {
                     Type::Void
                 };
 
+                self.return_types.push(return_type.clone());
                 if let Some(expected) = self.env.return_type() {
                     if !return_type.is_assignable_to(expected) {
-                        return Err(TypeError::mismatch(
+                        let error = TypeError::mismatch(
                             format!("{}", expected),
                             format!("{}", return_type),
                             stmt.span,
-                        ));
+                        );
+                        return Err(match self.return_annotation {
+                            Some(annotation) => error.with_note(
+                                annotation,
+                                format!("expected `{}` because of this return type", expected),
+                            ),
+                            None => error,
+                        });
                     }
                 }
                 Ok(())
@@ -165,40 +259,37 @@ impl TypeChecker {
 
             StmtKind::Function(decl) => {
                 // Resolve parameter types and return type
-                let param_types: Vec<Type> = decl
-                    .params
-                    .iter()
-                    .map(|p| self.resolve_type(&p.type_annotation))
-                    .collect();
-                let return_type = decl
-                    .return_type
-                    .as_ref()
-                    .map(|t| self.resolve_type(t))
-                    .unwrap_or(Type::Any);
-
-                // Register function in the OUTER scope so callers (and recursion) can see it
-                let func_type = Type::Function {
-                    params: param_types.clone(),
-                    return_type: Box::new(return_type.clone()),
-                };
-                self.env.define(decl.name.clone(), func_type);
+                let param_types: Vec<Type> =
+                    decl.params.iter().map(|p| self.param_type(p)).collect();
+                let return_type =
+                    self.call_return_type(decl.return_type.as_ref(), &decl.body, Type::Any);
+
+                // Register function in the OUTER scope so callers (and recursion) can see it,
+                // unless a stub already declared what callers see.
+                if !self.stubs.iter().any(|s| s.function(&decl.name).is_some()) {
+                    let func_type = Type::Function {
+                        params: param_types.clone(),
+                        return_type: Box::new(Self::call_result_type(
+                            decl.is_async,
+                            return_type.clone(),
+                        )),
+                    };
+                    self.env.define_with_signature(
+                        decl.name.clone(),
+                        func_type,
+                        ParamSignature::of(&decl.params),
+                    );
+                }
 
                 // Now push inner scope for the body
                 self.env.push_scope();
 
-                // Define parameters
-                for (param, ty) in decl.params.iter().zip(param_types.iter()) {
-                    self.env.define(param.name.clone(), ty.clone());
-                }
-
-                self.env.set_return_type(Some(return_type));
-
-                // Check body
-                for s in &decl.body {
-                    self.check_stmt(s)?;
-                }
-
-                self.env.set_return_type(None);
+                self.define_params(&decl.params);
+                self.check_function_body(
+                    &decl.body,
+                    return_type,
+                    decl.return_type.as_ref().map(|t| t.span),
+                );
                 self.env.pop_scope();
                 Ok(())
             }
@@ -206,8 +297,9 @@ impl TypeChecker {
             StmtKind::Class(decl) => self.check_class_stmt(decl),
 
             StmtKind::Enum(decl) => self.check_enum_stmt(decl),
+            StmtKind::Struct(decl) => self.check_struct_stmt(decl),
 
-            StmtKind::Interface(_) => {
+            StmtKind::Interface(_) | StmtKind::TypeAlias(_) => {
                 // Already handled in first pass
                 Ok(())
             }
@@ -228,11 +320,37 @@ impl TypeChecker {
                 Ok(())
             }
 
+            StmtKind::Yield(value) => {
+                let yielded = self.check_expr(value)?;
+                let Some(Type::Iterator(expected)) = self.env.return_type() else {
+                    return Err(TypeError::General {
+                        message: "yield outside a generator function".to_string(),
+                        span: stmt.span,
+                    });
+                };
+                if !yielded.is_assignable_to(expected) {
+                    return Err(TypeError::mismatch(
+                        format!("{}", expected),
+                        format!("{}", yielded),
+                        value.span,
+                    ));
+                }
+                Ok(())
+            }
+
             StmtKind::Try {
                 try_block,
                 catch_clauses,
                 finally_block,
             } => {
+                // A generator can't pause inside a `try`: the runtime only
+                // steps blocks, `if`s and loops (see `executor::generators`).
+                if stmt.yields() {
+                    return Err(TypeError::General {
+                        message: "yield can't appear inside try".to_string(),
+                        span: stmt.span,
+                    });
+                }
                 self.check_stmt(try_block)?;
 
                 for clause in catch_clauses {
@@ -255,6 +373,28 @@ impl TypeChecker {
         }
     }
 
+    /// Check each field default against the field's annotation. The type
+    /// itself is registered in the first pass (see `declare_struct`).
+    fn check_struct_stmt(&mut self, decl: &StructDecl) -> TypeResult<()> {
+        for field in &decl.fields {
+            let Some(default) = &field.default_value else {
+                continue;
+            };
+            let default_type = self.check_expr(default)?;
+            if let Some(ta) = &field.type_annotation {
+                let declared = self.resolve_type(ta);
+                if !default_type.is_assignable_to(&declared) {
+                    return Err(TypeError::mismatch(
+                        format!("{}", declared),
+                        format!("{}", default_type),
+                        default.span,
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn check_enum_stmt(&mut self, _decl: &EnumDecl) -> TypeResult<()> {
         // Enum type registration, member-access checking, and match
         // exhaustiveness are handled separately (see `declare_enum`). The user
@@ -263,6 +403,86 @@ impl TypeChecker {
         Ok(())
     }
 
+    /// Define a function's parameters in the current scope, in order. Each
+    /// default is checked just before its own parameter is defined, so it
+    /// sees the parameters before it, as when it runs, and must fit the
+    /// annotation (`null` fits any, standing in for `Type?`).
+    /// Bind `params` in the current scope. A bad default is reported and
+    /// the parameter still bound, so the body is checked all the same.
+    fn define_params(&mut self, params: &[Parameter]) {
+        for param in params {
+            let ty = self.param_local_type(param);
+            if let Some(default) = &param.default_value {
+                match self.check_expr(default) {
+                    Ok(default_type)
+                        if default_type != Type::Null && !default_type.is_assignable_to(&ty) =>
+                    {
+                        self.errors.push(
+                            TypeError::mismatch(
+                                format!("{}", ty),
+                                format!("{}", default_type),
+                                default.span,
+                            )
+                            .with_note(param.type_annotation.span, annotation_note(&ty)),
+                        );
+                    }
+                    Ok(_) => {}
+                    Err(e) => self.errors.push(e),
+                }
+            }
+            self.env.define(param.name.clone(), ty);
+        }
+    }
+
+    /// Check each statement of `body`, reporting an error and moving on to
+    /// the next statement rather than stopping at the first. A declaration
+    /// that failed still binds its names (as `Any`), so later uses don't
+    /// report them as undefined.
+    pub(crate) fn check_body(&mut self, body: &[Stmt]) {
+        for stmt in body {
+            let outer_in_import = self.in_import;
+            self.in_import |= stmt.source_path.is_some();
+            let result = self.check_stmt(stmt);
+            self.in_import = outer_in_import;
+            if let Err(e) = result {
+                self.errors.push(e);
+                match &stmt.kind {
+                    StmtKind::Let { name, .. } => self.env.define(name.clone(), Type::Any),
+                    StmtKind::Const { name, .. } => {
+                        self.env.define_const(name.clone(), Type::Any, stmt.span)
+                    }
+                    StmtKind::LetTuple { names, .. } => {
+                        for name in names {
+                            self.env.define(name.clone(), Type::Any);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Check a function body whose `return`s must produce `return_type`,
+    /// declared by the annotation at `annotation`, and hand back the types
+    /// those `return`s produced. The enclosing function's return type is
+    /// restored afterwards, so a `yield` after a nested `fn` still sees the
+    /// enclosing generator.
+    pub(crate) fn check_function_body(
+        &mut self,
+        body: &[Stmt],
+        return_type: Type,
+        annotation: Option<crate::span::Span>,
+    ) -> Vec<Type> {
+        let enclosing = self.env.return_type().cloned();
+        let enclosing_annotation = std::mem::replace(&mut self.return_annotation, annotation);
+        let enclosing_returns = std::mem::take(&mut self.return_types);
+        self.env.set_return_type(Some(return_type));
+        self.check_body(body);
+        self.env.set_return_type(enclosing);
+        self.return_annotation = enclosing_annotation;
+        std::mem::replace(&mut self.return_types, enclosing_returns)
+    }
+
     fn check_class_stmt(&mut self, decl: &ClassDecl) -> TypeResult<()> {
         self.env.set_current_class(Some(decl.name.clone()));
 
@@ -276,25 +496,16 @@ impl TypeChecker {
                     .define("this".to_string(), Type::Class(class_type.clone()));
             }
 
-            // Define parameters
-            for param in &method.params {
-                let ty = self.resolve_type(&param.type_annotation);
-                self.env.define(param.name.clone(), ty);
-            }
+            self.define_params(&method.params);
 
             // Set return type (Any if not annotated)
-            let return_type = method
-                .return_type
-                .as_ref()
-                .map(|t| self.resolve_type(t))
-                .unwrap_or(Type::Any);
-            self.env.set_return_type(Some(return_type));
-
-            for s in &method.body {
-                self.check_stmt(s)?;
-            }
-
-            self.env.set_return_type(None);
+            let return_type =
+                self.call_return_type(method.return_type.as_ref(), &method.body, Type::Any);
+            self.check_function_body(
+                &method.body,
+                return_type,
+                method.return_type.as_ref().map(|t| t.span),
+            );
             self.env.pop_scope();
         }
 
@@ -307,18 +518,8 @@ impl TypeChecker {
                     .define("this".to_string(), Type::Class(class_type.clone()));
             }
 
-            for param in &ctor.params {
-                let ty = self.resolve_type(&param.type_annotation);
-                self.env.define(param.name.clone(), ty);
-            }
-
-            self.env.set_return_type(Some(Type::Void));
-
-            for s in &ctor.body {
-                self.check_stmt(s)?;
-            }
-
-            self.env.set_return_type(None);
+            self.define_params(&ctor.params);
+            self.check_function_body(&ctor.body, Type::Void, None);
             self.env.pop_scope();
         }
 
@@ -329,37 +530,109 @@ impl TypeChecker {
         Ok(())
     }
 
+    /// Verify that the class provides every method of each interface it
+    /// `implements`, inherited methods included. A method conforms when it
+    /// takes the same number of parameters, accepts everything the interface
+    /// promises to pass, and returns something the interface's callers can use.
     fn check_interface_implementation(&mut self, decl: &ClassDecl) {
         for iface_name in &decl.interfaces {
-            if let Some(iface) = self.env.get_interface(iface_name).cloned() {
-                if let Some(class) = self.env.get_class(&decl.name) {
-                    for (method_name, sig) in &iface.methods {
-                        if let Some(method) = class.find_method(method_name) {
-                            // Check signature compatibility
-                            let method_params: Vec<Type> =
-                                method.params.iter().map(|(_, t)| t.clone()).collect();
-                            if method_params != sig.params || method.return_type != sig.return_type
-                            {
-                                self.errors.push(TypeError::General {
-                                    message: format!(
-                                        "method '{}' does not match interface signature",
-                                        method_name
-                                    ),
-                                    span: decl.span,
-                                });
-                            }
-                        } else {
-                            self.errors.push(TypeError::General {
-                                message: format!(
-                                    "class '{}' does not implement method '{}' from interface '{}'",
-                                    decl.name, method_name, iface_name
-                                ),
-                                span: decl.span,
-                            });
-                        }
-                    }
+            let Some(iface) = self.env.get_interface(iface_name).cloned() else {
+                self.errors.push(TypeError::General {
+                    message: format!(
+                        "class '{}' implements unknown interface '{}'",
+                        decl.name, iface_name
+                    ),
+                    span: decl.span,
+                });
+                continue;
+            };
+            let Some(class) = self.env.get_class(&decl.name).cloned() else {
+                continue;
+            };
+            let mut required: Vec<_> = iface.methods.values().collect();
+            required.sort_by(|a, b| a.name.cmp(&b.name));
+            for sig in required {
+                let Some(method) = class.find_method(&sig.name) else {
+                    self.errors.push(TypeError::General {
+                        message: format!(
+                            "class '{}' does not implement method '{}' from interface '{}'",
+                            decl.name, sig.name, iface_name
+                        ),
+                        span: decl.span,
+                    });
+                    continue;
+                };
+                let method_params: Vec<Type> =
+                    method.params.iter().map(|(_, t)| t.clone()).collect();
+                let conforms = method_params.len() == sig.params.len()
+                    && sig
+                        .params
+                        .iter()
+                        .zip(&method_params)
+                        .all(|(expected, actual)| expected.is_assignable_to(actual))
+                    && method.return_type.is_assignable_to(&sig.return_type);
+                if !conforms {
+                    self.errors.push(TypeError::General {
+                        message: format!(
+                            "method '{}' in class '{}' does not match interface '{}': expected {}, found {}",
+                            sig.name,
+                            decl.name,
+                            iface_name,
+                            signature(&sig.params, &sig.return_type),
+                            signature(&method_params, &method.return_type),
+                        ),
+                        span: decl.span,
+                    });
                 }
             }
         }
     }
 }
+
+/// A declared type that doesn't accept its initializer, pointing back at
+/// the annotation that declared it.
+fn annotation_mismatch(
+    declared: &Type,
+    found: &Type,
+    span: crate::span::Span,
+    annotation: Option<&TypeAnnotation>,
+) -> TypeError {
+    let error = TypeError::mismatch(format!("{}", declared), format!("{}", found), span);
+    match annotation {
+        Some(annotation) => error.with_note(annotation.span, annotation_note(declared)),
+        None => error,
+    }
+}
+
+fn annotation_note(declared: &Type) -> String {
+    format!("expected `{}` because of this annotation", declared)
+}
+
+/// Render a method signature the way it is written: `(String, Int) -> Bool`.
+fn signature(params: &[Type], return_type: &Type) -> String {
+    let params: Vec<String> = params.iter().map(|t| t.to_string()).collect();
+    format!("({}) -> {}", params.join(", "), return_type)
+}
+
+/// The types `let (a, b) = ...` / `for (k, v) in ...` bind, one per name.
+/// Arrays destructure at runtime too, so their element type fills every slot.
+fn destructured_types(ty: &Type, arity: usize, span: crate::span::Span) -> TypeResult<Vec<Type>> {
+    match ty {
+        Type::Tuple(elements) if elements.len() == arity => Ok(elements.clone()),
+        Type::Tuple(elements) => Err(TypeError::General {
+            message: format!(
+                "cannot destructure {} of {} elements into {} names",
+                ty,
+                elements.len(),
+                arity
+            ),
+            span,
+        }),
+        Type::Array(inner) => Ok(vec![(**inner).clone(); arity]),
+        Type::Any | Type::Unknown => Ok(vec![ty.clone(); arity]),
+        _ => Err(TypeError::General {
+            message: format!("cannot destructure {} into {} names", ty, arity),
+            span,
+        }),
+    }
+}
@@ -0,0 +1,139 @@
+//! Declaring `.sli` stub signatures (see `types::stubs`).
+
+use crate::types::stubs::{StubClass, StubFile, StubParam};
+use crate::types::type_repr::{ClassType, FieldInfo, MethodInfo, ParamSignature, Type};
+
+use super::TypeChecker;
+
+impl TypeChecker {
+    /// Use `stubs` for the names they declare. They are applied after the
+    /// program's own declarations are collected, so a stub overrides the
+    /// signature inferred from an imported module's source.
+    pub fn add_stubs(&mut self, stubs: StubFile) {
+        self.stubs.push(stubs);
+    }
+
+    /// Declare everything in `stubs`. Builtin stubs describe native
+    /// functions, which take named arguments as a trailing options hash, so
+    /// their parameter lists only check how many positional arguments a
+    /// call passes, not names, and a program is free to reassign them.
+    pub(crate) fn declare_stubs(&mut self, stubs: &StubFile, builtin: bool) {
+        // Every class exists before any signature names one.
+        for class in &stubs.classes {
+            if self.env.get_class(&class.name).is_none() {
+                self.env.define_class(ClassType::new(class.name.clone()));
+            }
+        }
+        for class in &stubs.classes {
+            self.declare_stub_class(class, builtin);
+        }
+
+        for function in &stubs.functions {
+            let (params, signature) = self.stub_params(&function.params);
+            self.env.define_function(
+                function.name.clone(),
+                Type::Function {
+                    params,
+                    return_type: Box::new(self.resolve_type(&function.return_type)),
+                },
+            );
+            if builtin {
+                self.env.mark_builtin(&function.name);
+                self.env
+                    .define_builtin_signature(function.name.clone(), signature);
+            } else {
+                self.env
+                    .define_function_signature(function.name.clone(), signature);
+            }
+        }
+
+        for global in &stubs.globals {
+            let ty = self.resolve_type(&global.type_annotation);
+            self.env.define(global.name.clone(), ty);
+        }
+    }
+
+    /// Stubbed members replace the ones of the same name; the rest of a class
+    /// declared from source (its private members, say) stay as they were.
+    fn declare_stub_class(&mut self, class: &StubClass, builtin: bool) {
+        let mut class_type = self
+            .env
+            .get_class(&class.name)
+            .cloned()
+            .unwrap_or_else(|| ClassType::new(class.name.clone()));
+        if let Some(superclass) = class
+            .superclass
+            .as_ref()
+            .and_then(|name| self.env.get_class(name))
+        {
+            class_type.superclass = Some(Box::new(superclass.clone()));
+        }
+
+        for field in &class.fields {
+            class_type.fields.insert(
+                field.name.clone(),
+                FieldInfo {
+                    name: field.name.clone(),
+                    ty: self.resolve_type(&field.type_annotation),
+                    is_private: false,
+                    is_static: field.is_static,
+                },
+            );
+        }
+
+        for method in &class.methods {
+            let (params, signature) = self.stub_params(&method.params);
+            let is_constructor = method.name == "new";
+            let return_type = if is_constructor {
+                Type::Class(ClassType::new(class.name.clone()))
+            } else {
+                self.resolve_type(&method.return_type)
+            };
+            if !builtin {
+                class_type.signatures.insert(method.name.clone(), signature);
+            }
+            class_type.methods.insert(
+                method.name.clone(),
+                MethodInfo {
+                    name: method.name.clone(),
+                    params: method
+                        .params
+                        .iter()
+                        .map(|p| p.name.clone())
+                        .zip(params)
+                        .collect(),
+                    return_type,
+                    is_private: false,
+                    is_static: method.is_static || is_constructor,
+                },
+            );
+        }
+
+        self.env.define_class(class_type);
+    }
+
+    /// Parameter types as a caller sees them, and the parameter list.
+    fn stub_params(&self, params: &[StubParam]) -> (Vec<Type>, Vec<ParamSignature>) {
+        let types = params
+            .iter()
+            .map(|p| {
+                if p.is_variadic {
+                    Type::Any
+                } else {
+                    self.resolve_type(&p.type_annotation)
+                }
+            })
+            .collect();
+        let signature = params
+            .iter()
+            .map(|p| ParamSignature {
+                name: p.name.clone(),
+                has_default: p.default.is_some(),
+                is_keyword_only: p.is_keyword_only,
+                is_block: p.is_block,
+                is_variadic: p.is_variadic,
+            })
+            .collect();
+        (types, signature)
+    }
+}
@@ -0,0 +1,116 @@
+//! Type-checking a template's embedded code against what renders it.
+//!
+//! A view's Soli code is checked like a script, with the template's scope
+//! declared first: the locals every `render(...)` passes (typed when the
+//! controller writes the value as a literal), the `@fields` of the
+//! controllers that render it, and the builtins, template helpers and app
+//! definitions it can call. A name none of those provide is an undefined
+//! local — at runtime it would silently read as `null`.
+
+use std::collections::HashSet;
+
+use crate::ast::{ExprKind, Program};
+use crate::error::TypeError;
+use crate::lint::rules::scope::is_likely_global;
+use crate::lint::templates::{for_each_expr, TemplateScope};
+use crate::types::type_repr::{ClassType, FieldInfo, Type};
+
+use super::TypeChecker;
+
+impl TypeChecker {
+    /// Check a template's extracted code (see
+    /// [`crate::template::parser::extract_lintable_code`]) in `scope`. When
+    /// some render site's data isn't statically known, undefined names are
+    /// left to the runtime and only the other type errors are reported.
+    pub fn check_template(
+        &mut self,
+        program: &Program,
+        scope: &TemplateScope,
+    ) -> Result<(), Vec<TypeError>> {
+        self.env.define(
+            "locals".to_string(),
+            Type::Hash {
+                key_type: Box::new(Type::String),
+                value_type: Box::new(Type::Any),
+            },
+        );
+        self.env.define("content".to_string(), Type::Any);
+        for name in &scope.known {
+            if self.env.get(name).is_none() && self.env.get_class(name).is_none() {
+                self.env.define(name.clone(), Type::Any);
+            }
+        }
+        for (name, ty) in scope.locals.iter().flatten() {
+            self.env.define(name.clone(), ty.clone());
+        }
+
+        // `@field` reads `this.field`: the view answers to a class holding
+        // the controllers' fields.
+        let mut view = ClassType::new(scope.name.clone());
+        for field in &scope.fields {
+            view.fields.insert(
+                field.clone(),
+                FieldInfo {
+                    name: field.clone(),
+                    ty: Type::Any,
+                    is_private: false,
+                    is_static: false,
+                },
+            );
+        }
+        self.env.define_class(view);
+        self.env.set_current_class(Some(scope.name.clone()));
+
+        let result = self.check(program);
+        self.env.set_current_class(None);
+        let Err(errors) = result else {
+            return Ok(());
+        };
+
+        // An unknown function is an undefined helper, which the
+        // `template/undefined-helper` lint reports; only reads are locals.
+        let mut helpers = HashSet::new();
+        for_each_expr(&program.statements, &mut |expr| {
+            if let ExprKind::Call { callee, .. } = &expr.kind {
+                if let ExprKind::Variable(name, _) = &callee.kind {
+                    helpers.insert(name.as_str());
+                }
+            }
+        });
+
+        let known_locals = scope.locals.is_some();
+        let mut reported = HashSet::new();
+        let errors: Vec<TypeError> = errors
+            .into_iter()
+            .filter_map(|error| match error {
+                TypeError::UndefinedVariable(name, span) => {
+                    let route_helper = name.ends_with("_path") || name.ends_with("_url");
+                    if !known_locals
+                        || route_helper
+                        || helpers.contains(name.as_str())
+                        || is_likely_global(&name)
+                    {
+                        return None;
+                    }
+                    // One report per name, at its first use.
+                    reported.insert(name.clone()).then(|| TypeError::General {
+                        message: format!(
+                            "'{}' is not a local of '{}': no render passes it and the template never assigns it",
+                            name, scope.name
+                        ),
+                        span,
+                    })
+                }
+                TypeError::NoSuchMember {
+                    ref type_name, ..
+                } if type_name == &scope.name && !known_locals => None,
+                error => Some(error),
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
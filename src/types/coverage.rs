@@ -0,0 +1,89 @@
+//! Gradual typing coverage: how much of a program the checker could type.
+
+use crate::ast::{Expr, NodeMap};
+use crate::types::type_repr::Type;
+
+/// For each expression the checker typed, whether its inferred type is
+/// something other than `Any`. Powers `soli types --coverage`.
+#[derive(Debug, Clone, Default)]
+pub struct TypeCoverage {
+    /// The line of each expression and whether it is typed, by node id: an
+    /// expression checked twice (a receiver re-checked for its block types,
+    /// say) counts once, as last inferred.
+    expressions: NodeMap<(u32, bool)>,
+}
+
+impl TypeCoverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `expr` has type `ty`.
+    pub fn record(&mut self, expr: &Expr, ty: &Type) {
+        let typed = !matches!(ty, Type::Any | Type::Unknown | Type::Var(_));
+        self.expressions.insert(expr.id, (expr.span.line, typed));
+    }
+
+    /// Number of expressions recorded.
+    pub fn total(&self) -> usize {
+        self.expressions.len()
+    }
+
+    /// Number of expressions with a type other than `Any`.
+    pub fn typed(&self) -> usize {
+        self.expressions
+            .values()
+            .filter(|(_, typed)| *typed)
+            .count()
+    }
+
+    /// Percentage of typed expressions; a file without expressions is fully
+    /// typed.
+    pub fn percent(&self) -> f64 {
+        if self.expressions.is_empty() {
+            return 100.0;
+        }
+        self.typed() as f64 * 100.0 / self.total() as f64
+    }
+
+    /// The lines holding an expression typed `Any`, in order.
+    pub fn untyped_lines(&self) -> Vec<usize> {
+        let mut lines: Vec<usize> = self
+            .expressions
+            .values()
+            .filter(|(_, typed)| !typed)
+            .map(|(line, _)| *line as usize)
+            .collect();
+        lines.sort_unstable();
+        lines.dedup();
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_each_expression_once_as_last_recorded() {
+        let program = crate::parse("1\nname\nother").unwrap();
+        let exprs: Vec<&Expr> = program
+            .statements
+            .iter()
+            .map(|stmt| match &stmt.kind {
+                crate::ast::StmtKind::Expression(expr) => expr,
+                other => panic!("expected an expression, got {:?}", other),
+            })
+            .collect();
+        let mut coverage = TypeCoverage::new();
+        coverage.record(exprs[0], &Type::Int);
+        coverage.record(exprs[1], &Type::Any);
+        coverage.record(exprs[1], &Type::String);
+        coverage.record(exprs[2], &Type::Unknown);
+        assert_eq!(coverage.total(), 3);
+        assert_eq!(coverage.typed(), 2);
+        assert_eq!(coverage.untyped_lines(), vec![3]);
+        assert!((coverage.percent() - 200.0 / 3.0).abs() < 1e-9);
+        assert_eq!(TypeCoverage::new().percent(), 100.0);
+    }
+}
@@ -0,0 +1,285 @@
+//! `soli check` findings in machine-readable form: JSON for scripts and
+//! SARIF 2.1.0 for CI annotations (GitHub code scanning, GitLab, ...).
+
+use crate::error::{SolilangError, TypeError};
+use crate::span::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+/// One finding of `soli check`, located in the file that was checked.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub path: String,
+    pub severity: Severity,
+    /// `syntax-error`, `type-error` or `non-exhaustive-match`.
+    pub rule: &'static str,
+    /// The message without its trailing `at line:column`.
+    pub message: String,
+    /// `None` when the error carries no position (a template that failed
+    /// to extract, say).
+    pub span: Option<Span>,
+    /// Related locations: where a name was declared, the other branch, ...
+    pub notes: Vec<(Span, String)>,
+}
+
+impl Diagnostic {
+    /// A type error or an exhaustiveness warning.
+    pub fn from_type_error(path: &str, error: &TypeError) -> Self {
+        let warning = matches!(
+            error.without_notes(),
+            TypeError::ExhaustivenessWarning { .. }
+        );
+        Self {
+            path: path.to_string(),
+            severity: if warning {
+                Severity::Warning
+            } else {
+                Severity::Error
+            },
+            rule: if warning {
+                "non-exhaustive-match"
+            } else {
+                "type-error"
+            },
+            message: error.headline(),
+            span: Some(error.span()),
+            notes: error.notes().to_vec(),
+        }
+    }
+
+    /// Every diagnostic `error` stands for: one per type error it carries,
+    /// or a single one for a lexer, parser or other failure.
+    pub fn from_error(path: &str, error: &SolilangError) -> Vec<Self> {
+        let type_errors = error.type_errors();
+        if !type_errors.is_empty() {
+            return type_errors
+                .iter()
+                .map(|e| Self::from_type_error(path, e))
+                .collect();
+        }
+        let span = error_span(error);
+        // Drop the phase prefix: the rule already says it's a syntax error.
+        let message = match error {
+            SolilangError::Lexer(e) => e.to_string(),
+            SolilangError::Parser(e) => e.to_string(),
+            other => other.to_string(),
+        };
+        let message = match span {
+            Some(span) => message
+                .strip_suffix(&format!(" at {}", span))
+                .map(str::to_string)
+                .unwrap_or(message),
+            None => message,
+        };
+        vec![Self {
+            path: path.to_string(),
+            severity: Severity::Error,
+            rule: match error {
+                SolilangError::Lexer(_) | SolilangError::Parser(_) => "syntax-error",
+                _ => "type-error",
+            },
+            message,
+            span,
+            notes: Vec::new(),
+        }]
+    }
+
+    /// A `path:line:column` location (`path` alone without a span).
+    pub fn location(&self) -> String {
+        match self.span {
+            Some(span) => format!("{}:{}", self.path, span),
+            None => self.path.clone(),
+        }
+    }
+}
+
+fn error_span(error: &SolilangError) -> Option<Span> {
+    match error {
+        SolilangError::Lexer(e) => Some(e.span()),
+        SolilangError::Parser(e) => Some(e.span()),
+        SolilangError::Compile(e) => Some(e.span()),
+        SolilangError::Runtime(e) => Some(e.span()),
+        SolilangError::Type(e) => Some(e.span()),
+        SolilangError::TypeErrors(errors) => errors.first().map(TypeError::span),
+        SolilangError::Context { error, .. } => error_span(error),
+        SolilangError::Io(_) | SolilangError::Template(_) => None,
+    }
+}
+
+/// The findings as one JSON document, with counts for a CI summary line.
+pub fn format_json(diagnostics: &[Diagnostic], files_checked: usize) -> String {
+    let count = |severity| {
+        diagnostics
+            .iter()
+            .filter(|d| d.severity == severity)
+            .count()
+    };
+    let value = serde_json::json!({
+        "files": files_checked,
+        "errors": count(Severity::Error),
+        "warnings": count(Severity::Warning),
+        "diagnostics": diagnostics.iter().map(|d| serde_json::json!({
+            "path": d.path,
+            "line": d.span.map(|s| s.line),
+            "column": d.span.map(|s| s.column),
+            "severity": d.severity.as_str(),
+            "rule": d.rule,
+            "message": d.message,
+            "notes": d.notes.iter().map(|(span, note)| serde_json::json!({
+                "line": span.line,
+                "column": span.column,
+                "message": note,
+            })).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>(),
+    });
+    serde_json::to_string_pretty(&value).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// The findings as a SARIF 2.1.0 log with a single run.
+pub fn format_sarif(diagnostics: &[Diagnostic]) -> String {
+    const RULES: [(&str, &str); 3] = [
+        ("syntax-error", "The file does not lex or parse."),
+        (
+            "type-error",
+            "A value does not have the type its use requires.",
+        ),
+        (
+            "non-exhaustive-match",
+            "A match on a known type can fall through.",
+        ),
+    ];
+    let location = |path: &str, span: Option<Span>| {
+        let mut physical = serde_json::json!({
+            "artifactLocation": { "uri": sarif_uri(path) },
+        });
+        if let Some(span) = span {
+            physical["region"] = serde_json::json!({
+                "startLine": span.line.max(1),
+                "startColumn": span.column.max(1),
+            });
+        }
+        serde_json::json!({ "physicalLocation": physical })
+    };
+    let results: Vec<serde_json::Value> = diagnostics
+        .iter()
+        .map(|d| {
+            let mut result = serde_json::json!({
+                "ruleId": d.rule,
+                "level": d.severity.as_str(),
+                "message": { "text": d.message },
+                "locations": [location(&d.path, d.span)],
+            });
+            if !d.notes.is_empty() {
+                result["relatedLocations"] = d
+                    .notes
+                    .iter()
+                    .enumerate()
+                    .map(|(id, (span, note))| {
+                        let mut related = location(&d.path, Some(*span));
+                        related["id"] = serde_json::json!(id);
+                        related["message"] = serde_json::json!({ "text": note });
+                        related
+                    })
+                    .collect();
+            }
+            result
+        })
+        .collect();
+    let value = serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "soli check",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": RULES.iter().map(|(id, description)| serde_json::json!({
+                        "id": id,
+                        "shortDescription": { "text": description },
+                    })).collect::<Vec<_>>(),
+                }
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&value).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// SARIF wants URIs: forward slashes, and relative paths without `./`.
+fn sarif_uri(path: &str) -> String {
+    let uri = path.replace('\\', "/");
+    uri.strip_prefix("./").map(str::to_string).unwrap_or(uri)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ParserError;
+
+    fn type_error() -> TypeError {
+        TypeError::mismatch("Int", "String", Span::new(8, 9, 2, 5))
+            .with_note(Span::new(0, 3, 1, 1), "declared here")
+    }
+
+    #[test]
+    fn type_errors_keep_their_location_and_notes() {
+        let diagnostics = Diagnostic::from_error("app/a.sl", &vec![type_error()].into());
+        assert_eq!(diagnostics.len(), 1);
+        let d = &diagnostics[0];
+        assert_eq!((d.severity, d.rule), (Severity::Error, "type-error"));
+        assert_eq!(d.location(), "app/a.sl:2:5");
+        assert!(!d.message.ends_with("2:5"), "{}", d.message);
+        assert_eq!(d.notes.len(), 1);
+    }
+
+    #[test]
+    fn parse_errors_are_syntax_errors() {
+        let error = SolilangError::Parser(ParserError::unexpected_token(
+            ")",
+            "}",
+            Span::new(4, 5, 3, 7),
+        ));
+        let d = &Diagnostic::from_error("a.sl", &error)[0];
+        assert_eq!(d.rule, "syntax-error");
+        assert!(d.message.starts_with("Unexpected token"), "{}", d.message);
+        assert_eq!(d.span.map(|s| (s.line, s.column)), Some((3, 7)));
+    }
+
+    #[test]
+    fn sarif_log_has_a_result_per_diagnostic() {
+        let diagnostics = Diagnostic::from_error("./app/a.sl", &vec![type_error()].into());
+        let sarif: serde_json::Value = serde_json::from_str(&format_sarif(&diagnostics)).unwrap();
+        assert_eq!(sarif["version"], "2.1.0");
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "type-error");
+        assert_eq!(result["level"], "error");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "app/a.sl");
+        assert_eq!(location["region"]["startLine"], 2);
+        assert_eq!(
+            result["relatedLocations"][0]["message"]["text"],
+            "declared here"
+        );
+    }
+
+    #[test]
+    fn json_report_counts_by_severity() {
+        let json: serde_json::Value = serde_json::from_str(&format_json(&[], 3)).unwrap();
+        assert_eq!(json["files"], 3);
+        assert_eq!(json["errors"], 0);
+        assert_eq!(json["diagnostics"], serde_json::json!([]));
+    }
+}
@@ -1,17 +1,33 @@
 //! Type environment for the type checker.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::types::type_repr::{ClassType, EnumType, InterfaceType, MethodInfo, Type};
+use crate::span::Span;
+use crate::types::type_repr::{
+    ClassType, EnumType, FieldInfo, InterfaceType, MethodInfo, ParamSignature, Type,
+};
 
 /// A type environment tracking types of variables and declarations.
 #[derive(Debug, Clone)]
 pub struct TypeEnvironment {
     scopes: Vec<HashMap<String, Type>>,
+    /// Parameter lists of the functions bound in the matching entry of
+    /// `scopes`, for checking named arguments.
+    signatures: Vec<HashMap<String, Vec<ParamSignature>>>,
+    /// Names declared with `const` in the matching entry of `scopes`, with
+    /// where they were declared.
+    constants: Vec<HashMap<String, Span>>,
     classes: HashMap<String, ClassType>,
     enums: HashMap<String, EnumType>,
     interfaces: HashMap<String, InterfaceType>,
+    /// `type Name = ...` declarations, by name.
+    type_aliases: HashMap<String, Type>,
     functions: HashMap<String, Type>,
+    function_signatures: HashMap<String, Vec<ParamSignature>>,
+    /// Functions the runtime provides (rather than the program declares).
+    builtins: HashSet<String>,
+    /// Parameter lists the bundled stubs declare for builtins.
+    builtin_signatures: HashMap<String, Vec<ParamSignature>>,
     current_class: Option<String>,
     current_function_return: Option<Type>,
 }
@@ -20,20 +36,33 @@ impl TypeEnvironment {
     pub fn new() -> Self {
         let mut env = Self {
             scopes: vec![HashMap::new()],
+            signatures: vec![HashMap::new()],
+            constants: vec![HashMap::new()],
             classes: HashMap::new(),
             enums: HashMap::new(),
             interfaces: HashMap::new(),
+            type_aliases: HashMap::new(),
             functions: HashMap::new(),
+            function_signatures: HashMap::new(),
+            builtins: HashSet::new(),
+            builtin_signatures: HashMap::new(),
             current_class: None,
             current_function_return: None,
         };
 
         // Register built-in functions
         env.register_builtins();
+        env.builtins = env.functions.keys().cloned().collect();
 
         // Built-in globals injected at request time by the server (see call_handler).
         env.define("params".to_string(), Type::Any);
 
+        // The `Events` bus and `Export` are Soli prelude classes (see
+        // builtins/events.rs, builtins/export.rs), defined when the
+        // interpreter starts rather than by a declaration.
+        env.define("Events".to_string(), Type::Any);
+        env.define("Export".to_string(), Type::Any);
+
         // Class-object globals for primitive types — used for metaprogramming
         // (e.g. `Int.class_eval do define_method(:double) { ... } end`). Typed
         // as Any here so that `.method_name` access doesn't fail type-checking;
@@ -87,6 +116,29 @@ impl TypeEnvironment {
             },
         );
 
+        // connected_to(name, block) -> Any — runs the block's model queries
+        // on a named database connection and returns the block's value.
+        self.functions.insert(
+            "connected_to".to_string(),
+            Type::Function {
+                params: vec![Type::String, Type::Any],
+                return_type: Box::new(Type::Any),
+            },
+        );
+
+        // at_exit / on_boot / on_worker_start / on_shutdown (block) -> Void —
+        // register a block to run when the program exits or at a `soli serve`
+        // lifecycle point.
+        for name in ["at_exit", "on_boot", "on_worker_start", "on_shutdown"] {
+            self.functions.insert(
+                name.to_string(),
+                Type::Function {
+                    params: vec![Type::Any],
+                    return_type: Box::new(Type::Void),
+                },
+            );
+        }
+
         // broadcast(channel, payload) -> Int — pub/sub fan-out to the WS channel
         // and SSE topic of the same name; returns the SSE subscriber count.
         self.functions.insert(
@@ -160,6 +212,18 @@ impl TypeEnvironment {
             },
         );
 
+        // runtime_stats() -> Hash — this thread's heap counters
+        self.functions.insert(
+            "runtime_stats".to_string(),
+            Type::Function {
+                params: vec![],
+                return_type: Box::new(Type::Hash {
+                    key_type: Box::new(Type::String),
+                    value_type: Box::new(Type::Any),
+                }),
+            },
+        );
+
         // debug() -> Any — trigger a breakpoint (dev-mode REPL); no-op in production.
         self.functions.insert(
             "debug".to_string(),
@@ -515,6 +579,15 @@ impl TypeEnvironment {
             },
         );
 
+        // limit_concurrency(pattern_or_limit, limit?, options?) -> Void
+        self.functions.insert(
+            "limit_concurrency".to_string(),
+            Type::Function {
+                params: vec![Type::Any, Type::Any, Type::Any],
+                return_type: Box::new(Type::Void),
+            },
+        );
+
         // permit(params, shape) -> Hash
         self.functions.insert(
             "permit".to_string(),
@@ -1812,6 +1885,99 @@ impl TypeEnvironment {
         );
         self.classes.insert("X509".to_string(), x509_class);
 
+        // Request / Response — the typed controller-action classes. Accessors
+        // are zero-arg instance methods; `param`/`header`/`cookie` take the
+        // name. The builders' trailing `status:`/`headers:` options arrive as
+        // named arguments, so they take a single `Any` and leave the rest to
+        // the runtime (like Crypto above).
+        let string_hash = || Type::Hash {
+            key_type: Box::new(Type::String),
+            value_type: Box::new(Type::Any),
+        };
+        let mut request_class = ClassType::new("Request".to_string());
+        for (name, params, ret) in [
+            ("method", vec![], Type::String),
+            ("path", vec![], Type::String),
+            ("body", vec![], Type::Any),
+            ("json", vec![], Type::Any),
+            ("form", vec![], Type::Any),
+            ("files", vec![], Type::Any),
+            ("remote_addr", vec![], Type::Any),
+            ("params", vec![], string_hash()),
+            ("query", vec![], string_hash()),
+            ("headers", vec![], string_hash()),
+            ("cookies", vec![], string_hash()),
+            ("session", vec![], string_hash()),
+            ("to_h", vec![], string_hash()),
+            ("param", vec![("name".to_string(), Type::String)], Type::Any),
+            (
+                "header",
+                vec![("name".to_string(), Type::String)],
+                Type::Any,
+            ),
+            (
+                "cookie",
+                vec![("name".to_string(), Type::String)],
+                Type::Any,
+            ),
+        ] {
+            request_class.methods.insert(
+                name.to_string(),
+                MethodInfo {
+                    name: name.to_string(),
+                    params,
+                    return_type: ret,
+                    is_private: false,
+                    is_static: false,
+                },
+            );
+        }
+        self.classes.insert("Request".to_string(), request_class);
+
+        let response_type = || Type::Class(ClassType::new("Response".to_string()));
+        let mut response_class = ClassType::new("Response".to_string());
+        for (name, ty) in [
+            ("status", Type::Int),
+            ("headers", string_hash()),
+            ("body", Type::String),
+        ] {
+            response_class.fields.insert(
+                name.to_string(),
+                FieldInfo {
+                    name: name.to_string(),
+                    ty,
+                    is_private: false,
+                    is_static: false,
+                },
+            );
+        }
+        for name in ["json", "text", "html", "redirect", "empty"] {
+            response_class.methods.insert(
+                name.to_string(),
+                MethodInfo {
+                    name: name.to_string(),
+                    params: vec![("args".to_string(), Type::Any)],
+                    return_type: response_type(),
+                    is_private: false,
+                    is_static: true,
+                },
+            );
+        }
+        response_class.methods.insert(
+            "with_header".to_string(),
+            MethodInfo {
+                name: "with_header".to_string(),
+                params: vec![
+                    ("name".to_string(), Type::String),
+                    ("value".to_string(), Type::String),
+                ],
+                return_type: response_type(),
+                is_private: false,
+                is_static: false,
+            },
+        );
+        self.classes.insert("Response".to_string(), response_class);
+
         // HTTP class
         let mut http_class = ClassType::new("HTTP".to_string());
         http_class.methods.insert(
@@ -1972,6 +2138,16 @@ impl TypeEnvironment {
                 is_static: true,
             },
         );
+        system_class.methods.insert(
+            "args".to_string(),
+            MethodInfo {
+                name: "args".to_string(),
+                params: vec![],
+                return_type: Type::Array(Box::new(Type::String)),
+                is_private: false,
+                is_static: true,
+            },
+        );
         self.classes.insert("System".to_string(), system_class);
 
         // Markdown class
@@ -2203,6 +2379,7 @@ impl TypeEnvironment {
             ("bounding_box", Type::Any),
             ("geohash", Type::String),
             ("geohash_decode", Type::Any),
+            ("feature_collection", Type::Any),
         ] {
             geo_class.methods.insert(
                 name.to_string(),
@@ -2217,6 +2394,56 @@ impl TypeEnvironment {
         }
         self.classes.insert("Geo".to_string(), geo_class);
 
+        // Point class — Point.new(lat, lon) / Point.cast(value) build a
+        // position; lat/lon are fields, the rest are instance methods.
+        let point_type = || Type::Class(ClassType::new("Point".to_string()));
+        let mut point_class = ClassType::new("Point".to_string());
+        for name in ["lat", "lon"] {
+            point_class.fields.insert(
+                name.to_string(),
+                FieldInfo {
+                    name: name.to_string(),
+                    ty: Type::Float,
+                    is_private: false,
+                    is_static: false,
+                },
+            );
+        }
+        for (name, params, return_type, is_static) in [
+            ("new", vec!["lat", "lon"], point_type(), true),
+            ("cast", vec!["value"], Type::nullable(point_type()), true),
+            ("lng", vec![], Type::Float, false),
+            ("distance_to", vec!["other"], Type::Float, false),
+            ("to_geojson", vec![], Type::Any, false),
+            ("to_h", vec![], Type::Any, false),
+            ("to_s", vec![], Type::String, false),
+        ] {
+            point_class.methods.insert(
+                name.to_string(),
+                MethodInfo {
+                    name: name.to_string(),
+                    params: params
+                        .into_iter()
+                        .map(|p| (p.to_string(), Type::Any))
+                        .collect(),
+                    return_type,
+                    is_private: false,
+                    is_static,
+                },
+            );
+        }
+        point_class.methods.insert(
+            "geohash".to_string(),
+            MethodInfo {
+                name: "geohash".to_string(),
+                params: vec![("args".to_string(), Type::Any)],
+                return_type: Type::String,
+                is_private: false,
+                is_static: false,
+            },
+        );
+        self.classes.insert("Point".to_string(), point_class);
+
         // Push class — Push.deliver(channel, payload, options) -> Hash
         // {reached_live, transport, sent, failed, prune}.
         let mut push_class = ClassType::new("Push".to_string());
@@ -2304,20 +2531,78 @@ impl TypeEnvironment {
     /// Enter a new scope.
     pub fn push_scope(&mut self) {
         self.scopes.push(HashMap::new());
+        self.signatures.push(HashMap::new());
+        self.constants.push(HashMap::new());
     }
 
     /// Exit the current scope.
     pub fn pop_scope(&mut self) {
         self.scopes.pop();
+        self.signatures.pop();
+        self.constants.pop();
     }
 
     /// Define a variable in the current scope.
     pub fn define(&mut self, name: String, ty: Type) {
+        if let Some(signatures) = self.signatures.last_mut() {
+            signatures.remove(&name);
+        }
+        if let Some(constants) = self.constants.last_mut() {
+            constants.remove(&name);
+        }
         if let Some(scope) = self.scopes.last_mut() {
             scope.insert(name, ty);
         }
     }
 
+    /// Define a constant in the current scope.
+    pub fn define_const(&mut self, name: String, ty: Type, span: Span) {
+        self.define(name.clone(), ty);
+        if let Some(constants) = self.constants.last_mut() {
+            constants.insert(name, span);
+        }
+    }
+
+    /// Whether `name` resolves to a constant (and not to a variable
+    /// shadowing it).
+    pub fn is_const(&self, name: &str) -> bool {
+        self.const_span(name).is_some()
+    }
+
+    /// Where the constant `name` resolves to was declared, if it is one.
+    pub fn const_span(&self, name: &str) -> Option<Span> {
+        for (scope, constants) in self.scopes.iter().zip(&self.constants).rev() {
+            if scope.contains_key(name) {
+                return constants.get(name).copied();
+            }
+        }
+        None
+    }
+
+    /// Define a function in the current scope, with its parameter list.
+    pub fn define_with_signature(
+        &mut self,
+        name: String,
+        ty: Type,
+        signature: Vec<ParamSignature>,
+    ) {
+        self.define(name.clone(), ty);
+        if let Some(signatures) = self.signatures.last_mut() {
+            signatures.insert(name, signature);
+        }
+    }
+
+    /// The parameter list of the function `name` resolves to, if it is a
+    /// declared function (and not shadowed by a plain variable).
+    pub fn get_signature(&self, name: &str) -> Option<&[ParamSignature]> {
+        for (scope, signatures) in self.scopes.iter().zip(&self.signatures).rev() {
+            if scope.contains_key(name) {
+                return signatures.get(name).map(Vec::as_slice);
+            }
+        }
+        self.function_signatures.get(name).map(Vec::as_slice)
+    }
+
     /// Look up a variable's type.
     pub fn get(&self, name: &str) -> Option<Type> {
         // Search scopes from innermost to outermost
@@ -2370,11 +2655,52 @@ impl TypeEnvironment {
         self.interfaces.get(name)
     }
 
+    /// Define a type alias.
+    pub fn define_type_alias(&mut self, name: String, ty: Type) {
+        self.type_aliases.insert(name, ty);
+    }
+
+    /// Get the type a type alias stands for.
+    pub fn get_type_alias(&self, name: &str) -> Option<&Type> {
+        self.type_aliases.get(name)
+    }
+
     /// Define a function type.
     pub fn define_function(&mut self, name: String, ty: Type) {
+        self.builtins.remove(&name);
         self.functions.insert(name, ty);
     }
 
+    /// Record that the function `name` comes from the runtime.
+    pub fn mark_builtin(&mut self, name: &str) {
+        self.builtins.insert(name.to_string());
+    }
+
+    /// Whether `name` resolves to a runtime-provided function, which an
+    /// assignment may rebind to a value of any type.
+    pub fn is_builtin(&self, name: &str) -> bool {
+        !self.scopes.iter().any(|scope| scope.contains_key(name)) && self.builtins.contains(name)
+    }
+
+    /// Record the parameter list the stubs declare for the builtin `name`.
+    pub fn define_builtin_signature(&mut self, name: String, signature: Vec<ParamSignature>) {
+        self.builtin_signatures.insert(name, signature);
+    }
+
+    /// The declared parameter list of the builtin `name` resolves to; `None`
+    /// once the program rebinds the name.
+    pub fn builtin_signature(&self, name: &str) -> Option<&[ParamSignature]> {
+        if !self.is_builtin(name) {
+            return None;
+        }
+        self.builtin_signatures.get(name).map(Vec::as_slice)
+    }
+
+    /// Record the parameter list of a top-level function.
+    pub fn define_function_signature(&mut self, name: String, signature: Vec<ParamSignature>) {
+        self.function_signatures.insert(name, signature);
+    }
+
     /// Set the current class context.
     pub fn set_current_class(&mut self, name: Option<String>) {
         self.current_class = name;
@@ -1,8 +1,13 @@
 //! Type system module for Solilang.
 
 pub mod checker;
+pub mod coverage;
+pub mod diagnostics;
 pub mod environment;
+pub mod stubs;
 pub mod type_repr;
 
 pub use checker::TypeChecker;
+pub use coverage::TypeCoverage;
+pub use diagnostics::Diagnostic;
 pub use type_repr::Type;
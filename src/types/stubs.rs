@@ -0,0 +1,816 @@
+//! Interface stubs (`.sli`): typed signatures without bodies.
+//!
+//! A stub file tells the type checker and the LSP what a builtin or a
+//! package provides without their having to read (or being able to read) its
+//! implementation. The syntax is Soli declarations with the bodies left off:
+//!
+//! ```text
+//! # Comment lines directly above a declaration are its documentation.
+//! fn slugify(text: String, separator: String = "-") -> String
+//! let VERSION: String
+//!
+//! class Money {
+//!     amount: Int
+//!     fn new(amount: Int, currency: String = "EUR")
+//!     static fn zero() -> Money
+//!     fn format(*, symbol: Bool = true) -> String
+//! }
+//! ```
+//!
+//! A missing return type reads as `Any`; a default only marks the parameter
+//! optional. The checker always loads the builtin stubs bundled with soli,
+//! which declare every builtin: a call that leaves out a required argument
+//! is an error. `soli check` also loads `stubs/*.sli` at the project root
+//! and, for every module it imports, a `.sli` file next to it
+//! (`lib/money.sl` → `lib/money.sli`), whose signatures replace the ones
+//! inferred from the module's source. `soli stubs gen` writes that file from
+//! a module's exported declarations.
+//!
+//! A declaration file (`.d.sl`) is the same format under the name a package
+//! ships: it is accepted wherever a `.sli` is, and a package whose code
+//! isn't (all) Soli — a native extension — names one in its soli.toml
+//! (`types = "index.d.sl"`) so importers are checked against it.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::ast::{
+    ClassDecl, ExprKind, FunctionDecl, Parameter, Program, Stmt, StmtKind, TypeAnnotation,
+    TypeKind, Visibility,
+};
+use crate::error::{ParserError, SolilangError};
+use crate::lexer::{Scanner, TokenKind};
+use crate::parser::Parser;
+use crate::span::Span;
+
+/// The builtin stubs bundled with soli.
+const BUILTIN_STUBS: &str = include_str!("builtins.sli");
+
+/// The declarations of one `.sli` file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StubFile {
+    pub functions: Vec<StubFunction>,
+    pub classes: Vec<StubClass>,
+    /// `let NAME: Type` — globals that aren't functions or classes.
+    pub globals: Vec<StubField>,
+}
+
+/// A function or method signature.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StubFunction {
+    pub name: String,
+    pub params: Vec<StubParam>,
+    pub return_type: TypeAnnotation,
+    pub is_static: bool,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StubParam {
+    pub name: String,
+    pub type_annotation: TypeAnnotation,
+    /// The default as written; only its presence matters to the checker.
+    pub default: Option<String>,
+    pub is_block: bool,
+    pub is_variadic: bool,
+    pub is_keyword_only: bool,
+}
+
+/// A class field, or a global.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StubField {
+    pub name: String,
+    pub type_annotation: TypeAnnotation,
+    pub is_static: bool,
+    pub doc: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StubClass {
+    pub name: String,
+    pub superclass: Option<String>,
+    pub fields: Vec<StubField>,
+    /// Methods; `fn new(...)` describes the constructor.
+    pub methods: Vec<StubFunction>,
+    pub doc: Option<String>,
+}
+
+impl StubFile {
+    /// Parse the text of a `.sli` file.
+    pub fn parse(source: &str) -> Result<Self, SolilangError> {
+        let tokens = Scanner::new(source).scan_tokens()?;
+        let mut parser = StubParser {
+            parser: Parser::new(tokens),
+            source,
+            docs: doc_comments(source),
+        };
+        Ok(parser.file()?)
+    }
+
+    /// Read and parse a `.sli` or `.d.sl` file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("{}: cannot read stub file: {}", path.display(), e))?;
+        Self::parse(&source).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Stubs for a module: its exported functions and classes, or every
+    /// top-level one when it exports nothing. Unannotated parameters and
+    /// return types become `Any`; comments above a declaration carry over.
+    pub fn from_module(program: &Program, source: &str) -> Self {
+        let docs = doc_comments(source);
+        let exports: Vec<&Stmt> = program
+            .statements
+            .iter()
+            .filter_map(|stmt| match &stmt.kind {
+                StmtKind::Export(inner) => Some(&**inner),
+                _ => None,
+            })
+            .collect();
+        let decls: Vec<&Stmt> = if exports.is_empty() {
+            program.statements.iter().collect()
+        } else {
+            exports
+        };
+        Self::from_decls(decls, source, &docs)
+    }
+
+    /// Stubs for every top-level function, class and `let`/`const` of
+    /// `program`, exported or not — what the rest of an app sees once the
+    /// file is loaded.
+    pub fn from_program(program: &Program, source: &str) -> Self {
+        let docs = doc_comments(source);
+        let decls: Vec<&Stmt> = program
+            .statements
+            .iter()
+            .map(|stmt| match &stmt.kind {
+                StmtKind::Export(inner) => &**inner,
+                _ => stmt,
+            })
+            .collect();
+        let mut stubs = Self::from_decls(decls.iter().copied(), source, &docs);
+        for stmt in decls {
+            if let StmtKind::Let {
+                name,
+                type_annotation,
+                ..
+            }
+            | StmtKind::Const {
+                name,
+                type_annotation,
+                ..
+            } = &stmt.kind
+            {
+                stubs.globals.push(StubField {
+                    name: name.clone(),
+                    type_annotation: type_annotation
+                        .as_deref()
+                        .cloned()
+                        .unwrap_or_else(|| any_type(stmt.span)),
+                    is_static: false,
+                    doc: docs.get(&stmt.span.line).cloned(),
+                });
+            }
+        }
+        stubs
+    }
+
+    fn from_decls<'a>(
+        decls: impl IntoIterator<Item = &'a Stmt>,
+        source: &str,
+        docs: &HashMap<u32, String>,
+    ) -> Self {
+        let mut stubs = StubFile::default();
+        for stmt in decls {
+            match &stmt.kind {
+                StmtKind::Function(decl) => stubs.functions.push(StubFunction::from_decl(
+                    decl,
+                    source,
+                    docs.get(&decl.span.line),
+                )),
+                StmtKind::Class(decl) => {
+                    stubs.classes.push(StubClass::from_decl(decl, source, docs))
+                }
+                _ => {}
+            }
+        }
+        stubs
+    }
+
+    pub fn function(&self, name: &str) -> Option<&StubFunction> {
+        self.functions.iter().find(|f| f.name == name)
+    }
+
+    pub fn class(&self, name: &str) -> Option<&StubClass> {
+        self.classes.iter().find(|c| c.name == name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty() && self.classes.is_empty() && self.globals.is_empty()
+    }
+}
+
+impl StubFunction {
+    fn from_decl(decl: &FunctionDecl, source: &str, doc: Option<&String>) -> Self {
+        Self {
+            name: decl.name.clone(),
+            params: decl
+                .params
+                .iter()
+                .map(|p| StubParam::from_param(p, source))
+                .collect(),
+            return_type: decl
+                .return_type
+                .clone()
+                .unwrap_or_else(|| any_type(decl.span)),
+            is_static: false,
+            doc: doc.cloned(),
+        }
+    }
+
+    /// `name(a: Int, b: String = "x") -> Bool`, for hovers and completion
+    /// details.
+    pub fn signature(&self) -> String {
+        let mut out = format!("{}(", self.name);
+        for (i, param) in self.params.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            let after_variadic = self.params[..i].iter().any(|p| p.is_variadic);
+            if param.is_keyword_only
+                && !after_variadic
+                && !self.params[..i].iter().any(|p| p.is_keyword_only)
+            {
+                out.push_str("*, ");
+            }
+            out.push_str(&param.to_string());
+        }
+        out.push_str(&format!(") -> {}", self.return_type));
+        out
+    }
+}
+
+impl StubParam {
+    fn from_param(param: &Parameter, source: &str) -> Self {
+        Self {
+            name: param.name.clone(),
+            type_annotation: param.type_annotation.clone(),
+            default: param.default_value.as_ref().map(|expr| match &expr.kind {
+                // A string literal's span stops short of its closing quote.
+                ExprKind::StringLiteral(s) => format!("\"{}\"", s.escape_default()),
+                _ => source
+                    .get(expr.span.start_usize()..expr.span.end_usize())
+                    .unwrap_or("null")
+                    .to_string(),
+            }),
+            is_block: param.is_block_param,
+            is_variadic: param.is_variadic,
+            is_keyword_only: param.is_keyword_only,
+        }
+    }
+}
+
+impl fmt::Display for StubParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_block {
+            write!(f, "&")?;
+        }
+        if self.is_variadic {
+            write!(f, "...")?;
+        }
+        write!(f, "{}: {}", self.name, self.type_annotation)?;
+        if let Some(default) = &self.default {
+            write!(f, " = {}", default)?;
+        }
+        Ok(())
+    }
+}
+
+impl StubClass {
+    fn from_decl(decl: &ClassDecl, source: &str, docs: &HashMap<u32, String>) -> Self {
+        let mut methods = Vec::new();
+        if let Some(ctor) = &decl.constructor {
+            methods.push(StubFunction {
+                name: "new".to_string(),
+                params: ctor
+                    .params
+                    .iter()
+                    .map(|p| StubParam::from_param(p, source))
+                    .collect(),
+                return_type: TypeAnnotation::new(TypeKind::Named(decl.name.clone()), ctor.span),
+                is_static: false,
+                doc: docs.get(&ctor.span.line).cloned(),
+            });
+        }
+        for method in &decl.methods {
+            if matches!(method.visibility, Visibility::Private) {
+                continue;
+            }
+            methods.push(StubFunction {
+                name: method.name.clone(),
+                params: method
+                    .params
+                    .iter()
+                    .map(|p| StubParam::from_param(p, source))
+                    .collect(),
+                return_type: method
+                    .return_type
+                    .clone()
+                    .unwrap_or_else(|| any_type(method.span)),
+                is_static: method.is_static,
+                doc: docs.get(&method.span.line).cloned(),
+            });
+        }
+        Self {
+            name: decl.name.clone(),
+            superclass: decl.superclass.clone(),
+            fields: decl
+                .fields
+                .iter()
+                .filter(|field| !matches!(field.visibility, Visibility::Private))
+                .map(|field| StubField {
+                    name: field.name.clone(),
+                    type_annotation: field
+                        .type_annotation
+                        .clone()
+                        .unwrap_or_else(|| any_type(field.span)),
+                    is_static: field.is_static,
+                    doc: docs.get(&field.span.line).cloned(),
+                })
+                .collect(),
+            methods,
+            doc: docs.get(&decl.span.line).cloned(),
+        }
+    }
+
+    pub fn method(&self, name: &str) -> Option<&StubFunction> {
+        self.methods.iter().find(|m| m.name == name)
+    }
+}
+
+fn write_doc(f: &mut fmt::Formatter<'_>, doc: &Option<String>, indent: &str) -> fmt::Result {
+    if let Some(doc) = doc {
+        for line in doc.lines() {
+            if line.is_empty() {
+                writeln!(f, "{}#", indent)?;
+            } else {
+                writeln!(f, "{}# {}", indent, line)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for StubFile {
+    /// The `.sli` text: globals, then functions, then classes.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        let mut gap = |f: &mut fmt::Formatter<'_>| {
+            if !std::mem::take(&mut first) {
+                writeln!(f)?;
+            }
+            Ok::<_, fmt::Error>(())
+        };
+        for global in &self.globals {
+            gap(f)?;
+            write_doc(f, &global.doc, "")?;
+            writeln!(f, "let {}: {}", global.name, global.type_annotation)?;
+        }
+        for function in &self.functions {
+            gap(f)?;
+            write_doc(f, &function.doc, "")?;
+            writeln!(f, "fn {}", function.signature())?;
+        }
+        for class in &self.classes {
+            gap(f)?;
+            write_doc(f, &class.doc, "")?;
+            match &class.superclass {
+                Some(superclass) => writeln!(f, "class {} extends {} {{", class.name, superclass)?,
+                None => writeln!(f, "class {} {{", class.name)?,
+            }
+            for field in &class.fields {
+                write_doc(f, &field.doc, "    ")?;
+                let keyword = if field.is_static { "static " } else { "" };
+                writeln!(
+                    f,
+                    "    {}{}: {}",
+                    keyword, field.name, field.type_annotation
+                )?;
+            }
+            for method in &class.methods {
+                write_doc(f, &method.doc, "    ")?;
+                let keyword = if method.is_static { "static " } else { "" };
+                writeln!(f, "    {}fn {}", keyword, method.signature())?;
+            }
+            writeln!(f, "}}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the parser's signature rules over a stub file's tokens.
+struct StubParser<'a> {
+    parser: Parser,
+    source: &'a str,
+    docs: HashMap<u32, String>,
+}
+
+impl StubParser<'_> {
+    fn file(&mut self) -> Result<StubFile, ParserError> {
+        let mut file = StubFile::default();
+        while !self.parser.is_at_end() {
+            match self.parser.peek().kind {
+                TokenKind::Class => file.classes.push(self.class()?),
+                TokenKind::Let | TokenKind::Const => {
+                    self.parser.advance();
+                    file.globals.push(self.field(false)?);
+                }
+                _ => {
+                    let is_static = self.parser.match_token(&TokenKind::Static);
+                    file.functions.push(self.function(is_static)?);
+                }
+            }
+            self.parser.match_token(&TokenKind::Semicolon);
+        }
+        Ok(file)
+    }
+
+    fn class(&mut self) -> Result<StubClass, ParserError> {
+        let doc = self.doc_at_current();
+        self.parser.expect(&TokenKind::Class)?;
+        let name = self.parser.expect_identifier()?;
+        let superclass = if self.parser.match_token(&TokenKind::Extends)
+            || self.parser.match_token(&TokenKind::Less)
+        {
+            Some(self.parser.expect_identifier()?)
+        } else {
+            None
+        };
+        self.parser.expect(&TokenKind::LeftBrace)?;
+
+        let mut class = StubClass {
+            name,
+            superclass,
+            fields: Vec::new(),
+            methods: Vec::new(),
+            doc,
+        };
+        while !self.parser.check(&TokenKind::RightBrace) && !self.parser.is_at_end() {
+            let doc = self.doc_at_current();
+            let is_static = self.parser.match_token(&TokenKind::Static);
+            if self.parser.check(&TokenKind::Fn) {
+                let mut method = self.function(is_static)?;
+                method.doc = method.doc.or(doc);
+                class.methods.push(method);
+            } else {
+                let mut field = self.field(is_static)?;
+                field.doc = field.doc.or(doc);
+                class.fields.push(field);
+            }
+            self.parser.match_token(&TokenKind::Semicolon);
+        }
+        self.parser.expect(&TokenKind::RightBrace)?;
+        Ok(class)
+    }
+
+    /// `fn name(params) -> Type`, with `fn` next.
+    fn function(&mut self, is_static: bool) -> Result<StubFunction, ParserError> {
+        let doc = self.doc_at_current();
+        let span = self.parser.current_span();
+        self.parser.expect(&TokenKind::Fn)?;
+        let name = self.parser.expect_identifier()?;
+        let params = self
+            .parser
+            .parse_parameters()?
+            .iter()
+            .map(|p| StubParam::from_param(p, self.source))
+            .collect();
+        let return_type = if self.parser.match_token(&TokenKind::Arrow) {
+            self.parser.parse_type()?
+        } else {
+            any_type(span)
+        };
+        Ok(StubFunction {
+            name,
+            params,
+            return_type,
+            is_static,
+            doc,
+        })
+    }
+
+    /// `name: Type`, after any `let` / `static`.
+    fn field(&mut self, is_static: bool) -> Result<StubField, ParserError> {
+        let doc = self.doc_at_current();
+        let name = self.parser.expect_identifier()?;
+        self.parser.expect(&TokenKind::Colon)?;
+        let type_annotation = self.parser.parse_type()?;
+        Ok(StubField {
+            name,
+            type_annotation,
+            is_static,
+            doc,
+        })
+    }
+
+    fn doc_at_current(&self) -> Option<String> {
+        self.docs.get(&self.parser.current_span().line).cloned()
+    }
+}
+
+fn any_type(span: Span) -> TypeAnnotation {
+    TypeAnnotation::new(TypeKind::Named("Any".to_string()), span)
+}
+
+/// The text of a full-line comment, without its marker.
+fn comment_text(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    let text = if let Some(rest) = trimmed.strip_prefix("//") {
+        rest.trim_start_matches('/')
+    } else {
+        trimmed.strip_prefix('#')?
+    };
+    Some(text.strip_prefix(' ').unwrap_or(text).trim_end())
+}
+
+/// Comment blocks that sit directly above a line (no blank line between),
+/// keyed by that line, with their markers stripped.
+fn doc_comments(source: &str) -> HashMap<u32, String> {
+    let lines: Vec<&str> = source.lines().collect();
+    // Lines holding nothing but a line comment.
+    let line_comments: HashSet<usize> = crate::fmt::comments::extract_comments(source)
+        .into_iter()
+        .filter(|c| c.kind == crate::fmt::comments::CommentKind::Line)
+        .filter(|c| {
+            lines.get(c.line - 1).is_some_and(|l| {
+                let indent = l.len() - l.trim_start().len();
+                c.column == indent + 1
+            })
+        })
+        .map(|c| c.line)
+        .collect();
+
+    let mut docs = HashMap::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let line_no = idx + 1;
+        if line.trim().is_empty() || line_comments.contains(&line_no) {
+            continue;
+        }
+        let mut block = Vec::new();
+        let mut above = line_no - 1;
+        while above >= 1 && line_comments.contains(&above) {
+            if let Some(text) = comment_text(lines[above - 1]) {
+                block.push(text);
+            }
+            above -= 1;
+        }
+        if !block.is_empty() {
+            block.reverse();
+            docs.insert(line_no as u32, block.join("\n"));
+        }
+    }
+    docs
+}
+
+/// The builtin stubs bundled with soli, parsed once.
+pub fn builtin_stubs() -> &'static StubFile {
+    static STUBS: OnceLock<StubFile> = OnceLock::new();
+    STUBS.get_or_init(|| StubFile::parse(BUILTIN_STUBS).expect("bundled builtins.sli must parse"))
+}
+
+/// The project's `stubs/*.sli` and `stubs/*.d.sl`, for the project
+/// containing `start_dir` (the directory holding soli.toml, else `start_dir`
+/// itself), sorted by name.
+pub fn project_stubs(start_dir: &Path) -> Result<Vec<StubFile>, String> {
+    let root = project_root(start_dir);
+    let Ok(entries) = std::fs::read_dir(root.join("stubs")) else {
+        return Ok(Vec::new());
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| is_declaration_file(p))
+        .collect();
+    paths.sort();
+    paths.iter().map(|p| StubFile::load(p)).collect()
+}
+
+/// The declarations sitting next to `modules`: `lib/money.d.sl`, else
+/// `lib/money.sli`.
+pub fn module_stubs<'a>(
+    modules: impl IntoIterator<Item = &'a Path>,
+) -> Result<Vec<StubFile>, String> {
+    modules
+        .into_iter()
+        .filter_map(|m| {
+            [m.with_extension("d.sl"), m.with_extension("sli")]
+                .into_iter()
+                .find(|p| p.is_file())
+        })
+        .map(|p| StubFile::load(&p))
+        .collect()
+}
+
+/// The declaration files (`types = "..."` in soli.toml) of the packages
+/// `modules` belong to, once each. The project containing `start_dir` is
+/// left out: its own code is checked from source.
+pub fn package_stubs<'a>(
+    start_dir: &Path,
+    modules: impl IntoIterator<Item = &'a Path>,
+) -> Result<Vec<StubFile>, String> {
+    let own_root = project_root(start_dir);
+    let mut seen = HashSet::new();
+    let mut stubs = Vec::new();
+    for module in modules {
+        let Some(toml) = module.parent().and_then(crate::module::Package::find) else {
+            continue;
+        };
+        let Some(dir) = toml.parent().filter(|dir| *dir != own_root) else {
+            continue;
+        };
+        if !seen.insert(dir.to_path_buf()) {
+            continue;
+        }
+        let package = crate::module::Package::load(&toml)
+            .map_err(|e| format!("{}: {}", toml.display(), e))?;
+        if let Some(types) = package.types {
+            stubs.push(StubFile::load(&dir.join(types))?);
+        }
+    }
+    Ok(stubs)
+}
+
+fn project_root(start_dir: &Path) -> PathBuf {
+    crate::module::Package::find(start_dir)
+        .and_then(|toml| toml.parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| start_dir.to_path_buf())
+}
+
+/// Whether `path` names a `.sli` or `.d.sl` declaration file.
+pub fn is_declaration_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.ends_with(".sli") || n.ends_with(".d.sl"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONEY: &str = "\
+# Formats an amount.
+fn format_money(amount: Int, *, symbol: Bool = true) -> String
+
+class Money extends Value {
+    amount: Int
+    # Builds one.
+    fn new(amount: Int, currency: String = \"EUR\") -> Money
+    static fn zero() -> Money
+    fn add(...others: Money) -> Money
+    fn each(&block: Any)
+}
+";
+
+    #[test]
+    fn parses_signatures_and_doc_comments() {
+        let stubs = StubFile::parse(MONEY).unwrap();
+        let format = stubs.function("format_money").unwrap();
+        assert_eq!(format.doc.as_deref(), Some("Formats an amount."));
+        assert!(format.params[1].is_keyword_only);
+        assert_eq!(format.params[1].default.as_deref(), Some("true"));
+
+        let money = stubs.class("Money").unwrap();
+        assert_eq!(money.superclass.as_deref(), Some("Value"));
+        assert_eq!(money.fields[0].name, "amount");
+        assert_eq!(
+            money.method("new").unwrap().doc.as_deref(),
+            Some("Builds one.")
+        );
+        assert!(money.method("zero").unwrap().is_static);
+        assert!(money.method("add").unwrap().params[0].is_variadic);
+        assert!(money.method("each").unwrap().params[0].is_block);
+        assert_eq!(money.method("each").unwrap().return_type.to_string(), "Any");
+    }
+
+    #[test]
+    fn rendering_round_trips() {
+        let stubs = StubFile::parse(MONEY).unwrap();
+        let rendered = stubs.to_string();
+        assert!(rendered.contains("fn format_money(amount: Int, *, symbol: Bool = true) -> String"));
+        assert_eq!(StubFile::parse(&rendered).unwrap().to_string(), rendered);
+    }
+
+    #[test]
+    fn generates_from_exported_declarations() {
+        let source = "\
+# Adds.
+export fn add(a: Int, b) -> Int { return a + b }
+fn helper() { return 1 }
+export class Counter {
+    count: Int = 0
+    private fn bump() { return 1 }
+    fn value() -> Int { return this.count }
+}
+";
+        let tokens = Scanner::new(source).scan_tokens().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let stubs = StubFile::from_module(&program, source);
+        assert_eq!(
+            stubs.to_string(),
+            "# Adds.\nfn add(a: Int, b: Any) -> Int\n\nclass Counter {\n    count: Int\n    fn value() -> Int\n}\n"
+        );
+    }
+
+    #[test]
+    fn bundled_builtins_parse() {
+        let builtins = builtin_stubs();
+        assert!(builtins.class("Math").is_some());
+        assert!(builtins.function("sha256").is_some());
+    }
+
+    #[test]
+    fn every_builtin_is_declared() {
+        let checker = crate::types::TypeChecker::new();
+        let undeclared: Vec<String> = crate::template::core_eval::builtin_names()
+            .into_iter()
+            // Runtime internals and `Class.method` aliases.
+            .filter(|name| !name.starts_with("__") && !name.contains('.'))
+            .filter(|name| checker.env.get(name).is_none())
+            .collect();
+        assert!(
+            undeclared.is_empty(),
+            "undeclared builtins: {:?}",
+            undeclared
+        );
+    }
+
+    #[test]
+    fn builtin_stubs_agree_with_runtime_arity() {
+        use crate::interpreter::value::Value;
+
+        let mut env = crate::interpreter::environment::Environment::new();
+        crate::interpreter::builtins::register_builtins(&mut env, true);
+        crate::interpreter::builtins::template::register_static_template_helpers(&mut env);
+        for function in &builtin_stubs().functions {
+            let Some(Value::NativeFunction(native)) = env.get(&function.name) else {
+                continue;
+            };
+            let Some(arity) = native.arity else {
+                continue;
+            };
+            let positional = function.params.iter().filter(|p| !p.is_keyword_only);
+            let required = positional
+                .clone()
+                .filter(|p| p.default.is_none() && !p.is_block && !p.is_variadic)
+                .count();
+            let variadic = function.params.iter().any(|p| p.is_variadic);
+            assert!(
+                required <= arity && (variadic || positional.count() >= arity),
+                "{} takes {} argument(s) but is declared as {}",
+                function.name,
+                arity,
+                function.signature()
+            );
+        }
+    }
+
+    #[test]
+    fn declaration_files_sit_next_to_modules_and_in_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("money")).unwrap();
+        std::fs::write(
+            root.join("money/lib.sl"),
+            "export fn format(amount) { return amount }\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("money/lib.d.sl"),
+            "fn format(amount: Int) -> String\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("money/soli.toml"),
+            "[package]\nname = \"money\"\ntypes = \"native.d.sl\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("money/native.d.sl"),
+            "fn parse(text: String) -> Int\n",
+        )
+        .unwrap();
+
+        let module = root.join("money/lib.sl");
+        let beside = module_stubs([module.as_path()]).unwrap();
+        assert!(beside[0].function("format").is_some());
+        let package = package_stubs(root, [module.as_path()]).unwrap();
+        assert!(package[0].function("parse").is_some());
+        // The package being checked reads its own code, not its declarations.
+        assert!(package_stubs(&root.join("money"), [module.as_path()])
+            .unwrap()
+            .is_empty());
+        assert!(is_declaration_file(Path::new("lib/money.d.sl")));
+        assert!(!is_declaration_file(Path::new("lib/money.sl")));
+    }
+}
@@ -34,8 +34,17 @@ pub enum Type {
         params: Vec<Type>,
         return_type: Box<Type>,
     },
+    /// Tuple type: fixed arity, one type per position
+    Tuple(Vec<Type>),
+    /// Record type: a hash with known keys, one type per key. A nullable
+    /// field may be missing.
+    Record(Vec<(String, Type)>),
     /// Future type (async result)
     Future(Box<Type>),
+    /// Lazy iterator (a generator call or a stage chained onto one)
+    Iterator(Box<Type>),
+    /// Nullable type: `T?` — a `T` or `null`
+    Nullable(Box<Type>),
     /// Class type
     Class(ClassType),
     /// Interface type
@@ -60,6 +69,39 @@ impl Type {
         )
     }
 
+    /// `inner?`, collapsing the forms that already admit null (`Int??`,
+    /// `Null?`, `Any?`) so `Nullable` only ever wraps a non-null type.
+    pub fn nullable(inner: Type) -> Type {
+        match inner {
+            Type::Nullable(_) | Type::Null | Type::Any | Type::Unknown => inner,
+            other => Type::Nullable(Box::new(other)),
+        }
+    }
+
+    /// The type with its nullability stripped (`User?` → `User`).
+    pub fn non_null(&self) -> &Type {
+        match self {
+            Type::Nullable(inner) => inner,
+            other => other,
+        }
+    }
+
+    /// The one type all of `types` share (a tuple's elements, say), or `Any`.
+    pub fn common(types: &[Type]) -> Type {
+        match types.split_first() {
+            Some((first, rest)) if rest.iter().all(|t| t == first) => first.clone(),
+            _ => Type::Any,
+        }
+    }
+
+    /// The type of field `name` of a record type.
+    pub fn record_field<'a>(fields: &'a [(String, Type)], name: &str) -> Option<&'a Type> {
+        fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, ty)| ty)
+    }
+
     /// Check if this type is assignable to another type.
     pub fn is_assignable_to(&self, target: &Type) -> bool {
         if self == target {
@@ -73,10 +115,17 @@ impl Type {
             (Type::Unknown, _) | (_, Type::Unknown) => true,
             // Null is assignable to class and interface types
             (Type::Null, Type::Class(_)) | (Type::Null, Type::Interface(_)) => true,
+            // `T?` admits null and anything `T` admits; a `T?` only fits
+            // another nullable (or Any) — it has to be checked for null first
+            (Type::Null, Type::Nullable(_)) => true,
+            (Type::Nullable(a), Type::Nullable(b)) => a.is_assignable_to(b),
+            (_, Type::Nullable(b)) => self.is_assignable_to(b),
+            (Type::Nullable(_), _) => false,
             // Int can be assigned to Float (widening)
             (Type::Int, Type::Float) => true,
             // Array covariance
             (Type::Array(a), Type::Array(b)) => a.is_assignable_to(b),
+            (Type::Iterator(a), Type::Iterator(b)) => a.is_assignable_to(b),
             // Hash covariance
             (
                 Type::Hash {
@@ -88,6 +137,36 @@ impl Type {
                     value_type: v2,
                 },
             ) => k1.is_assignable_to(k2) && v1.is_assignable_to(v2),
+            // Tuple covariance, position by position
+            (Type::Tuple(a), Type::Tuple(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.is_assignable_to(y))
+            }
+            // Records are structural: every field of the target present with
+            // an assignable type (or left out, when nullable); extra fields
+            // are fine
+            (Type::Record(a), Type::Record(b)) => {
+                b.iter()
+                    .all(|(name, ty)| match Type::record_field(a, name) {
+                        Some(field) => field.is_assignable_to(ty),
+                        None => matches!(ty, Type::Nullable(_) | Type::Any | Type::Unknown),
+                    })
+            }
+            // A record is a string-keyed hash of its field types
+            (
+                Type::Record(fields),
+                Type::Hash {
+                    key_type,
+                    value_type,
+                },
+            ) => {
+                Type::String.is_assignable_to(key_type)
+                    && fields.iter().all(|(_, ty)| ty.is_assignable_to(value_type))
+            }
+            // A hash of unknown values (parsed JSON, request params) may be
+            // any record — its keys are only known at runtime
+            (Type::Hash { value_type, .. }, Type::Record(_)) => {
+                matches!(**value_type, Type::Any | Type::Unknown)
+            }
             // Function contravariance in params, covariance in return
             (
                 Type::Function {
@@ -121,8 +200,14 @@ impl Type {
                 }
                 false
             }
-            // Class to interface
-            (Type::Class(class), Type::Interface(iface)) => class.interfaces.contains(&iface.name),
+            // Class to interface: declared on the class or any ancestor
+            (Type::Class(class), Type::Interface(iface)) => {
+                class.interfaces.contains(&iface.name)
+                    || class
+                        .superclass
+                        .as_ref()
+                        .is_some_and(|parent| Type::Class(*parent.clone()).is_assignable_to(target))
+            }
             _ => false,
         }
     }
@@ -157,7 +242,32 @@ impl fmt::Display for Type {
                 }
                 write!(f, ") -> {}", return_type)
             }
+            Type::Tuple(elements) => {
+                write!(f, "(")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, ")")
+            }
+            Type::Record(fields) => {
+                write!(f, "{{")?;
+                for (i, (name, ty)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, " {}: {}", name, ty)?;
+                }
+                write!(f, " }}")
+            }
+            Type::Nullable(inner) => match **inner {
+                Type::Function { .. } => write!(f, "({})?", inner),
+                _ => write!(f, "{}?", inner),
+            },
             Type::Future(inner) => write!(f, "Future<{}>", inner),
+            Type::Iterator(inner) => write!(f, "Iterator<{}>", inner),
             Type::Class(class) => write!(f, "{}", class.name),
             Type::Interface(iface) => write!(f, "{}", iface.name),
             Type::Var(id) => write!(f, "?T{}", id),
@@ -192,6 +302,15 @@ pub struct ClassType {
     pub interfaces: Vec<String>,
     pub fields: HashMap<String, FieldInfo>,
     pub methods: HashMap<String, MethodInfo>,
+    /// Declared parameter lists of the user-defined methods (and `new`),
+    /// for checking named arguments. Builtin classes have none.
+    pub signatures: HashMap<String, Vec<ParamSignature>>,
+    /// `attribute "name", "type"` declarations of a Model subclass. They are
+    /// also in `fields`; this keeps the declared schema apart for typo checks.
+    pub attributes: Vec<String>,
+    /// Declared with `struct`: an immutable value type whose fields can't
+    /// be assigned.
+    pub is_struct: bool,
 }
 
 impl ClassType {
@@ -202,9 +321,31 @@ impl ClassType {
             interfaces: Vec::new(),
             fields: HashMap::new(),
             methods: HashMap::new(),
+            signatures: HashMap::new(),
+            attributes: Vec::new(),
+            is_struct: false,
         }
     }
 
+    /// Declared model attributes, own first then inherited.
+    pub fn declared_attributes(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.attributes.iter().map(String::as_str).collect();
+        if let Some(ref super_) = self.superclass {
+            names.extend(super_.declared_attributes());
+        }
+        names
+    }
+
+    pub fn find_signature(&self, name: &str) -> Option<&[ParamSignature]> {
+        if let Some(signature) = self.signatures.get(name) {
+            return Some(signature);
+        }
+        if let Some(ref super_) = self.superclass {
+            return super_.find_signature(name);
+        }
+        None
+    }
+
     pub fn find_field(&self, name: &str) -> Option<&FieldInfo> {
         if let Some(field) = self.fields.get(name) {
             return Some(field);
@@ -256,6 +397,32 @@ pub struct MethodInfo {
     pub is_static: bool,
 }
 
+/// How a declared parameter may be passed: what a call's named arguments
+/// are checked against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamSignature {
+    pub name: String,
+    pub has_default: bool,
+    pub is_keyword_only: bool,
+    pub is_block: bool,
+    pub is_variadic: bool,
+}
+
+impl ParamSignature {
+    pub fn of(params: &[crate::ast::Parameter]) -> Vec<Self> {
+        params
+            .iter()
+            .map(|p| Self {
+                name: p.name.clone(),
+                has_default: p.default_value.is_some(),
+                is_keyword_only: p.is_keyword_only,
+                is_block: p.is_block_param,
+                is_variadic: p.is_variadic,
+            })
+            .collect()
+    }
+}
+
 /// Interface type information.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct InterfaceType {
@@ -417,6 +584,36 @@ mod tests {
         assert!(!Type::Class(class("Foo")).is_assignable_to(&Type::Null));
     }
 
+    // ---------- is_assignable_to: nullable ----------
+
+    #[test]
+    fn assignable_null_and_inner_to_nullable() {
+        let nullable_string = Type::nullable(Type::String);
+        assert!(Type::Null.is_assignable_to(&nullable_string));
+        assert!(Type::String.is_assignable_to(&nullable_string));
+        assert!(Type::Int.is_assignable_to(&Type::nullable(Type::Float)));
+        assert!(!Type::Int.is_assignable_to(&nullable_string));
+    }
+
+    #[test]
+    fn assignable_nullable_to_non_null_rejected() {
+        let nullable_string = Type::nullable(Type::String);
+        assert!(!nullable_string.is_assignable_to(&Type::String));
+        assert!(nullable_string.is_assignable_to(&nullable_string));
+        assert!(nullable_string.is_assignable_to(&Type::Any));
+    }
+
+    #[test]
+    fn nullable_collapses_types_that_already_admit_null() {
+        assert_eq!(
+            Type::nullable(Type::nullable(Type::Int)),
+            Type::nullable(Type::Int)
+        );
+        assert_eq!(Type::nullable(Type::Any), Type::Any);
+        assert_eq!(Type::nullable(Type::Null), Type::Null);
+        assert_eq!(Type::nullable(Type::Int).to_string(), "Int?");
+    }
+
     // ---------- is_assignable_to: arrays / hashes ----------
 
     #[test]
@@ -460,6 +657,45 @@ mod tests {
         assert!(!h1.is_assignable_to(&h2));
     }
 
+    // ---------- is_assignable_to: records ----------
+
+    fn user_record() -> Type {
+        Type::Record(vec![
+            ("id".to_string(), Type::Int),
+            ("email".to_string(), Type::nullable(Type::String)),
+        ])
+    }
+
+    #[test]
+    fn assignable_record_structurally() {
+        let wider = Type::Record(vec![
+            ("id".to_string(), Type::Int),
+            ("name".to_string(), Type::String),
+        ]);
+        // `email` is nullable, so it may be missing; `name` is extra.
+        assert!(wider.is_assignable_to(&user_record()));
+        let wrong = Type::Record(vec![("id".to_string(), Type::String)]);
+        assert!(!wrong.is_assignable_to(&user_record()));
+        assert!(!Type::Record(vec![]).is_assignable_to(&user_record()));
+    }
+
+    #[test]
+    fn assignable_record_to_and_from_hash() {
+        let any_hash = Type::Hash {
+            key_type: Box::new(Type::String),
+            value_type: Box::new(Type::Any),
+        };
+        let int_hash = Type::Hash {
+            key_type: Box::new(Type::String),
+            value_type: Box::new(Type::Int),
+        };
+        assert!(user_record().is_assignable_to(&any_hash));
+        assert!(!user_record().is_assignable_to(&int_hash));
+        assert!(any_hash.is_assignable_to(&user_record()));
+        assert!(!int_hash.is_assignable_to(&user_record()));
+        assert_eq!(user_record().to_string(), "{ id: Int, email: String? }");
+    }
+
     // ---------- is_assignable_to: function variance ----------
 
     #[test]
@@ -709,6 +945,10 @@ mod tests {
     #[test]
     fn display_future_and_named_types() {
         assert_eq!(Type::Future(Box::new(Type::Int)).to_string(), "Future<Int>");
+        assert_eq!(
+            Type::Iterator(Box::new(Type::Int)).to_string(),
+            "Iterator<Int>"
+        );
         assert_eq!(Type::Class(class("User")).to_string(), "User");
         assert_eq!(Type::Interface(iface("Greeter")).to_string(), "Greeter");
         assert_eq!(Type::Var(7).to_string(), "?T7");
@@ -0,0 +1,304 @@
+//! `.slc` files: a compiled module saved to disk so the VM can run it without
+//! lexing, parsing, type-checking or compiling the source again.
+//!
+//! Layout: `SLBC` + format-version byte + header length (u32 LE) + JSON
+//! header + MessagePack of the [`CompiledModule`]. The header is read first
+//! so a file from another soli version is rejected with a clear message
+//! instead of failing halfway through decoding opcodes.
+//!
+//! `soli build app.sl` writes `app.slc` next to the source. `soli app.slc`
+//! runs it as is; `soli --vm app.sl` picks it up instead of compiling as
+//! long as none of the sources it was built from changed.
+//!
+//! `soli build --precompile my_app` writes a `.slc` next to every app file
+//! `soli serve` loads. Serve runs app files on the interpreter and compiles
+//! their functions for the VM on demand, so those hold the file's parsed
+//! unit rather than a compiled module: a worker loads it instead of lexing
+//! and parsing the source, for as long as the source hashes the same.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::chunk::CompiledModule;
+use crate::ast::Program;
+
+pub const BYTECODE_MAGIC: &[u8; 4] = b"SLBC";
+/// Bump on ANY change to the opcode or constant types: rmp of derived enums
+/// is not stable across variant reordering.
+pub const BYTECODE_FORMAT_VERSION: u8 = 6;
+/// The extension `soli build` gives a compiled script.
+pub const BYTECODE_EXTENSION: &str = "slc";
+
+/// magic(4) + version(1) + header length(4)
+const PREAMBLE_LEN: usize = 4 + 1 + 4;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    soli_version: String,
+    /// Every file the module was compiled from (the script and the modules
+    /// it imports) with the SHA-256 of its contents. Paths under the `.slc`'s
+    /// directory are stored relative to it, so a build directory can move.
+    sources: Vec<(String, String)>,
+    #[serde(default)]
+    kind: Kind,
+    /// `bundle::AST_FORMAT_VERSION` when the body is a [`Kind::Unit`].
+    #[serde(default)]
+    ast_format: u8,
+}
+
+/// What the body of a `.slc` is.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum Kind {
+    /// A [`CompiledModule`] the VM runs.
+    #[default]
+    Script,
+    /// The parsed [`Program`] of one serve app file.
+    Unit,
+}
+
+/// True when the bytes are a compiled `.slc` module rather than source text.
+pub fn is_bytecode(data: &[u8]) -> bool {
+    data.len() >= PREAMBLE_LEN && &data[..4] == BYTECODE_MAGIC
+}
+
+/// Where `soli build` puts the compiled form of `source`.
+pub fn cache_path(source: &Path) -> PathBuf {
+    source.with_extension(BYTECODE_EXTENSION)
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+fn base_dir(out: &Path) -> PathBuf {
+    let dir = out
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf())
+}
+
+/// Serialize `module`, recording the hashes of the `sources` it was compiled
+/// from, and write it to `out`. Returns the number of bytes written.
+pub fn write_module(
+    module: &CompiledModule,
+    sources: &[PathBuf],
+    out: &Path,
+) -> Result<usize, String> {
+    let base = base_dir(out);
+    let mut recorded = Vec::with_capacity(sources.len());
+    for source in sources {
+        let content = std::fs::read(source)
+            .map_err(|e| format!("Failed to read '{}': {}", source.display(), e))?;
+        let full = source
+            .canonicalize()
+            .unwrap_or_else(|_| source.to_path_buf());
+        let stored = full.strip_prefix(&base).unwrap_or(&full);
+        recorded.push((stored.to_string_lossy().into_owned(), sha256_hex(&content)));
+    }
+    let header = Header {
+        soli_version: env!("CARGO_PKG_VERSION").to_string(),
+        sources: recorded,
+        kind: Kind::Script,
+        ast_format: 0,
+    };
+    let data = encode(module, &header)?;
+    std::fs::write(out, &data)
+        .map_err(|e| format!("Failed to write '{}': {}", out.display(), e))?;
+    Ok(data.len())
+}
+
+/// Serialize `program`, the parsed unit of the app file `source` whose
+/// content is `content`, and write it to `out`. Returns the number of bytes
+/// written.
+pub fn write_unit(
+    program: &Program,
+    source: &Path,
+    content: &[u8],
+    out: &Path,
+) -> Result<usize, String> {
+    let name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let header = Header {
+        soli_version: env!("CARGO_PKG_VERSION").to_string(),
+        sources: vec![(name, sha256_hex(content))],
+        kind: Kind::Unit,
+        ast_format: crate::bundle::AST_FORMAT_VERSION,
+    };
+    let data = encode(program, &header)?;
+    std::fs::write(out, &data)
+        .map_err(|e| format!("Failed to write '{}': {}", out.display(), e))?;
+    Ok(data.len())
+}
+
+fn encode<T: Serialize>(body: &T, header: &Header) -> Result<Vec<u8>, String> {
+    let header =
+        serde_json::to_vec(header).map_err(|e| format!("bytecode header failed: {}", e))?;
+    let body =
+        rmp_serde::to_vec(body).map_err(|e| format!("bytecode serialization failed: {}", e))?;
+    let mut out = Vec::with_capacity(PREAMBLE_LEN + header.len() + body.len());
+    out.extend_from_slice(BYTECODE_MAGIC);
+    out.push(BYTECODE_FORMAT_VERSION);
+    out.extend_from_slice(&(header.len() as u32).to_le_bytes());
+    out.extend_from_slice(&header);
+    out.extend_from_slice(&body);
+    Ok(out)
+}
+
+/// Split a `.slc` into its header and module body, rejecting one built by
+/// a different soli: the opcodes have no cross-version stability guarantee.
+fn decode_header(data: &[u8]) -> Result<(Header, &[u8]), String> {
+    if !is_bytecode(data) {
+        return Err("not a compiled soli module (bad magic)".to_string());
+    }
+    let version = data[4];
+    let header_len = u32::from_le_bytes([data[5], data[6], data[7], data[8]]) as usize;
+    let body_start = PREAMBLE_LEN + header_len;
+    if data.len() < body_start {
+        return Err("compiled module is truncated".to_string());
+    }
+    let header: Header = serde_json::from_slice(&data[PREAMBLE_LEN..body_start])
+        .map_err(|e| format!("invalid compiled module header: {}", e))?;
+    let running = env!("CARGO_PKG_VERSION");
+    if version != BYTECODE_FORMAT_VERSION || header.soli_version != running {
+        return Err(format!(
+            "this module was compiled by soli {} (bytecode format v{}) but this is soli {} \
+             (bytecode format v{}) — rebuild it with `soli build`",
+            header.soli_version, version, running, BYTECODE_FORMAT_VERSION
+        ));
+    }
+    Ok((header, &data[body_start..]))
+}
+
+fn decode_body(body: &[u8]) -> Result<CompiledModule, String> {
+    rmp_serde::from_slice(body)
+        .map_err(|e| format!("compiled module is corrupted (rebuild it): {}", e))
+}
+
+/// Decode a `.slc` without looking at its sources, which need not exist.
+pub fn decode(data: &[u8]) -> Result<CompiledModule, String> {
+    let (header, body) = decode_header(data)?;
+    if header.kind != Kind::Script {
+        return Err("this is a precompiled app file, which `soli serve` loads".to_string());
+    }
+    decode_body(body)
+}
+
+/// Load the `.slc` at `path` if it is usable in place of its sources: built
+/// by this soli, and every file it was compiled from is unchanged. Anything
+/// else, including a missing file, means compiling from source.
+pub fn load_fresh(path: &Path) -> Option<CompiledModule> {
+    let data = std::fs::read(path).ok()?;
+    let (header, body) = decode_header(&data).ok()?;
+    if header.kind != Kind::Script {
+        return None;
+    }
+    let base = base_dir(path);
+    for (source, hash) in &header.sources {
+        let content = std::fs::read(base.join(source)).ok()?;
+        if sha256_hex(&content) != *hash {
+            return None;
+        }
+    }
+    decode_body(body).ok()
+}
+
+/// The unit in the `.slc` at `path` if it was built by this soli from
+/// exactly `source`, the current content of the app file it stands in for.
+/// Anything else, including a missing file, means parsing the source.
+pub fn load_fresh_unit(path: &Path, source: &[u8]) -> Option<Program> {
+    let data = std::fs::read(path).ok()?;
+    let (header, body) = decode_header(&data).ok()?;
+    let fresh = header.kind == Kind::Unit
+        && header.ast_format == crate::bundle::AST_FORMAT_VERSION
+        && matches!(header.sources.as_slice(), [(_, hash)] if *hash == sha256_hex(source));
+    if !fresh {
+        return None;
+    }
+    rmp_serde::from_slice(body).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::value::Value;
+    use crate::lexer::Scanner;
+    use crate::parser::Parser;
+    use crate::vm::{Compiler, Vm};
+
+    fn compile(source: &str) -> CompiledModule {
+        let tokens = Scanner::new(source).scan_tokens().expect("lexer error");
+        let program = Parser::new(tokens).parse().expect("parser error");
+        Compiler::compile(&program).expect("compile error")
+    }
+
+    fn header() -> Header {
+        Header {
+            soli_version: env!("CARGO_PKG_VERSION").to_string(),
+            sources: Vec::new(),
+            kind: Kind::Script,
+            ast_format: 0,
+        }
+    }
+
+    #[test]
+    fn decoded_module_runs_like_the_original() {
+        let module = compile(
+            "fn fib(n) { if (n < 2) { return n; } return fib(n - 1) + fib(n - 2); }\n\
+             let words = [\"a\", \"bc\"];\n\
+             let total = fib(10) + words[1].length;",
+        );
+        let data = encode(&module, &header()).unwrap();
+        assert!(is_bytecode(&data));
+        let decoded = decode(&data).unwrap();
+        assert_eq!(decoded.main.chunk.code, module.main.chunk.code);
+        let mut vm = Vm::new();
+        vm.execute(&decoded.main).unwrap();
+        assert_eq!(vm.globals.get("total"), Some(&Value::Int(57)));
+    }
+
+    #[test]
+    fn rejects_a_module_from_another_soli() {
+        let mut stale = header();
+        stale.soli_version = "0.0.1".to_string();
+        let data = encode(&compile("1"), &stale).unwrap();
+        let err = decode(&data).unwrap_err();
+        assert!(err.contains("compiled by soli 0.0.1"), "{}", err);
+        assert!(decode(b"let x = 1").is_err());
+    }
+
+    #[test]
+    fn load_fresh_ignores_a_module_whose_source_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("app.sl");
+        std::fs::write(&source, "1 + 2").unwrap();
+        let out = cache_path(&source);
+        write_module(&compile("1 + 2"), std::slice::from_ref(&source), &out).unwrap();
+        assert!(load_fresh(&out).is_some());
+
+        std::fs::write(&source, "1 + 3").unwrap();
+        assert!(load_fresh(&out).is_none());
+    }
+
+    #[test]
+    fn load_fresh_unit_needs_the_exact_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("users_controller.sl");
+        std::fs::write(&source, "fn index() { 1 }").unwrap();
+        let out = cache_path(&source);
+        let program = Parser::new(Scanner::new("fn index() { 1 }").scan_tokens().unwrap())
+            .parse()
+            .unwrap();
+        write_unit(&program, &source, b"fn index() { 1 }", &out).unwrap();
+
+        assert_eq!(load_fresh_unit(&out, b"fn index() { 1 }"), Some(program));
+        assert!(load_fresh_unit(&out, b"fn index() { 2 }").is_none());
+        // Not a script: neither `soli app.slc` nor the script cache take it.
+        assert!(decode(&std::fs::read(&out).unwrap()).is_err());
+        assert!(load_fresh(&out).is_none());
+    }
+}
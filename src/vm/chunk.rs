@@ -1,12 +1,14 @@
 //! Bytecode chunk and function prototype types.
 
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+
+use serde::{Deserialize, Serialize};
 
 use super::opcode::Op;
 use super::upvalue::UpvalueDescriptor;
 
 /// A constant value stored in a chunk's constant pool.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Constant {
     Int(i64),
     Float(f64),
@@ -28,10 +30,22 @@ pub enum Constant {
     /// block) argument. `CallNamed` reads it to split the evaluated arguments
     /// back into positional and named groups before binding them to the callee.
     ArgNames(Arc<Vec<Option<crate::interpreter::value::SoliStr>>>),
+    /// Argument layout for a `CallSpread`: like `ArgNames`, one entry per
+    /// argument slot in source order, with the `*array` slots marked so the
+    /// call can expand them before binding.
+    SpreadArgs(Arc<Vec<ArgSlot>>),
+}
+
+/// How one argument slot of a `CallSpread` is passed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArgSlot {
+    Positional,
+    Named(crate::interpreter::value::SoliStr),
+    Spread,
 }
 
 /// A compiled function (or top-level script).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FunctionProto {
     /// Function name (empty string for top-level script).
     pub name: String,
@@ -46,6 +60,14 @@ pub struct FunctionProto {
     /// parameters — beyond that a parameter reads as defaulted, which errs
     /// toward running the prologue rather than a spurious arity error.
     pub defaults_mask: u64,
+    /// Bit `i` is set when parameter `i` is keyword-only (declared after a
+    /// bare `*`), so positional arguments skip it. Zero for almost every
+    /// function, which keeps positional calls on the fast path. Parameters
+    /// past bit 63 are never keyword-only.
+    pub keyword_only_mask: u64,
+    /// Index of the `...rest` parameter, if any, which collects the positional
+    /// arguments past the others into an array.
+    pub variadic_index: Option<usize>,
     /// Parameter names (for named argument resolution).
     pub param_names: Vec<String>,
     /// The bytecode instructions.
@@ -54,8 +76,17 @@ pub struct FunctionProto {
     pub upvalue_descriptors: Vec<UpvalueDescriptor>,
     /// Whether this is a method (has `this` in slot 0).
     pub is_method: bool,
+    /// `move` lambda: `Op::Closure` gives it closed copies of the captured
+    /// values instead of sharing the enclosing function's upvalues.
+    pub captures_by_value: bool,
 }
 
+// Protos are shared across worker threads (`vm::shared_code`).
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<FunctionProto>();
+};
+
 impl FunctionProto {
     pub fn new(name: String) -> Self {
         Self {
@@ -63,16 +94,27 @@ impl FunctionProto {
             arity: 0,
             defaults: 0,
             defaults_mask: 0,
+            keyword_only_mask: 0,
+            variadic_index: None,
             param_names: Vec::new(),
             chunk: Chunk::new(),
             upvalue_descriptors: Vec::new(),
             is_method: false,
+            captures_by_value: false,
         }
     }
+
+    /// Whether a positional call must go through the argument binder rather
+    /// than fill the parameter slots in order: some parameter is keyword-only
+    /// or variadic. False for almost every function.
+    #[inline]
+    pub fn needs_argument_binding(&self) -> bool {
+        self.keyword_only_mask != 0 || self.variadic_index.is_some()
+    }
 }
 
 /// A chunk of bytecode: instructions + constant pool + line info.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Chunk {
     /// The bytecode instructions.
     pub code: Vec<Op>,
@@ -80,6 +122,15 @@ pub struct Chunk {
     pub lines: Vec<usize>,
     /// Constant pool.
     pub constants: Vec<Constant>,
+    /// Global slot for each name constant a global opcode references,
+    /// resolved on first execution (see `vm::globals`). Not serialized:
+    /// slots are per-process, so a loaded `.slc` resolves them afresh.
+    #[serde(skip)]
+    global_slots: OnceLock<Box<[u32]>>,
+    /// Inline-cache site number for each method-call instruction, by
+    /// instruction index (`u32::MAX` elsewhere). See `vm::inline_cache`.
+    #[serde(skip)]
+    method_sites: OnceLock<Box<[u32]>>,
 }
 
 impl Chunk {
@@ -88,7 +139,69 @@ impl Chunk {
             code: Vec::new(),
             lines: Vec::new(),
             constants: Vec::new(),
+            global_slots: OnceLock::new(),
+            method_sites: OnceLock::new(),
+        }
+    }
+
+    /// Global slot of the name in string constant `idx`, as used by
+    /// `GetGlobal` and the other global opcodes.
+    #[inline]
+    pub fn global_slot(&self, idx: u16) -> u32 {
+        let slots = self
+            .global_slots
+            .get_or_init(|| self.resolve_global_slots());
+        match slots.get(idx as usize) {
+            Some(&slot) if slot != u32::MAX => slot,
+            // Not referenced by a global opcode at resolve time (only
+            // possible for hand-built chunks); resolve directly.
+            _ => match &self.constants[idx as usize] {
+                Constant::String(s) => super::globals::global_slot(s),
+                _ => super::globals::global_slot(""),
+            },
+        }
+    }
+
+    fn resolve_global_slots(&self) -> Box<[u32]> {
+        let mut slots = vec![u32::MAX; self.constants.len()];
+        for op in &self.code {
+            let idx = match *op {
+                Op::GetGlobal(i)
+                | Op::SetGlobal(i)
+                | Op::DefineGlobal(i)
+                | Op::CallGlobal(i, _)
+                | Op::GetGlobalNullCheck(i)
+                | Op::GetGlobalCall(i, _)
+                | Op::HashGetGlobalConst(i, _)
+                | Op::HashHasKeyGlobalConst(i, _)
+                | Op::HashDeleteGlobalConst(i, _)
+                | Op::HashSetGlobalConst(i, _) => i as usize,
+                _ => continue,
+            };
+            if let Some(Constant::String(name)) = self.constants.get(idx) {
+                slots[idx] = super::globals::global_slot(name);
+            }
         }
+        slots.into_boxed_slice()
+    }
+
+    /// Inline-cache site of the method call at instruction `ip`, if it is one.
+    #[inline]
+    pub fn method_site(&self, ip: usize) -> Option<u32> {
+        let sites = self.method_sites.get_or_init(|| {
+            let mut next = 0;
+            self.code
+                .iter()
+                .map(|op| match op {
+                    Op::CallMethod(..) | Op::CallMethodById(..) | Op::CallMethodSpread(..) => {
+                        next += 1;
+                        next - 1
+                    }
+                    _ => u32::MAX,
+                })
+                .collect()
+        });
+        sites.get(ip).copied().filter(|&site| site != u32::MAX)
     }
 
     /// Emit an instruction and record its source line.
@@ -135,6 +248,7 @@ impl Chunk {
             | Op::JumpIfFalseNoPop(target)
             | Op::JumpIfTrueNoPop(target)
             | Op::NullishJump(target)
+            | Op::JumpIfNull(target)
             | Op::ForIter(target)
             | Op::ForIterRange(target)
             | Op::JumpIfParamSupplied(_, target) => {
@@ -152,7 +266,7 @@ impl Default for Chunk {
 }
 
 /// A compiled module: the top-level script function.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompiledModule {
     pub main: Arc<FunctionProto>,
 }
@@ -215,6 +329,31 @@ mod tests {
         assert_eq!(c.constants.len(), 1);
     }
 
+    #[test]
+    fn global_slot_resolves_names_used_by_global_ops() {
+        let mut c = Chunk::new();
+        let name = c.add_constant(Constant::String("chunk_test_global".into()));
+        c.emit(Op::GetGlobal(name), 1);
+        assert_eq!(
+            c.global_slot(name),
+            super::super::globals::global_slot("chunk_test_global")
+        );
+        // A clone (as `.slc` loading and proto caching do) agrees.
+        assert_eq!(c.clone().global_slot(name), c.global_slot(name));
+    }
+
+    #[test]
+    fn method_sites_number_method_calls_in_order() {
+        let mut c = Chunk::new();
+        c.emit(Op::CallMethod(0, 0), 1);
+        c.emit(Op::Pop, 1);
+        c.emit(Op::CallMethodById(0, 1, 0), 1);
+        assert_eq!(c.method_site(0), Some(0));
+        assert_eq!(c.method_site(1), None);
+        assert_eq!(c.method_site(2), Some(1));
+        assert_eq!(c.method_site(9), None);
+    }
+
     #[test]
     fn add_constant_does_not_dedupe_non_string_kinds() {
         // Pin: only String dedup is implemented. Two equal Int constants
@@ -259,6 +398,7 @@ mod tests {
             Op::JumpIfFalseNoPop(0),
             Op::JumpIfTrueNoPop(0),
             Op::NullishJump(0),
+            Op::JumpIfNull(0),
             Op::ForIter(0),
             Op::ForIterRange(0),
         ];
@@ -276,6 +416,7 @@ mod tests {
                 | Op::JumpIfFalseNoPop(t)
                 | Op::JumpIfTrueNoPop(t)
                 | Op::NullishJump(t)
+                | Op::JumpIfNull(t)
                 | Op::ForIter(t)
                 | Op::ForIterRange(t) => t,
                 other => panic!("unexpected variant after patch: {other:?}"),
@@ -303,6 +444,7 @@ mod tests {
         assert_eq!(p.defaults, 0);
         assert!(p.param_names.is_empty());
         assert_eq!(p.defaults_mask, 0);
+        assert_eq!(p.keyword_only_mask, 0);
         assert!(p.upvalue_descriptors.is_empty());
         assert!(p.chunk.is_empty());
         assert!(!p.is_method);
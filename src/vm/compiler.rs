@@ -22,21 +22,19 @@ pub type CompileResult<T> = Result<T, CompileError>;
 /// Whether the VM honors Soli's optional-`let` (bare assignment creates a
 /// binding) by hoisting function-locals and upserting globals.
 ///
-/// **Off by default.** When disabled, a bare assignment to an undeclared name
-/// compiles to `SetGlobal`, which raises "undefined variable" at runtime —
-/// causing the server to fall back to the tree-walking interpreter for that
-/// handler (the long-standing behavior). Enabling it (`SOLI_VM_OPTIONAL_LET=1`)
-/// lets such handlers run on the VM, but that also widens the VM's exposure to
-/// a class of latent control-flow/local-assignment bugs (e.g. assignment inside
-/// `for`-with-index and `try`/`catch` blocks) that are otherwise masked by the
-/// fallback. Keep it off until those are fixed and differentially tested.
+/// On by default, matching the tree-walking interpreter now that the
+/// local-assignment bugs it used to expose (assignment inside `for`-with-index
+/// and `try`/`catch`) are fixed and differentially tested. `SOLI_VM_OPTIONAL_LET=0`
+/// restores the old behavior: a bare assignment to an undeclared name compiles
+/// to `SetGlobal`, which raises "undefined variable" at runtime and sends the
+/// handler back to the interpreter.
 pub fn optional_let_enabled() -> bool {
     use std::sync::OnceLock;
     static ENABLED: OnceLock<bool> = OnceLock::new();
     *ENABLED.get_or_init(|| {
         std::env::var("SOLI_VM_OPTIONAL_LET")
-            .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
-            .unwrap_or(false)
+            .map(|v| !(v == "0" || v.eq_ignore_ascii_case("false")))
+            .unwrap_or(true)
     })
 }
 
@@ -95,6 +93,12 @@ pub struct Compiler {
     /// over-count merely causes an extra (safe) fallback; the design must never
     /// under-count (which would pick a wrong slot).
     pub stack_height: usize,
+    /// Number of `try` statements around the emit point. A call inside one
+    /// is never a tail call: its exception handler belongs to this frame.
+    pub try_depth: usize,
+    /// The exception handlers live at the emit point, innermost last, each
+    /// with the `finally` block a `return` or `break` leaving it must run.
+    pub handlers: Vec<Option<Stmt>>,
 }
 
 #[derive(Debug, Clone)]
@@ -102,6 +106,13 @@ pub struct LoopContext {
     pub start: usize,
     pub break_patches: Vec<usize>,
     pub enclosing: Option<Box<LoopContext>>,
+    /// Locals live when the loop began; a `break` pops the ones above.
+    pub locals: usize,
+    /// Handlers live when the loop began; a `break` unwinds the ones above.
+    pub handlers: usize,
+    /// Whether this is a `for` loop, whose iterator sits on the VM's
+    /// iterator stack until the loop ends.
+    pub iterator: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -122,6 +133,8 @@ impl Compiler {
             class_context: None,
             known_globals: Rc::new(RefCell::new(HashSet::new())),
             stack_height: 0,
+            try_depth: 0,
+            handlers: Vec::new(),
         };
 
         // Reserve slot 0 for `this` in methods, or an empty slot otherwise
@@ -203,6 +216,8 @@ impl Compiler {
             .count() as u8;
         compiler.proto.param_names = func.params.iter().map(|p| p.name.clone()).collect();
         compiler.proto.defaults_mask = defaults_mask(&func.params);
+        compiler.proto.keyword_only_mask = keyword_only_mask(&func.params);
+        compiler.proto.variadic_index = func.params.iter().position(|p| p.is_variadic);
 
         let line = func.span.map(|s| s.line as usize).unwrap_or(0);
         compiler.begin_scope();
@@ -348,6 +363,19 @@ impl Compiler {
         }
     }
 
+    /// Whether `name` resolves to a `const` local, here or (as an upvalue) in
+    /// an enclosing function.
+    pub fn is_const_local(&self, name: &str) -> bool {
+        let mut current = Some(self);
+        while let Some(compiler) = current {
+            if let Some(slot) = compiler.resolve_local(name) {
+                return compiler.locals[slot as usize].is_const;
+            }
+            current = compiler.enclosing.as_deref();
+        }
+        false
+    }
+
     /// Whether `name` is a local in some enclosing compiler (i.e. it would be
     /// captured as an upvalue rather than introduced as a new local here).
     fn enclosing_has_local(&self, name: &str) -> bool {
@@ -370,12 +398,11 @@ impl Compiler {
         if self.scope_depth == 0 {
             return Ok(()); // globals are handled differently
         }
-        // Check for redeclaration in the same scope
-        for local in self.locals.iter().rev() {
-            if local.depth != -1 && local.depth < self.scope_depth {
-                break;
-            }
-            if local.name == name {
+        // `let` redeclarations reuse the slot (see `same_scope_local`); one
+        // that reaches here (a tuple `let`) shadows it with a fresh slot —
+        // except over a constant, which can't be displaced.
+        if let Some(slot) = self.same_scope_local(name) {
+            if self.locals[slot as usize].is_const && !is_const {
                 return Err(CompileError::new(
                     format!("Variable '{}' already declared in this scope", name),
                     span,
@@ -386,6 +413,19 @@ impl Compiler {
         Ok(())
     }
 
+    /// The slot of a local called `name` declared in the current scope.
+    pub fn same_scope_local(&self, name: &str) -> Option<u16> {
+        for (i, local) in self.locals.iter().enumerate().rev() {
+            if local.depth != -1 && local.depth < self.scope_depth {
+                break;
+            }
+            if local.name == name {
+                return Some(i as u16);
+            }
+        }
+        None
+    }
+
     pub fn resolve_local(&self, name: &str) -> Option<u16> {
         for (i, local) in self.locals.iter().enumerate().rev() {
             if local.name == name && local.depth != -1 {
@@ -458,6 +498,8 @@ impl Compiler {
             params.iter().filter(|p| p.default_value.is_some()).count() as u8;
         new_compiler.proto.param_names = params.iter().map(|p| p.name.clone()).collect();
         new_compiler.proto.defaults_mask = defaults_mask(params);
+        new_compiler.proto.keyword_only_mask = keyword_only_mask(params);
+        new_compiler.proto.variadic_index = params.iter().position(|p| p.is_variadic);
 
         // Swap self with the new compiler, storing self as enclosing
         let old = std::mem::replace(self, new_compiler);
@@ -526,23 +568,30 @@ impl Compiler {
 
     // --- Loop context ---
 
-    pub fn begin_loop(&mut self, start: usize) {
+    pub fn begin_loop(&mut self, start: usize, iterator: bool) {
         let enclosing = self.loop_context.take().map(Box::new);
         self.loop_context = Some(LoopContext {
             start,
             break_patches: Vec::new(),
             enclosing,
+            locals: self.locals.len(),
+            handlers: self.handlers.len(),
+            iterator,
         });
     }
 
-    pub fn end_loop(&mut self) {
+    /// Close the innermost loop, patching its `break`s to the current
+    /// offset. Returns whether there were any.
+    pub fn end_loop(&mut self) -> bool {
         if let Some(ctx) = self.loop_context.take() {
             // Patch all break jumps
             for patch in &ctx.break_patches {
                 self.patch_jump(*patch);
             }
             self.loop_context = ctx.enclosing.map(|b| *b);
+            return !ctx.break_patches.is_empty();
         }
+        false
     }
 
     pub fn add_break_patch(&mut self, offset: usize) {
@@ -603,6 +652,17 @@ fn defaults_mask(params: &[Parameter]) -> u64 {
     mask
 }
 
+/// Bitmask of the keyword-only parameters — see
+/// [`FunctionProto::keyword_only_mask`].
+fn keyword_only_mask(params: &[Parameter]) -> u64 {
+    params
+        .iter()
+        .take(64)
+        .enumerate()
+        .filter(|(_, p)| p.is_keyword_only)
+        .fold(0, |mask, (i, _)| mask | 1u64 << i)
+}
+
 fn stack_effect(op: Op) -> i32 {
     use Op::*;
     match op {
@@ -614,6 +674,7 @@ fn stack_effect(op: Op) -> i32 {
         GetLocal(_) | GetGlobal(_) | GetUpvalue(_) | GetThis | GetSuper(_) => 1,
         SetLocal(_) | SetGlobal(_) | SetUpvalue(_) => 0,
         DefineGlobal(_) | CloseUpvalue => -1,
+        GlobalByName(_) | ConstGlobal(_) | AssignConst(_) => 0,
         // Binary arithmetic / comparison: pop 2, push 1. Unary: pop 1, push 1.
         Add | Subtract | Multiply | Divide | Modulo => -1,
         Equal | NotEqual | Less | LessEqual | Greater | GreaterEqual => -1,
@@ -629,21 +690,26 @@ fn stack_effect(op: Op) -> i32 {
         | JumpIfParamSupplied(_, _) => 0,
         JumpIfFalse(_) => -1,
         // Calls: pop callee/receiver + argc args, push the result.
-        Call(argc) | CallMethod(_, argc) | CallMethodById(_, argc, _) => -(argc as i32),
+        Call(argc) | TailCall(argc) | CallMethod(_, argc) | CallMethodById(_, argc, _) => {
+            -(argc as i32)
+        }
+        CallMethodSpread(_, argc, _) => -(argc as i32),
         // Same shape as Call/New: the callee plus argc slots collapse to one result.
         CallNamed(argc, _) | NewNamed(argc, _) => -(argc as i32),
+        CallSpread(argc, _) | NewSpread(argc, _) => -(argc as i32),
         // [this, args…] collapse to the result: net -argc.
         CallSuperInit(argc) | CallSuperMethod(_, argc) => -(argc as i32),
         CallGlobal(_, argc) | GetGlobalCall(_, argc) => 1 - argc as i32,
         Closure(_) => 1,
         Return => 0,
         // Collections.
-        Array(n) | BuildString(n) | HashWithKeys(_, n) => 1 - n as i32,
+        Array(n) | Tuple(n) | BuildString(n) | HashWithKeys(_, n) => 1 - n as i32,
+        Unpack(n) => n as i32 - 1,
         Hash(n) => 1 - 2 * n as i32,
         ArrayPush => -1,
-        Range | GetIndex => -1,
+        Range(_) | GetIndex => -1,
         SetIndex => -2,
-        Spread => 0,
+        Spread => -1,
         // Objects.
         GetProperty(_) => 0,
         SetProperty(_) => -1,
@@ -651,19 +717,29 @@ fn stack_effect(op: Op) -> i32 {
         Class(_) => 1,
         Inherit | Method(_) | StaticMethod(_) | Field(_) | StaticField(_) | ConstField(_)
         | StaticConstField(_) => -1,
+        Implement(_) | DeclareConst(_) | DeclareStaticConst(_) => 0,
+        NestedClass(_) => -1,
+        IsModelClass => 1,
+        // Pops the defaults hash, pushes the struct type.
+        Struct(_, _) => 0,
         New(argc) => -(argc as i32),
         // Exceptions.
         TryBegin(_, _) | TryEnd | CatchMatch(_, _) | PopHandler | RescueJump(_) => 0,
         Throw | Rethrow => -1,
         // Iterators (GetIter/GetIterRange consume from the value stack; ForIter
         // pushes the element on the continue path — loops resync regardless).
-        GetIter => -1,
-        GetIterRange => -2,
+        GetIter | GetEntryIter => -1,
+        GetIterRange(_) => -2,
         ForIter(_) | ForIterRange(_) => 1,
+        PopIter => 0,
         // I/O: pop n, push the Null result.
         Print(n) => 1 - n as i32,
         Import(_) => 0,
         JsonParse | JsonStringify => 0,
+        // Pattern tests: pop the value, push the boolean.
+        IsType(_) | IsArrayLen(_, _) | IsTupleLen(_) | IsHash => 0,
+        IsInstance(_) | HasField(_) | InstanceField(_) => 0,
+        IsEnumVariant(_, _, _) | VariantField(_, _) => 0,
         // Peephole super-instructions (not emitted during the tracked pass; values
         // for completeness). Hash*Const directly-emitted variants are exact.
         HashGetConst(_) | HashHasKeyConst(_) | HashDeleteConst(_) => 0,
@@ -732,15 +808,27 @@ fn peephole_optimize_chunk(chunk: &mut Chunk) {
             | Op::JumpIfFalseNoPop(offset)
             | Op::JumpIfTrueNoPop(offset)
             | Op::NullishJump(offset)
+            | Op::JumpIfNull(offset)
             | Op::ForIter(offset)
             | Op::ForIterRange(offset)
+            | Op::JumpIfNotNull(offset)
+            | Op::JumpIfParamSupplied(_, offset)
             | Op::TestLessEqualJump(offset)
-            | Op::TestLessJump(offset) => {
+            | Op::TestLessJump(offset)
+            | Op::CatchMatch(_, offset) => {
                 let target = i + 1 + *offset as usize;
                 if target < len {
                     is_jump_target[target] = true;
                 }
             }
+            Op::TryBegin(catch_offset, finally_offset) => {
+                for offset in [catch_offset, finally_offset] {
+                    let target = i + 1 + *offset as usize;
+                    if target < len {
+                        is_jump_target[target] = true;
+                    }
+                }
+            }
             Op::Loop(offset) => {
                 let target = i + 1 - *offset as usize;
                 if target < len {
@@ -1277,9 +1365,12 @@ fn peephole_optimize_chunk(chunk: &mut Chunk) {
     compact_nops(chunk);
 }
 
-/// Check if any offset in range [start+1, start+count) is a jump target.
+/// Check whether a jump lands inside a `count`-op pattern whose second op is
+/// at `start` (callers pass `i + 1`): any offset in `[start, start + count - 1)`
+/// being a target means the ops can't be fused. The first op itself may be a
+/// target — it stays the fused op's address.
 fn any_jump_target(targets: &[bool], start: usize, count: usize) -> bool {
-    for j in (start + 1)..(start + count) {
+    for j in start..(start + count - 1) {
         if j < targets.len() && targets[j] {
             return true;
         }
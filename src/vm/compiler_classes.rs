@@ -2,7 +2,8 @@
 
 use std::sync::Arc;
 
-use crate::ast::stmt::{ClassDecl, ConstructorDecl, FieldDecl, MethodDecl};
+use crate::ast::expr::ExprKind;
+use crate::ast::stmt::{ClassDecl, ConstructorDecl, FieldDecl, MethodDecl, StmtKind, StructDecl};
 
 use super::chunk::Constant;
 use super::compiler::{CompileResult, Compiler, FunctionType};
@@ -11,8 +12,17 @@ use super::opcode::Op;
 impl Compiler {
     /// Compile a class declaration.
     pub fn compile_class_decl(&mut self, decl: &ClassDecl, line: usize) -> CompileResult<()> {
-        // A top-level class declaration defines a global of that name.
-        if self.scope_depth == 0 {
+        self.compile_class(decl, false, line)
+    }
+
+    /// Compile a class declaration. A `nested` class is left on the stack for
+    /// the enclosing class to store as a property instead of being bound to
+    /// a local of its own.
+    fn compile_class(&mut self, decl: &ClassDecl, nested: bool, line: usize) -> CompileResult<()> {
+        // A top-level class declaration defines a global of that name. A
+        // nested class is only reachable through its parent (`Outer::Inner`).
+        let global = self.scope_depth == 0 && !nested;
+        if global {
             self.known_globals.borrow_mut().insert(decl.name.clone());
         }
         let name_idx = self.add_string_constant(&decl.name);
@@ -21,11 +31,24 @@ impl Compiler {
         self.emit(Op::Class(name_idx), line);
 
         // Handle superclass
+        // (resolved like any other name, so a class declared inside a
+        // function can extend another declared there)
         if let Some(ref superclass_name) = decl.superclass {
-            let super_idx = self.add_string_constant(superclass_name);
-            self.emit(Op::GetGlobal(super_idx), line);
+            self.compile_variable_get(superclass_name, line)?;
             self.emit(Op::Inherit, line);
         }
+        for interface in &decl.interfaces {
+            let iface_idx = self.add_string_constant(interface);
+            self.emit(Op::Implement(iface_idx), line);
+        }
+        for field in decl.fields.iter().filter(|f| f.is_const) {
+            let field_idx = self.add_string_constant(&field.name);
+            if field.is_static {
+                self.emit(Op::DeclareStaticConst(field_idx), line);
+            } else {
+                self.emit(Op::DeclareConst(field_idx), line);
+            }
+        }
 
         // Bind the class to its global name *before* compiling static field
         // initializers / static blocks / class statements. The tree-walker
@@ -33,13 +56,24 @@ impl Compiler {
         // unit-variant static consts reference the class by name during init
         // (`static const Active = __enum_construct(Status, "Active", {})`).
         // Method/field attachments below remain visible through the global
-        // because `Class` uses interior mutability. (Top-level only; nested
-        // classes bind to a local at the end, as before.)
-        if self.scope_depth == 0 {
+        // because `Class` uses interior mutability. Inside a function the
+        // class value itself becomes the local, so its own methods can name
+        // it (they capture the slot as an upvalue).
+        if global {
             let gname = self.add_string_constant(&decl.name);
             self.emit(Op::Dup, line);
             self.emit(Op::DefineGlobal(gname), line);
+        } else if !nested {
+            self.add_local(decl.name.clone(), false);
+        }
+        // The working class value stays on the stack while the body compiles;
+        // track it as a local so the static block's `this` and locals, and the
+        // class-level DSL calls, address the right slots.
+        if global || nested {
+            self.begin_scope();
+            self.add_local(String::new(), false);
         }
+        let class_slot = (self.locals.len() - 1) as u16;
 
         // Store class context for this/super resolution
         let prev_class_ctx = self.class_context.take();
@@ -69,47 +103,102 @@ impl Compiler {
         {
             let synthetic = ConstructorDecl {
                 params: Vec::new(),
-                body: Vec::new(),
+                body: Arc::default(),
                 span: decl.span,
             };
             self.compile_constructor(&synthetic, &decl.fields, line)?;
         }
 
-        // Compile static block
+        // Compile static block: its own scope, with `this` bound to the class.
         if let Some(ref static_block) = decl.static_block {
+            self.begin_scope();
+            self.emit(Op::GetLocal(class_slot), line);
+            self.add_local("this".to_string(), false);
             for stmt in static_block {
-                // Static block runs with the class on top of the stack
-                self.emit(Op::Dup, line);
                 self.compile_stmt(stmt)?;
             }
+            self.end_scope(line);
         }
 
-        // Compile class statements (validates, before_save, etc.)
-        for stmt in &decl.class_statements {
-            self.emit(Op::Dup, line);
-            self.compile_stmt(stmt)?;
+        // Class-level DSL statements (`validates`, `has_many`, `attribute`, ...)
+        // only run for Model subclasses, with the class as the implicit first
+        // argument — as the tree-walker's `execute_class` does.
+        if !decl.class_statements.is_empty() {
+            self.emit(Op::IsModelClass, line);
+            let skip = self.emit_jump(Op::JumpIfFalse(0), line);
+            for stmt in &decl.class_statements {
+                if let StmtKind::Expression(expr) = &stmt.kind {
+                    if let ExprKind::Call { callee, arguments } = &expr.kind {
+                        let line = stmt.span.line as usize;
+                        self.compile_class_dsl_call(callee, arguments, class_slot, line)?;
+                        self.emit(Op::Pop, line);
+                    }
+                }
+            }
+            self.patch_jump(skip);
         }
 
         // Compile nested classes
         for nested in &decl.nested_classes {
-            self.emit(Op::Dup, line); // parent class on stack
-            self.compile_class_decl(nested, line)?;
-            // Store nested class as property of parent
+            // The parent stays beneath the nested class; `NestedClass`
+            // pops the nested one and attaches it to the parent.
+            self.compile_class(nested, true, line)?;
             let nested_name_idx = self.add_string_constant(&nested.name);
-            self.emit(Op::SetProperty(nested_name_idx), line);
+            self.emit(Op::NestedClass(nested_name_idx), line);
         }
 
         // Restore class context
         self.class_context = prev_class_ctx;
 
-        // Bind the class name. Top-level classes were bound to their global
-        // early (above); just discard the working copy left on the stack.
+        // Top-level classes were bound to their global early (above); just
+        // discard the working copy left on the stack. Elsewhere the class is
+        // already the local it was bound to, or the nested value its parent
+        // consumes.
+        if global {
+            self.end_scope(line);
+        } else if nested {
+            // Untrack without popping: `NestedClass` consumes the value.
+            self.locals.pop();
+            self.scope_depth -= 1;
+        }
+
+        Ok(())
+    }
+
+    /// Compile a struct declaration: the defaults are evaluated once, in
+    /// field order, and `Op::Struct` builds the type from them.
+    pub fn compile_struct_decl(&mut self, decl: &StructDecl, line: usize) -> CompileResult<()> {
+        use crate::interpreter::value::HashKey;
+
+        if self.scope_depth == 0 {
+            self.known_globals.borrow_mut().insert(decl.name.clone());
+        }
+        let mut defaulted = Vec::new();
+        for field in &decl.fields {
+            if let Some(ref default) = field.default_value {
+                self.compile_expr(default)?;
+                defaulted.push(HashKey::String(field.name.clone().into()));
+            }
+        }
+        let count = defaulted.len() as u16;
+        let keys_idx = self.add_constant(Constant::HashKeys(Arc::new(defaulted)));
+        self.emit(Op::HashWithKeys(keys_idx, count), line);
+
+        let names = decl
+            .fields
+            .iter()
+            .map(|f| HashKey::String(f.name.clone().into()))
+            .collect();
+        let fields_idx = self.add_constant(Constant::HashKeys(Arc::new(names)));
+        let name_idx = self.add_string_constant(&decl.name);
+        self.emit(Op::Struct(name_idx, fields_idx), line);
+
         if self.scope_depth > 0 {
             self.add_local(decl.name.clone(), false);
         } else {
-            self.emit(Op::Pop, line);
+            let gname = self.add_string_constant(&decl.name);
+            self.emit(Op::DefineGlobal(gname), line);
         }
-
         Ok(())
     }
 
@@ -142,10 +231,7 @@ impl Compiler {
 
         self.begin_scope();
         self.emit_param_defaults(&method.params)?;
-        self.hoist_locals(&method.body, line);
-        for stmt in &method.body {
-            self.compile_stmt(stmt)?;
-        }
+        self.compile_function_body(&method.body)?;
         self.end_scope(line);
 
         let proto = self.finish_function(line);
@@ -187,7 +273,7 @@ impl Compiler {
         }
 
         self.hoist_locals(&ctor.body, line);
-        for stmt in &ctor.body {
+        for stmt in ctor.body.iter() {
             self.compile_stmt(stmt)?;
         }
         self.end_scope(line);
@@ -7,10 +7,31 @@ use crate::ast::stmt::StmtKind;
 use crate::ast::{Expr, Stmt};
 use crate::error::CompileError;
 
-use super::chunk::Constant;
+use super::chunk::{ArgSlot, Constant};
 use super::compiler::{CompileResult, Compiler, FunctionType, VariableAccess};
 use super::opcode::Op;
 
+/// The opcodes one kind of call site emits, depending on its arguments:
+/// all positional, some labelled, or some spread.
+#[derive(Clone, Copy)]
+struct CallOps {
+    positional: fn(u8) -> Op,
+    named: fn(u8, u16) -> Op,
+    spread: fn(u8, u16) -> Op,
+}
+
+const CALL_OPS: CallOps = CallOps {
+    positional: Op::Call,
+    named: Op::CallNamed,
+    spread: Op::CallSpread,
+};
+
+const NEW_OPS: CallOps = CallOps {
+    positional: Op::New,
+    named: Op::NewNamed,
+    spread: Op::NewSpread,
+};
+
 impl Compiler {
     /// Compile an expression — the result is left on the stack.
     pub fn compile_expr(&mut self, expr: &Expr) -> CompileResult<()> {
@@ -44,7 +65,7 @@ impl Compiler {
             ExprKind::Null => {
                 self.emit(Op::Null, line);
             }
-            ExprKind::Variable(name) => {
+            ExprKind::Variable(name, _) => {
                 self.compile_variable_get(name, line)?;
             }
             ExprKind::Binary {
@@ -71,16 +92,13 @@ impl Compiler {
                 let idx = self.add_string_constant(name);
                 self.emit(Op::GetProperty(idx), line);
             }
-            ExprKind::SafeMember { .. } => {
-                // Not implemented in the VM — return a compile error so the
-                // handler falls back to the tree-walking interpreter. This
-                // used to be `unimplemented!()`, which panicked (and with
-                // panic="abort" in release, core-dumped the whole server)
-                // the moment any handler used `&.` safe navigation.
-                return Err(CompileError::new(
-                    "Safe navigation (&.) is not supported in compiled mode",
-                    expr.span,
-                ));
+            ExprKind::SafeMember { object, name } => {
+                // A null receiver skips the lookup and is itself the result.
+                self.compile_expr(object)?;
+                let skip = self.emit_jump(Op::JumpIfNull(0), line);
+                let idx = self.add_string_constant(name);
+                self.emit(Op::GetProperty(idx), line);
+                self.patch_jump(skip);
             }
             ExprKind::QualifiedName { qualifier, name } => {
                 self.compile_expr(qualifier)?;
@@ -115,6 +133,12 @@ impl Compiler {
             ExprKind::Array(elements) => {
                 self.compile_array(elements, line)?;
             }
+            ExprKind::Tuple(elements) => {
+                for elem in elements {
+                    self.compile_expr(elem)?;
+                }
+                self.emit(Op::Tuple(elements.len() as u16), line);
+            }
             ExprKind::Hash(pairs) => {
                 self.compile_hash(pairs, line)?;
             }
@@ -147,10 +171,11 @@ impl Compiler {
             }
             ExprKind::Lambda {
                 params,
-                return_type: _,
                 body,
+                is_move,
+                ..
             } => {
-                self.compile_lambda(params, body, line)?;
+                self.compile_lambda(params, body, *is_move, line)?;
             }
             ExprKind::If {
                 condition,
@@ -162,6 +187,14 @@ impl Compiler {
             ExprKind::Match { expression, arms } => {
                 self.compile_match(expression, arms, line)?;
             }
+            // A comprehension addresses its result by slot, which only works
+            // with no anonymous temporaries beneath it; see
+            // `compile_comprehension_in_closure`.
+            ExprKind::ListComprehension { .. } | ExprKind::HashComprehension(_)
+                if self.stack_height != self.locals.len() =>
+            {
+                self.compile_comprehension_in_closure(expr, line)?;
+            }
             ExprKind::ListComprehension {
                 element,
                 variable,
@@ -176,19 +209,13 @@ impl Compiler {
                     line,
                 )?;
             }
-            ExprKind::HashComprehension {
-                key,
-                value,
-                variable,
-                iterable,
-                condition,
-            } => {
+            ExprKind::HashComprehension(comp) => {
                 self.compile_hash_comprehension(
-                    key,
-                    value,
-                    variable,
-                    iterable,
-                    condition.as_deref(),
+                    &comp.key,
+                    &comp.value,
+                    &comp.variable,
+                    &comp.iterable,
+                    comp.condition.as_ref(),
                     line,
                 )?;
             }
@@ -201,14 +228,25 @@ impl Compiler {
             } => {
                 self.compile_sdql_block(query, interpolations, line)?;
             }
-            ExprKind::Spread(inner) => {
-                self.compile_expr(inner)?;
-                self.emit(Op::Spread, line);
+            ExprKind::Spread(_) => {
+                return Err(CompileError::new(
+                    "spread (...) is only valid inside an array or hash literal",
+                    expr.span,
+                ));
             }
             ExprKind::Throw(inner) => {
                 self.compile_expr(inner)?;
                 self.emit(Op::Throw, line);
             }
+            ExprKind::Await(inner) => {
+                // Lower to the `await(value)` builtin. The VM has no async
+                // functions (they run in the interpreter), so the only
+                // futures it sees are I/O ones, which `await` resolves.
+                let fn_idx = self.add_string_constant("await");
+                self.emit(Op::GetGlobal(fn_idx), line);
+                self.compile_expr(inner)?;
+                self.emit(Op::Call(1), line);
+            }
             ExprKind::Rescue { expr, fallback } => {
                 let try_begin = self.emit(Op::TryBegin(0, 0), line);
                 self.compile_expr(expr)?;
@@ -316,7 +354,7 @@ impl Compiler {
         Ok(())
     }
 
-    fn compile_variable_get(&mut self, name: &str, line: usize) -> CompileResult<()> {
+    pub(crate) fn compile_variable_get(&mut self, name: &str, line: usize) -> CompileResult<()> {
         match self.resolve_variable(name) {
             VariableAccess::Local(slot) => {
                 self.emit(Op::GetLocal(slot), line);
@@ -353,7 +391,8 @@ impl Compiler {
             BinaryOp::LessEqual => self.emit(Op::LessEqual, line),
             BinaryOp::Greater => self.emit(Op::Greater, line),
             BinaryOp::GreaterEqual => self.emit(Op::GreaterEqual, line),
-            BinaryOp::Range => self.emit(Op::Range, line),
+            BinaryOp::Range => self.emit(Op::Range(false), line),
+            BinaryOp::RangeInclusive => self.emit(Op::Range(true), line),
             BinaryOp::Shovel => self.emit(Op::ArrayPush, line),
         };
         Ok(())
@@ -375,15 +414,29 @@ impl Compiler {
         line: usize,
     ) -> CompileResult<()> {
         // Special case: print() calls
-        if let ExprKind::Variable(name) = &callee.kind {
+        if let ExprKind::Variable(name, _) = &callee.kind {
             if name == "print" || name == "puts" || name == "println" {
                 return self.compile_print(arguments, line);
             }
         }
 
+        // `const_get(name)` / `defined(name)` resolve a name through the
+        // caller's scope, which the tree-walker reads off its environment. A
+        // literal naming a local or upvalue compiles to that variable itself;
+        // any other name is looked up among the globals at run time.
+        if let ExprKind::Variable(name, _) = &callee.kind {
+            if name == "const_get" || name == "defined" {
+                if let [Argument::Positional(arg)] = arguments {
+                    if matches!(self.resolve_variable(name), VariableAccess::Global(_)) {
+                        return self.compile_name_lookup(name == "defined", arg, line);
+                    }
+                }
+            }
+        }
+
         // Special case: JSON.parse() and JSON.stringify()
         if let ExprKind::Member { object, name } = &callee.kind {
-            if let ExprKind::Variable(obj_name) = &object.kind {
+            if let ExprKind::Variable(obj_name, _) = &object.kind {
                 if obj_name == "JSON" {
                     if name == "parse" {
                         return self.compile_json_parse(arguments, line);
@@ -423,7 +476,7 @@ impl Compiler {
         // so the enclosing handler falls back to the tree-walking interpreter,
         // where the `grouped` interceptor (and `Value::Deferred` resolution)
         // runs it correctly — production requests still get the coalescing.
-        if let ExprKind::Variable(name) = &callee.kind {
+        if let ExprKind::Variable(name, _) = &callee.kind {
             if name == "grouped" && arguments.len() == 1 {
                 let is_block = match &arguments[0] {
                     Argument::Block(_) => true,
@@ -439,6 +492,37 @@ impl Compiler {
             }
         }
 
+        // Block forms of the clock controls (`freeze_time(t) { ... }`,
+        // `travel(secs) { ... }`) restore the previous clock after the block,
+        // which needs the interpreter's interceptor. Refuse them so the
+        // enclosing code falls back to the tree-walker; the bare forms are
+        // plain native calls and compile normally.
+        if let ExprKind::Variable(name, _) = &callee.kind {
+            if matches!(name.as_str(), "freeze_time" | "travel_to" | "travel")
+                && arguments.len() == 2
+            {
+                let is_block = match &arguments[1] {
+                    Argument::Block(_) => true,
+                    Argument::Positional(e) => matches!(e.kind, ExprKind::Lambda { .. }),
+                    _ => false,
+                };
+                if is_block {
+                    return Err(CompileError::new(
+                        "freeze_time/travel block form runs in the interpreter, not the VM",
+                        callee.span,
+                    ));
+                }
+            }
+            // Same for `with_cassette(name) { ... }` and
+            // `connected_to(name) { ... }`, which have no bare form.
+            if name == "with_cassette" || name == "connected_to" {
+                return Err(CompileError::new(
+                    format!("{} runs in the interpreter, not the VM", name),
+                    callee.span,
+                ));
+            }
+        }
+
         // `super(...)` / `super.method(...)` — dispatch against the
         // *defining* class's superclass at runtime (the CallSuper* handlers
         // read the class recorded on the call frame). `this` goes in the
@@ -487,12 +571,65 @@ impl Compiler {
             }
         }
 
+        // `obj&.method(args)`: a null receiver short-circuits the whole call,
+        // arguments included, leaving the null as the result.
+        if let ExprKind::SafeMember { object, name } = &callee.kind {
+            self.compile_expr(object)?;
+            let skip = self.emit_jump(Op::JumpIfNull(0), line);
+            let all_positional = arguments
+                .iter()
+                .all(|a| matches!(a, Argument::Positional(_) | Argument::Block(_)));
+            if all_positional && arguments.len() <= 255 {
+                for arg in arguments {
+                    if let Argument::Positional(expr) | Argument::Block(expr) = arg {
+                        self.compile_expr(expr)?;
+                    }
+                }
+                let name_idx = self.add_string_constant(name);
+                self.emit(Op::CallMethod(name_idx, arguments.len() as u8), line);
+            } else {
+                let idx = self.add_string_constant(name);
+                self.emit(Op::GetProperty(idx), line);
+                self.compile_call_arguments(arguments, line, CALL_OPS)?;
+            }
+            self.patch_jump(skip);
+            return Ok(());
+        }
+
         // Optimized path: method calls (obj.method(args)) use CallMethod opcode
         // to avoid allocating Value::Method intermediary.
         if let ExprKind::Member { object, name } = &callee.kind {
             let all_positional = arguments
                 .iter()
-                .all(|a| matches!(a, Argument::Positional(_)));
+                .all(|a| matches!(a, Argument::Positional(_) | Argument::Block(_)));
+            let has_spread = arguments.iter().any(|a| matches!(a, Argument::Spread(_)));
+            let spread_only = arguments
+                .iter()
+                .all(|a| matches!(a, Argument::Positional(_) | Argument::Spread(_)));
+            if has_spread && spread_only && arguments.len() <= 255 {
+                self.compile_expr(object)?;
+                let mut layout = Vec::with_capacity(arguments.len());
+                for arg in arguments {
+                    match arg {
+                        Argument::Spread(expr) => {
+                            self.compile_expr(expr)?;
+                            layout.push(ArgSlot::Spread);
+                        }
+                        Argument::Positional(expr) => {
+                            self.compile_expr(expr)?;
+                            layout.push(ArgSlot::Positional);
+                        }
+                        _ => unreachable!("checked above"),
+                    }
+                }
+                let name_idx = self.add_string_constant(name);
+                let layout_idx = self.add_constant(Constant::SpreadArgs(Arc::new(layout)));
+                self.emit(
+                    Op::CallMethodSpread(name_idx, arguments.len() as u8, layout_idx),
+                    line,
+                );
+                return Ok(());
+            }
             if all_positional && arguments.len() <= 255 {
                 if let Some(op) =
                     self.try_compile_hash_const_string_call(object, name, arguments, line)?
@@ -512,7 +649,7 @@ impl Compiler {
                 self.compile_expr(object)?;
                 let mut argc = 0u8;
                 for arg in arguments {
-                    if let Argument::Positional(expr) = arg {
+                    if let Argument::Positional(expr) | Argument::Block(expr) = arg {
                         self.compile_expr(expr)?;
                         argc += 1;
                     }
@@ -529,7 +666,21 @@ impl Compiler {
         }
 
         self.compile_expr(callee)?;
-        self.compile_call_arguments(arguments, line, Op::Call, Op::CallNamed)
+        self.compile_call_arguments(arguments, line, CALL_OPS)
+    }
+
+    /// Compile a class-body DSL call (`validates :name, presence: true`) with
+    /// the class in local `class_slot` as its implicit first argument.
+    pub(super) fn compile_class_dsl_call(
+        &mut self,
+        callee: &Expr,
+        arguments: &[Argument],
+        class_slot: u16,
+        line: usize,
+    ) -> CompileResult<()> {
+        self.compile_expr(callee)?;
+        self.emit(Op::GetLocal(class_slot), line);
+        self.compile_call_arguments_after(arguments, line, 1, CALL_OPS)
     }
 
     /// Compile a call's arguments (the callee is already on the stack) and emit
@@ -537,18 +688,19 @@ impl Compiler {
     ///
     /// Every argument is evaluated in source order regardless of labelling, so
     /// side effects happen in the order written. When no argument is labelled
-    /// this emits the plain `positional` opcode; otherwise it records the
-    /// per-slot labels as a constant and emits `named`, leaving the actual
-    /// binding to run time where the callee's identity — and therefore which of
-    /// the two calling conventions applies — is known.
+    /// this emits the plain positional opcode; otherwise it records the
+    /// per-slot labels as a constant and emits the named one, leaving the
+    /// actual binding to run time where the callee's identity — and therefore
+    /// which of the two calling conventions applies — is known. A `*array`
+    /// argument makes the slot count itself a run-time fact, so any call with
+    /// one records the full layout and emits the spread opcode instead.
     fn compile_call_arguments(
         &mut self,
         arguments: &[Argument],
         line: usize,
-        positional: fn(u8) -> Op,
-        named: fn(u8, u16) -> Op,
+        ops: CallOps,
     ) -> CompileResult<()> {
-        self.compile_call_arguments_after(arguments, line, 0, positional, named)
+        self.compile_call_arguments_after(arguments, line, 0, ops)
     }
 
     /// As [`Self::compile_call_arguments`], but with `leading` argument values
@@ -560,8 +712,7 @@ impl Compiler {
         arguments: &[Argument],
         line: usize,
         leading: u8,
-        positional: fn(u8) -> Op,
-        named: fn(u8, u16) -> Op,
+        ops: CallOps,
     ) -> CompileResult<()> {
         if arguments.len() + leading as usize > u8::MAX as usize {
             return Err(CompileError::new(
@@ -574,6 +725,7 @@ impl Compiler {
         let mut labels: Vec<Option<crate::interpreter::value::SoliStr>> =
             vec![None; leading as usize];
         labels.reserve(arguments.len());
+        let mut spreads: Vec<usize> = Vec::new();
         for arg in arguments {
             match arg {
                 Argument::Positional(expr) => {
@@ -590,19 +742,56 @@ impl Compiler {
                     self.compile_expr(expr)?;
                     labels.push(None);
                 }
+                Argument::Spread(expr) => {
+                    self.compile_expr(expr)?;
+                    labels.push(None);
+                    spreads.push(labels.len() - 1);
+                }
             }
             argc += 1;
         }
 
-        if labels.iter().any(Option::is_some) {
+        if !spreads.is_empty() {
+            let layout = labels
+                .into_iter()
+                .enumerate()
+                .map(|(i, label)| match label {
+                    Some(name) => ArgSlot::Named(name),
+                    None if spreads.contains(&i) => ArgSlot::Spread,
+                    None => ArgSlot::Positional,
+                })
+                .collect();
+            let idx = self.add_constant(Constant::SpreadArgs(std::sync::Arc::new(layout)));
+            self.emit((ops.spread)(argc, idx), line);
+        } else if labels.iter().any(Option::is_some) {
             let idx = self.add_constant(Constant::ArgNames(std::sync::Arc::new(labels)));
-            self.emit(named(argc, idx), line);
+            self.emit((ops.named)(argc, idx), line);
         } else {
-            self.emit(positional(argc), line);
+            self.emit((ops.positional)(argc), line);
         }
         Ok(())
     }
 
+    fn compile_name_lookup(
+        &mut self,
+        defined: bool,
+        name: &Expr,
+        line: usize,
+    ) -> CompileResult<()> {
+        if let ExprKind::StringLiteral(literal) = &name.kind {
+            if !matches!(self.resolve_variable(literal), VariableAccess::Global(_)) {
+                if defined {
+                    self.emit(Op::True, line);
+                    return Ok(());
+                }
+                return self.compile_variable_get(literal, line);
+            }
+        }
+        self.compile_expr(name)?;
+        self.emit(Op::GlobalByName(defined), line);
+        Ok(())
+    }
+
     fn compile_print(&mut self, arguments: &[Argument], line: usize) -> CompileResult<()> {
         let mut argc = 0u8;
         for arg in arguments {
@@ -619,6 +808,10 @@ impl Compiler {
                     // Block args to print don't make sense, but compile them anyway
                     return self.compile_print_fallback(arguments, line);
                 }
+                Argument::Spread(_) => {
+                    // `Print` takes a fixed count; let the call expand it.
+                    return self.compile_print_fallback(arguments, line);
+                }
             }
         }
         self.emit(Op::Print(argc), line);
@@ -629,7 +822,7 @@ impl Compiler {
         // Fall back to calling print as a regular function
         let idx = self.add_string_constant("print");
         self.emit(Op::GetGlobal(idx), line);
-        self.compile_call_arguments(arguments, line, Op::Call, Op::CallNamed)
+        self.compile_call_arguments(arguments, line, CALL_OPS)
     }
 
     fn compile_json_parse(&mut self, arguments: &[Argument], line: usize) -> CompileResult<()> {
@@ -679,7 +872,7 @@ impl Compiler {
                 // Compile callee first, then left as first arg, then rest of args
                 self.compile_expr(callee)?;
                 self.compile_expr(left)?;
-                self.compile_call_arguments_after(arguments, line, 1, Op::Call, Op::CallNamed)?;
+                self.compile_call_arguments_after(arguments, line, 1, CALL_OPS)?;
             }
             _ => {
                 // If right is just a function reference, call it with left as sole argument
@@ -728,14 +921,32 @@ impl Compiler {
         line: usize,
     ) -> CompileResult<()> {
         self.compile_expr(class_expr)?;
-        self.compile_call_arguments(arguments, line, Op::New, Op::NewNamed)
+        self.compile_call_arguments(arguments, line, NEW_OPS)
     }
 
     fn compile_array(&mut self, elements: &[Expr], line: usize) -> CompileResult<()> {
-        for elem in elements {
+        // Elements before the first spread build the array in one go; the
+        // rest are appended one by one (or spliced in, for `...expr`).
+        let leading = elements
+            .iter()
+            .position(|e| matches!(e.kind, ExprKind::Spread(_)))
+            .unwrap_or(elements.len());
+        for elem in &elements[..leading] {
             self.compile_expr(elem)?;
         }
-        self.emit(Op::Array(elements.len() as u16), line);
+        self.emit(Op::Array(leading as u16), line);
+        for elem in &elements[leading..] {
+            match &elem.kind {
+                ExprKind::Spread(inner) => {
+                    self.compile_expr(inner)?;
+                    self.emit(Op::Spread, line);
+                }
+                _ => {
+                    self.compile_expr(elem)?;
+                    self.emit(Op::ArrayPush, line);
+                }
+            }
+        }
         Ok(())
     }
 
@@ -812,8 +1023,13 @@ impl Compiler {
 
     fn compile_assign(&mut self, target: &Expr, value: &Expr, line: usize) -> CompileResult<()> {
         match &target.kind {
-            ExprKind::Variable(name) => {
+            ExprKind::Variable(name, _) => {
                 self.compile_expr(value)?;
+                if self.is_const_local(name) {
+                    let idx = self.add_string_constant(name);
+                    self.emit(Op::AssignConst(idx), line);
+                    return Ok(());
+                }
                 let name_clone = name.clone();
                 match self.resolve_variable(&name_clone) {
                     VariableAccess::Local(slot) => {
@@ -868,6 +1084,7 @@ impl Compiler {
         &mut self,
         params: &[crate::ast::stmt::Parameter],
         body: &[Stmt],
+        is_move: bool,
         line: usize,
     ) -> CompileResult<()> {
         // Compile lambda as a nested function
@@ -878,7 +1095,8 @@ impl Compiler {
         self.compile_function_body(body)?;
         self.end_scope(line);
 
-        let proto = self.finish_function(line);
+        let mut proto = self.finish_function(line);
+        proto.captures_by_value = is_move;
         let upvalue_count = proto.upvalue_descriptors.len();
         let idx = self.add_constant(Constant::Function(Arc::new(proto)));
         self.emit(Op::Closure(idx), line);
@@ -985,7 +1203,7 @@ impl Compiler {
         arguments: &[Argument],
         line: usize,
     ) -> CompileResult<Option<()>> {
-        if let ExprKind::Variable(var_name) = &object.kind {
+        if let ExprKind::Variable(var_name, _) = &object.kind {
             if let Some(slot) = self.resolve_local(var_name) {
                 match (name, arguments) {
                     ("get", [Argument::Positional(arg)])
@@ -1074,6 +1292,26 @@ impl Compiler {
         }
     }
 
+    /// A comprehension that is a sub-expression (a call argument, an element
+    /// of an enclosing literal, ...) has temporaries beneath it, so its result
+    /// can't be addressed by slot. Compile it as the body of a zero-argument
+    /// closure called in place: inside that closure it sits at a clean
+    /// position, and the enclosing locals reach it as upvalues.
+    fn compile_comprehension_in_closure(
+        &mut self,
+        comprehension: &Expr,
+        line: usize,
+    ) -> CompileResult<()> {
+        let body = [Stmt::new(
+            StmtKind::Return(Some(comprehension.clone())),
+            comprehension.span,
+            None,
+        )];
+        self.compile_lambda(&[], &body, false, line)?;
+        self.emit(Op::Call(0), line);
+        Ok(())
+    }
+
     fn compile_list_comprehension(
         &mut self,
         element: &Expr,
@@ -1082,45 +1320,16 @@ impl Compiler {
         condition: Option<&Expr>,
         line: usize,
     ) -> CompileResult<()> {
-        // Clean-position gate: the result array is addressed by the slot
-        // `locals.len()`, which only equals its true stack position when no
-        // anonymous temporaries sit below it. If the comprehension is a
-        // sub-expression (a call argument, an element of an enclosing array
-        // literal, ...) there are temporaries on the stack — fall back to the
-        // interpreter (which handles comprehensions correctly) rather than risk
-        // a wrong slot. See `Compiler::stack_height`.
-        if self.stack_height != self.locals.len() {
-            return Err(CompileError::new(
-                "list comprehension not supported by the VM as a sub-expression",
-                element.span,
-            ));
-        }
-
+        // The result array is addressed by the slot `locals.len()`, which
+        // only equals its true stack position when no anonymous temporaries
+        // sit below it — `compile_expr` routes the other case through
+        // `compile_comprehension_in_closure`. See `Compiler::stack_height`.
         let result_slot = self.locals.len() as u16;
         self.emit(Op::Array(0), line); // empty result array, at result_slot
         self.add_local(String::new(), false); // anonymous result-array local
 
-        // Iterator setup (mirrors compile_for's range optimization).
-        let is_range = matches!(
-            &iterable.kind,
-            crate::ast::ExprKind::Binary {
-                operator: crate::ast::BinaryOp::Range,
-                ..
-            }
-        );
-        if let crate::ast::ExprKind::Binary {
-            left,
-            operator: crate::ast::BinaryOp::Range,
-            right,
-        } = &iterable.kind
-        {
-            self.compile_expr(left)?;
-            self.compile_expr(right)?;
-            self.emit(Op::GetIterRange, line);
-        } else {
-            self.compile_expr(iterable)?;
-            self.emit(Op::GetIter, line);
-        }
+        // Iterator setup (shares compile_for's range optimization).
+        let is_range = self.compile_iterator(iterable, false, line)?;
         // The iterable/range now lives on the iter_stack; value stack is back at
         // the result-array baseline.
         self.resync_stack_height();
@@ -1173,38 +1382,12 @@ impl Compiler {
         condition: Option<&Expr>,
         line: usize,
     ) -> CompileResult<()> {
-        // Clean-position gate — same reasoning as compile_list_comprehension.
-        if self.stack_height != self.locals.len() {
-            return Err(CompileError::new(
-                "hash comprehension not supported by the VM as a sub-expression",
-                key.span,
-            ));
-        }
-
+        // Clean position only — same reasoning as compile_list_comprehension.
         let result_slot = self.locals.len() as u16;
         self.emit(Op::Hash(0), line); // empty result hash, at result_slot
         self.add_local(String::new(), false); // anonymous result-hash local
 
-        let is_range = matches!(
-            &iterable.kind,
-            crate::ast::ExprKind::Binary {
-                operator: crate::ast::BinaryOp::Range,
-                ..
-            }
-        );
-        if let crate::ast::ExprKind::Binary {
-            left,
-            operator: crate::ast::BinaryOp::Range,
-            right,
-        } = &iterable.kind
-        {
-            self.compile_expr(left)?;
-            self.compile_expr(right)?;
-            self.emit(Op::GetIterRange, line);
-        } else {
-            self.compile_expr(iterable)?;
-            self.emit(Op::GetIter, line);
-        }
+        let is_range = self.compile_iterator(iterable, false, line)?;
         self.resync_stack_height();
 
         let loop_start = self.current_offset();
@@ -1312,6 +1495,29 @@ mod grouped_compile_tests {
     }
 }
 
+#[cfg(test)]
+mod generator_compile_tests {
+    //! A function containing `yield` must NOT compile in the bytecode VM: a
+    //! generator suspends mid-body between pulls, which VM call frames can't
+    //! do. Rejecting it makes the production request path's VM→interpreter
+    //! fallback run the handler on the tree-walker, which steps generators.
+    use crate::vm::compiler::Compiler;
+
+    fn compiles(src: &str) -> bool {
+        let tokens = crate::lexer::Scanner::new(src).scan_tokens().expect("lex");
+        let program = crate::parser::Parser::new(tokens).parse().expect("parse");
+        Compiler::compile(&program).is_ok()
+    }
+
+    #[test]
+    fn generator_function_is_rejected_for_interpreter_fallback() {
+        assert!(
+            !compiles("def count() { yield 1 }"),
+            "a generator must fail VM compilation so it falls back to the interpreter"
+        );
+    }
+}
+
 #[cfg(test)]
 mod named_args_compile_tests {
     //! Named-argument calls compile to `Op::CallNamed` / `Op::NewNamed`, which
@@ -1369,6 +1575,82 @@ mod named_args_compile_tests {
         );
     }
 
+    #[test]
+    fn keyword_only_params_skip_positional_slots() {
+        let src = "def connect(host, *, port = 5432, tls) { return host + port + tls }\n\
+                   let a = connect(1, tls: 10);\n\
+                   let b = connect(tls: 1, host: 2, port: 3);";
+        assert_eq!(run_and_get(src, "a"), Value::Int(5443));
+        assert_eq!(run_and_get(src, "b"), Value::Int(6));
+    }
+
+    #[test]
+    fn defaults_are_evaluated_in_the_callee_scope() {
+        assert_eq!(
+            run_and_get(
+                "def f(a, b = a * 2) { return a + b }\nlet x = f(3) + f(3, b: 1);",
+                "x"
+            ),
+            Value::Int(13)
+        );
+    }
+
+    #[test]
+    fn variadic_params_collect_the_extra_positionals() {
+        let src =
+            "def count(first, ...rest, scale = 1) { return (first + rest.length()) * scale }\n\
+                   let a = count(10);\n\
+                   let b = count(10, 1, 2, 3, scale: 2);\n\
+                   let c = fn(...xs) { return xs }(1, 2);";
+        assert_eq!(run_and_get(src, "a"), Value::Int(10));
+        assert_eq!(run_and_get(src, "b"), Value::Int(26));
+        assert_eq!(run_and_get(src, "c").to_string(), "[1, 2]");
+    }
+
+    #[test]
+    fn spread_args_expand_at_call_time() {
+        let src = "def add(a, b, c = 0) { return a + b + c }\n\
+                   let xs = [1, 2];\n\
+                   let a = add(*xs);\n\
+                   let b = add(*xs, c: 10);\n\
+                   let c = add(*[], 3, *[4]);\n\
+                   let d = [0];\n\
+                   d.push(*[9]);\n\
+                   let e = d.length();";
+        assert_eq!(run_and_get(src, "a"), Value::Int(3));
+        assert_eq!(run_and_get(src, "b"), Value::Int(13));
+        assert_eq!(run_and_get(src, "c"), Value::Int(7));
+        assert_eq!(run_and_get(src, "e"), Value::Int(2));
+    }
+
+    #[test]
+    fn operators_dispatch_to_class_overloads() {
+        let src = "class V {\n\
+                     x: Int\n\
+                     new(x) { this.x = x }\n\
+                     fn op_add(o) { return new V(this.x + o.x) }\n\
+                     fn op_eq(o) { return this.x == o.x }\n\
+                     fn op_lt(o) { return this.x < o.x }\n\
+                     fn op_neg() { return new V(-this.x) }\n\
+                     fn op_index(i) { return this.x * i }\n\
+                   }\n\
+                   def pick(p, q) { return p < q }\n\
+                   let a = new V(1);\n\
+                   let b = new V(2);\n\
+                   let sum = (a + b).x;\n\
+                   let same = a == new V(1);\n\
+                   let differ = a != b;\n\
+                   let less = pick(a, b);\n\
+                   let neg = (-b).x;\n\
+                   let idx = b[10];";
+        assert_eq!(run_and_get(src, "sum"), Value::Int(3));
+        assert_eq!(run_and_get(src, "same"), Value::Bool(true));
+        assert_eq!(run_and_get(src, "differ"), Value::Bool(true));
+        assert_eq!(run_and_get(src, "less"), Value::Bool(true));
+        assert_eq!(run_and_get(src, "neg"), Value::Int(-2));
+        assert_eq!(run_and_get(src, "idx"), Value::Int(20));
+    }
+
     #[test]
     fn named_arg_to_print_is_accepted() {
         // print's fallback path routes through the generic call machinery,
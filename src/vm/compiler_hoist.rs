@@ -23,7 +23,7 @@
 
 use std::collections::HashSet;
 
-use crate::ast::expr::{Argument, Expr, ExprKind, InterpolatedPart};
+use crate::ast::expr::{Argument, Expr, ExprKind, HashComprehension, InterpolatedPart};
 use crate::ast::stmt::{Stmt, StmtKind};
 
 /// Collect names that should be declared as function-scoped locals because a
@@ -75,6 +75,12 @@ impl Scan {
                 self.declared.insert(name.clone());
                 self.expr(initializer);
             }
+            StmtKind::LetTuple {
+                names, initializer, ..
+            } => {
+                self.declared.extend(names.iter().cloned());
+                self.expr(initializer);
+            }
             StmtKind::Block(stmts) => {
                 // Same function scope — block boundaries don't matter here.
                 for s in stmts {
@@ -99,6 +105,7 @@ impl Scan {
             StmtKind::For {
                 variable,
                 index_variable,
+                destructure,
                 iterable,
                 body,
             } => {
@@ -106,12 +113,15 @@ impl Scan {
                 if let Some(iv) = index_variable {
                     self.declared.insert(iv.clone());
                 }
+                if let Some(names) = destructure {
+                    self.declared.extend(names.iter().cloned());
+                }
                 self.expr(iterable);
                 self.stmt(body);
             }
             StmtKind::Return(Some(e)) => self.expr(e),
             StmtKind::Return(None) => {}
-            StmtKind::Throw(e) => self.expr(e),
+            StmtKind::Throw(e) | StmtKind::Yield(e) => self.expr(e),
             StmtKind::Try {
                 try_block,
                 catch_clauses,
@@ -133,7 +143,9 @@ impl Scan {
             StmtKind::Function(_)
             | StmtKind::Class(_)
             | StmtKind::Enum(_)
+            | StmtKind::Struct(_)
             | StmtKind::Interface(_)
+            | StmtKind::TypeAlias(_)
             | StmtKind::Import(_) => {}
         }
     }
@@ -141,7 +153,7 @@ impl Scan {
     fn expr(&mut self, expr: &Expr) {
         match &expr.kind {
             ExprKind::Assign { target, value } => {
-                if let ExprKind::Variable(name) = &target.kind {
+                if let ExprKind::Variable(name, _) = &target.kind {
                     self.record_assigned(name);
                 } else {
                     // obj.field = / arr[i] = : the target has sub-expressions
@@ -166,7 +178,10 @@ impl Scan {
                 self.expr(right);
             }
             ExprKind::Unary { operand, .. } => self.expr(operand),
-            ExprKind::Grouping(e) | ExprKind::Spread(e) | ExprKind::Throw(e) => self.expr(e),
+            ExprKind::Grouping(e)
+            | ExprKind::Spread(e)
+            | ExprKind::Throw(e)
+            | ExprKind::Await(e) => self.expr(e),
             ExprKind::Call { callee, arguments } => {
                 self.expr(callee);
                 self.arguments(arguments);
@@ -186,7 +201,7 @@ impl Scan {
                 self.expr(object);
                 self.expr(index);
             }
-            ExprKind::Array(elems) => {
+            ExprKind::Array(elems) | ExprKind::Tuple(elems) => {
                 for e in elems {
                     self.expr(e);
                 }
@@ -235,13 +250,14 @@ impl Scan {
                     self.expr(c);
                 }
             }
-            ExprKind::HashComprehension {
-                key,
-                value,
-                variable,
-                iterable,
-                condition,
-            } => {
+            ExprKind::HashComprehension(comp) => {
+                let HashComprehension {
+                    key,
+                    value,
+                    variable,
+                    iterable,
+                    condition,
+                } = &**comp;
                 self.declared.insert(variable.clone());
                 self.expr(key);
                 self.expr(value);
@@ -273,7 +289,7 @@ impl Scan {
             | ExprKind::BoolLiteral(_)
             | ExprKind::Symbol(_)
             | ExprKind::Null
-            | ExprKind::Variable(_)
+            | ExprKind::Variable(_, _)
             | ExprKind::This
             | ExprKind::Super => {}
         }
@@ -282,7 +298,7 @@ impl Scan {
     fn arguments(&mut self, arguments: &[Argument]) {
         for arg in arguments {
             match arg {
-                Argument::Positional(e) | Argument::Block(e) => self.expr(e),
+                Argument::Positional(e) | Argument::Block(e) | Argument::Spread(e) => self.expr(e),
                 Argument::Named(named) => self.expr(&named.value),
             }
         }
@@ -8,34 +8,72 @@ use super::chunk::Constant;
 use super::compiler::{CompileResult, Compiler};
 use super::opcode::Op;
 
-/// Whether a pattern must run on the tree-walking interpreter rather than
-/// compile to bytecode.
-///
-/// Two reasons land a pattern here. **Binding** patterns (`x`, `[a, b]`,
-/// `{k: v}`, enum variants) alias the subject's stack slot, which the VM's
-/// compilation does not yet model. **Composite** patterns (array, hash, and/or)
-/// interleave their own `Dup`/`Pop` with each sub-pattern's, and the two only
-/// balance when every sub-pattern leaves the duplicated subject in place — which
-/// a literal sub-pattern does not (its `Equal` consumes it). That mismatch
-/// silently popped one value too many, corrupting the value stack for whatever
-/// followed the match; see [`Compiler::compile_pattern`] for the contract that
-/// now makes dup-consumption explicit for the kinds the VM does compile.
-///
-/// Only wildcard and literal patterns compile — both have a proven stack effect.
-fn pattern_needs_interpreter(pattern: &MatchPattern) -> bool {
+/// Whether a pattern is a bare wildcard or literal, which test the subject in
+/// place without binding anything.
+fn is_flat_pattern(pattern: &MatchPattern) -> bool {
+    matches!(pattern, MatchPattern::Wildcard | MatchPattern::Literal(_))
+}
+
+/// The names a pattern binds, in first-appearance order, without duplicates.
+fn collect_bindings(pattern: &MatchPattern, names: &mut Vec<String>) {
+    fn push(names: &mut Vec<String>, name: &str) {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
     match pattern {
-        MatchPattern::Wildcard | MatchPattern::Literal(_) => false,
-        MatchPattern::Variable(_) | MatchPattern::Typed { .. } => true,
-        MatchPattern::EnumVariant { .. } => true,
-        // Composite patterns: unbalanced Dup/Pop against literal sub-patterns.
-        MatchPattern::Array { .. }
-        | MatchPattern::Hash { .. }
-        | MatchPattern::Destructuring { .. }
-        | MatchPattern::And(_)
-        | MatchPattern::Or(_) => true,
+        MatchPattern::Wildcard | MatchPattern::Literal(_) => {}
+        MatchPattern::Variable(name) | MatchPattern::Typed { name, .. } => push(names, name),
+        MatchPattern::Array { elements, rest } => {
+            for element in elements {
+                collect_bindings(element, names);
+            }
+            if let Some(rest) = rest {
+                push(names, rest);
+            }
+        }
+        MatchPattern::Tuple(elements) => {
+            for element in elements {
+                collect_bindings(element, names);
+            }
+        }
+        MatchPattern::Hash { fields, rest } => {
+            for (_, field) in fields {
+                collect_bindings(field, names);
+            }
+            if let Some(rest) = rest {
+                push(names, rest);
+            }
+        }
+        MatchPattern::Destructuring { fields, .. } => {
+            for (_, field) in fields {
+                collect_bindings(field, names);
+            }
+        }
+        MatchPattern::EnumVariant { bindings, .. } => {
+            for binding in bindings {
+                collect_bindings(binding, names);
+            }
+        }
+        MatchPattern::And(patterns) | MatchPattern::Or(patterns) => {
+            for p in patterns {
+                collect_bindings(p, names);
+            }
+        }
     }
 }
 
+/// One step from the match subject down to the value a sub-pattern tests.
+#[derive(Clone)]
+enum PathStep {
+    Index(i64),
+    Key(String),
+    /// An instance field, read raw (no getters), as destructuring does.
+    Field(String),
+    /// The Nth payload field of an enum variant.
+    Variant(String, u8),
+}
+
 impl Compiler {
     /// Compile a match expression.
     pub fn compile_match(
@@ -44,17 +82,21 @@ impl Compiler {
         arms: &[MatchArm],
         line: usize,
     ) -> CompileResult<()> {
-        // Patterns the VM cannot compile with a proven stack effect run on the
-        // tree-walking interpreter instead (see `pattern_needs_interpreter`).
-        // Failing compilation here is what routes them there; the alternative
-        // was miscompiled bytecode that corrupted the value stack.
-        if let Some(arm) = arms.iter().find(|a| pattern_needs_interpreter(&a.pattern)) {
-            return Err(CompileError::new(
-                "this match pattern is not yet supported by the bytecode VM",
-                arm.body.span,
-            ));
+        if arms.iter().all(|a| is_flat_pattern(&a.pattern)) {
+            self.compile_flat_match(expression, arms, line)
+        } else {
+            self.compile_binding_match(expression, arms, line)
         }
+    }
 
+    /// Compile a match whose arms are all wildcards or literals. The subject
+    /// is an anonymous temporary, so this works in any stack position.
+    fn compile_flat_match(
+        &mut self,
+        expression: &Expr,
+        arms: &[MatchArm],
+        line: usize,
+    ) -> CompileResult<()> {
         // Evaluate the match subject
         self.compile_expr(expression)?;
 
@@ -104,9 +146,12 @@ impl Compiler {
             }
         }
 
-        // Default: if no arm matched, pop the subject and push null
+        // No arm matched: an error, as on the tree-walker.
         self.emit(Op::Pop, line); // pop subject
-        self.emit(Op::Null, line);
+        self.emit_no_match(line);
+        // Nothing falls through the throw; what reaches the end is an arm's
+        // result, one value above the baseline.
+        self.stack_height += 1;
 
         // Patch all end jumps
         for ej in end_jumps {
@@ -116,16 +161,121 @@ impl Compiler {
         Ok(())
     }
 
+    /// Compile a match with binding or composite patterns.
+    ///
+    /// The subject lives in an anonymous local and every name an arm binds
+    /// gets a slot of its own, pre-filled with null before the arm's tests
+    /// run. Tests then read the subject (or a path into it) by slot and store
+    /// bindings with `SetLocal`, so every test leaves the stack exactly at the
+    /// arm's baseline — on the success path and at every fail jump alike — and
+    /// the arm's teardown is the same fixed sequence of pops either way. The
+    /// winning body's value is stored over the subject slot, which is where
+    /// the match expression's result ends up.
+    ///
+    /// Slots are addressed as `locals.len()`, which only matches the real stack
+    /// position when no anonymous temporaries sit below the subject. In a
+    /// sub-expression (`1 + match ...`, a call argument) the temporaries are
+    /// given anonymous slots of their own for the match's duration, so the
+    /// numbering lines up; they stay on the stack untouched.
+    fn compile_binding_match(
+        &mut self,
+        expression: &Expr,
+        arms: &[MatchArm],
+        line: usize,
+    ) -> CompileResult<()> {
+        let base = self.locals.len();
+        let temporaries = self.stack_height - base;
+
+        self.compile_expr(expression)?;
+        self.begin_scope();
+        for _ in 0..temporaries {
+            self.add_local(String::new(), false);
+        }
+        let subject_slot = self.locals.len() as u16;
+        self.add_local(String::new(), false); // anonymous subject local
+        self.resync_stack_height();
+
+        let mut end_jumps = Vec::new();
+
+        for arm in arms {
+            self.begin_scope();
+            let mut names = Vec::new();
+            collect_bindings(&arm.pattern, &mut names);
+            for name in names {
+                self.emit(Op::Null, line);
+                self.add_local(name, false);
+            }
+            let arm_height = self.locals.len();
+            self.resync_stack_height();
+
+            let mut fail_jumps = Vec::new();
+            self.compile_pattern_test(&arm.pattern, subject_slot, &[], &mut fail_jumps, line)?;
+
+            if let Some(ref guard) = arm.guard {
+                self.compile_expr(guard)?;
+                fail_jumps.push(self.emit_jump(Op::JumpIfFalse(0), line));
+            }
+
+            self.compile_expr(&arm.body)?;
+            self.emit(Op::SetLocal(subject_slot), line);
+            self.emit(Op::Pop, line);
+
+            // Bindings captured by a closure in the guard or body must be
+            // closed on both paths, so snapshot the flags before `end_scope`
+            // drops the locals.
+            let teardown: Vec<bool> = self.locals[subject_slot as usize + 1..]
+                .iter()
+                .rev()
+                .map(|local| local.is_captured)
+                .collect();
+            self.end_scope(line);
+            end_jumps.push(self.emit_jump(Op::Jump(0), line));
+
+            for fj in fail_jumps {
+                self.patch_jump(fj);
+            }
+            self.stack_height = arm_height;
+            for captured in teardown {
+                self.emit(if captured { Op::CloseUpvalue } else { Op::Pop }, line);
+            }
+        }
+
+        // No arm matched.
+        self.emit_no_match(line);
+
+        for ej in end_jumps {
+            self.patch_jump(ej);
+        }
+
+        // The result sits in the subject slot and stays on the stack as the
+        // expression's value, above any temporaries; drop only the
+        // compiler-side bookkeeping.
+        self.locals.truncate(base);
+        self.scope_depth -= 1;
+        self.stack_height = subject_slot as usize + 1;
+        Ok(())
+    }
+
+    /// Raise the tree-walker's "no pattern matched" error.
+    fn emit_no_match(&mut self, line: usize) {
+        self.emit_constant(
+            Constant::String("no pattern matched the value".to_string().into()),
+            line,
+        );
+        self.emit(Op::Throw, line);
+    }
+
     /// Compile a single pattern test.
     ///
     /// Returns the jump offsets to patch to the "fail" path, and whether the
-    /// test consumed the duplicated subject that `compile_match` pushed for it.
-    /// That flag is the stack contract between the two: the caller emits a `Pop`
-    /// only when the pattern left the duplicate in place, so exactly one value
-    /// is removed either way, on both the success and the fail path.
+    /// test consumed the duplicated subject that `compile_flat_match` pushed
+    /// for it. That flag is the stack contract between the two: the caller
+    /// emits a `Pop` only when the pattern left the duplicate in place, so
+    /// exactly one value is removed either way, on both the success and the
+    /// fail path.
     ///
-    /// Only the kinds `pattern_needs_interpreter` admits reach here; everything
-    /// else has already failed compilation and runs on the tree-walker.
+    /// Only wildcards and literals reach here; everything else goes through
+    /// `compile_pattern_test`.
     fn compile_pattern(
         &mut self,
         pattern: &MatchPattern,
@@ -146,6 +296,255 @@ impl Compiler {
         }
     }
 
+    /// Compile a test of the value at `path` under the subject in
+    /// `subject_slot`, binding into the arm's pre-allocated slots. Net stack
+    /// effect is zero, including at each jump pushed onto `fail_jumps`.
+    fn compile_pattern_test(
+        &mut self,
+        pattern: &MatchPattern,
+        subject_slot: u16,
+        path: &[PathStep],
+        fail_jumps: &mut Vec<usize>,
+        line: usize,
+    ) -> CompileResult<()> {
+        match pattern {
+            MatchPattern::Wildcard => {}
+            MatchPattern::Literal(expr_kind) => {
+                self.emit_pattern_value(subject_slot, path, line);
+                fail_jumps.extend(self.compile_literal_pattern(expr_kind, line)?);
+            }
+            MatchPattern::Variable(name) => {
+                self.emit_pattern_value(subject_slot, path, line);
+                self.emit_bind(name, line)?;
+            }
+            MatchPattern::Typed { name, type_name } => {
+                self.emit_pattern_value(subject_slot, path, line);
+                let idx = self.add_string_constant(type_name);
+                self.emit(Op::IsType(idx), line);
+                fail_jumps.push(self.emit_jump(Op::JumpIfFalse(0), line));
+                self.emit_pattern_value(subject_slot, path, line);
+                self.emit_bind(name, line)?;
+            }
+            MatchPattern::Array { elements, rest } => {
+                let len = u16::try_from(elements.len()).map_err(|_| {
+                    CompileError::new(
+                        "array pattern too long for the bytecode VM",
+                        crate::span::Span::new(0, 0, line, 0),
+                    )
+                })?;
+                self.emit_pattern_value(subject_slot, path, line);
+                self.emit(Op::IsArrayLen(len, rest.is_some()), line);
+                fail_jumps.push(self.emit_jump(Op::JumpIfFalse(0), line));
+
+                let mut element_path = path.to_vec();
+                for (i, element) in elements.iter().enumerate() {
+                    element_path.push(PathStep::Index(i as i64));
+                    self.compile_pattern_test(
+                        element,
+                        subject_slot,
+                        &element_path,
+                        fail_jumps,
+                        line,
+                    )?;
+                    element_path.pop();
+                }
+
+                if let Some(rest) = rest {
+                    self.emit_pattern_value(subject_slot, path, line);
+                    self.emit_constant(Constant::Int(elements.len() as i64), line);
+                    self.emit_method_call("drop", 1, line);
+                    self.emit_bind(rest, line)?;
+                }
+            }
+            MatchPattern::Tuple(elements) => {
+                let len = u16::try_from(elements.len()).map_err(|_| {
+                    CompileError::new(
+                        "tuple pattern too long for the bytecode VM",
+                        crate::span::Span::new(0, 0, line, 0),
+                    )
+                })?;
+                self.emit_pattern_value(subject_slot, path, line);
+                self.emit(Op::IsTupleLen(len), line);
+                fail_jumps.push(self.emit_jump(Op::JumpIfFalse(0), line));
+
+                let mut element_path = path.to_vec();
+                for (i, element) in elements.iter().enumerate() {
+                    element_path.push(PathStep::Index(i as i64));
+                    self.compile_pattern_test(
+                        element,
+                        subject_slot,
+                        &element_path,
+                        fail_jumps,
+                        line,
+                    )?;
+                    element_path.pop();
+                }
+            }
+            MatchPattern::Hash { fields, rest } => {
+                self.emit_pattern_value(subject_slot, path, line);
+                self.emit(Op::IsHash, line);
+                fail_jumps.push(self.emit_jump(Op::JumpIfFalse(0), line));
+
+                let mut field_path = path.to_vec();
+                for (key, field) in fields {
+                    self.emit_pattern_value(subject_slot, path, line);
+                    self.emit_constant(Constant::String(key.clone().into()), line);
+                    self.emit_method_call("has_key", 1, line);
+                    fail_jumps.push(self.emit_jump(Op::JumpIfFalse(0), line));
+
+                    field_path.push(PathStep::Key(key.clone()));
+                    self.compile_pattern_test(field, subject_slot, &field_path, fail_jumps, line)?;
+                    field_path.pop();
+                }
+
+                if let Some(rest) = rest {
+                    self.emit_pattern_value(subject_slot, path, line);
+                    for (key, _) in fields {
+                        self.emit_constant(Constant::String(key.clone().into()), line);
+                    }
+                    self.emit(Op::Array(fields.len() as u16), line);
+                    self.emit_method_call("except", 1, line);
+                    self.emit_bind(rest, line)?;
+                }
+            }
+            MatchPattern::Destructuring { type_name, fields } => {
+                self.emit_pattern_value(subject_slot, path, line);
+                let type_idx = self.add_string_constant(type_name);
+                self.emit(Op::IsInstance(type_idx), line);
+                fail_jumps.push(self.emit_jump(Op::JumpIfFalse(0), line));
+
+                let mut field_path = path.to_vec();
+                for (name, field) in fields {
+                    self.emit_pattern_value(subject_slot, path, line);
+                    let name_idx = self.add_string_constant(name);
+                    self.emit(Op::HasField(name_idx), line);
+                    fail_jumps.push(self.emit_jump(Op::JumpIfFalse(0), line));
+
+                    field_path.push(PathStep::Field(name.clone()));
+                    self.compile_pattern_test(field, subject_slot, &field_path, fail_jumps, line)?;
+                    field_path.pop();
+                }
+            }
+            MatchPattern::EnumVariant {
+                enum_name,
+                variant_name,
+                bindings,
+            } => {
+                let arity = u8::try_from(bindings.len()).map_err(|_| {
+                    CompileError::new(
+                        "enum variant pattern too long for the bytecode VM",
+                        crate::span::Span::new(0, 0, line, 0),
+                    )
+                })?;
+                self.emit_pattern_value(subject_slot, path, line);
+                let enum_idx = self.add_string_constant(enum_name);
+                let variant_idx = self.add_string_constant(variant_name);
+                self.emit(Op::IsEnumVariant(enum_idx, variant_idx, arity), line);
+                fail_jumps.push(self.emit_jump(Op::JumpIfFalse(0), line));
+
+                let mut payload_path = path.to_vec();
+                for (i, binding) in bindings.iter().enumerate() {
+                    payload_path.push(PathStep::Variant(variant_name.clone(), i as u8));
+                    self.compile_pattern_test(
+                        binding,
+                        subject_slot,
+                        &payload_path,
+                        fail_jumps,
+                        line,
+                    )?;
+                    payload_path.pop();
+                }
+            }
+            MatchPattern::And(patterns) => {
+                for pattern in patterns {
+                    self.compile_pattern_test(pattern, subject_slot, path, fail_jumps, line)?;
+                }
+            }
+            MatchPattern::Or(patterns) => {
+                // Each alternative but the last jumps to `matched` on success
+                // and on failure falls through to the next one, with whatever
+                // it bound reset to null; the last alternative's failure is
+                // the whole pattern's.
+                let mut matched = Vec::new();
+                if let Some((last, alternatives)) = patterns.split_last() {
+                    for alternative in alternatives {
+                        let mut next = Vec::new();
+                        self.compile_pattern_test(
+                            alternative,
+                            subject_slot,
+                            path,
+                            &mut next,
+                            line,
+                        )?;
+                        matched.push(self.emit_jump(Op::Jump(0), line));
+                        for jump in next {
+                            self.patch_jump(jump);
+                        }
+                        let mut names = Vec::new();
+                        collect_bindings(alternative, &mut names);
+                        for name in names {
+                            self.emit(Op::Null, line);
+                            self.emit_bind(&name, line)?;
+                        }
+                    }
+                    self.compile_pattern_test(last, subject_slot, path, fail_jumps, line)?;
+                }
+                for jump in matched {
+                    self.patch_jump(jump);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Push the value at `path` under the subject in `subject_slot`.
+    fn emit_pattern_value(&mut self, subject_slot: u16, path: &[PathStep], line: usize) {
+        self.emit(Op::GetLocal(subject_slot), line);
+        for step in path {
+            match step {
+                PathStep::Index(i) => {
+                    self.emit_constant(Constant::Int(*i), line);
+                    self.emit(Op::GetIndex, line);
+                }
+                PathStep::Key(key) => {
+                    self.emit_constant(Constant::String(key.clone().into()), line);
+                    self.emit(Op::GetIndex, line);
+                }
+                PathStep::Field(name) => {
+                    let idx = self.add_string_constant(name);
+                    self.emit(Op::InstanceField(idx), line);
+                }
+                PathStep::Variant(variant, index) => {
+                    let idx = self.add_string_constant(variant);
+                    self.emit(Op::VariantField(idx, *index), line);
+                }
+            }
+        }
+    }
+
+    /// Pop the top of the stack into the arm's slot for `name`.
+    fn emit_bind(&mut self, name: &str, line: usize) -> CompileResult<()> {
+        let slot = self.resolve_local(name).ok_or_else(|| {
+            CompileError::new(
+                format!("unbound pattern variable '{}'", name),
+                crate::span::Span::new(0, 0, line, 0),
+            )
+        })?;
+        self.emit(Op::SetLocal(slot), line);
+        self.emit(Op::Pop, line);
+        Ok(())
+    }
+
+    fn emit_method_call(&mut self, name: &str, argc: u8, line: usize) {
+        let name_idx = self.add_string_constant(name);
+        let method_id = super::method_table::resolve_method_id(name);
+        if method_id != super::method_table::METHOD_UNKNOWN {
+            self.emit(Op::CallMethodById(name_idx, argc, method_id), line);
+        } else {
+            self.emit(Op::CallMethod(name_idx, argc), line);
+        }
+    }
+
     fn compile_literal_pattern(
         &mut self,
         expr_kind: &ExprKind,
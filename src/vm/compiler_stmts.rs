@@ -2,9 +2,12 @@
 
 use std::sync::Arc;
 
-use crate::ast::stmt::{CatchClause, FunctionDecl, ImportDecl, StmtKind};
+use crate::ast::stmt::{
+    CatchClause, ClassDecl, Decorator, FunctionDecl, ImportDecl, MethodDecl, StmtKind,
+};
 use crate::ast::Stmt;
 use crate::error::CompileError;
+use crate::span::Span;
 
 use super::chunk::Constant;
 use super::compiler::{CompileResult, Compiler, FunctionType};
@@ -28,6 +31,7 @@ impl Compiler {
                 name,
                 type_annotation: _,
                 initializer,
+                ..
             } => {
                 self.compile_let(name, initializer.as_ref(), false, line, stmt.span)?;
             }
@@ -38,6 +42,25 @@ impl Compiler {
             } => {
                 self.compile_let(name, Some(initializer), true, line, stmt.span)?;
             }
+            StmtKind::LetTuple {
+                names, initializer, ..
+            } => {
+                self.compile_expr(initializer)?;
+                self.emit(Op::Unpack(names.len() as u16), line);
+                if self.scope_depth > 0 {
+                    // Each element already sits in its local's slot.
+                    for name in names {
+                        self.declare_variable(name, false, stmt.span)?;
+                    }
+                } else {
+                    // The last element is on top: define back to front.
+                    for name in names.iter().rev() {
+                        self.known_globals.borrow_mut().insert(name.clone());
+                        let idx = self.add_string_constant(name);
+                        self.emit(Op::DefineGlobal(idx), line);
+                    }
+                }
+            }
             StmtKind::Block(stmts) => {
                 self.begin_scope();
                 for s in stmts {
@@ -58,36 +81,43 @@ impl Compiler {
             StmtKind::For {
                 variable,
                 index_variable,
+                destructure,
                 iterable,
                 body,
             } => {
-                self.compile_for(variable, index_variable.as_deref(), iterable, body, line)?;
+                self.compile_for(
+                    variable,
+                    index_variable.as_deref(),
+                    destructure.as_deref(),
+                    iterable,
+                    body,
+                    line,
+                )?;
             }
             StmtKind::Break => {
-                // TODO: compile `break` natively. Doing it correctly means
-                // unwinding, at the jump site, everything the loop body pushed:
-                // body locals (Pop/CloseUpvalue above the loop's baseline), the
-                // `for` iterator on `iter_stack`, and any live exception handler
-                // when the `break` sits inside a `try`. Until that is in place,
-                // refuse compilation so the handler falls back to the
-                // tree-walking interpreter, which implements `break` fully.
-                return Err(CompileError::new(
-                    "`break` is not supported in compiled mode",
-                    stmt.span,
-                ));
+                self.compile_break(stmt.span)?;
             }
             StmtKind::Return(expr) => {
                 if let Some(expr) = expr {
                     self.compile_expr(expr)?;
+                    self.mark_tail_call(expr);
                 } else {
                     self.emit(Op::Null, line);
                 }
-                self.emit(Op::Return, line);
+                self.emit_return(line)?;
             }
             StmtKind::Throw(expr) => {
                 self.compile_expr(expr)?;
                 self.emit(Op::Throw, line);
             }
+            StmtKind::Yield(_) => {
+                // Generators suspend mid-body, which the VM's call frames
+                // can't do; the whole unit falls back to the interpreter.
+                return Err(CompileError::new(
+                    "generator functions (yield) run in the interpreter, not the VM",
+                    stmt.span,
+                ));
+            }
             StmtKind::Try {
                 try_block,
                 catch_clauses,
@@ -96,9 +126,21 @@ impl Compiler {
                 self.compile_try(try_block, catch_clauses, finally_block.as_deref(), line)?;
             }
             StmtKind::Function(decl) => {
+                if let Some(decorator) = decl.decorators.first() {
+                    return Err(decorators_unsupported(decorator));
+                }
+                if decl.is_async {
+                    return Err(async_unsupported(decl.span));
+                }
                 self.compile_function_decl(decl, line)?;
             }
             StmtKind::Class(decl) => {
+                if let Some(decorator) = first_class_decorator(decl) {
+                    return Err(decorators_unsupported(decorator));
+                }
+                if let Some(method) = first_async_method(decl) {
+                    return Err(async_unsupported(method.span));
+                }
                 self.compile_class_decl(decl, line)?;
             }
             StmtKind::Enum(decl) => {
@@ -106,8 +148,11 @@ impl Compiler {
                 // declaration, construction, and methods are all VM-native.
                 self.compile_class_decl(&decl.lower_to_class(), line)?;
             }
-            StmtKind::Interface(_) => {
-                // Interfaces are type-only, no runtime representation needed
+            StmtKind::Struct(decl) => {
+                self.compile_struct_decl(decl, line)?;
+            }
+            StmtKind::Interface(_) | StmtKind::TypeAlias(_) => {
+                // Interfaces and type aliases are type-only, no runtime representation needed
             }
             StmtKind::Import(decl) => {
                 self.compile_import(decl, line)?;
@@ -135,6 +180,21 @@ impl Compiler {
         }
 
         if self.scope_depth > 0 {
+            // Redeclaring a local of this scope (`let x = 1; let x = 2`)
+            // rebinds the same variable, so closures that captured it see the
+            // new value, as in the tree-walker. A constant stays put: a later
+            // `let` of its name is dropped, while `const` over a `let` freezes
+            // it.
+            if let Some(slot) = self.same_scope_local(name) {
+                if is_const {
+                    self.locals[slot as usize].is_const = true;
+                }
+                if is_const || !self.locals[slot as usize].is_const {
+                    self.emit(Op::SetLocal(slot), line);
+                }
+                self.emit(Op::Pop, line);
+                return Ok(());
+            }
             // Local variable
             self.declare_variable(name, is_const, span)?;
             // The value is already on the stack at the right slot
@@ -143,6 +203,9 @@ impl Compiler {
             self.known_globals.borrow_mut().insert(name.to_string());
             let idx = self.add_string_constant(name);
             self.emit(Op::DefineGlobal(idx), line);
+            if is_const {
+                self.emit(Op::ConstGlobal(idx), line);
+            }
         }
         Ok(())
     }
@@ -177,7 +240,7 @@ impl Compiler {
         line: usize,
     ) -> CompileResult<()> {
         let loop_start = self.current_offset();
-        self.begin_loop(loop_start);
+        self.begin_loop(loop_start, false);
 
         self.compile_expr(condition)?;
         let exit_jump = self.emit_jump(Op::JumpIfFalse(0), line);
@@ -194,6 +257,7 @@ impl Compiler {
         &mut self,
         variable: &str,
         index_variable: Option<&str>,
+        destructure: Option<&[String]>,
         iterable: &crate::ast::Expr,
         body: &Stmt,
         line: usize,
@@ -216,30 +280,10 @@ impl Compiler {
             None
         };
 
-        // Optimize: for x in a..b => GetIterRange + ForIterRange (zero allocation, inlined)
-        let is_range = matches!(
-            &iterable.kind,
-            crate::ast::ExprKind::Binary {
-                operator: crate::ast::BinaryOp::Range,
-                ..
-            }
-        );
-        if let crate::ast::ExprKind::Binary {
-            left,
-            operator: crate::ast::BinaryOp::Range,
-            right,
-        } = &iterable.kind
-        {
-            self.compile_expr(left)?;
-            self.compile_expr(right)?;
-            self.emit(Op::GetIterRange, line);
-        } else {
-            self.compile_expr(iterable)?;
-            self.emit(Op::GetIter, line);
-        }
+        let is_range = self.compile_iterator(iterable, destructure.is_some(), line)?;
 
         let loop_start = self.current_offset();
-        self.begin_loop(loop_start);
+        self.begin_loop(loop_start, true);
         let exit_jump = if is_range {
             self.emit_jump(Op::ForIterRange(0), line)
         } else {
@@ -249,7 +293,23 @@ impl Compiler {
         // Bind the loop variable to the freshly-yielded element value.
         self.add_local(variable.to_string(), false);
 
-        self.compile_stmt(body)?;
+        if let Some(names) = destructure {
+            // `for (k, v) in ...`: unpack the item into one local per name,
+            // torn down with the body's scope each iteration.
+            self.begin_scope();
+            let slot = self
+                .resolve_local(variable)
+                .expect("loop local just declared");
+            self.emit(Op::GetLocal(slot), line);
+            self.emit(Op::Unpack(names.len() as u16), line);
+            for name in names {
+                self.add_local(name.clone(), false);
+            }
+            self.compile_stmt(body)?;
+            self.end_scope(line);
+        } else {
+            self.compile_stmt(body)?;
+        }
 
         // Pop the loop variable (closing its upvalue if a body closure captured
         // it, so closures from different iterations don't share a binding).
@@ -265,22 +325,73 @@ impl Compiler {
         }
 
         self.emit_loop(loop_start, line);
+        // A `break` lands here, ahead of the exhausted-iterator exit, and
+        // drops the iterator that `ForIter` would have.
+        if self.end_loop() {
+            self.emit(Op::PopIter, line);
+        }
         self.patch_jump(exit_jump);
 
-        self.end_loop();
-
         // Pop the index counter (closing its upvalue if it was captured).
         if index_slot.is_some() {
             self.emit_pop_or_close_top(line);
         }
 
         // Note: no Pop needed for the iterator — GetIter moved it to iter_stack,
-        // and ForIter pops it from iter_stack when exhausted.
+        // and ForIter pops it from iter_stack when exhausted (`break` and
+        // `return` pop it themselves).
 
         self.end_scope(line);
         Ok(())
     }
 
+    /// Push the iterator for a loop over `iterable`. A literal `a..b` /
+    /// `a..=b` skips building the range value (GetIterRange + ForIterRange);
+    /// returns whether that fast path was taken. `entries` selects the
+    /// destructuring iterator behind `for (a, b) in ...`.
+    pub(crate) fn compile_iterator(
+        &mut self,
+        iterable: &crate::ast::Expr,
+        entries: bool,
+        line: usize,
+    ) -> CompileResult<bool> {
+        use crate::ast::{BinaryOp, ExprKind};
+        if let ExprKind::Binary {
+            left,
+            operator: operator @ (BinaryOp::Range | BinaryOp::RangeInclusive),
+            right,
+        } = &iterable.kind
+        {
+            if !entries {
+                self.compile_expr(left)?;
+                self.compile_expr(right)?;
+                self.emit(
+                    Op::GetIterRange(*operator == BinaryOp::RangeInclusive),
+                    line,
+                );
+                return Ok(true);
+            }
+        }
+        self.compile_expr(iterable)?;
+        self.emit(
+            if entries {
+                Op::GetEntryIter
+            } else {
+                Op::GetIter
+            },
+            line,
+        );
+        Ok(false)
+    }
+
+    /// Compile `try`/`catch`/`finally`.
+    ///
+    /// The exception lands in an anonymous local that every catch clause
+    /// tests in turn; a clause's variable is a copy of it. With a `finally`,
+    /// the catch clauses run under a second handler so that a throw from a
+    /// clause, like one no clause matches, runs `finally` before
+    /// propagating. A `break` or `return` leaving the statement runs it too
+    /// (see [`Self::emit_unwind_handlers`]).
     fn compile_try(
         &mut self,
         try_block: &Stmt,
@@ -288,100 +399,223 @@ impl Compiler {
         finally_block: Option<&Stmt>,
         line: usize,
     ) -> CompileResult<()> {
-        // Emit TryBegin with placeholder offsets
+        self.try_depth += 1;
         let try_begin = self.emit(Op::TryBegin(0, 0), line);
-
-        // Compile try body
+        self.handlers.push(finally_block.cloned());
         self.compile_stmt(try_block)?;
+        self.handlers.pop();
         self.emit(Op::TryEnd, line);
-
-        // Jump over catch/finally if no exception
         let no_exception_jump = self.emit_jump(Op::Jump(0), line);
 
-        // Patch catch offset — exception value is now on the stack
-        let catch_start = self.current_offset();
-        let catch_offset = catch_start - try_begin - 1;
-
-        if catch_clauses.is_empty() {
-            // No catch block — pop the exception
-            self.emit(Op::Pop, line);
-        } else {
-            // Compile catch clauses. Exception value is on top of stack.
-            // For typed catches, emit CatchMatch to check type and jump to next clause.
-            let mut end_jumps = Vec::new();
-            let mut next_clause_patches: Vec<usize> = Vec::new();
-
-            for (i, clause) in catch_clauses.iter().enumerate() {
-                // Patch the previous clause's "no match" jump to here
-                for patch_idx in next_clause_patches.drain(..) {
-                    let target = self.current_offset();
-                    let jump_offset = target - patch_idx - 1;
-                    if let Op::CatchMatch(_, ref mut off) = self.proto.chunk.code[patch_idx] {
-                        *off = jump_offset as u16;
-                    }
-                }
-
-                if let Some(ref type_name) = clause.type_name {
-                    // Typed catch: check if exception matches the type
-                    let name_idx = self.add_constant(Constant::String(type_name.clone().into()));
-                    let catch_match_idx = self.emit(Op::CatchMatch(name_idx, 0), line);
-                    next_clause_patches.push(catch_match_idx);
-                }
-
-                // Matched — compile the catch body in a new scope
-                self.begin_scope();
-                if let Some(ref var_name) = clause.var_name {
-                    // The exception value is on the stack — declare it as a local
-                    self.add_local(var_name.clone(), false);
-                }
-                self.compile_stmt(&clause.body)?;
-                self.end_scope(line);
-
-                // Jump to after all catch clauses (to finally)
-                // But not for the last clause — it falls through
-                if i < catch_clauses.len() - 1 {
-                    end_jumps.push(self.emit_jump(Op::Jump(0), line));
-                }
+        // The handler left the exception on top of the stack.
+        self.patch_try_begin(try_begin);
+        self.begin_scope();
+        self.add_local(String::new(), false);
+        let exception_slot = (self.locals.len() - 1) as u16;
+        let clause_try_begin = match finally_block {
+            Some(_) => {
+                let at = self.emit(Op::TryBegin(0, 0), line);
+                self.handlers.push(finally_block.cloned());
+                Some(at)
             }
+            None => None,
+        };
 
-            // If the last clause was typed and didn't match, re-throw
-            if !next_clause_patches.is_empty() {
-                for patch_idx in next_clause_patches.drain(..) {
-                    let target = self.current_offset();
-                    let jump_offset = target - patch_idx - 1;
-                    if let Op::CatchMatch(_, ref mut off) = self.proto.chunk.code[patch_idx] {
-                        *off = jump_offset as u16;
-                    }
-                }
-                self.emit(Op::Rethrow, line);
+        let mut clause_jumps = Vec::new();
+        let mut no_match: Option<usize> = None;
+        for clause in catch_clauses {
+            if let Some(at) = no_match.take() {
+                self.patch_catch_match(at);
             }
-
-            // Patch all end jumps to here
-            for j in end_jumps {
-                self.patch_jump(j);
+            if let Some(ref type_name) = clause.type_name {
+                let name_idx = self.add_constant(Constant::String(type_name.clone().into()));
+                no_match = Some(self.emit(Op::CatchMatch(name_idx, 0), line));
             }
+            self.begin_scope();
+            if let Some(ref var_name) = clause.var_name {
+                self.emit(Op::GetLocal(exception_slot), line);
+                self.add_local(var_name.clone(), false);
+            }
+            self.compile_stmt(&clause.body)?;
+            self.end_scope(line);
+            clause_jumps.push(self.emit_jump(Op::Jump(0), line));
+        }
+        if clause_try_begin.is_some() {
+            self.handlers.pop();
         }
 
-        // Patch the finally offset
-        let finally_start = self.current_offset();
-        let finally_offset = finally_start - try_begin - 1;
+        // No clause took the exception: run `finally`, then let it go on.
+        if catch_clauses.is_empty() || no_match.is_some() {
+            if let Some(at) = no_match {
+                self.patch_catch_match(at);
+            }
+            if let Some(finally_body) = finally_block {
+                self.emit(Op::TryEnd, line);
+                self.compile_stmt(finally_body)?;
+            }
+            self.emit(Op::GetLocal(exception_slot), line);
+            self.emit(Op::Rethrow, line);
+        }
 
+        for jump in clause_jumps {
+            self.patch_jump(jump);
+        }
+        if clause_try_begin.is_some() {
+            self.emit(Op::TryEnd, line);
+        }
+        self.end_scope(line);
         self.patch_jump(no_exception_jump);
 
-        // Compile finally block
         if let Some(finally_body) = finally_block {
             self.compile_stmt(finally_body)?;
         }
 
-        // Patch TryBegin offsets
-        if let Op::TryBegin(ref mut co, ref mut fo) = self.proto.chunk.code[try_begin] {
-            *co = catch_offset as u16;
-            *fo = finally_offset as u16;
+        // A throw out of a catch clause: the stack holds the first
+        // exception's slot and the new exception. Run `finally` and rethrow.
+        if let (Some(at), Some(finally_body)) = (clause_try_begin, finally_block) {
+            let end_jump = self.emit_jump(Op::Jump(0), line);
+            self.patch_try_begin(at);
+            self.begin_scope();
+            self.add_local(String::new(), false);
+            self.add_local(String::new(), false);
+            let rethrown_slot = (self.locals.len() - 1) as u16;
+            self.compile_stmt(finally_body)?;
+            self.emit(Op::GetLocal(rethrown_slot), line);
+            self.emit(Op::Rethrow, line);
+            self.drop_scope();
+            self.patch_jump(end_jump);
+        }
+
+        self.try_depth -= 1;
+        Ok(())
+    }
+
+    /// Point the `TryBegin` at `at` to the current offset as its catch target.
+    fn patch_try_begin(&mut self, at: usize) {
+        let offset = (self.current_offset() - at - 1) as u16;
+        if let Op::TryBegin(ref mut catch_offset, _) = self.proto.chunk.code[at] {
+            *catch_offset = offset;
+        }
+    }
+
+    /// Point the `CatchMatch` at `at` to the current offset as its no-match target.
+    fn patch_catch_match(&mut self, at: usize) {
+        let offset = (self.current_offset() - at - 1) as u16;
+        if let Op::CatchMatch(_, ref mut no_match) = self.proto.chunk.code[at] {
+            *no_match = offset;
+        }
+    }
+
+    /// Close a scope whose code never falls through (every path ends in a
+    /// `Return` or `Rethrow`): its locals leave the compiler's tracking with
+    /// no pops emitted.
+    fn drop_scope(&mut self) {
+        self.scope_depth -= 1;
+        while self
+            .locals
+            .last()
+            .is_some_and(|local| local.depth > self.scope_depth)
+        {
+            self.locals.pop();
+        }
+    }
+
+    /// `break` out of the innermost loop: run the `finally` blocks of the
+    /// `try`s it leaves, pop the locals the body declared and jump past the
+    /// loop, whose exit drops a `for` iterator (see [`Self::compile_for`]).
+    ///
+    /// Outside any loop of its own function, `break` ends that function
+    /// call instead, as in the tree-walker, where the function boundary
+    /// absorbs it: `each(fn(x) { break })` never breaks a loop around it.
+    fn compile_break(&mut self, span: Span) -> CompileResult<()> {
+        let Some(ctx) = self.loop_context.as_ref() else {
+            if self.function_type == FunctionType::Script {
+                return Err(CompileError::new("`break` outside of a loop", span));
+            }
+            let line = span.line as usize;
+            self.emit(Op::Null, line);
+            return self.emit_return(line);
+        };
+        let (locals, handlers) = (ctx.locals, ctx.handlers);
+        let line = span.line as usize;
+        self.emit_unwind_handlers(handlers, line)?;
+        for i in (locals..self.locals.len()).rev() {
+            if self.locals[i].is_captured {
+                self.emit(Op::CloseUpvalue, line);
+            } else {
+                self.emit(Op::Pop, line);
+            }
+        }
+        let jump = self.emit_jump(Op::Jump(0), line);
+        self.add_break_patch(jump);
+        Ok(())
+    }
+
+    /// Return the value on top of the stack. Inside a `try` or a `for` loop
+    /// the value waits in a slot while the `finally` blocks run and the
+    /// loops' iterators are dropped.
+    fn emit_return(&mut self, line: usize) -> CompileResult<()> {
+        let mut iterators = 0;
+        let mut ctx = self.loop_context.as_ref();
+        while let Some(loop_ctx) = ctx {
+            iterators += loop_ctx.iterator as usize;
+            ctx = loop_ctx.enclosing.as_deref();
+        }
+        if self.handlers.is_empty() && iterators == 0 {
+            self.emit(Op::Return, line);
+            return Ok(());
         }
+        self.add_local(String::new(), false);
+        let slot = (self.locals.len() - 1) as u16;
+        self.emit_unwind_handlers(0, line)?;
+        for _ in 0..iterators {
+            self.emit(Op::PopIter, line);
+        }
+        self.emit(Op::GetLocal(slot), line);
+        self.emit(Op::Return, line);
+        self.locals.pop();
+        Ok(())
+    }
 
+    /// Leave the exception handlers above `depth`, innermost first: pop each
+    /// one and run its `finally` block. A `break` or `return` inside that
+    /// block only unwinds the handlers outside it.
+    fn emit_unwind_handlers(&mut self, depth: usize, line: usize) -> CompileResult<()> {
+        let handlers = self.handlers.clone();
+        for i in (depth..handlers.len()).rev() {
+            self.emit(Op::PopHandler, line);
+            if let Some(finally_body) = &handlers[i] {
+                self.handlers.truncate(i);
+                self.compile_stmt(finally_body)?;
+            }
+        }
+        self.handlers = handlers;
         Ok(())
     }
 
+    /// Turn the `Call` that ends `return f(...)` (or an implicit return) into
+    /// a `TailCall`. Not in scripts and constructors (whose callers expect
+    /// their own frame back), inside a `try` (the handler is this frame's) or
+    /// inside a loop (a `for` keeps its iterator on the VM's iterator stack
+    /// until the loop ends).
+    fn mark_tail_call(&mut self, expr: &crate::ast::Expr) {
+        if !matches!(expr.kind, crate::ast::ExprKind::Call { .. })
+            || !matches!(
+                self.function_type,
+                FunctionType::Function | FunctionType::Method | FunctionType::Lambda
+            )
+            || self.try_depth > 0
+            || self.loop_context.is_some()
+        {
+            return;
+        }
+        if let Some(op) = self.proto.chunk.code.last_mut() {
+            if let Op::Call(argc) = *op {
+                *op = Op::TailCall(argc);
+            }
+        }
+    }
+
     fn compile_function_decl(&mut self, decl: &FunctionDecl, line: usize) -> CompileResult<()> {
         let name = decl.name.clone();
 
@@ -390,6 +624,10 @@ impl Compiler {
         // resolve a bare assignment to this name as the global, not a new local.
         if self.scope_depth == 0 {
             self.known_globals.borrow_mut().insert(name.clone());
+        } else {
+            // A local function is in scope in its own body, so it can
+            // recurse: declare the slot its closure is about to fill.
+            self.add_local(name.clone(), false);
         }
 
         // Start compiling the function body
@@ -404,39 +642,82 @@ impl Compiler {
         let idx = self.add_constant(Constant::Function(Arc::new(proto)));
         self.emit(Op::Closure(idx), line);
 
-        // Bind the function name
-        if self.scope_depth > 0 {
-            self.add_local(name, false);
-        } else {
-            let name_idx = self.add_string_constant(&decl.name);
+        // Bind the function name (a local's slot was declared above).
+        if self.scope_depth == 0 {
+            let name_idx = self.add_string_constant(&name);
             self.emit(Op::DefineGlobal(name_idx), line);
         }
         Ok(())
     }
 
     /// Compile a function body with implicit return support.
-    /// If the last statement is an expression, its value is kept on the stack
-    /// (not popped) and returned implicitly, matching tree-walking interpreter behavior.
+    /// The last statement's value is returned implicitly, matching the
+    /// tree-walking interpreter (see [`Self::compile_tail_stmt`]).
     pub fn compile_function_body(&mut self, body: &[Stmt]) -> CompileResult<()> {
-        if body.is_empty() {
+        let Some((last, init)) = body.split_last() else {
             return Ok(());
-        }
+        };
 
         // Declare locals introduced by bare assignment (optional-`let`) up front.
         self.hoist_locals(body, body[0].span.line as usize);
 
-        let last_idx = body.len() - 1;
-        for (i, stmt) in body.iter().enumerate() {
-            if i == last_idx {
-                // Last statement: if it's an expression, compile it without Pop
-                // and emit Return so the value is returned implicitly
-                if let StmtKind::Expression(expr) = &stmt.kind {
-                    self.compile_expr(expr)?;
-                    self.emit(Op::Return, stmt.span.line as usize);
-                    return Ok(());
+        for stmt in init {
+            self.compile_stmt(stmt)?;
+        }
+        self.compile_tail_stmt(last)
+    }
+
+    /// Compile a function body's last statement and return its value: an
+    /// expression's value, or that of the last statement run by the branch
+    /// or block it ends in. Any other statement returns null.
+    fn compile_tail_stmt(&mut self, stmt: &Stmt) -> CompileResult<()> {
+        let line = stmt.span.line as usize;
+        match &stmt.kind {
+            StmtKind::Expression(expr) => {
+                self.resync_stack_height();
+                self.compile_expr(expr)?;
+                self.mark_tail_call(expr);
+                self.emit(Op::Return, line);
+            }
+            StmtKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.resync_stack_height();
+                self.compile_expr(condition)?;
+                let then_jump = self.emit_jump(Op::JumpIfFalse(0), line);
+                self.compile_tail_stmt(then_branch)?;
+                self.patch_jump(then_jump);
+                match else_branch {
+                    Some(else_stmt) => self.compile_tail_stmt(else_stmt)?,
+                    None => {
+                        self.emit(Op::Null, line);
+                        self.emit(Op::Return, line);
+                    }
                 }
             }
-            self.compile_stmt(stmt)?;
+            StmtKind::Block(stmts) => {
+                self.begin_scope();
+                match stmts.split_last() {
+                    Some((last, init)) => {
+                        for s in init {
+                            self.compile_stmt(s)?;
+                        }
+                        self.compile_tail_stmt(last)?;
+                    }
+                    None => {
+                        self.emit(Op::Null, line);
+                        self.emit(Op::Return, line);
+                    }
+                }
+                self.drop_scope();
+            }
+            _ => {
+                self.compile_stmt(stmt)?;
+                self.emit(Op::Null, line);
+                self.emit(Op::Return, line);
+            }
         }
         Ok(())
     }
@@ -447,3 +728,35 @@ impl Compiler {
         Ok(())
     }
 }
+
+/// Decorator values are attached to the interpreter's `Function`/`Class`
+/// values for reflection; the VM's closures have nowhere to keep them, so a
+/// decorated declaration falls back to the interpreter.
+fn decorators_unsupported(decorator: &Decorator) -> CompileError {
+    CompileError::new(
+        "decorated declarations run in the interpreter, not the VM",
+        decorator.span,
+    )
+}
+
+/// The first decorator on `decl`, its methods or its nested classes.
+fn first_class_decorator(decl: &ClassDecl) -> Option<&Decorator> {
+    decl.decorators
+        .first()
+        .or_else(|| decl.methods.iter().find_map(|m| m.decorators.first()))
+        .or_else(|| decl.nested_classes.iter().find_map(first_class_decorator))
+}
+
+/// Async calls are queued on the interpreter's scheduler, which the VM
+/// doesn't have, so async functions fall back to the interpreter.
+fn async_unsupported(span: Span) -> CompileError {
+    CompileError::new("async functions run in the interpreter, not the VM", span)
+}
+
+/// The first `async` method of `decl` or its nested classes.
+fn first_async_method(decl: &ClassDecl) -> Option<&MethodDecl> {
+    decl.methods
+        .iter()
+        .find(|m| m.is_async)
+        .or_else(|| decl.nested_classes.iter().find_map(first_async_method))
+}
@@ -1,6 +1,6 @@
 //! Bytecode disassembler for debug output.
 
-use super::chunk::{Chunk, Constant, FunctionProto};
+use super::chunk::{ArgSlot, Chunk, Constant, FunctionProto};
 use super::opcode::Op;
 
 /// Disassemble a function prototype to a human-readable string.
@@ -72,6 +72,15 @@ fn disassemble_op(op: &Op, chunk: &Chunk, out: &mut String) {
             let name = constant_string(chunk, *idx);
             out.push_str(&format!("DEF_GLOBAL   {:>5} ({})", idx, name));
         }
+        Op::ConstGlobal(idx) => {
+            let name = constant_string(chunk, *idx);
+            out.push_str(&format!("CONST_GLOBAL {:>5} ({})", idx, name));
+        }
+        Op::AssignConst(idx) => {
+            let name = constant_string(chunk, *idx);
+            out.push_str(&format!("ASSIGN_CONST {:>5} ({})", idx, name));
+        }
+        Op::GlobalByName(defined) => out.push_str(if *defined { "DEFINED" } else { "CONST_GET" }),
         Op::GetUpvalue(idx) => out.push_str(&format!("GET_UPVALUE  {:>5}", idx)),
         Op::SetUpvalue(idx) => out.push_str(&format!("SET_UPVALUE  {:>5}", idx)),
         Op::CloseUpvalue => out.push_str("CLOSE_UPVALUE"),
@@ -103,6 +112,7 @@ fn disassemble_op(op: &Op, chunk: &Chunk, out: &mut String) {
             param_index, offset
         )),
         Op::Call(argc) => out.push_str(&format!("CALL         {:>5}", argc)),
+        Op::TailCall(argc) => out.push_str(&format!("TAIL_CALL    {:>5}", argc)),
         Op::CallNamed(argc, names_idx) => out.push_str(&format!(
             "CALL_NAMED   {:>5}  {}",
             argc,
@@ -113,6 +123,16 @@ fn disassemble_op(op: &Op, chunk: &Chunk, out: &mut String) {
             argc,
             format_constant(chunk.constants.get(*names_idx as usize))
         )),
+        Op::CallSpread(argc, layout_idx) => out.push_str(&format!(
+            "CALL_SPREAD  {:>5}  {}",
+            argc,
+            format_constant(chunk.constants.get(*layout_idx as usize))
+        )),
+        Op::NewSpread(argc, layout_idx) => out.push_str(&format!(
+            "NEW_SPREAD   {:>5}  {}",
+            argc,
+            format_constant(chunk.constants.get(*layout_idx as usize))
+        )),
         Op::Closure(idx) => {
             let val = chunk.constants.get(*idx as usize);
             out.push_str(&format!(
@@ -124,9 +144,12 @@ fn disassemble_op(op: &Op, chunk: &Chunk, out: &mut String) {
         Op::Return => out.push_str("RETURN"),
         Op::Array(n) => out.push_str(&format!("ARRAY        {:>5}", n)),
         Op::ArrayPush => out.push_str("ARRAY_PUSH"),
+        Op::Tuple(n) => out.push_str(&format!("TUPLE        {:>5}", n)),
+        Op::Unpack(n) => out.push_str(&format!("UNPACK       {:>5}", n)),
         Op::Hash(n) => out.push_str(&format!("HASH         {:>5}", n)),
         Op::HashWithKeys(idx, n) => out.push_str(&format!("HASH_W_KEYS  k={:>3} n={:>3}", idx, n)),
-        Op::Range => out.push_str("RANGE"),
+        Op::Range(false) => out.push_str("RANGE"),
+        Op::Range(true) => out.push_str("RANGE_INCL"),
         Op::GetIndex => out.push_str("GET_INDEX"),
         Op::SetIndex => out.push_str("SET_INDEX"),
         Op::BuildString(n) => out.push_str(&format!("BUILD_STRING {:>5}", n)),
@@ -144,6 +167,23 @@ fn disassemble_op(op: &Op, chunk: &Chunk, out: &mut String) {
             out.push_str(&format!("CLASS        {:>5} ({})", idx, name));
         }
         Op::Inherit => out.push_str("INHERIT"),
+        Op::Implement(idx) => {
+            let name = constant_string(chunk, *idx);
+            out.push_str(&format!("IMPLEMENT    {:>5} ({})", idx, name));
+        }
+        Op::DeclareConst(idx) => {
+            let name = constant_string(chunk, *idx);
+            out.push_str(&format!("DECL_CONST   {:>5} ({})", idx, name));
+        }
+        Op::DeclareStaticConst(idx) => {
+            let name = constant_string(chunk, *idx);
+            out.push_str(&format!("DECL_ST_CNST {:>5} ({})", idx, name));
+        }
+        Op::NestedClass(idx) => {
+            let name = constant_string(chunk, *idx);
+            out.push_str(&format!("NESTED_CLASS {:>5} ({})", idx, name));
+        }
+        Op::IsModelClass => out.push_str("IS_MODEL_CLASS"),
         Op::Method(idx) => {
             let name = constant_string(chunk, *idx);
             out.push_str(&format!("METHOD       {:>5} ({})", idx, name));
@@ -174,6 +214,10 @@ fn disassemble_op(op: &Op, chunk: &Chunk, out: &mut String) {
             let name = constant_string(chunk, *idx);
             out.push_str(&format!("ST_CONST_FLD {:>5} ({})", idx, name));
         }
+        Op::Struct(idx, fields) => {
+            let name = constant_string(chunk, *idx);
+            out.push_str(&format!("STRUCT       {:>5} ({}) f:{}", idx, name, fields));
+        }
         Op::TryBegin(catch, finally) => {
             out.push_str(&format!("TRY_BEGIN    c:{} f:{}", catch, finally));
         }
@@ -190,9 +234,12 @@ fn disassemble_op(op: &Op, chunk: &Chunk, out: &mut String) {
         Op::PopHandler => out.push_str("POP_HANDLER"),
         Op::RescueJump(offset) => out.push_str(&format!("RESCUE_JUMP  {:>5}", offset)),
         Op::GetIter => out.push_str("GET_ITER"),
-        Op::GetIterRange => out.push_str("GET_ITER_RNG"),
+        Op::GetEntryIter => out.push_str("GET_ENTRY_ITER"),
+        Op::GetIterRange(false) => out.push_str("GET_ITER_RNG"),
+        Op::GetIterRange(true) => out.push_str("GET_ITER_RNG_INCL"),
         Op::ForIter(offset) => out.push_str(&format!("FOR_ITER     {:>5}", offset)),
         Op::ForIterRange(offset) => out.push_str(&format!("FOR_ITER_RNG {:>5}", offset)),
+        Op::PopIter => out.push_str("POP_ITER"),
         Op::Print(n) => out.push_str(&format!("PRINT        {:>5}", n)),
         Op::Import(idx) => {
             let name = constant_string(chunk, *idx);
@@ -200,6 +247,37 @@ fn disassemble_op(op: &Op, chunk: &Chunk, out: &mut String) {
         }
         Op::JsonParse => out.push_str("JSON_PARSE"),
         Op::JsonStringify => out.push_str("JSON_STRINGIFY"),
+        Op::IsType(idx) => {
+            let name = constant_string(chunk, *idx);
+            out.push_str(&format!("IS_TYPE      {:>5} ({})", idx, name));
+        }
+        Op::IsArrayLen(len, at_least) => {
+            let cmp = if *at_least { ">=" } else { "==" };
+            out.push_str(&format!("IS_ARRAY_LEN {} {}", cmp, len));
+        }
+        Op::IsTupleLen(len) => out.push_str(&format!("IS_TUPLE_LEN == {}", len)),
+        Op::IsHash => out.push_str("IS_HASH"),
+        Op::IsInstance(idx) => {
+            let name = constant_string(chunk, *idx);
+            out.push_str(&format!("IS_INSTANCE  {:>5} ({})", idx, name));
+        }
+        Op::HasField(idx) => {
+            let name = constant_string(chunk, *idx);
+            out.push_str(&format!("HAS_FIELD    {:>5} ({})", idx, name));
+        }
+        Op::InstanceField(idx) => {
+            let name = constant_string(chunk, *idx);
+            out.push_str(&format!("INST_FIELD   {:>5} ({})", idx, name));
+        }
+        Op::IsEnumVariant(enum_idx, variant_idx, arity) => {
+            let enum_name = constant_string(chunk, *enum_idx);
+            let variant = constant_string(chunk, *variant_idx);
+            out.push_str(&format!("IS_VARIANT   {}.{}/{}", enum_name, variant, arity));
+        }
+        Op::VariantField(idx, index) => {
+            let variant = constant_string(chunk, *idx);
+            out.push_str(&format!("VARIANT_FIELD {}[{}]", variant, index));
+        }
         Op::IncrLocal(slot) => out.push_str(&format!("INCR_LOCAL   {:>5}", slot)),
         Op::DecrLocal(slot) => out.push_str(&format!("DECR_LOCAL   {:>5}", slot)),
         Op::AddLocalLocal(a, b) => out.push_str(&format!("ADD_LL       {:>3},{:>3}", a, b)),
@@ -236,6 +314,16 @@ fn disassemble_op(op: &Op, chunk: &Chunk, out: &mut String) {
                 idx, name, argc, mid
             ));
         }
+        Op::CallMethodSpread(idx, argc, layout_idx) => {
+            let name = constant_string(chunk, *idx);
+            out.push_str(&format!(
+                "CALL_M_SPRD  {:>5} ({}) argc={} {}",
+                idx,
+                name,
+                argc,
+                format_constant(chunk.constants.get(*layout_idx as usize))
+            ));
+        }
         Op::HashGetConst(idx) => {
             let name = constant_string(chunk, *idx);
             out.push_str(&format!("HASH_GET_C   {:>5} ({})", idx, name));
@@ -381,6 +469,17 @@ fn format_constant(val: Option<&Constant>) -> String {
                 .collect();
             format!("ArgNames[{}]", rendered.join(", "))
         }
+        Some(Constant::SpreadArgs(slots)) => {
+            let rendered: Vec<String> = slots
+                .iter()
+                .map(|slot| match slot {
+                    ArgSlot::Positional => "_".to_string(),
+                    ArgSlot::Named(name) => name.to_string(),
+                    ArgSlot::Spread => "*".to_string(),
+                })
+                .collect();
+            format!("SpreadArgs[{}]", rendered.join(", "))
+        }
         None => "???".to_string(),
     }
 }
@@ -480,6 +579,7 @@ mod tests {
             (Op::Return, "RETURN"),
             (Op::CloseUpvalue, "CLOSE_UPVALUE"),
             (Op::PopNull, "POP_NULL"),
+            (Op::IsHash, "IS_HASH"),
         ] {
             let body = body_for(vec![(op, 1)]);
             assert!(body.contains(expected), "for {op:?}: got body {body:?}");
@@ -0,0 +1,237 @@
+//! Slot-indexed global variable storage.
+//!
+//! Every global name the VM sees is interned once into a process-wide table
+//! and gets a dense slot index. A `Globals` store is a `Vec` indexed by that
+//! slot, and each chunk resolves the slots of the names its global opcodes
+//! reference the first time it runs (`Chunk::global_slot`). `GetGlobal` and
+//! friends are then a plain array index instead of a string hash per access.
+//!
+//! Slot numbers are shared by every VM in the process (so a chunk cached in
+//! an `Arc` resolves once for all workers); the values are per-VM.
+//!
+//! The name table only grows: a chunk may hold a slot for as long as the
+//! process runs, so a name is never un-interned (hence `Box::leak`). It holds
+//! one entry per distinct global name the process compiles or inserts — the
+//! builtins, the app's top-level names and the fixed request locals
+//! (`params`, `cookies`, `req`) — so it is bounded by the code loaded, not
+//! by traffic. Reloading the same code interns nothing new. Only the crate
+//! binds globals by an arbitrary name; embedders use [`Globals::define`],
+//! whose `&'static str` names are fixed when the embedder is built, so
+//! request-derived names can't grow the table.
+
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use crate::interpreter::value::Value;
+
+lazy_static! {
+    static ref GLOBAL_NAMES: RwLock<NameTable> = RwLock::new(NameTable::default());
+}
+
+#[derive(Default)]
+struct NameTable {
+    names: Vec<&'static str>,
+    slots: HashMap<&'static str, u32>,
+}
+
+/// Slot for global `name`, interning it on first use.
+pub(crate) fn global_slot(name: &str) -> u32 {
+    intern(name, || Box::leak(name.to_string().into_boxed_str()))
+}
+
+/// [`global_slot`] for a name that already lives for the whole process, which
+/// is interned without a copy.
+fn static_global_slot(name: &'static str) -> u32 {
+    intern(name, || name)
+}
+
+fn intern(name: &str, to_static: impl FnOnce() -> &'static str) -> u32 {
+    if let Some(slot) = lookup_slot(name) {
+        return slot;
+    }
+    let mut table = GLOBAL_NAMES.write().unwrap();
+    if let Some(&slot) = table.slots.get(name) {
+        return slot;
+    }
+    let slot = table.names.len() as u32;
+    let name = to_static();
+    table.names.push(name);
+    table.slots.insert(name, slot);
+    slot
+}
+
+/// Slot for global `name` if any VM has interned it.
+fn lookup_slot(name: &str) -> Option<u32> {
+    GLOBAL_NAMES.read().unwrap().slots.get(name).copied()
+}
+
+/// Number of names interned so far, for memory diagnostics.
+pub fn interned_count() -> usize {
+    GLOBAL_NAMES.read().unwrap().names.len()
+}
+
+/// Name of global `slot`.
+pub fn global_name(slot: u32) -> &'static str {
+    GLOBAL_NAMES.read().unwrap().names[slot as usize]
+}
+
+/// A VM's global bindings, indexed by slot.
+///
+/// The by-name methods mirror the `HashMap<String, Value>` this replaced, so
+/// embedding code (seeding globals from the interpreter, request locals)
+/// keeps working unchanged; the VM's opcodes use the `*_slot` methods.
+#[derive(Debug, Clone, Default)]
+pub struct Globals {
+    values: Vec<Option<Value>>,
+    len: usize,
+    /// Slots bound by a top-level `const`, which assignment can't rebind.
+    consts: Vec<u32>,
+}
+
+impl Globals {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn get_slot(&self, slot: u32) -> Option<&Value> {
+        self.values.get(slot as usize).and_then(Option::as_ref)
+    }
+
+    #[inline]
+    pub fn get_slot_mut(&mut self, slot: u32) -> Option<&mut Value> {
+        self.values.get_mut(slot as usize).and_then(Option::as_mut)
+    }
+
+    /// Bind `slot`, returning the previous value.
+    pub fn set_slot(&mut self, slot: u32, value: Value) -> Option<Value> {
+        let idx = slot as usize;
+        if idx >= self.values.len() {
+            self.values.resize(idx + 1, None);
+        }
+        let previous = self.values[idx].replace(value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    /// Make `slot` a constant: [`Self::is_const_slot`] answers `true` for it.
+    pub fn mark_const(&mut self, slot: u32) {
+        if !self.consts.contains(&slot) {
+            self.consts.push(slot);
+        }
+    }
+
+    #[inline]
+    pub fn is_const_slot(&self, slot: u32) -> bool {
+        !self.consts.is_empty() && self.consts.contains(&slot)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.get_slot(lookup_slot(name)?)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Value> {
+        self.get_slot_mut(lookup_slot(name)?)
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    pub(crate) fn insert(&mut self, name: impl AsRef<str>, value: Value) -> Option<Value> {
+        self.set_slot(global_slot(name.as_ref()), value)
+    }
+
+    /// Bind global `name` for an embedder, returning the previous value.
+    pub fn define(&mut self, name: &'static str, value: Value) -> Option<Value> {
+        self.set_slot(static_global_slot(name), value)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Value> {
+        let slot = lookup_slot(name)? as usize;
+        let previous = self.values.get_mut(slot)?.take();
+        if previous.is_some() {
+            self.len -= 1;
+        }
+        previous
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Bound `(name, value)` pairs, in slot order.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &Value)> {
+        let names = GLOBAL_NAMES.read().unwrap().names.clone();
+        self.values
+            .iter()
+            .enumerate()
+            .filter_map(move |(slot, v)| v.as_ref().map(|v| (names[slot], v)))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.iter().map(|(name, _)| name)
+    }
+
+    /// Owned bound names, for the compiler entry points that take them.
+    pub fn names(&self) -> impl Iterator<Item = String> + '_ {
+        self.keys().map(str::to_string)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.values.iter().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slots_are_shared_and_stable() {
+        let a = global_slot("globals_test_shared");
+        assert_eq!(global_slot("globals_test_shared"), a);
+        assert_ne!(global_slot("globals_test_other"), a);
+        assert_eq!(global_name(a), "globals_test_shared");
+    }
+
+    #[test]
+    fn by_name_and_by_slot_views_agree() {
+        let mut g = Globals::new();
+        assert!(g.get("globals_test_x").is_none());
+        assert_eq!(g.insert("globals_test_x", Value::Int(1)), None);
+        let slot = global_slot("globals_test_x");
+        assert_eq!(g.get_slot(slot), Some(&Value::Int(1)));
+        *g.get_slot_mut(slot).unwrap() = Value::Int(2);
+        assert_eq!(g.get("globals_test_x"), Some(&Value::Int(2)));
+        assert_eq!(g.len(), 1);
+        assert_eq!(g.keys().collect::<Vec<_>>(), vec!["globals_test_x"]);
+
+        assert_eq!(g.remove("globals_test_x"), Some(Value::Int(2)));
+        assert!(g.is_empty());
+        assert!(!g.contains_key("globals_test_x"));
+        // The slot survives removal, so chunks that resolved it stay valid.
+        assert_eq!(global_slot("globals_test_x"), slot);
+    }
+
+    #[test]
+    fn define_shares_slots_with_insert() {
+        let mut g = Globals::new();
+        assert_eq!(g.define("globals_test_defined", Value::Int(3)), None);
+        assert_eq!(
+            g.get_slot(global_slot("globals_test_defined")),
+            Some(&Value::Int(3))
+        );
+        assert_eq!(
+            g.insert("globals_test_defined", Value::Int(4)),
+            Some(Value::Int(3))
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Per-call-site inline caches for compiled method dispatch.
+//!
+//! Resolving `obj.name(...)` on an instance walks the class chain probing
+//! each class's `vm_methods` map. Each `CallMethod` site remembers the last
+//! receiver class it dispatched on and the method it found, so a loop over
+//! same-class receivers (models in a collection, `this.helper()` inside a
+//! controller action) pays one pointer compare instead of the walk.
+//!
+//! Sites are numbered per chunk (`Chunk::method_site`) and the entries live
+//! on the calling `VmClosure`: classes and closures are `Rc`s, so the cache
+//! can't sit in the `Arc`-shared chunk. Entries hold the receiver class, so
+//! its address can't be reused while cached, and they are stamped with the
+//! method epoch, which `Op::Method` bumps whenever a compiled method is
+//! (re)defined — a subclass gaining an override invalidates every site.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::interpreter::value::Class;
+
+use super::upvalue::VmClosure;
+
+thread_local! {
+    static METHOD_EPOCH: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Invalidate every method inline cache on this thread.
+pub fn bump_method_epoch() {
+    METHOD_EPOCH.with(|e| e.set(e.get().wrapping_add(1)));
+}
+
+fn method_epoch() -> u32 {
+    METHOD_EPOCH.with(Cell::get)
+}
+
+/// The dispatch one call site last resolved.
+#[derive(Clone)]
+pub struct MethodCacheEntry {
+    /// Receiver class the entry is valid for.
+    class: Rc<Class>,
+    /// Whether the receiver was the class itself (a static call).
+    is_static: bool,
+    epoch: u32,
+    /// The resolved method and the class that defines it.
+    pub closure: Rc<VmClosure>,
+    pub defining_class: Rc<Class>,
+}
+
+/// A closure's method caches, indexed by call site.
+#[derive(Clone, Default)]
+pub struct MethodCache {
+    entries: Vec<Option<MethodCacheEntry>>,
+}
+
+impl MethodCache {
+    /// Cached dispatch for `site` if the receiver class still matches.
+    #[inline]
+    pub fn lookup(
+        &self,
+        site: u32,
+        class: &Rc<Class>,
+        is_static: bool,
+    ) -> Option<(Rc<VmClosure>, Rc<Class>)> {
+        let entry = self.entries.get(site as usize)?.as_ref()?;
+        if Rc::ptr_eq(&entry.class, class)
+            && entry.is_static == is_static
+            && entry.epoch == method_epoch()
+        {
+            Some((entry.closure.clone(), entry.defining_class.clone()))
+        } else {
+            None
+        }
+    }
+
+    /// Record the dispatch for `site`, replacing whatever was there.
+    pub fn insert(
+        &mut self,
+        site: u32,
+        class: Rc<Class>,
+        is_static: bool,
+        closure: Rc<VmClosure>,
+        defining_class: Rc<Class>,
+    ) {
+        let idx = site as usize;
+        if idx >= self.entries.len() {
+            self.entries.resize(idx + 1, None);
+        }
+        self.entries[idx] = Some(MethodCacheEntry {
+            class,
+            is_static,
+            epoch: method_epoch(),
+            closure,
+            defining_class,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::chunk::FunctionProto;
+    use std::sync::Arc;
+
+    fn class(name: &str) -> Rc<Class> {
+        Rc::new(Class {
+            name: name.to_string(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn hits_only_for_the_cached_class_and_epoch() {
+        let a = class("A");
+        let b = class("B");
+        let closure = Rc::new(VmClosure::new(
+            Arc::new(FunctionProto::new("m".to_string())),
+            Vec::new(),
+        ));
+        let mut cache = MethodCache::default();
+        assert!(cache.lookup(0, &a, false).is_none());
+
+        cache.insert(3, a.clone(), false, closure, a.clone());
+        assert!(cache.lookup(3, &a, false).is_some());
+        assert!(cache.lookup(3, &b, false).is_none());
+        assert!(cache.lookup(3, &a, true).is_none());
+        assert!(cache.lookup(0, &a, false).is_none());
+
+        bump_method_epoch();
+        assert!(cache.lookup(3, &a, false).is_none());
+    }
+}
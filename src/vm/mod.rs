@@ -1,8 +1,12 @@
 //! Bytecode VM for Soli — compiles AST to bytecode and executes via a stack-based VM.
 //!
-//! Used as the default execution engine in `soli serve` production mode.
-//! Tree-walking interpreter remains for `--dev` mode and REPL.
+//! `soli serve` production mode runs function handlers on it first, and
+//! falls back to the tree-walking interpreter for a handler it fails on
+//! (`failed_handlers`). Scripts, `--dev` mode and the REPL run on the
+//! interpreter; `--vm` runs a script here. The VM doesn't compile generators,
+//! async functions or decorators yet, so it is not the default.
 
+pub mod bytecode;
 pub mod chunk;
 pub mod compiler;
 pub mod compiler_classes;
@@ -11,8 +15,11 @@ pub mod compiler_hoist;
 pub mod compiler_patterns;
 pub mod compiler_stmts;
 pub mod disassembler;
+pub mod globals;
+pub mod inline_cache;
 pub mod method_table;
 pub mod opcode;
+pub(crate) mod shared_code;
 pub mod upvalue;
 #[allow(clippy::module_inception)]
 pub mod vm;
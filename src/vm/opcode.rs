@@ -1,7 +1,7 @@
 //! Bytecode opcodes for the Soli VM.
 
 /// A single bytecode instruction.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum Op {
     // --- Constants & Literals ---
     /// Push a constant from the constant pool onto the stack.
@@ -33,6 +33,16 @@ pub enum Op {
     SetGlobal(u16),
     /// Define a global variable by name constant index.
     DefineGlobal(u16),
+    /// Mark the global (name constant) just defined by `DefineGlobal` as a
+    /// `const`: a later `SetGlobal` of it raises.
+    ConstGlobal(u16),
+    /// Raise the error for assigning to the constant named by the name
+    /// constant; emitted in place of the store to a `const` local.
+    AssignConst(u16),
+    /// Pop a name string and push the global of that name (`null` when
+    /// unset) or, with `true`, whether one is defined — `const_get(name)` and
+    /// `defined(name)` for a name no enclosing local answers.
+    GlobalByName(bool),
 
     // --- Upvalues (closures) ---
     /// Get an upvalue by index.
@@ -86,6 +96,11 @@ pub enum Op {
     // --- Functions ---
     /// Call a function with N arguments.
     Call(u8),
+    /// `Call` in tail position (`return f(...)`, always followed by
+    /// `Return`): a compiled callee reuses the returning frame instead of
+    /// pushing one, so tail recursion runs in constant frame depth. Any
+    /// other callee is called normally and the `Return` hands its result back.
+    TailCall(u8),
     /// Call a function with N argument slots, some of them labelled. The second
     /// operand indexes a `Constant::ArgNames` giving each slot's label (or
     /// `None` for positional). Binding happens at call time, where the callee
@@ -95,6 +110,13 @@ pub enum Op {
     CallNamed(u8, u16),
     /// `New` with labelled argument slots — see [`Op::CallNamed`].
     NewNamed(u8, u16),
+    /// Call a function with N argument slots, some of them `*array` spreads.
+    /// The second operand indexes a `Constant::SpreadArgs`. The spread slots
+    /// are expanded into positional arguments at call time, then the call
+    /// binds like `Call` or `CallNamed`.
+    CallSpread(u8, u16),
+    /// `New` with spread argument slots — see [`Op::CallSpread`].
+    NewSpread(u8, u16),
     /// Create a closure from a function prototype constant index.
     /// Followed by N upvalue descriptors encoded as (is_local: u8, index: u16) in the bytecode.
     Closure(u16),
@@ -104,6 +126,11 @@ pub enum Op {
     // --- Collections ---
     /// Build an array from N elements on the stack.
     Array(u16),
+    /// Build a tuple from N elements on the stack.
+    Tuple(u16),
+    /// Pop a tuple (or array) of exactly N elements and push them in order —
+    /// the `let (a, b) = ...` / `for (k, v) in ...` destructuring step.
+    Unpack(u16),
     /// Push value onto array at top of stack. Stack: [..., array], value on top.
     /// Pops value, leaves array on stack.
     ArrayPush,
@@ -113,15 +140,16 @@ pub enum Op {
     /// and N values on the stack. Used for hash literals with all-literal keys —
     /// avoids pushing/converting keys at runtime.
     HashWithKeys(u16, u16),
-    /// Build a range from two values on the stack.
-    Range,
+    /// Build a range from two ints on the stack; `true` includes the end.
+    Range(bool),
     /// Get element at index: stack has [obj, index].
     GetIndex,
     /// Set element at index: stack has [obj, index, value].
     SetIndex,
     /// Build a string from N parts on the stack (for interpolation).
     BuildString(u16),
-    /// Spread an iterable's elements onto the stack.
+    /// Append the elements of the array or range on top of the stack to the
+    /// array beneath it (an `...expr` element of an array literal).
     Spread,
 
     // --- Objects ---
@@ -135,6 +163,20 @@ pub enum Op {
     Class(u16),
     /// Set up inheritance: stack has [subclass, superclass].
     Inherit,
+    /// Record an `implements` interface (name constant) on the class on top
+    /// of the stack.
+    Implement(u16),
+    /// Mark an instance field (name constant) of the class on top of the
+    /// stack as `const`. Emitted before the class is bound, like `Implement`.
+    DeclareConst(u16),
+    /// Mark a static field (name constant) as `const`.
+    DeclareStaticConst(u16),
+    /// Pop a nested class and register it on the class beneath it under the
+    /// name constant (`Outer::Inner`).
+    NestedClass(u16),
+    /// Push whether the class on top of the stack extends `Model` (its
+    /// class-level DSL statements only run for Model subclasses).
+    IsModelClass,
     /// Add a method to a class. Name from constant index.
     Method(u16),
     /// Add a static method to a class. Name from constant index.
@@ -160,6 +202,9 @@ pub enum Op {
     ConstField(u16),
     /// Add a static const field to a class. Name from constant index.
     StaticConstField(u16),
+    /// Create a struct type. Operands: name constant, `HashKeys` constant of
+    /// the field names in declaration order. Pops a hash of field defaults.
+    Struct(u16, u16),
 
     // --- Exceptions ---
     /// Begin a try block. Operands: catch_offset, finally_offset.
@@ -181,12 +226,20 @@ pub enum Op {
     // --- Iterators ---
     /// Pop iterable, push iterator state.
     GetIter,
+    /// Like `GetIter`, but a hash yields `(key, value)` tuples instead of its
+    /// keys and every other element comes paired with its index — the
+    /// iterator behind `for (k, v) in hash` / `for (i, item) in items`.
+    GetEntryIter,
     /// Pop two ints (start, end), push IterState::Range directly (zero allocation).
-    GetIterRange,
+    /// `true` includes the end.
+    GetIterRange(bool),
     /// Advance iterator or jump to exit offset.
     ForIter(u16),
     /// Specialized ForIter for ranges: inline range check + increment, no method call.
     ForIterRange(u16),
+    /// Drop the innermost iterator state — a `break` or `return` leaving a
+    /// `for` loop before `ForIter` exhausts it.
+    PopIter,
 
     // --- I/O ---
     /// Print N values from the stack.
@@ -202,6 +255,32 @@ pub enum Op {
     /// Stringify value: pops value, pushes string.
     JsonStringify,
 
+    // --- Pattern matching ---
+    /// Pop a value, push whether it satisfies a typed pattern (`n: Int`,
+    /// `p: Point`). Operand: type-name constant index.
+    IsType(u16),
+    /// Pop a value, push whether it is an array of exactly N elements — or at
+    /// least N when the flag is set (a pattern ending in `...rest`).
+    IsArrayLen(u16, bool),
+    /// Pop a value, push whether it is a tuple of exactly N elements.
+    IsTupleLen(u16),
+    /// Pop a value, push whether it is a hash.
+    IsHash,
+    /// Pop a value, push whether it is an instance of exactly the named class
+    /// (`Point { x: px }`). Operand: class-name constant index.
+    IsInstance(u16),
+    /// Pop an instance, push whether the named field is set on it.
+    HasField(u16),
+    /// Pop an instance, push the named field's value (null when unset).
+    InstanceField(u16),
+    /// Pop a value, push whether it is the variant of the named enum with at
+    /// least N payload fields set (`Status.Pending(r)`). Operands: enum-name
+    /// and variant-name constant indices, payload arity.
+    IsEnumVariant(u16, u16, u8),
+    /// Pop an enum instance, push the Nth payload field of the named variant,
+    /// in declaration order.
+    VariantField(u16, u8),
+
     // --- Super-instructions (optimized compound ops) ---
     /// Increment a local integer by 1: local[slot] += 1
     /// Replaces: GetLocal(slot), Constant(1), Add, SetLocal(slot), Pop
@@ -230,6 +309,11 @@ pub enum Op {
     /// Like CallMethod but with a resolved method ID for direct dispatch (no string matching).
     /// Fields: (name_constant_idx, argc, method_id). Falls back to string dispatch for classes.
     CallMethodById(u16, u8, u16),
+    /// Like CallMethod, with some of the argc slots `*array` spreads.
+    /// Fields: (name_constant_idx, argc, layout_idx), the layout being a
+    /// `Constant::SpreadArgs`. The spreads are expanded in place, then the
+    /// call takes CallMethod's general dispatch.
+    CallMethodSpread(u16, u8, u16),
     /// Specialized hash get with a compile-time constant string key.
     HashGetConst(u16),
     /// Specialized hash has_key? with a compile-time constant string key.
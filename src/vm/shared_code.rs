@@ -0,0 +1,172 @@
+//! Compiled bytecode shared by every thread in the process.
+//!
+//! A serve worker's functions and classes are `Rc` values built by its own
+//! interpreter, so each worker has to load the app itself. What those values
+//! point at doesn't have to be per-worker, though: a function's body is the
+//! `Arc`-shared AST of the parsed file (see `serve::unit_cache`), and the
+//! [`FunctionProto`] compiled from it is immutable and `Send + Sync` — the
+//! per-call-site caches live on the worker's `VmClosure`s, not in the chunk.
+//! So the first worker to JIT a body compiles it and the others reuse its
+//! proto, instead of every worker compiling every handler at warmup.
+//!
+//! Entries are keyed by the body's address and hold a clone of the body
+//! `Arc`, so the address can't be reused while the entry lives. A compile
+//! also depends on the function's name and kind and on which global names
+//! the compiler knows, so those are part of the key. An entry whose body no
+//! worker holds any more (a hot reload replaced the file) is purged as the
+//! cache grows.
+//!
+//! Classes and constants are not shared, and this module does not try to.
+//! A `Class` is not an immutable table: `define_method` and `class_eval` add
+//! methods at runtime, static fields are assigned, and the flattened method
+//! caches fill in lazily — all through `Rc<RefCell<…>>`. Constants are
+//! `Value`s, whose arrays, hashes and instances are `Rc`-backed too.
+//! Sharing either would mean a `Send + Sync` value representation, so each
+//! worker still builds its own from the shared AST. What they reference that
+//! is immutable — method bodies and their protos — is shared here.
+
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use crate::ast::Body;
+use crate::interpreter::value::Function;
+
+use super::chunk::FunctionProto;
+
+/// Fewest entries before the cache looks for dead bodies.
+const PURGE_FLOOR: usize = 256;
+
+#[derive(PartialEq, Eq, Hash)]
+struct Key {
+    body: usize,
+    name: String,
+    is_method: bool,
+    has_superclass: bool,
+    globals: u64,
+}
+
+struct Entry {
+    body: Arc<Body>,
+    proto: Arc<FunctionProto>,
+}
+
+#[derive(Default)]
+struct Cache {
+    entries: HashMap<Key, Entry>,
+    purge_at: usize,
+}
+
+static CACHE: LazyLock<Mutex<Cache>> = LazyLock::new(Mutex::default);
+
+fn key(func: &Function, is_method: bool, globals: &[String]) -> Key {
+    // Order-independent: workers list the same globals in different orders.
+    let globals = globals.iter().fold(globals.len() as u64, |acc, name| {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        acc.wrapping_add(hasher.finish())
+    });
+    Key {
+        body: Arc::as_ptr(&func.body) as usize,
+        name: func.name.clone(),
+        is_method,
+        has_superclass: func.defining_superclass.is_some(),
+        globals,
+    }
+}
+
+/// The proto compiled from `func`'s body, compiling it with `compile` if no
+/// thread has yet. `globals` are the names the compile was seeded with.
+/// Compile errors are not cached.
+pub(crate) fn get_or_compile(
+    func: &Function,
+    is_method: bool,
+    globals: &[String],
+    compile: impl FnOnce() -> Result<Arc<FunctionProto>, String>,
+) -> Result<Arc<FunctionProto>, String> {
+    let key = key(func, is_method, globals);
+    if let Some(entry) = CACHE.lock().unwrap().entries.get(&key) {
+        return Ok(Arc::clone(&entry.proto));
+    }
+
+    // Compile without the lock so workers compiling different functions
+    // don't wait on each other.
+    let proto = compile()?;
+    let mut cache = CACHE.lock().unwrap();
+    if cache.entries.len() >= cache.purge_at {
+        cache
+            .entries
+            .retain(|_, entry| Arc::strong_count(&entry.body) > 1);
+        cache.purge_at = (cache.entries.len() * 2).max(PURGE_FLOOR);
+    }
+    let proto = Arc::clone(
+        &cache
+            .entries
+            .entry(key)
+            .or_insert_with(|| Entry {
+                body: Arc::clone(&func.body),
+                proto,
+            })
+            .proto,
+    );
+    Ok(proto)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(name: &str, body: &Arc<Body>) -> Function {
+        Function {
+            name: name.to_string(),
+            body: Arc::clone(body),
+            ..Function::default()
+        }
+    }
+
+    fn proto() -> Result<Arc<FunctionProto>, String> {
+        Ok(Arc::new(FunctionProto::new("test".to_string())))
+    }
+
+    #[test]
+    fn functions_sharing_a_body_share_the_proto() {
+        let body = Arc::new(Body::default());
+        let globals = ["a".to_string(), "b".to_string()];
+        let first = get_or_compile(&function("shared", &body), false, &globals, proto).unwrap();
+
+        // Another worker: its own `Function`, the same body, globals listed
+        // in another order.
+        let reordered = ["b".to_string(), "a".to_string()];
+        let again = get_or_compile(&function("shared", &body), false, &reordered, || {
+            panic!("compiled twice")
+        })
+        .unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+    }
+
+    #[test]
+    fn a_different_compile_context_compiles_again() {
+        let body = Arc::new(Body::default());
+        let first = get_or_compile(&function("context", &body), false, &[], proto).unwrap();
+        let as_method = get_or_compile(&function("context", &body), true, &[], proto).unwrap();
+        let more_globals = get_or_compile(
+            &function("context", &body),
+            false,
+            &["x".to_string()],
+            proto,
+        )
+        .unwrap();
+        assert!(!Arc::ptr_eq(&first, &as_method));
+        assert!(!Arc::ptr_eq(&first, &more_globals));
+    }
+
+    #[test]
+    fn compile_errors_are_not_cached() {
+        let body = Arc::new(Body::default());
+        let failed = get_or_compile(&function("broken", &body), false, &[], || {
+            Err("compile error".to_string())
+        });
+        assert!(failed.is_err());
+        assert!(get_or_compile(&function("broken", &body), false, &[], proto).is_ok());
+    }
+}
@@ -8,17 +8,24 @@ use std::sync::Arc;
 use crate::interpreter::value::Value;
 
 use super::chunk::FunctionProto;
+use super::inline_cache::MethodCache;
 
 /// A VM closure: a function prototype paired with captured upvalues.
 #[derive(Clone)]
 pub struct VmClosure {
     pub proto: Arc<FunctionProto>,
     pub upvalues: Vec<Rc<RefCell<Upvalue>>>,
+    /// Inline caches for the method calls in this closure's body.
+    pub method_cache: RefCell<MethodCache>,
 }
 
 impl VmClosure {
     pub fn new(proto: Arc<FunctionProto>, upvalues: Vec<Rc<RefCell<Upvalue>>>) -> Self {
-        Self { proto, upvalues }
+        Self {
+            proto,
+            upvalues,
+            method_cache: RefCell::default(),
+        }
     }
 }
 
@@ -44,7 +51,7 @@ pub enum Upvalue {
 
 /// Descriptor emitted by the compiler for each upvalue a closure captures.
 /// Used at runtime when creating the closure to wire up the upvalue references.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
 pub struct UpvalueDescriptor {
     /// If true, the upvalue captures a local from the immediately enclosing function.
     /// If false, it captures an upvalue from the enclosing function's upvalue list.
@@ -1,17 +1,20 @@
 //! The bytecode virtual machine — stack-based execution engine.
 
-use ahash::AHashMap as HashMap;
 use ahash::RandomState as AHasher;
 use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::Arc;
 
+use crate::ast::BinaryOp;
 use crate::error::RuntimeError;
-use crate::interpreter::value::{Class, HashKey, HashPairs, StrKey, Value};
+use crate::interpreter::executor::enum_variant_field_names;
+use crate::interpreter::value::{Class, HashKey, HashPairs, RangeValue, StrKey, Value};
+use crate::interpreter::EvalBudget;
 use crate::metrics::VmTimingGuard;
 use crate::span::Span;
 
 use super::chunk::{Constant, FunctionProto};
+use super::globals::Globals;
 use super::opcode::Op;
 use super::upvalue::{Upvalue, VmClosure};
 
@@ -97,6 +100,9 @@ pub struct ExceptionHandler {
     pub stack_depth: usize,
     /// Call frame depth when TryBegin was executed.
     pub frame_depth: usize,
+    /// `for` iterators live when TryBegin was executed; the ones above it
+    /// belong to loops the exception escapes.
+    pub iter_depth: usize,
 }
 
 /// Iterator state for for-in loops.
@@ -105,14 +111,22 @@ pub enum IterState {
     Array {
         values: Rc<RefCell<Vec<Value>>>,
         index: usize,
+        /// Pair each element with its index: `(index, element)`.
+        entries: bool,
     },
     Hash {
         values: Rc<RefCell<HashPairs>>,
         index: usize,
+        /// Yield `(key, value)` tuples rather than keys.
+        entries: bool,
     },
+    /// `end` is exclusive (an inclusive range is stored as `end + 1`).
     Range {
         current: i64,
         end: i64,
+        start: i64,
+        /// Yield `(index, value)` tuples rather than values.
+        entries: bool,
     },
     String {
         s: crate::interpreter::value::SoliStr,
@@ -126,8 +140,8 @@ pub struct Vm {
     pub stack: Vec<Value>,
     /// Call frame stack.
     pub frames: Vec<CallFrame>,
-    /// Global variables.
-    pub globals: HashMap<String, Value>,
+    /// Global variables, indexed by slot (see `vm::globals`).
+    pub globals: Globals,
     /// Open upvalues (pointing to stack slots that are still live).
     pub open_upvalues: Vec<Rc<RefCell<Upvalue>>>,
     /// Exception handler stack.
@@ -142,6 +156,15 @@ pub struct Vm {
     /// need to synchronously invoke a user closure (e.g. array.map); `Op::Return`
     /// treats frames shrinking back to this depth as the exit condition.
     pub return_depth: usize,
+    /// Limits set by [`Vm::set_budget`], spent per loop iteration and call.
+    pub(crate) budget: Option<EvalBudget>,
+    /// Nested calls allowed before a call fails; see [`Vm::set_max_depth`].
+    pub(crate) max_depth: usize,
+    /// A value thrown past every handler of this `run()`, with the text of
+    /// the error carrying it out (see `throw_exception`), so a handler in an
+    /// outer run (across a native callback such as `array.map`) gets it
+    /// back as thrown.
+    pub(crate) escaped_throw: Option<(String, Value)>,
 }
 
 impl Vm {
@@ -149,13 +172,99 @@ impl Vm {
         Self {
             stack: Vec::with_capacity(256),
             frames: Vec::with_capacity(64),
-            globals: HashMap::new(),
+            globals: Globals::new(),
             open_upvalues: Vec::new(),
             exception_handlers: Vec::new(),
             iter_stack: Vec::new(),
             output: Vec::new(),
             failed_handlers: ahash::AHashSet::new(),
             return_depth: 0,
+            budget: None,
+            max_depth: crate::interpreter::executor::max_call_depth_from_env(),
+            escaped_throw: None,
+        }
+    }
+
+    /// Limit nested calls to `depth`, as [`crate::interpreter::Interpreter::set_max_depth`]
+    /// does for the tree-walker: a call past the limit fails with the same
+    /// catchable "maximum call depth exceeded" error instead of growing the
+    /// frame stack until the process runs out of memory.
+    pub fn set_max_depth(&mut self, depth: usize) {
+        self.max_depth = depth.max(1);
+    }
+
+    /// The current call-depth limit.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Fail the call about to push a frame when it would nest deeper than
+    /// `max_depth`. The script's own frame doesn't count.
+    #[inline]
+    pub(crate) fn check_call_depth(&self) -> Result<(), RuntimeError> {
+        if self.frames.len() <= self.max_depth {
+            return Ok(());
+        }
+        Err(self.call_depth_exceeded())
+    }
+
+    /// The error for [`Self::check_call_depth`], with a trace that keeps the
+    /// outermost and innermost frames of the (usually recursive) call run —
+    /// the interpreter's format.
+    #[cold]
+    fn call_depth_exceeded(&self) -> RuntimeError {
+        const KEEP_OUTER: usize = 5;
+        const KEEP_INNER: usize = 15;
+        let mut stack_trace: Vec<String> = self
+            .frames
+            .iter()
+            .skip(1)
+            .map(|frame| {
+                let ip = frame.ip.saturating_sub(1);
+                let line = frame
+                    .closure
+                    .proto
+                    .chunk
+                    .lines
+                    .get(ip)
+                    .copied()
+                    .unwrap_or(0);
+                format!("{} at unknown:{}", frame.closure.proto.name, line)
+            })
+            .collect();
+        if stack_trace.len() > KEEP_OUTER + KEEP_INNER {
+            let omitted = stack_trace.len() - KEEP_OUTER - KEEP_INNER;
+            stack_trace.splice(
+                KEEP_OUTER..KEEP_OUTER + omitted,
+                std::iter::once(format!("... {} more frames ...", omitted)),
+            );
+        }
+        RuntimeError::with_env(
+            format!(
+                "maximum call depth exceeded ({} nested calls)",
+                self.max_depth
+            ),
+            self.current_span(),
+            "{}",
+            stack_trace,
+        )
+    }
+
+    /// Limit the executions that follow to `budget`, or lift the limit with
+    /// `None`. A step is a loop iteration or a call. Running past the budget
+    /// fails with a `RuntimeError` (`is_budget_exceeded()`) that the
+    /// program's own `try`/`catch` can't intercept.
+    pub fn set_budget(&mut self, budget: Option<EvalBudget>) {
+        self.budget = budget.map(EvalBudget::start);
+    }
+
+    /// Spend one budget step. Callers check `self.budget.is_some()` first so
+    /// unlimited runs only pay for that test.
+    #[cold]
+    pub(crate) fn spend_budget(&mut self) -> Result<(), RuntimeError> {
+        match self.budget.as_mut().map(EvalBudget::check) {
+            Some(Err(message)) => Err(RuntimeError::BudgetExceeded(message, self.current_span())),
+            _ => Ok(()),
         }
     }
 
@@ -185,7 +294,19 @@ impl Vm {
         Span::new(0, 0, line, 0)
     }
 
-    /// Read a string constant as an owned String (for global-map keys).
+    /// Global slot named by string constant `idx` of the running chunk.
+    #[inline]
+    fn global_slot(&self, idx: u16) -> u32 {
+        self.frames
+            .last()
+            .unwrap()
+            .closure
+            .proto
+            .chunk
+            .global_slot(idx)
+    }
+
+    /// Read a string constant as an owned String (for error messages).
     #[inline]
     fn read_string_constant_owned(&self, idx: u16) -> String {
         let frame = self.frames.last().unwrap();
@@ -216,11 +337,16 @@ impl Vm {
                 .exception_handlers
                 .last()
                 .is_some_and(|handler| handler.frame_depth > self.return_depth);
-            if !catchable || err.is_engine_fallback() {
+            if !catchable || err.is_engine_fallback() || err.is_budget_exceeded() {
                 return Err(err);
             }
             let span = err.span();
-            self.throw_exception(Value::String(format!("{}", err).into()), span)?;
+            let text = err.to_string();
+            let value = match self.escaped_throw.take() {
+                Some((escaped, value)) if escaped == text => value,
+                _ => Value::String(text.into()),
+            };
+            self.throw_exception(value, span)?;
         }
     }
 
@@ -302,15 +428,8 @@ impl Vm {
                     self.stack[base + slot as usize] = val;
                 }
                 Op::GetGlobal(idx) => {
-                    // Avoid cloning the string constant for lookup
-                    let val = {
-                        let frame = self.frames.last().unwrap();
-                        let name = match &frame.closure.proto.chunk.constants[idx as usize] {
-                            Constant::String(s) => s.as_ref(),
-                            _ => "",
-                        };
-                        self.globals.get(name).cloned()
-                    };
+                    let slot = self.global_slot(idx);
+                    let val = self.globals.get_slot(slot).cloned();
                     match val {
                         Some(v) => self.stack.push(v),
                         None => {
@@ -322,6 +441,38 @@ impl Vm {
                         }
                     }
                 }
+                Op::ConstGlobal(idx) => {
+                    let slot = self.global_slot(idx);
+                    self.globals.mark_const(slot);
+                }
+                Op::AssignConst(idx) => {
+                    let name = self.read_string_constant_owned(idx);
+                    return Err(RuntimeError::type_error(
+                        format!("cannot reassign constant '{}'", name),
+                        self.current_span(),
+                    ));
+                }
+                Op::GlobalByName(defined) => {
+                    let name = match self.stack.pop().unwrap() {
+                        Value::String(s) => s,
+                        other => {
+                            return Err(RuntimeError::General {
+                                message: format!(
+                                    "{}() expects a string, got {}",
+                                    if defined { "defined" } else { "const_get" },
+                                    other.type_name()
+                                ),
+                                span: self.current_span(),
+                            })
+                        }
+                    };
+                    let value = self.globals.get(&name);
+                    self.stack.push(if defined {
+                        Value::Bool(value.is_some())
+                    } else {
+                        value.cloned().unwrap_or(Value::Null)
+                    });
+                }
                 Op::SetGlobal(idx) => {
                     // Bare assignment (`name = value`) creates the binding if it
                     // doesn't exist yet — matching the tree-walker, where a
@@ -330,39 +481,30 @@ impl Vm {
                     // or for in-function assignments to names it knows are
                     // globals; brand-new in-function names become locals instead.
                     let val = self.stack.last().unwrap().clone();
-                    // Avoid cloning the string constant for lookup
-                    let updated = {
-                        let frame = self.frames.last().unwrap();
-                        let name = match &frame.closure.proto.chunk.constants[idx as usize] {
-                            Constant::String(s) => s.as_ref(),
-                            _ => "",
-                        };
-                        if let Some(entry) = self.globals.get_mut(name) {
-                            *entry = val.clone();
-                            true
-                        } else {
-                            false
-                        }
-                    };
-                    if !updated {
+                    let slot = self.global_slot(idx);
+                    if self.globals.is_const_slot(slot) {
                         let name = self.read_string_constant_owned(idx);
+                        return Err(RuntimeError::type_error(
+                            format!("cannot reassign constant '{}'", name),
+                            self.current_span(),
+                        ));
+                    }
+                    if let Some(entry) = self.globals.get_slot_mut(slot) {
+                        *entry = val;
+                    } else if crate::vm::compiler::optional_let_enabled() {
                         // Define-if-absent only when optional-`let` is enabled;
                         // otherwise a bare assignment to an undefined name is a
                         // runtime error (which triggers the interpreter fallback).
-                        if crate::vm::compiler::optional_let_enabled() {
-                            self.globals.insert(name, val);
-                        } else {
-                            return Err(RuntimeError::undefined_variable(
-                                name,
-                                self.current_span(),
-                            ));
-                        }
+                        self.globals.set_slot(slot, val);
+                    } else {
+                        let name = self.read_string_constant_owned(idx);
+                        return Err(RuntimeError::undefined_variable(name, self.current_span()));
                     }
                 }
                 Op::DefineGlobal(idx) => {
-                    let name = self.read_string_constant_owned(idx);
+                    let slot = self.global_slot(idx);
                     let val = self.stack.pop().unwrap();
-                    self.globals.insert(name, val);
+                    self.globals.set_slot(slot, val);
                 }
 
                 Op::GetUpvalue(idx) => {
@@ -494,10 +636,8 @@ impl Vm {
                             crate::interpreter::value::DecimalValue(-d.0, d.1),
                         )),
                         _ => {
-                            return Err(RuntimeError::type_error(
-                                format!("Cannot negate {}", val.type_name()),
-                                self.current_span(),
-                            ));
+                            let result = self.op_negate_instance(&val)?;
+                            self.stack.push(result);
                         }
                     }
                 }
@@ -508,6 +648,7 @@ impl Vm {
                     let result = match (&a, &b) {
                         (Value::Int(x), Value::Int(y)) => x == y,
                         (Value::Bool(x), Value::Bool(y)) => x == y,
+                        (Value::Instance(_), _) => self.op_equal(&a, &b)?,
                         _ => crate::interpreter::value::enum_aware_equal(&a, &b),
                     };
                     self.stack.push(Value::Bool(result));
@@ -517,6 +658,7 @@ impl Vm {
                     let result = match (&a, &b) {
                         (Value::Int(x), Value::Int(y)) => x != y,
                         (Value::Bool(x), Value::Bool(y)) => x != y,
+                        (Value::Instance(_), _) => !self.op_equal(&a, &b)?,
                         _ => !crate::interpreter::value::enum_aware_equal(&a, &b),
                     };
                     self.stack.push(Value::Bool(result));
@@ -528,7 +670,7 @@ impl Vm {
                         (Value::Float(x), Value::Float(y)) => *x < *y,
                         _ => {
                             let span = self.current_span();
-                            self.op_compare_less(&a, &b, span)?
+                            self.op_compare(BinaryOp::Less, &a, &b, span)?
                         }
                     };
                     self.stack.push(Value::Bool(result));
@@ -540,7 +682,7 @@ impl Vm {
                         (Value::Float(x), Value::Float(y)) => *x <= *y,
                         _ => {
                             let span = self.current_span();
-                            self.op_compare_less_equal(&a, &b, span)?
+                            self.op_compare(BinaryOp::LessEqual, &a, &b, span)?
                         }
                     };
                     self.stack.push(Value::Bool(result));
@@ -552,7 +694,7 @@ impl Vm {
                         (Value::Float(x), Value::Float(y)) => *x > *y,
                         _ => {
                             let span = self.current_span();
-                            self.op_compare_less(&b, &a, span)?
+                            self.op_compare(BinaryOp::Greater, &a, &b, span)?
                         }
                     };
                     self.stack.push(Value::Bool(result));
@@ -564,7 +706,7 @@ impl Vm {
                         (Value::Float(x), Value::Float(y)) => *x >= *y,
                         _ => {
                             let span = self.current_span();
-                            self.op_compare_less_equal(&b, &a, span)?
+                            self.op_compare(BinaryOp::GreaterEqual, &a, &b, span)?
                         }
                     };
                     self.stack.push(Value::Bool(result));
@@ -588,6 +730,9 @@ impl Vm {
                 }
                 Op::Loop(offset) => {
                     self.frames.last_mut().unwrap().ip -= offset as usize;
+                    if self.budget.is_some() {
+                        self.spend_budget()?;
+                    }
                 }
                 Op::JumpIfFalseNoPop(offset) => {
                     if !self.stack.last().unwrap().is_truthy() {
@@ -613,8 +758,13 @@ impl Vm {
                     // needed on the cold arity-error branch) and no
                     // call_value double dispatch — this is the hot path for
                     // every compiled function call (e.g. recursion).
-                    if let Value::VmClosure(closure) = &self.stack[callee_idx] {
-                        let closure = closure.clone();
+                    let closure = match &self.stack[callee_idx] {
+                        Value::VmClosure(closure) if !closure.proto.needs_argument_binding() => {
+                            Some(closure.clone())
+                        }
+                        _ => None,
+                    };
+                    if let Some(closure) = closure {
                         let arity = closure.proto.arity as usize;
                         let total_params = closure.proto.param_names.len();
                         if argc < arity || argc > total_params {
@@ -628,12 +778,59 @@ impl Vm {
                             self.stack.push(Value::Null);
                         }
                         let stack_base = self.stack.len() - total_params - 1;
+                        self.check_call_depth()?;
                         self.frames.push(CallFrame::new(
                             closure,
                             stack_base,
                             None,
                             positional_supplied_mask(argc),
                         ));
+                        if self.budget.is_some() {
+                            self.spend_budget()?;
+                        }
+                    } else {
+                        let span = self.current_span();
+                        self.call_value(argc, span)?;
+                    }
+                }
+                Op::TailCall(argc) => {
+                    let argc = argc as usize;
+                    let callee_idx = self.stack.len() - 1 - argc;
+                    let closure = match &self.stack[callee_idx] {
+                        Value::VmClosure(closure) if !closure.proto.needs_argument_binding() => {
+                            Some(closure.clone())
+                        }
+                        _ => None,
+                    };
+                    if let Some(closure) = closure {
+                        let arity = closure.proto.arity as usize;
+                        let total_params = closure.proto.param_names.len();
+                        if argc < arity || argc > total_params {
+                            return Err(RuntimeError::wrong_arity(
+                                total_params,
+                                argc,
+                                self.current_span(),
+                            ));
+                        }
+                        // Finish the current frame as `Return` would, then
+                        // slide the callee and its arguments down into it.
+                        let stack_base = self.frames.last().unwrap().stack_base;
+                        if !self.open_upvalues.is_empty() {
+                            self.close_upvalues(stack_base);
+                        }
+                        self.stack.drain(stack_base..callee_idx);
+                        for _ in argc..total_params {
+                            self.stack.push(Value::Null);
+                        }
+                        *self.frames.last_mut().unwrap() = CallFrame::new(
+                            closure,
+                            stack_base,
+                            None,
+                            positional_supplied_mask(argc),
+                        );
+                        if self.budget.is_some() {
+                            self.spend_budget()?;
+                        }
                     } else {
                         let span = self.current_span();
                         self.call_value(argc, span)?;
@@ -641,14 +838,8 @@ impl Vm {
                 }
                 Op::CallGlobal(name_idx, argc) => {
                     // Combined GetGlobal + Call: lookup global, push, and call in one step
-                    let val = {
-                        let frame = self.frames.last().unwrap();
-                        let name = match &frame.closure.proto.chunk.constants[name_idx as usize] {
-                            Constant::String(s) => s.as_ref(),
-                            _ => "",
-                        };
-                        self.globals.get(name).cloned()
-                    };
+                    let slot = self.global_slot(name_idx);
+                    let val = self.globals.get_slot(slot).cloned();
                     match val {
                         Some(func) => {
                             // Insert the function below the arguments
@@ -774,7 +965,7 @@ impl Vm {
                             }
                         }
                         Value::Instance(_) | Value::Class(_) | Value::VmClosure(_) => {
-                            self.call_method_slow_path(receiver_idx, argc, name)?;
+                            self.call_method_slow_path(receiver_idx, argc, name, true)?;
                         }
                         Value::Array(_) => {
                             // Fast path for common zero-arg array methods
@@ -869,7 +1060,7 @@ impl Vm {
                             self.stack.push(result);
                         }
                         _ => {
-                            self.call_method_slow_path(receiver_idx, argc, name)?;
+                            self.call_method_slow_path(receiver_idx, argc, name, true)?;
                         }
                     }
                 }
@@ -1010,7 +1201,7 @@ impl Vm {
                         }
                         _ => {
                             // Class instances, closures: fall back to property dispatch
-                            self.call_method_slow_path(receiver_idx, argc, name)?;
+                            self.call_method_slow_path(receiver_idx, argc, name, true)?;
                         }
                     }
                 }
@@ -1034,11 +1225,12 @@ impl Vm {
                             self.push(value);
                         }
                         other => {
-                            return Err(RuntimeError::NoSuchProperty {
-                                value_type: other.type_name(),
-                                property: "get".to_string(),
-                                span: self.current_span(),
-                            });
+                            self.call_hash_peephole_fallback(
+                                other,
+                                "get",
+                                vec![Value::String(key.into())],
+                            )?;
+                            continue;
                         }
                     }
                 }
@@ -1057,11 +1249,12 @@ impl Vm {
                             self.push(Value::Bool(hash.borrow().contains_key(&StrKey(key))));
                         }
                         other => {
-                            return Err(RuntimeError::NoSuchProperty {
-                                value_type: other.type_name(),
-                                property: "has_key".to_string(),
-                                span: self.current_span(),
-                            });
+                            self.call_hash_peephole_fallback(
+                                other,
+                                "has_key",
+                                vec![Value::String(key.into())],
+                            )?;
+                            continue;
                         }
                     }
                 }
@@ -1084,11 +1277,12 @@ impl Vm {
                             self.push(value);
                         }
                         other => {
-                            return Err(RuntimeError::NoSuchProperty {
-                                value_type: other.type_name(),
-                                property: "delete".to_string(),
-                                span: self.current_span(),
-                            });
+                            self.call_hash_peephole_fallback(
+                                other,
+                                "delete",
+                                vec![Value::String(key.into())],
+                            )?;
+                            continue;
                         }
                     }
                 }
@@ -1115,11 +1309,12 @@ impl Vm {
                             self.push(Value::Null);
                         }
                         other => {
-                            return Err(RuntimeError::NoSuchProperty {
-                                value_type: other.type_name(),
-                                property: "set".to_string(),
-                                span: self.current_span(),
-                            });
+                            self.call_hash_peephole_fallback(
+                                other,
+                                "set",
+                                vec![Value::String(key.into()), value],
+                            )?;
+                            continue;
                         }
                     }
                 }
@@ -1140,11 +1335,12 @@ impl Vm {
                             .cloned()
                             .unwrap_or(Value::Null),
                         other => {
-                            return Err(RuntimeError::NoSuchProperty {
-                                value_type: other.type_name(),
-                                property: "get".to_string(),
-                                span: self.current_span(),
-                            });
+                            self.call_hash_peephole_fallback(
+                                other.clone(),
+                                "get",
+                                vec![Value::String(key.into())],
+                            )?;
+                            continue;
                         }
                     };
                     self.push(result);
@@ -1162,11 +1358,12 @@ impl Vm {
                     let result = match &self.stack[base + slot as usize] {
                         Value::Hash(hash) => Value::Bool(hash.borrow().contains_key(&StrKey(key))),
                         other => {
-                            return Err(RuntimeError::NoSuchProperty {
-                                value_type: other.type_name(),
-                                property: "has_key".to_string(),
-                                span: self.current_span(),
-                            });
+                            self.call_hash_peephole_fallback(
+                                other.clone(),
+                                "has_key",
+                                vec![Value::String(key.into())],
+                            )?;
+                            continue;
                         }
                     };
                     self.push(result);
@@ -1187,11 +1384,12 @@ impl Vm {
                             .swap_remove(&StrKey(key))
                             .unwrap_or(Value::Null),
                         other => {
-                            return Err(RuntimeError::NoSuchProperty {
-                                value_type: other.type_name(),
-                                property: "delete".to_string(),
-                                span: self.current_span(),
-                            });
+                            self.call_hash_peephole_fallback(
+                                other.clone(),
+                                "delete",
+                                vec![Value::String(key.into())],
+                            )?;
+                            continue;
                         }
                     };
                     self.push(result);
@@ -1217,23 +1415,18 @@ impl Vm {
                             }
                         }
                         other => {
-                            return Err(RuntimeError::NoSuchProperty {
-                                value_type: other.type_name(),
-                                property: "set".to_string(),
-                                span: self.current_span(),
-                            });
+                            self.call_hash_peephole_fallback(
+                                other.clone(),
+                                "set",
+                                vec![Value::String(key.into()), value],
+                            )?;
+                            continue;
                         }
                     }
                     self.push(Value::Null);
                 }
                 Op::HashGetGlobalConst(global_idx, key_idx) => {
-                    let global_name: *const str = {
-                        let frame = self.frames.last().unwrap();
-                        match &frame.closure.proto.chunk.constants[global_idx as usize] {
-                            Constant::String(s) => s.as_ref() as *const str,
-                            _ => "" as *const str,
-                        }
-                    };
+                    let slot = self.global_slot(global_idx);
                     let key: *const str = {
                         let frame = self.frames.last().unwrap();
                         match &frame.closure.proto.chunk.constants[key_idx as usize] {
@@ -1241,9 +1434,8 @@ impl Vm {
                             _ => "" as *const str,
                         }
                     };
-                    let global_name: &str = unsafe { &*global_name };
                     let key: &str = unsafe { &*key };
-                    match self.globals.get(global_name) {
+                    match self.globals.get_slot(slot) {
                         Some(Value::Hash(hash)) => {
                             let value = hash
                                 .borrow()
@@ -1253,28 +1445,23 @@ impl Vm {
                             self.push(value);
                         }
                         Some(other) => {
-                            return Err(RuntimeError::NoSuchProperty {
-                                value_type: other.type_name(),
-                                property: "get".to_string(),
-                                span: self.current_span(),
-                            });
+                            self.call_hash_peephole_fallback(
+                                other.clone(),
+                                "get",
+                                vec![Value::String(key.into())],
+                            )?;
+                            continue;
                         }
                         None => {
                             return Err(RuntimeError::undefined_variable(
-                                global_name.to_string(),
+                                self.read_string_constant_owned(global_idx),
                                 self.current_span(),
                             ));
                         }
                     }
                 }
                 Op::HashHasKeyGlobalConst(global_idx, key_idx) => {
-                    let global_name: *const str = {
-                        let frame = self.frames.last().unwrap();
-                        match &frame.closure.proto.chunk.constants[global_idx as usize] {
-                            Constant::String(s) => s.as_ref() as *const str,
-                            _ => "" as *const str,
-                        }
-                    };
+                    let slot = self.global_slot(global_idx);
                     let key: *const str = {
                         let frame = self.frames.last().unwrap();
                         match &frame.closure.proto.chunk.constants[key_idx as usize] {
@@ -1282,22 +1469,22 @@ impl Vm {
                             _ => "" as *const str,
                         }
                     };
-                    let global_name: &str = unsafe { &*global_name };
                     let key: &str = unsafe { &*key };
-                    let result = match self.globals.get(global_name) {
+                    let result = match self.globals.get_slot(slot) {
                         Some(Value::Hash(hash)) => {
                             Value::Bool(hash.borrow().contains_key(&StrKey(key)))
                         }
                         Some(other) => {
-                            return Err(RuntimeError::NoSuchProperty {
-                                value_type: other.type_name(),
-                                property: "has_key".to_string(),
-                                span: self.current_span(),
-                            });
+                            self.call_hash_peephole_fallback(
+                                other.clone(),
+                                "has_key",
+                                vec![Value::String(key.into())],
+                            )?;
+                            continue;
                         }
                         None => {
                             return Err(RuntimeError::undefined_variable(
-                                global_name.to_string(),
+                                self.read_string_constant_owned(global_idx),
                                 self.current_span(),
                             ));
                         }
@@ -1305,13 +1492,7 @@ impl Vm {
                     self.push(result);
                 }
                 Op::HashDeleteGlobalConst(global_idx, key_idx) => {
-                    let global_name: *const str = {
-                        let frame = self.frames.last().unwrap();
-                        match &frame.closure.proto.chunk.constants[global_idx as usize] {
-                            Constant::String(s) => s.as_ref() as *const str,
-                            _ => "" as *const str,
-                        }
-                    };
+                    let slot = self.global_slot(global_idx);
                     let key: *const str = {
                         let frame = self.frames.last().unwrap();
                         match &frame.closure.proto.chunk.constants[key_idx as usize] {
@@ -1319,9 +1500,8 @@ impl Vm {
                             _ => "" as *const str,
                         }
                     };
-                    let global_name: &str = unsafe { &*global_name };
                     let key: &str = unsafe { &*key };
-                    match self.globals.get(global_name) {
+                    match self.globals.get_slot(slot) {
                         Some(Value::Hash(hash)) => {
                             let value = hash
                                 .borrow_mut()
@@ -1330,15 +1510,16 @@ impl Vm {
                             self.push(value);
                         }
                         Some(other) => {
-                            return Err(RuntimeError::NoSuchProperty {
-                                value_type: other.type_name(),
-                                property: "delete".to_string(),
-                                span: self.current_span(),
-                            });
+                            self.call_hash_peephole_fallback(
+                                other.clone(),
+                                "delete",
+                                vec![Value::String(key.into())],
+                            )?;
+                            continue;
                         }
                         None => {
                             return Err(RuntimeError::undefined_variable(
-                                global_name.to_string(),
+                                self.read_string_constant_owned(global_idx),
                                 self.current_span(),
                             ));
                         }
@@ -1346,13 +1527,7 @@ impl Vm {
                 }
                 Op::HashSetGlobalConst(global_idx, key_idx) => {
                     let value = self.pop();
-                    let global_name: *const str = {
-                        let frame = self.frames.last().unwrap();
-                        match &frame.closure.proto.chunk.constants[global_idx as usize] {
-                            Constant::String(s) => s.as_ref() as *const str,
-                            _ => "" as *const str,
-                        }
-                    };
+                    let slot = self.global_slot(global_idx);
                     let key: *const str = {
                         let frame = self.frames.last().unwrap();
                         match &frame.closure.proto.chunk.constants[key_idx as usize] {
@@ -1360,9 +1535,8 @@ impl Vm {
                             _ => "" as *const str,
                         }
                     };
-                    let global_name: &str = unsafe { &*global_name };
                     let key: &str = unsafe { &*key };
-                    match self.globals.get(global_name) {
+                    match self.globals.get_slot(slot) {
                         Some(Value::Hash(hash)) => {
                             let mut hash_ref = hash.borrow_mut();
                             if let Some((_, _, existing)) = hash_ref.get_full_mut(&StrKey(key)) {
@@ -1372,15 +1546,16 @@ impl Vm {
                             }
                         }
                         Some(other) => {
-                            return Err(RuntimeError::NoSuchProperty {
-                                value_type: other.type_name(),
-                                property: "set".to_string(),
-                                span: self.current_span(),
-                            });
+                            self.call_hash_peephole_fallback(
+                                other.clone(),
+                                "set",
+                                vec![Value::String(key.into()), value],
+                            )?;
+                            continue;
                         }
                         None => {
                             return Err(RuntimeError::undefined_variable(
-                                global_name.to_string(),
+                                self.read_string_constant_owned(global_idx),
                                 self.current_span(),
                             ));
                         }
@@ -1395,7 +1570,18 @@ impl Vm {
                         let mut upvalues = Vec::with_capacity(proto.upvalue_descriptors.len());
 
                         for desc in &proto.upvalue_descriptors {
-                            if desc.is_local {
+                            if proto.captures_by_value {
+                                let frame = self.frames.last().unwrap();
+                                let value = if desc.is_local {
+                                    self.stack[frame.stack_base + desc.index as usize].clone()
+                                } else {
+                                    match &*frame.closure.upvalues[desc.index as usize].borrow() {
+                                        Upvalue::Open(slot) => self.stack[*slot].clone(),
+                                        Upvalue::Closed(v) => v.clone(),
+                                    }
+                                };
+                                upvalues.push(Rc::new(RefCell::new(Upvalue::Closed(value))));
+                            } else if desc.is_local {
                                 let base = self.frames.last().unwrap().stack_base;
                                 let slot = base + desc.index as usize;
                                 let upvalue = self.capture_upvalue(slot);
@@ -1441,6 +1627,18 @@ impl Vm {
                     self.stack
                         .push(Value::Array(Rc::new(RefCell::new(elements))));
                 }
+                Op::Tuple(n) => {
+                    let start = self.stack.len() - n as usize;
+                    let elements = self.stack.split_off(start);
+                    self.stack.push(Value::Tuple(Rc::new(elements)));
+                }
+                Op::Unpack(n) => {
+                    let value = self.stack.pop().unwrap();
+                    let items = value
+                        .destructure(n as usize)
+                        .map_err(|e| RuntimeError::type_error(e, self.current_span()))?;
+                    self.stack.extend(items);
+                }
                 Op::ArrayPush => {
                     let value = self.stack.pop().unwrap();
                     // Array is at stack.len() - 1 (right below the value we just popped)
@@ -1492,14 +1690,12 @@ impl Vm {
                     drop(drained);
                     self.stack.push(Value::Hash(Rc::new(RefCell::new(map))));
                 }
-                Op::Range => {
+                Op::Range(inclusive) => {
                     let (start, end) = self.pop2();
                     match (&start, &end) {
                         (Value::Int(a), Value::Int(b)) => {
-                            // Exclusive of the end, matching the tree-walking
-                            // interpreter's `eval_range` (`start..end`).
-                            let arr: Vec<Value> = (*a..*b).map(Value::Int).collect();
-                            self.stack.push(Value::Array(Rc::new(RefCell::new(arr))));
+                            let range = RangeValue::new(*a, *b, inclusive);
+                            self.stack.push(Value::Range(Rc::new(range)));
                         }
                         _ => {
                             return Err(RuntimeError::type_error(
@@ -1541,7 +1737,26 @@ impl Vm {
                     self.stack.push(Value::String(result.into()));
                 }
                 Op::Spread => {
-                    // Spread is handled by the array/hash/call compilation
+                    let value = self.stack.pop().unwrap();
+                    let items: Vec<Value> = match &value {
+                        Value::Array(items) => items.borrow().clone(),
+                        Value::Range(range) => range.iter().map(Value::Int).collect(),
+                        _ => {
+                            return Err(RuntimeError::type_error(
+                                "cannot spread non-array value",
+                                self.current_span(),
+                            ))
+                        }
+                    };
+                    match self.stack.last() {
+                        Some(Value::Array(arr)) => arr.borrow_mut().extend(items),
+                        _ => {
+                            return Err(RuntimeError::type_error(
+                                "can only push to arrays",
+                                self.current_span(),
+                            ))
+                        }
+                    }
                 }
 
                 // --- Properties ---
@@ -1580,22 +1795,28 @@ impl Vm {
                     // the owned name for the insert anyway.
                     let updated = {
                         let object = self.stack.last().unwrap();
-                        if let Value::Instance(inst) = object {
-                            let frame = self.frames.last().unwrap();
-                            match &frame.closure.proto.chunk.constants[idx as usize] {
-                                Constant::String(name) => {
-                                    let mut inst_mut = inst.borrow_mut();
-                                    if let Some(slot) = inst_mut.fields.get_mut(name.as_ref()) {
-                                        *slot = value.clone();
-                                        true
-                                    } else {
-                                        false
+                        // Const fields and model attributes need the checks
+                        // in op_set_property.
+                        match object {
+                            Value::Instance(inst)
+                                if inst.borrow().class.const_fields.is_empty()
+                                    && !inst.borrow().class.is_model_subclass() =>
+                            {
+                                let frame = self.frames.last().unwrap();
+                                match &frame.closure.proto.chunk.constants[idx as usize] {
+                                    Constant::String(name) => {
+                                        let mut inst_mut = inst.borrow_mut();
+                                        if let Some(slot) = inst_mut.fields.get_mut(name.as_ref()) {
+                                            *slot = value.clone();
+                                            true
+                                        } else {
+                                            false
+                                        }
                                     }
+                                    _ => false,
                                 }
-                                _ => false,
                             }
-                        } else {
-                            false
+                            _ => false,
                         }
                     };
                     if updated {
@@ -1626,6 +1847,36 @@ impl Vm {
                     let span = self.current_span();
                     self.op_inherit(&subclass_val, &superclass_val, span)?;
                 }
+                Op::Implement(idx) => {
+                    let name = self.read_string_constant_owned(idx);
+                    let span = self.current_span();
+                    self.op_implement(name, span)?;
+                }
+                Op::DeclareConst(idx) | Op::DeclareStaticConst(idx) => {
+                    let name = self.read_string_constant_owned(idx);
+                    let is_static = matches!(op, Op::DeclareStaticConst(_));
+                    let span = self.current_span();
+                    self.op_declare_const(name, is_static, span)?;
+                }
+                Op::NestedClass(idx) => {
+                    let name = self.read_string_constant_owned(idx);
+                    let nested = self.stack.pop().unwrap();
+                    if let (Value::Class(parent), Value::Class(nested)) =
+                        (self.stack.last().unwrap(), nested)
+                    {
+                        parent.nested_classes.borrow_mut().insert(name, nested);
+                    }
+                }
+                Op::IsModelClass => {
+                    let is_model = match self.stack.last().unwrap() {
+                        Value::Class(class) => class
+                            .superclass
+                            .as_ref()
+                            .is_some_and(|superclass| superclass.is_model_subclass()),
+                        _ => false,
+                    };
+                    self.stack.push(Value::Bool(is_model));
+                }
                 Op::Method(idx) => {
                     let name = self.read_string_constant_owned(idx);
                     let method = self.stack.pop().unwrap();
@@ -1658,6 +1909,41 @@ impl Vm {
                     let span = self.current_span();
                     self.call_value_named(argc as usize, &labels, span)?;
                 }
+                Op::CallMethodSpread(name_idx, argc, layout_idx) => {
+                    let layout = {
+                        let frame = self.frames.last().unwrap();
+                        match &frame.closure.proto.chunk.constants[layout_idx as usize] {
+                            Constant::SpreadArgs(slots) => slots.clone(),
+                            _ => unreachable!(
+                                "CallMethodSpread must reference a SpreadArgs constant"
+                            ),
+                        }
+                    };
+                    let span = self.current_span();
+                    let receiver_idx = self.stack.len() - 1 - argc as usize;
+                    let (argc, _) = self.expand_spread_arguments(argc as usize, &layout, span)?;
+                    let name = self.read_string_constant_owned(name_idx);
+                    self.call_method_slow_path(receiver_idx, argc, &name, true)?;
+                }
+                Op::CallSpread(argc, layout_idx) | Op::NewSpread(argc, layout_idx) => {
+                    let layout = {
+                        let frame = self.frames.last().unwrap();
+                        match &frame.closure.proto.chunk.constants[layout_idx as usize] {
+                            Constant::SpreadArgs(slots) => slots.clone(),
+                            _ => unreachable!("CallSpread must reference a SpreadArgs constant"),
+                        }
+                    };
+                    let span = self.current_span();
+                    let (argc, labels) =
+                        self.expand_spread_arguments(argc as usize, &layout, span)?;
+                    if labels.iter().any(Option::is_some) {
+                        self.call_value_named(argc, &labels, span)?;
+                    } else if matches!(op, Op::NewSpread(..)) {
+                        self.op_new(argc, span)?;
+                    } else {
+                        self.call_value(argc, span)?;
+                    }
+                }
                 Op::GetThis => {
                     let base = self.frames.last().unwrap().stack_base;
                     let this = self.stack[base].clone();
@@ -1775,6 +2061,30 @@ impl Vm {
                         cls.static_fields.borrow_mut().insert(name, value);
                     }
                 }
+                Op::Struct(name_idx, fields_idx) => {
+                    let name = self.read_string_constant_owned(name_idx);
+                    let defaults = match self.stack.pop().unwrap() {
+                        Value::Hash(hash) => hash,
+                        _ => unreachable!("Struct expects its defaults hash"),
+                    };
+                    let frame = self.frames.last().unwrap();
+                    let fields = match &frame.closure.proto.chunk.constants[fields_idx as usize] {
+                        Constant::HashKeys(keys) => keys
+                            .iter()
+                            .map(|key| crate::interpreter::value_struct::StructField {
+                                name: key.to_string(),
+                                default: defaults.borrow().get(key).cloned(),
+                            })
+                            .collect(),
+                        _ => unreachable!("Struct must reference a HashKeys constant"),
+                    };
+                    let class = Class {
+                        name,
+                        struct_fields: Some(Rc::new(fields)),
+                        ..Default::default()
+                    };
+                    self.stack.push(Value::Class(Rc::new(class)));
+                }
 
                 // --- Exceptions ---
                 Op::TryBegin(catch_offset, finally_offset) => {
@@ -1791,6 +2101,7 @@ impl Vm {
                         finally_ip: frame.ip + finally_offset as usize,
                         stack_depth: self.stack.len(),
                         frame_depth: self.frames.len(),
+                        iter_depth: self.iter_stack.len(),
                     };
                     self.exception_handlers.push(handler);
                 }
@@ -1802,6 +2113,91 @@ impl Vm {
                     let span = self.current_span();
                     self.throw_exception(value, span)?;
                 }
+                // --- Pattern matching ---
+                Op::IsType(name_idx) => {
+                    let type_name = self.read_string_constant_owned(name_idx);
+                    let value = self.stack.pop().unwrap();
+                    self.stack
+                        .push(Value::Bool(typed_pattern_matches(&value, &type_name)));
+                }
+                Op::IsArrayLen(len, at_least) => {
+                    let value = self.stack.pop().unwrap();
+                    let matches = match &value {
+                        Value::Array(arr) => {
+                            let actual = arr.borrow().len();
+                            if at_least {
+                                actual >= len as usize
+                            } else {
+                                actual == len as usize
+                            }
+                        }
+                        _ => false,
+                    };
+                    self.stack.push(Value::Bool(matches));
+                }
+                Op::IsTupleLen(len) => {
+                    let value = self.stack.pop().unwrap();
+                    let matches =
+                        matches!(&value, Value::Tuple(items) if items.len() == len as usize);
+                    self.stack.push(Value::Bool(matches));
+                }
+                Op::IsHash => {
+                    let value = self.stack.pop().unwrap();
+                    self.stack
+                        .push(Value::Bool(matches!(value, Value::Hash(_))));
+                }
+                Op::IsInstance(name_idx) => {
+                    let type_name = self.read_string_constant_owned(name_idx);
+                    let value = self.stack.pop().unwrap();
+                    let matches = match &value {
+                        Value::Instance(inst) => inst.borrow().class.name == type_name,
+                        _ => false,
+                    };
+                    self.stack.push(Value::Bool(matches));
+                }
+                Op::HasField(name_idx) => {
+                    let name = self.read_string_constant_owned(name_idx);
+                    let value = self.stack.pop().unwrap();
+                    let has = match &value {
+                        Value::Instance(inst) => inst.borrow().fields.contains_key(&name),
+                        _ => false,
+                    };
+                    self.stack.push(Value::Bool(has));
+                }
+                Op::InstanceField(name_idx) => {
+                    let name = self.read_string_constant_owned(name_idx);
+                    let value = self.stack.pop().unwrap();
+                    let field = match &value {
+                        Value::Instance(inst) => inst.borrow().fields.get(&name).cloned(),
+                        _ => None,
+                    };
+                    self.stack.push(field.unwrap_or(Value::Null));
+                }
+                Op::IsEnumVariant(enum_idx, variant_idx, arity) => {
+                    let enum_name = self.read_string_constant_owned(enum_idx);
+                    let variant = self.read_string_constant_owned(variant_idx);
+                    let value = self.stack.pop().unwrap();
+                    self.stack.push(Value::Bool(enum_variant_matches(
+                        &value,
+                        &enum_name,
+                        &variant,
+                        arity as usize,
+                    )));
+                }
+                Op::VariantField(variant_idx, index) => {
+                    let variant = self.read_string_constant_owned(variant_idx);
+                    let value = self.stack.pop().unwrap();
+                    let field = match &value {
+                        Value::Instance(inst) => {
+                            let inst = inst.borrow();
+                            enum_variant_field_names(&inst.class, &variant)
+                                .get(index as usize)
+                                .and_then(|name| inst.fields.get(name).cloned())
+                        }
+                        _ => None,
+                    };
+                    self.stack.push(field.unwrap_or(Value::Null));
+                }
                 Op::CatchMatch(name_idx, jump_offset) => {
                     let type_name = self.read_string_constant_owned(name_idx);
                     let matches = match self.stack.last().unwrap() {
@@ -1833,16 +2229,25 @@ impl Vm {
                 Op::GetIter => {
                     let iterable = self.stack.pop().unwrap();
                     let span = self.current_span();
-                    let state = self.create_iterator(iterable, span)?;
+                    let state = self.create_iterator(iterable, false, span)?;
                     self.iter_stack.push(state);
                 }
-                Op::GetIterRange => {
+                Op::GetEntryIter => {
+                    let iterable = self.stack.pop().unwrap();
+                    let span = self.current_span();
+                    let state = self.create_iterator(iterable, true, span)?;
+                    self.iter_stack.push(state);
+                }
+                Op::GetIterRange(inclusive) => {
                     let (start, end) = self.pop2();
                     match (&start, &end) {
                         (Value::Int(a), Value::Int(b)) => {
+                            let range = RangeValue::new(*a, *b, inclusive);
                             self.iter_stack.push(IterState::Range {
-                                current: *a,
-                                end: *b,
+                                current: range.start,
+                                end: range.stop(),
+                                start: range.start,
+                                entries: false,
                             });
                         }
                         _ => {
@@ -1865,7 +2270,7 @@ impl Vm {
                 Op::ForIterRange(exit_offset) => {
                     // Inlined range iteration — no method call, no enum match
                     let state = self.iter_stack.last_mut().unwrap();
-                    if let IterState::Range { current, end } = state {
+                    if let IterState::Range { current, end, .. } = state {
                         // Exclusive of `end`, matching the tree-walker.
                         if *current < *end {
                             let val = Value::Int(*current);
@@ -1879,6 +2284,9 @@ impl Vm {
                         unreachable!("ForIterRange used with non-range iterator");
                     }
                 }
+                Op::PopIter => {
+                    self.iter_stack.pop();
+                }
 
                 // --- I/O ---
                 Op::Print(n) => {
@@ -1909,7 +2317,7 @@ impl Vm {
                         (Value::Float(x), Value::Float(y)) => *x <= *y,
                         _ => {
                             let span = self.current_span();
-                            self.op_compare_less_equal(&a, &b, span)?
+                            self.op_compare(BinaryOp::LessEqual, &a, &b, span)?
                         }
                     };
                     if !result {
@@ -1923,7 +2331,7 @@ impl Vm {
                         (Value::Float(x), Value::Float(y)) => *x < *y,
                         _ => {
                             let span = self.current_span();
-                            self.op_compare_less(&a, &b, span)?
+                            self.op_compare(BinaryOp::Less, &a, &b, span)?
                         }
                     };
                     if !result {
@@ -1992,7 +2400,8 @@ impl Vm {
                         (Value::Float(x), Value::Float(y)) => *x <= *y,
                         _ => {
                             let span = self.current_span();
-                            self.op_compare_less_equal(a, b, span)?
+                            let (a, b) = (a.clone(), b.clone());
+                            self.op_compare(BinaryOp::LessEqual, &a, &b, span)?
                         }
                     };
                     self.stack.push(Value::Bool(result));
@@ -2174,7 +2583,8 @@ impl Vm {
                         (Value::Float(x), Value::Float(y)) => *x < *y,
                         _ => {
                             let span = self.current_span();
-                            self.op_compare_less(a, b, span)?
+                            let (a, b) = (a.clone(), b.clone());
+                            self.op_compare(BinaryOp::Less, &a, &b, span)?
                         }
                     };
                     self.stack.push(Value::Bool(result));
@@ -2188,7 +2598,8 @@ impl Vm {
                         (Value::Float(x), Value::Float(y)) => *x > *y,
                         _ => {
                             let span = self.current_span();
-                            self.op_compare_less(b, a, span)?
+                            let (a, b) = (a.clone(), b.clone());
+                            self.op_compare(BinaryOp::Greater, &a, &b, span)?
                         }
                     };
                     self.stack.push(Value::Bool(result));
@@ -2202,6 +2613,11 @@ impl Vm {
                         (Value::Int(x), Constant::Int(y)) => *x != *y,
                         (Value::Float(x), Constant::Float(y)) => *x != *y,
                         (Value::String(x), Constant::String(y)) => x != y,
+                        (Value::Instance(_), _) => {
+                            let a = local.clone();
+                            let b = constant_to_value(constant);
+                            !self.op_equal(&a, &b)?
+                        }
                         _ => local != &constant_to_value(constant),
                     };
                     self.stack.push(Value::Bool(result));
@@ -2215,6 +2631,11 @@ impl Vm {
                         (Value::Int(x), Constant::Int(y)) => *x == *y,
                         (Value::Float(x), Constant::Float(y)) => *x == *y,
                         (Value::String(x), Constant::String(y)) => x == y,
+                        (Value::Instance(_), _) => {
+                            let a = local.clone();
+                            let b = constant_to_value(constant);
+                            self.op_equal(&a, &b)?
+                        }
                         _ => local == &constant_to_value(constant),
                     };
                     self.stack.push(Value::Bool(result));
@@ -2227,7 +2648,7 @@ impl Vm {
                         (Value::Float(x), Value::Float(y)) => *x > *y,
                         _ => {
                             let span = self.current_span();
-                            self.op_compare_less(&b, &a, span)?
+                            self.op_compare(BinaryOp::Greater, &a, &b, span)?
                         }
                     };
                     if !result {
@@ -2241,7 +2662,7 @@ impl Vm {
                         (Value::Float(x), Value::Float(y)) => *x >= *y,
                         _ => {
                             let span = self.current_span();
-                            self.op_compare_less_equal(&b, &a, span)?
+                            self.op_compare(BinaryOp::GreaterEqual, &a, &b, span)?
                         }
                     };
                     if !result {
@@ -2253,6 +2674,7 @@ impl Vm {
                     let result = match (&a, &b) {
                         (Value::Int(x), Value::Int(y)) => x != y,
                         (Value::Bool(x), Value::Bool(y)) => x != y,
+                        (Value::Instance(_), _) => !self.op_equal(&a, &b)?,
                         _ => a != b,
                     };
                     if !result {
@@ -2356,28 +2778,16 @@ impl Vm {
                     self.stack.push(old_val);
                 }
                 Op::GetGlobalNullCheck(idx) => {
-                    let val = {
-                        let frame = self.frames.last().unwrap();
-                        let name = match &frame.closure.proto.chunk.constants[idx as usize] {
-                            Constant::String(s) => s.as_ref(),
-                            _ => "",
-                        };
-                        self.globals.get(name).cloned()
-                    };
+                    let slot = self.global_slot(idx);
+                    let val = self.globals.get_slot(slot).cloned();
                     match val {
                         Some(v) => self.stack.push(v),
                         None => self.stack.push(Value::Null),
                     };
                 }
                 Op::GetGlobalCall(idx, argc) => {
-                    let val = {
-                        let frame = self.frames.last().unwrap();
-                        let name = match &frame.closure.proto.chunk.constants[idx as usize] {
-                            Constant::String(s) => s.as_ref(),
-                            _ => "",
-                        };
-                        self.globals.get(name).cloned()
-                    };
+                    let slot = self.global_slot(idx);
+                    let val = self.globals.get_slot(slot).cloned();
                     match val {
                         Some(v) => {
                             self.stack.push(v);
@@ -2407,12 +2817,7 @@ impl Vm {
                         Value::Decimal(d) => {
                             Value::Decimal(crate::interpreter::value::DecimalValue(-d.0, d.1))
                         }
-                        _ => {
-                            return Err(RuntimeError::type_error(
-                                format!("Cannot negate {}", val.type_name()),
-                                self.current_span(),
-                            ));
-                        }
+                        _ => self.op_negate_instance(&val)?,
                     };
                     self.stack.push(result);
                 }
@@ -2423,6 +2828,10 @@ impl Vm {
                     let result = match (a, b) {
                         (Value::Int(x), Value::Int(y)) => x == y,
                         (Value::Bool(x), Value::Bool(y)) => x == y,
+                        (Value::Instance(_), _) => {
+                            let (a, b) = (a.clone(), b.clone());
+                            self.op_equal(&a, &b)?
+                        }
                         _ => a == b,
                     };
                     self.stack.push(Value::Bool(result));
@@ -2434,6 +2843,10 @@ impl Vm {
                     let result = match (a, b) {
                         (Value::Int(x), Value::Int(y)) => x != y,
                         (Value::Bool(x), Value::Bool(y)) => x != y,
+                        (Value::Instance(_), _) => {
+                            let (a, b) = (a.clone(), b.clone());
+                            !self.op_equal(&a, &b)?
+                        }
                         _ => a != b,
                     };
                     self.stack.push(Value::Bool(result));
@@ -2595,35 +3008,57 @@ impl Vm {
     fn iter_next(&mut self) -> Option<Value> {
         let state = self.iter_stack.last_mut()?;
         match state {
-            IterState::Array { values, index } => {
+            IterState::Array {
+                values,
+                index,
+                entries,
+            } => {
                 let arr = values.borrow();
                 if *index < arr.len() {
                     let val = arr[*index].clone();
                     *index += 1;
-                    Some(val)
+                    if *entries {
+                        let position = Value::Int(*index as i64 - 1);
+                        Some(Value::Tuple(Rc::new(vec![position, val])))
+                    } else {
+                        Some(val)
+                    }
                 } else {
                     None
                 }
             }
-            IterState::Hash { values, index } => {
+            IterState::Hash {
+                values,
+                index,
+                entries,
+            } => {
                 // Live indexing into the IndexMap (insertion-ordered), matching
                 // the Array variant: no upfront key-vector clone. Mutation
                 // during iteration is observed live, same as arrays.
                 let map = values.borrow();
-                if let Some((key, _)) = map.get_index(*index) {
-                    let key = key.to_value();
-                    *index += 1;
-                    Some(key)
+                let (key, value) = map.get_index(*index)?;
+                *index += 1;
+                if *entries {
+                    Some(Value::Tuple(Rc::new(vec![key.to_value(), value.clone()])))
                 } else {
-                    None
+                    Some(key.to_value())
                 }
             }
-            IterState::Range { current, end } => {
-                // Exclusive of `end`, matching the tree-walker.
+            IterState::Range {
+                current,
+                end,
+                start,
+                entries,
+            } => {
                 if *current < *end {
                     let val = Value::Int(*current);
                     *current += 1;
-                    Some(val)
+                    if *entries {
+                        let position = Value::Int(*current - 1 - *start);
+                        Some(Value::Tuple(Rc::new(vec![position, val])))
+                    } else {
+                        Some(val)
+                    }
                 } else {
                     None
                 }
@@ -2640,15 +3075,33 @@ impl Vm {
         }
     }
 
-    fn create_iterator(&self, iterable: Value, span: Span) -> Result<IterState, RuntimeError> {
+    fn create_iterator(
+        &self,
+        iterable: Value,
+        entries: bool,
+        span: Span,
+    ) -> Result<IterState, RuntimeError> {
         match iterable {
             Value::Array(arr) => Ok(IterState::Array {
                 values: arr,
                 index: 0,
+                entries,
+            }),
+            Value::Tuple(items) => Ok(IterState::Array {
+                values: Rc::new(RefCell::new(items.to_vec())),
+                index: 0,
+                entries,
+            }),
+            Value::Range(range) => Ok(IterState::Range {
+                current: range.start,
+                end: range.stop(),
+                start: range.start,
+                entries,
             }),
             Value::Hash(hash) => Ok(IterState::Hash {
                 values: hash,
                 index: 0,
+                entries,
             }),
             Value::String(s) => Ok(IterState::String { s, byte_offset: 0 }),
             _ => Err(RuntimeError::type_error(
@@ -2660,7 +3113,10 @@ impl Vm {
 
     // --- Arithmetic operations ---
 
-    fn op_add(&self, a: Value, b: Value, span: Span) -> Result<Value, RuntimeError> {
+    fn op_add(&mut self, a: Value, b: Value, span: Span) -> Result<Value, RuntimeError> {
+        if let Some(result) = self.call_binary_operator_method(&a, BinaryOp::Add, &b)? {
+            return Ok(result);
+        }
         match (&a, &b) {
             (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a + b)),
             (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a + b)),
@@ -2683,12 +3139,25 @@ impl Vm {
         }
     }
 
-    fn op_subtract(&self, a: Value, b: Value, span: Span) -> Result<Value, RuntimeError> {
+    fn op_subtract(&mut self, a: Value, b: Value, span: Span) -> Result<Value, RuntimeError> {
+        if let Some(result) = self.call_binary_operator_method(&a, BinaryOp::Subtract, &b)? {
+            return Ok(result);
+        }
         match (&a, &b) {
             (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a - b)),
             (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a - b)),
             (Value::Int(a), Value::Float(b)) => Ok(Value::Float(*a as f64 - b)),
             (Value::Float(a), Value::Int(b)) => Ok(Value::Float(a - *b as f64)),
+            (Value::Array(a), Value::Array(b)) => {
+                let to_remove = b.borrow();
+                let result: Vec<Value> = a
+                    .borrow()
+                    .iter()
+                    .filter(|item| !to_remove.contains(item))
+                    .cloned()
+                    .collect();
+                Ok(Value::Array(Rc::new(RefCell::new(result))))
+            }
             _ => Err(RuntimeError::type_error(
                 format!("Cannot subtract {} from {}", b.type_name(), a.type_name()),
                 span,
@@ -2696,7 +3165,10 @@ impl Vm {
         }
     }
 
-    fn op_multiply(&self, a: Value, b: Value, span: Span) -> Result<Value, RuntimeError> {
+    fn op_multiply(&mut self, a: Value, b: Value, span: Span) -> Result<Value, RuntimeError> {
+        if let Some(result) = self.call_binary_operator_method(&a, BinaryOp::Multiply, &b)? {
+            return Ok(result);
+        }
         match (&a, &b) {
             (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a * b)),
             (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a * b)),
@@ -2712,7 +3184,10 @@ impl Vm {
         }
     }
 
-    fn op_divide(&self, a: Value, b: Value, span: Span) -> Result<Value, RuntimeError> {
+    fn op_divide(&mut self, a: Value, b: Value, span: Span) -> Result<Value, RuntimeError> {
+        if let Some(result) = self.call_binary_operator_method(&a, BinaryOp::Divide, &b)? {
+            return Ok(result);
+        }
         match (&a, &b) {
             (_, Value::Int(0)) => Err(RuntimeError::division_by_zero(span)),
             (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a / b)),
@@ -2738,7 +3213,10 @@ impl Vm {
         }
     }
 
-    fn op_modulo(&self, a: Value, b: Value, span: Span) -> Result<Value, RuntimeError> {
+    fn op_modulo(&mut self, a: Value, b: Value, span: Span) -> Result<Value, RuntimeError> {
+        if let Some(result) = self.call_binary_operator_method(&a, BinaryOp::Modulo, &b)? {
+            return Ok(result);
+        }
         match (&a, &b) {
             (_, Value::Int(0)) => Err(RuntimeError::division_by_zero(span)),
             (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a % b)),
@@ -2752,6 +3230,47 @@ impl Vm {
         }
     }
 
+    /// `-value` for anything but a number: the class's `op_neg`, if any.
+    fn op_negate_instance(&mut self, value: &Value) -> Result<Value, RuntimeError> {
+        self.call_operator_method(value, crate::ast::expr::NEGATE_METHOD, Vec::new())?
+            .ok_or_else(|| {
+                RuntimeError::type_error(
+                    format!("Cannot negate {}", value.type_name()),
+                    self.current_span(),
+                )
+            })
+    }
+
+    /// `a <op> b` for the four ordering operators, through the class's
+    /// overload when `a` is an instance that has one.
+    fn op_compare(
+        &mut self,
+        op: BinaryOp,
+        a: &Value,
+        b: &Value,
+        span: Span,
+    ) -> Result<bool, RuntimeError> {
+        if let Some(result) = self.call_binary_operator_method(a, op, b)? {
+            return Ok(result.is_truthy());
+        }
+        match op {
+            BinaryOp::Less => self.op_compare_less(a, b, span),
+            BinaryOp::LessEqual => self.op_compare_less_equal(a, b, span),
+            BinaryOp::Greater => self.op_compare_less(b, a, span),
+            BinaryOp::GreaterEqual => self.op_compare_less_equal(b, a, span),
+            _ => unreachable!("op_compare called with {}", op),
+        }
+    }
+
+    /// `a == b`, through the class's `op_eq` when `a` is an instance that
+    /// defines one.
+    fn op_equal(&mut self, a: &Value, b: &Value) -> Result<bool, RuntimeError> {
+        match self.call_binary_operator_method(a, BinaryOp::Equal, b)? {
+            Some(result) => Ok(result.is_truthy()),
+            None => Ok(crate::interpreter::value::enum_aware_equal(a, b)),
+        }
+    }
+
     fn op_compare_less(&self, a: &Value, b: &Value, span: Span) -> Result<bool, RuntimeError> {
         match (a, b) {
             (Value::Int(a), Value::Int(b)) => Ok(a < b),
@@ -2763,6 +3282,9 @@ impl Vm {
                 if let (Some(ts_a), Some(ts_b)) = (a.datetime_ts(), b.datetime_ts()) {
                     return Ok(ts_a < ts_b);
                 }
+                if let Some((a, b)) = decimal_operands(a, b) {
+                    return Ok(a < b);
+                }
                 Err(RuntimeError::type_error(
                     format!("Cannot compare {} and {}", a.type_name(), b.type_name()),
                     span,
@@ -2787,6 +3309,9 @@ impl Vm {
                 if let (Some(ts_a), Some(ts_b)) = (a.datetime_ts(), b.datetime_ts()) {
                     return Ok(ts_a <= ts_b);
                 }
+                if let Some((a, b)) = decimal_operands(a, b) {
+                    return Ok(a <= b);
+                }
                 Err(RuntimeError::type_error(
                     format!("Cannot compare {} and {}", a.type_name(), b.type_name()),
                     span,
@@ -2798,11 +3323,16 @@ impl Vm {
     // --- Index operations ---
 
     fn op_get_index(
-        &self,
+        &mut self,
         object: &Value,
         index: &Value,
         span: Span,
     ) -> Result<Value, RuntimeError> {
+        if let Some(result) =
+            self.call_operator_method(object, crate::ast::expr::INDEX_METHOD, vec![index.clone()])?
+        {
+            return Ok(result);
+        }
         match (object, index) {
             (Value::Array(arr), Value::Int(i)) => {
                 let arr = arr.borrow();
@@ -2819,6 +3349,31 @@ impl Vm {
                         span,
                     })
             }
+            (Value::Tuple(items), Value::Int(i)) => {
+                let idx = if *i < 0 {
+                    (items.len() as i64 + i) as usize
+                } else {
+                    *i as usize
+                };
+                items
+                    .get(idx)
+                    .cloned()
+                    .ok_or_else(|| RuntimeError::IndexOutOfBounds {
+                        index: *i,
+                        length: items.len(),
+                        span,
+                    })
+            }
+            (Value::Range(range), Value::Int(i)) => {
+                range
+                    .get(*i)
+                    .map(Value::Int)
+                    .ok_or(RuntimeError::IndexOutOfBounds {
+                        index: *i,
+                        length: range.len(),
+                        span,
+                    })
+            }
             (Value::Hash(hash), key) => {
                 use crate::interpreter::value::{hash_get_value, StrKey};
                 let hash = hash.borrow();
@@ -2865,12 +3420,22 @@ impl Vm {
     }
 
     fn op_set_index(
-        &self,
+        &mut self,
         object: &Value,
         index: &Value,
         value: Value,
         span: Span,
     ) -> Result<(), RuntimeError> {
+        if self
+            .call_operator_method(
+                object,
+                crate::ast::expr::INDEX_SET_METHOD,
+                vec![index.clone(), value.clone()],
+            )?
+            .is_some()
+        {
+            return Ok(());
+        }
         match (object, index) {
             (Value::Array(arr), Value::Int(i)) => {
                 let mut arr = arr.borrow_mut();
@@ -2915,6 +3480,66 @@ impl Default for Vm {
     }
 }
 
+/// The operands of an ordering comparison that involves a decimal, widened to
+/// `f64` the way the tree-walker's `compare_values` does. `None` when neither
+/// side is a decimal or the other side isn't numeric.
+fn decimal_operands(a: &Value, b: &Value) -> Option<(f64, f64)> {
+    use rust_decimal::prelude::ToPrimitive;
+    if !matches!(a, Value::Decimal(_)) && !matches!(b, Value::Decimal(_)) {
+        return None;
+    }
+    let widen = |v: &Value| match v {
+        Value::Decimal(d) => Some(d.value().to_f64().unwrap_or(0.0)),
+        Value::Int(i) => Some(*i as f64),
+        Value::Float(f) => Some(*f),
+        _ => None,
+    };
+    Some((widen(a)?, widen(b)?))
+}
+
+/// Whether `value` satisfies the typed match pattern `_: type_name`. Mirrors
+/// the tree-walker: the primitive names check the variant, anything else the
+/// instance's own class (not its superclasses).
+fn typed_pattern_matches(value: &Value, type_name: &str) -> bool {
+    match type_name {
+        "Int" => matches!(value, Value::Int(_)),
+        "Float" => matches!(value, Value::Float(_)),
+        "Bool" => matches!(value, Value::Bool(_)),
+        "String" => matches!(value, Value::String(_)),
+        "Void" => matches!(value, Value::Null),
+        "Array" => matches!(value, Value::Array(_)),
+        "Hash" => matches!(value, Value::Hash(_)),
+        _ => match value {
+            Value::Instance(inst) => inst.borrow().class.name == type_name,
+            _ => false,
+        },
+    }
+}
+
+/// Whether `value` is the `variant` of the enum named `enum_name` with its
+/// first `arity` payload fields set — what the tree-walker requires before
+/// binding an `Enum.Variant(...)` pattern's payload.
+fn enum_variant_matches(value: &Value, enum_name: &str, variant: &str, arity: usize) -> bool {
+    let Value::Instance(inst) = value else {
+        return false;
+    };
+    let inst = inst.borrow();
+    if inst.class.name != enum_name {
+        return false;
+    }
+    if !matches!(inst.fields.get("__variant"), Some(Value::String(v)) if v.as_str() == variant) {
+        return false;
+    }
+    if arity == 0 {
+        return true;
+    }
+    let names = enum_variant_field_names(&inst.class, variant);
+    names.len() >= arity
+        && names[..arity]
+            .iter()
+            .all(|name| inst.fields.contains_key(name))
+}
+
 /// Check if a class (or any of its superclasses) matches the given name.
 fn class_name_matches(class: &Class, name: &str) -> bool {
     if class.name == name {
@@ -2952,6 +3577,10 @@ fn constant_to_value(constant: &Constant) -> Value {
             // Never loaded as a Value — only consumed by Op::CallNamed/NewNamed.
             unreachable!("ArgNames constant should not be loaded as a Value")
         }
+        Constant::SpreadArgs(_) => {
+            // Never loaded as a Value — only consumed by Op::CallSpread/NewSpread.
+            unreachable!("SpreadArgs constant should not be loaded as a Value")
+        }
     }
 }
 
@@ -2970,11 +3599,11 @@ mod tests {
         let module = Compiler::compile(&program).expect("compile error");
         let mut vm = Vm::new();
         vm.globals.insert(
-            "print".to_string(),
+            "print",
             Value::NativeFunction(NativeFunction::new("print", None, |_| Ok(Value::Null))),
         );
         vm.globals.insert(
-            "puts".to_string(),
+            "puts",
             Value::NativeFunction(NativeFunction::new("puts", None, |_| Ok(Value::Null))),
         );
         vm.execute(&module.main)
@@ -3017,6 +3646,54 @@ mod tests {
         );
     }
 
+    /// Binding and destructuring patterns run natively: each arm binds into
+    /// its own slots and both the success and fail paths leave the stack at
+    /// the subject, so a following `catch` still reads the right slot.
+    #[test]
+    fn test_vm_match_binding_patterns() {
+        let src = "fn f(v) { return match v {\n\
+                   0 => 100,\n\
+                   Int: n if n < 0 => -1,\n\
+                   [] => 0,\n\
+                   [x] => x,\n\
+                   [ [a, b], ...rest] => a + b + rest.len() * 1000,\n\
+                   [x, y, ...rest] => x + y + rest.len() * 1000,\n\
+                   {id: i, ...others} => i + others.len() * 1000,\n\
+                   other => 42 } }\n";
+        for (call, expected) in [
+            ("f(0)", 100),
+            ("f(-4)", -1),
+            ("f(4)", 42),
+            ("f(\"s\")", 42),
+            ("f([])", 0),
+            ("f([7])", 7),
+            ("f([ [1, 2], 3, 4])", 2003),
+            ("f([1, 2, 3])", 1003),
+            ("f({\"id\": 9, \"a\": 1, \"b\": 2})", 2009),
+        ] {
+            assert_eq!(
+                compile_and_get_global(&format!("{src}let x = {call};"), "x"),
+                Value::Int(expected),
+                "{call}"
+            );
+        }
+
+        assert_eq!(
+            compile_and_get_global(
+                "let m = match [1, 2] { [a, b] => a * b }\nlet x = \"unset\"\ntry { throw \"boom\" } catch e { x = e }",
+                "x"
+            ),
+            Value::String("boom".into())
+        );
+    }
+
+    /// No matching arm is an error on both engines, not a silent `null`.
+    #[test]
+    fn test_vm_match_without_matching_arm_throws() {
+        assert!(compile_and_run("let m = match 3 { 1 => \"one\" };").is_err());
+        assert!(compile_and_run("let m = match [1] { [a, b] => a };").is_err());
+    }
+
     /// Parameter defaults must be evaluated by the callee's prologue.
     ///
     /// These previously bound `null`: the compiler counted defaults into
@@ -3126,11 +3803,11 @@ mod tests {
         let module = Compiler::compile(&program).expect("compile error");
         let mut vm = Vm::new();
         vm.globals.insert(
-            "print".to_string(),
+            "print",
             Value::NativeFunction(NativeFunction::new("print", None, |_| Ok(Value::Null))),
         );
         vm.globals.insert(
-            "puts".to_string(),
+            "puts",
             Value::NativeFunction(NativeFunction::new("puts", None, |_| Ok(Value::Null))),
         );
         vm.execute(&module.main).expect("vm error");
@@ -3167,7 +3844,7 @@ mod tests {
 
         let mut vm = Vm::new();
         vm.globals.insert(
-            "__sdql_exec".to_string(),
+            "__sdql_exec",
             Value::NativeFunction(NativeFunction::new("__sdql_exec", None, move |args| {
                 let query = match args.first() {
                     Some(Value::String(s)) => s.as_ref().to_string(),
@@ -3203,6 +3880,56 @@ mod tests {
         assert_eq!(result, Value::Int(15));
     }
 
+    #[test]
+    fn test_vm_failed_and_guard_falls_through_to_the_next_arm() {
+        // The guard's last comparison and its `JumpIfFalse` are also the
+        // target of the `&&` short-circuit, so they must not be fused.
+        let result = compile_and_get_global(
+            "fn f(p) { return match p { [x, y] if x > 0 && y > 0 => \"first\", [_, _] => \"elsewhere\", _ => \"no\" } }\nlet r = f([-1, 2]);",
+            "r",
+        );
+        assert_eq!(result, Value::String("elsewhere".into()));
+    }
+
+    #[test]
+    fn test_vm_call_depth_limit_is_catchable() {
+        let result = compile_and_get_global(
+            "fn deep(n) { return 1 + deep(n + 1) }\nlet r = \"none\"\ntry { deep(0) } catch e { r = e }",
+            "r",
+        );
+        assert!(
+            result.to_string().contains("maximum call depth exceeded"),
+            "{}",
+            result
+        );
+    }
+
+    #[test]
+    fn test_vm_let_redeclaration_rebinds_the_local() {
+        let result = compile_and_get_global(
+            "fn f() { let x = 1; let g = fn() { x }; let x = 2; return g() }\nlet r = f();",
+            "r",
+        );
+        assert_eq!(result, Value::Int(2));
+    }
+
+    #[test]
+    fn test_vm_const_reassignment_throws() {
+        for source in [
+            "fn f() { const V = 1; V = 2 }\nf();",
+            "fn f() { const V = 1; let g = fn() { V = 2 }; g() }\nf();",
+            "const G = 1;\nG = 2;",
+        ] {
+            let err = compile_and_run(source).expect_err(source);
+            assert!(
+                err.to_string().contains("cannot reassign constant"),
+                "{}: {}",
+                source,
+                err
+            );
+        }
+    }
+
     #[test]
     fn test_vm_if_else() {
         let result = compile_and_get_global(
@@ -3317,6 +4044,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_vm_array_spread_splices_elements() {
+        let result =
+            compile_and_get_global("let a = [1, 2]; let x = [0, ...a, ...(3..5), 9];", "x");
+        let expected: Vec<Value> = [0, 1, 2, 3, 4, 9].into_iter().map(Value::Int).collect();
+        match result {
+            Value::Array(arr) => assert_eq!(*arr.borrow(), expected),
+            other => panic!("Expected array, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_vm_loop_sum_10000() {
         let result = compile_and_get_global(
@@ -3721,7 +4459,7 @@ mod tests {
             let program = Parser::new(tokens).parse().expect("parser error");
             let module = Compiler::compile(&program).expect("compile error");
             let mut vm = Vm::new();
-            vm.globals.insert("obj".to_string(), obj.clone());
+            vm.globals.insert("obj", obj.clone());
             vm.execute(&module.main).expect("vm error");
             assert_eq!(vm.globals.get("x"), Some(&Value::Int(7)), "{}", source);
         }
@@ -3733,7 +4471,7 @@ mod tests {
         let program = Parser::new(tokens).parse().expect("parser error");
         let module = Compiler::compile(&program).expect("compile error");
         let mut vm = Vm::new();
-        vm.globals.insert("obj".to_string(), obj.clone());
+        vm.globals.insert("obj", obj.clone());
         vm.execute(&module.main).expect("vm error");
         assert_eq!(vm.globals.get("x"), Some(&Value::Int(10)));
 
@@ -3759,7 +4497,7 @@ mod tests {
         let program = Parser::new(tokens).parse().expect("parser error");
         let module = Compiler::compile(&program).expect("compile error");
         let mut vm = Vm::new();
-        vm.globals.insert("obj".to_string(), obj);
+        vm.globals.insert("obj", obj);
         vm.execute(&module.main).expect("vm error");
         assert_eq!(vm.globals.get("x"), Some(&Value::Int(42)));
     }
@@ -3800,8 +4538,7 @@ mod tests {
         let program = Parser::new(tokens).parse().expect("parser error");
         let module = Compiler::compile(&program).expect("compile error");
         let mut vm = Vm::new();
-        vm.globals
-            .insert("User".to_string(), Value::Class(user_class));
+        vm.globals.insert("User", Value::Class(user_class));
         vm.execute(&module.main).expect("vm error");
         assert_eq!(vm.globals.get("x"), Some(&Value::String("User".into())));
     }
@@ -3844,7 +4581,7 @@ mod tests {
             let program = Parser::new(tokens).parse().expect("parser error");
             let module = Compiler::compile(&program).expect("compile error");
             let mut vm = Vm::new();
-            vm.globals.insert("record".to_string(), record.clone());
+            vm.globals.insert("record", record.clone());
             let result = vm.execute(&module.main);
             match result {
                 Err(err) => assert!(err.is_engine_fallback(), "{}: {}", source, err),
@@ -4969,4 +5706,96 @@ mod tests {
             Value::Int(3)
         );
     }
+
+    /// A method-call site caches the last receiver class it dispatched on;
+    /// alternating receiver classes (and a static call on the class itself)
+    /// through one site must still resolve each class's own method.
+    #[test]
+    fn test_vm_method_inline_cache_follows_receiver_class() {
+        let source = "class A { fn who() { return \"a\" } static fn who() { return \"A\" } }
+class B extends A { fn who() { return \"b\" } }
+class C extends A { }
+let out = \"\"
+for r in [new A(), new B(), new C(), new B(), A] { out = out + r.who() }";
+        assert_eq!(
+            compile_and_get_global(source, "out"),
+            Value::String("ababA".into())
+        );
+    }
+
+    /// `return f(...)` reuses the frame: the recursion depth here would leave
+    /// a frame per level otherwise, and a default still fills in on each round.
+    #[test]
+    fn test_vm_tail_call_reuses_frame() {
+        let source = "fn count(n, step = 1, acc = 0) { if n <= 0 { return acc }
+  return count(n - step, step, acc + 1) }
+let deep = count(1000000)
+let stepped = count(10, 3)";
+        assert_eq!(compile_and_get_global(source, "deep"), Value::Int(1000000));
+        assert_eq!(compile_and_get_global(source, "stepped"), Value::Int(4));
+
+        let tokens = Scanner::new(source).scan_tokens().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let module = Compiler::compile(&program).unwrap();
+        let count = module
+            .main
+            .chunk
+            .constants
+            .iter()
+            .find_map(|c| match c {
+                Constant::Function(proto) if proto.name == "count" => Some(proto.clone()),
+                _ => None,
+            })
+            .unwrap();
+        assert!(count.chunk.code.contains(&Op::TailCall(3)));
+    }
+
+    fn run_with_budget(source: &str, budget: EvalBudget) -> Result<Value, RuntimeError> {
+        let tokens = Scanner::new(source).scan_tokens().expect("lexer error");
+        let program = Parser::new(tokens).parse().expect("parser error");
+        let module = Compiler::compile(&program).expect("compile error");
+        let mut vm = Vm::new();
+        vm.set_budget(Some(budget));
+        vm.execute(&module.main)
+    }
+
+    #[test]
+    fn budget_stops_an_infinite_loop() {
+        let err = run_with_budget(
+            "let i = 0\nwhile (true) { i = i + 1 }",
+            EvalBudget::default().with_max_steps(10_000),
+        )
+        .unwrap_err();
+        assert!(err.is_budget_exceeded(), "{}", err);
+        assert!(err.to_string().contains("step limit (10000)"), "{}", err);
+    }
+
+    #[test]
+    fn budget_stops_tail_recursion_and_ignores_try_catch() {
+        // A `return` inside `try` isn't a tail call, so this recursion nests;
+        // once past the call-depth limit, whose error a `catch` may handle,
+        // it retries from the top until the time budget runs out.
+        let err = run_with_budget(
+            "fn spin(n) { try { return spin(n + 1) } catch e { return spin(0) } }\nspin(0)",
+            EvalBudget::default().with_timeout(std::time::Duration::from_millis(50)),
+        )
+        .unwrap_err();
+        assert!(err.is_budget_exceeded(), "{}", err);
+
+        let err = run_with_budget(
+            "fn spin(n) { return spin(n + 1) }\nspin(0)",
+            EvalBudget::default().with_max_steps(500),
+        )
+        .unwrap_err();
+        assert!(err.is_budget_exceeded(), "{}", err);
+    }
+
+    #[test]
+    fn budget_leaves_finishing_code_alone() {
+        run_with_budget(
+            "let total = 0\nfor (i in 0..100) { total = total + i }",
+            EvalBudget::default().with_max_steps(1_000),
+        )
+        .unwrap();
+    }
 }
@@ -430,16 +430,57 @@ impl Vm {
                 Ok(max.clone())
             }
             "sort" => {
-                if !args.is_empty() {
-                    return Err(RuntimeError::wrong_arity(0, args.len(), span));
+                if args.len() > 1 {
+                    return Err(RuntimeError::wrong_arity(1, args.len(), span));
                 }
+                // Sort a snapshot: a comparator may mutate the receiver.
                 let mut sorted = arr.borrow().clone();
+                if let Some(cb) = args.first() {
+                    if !matches!(cb, Value::Function(_) | Value::VmClosure(_)) {
+                        return Err(RuntimeError::type_error(
+                            "sort expects a function argument",
+                            span,
+                        ));
+                    }
+                    // The comparator's sign orders the pair; anything but a
+                    // number leaves it as is. The first error stops the
+                    // comparisons and is reported once the sort unwinds.
+                    let mut failure = None;
+                    let batch = self.enter_callable_batch();
+                    sorted.sort_by(|a, b| {
+                        if failure.is_some() {
+                            return std::cmp::Ordering::Equal;
+                        }
+                        match self.invoke_in_batch_two(&batch, cb, a.clone(), b.clone(), span) {
+                            Ok(Value::Int(n)) => n.cmp(&0),
+                            Ok(Value::Float(n)) => {
+                                n.partial_cmp(&0.0).unwrap_or(std::cmp::Ordering::Equal)
+                            }
+                            Ok(_) => std::cmp::Ordering::Equal,
+                            Err(e) => {
+                                failure = Some(e);
+                                std::cmp::Ordering::Equal
+                            }
+                        }
+                    });
+                    self.exit_callable_batch(batch);
+                    if let Some(e) = failure {
+                        return Err(e);
+                    }
+                    return Ok(Value::Array(Rc::new(RefCell::new(sorted))));
+                }
                 sorted.sort_by(|a, b| match (a, b) {
                     (Value::Int(x), Value::Int(y)) => x.cmp(y),
                     (Value::Float(x), Value::Float(y)) => {
                         x.partial_cmp(y).unwrap_or(std::cmp::Ordering::Equal)
                     }
                     (Value::String(x), Value::String(y)) => x.cmp(y),
+                    (Value::Int(x), Value::Float(y)) => (*x as f64)
+                        .partial_cmp(y)
+                        .unwrap_or(std::cmp::Ordering::Equal),
+                    (Value::Float(x), Value::Int(y)) => x
+                        .partial_cmp(&(*y as f64))
+                        .unwrap_or(std::cmp::Ordering::Equal),
                     _ => std::cmp::Ordering::Equal,
                 });
                 Ok(Value::Array(Rc::new(RefCell::new(sorted))))
@@ -868,7 +909,7 @@ impl Vm {
                     return Ok(Value::Int(arr.borrow().len() as i64));
                 }
                 if args.len() == 1 {
-                    if let Value::Function(_) = &args[0] {
+                    if matches!(&args[0], Value::Function(_) | Value::VmClosure(_)) {
                         let cb = args[0].clone();
                         let len = arr.borrow().len();
                         let mut count = 0i64;
@@ -1029,8 +1070,9 @@ impl Vm {
                 }
                 use rand::seq::SliceRandom;
                 let items = arr.borrow();
-                let mut rng = rand::thread_rng();
-                Ok(items.choose(&mut rng).cloned().unwrap_or(Value::Null))
+                Ok(crate::interpreter::builtins::rng::with_rng(|rng| {
+                    items.choose(rng).cloned().unwrap_or(Value::Null)
+                }))
             }
             "shuffle" => {
                 if !args.is_empty() {
@@ -1038,8 +1080,7 @@ impl Vm {
                 }
                 use rand::seq::SliceRandom;
                 let mut result = arr.borrow().clone();
-                let mut rng = rand::thread_rng();
-                result.shuffle(&mut rng);
+                crate::interpreter::builtins::rng::with_rng(|rng| result.shuffle(rng));
                 Ok(Value::Array(Rc::new(RefCell::new(result))))
             }
             "zip" => {
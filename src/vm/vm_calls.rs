@@ -1,23 +1,30 @@
 //! Function call dispatch for the VM.
 
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::sync::Arc;
 
 use crate::ast::stmt::{FunctionDecl, Program, Stmt, StmtKind};
+use crate::ast::BinaryOp;
 use crate::error::RuntimeError;
+use crate::interpreter::executor::calls::range_methods::call_range_method_impl;
 use crate::interpreter::value::{Class, Function, Instance, NativeFunction, Value};
+use crate::interpreter::value_struct::StructValue;
 use crate::span::Span;
 
-use super::chunk::{Constant, FunctionProto};
+use super::chunk::{ArgSlot, Constant, FunctionProto};
 use super::compiler::Compiler;
+use super::shared_code;
 use super::upvalue::VmClosure;
 use super::vm::{CallFrame, Vm};
 
 /// JIT-compile a tree-walking [`Function`] to a bytecode [`FunctionProto`] and
 /// cache it in `func.jit_cache`. Returns the cached proto on a hit, otherwise
-/// compiles, stores, and returns it. Pure compilation — no execution, no side
-/// effects — so it is safe to call ahead of time to warm a worker's handlers.
+/// takes it from the process-wide [`shared_code`] cache (another worker
+/// compiled the same body) or compiles it, stores, and returns it. Pure
+/// compilation — no execution, no side effects — so it is safe to call ahead
+/// of time to warm a worker's handlers.
 pub(crate) fn jit_compile_function<I: IntoIterator<Item = String>>(
     func: &Function,
     globals: I,
@@ -26,36 +33,42 @@ pub(crate) fn jit_compile_function<I: IntoIterator<Item = String>>(
         return Ok(proto);
     }
 
-    let func_decl = FunctionDecl {
-        name: func.name.clone(),
-        params: func.params.to_vec(),
-        return_type: None,
-        body: func.body.to_vec(),
-        span: func.span.unwrap_or_default(),
-    };
-
-    let program = Program::new(vec![Stmt {
-        kind: StmtKind::function(func_decl),
-        span: func.span.unwrap_or_default(),
-        source_path: None,
-    }]);
-
-    let module = Compiler::compile_with_globals(&program, globals).map_err(|e| e.to_string())?;
-
-    // Extract the compiled FunctionProto from the module's constant pool.
-    let proto = module
-        .main
-        .chunk
-        .constants
-        .iter()
-        .find_map(|c| {
-            if let Constant::Function(p) = c {
-                Some(p.clone())
-            } else {
-                None
-            }
-        })
-        .ok_or_else(|| "Failed to extract compiled function from JIT".to_string())?;
+    let globals: Vec<String> = globals.into_iter().collect();
+    let proto = shared_code::get_or_compile(func, false, &globals, || {
+        let func_decl = FunctionDecl {
+            name: func.name.clone(),
+            params: func.params.to_vec(),
+            return_type: None,
+            body: Arc::clone(&func.body),
+            decorators: Vec::new(),
+            is_async: false,
+            span: func.span.unwrap_or_default(),
+        };
+
+        let program = Program::new(vec![Stmt {
+            kind: StmtKind::function(func_decl),
+            span: func.span.unwrap_or_default(),
+            source_path: None,
+        }]);
+
+        let module = Compiler::compile_with_globals(&program, globals.iter().cloned())
+            .map_err(|e| e.to_string())?;
+
+        // Extract the compiled FunctionProto from the module's constant pool.
+        module
+            .main
+            .chunk
+            .constants
+            .iter()
+            .find_map(|c| {
+                if let Constant::Function(p) = c {
+                    Some(p.clone())
+                } else {
+                    None
+                }
+            })
+            .ok_or_else(|| "Failed to extract compiled function from JIT".to_string())
+    })?;
 
     *func.jit_cache.borrow_mut() = Some(proto.clone());
     Ok(proto)
@@ -74,21 +87,28 @@ pub(crate) fn jit_compile_method<I: IntoIterator<Item = String>>(
     if let Some(proto) = func.jit_cache.borrow().clone() {
         return Ok(proto);
     }
-    let proto = Compiler::compile_method_standalone(func, globals).map_err(|e| e.to_string())?;
-    let arc = std::sync::Arc::new(proto);
-    *func.jit_cache.borrow_mut() = Some(arc.clone());
-    Ok(arc)
+    let globals: Vec<String> = globals.into_iter().collect();
+    let proto = shared_code::get_or_compile(func, true, &globals, || {
+        Compiler::compile_method_standalone(func, globals.iter().cloned())
+            .map(Arc::new)
+            .map_err(|e| e.to_string())
+    })?;
+    *func.jit_cache.borrow_mut() = Some(proto.clone());
+    Ok(proto)
 }
 
 /// Lay labelled arguments out in parameter order for `proto`, returning the
 /// slot values and a mask of which parameters were actually supplied.
 ///
-/// Follows the tree-walking interpreter's rules: positional arguments fill the
-/// leading parameters, labelled arguments fill by name, an unknown label is an
-/// undefined-variable error, and a parameter that ends up unfilled is an arity
-/// error unless it declares a default. Unfilled defaulted slots are left null
-/// here and written by the callee's prologue, so a default expression is
-/// evaluated in the callee — and only when it is actually needed.
+/// Follows the tree-walking interpreter's rules (`calls::arguments`):
+/// positional arguments fill the parameters that aren't keyword-only, in
+/// order, with any extras collected into the `...rest` parameter's array;
+/// labelled arguments fill the others by name; a label that names no parameter,
+/// or one already passed by position, is an error; and a parameter that ends
+/// up unfilled is an error unless it declares a default. Unfilled defaulted
+/// slots are left null here and written by the callee's prologue, so a
+/// default expression is evaluated in the callee — and only when it is
+/// actually needed.
 fn bind_named_arguments(
     proto: &FunctionProto,
     positional: Vec<Value>,
@@ -96,23 +116,34 @@ fn bind_named_arguments(
     span: Span,
 ) -> Result<(Vec<Value>, u64), RuntimeError> {
     let total_params = proto.param_names.len();
+    let bit = |index: usize| if index < 64 { 1u64 << index } else { 0 };
+    let keyword_only = |index: usize| proto.keyword_only_mask & bit(index) != 0;
 
-    if positional.len() > total_params {
+    let positional_slots: Vec<usize> = (0..total_params)
+        .filter(|&i| !keyword_only(i) && proto.variadic_index != Some(i))
+        .collect();
+    if positional.len() > positional_slots.len() && proto.variadic_index.is_none() {
         return Err(RuntimeError::wrong_arity(
-            total_params,
-            positional.len() + named.len(),
+            positional_slots.len(),
+            positional.len(),
             span,
         ));
     }
 
     let mut slots = vec![Value::Null; total_params];
     let mut supplied = 0u64;
+    let mut filled = vec![false; total_params];
 
-    for (i, value) in positional.into_iter().enumerate() {
-        slots[i] = value;
-        if i < 64 {
-            supplied |= 1u64 << i;
-        }
+    let mut positional = positional.into_iter();
+    for (&index, value) in positional_slots.iter().zip(positional.by_ref()) {
+        slots[index] = value;
+        filled[index] = true;
+        supplied |= bit(index);
+    }
+    if let Some(index) = proto.variadic_index {
+        slots[index] = Value::Array(Rc::new(RefCell::new(positional.collect())));
+        filled[index] = true;
+        supplied |= bit(index);
     }
 
     for (name, value) in named {
@@ -120,42 +151,51 @@ fn bind_named_arguments(
             .param_names
             .iter()
             .position(|p| p.as_str() == name.as_ref())
+            .filter(|&i| proto.variadic_index != Some(i))
         else {
-            // Same error the interpreter raises for `f(nope: 1)`.
-            return Err(RuntimeError::undefined_variable(name.to_string(), span));
+            return Err(RuntimeError::type_error(
+                format!("{}() has no parameter named '{}'", proto.name, name),
+                span,
+            ));
         };
-        // A label that names an already-positionally-filled parameter is
-        // dropped, and the positional value wins. That is not an obviously good
-        // rule — arguably `f(3, a: 9)` should be an error — but it is what the
-        // tree-walking interpreter does (`used_params.contains(...) { continue }`
-        // in `call_value_with_named`), and the engines must agree.
-        if index < 64 && supplied & (1u64 << index) != 0 {
-            continue;
+        if filled[index] {
+            return Err(RuntimeError::type_error(
+                format!(
+                    "{}() got argument '{}' both by position and by name",
+                    proto.name, name
+                ),
+                span,
+            ));
         }
         slots[index] = value;
-        if index < 64 {
-            supplied |= 1u64 << index;
-        }
+        filled[index] = true;
+        supplied |= bit(index);
     }
 
     // Anything still unfilled must have a default to fall back on.
     for index in 0..total_params {
-        let filled = index >= 64 || supplied & (1u64 << index) != 0;
-        let has_default = index >= 64 || proto.defaults_mask & (1u64 << index) != 0;
-        if !filled && !has_default {
-            // Report how many parameters were bound before this one, matching
-            // the interpreter's `final_args.len()` at the point it gives up.
-            let bound_before = if index >= 64 {
-                index
-            } else {
-                (supplied & ((1u64 << index) - 1)).count_ones() as usize
-            };
-            return Err(RuntimeError::wrong_arity(
-                proto.arity as usize,
-                bound_before,
+        let has_default = index >= 64 || proto.defaults_mask & bit(index) != 0;
+        if filled[index] || has_default {
+            continue;
+        }
+        if keyword_only(index) {
+            return Err(RuntimeError::type_error(
+                format!(
+                    "{}() is missing keyword argument '{}'",
+                    proto.name, proto.param_names[index]
+                ),
                 span,
             ));
         }
+        let required = (0..total_params.min(64))
+            .filter(|&i| {
+                proto.defaults_mask & bit(i) == 0
+                    && !keyword_only(i)
+                    && proto.variadic_index != Some(i)
+            })
+            .count();
+        let bound_before = filled[..index].iter().filter(|f| **f).count();
+        return Err(RuntimeError::wrong_arity(required, bound_before, span));
     }
 
     Ok((slots, supplied))
@@ -211,6 +251,9 @@ impl Vm {
         span: Span,
         class: Option<Rc<Class>>,
     ) -> Result<(), RuntimeError> {
+        if closure.proto.needs_argument_binding() {
+            return self.call_closure_binding_positional(closure, argc, class, span);
+        }
         let arity = closure.proto.arity as usize;
         let total_params = closure.proto.param_names.len();
 
@@ -230,16 +273,39 @@ impl Vm {
 
         let stack_base = self.stack.len() - total_params - 1;
 
+        self.check_call_depth()?;
         self.frames.push(CallFrame::new(
             closure,
             stack_base,
             class,
             crate::vm::vm::positional_supplied_mask(argc),
         ));
+        if self.budget.is_some() {
+            self.spend_budget()?;
+        }
 
         Ok(())
     }
 
+    /// Positional call of a function with keyword-only parameters, which the
+    /// positional arguments must skip, or a `...rest` parameter, which
+    /// collects the extras: bind them like a named call without labels.
+    /// Callers check `needs_argument_binding` first, so ordinary functions
+    /// never get here.
+    #[cold]
+    pub(crate) fn call_closure_binding_positional(
+        &mut self,
+        closure: Rc<VmClosure>,
+        argc: usize,
+        class: Option<Rc<Class>>,
+        span: Span,
+    ) -> Result<(), RuntimeError> {
+        let at = self.stack.len() - argc;
+        let positional = self.stack.split_off(at);
+        let (slots, supplied) = bind_named_arguments(&closure.proto, positional, Vec::new(), span)?;
+        self.call_closure_with_slots(closure, slots, supplied, class)
+    }
+
     /// Push `slots` (already in parameter order) and open a frame with an
     /// explicit supplied-parameter mask.
     ///
@@ -260,6 +326,7 @@ impl Vm {
             self.push(value);
         }
         let stack_base = self.stack.len() - total_params - 1;
+        self.check_call_depth()?;
         self.frames
             .push(CallFrame::new(closure, stack_base, class, supplied));
         Ok(())
@@ -339,27 +406,38 @@ impl Vm {
             // Tree-walking function reached from compiled code: compile it,
             // then bind exactly as above.
             Value::Function(ref func) => {
-                let proto =
-                    jit_compile_function(func, self.globals.keys().cloned()).map_err(|e| {
-                        RuntimeError::EngineFallback(
-                            format!("a function the VM cannot compile ({})", e),
-                            span,
-                        )
-                    })?;
+                let proto = jit_compile_function(func, self.globals.names()).map_err(|e| {
+                    RuntimeError::EngineFallback(
+                        format!("a function the VM cannot compile ({})", e),
+                        span,
+                    )
+                })?;
                 let closure = Rc::new(VmClosure::new(proto, Vec::new()));
                 self.stack[callee_idx] = Value::VmClosure(closure.clone());
                 let (slots, supplied) =
                     bind_named_arguments(&closure.proto, positional, named, span)?;
                 self.call_closure_with_slots(closure, slots, supplied, None)
             }
+            // `Point(x: 1, y: 2)` — structs carry no constructor; fields
+            // bind by name against the declared defaults.
+            Value::Class(ref class) if class.struct_fields.is_some() => {
+                let named = named
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value))
+                    .collect();
+                self.stack[callee_idx] = StructValue::construct(class, positional, named)
+                    .map_err(|msg| RuntimeError::type_error(msg, span))?;
+                Ok(())
+            }
             // `Config(port: 3000)` — bind against the compiled constructor and
             // let it run with `this` in the callee slot.
             Value::Class(ref class) => {
                 if let Some((ctor, defining_class)) = class.find_vm_method_with_class("init") {
                     let (slots, supplied) =
                         bind_named_arguments(&ctor.proto, positional, named, span)?;
-                    let instance =
-                        Value::Instance(Rc::new(RefCell::new(Instance::new(class.clone()))));
+                    let instance = Value::Instance(crate::interpreter::cycles::new_instance(
+                        Instance::new(class.clone()),
+                    ));
                     self.stack[callee_idx] = instance;
                     self.call_closure_with_slots(ctor, slots, supplied, Some(defining_class))
                 } else {
@@ -378,6 +456,52 @@ impl Vm {
         }
     }
 
+    /// Expand the `*array` slots among the `argc` argument slots on the stack
+    /// in place, per `layout`, and return the new slot count with one label
+    /// per slot for [`Self::call_value_named`].
+    pub(crate) fn expand_spread_arguments(
+        &mut self,
+        argc: usize,
+        layout: &[ArgSlot],
+        span: Span,
+    ) -> Result<(usize, Vec<Option<crate::interpreter::value::SoliStr>>), RuntimeError> {
+        let at = self.stack.len() - argc;
+        let values = self.stack.split_off(at);
+        let mut labels = Vec::with_capacity(argc);
+        for (value, slot) in values.into_iter().zip(layout) {
+            match slot {
+                ArgSlot::Positional => {
+                    self.push(value);
+                    labels.push(None);
+                }
+                ArgSlot::Named(name) => {
+                    self.push(value);
+                    labels.push(Some(name.clone()));
+                }
+                ArgSlot::Spread => {
+                    let items = match value {
+                        Value::Array(items) => items.borrow().clone(),
+                        Value::Range(range) => range.to_vec(),
+                        _ => {
+                            return Err(RuntimeError::type_error(
+                                format!(
+                                    "cannot spread {} into arguments, expected an array",
+                                    value.type_name()
+                                ),
+                                span,
+                            ));
+                        }
+                    };
+                    for item in items {
+                        self.push(item);
+                        labels.push(None);
+                    }
+                }
+            }
+        }
+        Ok((labels.len(), labels))
+    }
+
     fn call_native(
         &mut self,
         native: &NativeFunction,
@@ -428,7 +552,7 @@ impl Vm {
         // routed through user-level `try`/`rescue`, and a handler that wrapped
         // the call would swallow the VM's internal limitation as if it were an
         // application error — returning a rescue value instead of demoting.
-        let proto = jit_compile_function(func, self.globals.keys().cloned()).map_err(|e| {
+        let proto = jit_compile_function(func, self.globals.names()).map_err(|e| {
             RuntimeError::EngineFallback(format!("a function the VM cannot compile ({})", e), span)
         })?;
 
@@ -449,7 +573,16 @@ impl Vm {
         span: Span,
     ) -> Result<(), RuntimeError> {
         let callee_idx = self.stack.len() - 1 - argc;
-        let instance_val = Value::Instance(Rc::new(RefCell::new(Instance::new(class.clone()))));
+        if class.struct_fields.is_some() {
+            let args = self.stack.split_off(callee_idx + 1);
+            let value = StructValue::construct(class, args, HashMap::new())
+                .map_err(|msg| RuntimeError::type_error(msg, span))?;
+            self.stack[callee_idx] = value;
+            return Ok(());
+        }
+        let instance_val = Value::Instance(crate::interpreter::cycles::new_instance(
+            Instance::new(class.clone()),
+        ));
 
         // Bytecode constructor (classes compiled in the VM): registered as
         // "init" by compile_constructor and returns `this`, so the frame's
@@ -465,7 +598,7 @@ impl Vm {
         // e.g. native classes in serve mode): JIT-compile as a method, run
         // it to completion, discard its return value, and yield the instance.
         if let Some(ctor) = class.find_constructor() {
-            let proto = jit_compile_method(&ctor, self.globals.keys().cloned()).map_err(|e| {
+            let proto = jit_compile_method(&ctor, self.globals.names()).map_err(|e| {
                 RuntimeError::EngineFallback(
                     format!("a function the VM cannot compile ({})", e),
                     span,
@@ -518,7 +651,7 @@ impl Vm {
         argc: usize,
         span: Span,
     ) -> Result<Value, RuntimeError> {
-        let proto = jit_compile_method(method, self.globals.keys().cloned()).map_err(|e| {
+        let proto = jit_compile_method(method, self.globals.names()).map_err(|e| {
             RuntimeError::EngineFallback(format!("a function the VM cannot compile ({})", e), span)
         })?;
         let closure = Rc::new(VmClosure::new(proto, Vec::new()));
@@ -544,27 +677,82 @@ impl Vm {
     /// access (tree-walker parity). Native instance methods invoke directly
     /// (no per-call bound-wrapper alloc). Everything else goes through
     /// property lookup + call_value.
+    ///
+    /// `from_call_site` is true when invoked by the method-call opcode being
+    /// executed, so compiled-method lookups can use its inline cache (see
+    /// `vm::inline_cache`); internal callers pass false.
     pub(crate) fn call_method_slow_path(
         &mut self,
         receiver_idx: usize,
         argc: usize,
         name: &str,
+        from_call_site: bool,
     ) -> Result<(), RuntimeError> {
-        let compiled = match &self.stack[receiver_idx] {
-            Value::Instance(inst) => {
-                let class = inst.borrow().class.clone();
-                class.find_vm_method_with_class(name)
-            }
-            // Statics compile as plain functions; the class value left in
-            // the callee slot is ignored by the bytecode.
-            Value::Class(class) => class
-                .find_vm_static_method(name)
-                .map(|closure| (closure, class.clone())),
+        let receiver_class = match &self.stack[receiver_idx] {
+            Value::Instance(inst) => Some((inst.borrow().class.clone(), false)),
+            Value::Class(class) => Some((class.clone(), true)),
             _ => None,
         };
+        let compiled = match receiver_class {
+            Some((class, is_static)) => {
+                let site = if from_call_site {
+                    let frame = self.frames.last().unwrap();
+                    frame
+                        .closure
+                        .proto
+                        .chunk
+                        .method_site(frame.ip - 1)
+                        .map(|site| (site, frame.closure.clone()))
+                } else {
+                    None
+                };
+                let cached = site.as_ref().and_then(|(site, caller)| {
+                    caller
+                        .method_cache
+                        .borrow()
+                        .lookup(*site, &class, is_static)
+                });
+                match cached {
+                    Some(hit) => Some(hit),
+                    None => {
+                        let found = if is_static {
+                            // Statics compile as plain functions; the class value
+                            // left in the callee slot is ignored by the bytecode.
+                            class
+                                .find_vm_static_method(name)
+                                .map(|closure| (closure, class.clone()))
+                        } else {
+                            class.find_vm_method_with_class(name)
+                        };
+                        if let (Some((site, caller)), Some((closure, defining_class))) =
+                            (site, &found)
+                        {
+                            caller.method_cache.borrow_mut().insert(
+                                site,
+                                class,
+                                is_static,
+                                closure.clone(),
+                                defining_class.clone(),
+                            );
+                        }
+                        found
+                    }
+                }
+            }
+            None => None,
+        };
         if let Some((closure, defining_class)) = compiled {
             // Hot path for compiled method calls — span is computed only on
             // the cold arity-error branch.
+            if closure.proto.needs_argument_binding() {
+                let span = self.current_span();
+                return self.call_closure_binding_positional(
+                    closure,
+                    argc,
+                    Some(defining_class),
+                    span,
+                );
+            }
             let arity = closure.proto.arity as usize;
             let total_params = closure.proto.param_names.len();
             if argc < arity || argc > total_params {
@@ -578,12 +766,16 @@ impl Vm {
                 self.stack.push(Value::Null);
             }
             let stack_base = self.stack.len() - total_params - 1;
+            self.check_call_depth()?;
             self.frames.push(CallFrame::new(
                 closure,
                 stack_base,
                 Some(defining_class),
                 crate::vm::vm::positional_supplied_mask(argc),
             ));
+            if self.budget.is_some() {
+                self.spend_budget()?;
+            }
             return Ok(());
         }
 
@@ -662,11 +854,21 @@ impl Vm {
 
         let result = match &receiver {
             Value::Array(arr) => self.vm_call_array_method(arr, method_name, &args, span)?,
+            Value::Range(range) => match call_range_method_impl(range, method_name, &args, span) {
+                Some(result) => result?,
+                None => {
+                    let arr = Rc::new(RefCell::new(range.to_vec()));
+                    self.vm_call_array_method(&arr, method_name, &args, span)?
+                }
+            },
             Value::String(s) => self.vm_call_string_method(s.as_ref(), method_name, &args, span)?,
             Value::Hash(hash) => self.vm_call_hash_method(hash, method_name, &args, span)?,
             Value::Int(_) | Value::Float(_) | Value::Bool(_) | Value::Null | Value::Decimal(_) => {
                 self.vm_call_primitive_method(&receiver, method_name, &args, span)?
             }
+            Value::QueryBuilder(qb) => {
+                self.vm_call_query_builder_method(qb, method_name, &args, span)?
+            }
             _ => {
                 return Err(RuntimeError::NoSuchProperty {
                     value_type: receiver.type_name(),
@@ -679,6 +881,43 @@ impl Vm {
         Ok(())
     }
 
+    /// Dispatch a QueryBuilder method. Chaining and terminal methods share
+    /// the tree-walker's implementation; array methods run on the
+    /// materialized rows. `create` saves through the model callbacks, which
+    /// only the tree-walker fires — same punt as model instance methods.
+    pub(crate) fn vm_call_query_builder_method(
+        &mut self,
+        qb: &Rc<RefCell<crate::interpreter::builtins::model::QueryBuilder>>,
+        method_name: &str,
+        args: &[Value],
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        use crate::interpreter::executor::calls::query_builder_methods::is_query_builder_passthrough;
+        if method_name == "create" {
+            return Err(RuntimeError::EngineFallback(
+                "query builder method 'create'".to_string(),
+                span,
+            ));
+        }
+        if is_query_builder_passthrough(method_name) {
+            let rows = crate::interpreter::builtins::model::execute_query_builder(&qb.borrow());
+            return match rows {
+                Value::Array(arr) => self.vm_call_array_method(&arr, method_name, args, span),
+                other => Err(RuntimeError::NoSuchProperty {
+                    value_type: other.type_name().to_string(),
+                    property: method_name.to_string(),
+                    span,
+                }),
+            };
+        }
+        crate::interpreter::executor::Interpreter::call_query_builder_chain(
+            qb.clone(),
+            method_name,
+            args.to_vec(),
+            span,
+        )
+    }
+
     /// Call a global function by name (used by server integration).
     pub fn call_global(
         &mut self,
@@ -760,14 +999,13 @@ impl Vm {
             if let Some(cached) = cached {
                 cached
             } else {
-                let compiled =
-                    Compiler::compile_method_standalone(method, self.globals.keys().cloned())
-                        .map_err(|e| {
-                            RuntimeError::EngineFallback(
-                                format!("a function the VM cannot compile ({})", e),
-                                span,
-                            )
-                        })?;
+                let compiled = Compiler::compile_method_standalone(method, self.globals.names())
+                    .map_err(|e| {
+                        RuntimeError::EngineFallback(
+                            format!("a function the VM cannot compile ({})", e),
+                            span,
+                        )
+                    })?;
                 let arc = Arc::new(compiled);
                 *method.jit_cache.borrow_mut() = Some(arc.clone());
                 arc
@@ -795,6 +1033,22 @@ impl Vm {
         self.return_depth = 0;
     }
 
+    /// Fallback for the `x.get("k")` / `x.set("k", v)` peephole opcodes
+    /// when the receiver turns out not to be a hash (an instance with its
+    /// own `get`, say): dispatch it as an ordinary method call instead.
+    pub(crate) fn call_hash_peephole_fallback(
+        &mut self,
+        receiver: Value,
+        name: &str,
+        args: Vec<Value>,
+    ) -> Result<(), RuntimeError> {
+        let receiver_idx = self.stack.len();
+        let argc = args.len();
+        self.push(receiver);
+        self.stack.extend(args);
+        self.call_method_slow_path(receiver_idx, argc, name, false)
+    }
+
     /// Invoke a callable synchronously from within a native method.
     /// Bumps `return_depth` so nested `run()` exits when this specific call returns,
     /// letting the native caller resume with the result on its own path.
@@ -821,6 +1075,69 @@ impl Vm {
         result
     }
 
+    /// Call `receiver.<name>(arguments)` when `receiver` is an instance whose
+    /// class defines `name` — the magic methods behind operator overloading.
+    /// `None` when it doesn't, so the caller falls back to the built-in
+    /// meaning of the operator. Runs the method to completion like
+    /// [`Self::invoke_callable`].
+    pub(crate) fn call_operator_method(
+        &mut self,
+        receiver: &Value,
+        name: &str,
+        arguments: Vec<Value>,
+    ) -> Result<Option<Value>, RuntimeError> {
+        let Value::Instance(inst) = receiver else {
+            return Ok(None);
+        };
+        let defined = inst
+            .borrow()
+            .class
+            .find_vm_method_with_class(name)
+            .is_some();
+        if !defined {
+            return Ok(None);
+        }
+        let saved_depth = self.return_depth;
+        let frames_before = self.frames.len();
+        let receiver_idx = self.stack.len();
+        let argc = arguments.len();
+        self.push(receiver.clone());
+        for arg in arguments {
+            self.push(arg);
+        }
+        self.call_method_slow_path(receiver_idx, argc, name, false)?;
+        if self.frames.len() == frames_before {
+            return Ok(Some(self.pop()));
+        }
+        self.return_depth = frames_before;
+        let result = self.run();
+        self.return_depth = saved_depth;
+        result.map(Some)
+    }
+
+    /// `a <op> b` through the magic method `a`'s class defines for `op`
+    /// (`op_add` for `+`, see [`BinaryOp::method_name`]), the way the
+    /// tree-walker's `evaluate_binary_operator` does: comparisons take the
+    /// result by truthiness and `!=` negates `op_eq`.
+    pub(crate) fn call_binary_operator_method(
+        &mut self,
+        a: &Value,
+        op: BinaryOp,
+        b: &Value,
+    ) -> Result<Option<Value>, RuntimeError> {
+        let (Value::Instance(_), Some(name)) = (a, op.method_name()) else {
+            return Ok(None);
+        };
+        let Some(result) = self.call_operator_method(a, name, vec![b.clone()])? else {
+            return Ok(None);
+        };
+        Ok(Some(match op {
+            BinaryOp::NotEqual => Value::Bool(!result.is_truthy()),
+            _ if op.is_comparison() => Value::Bool(result.is_truthy()),
+            _ => result,
+        }))
+    }
+
     /// Optimized single-arg variant — borrows the callee (clones once for the stack
     /// push) and avoids the Vec allocation. Hot path for array.map/filter/each.
     #[inline]
@@ -833,8 +1150,11 @@ impl Vm {
         let saved_depth = self.return_depth;
         let frames_before = self.frames.len();
         self.push(callee.clone());
-        self.push(arg);
-        self.call_value(1, span)?;
+        let argc = block_argc(callee, 1);
+        if argc == 1 {
+            self.push(arg);
+        }
+        self.call_value(argc, span)?;
         if self.frames.len() == frames_before {
             return Ok(self.pop());
         }
@@ -856,9 +1176,14 @@ impl Vm {
         let saved_depth = self.return_depth;
         let frames_before = self.frames.len();
         self.push(callee.clone());
-        self.push(a);
-        self.push(b);
-        self.call_value(2, span)?;
+        let argc = block_argc(callee, 2);
+        if argc >= 1 {
+            self.push(a);
+        }
+        if argc == 2 {
+            self.push(b);
+        }
+        self.call_value(argc, span)?;
         if self.frames.len() == frames_before {
             return Ok(self.pop());
         }
@@ -898,8 +1223,11 @@ impl Vm {
         span: Span,
     ) -> Result<Value, RuntimeError> {
         self.push(callee.clone());
-        self.push(arg);
-        self.call_value(1, span)?;
+        let argc = block_argc(callee, 1);
+        if argc == 1 {
+            self.push(arg);
+        }
+        self.call_value(argc, span)?;
         if self.frames.len() == batch.frames_before {
             return Ok(self.pop());
         }
@@ -916,9 +1244,14 @@ impl Vm {
         span: Span,
     ) -> Result<Value, RuntimeError> {
         self.push(callee.clone());
-        self.push(a);
-        self.push(b);
-        self.call_value(2, span)?;
+        let argc = block_argc(callee, 2);
+        if argc >= 1 {
+            self.push(a);
+        }
+        if argc == 2 {
+            self.push(b);
+        }
+        self.call_value(argc, span)?;
         if self.frames.len() == batch.frames_before {
             return Ok(self.pop());
         }
@@ -926,6 +1259,20 @@ impl Vm {
     }
 }
 
+/// How many of the `argc` arguments a native iteration method hands its block
+/// the block takes: a compiled closure that declares fewer parameters
+/// (`3.times do ... end`, `each(fn() { ... })`) just doesn't see the rest, as
+/// in the tree-walker.
+#[inline]
+fn block_argc(callee: &Value, argc: usize) -> usize {
+    match callee {
+        Value::VmClosure(closure) if closure.proto.variadic_index.is_none() => {
+            argc.min(closure.proto.arity as usize)
+        }
+        _ => argc,
+    }
+}
+
 /// Snapshot of VM state for a batch of closure invocations made by a single
 /// native method (e.g. array.map's loop). Captured by `enter_callable_batch`
 /// and consumed by `exit_callable_batch`.
@@ -975,10 +1322,10 @@ mod tests {
         use crate::vm::Compiler;
 
         // `helper` is a tree-walking Function whose body cannot be compiled.
-        // `break` is a deliberate, documented VM punt (it would need to unwind
-        // the iterator and handler stacks), which makes it a stable stand-in
-        // for "any construct the compiler refuses".
-        let body_src = "for i in [1, 2] { break }";
+        // An `async fn` is a deliberate, documented VM punt (async calls are
+        // queued on the interpreter's scheduler), which makes it a stable
+        // stand-in for "any construct the compiler refuses".
+        let body_src = "async fn later() { return 1 }";
         let body_tokens = Scanner::new(body_src).scan_tokens().expect("lexer error");
         let body = Parser::new(body_tokens)
             .parse()
@@ -986,7 +1333,7 @@ mod tests {
             .statements;
         let helper = Value::Function(Rc::new(Function {
             name: "helper".to_string(),
-            body: body.into(),
+            body: std::sync::Arc::new(body.into()),
             ..Function::default()
         }));
 
@@ -1008,7 +1355,7 @@ mod tests {
             let program = Parser::new(tokens).parse().expect("parser error");
             let module = Compiler::compile(&program).expect("compile error");
             let mut vm = Vm::new();
-            vm.globals.insert("helper".to_string(), helper.clone());
+            vm.globals.insert("helper", helper.clone());
             match vm.execute(&module.main) {
                 Err(err) => assert!(err.is_engine_fallback(), "{}: {}", source, err),
                 Ok(_) => panic!("{}: expected EngineFallback, got Ok", source),
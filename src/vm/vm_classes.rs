@@ -1,8 +1,12 @@
 //! Class operations for the VM: property access, inheritance, instantiation.
 
+use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::error::RuntimeError;
+use crate::interpreter::executor::calls::range_methods::{
+    call_range_method_impl, is_range_method_with_args,
+};
 use crate::interpreter::executor::Interpreter;
 use crate::interpreter::value::{Class, HashKey, NativeFunction, Value, ValueMethod};
 use crate::span::Span;
@@ -44,6 +48,21 @@ impl Vm {
             }
         }
         match object {
+            // An async result (`HTTP.get(url).len`) or a `grouped {}`
+            // deferred resolves on first member access, then dispatches on
+            // the value — same as the tree-walker.
+            Value::Future(_) => {
+                let resolved = object
+                    .clone()
+                    .resolve()
+                    .map_err(|e| RuntimeError::new(e, span))?;
+                self.op_get_property(&resolved, name, span)
+            }
+            Value::Deferred(cell) => {
+                let resolved = crate::interpreter::builtins::model::batch::force(cell)
+                    .map_err(|e| RuntimeError::General { message: e, span })?;
+                self.op_get_property(&resolved, name, span)
+            }
             Value::Instance(inst) => {
                 let inst_ref = inst.borrow();
                 // Check instance fields first
@@ -105,15 +124,9 @@ impl Vm {
                                     Some(Value::String(s)) => s.clone(),
                                     _ => return Err("is_a? expects a string argument".to_string()),
                                 };
-                                let inst_ref = inst_clone.borrow();
-                                let mut current: Option<&Class> = Some(&inst_ref.class);
-                                while let Some(c) = current {
-                                    if c.name == class_name.as_ref() {
-                                        return Ok(Value::Bool(true));
-                                    }
-                                    current = c.superclass.as_deref();
-                                }
-                                Ok(Value::Bool(false))
+                                Ok(Value::Bool(
+                                    inst_clone.borrow().class.conforms_to(class_name.as_ref()),
+                                ))
                             },
                         )));
                     }
@@ -142,9 +155,9 @@ impl Vm {
                 })
             }
             Value::Class(class) => {
-                // Static field access
-                if let Some(val) = class.static_fields.borrow().get(name) {
-                    return Ok(val.clone());
+                // Static field access (including inherited static fields)
+                if let Some(val) = class.find_static_field(name) {
+                    return Ok(val);
                 }
                 // Static method access (AST-interpreted)
                 if let Some(method) = class.find_static_method(name) {
@@ -171,10 +184,30 @@ impl Vm {
                     }
                     return Ok(Value::NativeFunction((*native).clone()));
                 }
+                if let Some(value) = Interpreter::struct_type_member(class, name) {
+                    return Ok(value);
+                }
                 // Nested class
                 if let Some(nested) = class.nested_classes.borrow().get(name) {
                     return Ok(Value::Class(nested.clone()));
                 }
+                if let Some(value) =
+                    crate::interpreter::decorators::reflect(&class.decorators, name)
+                {
+                    return Ok(value);
+                }
+                if let Some(value) = crate::interpreter::decorators::reflect_methods(class, name) {
+                    return Ok(value);
+                }
+                if name == "new" {
+                    return Ok(Interpreter::class_new_member(object));
+                }
+                if let Some(value) = Interpreter::class_universal_member(class, name) {
+                    return Ok(value);
+                }
+                if let Some(value) = Interpreter::dynamic_finder_member(class, name) {
+                    return Ok(value);
+                }
                 // Class-level `method_missing` is dispatched only by the
                 // tree-walker (see executor/access/member.rs). When the class
                 // (or a superclass) defines one, punt to the interpreter via
@@ -198,11 +231,14 @@ impl Vm {
                 let key = HashKey::String(name.to_string().into());
                 if let Some(val) = hash.get(&key) {
                     Ok(val.clone())
-                } else {
+                } else if crate::interpreter::executor::access::member::is_hash_member_method(name)
+                {
                     Ok(Value::method(ValueMethod {
                         receiver: Box::new(object.clone()),
                         method_name: name.to_string(),
                     }))
+                } else {
+                    Ok(Value::Null)
                 }
             }
             Value::Array(_) => {
@@ -212,6 +248,24 @@ impl Vm {
                     method_name: name.to_string(),
                 }))
             }
+            Value::Range(range) => {
+                if !is_range_method_with_args(name) {
+                    if let Some(result) = call_range_method_impl(range, name, &[], span) {
+                        return result;
+                    }
+                }
+                // `include?` binds to the range; everything else runs as
+                // an array method on the materialized range.
+                let receiver = if is_range_method_with_args(name) {
+                    object.clone()
+                } else {
+                    Value::Array(Rc::new(RefCell::new(range.to_vec())))
+                };
+                Ok(Value::method(ValueMethod {
+                    receiver: Box::new(receiver),
+                    method_name: name.to_string(),
+                }))
+            }
             Value::String(s) => {
                 // String properties
                 if name == "length" {
@@ -233,6 +287,11 @@ impl Vm {
             Value::Bool(b) => Interpreter::bool_member_access(*b, name, span),
             Value::Null => Interpreter::null_member_access(name, span),
             Value::Decimal(d) => Interpreter::decimal_member_access(d, name, span),
+            Value::Tuple(items) => Interpreter::tuple_member_access(items, name, span),
+            Value::QueryBuilder(_) => {
+                Interpreter::query_builder_member_access(name, span, object.clone())
+            }
+            Value::Struct(value) => Interpreter::struct_member_access(value, name, span),
             Value::Symbol(s) => match name {
                 "to_s" | "to_string" => Ok(Value::String(s.clone())),
                 "inspect" => Ok(Value::String(format!(":{}", s).into())),
@@ -259,7 +318,7 @@ impl Vm {
                     span,
                 })
             }
-            Value::Function(_) | Value::NativeFunction(_) => match name {
+            Value::Function(_) | Value::NativeFunction(_) | Value::VmClosure(_) => match name {
                 "nil?" => Ok(Value::Bool(false)),
                 "blank?" => Ok(Value::Bool(false)),
                 "present?" => Ok(Value::Bool(true)),
@@ -357,6 +416,9 @@ impl Vm {
                 | Value::Decimal(_) => {
                     self.vm_call_primitive_method(&receiver, &method_name, &[], span)
                 }
+                Value::QueryBuilder(qb) => {
+                    self.vm_call_query_builder_method(qb, &method_name, &[], span)
+                }
                 _ => Ok(val),
             },
             None => Ok(val),
@@ -373,10 +435,44 @@ impl Vm {
     ) -> Result<(), RuntimeError> {
         match object {
             Value::Instance(inst) => {
+                // Constructor field initializers write a const field the
+                // first time; any later write is a reassignment.
+                if inst.borrow().class.const_fields.contains(name)
+                    && inst.borrow().fields.contains_key(name)
+                {
+                    return Err(RuntimeError::type_error(
+                        format!("cannot reassign const field '{}'", name),
+                        span,
+                    ));
+                }
+                if inst.borrow().class.is_model_subclass() {
+                    if name.starts_with('_') && name != "_errors" {
+                        return Err(RuntimeError::type_error(
+                            format!("cannot assign to read-only field '{}'", name),
+                            span,
+                        ));
+                    }
+                    let class_name = inst.borrow().class.name.clone();
+                    if let Some(msg) =
+                        crate::interpreter::builtins::model::attributes::check_assignment(
+                            &class_name,
+                            name,
+                            &value,
+                        )
+                    {
+                        return Err(RuntimeError::type_error(msg, span));
+                    }
+                }
                 inst.borrow_mut().fields.insert(name.to_string(), value);
                 Ok(())
             }
             Value::Class(class) => {
+                if class.static_const_fields.contains(name) {
+                    return Err(RuntimeError::type_error(
+                        format!("cannot reassign static const field '{}'", name),
+                        span,
+                    ));
+                }
                 class
                     .static_fields
                     .borrow_mut()
@@ -434,6 +530,7 @@ impl Vm {
             // Preserve the shared bytecode-method maps across rebuilds.
             new_class.vm_methods = sub.vm_methods.clone();
             new_class.vm_static_methods = sub.vm_static_methods.clone();
+            new_class.interfaces = sub.interfaces.clone();
             // Replace the class on top of the stack
             let top = self.stack.len() - 1;
             self.stack[top] = Value::Class(Rc::new(new_class));
@@ -446,6 +543,60 @@ impl Vm {
         }
     }
 
+    /// Record an `implements` interface on the class on top of the stack.
+    /// Emitted right after `Op::Class`/`Op::Inherit`, before the class is
+    /// bound anywhere, so the stack holds its only reference.
+    pub fn op_implement(&mut self, interface: String, span: Span) -> Result<(), RuntimeError> {
+        let top = self.stack.len() - 1;
+        match &mut self.stack[top] {
+            Value::Class(class) => match Rc::get_mut(class) {
+                Some(class) => {
+                    class.interfaces.push(interface);
+                    Ok(())
+                }
+                None => Err(RuntimeError::type_error(
+                    format!("cannot add interface '{}' to a shared class", interface),
+                    span,
+                )),
+            },
+            other => Err(RuntimeError::type_error(
+                format!("Expected class, got {}", other.type_name()),
+                span,
+            )),
+        }
+    }
+
+    /// Mark a field of the class on top of the stack `const`. Emitted with
+    /// `Op::Implement`, while the stack still holds the only reference.
+    pub fn op_declare_const(
+        &mut self,
+        field: String,
+        is_static: bool,
+        span: Span,
+    ) -> Result<(), RuntimeError> {
+        let top = self.stack.len() - 1;
+        match &mut self.stack[top] {
+            Value::Class(class) => match Rc::get_mut(class) {
+                Some(class) => {
+                    if is_static {
+                        class.static_const_fields.insert(field);
+                    } else {
+                        class.const_fields.insert(field);
+                    }
+                    Ok(())
+                }
+                None => Err(RuntimeError::type_error(
+                    format!("cannot declare const field '{}' on a shared class", field),
+                    span,
+                )),
+            },
+            other => Err(RuntimeError::type_error(
+                format!("Expected class, got {}", other.type_name()),
+                span,
+            )),
+        }
+    }
+
     /// Add a method to a class on top of the stack.
     pub fn op_add_method(
         &mut self,
@@ -472,6 +623,7 @@ impl Vm {
                         // are shared `Rc`s, so no class rebuild is needed —
                         // and the constructor ("init") plus instance methods
                         // become dispatchable via find_vm_method.
+                        crate::vm::inline_cache::bump_method_epoch();
                         if is_static {
                             current
                                 .vm_static_methods
@@ -510,6 +662,9 @@ impl Vm {
                 // Preserve the shared bytecode-method maps across rebuilds.
                 new_class.vm_methods = current.vm_methods.clone();
                 new_class.vm_static_methods = current.vm_static_methods.clone();
+                new_class.interfaces = current.interfaces.clone();
+                new_class.const_fields = current.const_fields.clone();
+                new_class.static_const_fields = current.static_const_fields.clone();
                 self.stack[top] = Value::Class(Rc::new(new_class));
             }
             Ok(())
@@ -25,8 +25,9 @@ impl Vm {
                 self.close_upvalues(frame.stack_base);
             }
 
-            // Unwind the stack
+            // Unwind the stack and the iterators of loops left behind
             self.stack.truncate(handler.stack_depth);
+            self.iter_stack.truncate(handler.iter_depth);
 
             // Push the exception value for the catch block
             self.push(value.clone());
@@ -52,6 +53,8 @@ impl Vm {
             other => format!("{}", other),
         };
 
-        Err(RuntimeError::new(message, span))
+        let error = RuntimeError::new(message, span);
+        self.escaped_throw = Some((error.to_string(), value));
+        Err(error)
     }
 }
@@ -597,11 +597,26 @@ impl Vm {
                 outcome?;
                 Ok(Value::Bool(answer))
             }
-            _ => Err(RuntimeError::NoSuchProperty {
-                value_type: "Hash".to_string(),
-                property: name.to_string(),
-                span,
-            }),
+            // Not a builtin: `h.name(...)` reads the key and calls what is
+            // stored there, the way the tree-walker evaluates the member and
+            // then the call (`V.string().required()` chains on such hashes).
+            _ => {
+                let stored = hash
+                    .borrow()
+                    .get(&crate::interpreter::value::StrKey(name))
+                    .cloned();
+                match stored {
+                    Some(callee) if callee.is_callable() => {
+                        self.invoke_callable(callee, args.to_vec(), span)
+                    }
+                    Some(value) if args.is_empty() => Ok(value),
+                    _ => Err(RuntimeError::NoSuchProperty {
+                        value_type: "Hash".to_string(),
+                        property: name.to_string(),
+                        span,
+                    }),
+                }
+            }
         }
     }
 
@@ -0,0 +1,121 @@
+//! End-to-end: `soli script.sl` runs the interpreter on a thread sized for
+//! the default call-depth limit, so runaway recursion fails with the
+//! interpreter's `maximum call depth exceeded` error rather than aborting on
+//! a Rust stack overflow, and recursion within the limit completes. `soli -e`
+//! does the same. The VM (`--vm`) enforces the same limit on its frame stack.
+
+use std::process::{Command, Output};
+
+fn run_script(name: &str, source: &str) -> Output {
+    run_script_with_depth(name, source, None)
+}
+
+fn run_script_with_depth(name: &str, source: &str, depth: Option<&str>) -> Output {
+    run_on_engine("--interp", name, source, depth)
+}
+
+fn run_on_engine(engine: &str, name: &str, source: &str, depth: Option<&str>) -> Output {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(name);
+    std::fs::write(&path, source).unwrap();
+    let mut command = Command::new(env!("CARGO_BIN_EXE_soli"));
+    command.arg(engine).arg(&path);
+    match depth {
+        Some(depth) => command.env("SOLI_MAX_CALL_DEPTH", depth),
+        None => command.env_remove("SOLI_MAX_CALL_DEPTH"),
+    };
+    command.output().expect("run soli")
+}
+
+#[test]
+fn unbounded_recursion_fails_gracefully() {
+    let output = run_script(
+        "runaway.sl",
+        "fn f(n) { return 1 + f(n + 1); }\nprint(f(0));\n",
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("maximum call depth exceeded"),
+        "stderr: {}",
+        stderr
+    );
+    assert!(
+        !stderr.contains("overflowed its stack"),
+        "stderr: {}",
+        stderr
+    );
+}
+
+#[test]
+fn unbounded_recursion_in_eval_fails_gracefully() {
+    let output = Command::new(env!("CARGO_BIN_EXE_soli"))
+        .args(["-e", "fn f() { return 1 + f() }\nf()"])
+        .env_remove("SOLI_MAX_CALL_DEPTH")
+        .output()
+        .expect("run soli");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("maximum call depth exceeded"),
+        "stderr: {}",
+        stderr
+    );
+}
+
+#[test]
+fn recursion_within_the_limit_completes() {
+    let output = run_script(
+        "sum.sl",
+        "fn sum(n) {\n    if (n == 0) { return 0; }\n    return n + sum(n - 1);\n}\nprint(sum(5000));\n",
+    );
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "12502500");
+}
+
+#[test]
+fn an_oversized_depth_is_clamped_rather_than_crashing() {
+    let output = run_script_with_depth("clamped.sl", "print(1 + 1);\n", Some("1000000000000"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "stderr: {}", stderr);
+    assert!(!stderr.contains("panicked"), "stderr: {}", stderr);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "2");
+}
+
+#[test]
+fn unbounded_recursion_fails_gracefully_on_the_vm() {
+    let output = run_on_engine(
+        "--vm",
+        "runaway.sl",
+        "fn deep(n) { return 1 + deep(n + 1) }\nprint(deep(0))\n",
+        None,
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(
+        stderr.contains("maximum call depth exceeded"),
+        "stderr: {}",
+        stderr
+    );
+}
+
+#[test]
+fn the_vm_honours_the_configured_depth() {
+    let source = "fn sum(n) {\n    if (n == 0) { return 0; }\n    return n + sum(n - 1);\n}\nprint(sum(500));\n";
+    let output = run_on_engine("--vm", "sum.sl", source, Some("100"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("(100 nested calls)"), "stderr: {}", stderr);
+
+    let output = run_on_engine("--vm", "sum.sl", source, Some("1000"));
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "125250");
+}
@@ -0,0 +1,115 @@
+//! `soli test --jobs N` coverage: each worker thread keeps its hits in its
+//! own profile, and merging the profiles gives the report a single worker
+//! would have produced, with every hit counted once.
+
+use solilang::coverage::{take_test_coverage, CoverageConfig, CoverageProfile, CoverageTracker};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+const MODEL: &str = "\
+class Calc {
+    static fn add(a, b) {
+        return a + b;
+    }
+    static fn sign(x) {
+        if (x > 0) {
+            return 1;
+        }
+        return -1;
+    }
+}
+";
+
+fn tracker(model_path: &PathBuf) -> Arc<Mutex<CoverageTracker>> {
+    let mut tracker = CoverageTracker::new(CoverageConfig::new());
+    tracker.register_executable_lines_from_source(model_path, MODEL);
+    Arc::new(Mutex::new(tracker))
+}
+
+/// Run each test on its own thread, as the runner's workers do, and return
+/// the profile each thread recorded.
+fn run_workers(
+    tracker: &Arc<Mutex<CoverageTracker>>,
+    model_path: &Path,
+    tests: &[(PathBuf, String)],
+) -> Vec<CoverageProfile> {
+    std::thread::scope(|s| {
+        let handles: Vec<_> = tests
+            .iter()
+            .map(|(path, source)| {
+                s.spawn(move || {
+                    tracker.lock().unwrap().start_test(&path.to_string_lossy());
+                    let (_, result) = solilang::run_with_path_and_coverage(
+                        source,
+                        Some(path),
+                        false,
+                        Some(tracker),
+                        Some(path),
+                        &[(model_path.to_path_buf(), MODEL.to_string())],
+                    );
+                    assert!(result.is_ok(), "run failed: {:?}", result.err());
+                    let mut profile = CoverageProfile::new();
+                    profile.add_test_coverage(&take_test_coverage().unwrap());
+                    profile
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    })
+}
+
+fn calc_hits(tracker: &Arc<Mutex<CoverageTracker>>) -> (Vec<(usize, u32)>, u32, u32) {
+    let coverage = tracker.lock().unwrap().get_aggregated_coverage();
+    let file = coverage
+        .file_coverages
+        .values()
+        .find(|f| f.path.file_name().is_some_and(|n| n == "calc.sl"))
+        .expect("calc.sl in the report");
+    let mut hits: Vec<(usize, u32)> = file
+        .lines
+        .values()
+        .map(|l| (l.line_number, l.hits))
+        .collect();
+    hits.sort_unstable();
+    (hits, file.covered_lines, file.total_lines)
+}
+
+#[test]
+fn worker_profiles_merge_into_the_single_worker_report() {
+    let dir = tempfile::tempdir().unwrap();
+    let model_path = dir.path().join("calc.sl");
+    std::fs::write(&model_path, MODEL).unwrap();
+    let tests: Vec<(PathBuf, String)> = (0..4)
+        .map(|i| {
+            let path = dir.path().join(format!("t{}_test.sl", i));
+            let source = format!("Calc.add({i}, 1);\nCalc.sign({});\n", i - 2);
+            std::fs::write(&path, &source).unwrap();
+            (path, source)
+        })
+        .collect();
+
+    let parallel = tracker(&model_path);
+    let profiles = run_workers(&parallel, &model_path, &tests);
+    // Nothing reaches the shared aggregate until the profiles are merged.
+    assert_eq!(calc_hits(&parallel).1, 0);
+    for profile in profiles.iter().rev() {
+        parallel.lock().unwrap().merge_profile(profile);
+    }
+
+    // One worker running the files one after the other.
+    let serial = tracker(&model_path);
+    let mut combined = CoverageProfile::new();
+    for test in &tests {
+        for profile in run_workers(&serial, &model_path, std::slice::from_ref(test)) {
+            combined.merge(&profile);
+        }
+    }
+    serial.lock().unwrap().merge_profile(&combined);
+
+    let (hits, covered, total) = calc_hits(&parallel);
+    assert_eq!((hits.clone(), covered, total), calc_hits(&serial));
+    // The class declaration ran once per test file: four hits, not eight.
+    assert!(hits.contains(&(1, 4)), "{:?}", hits);
+    assert!(hits.contains(&(7, 1)), "{:?}", hits);
+    assert_eq!((covered, total), (5, 6));
+}
@@ -0,0 +1,34 @@
+//! Synthetic sources (the `@soli/<name>.sl` preludes the runtime embeds)
+//! run with their own path as the source path, so their statements must not
+//! be recorded as coverage hits: they are not project files and would show
+//! up in every report.
+
+use solilang::coverage::{CoverageConfig, CoverageTracker};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn embedded_preludes_are_not_line_counted() {
+    let dir = tempfile::tempdir().unwrap();
+    let test_path = dir.path().join("prelude_run.sl");
+    let test_src = "let x = 1 + 2;\n";
+    std::fs::write(&test_path, test_src).unwrap();
+
+    let tracker = Arc::new(Mutex::new(CoverageTracker::new(CoverageConfig::new())));
+    let (_assertions, result) = solilang::run_with_path_and_coverage(
+        test_src,
+        Some(&test_path),
+        false,
+        Some(&tracker),
+        Some(&test_path),
+        &[],
+    );
+    assert!(result.is_ok(), "run failed: {:?}", result.err());
+
+    let aggregated = tracker.lock().unwrap().get_aggregated_coverage();
+    let synthetic: Vec<_> = aggregated
+        .file_coverages
+        .keys()
+        .filter(|p| solilang::source_map::is_synthetic(p))
+        .collect();
+    assert!(synthetic.is_empty(), "{:?}", synthetic);
+}
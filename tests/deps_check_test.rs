@@ -0,0 +1,77 @@
+//! Integration tests for `soli deps check` (`solilang::module::deps_check`).
+
+use std::fs;
+use std::path::Path;
+
+use solilang::module::deps_check::{check_dependencies, prune_dependencies};
+use solilang::module::Package;
+
+fn write(root: &Path, rel: &str, content: &str) {
+    let path = root.join(rel);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+fn project() -> tempfile::TempDir {
+    let tmp = tempfile::tempdir().unwrap();
+    write(
+        tmp.path(),
+        "soli.toml",
+        r#"[package]
+name = "demo"
+version = "0.1.0"
+
+[dependencies]
+util = { path = "vendor/util" }
+math = { git = "https://github.com/user/soli-math", tag = "v1.0.0" }
+stale = "1.0.0"
+"#,
+    );
+    write(
+        tmp.path(),
+        "app.sl",
+        "import \"util/strings\";\nimport { sq } from \"math\";\nimport \"./lib/helpers\";\nimport \"lib/helpers\";\nimport \"nowhere/thing\";\n",
+    );
+    write(tmp.path(), "lib/helpers.sl", "fn h() {}\n");
+    // A vendored dependency's own imports aren't the project's concern.
+    write(
+        tmp.path(),
+        "vendor/util/strings.sl",
+        "import \"its_own_dep\";\n",
+    );
+    tmp
+}
+
+#[test]
+fn reports_unused_dependencies_and_uncovered_imports() {
+    let tmp = project();
+    let pkg = Package::load(&tmp.path().join("soli.toml")).unwrap();
+    let report = check_dependencies(&pkg, tmp.path());
+
+    assert_eq!(report.unused, vec!["stale".to_string()]);
+    assert_eq!(report.uncovered.len(), 1);
+    let import = &report.uncovered[0];
+    assert_eq!(import.path, "nowhere/thing");
+    assert_eq!(import.line, 5);
+    assert!(import.file.ends_with("app.sl"));
+    assert!(!report.is_clean());
+}
+
+#[test]
+fn fix_prunes_only_unused_dependencies() {
+    let tmp = project();
+    let toml_path = tmp.path().join("soli.toml");
+    let mut pkg = Package::load(&toml_path).unwrap();
+    let report = check_dependencies(&pkg, tmp.path());
+    prune_dependencies(&mut pkg, &toml_path, &report.unused).unwrap();
+
+    let written = fs::read_to_string(&toml_path).unwrap();
+    assert!(!written.contains("stale"));
+    assert!(written.contains("util = { path = \"vendor/util\" }"));
+    assert!(written.contains("math = "));
+    // An unset `main` isn't materialised as `main = ""` on rewrite.
+    assert!(!written.contains("main"));
+
+    let pkg = Package::load(&toml_path).unwrap();
+    assert!(check_dependencies(&pkg, tmp.path()).unused.is_empty());
+}
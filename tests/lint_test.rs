@@ -487,3 +487,540 @@ fn slv_unclosed_tag_is_a_template_error() {
         err
     );
 }
+
+// =====================================================================
+// Cross-file template checks — locals passed by `render`, app helpers and
+// partials are resolved against the app the template lives in.
+// =====================================================================
+
+/// An app whose `PostsController#show` renders `posts/show` with `entry` and
+/// sets `@title`; `posts/show` renders the `posts/_card` partial with `item`.
+fn make_view_tree(tmp: &tempfile::TempDir, show: &str) -> std::path::PathBuf {
+    let root = tmp.path().to_path_buf();
+    for dir in &["app/controllers", "app/helpers", "app/views/posts"] {
+        std::fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    std::fs::write(
+        root.join("app/controllers/posts_controller.sl"),
+        r#"class PostsController < Controller
+    fn show(req: Any) -> Any {
+        @title = "Post";
+        return render("posts/show", { "entry": req["params"]["id"] });
+    }
+}
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("app/helpers/format_helper.sl"),
+        "fn shout(s: String) -> String { return s.upcase(); }\n",
+    )
+    .unwrap();
+    std::fs::write(root.join("app/views/posts/show.html.slv"), show).unwrap();
+    std::fs::write(
+        root.join("app/views/posts/_card.html.slv"),
+        "<p><%= item %></p>\n<p><%= entry %></p>\n<p><%= title %></p>\n<p><%= missing_thing %></p>\n",
+    )
+    .unwrap();
+    root
+}
+
+fn lint_view(root: &Path, rel: &str) -> Vec<LintDiagnostic> {
+    let path = root.join(rel);
+    let source = std::fs::read_to_string(&path).unwrap();
+    solilang::lint_file(&source, path.to_str().unwrap()).expect("template should parse")
+}
+
+#[test]
+fn template_locals_resolve_through_render_sites() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let root = make_view_tree(
+        &tmp,
+        "<h1><%= shout(title) %></h1>\n<%= entry %>\n<%= render \"posts/card\", { \"item\": entry } %>\n",
+    );
+    let diags = lint_view(&root, "app/views/posts/show.html.slv");
+    assert!(diags.is_empty(), "unexpected diagnostics: {:?}", diags);
+
+    // The partial sees its own data, its caller's `@fields` — and nothing else.
+    let diags = lint_view(&root, "app/views/posts/_card.html.slv");
+    assert_eq!(
+        rules(&diags),
+        vec!["template/undefined-local"; 2],
+        "{:?}",
+        diags
+    );
+    assert!(diags[0].message.contains("'entry'"), "{:?}", diags);
+    assert!(diags[1].message.contains("'missing_thing'"), "{:?}", diags);
+}
+
+#[test]
+fn template_typos_and_unknown_helpers_are_flagged() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let root = make_view_tree(
+        &tmp,
+        "<%= entryy %>\n<%= shuot(title) %>\n<%= posts_path() %>\n<% # soli-lint-disable-next-line template/undefined-helper %>\n<%= later() %>\n",
+    );
+    let diags = lint_view(&root, "app/views/posts/show.html.slv");
+    let found: Vec<_> = diags.iter().map(|d| (d.rule, d.span.line)).collect();
+    assert_eq!(
+        found,
+        vec![
+            ("template/undefined-local", 1),
+            ("template/undefined-helper", 2)
+        ],
+        "{:?}",
+        diags
+    );
+}
+
+#[test]
+fn template_render_of_missing_partial_is_flagged() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let root = make_view_tree(
+        &tmp,
+        "<%= render \"posts/card\", { \"item\": 1 } %>\n<%= render \"posts/sidebar\" %>\n<%= render_partial(\"shared/nav\") %>\n",
+    );
+    let diags = lint_view(&root, "app/views/posts/show.html.slv");
+    let found: Vec<_> = diags.iter().map(|d| (d.rule, d.span.line)).collect();
+    assert_eq!(
+        found,
+        vec![
+            ("template/missing-partial", 2),
+            ("template/missing-partial", 3)
+        ],
+        "{:?}",
+        diags
+    );
+    assert!(diags[0]
+        .message
+        .contains("app/views/posts/_sidebar.html.slv"));
+}
+
+// =====================================================================
+// `soli lint --dead-code` — whole-project reachability from the roots.
+// =====================================================================
+
+fn write_tree(root: &Path, files: &[(&str, &str)]) {
+    for (rel, content) in files {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+}
+
+fn dead_code(root: &Path) -> Vec<(String, &'static str, u32)> {
+    solilang::lint::dead_code::find_dead_code(root)
+        .into_iter()
+        .map(|d| {
+            let rel = d.file.strip_prefix(root).unwrap().to_string_lossy();
+            (
+                rel.replace('\\', "/"),
+                d.diagnostic.rule,
+                d.diagnostic.span.line,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn dead_code_follows_routes_views_and_relations() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    write_tree(
+        tmp.path(),
+        &[
+            ("config/routes.sl", "get(\"/posts\", \"posts#index\")\n"),
+            (
+                "app/controllers/posts_controller.sl",
+                r#"class PostsController < Controller
+    fn index(req: Any) -> Any {
+        @posts = Post.all();
+        return render("posts/index", { "total": count_posts() });
+    }
+}
+"#,
+            ),
+            (
+                "app/controllers/legacy_controller.sl",
+                "class LegacyController < Controller\n    fn show(req: Any) -> Any { return render(\"legacy/show\"); }\n}\n",
+            ),
+            (
+                "app/models/post.sl",
+                "class Post < Model\n    has_many(\"comments\")\n}\n",
+            ),
+            ("app/models/comment.sl", "class Comment < Model\n}\n"),
+            ("app/models/tag.sl", "class Tag < Model\n}\n"),
+            (
+                "app/services/stats.sl",
+                "fn count_posts() -> Int { return 0; }\n\nfn unused_stat() -> Int { return 1; }\n",
+            ),
+            (
+                "app/helpers/format_helper.sl",
+                "fn shout(s: String) -> String { return s.upcase(); }\n\nfn whisper(s: String) -> String { return s.downcase(); }\n",
+            ),
+            (
+                "app/views/posts/index.html.slv",
+                "<%= shout(\"hi\") %>\n<%= render \"posts/row\", { \"n\": total } %>\n",
+            ),
+            ("app/views/posts/_row.html.slv", "<p><%= n %></p>\n"),
+            ("app/views/posts/_old.html.slv", "<p>old</p>\n"),
+            ("app/views/legacy/show.html.slv", "<p>legacy</p>\n"),
+            ("app/views/layouts/application.html.slv", "<%= yield %>\n"),
+        ],
+    );
+    assert_eq!(
+        dead_code(tmp.path()),
+        vec![
+            (
+                "app/controllers/legacy_controller.sl".to_string(),
+                "dead-code/unused-class",
+                1
+            ),
+            (
+                "app/helpers/format_helper.sl".to_string(),
+                "dead-code/unused-helper",
+                3
+            ),
+            ("app/models/tag.sl".to_string(), "dead-code/unused-class", 1),
+            (
+                "app/services/stats.sl".to_string(),
+                "dead-code/unused-function",
+                3
+            ),
+            (
+                "app/views/legacy/show.html.slv".to_string(),
+                "dead-code/unused-template",
+                1
+            ),
+            (
+                "app/views/posts/_old.html.slv".to_string(),
+                "dead-code/unused-template",
+                1
+            ),
+        ]
+    );
+}
+
+#[test]
+fn dead_code_in_a_plain_project_keeps_entry_exports() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    write_tree(
+        tmp.path(),
+        &[
+            (
+                "main.sl",
+                "import { area } from \"./lib/geometry.sl\";\nprint(area(2));\n\nexport fn api() -> Int { return 1; }\n\nfn orphan() -> Int { return 2; }\n",
+            ),
+            (
+                "lib/geometry.sl",
+                "export fn area(r: Int) -> Int { return r * r; }\n\n# soli-lint-disable-next-line dead-code/unused-function\nexport fn kept() -> Int { return 0; }\n\nexport fn perimeter(r: Int) -> Int { return r * 6; }\n",
+            ),
+            ("tests/geometry_test.sl", "print(perimeter(1));\n"),
+        ],
+    );
+    assert_eq!(
+        dead_code(tmp.path()),
+        vec![
+            (
+                "lib/geometry.sl".to_string(),
+                "dead-code/unused-function",
+                6
+            ),
+            ("main.sl".to_string(), "dead-code/unused-function", 6),
+        ]
+    );
+}
+
+// security/* rule pack.
+
+#[test]
+fn interpolated_query_string_is_flagged_but_bind_vars_are_not() {
+    let diags = lint(
+        "let q = \"x\";\nlet a = Post.where(\"doc.title == '#{q}'\");\nlet b = Post.where(\"doc.title == '\" + q + \"'\");\nlet c = Post.where(\"doc.title == @q\", { \"q\": q });\n",
+    );
+    let flagged: Vec<u32> = diags
+        .iter()
+        .filter(|d| d.rule == "security/interpolated-query")
+        .map(|d| d.span.line)
+        .collect();
+    assert_eq!(flagged, vec![2, 3], "{:?}", diags);
+    assert!(diags[0].message.contains("@title"), "{}", diags[0].message);
+}
+
+#[test]
+fn hardcoded_secrets_are_flagged_outside_tests() {
+    let src = "let api_key = \"f3a9c2d18b7e4a50\";\nlet password_label = \"Your password\";\nlet stripe = \"sk_live_abcdefghijklmnop1234\";\nlet config = { \"client_secret\": \"9b1d2e7fa0c3\" };\nlet token_env = getenv(\"API_KEY\");\n";
+    let diags = lint_with_path(src, Path::new("app/services/billing.sl"));
+    let flagged: Vec<u32> = diags
+        .iter()
+        .filter(|d| d.rule == "security/hardcoded-secret")
+        .map(|d| d.span.line)
+        .collect();
+    assert_eq!(flagged, vec![1, 3, 4], "{:?}", diags);
+
+    let in_spec = lint_with_path(src, Path::new("tests/billing_spec.sl"));
+    assert!(!rules(&in_spec).contains(&"security/hardcoded-secret"));
+}
+
+#[test]
+fn template_form_without_csrf_is_flagged() {
+    let src = "<form method=\"post\" action=\"/a\">\n</form>\n<form method=\"post\" action=\"/b\">\n<%- csrf_field() %>\n</form>\n<form method=\"get\" action=\"/search\"></form>\n";
+    let diags = solilang::lint_file(src, "app/views/x/edit.html.slv").unwrap();
+    let flagged: Vec<u32> = diags
+        .iter()
+        .filter(|d| d.rule == "security/missing-csrf")
+        .map(|d| d.span.line)
+        .collect();
+    assert_eq!(flagged, vec![1], "{:?}", diags);
+}
+
+#[test]
+fn raw_output_of_request_data_is_flagged() {
+    let src = "<p><%- params[\"bio\"] %></p>\n<p><%= params[\"bio\"] %></p>\n<p><%- render_markdown(post[\"body\"]) %></p>\n";
+    let diags = solilang::lint_file(src, "app/views/x/show.html.slv").unwrap();
+    let flagged: Vec<u32> = diags
+        .iter()
+        .filter(|d| d.rule == "security/raw-request-data")
+        .map(|d| d.span.line)
+        .collect();
+    assert_eq!(flagged, vec![1], "{:?}", diags);
+}
+
+#[test]
+fn interpolated_query_builtins_are_flagged() {
+    let diags = lint(
+        "let name = \"x\";\ndb_query_raw(\"FOR u IN users FILTER u.name == '\" + name + \"' RETURN u\");\nsolidb_query(\"localhost:5678\", \"app\", \"FOR u IN users FILTER u.name == '#{name}' RETURN u\");\nsolidb_query(\"localhost:5678\", \"app\", \"FOR u IN users FILTER u.name == @name RETURN u\", { \"name\": name });\n",
+    );
+    let flagged: Vec<u32> = diags
+        .iter()
+        .filter(|d| d.rule == "security/interpolated-query")
+        .map(|d| d.span.line)
+        .collect();
+    assert_eq!(flagged, vec![2, 3], "{:?}", diags);
+    assert!(
+        diags.iter().any(|d| d.message.contains("@sdbql")),
+        "{:?}",
+        diags
+    );
+}
+
+#[test]
+fn raw_output_of_a_request_derived_variable_is_flagged() {
+    let src = "<% q = params[\"q\"] %>\n<% heading = \"Results for \" + q %>\n<% title = \"Search\" %>\n<h1><%- heading %></h1>\n<p><%- title %></p>\n<p><%= heading %></p>\n";
+    let diags = solilang::lint_file(src, "app/views/search/index.html.slv").unwrap();
+    let flagged: Vec<u32> = diags
+        .iter()
+        .filter(|d| d.rule == "security/raw-request-data")
+        .map(|d| d.span.line)
+        .collect();
+    assert_eq!(flagged, vec![4], "{:?}", diags);
+}
+
+#[test]
+fn routes_exempted_from_csrf_are_flagged() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let config = tmp.path().join("config");
+    std::fs::create_dir_all(&config).unwrap();
+    std::fs::write(
+        config.join("application.sl"),
+        "skip_csrf(\"/account/*\");\n",
+    )
+    .unwrap();
+    let routes = "skip_csrf(\"/webhooks/*\");\nskip_csrf(\"/admin/*\");\npost(\"/webhooks/stripe\", \"webhooks#stripe\");\npost(\"/account/close\", \"accounts#close\");\nnamespace(\"admin\", fn() {\n    resources(\"users\");\n});\nresources(\"posts\");\n";
+    let lint_config = solilang::lint::LintConfig::find(&config).unwrap();
+    assert_eq!(lint_config.csrf_skips(), ["/account/*"]);
+    let routes_path = config.join("routes.sl");
+    let diags =
+        solilang::lint_file_with_config(routes, &routes_path.to_string_lossy(), &lint_config)
+            .unwrap();
+    let flagged: Vec<u32> = diags
+        .iter()
+        .filter(|d| d.rule == "security/missing-csrf")
+        .map(|d| d.span.line)
+        .collect();
+    assert_eq!(flagged, vec![4, 6], "{:?}", diags);
+    assert!(
+        diags
+            .iter()
+            .any(|d| d.message.contains("POST /admin/users")),
+        "{:?}",
+        diags
+    );
+}
+
+#[test]
+fn csrf_exempt_paths_match_whole_segments() {
+    let routes = "skip_csrf(\"/*\");\npost(\"/api/v1/orders\", \"orders#create\");\npost(\"/billing/webhooks/stripe\", \"billing#stripe\");\npost(\"/auth/oauth/callback\", \"sessions#callback\");\npost(\"/capital\", \"funds#create\");\npost(\"/therapists\", \"therapists#create\");\npost(\"/rapid-signup\", \"signups#create\");\npost(\"/hooks_admin\", \"admin#create\");\npost(\"/admin/api\", \"admin#api\");\n";
+    let diags = lint_with_path(routes, Path::new("config/routes.sl"));
+    let flagged: Vec<u32> = diags
+        .iter()
+        .filter(|d| d.rule == "security/missing-csrf")
+        .map(|d| d.span.line)
+        .collect();
+    assert_eq!(flagged, vec![5, 6, 7, 8, 9], "{:?}", diags);
+}
+
+// =====================================================================
+// Fixes — `soli lint --fix` applies what the rules suggest.
+// =====================================================================
+
+/// Lint with the opt-in rules turned on too, as a `[lint]` config naming
+/// them would.
+fn lint_all(source: &str) -> Vec<LintDiagnostic> {
+    let tokens = Scanner::new(source).scan_tokens().expect("lexer ok");
+    let program = Parser::new(tokens).parse().expect("parser ok");
+    Linter::new(source)
+        .with_opt_in_rules(solilang::lint::OPT_IN_RULES)
+        .lint(&program)
+}
+
+fn fixed(source: &str) -> (String, usize) {
+    solilang::lint::apply_fixes(source, &lint_all(source))
+}
+
+#[test]
+fn unused_let_is_renamed_with_an_underscore() {
+    let src =
+        "fn f(x) {\n    let unused = x * 2\n    let used = x + 1\n    print(\"#{used}\")\n}\n";
+    let diags = lint(src);
+    let flagged: Vec<u32> = diags
+        .iter()
+        .filter(|d| d.rule == "smell/unused-variable")
+        .map(|d| d.span.line)
+        .collect();
+    assert_eq!(flagged, vec![2], "{:?}", diags);
+    assert_eq!(
+        fixed(src).0,
+        "fn f(x) {\n    let _unused = x * 2\n    let used = x + 1\n    print(\"#{used}\")\n}\n"
+    );
+}
+
+#[test]
+fn trailing_return_and_concatenation_are_rewritten() {
+    let src = "def greet(name)\n  return \"Hello \" + name.upcase() + \"!\"\nend\n\ndef sum(a, b)\n  let total = a + b + \" items\"\n  return {\"total\": total}\nend\n";
+    let (out, applied) = fixed(src);
+    assert_eq!(applied, 2);
+    // `a + b + "..."` adds before it concatenates; a returned hash literal
+    // needs its `return`.
+    assert_eq!(
+        out,
+        "def greet(name)\n  \"Hello #{name.upcase()}!\"\nend\n\ndef sum(a, b)\n  let total = a + b + \" items\"\n  return {\"total\": total}\nend\n"
+    );
+    assert!(lint_all(&out).is_empty(), "{:?}", lint_all(&out));
+}
+
+#[test]
+fn trailing_return_is_only_flagged_when_opted_in() {
+    let src = "def index(req)\n  return render(\"home/index\", {})\nend\n";
+    assert!(lint(src).is_empty(), "{:?}", lint(src));
+    assert_eq!(rules(&lint_all(src)), vec!["style/redundant-return"]);
+}
+
+#[test]
+fn template_diagnostics_carry_no_fixes() {
+    let src = "<p><%= \"Hi \" + name %></p>\n";
+    let diags = solilang::lint_file(src, "views/hi.html.slv").unwrap();
+    assert!(rules(&diags).contains(&"idiom/prefer-interpolation"));
+    assert!(diags.iter().all(|d| d.fix.is_none()), "{:?}", diags);
+}
+
+// =====================================================================
+// Unused imports, unused private methods and unreachable branches.
+// =====================================================================
+
+#[test]
+fn unused_imports_are_flagged_and_removed() {
+    let tmp = tempfile::tempdir().unwrap();
+    write_tree(
+        tmp.path(),
+        &[
+            (
+                "util.sl",
+                "export fn helper() { 1 }\nexport fn other() { 2 }\n",
+            ),
+            ("extra.sl", "export fn thing() { 3 }\n"),
+            ("used.sl", "export fn shout(s) { s.upcase() }\n"),
+        ],
+    );
+    let src = "import { helper, other } from \"./util.sl\";\nimport \"./extra.sl\";\nimport \"./used.sl\";\nprint(helper());\nprint(shout(\"hi\"));\n";
+    let main = tmp.path().join("main.sl");
+    let diags = lint_with_path(src, &main);
+    let flagged: Vec<(u32, &str)> = diags
+        .iter()
+        .filter(|d| d.rule == "smell/unused-import")
+        .map(|d| (d.span.line, d.message.as_str()))
+        .collect();
+    assert_eq!(
+        flagged,
+        vec![
+            (1, "imported 'other' is never used"),
+            (2, "nothing imported from './extra.sl' is used"),
+        ]
+    );
+    // Only the import nothing uses goes; `other` shares a line with `helper`.
+    let (out, _) = solilang::lint::apply_fixes(src, &diags);
+    assert_eq!(
+        out,
+        "import { helper, other } from \"./util.sl\";\nimport \"./used.sl\";\nprint(helper());\nprint(shout(\"hi\"));\n"
+    );
+}
+
+#[test]
+fn unused_private_method_is_flagged() {
+    let src = r#"
+class Report {
+    fn run() { this.total() }
+    private fn total() { 1 }
+    private fn stale() { 2 }
+}
+"#;
+    let diags = lint(src);
+    let flagged: Vec<&str> = diags
+        .iter()
+        .filter(|d| d.rule == "smell/unused-private-method")
+        .map(|d| d.message.as_str())
+        .collect();
+    assert_eq!(
+        flagged,
+        vec!["private method 'stale' is never called in class 'Report'"]
+    );
+}
+
+#[test]
+fn unreachable_branches_and_code_after_exits_are_flagged() {
+    let src = r#"
+fn pick(x) {
+    if (x) {
+        return 1;
+    } else {
+        throw "no";
+    }
+    print("never");
+}
+
+fn loops() {
+    if (false) { print(1); }
+    while (false) { print(2); }
+    if (true) { print(3); } else { print(4); }
+    let r = match 3 {
+        n => n,
+        3 => 0,
+    };
+    print(r);
+}
+"#;
+    let found: Vec<(&str, u32)> = lint(src)
+        .iter()
+        .filter(|d| d.rule.starts_with("smell/unreachable"))
+        .map(|d| (d.rule, d.span.line))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("smell/unreachable-code", 8),
+            ("smell/unreachable-branch", 12),
+            ("smell/unreachable-branch", 13),
+            ("smell/unreachable-branch", 14),
+            ("smell/unreachable-branch", 17),
+        ]
+    );
+}
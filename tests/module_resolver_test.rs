@@ -5,7 +5,7 @@ use std::fs;
 
 use solilang::ast::Program;
 use solilang::lexer::Scanner;
-use solilang::module::ModuleResolver;
+use solilang::module::{ModuleResolver, Package};
 use solilang::parser::Parser;
 
 fn parse(source: &str) -> Program {
@@ -84,25 +84,70 @@ fn circular_import_is_detected() {
     let tmp = tempfile::tempdir().unwrap();
     let a = tmp.path().join("a.sl");
     let b = tmp.path().join("b.sl");
+    let c = tmp.path().join("c.sl");
 
     fs::write(
         &a,
-        "import \"./b.sl\";\nexport fn from_a() -> Int { return 1; }\n",
+        "import { from_b } from \"./b.sl\";\nexport fn from_a() -> Int { return 1; }\n",
     )
     .unwrap();
     fs::write(
         &b,
-        "import \"./a.sl\";\nexport fn from_b() -> Int { return 2; }\n",
+        "import { from_c } from \"./c.sl\";\nexport fn from_b() -> Int { return 2; }\n",
+    )
+    .unwrap();
+    fs::write(
+        &c,
+        "\nimport { from_a } from \"./a.sl\";\nexport fn from_c() -> Int { return 3; }\n",
     )
     .unwrap();
 
     let program = parse(&fs::read_to_string(&a).unwrap());
     let mut resolver = ModuleResolver::new(tmp.path());
-    let result = resolver.resolve(program, &a);
-    // Either errors with circular detection, or returns successfully if the
-    // resolver handles cycles via memoization. Both are acceptable; we just
-    // want to make sure it doesn't infinite-loop or panic.
-    let _ = result;
+    let message = resolver.resolve(program, &a).unwrap_err().to_string();
+    assert!(
+        message.contains("a.sl -> b.sl -> c.sl -> a.sl"),
+        "{}",
+        message
+    );
+    assert!(
+        message.contains("c.sl:2:1 imports \"./a.sl\""),
+        "{}",
+        message
+    );
+    assert!(message.contains("lazy_imports"), "{}", message);
+}
+
+#[test]
+fn lazy_imports_allow_a_cycle() {
+    let tmp = tempfile::tempdir().unwrap();
+    let a = tmp.path().join("a.sl");
+    let b = tmp.path().join("b.sl");
+
+    fs::write(
+        &a,
+        "import { from_b } from \"./b.sl\";\nexport fn from_a() -> Int { return 1; }\nlet x = from_b();\n",
+    )
+    .unwrap();
+    fs::write(
+        &b,
+        "import { from_a as one } from \"./a.sl\";\nexport fn from_b() -> Int { return one() + 1; }\n",
+    )
+    .unwrap();
+
+    let mut package = Package::new("cycle");
+    package.lazy_imports = true;
+    let program = parse(&fs::read_to_string(&a).unwrap());
+    let mut resolver = ModuleResolver::with_package(tmp.path(), package);
+    let resolved = resolver.resolve(program, &a).unwrap();
+
+    let names = declared_names(&resolved);
+    assert_eq!(names.iter().filter(|n| *n == "from_a").count(), 1);
+    assert_eq!(names.iter().filter(|n| *n == "from_b").count(), 1);
+
+    // b's call through the cycle ends up pointing at a's own declaration.
+    let mut interpreter = solilang::interpreter::Interpreter::new();
+    interpreter.interpret(&resolved).unwrap();
 }
 
 #[test]
@@ -172,3 +217,94 @@ fn private_helper() -> Int { return 3; }
         resolved.statements.len()
     );
 }
+
+fn declared_names(program: &Program) -> Vec<String> {
+    use solilang::ast::StmtKind;
+    program
+        .statements
+        .iter()
+        .filter_map(|stmt| match &stmt.kind {
+            StmtKind::Function(decl) => Some(decl.name.clone()),
+            StmtKind::Class(decl) => Some(decl.name.clone()),
+            StmtKind::Let { name, .. } => Some(name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn named_import_qualifies_the_rest_of_the_module() {
+    let tmp = tempfile::tempdir().unwrap();
+    let main = tmp.path().join("main.sl");
+    fs::create_dir_all(tmp.path().join("pkg")).unwrap();
+
+    fs::write(
+        tmp.path().join("pkg/views.sl"),
+        r#"fn format(x) { return x; }
+export fn render(x) { return format(x); }
+export fn helpers() { return 1; }
+"#,
+    )
+    .unwrap();
+    fs::write(
+        tmp.path().join("other.sl"),
+        "export fn format(x) { return x; }\n",
+    )
+    .unwrap();
+    fs::write(
+        &main,
+        r#"import { render, helpers as h } from "./pkg/views";
+import { format } from "./other";
+"#,
+    )
+    .unwrap();
+
+    let program = parse(&fs::read_to_string(&main).unwrap());
+    let mut resolver = ModuleResolver::new(tmp.path());
+    let resolved = resolver.resolve(program, &main).expect("resolve ok");
+
+    let names = declared_names(&resolved);
+    assert_eq!(names, ["pkg/views::format", "render", "h", "format"]);
+}
+
+#[test]
+fn module_imported_twice_defines_its_internals_once() {
+    let tmp = tempfile::tempdir().unwrap();
+    let main = tmp.path().join("main.sl");
+
+    fs::write(
+        tmp.path().join("lib.sl"),
+        "fn helper() { return 1; }\nexport fn a() { return helper(); }\nexport fn b() { return helper(); }\n",
+    )
+    .unwrap();
+    fs::write(
+        &main,
+        "import { a } from \"./lib\";\nimport { b } from \"./lib\";\n",
+    )
+    .unwrap();
+
+    let program = parse(&fs::read_to_string(&main).unwrap());
+    let mut resolver = ModuleResolver::new(tmp.path());
+    let resolved = resolver.resolve(program, &main).expect("resolve ok");
+
+    let names = declared_names(&resolved);
+    assert_eq!(names, ["lib::helper", "a", "lib::b", "lib::a", "b"]);
+}
+
+#[test]
+fn importing_an_unexported_name_errors() {
+    let tmp = tempfile::tempdir().unwrap();
+    let main = tmp.path().join("main.sl");
+
+    fs::write(tmp.path().join("lib.sl"), "fn hidden() { return 1; }\n").unwrap();
+    fs::write(&main, "import { hidden } from \"./lib\";\n").unwrap();
+
+    let program = parse(&fs::read_to_string(&main).unwrap());
+    let mut resolver = ModuleResolver::new(tmp.path());
+    let err = resolver.resolve(program, &main).expect_err("not exported");
+    assert!(
+        err.to_string().contains("'hidden' is not exported"),
+        "{}",
+        err
+    );
+}
@@ -468,6 +468,56 @@ fn set_cookie_emits_set_cookie_header() {
     );
 }
 
+#[test]
+fn fresh_when_answers_matching_if_none_match_with_304() {
+    let server = shared_server();
+    let resp = ureq::get(&server.url("/cached?v=1"))
+        .timeout(Duration::from_secs(3))
+        .call()
+        .expect("first cached request");
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.header("cache-control"), Some("max-age=60, private"));
+    let etag = resp.header("etag").expect("ETag header").to_string();
+    assert_eq!(resp.into_string().unwrap(), "post v1");
+
+    let resp = ureq::get(&server.url("/cached?v=1"))
+        .set("If-None-Match", &etag)
+        .timeout(Duration::from_secs(3))
+        .call()
+        .expect("revalidation request");
+    assert_eq!(resp.status(), 304);
+    assert_eq!(resp.header("etag"), Some(etag.as_str()));
+    assert_eq!(resp.into_string().unwrap(), "");
+
+    // A new version invalidates the client's copy.
+    let resp = ureq::get(&server.url("/cached?v=2"))
+        .set("If-None-Match", &etag)
+        .timeout(Duration::from_secs(3))
+        .call()
+        .expect("stale revalidation request");
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.into_string().unwrap(), "post v2");
+}
+
+#[test]
+fn typed_action_gets_request_and_returns_response() {
+    let server = shared_server();
+    let resp = ureq::get(&server.url("/typed/42"))
+        .set("X-Probe", "hello")
+        .timeout(Duration::from_secs(3))
+        .call()
+        .expect("typed action request");
+    assert_eq!(resp.status(), 201);
+    assert_eq!(resp.header("x-typed"), Some("GET"));
+    assert!(resp
+        .header("content-type")
+        .unwrap_or("")
+        .starts_with("application/json"));
+    let body: serde_json::Value = serde_json::from_str(&resp.into_string().unwrap()).unwrap();
+    assert_eq!(body["id"], "42");
+    assert_eq!(body["probe"], "hello");
+}
+
 /// GET /jar/write and return its two sealed Set-Cookie header values
 /// (jar_enc, jar_sig) plus the response body.
 fn jar_write(server: &ServerProcess) -> (String, String, String) {
@@ -0,0 +1,30 @@
+//! Request specs that move the clock reach the test server: `travel()` past
+//! `SOLI_SESSION_TTL` in a spec expires the session the server holds for it.
+//!
+//! Runs `soli test` against `tests/fixtures/_session_expiry_app/`, whose
+//! controller keeps a name in the session and whose spec reads it back on
+//! either side of the TTL.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn travel_past_the_ttl_expires_the_server_session() {
+    let binary = PathBuf::from(env!("CARGO_BIN_EXE_soli"));
+    let fixture =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/_session_expiry_app");
+    let output = Command::new(&binary)
+        .current_dir(&fixture)
+        .arg("test")
+        .arg("tests/session_expiry_spec.sl")
+        .arg("--no-coverage")
+        .output()
+        .expect("spawn soli test");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success() && stdout.contains("0 failed"),
+        "session expiry spec failed:\n{}\n{}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,63 @@
+//! `each(cases, body)` / `it_each(name, cases, body)`: one test per case,
+//! called with that case's values and named after them, on the interpreter
+//! and on the VM.
+
+const SPEC: &str = r#"
+describe("Math") do
+  let offset = 0
+  it_each("adds", [[1, 2, 3], [4, 5, 9], [2, 2, 5]], fn(a, b, sum) {
+    assert_eq(a + b + offset, sum)
+  })
+  context("strings") do
+    each(["a", "bb"]) do |s|
+      assert(s.length() < 2)
+    end
+  end
+  xdescribe("later") do
+    test_each("never", [1], fn(n) {
+      throw "ran"
+    })
+  end
+end
+"#;
+
+fn failed_cases(report: &str) -> Vec<&str> {
+    let mut names: Vec<&str> = report
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("- "))
+        .filter_map(|rest| rest.split(": ").next())
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+#[test]
+fn each_case_runs_and_fails_on_its_own() {
+    let (assertions, result) =
+        solilang::run_with_path_and_coverage(SPEC, None, false, None, None, &[]);
+    let report = result.expect_err("two cases fail").to_string();
+    assert_eq!(
+        failed_cases(&report),
+        vec!["(\"bb\")", "adds (2, 2, 5)"],
+        "{}",
+        report
+    );
+    assert_eq!(assertions, 3);
+    let outcomes = solilang::interpreter::builtins::test_dsl::take_test_outcomes();
+    assert_eq!(outcomes.skipped, vec!["Math later never (1)".to_string()]);
+}
+
+#[test]
+fn each_case_runs_and_fails_on_its_own_on_the_vm() {
+    let (assertions, result) = solilang::run_tests_vm(SPEC, None, &[]);
+    let report = result.expect_err("two cases fail").to_string();
+    assert_eq!(
+        failed_cases(&report),
+        vec!["(\"bb\")", "adds (2, 2, 5)"],
+        "{}",
+        report
+    );
+    assert_eq!(assertions, 3);
+    let outcomes = solilang::interpreter::builtins::test_dsl::take_test_outcomes();
+    assert_eq!(outcomes.skipped, vec!["Math later never (1)".to_string()]);
+}
@@ -0,0 +1,115 @@
+//! `soli test --filter PATTERN --exclude-tag T`: only the tests whose full
+//! name matches the pattern and that carry none of the excluded tags run, on
+//! the interpreter and on the VM.
+//!
+//! Every test throws its name, so the run's error lists the tests that ran.
+//! The filter is process-global, which is why this lives in its own test
+//! binary.
+
+use solilang::interpreter::builtins::test_dsl::{set_test_filter, TestFilter};
+
+const SPEC: &str = r#"
+describe("User") do
+  test("login", fn() {
+    throw "login"
+  }, tags: ["fast"])
+  test("signup") do
+    throw "signup"
+  end
+  let names = ["a"]
+  it_each("logs in", names, fn(name) {
+    throw "logs in"
+  })
+  describe("export", tags: ["slow"]) do
+    it("writes a CSV") do
+      throw "csv"
+    end
+  end
+end
+
+describe("Billing") do
+  test("charges the user login fee") do
+    throw "fee"
+  end
+end
+
+describe("Reports") do
+  it_each("totals", [[1, 2], [3, 4]], fn(a, b) {
+    throw "totals"
+  })
+  throw "reports body ran"
+end
+"#;
+
+fn tests_run(report: &str) -> Vec<&str> {
+    let mut names: Vec<&str> = report
+        .lines()
+        .filter_map(|line| {
+            line.trim()
+                .strip_prefix("- ")
+                .or_else(|| line.strip_prefix("Runtime error: Test failed: "))
+        })
+        .filter_map(|rest| rest.split(':').next())
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+#[test]
+fn filter_and_excluded_tags_select_the_tests_that_run() {
+    let (_, result) = solilang::run_with_path_and_coverage(SPEC, None, false, None, None, &[]);
+    assert_eq!(
+        tests_run(&result.expect_err("every test throws").to_string()),
+        vec![
+            "Reports",
+            "charges the user login fee",
+            "login",
+            "logs in (\"a\")",
+            "signup",
+            "writes a CSV"
+        ]
+    );
+
+    set_test_filter(TestFilter::new(Some("user"), Vec::new(), vec!["slow".to_string()]).unwrap());
+
+    // A table of literals none of whose cases match keeps its suite's body
+    // from running at all; one read from a `let` can only be known by
+    // running it.
+    let (_, result) = solilang::run_with_path_and_coverage(SPEC, None, false, None, None, &[]);
+    let report = result.expect_err("the selected tests throw").to_string();
+    assert_eq!(
+        tests_run(&report),
+        vec![
+            "charges the user login fee",
+            "login",
+            "logs in (\"a\")",
+            "signup"
+        ],
+        "{}",
+        report
+    );
+
+    // The VM only learns a suite's tests by running its body.
+    let (_, result) = solilang::run_tests_vm(SPEC, None, &[]);
+    let report = result.expect_err("the selected tests throw").to_string();
+    assert_eq!(
+        tests_run(&report),
+        vec![
+            "Reports",
+            "charges the user login fee",
+            "login",
+            "logs in (\"a\")",
+            "signup"
+        ],
+        "{}",
+        report
+    );
+}
+
+#[test]
+fn tag_filters_and_invalid_patterns() {
+    let filter = TestFilter::new(None, vec!["slow".to_string()], Vec::new()).unwrap();
+    assert!(filter.selects("User export writes a CSV", &["slow".to_string()]));
+    assert!(!filter.selects("User signup", &[]));
+    assert!(TestFilter::new(Some("("), Vec::new(), Vec::new()).is_err());
+}
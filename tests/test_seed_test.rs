@@ -0,0 +1,63 @@
+//! `soli test --seed N`: the same seed replays the same test order and the
+//! same values from the randomness builtins; a different seed reshuffles.
+//!
+//! Every test in the suite throws what it saw, so the run's error lists the
+//! tests in execution order along with their random values. The seed is
+//! process-global, which is why this lives in its own test binary.
+
+use solilang::interpreter::builtins::rng::set_test_seed;
+
+const SPEC: &str = r#"
+describe("seeded") do
+  test("alpha") do
+    throw "alpha " + str(Math.random())
+  end
+  test("beta") do
+    throw "beta " + str([1, 2, 3, 4, 5, 6].shuffle())
+  end
+  test("gamma") do
+    throw "gamma " + str([1, 2, 3, 4, 5, 6].sample())
+  end
+  test("delta") do
+    throw "delta"
+  end
+  test("epsilon") do
+    throw "epsilon"
+  end
+end
+"#;
+
+fn run_spec() -> String {
+    let (_assertions, result) =
+        solilang::run_with_path_and_coverage(SPEC, None, false, None, None, &[]);
+    result.expect_err("every test throws").to_string()
+}
+
+fn test_order(report: &str) -> Vec<&str> {
+    report
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("- "))
+        .filter_map(|rest| rest.split(':').next())
+        .collect()
+}
+
+#[test]
+fn seed_replays_order_and_random_values() {
+    let unseeded = run_spec();
+    assert_eq!(
+        test_order(&unseeded),
+        vec!["alpha", "beta", "gamma", "delta", "epsilon"],
+        "without a seed, tests run in declaration order"
+    );
+
+    set_test_seed(20240615);
+    let first = run_spec();
+    let second = run_spec();
+    assert_eq!(first, second, "the same seed must replay the same run");
+
+    let reshuffled = (1..=20).any(|seed| {
+        set_test_seed(seed);
+        test_order(&run_spec()) != test_order(&first)
+    });
+    assert!(reshuffled, "other seeds should produce other orders");
+}
@@ -0,0 +1,76 @@
+//! `xit`, `xdescribe`, `todo`, `skip(reason)` and `pending(reason)`: the
+//! tests are reported as skipped or pending rather than run or failed, on the
+//! interpreter and on the VM.
+
+use solilang::interpreter::builtins::test_dsl::{take_test_outcomes, TestOutcomes};
+
+const SPEC: &str = r#"
+describe("Export") do
+  test("writes a CSV") do
+    assert_eq(1, 1)
+  end
+  xit("writes a PDF") do
+    throw "xit ran"
+  end
+  todo("writes an XLSX")
+  test("uploads to S3") do
+    skip("needs network")
+    throw "ran past skip"
+  end
+  test("zips") do
+    pending()
+  end
+  xdescribe("Legacy") do
+    before_each do
+      throw "hook ran"
+    end
+    it("reads v1") do
+      throw "v1 ran"
+    end
+    context("nested") do
+      it("reads v0") do
+        throw "v0 ran"
+      end
+    end
+    throw "setup ran"
+  end
+end
+"#;
+
+fn sorted(mut outcomes: TestOutcomes) -> TestOutcomes {
+    outcomes.skipped.sort_unstable();
+    outcomes.pending.sort_unstable();
+    outcomes
+}
+
+fn expected() -> TestOutcomes {
+    TestOutcomes {
+        skipped: vec![
+            "Export Legacy nested reads v0".to_string(),
+            "Export Legacy reads v1".to_string(),
+            "Export uploads to S3 (needs network)".to_string(),
+            "Export writes a PDF".to_string(),
+        ],
+        pending: vec![
+            "Export writes an XLSX".to_string(),
+            "Export zips".to_string(),
+        ],
+    }
+}
+
+#[test]
+fn skipped_and_pending_tests_are_reported_not_failed() {
+    let (assertions, result) =
+        solilang::run_with_path_and_coverage(SPEC, None, false, None, None, &[]);
+    assert!(result.is_ok(), "{:?}", result.err());
+    assert_eq!(assertions, 1);
+    assert_eq!(sorted(take_test_outcomes()), expected());
+}
+
+#[test]
+fn skipped_and_pending_tests_on_the_vm() {
+    let (assertions, result) = solilang::run_tests_vm(SPEC, None, &[]);
+    assert!(result.is_ok(), "{:?}", result.err());
+    assert_eq!(assertions, 1);
+    assert_eq!(sorted(take_test_outcomes()), expected());
+}
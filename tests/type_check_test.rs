@@ -92,3 +92,226 @@ print(describe(Status.Active))
         warnings
     );
 }
+
+#[test]
+fn non_exhaustive_bool_and_array_matches_warn() {
+    let src = r#"
+fn answer(flag: Bool) -> String {
+  return match flag { true => "yes" }
+}
+fn total(xs: Int[]) -> Int {
+  return match xs { [x, y] => x + y }
+}
+print(answer(true))
+"#;
+    let warnings = type_check_source(src, None).expect("non-exhaustive match must not fail");
+    assert!(
+        warnings
+            .iter()
+            .any(|w| w.contains("'Bool' is not exhaustive") && w.contains("missing: false")),
+        "expected a Bool warning naming false, got: {:?}",
+        warnings
+    );
+    assert!(
+        warnings
+            .iter()
+            .any(|w| w.contains("not exhaustive") && w.contains("missing: [], [_], [_, _, _, ...]")),
+        "expected an array warning listing the uncovered lengths, got: {:?}",
+        warnings
+    );
+}
+
+#[test]
+fn array_match_covering_every_length_is_exhaustive() {
+    let src = r#"
+fn total(xs: Int[]) -> Int {
+  return match xs {
+    [] => 0,
+    [x] => x,
+    [x, y, ...rest] => x + y,
+  }
+}
+print(total([1, 2, 3]))
+"#;
+    let warnings = type_check_source(src, None).expect("should type-check");
+    assert!(
+        warnings.is_empty(),
+        "empty, single and open arms cover every array, got: {:?}",
+        warnings
+    );
+}
+
+#[test]
+fn tuple_matches_check_their_shape_and_coverage() {
+    let src = r#"
+fn both(t: (Bool, Int)) -> Int {
+  return match t {
+    (true, n) => n,
+    (false, _) => 0,
+  }
+}
+fn first(t: (Bool, Int)) -> Int {
+  return match t { (true, n) => n }
+}
+print(both((true, 1)) + first((true, 2)))
+"#;
+    let warnings = type_check_source(src, None).expect("tuple patterns should type-check");
+    assert_eq!(
+        warnings.len(),
+        1,
+        "only the partial match should warn, got: {:?}",
+        warnings
+    );
+    assert!(
+        warnings[0].contains("not exhaustive") && warnings[0].contains("missing: (_, _)"),
+        "got: {:?}",
+        warnings
+    );
+
+    let src = "let t = (3, 4)
+let s = match t {
+  [a, b] => a + b,
+  _ => 0,
+}
+";
+    let errors = type_check_source(src, None).expect_err("an array pattern never matches a tuple");
+    let error = errors
+        .iter()
+        .find(|e| e.to_string().contains("expected Array"))
+        .unwrap_or_else(|| panic!("got: {:?}", errors));
+    match error {
+        solilang::error::SolilangError::Type(e) => {
+            assert_eq!(e.span().line, 3, "the error points at the arm: {:?}", e)
+        }
+        other => panic!("expected a type error, got: {:?}", other),
+    }
+}
+
+#[test]
+fn every_error_in_a_body_is_reported() {
+    let src = r#"
+fn total(items: Int[]) -> Int {
+  let label: String = 42
+  print(label)
+  return "none"
+}
+let flag: Bool = "yes"
+"#;
+    let errors = type_check_source(src, None).expect_err("should fail to type-check");
+    let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(messages.len(), 3, "got: {:?}", messages);
+    assert!(messages[0].contains("expected String, found Int"));
+    assert!(messages[1].contains("expected Int, found String"));
+    assert!(messages[2].contains("expected Bool, found String"));
+}
+
+#[test]
+fn errors_point_back_at_what_they_conflict_with() {
+    let src = "const LIMIT: Int = 10\nfn name() -> String {\n  return 1\n}\nLIMIT = 11\n";
+    let errors = type_check_source(src, None).expect_err("should fail to type-check");
+    let notes: Vec<&str> = errors
+        .iter()
+        .flat_map(|e| e.type_errors())
+        .flat_map(|e| e.notes())
+        .map(|(_, note)| note.as_str())
+        .collect();
+    assert_eq!(
+        notes,
+        [
+            "expected `String` because of this return type",
+            "declared as a constant here"
+        ]
+    );
+
+    let rendered = errors[1].type_errors()[0].render(src, "limits.sl");
+    assert!(rendered.starts_with("error: Cannot reassign constant 'LIMIT'\n"));
+    assert!(
+        rendered.contains("1 | const LIMIT: Int = 10\n"),
+        "{}",
+        rendered
+    );
+    assert!(rendered.contains("5 | LIMIT = 11\n"), "{}", rendered);
+}
+
+/// An app with one controller rendering `posts/index` and the template at
+/// `app/views/posts/index.html.slv`.
+fn template_app(template: &str) -> (tempfile::TempDir, std::path::PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let app = dir.path().join("app");
+    std::fs::create_dir_all(app.join("controllers")).unwrap();
+    std::fs::create_dir_all(app.join("views/posts")).unwrap();
+    std::fs::write(
+        app.join("controllers/posts_controller.sl"),
+        "class PostsController extends Controller {\n    fn index(req) {\n        this.heading = \"Posts\"\n        return render(\"posts/index\", {\"title\": \"All posts\", \"count\": 3})\n    }\n}\n",
+    )
+    .unwrap();
+    let path = app.join("views/posts/index.html.slv");
+    std::fs::write(&path, template).unwrap();
+    (dir, path)
+}
+
+#[test]
+fn templates_are_checked_against_the_locals_renders_pass() {
+    let template = "<h1><%= title %></h1>\n<p><%= count + 1 %></p>\n<p><%= subtitle %></p>\n<% let n: String = count %>\n";
+    let (_dir, path) = template_app(template);
+    let errors =
+        solilang::type_check_template(template, &path).expect_err("should fail to type-check");
+    let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(messages.len(), 2, "got: {:?}", messages);
+    assert!(
+        messages[0].contains("'subtitle' is not a local of 'posts/index'"),
+        "{}",
+        messages[0]
+    );
+    assert!(messages[1].contains("expected String, found Int"));
+
+    // Positions are in the template, not the extracted code.
+    let rendered = errors[0].type_errors()[0].render(template, "index.html.slv");
+    assert!(
+        rendered.contains(" --> index.html.slv:3:8\n"),
+        "{}",
+        rendered
+    );
+    assert!(
+        rendered.contains("3 | <p><%= subtitle %></p>\n"),
+        "{}",
+        rendered
+    );
+}
+
+#[test]
+fn template_helpers_and_known_locals_pass() {
+    let template = "<h1><%= title %> (<%= @heading %>)</h1>\n<%= count * 2 %>\n<%= h(title) %>\n";
+    let (_dir, path) = template_app(template);
+    assert!(solilang::type_check_template(template, &path).is_ok());
+}
+
+#[test]
+fn diagnostics_carry_errors_and_warnings_with_locations() {
+    use solilang::types::diagnostics::Severity;
+
+    let src = r#"
+fn answer(flag: Bool) -> String {
+  return match flag { true => "yes" }
+}
+let n: Int = "one"
+"#;
+    let diagnostics = solilang::type_check_diagnostics(src, None, "app/a.sl");
+    let error = diagnostics
+        .iter()
+        .find(|d| d.severity == Severity::Error)
+        .expect("the mismatch is an error");
+    assert_eq!(error.rule, "type-error");
+    assert_eq!(error.span.map(|s| s.line), Some(5));
+    assert!(
+        diagnostics
+            .iter()
+            .any(|d| d.severity == Severity::Warning && d.rule == "non-exhaustive-match"),
+        "the warning is kept alongside the error, got: {:?}",
+        diagnostics
+    );
+
+    let syntax = solilang::type_check_diagnostics("fn broken( {\n", None, "b.sl");
+    assert_eq!(syntax.len(), 1);
+    assert_eq!(syntax[0].rule, "syntax-error");
+}
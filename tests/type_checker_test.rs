@@ -34,7 +34,7 @@ fn check_err(source: &str) -> Vec<TypeError> {
 
 fn assert_any<P: Fn(&TypeError) -> bool>(errors: &[TypeError], pred: P, label: &str) {
     assert!(
-        errors.iter().any(pred),
+        errors.iter().any(|e| pred(e.without_notes())),
         "expected at least one error matching {label}, got:\n{}",
         errors
             .iter()
@@ -118,6 +118,29 @@ fn const_typechecks() {
     check_ok("const MAX = 100;");
 }
 
+#[test]
+fn const_reassignment_errors() {
+    for source in [
+        "const MAX = 100;\nMAX = 200;",
+        "const MAX = 100;\nMAX += 1;",
+        "const MAX = 100;\nMAX++;",
+        "const MAX = 100;\nfn bump() { MAX = 1; }",
+    ] {
+        let errors = check_err(source);
+        assert_any(
+            &errors,
+            |e| matches!(e, TypeError::ConstReassignment(name, _) if name == "MAX"),
+            "ConstReassignment",
+        );
+    }
+}
+
+#[test]
+fn shadowed_const_can_be_assigned() {
+    check_ok("const MAX = 100;\nfn f(MAX: Int) -> Int { MAX = 1; return MAX; }");
+    check_ok("const MAX = 100;\nfn g() -> Int { let MAX = 1; MAX += 1; return MAX; }");
+}
+
 // =====================================================================
 // Binary operators
 // =====================================================================
@@ -158,6 +181,25 @@ fn bool_plus_int_errors() {
     );
 }
 
+#[test]
+fn overloaded_operator_takes_the_method_types() {
+    let class = "class Meters { v: Int\n new(v: Int) { this.v = v }\n \
+                 fn op_add(n: Int) -> Int { return this.v + n }\n \
+                 fn op_lt(n: Int) -> String { return \"yes\" }\n \
+                 fn op_index(i: Int) -> String { return \"cell\" } }\n\
+                 let m: Meters = new Meters(1);\n";
+    check_ok(&format!(
+        "{}let a: Int = m + 2;\nlet b: Bool = m < 3;\nlet c: String = m[0];",
+        class
+    ));
+    let errors = check_err(&format!("{}let a = m + \"x\";", class));
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { .. }),
+        "operand checked against the op_add parameter",
+    );
+}
+
 #[test]
 fn comparison_returns_bool() {
     check_ok("let x: Bool = 1 < 2;");
@@ -434,6 +476,74 @@ fn array_method_map_typechecks() {
     check_ok("let xs = [1, 2, 3]; let ys = xs.map(fn(x) x * 2);");
 }
 
+#[test]
+fn array_map_infers_element_and_result_types() {
+    check_ok("let xs = [1, 2, 3]; let ys: Int[] = xs.map(fn(x) x * 2); let y: Int = ys[0];");
+    let errors = check_err(r#"let xs = [1, 2, 3]; let ys: String[] = xs.map(fn(x) x * 2);"#);
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { expected, found, .. } if expected == "String[]" && found == "Int[]"),
+        "Mismatch String[] vs Int[] on a mapped array",
+    );
+}
+
+#[test]
+fn block_params_take_the_element_type() {
+    let errors = check_err(r#"let xs = [1, 2, 3]; xs.each(fn(x) { let s: String = x });"#);
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { expected, found, .. } if expected == "String" && found == "Int"),
+        "Mismatch String vs Int on an inferred block parameter",
+    );
+}
+
+#[test]
+fn hash_block_params_take_key_and_value_types() {
+    let errors = check_err(r#"let h = {"a": 1}; h.each(fn(k, v) { let b: Bool = v });"#);
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { expected, found, .. } if expected == "Bool" && found == "Int"),
+        "Mismatch Bool vs Int on an inferred hash value",
+    );
+    // A one-parameter block gets the `[key, value]` pair, left untyped.
+    check_ok(r#"let h = {"a": 1}; h.each(fn(pair) { let s: String = pair });"#);
+}
+
+#[test]
+fn pipeline_stage_infers_block_types() {
+    let errors =
+        check_err(r#"let xs = [1, 2]; let ys = xs |> map(fn(x) "n"); let y: Int = ys[0];"#);
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { expected, found, .. } if expected == "Int" && found == "String"),
+        "Mismatch Int vs String on a piped map result",
+    );
+}
+
+#[test]
+fn lambda_params_take_the_expected_function_type() {
+    let errors = check_err("let inc: (Int) -> Int = fn(x) { let s: String = x\n return x }");
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { expected, found, .. } if expected == "String" && found == "Int"),
+        "Mismatch String vs Int on a parameter typed by the annotation",
+    );
+    let errors = check_err(
+        "fn apply(f: (String) -> Int) -> Int { return f(\"a\") }\napply(fn(s) { let n: Int = s\n return 1 })",
+    );
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { expected, found, .. } if expected == "Int" && found == "String"),
+        "Mismatch Int vs String on a parameter typed by the callee",
+    );
+}
+
+#[test]
+fn annotated_lambda_params_are_not_overridden() {
+    check_ok("let xs = [1, 2, 3]; let ys = xs.map(fn(x: Any) x);");
+    check_ok("let xs = [1, 2, 3]; let ys = xs.map(fn(x: Float) x * 1.5);");
+}
+
 #[test]
 fn array_method_unknown_errors() {
     let errors = check_err("let xs = [1, 2, 3]; let y = xs.no_such_method;");
@@ -601,10 +711,234 @@ fn class_interface_signature_mismatch_errors() {
     );
 }
 
+#[test]
+fn interface_method_signature_mismatch_reports_both_signatures() {
+    let errors = check_err(
+        "
+        interface Greeter {
+            fn greet(name: String) -> String;
+        }
+        class Bad implements Greeter {
+            fn greet(name: Int) -> String { return \"x\"; }
+        }
+        ",
+    );
+    assert_any(
+        &errors,
+        |e| {
+            matches!(e, TypeError::General { message, .. }
+            if message.contains("expected (String) -> String, found (Int) -> String"))
+        },
+        "General with expected/found signatures",
+    );
+}
+
+#[test]
+fn interface_method_inherited_from_superclass_satisfies_it() {
+    check_ok(
+        "
+        interface Greeter {
+            fn greet() -> String;
+        }
+        class Base {
+            fn greet() -> String { return \"hi\"; }
+        }
+        class Child extends Base implements Greeter {
+        }
+        ",
+    );
+}
+
+#[test]
+fn implementing_unknown_interface_errors() {
+    let errors = check_err("class A implements Nope {}");
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("unknown interface 'Nope'")),
+        "General 'unknown interface'",
+    );
+}
+
+#[test]
+fn interface_typed_parameter_exposes_interface_methods() {
+    check_ok(
+        "
+        interface Greeter {
+            fn greet(name: String) -> String;
+        }
+        fn welcome(g: Greeter) -> String {
+            return g.greet(\"ada\");
+        }
+        ",
+    );
+    let errors = check_err(
+        "
+        interface Greeter {
+            fn greet(name: String) -> String;
+        }
+        fn welcome(g: Greeter) -> String {
+            return g.shout();
+        }
+        ",
+    );
+    assert_any(
+        &errors,
+        |e| {
+            matches!(e, TypeError::NoSuchMember { type_name, member, .. }
+            if type_name == "Greeter" && member == "shout")
+        },
+        "NoSuchMember(Greeter.shout)",
+    );
+}
+
+#[test]
+fn subclass_is_assignable_to_ancestor_interface() {
+    check_ok(
+        "
+        interface Greeter {
+            fn greet() -> String;
+        }
+        class Base implements Greeter {
+            fn greet() -> String { return \"hi\"; }
+        }
+        class Child extends Base {}
+        fn pick(c: Child) -> Greeter {
+            return c;
+        }
+        ",
+    );
+}
+
 // (member access on instances of `new ClassName()` is currently
 // silently accepted; see `bug_member_access_on_new_instance_is_silently_accepted`
 // in the bug-pinning section below.)
 
+// =====================================================================
+// Call signatures: defaults, named, keyword-only and variadic arguments
+// =====================================================================
+
+#[test]
+fn named_and_defaulted_arguments_typecheck() {
+    check_ok(
+        r#"
+        fn connect(host: String, *, port: Int = 5432, tls: Bool) -> String { return host; }
+        connect("db", tls: true);
+        connect(port: 1, tls: false, host: "db");
+        "#,
+    );
+}
+
+#[test]
+fn unknown_named_argument_errors() {
+    let errors = check_err(r#"fn f(a: Int) -> Int { return a; } f(a: 1, b: 2);"#);
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("no parameter named 'b'")),
+        "TypeError::General (unknown named argument)",
+    );
+}
+
+#[test]
+fn argument_given_by_position_and_name_errors() {
+    let errors = check_err(r#"fn f(a: Int) -> Int { return a; } f(1, a: 2);"#);
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("both by position and by name")),
+        "TypeError::General (argument given twice)",
+    );
+}
+
+#[test]
+fn missing_keyword_argument_errors() {
+    let errors = check_err(r#"fn f(a: Int, *, b: Int) -> Int { return a; } f(1);"#);
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("missing keyword argument 'b'")),
+        "TypeError::General (missing keyword argument)",
+    );
+}
+
+#[test]
+fn keyword_only_parameter_rejects_positional_argument() {
+    let errors = check_err(r#"fn f(a: Int, *, b: Int = 1) -> Int { return a; } f(1, 2);"#);
+    assert_any(
+        &errors,
+        |e| {
+            matches!(
+                e,
+                TypeError::WrongArity {
+                    expected: 1,
+                    got: 2,
+                    ..
+                }
+            )
+        },
+        "TypeError::WrongArity { expected: 1, got: 2 }",
+    );
+}
+
+#[test]
+fn default_value_type_mismatch_errors() {
+    let errors = check_err(r#"fn f(a: Int = "one") -> Int { return a; }"#);
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { .. }),
+        "TypeError::Mismatch (default value)",
+    );
+}
+
+#[test]
+fn constructor_defaults_allow_shorter_calls() {
+    check_ok(
+        r#"
+        class Point {
+            x: Int;
+            y: Int;
+            new(x: Int, y: Int = x + 1) { this.x = x; this.y = y; }
+        }
+        let p = new Point(1);
+        let q = new Point(1, y: 5);
+        "#,
+    );
+}
+
+#[test]
+fn variadic_parameters_and_spread_arguments_typecheck() {
+    check_ok(
+        r#"
+        fn log(level: String, ...parts: Array<Any>, sep: String = " ") -> String {
+            return level + parts.join(sep);
+        }
+        log("info");
+        log("info", 1, "two", sep: ",");
+        let parts = [1, 2];
+        log("warn", *parts);
+        let forward = fn(...args) { return log(*args); };
+        forward("debug", 3);
+        "#,
+    );
+}
+
+#[test]
+fn variadic_parameter_cannot_be_named() {
+    let errors = check_err(r#"fn f(a: Int, ...rest) -> Int { return a; } f(1, rest: [2]);"#);
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("no parameter named 'rest'")),
+        "TypeError::General (named variadic)",
+    );
+}
+
+#[test]
+fn spreading_a_non_array_errors() {
+    let errors = check_err(r#"fn f(a: Int, b: Int) -> Int { return a; } f(*5);"#);
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { .. }),
+        "TypeError::Mismatch (spread of an Int)",
+    );
+}
+
 // =====================================================================
 // Try / catch / throw
 // =====================================================================
@@ -741,6 +1075,293 @@ fn interpolated_string_typechecks() {
     check_ok(r#"let n = 5; let s: String = "n=#{n}";"#);
 }
 
+// =====================================================================
+// Tuples
+// =====================================================================
+
+#[test]
+fn tuple_destructuring_binds_element_types() {
+    check_ok(
+        r#"
+        fn split() -> (Int, String) { return (1, "one"); }
+        let (n, word) = split();
+        let a: Int = n;
+        let b: String = word;
+        "#,
+    );
+}
+
+#[test]
+fn tuple_destructuring_arity_mismatch_errors() {
+    let errors = check_err("let (a, b) = (1, 2, 3);");
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("3 elements into 2 names")),
+        "General(arity mismatch)",
+    );
+}
+
+#[test]
+fn tuple_literal_index_uses_element_type() {
+    let errors = check_err(r#"let t = (1, "a"); let s: Int = t[1];"#);
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { .. }),
+        "Mismatch on t[1]",
+    );
+    let errors = check_err(r#"let t = (1, "a"); let x = t[2];"#);
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("out of range")),
+        "General(index out of range)",
+    );
+}
+
+#[test]
+fn for_destructuring_over_hash_entries_typechecks() {
+    check_ok(r#"let h = {"a": 1}; for (k, v) in h { let s: String = k; let n: Int = v; }"#);
+}
+
+#[test]
+fn for_destructuring_an_array_binds_index() {
+    check_ok(r#"for (i, s) in ["a", "b"] { let n: Int = i; let t: String = s; }"#);
+    check_ok("for (i, n) in 0..=3 { let a: Int = i + n; }");
+    check_ok(r#"for (i, t) in [(7, "a")] { let n: Int = i; let p: (Int, String) = t; }"#);
+    let errors = check_err(r#"for (i, s) in ["a"] { let t: String = i; }"#);
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { .. }),
+        "Mismatch on index binding",
+    );
+}
+
+#[test]
+fn inclusive_range_is_an_int_array() {
+    check_ok("let r: Int[] = 1..=10; let n: Int = r[0];");
+    let errors = check_err(r#"let r = 1..="z";"#);
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("range (..=)")),
+        "General(range operands)",
+    );
+}
+
+#[test]
+fn generator_call_is_an_iterator_of_its_yields() {
+    check_ok("def count() -> Int { yield 1; yield 2; } let xs: Int[] = count().to_a; let n: Int = count().next;");
+    check_ok("def count() -> Int { yield 1; } for n in count() { let m: Int = n; }");
+    let errors = check_err(r#"def count() -> Int { yield "one"; }"#);
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { .. }),
+        "Mismatch on yielded value",
+    );
+}
+
+#[test]
+fn yield_outside_generator_and_return_value_in_generator_are_rejected() {
+    let errors = check_err("yield 1;");
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("yield outside")),
+        "General(yield outside a generator)",
+    );
+    let errors = check_err("def count() { yield 1; return 2; }");
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("can't return a value")),
+        "General(return value in generator)",
+    );
+    let errors = check_err("def count() { try { yield 1; } catch e { } }");
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("inside try")),
+        "General(yield inside try)",
+    );
+}
+
+#[test]
+fn pipeline_stages_keep_iterators_lazy() {
+    check_ok("def nat() -> Int { yield 1; } let it = nat() |> map(fn(x) x * 2) |> filter(fn(x) x > 2); let xs = it.take(3).to_a;");
+    check_ok("let xs = [1, 2] |> map(fn(x) x * 2); let n = xs.length;");
+    let errors = check_err("let x = 5 |> map(fn(x) x);");
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("expects an array or iterator")),
+        "General(map on Int)",
+    );
+}
+
+const POST_MODEL: &str =
+    "class Post < Model\n  attribute \"title\", \"string\"\n  attribute :views, :int\nend\n";
+
+#[test]
+fn model_attributes_are_typed() {
+    check_ok(&format!(
+        "{}def f(p: Post) -> String {{ p.views = 3; return p.title; }}",
+        POST_MODEL
+    ));
+    check_ok(&format!(
+        "{}def f(id: String) -> Int {{ return Post.find(id).views; }}",
+        POST_MODEL
+    ));
+    let errors = check_err(&format!(
+        "{}def f(p: Post) {{ p.views = \"ten\"; }}",
+        POST_MODEL
+    ));
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { expected, .. } if expected == "Int"),
+        "Mismatch(assign String to Int attribute)",
+    );
+}
+
+#[test]
+fn model_attribute_typos_are_caught() {
+    let errors = check_err(&format!(
+        "{}def f(p: Post) {{ print(p.tilte); }}",
+        POST_MODEL
+    ));
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("did you mean 'title'")),
+        "General(attribute typo)",
+    );
+    // Anything that isn't a near miss may be a runtime model method.
+    check_ok(&format!(
+        "{}def f(p: Post) {{ p.save; print(p.errors); print(p._key); }}",
+        POST_MODEL
+    ));
+    // Schemaless models keep accepting any member.
+    check_ok("class Tag < Model\nend\ndef f(t: Tag) { print(t.tilte); }");
+}
+
+// =====================================================================
+// Null safety: `T?`, `?.`, `??`
+// =====================================================================
+
+const USER_CLASS: &str = "
+class User {
+    name: String;
+    new(name: String) { this.name = name; }
+    fn greet() -> String { return \"hi \" + this.name; }
+}
+";
+
+fn is_null_dereference(e: &TypeError) -> bool {
+    matches!(e, TypeError::General { message, .. } if message.contains("possibly-null"))
+}
+
+#[test]
+fn nullable_member_access_is_rejected() {
+    let errors = check_err(&format!(
+        "{}def f(u: User?) -> String {{ return u.name; }}",
+        USER_CLASS
+    ));
+    assert_any(&errors, is_null_dereference, "General(possibly-null)");
+
+    let errors = check_err("def f(xs: Int[]?) -> Int { return xs[0]; }");
+    assert_any(&errors, is_null_dereference, "General(possibly-null index)");
+}
+
+#[test]
+fn nullable_value_is_not_assignable_to_non_null() {
+    let errors = check_err(&format!(
+        "{}def need(u: User) -> String {{ return u.name; }}\ndef f(u: User?) {{ need(u); }}",
+        USER_CLASS
+    ));
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { found, .. } if found == "User?"),
+        "Mismatch(User?)",
+    );
+    check_ok("let s: String? = null; let t: String? = \"x\";");
+}
+
+#[test]
+fn safe_navigation_and_coalescing_unwrap_nullable() {
+    check_ok(&format!(
+        "{}def f(u: User?) -> String {{ return u?.name ?? \"nobody\"; }}",
+        USER_CLASS
+    ));
+    check_ok(&format!(
+        "{}def f(u: User?) -> String {{ return u&.greet() ?? \"\"; }}",
+        USER_CLASS
+    ));
+    // The safe-navigation result is itself nullable.
+    let errors = check_err(&format!(
+        "{}def f(u: User?) -> Int {{ return u?.name.length; }}",
+        USER_CLASS
+    ));
+    assert_any(&errors, is_null_dereference, "General(possibly-null chain)");
+}
+
+#[test]
+fn null_checks_narrow_nullable_variables() {
+    for body in [
+        "if u != null { return u.name; } return \"\";",
+        "if u == null { return \"\"; } return u.name;",
+        "if !u { return \"\"; } else { return u.name; }",
+        "if u.present? { return u.name; } return \"\";",
+        "if u != null && u.name != \"\" { return u.greet(); } return \"\";",
+        "if u == null || u.name == \"\" { return \"\"; } return u.name;",
+    ] {
+        check_ok(&format!(
+            "{}def f(u: User?) -> String {{ {} }}",
+            USER_CLASS, body
+        ));
+    }
+    // A check that doesn't exit leaves the variable nullable afterwards.
+    let errors = check_err(&format!(
+        "{}def f(u: User?) -> String {{ if u == null {{ print(1); }} return u.name; }}",
+        USER_CLASS
+    ));
+    assert_any(
+        &errors,
+        is_null_dereference,
+        "General(possibly-null after check)",
+    );
+}
+
+const MONEY_STRUCT: &str = "struct Money { amount: Int, currency: String = \"EUR\" } ";
+
+#[test]
+fn struct_fields_are_typed() {
+    for body in [
+        "let m = Money(5); let n: Int = m.amount;",
+        "let m = Money(amount: 5, currency: \"USD\"); let c: String = m.currency;",
+        "let m = Money.new(5); let n: Int = m.with(amount: 6).amount;",
+        "let m = new Money(5); let h = m.to_h;",
+    ] {
+        check_ok(&format!("{}{}", MONEY_STRUCT, body));
+    }
+    let errors = check_err(&format!(
+        "{}let m = Money(5); let s: String = m.amount;",
+        MONEY_STRUCT
+    ));
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { .. }),
+        "Mismatch on field type",
+    );
+    let errors = check_err("struct Money { amount: Int = \"five\" }");
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { .. }),
+        "Mismatch on field default",
+    );
+}
+
+#[test]
+fn struct_fields_cannot_be_assigned() {
+    let errors = check_err(&format!("{}let m = Money(5); m.amount = 6;", MONEY_STRUCT));
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("use .with(amount")),
+        "General(assign to struct field)",
+    );
+}
+
 // =====================================================================
 // Bug-pinning tests
 //
@@ -801,8 +1422,187 @@ fn bug_member_access_on_new_instance_is_silently_accepted() {
 // features are added later, those checker paths will suddenly become live — at
 // which point `check_throw_expr` will panic with `unimplemented!()`. Worth
 // fixing pre-emptively, or removing the dead code.
-//
-// The parallel `await` case is gone: the `await`/`async` keywords and the dead
-// `ExprKind::Await` variant were removed. `await(...)` is now an ordinary call
-// to the `await()` builtin, so no `ExprKind::Await` / `check_await_expr` path
-// exists to become live.
+
+#[test]
+fn class_decorator_reflection_is_typed() {
+    let classes = "@route(\"/users\") class Users { @route(\"/\") fn index(req: Any) -> String { return \"all\"; } } ";
+    for body in [
+        "let count: Int = Users.decorators.length;",
+        "let names: Array<String> = Users.decorated_methods(\"route\");",
+        "let route = Users.decorator(\"route\");",
+        "let routes = Users.method_decorators(\"index\");",
+    ] {
+        check_ok(&format!("{}{}", classes, body));
+    }
+    let errors = check_err(&format!(
+        "{}let names: Int = Users.decorated_methods(\"route\");",
+        classes
+    ));
+    assert!(!errors.is_empty());
+}
+
+#[test]
+fn async_calls_return_futures_until_awaited() {
+    let decl = "async fn load(id: Int) -> String { return \"user\"; } ";
+    check_ok(&format!("{}let name: String = await load(1);", decl));
+    check_ok(&format!(
+        "{}let names: Array<String> = await [load(1), load(2)];",
+        decl
+    ));
+    let errors = check_err(&format!("{}let name: String = load(1);", decl));
+    assert!(!errors.is_empty());
+}
+
+#[test]
+fn ternary_accepts_the_conditions_an_if_statement_does() {
+    check_ok("fn label(liked) -> String { return liked ? \"Liked\" : \"Like\"; }");
+    check_ok("fn label(liked: Bool?) -> String { return liked ? \"Liked\" : \"Like\"; }");
+    let errors = check_err("let label = 1 ? \"Liked\" : \"Like\";");
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { expected, .. } if expected == "Bool"),
+        "Mismatch(Bool)",
+    );
+}
+
+#[test]
+fn builtin_calls_need_their_required_arguments() {
+    let errors = check_err("let stamp = datetime_add_days(0);");
+    assert_any(
+        &errors,
+        |e| {
+            matches!(
+                e,
+                TypeError::WrongArity {
+                    expected: 2,
+                    got: 1,
+                    ..
+                }
+            )
+        },
+        "WrongArity(2, 1)",
+    );
+    let errors = check_err("let label: Int = datetime_format(0, \"%Y\");");
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { expected, .. } if expected == "Int"),
+        "Mismatch(Int)",
+    );
+    check_ok("let page = paginate(null);");
+    check_ok("let stamp = l(0, format: \"short\");");
+    // A program's own definition replaces the builtin's signature.
+    check_ok(
+        "fn datetime_add_days(days: Int) -> Int { return days; } let later = datetime_add_days(1);",
+    );
+}
+
+#[test]
+fn hash_literals_check_against_a_record_type() {
+    let alias = "type User = { id: Int, name: String, email: String? } ";
+    check_ok(&format!(
+        "{}let u: User = {{ id: 1, name: \"Ann\" }};",
+        alias
+    ));
+    check_ok(&format!(
+        "{}let u: User = {{ \"id\": 1, \"name\": \"Ann\", \"email\": null }};",
+        alias
+    ));
+    let errors = check_err(&format!(
+        "{}let u: User = {{ id: \"1\", name: \"Ann\" }};",
+        alias
+    ));
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::Mismatch { expected, .. } if expected == "Int"),
+        "Mismatch(Int)",
+    );
+    let errors = check_err(&format!("{}let u: User = {{ id: 1 }};", alias));
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("missing field 'name'")),
+        "missing field 'name'",
+    );
+    let errors = check_err(&format!(
+        "{}let u: User = {{ id: 1, name: \"Ann\", nmae: \"x\" }};",
+        alias
+    ));
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::NoSuchMember { member, .. } if member == "nmae"),
+        "NoSuchMember(nmae)",
+    );
+}
+
+#[test]
+fn record_field_access_is_typed() {
+    let decl = "type User = { id: Int, email: String? } \
+                fn load() -> User { return { id: 1 }; } ";
+    check_ok(&format!("{}let id: Int = load().id;", decl));
+    check_ok(&format!("{}let id: Int = load()[\"id\"];", decl));
+    check_ok(&format!("{}let n: Int = load().length;", decl));
+    let errors = check_err(&format!("{}let email: String = load().email;", decl));
+    assert!(!errors.is_empty());
+    let errors = check_err(&format!("{}let x = load().emial;", decl));
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::NoSuchMember { member, .. } if member == "emial"),
+        "NoSuchMember(emial)",
+    );
+    let errors = check_err(&format!("{}let x = load()[\"nope\"];", decl));
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::NoSuchMember { member, .. } if member == "nope"),
+        "NoSuchMember(nope)",
+    );
+}
+
+#[test]
+fn request_hashes_take_record_types() {
+    let handler = "type CreateUser = { params: { name: String, age: Int? } } \
+                   fn create(req: CreateUser) -> Any { BODY } ";
+    check_ok(&handler.replace(
+        "BODY",
+        "let name: String = req[\"params\"][\"name\"]; return name;",
+    ));
+    check_ok(&handler.replace("BODY", "return req.params.age;"));
+    let errors = check_err(&handler.replace("BODY", "return req[\"params\"][\"email\"];"));
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::NoSuchMember { member, .. } if member == "email"),
+        "NoSuchMember(email)",
+    );
+    // A hash of unknown values (the raw request) fits any record.
+    check_ok(&format!(
+        "{}let raw = {{}}; create(raw);",
+        handler.replace("BODY", "return null;")
+    ));
+}
+
+#[test]
+fn self_referring_type_alias_is_an_error() {
+    let errors = check_err("type A = B? type B = A[]");
+    assert_any(
+        &errors,
+        |e| matches!(e, TypeError::General { message, .. } if message.contains("refers to itself")),
+        "alias refers to itself",
+    );
+}
+
+#[test]
+fn type_coverage_counts_expressions_typed_other_than_any() {
+    let typed = solilang::type_coverage_source("let x = 1 + 2; let y = x * 3;", None).unwrap();
+    assert_eq!(typed.typed(), typed.total());
+    assert_eq!(typed.percent(), 100.0);
+
+    let source = "fn f(a) { return a; }\nlet n: Int = 1;\nf(n);";
+    let partial = solilang::type_coverage_source(source, None).unwrap();
+    assert!(partial.typed() < partial.total());
+    assert!(partial.untyped_lines().contains(&1));
+    assert!(!partial.untyped_lines().contains(&2));
+}
+
+#[test]
+fn type_coverage_reports_files_with_type_errors() {
+    let coverage = solilang::type_coverage_source("let s: String = 1;", None).unwrap();
+    assert!(coverage.total() > 0);
+}